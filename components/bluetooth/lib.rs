@@ -30,7 +30,7 @@ use bluetooth_traits::{
     BluetoothRequest, BluetoothResponse, BluetoothResponseResult, BluetoothResult,
     BluetoothServiceMsg, GATTType,
 };
-use embedder_traits::{EmbedderMsg, EmbedderProxy};
+use embedder_traits::{BluetoothDeviceDescription, EmbedderMsg, EmbedderProxy};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use log::warn;
 use servo_config::pref;
@@ -389,6 +389,7 @@ impl BluetoothManager {
     fn select_device(
         &mut self,
         webview_id: WebViewId,
+        origin: &str,
         devices: Vec<BluetoothDevice>,
         adapter: &BluetoothAdapter,
     ) -> Option<String> {
@@ -401,26 +402,31 @@ impl BluetoothManager {
             return None;
         }
 
-        let mut dialog_rows: Vec<String> = vec![];
-        for device in devices {
-            dialog_rows.extend_from_slice(&[
-                device.get_address().unwrap_or("".to_string()),
-                device.get_name().unwrap_or("".to_string()),
-            ]);
-        }
+        let devices = devices
+            .iter()
+            .filter_map(|device| {
+                Some(BluetoothDeviceDescription {
+                    address: device.get_address().ok()?,
+                    name: device.get_name().ok(),
+                })
+            })
+            .collect();
 
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
-        self.embedder_proxy
-            .send(EmbedderMsg::GetSelectedBluetoothDevice(
-                webview_id,
-                dialog_rows,
-                ipc_sender,
-            ));
+        self.embedder_proxy.send(EmbedderMsg::SelectBluetoothDevice(
+            webview_id,
+            origin.to_owned(),
+            devices,
+            ipc_sender,
+        ));
 
         match ipc_receiver.recv() {
             Ok(result) => result,
             Err(e) => {
-                warn!("Failed to receive files from embedder ({:?}).", e);
+                warn!(
+                    "Failed to receive selected Bluetooth device from embedder ({:?}).",
+                    e
+                );
                 None
             },
         }
@@ -631,7 +637,12 @@ impl BluetoothManager {
         }
 
         // Step 9.
-        if let Some(address) = self.select_device(options.webview_id(), matched_devices, &adapter) {
+        if let Some(address) = self.select_device(
+            options.webview_id(),
+            options.origin(),
+            matched_devices,
+            &adapter,
+        ) {
             let device_id = match self.address_to_id.get(&address) {
                 Some(id) => id.clone(),
                 None => return Err(BluetoothError::NotFound),
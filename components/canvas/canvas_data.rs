@@ -29,6 +29,7 @@ use crate::backend::{
     Backend, DrawOptionsHelpers as _, GenericDrawTarget as _, PatternHelpers,
     StrokeOptionsHelpers as _,
 };
+use crate::filters;
 
 // Asserts on WR texture cache update for zero sized image with raw data.
 // https://github.com/servo/webrender/blob/main/webrender/src/texture_cache.rs#L1475
@@ -89,6 +90,22 @@ impl PathBuilderRef<'_> {
         self.move_to(&first);
     }
 
+    /// <https://html.spec.whatwg.org/multipage#dom-context-2d-roundrect>
+    pub(crate) fn round_rect(&mut self, rect: &Rect<f32>, radii: &[Point2D<f32>; 4]) {
+        // The corners are rounded in user space, so that they are transformed along with
+        // the rest of the rectangle.
+        let mut path = Path::new();
+        path.round_rect(
+            rect.origin.x as f64,
+            rect.origin.y as f64,
+            rect.size.width as f64,
+            rect.size.height as f64,
+            radii.map(|radius| radius.cast()),
+        );
+        path.transform(self.transform.cast());
+        self.builder.0.extend(path.0);
+    }
+
     /// <https://html.spec.whatwg.org/multipage#dom-context-2d-quadraticcurveto>
     pub(crate) fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         let cp = self.transform.transform_point(*cp).cast();
@@ -246,7 +263,7 @@ impl UnshapedTextRun<'_> {
         }
     }
 
-    fn into_shaped_text_run(self) -> Option<TextRun> {
+    fn into_shaped_text_run(self, is_rtl: bool) -> Option<TextRun> {
         let font = self.font?;
         if self.string.is_empty() {
             return None;
@@ -257,11 +274,16 @@ impl UnshapedTextRun<'_> {
                 .map(|glyph_id| font.glyph_h_advance(glyph_id))
                 .unwrap_or(LAST_RESORT_GLYPH_ADVANCE),
         );
+        let flags = if is_rtl {
+            ShapingFlags::RTL_FLAG
+        } else {
+            ShapingFlags::empty()
+        };
         let options = ShapingOptions {
             letter_spacing: None,
             word_spacing,
             script: self.script,
-            flags: ShapingFlags::empty(),
+            flags,
         };
         let glyphs = font.shape_text(self.string, &options);
         Some(TextRun { font, glyphs })
@@ -357,28 +379,30 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             snapshot
         };
 
-        let draw_options = self.state.draw_options.clone();
-        let writer = |draw_target: &mut B::DrawTarget| {
-            write_image::<B>(
-                draw_target,
-                snapshot,
-                dest_rect,
-                smoothing_enabled,
-                &draw_options,
-            );
-        };
+        self.draw_with_filter(|self_| {
+            let draw_options = self_.state.draw_options.clone();
+            let writer = |draw_target: &mut B::DrawTarget| {
+                write_image::<B>(
+                    draw_target,
+                    snapshot,
+                    dest_rect,
+                    smoothing_enabled,
+                    &draw_options,
+                );
+            };
 
-        if self.need_to_draw_shadow() {
-            let rect = Rect::new(
-                Point2D::new(dest_rect.origin.x as f32, dest_rect.origin.y as f32),
-                Size2D::new(dest_rect.size.width as f32, dest_rect.size.height as f32),
-            );
+            if self_.need_to_draw_shadow() {
+                let rect = Rect::new(
+                    Point2D::new(dest_rect.origin.x as f32, dest_rect.origin.y as f32),
+                    Size2D::new(dest_rect.size.width as f32, dest_rect.size.height as f32),
+                );
 
-            // TODO(pylbrecht) pass another closure for raqote
-            self.draw_with_shadow(&rect, writer);
-        } else {
-            writer(&mut self.drawtarget);
-        }
+                // TODO(pylbrecht) pass another closure for raqote
+                self_.draw_with_shadow(&rect, writer);
+            } else {
+                writer(&mut self_.drawtarget);
+            }
+        });
     }
 
     pub(crate) fn save_context_state(&mut self) {
@@ -422,11 +446,8 @@ impl<'a, B: Backend> CanvasData<'a, B> {
 
         let runs = self.build_unshaped_text_runs(&text, &mut font_group);
         // TODO: This doesn't do any kind of line layout at all. In particular, there needs
-        // to be some alignment along a baseline and also support for bidi text.
-        let shaped_runs: Vec<_> = runs
-            .into_iter()
-            .filter_map(UnshapedTextRun::into_shaped_text_run)
-            .collect();
+        // to be some alignment along a baseline and also support for full bidi text.
+        let shaped_runs = Self::shape_text_runs(runs, is_rtl);
         let total_advance = shaped_runs
             .iter()
             .map(|run| run.glyphs.total_advance())
@@ -463,18 +484,20 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         // > from left to right (if any), adding to the array, for each glyph, the shape of the glyph
         // > as it is in the inline box, positioned on a coordinate space using CSS pixels with its
         // > origin is at the anchor point.
-        self.maybe_bound_shape_with_pattern(
-            self.state.fill_style.clone(),
-            &Rect::from_size(Size2D::new(total_advance, size)),
-            |self_| {
-                self_.drawtarget.fill_text(
-                    shaped_runs,
-                    start,
-                    &self_.state.fill_style,
-                    &self_.state.draw_options,
-                );
-            },
-        );
+        self.draw_with_filter(|self_| {
+            self_.maybe_bound_shape_with_pattern(
+                self_.state.fill_style.clone(),
+                &Rect::from_size(Size2D::new(total_advance, size)),
+                |self_| {
+                    self_.drawtarget.fill_text(
+                        shaped_runs,
+                        start,
+                        &self_.state.fill_style,
+                        &self_.state.draw_options,
+                    );
+                },
+            );
+        });
     }
 
     /// <https://html.spec.whatwg.org/multipage/#text-preparation-algorithm>
@@ -496,7 +519,7 @@ impl<'a, B: Backend> CanvasData<'a, B> {
 
     /// <https://html.spec.whatwg.org/multipage/#text-preparation-algorithm>
     /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-measuretext>
-    pub(crate) fn measure_text(&mut self, text: String) -> TextMetrics {
+    pub(crate) fn measure_text(&mut self, text: String, is_rtl: bool) -> TextMetrics {
         // > Step 2: Replace all ASCII whitespace in text with U+0020 SPACE characters.
         let text = replace_ascii_whitespace(text);
         let Some(ref font_style) = self.state.font_style else {
@@ -511,11 +534,7 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         let ascent = font.metrics.ascent.to_f32_px();
         let descent = font.metrics.descent.to_f32_px();
         let runs = self.build_unshaped_text_runs(&text, &mut font_group);
-
-        let shaped_runs: Vec<_> = runs
-            .into_iter()
-            .filter_map(UnshapedTextRun::into_shaped_text_run)
-            .collect();
+        let shaped_runs = Self::shape_text_runs(runs, is_rtl);
         let total_advance = shaped_runs
             .iter()
             .map(|run| run.glyphs.total_advance())
@@ -544,8 +563,7 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             },
         };
 
-        let anchor_x = match self.state.text_align {
-            TextAlign::End => total_advance,
+        let anchor_x = match self.resolve_text_align(is_rtl) {
             TextAlign::Center => total_advance / 2.,
             TextAlign::Right => total_advance,
             _ => 0.,
@@ -611,6 +629,32 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         runs
     }
 
+    /// Shape the given runs of text, in visual order. Each run is shaped in the direction
+    /// of the text, and for right-to-left text the order of the runs is reversed.
+    fn shape_text_runs(runs: Vec<UnshapedTextRun>, is_rtl: bool) -> Vec<TextRun> {
+        let mut shaped_runs: Vec<_> = runs
+            .into_iter()
+            .filter_map(|run| run.into_shaped_text_run(is_rtl))
+            .collect();
+        if is_rtl {
+            shaped_runs.reverse();
+        }
+        shaped_runs
+    }
+
+    /// Resolve the `start` and `end` values of the current text alignment to a physical
+    /// alignment, given the direction of the text.
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-textalign>
+    fn resolve_text_align(&self, is_rtl: bool) -> TextAlign {
+        match self.state.text_align {
+            TextAlign::Start if is_rtl => TextAlign::Right,
+            TextAlign::Start => TextAlign::Left,
+            TextAlign::End if is_rtl => TextAlign::Left,
+            TextAlign::End => TextAlign::Right,
+            text_align => text_align,
+        }
+    }
+
     /// Find the *anchor_point* for the given parameters of a line of text.
     /// See <https://html.spec.whatwg.org/multipage/#text-preparation-algorithm>.
     fn find_anchor_point_for_line_of_text(
@@ -621,14 +665,7 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         width: f32,
         is_rtl: bool,
     ) -> Point2D<f32> {
-        let text_align = match self.state.text_align {
-            TextAlign::Start if is_rtl => TextAlign::Right,
-            TextAlign::Start => TextAlign::Left,
-            TextAlign::End if is_rtl => TextAlign::Left,
-            TextAlign::End => TextAlign::Right,
-            text_align => text_align,
-        };
-        let anchor_x = match text_align {
+        let anchor_x = match self.resolve_text_align(is_rtl) {
             TextAlign::Center => -width / 2.,
             TextAlign::Right => -width,
             _ => 0.,
@@ -653,23 +690,29 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Paint nothing if gradient size is zero.
         }
 
-        if self.need_to_draw_shadow() {
-            self.draw_with_shadow(rect, |new_draw_target: &mut B::DrawTarget| {
-                new_draw_target.fill_rect(rect, &self.state.fill_style, &self.state.draw_options);
-            });
-        } else {
-            self.maybe_bound_shape_with_pattern(
-                self.state.fill_style.clone(),
-                &rect.cast(),
-                |self_| {
-                    self_.drawtarget.fill_rect(
+        self.draw_with_filter(|self_| {
+            if self_.need_to_draw_shadow() {
+                self_.draw_with_shadow(rect, |new_draw_target: &mut B::DrawTarget| {
+                    new_draw_target.fill_rect(
                         rect,
                         &self_.state.fill_style,
                         &self_.state.draw_options,
                     );
-                },
-            );
-        }
+                });
+            } else {
+                self_.maybe_bound_shape_with_pattern(
+                    self_.state.fill_style.clone(),
+                    &rect.cast(),
+                    |self_| {
+                        self_.drawtarget.fill_rect(
+                            rect,
+                            &self_.state.fill_style,
+                            &self_.state.draw_options,
+                        );
+                    },
+                );
+            }
+        });
     }
 
     pub(crate) fn clear_rect(&mut self, rect: &Rect<f32>) {
@@ -681,29 +724,31 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Paint nothing if gradient size is zero.
         }
 
-        if self.need_to_draw_shadow() {
-            self.draw_with_shadow(rect, |new_draw_target: &mut B::DrawTarget| {
-                new_draw_target.stroke_rect(
-                    rect,
-                    &self.state.stroke_style,
-                    &self.state.stroke_opts,
-                    &self.state.draw_options,
-                );
-            });
-        } else {
-            self.maybe_bound_shape_with_pattern(
-                self.state.stroke_style.clone(),
-                &rect.cast(),
-                |self_| {
-                    self_.drawtarget.stroke_rect(
+        self.draw_with_filter(|self_| {
+            if self_.need_to_draw_shadow() {
+                self_.draw_with_shadow(rect, |new_draw_target: &mut B::DrawTarget| {
+                    new_draw_target.stroke_rect(
                         rect,
                         &self_.state.stroke_style,
                         &self_.state.stroke_opts,
                         &self_.state.draw_options,
                     );
-                },
-            )
-        }
+                });
+            } else {
+                self_.maybe_bound_shape_with_pattern(
+                    self_.state.stroke_style.clone(),
+                    &rect.cast(),
+                    |self_| {
+                        self_.drawtarget.stroke_rect(
+                            rect,
+                            &self_.state.stroke_style,
+                            &self_.state.stroke_opts,
+                            &self_.state.draw_options,
+                        );
+                    },
+                )
+            }
+        });
     }
 
     pub(crate) fn begin_path(&mut self) {
@@ -767,17 +812,19 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Path is uninvertible.
         };
 
-        self.maybe_bound_shape_with_pattern(
-            self.state.fill_style.clone(),
-            &path.bounding_box(),
-            |self_| {
-                self_.drawtarget.fill(
-                    &path,
-                    &self_.state.fill_style,
-                    &self_.state.draw_options.clone(),
-                );
-            },
-        )
+        self.draw_with_filter(|self_| {
+            self_.maybe_bound_shape_with_pattern(
+                self_.state.fill_style.clone(),
+                &path.bounding_box(),
+                |self_| {
+                    self_.drawtarget.fill(
+                        &path,
+                        &self_.state.fill_style,
+                        &self_.state.draw_options.clone(),
+                    );
+                },
+            )
+        });
     }
 
     pub(crate) fn fill_path(&mut self, path: &Path) {
@@ -785,8 +832,11 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Paint nothing if gradient size is zero.
         }
 
-        self.drawtarget
-            .fill(path, &self.state.fill_style, &self.state.draw_options);
+        self.draw_with_filter(|self_| {
+            self_
+                .drawtarget
+                .fill(path, &self_.state.fill_style, &self_.state.draw_options);
+        });
     }
 
     pub(crate) fn stroke(&mut self) {
@@ -798,18 +848,20 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Path is uninvertible.
         };
 
-        self.maybe_bound_shape_with_pattern(
-            self.state.stroke_style.clone(),
-            &path.bounding_box(),
-            |self_| {
-                self_.drawtarget.stroke(
-                    &path,
-                    &self_.state.stroke_style,
-                    &self_.state.stroke_opts,
-                    &self_.state.draw_options,
-                );
-            },
-        )
+        self.draw_with_filter(|self_| {
+            self_.maybe_bound_shape_with_pattern(
+                self_.state.stroke_style.clone(),
+                &path.bounding_box(),
+                |self_| {
+                    self_.drawtarget.stroke(
+                        &path,
+                        &self_.state.stroke_style,
+                        &self_.state.stroke_opts,
+                        &self_.state.draw_options,
+                    );
+                },
+            )
+        });
     }
 
     pub(crate) fn stroke_path(&mut self, path: &Path) {
@@ -817,18 +869,20 @@ impl<'a, B: Backend> CanvasData<'a, B> {
             return; // Paint nothing if gradient size is zero.
         }
 
-        self.maybe_bound_shape_with_pattern(
-            self.state.stroke_style.clone(),
-            &path.bounding_box(),
-            |self_| {
-                self_.drawtarget.stroke(
-                    path,
-                    &self_.state.stroke_style,
-                    &self_.state.stroke_opts,
-                    &self_.state.draw_options,
-                );
-            },
-        )
+        self.draw_with_filter(|self_| {
+            self_.maybe_bound_shape_with_pattern(
+                self_.state.stroke_style.clone(),
+                &path.bounding_box(),
+                |self_| {
+                    self_.drawtarget.stroke(
+                        path,
+                        &self_.state.stroke_style,
+                        &self_.state.stroke_opts,
+                        &self_.state.draw_options,
+                    );
+                },
+            )
+        });
     }
 
     pub(crate) fn clip(&mut self) {
@@ -931,6 +985,10 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         self.path_builder().rect(rect);
     }
 
+    pub(crate) fn round_rect(&mut self, rect: &Rect<f32>, radii: &[Point2D<f32>; 4]) {
+        self.path_builder().round_rect(rect, radii);
+    }
+
     pub(crate) fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         if self.path_state.is_none() {
             self.move_to(cp);
@@ -1123,6 +1181,10 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         self.state.text_baseline = text_baseline;
     }
 
+    pub(crate) fn set_filter(&mut self, filter: Vec<CanvasFilter>) {
+        self.state.filter = filter;
+    }
+
     // https://html.spec.whatwg.org/multipage/#when-shadows-are-drawn
     fn need_to_draw_shadow(&self) -> bool {
         self.backend.need_to_draw_shadow(&self.state.shadow_color) &&
@@ -1163,6 +1225,45 @@ impl<'a, B: Backend> CanvasData<'a, B> {
         );
     }
 
+    /// Draw with the current filter applied: the drawing happens in a transparent layer
+    /// which is then filtered and composited into the canvas, with the current global
+    /// alpha, compositing operator and clipping region.
+    /// <https://html.spec.whatwg.org/multipage/#drawing-model>
+    fn draw_with_filter<F>(&mut self, draw: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        if self.state.filter.is_empty() {
+            draw(self);
+            return;
+        }
+
+        let size = self.drawtarget.get_size();
+        let transform = self.drawtarget.get_transform();
+        let mut layer = self.drawtarget.create_similar_draw_target(&size);
+        layer.set_transform(&transform);
+
+        let canvas = mem::replace(&mut self.drawtarget, layer);
+        let draw_options = mem::replace(
+            &mut self.state.draw_options,
+            self.backend.new_paint_state().draw_options,
+        );
+        draw(self);
+        self.state.draw_options = draw_options;
+        let layer = mem::replace(&mut self.drawtarget, canvas);
+
+        let snapshot = filters::apply_filters(layer.snapshot(), &self.state.filter);
+        self.drawtarget.set_transform(&Transform2D::identity());
+        write_image::<B>(
+            &mut self.drawtarget,
+            snapshot,
+            Rect::from_size(size.to_f64()),
+            false,
+            &self.state.draw_options,
+        );
+        self.drawtarget.set_transform(&transform);
+    }
+
     /// Push a clip to the draw target to respect the non-repeating bound (either x, y, or both)
     /// of the given pattern.
     fn maybe_bound_shape_with_pattern<F>(
@@ -1242,6 +1343,7 @@ pub(crate) struct CanvasPaintState<'a, B: Backend> {
     pub(crate) font_style: Option<ServoArc<FontStyleStruct>>,
     pub(crate) text_align: TextAlign,
     pub(crate) text_baseline: TextBaseline,
+    pub(crate) filter: Vec<CanvasFilter>,
     pub(crate) _backend: PhantomData<B>,
 }
 
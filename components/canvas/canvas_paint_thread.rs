@@ -18,12 +18,16 @@ use ipc_channel::router::ROUTER;
 use log::warn;
 use net_traits::ResourceThreads;
 use pixels::Snapshot;
+#[cfg(feature = "vello")]
+use servo_config::pref;
 use style::color::AbsoluteColor;
 use style::properties::style_structs::Font as FontStyleStruct;
 use webrender_api::ImageKey;
 
 use crate::canvas_data::*;
 use crate::raqote_backend::RaqoteBackend;
+#[cfg(feature = "vello")]
+use crate::vello_backend::VelloBackend;
 
 pub struct CanvasPaintThread<'a> {
     canvases: HashMap<CanvasId, Canvas<'a>>,
@@ -120,6 +124,21 @@ impl<'a> CanvasPaintThread<'a> {
         let canvas_id = self.next_canvas_id;
         self.next_canvas_id.0 += 1;
 
+        #[cfg(feature = "vello")]
+        if pref!(dom_canvas_vello_enabled) {
+            if let Some(backend) = VelloBackend::new(size) {
+                let canvas_data = CanvasData::new(
+                    size,
+                    self.compositor_api.clone(),
+                    self.font_context.clone(),
+                    backend,
+                );
+                let image_key = canvas_data.image_key();
+                self.canvases.insert(canvas_id, Canvas::Vello(canvas_data));
+                return (canvas_id, image_key);
+            }
+        }
+
         let canvas_data = CanvasData::new(
             size,
             self.compositor_api.clone(),
@@ -202,6 +221,9 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::MoveTo(ref point) => self.canvas(canvas_id).move_to(point),
             Canvas2dMsg::LineTo(ref point) => self.canvas(canvas_id).line_to(point),
             Canvas2dMsg::Rect(ref rect) => self.canvas(canvas_id).rect(rect),
+            Canvas2dMsg::RoundRect(ref rect, ref radii) => {
+                self.canvas(canvas_id).round_rect(rect, radii)
+            },
             Canvas2dMsg::QuadraticCurveTo(ref cp, ref pt) => {
                 self.canvas(canvas_id).quadratic_curve_to(cp, pt)
             },
@@ -217,8 +239,8 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::Ellipse(ref center, radius_x, radius_y, rotation, start, end, ccw) => self
                 .canvas(canvas_id)
                 .ellipse(center, radius_x, radius_y, rotation, start, end, ccw),
-            Canvas2dMsg::MeasureText(text, sender) => {
-                let metrics = self.canvas(canvas_id).measure_text(text);
+            Canvas2dMsg::MeasureText(text, is_rtl, sender) => {
+                let metrics = self.canvas(canvas_id).measure_text(text, is_rtl);
                 sender.send(metrics).unwrap();
             },
             Canvas2dMsg::RestoreContext => self.canvas(canvas_id).restore_context_state(),
@@ -265,6 +287,7 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::SetTextBaseline(text_baseline) => {
                 self.canvas(canvas_id).set_text_baseline(text_baseline)
             },
+            Canvas2dMsg::SetFilter(filter) => self.canvas(canvas_id).set_filter(filter),
             Canvas2dMsg::UpdateImage(sender) => {
                 self.canvas(canvas_id).update_image_rendering();
                 sender.send(()).unwrap();
@@ -279,90 +302,120 @@ impl<'a> CanvasPaintThread<'a> {
 
 enum Canvas<'a> {
     Raqote(CanvasData<'a, RaqoteBackend>),
+    #[cfg(feature = "vello")]
+    Vello(CanvasData<'a, VelloBackend>),
 }
 
 impl Canvas<'_> {
     fn set_fill_style(&mut self, style: FillOrStrokeStyle) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_fill_style(style),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_fill_style(style),
         }
     }
 
     fn fill(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.fill(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.fill(),
         }
     }
 
     fn fill_text(&mut self, text: String, x: f64, y: f64, max_width: Option<f64>, is_rtl: bool) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.fill_text(text, x, y, max_width, is_rtl),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.fill_text(text, x, y, max_width, is_rtl),
         }
     }
 
     fn fill_rect(&mut self, rect: &Rect<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.fill_rect(rect),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.fill_rect(rect),
         }
     }
 
     fn set_stroke_style(&mut self, style: FillOrStrokeStyle) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_stroke_style(style),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_stroke_style(style),
         }
     }
 
     fn stroke_rect(&mut self, rect: &Rect<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.stroke_rect(rect),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.stroke_rect(rect),
         }
     }
 
     fn begin_path(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.begin_path(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.begin_path(),
         }
     }
 
     fn close_path(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.close_path(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.close_path(),
         }
     }
 
     fn fill_path(&mut self, path: &Path) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.fill_path(path),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.fill_path(path),
         }
     }
 
     fn stroke(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.stroke(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.stroke(),
         }
     }
 
     fn stroke_path(&mut self, path: &Path) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.stroke_path(path),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.stroke_path(path),
         }
     }
 
     fn clip(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.clip(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.clip(),
         }
     }
 
     fn is_point_in_path(&mut self, x: f64, y: f64, fill_rule: FillRule, chan: IpcSender<bool>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.is_point_in_path(x, y, fill_rule, chan),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.is_point_in_path(x, y, fill_rule, chan),
         }
     }
 
     fn clear_rect(&mut self, rect: &Rect<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.clear_rect(rect),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.clear_rect(rect),
         }
     }
 
@@ -377,6 +430,10 @@ impl Canvas<'_> {
             Canvas::Raqote(canvas_data) => {
                 canvas_data.draw_image(snapshot, dest_rect, source_rect, smoothing_enabled)
             },
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => {
+                canvas_data.draw_image(snapshot, dest_rect, source_rect, smoothing_enabled)
+            },
         }
     }
 
@@ -387,48 +444,72 @@ impl Canvas<'_> {
     ) -> Snapshot {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.read_pixels(read_rect, canvas_size),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.read_pixels(read_rect, canvas_size),
         }
     }
 
     fn move_to(&mut self, point: &Point2D<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.move_to(point),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.move_to(point),
         }
     }
 
     fn line_to(&mut self, point: &Point2D<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.line_to(point),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.line_to(point),
         }
     }
 
     fn rect(&mut self, rect: &Rect<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.rect(rect),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.rect(rect),
+        }
+    }
+
+    fn round_rect(&mut self, rect: &Rect<f32>, radii: &[Point2D<f32>; 4]) {
+        match self {
+            Canvas::Raqote(canvas_data) => canvas_data.round_rect(rect, radii),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.round_rect(rect, radii),
         }
     }
 
     fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, pt: &Point2D<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.quadratic_curve_to(cp, pt),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.quadratic_curve_to(cp, pt),
         }
     }
 
     fn bezier_curve_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, pt: &Point2D<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.bezier_curve_to(cp1, cp2, pt),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.bezier_curve_to(cp1, cp2, pt),
         }
     }
 
     fn arc(&mut self, center: &Point2D<f32>, radius: f32, start: f32, end: f32, ccw: bool) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.arc(center, radius, start, end, ccw),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.arc(center, radius, start, end, ccw),
         }
     }
 
     fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.arc_to(cp1, cp2, radius),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.arc_to(cp1, cp2, radius),
         }
     }
 
@@ -447,150 +528,210 @@ impl Canvas<'_> {
             Canvas::Raqote(canvas_data) => {
                 canvas_data.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
             },
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => {
+                canvas_data.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
+            },
         }
     }
 
     fn restore_context_state(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.restore_context_state(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.restore_context_state(),
         }
     }
 
     fn save_context_state(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.save_context_state(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.save_context_state(),
         }
     }
 
     fn set_line_width(&mut self, width: f32) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_line_width(width),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_line_width(width),
         }
     }
 
     fn set_line_cap(&mut self, cap: LineCapStyle) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_line_cap(cap),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_line_cap(cap),
         }
     }
 
     fn set_line_join(&mut self, join: LineJoinStyle) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_line_join(join),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_line_join(join),
         }
     }
 
     fn set_miter_limit(&mut self, limit: f32) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_miter_limit(limit),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_miter_limit(limit),
         }
     }
 
     fn set_line_dash(&mut self, items: Vec<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_line_dash(items),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_line_dash(items),
         }
     }
 
     fn set_line_dash_offset(&mut self, offset: f32) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_line_dash_offset(offset),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_line_dash_offset(offset),
         }
     }
 
     fn set_transform(&mut self, matrix: &Transform2D<f32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_transform(matrix),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_transform(matrix),
         }
     }
 
     fn set_global_alpha(&mut self, alpha: f32) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_global_alpha(alpha),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_global_alpha(alpha),
         }
     }
 
     fn set_global_composition(&mut self, op: CompositionOrBlending) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_global_composition(op),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_global_composition(op),
         }
     }
 
     fn set_shadow_offset_x(&mut self, value: f64) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_shadow_offset_x(value),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_shadow_offset_x(value),
         }
     }
 
     fn set_shadow_offset_y(&mut self, value: f64) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_shadow_offset_y(value),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_shadow_offset_y(value),
         }
     }
 
     fn set_shadow_blur(&mut self, value: f64) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_shadow_blur(value),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_shadow_blur(value),
         }
     }
 
     fn set_shadow_color(&mut self, color: AbsoluteColor) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_shadow_color(color),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_shadow_color(color),
         }
     }
 
     fn set_font(&mut self, font_style: FontStyleStruct) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_font(font_style),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_font(font_style),
         }
     }
 
     fn set_text_align(&mut self, text_align: TextAlign) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_text_align(text_align),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_text_align(text_align),
         }
     }
 
     fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.set_text_baseline(text_baseline),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_text_baseline(text_baseline),
+        }
+    }
+
+    fn set_filter(&mut self, filter: Vec<CanvasFilter>) {
+        match self {
+            Canvas::Raqote(canvas_data) => canvas_data.set_filter(filter),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.set_filter(filter),
         }
     }
 
-    fn measure_text(&mut self, text: String) -> TextMetrics {
+    fn measure_text(&mut self, text: String, is_rtl: bool) -> TextMetrics {
         match self {
-            Canvas::Raqote(canvas_data) => canvas_data.measure_text(text),
+            Canvas::Raqote(canvas_data) => canvas_data.measure_text(text, is_rtl),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.measure_text(text, is_rtl),
         }
     }
 
     fn clip_path(&mut self, path: &Path) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.clip_path(path),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.clip_path(path),
         }
     }
 
     fn get_transform(&self) -> Transform2D<f32> {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.get_transform(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.get_transform(),
         }
     }
 
     fn put_image_data(&mut self, snapshot: Snapshot, rect: Rect<u32>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.put_image_data(snapshot, rect),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.put_image_data(snapshot, rect),
         }
     }
 
     fn update_image_rendering(&mut self) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.update_image_rendering(),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.update_image_rendering(),
         }
     }
 
     fn recreate(&mut self, size: Option<Size2D<u64>>) {
         match self {
             Canvas::Raqote(canvas_data) => canvas_data.recreate(size),
+            #[cfg(feature = "vello")]
+            Canvas::Vello(canvas_data) => canvas_data.recreate(size),
         }
     }
 }
@@ -0,0 +1,295 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The pixel operations behind the `filter` attribute of 2D canvas contexts.
+//! <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+
+use canvas_traits::canvas::CanvasFilter;
+use euclid::default::Size2D;
+use pixels::{Snapshot, SnapshotAlphaMode, SnapshotPixelFormat};
+
+/// Apply the given list of filters, in order, to the pixels of `snapshot`.
+pub(crate) fn apply_filters(mut snapshot: Snapshot, filters: &[CanvasFilter]) -> Snapshot {
+    let size = snapshot.size();
+    let format = snapshot.format();
+    let alpha_mode = snapshot.alpha_mode();
+    let unpremultiplied = SnapshotAlphaMode::Transparent {
+        premultiplied: false,
+    };
+
+    snapshot.transform(unpremultiplied, SnapshotPixelFormat::RGBA);
+    let mut image = FilterImage::from_bytes(size.to_usize(), snapshot.as_raw_bytes());
+    for filter in filters {
+        image.apply(filter);
+    }
+
+    let mut result = Snapshot::from_vec(
+        size,
+        SnapshotPixelFormat::RGBA,
+        unpremultiplied,
+        image.into_bytes(),
+    );
+    result.transform(alpha_mode, format);
+    result
+}
+
+/// An image of unpremultiplied RGBA pixels, with components in the range 0 to 1.
+#[derive(Clone)]
+struct FilterImage {
+    size: Size2D<usize>,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl FilterImage {
+    fn from_bytes(size: Size2D<usize>, bytes: &[u8]) -> Self {
+        let pixels = bytes
+            .chunks_exact(4)
+            .map(|pixel| [0, 1, 2, 3].map(|index| pixel[index] as f32 / 255.))
+            .collect();
+        FilterImage { size, pixels }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.pixels
+            .into_iter()
+            .flat_map(|pixel| pixel.map(|component| (component * 255.).round() as u8))
+            .collect()
+    }
+
+    /// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+    fn apply(&mut self, filter: &CanvasFilter) {
+        match *filter {
+            CanvasFilter::Blur(radius) => self.blur(radius as f32),
+            CanvasFilter::Brightness(amount) => {
+                let amount = amount as f32;
+                self.map_colors(|color| color * amount);
+            },
+            CanvasFilter::Contrast(amount) => {
+                let amount = amount as f32;
+                self.map_colors(|color| (color - 0.5) * amount + 0.5);
+            },
+            CanvasFilter::DropShadow {
+                offset_x,
+                offset_y,
+                blur,
+                color,
+            } => {
+                let color = color.into_srgb_legacy();
+                let components = color.components;
+                self.drop_shadow(
+                    offset_x.round() as isize,
+                    offset_y.round() as isize,
+                    blur as f32,
+                    [components.0, components.1, components.2, color.alpha],
+                );
+            },
+            CanvasFilter::Grayscale(amount) => {
+                let a = 1. - (amount as f32).min(1.);
+                self.color_matrix([
+                    [
+                        0.2126 + 0.7874 * a,
+                        0.7152 - 0.7152 * a,
+                        0.0722 - 0.0722 * a,
+                    ],
+                    [
+                        0.2126 - 0.2126 * a,
+                        0.7152 + 0.2848 * a,
+                        0.0722 - 0.0722 * a,
+                    ],
+                    [
+                        0.2126 - 0.2126 * a,
+                        0.7152 - 0.7152 * a,
+                        0.0722 + 0.9278 * a,
+                    ],
+                ]);
+            },
+            CanvasFilter::HueRotate(angle) => {
+                let (sin, cos) = (angle as f32).to_radians().sin_cos();
+                self.color_matrix([
+                    [
+                        0.213 + cos * 0.787 - sin * 0.213,
+                        0.715 - cos * 0.715 - sin * 0.715,
+                        0.072 - cos * 0.072 + sin * 0.928,
+                    ],
+                    [
+                        0.213 - cos * 0.213 + sin * 0.143,
+                        0.715 + cos * 0.285 + sin * 0.140,
+                        0.072 - cos * 0.072 - sin * 0.283,
+                    ],
+                    [
+                        0.213 - cos * 0.213 - sin * 0.787,
+                        0.715 - cos * 0.715 + sin * 0.715,
+                        0.072 + cos * 0.928 + sin * 0.072,
+                    ],
+                ]);
+            },
+            CanvasFilter::Invert(amount) => {
+                let amount = (amount as f32).min(1.);
+                self.map_colors(|color| amount * (1. - color) + (1. - amount) * color);
+            },
+            CanvasFilter::Opacity(amount) => {
+                let amount = (amount as f32).min(1.);
+                for pixel in self.pixels.iter_mut() {
+                    pixel[3] *= amount;
+                }
+            },
+            CanvasFilter::Saturate(amount) => {
+                let s = amount as f32;
+                self.color_matrix([
+                    [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s],
+                    [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s],
+                    [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s],
+                ]);
+            },
+            CanvasFilter::Sepia(amount) => {
+                let a = 1. - (amount as f32).min(1.);
+                self.color_matrix([
+                    [0.393 + 0.607 * a, 0.769 - 0.769 * a, 0.189 - 0.189 * a],
+                    [0.349 - 0.349 * a, 0.686 + 0.314 * a, 0.168 - 0.168 * a],
+                    [0.272 - 0.272 * a, 0.534 - 0.534 * a, 0.131 + 0.869 * a],
+                ]);
+            },
+        }
+    }
+
+    /// Apply `function` to the red, green and blue components of every pixel.
+    fn map_colors(&mut self, function: impl Fn(f32) -> f32) {
+        for pixel in self.pixels.iter_mut() {
+            for component in &mut pixel[0..3] {
+                *component = function(*component).clamp(0., 1.);
+            }
+        }
+    }
+
+    /// <https://drafts.fxtf.org/filter-effects/#feColorMatrixElement>
+    fn color_matrix(&mut self, matrix: [[f32; 3]; 3]) {
+        for pixel in self.pixels.iter_mut() {
+            let [r, g, b, _] = *pixel;
+            for (component, row) in pixel.iter_mut().zip(matrix) {
+                *component = (row[0] * r + row[1] * g + row[2] * b).clamp(0., 1.);
+            }
+        }
+    }
+
+    /// A gaussian blur with the given standard deviation, approximated by three
+    /// successive box blurs.
+    /// <https://drafts.fxtf.org/filter-effects/#feGaussianBlurElement>
+    fn blur(&mut self, deviation: f32) {
+        let box_size =
+            (deviation * 3. * (2. * std::f32::consts::PI).sqrt() / 4. + 0.5).floor() as usize;
+        let radius = box_size / 2;
+        if radius == 0 {
+            return;
+        }
+
+        // Blurring happens on premultiplied colors, so that transparent pixels don't
+        // bleed their color into their neighbours.
+        for pixel in self.pixels.iter_mut() {
+            let alpha = pixel[3];
+            for component in &mut pixel[0..3] {
+                *component *= alpha;
+            }
+        }
+
+        for _ in 0..3 {
+            self.box_blur(radius, true);
+            self.box_blur(radius, false);
+        }
+
+        for pixel in self.pixels.iter_mut() {
+            let alpha = pixel[3];
+            for component in &mut pixel[0..3] {
+                *component = if alpha > 0. {
+                    (*component / alpha).clamp(0., 1.)
+                } else {
+                    0.
+                };
+            }
+        }
+    }
+
+    /// Average every pixel with the `radius` pixels on either side of it, in rows if
+    /// `horizontal` is true and in columns otherwise. Pixels outside of the image are
+    /// transparent black.
+    fn box_blur(&mut self, radius: usize, horizontal: bool) {
+        let (lines, length) = if horizontal {
+            (self.size.height, self.size.width)
+        } else {
+            (self.size.width, self.size.height)
+        };
+        let index = |line: usize, position: usize| {
+            if horizontal {
+                line * self.size.width + position
+            } else {
+                position * self.size.width + line
+            }
+        };
+        let window = (2 * radius + 1) as f32;
+
+        let mut blurred = vec![[0.; 4]; self.pixels.len()];
+        for line in 0..lines {
+            let mut sum = [0.; 4];
+            for position in 0..radius.min(length) {
+                add_pixel(&mut sum, &self.pixels[index(line, position)], 1.);
+            }
+            for position in 0..length {
+                if position + radius < length {
+                    add_pixel(&mut sum, &self.pixels[index(line, position + radius)], 1.);
+                }
+                if position > radius {
+                    add_pixel(
+                        &mut sum,
+                        &self.pixels[index(line, position - radius - 1)],
+                        -1.,
+                    );
+                }
+                blurred[index(line, position)] = sum.map(|component| component / window);
+            }
+        }
+        self.pixels = blurred;
+    }
+
+    /// <https://drafts.fxtf.org/filter-effects/#funcdef-filter-drop-shadow>
+    fn drop_shadow(&mut self, offset_x: isize, offset_y: isize, blur: f32, color: [f32; 4]) {
+        let (width, height) = (self.size.width as isize, self.size.height as isize);
+        let mut shadow = FilterImage {
+            size: self.size,
+            pixels: vec![[0.; 4]; self.pixels.len()],
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let (source_x, source_y) = (x - offset_x, y - offset_y);
+                if source_x < 0 || source_y < 0 || source_x >= width || source_y >= height {
+                    continue;
+                }
+                let source_alpha = self.pixels[(source_y * width + source_x) as usize][3];
+                shadow.pixels[(y * width + x) as usize] =
+                    [color[0], color[1], color[2], color[3] * source_alpha];
+            }
+        }
+        shadow.blur(blur);
+
+        // Composite the image over its shadow.
+        for (pixel, shadow) in self.pixels.iter_mut().zip(shadow.pixels) {
+            let (source_alpha, shadow_alpha) = (pixel[3], shadow[3]);
+            let alpha = source_alpha + shadow_alpha * (1. - source_alpha);
+            if alpha <= 0. {
+                *pixel = [0.; 4];
+                continue;
+            }
+            for (component, shadow_component) in pixel[0..3].iter_mut().zip(shadow) {
+                *component = (*component * source_alpha +
+                    shadow_component * shadow_alpha * (1. - source_alpha)) /
+                    alpha;
+            }
+            pixel[3] = alpha;
+        }
+    }
+}
+
+fn add_pixel(sum: &mut [f32; 4], pixel: &[f32; 4], sign: f32) {
+    for (total, component) in sum.iter_mut().zip(pixel) {
+        *total += sign * component;
+    }
+}
@@ -5,7 +5,10 @@
 #![deny(unsafe_code)]
 
 mod backend;
+mod filters;
 mod raqote_backend;
+#[cfg(feature = "vello")]
+mod vello_backend;
 
 pub mod canvas_data;
 pub mod canvas_paint_thread;
@@ -107,6 +107,7 @@ impl Backend for RaqoteBackend {
             font_style: None,
             text_align: TextAlign::default(),
             text_baseline: TextBaseline::default(),
+            filter: Vec::new(),
             _backend: std::marker::PhantomData,
         }
     }
@@ -118,6 +119,7 @@ pub enum Pattern {
     Color(u8, u8, u8, u8),
     LinearGradient(LinearGradientPattern),
     RadialGradient(RadialGradientPattern),
+    ConicGradient(ConicGradientPattern),
     Surface(SurfacePattern),
 }
 
@@ -165,6 +167,24 @@ impl RadialGradientPattern {
     }
 }
 
+#[derive(Clone)]
+pub struct ConicGradientPattern {
+    gradient: raqote::Gradient,
+    center: Point2D<f32>,
+    /// The start angle of the gradient, in degrees clockwise from the positive x axis.
+    start_angle: f32,
+}
+
+impl ConicGradientPattern {
+    fn new(center: Point2D<f32>, start_angle: f32, stops: Vec<raqote::GradientStop>) -> Self {
+        ConicGradientPattern {
+            gradient: raqote::Gradient { stops },
+            center,
+            start_angle,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SurfacePattern {
     image: Snapshot,
@@ -242,6 +262,13 @@ pub fn source(pattern: &Pattern) -> raqote::Source {
             pattern.radius2,
             raqote::Spread::Pad,
         ),
+        Pattern::ConicGradient(pattern) => raqote::Source::new_sweep_gradient(
+            pattern.gradient.clone(),
+            pattern.center,
+            pattern.start_angle,
+            pattern.start_angle + 360.,
+            raqote::Spread::Pad,
+        ),
         Pattern::Surface(pattern) => {
             #[allow(unsafe_code)]
             let data = unsafe {
@@ -276,6 +303,7 @@ impl PatternHelpers for Pattern {
             Pattern::LinearGradient(pattern) => {
                 (pattern.start == pattern.end) || pattern.gradient.stops.is_empty()
             },
+            Pattern::ConicGradient(pattern) => pattern.gradient.stops.is_empty(),
             Pattern::Color(..) | Pattern::Surface(..) => false,
         }
     }
@@ -286,7 +314,10 @@ impl PatternHelpers for Pattern {
                 Repetition::RepeatX | Repetition::Repeat => None, // x is not bounded
                 Repetition::RepeatY | Repetition::NoRepeat => Some(pattern.image.size().width),
             },
-            Pattern::Color(..) | Pattern::LinearGradient(..) | Pattern::RadialGradient(..) => None,
+            Pattern::Color(..) |
+            Pattern::LinearGradient(..) |
+            Pattern::RadialGradient(..) |
+            Pattern::ConicGradient(..) => None,
         }
     }
 
@@ -296,7 +327,10 @@ impl PatternHelpers for Pattern {
                 Repetition::RepeatY | Repetition::Repeat => None, // y is not bounded
                 Repetition::RepeatX | Repetition::NoRepeat => Some(pattern.image.size().height),
             },
-            Pattern::Color(..) | Pattern::LinearGradient(..) | Pattern::RadialGradient(..) => None,
+            Pattern::Color(..) |
+            Pattern::LinearGradient(..) |
+            Pattern::RadialGradient(..) |
+            Pattern::ConicGradient(..) => None,
         }
     }
 }
@@ -757,6 +791,15 @@ impl ToRaqotePattern for FillOrStrokeStyle {
                     stops,
                 )))
             },
+            ConicGradient(style) => {
+                let center = Point2D::new(style.x as f32, style.y as f32);
+                let stops = create_gradient_stops(style.stops);
+                Some(Pattern::ConicGradient(ConicGradientPattern::new(
+                    center,
+                    style.angle.to_degrees() as f32,
+                    stops,
+                )))
+            },
             Surface(style) => {
                 let repeat = Repetition::from_xy(style.repeat_x, style.repeat_y);
                 let mut snapshot = style.surface_data.to_owned();
@@ -0,0 +1,915 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A GPU accelerated canvas backend, which records drawing commands into a vello
+//! [`Scene`] and rasterizes them with wgpu.
+//!
+//! The contents of a canvas live in one of two textures. Pending commands are drawn on
+//! top of the texture holding the current contents, and rendered into the other one
+//! whenever the canvas is read or too many commands have accumulated.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use canvas_traits::canvas::*;
+use compositing_traits::SerializableImageData;
+use cssparser::color::clamp_unit_f32;
+use euclid::default::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+use fonts::{ByteIndex, FontIdentifier, FontTemplateRefMethods};
+use ipc_channel::ipc::IpcSharedMemory;
+use kurbo::{Affine, Cap, Join, Shape};
+use log::warn;
+use pixels::{Snapshot, SnapshotAlphaMode, SnapshotPixelFormat};
+use range::Range;
+use style::color::AbsoluteColor;
+use vello::peniko::{self, BlendMode, Blob, Brush, ColorStop, Compose, Extend, Fill, Mix};
+use vello::{AaConfig, AaSupport, Glyph, RenderParams, Renderer, RendererOptions, Scene, wgpu};
+use webrender_api::{ImageDescriptor, ImageDescriptorFlags, ImageFormat};
+
+use crate::backend::{
+    Backend, DrawOptionsHelpers, GenericDrawTarget, PatternHelpers, StrokeOptionsHelpers,
+};
+use crate::canvas_data::{CanvasPaintState, Filter, TextRun};
+
+/// The number of drawing commands after which pending commands are rendered, so that
+/// scenes of long running animations don't grow without bounds.
+const MAX_PENDING_COMMANDS: usize = 1024;
+
+thread_local! {
+    /// The GPU device shared by all canvases that render on a thread, or `None` if no
+    /// usable device could be created.
+    static SHARED_GPU_CONTEXT: Option<Rc<GpuContext>> = GpuContext::new();
+
+    /// The shared font cache used by all canvases that render on a thread, so that font
+    /// data is only copied into a [`Blob`] once.
+    static SHARED_FONT_CACHE: RefCell<HashMap<FontIdentifier, peniko::Font>> =
+        RefCell::default();
+}
+
+struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    renderer: RefCell<Renderer>,
+}
+
+impl GpuContext {
+    fn new() -> Option<Rc<Self>> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let Some(adapter) =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            }))
+        else {
+            warn!("No GPU adapter available for canvas rendering");
+            return None;
+        };
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Canvas"),
+                ..Default::default()
+            },
+            None,
+        ))
+        .inspect_err(|error| warn!("Could not create a GPU device for canvas: {error}"))
+        .ok()?;
+        let renderer = Renderer::new(
+            &device,
+            RendererOptions {
+                use_cpu: false,
+                antialiasing_support: AaSupport::area_only(),
+                num_init_threads: None,
+                pipeline_cache: None,
+            },
+        )
+        .inspect_err(|error| warn!("Could not create a canvas renderer: {error}"))
+        .ok()?;
+        Some(Rc::new(GpuContext {
+            device,
+            queue,
+            renderer: RefCell::new(renderer),
+        }))
+    }
+
+    fn max_size(&self) -> i32 {
+        self.device.limits().max_texture_dimension_2d as i32
+    }
+
+    fn render(&self, scene: &Scene, texture: &wgpu::Texture) {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let params = RenderParams {
+            base_color: peniko::Color::TRANSPARENT,
+            width: texture.width(),
+            height: texture.height(),
+            antialiasing_method: AaConfig::Area,
+        };
+        if let Err(error) = self.renderer.borrow_mut().render_to_texture(
+            &self.device,
+            &self.queue,
+            scene,
+            &view,
+            &params,
+        ) {
+            warn!("Could not render canvas: {error}");
+        }
+    }
+
+    /// Read back the unpremultiplied RGBA pixels of `texture`.
+    fn read_texture(&self, texture: &wgpu::Texture) -> Vec<u8> {
+        let (width, height) = (texture.width(), texture.height());
+        let row_length = width * 4;
+        let padded_row_length = row_length.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Canvas readback"),
+            size: (padded_row_length * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_length),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        let _ = self.device.poll(wgpu::Maintain::Wait);
+
+        let mut pixels = Vec::with_capacity((row_length * height) as usize);
+        for row in slice
+            .get_mapped_range()
+            .chunks_exact(padded_row_length as usize)
+        {
+            pixels.extend_from_slice(&row[..row_length as usize]);
+        }
+        buffer.unmap();
+        pixels
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct VelloBackend {
+    context: Rc<GpuContext>,
+}
+
+impl VelloBackend {
+    /// Returns a backend which renders with the GPU device of the current thread, or
+    /// `None` if there is no GPU device or it cannot hold a canvas of the given size.
+    pub(crate) fn new(size: Size2D<u64>) -> Option<VelloBackend> {
+        let context = SHARED_GPU_CONTEXT.with(Clone::clone)?;
+        let max_size = context.max_size() as u64;
+        if size.width > max_size || size.height > max_size {
+            return None;
+        }
+        Some(VelloBackend { context })
+    }
+}
+
+impl Backend for VelloBackend {
+    type Pattern<'a> = Pattern;
+    type StrokeOptions = kurbo::Stroke;
+    type Color = peniko::Color;
+    type DrawOptions = DrawOptions;
+    type CompositionOp = BlendMode;
+    type DrawTarget = DrawTarget;
+    type SourceSurface = peniko::Image;
+    type GradientStop = ColorStop;
+    type GradientStops = Vec<ColorStop>;
+
+    fn get_composition_op(&self, opts: &Self::DrawOptions) -> Self::CompositionOp {
+        opts.blend_mode
+    }
+
+    fn need_to_draw_shadow(&self, color: &Self::Color) -> bool {
+        color.components[3] != 0.
+    }
+
+    fn set_shadow_color(&mut self, color: AbsoluteColor, state: &mut CanvasPaintState<'_, Self>) {
+        state.shadow_color = to_color(color);
+    }
+
+    fn set_fill_style(
+        &mut self,
+        style: FillOrStrokeStyle,
+        state: &mut CanvasPaintState<'_, Self>,
+        _drawtarget: &Self::DrawTarget,
+    ) {
+        state.fill_style = style.into();
+    }
+
+    fn set_stroke_style(
+        &mut self,
+        style: FillOrStrokeStyle,
+        state: &mut CanvasPaintState<'_, Self>,
+        _drawtarget: &Self::DrawTarget,
+    ) {
+        state.stroke_style = style.into();
+    }
+
+    fn set_global_composition(
+        &mut self,
+        op: CompositionOrBlending,
+        state: &mut CanvasPaintState<'_, Self>,
+    ) {
+        state.draw_options.blend_mode = to_blend_mode(op);
+    }
+
+    fn create_drawtarget(&self, size: Size2D<u64>) -> Self::DrawTarget {
+        DrawTarget::new(self.context.clone(), size.cast())
+    }
+
+    fn new_paint_state<'a>(&self) -> CanvasPaintState<'a, Self> {
+        let pattern = Pattern {
+            brush: Brush::Solid(peniko::Color::BLACK),
+            transform: None,
+        };
+        CanvasPaintState {
+            draw_options: DrawOptions::default(),
+            fill_style: pattern.clone(),
+            stroke_style: pattern,
+            stroke_opts: kurbo::Stroke::new(1.)
+                .with_join(Join::Miter)
+                .with_miter_limit(10.)
+                .with_caps(Cap::Butt),
+            transform: Transform2D::identity(),
+            shadow_offset_x: 0.0,
+            shadow_offset_y: 0.0,
+            shadow_blur: 0.0,
+            shadow_color: peniko::Color::TRANSPARENT,
+            font_style: None,
+            text_align: TextAlign::default(),
+            text_baseline: TextBaseline::default(),
+            filter: Vec::new(),
+            _backend: std::marker::PhantomData,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Pattern {
+    brush: Brush,
+    /// The transform from the pattern space to the user space, if any.
+    transform: Option<Affine>,
+}
+
+impl PatternHelpers for Pattern {
+    fn is_zero_size_gradient(&self) -> bool {
+        let Brush::Gradient(gradient) = &self.brush else {
+            return false;
+        };
+        gradient.stops.is_empty() ||
+            match gradient.kind {
+                peniko::GradientKind::Linear { start, end } => start == end,
+                peniko::GradientKind::Radial {
+                    start_center,
+                    start_radius,
+                    end_center,
+                    end_radius,
+                } => start_center == end_center && start_radius == end_radius,
+                peniko::GradientKind::Sweep { .. } => false,
+            }
+    }
+
+    fn x_bound(&self) -> Option<u32> {
+        match &self.brush {
+            Brush::Image(image) if image.x_extend == Extend::Pad => Some(image.width),
+            _ => None,
+        }
+    }
+
+    fn y_bound(&self) -> Option<u32> {
+        match &self.brush {
+            Brush::Image(image) if image.y_extend == Extend::Pad => Some(image.height),
+            _ => None,
+        }
+    }
+}
+
+impl From<FillOrStrokeStyle> for Pattern {
+    fn from(style: FillOrStrokeStyle) -> Self {
+        let (brush, transform) = match style {
+            FillOrStrokeStyle::Color(color) => (Brush::Solid(to_color(color)), None),
+            FillOrStrokeStyle::LinearGradient(style) => {
+                let gradient =
+                    peniko::Gradient::new_linear((style.x0, style.y0), (style.x1, style.y1))
+                        .with_stops(create_gradient_stops(style.stops).as_slice());
+                (Brush::Gradient(gradient), None)
+            },
+            FillOrStrokeStyle::RadialGradient(style) => {
+                let gradient = peniko::Gradient::new_two_point_radial(
+                    (style.x0, style.y0),
+                    style.r0 as f32,
+                    (style.x1, style.y1),
+                    style.r1 as f32,
+                )
+                .with_stops(create_gradient_stops(style.stops).as_slice());
+                (Brush::Gradient(gradient), None)
+            },
+            FillOrStrokeStyle::ConicGradient(style) => {
+                let start_angle = style.angle as f32;
+                let gradient = peniko::Gradient::new_sweep(
+                    (style.x, style.y),
+                    start_angle,
+                    start_angle + std::f32::consts::TAU,
+                )
+                .with_stops(create_gradient_stops(style.stops).as_slice());
+                (Brush::Gradient(gradient), None)
+            },
+            FillOrStrokeStyle::Surface(style) => {
+                let extend = |repeat| if repeat { Extend::Repeat } else { Extend::Pad };
+                let image = to_image(style.surface_data.to_owned())
+                    .with_x_extend(extend(style.repeat_x))
+                    .with_y_extend(extend(style.repeat_y))
+                    .with_quality(peniko::ImageQuality::Low);
+                (Brush::Image(image), Some(to_affine(&style.transform)))
+            },
+        };
+        Pattern { brush, transform }
+    }
+}
+
+impl StrokeOptionsHelpers for kurbo::Stroke {
+    fn set_line_width(&mut self, _val: f32) {
+        self.width = _val as f64;
+    }
+    fn set_miter_limit(&mut self, _val: f32) {
+        self.miter_limit = _val as f64;
+    }
+    fn set_line_join(&mut self, val: LineJoinStyle) {
+        self.join = match val {
+            LineJoinStyle::Round => Join::Round,
+            LineJoinStyle::Bevel => Join::Bevel,
+            LineJoinStyle::Miter => Join::Miter,
+        };
+    }
+    fn set_line_cap(&mut self, val: LineCapStyle) {
+        let cap = match val {
+            LineCapStyle::Butt => Cap::Butt,
+            LineCapStyle::Round => Cap::Round,
+            LineCapStyle::Square => Cap::Square,
+        };
+        self.start_cap = cap;
+        self.end_cap = cap;
+    }
+    fn set_line_dash(&mut self, items: Vec<f32>) {
+        self.dash_pattern = items.into_iter().map(f64::from).collect();
+    }
+    fn set_line_dash_offset(&mut self, offset: f32) {
+        self.dash_offset = offset as f64;
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct DrawOptions {
+    alpha: f32,
+    blend_mode: BlendMode,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        DrawOptions {
+            alpha: 1.,
+            blend_mode: BlendMode::default(),
+        }
+    }
+}
+
+impl DrawOptionsHelpers for DrawOptions {
+    fn set_alpha(&mut self, val: f32) {
+        self.alpha = val;
+    }
+
+    fn is_clear(&self) -> bool {
+        self.blend_mode.compose == Compose::Clear
+    }
+}
+
+pub(crate) struct DrawTarget {
+    context: Rc<GpuContext>,
+    size: Size2D<i32>,
+    transform: Transform2D<f32>,
+    /// The clip paths pushed to the scene, with the transforms they were pushed with.
+    clips: Vec<(kurbo::BezPath, Affine)>,
+    /// The commands which have not been rendered into `textures` yet.
+    scene: RefCell<Scene>,
+    pending_commands: Cell<usize>,
+    textures: [wgpu::Texture; 2],
+    /// Images standing for `textures` in scenes. Their pixels are never uploaded, as
+    /// the renderer is told to sample from the textures directly.
+    backdrops: [peniko::Image; 2],
+    /// The index of the texture holding the rendered contents of the canvas, or `None`
+    /// if nothing has been rendered yet.
+    current_texture: Cell<Option<usize>>,
+    /// The pixels read back after the last rendering, until something is drawn again.
+    pixels: RefCell<Option<Vec<u8>>>,
+}
+
+impl DrawTarget {
+    fn new(context: Rc<GpuContext>, size: Size2D<i32>) -> DrawTarget {
+        let max_size = context.max_size();
+        if size.width > max_size || size.height > max_size {
+            warn!("Canvas of size {size:?} is larger than the GPU allows, clamping it");
+        }
+        let size = size
+            .max(Size2D::new(1, 1))
+            .min(Size2D::new(max_size, max_size));
+
+        let textures = [(); 2].map(|_| {
+            context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Canvas"),
+                size: wgpu::Extent3d {
+                    width: size.width as u32,
+                    height: size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::STORAGE_BINDING |
+                    wgpu::TextureUsages::TEXTURE_BINDING |
+                    wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        });
+        let backdrops = textures.each_ref().map(|texture| {
+            let image = peniko::Image::new(
+                Blob::new(Arc::new(Vec::new())),
+                peniko::ImageFormat::Rgba8,
+                size.width as u32,
+                size.height as u32,
+            );
+            context.renderer.borrow_mut().override_image(
+                &image,
+                Some(wgpu::TexelCopyTextureInfoBase {
+                    texture: texture.clone(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                }),
+            );
+            image
+        });
+
+        DrawTarget {
+            context,
+            size,
+            transform: Transform2D::identity(),
+            clips: Vec::new(),
+            scene: RefCell::new(Scene::new()),
+            pending_commands: Cell::new(0),
+            textures,
+            backdrops,
+            current_texture: Cell::new(None),
+            pixels: RefCell::new(None),
+        }
+    }
+
+    fn bounds(&self) -> kurbo::Rect {
+        kurbo::Rect::new(0., 0., self.size.width as f64, self.size.height as f64)
+    }
+
+    /// Record commands into the scene, rendering it if enough commands are pending.
+    fn record(&self, record: impl FnOnce(&mut Scene)) {
+        self.pixels.take();
+        record(&mut self.scene.borrow_mut());
+        self.pending_commands.set(self.pending_commands.get() + 1);
+        if self.pending_commands.get() >= MAX_PENDING_COMMANDS {
+            self.flush();
+        }
+    }
+
+    /// Record a drawing command with the given global alpha and compositing operator,
+    /// which apply to the whole drawing at once.
+    fn draw(&self, draw_options: &DrawOptions, draw: impl FnOnce(&mut Scene, Affine)) {
+        let transform = to_affine(&self.transform);
+        let needs_layer =
+            draw_options.alpha != 1. || draw_options.blend_mode != BlendMode::default();
+        let bounds = self.bounds();
+        self.record(|scene| {
+            if needs_layer {
+                scene.push_layer(
+                    draw_options.blend_mode,
+                    draw_options.alpha,
+                    Affine::IDENTITY,
+                    &bounds,
+                );
+            }
+            draw(scene, transform);
+            if needs_layer {
+                scene.pop_layer();
+            }
+        });
+    }
+
+    /// Record commands which must not be affected by the current clip.
+    fn record_unclipped(&self, record: impl FnOnce(&mut Scene)) {
+        self.record(|scene| {
+            self.clips.iter().for_each(|_| scene.pop_layer());
+            record(scene);
+            self.push_clips(scene);
+        });
+    }
+
+    fn push_clips(&self, scene: &mut Scene) {
+        for (path, transform) in &self.clips {
+            scene.push_layer(Mix::Clip, 1., *transform, path);
+        }
+    }
+
+    /// Render the pending commands on top of the current contents of the canvas, into
+    /// the other texture.
+    fn flush(&self) {
+        if self.pending_commands.replace(0) == 0 {
+            return;
+        }
+
+        let mut scene = self.scene.borrow_mut();
+        self.clips.iter().for_each(|_| scene.pop_layer());
+        let target = self.current_texture.get().map_or(0, |index| 1 - index);
+        self.context.render(&scene, &self.textures[target]);
+        self.current_texture.set(Some(target));
+
+        scene.reset();
+        scene.draw_image(&self.backdrops[target], Affine::IDENTITY);
+        self.push_clips(&mut scene);
+    }
+
+    /// The unpremultiplied RGBA pixels of the canvas.
+    fn pixels(&self) -> Vec<u8> {
+        if let Some(pixels) = self.pixels.borrow().as_ref() {
+            return pixels.clone();
+        }
+
+        self.flush();
+        let pixels = match self.current_texture.get() {
+            Some(index) => self.context.read_texture(&self.textures[index]),
+            None => vec![0; self.size.area() as usize * 4],
+        };
+        *self.pixels.borrow_mut() = Some(pixels.clone());
+        pixels
+    }
+}
+
+impl Drop for DrawTarget {
+    fn drop(&mut self) {
+        let mut renderer = self.context.renderer.borrow_mut();
+        for backdrop in &self.backdrops {
+            renderer.override_image(backdrop, None);
+        }
+    }
+}
+
+impl GenericDrawTarget<VelloBackend> for DrawTarget {
+    fn clear_rect(&mut self, rect: &Rect<f32>) {
+        let transform = to_affine(&self.transform);
+        self.record(|scene| {
+            scene.push_layer(Compose::Clear, 1., transform, &to_rect(rect.cast()));
+            scene.pop_layer();
+        });
+    }
+
+    fn copy_surface(
+        &mut self,
+        surface: <VelloBackend as Backend>::SourceSurface,
+        source: Rect<i32>,
+        destination: Point2D<i32>,
+    ) {
+        let destination_rect = Rect::new(destination, source.size);
+        let offset = (destination - source.origin).cast::<f64>();
+        self.record_unclipped(|scene| {
+            scene.push_layer(
+                Compose::Copy,
+                1.,
+                Affine::IDENTITY,
+                &to_rect(destination_rect.cast()),
+            );
+            scene.draw_image(&surface, Affine::translate((offset.x, offset.y)));
+            scene.pop_layer();
+        });
+    }
+
+    fn create_similar_draw_target(
+        &self,
+        size: &Size2D<i32>,
+    ) -> <VelloBackend as Backend>::DrawTarget {
+        DrawTarget::new(self.context.clone(), *size)
+    }
+    fn create_source_surface_from_data(
+        &self,
+        data: Snapshot,
+    ) -> Option<<VelloBackend as Backend>::SourceSurface> {
+        Some(to_image(data))
+    }
+    fn draw_surface(
+        &mut self,
+        surface: <VelloBackend as Backend>::SourceSurface,
+        dest: Rect<f64>,
+        source: Rect<f64>,
+        filter: Filter,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        let quality = match filter {
+            Filter::Bilinear => peniko::ImageQuality::Medium,
+            Filter::Nearest => peniko::ImageQuality::Low,
+        };
+        let image = surface.with_quality(quality);
+        let image_transform = Affine::translate((dest.origin.x, dest.origin.y)) *
+            Affine::scale_non_uniform(
+                dest.size.width / source.size.width,
+                dest.size.height / source.size.height,
+            );
+        self.draw(draw_options, |scene, transform| {
+            scene.draw_image(&image, transform * image_transform)
+        });
+    }
+    fn draw_surface_with_shadow(
+        &self,
+        _surface: <VelloBackend as Backend>::SourceSurface,
+        _dest: &Point2D<f32>,
+        _color: &<VelloBackend as Backend>::Color,
+        _offset: &Vector2D<f32>,
+        _sigma: f32,
+        _operator: <VelloBackend as Backend>::CompositionOp,
+    ) {
+        warn!("no support for drawing shadows");
+    }
+    fn fill(
+        &mut self,
+        path: &canvas_traits::canvas::Path,
+        pattern: &Pattern,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        self.draw(draw_options, |scene, transform| {
+            scene.fill(
+                Fill::NonZero,
+                transform,
+                &pattern.brush,
+                pattern.transform,
+                &path.0,
+            )
+        });
+    }
+
+    fn fill_text(
+        &mut self,
+        text_runs: Vec<TextRun>,
+        start: Point2D<f32>,
+        pattern: &Pattern,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        self.draw(draw_options, |scene, transform| {
+            let mut advance = 0.;
+            for run in text_runs.iter() {
+                let glyphs = &run.glyphs;
+                let glyphs: Vec<_> = glyphs
+                    .iter_glyphs_for_byte_range(&Range::new(ByteIndex(0), glyphs.len()))
+                    .map(|glyph| {
+                        let glyph_offset = glyph.offset().unwrap_or(Point2D::zero());
+                        let position = Glyph {
+                            id: glyph.id(),
+                            x: advance + start.x + glyph_offset.x.to_f32_px(),
+                            y: start.y + glyph_offset.y.to_f32_px(),
+                        };
+                        advance += glyph.advance().to_f32_px();
+                        position
+                    })
+                    .collect();
+
+                SHARED_FONT_CACHE.with(|font_cache| {
+                    let identifier = run.font.template.identifier();
+                    let font = font_cache
+                        .borrow_mut()
+                        .entry(identifier.clone())
+                        .or_insert_with(|| {
+                            let data = Arc::new(run.font.data().as_ref().to_vec());
+                            peniko::Font::new(Blob::new(data), identifier.index())
+                        })
+                        .clone();
+                    scene
+                        .draw_glyphs(&font)
+                        .font_size(run.font.descriptor.pt_size.to_f32_px())
+                        .transform(transform)
+                        .brush(&pattern.brush)
+                        .draw(Fill::NonZero, glyphs.into_iter());
+                })
+            }
+        });
+    }
+
+    fn fill_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        pattern: &<VelloBackend as Backend>::Pattern<'_>,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        let rect = to_rect(rect.cast());
+        self.draw(draw_options, |scene, transform| {
+            scene.fill(
+                Fill::NonZero,
+                transform,
+                &pattern.brush,
+                pattern.transform,
+                &rect,
+            )
+        });
+    }
+    fn get_size(&self) -> Size2D<i32> {
+        self.size
+    }
+    fn get_transform(&self) -> Transform2D<f32> {
+        self.transform
+    }
+    fn pop_clip(&mut self) {
+        if self.clips.pop().is_some() {
+            self.record(|scene| scene.pop_layer());
+        }
+    }
+    fn push_clip(&mut self, path: &canvas_traits::canvas::Path) {
+        let transform = to_affine(&self.transform);
+        self.record(|scene| scene.push_layer(Mix::Clip, 1., transform, &path.0));
+        self.clips.push((path.0.clone(), transform));
+    }
+    fn push_clip_rect(&mut self, rect: &Rect<i32>) {
+        let path = to_rect(rect.cast()).to_path(0.1);
+        self.record(|scene| scene.push_layer(Mix::Clip, 1., Affine::IDENTITY, &path));
+        self.clips.push((path, Affine::IDENTITY));
+    }
+    fn set_transform(&mut self, matrix: &Transform2D<f32>) {
+        self.transform = *matrix;
+    }
+    fn surface(&self) -> <VelloBackend as Backend>::SourceSurface {
+        to_image(self.snapshot())
+    }
+    fn stroke(
+        &mut self,
+        path: &canvas_traits::canvas::Path,
+        pattern: &Pattern,
+        stroke_options: &<VelloBackend as Backend>::StrokeOptions,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        self.draw(draw_options, |scene, transform| {
+            scene.stroke(
+                stroke_options,
+                transform,
+                &pattern.brush,
+                pattern.transform,
+                &path.0,
+            )
+        });
+    }
+    fn stroke_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        pattern: &<VelloBackend as Backend>::Pattern<'_>,
+        stroke_options: &<VelloBackend as Backend>::StrokeOptions,
+        draw_options: &<VelloBackend as Backend>::DrawOptions,
+    ) {
+        let rect = to_rect(rect.cast());
+        self.draw(draw_options, |scene, transform| {
+            scene.stroke(
+                stroke_options,
+                transform,
+                &pattern.brush,
+                pattern.transform,
+                &rect,
+            )
+        });
+    }
+
+    fn image_descriptor_and_serializable_data(
+        &self,
+    ) -> (
+        webrender_api::ImageDescriptor,
+        compositing_traits::SerializableImageData,
+    ) {
+        let descriptor = ImageDescriptor {
+            size: self.size.cast_unit(),
+            stride: None,
+            format: ImageFormat::BGRA8,
+            offset: 0,
+            flags: ImageDescriptorFlags::empty(),
+        };
+        let mut snapshot = self.snapshot();
+        snapshot.transform(
+            SnapshotAlphaMode::Transparent {
+                premultiplied: true,
+            },
+            SnapshotPixelFormat::BGRA,
+        );
+        let data = SerializableImageData::Raw(IpcSharedMemory::from_bytes(snapshot.as_raw_bytes()));
+        (descriptor, data)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::from_vec(
+            self.size.cast(),
+            SnapshotPixelFormat::RGBA,
+            SnapshotAlphaMode::Transparent {
+                premultiplied: false,
+            },
+            self.pixels(),
+        )
+    }
+}
+
+fn to_color(color: AbsoluteColor) -> peniko::Color {
+    let srgb = color.into_srgb_legacy();
+    peniko::Color::from_rgba8(
+        clamp_unit_f32(srgb.components.0),
+        clamp_unit_f32(srgb.components.1),
+        clamp_unit_f32(srgb.components.2),
+        clamp_unit_f32(srgb.alpha),
+    )
+}
+
+fn to_affine(transform: &Transform2D<f32>) -> Affine {
+    Affine::new(transform.cast::<f64>().to_array())
+}
+
+fn to_rect(rect: Rect<f64>) -> kurbo::Rect {
+    kurbo::Rect::from_origin_size(
+        (rect.origin.x, rect.origin.y),
+        (rect.size.width, rect.size.height),
+    )
+}
+
+/// Create an image with the unpremultiplied RGBA pixels vello expects.
+fn to_image(mut snapshot: Snapshot) -> peniko::Image {
+    snapshot.transform(
+        SnapshotAlphaMode::Transparent {
+            premultiplied: false,
+        },
+        SnapshotPixelFormat::RGBA,
+    );
+    let size = snapshot.size();
+    peniko::Image::new(
+        Blob::new(Arc::new(snapshot.as_raw_bytes().to_vec())),
+        peniko::ImageFormat::Rgba8,
+        size.width,
+        size.height,
+    )
+}
+
+fn create_gradient_stops(gradient_stops: Vec<CanvasGradientStop>) -> Vec<ColorStop> {
+    let mut stops = gradient_stops
+        .into_iter()
+        .map(|stop| ColorStop::from((stop.offset as f32, to_color(stop.color))))
+        .collect::<Vec<ColorStop>>();
+    // https://www.w3.org/html/test/results/2dcontext/annotated-spec/canvas.html#testrefs.2d.gradient.interpolate.overlap
+    stops.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+    stops
+}
+
+fn to_blend_mode(op: CompositionOrBlending) -> BlendMode {
+    match op {
+        CompositionOrBlending::Composition(op) => match op {
+            CompositionStyle::SourceIn => Compose::SrcIn,
+            CompositionStyle::SourceOut => Compose::SrcOut,
+            CompositionStyle::SourceOver => Compose::SrcOver,
+            CompositionStyle::SourceAtop => Compose::SrcAtop,
+            CompositionStyle::DestinationIn => Compose::DestIn,
+            CompositionStyle::DestinationOut => Compose::DestOut,
+            CompositionStyle::DestinationOver => Compose::DestOver,
+            CompositionStyle::DestinationAtop => Compose::DestAtop,
+            CompositionStyle::Copy => Compose::Copy,
+            CompositionStyle::Lighter => Compose::PlusLighter,
+            CompositionStyle::Xor => Compose::Xor,
+            CompositionStyle::Clear => Compose::Clear,
+        }
+        .into(),
+        CompositionOrBlending::Blending(op) => match op {
+            BlendingStyle::Multiply => Mix::Multiply,
+            BlendingStyle::Screen => Mix::Screen,
+            BlendingStyle::Overlay => Mix::Overlay,
+            BlendingStyle::Darken => Mix::Darken,
+            BlendingStyle::Lighten => Mix::Lighten,
+            BlendingStyle::ColorDodge => Mix::ColorDodge,
+            BlendingStyle::HardLight => Mix::HardLight,
+            BlendingStyle::SoftLight => Mix::SoftLight,
+            BlendingStyle::Difference => Mix::Difference,
+            BlendingStyle::Exclusion => Mix::Exclusion,
+            BlendingStyle::Hue => Mix::Hue,
+            BlendingStyle::Saturation => Mix::Saturation,
+            BlendingStyle::Color => Mix::Color,
+            BlendingStyle::Luminosity => Mix::Luminosity,
+            BlendingStyle::ColorBurn => Mix::ColorBurn,
+        }
+        .into(),
+    }
+}
@@ -9,26 +9,27 @@ use std::fs::create_dir_all;
 use std::iter::once;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use base::cross_process_instant::CrossProcessInstant;
 use base::id::{PipelineId, WebViewId};
 use base::{Epoch, WebRenderEpochToU16};
 use bitflags::bitflags;
 use compositing_traits::display_list::{
-    CompositorDisplayListInfo, HitTestInfo, ScrollTree, ScrollType,
+    CompositorAnimation, CompositorDisplayListInfo, HitTestInfo, ScrollAnimation, ScrollTree,
+    ScrollType,
 };
 use compositing_traits::rendering_context::RenderingContext;
 use compositing_traits::{
-    CompositionPipeline, CompositorMsg, ImageUpdate, PipelineExitSource, SendableFrameTree,
-    WebViewTrait,
+    CompositionPipeline, CompositorMsg, ImageUpdate, IntersectionObservationTarget,
+    NavigationTiming, PipelineExitSource, SendableFrameTree, WebViewTrait,
 };
 use constellation_traits::{EmbedderToConstellationMessage, PaintMetricEvent};
 use crossbeam_channel::{Receiver, Sender};
 use dpi::PhysicalSize;
 use embedder_traits::{
-    CompositorHitTestResult, Cursor, InputEvent, ShutdownState, UntrustedNodeAddress,
-    ViewportDetails,
+    CompositorHitTestResult, Cursor, FrameReport, InputEvent, PageLoadTiming, ScreenshotMetadata,
+    ShutdownState, UntrustedNodeAddress, ViewportDetails,
 };
 use euclid::{Point2D, Rect, Scale, Size2D, Transform3D};
 use ipc_channel::ipc::{self, IpcSharedMemory};
@@ -47,18 +48,25 @@ use webrender_api::units::{
     LayoutSize, WorldPoint,
 };
 use webrender_api::{
-    self, BuiltDisplayList, DirtyRect, DisplayListPayload, DocumentId, Epoch as WebRenderEpoch,
-    FontInstanceFlags, FontInstanceKey, FontInstanceOptions, FontKey, HitTestFlags,
-    PipelineId as WebRenderPipelineId, PropertyBinding, ReferenceFrameKind, RenderReasons,
-    SampledScrollOffset, ScrollLocation, SpaceAndClipInfo, SpatialId, SpatialTreeItemKey,
-    TransformStyle,
+    self, BuiltDisplayList, DirtyRect, DisplayListPayload, DocumentId, DynamicProperties,
+    Epoch as WebRenderEpoch, ExternalScrollId, FontInstanceFlags, FontInstanceKey,
+    FontInstanceOptions, FontKey, HitTestFlags, PipelineId as WebRenderPipelineId, PropertyBinding,
+    ReferenceFrameKind, RenderReasons, SampledScrollOffset, ScrollLocation, SpaceAndClipInfo,
+    SpatialId, SpatialTreeItemKey, TransformStyle,
 };
 
 use crate::InitialCompositorState;
+use crate::compositor_animations::{animation_timeline_now, sample_compositor_animation};
+use crate::frame_reports::FrameReporter;
+use crate::picture_in_picture::PictureInPictureSurface;
 use crate::refresh_driver::RefreshDriver;
+use crate::smooth_scroll::SmoothScroll;
 use crate::webview_manager::WebViewManager;
 use crate::webview_renderer::{PinchZoomResult, UnknownWebView, WebViewRenderer};
 
+/// The color of the areas of a masked screenshot that are hidden, as RGBA.
+const SCREENSHOT_MASK_COLOR: [u8; 4] = [0, 0, 0, 255];
+
 #[derive(Debug, PartialEq)]
 enum UnableToComposite {
     NotReadyToPaintImage(NotReadyToPaint),
@@ -157,6 +165,12 @@ pub struct IOCompositor {
     /// The number of frames pending to receive from WebRender.
     pending_frames: usize,
 
+    /// Collects the timings of the frames that are painted, for [`FrameReport`]s.
+    frame_reporter: FrameReporter,
+
+    /// The surface of the picture-in-picture window, if a video is shown in one.
+    picture_in_picture: Option<PictureInPictureSurface>,
+
     /// A handle to the memory profiler which will automatically unregister
     /// when it's dropped.
     _mem_profiler_registration: ProfilerRegistration,
@@ -194,6 +208,55 @@ pub(crate) enum PaintMetricState {
     Sent,
 }
 
+/// The largest contentful paint of a pipeline, which is when its largest image or text was
+/// painted. See <https://w3c.github.io/largest-contentful-paint/>.
+#[derive(Default)]
+pub(crate) struct LargestContentfulPaint {
+    /// The area of the largest contentful item painted so far, and when it was painted.
+    painted: Option<(f32, CrossProcessInstant)>,
+    /// The area of a larger contentful item in a display list that has not been rendered
+    /// yet, and the epoch of that display list.
+    pending: Option<(f32, WebRenderEpoch)>,
+    /// Whether the user interacted with the page, after which the largest contentful paint
+    /// is no longer updated.
+    stopped: bool,
+}
+
+impl LargestContentfulPaint {
+    fn note_display_list(&mut self, area: f32, epoch: WebRenderEpoch) {
+        let largest_area = self
+            .pending
+            .map(|(area, _)| area)
+            .or(self.painted.map(|(area, _)| area))
+            .unwrap_or_default();
+        if !self.stopped && area > largest_area {
+            self.pending = Some((area, epoch));
+        }
+    }
+
+    /// Note that the given epoch was rendered, returning whether the largest contentful paint
+    /// changed.
+    fn note_epoch_rendered(
+        &mut self,
+        current_epoch: WebRenderEpoch,
+        paint_time: CrossProcessInstant,
+    ) -> bool {
+        match self.pending {
+            Some((area, epoch)) if epoch <= current_epoch => {
+                self.painted = Some((area, paint_time));
+                self.pending = None;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub(crate) fn stop(&mut self) {
+        self.stopped = true;
+        self.pending = None;
+    }
+}
+
 pub(crate) struct PipelineDetails {
     /// The pipeline associated with this PipelineDetails object.
     pub pipeline: Option<CompositionPipeline>,
@@ -205,6 +268,11 @@ pub(crate) struct PipelineDetails {
     /// list might not be displayed, as WebRender processes display lists asynchronously.
     pub most_recent_display_list_epoch: Option<WebRenderEpoch>,
 
+    /// The epoch of the hit testing tags of the most recent display list for this pipeline.
+    /// This differs from [`Self::most_recent_display_list_epoch`] when that display list
+    /// reused display items, and their tags, from a previous one.
+    pub most_recent_hit_test_epoch: Option<WebRenderEpoch>,
+
     /// Whether animations are running
     pub animations_running: bool,
 
@@ -222,12 +290,36 @@ pub(crate) struct PipelineDetails {
     /// nodes in the compositor before forwarding new offsets to WebRender.
     pub scroll_tree: ScrollTree,
 
+    /// The smooth scrolls requested by script that are currently animating nodes of
+    /// [`Self::scroll_tree`].
+    pub smooth_scrolls: HashMap<ExternalScrollId, SmoothScroll>,
+
+    /// The animations of `transform` and `opacity` that layout handed over to the
+    /// compositor with the most recent display list of this pipeline.
+    pub compositor_animations: Vec<CompositorAnimation>,
+
+    /// The `IntersectionObserver` targets whose intersection with the viewport of this
+    /// pipeline is tracked while scrolling, as last sent by script.
+    pub intersection_observation_targets: Vec<IntersectionObservationTarget>,
+
     /// The paint metric status of the first paint.
     pub first_paint_metric: PaintMetricState,
 
     /// The paint metric status of the first contentful paint.
     pub first_contentful_paint_metric: PaintMetricState,
 
+    /// When the first paint of this pipeline was rendered.
+    pub first_paint_time: Option<CrossProcessInstant>,
+
+    /// When the first contentful paint of this pipeline was rendered.
+    pub first_contentful_paint_time: Option<CrossProcessInstant>,
+
+    /// The largest contentful paint of this pipeline.
+    pub largest_contentful_paint: LargestContentfulPaint,
+
+    /// The navigation timing of the document of this pipeline, as last sent by script.
+    pub navigation_timing: Option<NavigationTiming>,
+
     /// Which parts of Servo have reported that this `Pipeline` has exited. Only when all
     /// have done so will it be discarded.
     pub exited: PipelineExitSource,
@@ -243,7 +335,11 @@ impl PipelineDetails {
     }
 
     pub(crate) fn animating(&self) -> bool {
-        !self.throttled && (self.animation_callbacks_running || self.animations_running)
+        !self.throttled &&
+            (self.animation_callbacks_running ||
+                self.animations_running ||
+                !self.smooth_scrolls.is_empty() ||
+                !self.compositor_animations.is_empty())
     }
 }
 
@@ -253,13 +349,21 @@ impl PipelineDetails {
             pipeline: None,
             parent_pipeline_id: None,
             most_recent_display_list_epoch: None,
+            most_recent_hit_test_epoch: None,
             animations_running: false,
             animation_callbacks_running: false,
             throttled: false,
             hit_test_items: Vec::new(),
             scroll_tree: ScrollTree::default(),
+            smooth_scrolls: HashMap::new(),
+            compositor_animations: Vec::new(),
+            intersection_observation_targets: Vec::new(),
             first_paint_metric: PaintMetricState::Waiting,
             first_contentful_paint_metric: PaintMetricState::Waiting,
+            first_paint_time: None,
+            first_contentful_paint_time: None,
+            largest_contentful_paint: LargestContentfulPaint::default(),
+            navigation_timing: None,
             exited: PipelineExitSource::empty(),
         }
     }
@@ -324,7 +428,7 @@ impl ServoRenderer {
 
                 // If the epoch in the tag does not match the current epoch of the pipeline,
                 // then the hit test is against an old version of the display list.
-                match details.most_recent_display_list_epoch {
+                match details.most_recent_hit_test_epoch {
                     Some(epoch) => {
                         if epoch.as_u16() != item.tag.1 {
                             // It's too early to hit test for now.
@@ -446,6 +550,8 @@ impl IOCompositor {
             webrender: Some(state.webrender),
             rendering_context: state.rendering_context,
             pending_frames: 0,
+            frame_reporter: FrameReporter::default(),
+            picture_in_picture: None,
             _mem_profiler_registration: registration,
         };
 
@@ -459,6 +565,9 @@ impl IOCompositor {
     }
 
     pub fn deinit(&mut self) {
+        if let Some(mut surface) = self.picture_in_picture.take() {
+            surface.deinit();
+        }
         if let Err(err) = self.rendering_context.make_current() {
             warn!("Failed to make the rendering context current: {:?}", err);
         }
@@ -598,7 +707,7 @@ impl IOCompositor {
             },
 
             CompositorMsg::CreatePng(webview_id, page_rect, reply) => {
-                let res = self.render_to_shared_memory(webview_id, page_rect);
+                let res = self.render_to_shared_memory(webview_id, page_rect, &[]);
                 if let Err(ref e) = res {
                     info!("Error retrieving PNG: {:?}", e);
                 }
@@ -608,6 +717,19 @@ impl IOCompositor {
                 }
             },
 
+            CompositorMsg::CreateMaskedPng(webview_id, masks, reply) => {
+                let res = self.render_to_shared_memory(webview_id, None, &masks);
+                if let Err(ref e) = res {
+                    info!("Error retrieving masked PNG: {:?}", e);
+                }
+                let screenshot = res
+                    .unwrap_or(None)
+                    .map(|img| (img, self.screenshot_metadata(webview_id)));
+                if let Err(e) = reply.send(screenshot) {
+                    warn!("Sending reply to create masked png failed ({:?}).", e);
+                }
+            },
+
             CompositorMsg::IsReadyToSaveImageReply(is_ready) => {
                 assert_eq!(
                     self.ready_to_save_state,
@@ -679,12 +801,31 @@ impl IOCompositor {
                 self.global.borrow_mut().send_transaction(txn);
             },
 
-            CompositorMsg::SendScrollNode(webview_id, pipeline_id, offset, external_scroll_id) => {
+            CompositorMsg::SendScrollNode(
+                webview_id,
+                pipeline_id,
+                offset,
+                external_scroll_id,
+                animation,
+            ) => {
                 let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) else {
                     return;
                 };
 
                 let pipeline_id = pipeline_id.into();
+                if animation == ScrollAnimation::Smooth {
+                    if webview_renderer.start_smooth_scroll(pipeline_id, external_scroll_id, offset)
+                    {
+                        self.global
+                            .borrow()
+                            .refresh_driver
+                            .notify_animation_state_changed(webview_renderer);
+                    }
+                    return;
+                }
+
+                // An instant scroll interrupts any smooth scroll of the same node.
+                webview_renderer.cancel_smooth_scroll(pipeline_id, external_scroll_id);
                 let Some(pipeline_details) = webview_renderer.pipelines.get_mut(&pipeline_id)
                 else {
                     return;
@@ -778,15 +919,28 @@ impl IOCompositor {
                 let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) else {
                     return warn!("Could not find WebView for incoming display list");
                 };
+                self.frame_reporter
+                    .note_display_list(&display_list_info.frame_timings);
+
                 // WebRender is not ready until we receive "NewWebRenderFrameReady"
                 webview_renderer.webrender_frame_ready.set(false);
 
                 let pipeline_id = display_list_info.pipeline_id;
                 let details = webview_renderer.ensure_pipeline_details(pipeline_id.into());
                 details.most_recent_display_list_epoch = Some(display_list_info.epoch);
+                details.most_recent_hit_test_epoch = Some(display_list_info.hit_test_epoch);
                 details.hit_test_items = display_list_info.hit_test_info;
                 details.install_new_scroll_tree(display_list_info.scroll_tree);
 
+                // The property bindings of the new display list have to start out with the
+                // current values of their animations rather than the ones sampled by layout.
+                details.compositor_animations = display_list_info.compositor_animations;
+                let mut dynamic_properties = DynamicProperties::default();
+                let now = animation_timeline_now();
+                for animation in details.compositor_animations.iter() {
+                    sample_compositor_animation(animation, now, &mut dynamic_properties);
+                }
+
                 let epoch = display_list_info.epoch;
                 let first_reflow = display_list_info.first_reflow;
                 if details.first_paint_metric == PaintMetricState::Waiting {
@@ -798,10 +952,16 @@ impl IOCompositor {
                     details.first_contentful_paint_metric =
                         PaintMetricState::Seen(epoch, first_reflow);
                 }
+                details
+                    .largest_contentful_paint
+                    .note_display_list(display_list_info.largest_contentful_area, epoch);
+
+                webview_renderer.update_animation_state();
 
                 let mut transaction = Transaction::new();
                 transaction
                     .set_display_list(display_list_info.epoch, (pipeline_id, built_display_list));
+                transaction.append_dynamic_properties(dynamic_properties);
                 self.update_transaction_with_all_scroll_offsets(&mut transaction);
                 self.generate_frame(&mut transaction, RenderReasons::SCENE);
                 self.global.borrow_mut().send_transaction(transaction);
@@ -853,7 +1013,11 @@ impl IOCompositor {
             },
             CompositorMsg::UpdateImages(updates) => {
                 let mut txn = Transaction::new();
+                let mut new_picture_in_picture_frame = false;
                 for update in updates {
+                    if let Some(surface) = self.picture_in_picture.as_mut() {
+                        new_picture_in_picture_frame |= surface.note_image_update(&update);
+                    }
                     match update {
                         ImageUpdate::AddImage(key, desc, data) => {
                             txn.add_image(key, desc, data.into(), None)
@@ -865,6 +1029,20 @@ impl IOCompositor {
                     }
                 }
                 self.global.borrow_mut().send_transaction(txn);
+                if new_picture_in_picture_frame {
+                    if let Some(surface) = self.picture_in_picture.as_mut() {
+                        surface.paint();
+                    }
+                }
+            },
+
+            CompositorMsg::SetPictureInPictureImage(webview_id, image_key) => {
+                match self.picture_in_picture.as_mut() {
+                    Some(surface) if surface.webview_id() == webview_id => {
+                        surface.set_image_key(image_key)
+                    },
+                    _ => warn!("{webview_id}: Setting image of missing picture-in-picture window"),
+                }
             },
 
             CompositorMsg::AddFont(font_key, data, index) => {
@@ -917,6 +1095,24 @@ impl IOCompositor {
                     webview.set_viewport_description(viewport_description);
                 }
             },
+            CompositorMsg::UpdateIntersectionObservationTargets(
+                webview_id,
+                pipeline_id,
+                targets,
+            ) => {
+                if let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) {
+                    webview_renderer
+                        .ensure_pipeline_details(pipeline_id)
+                        .intersection_observation_targets = targets;
+                }
+            },
+            CompositorMsg::UpdateNavigationTiming(webview_id, pipeline_id, navigation_timing) => {
+                if let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) {
+                    webview_renderer
+                        .ensure_pipeline_details(pipeline_id)
+                        .navigation_timing = Some(navigation_timing);
+                }
+            },
         }
     }
 
@@ -1123,6 +1319,14 @@ impl IOCompositor {
             return;
         };
 
+        if self
+            .picture_in_picture
+            .as_ref()
+            .is_some_and(|surface| surface.webview_id() == webview_id)
+        {
+            self.exit_picture_in_picture(webview_id);
+        }
+
         self.send_root_pipeline_display_list();
     }
 
@@ -1238,6 +1442,47 @@ impl IOCompositor {
         self.set_needs_repaint(RepaintReason::Resize);
     }
 
+    /// Start painting the video that the given `WebView` shows in a picture-in-picture
+    /// window to the [`RenderingContext`] of that window, replacing any other window.
+    pub fn enter_picture_in_picture(
+        &mut self,
+        webview_id: WebViewId,
+        rendering_context: Rc<dyn RenderingContext>,
+    ) {
+        if let Some(mut surface) = self.picture_in_picture.take() {
+            surface.deinit();
+        }
+        let mut surface = PictureInPictureSurface::new(webview_id, rendering_context);
+        surface.paint();
+        self.picture_in_picture = Some(surface);
+    }
+
+    /// Stop painting to the picture-in-picture window of the given `WebView`, if it has one.
+    pub fn exit_picture_in_picture(&mut self, webview_id: WebViewId) {
+        if self
+            .picture_in_picture
+            .as_ref()
+            .is_none_or(|surface| surface.webview_id() != webview_id)
+        {
+            return;
+        }
+        if let Some(mut surface) = self.picture_in_picture.take() {
+            surface.deinit();
+        }
+    }
+
+    /// Resize the picture-in-picture window of the given `WebView`, if it has one.
+    pub fn resize_picture_in_picture(
+        &mut self,
+        webview_id: WebViewId,
+        new_size: PhysicalSize<u32>,
+    ) {
+        match self.picture_in_picture.as_mut() {
+            Some(surface) if surface.webview_id() == webview_id => surface.resize(new_size),
+            _ => {},
+        }
+    }
+
     pub fn on_zoom_reset_window_event(&mut self, webview_id: WebViewId) {
         if self.global.borrow().shutdown_state() != ShutdownState::NotShuttingDown {
             return;
@@ -1344,6 +1589,7 @@ impl IOCompositor {
     /// Render the WebRender scene to the active `RenderingContext`. If successful, trigger
     /// the next round of animations.
     pub fn render(&mut self) -> bool {
+        let frame_start = Instant::now();
         self.global
             .borrow()
             .refresh_driver
@@ -1354,6 +1600,15 @@ impl IOCompositor {
             return false;
         }
 
+        let global = self.global.borrow();
+        self.frame_reporter.note_frame_rendered(
+            frame_start,
+            frame_start.elapsed(),
+            global.refresh_driver.refresh_interval(),
+            global.refresh_driver.animating.get(),
+        );
+        drop(global);
+
         // We've painted the default target, which means that from the embedder's perspective,
         // the scene no longer needs to be repainted.
         self.needs_repaint.set(RepaintReason::empty());
@@ -1361,24 +1616,39 @@ impl IOCompositor {
         true
     }
 
+    /// Present the frame last rendered by [`Self::render`] in the active `RenderingContext`,
+    /// timing it for the [`FrameReport`] of that frame.
+    pub fn present(&mut self) {
+        let present_start = Instant::now();
+        self.rendering_context.present();
+        self.frame_reporter
+            .note_frame_presented(present_start.elapsed());
+    }
+
+    /// Subscribe to the [`FrameReport`]s of the frames painted from now on.
+    pub fn subscribe_to_frame_reports(&mut self) -> Receiver<FrameReport> {
+        self.frame_reporter.subscribe()
+    }
+
     /// Render the WebRender scene to the shared memory, without updating other state of this
-    /// [`IOCompositor`]. If succesful return the output image in shared memory.
+    /// [`IOCompositor`]. If succesful return the output image in shared memory, with the
+    /// `masks` rectangles of the page filled with an opaque color.
     fn render_to_shared_memory(
         &mut self,
         webview_id: WebViewId,
         page_rect: Option<Rect<f32, CSSPixel>>,
+        masks: &[Rect<f32, CSSPixel>],
     ) -> Result<Option<RasterImage>, UnableToComposite> {
         self.render_inner()?;
 
         let size = self.rendering_context.size2d().to_i32();
+        let scale = self
+            .webview_renderers
+            .get(webview_id)
+            .map(WebViewRenderer::device_pixels_per_page_pixel)
+            .unwrap_or_else(|| Scale::new(1.0));
+        let page_rect = page_rect.map(|rect| scale.transform_rect(&rect));
         let rect = if let Some(rect) = page_rect {
-            let scale = self
-                .webview_renderers
-                .get(webview_id)
-                .map(WebViewRenderer::device_pixels_per_page_pixel)
-                .unwrap_or_else(|| Scale::new(1.0));
-            let rect = scale.transform_rect(&rect);
-
             let x = rect.origin.x as i32;
             // We need to convert to the bottom-left origin coordinate
             // system used by OpenGL
@@ -1394,6 +1664,27 @@ impl IOCompositor {
         Ok(self
             .rendering_context
             .read_to_image(rect)
+            .map(|mut image| {
+                let image_origin = page_rect
+                    .map(|rect| rect.origin.to_vector())
+                    .unwrap_or_default();
+                for mask in masks {
+                    let mask = scale
+                        .transform_rect(mask)
+                        .translate(-image_origin)
+                        .round_out();
+                    let (width, height) = image.dimensions();
+                    let columns =
+                        (mask.min_x() as u32).min(width)..(mask.max_x() as u32).min(width);
+                    for y in (mask.min_y() as u32)..(mask.max_y() as u32).min(height) {
+                        for x in columns.clone() {
+                            let offset = (y * width + x) as usize * 4;
+                            image[offset..offset + 4].copy_from_slice(&SCREENSHOT_MASK_COLOR);
+                        }
+                    }
+                }
+                image
+            })
             .map(|image| RasterImage {
                 metadata: ImageMetadata {
                     width: image.width(),
@@ -1412,6 +1703,65 @@ impl IOCompositor {
             }))
     }
 
+    /// Describe the frame that was last rendered for the given webview, for the metadata of
+    /// its screenshots.
+    fn screenshot_metadata(&self, webview_id: WebViewId) -> ScreenshotMetadata {
+        let Some(webview_renderer) = self.webview_renderers.get(webview_id) else {
+            return ScreenshotMetadata {
+                pipeline_epochs: Vec::new(),
+                viewport_size: self.rendering_context.size2d().to_i32(),
+                device_pixels_per_css_pixel: 1.0,
+                page_load_timing: None,
+            };
+        };
+
+        let mut pipeline_epochs: Vec<_> = webview_renderer
+            .pipeline_ids()
+            .filter_map(|id| {
+                let WebRenderEpoch(epoch) = self
+                    .webrender
+                    .as_ref()?
+                    .current_epoch(self.webrender_document(), id.into())?;
+                Some((*id, Epoch(epoch)))
+            })
+            .collect();
+        pipeline_epochs.sort();
+
+        ScreenshotMetadata {
+            pipeline_epochs,
+            viewport_size: webview_renderer.rect.size().to_i32(),
+            device_pixels_per_css_pixel: webview_renderer.device_pixels_per_page_pixel().get(),
+            page_load_timing: webview_renderer
+                .root_pipeline_id
+                .and_then(|pipeline_id| webview_renderer.pipelines.get(&pipeline_id))
+                .and_then(Self::page_load_timing),
+        }
+    }
+
+    /// The paint and navigation timing of the given pipeline, as of the frame that was last
+    /// rendered. Both are gathered here, rather than from the compositor and script
+    /// separately, so that they are consistent with each other and with that frame.
+    fn page_load_timing(pipeline: &PipelineDetails) -> Option<PageLoadTiming> {
+        let navigation_timing = pipeline.navigation_timing?;
+        let since_navigation_start = |time: Option<CrossProcessInstant>| -> Option<Duration> {
+            (time? - navigation_timing.navigation_start).try_into().ok()
+        };
+        let largest_contentful_paint = pipeline.largest_contentful_paint.painted;
+        Some(PageLoadTiming {
+            first_paint: since_navigation_start(pipeline.first_paint_time),
+            first_contentful_paint: since_navigation_start(pipeline.first_contentful_paint_time),
+            largest_contentful_paint: since_navigation_start(
+                largest_contentful_paint.map(|(_, paint_time)| paint_time),
+            ),
+            largest_contentful_paint_size: largest_contentful_paint.map(|(area, _)| area),
+            dom_interactive: since_navigation_start(navigation_timing.dom_interactive),
+            dom_content_loaded_event_end: since_navigation_start(
+                navigation_timing.dom_content_loaded_event_end,
+            ),
+            load_event_end: since_navigation_start(navigation_timing.load_event_end),
+        })
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn render_inner(&mut self) -> Result<(), UnableToComposite> {
         if let Err(err) = self.rendering_context.make_current() {
@@ -1498,6 +1848,7 @@ impl IOCompositor {
                             );
                         }
                         pipeline.first_paint_metric = PaintMetricState::Sent;
+                        pipeline.first_paint_time = Some(paint_time);
                     },
                     _ => {},
                 }
@@ -1515,9 +1866,24 @@ impl IOCompositor {
                             );
                         }
                         pipeline.first_contentful_paint_metric = PaintMetricState::Sent;
+                        pipeline.first_contentful_paint_time = Some(paint_time);
                     },
                     _ => {},
                 }
+
+                if pipeline
+                    .largest_contentful_paint
+                    .note_epoch_rendered(current_epoch, paint_time)
+                {
+                    if let Err(error) = self.global.borrow().constellation_sender.send(
+                        EmbedderToConstellationMessage::PaintMetric(
+                            *pipeline_id,
+                            PaintMetricEvent::LargestContentfulPaint(paint_time),
+                        ),
+                    ) {
+                        warn!("Sending paint metric event to constellation failed ({error:?}).");
+                    }
+                }
             }
         }
     }
@@ -1630,16 +1996,43 @@ impl IOCompositor {
             })
             .collect();
 
-        if need_zoom || !scroll_offset_updates.is_empty() {
+        let now = Instant::now();
+        let smooth_scroll_updates: Vec<_> = self
+            .webview_renderers
+            .iter_mut()
+            .flat_map(|webview_renderer| webview_renderer.tick_smooth_scrolls(now))
+            .collect();
+
+        let mut dynamic_properties = DynamicProperties::default();
+        let animation_timeline_now = animation_timeline_now();
+        for webview_renderer in self.webview_renderers.iter_mut() {
+            webview_renderer
+                .tick_compositor_animations(animation_timeline_now, &mut dynamic_properties);
+        }
+        let need_dynamic_properties =
+            !dynamic_properties.transforms.is_empty() || !dynamic_properties.floats.is_empty();
+
+        if need_zoom ||
+            need_dynamic_properties ||
+            !scroll_offset_updates.is_empty() ||
+            !smooth_scroll_updates.is_empty()
+        {
             let mut transaction = Transaction::new();
+            if need_dynamic_properties {
+                transaction.append_dynamic_properties(dynamic_properties);
+            }
             if need_zoom {
                 self.send_root_pipeline_display_list_in_transaction(&mut transaction);
             }
-            for update in scroll_offset_updates {
+            let updates = scroll_offset_updates
+                .into_iter()
+                .map(|update| (update.external_scroll_id, update.offset))
+                .chain(smooth_scroll_updates);
+            for (external_scroll_id, offset) in updates {
                 transaction.set_scroll_offsets(
-                    update.external_scroll_id,
+                    external_scroll_id,
                     vec![SampledScrollOffset {
-                        offset: update.offset,
+                        offset,
                         generation: 0,
                     }],
                 );
@@ -1647,6 +2040,10 @@ impl IOCompositor {
 
             self.generate_frame(&mut transaction, RenderReasons::APZ);
             self.global.borrow_mut().send_transaction(transaction);
+
+            for webview_renderer in self.webview_renderers.iter_mut() {
+                webview_renderer.update_intersection_observations();
+            }
         }
 
         self.global.borrow().shutdown_state() != ShutdownState::FinishedShuttingDown
@@ -1738,6 +2135,16 @@ impl IOCompositor {
         }
     }
 
+    /// Stop updating the largest contentful paint of the pipelines of the given `WebView`,
+    /// because the user interacted with it.
+    pub fn stop_largest_contentful_paint_updates(&mut self, webview_id: WebViewId) {
+        if let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) {
+            for pipeline in webview_renderer.pipelines.values_mut() {
+                pipeline.largest_contentful_paint.stop();
+            }
+        }
+    }
+
     pub fn notify_scroll_event(
         &mut self,
         webview_id: WebViewId,
@@ -1761,6 +2168,12 @@ impl IOCompositor {
         }
     }
 
+    pub fn reset_pinch_zoom(&mut self, webview_id: WebViewId) {
+        if let Some(webview_renderer) = self.webview_renderers.get_mut(webview_id) {
+            webview_renderer.reset_pinch_zoom();
+        }
+    }
+
     fn webrender_document(&self) -> DocumentId {
         self.global.borrow().webrender_document
     }
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Animations of `transform` and `opacity` that layout has handed over to the compositor.
+//! Layout samples these animations ahead of time when it builds a display list and binds
+//! the animated properties to WebRender property bindings, so that the compositor can keep
+//! them running at the refresh rate of the display even while script and layout are busy.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use compositing_traits::display_list::{
+    COMPOSITOR_ANIMATION_SAMPLE_INTERVAL, CompositorAnimation, CompositorAnimationValues,
+};
+use webrender_api::{DynamicProperties, PropertyValue};
+
+/// The current value of the animation timeline that layout sampled the animations on,
+/// which is the number of seconds since the UNIX epoch.
+pub(crate) fn animation_timeline_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Add the values of `animation` at the time `now` to `properties`.
+pub(crate) fn sample_compositor_animation(
+    animation: &CompositorAnimation,
+    now: f64,
+    properties: &mut DynamicProperties,
+) {
+    let position = ((now - animation.start_time) / COMPOSITOR_ANIMATION_SAMPLE_INTERVAL).max(0.);
+    match &animation.values {
+        CompositorAnimationValues::Transform(key, values) => {
+            // Interpolating between two matrices does not interpolate between the transform
+            // functions that they were computed from, so use the nearest sample instead.
+            let index = (position.round() as usize).min(values.len().saturating_sub(1));
+            let Some(value) = values.get(index) else {
+                return;
+            };
+            properties.transforms.push(PropertyValue {
+                key: *key,
+                value: *value,
+            });
+        },
+        CompositorAnimationValues::Opacity(key, values) => {
+            let Some(last_index) = values.len().checked_sub(1) else {
+                return;
+            };
+            let index = (position.floor() as usize).min(last_index);
+            let next_value = values[(index + 1).min(last_index)];
+            let progress = (position - index as f64).min(1.) as f32;
+            properties.floats.push(PropertyValue {
+                key: *key,
+                value: values[index] + (next_value - values[index]) * progress,
+            });
+        },
+    }
+}
+
+/// Whether `animation` has reached its last sampled value at the time `now`.
+pub(crate) fn compositor_animation_finished(animation: &CompositorAnimation, now: f64) -> bool {
+    let sample_count = match &animation.values {
+        CompositorAnimationValues::Transform(_, values) => values.len(),
+        CompositorAnimationValues::Opacity(_, values) => values.len(),
+    };
+    let duration = sample_count.saturating_sub(1) as f64 * COMPOSITOR_ANIMATION_SAMPLE_INTERVAL;
+    now >= animation.start_time + duration
+}
@@ -0,0 +1,104 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender};
+use embedder_traits::{FrameReport, FrameStageTimings};
+
+/// Frames that are further apart than this are not part of the same animation, so no
+/// frames are counted as dropped between them.
+const MAX_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Collects the timings of each frame painted by the renderer and sends them as a
+/// [`FrameReport`] to everyone that subscribed to them.
+///
+/// A report is sent once its frame has been presented with [`Self::note_frame_presented`],
+/// or when the next frame is rendered if the embedder presented it on its own.
+#[derive(Default)]
+pub(crate) struct FrameReporter {
+    /// The channels on which to send frame reports.
+    subscribers: Vec<Sender<FrameReport>>,
+    /// The number of frames rendered so far.
+    frame_count: u64,
+    /// The timings of the display lists received since the last frame was rendered.
+    pending_stage_timings: FrameStageTimings,
+    /// The number of display lists received since the last frame was rendered.
+    pending_display_lists: u32,
+    /// The time at which the last frame started, if it was part of an animation.
+    last_animation_frame_start: Option<Instant>,
+    /// The number of frames dropped so far.
+    total_dropped_frames: u64,
+    /// The report of the last rendered frame, until it is presented.
+    unpresented_report: Option<FrameReport>,
+}
+
+impl FrameReporter {
+    pub(crate) fn subscribe(&mut self) -> Receiver<FrameReport> {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Note that a display list was received, which will be part of the next frame.
+    pub(crate) fn note_display_list(&mut self, timings: &FrameStageTimings) {
+        self.pending_stage_timings.accumulate(timings);
+        self.pending_display_lists += 1;
+    }
+
+    /// Note that a frame which started at `frame_start` was rendered in `render_time`.
+    pub(crate) fn note_frame_rendered(
+        &mut self,
+        frame_start: Instant,
+        render_time: Duration,
+        refresh_interval: Duration,
+        animating: bool,
+    ) {
+        if let Some(report) = self.unpresented_report.take() {
+            self.send(report);
+        }
+
+        let frame_interval = self
+            .last_animation_frame_start
+            .map(|last_frame_start| frame_start.saturating_duration_since(last_frame_start))
+            .filter(|frame_interval| *frame_interval <= MAX_FRAME_INTERVAL);
+        self.last_animation_frame_start = animating.then_some(frame_start);
+
+        // Frames are expected once per refresh interval while animating, so any further
+        // intervals that went by without a frame were dropped.
+        let dropped_frames = frame_interval
+            .map(|frame_interval| {
+                let intervals = frame_interval.as_secs_f64() / refresh_interval.as_secs_f64();
+                (intervals.round() as u32).saturating_sub(1)
+            })
+            .unwrap_or_default();
+        self.total_dropped_frames += dropped_frames as u64;
+
+        self.frame_count += 1;
+        self.unpresented_report = Some(FrameReport {
+            frame_number: self.frame_count,
+            display_lists: std::mem::take(&mut self.pending_display_lists),
+            stages: std::mem::take(&mut self.pending_stage_timings),
+            render: render_time,
+            present: None,
+            frame_interval,
+            refresh_interval,
+            dropped_frames,
+            total_dropped_frames: self.total_dropped_frames,
+        });
+    }
+
+    /// Note that the last rendered frame was presented in `present_time`.
+    pub(crate) fn note_frame_presented(&mut self, present_time: Duration) {
+        if let Some(mut report) = self.unpresented_report.take() {
+            report.present = Some(present_time);
+            self.send(report);
+        }
+    }
+
+    fn send(&mut self, report: FrameReport) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(report.clone()).is_ok());
+    }
+}
@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Tracking the intersections of `IntersectionObserver` targets with the viewport while
+//! the renderer scrolls.
+//!
+//! Script only runs the update intersection observations steps when it updates the
+//! rendering, which doesn't happen for scrolls that the renderer performs on its own. For
+//! observers whose root is the viewport of the document of their targets, the renderer
+//! knows everything that is needed to compute the intersections though: script sends it
+//! the unscrolled rectangles of the targets and the renderer applies the scroll offset of
+//! the viewport to them after every scroll, reporting back the targets that crossed a
+//! threshold. Script then queues the entries for those targets.
+
+use compositing_traits::IntersectionObservationTarget;
+use constellation_traits::IntersectionObservationChange;
+use webrender_api::units::{LayoutRect, LayoutVector2D};
+
+/// Recompute the intersection of `target` with its root given the scroll offset of the
+/// viewport. If its threshold index or whether it intersects the root changed since they
+/// were last reported to script, update them and return the change to report.
+pub(crate) fn update_intersection_observation(
+    target: &mut IntersectionObservationTarget,
+    viewport_scroll_offset: LayoutVector2D,
+) -> Option<IntersectionObservationChange> {
+    let target_rect = target.rect.translate(-viewport_scroll_offset);
+    let root_bounds = target.root_bounds;
+
+    // Edge-adjacent rectangles intersect too, so check for a negative rather than an empty
+    // intersection, as script does.
+    let intersection = target_rect.intersection_unchecked(&root_bounds);
+    let is_intersecting = !intersection.is_negative();
+    let intersection_rect = if is_intersecting {
+        intersection
+    } else {
+        LayoutRect::zero()
+    };
+
+    let intersection_ratio = if target_rect.width() == 0. || target_rect.height() == 0. {
+        is_intersecting.into()
+    } else {
+        (intersection_rect.width() as f64 / target_rect.width() as f64) *
+            (intersection_rect.height() as f64 / target_rect.height() as f64)
+    };
+    let threshold_index = target
+        .thresholds
+        .iter()
+        .position(|threshold| *threshold > intersection_ratio)
+        .unwrap_or(target.thresholds.len()) as i32;
+
+    if threshold_index == target.threshold_index && is_intersecting == target.is_intersecting {
+        return None;
+    }
+    target.threshold_index = threshold_index;
+    target.is_intersecting = is_intersecting;
+
+    Some(IntersectionObservationChange {
+        id: target.id,
+        threshold_index,
+        is_intersecting,
+        target_rect,
+        intersection_rect,
+        intersection_ratio,
+        root_bounds,
+    })
+}
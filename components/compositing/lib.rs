@@ -22,7 +22,12 @@ pub use crate::compositor::{IOCompositor, WebRenderDebugOption};
 mod tracing;
 
 mod compositor;
+mod compositor_animations;
+mod frame_reports;
+mod intersection_observation;
+mod picture_in_picture;
 mod refresh_driver;
+mod smooth_scroll;
 mod touch;
 mod webview_manager;
 mod webview_renderer;
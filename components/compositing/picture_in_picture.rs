@@ -0,0 +1,271 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Painting of the video shown in a picture-in-picture window.
+//!
+//! The window has a [`RenderingContext`] of its own, which is not the one WebRender renders
+//! to. Instead, the frames of the video are intercepted as script updates the image that
+//! holds them, and are copied to the window directly, scaled to fit its size.
+
+use std::rc::Rc;
+
+use base::id::WebViewId;
+use compositing_traits::rendering_context::RenderingContext;
+use compositing_traits::{ImageUpdate, SerializableImageData};
+use dpi::PhysicalSize;
+use euclid::{Box2D, Point2D, Size2D};
+use gleam::gl;
+use ipc_channel::ipc::IpcSharedMemory;
+use log::warn;
+use webrender_api::units::DeviceIntRect;
+use webrender_api::{ImageDescriptor, ImageFormat, ImageKey};
+
+/// A frame of the video, as it was given to WebRender.
+struct VideoFrame {
+    descriptor: ImageDescriptor,
+    data: IpcSharedMemory,
+}
+
+/// The secondary surface of the compositor that the video in a picture-in-picture window
+/// is painted to.
+pub(crate) struct PictureInPictureSurface {
+    /// The `WebView` whose video is shown.
+    webview_id: WebViewId,
+    /// The [`RenderingContext`] of the picture-in-picture window.
+    rendering_context: Rc<dyn RenderingContext>,
+    /// The image that holds the current frame of the video, once script has set it.
+    image_key: Option<ImageKey>,
+    /// The last frame of the video that was received.
+    frame: Option<VideoFrame>,
+    /// The texture that frames are uploaded to, and its size.
+    texture: Option<(gl::GLuint, Size2D<i32, ()>)>,
+    /// The framebuffer that the texture is attached to, in order to blit it.
+    framebuffer: Option<gl::GLuint>,
+}
+
+impl PictureInPictureSurface {
+    pub(crate) fn new(webview_id: WebViewId, rendering_context: Rc<dyn RenderingContext>) -> Self {
+        Self {
+            webview_id,
+            rendering_context,
+            image_key: None,
+            frame: None,
+            texture: None,
+            framebuffer: None,
+        }
+    }
+
+    pub(crate) fn webview_id(&self) -> WebViewId {
+        self.webview_id
+    }
+
+    /// Set the image that holds the current frame of the video. A new image is used
+    /// whenever the size of the video changes, and the first update of it is a new frame.
+    pub(crate) fn set_image_key(&mut self, image_key: Option<ImageKey>) {
+        self.image_key = image_key;
+        if image_key.is_none() {
+            self.frame = None;
+        }
+    }
+
+    /// Keep the data of the given image update if it is a new frame of the video. Returns
+    /// whether it was, in which case the surface needs to be painted again.
+    pub(crate) fn note_image_update(&mut self, update: &ImageUpdate) -> bool {
+        let (ImageUpdate::AddImage(key, descriptor, data) |
+        ImageUpdate::UpdateImage(key, descriptor, data)) = update
+        else {
+            return false;
+        };
+        if Some(*key) != self.image_key {
+            return false;
+        }
+        match data {
+            SerializableImageData::Raw(data) => {
+                self.frame = Some(VideoFrame {
+                    descriptor: *descriptor,
+                    data: data.clone(),
+                });
+                true
+            },
+            // Frames that are decoded to GL textures of the WebRender context cannot be
+            // shared with the context of the window.
+            SerializableImageData::External(..) => {
+                warn!("Cannot show hardware decoded video in picture-in-picture window");
+                false
+            },
+        }
+    }
+
+    /// Resize the surface along with its window, and paint the video again.
+    pub(crate) fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if self.rendering_context.size() == new_size {
+            return;
+        }
+        self.rendering_context.resize(new_size);
+        self.paint();
+    }
+
+    /// Paint the last frame of the video, letterboxed to fit the surface, and present it.
+    pub(crate) fn paint(&mut self) {
+        if let Err(error) = self.rendering_context.make_current() {
+            warn!("Failed to make picture-in-picture surface current: {error:?}");
+            return;
+        }
+        self.rendering_context.prepare_for_rendering();
+
+        let gl = self.rendering_context.gleam_gl_api();
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.clear(gl::COLOR_BUFFER_BIT);
+
+        if let Some(frame_size) = self.upload_frame(&*gl) {
+            let surface_size: Size2D<i32, ()> =
+                self.rendering_context.size2d().to_i32().cast_unit();
+            let target_rect = letterbox(frame_size, surface_size);
+            gl.bind_framebuffer(gl::READ_FRAMEBUFFER, self.framebuffer.unwrap_or_default());
+            // Frames are stored top row first, whereas GL framebuffers start at the bottom,
+            // so the target rectangle is flipped vertically.
+            gl.blit_framebuffer(
+                0,
+                0,
+                frame_size.width,
+                frame_size.height,
+                target_rect.min.x,
+                target_rect.max.y,
+                target_rect.max.x,
+                target_rect.min.y,
+                gl::COLOR_BUFFER_BIT,
+                gl::LINEAR,
+            );
+            gl.bind_framebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+
+        self.rendering_context.present();
+    }
+
+    /// Upload the last frame of the video to the texture, returning the size of the frame.
+    fn upload_frame(&mut self, gl: &dyn gl::Gl) -> Option<Size2D<i32, ()>> {
+        let frame = self.frame.as_ref()?;
+        let size = Size2D::new(frame.descriptor.size.width, frame.descriptor.size.height);
+        let pixels = rgba_pixels(frame)?;
+
+        let texture = match self.texture {
+            Some((texture, texture_size)) if texture_size == size => texture,
+            _ => {
+                if let Some((texture, _)) = self.texture.take() {
+                    gl.delete_textures(&[texture]);
+                }
+                let texture = gl.gen_textures(1)[0];
+                gl.bind_texture(gl::TEXTURE_2D, texture);
+                gl.tex_parameter_i(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+                gl.tex_parameter_i(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                gl.tex_image_2d(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGBA as gl::GLint,
+                    size.width,
+                    size.height,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    None,
+                );
+                self.texture = Some((texture, size));
+                texture
+            },
+        };
+
+        gl.bind_texture(gl::TEXTURE_2D, texture);
+        gl.tex_sub_image_2d(
+            gl::TEXTURE_2D,
+            0,
+            0,
+            0,
+            size.width,
+            size.height,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            &pixels,
+        );
+        gl.bind_texture(gl::TEXTURE_2D, 0);
+
+        let framebuffer = *self
+            .framebuffer
+            .get_or_insert_with(|| gl.gen_framebuffers(1)[0]);
+        gl.bind_framebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+        gl.framebuffer_texture_2d(
+            gl::READ_FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            texture,
+            0,
+        );
+        gl.bind_framebuffer(gl::READ_FRAMEBUFFER, 0);
+
+        Some(size)
+    }
+
+    /// Release the GL resources of the surface, before the window is closed.
+    pub(crate) fn deinit(&mut self) {
+        if self.rendering_context.make_current().is_err() {
+            return;
+        }
+        let gl = self.rendering_context.gleam_gl_api();
+        if let Some((texture, _)) = self.texture.take() {
+            gl.delete_textures(&[texture]);
+        }
+        if let Some(framebuffer) = self.framebuffer.take() {
+            gl.delete_framebuffers(&[framebuffer]);
+        }
+    }
+}
+
+/// Convert the pixels of a frame to tightly packed RGBA, which is the format every GL
+/// implementation can upload.
+fn rgba_pixels(frame: &VideoFrame) -> Option<Vec<u8>> {
+    let descriptor = &frame.descriptor;
+    let width = descriptor.size.width as usize;
+    let height = descriptor.size.height as usize;
+    let stride = descriptor
+        .stride
+        .map_or(width * 4, |stride| stride as usize);
+    let offset = descriptor.offset as usize;
+    let data = &*frame.data;
+    if data.len() < offset + stride * height.saturating_sub(1) + width * 4 {
+        warn!("Picture-in-picture video frame is smaller than its descriptor");
+        return None;
+    }
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        let start = offset + row * stride;
+        pixels.extend_from_slice(&data[start..start + width * 4]);
+    }
+    match descriptor.format {
+        ImageFormat::RGBA8 => {},
+        ImageFormat::BGRA8 => pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2)),
+        format => {
+            warn!("Cannot show video frames of format {format:?} in picture-in-picture window");
+            return None;
+        },
+    }
+    Some(pixels)
+}
+
+/// The largest rectangle with the aspect ratio of the frame that fits in the surface,
+/// centered in it.
+fn letterbox(frame_size: Size2D<i32, ()>, surface_size: Size2D<i32, ()>) -> DeviceIntRect {
+    if frame_size.is_empty() || surface_size.is_empty() {
+        return DeviceIntRect::zero();
+    }
+    let scale = (surface_size.width as f32 / frame_size.width as f32)
+        .min(surface_size.height as f32 / frame_size.height as f32);
+    let size = (frame_size.to_f32() * scale).round().to_i32();
+    let origin = Point2D::new(
+        (surface_size.width - size.width) / 2,
+        (surface_size.height - size.height) / 2,
+    );
+    Box2D::from_origin_and_size(origin, size).cast_unit()
+}
@@ -7,10 +7,11 @@ use std::collections::hash_map::Values;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use base::cross_process_instant::CrossProcessInstant;
 use base::id::WebViewId;
-use constellation_traits::EmbedderToConstellationMessage;
+use constellation_traits::{EmbedderToConstellationMessage, FrameClock};
 use crossbeam_channel::{Sender, select};
 use embedder_traits::EventLoopWaker;
 use log::warn;
@@ -19,14 +20,25 @@ use timers::{BoxedTimerCallback, TimerEventRequest, TimerScheduler};
 use crate::compositor::RepaintReason;
 use crate::webview_renderer::WebViewRenderer;
 
+/// The shortest interval between animation frames, which is also used until enough frames
+/// have been presented to know the refresh rate of the display.
 const FRAME_DURATION: Duration = Duration::from_millis(1000 / 120);
 
+/// Frames that are further apart than this are not part of the same animation, so the
+/// time between them says nothing about the refresh rate of the display.
+const MAX_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The weight of the most recent frame in the running average of the frame interval.
+const FRAME_INTERVAL_WEIGHT: f64 = 0.2;
+
 /// The [`RefreshDriver`] is responsible for controlling updates to aall `WebView`s
 /// onscreen presentation. Currently, it only manages controlling animation update
 /// requests.
 ///
-/// The implementation is very basic at the moment, only requesting new animation
-/// frames at a constant time after a repaint.
+/// New animation frames are requested one frame interval after a repaint. The interval
+/// follows the cadence at which frames are actually presented, which is the refresh rate
+/// of the display when presenting waits for vsync, and is sent to script along with each
+/// animation tick as a [`FrameClock`].
 pub(crate) struct RefreshDriver {
     /// The channel on which messages can be sent to the Constellation.
     pub(crate) constellation_sender: Sender<EmbedderToConstellationMessage>,
@@ -40,6 +52,13 @@ pub(crate) struct RefreshDriver {
     /// A [`TimerThread`] which is used to schedule frame timeouts in the future.
     timer_thread: TimerThread,
 
+    /// The time at which the most recent animation frame was started.
+    last_frame_start: Cell<Option<Instant>>,
+
+    /// The running average of the interval between the animation frames that were
+    /// presented.
+    refresh_interval: Cell<Duration>,
+
     /// An [`EventLoopWaker`] to be used to wake up the embedder when it is
     /// time to paint a frame.
     event_loop_waker: Box<dyn EventLoopWaker>,
@@ -55,10 +74,45 @@ impl RefreshDriver {
             animating: Default::default(),
             waiting_for_frame_timeout: Default::default(),
             timer_thread: Default::default(),
+            last_frame_start: Default::default(),
+            refresh_interval: Cell::new(FRAME_DURATION),
             event_loop_waker,
         }
     }
 
+    /// Note that a new animation frame is starting at `now`, updating the refresh interval
+    /// from the time elapsed since the previous one.
+    fn note_frame_start(&self, now: Instant) {
+        if let Some(last_frame_start) = self.last_frame_start.replace(Some(now)) {
+            let interval = now.saturating_duration_since(last_frame_start);
+            if interval <= MAX_FRAME_INTERVAL {
+                let refresh_interval = self
+                    .refresh_interval
+                    .get()
+                    .mul_f64(1. - FRAME_INTERVAL_WEIGHT) +
+                    interval.mul_f64(FRAME_INTERVAL_WEIGHT);
+                self.refresh_interval
+                    .set(refresh_interval.max(FRAME_DURATION));
+            }
+        }
+    }
+
+    /// The interval between the animation frames that are presented.
+    pub(crate) fn refresh_interval(&self) -> Duration {
+        self.refresh_interval.get()
+    }
+
+    /// The [`FrameClock`] for an animation frame starting now.
+    fn frame_clock(&self) -> FrameClock {
+        let refresh_interval = self.refresh_interval.get();
+        let frame_start = CrossProcessInstant::now();
+        FrameClock {
+            frame_start,
+            deadline: frame_start + time::Duration::try_from(refresh_interval).unwrap_or_default(),
+            refresh_interval,
+        }
+    }
+
     fn timer_callback(&self) -> BoxedTimerCallback {
         let waiting_for_frame_timeout = self.waiting_for_frame_timeout.clone();
         let event_loop_waker = self.event_loop_waker.clone_box();
@@ -96,13 +150,16 @@ impl RefreshDriver {
         // any noew frames nor triggering a new animation deadline.
         if animating_webviews.is_empty() {
             self.animating.set(false);
+            self.last_frame_start.set(None);
             return;
         }
 
+        self.note_frame_start(Instant::now());
         if let Err(error) =
             self.constellation_sender
                 .send(EmbedderToConstellationMessage::TickAnimation(
                     animating_webviews,
+                    self.frame_clock(),
                 ))
         {
             warn!("Sending tick to constellation failed ({error:?}).");
@@ -113,7 +170,7 @@ impl RefreshDriver {
         self.waiting_for_frame_timeout
             .store(true, Ordering::Relaxed);
         self.timer_thread
-            .queue_timer(FRAME_DURATION, self.timer_callback());
+            .queue_timer(self.refresh_interval.get(), self.timer_callback());
     }
 
     /// Notify the [`RefreshDriver`] that the animation state of a particular `WebView`
@@ -129,9 +186,10 @@ impl RefreshDriver {
 
         if let Err(error) =
             self.constellation_sender
-                .send(EmbedderToConstellationMessage::TickAnimation(vec![
-                    webview_renderer.id,
-                ]))
+                .send(EmbedderToConstellationMessage::TickAnimation(
+                    vec![webview_renderer.id],
+                    self.frame_clock(),
+                ))
         {
             warn!("Sending tick to constellation failed ({error:?}).");
         }
@@ -144,7 +202,7 @@ impl RefreshDriver {
         self.waiting_for_frame_timeout
             .store(true, Ordering::Relaxed);
         self.timer_thread
-            .queue_timer(FRAME_DURATION, self.timer_callback());
+            .queue_timer(self.refresh_interval.get(), self.timer_callback());
     }
 
     /// Whether or not the renderer should trigger a message to the embedder to request a
@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Smooth scrolling of scroll nodes on behalf of script, for instance for
+//! `scrollIntoView({ behavior: "smooth" })`. Script and layout move the node to its final
+//! offset right away, while the renderer animates the node from the offset that it
+//! currently displays to that final offset over the course of several frames.
+
+use std::time::{Duration, Instant};
+
+use webrender_api::units::LayoutVector2D;
+
+/// How long a smooth scroll takes, regardless of the distance that it covers.
+const SMOOTH_SCROLL_DURATION: Duration = Duration::from_millis(250);
+
+pub(crate) struct SmoothScroll {
+    start_offset: LayoutVector2D,
+    end_offset: LayoutVector2D,
+    start_time: Instant,
+}
+
+impl SmoothScroll {
+    pub(crate) fn new(
+        start_offset: LayoutVector2D,
+        end_offset: LayoutVector2D,
+        start_time: Instant,
+    ) -> Self {
+        Self {
+            start_offset,
+            end_offset,
+            start_time,
+        }
+    }
+
+    /// The offset of the animated scroll node at the given time.
+    pub(crate) fn sample(&self, now: Instant) -> LayoutVector2D {
+        let progress = (now.saturating_duration_since(self.start_time).as_secs_f32() /
+            SMOOTH_SCROLL_DURATION.as_secs_f32())
+        .min(1.0);
+
+        // Ease out, so that the scroll starts quickly and settles gently at its end.
+        let eased_progress = 1.0 - (1.0 - progress).powi(3);
+        self.start_offset.lerp(self.end_offset, eased_progress)
+    }
+
+    /// Whether the scroll node has reached its final offset at the given time.
+    pub(crate) fn finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.start_time) >= SMOOTH_SCROLL_DURATION
+    }
+}
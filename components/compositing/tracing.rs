@@ -35,6 +35,7 @@ mod from_constellation {
                 Self::RemoveWebView(..) => target!("RemoveWebView"),
                 Self::TouchEventProcessed(..) => target!("TouchEventProcessed"),
                 Self::CreatePng(..) => target!("CreatePng"),
+                Self::CreateMaskedPng(..) => target!("CreateMaskedPng"),
                 Self::IsReadyToSaveImageReply(..) => target!("IsReadyToSaveImageReply"),
                 Self::SetThrottled(..) => target!("SetThrottled"),
                 Self::NewWebRenderFrameReady(..) => target!("NewWebRenderFrameReady"),
@@ -46,6 +47,7 @@ mod from_constellation {
                 Self::HitTest(..) => target!("HitTest"),
                 Self::GenerateImageKey(..) => target!("GenerateImageKey"),
                 Self::UpdateImages(..) => target!("UpdateImages"),
+                Self::SetPictureInPictureImage(..) => target!("SetPictureInPictureImage"),
                 Self::GenerateFontKeys(..) => target!("GenerateFontKeys"),
                 Self::AddFont(..) => target!("AddFont"),
                 Self::AddSystemFont(..) => target!("AddSystemFont"),
@@ -54,6 +56,10 @@ mod from_constellation {
                 Self::CollectMemoryReport(..) => target!("CollectMemoryReport"),
                 Self::Viewport(..) => target!("Viewport"),
                 Self::GenerateImageKeysForPipeline(..) => target!("GenerateImageKeysForPipeline"),
+                Self::UpdateIntersectionObservationTargets(..) => {
+                    target!("UpdateIntersectionObservationTargets")
+                },
+                Self::UpdateNavigationTiming(..) => target!("UpdateNavigationTiming"),
             }
         }
     }
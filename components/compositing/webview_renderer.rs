@@ -6,6 +6,7 @@ use std::cell::{Cell, RefCell};
 use std::collections::hash_map::{Entry, Keys};
 use std::collections::{HashMap, VecDeque};
 use std::rc::Rc;
+use std::time::Instant;
 
 use base::id::{PipelineId, WebViewId};
 use compositing_traits::display_list::ScrollType;
@@ -25,9 +26,12 @@ use log::{debug, warn};
 use servo_geometry::DeviceIndependentPixel;
 use style_traits::{CSSPixel, PinchZoomFactor};
 use webrender_api::units::{DeviceIntPoint, DevicePixel, DevicePoint, DeviceRect, LayoutVector2D};
-use webrender_api::{ExternalScrollId, HitTestFlags, ScrollLocation};
+use webrender_api::{DynamicProperties, ExternalScrollId, HitTestFlags, ScrollLocation};
 
 use crate::compositor::{HitTestError, PipelineDetails, ServoRenderer};
+use crate::compositor_animations::{compositor_animation_finished, sample_compositor_animation};
+use crate::intersection_observation::update_intersection_observation;
+use crate::smooth_scroll::SmoothScroll;
 use crate::touch::{TouchHandler, TouchMoveAction, TouchMoveAllowed, TouchSequenceState};
 
 #[derive(Clone, Copy)]
@@ -328,7 +332,178 @@ impl WebViewRenderer {
         started_animating
     }
 
-    fn update_animation_state(&mut self) {
+    /// Start animating the scroll node with the given [`ExternalScrollId`] from its current
+    /// offset to `offset`, replacing any smooth scroll that is already running for it.
+    /// Returns true if the pipeline has started animating.
+    pub(crate) fn start_smooth_scroll(
+        &mut self,
+        pipeline_id: PipelineId,
+        external_scroll_id: ExternalScrollId,
+        offset: LayoutVector2D,
+    ) -> bool {
+        let Some(pipeline_details) = self.pipelines.get_mut(&pipeline_id) else {
+            return false;
+        };
+        let Some(start_offset) = pipeline_details
+            .scroll_tree
+            .scroll_offset(external_scroll_id)
+        else {
+            warn!("Could not scroll node with id: {external_scroll_id:?}");
+            return false;
+        };
+
+        let was_animating = pipeline_details.animating();
+        pipeline_details.smooth_scrolls.insert(
+            external_scroll_id,
+            SmoothScroll::new(start_offset, offset, Instant::now()),
+        );
+        let started_animating = !was_animating && pipeline_details.animating();
+
+        self.update_animation_state();
+        started_animating
+    }
+
+    /// Stop the smooth scroll of the given scroll node, if it has one, leaving the node at
+    /// the offset that it has been animated to so far.
+    pub(crate) fn cancel_smooth_scroll(
+        &mut self,
+        pipeline_id: PipelineId,
+        external_scroll_id: ExternalScrollId,
+    ) {
+        let Some(pipeline_details) = self.pipelines.get_mut(&pipeline_id) else {
+            return;
+        };
+        if pipeline_details
+            .smooth_scrolls
+            .remove(&external_scroll_id)
+            .is_some()
+        {
+            self.update_animation_state();
+        }
+    }
+
+    /// Advance the smooth scrolls of all pipelines of this [`WebViewRenderer`] to `now`.
+    /// Returns the new offsets of the scroll nodes that they moved, which still need to be
+    /// sent to WebRender.
+    pub(crate) fn tick_smooth_scrolls(
+        &mut self,
+        now: Instant,
+    ) -> Vec<(ExternalScrollId, LayoutVector2D)> {
+        let mut scroll_offset_updates = Vec::new();
+        let mut pipelines_with_finished_scrolls = Vec::new();
+        for (pipeline_id, pipeline_details) in self.pipelines.iter_mut() {
+            if pipeline_details.throttled {
+                continue;
+            }
+
+            let PipelineDetails {
+                smooth_scrolls,
+                scroll_tree,
+                ..
+            } = pipeline_details;
+            let smooth_scroll_count = smooth_scrolls.len();
+            smooth_scrolls.retain(|external_scroll_id, smooth_scroll| {
+                if let Some(offset) = scroll_tree
+                    .set_scroll_offset_for_node_with_external_scroll_id(
+                        *external_scroll_id,
+                        smooth_scroll.sample(now),
+                        ScrollType::Script,
+                    )
+                {
+                    scroll_offset_updates.push((*external_scroll_id, offset));
+                }
+                !smooth_scroll.finished(now)
+            });
+            if smooth_scrolls.len() != smooth_scroll_count {
+                pipelines_with_finished_scrolls.push(*pipeline_id);
+            }
+        }
+
+        if !pipelines_with_finished_scrolls.is_empty() {
+            // Layout has known about the final offsets all along, but it may have been told
+            // about intermediate ones when other nodes were scrolled during the animation.
+            for pipeline_id in pipelines_with_finished_scrolls {
+                self.send_scroll_positions_to_layout_for_pipeline(pipeline_id);
+            }
+            self.update_animation_state();
+        }
+
+        scroll_offset_updates
+    }
+
+    /// Advance the animations that the compositor runs for all pipelines of this
+    /// [`WebViewRenderer`] to `now`, on the animation timeline, adding their new values to
+    /// `properties`, which still need to be sent to WebRender.
+    pub(crate) fn tick_compositor_animations(
+        &mut self,
+        now: f64,
+        properties: &mut DynamicProperties,
+    ) {
+        let mut any_animation_finished = false;
+        for pipeline_details in self.pipelines.values_mut() {
+            if pipeline_details.throttled {
+                continue;
+            }
+
+            let animation_count = pipeline_details.compositor_animations.len();
+            pipeline_details.compositor_animations.retain(|animation| {
+                sample_compositor_animation(animation, now, properties);
+                !compositor_animation_finished(animation, now)
+            });
+            any_animation_finished |=
+                pipeline_details.compositor_animations.len() != animation_count;
+        }
+
+        // Finished animations keep their last value until script, which ticks the same
+        // animations, causes layout to build a display list without them.
+        if any_animation_finished {
+            self.update_animation_state();
+        }
+    }
+
+    /// Recompute the intersections of the tracked `IntersectionObserver` targets of all
+    /// pipelines after scrolling and report the ones that changed to script.
+    pub(crate) fn update_intersection_observations(&mut self) {
+        for (pipeline_id, pipeline_details) in self.pipelines.iter_mut() {
+            // Script moves smoothly scrolled nodes to their final offset right away, so
+            // reporting the intermediate offsets of the animation would make the entries
+            // that script queues flip-flop.
+            if pipeline_details.throttled ||
+                !pipeline_details.smooth_scrolls.is_empty() ||
+                pipeline_details.intersection_observation_targets.is_empty()
+            {
+                continue;
+            }
+
+            let viewport_scroll_offset = pipeline_details
+                .scroll_tree
+                .scroll_offset(pipeline_id.root_scroll_id())
+                .unwrap_or_default();
+            let changes: Vec<_> = pipeline_details
+                .intersection_observation_targets
+                .iter_mut()
+                .filter_map(|target| {
+                    update_intersection_observation(target, viewport_scroll_offset)
+                })
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+
+            if let Err(error) = self.global.borrow().constellation_sender.send(
+                EmbedderToConstellationMessage::IntersectionObservationsChanged(
+                    *pipeline_id,
+                    changes,
+                ),
+            ) {
+                warn!(
+                    "Sending intersection observation changes to constellation failed ({error:?})."
+                );
+            }
+        }
+    }
+
+    pub(crate) fn update_animation_state(&mut self) {
         self.animating = self.pipelines.values().any(PipelineDetails::animating);
         self.webview.set_animating(self.animating());
     }
@@ -410,6 +585,7 @@ impl WebViewRenderer {
                     warn!("Not hit test result.");
                 }
             },
+            InputEvent::ExternalDrag(_) => {},
             _ => unreachable!("Unexpected input event type: {event:?}"),
         }
 
@@ -859,6 +1035,11 @@ impl WebViewRenderer {
             )
         });
         if let Some(scroll_result) = scroll_result.clone() {
+            // Scrolling a node with input events takes over from any smooth scroll of it.
+            self.cancel_smooth_scroll(
+                scroll_result.hit_test_result.pipeline_id,
+                scroll_result.external_scroll_id,
+            );
             self.send_scroll_positions_to_layout_for_pipeline(
                 scroll_result.hit_test_result.pipeline_id,
             );
@@ -998,6 +1179,17 @@ impl WebViewRenderer {
             ));
     }
 
+    /// Undo any pinch zoom, so that the viewport shows the whole layout viewport again.
+    pub(crate) fn reset_pinch_zoom(&mut self) {
+        if self.global.borrow().shutdown_state() != ShutdownState::NotShuttingDown {
+            return;
+        }
+
+        let magnification = 1.0 / self.pinch_zoom_level().get();
+        self.pending_scroll_zoom_events
+            .push(ScrollZoomEvent::PinchZoom(magnification));
+    }
+
     fn send_window_size_message(&self) {
         // The device pixel ratio used by the style system should include the scale from page pixels
         // to device pixels, but not including any pinch zoom.
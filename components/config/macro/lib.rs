@@ -10,8 +10,8 @@ use synstructure::decl_derive;
 decl_derive!([ServoPreferences] => servo_preferences_derive);
 
 /// A derive macro that adds string-based getter and setter for each field of this struct
-/// (enums and other types are not supported). Each field must be able to be convertable
-/// (with `into()`) into a `PrefValue`.
+/// (enums and other types are not supported), as well as a list of the names of all fields.
+/// Each field must be able to be convertable (with `into()`) into a `PrefValue`.
 fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
     let ast = input.ast();
 
@@ -28,6 +28,12 @@ fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
         get_match_cases.extend(quote!(stringify!(#name) => self.#name.clone().into(),))
     }
 
+    let mut names = quote!();
+    for field in named_fields.named.iter() {
+        let name = field.ident.as_ref().unwrap();
+        names.extend(quote!(stringify!(#name),))
+    }
+
     let mut set_match_cases = quote!();
     for field in named_fields.named.iter() {
         let name = field.ident.as_ref().unwrap();
@@ -37,6 +43,9 @@ fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
     let structure_name = &ast.ident;
     quote! {
         impl #structure_name {
+            /// The names of all preferences, in the order in which they are declared.
+            pub const NAMES: &'static [&'static str] = &[#names];
+
             pub fn get_value(&self, name: &str) -> PrefValue {
                 match name {
                     #get_match_cases
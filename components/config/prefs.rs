@@ -38,10 +38,36 @@ pub fn set(preferences: Preferences) {
         "layout.container-queries.enabled",
         preferences.layout_container_queries_enabled,
     );
+    stylo_config::set_bool(
+        "layout.css.anchor-positioning.enabled",
+        preferences.layout_css_anchor_positioning_enabled,
+    );
+    stylo_config::set_bool(
+        "layout.css.backdrop-filter.enabled",
+        preferences.layout_css_backdrop_filter_enabled,
+    );
+    stylo_config::set_bool(
+        "layout.css.at-scope.enabled",
+        preferences.layout_css_at_scope_enabled,
+    );
+    stylo_config::set_bool(
+        "layout.css.text-wrap-style.enabled",
+        preferences.layout_css_text_wrap_style_enabled,
+    );
 
     *PREFERENCES.write().unwrap() = preferences;
 }
 
+/// Change the values of the preferences with the given names, leaving all other preferences
+/// as they are.
+pub fn set_values(values: &[(String, PrefValue)]) {
+    let mut preferences = get().clone();
+    for (name, value) in values {
+        preferences.set_value(name, value.clone());
+    }
+    set(preferences);
+}
+
 /// A convenience macro for accessing a preference value using its static path.
 /// Passing an invalid path is a compile-time error.
 #[macro_export]
@@ -75,18 +101,35 @@ pub struct Preferences {
     pub dom_allow_scripts_to_close_windows: bool,
     pub dom_canvas_capture_enabled: bool,
     pub dom_canvas_text_enabled: bool,
+    /// Whether to render 2D canvases on the GPU with vello, when Servo is built with the
+    /// `canvas-vello` feature and a GPU device is available.
+    pub dom_canvas_vello_enabled: bool,
     pub dom_clipboardevent_enabled: bool,
     pub dom_composition_event_enabled: bool,
     pub dom_crypto_subtle_enabled: bool,
     pub dom_customelements_enabled: bool,
     pub dom_document_dblclick_timeout: i64,
     pub dom_document_dblclick_dist: i64,
+    /// The time in milliseconds after which the documents of a hidden `WebView` are frozen
+    /// until it is shown again, or 0 to never freeze them.
+    pub dom_document_freeze_timeout: i64,
+    /// Enable the origin private file system and the file pickers of the File System
+    /// Access API.
+    pub dom_file_system_enabled: bool,
     pub dom_fontface_enabled: bool,
     pub dom_fullscreen_test: bool,
     pub dom_gamepad_enabled: bool,
+    /// Enable the Geolocation API (`navigator.geolocation`).
+    pub dom_geolocation_enabled: bool,
+    /// Enable the CSS Custom Highlight API (`CSS.highlights` and `Highlight`).
+    pub dom_highlight_api_enabled: bool,
     pub dom_imagebitmap_enabled: bool,
     pub dom_indexeddb_enabled: bool,
+    /// The amount of IndexedDB storage each origin may use, in megabytes.
+    pub dom_indexeddb_origin_quota_mb: i64,
     pub dom_intersection_observer_enabled: bool,
+    /// Enable `MediaSource` and `SourceBuffer` from Media Source Extensions.
+    pub dom_media_source_enabled: bool,
     pub dom_microdata_testing_enabled: bool,
     pub dom_mouse_event_which_enabled: bool,
     pub dom_mutation_observer_enabled: bool,
@@ -94,12 +137,24 @@ pub struct Preferences {
     pub dom_offscreen_canvas_enabled: bool,
     pub dom_permissions_enabled: bool,
     pub dom_permissions_testing_allowed_in_nonsecure_contexts: bool,
+    /// Enable the Picture-in-Picture API, which shows videos in a floating window of the
+    /// embedder.
+    pub dom_picture_in_picture_enabled: bool,
+    pub dom_push_enabled: bool,
     pub dom_resize_observer_enabled: bool,
+    /// Enable the Prioritized Task Scheduling API: `scheduler.postTask()`, `TaskController`
+    /// and `TaskSignal`.
+    pub dom_scheduler_enabled: bool,
     pub dom_script_asynch: bool,
     pub dom_serviceworker_enabled: bool,
     pub dom_serviceworker_timeout_seconds: i64,
     pub dom_servo_helpers_enabled: bool,
     pub dom_servoparser_async_html_tokenizer_enabled: bool,
+    /// Expose the `StorageManager` API as `navigator.storage`.
+    pub dom_storage_manager_enabled: bool,
+    /// The amount of storage all origins together may use, in megabytes. Above it, the data
+    /// of the least recently used origins whose storage is not persistent is evicted.
+    pub dom_storage_total_quota_mb: i64,
     pub dom_svg_enabled: bool,
     pub dom_testable_crash_enabled: bool,
     pub dom_testbinding_enabled: bool,
@@ -116,10 +171,20 @@ pub struct Preferences {
     pub dom_testperf_enabled: bool,
     // https://testutils.spec.whatwg.org#availability
     pub dom_testutils_enabled: bool,
+    /// The number of times per second that the animation frame callbacks of documents in
+    /// throttled windows, which are not visible, are run. Zero pauses them.
+    pub dom_throttled_animation_frame_rate: i64,
     pub dom_trusted_types_enabled: bool,
     pub dom_xpath_enabled: bool,
     /// Enable WebGL2 APIs.
     pub dom_webgl2_enabled: bool,
+    /// Expose the CSS animations and transitions of elements to script through
+    /// `getAnimations()` and the `Animation` interface.
+    pub dom_web_animations_enabled: bool,
+    /// Expose the Credential Management and Web Authentication APIs as
+    /// `navigator.credentials`, which create and use passkeys with the authenticators of the
+    /// embedder.
+    pub dom_webauthn_enabled: bool,
     pub dom_webrtc_enabled: bool,
     pub dom_webrtc_transceiver_enabled: bool,
     pub dom_webvtt_enabled: bool,
@@ -179,6 +244,9 @@ pub struct Preferences {
     pub js_mem_gc_high_frequency_high_limit_mb: i64,
     pub js_mem_gc_high_frequency_low_limit_mb: i64,
     pub js_mem_gc_high_frequency_time_limit_ms: i64,
+    /// Give the JavaScript engine a chance to collect garbage in the idle time between
+    /// frames, within a budget that adapts to how much time recent frames had to spare.
+    pub js_mem_gc_idle_scheduling_enabled: bool,
     pub js_mem_gc_incremental_enabled: bool,
     pub js_mem_gc_incremental_slice_ms: i64,
     pub js_mem_gc_low_frequency_heap_growth: i64,
@@ -193,14 +261,31 @@ pub struct Preferences {
     pub js_throw_on_asmjs_validation_failure: bool,
     pub js_throw_on_debuggee_would_run: bool,
     pub js_timers_minimum_duration: i64,
+    /// The time in milliseconds after which the timers of a hidden `WebView` are throttled
+    /// to `js_timers_intensive_throttling_minimum_duration`, or 0 to never do so.
+    pub js_timers_intensive_throttling_grace_period: i64,
+    pub js_timers_intensive_throttling_minimum_duration: i64,
     pub js_wasm_baseline_enabled: bool,
     pub js_wasm_enabled: bool,
     pub js_wasm_ion_enabled: bool,
     pub js_werror_enabled: bool,
     pub layout_animations_test_enabled: bool,
     pub layout_columns_enabled: bool,
+    /// Run animations of `transform` and `opacity` in the compositor, so that they stay
+    /// smooth while script and layout are busy.
+    pub layout_compositor_animations_enabled: bool,
     pub layout_grid_enabled: bool,
     pub layout_container_queries_enabled: bool,
+    /// Enable `anchor-name`, `position-anchor`, `anchor()` and `position-try-fallbacks`.
+    pub layout_css_anchor_positioning_enabled: bool,
+    /// Enable parsing of `@scope` rules and the `CSSScopeRule` interface. Servo does not
+    /// order scoped rules by scope proximity or restyle when scope roots change, that is
+    /// left to Stylo, so this stays off by default.
+    pub layout_css_at_scope_enabled: bool,
+    /// Enable the `backdrop-filter` property.
+    pub layout_css_backdrop_filter_enabled: bool,
+    /// Enable `text-wrap-style`, including balanced and "pretty" line breaking.
+    pub layout_css_text_wrap_style_enabled: bool,
     pub layout_css_transition_behavior_enabled: bool,
     pub layout_flexbox_enabled: bool,
     pub layout_threads: i64,
@@ -213,9 +298,21 @@ pub struct Preferences {
     pub network_enforce_tls_enabled: bool,
     pub network_enforce_tls_localhost: bool,
     pub network_enforce_tls_onion: bool,
+    /// Whether documents are denied the cookies of the sites other than the site of their
+    /// top-level document, instead of getting cookies partitioned by the top-level site.
+    pub network_http_block_third_party_cookies: bool,
     pub network_http_cache_disabled: bool,
+    /// The referrer policy used when neither the request nor its policy container specify one.
+    pub network_http_default_referrer_policy: String,
     pub network_local_directory_listing_enabled: bool,
     pub network_mime_sniff: bool,
+    /// Whether the user asked for data usage to be reduced, in which case no connections
+    /// are opened nor hosts resolved ahead of the requests that may need them.
+    pub network_save_data: bool,
+    /// Whether hosts are resolved and connected to ahead of the requests that are
+    /// expected to need them, for `<link rel=dns-prefetch>`, `<link rel=preconnect>` and
+    /// the hosts that the HTML parser discovers.
+    pub network_speculative_connections_enabled: bool,
     pub session_history_max_length: i64,
     /// The background color of shell's viewport. This will be used by OpenGL's `glClearColor`.
     pub shell_background_color_rgba: [f64; 4],
@@ -253,18 +350,25 @@ impl Preferences {
             dom_bluetooth_testing_enabled: false,
             dom_canvas_capture_enabled: false,
             dom_canvas_text_enabled: true,
+            dom_canvas_vello_enabled: false,
             dom_clipboardevent_enabled: true,
             dom_composition_event_enabled: false,
             dom_crypto_subtle_enabled: true,
             dom_customelements_enabled: true,
             dom_document_dblclick_dist: 1,
+            dom_document_freeze_timeout: 0,
             dom_document_dblclick_timeout: 300,
+            dom_file_system_enabled: false,
             dom_fontface_enabled: false,
             dom_fullscreen_test: false,
             dom_gamepad_enabled: true,
+            dom_geolocation_enabled: false,
+            dom_highlight_api_enabled: false,
             dom_imagebitmap_enabled: false,
             dom_indexeddb_enabled: false,
+            dom_indexeddb_origin_quota_mb: 1024,
             dom_intersection_observer_enabled: false,
+            dom_media_source_enabled: false,
             dom_microdata_testing_enabled: false,
             dom_mouse_event_which_enabled: false,
             dom_mutation_observer_enabled: true,
@@ -272,12 +376,17 @@ impl Preferences {
             dom_offscreen_canvas_enabled: false,
             dom_permissions_enabled: false,
             dom_permissions_testing_allowed_in_nonsecure_contexts: false,
+            dom_picture_in_picture_enabled: false,
+            dom_push_enabled: false,
             dom_resize_observer_enabled: false,
+            dom_scheduler_enabled: false,
             dom_script_asynch: true,
             dom_serviceworker_enabled: false,
             dom_serviceworker_timeout_seconds: 60,
             dom_servo_helpers_enabled: false,
             dom_servoparser_async_html_tokenizer_enabled: false,
+            dom_storage_manager_enabled: false,
+            dom_storage_total_quota_mb: 10 * 1024,
             dom_svg_enabled: false,
             dom_testable_crash_enabled: false,
             dom_testbinding_enabled: false,
@@ -293,8 +402,11 @@ impl Preferences {
             dom_testing_html_input_element_select_files_enabled: false,
             dom_testperf_enabled: false,
             dom_testutils_enabled: false,
+            dom_throttled_animation_frame_rate: 1,
             dom_trusted_types_enabled: false,
             dom_webgl2_enabled: false,
+            dom_web_animations_enabled: true,
+            dom_webauthn_enabled: false,
             dom_webgpu_enabled: false,
             dom_webgpu_wgpu_backend: String::new(),
             dom_webrtc_enabled: false,
@@ -355,6 +467,7 @@ impl Preferences {
             js_mem_gc_high_frequency_high_limit_mb: 500,
             js_mem_gc_high_frequency_low_limit_mb: 100,
             js_mem_gc_high_frequency_time_limit_ms: 1000,
+            js_mem_gc_idle_scheduling_enabled: true,
             js_mem_gc_incremental_enabled: true,
             js_mem_gc_incremental_slice_ms: 10,
             js_mem_gc_low_frequency_heap_growth: 150,
@@ -369,13 +482,20 @@ impl Preferences {
             js_throw_on_asmjs_validation_failure: false,
             js_throw_on_debuggee_would_run: false,
             js_timers_minimum_duration: 1000,
+            js_timers_intensive_throttling_grace_period: 300000,
+            js_timers_intensive_throttling_minimum_duration: 60000,
             js_wasm_baseline_enabled: true,
             js_wasm_enabled: true,
             js_wasm_ion_enabled: true,
             js_werror_enabled: false,
             layout_animations_test_enabled: false,
             layout_columns_enabled: false,
+            layout_compositor_animations_enabled: true,
             layout_container_queries_enabled: false,
+            layout_css_anchor_positioning_enabled: false,
+            layout_css_at_scope_enabled: false,
+            layout_css_backdrop_filter_enabled: false,
+            layout_css_text_wrap_style_enabled: false,
             layout_css_transition_behavior_enabled: true,
             layout_flexbox_enabled: true,
             layout_grid_enabled: false,
@@ -388,9 +508,13 @@ impl Preferences {
             network_enforce_tls_enabled: false,
             network_enforce_tls_localhost: false,
             network_enforce_tls_onion: false,
+            network_http_block_third_party_cookies: false,
             network_http_cache_disabled: false,
+            network_http_default_referrer_policy: String::from("strict-origin-when-cross-origin"),
             network_local_directory_listing_enabled: true,
             network_mime_sniff: false,
+            network_save_data: false,
+            network_speculative_connections_enabled: true,
             session_history_max_length: 20,
             shell_background_color_rgba: [1.0, 1.0, 1.0, 1.0],
             threadpools_async_runtime_workers_max: 6,
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scheduling of hidden `WebView`s, which use less resources the longer they stay hidden.
+//!
+//! When a `WebView` is hidden, its top-level pipeline is throttled right away and the
+//! priority of the content processes that only run hidden `WebView`s is lowered. After a
+//! grace period, the timers of its documents only run about once a minute, and after a
+//! longer period of inactivity, if configured, its documents are frozen until it is shown
+//! again, following <https://wicg.github.io/page-lifecycle/>.
+
+use std::time::{Duration, Instant};
+
+use servo_config::pref;
+
+/// How far a hidden `WebView` has been moved into the background.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum BackgroundLevel {
+    /// Rendering is throttled and timers run at most once a second.
+    Throttled,
+    /// Timers run at most as often as `js_timers_intensive_throttling_minimum_duration`.
+    IntensivelyThrottled,
+    /// None of the tasks of the documents run.
+    Frozen,
+}
+
+/// The background state of a hidden `WebView`.
+pub(crate) struct Background {
+    hidden_since: Instant,
+    pub level: BackgroundLevel,
+}
+
+impl Background {
+    pub(crate) fn new() -> Self {
+        Self {
+            hidden_since: Instant::now(),
+            level: BackgroundLevel::Throttled,
+        }
+    }
+
+    /// The next level that the `WebView` moves to if it stays hidden, and when.
+    pub(crate) fn next_level(&self) -> Option<(BackgroundLevel, Instant)> {
+        let (level, delay) = match self.level {
+            BackgroundLevel::Throttled => (
+                BackgroundLevel::IntensivelyThrottled,
+                pref!(js_timers_intensive_throttling_grace_period),
+            ),
+            BackgroundLevel::IntensivelyThrottled => {
+                (BackgroundLevel::Frozen, pref!(dom_document_freeze_timeout))
+            },
+            BackgroundLevel::Frozen => return None,
+        };
+        if delay <= 0 {
+            return None;
+        }
+        Some((
+            level,
+            self.hidden_since + Duration::from_millis(delay as u64),
+        ))
+    }
+}
@@ -92,6 +92,7 @@ use std::marker::PhantomData;
 use std::mem::replace;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{process, thread};
 
 use background_hang_monitor::HangMonitorRegister;
@@ -115,14 +116,17 @@ use compositing_traits::{
     WebrenderExternalImageRegistry,
 };
 use constellation_traits::{
-    AuxiliaryWebViewCreationRequest, AuxiliaryWebViewCreationResponse, BroadcastMsg, DocumentState,
-    EmbedderToConstellationMessage, IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState,
-    IFrameSizeMsg, Job, LoadData, LoadOrigin, LogEntry, MessagePortMsg, NavigationHistoryBehavior,
-    PaintMetricEvent, PortMessageTask, PortTransferInfo, SWManagerMsg, SWManagerSenders,
-    ScriptToConstellationChan, ScriptToConstellationMessage, ServiceWorkerManagerFactory,
-    ServiceWorkerMsg, StructuredSerializedData, TraversalDirection, WindowSizeType,
+    AuxiliaryWebViewCreationRequest, AuxiliaryWebViewCreationResponse, BroadcastMsg, CrashReport,
+    DocumentState, EmbedderToConstellationMessage, EventLoopReport, FrameClock, IFrameLoadInfo,
+    IFrameLoadInfoWithData, IFrameSandboxState, IFrameSizeMsg, IntersectionObservationChange, Job,
+    LoadData, LoadOrigin, LogEntry, MessagePortMsg, NavigationHistoryBehavior,
+    OffscreenCanvasPlaceholder, PaintMetricEvent, PipelineReport, PortMessageTask,
+    PortTransferInfo, RuntimeServicesReport, SWManagerMsg, SWManagerSenders,
+    ScriptToConstellationChan, ScriptToConstellationMessage, SequentialFocusDirection,
+    ServiceWorkerManagerFactory, ServiceWorkerMsg, StructuredSerializedData, TraversalDirection,
+    WindowSizeType,
 };
-use crossbeam_channel::{Receiver, Select, Sender, unbounded};
+use crossbeam_channel::{Receiver, Select, Sender, at, never, unbounded};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, DevtoolsPageInfo, NavigationState,
     ScriptToDevtoolsControlMsg,
@@ -130,11 +134,12 @@ use devtools_traits::{
 use embedder_traits::resources::{self, Resource};
 use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    AnimationState, CompositorHitTestResult, Cursor, EmbedderMsg, EmbedderProxy,
-    FocusSequenceNumber, InputEvent, JSValue, JavaScriptEvaluationError, JavaScriptEvaluationId,
-    KeyboardEvent, MediaSessionActionType, MediaSessionEvent, MediaSessionPlaybackState,
-    MouseButton, MouseButtonAction, MouseButtonEvent, Theme, ViewportDetails, WebDriverCommandMsg,
-    WebDriverCommandResponse, WebDriverLoadStatus,
+    AllowOrDeny, AnimationState, CompositorHitTestResult, CrashDetails, Cursor, EmbedderMsg,
+    EmbedderProxy, FindInPageRequest, FocusSequenceNumber, InputEvent, JSValue,
+    JavaScriptEvaluationError, JavaScriptEvaluationId, KeyboardEvent, MediaSessionActionType,
+    MediaSessionEvent, MediaSessionPlaybackState, MouseButton, MouseButtonAction, MouseButtonEvent,
+    PermissionDecision, PermissionFeature, Theme, UserAgentStylesheetOverride, ViewportDetails,
+    WebDriverCommandMsg, WebDriverCommandResponse, WebDriverLoadStatus,
 };
 use euclid::Size2D;
 use euclid::default::Size2D as UntypedSize2D;
@@ -146,10 +151,10 @@ use keyboard_types::{Key, KeyState, Modifiers};
 use layout_api::{LayoutFactory, ScriptThreadFactory};
 use log::{debug, error, info, trace, warn};
 use media::WindowGLContext;
-use net_traits::pub_domains::reg_host;
+use net_traits::pub_domains::Site;
 use net_traits::request::Referrer;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, IpcSend, ReferrerPolicy, ResourceThreads};
+use net_traits::{self, CoreResourceMsg, FetchChannels, IpcSend, ReferrerPolicy, ResourceThreads};
 use profile_traits::mem::ProfilerMsg;
 use profile_traits::{mem, time};
 use script_traits::{
@@ -157,26 +162,29 @@ use script_traits::{
     ScriptThreadMessage, UpdatePipelineIdReason,
 };
 use serde::{Deserialize, Serialize};
+use servo_config::prefs::{self, PrefValue};
 use servo_config::{opts, pref};
 use servo_rand::{Rng, ServoRng, SliceRandom, random};
-use servo_url::{Host, ImmutableOrigin, ServoUrl};
+use servo_url::{ImmutableOrigin, ServoUrl};
 use style_traits::CSSPixel;
 #[cfg(feature = "webgpu")]
 use webgpu::swapchain::WGPUImageMap;
 #[cfg(feature = "webgpu")]
 use webgpu_traits::{WebGPU, WebGPURequest};
 use webrender::RenderApiSender;
-use webrender_api::units::LayoutVector2D;
+use webrender_api::units::{DeviceIntSize, LayoutVector2D};
 use webrender_api::{DocumentId, ExternalScrollId, ImageKey};
 
+use crate::background::{Background, BackgroundLevel};
 use crate::browsingcontext::{
     AllBrowsingContextsIterator, BrowsingContext, FullyActiveBrowsingContextsIterator,
     NewBrowsingContextInfo,
 };
 use crate::constellation_webview::ConstellationWebView;
 use crate::event_loop::EventLoop;
+use crate::permission_manager::PermissionManager;
 use crate::pipeline::{InitialPipelineState, Pipeline};
-use crate::process_manager::ProcessManager;
+use crate::process_manager::{ProcessManager, set_process_backgrounded};
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 use crate::session_history::{
     JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
@@ -237,17 +245,24 @@ struct BrowsingContextGroup {
     top_level_browsing_context_set: HashSet<WebViewId>,
 
     /// The set of all event loops in this BrowsingContextGroup.
-    /// We store the event loops in a map
-    /// indexed by registered domain name (as a `Host`) to event loops.
-    /// It is important that scripts with the same eTLD+1,
+    /// We store the event loops in a map indexed by [`Site`], which is the scheme
+    /// and registered domain name (eTLD+1) of an origin.
+    /// It is important that scripts of the same site,
     /// who are part of the same browsing-context group
     /// share an event loop, since they can use `document.domain`
     /// to become same-origin, at which point they can share DOM objects.
-    event_loops: HashMap<Host, Weak<EventLoop>>,
+    /// Scripts of different sites, such as cross-site iframes, get their own
+    /// event loop, which runs in its own content process in multiprocess mode.
+    /// Keying by site only changes which documents share an event loop: the
+    /// frame tree operations that cross event loops were already proxied by the
+    /// constellation, see [`Constellation::handle_post_message_msg`],
+    /// [`Constellation::handle_focus_remote_document_msg`] and
+    /// [`Constellation::handle_iframe_size_msg`].
+    event_loops: HashMap<Site, Weak<EventLoop>>,
 
     /// The set of all WebGPU channels in this BrowsingContextGroup.
     #[cfg(feature = "webgpu")]
-    webgpus: HashMap<Host, WebGPU>,
+    webgpus: HashMap<Site, WebGPU>,
 }
 
 /// The `Constellation` itself. In the servo browser, there is one
@@ -445,6 +460,9 @@ pub struct Constellation<STF, SWF> {
     /// Pipeline ID of the active media session.
     active_media_session: Option<PipelineId>,
 
+    /// Pipeline ID of the document whose video is shown in a picture-in-picture window.
+    picture_in_picture_pipeline: Option<PipelineId>,
+
     /// The image bytes associated with the RippyPNG embedder resource.
     /// Read during startup and provided to image caches that are created
     /// on an as-needed basis, rather than retrieving it every time.
@@ -455,6 +473,25 @@ pub struct Constellation<STF, SWF> {
 
     /// The process manager.
     process_manager: ProcessManager,
+
+    /// The decisions of the user about the permissions of origins.
+    permission_manager: PermissionManager,
+
+    /// A channel on which the answers of the user to storage access prompts are sent from
+    /// the router thread, so that they can be recorded in the permission manager.
+    storage_access_decision_sender: Sender<StorageAccessDecision>,
+
+    /// The constellation's view of `storage_access_decision_sender`.
+    storage_access_decision_receiver: Receiver<StorageAccessDecision>,
+}
+
+/// The answer of the user to a prompt about letting `embedded_site` use its unpartitioned
+/// cookies and storage when it is embedded in `top_level_site`.
+#[derive(Debug)]
+struct StorageAccessDecision {
+    top_level_site: Site,
+    embedded_site: Site,
+    decision: PermissionDecision,
 }
 
 /// State needed to construct a constellation.
@@ -552,6 +589,10 @@ enum ExitPipelineMode {
 /// The number of warnings to include in each crash report.
 const WARNINGS_BUFFER_SIZE: usize = 32;
 
+/// How long to wait for script threads and resource threads to report on their state, after
+/// which they are reported as not responding.
+const RUNTIME_SERVICES_REPORT_TIMEOUT: Duration = Duration::from_millis(500);
+
 /// Route an ipc receiver to an crossbeam receiver, preserving any errors.
 fn route_ipc_receiver_to_new_crossbeam_receiver_preserving_errors<T>(
     ipc_receiver: IpcReceiver<T>,
@@ -651,6 +692,9 @@ where
 
                 let rippy_data = resources::read_bytes(Resource::RippyPNG);
 
+                let (storage_access_decision_sender, storage_access_decision_receiver) =
+                    unbounded();
+
                 let mut constellation: Constellation<STF, SWF> = Constellation {
                     namespace_receiver,
                     namespace_ipc_sender,
@@ -710,9 +754,13 @@ where
                     active_keyboard_modifiers: Modifiers::empty(),
                     hard_fail,
                     active_media_session: None,
+                    picture_in_picture_pipeline: None,
                     rippy_data,
                     user_content_manager: state.user_content_manager,
                     process_manager: ProcessManager::new(state.mem_profiler_chan),
+                    permission_manager: PermissionManager::new(),
+                    storage_access_decision_sender,
+                    storage_access_decision_receiver,
                 };
 
                 constellation.run();
@@ -749,7 +797,7 @@ where
 
     fn get_event_loop(
         &mut self,
-        host: &Host,
+        site: &Site,
         webview_id: &WebViewId,
         opener: &Option<BrowsingContextId>,
     ) -> Result<Weak<EventLoop>, &'static str> {
@@ -783,7 +831,7 @@ where
         };
         bc_group
             .event_loops
-            .get(host)
+            .get(site)
             .ok_or("Trying to get an event-loop from an unknown browsing context group")
             .cloned()
     }
@@ -791,7 +839,7 @@ where
     fn set_event_loop(
         &mut self,
         event_loop: Weak<EventLoop>,
-        host: Host,
+        site: Site,
         webview_id: WebViewId,
         opener: Option<BrowsingContextId>,
     ) {
@@ -830,12 +878,12 @@ where
         if let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) {
             if bc_group
                 .event_loops
-                .insert(host.clone(), event_loop)
+                .insert(site.clone(), event_loop)
                 .is_some()
             {
                 warn!(
                     "Double-setting an event-loop for {:?} at {:?}",
-                    host, relevant_top_level
+                    site, relevant_top_level
                 );
             }
         }
@@ -855,7 +903,7 @@ where
         // here, because it will be send on an ipc channel,
         // and ipc channels take onership of their data.
         // https://github.com/servo/ipc-channel/issues/138
-        load_data: LoadData,
+        mut load_data: LoadData,
         sandbox: IFrameSandboxState,
         is_private: bool,
         throttled: bool,
@@ -864,21 +912,42 @@ where
             return;
         }
 
-        let Some(theme) = self
-            .webviews
-            .get(webview_id)
-            .map(ConstellationWebView::theme)
-        else {
+        // The cookies and storage of nested documents are partitioned by the origin of the
+        // top-level document, whose URL is final by the time it has nested documents.
+        if parent_pipeline_id.is_some() {
+            load_data.top_level_origin = self
+                .browsing_contexts
+                .get(&BrowsingContextId::from(webview_id))
+                .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+                .map(|pipeline| pipeline.url.origin());
+        }
+
+        // Ancestors may live in other script threads, so the constellation is the only place
+        // where the origins of all of them are known.
+        load_data.ancestor_origins.clear();
+        let mut ancestor_pipeline_id = parent_pipeline_id;
+        while let Some(pipeline) = ancestor_pipeline_id.and_then(|id| self.pipelines.get(&id)) {
+            load_data.ancestor_origins.push(pipeline.url.origin());
+            ancestor_pipeline_id = self
+                .browsing_contexts
+                .get(&pipeline.browsing_context_id)
+                .and_then(|browsing_context| browsing_context.parent_pipeline_id);
+        }
+
+        let Some(webview) = self.webviews.get(webview_id) else {
             warn!("Tried to create Pipeline for uknown WebViewId: {webview_id:?}");
             return;
         };
+        let theme = webview.theme();
+        let user_agent_stylesheet_override = webview.user_agent_stylesheet_override().cloned();
+        let user_stylesheets = webview.user_stylesheets().to_vec();
 
         debug!(
             "{}: Creating new pipeline in {}",
             pipeline_id, browsing_context_id
         );
 
-        let (event_loop, host) = match sandbox {
+        let (event_loop, site) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
                 // If this is an about:blank or about:srcdoc load, it must share the creator's
@@ -887,18 +956,18 @@ where
                 if load_data.url.as_str() != "about:blank" &&
                     load_data.url.as_str() != "about:srcdoc"
                 {
-                    match reg_host(&load_data.url) {
+                    match Site::for_url(&load_data.url) {
                         None => (None, None),
-                        Some(host) => match self.get_event_loop(&host, &webview_id, &opener) {
+                        Some(site) => match self.get_event_loop(&site, &webview_id, &opener) {
                             Err(err) => {
                                 warn!("{}", err);
-                                (None, Some(host))
+                                (None, Some(site))
                             },
                             Ok(event_loop) => {
                                 if let Some(event_loop) = event_loop.upgrade() {
                                     (Some(event_loop), None)
                                 } else {
-                                    (None, Some(host))
+                                    (None, Some(site))
                                 }
                             },
                         },
@@ -952,6 +1021,8 @@ where
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             viewport_details: initial_viewport_details,
             theme,
+            user_agent_stylesheet_override,
+            user_stylesheets,
             event_loop,
             load_data,
             prev_throttled: throttled,
@@ -974,11 +1045,11 @@ where
             self.background_monitor_control_senders.push(chan);
         }
 
-        if let Some(host) = host {
-            debug!("{}: Adding new host entry {}", webview_id, host,);
+        if let Some(site) = site {
+            debug!("{}: Adding new site entry {}", webview_id, site);
             self.set_event_loop(
                 Rc::downgrade(&pipeline.pipeline.event_loop),
-                host,
+                site,
                 webview_id,
                 opener,
             );
@@ -1143,6 +1214,8 @@ where
             BackgroundHangMonitor(HangMonitorAlert),
             Compositor(EmbedderToConstellationMessage),
             FromSWManager(SWManagerMsg),
+            BackgroundLevelChange,
+            StorageAccessDecision(StorageAccessDecision),
             RemoveProcess(usize),
         }
         // Get one incoming request.
@@ -1162,6 +1235,9 @@ where
         sel.recv(&self.background_hang_monitor_receiver);
         sel.recv(&self.compositor_receiver);
         sel.recv(&self.swmanager_receiver);
+        let background_level_change = self.next_background_level_change().map_or_else(never, at);
+        sel.recv(&background_level_change);
+        sel.recv(&self.storage_access_decision_receiver);
 
         self.process_manager.register(&mut sel);
 
@@ -1193,9 +1269,17 @@ where
                     .recv(&self.swmanager_receiver)
                     .expect("Unexpected SW channel panic in constellation")
                     .map(Request::FromSWManager),
+                5 => {
+                    let _ = oper.recv(&background_level_change);
+                    Ok(Request::BackgroundLevelChange)
+                },
+                6 => Ok(Request::StorageAccessDecision(
+                    oper.recv(&self.storage_access_decision_receiver)
+                        .expect("Unexpected storage access channel panic in constellation"),
+                )),
                 _ => {
                     // This can only be a error reading on a closed lifeline receiver.
-                    let process_index = index - 5;
+                    let process_index = index - 7;
                     let _ = oper.recv(self.process_manager.receiver_at(process_index));
                     Ok(Request::RemoveProcess(process_index))
                 },
@@ -1221,6 +1305,17 @@ where
             Request::FromSWManager(message) => {
                 self.handle_request_from_swmanager(message);
             },
+            Request::BackgroundLevelChange => self.advance_background_levels(),
+            Request::StorageAccessDecision(StorageAccessDecision {
+                top_level_site,
+                embedded_site,
+                decision,
+            }) => self.permission_manager.set_storage_access(
+                &top_level_site,
+                &embedded_site,
+                decision.allow_or_deny,
+                decision.remember,
+            ),
             Request::RemoveProcess(index) => self.process_manager.remove(index),
         }
     }
@@ -1315,6 +1410,24 @@ where
                 self.public_resource_threads.clear_cache();
                 self.private_resource_threads.clear_cache();
             },
+            EmbedderToConstellationMessage::ClearSiteData(origin, since) => {
+                // Both the public and private resource threads share the storage thread.
+                let (sender, _) = ipc::channel().expect("ipc channel failure");
+                let message = StorageThreadMsg::ClearSiteData {
+                    sender,
+                    origin,
+                    since,
+                };
+                if let Err(error) = self.public_resource_threads.send(message) {
+                    warn!("Failed to send ClearSiteData to the storage thread: {error}");
+                }
+            },
+            EmbedderToConstellationMessage::SetContentBlockingFilterLists(filter_lists) => {
+                self.public_resource_threads
+                    .set_content_blocking_filter_lists(filter_lists.clone());
+                self.private_resource_threads
+                    .set_content_blocking_filter_lists(filter_lists);
+            },
             // Load a new page from a typed url
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
@@ -1355,8 +1468,19 @@ where
             },
             // Create a new top level browsing context. Will use response_chan to return
             // the browsing context id.
-            EmbedderToConstellationMessage::NewWebView(url, webview_id, viewport_details) => {
-                self.handle_new_top_level_browsing_context(url, webview_id, viewport_details, None);
+            EmbedderToConstellationMessage::NewWebView(
+                url,
+                webview_id,
+                viewport_details,
+                is_private,
+            ) => {
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    webview_id,
+                    viewport_details,
+                    is_private,
+                    None,
+                );
             },
             // Close a top level browsing context.
             EmbedderToConstellationMessage::CloseWebView(webview_id) => {
@@ -1368,7 +1492,7 @@ where
                 if webview_id.is_none() {
                     warn!("constellation got a SendError message without top level id");
                 }
-                self.handle_panic(webview_id, error, None);
+                self.handle_panic(webview_id, None, error, None);
             },
             EmbedderToConstellationMessage::FocusWebView(webview_id) => {
                 self.handle_focus_web_view(webview_id);
@@ -1395,8 +1519,17 @@ where
             EmbedderToConstellationMessage::ThemeChange(webview_id, theme) => {
                 self.handle_theme_change(webview_id, theme);
             },
-            EmbedderToConstellationMessage::TickAnimation(webview_ids) => {
-                self.handle_tick_animation(webview_ids)
+            EmbedderToConstellationMessage::SetUserAgentStylesheetOverride(
+                webview_id,
+                stylesheet_override,
+            ) => {
+                self.handle_set_user_agent_stylesheet_override(webview_id, stylesheet_override);
+            },
+            EmbedderToConstellationMessage::SetUserStylesheets(webview_id, user_stylesheets) => {
+                self.handle_set_user_stylesheets(webview_id, user_stylesheets);
+            },
+            EmbedderToConstellationMessage::TickAnimation(webview_ids, frame_clock) => {
+                self.handle_tick_animation(webview_ids, frame_clock)
             },
             EmbedderToConstellationMessage::WebDriverCommand(command) => {
                 self.handle_webdriver_msg(command);
@@ -1428,6 +1561,12 @@ where
             EmbedderToConstellationMessage::MediaSessionAction(action) => {
                 self.handle_media_session_action_msg(action);
             },
+            EmbedderToConstellationMessage::ExitPictureInPicture(webview_id) => {
+                self.handle_exit_picture_in_picture_msg(webview_id);
+            },
+            EmbedderToConstellationMessage::PictureInPictureWindowResized(webview_id, size) => {
+                self.handle_picture_in_picture_window_resized_msg(webview_id, size);
+            },
             EmbedderToConstellationMessage::SetWebViewThrottled(webview_id, throttled) => {
                 self.set_webview_throttled(webview_id, throttled);
             },
@@ -1437,6 +1576,12 @@ where
             EmbedderToConstellationMessage::PaintMetric(pipeline_id, paint_metric_event) => {
                 self.handle_paint_metric(pipeline_id, paint_metric_event);
             },
+            EmbedderToConstellationMessage::IntersectionObservationsChanged(
+                pipeline_id,
+                changes,
+            ) => {
+                self.handle_intersection_observations_changed(pipeline_id, changes);
+            },
             EmbedderToConstellationMessage::EvaluateJavaScript(
                 webview_id,
                 evaluation_id,
@@ -1444,6 +1589,9 @@ where
             ) => {
                 self.handle_evaluate_javascript(webview_id, evaluation_id, script);
             },
+            EmbedderToConstellationMessage::FindInPage(webview_id, request) => {
+                self.handle_find_in_page(webview_id, request);
+            },
             EmbedderToConstellationMessage::CreateMemoryReport(sender) => {
                 self.mem_profiler_chan.send(ProfilerMsg::Report(sender));
             },
@@ -1509,18 +1657,37 @@ where
         }
     }
 
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_find_in_page(&mut self, webview_id: WebViewId, request: Option<FindInPageRequest>) {
+        // TODO: Also find text in the documents of nested browsing contexts.
+        let browsing_context_id = BrowsingContextId::from(webview_id);
+        let Some(pipeline) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+        else {
+            return warn!("{webview_id}: Got find in page request for a closed WebView");
+        };
+
+        if let Err(error) = pipeline
+            .event_loop
+            .send(ScriptThreadMessage::FindInPage(pipeline.id, request))
+        {
+            warn!("{webview_id}: Could not send find in page request ({error:?})");
+        }
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn handle_request_from_script(&mut self, message: (PipelineId, ScriptToConstellationMessage)) {
         let (source_pipeline_id, content) = message;
         trace_script_msg!(content, "{source_pipeline_id}: {content:?}");
 
-        let webview_id = match self
-            .pipelines
-            .get(&source_pipeline_id)
-            .map(|pipeline| pipeline.webview_id)
-        {
+        let webview_id = match self.pipelines.get_mut(&source_pipeline_id) {
             None => return warn!("{}: ScriptMsg from closed pipeline", source_pipeline_id),
-            Some(ctx) => ctx,
+            Some(pipeline) => {
+                pipeline.last_message = Instant::now();
+                pipeline.webview_id
+            },
         };
 
         match content {
@@ -1670,6 +1837,11 @@ where
                     warn!("constellation got set final url message for dead pipeline");
                 }
             },
+            ScriptToConstellationMessage::SetCrashReport(crash_report) => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.crash_report = crash_report;
+                }
+            },
             ScriptToConstellationMessage::PostMessage {
                 target: browsing_context_id,
                 source: source_pipeline_id,
@@ -1695,6 +1867,21 @@ where
             ScriptToConstellationMessage::FocusRemoteDocument(focused_browsing_context_id) => {
                 self.handle_focus_remote_document_msg(focused_browsing_context_id);
             },
+            ScriptToConstellationMessage::MoveSequentialFocusIntoChild(
+                browsing_context_id,
+                direction,
+            ) => {
+                self.handle_move_sequential_focus_into_child_msg(browsing_context_id, direction);
+            },
+            ScriptToConstellationMessage::MoveSequentialFocusOutOfDocument(direction) => {
+                self.handle_move_sequential_focus_out_of_document_msg(
+                    source_pipeline_id,
+                    direction,
+                );
+            },
+            ScriptToConstellationMessage::SetFullscreenStateOfContainer(fullscreen) => {
+                self.handle_set_fullscreen_state_of_container_msg(source_pipeline_id, fullscreen);
+            },
             ScriptToConstellationMessage::SetThrottledComplete(throttled) => {
                 self.handle_set_throttled_complete(source_pipeline_id, throttled);
             },
@@ -1707,6 +1894,9 @@ where
             ScriptToConstellationMessage::CreateCanvasPaintThread(size, response_sender) => {
                 self.handle_create_canvas_paint_thread_msg(size, response_sender)
             },
+            ScriptToConstellationMessage::UpdatePlaceholderCanvas(placeholder, image_key, size) => {
+                self.handle_update_placeholder_canvas_msg(placeholder, image_key, size)
+            },
             ScriptToConstellationMessage::SetDocumentState(state) => {
                 self.document_states.insert(source_pipeline_id, state);
             },
@@ -1816,6 +2006,33 @@ where
                 self.embedder_proxy
                     .send(EmbedderMsg::MediaSessionEvent(webview_id, event));
             },
+            ScriptToConstellationMessage::RequestPictureInPicture(video_size, response_sender) => {
+                // There is only one picture-in-picture window, so a video of another
+                // document replaces the one that is shown.
+                if let Some(previous_pipeline_id) = self
+                    .picture_in_picture_pipeline
+                    .replace(source_pipeline_id)
+                    .filter(|pipeline_id| *pipeline_id != source_pipeline_id)
+                {
+                    self.send_picture_in_picture_message(
+                        previous_pipeline_id,
+                        ScriptThreadMessage::ExitPictureInPicture(previous_pipeline_id),
+                    );
+                }
+                self.embedder_proxy
+                    .send(EmbedderMsg::RequestPictureInPicture(
+                        webview_id,
+                        video_size,
+                        response_sender,
+                    ));
+            },
+            ScriptToConstellationMessage::ExitPictureInPicture => {
+                if self.picture_in_picture_pipeline == Some(source_pipeline_id) {
+                    self.picture_in_picture_pipeline = None;
+                    self.embedder_proxy
+                        .send(EmbedderMsg::ExitPictureInPicture(webview_id));
+                }
+            },
             #[cfg(feature = "webgpu")]
             ScriptToConstellationMessage::RequestAdapter(response_sender, options, ids) => self
                 .handle_wgpu_request(
@@ -1843,6 +2060,36 @@ where
                 self.mem_profiler_chan
                     .send(mem::ProfilerMsg::Report(sender));
             },
+            ScriptToConstellationMessage::CollectGarbageAndReportMemory(sender) => {
+                self.handle_collect_garbage_and_report_memory(sender)
+            },
+            ScriptToConstellationMessage::PreferencesChanged(values) => {
+                self.handle_preferences_changed(source_pipeline_id, values)
+            },
+            ScriptToConstellationMessage::GetPermissionDecision(
+                origin,
+                feature,
+                response_sender,
+            ) => {
+                let decision = self.permission_manager.get(&origin, feature);
+                let _ = response_sender.send(decision);
+            },
+            ScriptToConstellationMessage::SetPermissionDecision(
+                origin,
+                feature,
+                decision,
+                remember,
+            ) => self.handle_set_permission_decision(origin, feature, decision, remember),
+            ScriptToConstellationMessage::RequestStorageAccess(may_prompt, response_sender) => self
+                .handle_request_storage_access(
+                    webview_id,
+                    source_pipeline_id,
+                    may_prompt,
+                    response_sender,
+                ),
+            ScriptToConstellationMessage::ReportRuntimeServices(sender) => {
+                self.handle_report_runtime_services(sender)
+            },
             ScriptToConstellationMessage::FinishJavaScriptEvaluation(evaluation_id, result) => {
                 self.handle_finish_javascript_evaluation(evaluation_id, result)
             },
@@ -1884,7 +2131,7 @@ where
     /// Broadcast a message via routers in various event-loops.
     #[servo_tracing::instrument(skip_all)]
     fn handle_schedule_broadcast(
-        &self,
+        &mut self,
         pipeline_id: PipelineId,
         router_id: BroadcastChannelRouterId,
         message: BroadcastMsg,
@@ -1902,6 +2149,7 @@ where
                 Some(routers) => routers,
                 None => return warn!("Broadcast to channel name without active routers."),
             };
+            let mut dead_routers = vec![];
             for router in routers {
                 // Exclude the sender of the broadcast.
                 // Broadcasting locally is done at the point of sending.
@@ -1911,12 +2159,19 @@ where
 
                 if let Some(broadcast_ipc_sender) = self.broadcast_routers.get(router) {
                     if broadcast_ipc_sender.send(message.clone()).is_err() {
+                        // The event-loop of the router has gone away,
+                        // without removing the router first.
                         warn!("Failed to broadcast message to router: {:?}", router);
+                        dead_routers.push(*router);
                     }
                 } else {
                     warn!("No sender for broadcast router: {:?}", router);
+                    dead_routers.push(*router);
                 }
             }
+            for router in dead_routers {
+                self.remove_broadcast_router(router, &message.origin);
+            }
         } else {
             warn!(
                 "Attempt to schedule a broadcast for an origin without routers {:?}",
@@ -1997,8 +2252,28 @@ where
         {
             return warn!("Attempt to remove broadcast router from an unexpected origin.");
         }
-        if self.broadcast_routers.remove(&router_id).is_none() {
-            warn!("Attempt to remove unknown broadcast-channel router.");
+        if !self.broadcast_routers.contains_key(&router_id) {
+            return warn!("Attempt to remove unknown broadcast-channel router.");
+        }
+        self.remove_broadcast_router(router_id, &origin);
+    }
+
+    /// Remove a broadcast router, along with the channel-names noted for it,
+    /// so that broadcasts are no longer routed to it.
+    fn remove_broadcast_router(
+        &mut self,
+        router_id: BroadcastChannelRouterId,
+        origin: &ImmutableOrigin,
+    ) {
+        self.broadcast_routers.remove(&router_id);
+        if let Some(channels) = self.broadcast_channels.get_mut(origin) {
+            channels.retain(|_, routers| {
+                routers.retain(|router| router != &router_id);
+                !routers.is_empty()
+            });
+            if channels.is_empty() {
+                self.broadcast_channels.remove(origin);
+            }
         }
     }
 
@@ -2044,8 +2319,8 @@ where
             Some(pipeline) => pipeline,
             None => return warn!("{}: ScriptMsg from closed pipeline", source_pipeline_id),
         };
-        let host = match reg_host(&source_pipeline.url) {
-            Some(host) => host,
+        let site = match Site::for_url(&source_pipeline.url) {
+            Some(site) => site,
             None => return warn!("Invalid host url"),
         };
         let browsing_context_group = if let Some(bcg) = self
@@ -2056,7 +2331,7 @@ where
         } else {
             return warn!("Browsing context group not found");
         };
-        let webgpu_chan = match browsing_context_group.webgpus.entry(host) {
+        let webgpu_chan = match browsing_context_group.webgpus.entry(site) {
             Entry::Vacant(v) => start_webgpu_thread(
                 self.compositor_proxy.cross_process_compositor_api.clone(),
                 self.webrender_wgpu.webrender_external_images.clone(),
@@ -2770,6 +3045,15 @@ where
             return;
         };
 
+        // The resource threads keep the cookie partition of every pipeline that loaded a
+        // document.
+        for resource_threads in [
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ] {
+            let _ = resource_threads.send(CoreResourceMsg::PipelineExited(pipeline_id));
+        }
+
         // Now that the Script and Constellation parts of Servo no longer have a reference to
         // this pipeline, tell the compositor that it has shut down. This is delayed until the
         // last moment.
@@ -2789,13 +3073,36 @@ where
             .get(&pipeline_id)
             .map(|pipeline| pipeline.webview_id);
         let reason = format!("Send failed ({})", err);
-        self.handle_panic(webview_id, reason, None);
+        self.handle_panic(webview_id, Some(pipeline_id), reason, None);
+    }
+
+    /// Deliver the report of the crash of a pipeline to the reporting endpoint of its document.
+    ///
+    /// <https://wicg.github.io/crash-reporting/>
+    fn send_crash_report(
+        &self,
+        webview_id: WebViewId,
+        is_private: bool,
+        crash_report: &CrashReport,
+    ) {
+        let resource_threads = if is_private {
+            &self.private_resource_threads
+        } else {
+            &self.public_resource_threads
+        };
+        let request = crash_report.request(webview_id);
+        if let Err(error) =
+            resource_threads.send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch))
+        {
+            warn!("Failed to send crash report ({error:?})");
+        }
     }
 
     #[servo_tracing::instrument(skip_all)]
     fn handle_panic(
         &mut self,
         webview_id: Option<WebViewId>,
+        crashed_pipeline_id: Option<PipelineId>,
         reason: String,
         backtrace: Option<String>,
     ) {
@@ -2818,10 +3125,31 @@ where
 
         let browsing_context_id = BrowsingContextId::from(webview_id);
 
+        let crashed_pipeline = crashed_pipeline_id
+            .or_else(|| {
+                self.browsing_contexts
+                    .get(&browsing_context_id)
+                    .map(|browsing_context| browsing_context.pipeline_id)
+            })
+            .and_then(|pipeline_id| self.pipelines.get(&pipeline_id));
+        let url = crashed_pipeline.map(|pipeline| pipeline.url.clone());
+        if let Some(crash_report) =
+            crashed_pipeline.and_then(|pipeline| pipeline.crash_report.as_ref())
+        {
+            let is_private = self
+                .browsing_contexts
+                .get(&browsing_context_id)
+                .is_some_and(|browsing_context| browsing_context.is_private);
+            self.send_crash_report(webview_id, is_private, crash_report);
+        }
         self.embedder_proxy.send(EmbedderMsg::Panic(
             webview_id,
-            reason.clone(),
-            backtrace.clone(),
+            CrashDetails {
+                reason: reason.clone(),
+                backtrace: backtrace.clone(),
+                pipeline_id: crashed_pipeline_id,
+                url,
+            },
         ));
 
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
@@ -2909,7 +3237,7 @@ where
         entry: LogEntry,
     ) {
         if let LogEntry::Panic(ref reason, ref backtrace) = entry {
-            self.handle_panic(webview_id, reason.clone(), Some(backtrace.clone()));
+            self.handle_panic(webview_id, None, reason.clone(), Some(backtrace.clone()));
         }
 
         match entry {
@@ -3056,6 +3384,7 @@ where
         url: ServoUrl,
         webview_id: WebViewId,
         viewport_details: ViewportDetails,
+        is_private: bool,
         response_sender: Option<IpcSender<WebDriverLoadStatus>>,
     ) {
         let pipeline_id = PipelineId::new();
@@ -3071,7 +3400,6 @@ where
             false,
         );
         let sandbox = IFrameSandboxState::IFrameUnsandboxed;
-        let is_private = false;
         let throttled = false;
 
         // Register this new top-level browsing context id as a webview and set
@@ -3140,6 +3468,17 @@ where
                 browsing_context_id
             );
         };
+        // The private browsing partition only lives as long as its webviews: once the last
+        // private browsing context is closed, forget everything that was stored for it.
+        if browsing_context.is_private &&
+            !self
+                .browsing_contexts
+                .values()
+                .any(|browsing_context| browsing_context.is_private)
+        {
+            debug!("Discarding the private browsing partition");
+            self.private_resource_threads.discard_state();
+        }
         // https://html.spec.whatwg.org/multipage/#bcg-remove
         let bc_group_id = browsing_context.bc_group_id;
         let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) else {
@@ -3239,6 +3578,13 @@ where
             ..
         } = load_info.info;
 
+        if !self.may_script_load_url(parent_pipeline_id, &load_info.load_data.url) {
+            return warn!(
+                "{}: Tried to load internal page {} in iframe {}",
+                parent_pipeline_id, load_info.load_data.url, browsing_context_id,
+            );
+        }
+
         // If no url is specified, reload.
         let old_pipeline = load_info
             .old_pipeline_id
@@ -3529,7 +3875,7 @@ where
     }
 
     #[servo_tracing::instrument(skip_all)]
-    fn handle_tick_animation(&mut self, webview_ids: Vec<WebViewId>) {
+    fn handle_tick_animation(&mut self, webview_ids: Vec<WebViewId>, frame_clock: FrameClock) {
         let mut animating_event_loops = HashSet::new();
 
         for webview_id in webview_ids.iter() {
@@ -3546,7 +3892,324 @@ where
             // with a particular pipeline. In addition, the danger of not progressing animations is pretty
             // low, so it's probably safe to ignore this error and handle the crashed ScriptThread on
             // some other message.
-            let _ = event_loop.send(ScriptThreadMessage::TickAllAnimations(webview_ids.clone()));
+            let _ = event_loop.send(ScriptThreadMessage::TickAllAnimations(
+                webview_ids.clone(),
+                frame_clock,
+            ));
+        }
+    }
+
+    /// Ask every script thread to collect garbage and, once they all did so, ask the memory
+    /// profiler for a report, so that the report does not include memory that is only kept
+    /// alive until the next garbage collection.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_collect_garbage_and_report_memory(
+        &mut self,
+        sender: IpcSender<mem::MemoryReportResult>,
+    ) {
+        let event_loops: HashSet<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| pipeline.event_loop.clone())
+            .collect();
+
+        let Ok((gc_done_sender, gc_done_receiver)) = ipc::channel() else {
+            warn!("Could not create channel to wait for garbage collection");
+            return self
+                .mem_profiler_chan
+                .send(mem::ProfilerMsg::Report(sender));
+        };
+        let mut pending_event_loops = 0;
+        for event_loop in event_loops {
+            if event_loop
+                .send(ScriptThreadMessage::CollectGarbage(gc_done_sender.clone()))
+                .is_ok()
+            {
+                pending_event_loops += 1;
+            }
+        }
+        drop(gc_done_sender);
+
+        // Script threads may take a while to get to the message, so wait for them on a
+        // separate thread rather than blocking the constellation.
+        let mem_profiler_chan = self.mem_profiler_chan.clone();
+        let result = thread::Builder::new()
+            .name("GarbageCollectionWaiter".to_owned())
+            .spawn(move || {
+                for _ in 0..pending_event_loops {
+                    // An error means that the remaining script threads went away, in which
+                    // case there is nothing left to wait for.
+                    if gc_done_receiver.recv().is_err() {
+                        break;
+                    }
+                }
+                mem_profiler_chan.send(mem::ProfilerMsg::Report(sender));
+            });
+        if let Err(error) = result {
+            warn!("Could not spawn thread to wait for garbage collection: {error}");
+        }
+    }
+
+    /// Apply preferences that were changed at runtime to this process and pass them on to
+    /// all script threads, so that content processes pick them up without a restart. Content
+    /// processes that are started later get the new values along with all other preferences.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_preferences_changed(
+        &mut self,
+        source_pipeline_id: PipelineId,
+        values: Vec<(String, PrefValue)>,
+    ) {
+        // Only the privileged `servo:` pages can change preferences, so a content process
+        // that sends this message for any other page is not trusted with it.
+        let is_privileged = self
+            .pipelines
+            .get(&source_pipeline_id)
+            .is_some_and(|pipeline| pipeline.url.scheme() == "servo");
+        if !is_privileged {
+            return warn!(
+                "{source_pipeline_id}: Ignoring preference changes from an unprivileged page"
+            );
+        }
+
+        prefs::set_values(&values);
+
+        let event_loops: HashSet<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| pipeline.event_loop.clone())
+            .collect();
+        for event_loop in event_loops {
+            let _ = event_loop.send(ScriptThreadMessage::PreferencesChanged(values.clone()));
+        }
+    }
+
+    /// Record a decision of the user about a permission of an origin, or forget it, and tell
+    /// all script threads about the change so that `PermissionStatus` objects of the origin
+    /// can be updated.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_set_permission_decision(
+        &mut self,
+        origin: ImmutableOrigin,
+        feature: PermissionFeature,
+        decision: Option<AllowOrDeny>,
+        remember: bool,
+    ) {
+        if !self
+            .permission_manager
+            .set(&origin, feature, decision, remember)
+        {
+            return;
+        }
+
+        let event_loops: HashSet<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| pipeline.event_loop.clone())
+            .collect();
+        for event_loop in event_loops {
+            let _ = event_loop.send(ScriptThreadMessage::PermissionDecisionChanged(
+                origin.clone(),
+                feature,
+            ));
+        }
+    }
+
+    /// <https://privacycg.github.io/storage-access/#dom-document-requeststorageaccess>
+    ///
+    /// The sites that storage access is granted for are taken from the URLs of the pipelines
+    /// known here, and the user is asked through the embedder, so that a content process can
+    /// not grant itself access to the cookies of another site. Decisions are remembered for
+    /// the pair of the top-level site and the embedded site.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_request_storage_access(
+        &self,
+        webview_id: WebViewId,
+        source_pipeline_id: PipelineId,
+        may_prompt: bool,
+        response_sender: IpcSender<bool>,
+    ) {
+        let Some(pipeline) = self.pipelines.get(&source_pipeline_id) else {
+            let _ = response_sender.send(false);
+            return;
+        };
+        let embedded_origin = pipeline.url.origin();
+        let is_private = self
+            .browsing_contexts
+            .get(&pipeline.browsing_context_id)
+            .is_some_and(|browsing_context| browsing_context.is_private);
+        let Some(top_level_origin) = self
+            .browsing_contexts
+            .get(&BrowsingContextId::from(webview_id))
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+            .map(|pipeline| pipeline.url.origin())
+        else {
+            let _ = response_sender.send(false);
+            return;
+        };
+        let (Some(top_level_site), Some(embedded_site)) = (
+            Site::for_origin(top_level_origin.clone()),
+            Site::for_origin(embedded_origin.clone()),
+        ) else {
+            let _ = response_sender.send(false);
+            return;
+        };
+        if top_level_site == embedded_site {
+            let _ = response_sender.send(true);
+            return;
+        }
+
+        let resource_threads = if is_private {
+            self.private_resource_threads.clone()
+        } else {
+            self.public_resource_threads.clone()
+        };
+        let grant = {
+            let embedded_origin = embedded_origin.clone();
+            move |granted: bool| {
+                if granted {
+                    let _ = resource_threads.send(CoreResourceMsg::GrantStorageAccess(
+                        top_level_origin,
+                        embedded_origin,
+                    ));
+                }
+                let _ = response_sender.send(granted);
+            }
+        };
+
+        match self
+            .permission_manager
+            .get_storage_access(&top_level_site, &embedded_site)
+        {
+            Some(decision) => return grant(decision == AllowOrDeny::Allow),
+            // Prompting the user requires transient activation.
+            None if !may_prompt => return grant(false),
+            None => {},
+        }
+
+        let Ok((decision_sender, decision_receiver)) = ipc::channel() else {
+            return grant(false);
+        };
+        self.embedder_proxy.send(EmbedderMsg::PromptPermission(
+            webview_id,
+            embedded_origin.clone(),
+            PermissionFeature::StorageAccess,
+            decision_sender,
+        ));
+
+        // Wait for the user on the router thread rather than blocking the constellation, and
+        // send the decision back to be recorded for this pair of sites.
+        let storage_access_decision_sender = self.storage_access_decision_sender.clone();
+        let mut grant = Some(grant);
+        ROUTER.add_typed_route(
+            decision_receiver,
+            Box::new(move |decision: Result<PermissionDecision, _>| {
+                let Some(grant) = grant.take() else {
+                    return;
+                };
+                let Ok(decision) = decision else {
+                    return grant(false);
+                };
+                // Nothing about a private browsing session may outlive it.
+                let allowed = decision.allow_or_deny == AllowOrDeny::Allow;
+                let _ = storage_access_decision_sender.send(StorageAccessDecision {
+                    top_level_site: top_level_site.clone(),
+                    embedded_site: embedded_site.clone(),
+                    decision: PermissionDecision {
+                        remember: decision.remember && !is_private,
+                        ..decision
+                    },
+                });
+                grant(allowed);
+            }),
+        );
+    }
+
+    /// Report on the state of the pipelines, event loops and resource threads, for the
+    /// servo:services page. Script threads and resource threads are asked about their state
+    /// and those that do not answer in time are reported as not responding.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_report_runtime_services(&mut self, sender: IpcSender<RuntimeServicesReport>) {
+        let pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| PipelineReport {
+                id: pipeline.id,
+                webview_id: pipeline.webview_id,
+                event_loop_id: pipeline.event_loop.id(),
+                url: pipeline.url.clone(),
+                title: pipeline.title.clone(),
+                completely_loaded: pipeline.completely_loaded,
+                time_since_last_message: pipeline.last_message.elapsed(),
+            })
+            .collect();
+
+        let sites: HashMap<usize, String> = self
+            .browsing_context_group_set
+            .values()
+            .flat_map(|group| group.event_loops.iter())
+            .filter_map(|(site, event_loop)| Some((event_loop.upgrade()?.id(), site.to_string())))
+            .collect();
+        let event_loops: HashSet<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| pipeline.event_loop.clone())
+            .collect();
+        let mut pending_event_loops = Vec::with_capacity(event_loops.len());
+        for event_loop in event_loops {
+            let receiver = ipc::channel().ok().and_then(|(activity_sender, receiver)| {
+                event_loop
+                    .send(ScriptThreadMessage::ReportActivity(activity_sender))
+                    .ok()
+                    .map(|_| receiver)
+            });
+            let site = sites.get(&event_loop.id()).cloned();
+            pending_event_loops.push((event_loop.id(), site, receiver));
+        }
+
+        let fetch_count_receivers: Vec<_> = [
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ]
+        .into_iter()
+        .filter_map(|resource_threads| {
+            let (count_sender, receiver) = ipc::channel().ok()?;
+            resource_threads
+                .send(CoreResourceMsg::GetActiveFetchCount(count_sender))
+                .ok()?;
+            Some(receiver)
+        })
+        .collect();
+
+        // Wait for the answers on a separate thread, as a hung script thread is exactly what
+        // the report is meant to help find.
+        let result = thread::Builder::new()
+            .name("RuntimeServicesReporter".to_owned())
+            .spawn(move || {
+                let deadline = Instant::now() + RUNTIME_SERVICES_REPORT_TIMEOUT;
+                let time_left = || deadline.saturating_duration_since(Instant::now());
+
+                let event_loops = pending_event_loops
+                    .into_iter()
+                    .map(|(id, site, receiver)| EventLoopReport {
+                        id,
+                        site,
+                        activity: receiver
+                            .and_then(|receiver| receiver.try_recv_timeout(time_left()).ok()),
+                    })
+                    .collect();
+                let active_fetches = fetch_count_receivers
+                    .into_iter()
+                    .map(|receiver| receiver.try_recv_timeout(time_left()).ok())
+                    .sum();
+
+                let _ = sender.send(RuntimeServicesReport {
+                    pipelines,
+                    event_loops,
+                    active_fetches,
+                });
+            });
+        if let Err(error) = result {
+            warn!("Could not spawn thread to report on runtime services: {error}");
         }
     }
 
@@ -3561,6 +4224,12 @@ where
         load_data: LoadData,
         history_handling: NavigationHistoryBehavior,
     ) {
+        if !self.may_script_load_url(source_id, &load_data.url) {
+            return warn!(
+                "{}: Tried to navigate to internal page {}",
+                source_id, load_data.url
+            );
+        }
         match self.pending_approval_navigations.entry(source_id) {
             Entry::Occupied(_) => {
                 return warn!(
@@ -3581,6 +4250,16 @@ where
             ));
     }
 
+    /// Whether the document of a pipeline may load `url`. The `servo:` internal pages can
+    /// only be loaded by the user or by other internal pages, so that web content can neither
+    /// navigate to them nor frame them.
+    fn may_script_load_url(&self, pipeline_id: PipelineId, url: &ServoUrl) -> bool {
+        url.scheme() != "servo" ||
+            self.pipelines
+                .get(&pipeline_id)
+                .is_some_and(|pipeline| pipeline.url.scheme() == "servo")
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn load_url(
         &mut self,
@@ -4303,6 +4982,77 @@ where
         self.focus_browsing_context(None, focused_browsing_context_id);
     }
 
+    /// Continue a sequential focus navigation that reached the container of
+    /// `browsing_context_id` at the start or the end of its active document, depending on
+    /// `direction`.
+    fn handle_move_sequential_focus_into_child_msg(
+        &mut self,
+        browsing_context_id: BrowsingContextId,
+        direction: SequentialFocusDirection,
+    ) {
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => return warn!("Browsing context {} not found", browsing_context_id),
+        };
+        self.send_move_sequential_focus(pipeline_id, direction, None);
+    }
+
+    /// Continue a sequential focus navigation that ran past the start or the end of the
+    /// document of `pipeline_id` in the document containing it, after or before its
+    /// container depending on `direction`.
+    fn handle_move_sequential_focus_out_of_document_msg(
+        &mut self,
+        pipeline_id: PipelineId,
+        direction: SequentialFocusDirection,
+    ) {
+        let browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.browsing_context_id,
+            None => return warn!("{}: Sequential focus navigation after closure", pipeline_id),
+        };
+        let Some(parent_pipeline_id) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| browsing_context.parent_pipeline_id)
+        else {
+            return warn!(
+                "{}: Sequential focus navigation out of a top-level document",
+                pipeline_id
+            );
+        };
+        self.send_move_sequential_focus(parent_pipeline_id, direction, Some(browsing_context_id));
+    }
+
+    fn send_move_sequential_focus(
+        &mut self,
+        pipeline_id: PipelineId,
+        direction: SequentialFocusDirection,
+        starting_child: Option<BrowsingContextId>,
+    ) {
+        // Ignore if the document isn't fully active.
+        if self.get_activity(pipeline_id) != DocumentActivity::FullyActive {
+            debug!(
+                "Ignoring the sequential focus navigation because pipeline {} is not \
+                fully active",
+                pipeline_id
+            );
+            return;
+        }
+
+        let msg = ScriptThreadMessage::MoveSequentialFocus(pipeline_id, direction, starting_child);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return warn!(
+                    "{}: Sequential focus navigation in closed pipeline",
+                    pipeline_id
+                );
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     /// Perform [the focusing steps][1] for the active document of
     /// `focused_browsing_context_id`.
     ///
@@ -4541,6 +5291,39 @@ where
         }
     }
 
+    /// Forward a frame of an OffscreenCanvas to the script thread of its placeholder
+    /// canvas element, which may not be the one of the OffscreenCanvas.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_update_placeholder_canvas_msg(
+        &mut self,
+        placeholder: OffscreenCanvasPlaceholder,
+        image_key: Option<ImageKey>,
+        size: UntypedSize2D<u32>,
+    ) {
+        let pipeline_id = placeholder.pipeline_id;
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => {
+                return debug!(
+                    "{}: Got placeholder canvas frame after closure",
+                    pipeline_id
+                );
+            },
+            Some(pipeline) => {
+                pipeline
+                    .event_loop
+                    .send(ScriptThreadMessage::UpdatePlaceholderCanvas(
+                        pipeline_id,
+                        placeholder.canvas_id,
+                        image_key,
+                        size,
+                    ))
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn handle_webdriver_msg(&mut self, msg: WebDriverCommandMsg) {
         // Find the script channel for the given parent pipeline,
@@ -4615,6 +5398,13 @@ where
                     response_sender,
                 ));
             },
+            WebDriverCommandMsg::TakeMaskedScreenshot(webview_id, masks, response_sender) => {
+                self.compositor_proxy.send(CompositorMsg::CreateMaskedPng(
+                    webview_id,
+                    masks,
+                    response_sender,
+                ));
+            },
             _ => {
                 warn!("Unhandled WebDriver command: {:?}", msg);
             },
@@ -4634,6 +5424,110 @@ where
             None => warn!("{pipeline_id}: Tried to SetWebViewThrottled after closure"),
             Some(pipeline) => pipeline.set_throttled(throttled),
         }
+
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return;
+        };
+        if webview.background.is_some() == throttled {
+            return;
+        }
+        let old_level = webview
+            .background
+            .as_ref()
+            .map(|background| background.level);
+        webview.background = throttled.then(Background::new);
+        let new_level = webview
+            .background
+            .as_ref()
+            .map(|background| background.level);
+        self.send_background_level_change(webview_id, old_level, new_level);
+        self.update_process_priorities();
+    }
+
+    /// When the next hidden `WebView` moves further into the background, if any.
+    fn next_background_level_change(&self) -> Option<Instant> {
+        self.webviews
+            .iter()
+            .filter_map(|(_, webview)| webview.background.as_ref()?.next_level())
+            .map(|(_, deadline)| deadline)
+            .min()
+    }
+
+    /// Move the hidden `WebView`s whose time has come further into the background.
+    fn advance_background_levels(&mut self) {
+        let now = Instant::now();
+        let changes: Vec<_> = self
+            .webviews
+            .iter()
+            .filter_map(|(webview_id, webview)| {
+                let background = webview.background.as_ref()?;
+                let (new_level, deadline) = background.next_level()?;
+                (deadline <= now).then_some((*webview_id, background.level, new_level))
+            })
+            .collect();
+        for (webview_id, old_level, new_level) in changes {
+            if let Some(background) = self
+                .webviews
+                .get_mut(webview_id)
+                .and_then(|webview| webview.background.as_mut())
+            {
+                background.level = new_level;
+            }
+            self.send_background_level_change(webview_id, Some(old_level), Some(new_level));
+        }
+    }
+
+    /// Tell the fully active pipelines of a `WebView` that it moved further into the
+    /// background, or out of it when `new_level` is `None` because it was shown again.
+    /// Throttling of the top-level pipeline is handled by [`Self::set_webview_throttled`].
+    fn send_background_level_change(
+        &self,
+        webview_id: WebViewId,
+        old_level: Option<BackgroundLevel>,
+        new_level: Option<BackgroundLevel>,
+    ) {
+        let intensively_throttled =
+            |level: Option<BackgroundLevel>| level >= Some(BackgroundLevel::IntensivelyThrottled);
+        let frozen = |level: Option<BackgroundLevel>| level == Some(BackgroundLevel::Frozen);
+        for browsing_context in self.fully_active_browsing_contexts_iter(webview_id) {
+            let Some(pipeline) = self.pipelines.get(&browsing_context.pipeline_id) else {
+                continue;
+            };
+            if intensively_throttled(old_level) != intensively_throttled(new_level) {
+                let _ =
+                    pipeline
+                        .event_loop
+                        .send(ScriptThreadMessage::SetTimersIntensivelyThrottled(
+                            pipeline.id,
+                            intensively_throttled(new_level),
+                        ));
+            }
+            if frozen(old_level) != frozen(new_level) {
+                let _ = pipeline.event_loop.send(ScriptThreadMessage::SetFrozen(
+                    pipeline.id,
+                    frozen(new_level),
+                ));
+            }
+        }
+    }
+
+    /// Lower the priority of the content processes that only run pipelines of hidden
+    /// `WebView`s, and restore that of the others.
+    fn update_process_priorities(&self) {
+        let mut process_backgrounded: HashMap<u32, bool> = HashMap::new();
+        for pipeline in self.pipelines.values() {
+            let Some(process_id) = pipeline.event_loop.process_id() else {
+                continue;
+            };
+            let hidden = self
+                .webviews
+                .get(pipeline.webview_id)
+                .is_some_and(|webview| webview.background.is_some());
+            *process_backgrounded.entry(process_id).or_insert(true) &= hidden;
+        }
+        for (process_id, backgrounded) in process_backgrounded {
+            set_process_backgrounded(process_id, backgrounded);
+        }
     }
 
     #[servo_tracing::instrument(skip_all)]
@@ -4948,7 +5842,8 @@ where
             .map(|(_, webview)| webview.focused_browsing_context_id);
         focused_browsing_context_id.is_some_and(|focus_ctx_id| {
             focus_ctx_id == browsing_context_id ||
-                self.fully_active_descendant_browsing_contexts_iter(browsing_context_id)
+                self
+                    .fully_active_descendant_browsing_contexts_iter(browsing_context_id)
                     .any(|nested_ctx| nested_ctx.id == focus_ctx_id)
         })
     }
@@ -5079,11 +5974,60 @@ where
         }
     }
 
-    /// Called when the window exits from fullscreen mode
+    /// Called when the window exits from fullscreen mode. Every document of the `WebView`
+    /// exits fullscreen, as the fullscreen element of a nested document makes the
+    /// containers of its browsing context fullscreen too.
     #[servo_tracing::instrument(skip_all)]
     fn handle_exit_fullscreen_msg(&mut self, webview_id: WebViewId) {
-        let browsing_context_id = BrowsingContextId::from(webview_id);
-        self.switch_fullscreen_mode(browsing_context_id);
+        let browsing_context_ids: Vec<_> = self
+            .fully_active_browsing_contexts_iter(webview_id)
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            self.switch_fullscreen_mode(browsing_context_id);
+        }
+    }
+
+    /// Make the container of the browsing context of `pipeline_id` the fullscreen element of
+    /// its document, or stop it being one, as the document of `pipeline_id` entered or
+    /// exited fullscreen.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_set_fullscreen_state_of_container_msg(
+        &mut self,
+        pipeline_id: PipelineId,
+        fullscreen: bool,
+    ) {
+        let browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.browsing_context_id,
+            None => return warn!("{}: Fullscreen state changed after closure", pipeline_id),
+        };
+        let Some(parent_pipeline_id) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| browsing_context.parent_pipeline_id)
+        else {
+            return warn!(
+                "{}: Fullscreen state of the container of a top-level document",
+                pipeline_id
+            );
+        };
+        let msg = ScriptThreadMessage::SetFullscreenStateOfChild(
+            parent_pipeline_id,
+            browsing_context_id,
+            fullscreen,
+        );
+        let result = match self.pipelines.get(&parent_pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return warn!(
+                    "{}: Fullscreen state changed after closure",
+                    parent_pipeline_id
+                );
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(parent_pipeline_id, e);
+        }
     }
 
     /// Checks the state of all script and layout pipelines to see if they are idle
@@ -5307,6 +6251,77 @@ where
         }
     }
 
+    /// Handle a new user agent stylesheet override from the embedder and forward it to all
+    /// `ScriptThread`s of the `WebView`, so that their documents are restyled right away.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_set_user_agent_stylesheet_override(
+        &mut self,
+        webview_id: WebViewId,
+        stylesheet_override: Option<UserAgentStylesheetOverride>,
+    ) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            warn!("Received user agent stylesheet for uknown WebViewId: {webview_id:?}");
+            return;
+        };
+        if !webview.set_user_agent_stylesheet_override(stylesheet_override.clone()) {
+            return;
+        }
+
+        for pipeline in self.pipelines.values() {
+            if pipeline.webview_id != webview_id {
+                continue;
+            }
+            if let Err(error) =
+                pipeline
+                    .event_loop
+                    .send(ScriptThreadMessage::SetUserAgentStylesheetOverride(
+                        pipeline.id,
+                        stylesheet_override.clone(),
+                    ))
+            {
+                warn!(
+                    "{}: Failed to send user agent stylesheet to pipeline ({error:?}).",
+                    pipeline.id,
+                );
+            }
+        }
+    }
+
+    /// Handle new user stylesheets from the embedder and forward them to all
+    /// `ScriptThread`s of the `WebView`, so that their documents are restyled right away.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_set_user_stylesheets(
+        &mut self,
+        webview_id: WebViewId,
+        user_stylesheets: Vec<String>,
+    ) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            warn!("Received user stylesheets for uknown WebViewId: {webview_id:?}");
+            return;
+        };
+        if !webview.set_user_stylesheets(user_stylesheets.clone()) {
+            return;
+        }
+
+        for pipeline in self.pipelines.values() {
+            if pipeline.webview_id != webview_id {
+                continue;
+            }
+            if let Err(error) = pipeline
+                .event_loop
+                .send(ScriptThreadMessage::SetUserStylesheets(
+                    pipeline.id,
+                    user_stylesheets.clone(),
+                ))
+            {
+                warn!(
+                    "{}: Failed to send user stylesheets to pipeline ({error:?}).",
+                    pipeline.id,
+                );
+            }
+        }
+    }
+
     // Handle switching from fullscreen mode
     #[servo_tracing::instrument(skip_all)]
     fn switch_fullscreen_mode(&mut self, browsing_context_id: BrowsingContextId) {
@@ -5513,6 +6528,13 @@ where
             self.pending_changes.remove(pending_index);
         }
 
+        // Close the picture-in-picture window of a video of this pipeline.
+        if self.picture_in_picture_pipeline == Some(pipeline_id) {
+            self.picture_in_picture_pipeline = None;
+            self.embedder_proxy
+                .send(EmbedderMsg::ExitPictureInPicture(pipeline.webview_id));
+        }
+
         // Inform script, compositor that this pipeline has exited.
         pipeline.send_exit_message_to_script(dbc);
 
@@ -5634,6 +6656,63 @@ where
         }
     }
 
+    /// Called when the user closed the picture-in-picture window of the given `WebView`.
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_exit_picture_in_picture_msg(&mut self, webview_id: WebViewId) {
+        let Some(pipeline_id) = self.picture_in_picture_pipeline_of(webview_id) else {
+            return warn!("{webview_id}: Exited picture-in-picture without a video in it");
+        };
+        self.picture_in_picture_pipeline = None;
+        self.send_picture_in_picture_message(
+            pipeline_id,
+            ScriptThreadMessage::ExitPictureInPicture(pipeline_id),
+        );
+    }
+
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_picture_in_picture_window_resized_msg(
+        &mut self,
+        webview_id: WebViewId,
+        size: DeviceIntSize,
+    ) {
+        let Some(pipeline_id) = self.picture_in_picture_pipeline_of(webview_id) else {
+            return;
+        };
+        self.send_picture_in_picture_message(
+            pipeline_id,
+            ScriptThreadMessage::PictureInPictureWindowResized(pipeline_id, size),
+        );
+    }
+
+    /// The pipeline whose video is shown in the picture-in-picture window, if it belongs
+    /// to the given `WebView`.
+    fn picture_in_picture_pipeline_of(&self, webview_id: WebViewId) -> Option<PipelineId> {
+        self.picture_in_picture_pipeline.filter(|pipeline_id| {
+            self.pipelines
+                .get(pipeline_id)
+                .is_some_and(|pipeline| pipeline.webview_id == webview_id)
+        })
+    }
+
+    fn send_picture_in_picture_message(
+        &mut self,
+        pipeline_id: PipelineId,
+        msg: ScriptThreadMessage,
+    ) {
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => {
+                return warn!(
+                    "{}: Got picture-in-picture message after closure",
+                    pipeline_id
+                );
+            },
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn handle_set_scroll_states(
         &self,
@@ -5655,6 +6734,30 @@ where
         }
     }
 
+    #[servo_tracing::instrument(skip_all)]
+    fn handle_intersection_observations_changed(
+        &self,
+        pipeline_id: PipelineId,
+        changes: Vec<IntersectionObservationChange>,
+    ) {
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            warn!("Discarding intersection observation changes for unknown pipeline");
+            return;
+        };
+        if let Err(error) =
+            pipeline
+                .event_loop
+                .send(ScriptThreadMessage::IntersectionObservationsChanged(
+                    pipeline_id,
+                    changes,
+                ))
+        {
+            warn!(
+                "Could not send intersection observation changes to pipeline: {pipeline_id:?}: {error:?}"
+            );
+        }
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn handle_paint_metric(&mut self, pipeline_id: PipelineId, event: PaintMetricEvent) {
         let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
@@ -5672,6 +6775,11 @@ where
                 metric_value,
                 first_reflow,
             ),
+            PaintMetricEvent::LargestContentfulPaint(metric_value) => (
+                ProgressiveWebMetricType::LargestContentfulPaint,
+                metric_value,
+                false,
+            ),
         };
         if let Err(error) = pipeline.event_loop.send(ScriptThreadMessage::PaintMetric(
             pipeline_id,
@@ -3,8 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use base::id::BrowsingContextId;
-use embedder_traits::Theme;
+use embedder_traits::{Theme, UserAgentStylesheetOverride};
 
+use crate::background::Background;
 use crate::session_history::JointSessionHistory;
 
 /// The `Constellation`'s view of a `WebView` in the embedding layer. This tracks all of the
@@ -21,6 +22,18 @@ pub(crate) struct ConstellationWebView {
     /// The [`Theme`] that this [`ConstellationWebView`] uses. This is communicated to all
     /// `ScriptThread`s so that they know how to render the contents of a particular `WebView.
     theme: Theme,
+
+    /// The stylesheet that the embedder applies at the user agent origin of the documents
+    /// of this [`ConstellationWebView`], if any.
+    user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+
+    /// The stylesheets that the embedder applies at the user origin of the documents of
+    /// this [`ConstellationWebView`].
+    user_stylesheets: Vec<String>,
+
+    /// How far this [`ConstellationWebView`] has been moved into the background, if it is
+    /// hidden.
+    pub background: Option<Background>,
 }
 
 impl ConstellationWebView {
@@ -29,6 +42,9 @@ impl ConstellationWebView {
             focused_browsing_context_id,
             session_history: JointSessionHistory::new(),
             theme: Theme::Light,
+            user_agent_stylesheet_override: None,
+            user_stylesheets: Vec::new(),
+            background: None,
         }
     }
 
@@ -42,4 +58,32 @@ impl ConstellationWebView {
     pub(crate) fn theme(&self) -> Theme {
         self.theme
     }
+
+    /// Set the user agent stylesheet override of this [`ConstellationWebView`], returning
+    /// true if it changed.
+    pub(crate) fn set_user_agent_stylesheet_override(
+        &mut self,
+        new_override: Option<UserAgentStylesheetOverride>,
+    ) -> bool {
+        let old_override =
+            std::mem::replace(&mut self.user_agent_stylesheet_override, new_override);
+        old_override != self.user_agent_stylesheet_override
+    }
+
+    /// Get the user agent stylesheet override of this [`ConstellationWebView`].
+    pub(crate) fn user_agent_stylesheet_override(&self) -> Option<&UserAgentStylesheetOverride> {
+        self.user_agent_stylesheet_override.as_ref()
+    }
+
+    /// Set the user stylesheets of this [`ConstellationWebView`], returning true if they
+    /// changed.
+    pub(crate) fn set_user_stylesheets(&mut self, new_stylesheets: Vec<String>) -> bool {
+        let old_stylesheets = std::mem::replace(&mut self.user_stylesheets, new_stylesheets);
+        old_stylesheets != self.user_stylesheets
+    }
+
+    /// Get the user stylesheets of this [`ConstellationWebView`].
+    pub(crate) fn user_stylesheets(&self) -> &[String] {
+        &self.user_stylesheets
+    }
 }
@@ -22,6 +22,7 @@ pub struct EventLoop {
     script_chan: IpcSender<ScriptThreadMessage>,
     dont_send_or_sync: PhantomData<Rc<()>>,
     id: usize,
+    process_id: Option<u32>,
 }
 
 impl PartialEq for EventLoop {
@@ -45,16 +46,31 @@ impl Drop for EventLoop {
 }
 
 impl EventLoop {
-    /// Create a new event loop from the channel to its script thread.
-    pub fn new(script_chan: IpcSender<ScriptThreadMessage>) -> Rc<EventLoop> {
+    /// Create a new event loop from the channel to its script thread, and the id of the
+    /// content process that it runs in, if it does not run in this process.
+    pub fn new(
+        script_chan: IpcSender<ScriptThreadMessage>,
+        process_id: Option<u32>,
+    ) -> Rc<EventLoop> {
         let id = CURRENT_EVENT_LOOP_ID.fetch_add(1, Ordering::Relaxed);
         Rc::new(EventLoop {
             script_chan,
             dont_send_or_sync: PhantomData,
             id,
+            process_id,
         })
     }
 
+    /// An identifier for this event loop that is unique within this process.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// The id of the content process that this event loop runs in, if any.
+    pub fn process_id(&self) -> Option<u32> {
+        self.process_id
+    }
+
     /// Send a message to the event loop.
     pub fn send(&self, msg: ScriptThreadMessage) -> Result<(), Error> {
         self.script_chan.send(msg)
@@ -7,11 +7,13 @@
 #[macro_use]
 mod tracing;
 
+mod background;
 mod browsingcontext;
 mod constellation;
 mod constellation_webview;
 mod event_loop;
 mod logging;
+mod permission_manager;
 mod pipeline;
 mod process_manager;
 mod sandboxing;
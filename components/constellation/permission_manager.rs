@@ -0,0 +1,218 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The permission store, <https://w3c.github.io/permissions/#permission-store>, shared by
+//! all script threads.
+//!
+//! Decisions are keyed by origin and permission. A decision that the user asked to remember
+//! is written to `permissions.json` in the profile directory, if there is one, and applies
+//! to later sessions too. Other decisions only last until Servo exits.
+//!
+//! Decisions about storage access are kept apart, since they are about a pair of sites
+//! rather than a single origin: letting a site use its unpartitioned cookies and storage
+//! when it is embedded in one top-level site says nothing about other top-level sites.
+
+use std::collections::HashMap;
+
+use embedder_traits::{AllowOrDeny, PermissionFeature};
+use net::resource_thread::{read_json_from_file, write_json_to_file};
+use net_traits::pub_domains::Site;
+use serde::{Deserialize, Serialize};
+use servo_config::opts;
+use servo_url::ImmutableOrigin;
+
+const PERMISSIONS_FILE_NAME: &str = "permissions.json";
+const STORAGE_ACCESS_FILE_NAME: &str = "storage-access.json";
+
+/// The decisions of the user for the permissions of each origin, keyed by the ASCII
+/// serialization of the origin.
+#[derive(Default, Deserialize, Serialize)]
+struct PermissionDecisions(HashMap<String, HashMap<PermissionFeature, AllowOrDeny>>);
+
+impl PermissionDecisions {
+    fn get(&self, origin: &str, feature: PermissionFeature) -> Option<AllowOrDeny> {
+        self.0.get(origin)?.get(&feature).copied()
+    }
+
+    fn set(&mut self, origin: &str, feature: PermissionFeature, decision: AllowOrDeny) {
+        self.0
+            .entry(origin.to_owned())
+            .or_default()
+            .insert(feature, decision);
+    }
+
+    /// Remove the decision, returning whether there was one.
+    fn remove(&mut self, origin: &str, feature: PermissionFeature) -> bool {
+        let Some(decisions) = self.0.get_mut(origin) else {
+            return false;
+        };
+        let removed = decisions.remove(&feature).is_some();
+        if decisions.is_empty() {
+            self.0.remove(origin);
+        }
+        removed
+    }
+}
+
+/// The decisions of the user about letting embedded sites use their unpartitioned cookies and
+/// storage, keyed by the serialization of the top-level site and then of the embedded site.
+#[derive(Default, Deserialize, Serialize)]
+struct StorageAccessDecisions(HashMap<String, HashMap<String, AllowOrDeny>>);
+
+impl StorageAccessDecisions {
+    fn get(&self, top_level_site: &str, embedded_site: &str) -> Option<AllowOrDeny> {
+        self.0.get(top_level_site)?.get(embedded_site).copied()
+    }
+
+    fn set(&mut self, top_level_site: &str, embedded_site: &str, decision: AllowOrDeny) {
+        self.0
+            .entry(top_level_site.to_owned())
+            .or_default()
+            .insert(embedded_site.to_owned(), decision);
+    }
+
+    fn remove(&mut self, top_level_site: &str, embedded_site: &str) {
+        let Some(decisions) = self.0.get_mut(top_level_site) else {
+            return;
+        };
+        decisions.remove(embedded_site);
+        if decisions.is_empty() {
+            self.0.remove(top_level_site);
+        }
+    }
+}
+
+pub(crate) struct PermissionManager {
+    /// Decisions that the user asked to remember, which are persisted in the profile.
+    remembered: PermissionDecisions,
+    /// Decisions that only apply to this session.
+    session: PermissionDecisions,
+    /// Storage access decisions that the user asked to remember, which are persisted in the
+    /// profile.
+    remembered_storage_access: StorageAccessDecisions,
+    /// Storage access decisions that only apply to this session.
+    session_storage_access: StorageAccessDecisions,
+}
+
+impl PermissionManager {
+    pub(crate) fn new() -> Self {
+        let mut remembered = PermissionDecisions::default();
+        let mut remembered_storage_access = StorageAccessDecisions::default();
+        if let Some(config_dir) = &opts::get().config_dir {
+            if config_dir.join(PERMISSIONS_FILE_NAME).exists() {
+                read_json_from_file(&mut remembered, config_dir, PERMISSIONS_FILE_NAME);
+            }
+            if config_dir.join(STORAGE_ACCESS_FILE_NAME).exists() {
+                read_json_from_file(
+                    &mut remembered_storage_access,
+                    config_dir,
+                    STORAGE_ACCESS_FILE_NAME,
+                );
+            }
+        }
+        Self {
+            remembered,
+            session: PermissionDecisions::default(),
+            remembered_storage_access,
+            session_storage_access: StorageAccessDecisions::default(),
+        }
+    }
+
+    /// The decision of the user for the given permission of the given origin, if any. Opaque
+    /// origins never have one, since no other document can share their decisions.
+    pub(crate) fn get(
+        &self,
+        origin: &ImmutableOrigin,
+        feature: PermissionFeature,
+    ) -> Option<AllowOrDeny> {
+        if !origin.is_tuple() {
+            return None;
+        }
+        let origin = origin.ascii_serialization();
+        self.session
+            .get(&origin, feature)
+            .or_else(|| self.remembered.get(&origin, feature))
+    }
+
+    /// Record the decision of the user for the given permission of the given origin, or
+    /// forget it if the decision is `None`. Returns whether anything changed.
+    pub(crate) fn set(
+        &mut self,
+        origin: &ImmutableOrigin,
+        feature: PermissionFeature,
+        decision: Option<AllowOrDeny>,
+        remember: bool,
+    ) -> bool {
+        if !origin.is_tuple() {
+            return false;
+        }
+        let previous_decision = self.get(origin, feature);
+        let origin = origin.ascii_serialization();
+
+        let removed_remembered_decision = self.remembered.remove(&origin, feature);
+        self.session.remove(&origin, feature);
+        match decision {
+            Some(decision) if remember => self.remembered.set(&origin, feature, decision),
+            Some(decision) => self.session.set(&origin, feature, decision),
+            None => {},
+        }
+        if remember || removed_remembered_decision {
+            self.save();
+        }
+
+        previous_decision != decision
+    }
+
+    /// The decision of the user about letting `embedded_site` use its unpartitioned cookies
+    /// and storage when it is embedded in `top_level_site`, if any.
+    pub(crate) fn get_storage_access(
+        &self,
+        top_level_site: &Site,
+        embedded_site: &Site,
+    ) -> Option<AllowOrDeny> {
+        let (top_level_site, embedded_site) =
+            (top_level_site.to_string(), embedded_site.to_string());
+        self.session_storage_access
+            .get(&top_level_site, &embedded_site)
+            .or_else(|| {
+                self.remembered_storage_access
+                    .get(&top_level_site, &embedded_site)
+            })
+    }
+
+    /// Record the decision of the user about letting `embedded_site` use its unpartitioned
+    /// cookies and storage when it is embedded in `top_level_site`.
+    pub(crate) fn set_storage_access(
+        &mut self,
+        top_level_site: &Site,
+        embedded_site: &Site,
+        decision: AllowOrDeny,
+        remember: bool,
+    ) {
+        let (top_level_site, embedded_site) =
+            (top_level_site.to_string(), embedded_site.to_string());
+        if !remember {
+            self.session_storage_access
+                .set(&top_level_site, &embedded_site, decision);
+            return;
+        }
+        self.session_storage_access
+            .remove(&top_level_site, &embedded_site);
+        self.remembered_storage_access
+            .set(&top_level_site, &embedded_site, decision);
+        if let Some(config_dir) = &opts::get().config_dir {
+            write_json_to_file(
+                &self.remembered_storage_access,
+                config_dir,
+                STORAGE_ACCESS_FILE_NAME,
+            );
+        }
+    }
+
+    fn save(&self) {
+        if let Some(config_dir) = &opts::get().config_dir {
+            write_json_to_file(&self.remembered, config_dir, PERMISSIONS_FILE_NAME);
+        }
+    }
+}
@@ -5,6 +5,7 @@
 use std::collections::HashSet;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Instant;
 
 use background_hang_monitor::HangMonitorRegister;
 use background_hang_monitor_api::{
@@ -21,11 +22,13 @@ use canvas_traits::webgl::WebGLPipeline;
 use compositing_traits::{
     CompositionPipeline, CompositorMsg, CompositorProxy, CrossProcessCompositorApi,
 };
-use constellation_traits::{LoadData, SWManagerMsg, ScriptToConstellationChan};
+use constellation_traits::{CrashReport, LoadData, SWManagerMsg, ScriptToConstellationChan};
 use crossbeam_channel::{Sender, unbounded};
 use devtools_traits::{DevtoolsControlMsg, ScriptToDevtoolsControlMsg};
 use embedder_traits::user_content_manager::UserContentManager;
-use embedder_traits::{AnimationState, FocusSequenceNumber, Theme, ViewportDetails};
+use embedder_traits::{
+    AnimationState, FocusSequenceNumber, Theme, UserAgentStylesheetOverride, ViewportDetails,
+};
 use fonts::{SystemFontServiceProxy, SystemFontServiceProxySender};
 use ipc_channel::Error;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -104,6 +107,13 @@ pub struct Pipeline {
     pub layout_epoch: Epoch,
 
     pub focus_sequence: FocusSequenceNumber,
+
+    /// The last time the constellation received a message from this pipeline.
+    pub last_message: Instant,
+
+    /// The report to deliver if this pipeline crashes, if its document has an endpoint for
+    /// crash reports.
+    pub crash_report: Option<CrashReport>,
 }
 
 /// Initial setup data needed to construct a pipeline.
@@ -173,6 +183,14 @@ pub struct InitialPipelineState {
     /// The initial [`Theme`] to use when starting this new [`Pipeline`].
     pub theme: Theme,
 
+    /// The stylesheet that the embedder applies at the user agent origin of the documents
+    /// of the `WebView` of this new [`Pipeline`], if any.
+    pub user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+
+    /// The stylesheets that the embedder applies at the user origin of the documents of
+    /// the `WebView` of this new [`Pipeline`].
+    pub user_stylesheets: Vec<String>,
+
     /// The ID of the pipeline namespace for this script thread.
     pub pipeline_namespace_id: PipelineNamespaceId,
 
@@ -228,6 +246,8 @@ impl Pipeline {
                     load_data: state.load_data.clone(),
                     viewport_details: state.viewport_details,
                     theme: state.theme,
+                    user_agent_stylesheet_override: state.user_agent_stylesheet_override,
+                    user_stylesheets: state.user_stylesheets,
                 };
 
                 if let Err(e) = script_chan.send(ScriptThreadMessage::AttachLayout(new_layout_info))
@@ -285,6 +305,8 @@ impl Pipeline {
                     mem_profiler_chan: state.mem_profiler_chan,
                     viewport_details: state.viewport_details,
                     theme: state.theme,
+                    user_agent_stylesheet_override: state.user_agent_stylesheet_override,
+                    user_stylesheets: state.user_stylesheets,
                     script_chan: script_chan.clone(),
                     load_data: state.load_data.clone(),
                     script_port,
@@ -328,7 +350,11 @@ impl Pipeline {
                     (None, None)
                 };
 
-                (EventLoop::new(script_chan), multiprocess_data)
+                let process_id = multiprocess_data
+                    .1
+                    .as_ref()
+                    .map(|(_, process)| process.pid());
+                (EventLoop::new(script_chan, process_id), multiprocess_data)
             },
         };
 
@@ -378,6 +404,8 @@ impl Pipeline {
             title: String::new(),
             layout_epoch: Epoch(0),
             focus_sequence: FocusSequenceNumber::default(),
+            last_message: Instant::now(),
+            crash_report: None,
         };
 
         pipeline.set_throttled(throttled);
@@ -479,6 +507,8 @@ pub struct UnprivilegedPipelineContent {
     mem_profiler_chan: profile_mem::ProfilerChan,
     viewport_details: ViewportDetails,
     theme: Theme,
+    user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+    user_stylesheets: Vec<String>,
     script_chan: IpcSender<ScriptThreadMessage>,
     load_data: LoadData,
     script_port: IpcReceiver<ScriptThreadMessage>,
@@ -530,6 +560,8 @@ impl UnprivilegedPipelineContent {
                 devtools_server_sender: self.devtools_ipc_sender,
                 viewport_details: self.viewport_details,
                 theme: self.theme,
+                user_agent_stylesheet_override: self.user_agent_stylesheet_override,
+                user_stylesheets: self.user_stylesheets,
                 pipeline_namespace_id: self.pipeline_namespace_id,
                 content_process_shutdown_sender: content_process_shutdown_chan,
                 webgl_chan: self.webgl_chan,
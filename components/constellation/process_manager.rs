@@ -14,7 +14,7 @@ pub enum Process {
 }
 
 impl Process {
-    fn pid(&self) -> u32 {
+    pub(crate) fn pid(&self) -> u32 {
         match self {
             Self::Unsandboxed(child) => child.id(),
             Self::Sandboxed(pid) => *pid,
@@ -34,6 +34,32 @@ impl Process {
     }
 }
 
+/// The priority of content processes that only run hidden `WebView`s, as a nice value.
+#[cfg(unix)]
+const BACKGROUND_PROCESS_PRIORITY: libc::c_int = 10;
+
+/// Lower the OS scheduling priority of a content process that only runs hidden `WebView`s,
+/// or restore it when one of them is shown again. Restoring the priority is not allowed
+/// everywhere, in which case the process stays at the lower priority.
+#[cfg(unix)]
+#[allow(unsafe_code)]
+pub(crate) fn set_process_backgrounded(pid: u32, backgrounded: bool) {
+    let priority = if backgrounded {
+        BACKGROUND_PROCESS_PRIORITY
+    } else {
+        0
+    };
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS as _, pid as libc::id_t, priority) } != 0 {
+        debug!(
+            "Could not set the priority of process pid={pid} to {priority} ({}).",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn set_process_backgrounded(_pid: u32, _backgrounded: bool) {}
+
 type ProcessReceiver = Receiver<Result<(), ipc_channel::Error>>;
 
 pub(crate) struct ProcessManager {
@@ -91,7 +91,8 @@ pub fn content_process_sandbox_profile() -> Profile {
     Profile::new(operations).expect("Failed to create sandbox profile!")
 }
 
-/// Our content process sandbox profile on Linux. As restrictive as possible.
+/// Our content process sandbox profile on Linux. As restrictive as possible: system fonts
+/// are not accessible, as their data is read by the system font service instead.
 #[cfg(all(
     not(target_os = "macos"),
     not(target_os = "windows"),
@@ -54,9 +54,17 @@ mod from_compositor {
                 Self::AllowNavigationResponse(..) => target!("AllowNavigationResponse"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::ClearCache => target!("ClearCache"),
+                Self::ClearSiteData(..) => target!("ClearSiteData"),
+                Self::SetContentBlockingFilterLists(..) => {
+                    target!("SetContentBlockingFilterLists")
+                },
                 Self::TraverseHistory(..) => target!("TraverseHistory"),
                 Self::ChangeViewportDetails(..) => target!("ChangeViewportDetails"),
                 Self::ThemeChange(..) => target!("ThemeChange"),
+                Self::SetUserAgentStylesheetOverride(..) => {
+                    target!("SetUserAgentStylesheetOverride")
+                },
+                Self::SetUserStylesheets(..) => target!("SetUserStylesheets"),
                 Self::TickAnimation(..) => target!("TickAnimation"),
                 Self::WebDriverCommand(..) => target!("WebDriverCommand"),
                 Self::Reload(..) => target!("Reload"),
@@ -71,10 +79,18 @@ mod from_compositor {
                 Self::ToggleProfiler(..) => target!("EnableProfiler"),
                 Self::ExitFullScreen(_) => target!("ExitFullScreen"),
                 Self::MediaSessionAction(_) => target!("MediaSessionAction"),
+                Self::ExitPictureInPicture(_) => target!("ExitPictureInPicture"),
+                Self::PictureInPictureWindowResized(..) => {
+                    target!("PictureInPictureWindowResized")
+                },
                 Self::SetWebViewThrottled(_, _) => target!("SetWebViewThrottled"),
                 Self::SetScrollStates(..) => target!("SetScrollStates"),
                 Self::PaintMetric(..) => target!("PaintMetric"),
+                Self::IntersectionObservationsChanged(..) => {
+                    target!("IntersectionObservationsChanged")
+                },
                 Self::EvaluateJavaScript(..) => target!("EvaluateJavaScript"),
+                Self::FindInPage(..) => target!("FindInPage"),
                 Self::CreateMemoryReport(..) => target!("CreateMemoryReport"),
                 Self::SendImageKeysForPipeline(..) => target!("SendImageKeysForPipeline"),
                 Self::SetWebDriverResponseSender(..) => target!("SetWebDriverResponseSender"),
@@ -91,6 +107,7 @@ mod from_compositor {
             }
             match self {
                 InputEvent::EditingAction(..) => target_variant!("EditingAction"),
+                InputEvent::ExternalDrag(..) => target_variant!("ExternalDrag"),
                 InputEvent::Gamepad(..) => target_variant!("Gamepad"),
                 InputEvent::Ime(..) => target_variant!("Ime"),
                 InputEvent::Keyboard(..) => target_variant!("Keyboard"),
@@ -141,8 +158,16 @@ mod from_script {
                 Self::BroadcastStorageEvent(..) => target!("BroadcastStorageEvent"),
                 Self::ChangeRunningAnimationsState(..) => target!("ChangeRunningAnimationsState"),
                 Self::CreateCanvasPaintThread(..) => target!("CreateCanvasPaintThread"),
+                Self::UpdatePlaceholderCanvas(..) => target!("UpdatePlaceholderCanvas"),
                 Self::Focus(..) => target!("Focus"),
                 Self::FocusRemoteDocument(..) => target!("FocusRemoteDocument"),
+                Self::MoveSequentialFocusIntoChild(..) => target!("MoveSequentialFocusIntoChild"),
+                Self::MoveSequentialFocusOutOfDocument(..) => {
+                    target!("MoveSequentialFocusOutOfDocument")
+                },
+                Self::SetFullscreenStateOfContainer(..) => {
+                    target!("SetFullscreenStateOfContainer")
+                },
                 Self::GetTopForBrowsingContext(..) => target!("GetTopForBrowsingContext"),
                 Self::GetBrowsingContextInfo(..) => target!("GetBrowsingContextInfo"),
                 Self::GetChildBrowsingContextId(..) => target!("GetChildBrowsingContextId"),
@@ -164,6 +189,7 @@ mod from_script {
                 Self::SetDocumentState(..) => target!("SetDocumentState"),
                 Self::SetLayoutEpoch(..) => target!("SetLayoutEpoch"),
                 Self::SetFinalUrl(..) => target!("SetFinalUrl"),
+                Self::SetCrashReport(..) => target!("SetCrashReport"),
                 Self::TouchEventProcessed(..) => target!("TouchEventProcessed"),
                 Self::LogEntry(..) => target!("LogEntry"),
                 Self::DiscardDocument => target!("DiscardDocument"),
@@ -172,6 +198,8 @@ mod from_script {
                 Self::ForwardDOMMessage(..) => target!("ForwardDOMMessage"),
                 Self::ScheduleJob(..) => target!("ScheduleJob"),
                 Self::MediaSessionEvent(..) => target!("MediaSessionEvent"),
+                Self::RequestPictureInPicture(..) => target!("RequestPictureInPicture"),
+                Self::ExitPictureInPicture => target!("ExitPictureInPicture"),
                 #[cfg(feature = "webgpu")]
                 Self::RequestAdapter(..) => target!("RequestAdapter"),
                 #[cfg(feature = "webgpu")]
@@ -179,6 +207,14 @@ mod from_script {
                 Self::TitleChanged(..) => target!("TitleChanged"),
                 Self::IFrameSizes(..) => target!("IFrameSizes"),
                 Self::ReportMemory(..) => target!("ReportMemory"),
+                Self::CollectGarbageAndReportMemory(..) => {
+                    target!("CollectGarbageAndReportMemory")
+                },
+                Self::PreferencesChanged(..) => target!("PreferencesChanged"),
+                Self::GetPermissionDecision(..) => target!("GetPermissionDecision"),
+                Self::SetPermissionDecision(..) => target!("SetPermissionDecision"),
+                Self::RequestStorageAccess(..) => target!("RequestStorageAccess"),
+                Self::ReportRuntimeServices(..) => target!("ReportRuntimeServices"),
                 Self::WebDriverInputComplete(..) => target!("WebDriverInputComplete"),
                 Self::FinishJavaScriptEvaluation(..) => target!("FinishJavaScriptEvaluation"),
             }
@@ -198,6 +234,7 @@ mod from_script {
                 Self::MoveTo(..) => target_variant!("MoveTo"),
                 Self::ResizeTo(..) => target_variant!("ResizeTo"),
                 Self::ShowSimpleDialog(..) => target_variant!("ShowSimpleDialog"),
+                Self::ContentBlocked(..) => target_variant!("ContentBlocked"),
                 Self::RequestAuthentication(..) => target_variant!("RequestAuthentication"),
                 Self::ShowContextMenu(..) => target_variant!("ShowContextMenu"),
                 Self::AllowNavigationRequest(..) => target_variant!("AllowNavigationRequest"),
@@ -210,11 +247,18 @@ mod from_script {
                 Self::Keyboard(..) => target_variant!("Keyboard"),
                 Self::ClearClipboard(..) => target_variant!("ClearClipboard"),
                 Self::GetClipboardText(..) => target_variant!("GetClipboardText"),
+                Self::GetClipboardHtml(..) => target_variant!("GetClipboardHtml"),
+                Self::GetClipboardImage(..) => target_variant!("GetClipboardImage"),
                 Self::SetClipboardText(..) => target_variant!("SetClipboardText"),
+                Self::SetClipboardContents(..) => target_variant!("SetClipboardContents"),
+                Self::CheckSpelling(..) => target_variant!("CheckSpelling"),
+                Self::GetSpellingSuggestions(..) => target_variant!("GetSpellingSuggestions"),
                 Self::SetCursor(..) => target_variant!("SetCursor"),
                 Self::NewFavicon(..) => target_variant!("NewFavicon"),
+                Self::FaviconLoaded(..) => target_variant!("FaviconLoaded"),
                 Self::HistoryChanged(..) => target_variant!("HistoryChanged"),
                 Self::GetWindowRect(..) => target_variant!("GetWindowRect"),
+                Self::GetErrorPage(..) => target_variant!("GetErrorPage"),
                 Self::GetScreenMetrics(..) => target_variant!("GetScreenMetrics"),
                 Self::NotifyFullscreenStateChanged(..) => {
                     target_variant!("NotifyFullscreenStateChanged")
@@ -225,17 +269,27 @@ mod from_script {
                 Self::NotifyLoadStatusChanged(_, LoadStatus::HeadParsed) => {
                     target_variant!("NotifyLoadStatusChanged(LoadStatus::HeadParsed)")
                 },
+                Self::NotifyLoadStatusChanged(_, LoadStatus::DomContentLoaded) => {
+                    target_variant!("NotifyLoadStatusChanged(LoadStatus::DomContentLoaded)")
+                },
                 Self::NotifyLoadStatusChanged(_, LoadStatus::Complete) => {
                     target_variant!("NotifyLoadStatusChanged(LoadStatus::Complete")
                 },
+                Self::NotifyLoadProgressChanged(..) => target_variant!("NotifyLoadProgressChanged"),
+                Self::NotifyPaintMetricsChanged(..) => target_variant!("NotifyPaintMetricsChanged"),
                 Self::Panic(..) => target_variant!("Panic"),
-                Self::GetSelectedBluetoothDevice(..) => {
-                    target_variant!("GetSelectedBluetoothDevice")
+                Self::SelectBluetoothDevice(..) => {
+                    target_variant!("SelectBluetoothDevice")
                 },
                 Self::SelectFiles(..) => target_variant!("SelectFiles"),
+                Self::SelectFileToSave(..) => target_variant!("SelectFileToSave"),
+                Self::SelectDirectory(..) => target_variant!("SelectDirectory"),
+                Self::GetDroppedFiles(..) => target_variant!("GetDroppedFiles"),
+                Self::StartDrag(..) => target_variant!("StartDrag"),
                 Self::PromptPermission(..) => target_variant!("PromptPermission"),
                 Self::ShowIME(..) => target_variant!("ShowIME"),
                 Self::HideIME(..) => target_variant!("HideIME"),
+                Self::UpdateIMECursorArea(..) => target_variant!("UpdateIMECursorArea"),
                 Self::ReportProfile(..) => target_variant!("ReportProfile"),
                 Self::MediaSessionEvent(..) => target_variant!("MediaSessionEvent"),
                 Self::OnDevtoolsStarted(..) => target_variant!("OnDevtoolsStarted"),
@@ -244,7 +298,23 @@ mod from_script {
                 Self::StopGamepadHapticEffect(..) => target_variant!("StopGamepadHapticEffect"),
                 Self::ShutdownComplete => target_variant!("ShutdownComplete"),
                 Self::ShowNotification(..) => target_variant!("ShowNotification"),
+                Self::SubscribeToPush(..) => target_variant!("SubscribeToPush"),
+                Self::GetPushSubscription(..) => target_variant!("GetPushSubscription"),
+                Self::UnsubscribeFromPush(..) => target_variant!("UnsubscribeFromPush"),
+                Self::CreatePublicKeyCredential(..) => {
+                    target_variant!("CreatePublicKeyCredential")
+                },
+                Self::GetPublicKeyCredential(..) => target_variant!("GetPublicKeyCredential"),
                 Self::ShowFormControl(..) => target_variant!("ShowFormControl"),
+                Self::ShowValidationMessage(..) => target_variant!("ShowValidationMessage"),
+                Self::RequestPictureInPicture(..) => target_variant!("RequestPictureInPicture"),
+                Self::ExitPictureInPicture(..) => target_variant!("ExitPictureInPicture"),
+                Self::RequestGeolocationPosition(..) => {
+                    target_variant!("RequestGeolocationPosition")
+                },
+                Self::WatchGeolocationPosition(..) => target_variant!("WatchGeolocationPosition"),
+                Self::ClearGeolocationWatch(..) => target_variant!("ClearGeolocationWatch"),
+                Self::FindInPageResult(..) => target_variant!("FindInPageResult"),
                 Self::FinishJavaScriptEvaluation(..) => {
                     target_variant!("FinishJavaScriptEvaluation")
                 },
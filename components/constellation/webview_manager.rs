@@ -50,6 +50,10 @@ impl<WebView> WebViewManager<WebView> {
         self.webviews.get_mut(&webview_id)
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&WebViewId, &WebView)> {
+        self.webviews.iter()
+    }
+
     pub fn focused_webview(&self) -> Option<(WebViewId, &WebView)> {
         if !self.is_focused {
             return None;
@@ -12,7 +12,7 @@ use std::net::TcpStream;
 
 use base::id::PipelineId;
 use devtools_traits::DevtoolScriptControlMsg::{
-    self, GetCssDatabase, SimulateColorScheme, WantsLiveNotifications,
+    self, GetCssDatabase, SimulateColorScheme, StartCoverage, TakeCoverage, WantsLiveNotifications,
 };
 use devtools_traits::{DevtoolsPageInfo, NavigationState};
 use embedder_traits::Theme;
@@ -40,6 +40,12 @@ struct ListWorkersReply {
     workers: Vec<()>,
 }
 
+#[derive(Serialize)]
+struct TakeCoverageReply {
+    from: String,
+    coverage: Value,
+}
+
 #[derive(Serialize)]
 struct FrameUpdateReply {
     from: String,
@@ -185,6 +191,30 @@ impl Actor for BrowsingContextActor {
                     workers: vec![],
                 })?
             },
+            // These are Servo extensions used by tooling that trims unused scripts and
+            // stylesheets, see `script::coverage`.
+            "startCoverage" => {
+                self.script_chan
+                    .send(StartCoverage(self.active_pipeline_id.get()))
+                    .map_err(|_| ActorError::Internal)?;
+                let msg = EmptyReplyMsg { from: self.name() };
+                request.reply_final(&msg)?
+            },
+            "takeCoverage" => {
+                let (tx, rx) = ipc::channel().map_err(|_| ActorError::Internal)?;
+                self.script_chan
+                    .send(TakeCoverage(self.active_pipeline_id.get(), tx))
+                    .map_err(|_| ActorError::Internal)?;
+                let coverage = rx
+                    .recv()
+                    .map_err(|_| ActorError::Internal)?
+                    .and_then(|report| serde_json::from_str(&report).ok())
+                    .ok_or(ActorError::Internal)?;
+                request.reply_final(&TakeCoverageReply {
+                    from: self.name(),
+                    coverage,
+                })?
+            },
             _ => return Err(ActorError::UnrecognizedPacketType),
         };
         Ok(())
@@ -71,8 +71,13 @@ pub trait PlatformFontMethods: Sized {
         let font_identifier = template.identifier.clone();
 
         match font_identifier {
-            FontIdentifier::Local(font_identifier) => {
-                Self::new_from_local_font_identifier(font_identifier, pt_size)
+            // The data of local fonts is only provided when this process cannot read their
+            // files, see `FontContext::get_font_data`.
+            FontIdentifier::Local(font_identifier) => match data {
+                Some(data) => {
+                    Self::new_from_data(FontIdentifier::Local(font_identifier), data, pt_size)
+                },
+                None => Self::new_from_local_font_identifier(font_identifier, pt_size),
             },
             FontIdentifier::Web(_) => Self::new_from_data(
                 font_identifier,
@@ -352,6 +357,8 @@ bitflags! {
         const RTL_FLAG = 1 << 4;
         /// Set if word-break is set to keep-all.
         const KEEP_ALL_FLAG = 1 << 5;
+        /// Text is set upright in a vertical writing mode, so glyphs advance downward.
+        const VERTICAL_FLAG = 1 << 6;
     }
 }
 
@@ -401,6 +408,7 @@ impl Font {
                 .contains(ShapingFlags::ENDS_WITH_WHITESPACE_SHAPING_FLAG),
             is_single_preserved_newline,
             options.flags.contains(ShapingFlags::RTL_FLAG),
+            options.flags.contains(ShapingFlags::VERTICAL_FLAG),
         );
 
         if self.can_do_fast_shaping(text, options) {
@@ -437,7 +445,9 @@ impl Font {
     /// Note: This will eventually be removed.
     pub fn can_do_fast_shaping(&self, text: &str, options: &ShapingOptions) -> bool {
         options.script == Script::Latin &&
-            !options.flags.contains(ShapingFlags::RTL_FLAG) &&
+            !options
+                .flags
+                .intersects(ShapingFlags::RTL_FLAG | ShapingFlags::VERTICAL_FLAG) &&
             *self.can_do_fast_shaping.get_or_init(|| {
                 self.table_for_tag(KERN).is_some() &&
                     self.table_for_tag(GPOS).is_none() &&
@@ -77,7 +77,8 @@ pub struct FontContext {
     webrender_font_instance_keys: RwLock<HashMap<(FontKey, Au), FontInstanceKey>>,
 
     /// The data for each web font [`FontIdentifier`]. This data might be used by more than one
-    /// [`FontTemplate`] as each identifier refers to a URL.
+    /// [`FontTemplate`] as each identifier refers to a URL. In sandboxed content processes,
+    /// this also holds the data of the local fonts that have been used.
     font_data: RwLock<HashMap<FontIdentifier, FontData>>,
 
     have_removed_web_fonts: AtomicBool,
@@ -131,6 +132,20 @@ impl FontContext {
     fn get_font_data(&self, identifier: &FontIdentifier) -> Option<FontData> {
         match identifier {
             FontIdentifier::Web(_) => self.font_data.read().get(identifier).cloned(),
+            // Sandboxed content processes cannot read system font files, so the system font
+            // service reads them on their behalf.
+            FontIdentifier::Local(local_identifier) if local_font_files_are_inaccessible() => {
+                if let Some(data) = self.font_data.read().get(identifier).cloned() {
+                    return Some(data);
+                }
+                let data = self
+                    .system_font_service_proxy
+                    .get_local_font_data(local_identifier)?;
+                self.font_data
+                    .write()
+                    .insert(identifier.clone(), data.clone());
+                Some(data)
+            },
             FontIdentifier::Local(_) => None,
         }
     }
@@ -898,6 +913,7 @@ impl RemoteWebFontDownloader {
     ) -> DownloaderResponseResult {
         match response_message {
             FetchResponseMsg::ProcessRequestBody(..) |
+            FetchResponseMsg::ProcessRequestBodyChunkLength(..) |
             FetchResponseMsg::ProcessRequestEOF(..) |
             FetchResponseMsg::ProcessCspViolations(..) => DownloaderResponseResult::InProcess,
             FetchResponseMsg::ProcessResponse(_, meta_result) => {
@@ -962,3 +978,10 @@ impl Hash for FontGroupCacheKey {
         self.style.hash.hash(hasher)
     }
 }
+
+/// Whether the files of local fonts cannot be read by this process, because it is a sandboxed
+/// content process.
+fn local_font_files_are_inaccessible() -> bool {
+    let opts = servo_config::opts::get();
+    opts.multiprocess && opts.sandbox
+}
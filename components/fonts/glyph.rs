@@ -457,6 +457,11 @@ pub struct GlyphStore {
     is_single_preserved_newline: bool,
 
     is_rtl: bool,
+
+    /// Whether or not the glyphs in this store were shaped for upright vertical text. In
+    /// that case advances are along the vertical axis and glyph offsets are relative to
+    /// the vertical origin of each glyph, which is centered horizontally on the pen.
+    is_vertical: bool,
 }
 
 impl GlyphStore {
@@ -469,6 +474,7 @@ impl GlyphStore {
         ends_with_whitespace: bool,
         is_single_preserved_newline: bool,
         is_rtl: bool,
+        is_vertical: bool,
     ) -> GlyphStore {
         assert!(length > 0);
 
@@ -482,6 +488,7 @@ impl GlyphStore {
             ends_with_whitespace,
             is_single_preserved_newline,
             is_rtl,
+            is_vertical,
         }
     }
 
@@ -495,6 +502,16 @@ impl GlyphStore {
         ByteIndex(self.entry_buffer.len() as isize)
     }
 
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.is_rtl
+    }
+
+    #[inline]
+    pub fn is_vertical(&self) -> bool {
+        self.is_vertical
+    }
+
     #[inline]
     pub fn is_whitespace(&self) -> bool {
         self.is_whitespace
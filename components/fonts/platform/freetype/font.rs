@@ -93,19 +93,23 @@ impl Drop for PlatformFont {
 
 impl PlatformFontMethods for PlatformFont {
     fn new_from_data(
-        _font_identifier: FontIdentifier,
+        font_identifier: FontIdentifier,
         font_data: &FontData,
         requested_size: Option<Au>,
     ) -> Result<PlatformFont, &'static str> {
         let library = FreeTypeLibraryHandle::get().lock();
         let data: &[u8] = font_data.as_ref();
+        let face_index = match font_identifier {
+            FontIdentifier::Local(local_identifier) => local_identifier.index(),
+            FontIdentifier::Web(_) => 0,
+        };
         let mut face: FT_Face = ptr::null_mut();
         let result = unsafe {
             FT_New_Memory_Face(
                 library.freetype_library,
                 data.as_ptr(),
                 data.len() as FT_Long,
-                0, /* face_index */
+                face_index as FT_Long,
                 &mut face,
             )
         };
@@ -123,7 +127,7 @@ impl PlatformFontMethods for PlatformFont {
             face: ReentrantMutex::new(face),
             requested_face_size,
             actual_face_size,
-            table_provider_data: FreeTypeFaceTableProviderData::Web(font_data.clone()),
+            table_provider_data: FreeTypeFaceTableProviderData::Data(font_data.clone(), face_index),
         })
     }
 
@@ -540,14 +544,16 @@ unsafe extern "C" {
 }
 
 enum FreeTypeFaceTableProviderData {
-    Web(FontData),
+    /// The data of a web font, or of a local font that was read by the system font service
+    /// because this process cannot read font files.
+    Data(FontData, u32),
     Local(Mmap, u32),
 }
 
 impl FreeTypeFaceTableProviderData {
     fn font_ref(&self) -> Result<FontRef<'_>, ReadError> {
         match self {
-            Self::Web(ipc_shared_memory) => FontRef::new(&ipc_shared_memory.0),
+            Self::Data(font_data, index) => FontRef::from_index(&font_data.0, *index),
             Self::Local(mmap, index) => FontRef::from_index(mmap, *index),
         }
     }
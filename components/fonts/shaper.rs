@@ -14,16 +14,17 @@ use euclid::default::Point2D;
 // Eventually we would like the shaper to be pluggable, as many operating systems have their own
 // shapers. For now, however, HarfBuzz is a hard dependency.
 use harfbuzz_sys::{
-    HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_MEMORY_MODE_READONLY, HB_OT_LAYOUT_BASELINE_TAG_HANGING,
-    HB_OT_LAYOUT_BASELINE_TAG_IDEO_EMBOX_BOTTOM_OR_LEFT, HB_OT_LAYOUT_BASELINE_TAG_ROMAN,
-    hb_blob_create, hb_blob_t, hb_bool_t, hb_buffer_add_utf8, hb_buffer_create, hb_buffer_destroy,
-    hb_buffer_get_glyph_infos, hb_buffer_get_glyph_positions, hb_buffer_get_length,
-    hb_buffer_set_direction, hb_buffer_set_script, hb_buffer_t, hb_codepoint_t,
-    hb_face_create_for_tables, hb_face_destroy, hb_face_t, hb_feature_t, hb_font_create,
-    hb_font_destroy, hb_font_funcs_create, hb_font_funcs_set_glyph_h_advance_func,
-    hb_font_funcs_set_nominal_glyph_func, hb_font_funcs_t, hb_font_set_funcs, hb_font_set_ppem,
-    hb_font_set_scale, hb_font_t, hb_glyph_info_t, hb_glyph_position_t, hb_ot_layout_get_baseline,
-    hb_position_t, hb_shape, hb_tag_t,
+    HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_DIRECTION_TTB, HB_MEMORY_MODE_READONLY,
+    HB_OT_LAYOUT_BASELINE_TAG_HANGING, HB_OT_LAYOUT_BASELINE_TAG_IDEO_EMBOX_BOTTOM_OR_LEFT,
+    HB_OT_LAYOUT_BASELINE_TAG_ROMAN, hb_blob_create, hb_blob_t, hb_bool_t, hb_buffer_add_utf8,
+    hb_buffer_create, hb_buffer_destroy, hb_buffer_get_direction, hb_buffer_get_glyph_infos,
+    hb_buffer_get_glyph_positions, hb_buffer_get_length, hb_buffer_set_direction,
+    hb_buffer_set_script, hb_buffer_t, hb_codepoint_t, hb_face_create_for_tables, hb_face_destroy,
+    hb_face_t, hb_feature_t, hb_font_create, hb_font_destroy, hb_font_funcs_create,
+    hb_font_funcs_set_glyph_h_advance_func, hb_font_funcs_set_glyph_v_advance_func,
+    hb_font_funcs_set_glyph_v_origin_func, hb_font_funcs_set_nominal_glyph_func, hb_font_funcs_t,
+    hb_font_set_funcs, hb_font_set_ppem, hb_font_set_scale, hb_font_t, hb_glyph_info_t,
+    hb_glyph_position_t, hb_ot_layout_get_baseline, hb_position_t, hb_shape, hb_tag_t,
 };
 use log::debug;
 use num_traits::Zero;
@@ -43,6 +44,9 @@ pub struct ShapedGlyphData {
     count: usize,
     glyph_infos: *mut hb_glyph_info_t,
     pos_infos: *mut hb_glyph_position_t,
+    /// Whether the buffer was shaped top-to-bottom, for upright text in vertical writing
+    /// modes.
+    is_vertical: bool,
 }
 
 pub struct ShapedGlyphEntry {
@@ -65,11 +69,13 @@ impl ShapedGlyphData {
         let pos_infos = unsafe { hb_buffer_get_glyph_positions(buffer, &mut pos_count) };
         assert!(!pos_infos.is_null());
         assert_eq!(glyph_count, pos_count);
+        let is_vertical = unsafe { hb_buffer_get_direction(buffer) } == HB_DIRECTION_TTB;
 
         ShapedGlyphData {
             count: glyph_count as usize,
             glyph_infos,
             pos_infos,
+            is_vertical,
         }
     }
 
@@ -108,6 +114,18 @@ impl ShapedGlyphData {
             let x_advance = Au::from_f64_px(x_advance);
             let y_advance = Au::from_f64_px(y_advance);
 
+            // In vertical text HarfBuzz uses a y-up coordinate space, so advances are
+            // negative, and offsets are relative to the vertical origin of the glyph.
+            // Offsets are thus always present, as they position the glyph relative to
+            // its horizontal origin, which is where it is drawn from.
+            if self.is_vertical {
+                return ShapedGlyphEntry {
+                    codepoint: (*glyph_info_i).codepoint as GlyphId,
+                    advance: -y_advance,
+                    offset: Some(Point2D::new(x_offset, -y_offset)),
+                };
+            }
+
             let offset = if x_offset.is_zero() && y_offset.is_zero() && y_advance.is_zero() {
                 None
             } else {
@@ -373,7 +391,9 @@ impl Shaper {
             let hb_buffer: *mut hb_buffer_t = hb_buffer_create();
             hb_buffer_set_direction(
                 hb_buffer,
-                if options.flags.contains(ShapingFlags::RTL_FLAG) {
+                if options.flags.contains(ShapingFlags::VERTICAL_FLAG) {
+                    HB_DIRECTION_TTB
+                } else if options.flags.contains(ShapingFlags::RTL_FLAG) {
                     HB_DIRECTION_RTL
                 } else {
                     HB_DIRECTION_LTR
@@ -678,6 +698,18 @@ static HB_FONT_FUNCS: LazyLock<FontFuncs> = LazyLock::new(|| unsafe {
         ptr::null_mut(),
         None,
     );
+    hb_font_funcs_set_glyph_v_advance_func(
+        hb_funcs,
+        Some(glyph_v_advance_func),
+        ptr::null_mut(),
+        None,
+    );
+    hb_font_funcs_set_glyph_v_origin_func(
+        hb_funcs,
+        Some(glyph_v_origin_func),
+        ptr::null_mut(),
+        None,
+    );
 
     FontFuncs(hb_funcs)
 });
@@ -718,6 +750,46 @@ extern "C" fn glyph_h_advance_func(
     }
 }
 
+/// Vertical metrics (the `vhea` and `vmtx` tables) are not read yet, so every glyph
+/// advances by one em in vertical text, which is correct for the ideographic and kana
+/// glyphs that are set upright in vertical writing modes.
+extern "C" fn glyph_v_advance_func(
+    _: *mut hb_font_t,
+    font_data: *mut c_void,
+    _: hb_codepoint_t,
+    _: *mut c_void,
+) -> hb_position_t {
+    let font: *mut Font = font_data as *mut Font;
+    assert!(!font.is_null());
+
+    unsafe {
+        // Advances are downward, which is negative in the y-up space of HarfBuzz.
+        let em_size = (*font).descriptor.pt_size.to_f64_px();
+        Shaper::float_to_fixed(-em_size)
+    }
+}
+
+/// The vertical origin of a glyph is centered horizontally and placed at the ascent
+/// above its horizontal origin, which places the glyph in an em box below the pen.
+extern "C" fn glyph_v_origin_func(
+    _: *mut hb_font_t,
+    font_data: *mut c_void,
+    glyph: hb_codepoint_t,
+    x: *mut hb_position_t,
+    y: *mut hb_position_t,
+    _: *mut c_void,
+) -> hb_bool_t {
+    let font: *mut Font = font_data as *mut Font;
+    assert!(!font.is_null());
+
+    unsafe {
+        let advance = (*font).glyph_h_advance(glyph as GlyphId);
+        *x = Shaper::float_to_fixed(advance / 2.);
+        *y = Shaper::float_to_fixed((*font).metrics.ascent.to_f64_px());
+        true as hb_bool_t
+    }
+}
+
 // Callback to get a font table out of a font.
 extern "C" fn font_table_func(
     _: *mut hb_face_t,
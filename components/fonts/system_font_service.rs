@@ -6,12 +6,13 @@ use std::borrow::ToOwned;
 use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::ops::{Deref, RangeInclusive};
+use std::sync::Arc;
 use std::{fmt, thread};
 
 use app_units::Au;
 use compositing_traits::CrossProcessCompositorApi;
-use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
-use log::debug;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender, IpcSharedMemory};
+use log::{debug, warn};
 use malloc_size_of::MallocSizeOf as MallocSizeOfTrait;
 use malloc_size_of_derive::MallocSizeOf;
 use parking_lot::{Mutex, RwLock};
@@ -30,6 +31,7 @@ use style::values::computed::{FontStretch, FontWeight};
 use style::values::specified::FontStretch as SpecifiedFontStretch;
 use webrender_api::{FontInstanceFlags, FontInstanceKey, FontKey};
 
+use crate::FontData;
 use crate::font::FontDescriptor;
 use crate::font_store::FontStore;
 use crate::font_template::{FontTemplate, FontTemplateRef};
@@ -67,6 +69,7 @@ pub enum SystemFontServiceMessage {
         FontInstanceFlags,
         IpcSender<FontInstanceKey>,
     ),
+    GetLocalFontData(LocalFontIdentifier, IpcSender<Option<IpcSharedMemory>>),
     GetFontKey(IpcSender<FontKey>),
     GetFontInstanceKey(IpcSender<FontInstanceKey>),
     CollectMemoryReport(ReportsChan),
@@ -179,6 +182,9 @@ impl SystemFontService {
                 SystemFontServiceMessage::GetFontInstance(identifier, pt_size, flags, result) => {
                     let _ = result.send(self.get_font_instance(identifier, pt_size, flags));
                 },
+                SystemFontServiceMessage::GetLocalFontData(identifier, result_sender) => {
+                    let _ = result_sender.send(self.get_local_font_data(identifier));
+                },
                 SystemFontServiceMessage::GetFontKey(result_sender) => {
                     self.fetch_new_keys();
                     let _ = result_sender.send(self.free_font_keys.pop().unwrap());
@@ -240,6 +246,30 @@ impl SystemFontService {
             .collect()
     }
 
+    /// Read the data of a local font on behalf of a sandboxed content process, which cannot
+    /// read system font files itself. Only fonts that this service found while matching
+    /// templates are read, so that content processes cannot use this to read other files.
+    fn get_local_font_data(&self, identifier: LocalFontIdentifier) -> Option<IpcSharedMemory> {
+        let identifier = FontIdentifier::Local(identifier);
+        let is_known_font = self
+            .local_families
+            .families
+            .values()
+            .flat_map(|font_templates| font_templates.templates.iter())
+            .any(|template| *template.borrow().identifier() == identifier);
+        if !is_known_font {
+            warn!("Refusing to read data of unknown local font {identifier:?}");
+            return None;
+        }
+
+        let FontIdentifier::Local(local_identifier) = identifier else {
+            unreachable!("Checked to be a local font above");
+        };
+        local_identifier
+            .read_data_from_file()
+            .map(|data| IpcSharedMemory::from_bytes(&data))
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn refresh_local_families(&mut self) {
         self.local_families.clear();
@@ -548,6 +578,21 @@ impl SystemFontServiceProxy {
         templates
     }
 
+    pub(crate) fn get_local_font_data(&self, identifier: &LocalFontIdentifier) -> Option<FontData> {
+        let (response_chan, response_port) = ipc::channel().expect("failed to create IPC channel");
+        self.sender
+            .lock()
+            .send(SystemFontServiceMessage::GetLocalFontData(
+                identifier.clone(),
+                response_chan,
+            ))
+            .expect("failed to send message to system font service");
+        response_port
+            .recv()
+            .expect("Failed to communicate with system font service.")
+            .map(|data| FontData(Arc::new(data)))
+    }
+
     pub(crate) fn generate_font_key(&self) -> FontKey {
         let (result_sender, result_receiver) =
             ipc::channel().expect("failed to create IPC channel");
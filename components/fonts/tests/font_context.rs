@@ -134,6 +134,9 @@ mod font_context {
                     SystemFontServiceMessage::GetFontKey(result_sender) => {
                         let _ = result_sender.send(FontKey(IdNamespace(0), 0));
                     },
+                    SystemFontServiceMessage::GetLocalFontData(_, result_sender) => {
+                        let _ = result_sender.send(None);
+                    },
                     SystemFontServiceMessage::Exit(result_sender) => {
                         let _ = result_sender.send(());
                         break;
@@ -0,0 +1,661 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Support for CSS Anchor Positioning: resolving `anchor()` and `anchor-size()` functions in
+//! the inset properties of absolutely positioned boxes against the boxes that declare a
+//! matching `anchor-name`, and choosing between `position-try-fallbacks` when the box would
+//! overflow.
+//!
+//! <https://drafts.csswg.org/css-anchor-position-1/>
+
+use app_units::Au;
+use fxhash::FxHashMap;
+use style::Atom;
+use style::context::SharedStyleContext;
+use style::logical_geometry::{PhysicalSide, WritingMode};
+use style::properties::{ComputedValues, PropertyDeclaration, PropertyDeclarationBlock};
+use style::stylesheets::{CssRule, Origin, StylesheetInDocument};
+use style::values::DashedIdent;
+use style::values::computed::position::{
+    AnchorFunction, PositionAnchor, PositionTryFallbacksItem, PositionTryFallbacksTryTacticKeyword,
+};
+use style::values::computed::{Context, Inset, Length, LengthPercentage, ToComputedValue};
+use style::values::generics::length::GenericLengthPercentageOrAuto as AutoOr;
+use style::values::generics::position::{AnchorSideKeyword, AnchorSizeKeyword, GenericAnchorSide};
+
+use crate::context::LayoutContext;
+use crate::fragment_tree::{ContainingBlockManager, Fragment};
+use crate::geom::{
+    LengthPercentageOrAuto, LogicalSides, PhysicalPoint, PhysicalRect, PhysicalSides, PhysicalSize,
+};
+
+/// The rectangles of the boxes that can act as anchors for the absolutely positioned boxes of
+/// a single containing block, keyed by their `anchor-name`. All rectangles are border boxes
+/// relative to the padding box of that containing block, which is the coordinate space used
+/// to lay out absolutely positioned boxes.
+#[derive(Default)]
+pub(crate) struct AnchorMap {
+    anchors: FxHashMap<Atom, PhysicalRect<Au>>,
+}
+
+impl AnchorMap {
+    /// Collect all named anchors among the given fragments, which are the (already laid out)
+    /// children of a containing block for absolutely positioned boxes. `padding_rect_size` and
+    /// `containing_block_padding` describe that containing block.
+    pub(crate) fn collect(
+        fragments: &[Fragment],
+        padding_rect_size: PhysicalSize<Au>,
+        containing_block_padding: PhysicalSides<Au>,
+    ) -> Self {
+        let padding_rect = PhysicalRect::new(PhysicalPoint::origin(), padding_rect_size);
+        let content_rect = padding_rect.inner_rect(containing_block_padding);
+        let manager = ContainingBlockManager {
+            for_non_absolute_descendants: &content_rect,
+            for_absolute_descendants: Some(&padding_rect),
+            for_absolute_and_fixed_descendants: &padding_rect,
+        };
+
+        // When several boxes share an anchor name, the last one in tree order wins, which
+        // is what repeated insertion into the map gives us.
+        // <https://drafts.csswg.org/css-anchor-position-1/#target>
+        let mut anchors = FxHashMap::default();
+        for fragment in fragments {
+            fragment.find(&manager, 0, &mut |fragment, _, containing_block| {
+                let Fragment::Box(box_fragment) = fragment else {
+                    return None::<()>;
+                };
+                let box_fragment = box_fragment.borrow();
+                let anchor_name = &box_fragment.style.get_position().anchor_name;
+                if anchor_name.0.is_empty() {
+                    return None;
+                }
+                let rect = box_fragment
+                    .border_rect()
+                    .translate(containing_block.origin.to_vector());
+                for name in anchor_name.0.iter() {
+                    anchors.insert(name.0.clone(), rect);
+                }
+                None
+            });
+        }
+
+        Self { anchors }
+    }
+
+    fn get(&self, name: &DashedIdent) -> Option<&PhysicalRect<Au>> {
+        self.anchors.get(&name.0)
+    }
+}
+
+/// Whether the given style uses any anchor positioning feature which requires an
+/// [`AnchorMap`] to be built before laying the box out.
+pub(crate) fn uses_anchor_positioning(style: &ComputedValues) -> bool {
+    let position = style.get_position();
+    let is_anchor_function = |inset: &Inset| {
+        matches!(
+            inset,
+            Inset::AnchorFunction(_) |
+                Inset::AnchorSizeFunction(_) |
+                Inset::AnchorContainingCalcFunction(_)
+        )
+    };
+    is_anchor_function(&position.top) ||
+        is_anchor_function(&position.right) ||
+        is_anchor_function(&position.bottom) ||
+        is_anchor_function(&position.left) ||
+        !position.position_try_fallbacks.0.is_empty()
+}
+
+/// The inset property that a declaration in a `@position-try` rule sets.
+#[derive(Clone, Copy)]
+enum InsetProperty {
+    Physical(PhysicalSide),
+    BlockStart,
+    BlockEnd,
+    InlineStart,
+    InlineEnd,
+}
+
+impl InsetProperty {
+    /// The physical side that this property applies to for a box with the given writing mode.
+    fn physical_side(self, writing_mode: WritingMode) -> PhysicalSide {
+        let sides = LogicalSides::from_physical(
+            &PhysicalSides::new(
+                PhysicalSide::Top,
+                PhysicalSide::Right,
+                PhysicalSide::Bottom,
+                PhysicalSide::Left,
+            ),
+            writing_mode,
+        );
+        match self {
+            InsetProperty::Physical(side) => side,
+            InsetProperty::BlockStart => sides.block_start,
+            InsetProperty::BlockEnd => sides.block_end,
+            InsetProperty::InlineStart => sides.inline_start,
+            InsetProperty::InlineEnd => sides.inline_end,
+        }
+    }
+}
+
+/// The descriptors of a `@position-try` rule that are used during layout.
+///
+/// <https://drafts.csswg.org/css-anchor-position-1/#fallback-rule>
+#[derive(Default)]
+pub(crate) struct PositionTryDeclarations {
+    /// The inset properties declared by the rule, in declaration order.
+    insets: Vec<(InsetProperty, Inset)>,
+    position_anchor: Option<PositionAnchor>,
+}
+
+impl PositionTryDeclarations {
+    fn new(block: &PropertyDeclarationBlock, context: &Context) -> Self {
+        let mut rule = Self::default();
+        for declaration in block.declarations() {
+            let (property, inset) = match declaration {
+                PropertyDeclaration::Top(inset) => {
+                    (InsetProperty::Physical(PhysicalSide::Top), inset)
+                },
+                PropertyDeclaration::Right(inset) => {
+                    (InsetProperty::Physical(PhysicalSide::Right), inset)
+                },
+                PropertyDeclaration::Bottom(inset) => {
+                    (InsetProperty::Physical(PhysicalSide::Bottom), inset)
+                },
+                PropertyDeclaration::Left(inset) => {
+                    (InsetProperty::Physical(PhysicalSide::Left), inset)
+                },
+                PropertyDeclaration::InsetBlockStart(inset) => (InsetProperty::BlockStart, inset),
+                PropertyDeclaration::InsetBlockEnd(inset) => (InsetProperty::BlockEnd, inset),
+                PropertyDeclaration::InsetInlineStart(inset) => (InsetProperty::InlineStart, inset),
+                PropertyDeclaration::InsetInlineEnd(inset) => (InsetProperty::InlineEnd, inset),
+                PropertyDeclaration::PositionAnchor(position_anchor) => {
+                    rule.position_anchor = Some(position_anchor.to_computed_value(context));
+                    continue;
+                },
+                // TODO: Support the margin and sizing properties, as well as
+                // `position-area` and `align-self` and `justify-self`.
+                _ => continue,
+            };
+            rule.insets
+                .push((property, inset.to_computed_value(context)));
+        }
+        rule
+    }
+}
+
+/// The `@position-try` rules that apply to a document, keyed by name.
+#[derive(Default)]
+pub(crate) struct PositionTryRules {
+    rules: FxHashMap<Atom, PositionTryDeclarations>,
+}
+
+impl PositionTryRules {
+    /// Collect the `@position-try` rules of all of the stylesheets in the stylist. When more
+    /// than one rule has the same name, the last one in cascade order wins.
+    ///
+    /// Declarations are computed once for the whole document, so font-relative lengths in
+    /// them resolve against the initial font rather than the font of the positioned box.
+    pub(crate) fn collect(style_context: &SharedStyleContext) -> Self {
+        let stylist = style_context.stylist;
+        let device = stylist.device();
+        let mut rules = FxHashMap::default();
+        Context::for_media_query_evaluation(device, stylist.quirks_mode(), |context| {
+            for origin in [Origin::UserAgent, Origin::User, Origin::Author] {
+                let guard = style_context.guards.for_origin(origin);
+                for index in 0..stylist.sheet_count(origin) {
+                    let Some(stylesheet) = stylist.sheet_at(origin, index) else {
+                        continue;
+                    };
+                    for rule in stylesheet.effective_rules(device, guard) {
+                        let CssRule::PositionTry(ref lock) = *rule else {
+                            continue;
+                        };
+                        let rule = lock.read_with(guard);
+                        rules.insert(
+                            rule.name.0.clone(),
+                            PositionTryDeclarations::new(rule.block.read_with(guard), context),
+                        );
+                    }
+                }
+            }
+        });
+        Self { rules }
+    }
+
+    fn get(&self, name: &DashedIdent) -> Option<&PositionTryDeclarations> {
+        self.rules.get(&name.0)
+    }
+}
+
+/// A combination of try tactics from `position-try-fallbacks`, which swap the insets of the
+/// box between opposite sides of an axis, or between the block and inline axes.
+///
+/// The tactics of a fallback are applied in order, which is equivalent to applying a
+/// `flip-start` first, if any, followed by flips of the block and inline axes.
+///
+/// <https://drafts.csswg.org/css-anchor-position-1/#typedef-position-try-fallbacks-try-tactic>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct TryTactic {
+    flip_start: bool,
+    flip_block: bool,
+    flip_inline: bool,
+}
+
+impl TryTactic {
+    fn push(&mut self, keyword: &PositionTryFallbacksTryTacticKeyword) {
+        match keyword {
+            PositionTryFallbacksTryTacticKeyword::FlipBlock => self.flip_block = !self.flip_block,
+            PositionTryFallbacksTryTacticKeyword::FlipInline => {
+                self.flip_inline = !self.flip_inline
+            },
+            // Flipping across the start diagonal after flipping an axis is the same as
+            // flipping the other axis after flipping across the diagonal.
+            PositionTryFallbacksTryTacticKeyword::FlipStart => {
+                std::mem::swap(&mut self.flip_block, &mut self.flip_inline);
+                self.flip_start = !self.flip_start;
+            },
+            _ => {},
+        }
+    }
+
+    /// Move the given values between the sides of a box, as this tactic does with its
+    /// insets.
+    fn apply<T>(&self, sides: LogicalSides<T>) -> LogicalSides<T> {
+        let LogicalSides {
+            mut inline_start,
+            mut inline_end,
+            mut block_start,
+            mut block_end,
+        } = sides;
+        if self.flip_start {
+            std::mem::swap(&mut inline_start, &mut block_start);
+            std::mem::swap(&mut inline_end, &mut block_end);
+        }
+        if self.flip_block {
+            std::mem::swap(&mut block_start, &mut block_end);
+        }
+        if self.flip_inline {
+            std::mem::swap(&mut inline_start, &mut inline_end);
+        }
+        LogicalSides {
+            inline_start,
+            inline_end,
+            block_start,
+            block_end,
+        }
+    }
+}
+
+/// One of the ways of laying out an absolutely positioned box that `position-try-fallbacks`
+/// gives: the base style, optionally with the declarations of a `@position-try` rule, with
+/// a try tactic applied.
+///
+/// <https://drafts.csswg.org/css-anchor-position-1/#fallback-apply>
+#[derive(Clone, Copy, Default)]
+pub(crate) struct PositionTryOption<'a> {
+    rule: Option<&'a PositionTryDeclarations>,
+    tactic: TryTactic,
+}
+
+impl<'a> PositionTryOption<'a> {
+    /// The list of options to try, in order, when laying out a box with the given style.
+    /// The first entry is always the base style, with no rule or tactic applied.
+    pub(crate) fn all_for_style(
+        style: &ComputedValues,
+        layout_context: &'a LayoutContext,
+    ) -> Vec<Self> {
+        let mut options = vec![Self::default()];
+        for item in style.get_position().position_try_fallbacks.0.iter() {
+            let PositionTryFallbacksItem::IdentAndOrTactic(ident_and_or_tactic) = item else {
+                // TODO: Support `<position-area>` fallbacks.
+                continue;
+            };
+
+            // A fallback naming a `@position-try` rule that doesn't exist is skipped.
+            let rule = if ident_and_or_tactic.ident.0.is_empty() {
+                None
+            } else {
+                match layout_context
+                    .position_try_rules()
+                    .get(&ident_and_or_tactic.ident)
+                {
+                    Some(rule) => Some(rule),
+                    None => continue,
+                }
+            };
+            let mut tactic = TryTactic::default();
+            for keyword in ident_and_or_tactic.try_tactic.iter() {
+                tactic.push(keyword);
+            }
+
+            let option = Self { rule, tactic };
+            if !options.iter().any(|existing| existing.is_same_as(&option)) {
+                options.push(option);
+            }
+        }
+        options
+    }
+
+    fn is_same_as(&self, other: &Self) -> bool {
+        let same_rule = match (self.rule, other.rule) {
+            (Some(rule), Some(other_rule)) => std::ptr::eq(rule, other_rule),
+            (None, None) => true,
+            _ => false,
+        };
+        same_rule && self.tactic == other.tactic
+    }
+}
+
+/// The inset properties of an absolutely positioned box with all `anchor()` and
+/// `anchor-size()` functions resolved to lengths.
+pub(crate) struct ResolvedInsets {
+    sides: PhysicalSides<AutoOr<LengthPercentage>>,
+}
+
+impl ResolvedInsets {
+    pub(crate) fn new(
+        style: &ComputedValues,
+        option: &PositionTryOption,
+        anchors: &AnchorMap,
+        containing_block_size: PhysicalSize<Au>,
+        containing_block_writing_mode: WritingMode,
+    ) -> Self {
+        let position = style.get_position();
+        let mut insets = PhysicalSides::new(
+            &position.top,
+            &position.right,
+            &position.bottom,
+            &position.left,
+        );
+        let mut position_anchor = &position.position_anchor;
+        if let Some(rule) = option.rule {
+            for (property, inset) in rule.insets.iter() {
+                match property.physical_side(style.writing_mode) {
+                    PhysicalSide::Top => insets.top = inset,
+                    PhysicalSide::Right => insets.right = inset,
+                    PhysicalSide::Bottom => insets.bottom = inset,
+                    PhysicalSide::Left => insets.left = inset,
+                }
+            }
+            if let Some(rule_position_anchor) = rule.position_anchor.as_ref() {
+                position_anchor = rule_position_anchor;
+            }
+        }
+
+        // Pair each inset with the side it was specified for, and let the try tactic move
+        // them between the sides of the box.
+        let insets = PhysicalSides::new(
+            (PhysicalSide::Top, insets.top),
+            (PhysicalSide::Right, insets.right),
+            (PhysicalSide::Bottom, insets.bottom),
+            (PhysicalSide::Left, insets.left),
+        );
+        let insets = option
+            .tactic
+            .apply(LogicalSides::from_physical(
+                &insets,
+                containing_block_writing_mode,
+            ))
+            .to_physical(containing_block_writing_mode);
+
+        let resolver = AnchorResolver {
+            anchors,
+            default_anchor: match position_anchor {
+                PositionAnchor::Ident(name) => Some(name),
+                PositionAnchor::Auto => None,
+            },
+            source_sides: PhysicalSides::new(
+                insets.top.0,
+                insets.right.0,
+                insets.bottom.0,
+                insets.left.0,
+            ),
+            containing_block_size,
+            containing_block_writing_mode,
+            writing_mode: style.writing_mode,
+        };
+        let resolve = |target_side: PhysicalSide, (source_side, inset): (PhysicalSide, &Inset)| {
+            resolver.resolve_inset(inset, source_side, target_side)
+        };
+
+        Self {
+            sides: PhysicalSides::new(
+                resolve(PhysicalSide::Top, insets.top),
+                resolve(PhysicalSide::Right, insets.right),
+                resolve(PhysicalSide::Bottom, insets.bottom),
+                resolve(PhysicalSide::Left, insets.left),
+            ),
+        }
+    }
+
+    pub(crate) fn box_offsets(
+        &self,
+        writing_mode: WritingMode,
+    ) -> LogicalSides<LengthPercentageOrAuto<'_>> {
+        let borrowed = |inset: &AutoOr<LengthPercentage>| match inset {
+            AutoOr::LengthPercentage(value) => LengthPercentageOrAuto::LengthPercentage(value),
+            AutoOr::Auto => LengthPercentageOrAuto::Auto,
+        };
+        LogicalSides::from_physical(
+            &PhysicalSides::new(
+                borrowed(&self.sides.top),
+                borrowed(&self.sides.right),
+                borrowed(&self.sides.bottom),
+                borrowed(&self.sides.left),
+            ),
+            writing_mode,
+        )
+    }
+}
+
+struct AnchorResolver<'a> {
+    anchors: &'a AnchorMap,
+    default_anchor: Option<&'a DashedIdent>,
+    /// For each side of the box, the side whose inset property it takes its value from after
+    /// applying the try tactic.
+    source_sides: PhysicalSides<PhysicalSide>,
+    containing_block_size: PhysicalSize<Au>,
+    containing_block_writing_mode: WritingMode,
+    /// The writing mode of the absolutely positioned box itself.
+    writing_mode: WritingMode,
+}
+
+impl AnchorResolver<'_> {
+    /// Resolve the computed value of the inset property for `source_side` into a used value
+    /// for `target_side`. These differ when a try tactic moves the inset to another side, in
+    /// which case any anchor edge or size it refers to is moved in the same way.
+    fn resolve_inset(
+        &self,
+        inset: &Inset,
+        source_side: PhysicalSide,
+        target_side: PhysicalSide,
+    ) -> AutoOr<LengthPercentage> {
+        let length = |length: Au| {
+            AutoOr::LengthPercentage(LengthPercentage::new_length(Length::new(
+                length.to_f32_px(),
+            )))
+        };
+        match inset {
+            Inset::LengthPercentage(value) => AutoOr::LengthPercentage(value.clone()),
+            Inset::Auto => AutoOr::Auto,
+            // When an `anchor()` or `anchor-size()` function is invalid, it resolves to its
+            // fallback value. If there is none, the property is invalid at computed-value
+            // time and behaves as `auto`.
+            Inset::AnchorFunction(function) => self
+                .resolve_anchor_function(function, source_side, target_side)
+                .map(length)
+                .unwrap_or_else(|| match function.fallback.as_ref() {
+                    Some(fallback) => self.resolve_inset(fallback, source_side, target_side),
+                    None => AutoOr::Auto,
+                }),
+            Inset::AnchorSizeFunction(function) => self
+                .resolve_anchor_size_function(
+                    &function.target_element,
+                    function.size,
+                    source_side,
+                    target_side,
+                )
+                .map(length)
+                .unwrap_or_else(|| match function.fallback.as_ref() {
+                    Some(fallback) => self.resolve_inset(fallback, source_side, target_side),
+                    None => AutoOr::Auto,
+                }),
+            // TODO: Support `anchor()` and `anchor-size()` inside `calc()`.
+            Inset::AnchorContainingCalcFunction(_) => AutoOr::Auto,
+        }
+    }
+
+    fn anchor_rect(&self, target_element: &DashedIdent) -> Option<&PhysicalRect<Au>> {
+        let name = if target_element.0.is_empty() {
+            self.default_anchor?
+        } else {
+            target_element
+        };
+        self.anchors.get(name)
+    }
+
+    /// The side of the box that the inset property for `source_side` was moved to by the
+    /// try tactic.
+    fn target_side(&self, source_side: PhysicalSide) -> PhysicalSide {
+        let sides = &self.source_sides;
+        if sides.top == source_side {
+            PhysicalSide::Top
+        } else if sides.right == source_side {
+            PhysicalSide::Right
+        } else if sides.bottom == source_side {
+            PhysicalSide::Bottom
+        } else {
+            PhysicalSide::Left
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-anchor-position-1/#anchor-pos>
+    fn resolve_anchor_function(
+        &self,
+        function: &AnchorFunction,
+        source_side: PhysicalSide,
+        target_side: PhysicalSide,
+    ) -> Option<Au> {
+        let anchor_rect = self.anchor_rect(&function.target_element)?;
+
+        // The anchor side is looked up in the axis the inset was specified in, and then
+        // moved to the axis of `target_side` in the same way as the inset itself, so that
+        // e.g. `top: anchor(bottom)` becomes `bottom: anchor(top)` under `flip-block` and
+        // `left: anchor(right)` under `flip-start`.
+        let source_axis_is_horizontal =
+            matches!(source_side, PhysicalSide::Left | PhysicalSide::Right);
+        let is_horizontal_axis = matches!(target_side, PhysicalSide::Left | PhysicalSide::Right);
+        let (anchor_start, anchor_end) = if is_horizontal_axis {
+            (anchor_rect.min_x(), anchor_rect.max_x())
+        } else {
+            (anchor_rect.min_y(), anchor_rect.max_y())
+        };
+        let edge_position = |side: PhysicalSide| match self.target_side(side) {
+            PhysicalSide::Left | PhysicalSide::Top => anchor_start,
+            PhysicalSide::Right | PhysicalSide::Bottom => anchor_end,
+        };
+        let axis_start_side = self.logical_start_side(source_axis_is_horizontal);
+        let axis_end_side = opposite_side(axis_start_side);
+
+        let position = match &function.side {
+            GenericAnchorSide::Keyword(keyword) => match keyword {
+                AnchorSideKeyword::Inside => edge_position(source_side),
+                AnchorSideKeyword::Outside => edge_position(opposite_side(source_side)),
+                AnchorSideKeyword::Left | AnchorSideKeyword::Right
+                    if !source_axis_is_horizontal =>
+                {
+                    return None;
+                },
+                AnchorSideKeyword::Top | AnchorSideKeyword::Bottom if source_axis_is_horizontal => {
+                    return None;
+                },
+                AnchorSideKeyword::Left => edge_position(PhysicalSide::Left),
+                AnchorSideKeyword::Right => edge_position(PhysicalSide::Right),
+                AnchorSideKeyword::Top => edge_position(PhysicalSide::Top),
+                AnchorSideKeyword::Bottom => edge_position(PhysicalSide::Bottom),
+                AnchorSideKeyword::Start | AnchorSideKeyword::SelfStart => {
+                    edge_position(axis_start_side)
+                },
+                AnchorSideKeyword::End | AnchorSideKeyword::SelfEnd => edge_position(axis_end_side),
+                AnchorSideKeyword::Center => anchor_start + (anchor_end - anchor_start) / 2,
+            },
+            GenericAnchorSide::Percentage(percentage) => {
+                let start = edge_position(axis_start_side);
+                let end = edge_position(axis_end_side);
+                start + (end - start).scale_by(percentage.0)
+            },
+        };
+
+        Some(match target_side {
+            PhysicalSide::Left | PhysicalSide::Top => position,
+            PhysicalSide::Right => self.containing_block_size.width - position,
+            PhysicalSide::Bottom => self.containing_block_size.height - position,
+        })
+    }
+
+    /// <https://drafts.csswg.org/css-anchor-position-1/#anchor-size-fn>
+    fn resolve_anchor_size_function(
+        &self,
+        target_element: &DashedIdent,
+        size: AnchorSizeKeyword,
+        source_side: PhysicalSide,
+        target_side: PhysicalSide,
+    ) -> Option<Au> {
+        let anchor_rect = self.anchor_rect(target_element)?;
+
+        // Without a keyword, the size is taken in the axis of the property it is used in.
+        let source_axis_is_horizontal =
+            matches!(source_side, PhysicalSide::Left | PhysicalSide::Right);
+        let size_is_horizontal = match size {
+            AnchorSizeKeyword::None => source_axis_is_horizontal,
+            AnchorSizeKeyword::Width => true,
+            AnchorSizeKeyword::Height => false,
+            AnchorSizeKeyword::Block => self.containing_block_writing_mode.is_vertical(),
+            AnchorSizeKeyword::Inline => self.containing_block_writing_mode.is_horizontal(),
+            AnchorSizeKeyword::SelfBlock => self.writing_mode.is_vertical(),
+            AnchorSizeKeyword::SelfInline => self.writing_mode.is_horizontal(),
+        };
+
+        // A `flip-start` try tactic that moved the inset to the other axis swaps the
+        // dimension it refers to as well.
+        let target_axis_is_horizontal =
+            matches!(target_side, PhysicalSide::Left | PhysicalSide::Right);
+        let size_is_horizontal =
+            size_is_horizontal != (source_axis_is_horizontal != target_axis_is_horizontal);
+
+        Some(if size_is_horizontal {
+            anchor_rect.width()
+        } else {
+            anchor_rect.height()
+        })
+    }
+
+    /// The physical side which is the logical start side of the given physical axis in the
+    /// writing mode of the containing block.
+    fn logical_start_side(&self, is_horizontal_axis: bool) -> PhysicalSide {
+        let is_start = LogicalSides {
+            inline_start: true,
+            inline_end: false,
+            block_start: true,
+            block_end: false,
+        }
+        .to_physical(self.containing_block_writing_mode);
+        match (is_horizontal_axis, is_start.left, is_start.top) {
+            (true, true, _) => PhysicalSide::Left,
+            (true, false, _) => PhysicalSide::Right,
+            (false, _, true) => PhysicalSide::Top,
+            (false, _, false) => PhysicalSide::Bottom,
+        }
+    }
+}
+
+fn opposite_side(side: PhysicalSide) -> PhysicalSide {
+    match side {
+        PhysicalSide::Top => PhysicalSide::Bottom,
+        PhysicalSide::Right => PhysicalSide::Left,
+        PhysicalSide::Bottom => PhysicalSide::Top,
+        PhysicalSide::Left => PhysicalSide::Right,
+    }
+}
@@ -21,6 +21,11 @@ impl<T> ArcRefCell<T> {
             value: Arc::new(AtomicRefCell::new(value)),
         }
     }
+
+    /// The address of the shared value, which identifies it for as long as it's alive.
+    pub(crate) fn address(&self) -> usize {
+        &*self.value as *const AtomicRefCell<T> as usize
+    }
 }
 
 impl<T> Clone for ArcRefCell<T> {
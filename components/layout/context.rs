@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use euclid::Size2D;
 use fnv::FnvHashMap;
@@ -23,6 +23,8 @@ use style::dom::OpaqueNode;
 use style::values::computed::image::{Gradient, Image};
 use webrender_api::units::{DeviceIntSize, DeviceSize};
 
+use crate::anchor_positioning::PositionTryRules;
+
 pub(crate) type CachedImageOrError = Result<CachedImage, ResolveImageError>;
 
 pub(crate) struct LayoutContext<'a> {
@@ -40,6 +42,17 @@ pub(crate) struct LayoutContext<'a> {
     /// An [`ImageResolver`] used for resolving images during box and fragment
     /// tree construction. Later passed to display list construction.
     pub image_resolver: Arc<ImageResolver>,
+
+    /// The `@position-try` rules of the document, which are only collected once a box
+    /// names one of them in `position-try-fallbacks`.
+    pub position_try_rules: OnceLock<PositionTryRules>,
+}
+
+impl LayoutContext<'_> {
+    pub(crate) fn position_try_rules(&self) -> &PositionTryRules {
+        self.position_try_rules
+            .get_or_init(|| PositionTryRules::collect(&self.style_context))
+    }
 }
 
 pub enum ResolvedImage<'a> {
@@ -0,0 +1,175 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Promotion of animations of `transform` and `opacity` to the compositor.
+//!
+//! Animations are normally ticked by script, which restyles the animating elements and
+//! builds a new display list on every frame, so they stutter as soon as script or layout
+//! are busy. Animations of `transform` and `opacity` only change how the contents of a
+//! reference frame or stacking context are composited though, so the compositor can run
+//! them on its own: layout binds the animated property to a WebRender property binding
+//! and samples the values of the animation for the rest of its current iteration, which
+//! the compositor then applies to the binding as the animation timeline advances.
+
+use std::hash::{Hash, Hasher};
+
+use compositing_traits::display_list::{
+    COMPOSITOR_ANIMATION_SAMPLE_INTERVAL, CompositorAnimation, CompositorAnimationValues,
+};
+use fxhash::FxHasher;
+use style::animation::{AnimationSetKey, AnimationState, DocumentAnimationSet};
+use style::properties::animated_properties::AnimationValue;
+use style::values::computed::Transform;
+use webrender_api::units::LayoutTransform;
+use webrender_api::{self as wr};
+
+use crate::fragment_tree::Tag;
+
+/// The longest stretch of an animation that is sampled ahead of time, in seconds. Script
+/// keeps ticking the animation and refreshes the samples when it builds a new display list.
+const MAX_SAMPLED_DURATION: f64 = 10.;
+
+#[derive(Clone, Copy, Hash)]
+enum AnimatedProperty {
+    Transform,
+    Opacity,
+}
+
+/// Samples the animations of the document whose display list is being built, so that the
+/// compositor can run them.
+pub(crate) struct CompositorAnimationSampler {
+    animations: DocumentAnimationSet,
+    /// The current value of the animation timeline, in seconds.
+    now: f64,
+    pipeline_id: wr::PipelineId,
+    sampled_animations: Vec<CompositorAnimation>,
+}
+
+impl CompositorAnimationSampler {
+    pub(crate) fn new(
+        animations: DocumentAnimationSet,
+        now: f64,
+        pipeline_id: wr::PipelineId,
+    ) -> Self {
+        Self {
+            animations,
+            now,
+            pipeline_id,
+            sampled_animations: Vec::new(),
+        }
+    }
+
+    /// The animations sampled so far, to be run by the compositor.
+    pub(crate) fn take_sampled_animations(&mut self) -> Vec<CompositorAnimation> {
+        std::mem::take(&mut self.sampled_animations)
+    }
+
+    /// If the `transform` of the element or pseudo-element with the given [`Tag`] is
+    /// animated, sample its animation, converting each sampled value into the transform
+    /// of its reference frame with `to_reference_frame_transform`. Returns the key of the
+    /// property binding that the reference frame should use.
+    pub(crate) fn sample_transform_animation(
+        &mut self,
+        tag: Tag,
+        to_reference_frame_transform: impl Fn(&Transform) -> Option<LayoutTransform>,
+    ) -> Option<wr::PropertyBindingKey<LayoutTransform>> {
+        let values = self.sample(tag, |value| match value {
+            AnimationValue::Transform(transform) => to_reference_frame_transform(transform),
+            _ => None,
+        })?;
+        let key = wr::PropertyBindingKey::new(self.binding_id(tag, AnimatedProperty::Transform));
+        self.sampled_animations.push(CompositorAnimation {
+            start_time: self.now,
+            values: CompositorAnimationValues::Transform(key, values),
+        });
+        Some(key)
+    }
+
+    /// If the `opacity` of the element or pseudo-element with the given [`Tag`] is
+    /// animated, sample its animation. Returns the key of the property binding that the
+    /// opacity filter of its stacking context should use.
+    pub(crate) fn sample_opacity_animation(
+        &mut self,
+        tag: Tag,
+    ) -> Option<wr::PropertyBindingKey<f32>> {
+        let values = self.sample(tag, |value| match value {
+            AnimationValue::Opacity(opacity) => Some(*opacity),
+            _ => None,
+        })?;
+        let key = wr::PropertyBindingKey::new(self.binding_id(tag, AnimatedProperty::Opacity));
+        self.sampled_animations.push(CompositorAnimation {
+            start_time: self.now,
+            values: CompositorAnimationValues::Opacity(key, values),
+        });
+        Some(key)
+    }
+
+    /// Sample the value that `value_of` extracts from the running animations and
+    /// transitions of the element or pseudo-element with the given [`Tag`], from now until
+    /// the first of them reaches the end of its current iteration. Returns `None` if none
+    /// of them animates the property that `value_of` extracts.
+    fn sample<T>(
+        &self,
+        tag: Tag,
+        value_of: impl Fn(&AnimationValue) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let sets = self.animations.sets.read();
+        let set = sets.get(&AnimationSetKey::new(tag.node, tag.pseudo))?;
+
+        let is_active = |state: &AnimationState| {
+            matches!(state, AnimationState::Pending | AnimationState::Running)
+        };
+        let end_time = set
+            .animations
+            .iter()
+            .filter(|animation| is_active(&animation.state))
+            .map(|animation| animation.started_at + animation.duration)
+            .chain(
+                set.transitions
+                    .iter()
+                    .filter(|transition| is_active(&transition.state))
+                    .map(|transition| {
+                        transition.start_time + transition.property_animation.duration
+                    }),
+            )
+            .fold(self.now + MAX_SAMPLED_DURATION, f64::min);
+        if end_time <= self.now {
+            return None;
+        }
+
+        // Transitions override animations in the cascade, so look at them first.
+        let value_at = |time: f64| {
+            set.transitions
+                .iter()
+                .rev()
+                .filter(|transition| is_active(&transition.state))
+                .find_map(|transition| value_of(&transition.calculate_value(time)))
+                .or_else(|| {
+                    set.get_value_map_for_active_animations(time)?
+                        .values()
+                        .find_map(&value_of)
+                })
+        };
+
+        let sample_count =
+            ((end_time - self.now) / COMPOSITOR_ANIMATION_SAMPLE_INTERVAL).ceil() as usize + 1;
+        (0..sample_count)
+            .map(|index| {
+                let time = self.now + index as f64 * COMPOSITOR_ANIMATION_SAMPLE_INTERVAL;
+                value_at(time.min(end_time))
+            })
+            .collect()
+    }
+
+    /// An id for the property binding of `property` of the element or pseudo-element with
+    /// the given [`Tag`], which stays the same across display lists, so that the compositor
+    /// keeps animating the binding until it receives new samples.
+    fn binding_id(&self, tag: Tag, property: AnimatedProperty) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.pipeline_id.1.hash(&mut hasher);
+        tag.to_display_list_fragment_id().hash(&mut hasher);
+        property.hash(&mut hasher);
+        ((self.pipeline_id.0 as u64) << 32) | (hasher.finish() as u32 as u64)
+    }
+}
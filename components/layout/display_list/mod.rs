@@ -13,8 +13,9 @@ use compositing_traits::display_list::{CompositorDisplayListInfo, SpatialTreeNod
 use embedder_traits::Cursor;
 use euclid::{Point2D, Scale, SideOffsets2D, Size2D, UnknownUnit, Vector2D};
 use fonts::GlyphStore;
+use fxhash::FxHashSet;
 use gradient::WebRenderGradient;
-use layout_api::ReflowRequest;
+use layout_api::{HighlightKind, ReflowRequest};
 use net_traits::image_cache::Image as CachedImage;
 use range::Range as ServoRange;
 use servo_arc::Arc as ServoArc;
@@ -39,16 +40,19 @@ use style::values::generics::rect::Rect;
 use style::values::specified::text::TextDecorationLine;
 use style::values::specified::ui::CursorKind;
 use style_traits::{CSSPixel as StyloCSSPixel, DevicePixel as StyloDevicePixel};
-use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutPixel, LayoutRect, LayoutSize};
+use webrender_api::units::{
+    DeviceIntSize, DevicePixel, LayoutPixel, LayoutRect, LayoutSize, LayoutTransform,
+};
 use webrender_api::{
     self as wr, BorderDetails, BoxShadowClipMode, BuiltDisplayList, ClipChainId, ClipMode,
-    CommonItemProperties, ComplexClipRegion, ImageRendering, NinePatchBorder,
+    CommonItemProperties, ComplexClipRegion, ImageRendering, ItemKey, NinePatchBorder,
     NinePatchBorderSource, PropertyBinding, SpatialId, SpatialTreeItemKey, units,
 };
 use wr::units::LayoutVector2D;
 
 use crate::cell::ArcRefCell;
 use crate::context::{ImageResolver, ResolvedImage};
+use crate::display_list::conversions::FilterToWebRender;
 pub use crate::display_list::conversions::ToWebRender;
 use crate::display_list::stacking_context::StackingContextSection;
 use crate::fragment_tree::{
@@ -63,17 +67,48 @@ use crate::style_ext::{BorderStyleColor, ComputedValuesExt};
 
 mod background;
 mod clip;
+mod compositor_animations;
 mod conversions;
 mod gradient;
+mod parallel;
+mod retained;
 mod stacking_context;
 
 use background::BackgroundPainter;
+pub(crate) use compositor_animations::CompositorAnimationSampler;
+use parallel::{PreparedGlyphs, PreparedTextFragments};
+pub(crate) use retained::RetainedDisplayList;
 pub use stacking_context::*;
 
 // webrender's `ItemTag` is private.
 type ItemTag = (u64, u16);
 type HitInfo = Option<ItemTag>;
 const INSERTION_POINT_LOGICAL_WIDTH: Au = Au(AU_PER_PX);
+/// The color of the wavy underline painted below misspelled words in editable text.
+const SPELLING_ERROR_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The background color of highlighted text, which is that of the `Mark` system color. The
+/// style engine doesn't support the `::highlight()` pseudo-element yet, so custom highlights
+/// are painted with it too.
+const MARK_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
+/// The background color of the active match of the text that the user is finding in the
+/// page, which sets it apart from the other matches.
+const ACTIVE_FIND_MATCH_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 0.59,
+    b: 0.2,
+    a: 1.0,
+};
 
 pub(crate) struct DisplayListBuilder<'a> {
     /// The current [ScrollTreeNodeId] for this [DisplayListBuilder]. This
@@ -119,6 +154,46 @@ pub(crate) struct DisplayListBuilder<'a> {
 
     /// The device pixel ratio used for this `Document`'s display list.
     device_pixel_ratio: Scale<f32, StyloCSSPixel, StyloDevicePixel>,
+
+    /// A count of the number of SpatialTree nodes pushed to the WebRender display
+    /// list. This is merely to ensure that the currently-unused SpatialTreeItemKey
+    /// produced for every SpatialTree node is unique.
+    spatial_tree_item_count: u64,
+
+    /// The WebRender [`SpatialId`] of a reference frame that replaces the one of the
+    /// [`Self::current_scroll_node_id`] while painting vertical text, which is painted
+    /// in a rotated, line-relative coordinate space.
+    current_line_relative_spatial_id: Option<SpatialId>,
+
+    /// The display items retained by WebRender for the previous display list of the
+    /// [`StackingContextTree`] being painted.
+    retained_display_list: &'a mut RetainedDisplayList,
+
+    /// The nodes whose fragments were restyled since the previous display list, and
+    /// which thus need to be painted again.
+    dirty_nodes: &'a FxHashSet<OpaqueNode>,
+
+    /// Whether item groups that haven't changed reuse the items retained by WebRender
+    /// rather than being painted again.
+    reuse_retained_items: bool,
+
+    /// Whether any retained items were reused in this display list.
+    reused_retained_items: bool,
+
+    /// The [`ItemKey`] of the next item group.
+    next_item_group_key: usize,
+
+    /// Whether the item group currently being painted contains items that can't be
+    /// retained by WebRender and reused in later display lists.
+    current_item_group_is_unretainable: bool,
+
+    /// The glyphs of the text fragments to be painted, if they were prepared in parallel.
+    prepared_text_fragments: PreparedTextFragments,
+
+    /// How far the `filter`s of the stacking contexts being painted spread their contents
+    /// beyond where they are painted, for instance with `blur()`. Hit test areas and
+    /// largest contentful paint candidates are inflated by this much.
+    filter_outset: f32,
 }
 
 struct InspectorHighlight {
@@ -156,17 +231,44 @@ impl InspectorHighlight {
 }
 
 impl DisplayListBuilder<'_> {
+    /// Build a WebRender display list for `stacking_context_tree`, painting again only
+    /// the fragments of `dirty_nodes` if a display list was already built for this tree.
+    /// See [`RetainedDisplayList`]. If a `thread_pool` is given, the parts of painting
+    /// that don't depend on display list order are done in parallel on it.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn build(
         reflow_request: &ReflowRequest,
         stacking_context_tree: &mut StackingContextTree,
         fragment_tree: &FragmentTree,
         image_resolver: Arc<ImageResolver>,
         device_pixel_ratio: Scale<f32, StyloCSSPixel, StyloDevicePixel>,
+        dirty_nodes: &FxHashSet<OpaqueNode>,
+        thread_pool: Option<&rayon::ThreadPool>,
         debug: &DebugOptions,
     ) -> BuiltDisplayList {
         // Build the rest of the display list which inclues all of the WebRender primitives.
         let compositor_info = &mut stacking_context_tree.compositor_info;
-        compositor_info.hit_test_info.clear();
+        let retained_display_list = &mut stacking_context_tree.retained_display_list;
+
+        // The DOM inspector highlight is collected while painting fragments, so it needs
+        // every fragment to be painted.
+        let reuse_retained_items = reflow_request.highlighted_dom_node.is_none() &&
+            retained_display_list.can_reuse_items(compositor_info.hit_test_info.len());
+        if !reuse_retained_items {
+            compositor_info.hit_test_info.clear();
+            compositor_info.hit_test_epoch = compositor_info.epoch;
+        }
+
+        // When retained items are reused, only the contents of dirty nodes are certain to
+        // be painted, so only their glyphs are worth preparing.
+        let prepared_text_fragments = match thread_pool {
+            Some(thread_pool) => PreparedTextFragments::prepare(
+                thread_pool,
+                &stacking_context_tree.root_stacking_context,
+                |content| !reuse_retained_items || content.is_dirty(dirty_nodes),
+            ),
+            None => Default::default(),
+        };
 
         let mut webrender_display_list_builder =
             webrender_api::DisplayListBuilder::new(compositor_info.pipeline_id);
@@ -196,6 +298,16 @@ impl DisplayListBuilder<'_> {
             clip_map: Default::default(),
             image_resolver,
             device_pixel_ratio,
+            spatial_tree_item_count: 0,
+            current_line_relative_spatial_id: None,
+            retained_display_list,
+            dirty_nodes,
+            reuse_retained_items,
+            reused_retained_items: false,
+            next_item_group_key: 0,
+            current_item_group_is_unretainable: false,
+            prepared_text_fragments,
+            filter_outset: 0.,
         };
 
         builder.add_all_spatial_nodes();
@@ -213,9 +325,60 @@ impl DisplayListBuilder<'_> {
             .build_display_list(&mut builder);
         builder.paint_dom_inspector_highlight();
 
+        let reused_retained_items = builder.reused_retained_items;
+        let hit_test_info_len = builder.compositor_info.hit_test_info.len();
+        builder
+            .retained_display_list
+            .finish_display_list(reused_retained_items, hit_test_info_len);
+        let cache_size = builder.retained_display_list.cache_size();
+        webrender_display_list_builder.set_cache_size(cache_size);
+
         webrender_display_list_builder.end().1
     }
 
+    /// Paint a group of consecutive `contents` of a stacking context, or reuse the items
+    /// that WebRender retained for them from the previous display list if none of their
+    /// fragments need to be painted again.
+    pub(crate) fn build_item_group(&mut self, contents: &[&StackingContextContent]) {
+        if contents.is_empty() {
+            return;
+        }
+
+        let key = self.next_item_group_key;
+        let Ok(key) = ItemKey::try_from(key) else {
+            // There are more groups than WebRender can retain, so paint the rest of them
+            // as usual.
+            for content in contents {
+                content.build_display_list(self, &[]);
+            }
+            return;
+        };
+        self.next_item_group_key += 1;
+
+        if self.reuse_retained_items &&
+            self.retained_display_list
+                .can_reuse_group(key, contents, self.dirty_nodes)
+        {
+            self.wr().push_reuse_items(key);
+            self.reused_retained_items = true;
+            return;
+        }
+
+        self.current_item_group_is_unretainable = false;
+        self.wr().start_item_group();
+        for content in contents {
+            content.build_display_list(self, &[]);
+        }
+        let retained = if self.current_item_group_is_unretainable {
+            self.wr().cancel_item_group(false /* discard */);
+            false
+        } else {
+            self.wr().finish_item_group(key)
+        };
+        self.retained_display_list
+            .update_group(key, contents, retained);
+    }
+
     fn wr(&mut self) -> &mut wr::DisplayListBuilder {
         self.webrender_display_list_builder
     }
@@ -228,6 +391,27 @@ impl DisplayListBuilder<'_> {
         self.compositor_info.is_contentful = true;
     }
 
+    /// Mark the display list as contentful because of an image or text painted in `rect`,
+    /// which is a candidate for the largest contentful paint of the page. Its size is
+    /// approximated by the part of `rect`, grown by the outset of the filters applied to
+    /// it, that is in the initial viewport, ignoring scroll offsets and transforms.
+    fn mark_is_largest_contentful_paint_candidate(&mut self, rect: &LayoutRect) {
+        self.mark_is_contentful();
+        let viewport = LayoutRect::from_size(self.compositor_info.viewport_size);
+        let area = self
+            .inflate_by_filter_outset(*rect)
+            .intersection(&viewport)
+            .map_or(0., |visible_rect| visible_rect.area());
+        let largest_contentful_area = &mut self.compositor_info.largest_contentful_area;
+        *largest_contentful_area = largest_contentful_area.max(area);
+    }
+
+    /// Grow `rect` to cover everything that the `filter`s of the stacking contexts being
+    /// painted spread its contents over.
+    fn inflate_by_filter_outset(&self, rect: LayoutRect) -> LayoutRect {
+        rect.inflate(self.filter_outset, self.filter_outset)
+    }
+
     fn spatial_id(&self, id: ScrollTreeNodeId) -> SpatialId {
         self.compositor_info.scroll_tree.webrender_id(&id)
     }
@@ -242,20 +426,22 @@ impl DisplayListBuilder<'_> {
         }
     }
 
+    /// Produce a new SpatialTreeItemKey. This is currently unused by WebRender, but has
+    /// to be unique to the entire scene.
+    fn next_spatial_tree_item_key(&mut self) -> SpatialTreeItemKey {
+        let pipeline_id = self.pipeline_id();
+        let pipeline_tag = ((pipeline_id.0 as u64) << 32) | pipeline_id.1 as u64;
+        self.spatial_tree_item_count += 1;
+        SpatialTreeItemKey::new(pipeline_tag, self.spatial_tree_item_count)
+    }
+
     pub(crate) fn add_all_spatial_nodes(&mut self) {
-        // A count of the number of SpatialTree nodes pushed to the WebRender display
-        // list. This is merely to ensure that the currently-unused SpatialTreeItemKey
-        // produced for every SpatialTree node is unique.
-        let mut spatial_tree_count = 0;
         let mut scroll_tree = std::mem::take(&mut self.compositor_info.scroll_tree);
         let mut mapping = Vec::with_capacity(scroll_tree.nodes.len());
 
         mapping.push(SpatialId::root_reference_frame(self.pipeline_id()));
         mapping.push(SpatialId::root_scroll_node(self.pipeline_id()));
 
-        let pipeline_id = self.pipeline_id();
-        let pipeline_tag = ((pipeline_id.0 as u64) << 32) | pipeline_id.1 as u64;
-
         for node in scroll_tree.nodes.iter().skip(2) {
             let parent_scroll_node_id = node
                 .parent
@@ -264,10 +450,7 @@ impl DisplayListBuilder<'_> {
                 .get(parent_scroll_node_id.index)
                 .expect("Should add spatial nodes to display list in order");
 
-            // Produce a new SpatialTreeItemKey. This is currently unused by WebRender,
-            // but has to be unique to the entire scene.
-            spatial_tree_count += 1;
-            let spatial_tree_item_key = SpatialTreeItemKey::new(pipeline_tag, spatial_tree_count);
+            let spatial_tree_item_key = self.next_spatial_tree_item_key();
 
             mapping.push(match &node.info {
                 SpatialTreeNodeInfo::ReferenceFrame(info) => {
@@ -275,7 +458,10 @@ impl DisplayListBuilder<'_> {
                         info.origin,
                         *parent_spatial_node_id,
                         info.transform_style,
-                        PropertyBinding::Value(info.transform),
+                        match info.transform_binding {
+                            Some(key) => PropertyBinding::Binding(key, info.transform),
+                            None => PropertyBinding::Value(info.transform),
+                        },
                         info.kind,
                         spatial_tree_item_key,
                     );
@@ -369,6 +555,10 @@ impl DisplayListBuilder<'_> {
             return None;
         }
 
+        // WebRender assigns clip ids in display list order, so items that define clips
+        // can't be reused in later display lists.
+        self.current_item_group_is_unretainable = true;
+
         Some(self.add_clip_to_display_list(&Clip {
             id: ClipId(self.clip_map.len()),
             radii,
@@ -388,7 +578,9 @@ impl DisplayListBuilder<'_> {
         // for fragments that paint their entire border rectangle.
         wr::CommonItemProperties {
             clip_rect,
-            spatial_id: self.spatial_id(self.current_scroll_node_id),
+            spatial_id: self
+                .current_line_relative_spatial_id
+                .unwrap_or_else(|| self.spatial_id(self.current_scroll_node_id)),
             clip_chain_id: self.clip_chain_id(self.current_clip_id),
             flags: style.get_webrender_primitive_flags(),
         }
@@ -412,7 +604,10 @@ impl DisplayListBuilder<'_> {
             Some(cursor(inherited_ui.cursor.keyword, auto_cursor)),
             self.current_scroll_node_id,
         );
-        Some((hit_test_index as u64, self.compositor_info.epoch.as_u16()))
+        Some((
+            hit_test_index as u64,
+            self.compositor_info.hit_test_epoch.as_u16(),
+        ))
     }
 
     /// Draw highlights around the node that is currently hovered in the devtools.
@@ -641,6 +836,7 @@ impl Fragment {
                         let common = builder.common_properties(clip, &image.style);
 
                         if let Some(image_key) = image.image_key {
+                            builder.mark_is_largest_contentful_paint_candidate(&rect);
                             builder.wr().push_image(
                                 &common,
                                 rect,
@@ -662,6 +858,19 @@ impl Fragment {
                         builder.mark_is_contentful();
                         let rect = iframe.rect.translate(containing_block.origin.to_vector());
 
+                        // Hit testing a single pipeline, as done for `elementFromPoint()`,
+                        // doesn't see the contents of the nested pipeline, which may well
+                        // live in another process. Hits on those contents resolve to this
+                        // item, so that they find the `<iframe>` element in this pipeline,
+                        // like the compositor finds the nested pipeline.
+                        self.maybe_push_hit_test_for_style_and_tag(
+                            builder,
+                            &iframe.style,
+                            iframe.base.tag,
+                            rect,
+                            Cursor::Default,
+                        );
+
                         let common = builder.common_properties(rect.to_webrender(), &iframe.style);
                         builder.wr().push_iframe(
                             rect.to_webrender(),
@@ -678,8 +887,11 @@ impl Fragment {
                     Visibility::Collapse => (),
                 }
             },
-            Fragment::Text(text) => {
-                let text = &*text.borrow();
+            Fragment::Text(text_fragment) => {
+                let prepared_glyphs = builder
+                    .prepared_text_fragments
+                    .take(text_fragment.address());
+                let text = &*text_fragment.borrow();
                 match text
                     .inline_styles
                     .style
@@ -689,6 +901,7 @@ impl Fragment {
                 {
                     Visibility::Visible => self.build_display_list_for_text_fragment(
                         text,
+                        prepared_glyphs,
                         builder,
                         containing_block,
                         text_decorations,
@@ -716,8 +929,9 @@ impl Fragment {
 
         let clip_chain_id = builder.clip_chain_id(builder.current_clip_id);
         let spatial_id = builder.spatial_id(builder.current_scroll_node_id);
+        let rect = builder.inflate_by_filter_outset(rect.to_webrender());
         builder.wr().push_hit_test(
-            rect.to_webrender(),
+            rect,
             clip_chain_id,
             spatial_id,
             style.get_webrender_primitive_flags(),
@@ -728,6 +942,7 @@ impl Fragment {
     fn build_display_list_for_text_fragment(
         &self,
         fragment: &TextFragment,
+        prepared_glyphs: Option<PreparedGlyphs>,
         builder: &mut DisplayListBuilder,
         containing_block: &PhysicalRect<Au>,
         text_decorations: &Arc<Vec<FragmentTextDecoration>>,
@@ -735,53 +950,93 @@ impl Fragment {
         // NB: The order of painting text components (CSS Text Decoration Module Level 3) is:
         // shadows, underline, overline, text, text-emphasis, and then line-through.
 
-        builder.mark_is_contentful();
-
-        let rect = fragment.rect.translate(containing_block.origin.to_vector());
+        let parent_style = fragment.inline_styles.style.borrow();
+        let physical_rect = fragment.rect.translate(containing_block.origin.to_vector());
+        builder.mark_is_largest_contentful_paint_candidate(&physical_rect.to_webrender());
+
+        // Text in vertical writing modes is painted in a line-relative coordinate space,
+        // so that everything but upright glyphs can be painted like horizontal text.
+        let line_relative_space = parent_style
+            .writing_mode
+            .is_vertical()
+            .then(|| LineRelativeSpace::for_vertical_text(&physical_rect));
+        let (rect, containing_block) = match &line_relative_space {
+            Some(space) => (
+                space.rect_from_physical(&physical_rect),
+                space.rect_from_physical(containing_block),
+            ),
+            None => (physical_rect, *containing_block),
+        };
         let mut baseline_origin = rect.origin;
         baseline_origin.y += fragment.font_metrics.ascent;
 
-        let glyphs = glyphs(
-            &fragment.glyphs,
-            baseline_origin,
-            fragment.justification_adjustment,
-            !fragment.has_selection(),
-        );
-        if glyphs.is_empty() {
+        // Upright glyphs are centered on the central baseline of the fragment.
+        let upright_origin = PhysicalPoint::new(physical_rect.center().x, physical_rect.min_y());
+        let prepared_glyphs = prepared_glyphs.unwrap_or_else(|| {
+            PreparedGlyphs::new(
+                &fragment.glyphs,
+                fragment.justification_adjustment,
+                !fragment.has_selection(),
+            )
+        });
+        let (glyphs, upright_glyphs) = prepared_glyphs.position(baseline_origin, upright_origin);
+        if glyphs.is_empty() && upright_glyphs.is_empty() {
             return;
         }
 
-        let parent_style = fragment.inline_styles.style.borrow();
         self.maybe_push_hit_test_for_style_and_tag(
             builder,
             &parent_style,
             fragment.base.tag,
-            rect,
+            physical_rect,
             Cursor::Text,
         );
 
+        if let Some(space) = &line_relative_space {
+            space.push(builder);
+        }
+
+        // Highlighted text gets the background color of its highlight, which is painted
+        // below the text.
+        for (range, kind) in fragment.highlighted_ranges.iter() {
+            let start = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.begin(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let end = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.end(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let highlight_rect = LayoutRect::new(
+                Point2D::new(start.x.to_f32_px(), containing_block.min_y().to_f32_px()),
+                Point2D::new(end.x.to_f32_px(), containing_block.max_y().to_f32_px()),
+            );
+            let highlight_color = match kind {
+                HighlightKind::Custom | HighlightKind::FindMatch => MARK_COLOR,
+                HighlightKind::ActiveFindMatch => ACTIVE_FIND_MATCH_COLOR,
+            };
+            let highlight_common = builder.common_properties(highlight_rect, &parent_style);
+            builder
+                .wr()
+                .push_rect(&highlight_common, highlight_rect, highlight_color);
+        }
+
         let color = parent_style.clone_color();
         let font_metrics = &fragment.font_metrics;
         let dppx = builder.device_pixel_ratio.get();
         let common = builder.common_properties(rect.to_webrender(), &parent_style);
 
-        // Shadows. According to CSS-BACKGROUNDS, text shadows render in *reverse* order (front to
-        // back).
         let shadows = &parent_style.get_inherited_text().text_shadow;
-        for shadow in shadows.0.iter().rev() {
-            builder.wr().push_shadow(
-                &wr::SpaceAndClipInfo {
-                    spatial_id: common.spatial_id,
-                    clip_chain_id: common.clip_chain_id,
-                },
-                wr::Shadow {
-                    offset: LayoutVector2D::new(shadow.horizontal.px(), shadow.vertical.px()),
-                    color: rgba(shadow.color.resolve_to_absolute(&color)),
-                    blur_radius: shadow.blur.px(),
-                },
-                true, /* should_inflate */
-            );
-        }
+        push_text_shadows(
+            builder,
+            &common,
+            &parent_style,
+            line_relative_space.as_ref(),
+        );
 
         for text_decoration in text_decorations.iter() {
             if text_decoration.line.contains(TextDecorationLine::UNDERLINE) {
@@ -815,8 +1070,8 @@ impl Fragment {
             }
         }
 
-        // TODO: This caret/text selection implementation currently does not account for vertical text
-        // and RTL text properly.
+        // TODO: This caret/text selection implementation currently does not account for RTL text
+        // properly.
         if let Some(range) = fragment.selection_range {
             let baseline_origin = rect.origin;
             if !range.is_empty() {
@@ -880,14 +1135,81 @@ impl Fragment {
             }
         }
 
-        builder.wr().push_text(
-            &common,
-            rect.to_webrender(),
-            &glyphs,
-            fragment.font_key,
-            rgba(color),
-            None,
-        );
+        // Text that an input method is composing is underlined until it is committed.
+        if let Some(range) = fragment.composition_range {
+            let start = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.begin(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let end = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.end(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let underline_top = rect.origin.y + font_metrics.ascent - font_metrics.underline_offset;
+            let underline_height =
+                Au::from_f32_px(font_metrics.underline_size.to_nearest_pixel(dppx));
+            let underline_rect = LayoutRect::new(
+                Point2D::new(start.x.to_f32_px(), underline_top.to_f32_px()),
+                Point2D::new(
+                    end.x.to_f32_px(),
+                    (underline_top + underline_height).to_f32_px(),
+                ),
+            );
+            let underline_common = builder.common_properties(underline_rect, &parent_style);
+            builder
+                .wr()
+                .push_rect(&underline_common, underline_rect, rgba(color));
+        }
+
+        // Words that the spellchecker flagged are marked with a red squiggle below them.
+        for range in fragment.misspelled_ranges.iter() {
+            let start = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.begin(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let end = glyphs_advance_by_index(
+                &fragment.glyphs,
+                range.end(),
+                rect.origin,
+                fragment.justification_adjustment,
+            );
+            let line_thickness = font_metrics.underline_size.to_nearest_pixel(dppx).max(1.0);
+            let underline_top = rect.origin.y + font_metrics.ascent - font_metrics.underline_offset;
+            let squiggle_rect = LayoutRect::new(
+                Point2D::new(start.x.to_f32_px(), underline_top.to_f32_px()),
+                Point2D::new(
+                    end.x.to_f32_px(),
+                    underline_top.to_f32_px() + line_thickness,
+                ),
+            )
+            .inflate(0.0, line_thickness);
+            let squiggle_common = builder.common_properties(squiggle_rect, &parent_style);
+            builder.wr().push_line(
+                &squiggle_common,
+                &squiggle_rect,
+                line_thickness,
+                wr::LineOrientation::Horizontal,
+                &SPELLING_ERROR_COLOR,
+                wr::LineStyle::Wavy,
+            );
+        }
+
+        if !glyphs.is_empty() {
+            builder.wr().push_text(
+                &common,
+                rect.to_webrender(),
+                &glyphs,
+                fragment.font_key,
+                rgba(color),
+                None,
+            );
+        }
 
         for text_decoration in text_decorations.iter() {
             if text_decoration
@@ -911,6 +1233,28 @@ impl Fragment {
         if !shadows.0.is_empty() {
             builder.wr().pop_all_shadows();
         }
+
+        let Some(space) = line_relative_space else {
+            return;
+        };
+        space.pop(builder);
+        if upright_glyphs.is_empty() {
+            return;
+        }
+
+        let common = builder.common_properties(physical_rect.to_webrender(), &parent_style);
+        push_text_shadows(builder, &common, &parent_style, None);
+        builder.wr().push_text(
+            &common,
+            physical_rect.to_webrender(),
+            &upright_glyphs,
+            fragment.font_key,
+            rgba(color),
+            None,
+        );
+        if !shadows.0.is_empty() {
+            builder.wr().pop_all_shadows();
+        }
     }
 
     fn build_display_list_for_text_decoration(
@@ -1130,11 +1474,42 @@ impl<'a> BuilderForBoxFragment<'a> {
             return;
         }
 
+        self.build_backdrop_filter(builder);
         self.build_background(builder);
         self.build_box_shadow(builder);
         self.build_border(builder);
     }
 
+    /// Filter what has been painted behind the border box of this fragment so far, up to its
+    /// Backdrop Root. See <https://drafts.fxtf.org/filter-effects-2/#BackdropFilterProperty>.
+    ///
+    /// The filtered backdrop does not extend beyond the border box, so unlike `filter`, this
+    /// does not change the area painted by the fragment.
+    fn build_backdrop_filter(&mut self, builder: &mut DisplayListBuilder) {
+        let style = &self.fragment.style;
+        let backdrop_filter = &style.get_effects().backdrop_filter;
+        if backdrop_filter.0.is_empty() {
+            return;
+        }
+
+        let current_color = style.clone_color();
+        let filters: Vec<wr::FilterOp> = backdrop_filter
+            .0
+            .iter()
+            .map(|filter| FilterToWebRender::to_webrender(filter, &current_color))
+            .collect();
+        let mut common = builder.common_properties(self.border_rect, style);
+        if let Some(clip_chain_id) = self.border_edge_clip(builder, false) {
+            common.clip_chain_id = clip_chain_id;
+        }
+        builder.wr().push_backdrop_filter(
+            &common,
+            &filters,
+            &[], // filter_datas
+            &[], // filter_primitives
+        );
+    }
+
     fn build_hit_test(&self, builder: &mut DisplayListBuilder, rect: LayoutRect) {
         let hit_info = builder.hit_info(
             &self.fragment.style,
@@ -1146,6 +1521,7 @@ impl<'a> BuilderForBoxFragment<'a> {
             None => return,
         };
 
+        let rect = builder.inflate_by_filter_outset(rect);
         let mut common = builder.common_properties(rect, &self.fragment.style);
         if let Some(clip_chain_id) = self.border_edge_clip(builder, false) {
             common.clip_chain_id = clip_chain_id;
@@ -1556,19 +1932,54 @@ impl<'a> BuilderForBoxFragment<'a> {
             .max(-self.border_rect.width() / 2.0)
             .max(-self.border_rect.height() / 2.0) +
             width;
-        let outline_rect = self.border_rect.inflate(offset, offset);
-        let common = builder.common_properties(outline_rect, &self.fragment.style);
-        let widths = SideOffsets2D::new_all_same(width);
+        let color = style.resolve_color(&outline.outline_color);
         let border_style = match outline.outline_style {
-            // TODO: treating 'auto' as 'solid' is allowed by the spec,
-            // but we should do something better.
-            OutlineStyle::Auto => BorderStyle::Solid,
-            OutlineStyle::BorderStyle(s) => s,
+            // The focus ring of `outline-style: auto` is a ring in the outline color, with a
+            // thin halo of a contrasting color around it so that it can be seen on any
+            // background. Like other outlines, it follows the border radius of the box.
+            OutlineStyle::Auto => {
+                self.push_outline_ring(
+                    builder,
+                    offset,
+                    width,
+                    BorderStyleColor {
+                        style: BorderStyle::Solid,
+                        color,
+                    },
+                );
+                self.push_outline_ring(
+                    builder,
+                    offset + FOCUS_RING_HALO_WIDTH,
+                    FOCUS_RING_HALO_WIDTH,
+                    BorderStyleColor {
+                        style: BorderStyle::Solid,
+                        color: contrasting_color(color),
+                    },
+                );
+                return;
+            },
+            OutlineStyle::BorderStyle(border_style) => border_style,
         };
-        let side = self.build_border_side(BorderStyleColor {
+        let style_color = BorderStyleColor {
             style: border_style,
-            color: style.resolve_color(&outline.outline_color),
-        });
+            color,
+        };
+        self.push_outline_ring(builder, offset, width, style_color);
+    }
+
+    /// Push a ring of the given width whose outer edge is `offset` outside the border box,
+    /// with the border radius of the box grown or shrunk to match.
+    fn push_outline_ring(
+        &mut self,
+        builder: &mut DisplayListBuilder,
+        offset: f32,
+        width: f32,
+        style_color: BorderStyleColor,
+    ) {
+        let outline_rect = self.border_rect.inflate(offset, offset);
+        let common = builder.common_properties(outline_rect, &self.fragment.style);
+        let widths = SideOffsets2D::new_all_same(width);
+        let side = self.build_border_side(style_color);
         let details = wr::BorderDetails::Normal(wr::NormalBorder {
             top: side,
             right: side,
@@ -1614,6 +2025,21 @@ impl<'a> BuilderForBoxFragment<'a> {
     }
 }
 
+/// The width of the halo around the focus ring of `outline-style: auto`.
+const FOCUS_RING_HALO_WIDTH: f32 = 1.0;
+
+/// Black or white, whichever stands out more against the given color.
+fn contrasting_color(color: AbsoluteColor) -> AbsoluteColor {
+    let srgb = color.to_color_space(ColorSpace::Srgb);
+    let luminance =
+        0.2126 * srgb.components.0 + 0.7152 * srgb.components.1 + 0.0722 * srgb.components.2;
+    if luminance > 0.5 {
+        AbsoluteColor::BLACK
+    } else {
+        AbsoluteColor::WHITE
+    }
+}
+
 fn rgba(color: AbsoluteColor) -> wr::ColorF {
     let rgba = color.to_color_space(ColorSpace::Srgb);
     wr::ColorF::new(
@@ -1624,38 +2050,94 @@ fn rgba(color: AbsoluteColor) -> wr::ColorF {
     )
 }
 
-fn glyphs(
-    glyph_runs: &[Arc<GlyphStore>],
-    mut baseline_origin: PhysicalPoint<Au>,
-    justification_adjustment: Au,
-    ignore_whitespace: bool,
-) -> Vec<wr::GlyphInstance> {
-    use fonts_traits::ByteIndex;
-    use range::Range;
+/// Push the `text-shadow`s of the given style for text painted with `common`. Shadow offsets
+/// are physical, so they are rotated when painting in a [`LineRelativeSpace`].
+fn push_text_shadows(
+    builder: &mut DisplayListBuilder,
+    common: &CommonItemProperties,
+    style: &ComputedValues,
+    line_relative_space: Option<&LineRelativeSpace>,
+) {
+    // According to CSS-BACKGROUNDS, text shadows render in *reverse* order (front to back).
+    let color = style.clone_color();
+    for shadow in style.get_inherited_text().text_shadow.0.iter().rev() {
+        let mut offset = LayoutVector2D::new(shadow.horizontal.px(), shadow.vertical.px());
+        if let Some(space) = line_relative_space {
+            offset = space.vector_from_physical(offset);
+        }
+        builder.wr().push_shadow(
+            &wr::SpaceAndClipInfo {
+                spatial_id: common.spatial_id,
+                clip_chain_id: common.clip_chain_id,
+            },
+            wr::Shadow {
+                offset,
+                color: rgba(shadow.color.resolve_to_absolute(&color)),
+                blur_radius: shadow.blur.px(),
+            },
+            true, /* should_inflate */
+        );
+    }
+}
 
-    let mut glyphs = vec![];
-    for run in glyph_runs {
-        for glyph in run.iter_glyphs_for_byte_range(&Range::new(ByteIndex(0), run.len())) {
-            if !run.is_whitespace() || !ignore_whitespace {
-                let glyph_offset = glyph.offset().unwrap_or(Point2D::zero());
-                let point = units::LayoutPoint::new(
-                    baseline_origin.x.to_f32_px() + glyph_offset.x.to_f32_px(),
-                    baseline_origin.y.to_f32_px() + glyph_offset.y.to_f32_px(),
-                );
-                let glyph = wr::GlyphInstance {
-                    index: glyph.id(),
-                    point,
-                };
-                glyphs.push(glyph);
-            }
+/// A coordinate space for painting text in vertical writing modes. It is rotated 90 degrees
+/// clockwise around the top right corner of a text fragment, so that the inline axis runs
+/// rightward and the line-over side of the text is at the top, like in horizontal text.
+///
+/// TODO: `sideways-lr` text, which is rotated counter-clockwise, is not supported yet.
+struct LineRelativeSpace {
+    origin: PhysicalPoint<Au>,
+}
 
-            if glyph.char_is_word_separator() {
-                baseline_origin.x += justification_adjustment;
-            }
-            baseline_origin.x += glyph.advance();
+impl LineRelativeSpace {
+    fn for_vertical_text(rect: &PhysicalRect<Au>) -> Self {
+        Self {
+            origin: PhysicalPoint::new(rect.max_x(), rect.min_y()),
         }
     }
-    glyphs
+
+    fn rect_from_physical(&self, rect: &PhysicalRect<Au>) -> PhysicalRect<Au> {
+        PhysicalRect::new(
+            PhysicalPoint::new(rect.min_y() - self.origin.y, self.origin.x - rect.max_x()),
+            PhysicalSize::new(rect.height(), rect.width()),
+        )
+    }
+
+    fn vector_from_physical(&self, vector: LayoutVector2D) -> LayoutVector2D {
+        LayoutVector2D::new(vector.y, -vector.x)
+    }
+
+    /// Define the reference frame of this space and make the display items built after
+    /// this call, until [`Self::pop`], use it.
+    fn push(&self, builder: &mut DisplayListBuilder) {
+        // Like clips, spatial ids are assigned in display list order.
+        builder.current_item_group_is_unretainable = true;
+        let spatial_tree_item_key = builder.next_spatial_tree_item_key();
+        let parent_spatial_id = builder.spatial_id(builder.current_scroll_node_id);
+        let spatial_id = builder.wr().push_reference_frame(
+            self.origin.to_webrender(),
+            parent_spatial_id,
+            wr::TransformStyle::Flat,
+            PropertyBinding::Value(LayoutTransform::rotation(
+                0.,
+                0.,
+                1.,
+                euclid::Angle::frac_pi_2(),
+            )),
+            wr::ReferenceFrameKind::Transform {
+                is_2d_scale_translation: false,
+                should_snap: false,
+                paired_with_perspective: false,
+            },
+            spatial_tree_item_key,
+        );
+        builder.wr().pop_reference_frame();
+        builder.current_line_relative_spatial_id = Some(spatial_id);
+    }
+
+    fn pop(&self, builder: &mut DisplayListBuilder) {
+        builder.current_line_relative_spatial_id = None;
+    }
 }
 
 // TODO: The implementation here does not account for multiple glyph runs properly.
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Parallel preparation of display items ahead of painting.
+//!
+//! A WebRender display list is built by a single [`wr::DisplayListBuilder`], which assigns
+//! the ids of spatial nodes and clips in the order they are defined, so display items
+//! themselves have to be pushed one after another, in paint order. Converting the glyph
+//! runs of text fragments into WebRender glyph instances is the most expensive part of
+//! painting most pages though, and it only depends on the fragments themselves.
+//!
+//! Before painting, the text fragments of all stacking contexts of a
+//! [`StackingContextTree`](super::StackingContextTree) that are going to be painted are
+//! collected and their glyphs are prepared by the workers of the layout thread pool. The
+//! partial results of the workers are merged in order into [`PreparedTextFragments`] and
+//! painting then only needs to move the prepared glyphs to the position of each fragment.
+
+use std::sync::Arc;
+
+use app_units::Au;
+use euclid::Vector2D;
+use fonts::GlyphStore;
+use fxhash::FxHashMap;
+use rayon::ThreadPool;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use style::Zero;
+use webrender_api as wr;
+
+use super::{StackingContext, StackingContextContent};
+use crate::cell::ArcRefCell;
+use crate::fragment_tree::{Fragment, TextFragment};
+use crate::geom::PhysicalPoint;
+
+/// A glyph of a text fragment, positioned relative to the start of its text.
+struct PreparedGlyph {
+    index: wr::GlyphIndex,
+    /// The advance of all glyphs before this one, along the inline axis of the text.
+    inline_advance: Au,
+    /// The offset of the glyph from the pen position, if the font positions it.
+    offset: PhysicalPoint<Au>,
+}
+
+impl PreparedGlyph {
+    fn instance(&self, origin: PhysicalPoint<Au>) -> wr::GlyphInstance {
+        wr::GlyphInstance {
+            index: self.index,
+            point: wr::units::LayoutPoint::new(
+                origin.x.to_f32_px() + self.offset.x.to_f32_px(),
+                origin.y.to_f32_px() + self.offset.y.to_f32_px(),
+            ),
+        }
+    }
+}
+
+/// The glyphs of a text fragment, which have not yet been positioned in the display list.
+#[derive(Default)]
+pub(crate) struct PreparedGlyphs {
+    /// Glyphs which are laid out along the baseline, which is horizontal in the
+    /// line-relative coordinate space that text is painted in.
+    glyphs: Vec<PreparedGlyph>,
+    /// Glyphs of upright runs in vertical text, which are laid out downwards from the
+    /// top of the fragment.
+    upright_glyphs: Vec<PreparedGlyph>,
+}
+
+impl PreparedGlyphs {
+    pub(crate) fn new(
+        glyph_runs: &[Arc<GlyphStore>],
+        justification_adjustment: Au,
+        ignore_whitespace: bool,
+    ) -> Self {
+        use fonts_traits::ByteIndex;
+        use range::Range;
+
+        let mut prepared_glyphs = Self::default();
+        let mut inline_advance = Au::zero();
+        for run in glyph_runs {
+            for glyph in run.iter_glyphs_for_byte_range(&Range::new(ByteIndex(0), run.len())) {
+                if !run.is_whitespace() || !ignore_whitespace {
+                    let output = if run.is_vertical() {
+                        &mut prepared_glyphs.upright_glyphs
+                    } else {
+                        &mut prepared_glyphs.glyphs
+                    };
+                    let offset = glyph.offset().unwrap_or_default();
+                    output.push(PreparedGlyph {
+                        index: glyph.id(),
+                        inline_advance,
+                        offset: PhysicalPoint::new(offset.x, offset.y),
+                    });
+                }
+
+                if glyph.char_is_word_separator() {
+                    inline_advance += justification_adjustment;
+                }
+                inline_advance += glyph.advance();
+            }
+        }
+        prepared_glyphs
+    }
+
+    fn for_text_fragment(fragment: &TextFragment) -> Self {
+        Self::new(
+            &fragment.glyphs,
+            fragment.justification_adjustment,
+            !fragment.has_selection(),
+        )
+    }
+
+    /// Position these glyphs for a fragment whose baseline starts at `baseline_origin` and
+    /// whose upright glyphs start at `upright_origin`.
+    pub(crate) fn position(
+        &self,
+        baseline_origin: PhysicalPoint<Au>,
+        upright_origin: PhysicalPoint<Au>,
+    ) -> (Vec<wr::GlyphInstance>, Vec<wr::GlyphInstance>) {
+        let glyphs = self
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                glyph.instance(baseline_origin + Vector2D::new(glyph.inline_advance, Au::zero()))
+            })
+            .collect();
+        let upright_glyphs = self
+            .upright_glyphs
+            .iter()
+            .map(|glyph| {
+                glyph.instance(upright_origin + Vector2D::new(Au::zero(), glyph.inline_advance))
+            })
+            .collect();
+        (glyphs, upright_glyphs)
+    }
+}
+
+/// The [`PreparedGlyphs`] of the text fragments of a stacking context tree, keyed by the
+/// identity of their fragment.
+#[derive(Default)]
+pub(crate) struct PreparedTextFragments(FxHashMap<usize, PreparedGlyphs>);
+
+impl PreparedTextFragments {
+    /// Prepare the glyphs of the text fragments of all contents of `root_stacking_context`
+    /// and its descendants for which `will_paint` returns true, using `thread_pool`.
+    pub(crate) fn prepare(
+        thread_pool: &ThreadPool,
+        root_stacking_context: &StackingContext,
+        will_paint: impl Fn(&StackingContextContent) -> bool,
+    ) -> Self {
+        let mut text_fragments: Vec<(usize, ArcRefCell<TextFragment>)> = Vec::new();
+        root_stacking_context.for_each_content(&mut |content| {
+            if let StackingContextContent::Fragment {
+                fragment: Fragment::Text(text_fragment),
+                ..
+            } = content
+            {
+                if will_paint(content) {
+                    text_fragments.push((text_fragment.address(), text_fragment.clone()));
+                }
+            }
+        });
+
+        let prepared_glyphs: Vec<_> = thread_pool.install(|| {
+            text_fragments
+                .into_par_iter()
+                .map(|(identity, text_fragment)| {
+                    (
+                        identity,
+                        PreparedGlyphs::for_text_fragment(&text_fragment.borrow()),
+                    )
+                })
+                .collect()
+        });
+        Self(prepared_glyphs.into_iter().collect())
+    }
+
+    /// Take the prepared glyphs of the text fragment with the given identity, if they were
+    /// prepared.
+    pub(crate) fn take(&mut self, identity: usize) -> Option<PreparedGlyphs> {
+        self.0.remove(&identity)
+    }
+}
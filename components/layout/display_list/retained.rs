@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Retaining display items between the WebRender display lists built for a
+//! [`StackingContextTree`](super::StackingContextTree).
+//!
+//! As long as a stacking context tree is not rebuilt, the only thing that can change
+//! between two display lists built for it is the style of the fragments that it paints.
+//! The contents of each stacking context are split into item groups, runs of fragments
+//! that are painted one after another without any stacking context in between. Each group
+//! is assigned a WebRender [`ItemKey`], which makes WebRender keep its display items
+//! around, so that later display lists can refer to them with a single "reuse items"
+//! display item, rather than painting the fragments of the group again. Only groups that
+//! contain a fragment of a node that has been restyled are painted again.
+//!
+//! Items that define new spatial nodes or clips can't be reused, as their ids are
+//! assigned in display list order, so groups that contain them are always painted.
+
+use fxhash::FxHashSet;
+use style::dom::OpaqueNode;
+use webrender_api::ItemKey;
+
+use super::StackingContextContent;
+
+/// The maximum number of hit testing entries, as a multiple of the number of entries
+/// after the last display list that was built from scratch, before retained display
+/// items are discarded. Display lists that reuse items keep adding the entries of their
+/// newly painted items to the existing ones, so this bounds their growth.
+const MAX_HIT_TEST_INFO_GROWTH: usize = 2;
+
+#[derive(Default)]
+pub(crate) struct RetainedDisplayList {
+    /// The item groups of the previous display list, indexed by their [`ItemKey`].
+    groups: Vec<RetainedItemGroup>,
+
+    /// Whether WebRender holds the items of the previous display list built for this
+    /// stacking context tree, so that they can be reused.
+    has_previous_display_list: bool,
+
+    /// The number of hit testing entries after the last display list that was built
+    /// without reusing items.
+    hit_test_info_len_after_full_build: usize,
+}
+
+struct RetainedItemGroup {
+    /// The identity of the first fragment of this group, used to double-check that a
+    /// group with the same key still refers to the same contents.
+    first_fragment: usize,
+
+    /// Whether WebRender retained the items of this group.
+    retained: bool,
+}
+
+impl RetainedDisplayList {
+    /// Whether the next display list can reuse retained items, given the number of hit
+    /// testing entries it would start out with.
+    pub(crate) fn can_reuse_items(&self, hit_test_info_len: usize) -> bool {
+        self.has_previous_display_list &&
+            hit_test_info_len <=
+                self.hit_test_info_len_after_full_build.max(1) * MAX_HIT_TEST_INFO_GROWTH
+    }
+
+    /// Record that a display list was built. `reused_items` is whether it reused any
+    /// retained items, in which case it didn't start with fresh hit testing entries.
+    pub(crate) fn finish_display_list(&mut self, reused_items: bool, hit_test_info_len: usize) {
+        self.has_previous_display_list = true;
+        if !reused_items {
+            self.hit_test_info_len_after_full_build = hit_test_info_len;
+        }
+    }
+
+    /// The number of item keys used by the last display list, which WebRender needs to
+    /// size its cache of retained items.
+    pub(crate) fn cache_size(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Whether the group with the given `key` holds the items for `contents` and those
+    /// items are still up-to-date, given the nodes that were restyled since the previous
+    /// display list.
+    pub(crate) fn can_reuse_group(
+        &self,
+        key: ItemKey,
+        contents: &[&StackingContextContent],
+        dirty_nodes: &FxHashSet<OpaqueNode>,
+    ) -> bool {
+        let Some(group) = self.groups.get(key as usize) else {
+            return false;
+        };
+        group.retained &&
+            group.first_fragment == first_fragment_identity(contents) &&
+            !contents.iter().any(|content| content.is_dirty(dirty_nodes))
+    }
+
+    /// Record the group with the given `key` after its items were painted.
+    pub(crate) fn update_group(
+        &mut self,
+        key: ItemKey,
+        contents: &[&StackingContextContent],
+        retained: bool,
+    ) {
+        let group = RetainedItemGroup {
+            first_fragment: first_fragment_identity(contents),
+            retained,
+        };
+        match self.groups.get_mut(key as usize) {
+            Some(existing_group) => *existing_group = group,
+            None => {
+                debug_assert_eq!(self.groups.len(), key as usize);
+                self.groups.push(group);
+            },
+        }
+    }
+}
+
+fn first_fragment_identity(contents: &[&StackingContextContent]) -> usize {
+    contents
+        .first()
+        .map_or(0, |content| content.fragment_identity())
+}
@@ -4,7 +4,9 @@
 
 use core::f32;
 use std::cell::RefCell;
+use std::iter::{Enumerate, Peekable};
 use std::mem;
+use std::slice::Iter;
 use std::sync::Arc;
 
 use app_units::Au;
@@ -16,6 +18,7 @@ use compositing_traits::display_list::{
 };
 use euclid::SideOffsets2D;
 use euclid::default::{Point2D, Rect, Size2D};
+use fxhash::FxHashSet;
 use log::warn;
 use servo_config::opts::DebugOptions;
 use style::Zero;
@@ -25,9 +28,12 @@ use style::computed_values::mix_blend_mode::T as ComputedMixBlendMode;
 use style::computed_values::overflow_x::T as ComputedOverflow;
 use style::computed_values::position::T as ComputedPosition;
 use style::computed_values::text_decoration_style::T as TextDecorationStyle;
+use style::dom::OpaqueNode;
 use style::values::computed::angle::Angle;
 use style::values::computed::basic_shape::ClipPath;
-use style::values::computed::{ClipRectOrAuto, Length, TextDecorationLine};
+use style::values::computed::{
+    ClipRectOrAuto, Length, TextDecorationLine, Transform as ComputedTransform,
+};
 use style::values::generics::box_::Perspective;
 use style::values::generics::transform::{self, GenericRotate, GenericScale, GenericTranslate};
 use style::values::specified::box_::DisplayOutside;
@@ -38,9 +44,12 @@ use wr::units::{LayoutPixel, LayoutSize};
 
 use super::ClipId;
 use super::clip::StackingContextTreeClipStore;
+use super::compositor_animations::CompositorAnimationSampler;
 use crate::ArcRefCell;
 use crate::display_list::conversions::{FilterToWebRender, ToWebRender};
-use crate::display_list::{BuilderForBoxFragment, DisplayListBuilder, offset_radii};
+use crate::display_list::{
+    BuilderForBoxFragment, DisplayListBuilder, RetainedDisplayList, offset_radii,
+};
 use crate::fragment_tree::{
     BoxFragment, ContainingBlockManager, Fragment, FragmentFlags, FragmentTree,
     PositioningFragment, SpecificLayoutInfo,
@@ -113,6 +122,19 @@ pub(crate) struct StackingContextTree {
     /// for things like `overflow`. More clips may be created later during WebRender
     /// display list construction, but they are never added here.
     pub clip_store: StackingContextTreeClipStore,
+
+    /// The display items that WebRender retained from the previous display list built
+    /// for this [`StackingContextTree`], if any.
+    pub(crate) retained_display_list: RetainedDisplayList,
+
+    /// Samples the animations that the compositor should run while this tree is being
+    /// built, if animations can run in the compositor.
+    compositor_animation_sampler: Option<CompositorAnimationSampler>,
+
+    /// The stacking contexts of the boxes in the top layer, in tree order, which are
+    /// collected while the tree is being built and painted above the rest of the root
+    /// stacking context. See <https://drafts.csswg.org/css-position-4/#top-layer>.
+    top_layer_stacking_contexts: Vec<StackingContext>,
 }
 
 impl StackingContextTree {
@@ -123,6 +145,7 @@ impl StackingContextTree {
         viewport_size: LayoutSize,
         pipeline_id: wr::PipelineId,
         first_reflow: bool,
+        compositor_animation_sampler: Option<CompositorAnimationSampler>,
         debug: &DebugOptions,
     ) -> Self {
         let scrollable_overflow = fragment_tree.scrollable_overflow();
@@ -172,6 +195,9 @@ impl StackingContextTree {
             root_stacking_context: StackingContext::create_root(root_scroll_node_id, debug),
             compositor_info,
             clip_store: Default::default(),
+            retained_display_list: Default::default(),
+            compositor_animation_sampler,
+            top_layer_stacking_contexts: Vec::new(),
         };
 
         let mut root_stacking_context = StackingContext::create_root(root_scroll_node_id, debug);
@@ -185,6 +211,9 @@ impl StackingContextTree {
                 &text_decorations,
             );
         }
+        root_stacking_context
+            .real_stacking_contexts_and_positioned_stacking_containers
+            .append(&mut stacking_context_tree.top_layer_stacking_contexts);
         root_stacking_context.sort();
 
         if debug.dump_stacking_context_tree {
@@ -192,6 +221,10 @@ impl StackingContextTree {
         }
 
         stacking_context_tree.root_stacking_context = root_stacking_context;
+        if let Some(mut sampler) = stacking_context_tree.compositor_animation_sampler.take() {
+            stacking_context_tree.compositor_info.compositor_animations =
+                sampler.take_sampled_animations();
+        }
 
         stacking_context_tree
     }
@@ -203,6 +236,7 @@ impl StackingContextTree {
         transform_style: wr::TransformStyle,
         transform: LayoutTransform,
         kind: wr::ReferenceFrameKind,
+        transform_binding: Option<wr::PropertyBindingKey<LayoutTransform>>,
     ) -> ScrollTreeNodeId {
         self.compositor_info.scroll_tree.add_scroll_tree_node(
             Some(parent_scroll_node_id),
@@ -211,6 +245,7 @@ impl StackingContextTree {
                 transform_style,
                 transform,
                 kind,
+                transform_binding,
             }),
         )
     }
@@ -295,7 +330,27 @@ impl StackingContextContent {
         }
     }
 
-    fn build_display_list(
+    /// An identifier for the fragment of this content, if any, that is the same across
+    /// display lists for as long as the fragment is alive.
+    pub(super) fn fragment_identity(&self) -> usize {
+        match self {
+            Self::Fragment { fragment, .. } => fragment.identity(),
+            Self::AtomicInlineStackingContainer { .. } => 0,
+        }
+    }
+
+    /// Whether this content belongs to one of the nodes in `dirty_nodes` and needs to be
+    /// painted again.
+    pub(super) fn is_dirty(&self, dirty_nodes: &FxHashSet<OpaqueNode>) -> bool {
+        match self {
+            Self::Fragment { fragment, .. } => fragment
+                .tag()
+                .is_some_and(|tag| dirty_nodes.contains(&tag.node)),
+            Self::AtomicInlineStackingContainer { .. } => true,
+        }
+    }
+
+    pub(super) fn build_display_list(
         &self,
         builder: &mut DisplayListBuilder,
         inline_stacking_containers: &[StackingContext],
@@ -359,6 +414,10 @@ pub struct StackingContext {
     /// The type of this stacking context. Used for collecting and sorting.
     context_type: StackingContextType,
 
+    /// The key of the property binding of the opacity of this stacking context, if its
+    /// opacity is animated by the compositor.
+    opacity_binding: Option<wr::PropertyBindingKey<f32>>,
+
     /// The contents that need to be painted in fragment order.
     contents: Vec<StackingContextContent>,
 
@@ -429,6 +488,7 @@ impl StackingContext {
             clip_id,
             initializing_fragment: Some(initializing_fragment),
             context_type,
+            opacity_binding: None,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
             float_stacking_containers: vec![],
@@ -443,6 +503,7 @@ impl StackingContext {
             clip_id: None,
             initializing_fragment: None,
             context_type: StackingContextType::RealStackingContext,
+            opacity_binding: None,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
             float_stacking_containers: vec![],
@@ -468,9 +529,27 @@ impl StackingContext {
         .push(stacking_context)
     }
 
+    /// Call `f` for the contents of this stacking context and all of its descendants.
+    pub(super) fn for_each_content(&self, f: &mut impl FnMut(&StackingContextContent)) {
+        self.contents.iter().for_each(&mut *f);
+        for child in self
+            .real_stacking_contexts_and_positioned_stacking_containers
+            .iter()
+            .chain(&self.float_stacking_containers)
+            .chain(&self.atomic_inline_stacking_containers)
+        {
+            child.for_each_content(f);
+        }
+    }
+
     fn z_index(&self) -> i32 {
         self.initializing_fragment.as_ref().map_or(0, |fragment| {
             let fragment = fragment.borrow();
+            // Boxes in the top layer are painted above everything else, in the order in
+            // which they were collected, whatever their `z-index`.
+            if fragment.style.is_in_top_layer() {
+                return i32::MAX;
+            }
             fragment.style.effective_z_index(fragment.base.flags)
         })
     }
@@ -521,7 +600,9 @@ impl StackingContext {
         let style = &fragment.style;
         let effects = style.get_effects();
         if effects.filter.0.is_empty() &&
+            effects.backdrop_filter.0.is_empty() &&
             effects.opacity == 1.0 &&
+            self.opacity_binding.is_none() &&
             effects.mix_blend_mode == ComputedMixBlendMode::Normal &&
             !style.has_effective_transform_or_perspective(FragmentFlags::empty()) &&
             style.clone_clip_path() == ClipPath::None
@@ -537,11 +618,25 @@ impl StackingContext {
             .iter()
             .map(|filter| FilterToWebRender::to_webrender(filter, &current_color))
             .collect();
-        if effects.opacity != 1.0 {
-            filters.push(wr::FilterOp::Opacity(
-                effects.opacity.into(),
-                effects.opacity,
-            ));
+        if effects.opacity != 1.0 || self.opacity_binding.is_some() {
+            let opacity = match self.opacity_binding {
+                Some(key) => wr::PropertyBinding::Binding(key, effects.opacity),
+                None => effects.opacity.into(),
+            };
+            filters.push(wr::FilterOp::Opacity(opacity, effects.opacity));
+        }
+        builder.filter_outset += filter_outset(&filters);
+
+        // From <https://drafts.fxtf.org/filter-effects-2/#BackdropRoot>, these are the effects
+        // that make an element a Backdrop Root, which limits how far the `backdrop-filter` of
+        // its descendants can read. Transforms alone do not.
+        let mut flags = wr::StackingContextFlags::empty();
+        if !filters.is_empty() ||
+            !effects.backdrop_filter.0.is_empty() ||
+            effects.mix_blend_mode != ComputedMixBlendMode::Normal ||
+            style.clone_clip_path() != ClipPath::None
+        {
+            flags.insert(wr::StackingContextFlags::IS_BACKDROP_ROOT);
         }
 
         // TODO(jdm): WebRender now requires us to create stacking context items
@@ -563,7 +658,7 @@ impl StackingContext {
             &[], // filter_datas
             &[], // filter_primitives
             wr::RasterSpace::Screen,
-            wr::StackingContextFlags::empty(),
+            flags,
             None, // snapshot
         );
 
@@ -648,6 +743,7 @@ impl StackingContext {
     }
 
     pub(crate) fn build_display_list(&self, builder: &mut DisplayListBuilder) {
+        let old_filter_outset = builder.filter_outset;
         let pushed_context = self.push_webrender_stacking_context_if_necessary(builder);
 
         // Properly order display items that make up a stacking context.
@@ -659,13 +755,11 @@ impl StackingContext {
 
         // Steps 1 and 2: Borders and background for the root
         let mut contents = self.contents.iter().enumerate().peekable();
-        while contents.peek().is_some_and(|(_, child)| {
-            child.section() == StackingContextSection::OwnBackgroundsAndBorders
-        }) {
-            let (i, child) = contents.next().unwrap();
-            self.debug_push_print_item(DebugPrintField::Contents, i);
-            child.build_display_list(builder, &self.atomic_inline_stacking_containers);
-        }
+        self.build_section_display_list(
+            builder,
+            &mut contents,
+            StackingContextSection::OwnBackgroundsAndBorders,
+        );
 
         // Step 3: Stacking contexts with negative ‘z-index’
         let mut real_stacking_contexts_and_positioned_stacking_containers = self
@@ -688,13 +782,11 @@ impl StackingContext {
         }
 
         // Step 4: Block backgrounds and borders
-        while contents.peek().is_some_and(|(_, child)| {
-            child.section() == StackingContextSection::DescendantBackgroundsAndBorders
-        }) {
-            let (i, child) = contents.next().unwrap();
-            self.debug_push_print_item(DebugPrintField::Contents, i);
-            child.build_display_list(builder, &self.atomic_inline_stacking_containers);
-        }
+        self.build_section_display_list(
+            builder,
+            &mut contents,
+            StackingContextSection::DescendantBackgroundsAndBorders,
+        );
 
         // Step 5: Float stacking containers
         for (i, child) in self.float_stacking_containers.iter().enumerate() {
@@ -703,14 +795,7 @@ impl StackingContext {
         }
 
         // Steps 6 and 7: Fragments and inline stacking containers
-        while contents
-            .peek()
-            .is_some_and(|(_, child)| child.section() == StackingContextSection::Foreground)
-        {
-            let (i, child) = contents.next().unwrap();
-            self.debug_push_print_item(DebugPrintField::Contents, i);
-            child.build_display_list(builder, &self.atomic_inline_stacking_containers);
-        }
+        self.build_section_display_list(builder, &mut contents, StackingContextSection::Foreground);
 
         // Steps 8 and 9: Stacking contexts with non-negative ‘z-index’, and
         // positioned stacking containers (where ‘z-index’ is auto)
@@ -723,18 +808,36 @@ impl StackingContext {
         }
 
         // Step 10: Outline
-        while contents
-            .peek()
-            .is_some_and(|(_, child)| child.section() == StackingContextSection::Outline)
-        {
-            let (i, child) = contents.next().unwrap();
-            self.debug_push_print_item(DebugPrintField::Contents, i);
-            child.build_display_list(builder, &self.atomic_inline_stacking_containers);
-        }
+        self.build_section_display_list(builder, &mut contents, StackingContextSection::Outline);
 
         if pushed_context {
             builder.wr().pop_stacking_context();
         }
+        builder.filter_outset = old_filter_outset;
+    }
+
+    /// Paint the contents at the front of `contents` that belong to `section`. Runs of
+    /// fragments between inline stacking containers are painted as item groups, so that
+    /// they can be retained between display lists.
+    fn build_section_display_list<'a>(
+        &'a self,
+        builder: &mut DisplayListBuilder,
+        contents: &mut Peekable<Enumerate<Iter<'a, StackingContextContent>>>,
+        section: StackingContextSection,
+    ) {
+        let mut item_group = Vec::new();
+        while let Some((i, child)) = contents.next_if(|(_, child)| child.section() == section) {
+            self.debug_push_print_item(DebugPrintField::Contents, i);
+            match child {
+                StackingContextContent::Fragment { .. } => item_group.push(child),
+                StackingContextContent::AtomicInlineStackingContainer { .. } => {
+                    builder.build_item_group(&item_group);
+                    item_group.clear();
+                    child.build_display_list(builder, &self.atomic_inline_stacking_containers);
+                },
+            }
+        }
+        builder.build_item_group(&item_group);
     }
 
     /// Store the fact that something was painted, if [Self::debug_print_items] is not None.
@@ -998,6 +1101,7 @@ impl BoxFragment {
             self.style.get_box().transform_style.to_webrender(),
             reference_frame_data.transform,
             reference_frame_data.kind,
+            self.compositor_transform_animation(stacking_context_tree, &containing_block.rect),
         );
 
         // WebRender reference frames establish a new coordinate system at their
@@ -1100,6 +1204,13 @@ impl BoxFragment {
             box_fragment,
             context_type,
         );
+        if context_type == StackingContextType::RealStackingContext {
+            child_stacking_context.opacity_binding = self
+                .base
+                .tag
+                .zip(stacking_context_tree.compositor_animation_sampler.as_mut())
+                .and_then(|(tag, sampler)| sampler.sample_opacity_animation(tag));
+        }
         self.build_stacking_context_tree_for_children(
             fragment,
             stacking_context_tree,
@@ -1119,7 +1230,15 @@ impl BoxFragment {
         }
 
         child_stacking_context.sort();
-        parent_stacking_context.add_stacking_context(child_stacking_context);
+        if self.style.is_in_top_layer() {
+            // Boxes in the top layer are painted by the root stacking context, above the
+            // stacking contexts of their ancestors.
+            stacking_context_tree
+                .top_layer_stacking_contexts
+                .push(child_stacking_context);
+        } else {
+            parent_stacking_context.add_stacking_context(child_stacking_context);
+        }
         parent_stacking_context
             .real_stacking_contexts_and_positioned_stacking_containers
             .append(&mut stolen_children);
@@ -1621,9 +1740,44 @@ impl BoxFragment {
         })
     }
 
+    /// If the `transform` of this fragment is animated, sample its animation so that it runs
+    /// in the compositor, returning the key of the property binding of the transform of the
+    /// reference frame of this fragment.
+    fn compositor_transform_animation(
+        &self,
+        stacking_context_tree: &mut StackingContextTree,
+        containing_block_rect: &PhysicalRect<Au>,
+    ) -> Option<wr::PropertyBindingKey<LayoutTransform>> {
+        let tag = self.base.tag?;
+        let sampler = stacking_context_tree
+            .compositor_animation_sampler
+            .as_mut()?;
+        let border_rect = self
+            .border_rect()
+            .translate(containing_block_rect.origin.to_vector())
+            .to_untyped();
+        let perspective = self.calculate_perspective_matrix(&border_rect);
+        sampler.sample_transform_animation(tag, |list| {
+            let transform = self.calculate_transform_matrix_for_list(list, &border_rect)?;
+            Some(match perspective {
+                Some(perspective) => perspective.then(&transform),
+                None => transform,
+            })
+        })
+    }
+
     /// Returns the 4D matrix representing this fragment's transform.
     pub fn calculate_transform_matrix(&self, border_rect: &Rect<Au>) -> Option<LayoutTransform> {
-        let list = &self.style.get_box().transform;
+        self.calculate_transform_matrix_for_list(&self.style.get_box().transform, border_rect)
+    }
+
+    /// Returns the 4D matrix representing this fragment's transform, if its `transform`
+    /// property had the value `list`.
+    fn calculate_transform_matrix_for_list(
+        &self,
+        list: &ComputedTransform,
+        border_rect: &Rect<Au>,
+    ) -> Option<LayoutTransform> {
         let length_rect = au_rect_to_length_rect(border_rect);
         // https://drafts.csswg.org/css-transforms-2/#individual-transforms
         let rotate = match self.style.clone_rotate() {
@@ -1730,3 +1884,18 @@ pub fn au_rect_to_length_rect(rect: &Rect<Au>) -> Rect<Length> {
         Size2D::new(rect.size.width.into(), rect.size.height.into()),
     )
 }
+
+/// How far `filters` spread the contents of a stacking context beyond the area that they
+/// are painted in. WebRender samples a Gaussian blur up to three standard deviations away.
+fn filter_outset(filters: &[wr::FilterOp]) -> f32 {
+    filters
+        .iter()
+        .map(|filter| match filter {
+            wr::FilterOp::Blur(width, height) => 3. * width.max(*height),
+            wr::FilterOp::DropShadow(shadow) => {
+                3. * shadow.blur_radius + shadow.offset.x.abs().max(shadow.offset.y.abs())
+            },
+            _ => 0.,
+        })
+        .sum()
+}
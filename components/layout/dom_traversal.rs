@@ -8,7 +8,7 @@ use std::iter::FusedIterator;
 use fonts::ByteIndex;
 use html5ever::{LocalName, local_name};
 use layout_api::wrapper_traits::{LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode};
-use layout_api::{LayoutDamage, LayoutElementType, LayoutNodeType};
+use layout_api::{HighlightKind, LayoutDamage, LayoutElementType, LayoutNodeType};
 use range::Range;
 use script::layout_dom::ServoLayoutNode;
 use selectors::Element as SelectorsElement;
@@ -81,6 +81,18 @@ impl<'dom> NodeAndStyleInfo<'dom> {
     pub(crate) fn get_selection_range(&self) -> Option<Range<ByteIndex>> {
         self.node.to_threadsafe().selection()
     }
+
+    pub(crate) fn get_composition_range(&self) -> Option<Range<ByteIndex>> {
+        self.node.to_threadsafe().composition()
+    }
+
+    pub(crate) fn get_misspelled_ranges(&self) -> Vec<Range<ByteIndex>> {
+        self.node.to_threadsafe().misspellings()
+    }
+
+    pub(crate) fn get_highlighted_ranges(&self) -> Vec<(Range<ByteIndex>, HighlightKind)> {
+        self.node.to_threadsafe().highlights()
+    }
 }
 
 impl<'dom> From<&NodeAndStyleInfo<'dom>> for BaseFragmentInfo {
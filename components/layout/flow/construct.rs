@@ -14,6 +14,7 @@ use style::str::char_is_whitespace;
 use super::OutsideMarker;
 use super::inline::construct::InlineFormattingContextBuilder;
 use super::inline::inline_box::InlineBox;
+use super::inline::ruby::{RubySegment, RubySegmentsBuilder};
 use super::inline::{InlineFormattingContext, SharedInlineStyles};
 use crate::PropagatedBoxTreeData;
 use crate::cell::ArcRefCell;
@@ -471,6 +472,10 @@ impl<'dom> BlockContainerBuilder<'dom, '_> {
                 DisplayInside::Flow { is_list_item },
                 Contents::NonReplaced(non_replaced_contents),
             ) => (is_list_item, non_replaced_contents),
+            (DisplayInside::Ruby, Contents::NonReplaced(non_replaced_contents)) => {
+                self.handle_ruby_element(info, non_replaced_contents, box_slot);
+                return;
+            },
             (_, contents) => {
                 // If this inline element is an atomic, handle it and return.
                 let context = self.context;
@@ -528,6 +533,37 @@ impl<'dom> BlockContainerBuilder<'dom, '_> {
         ));
     }
 
+    /// Handle an inline-level `display: ruby` element. The ruby container is an inline box
+    /// whose contents are paired into [`RubySegment`]s, each of which is an atomic inline.
+    fn handle_ruby_element(
+        &mut self,
+        info: &NodeAndStyleInfo<'dom>,
+        contents: NonReplacedContents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        self.ensure_inline_formatting_context_builder()
+            .start_inline_box(InlineBox::new(info), None);
+
+        let mut segments_builder = RubySegmentsBuilder::default();
+        contents.traverse(self.context, info, &mut segments_builder);
+        for ruby_segment in RubySegment::construct_segments(
+            self.context,
+            info,
+            segments_builder.finish(),
+            self.propagated_data,
+        ) {
+            self.ensure_inline_formatting_context_builder()
+                .push_atomic(|| ArcRefCell::new(ruby_segment), None);
+        }
+
+        box_slot.set(LayoutBox::InlineLevel(
+            self.inline_formatting_context_builder
+                .as_mut()
+                .expect("Should be building an InlineFormattingContext")
+                .end_inline_box(),
+        ));
+    }
+
     fn handle_block_level_element(
         &mut self,
         info: &NodeAndStyleInfo<'dom>,
@@ -5,13 +5,16 @@
 use std::borrow::Cow;
 use std::char::{ToLowercase, ToUppercase};
 
+use fonts::ByteIndex;
 use icu_segmenter::WordSegmenter;
 use itertools::izip;
+use layout_api::HighlightKind;
+use range::Range as ServoRange;
 use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
 use style::values::specified::text::TextTransformCase;
 use unicode_bidi::Level;
 
-use super::text_run::TextRun;
+use super::text_run::{TextRun, WhitespaceCollapseTable};
 use super::{
     InlineBox, InlineBoxIdentifier, InlineBoxes, InlineFormattingContext, InlineItem,
     SharedInlineStyles,
@@ -341,6 +344,15 @@ impl InlineFormattingContextBuilder {
         }
 
         let selection_range = info.get_selection_range();
+        let composition_range = info.get_composition_range();
+        let misspelled_ranges = info.get_misspelled_ranges();
+        let highlighted_ranges =
+            self.highlighted_ranges_for_text(&text, info, self.current_text_offset);
+        let whitespace_collapse_table = WhitespaceCollapseTable::new(
+            &text,
+            white_space_collapse,
+            self.last_inline_box_ended_with_collapsible_white_space,
+        );
         if let Some(last_character) = new_text.chars().next_back() {
             self.on_word_boundary = last_character.is_whitespace();
             self.last_inline_box_ended_with_collapsible_white_space =
@@ -353,7 +365,12 @@ impl InlineFormattingContextBuilder {
 
         if let Some(inline_item) = self.inline_items.last() {
             if let InlineItem::TextRun(text_run) = &mut *inline_item.borrow_mut() {
-                text_run.borrow_mut().text_range.end = new_range.end;
+                let mut text_run = text_run.borrow_mut();
+                text_run.text_range.end = new_range.end;
+                text_run.highlighted_ranges.extend(highlighted_ranges);
+                let run_offset = new_range.start - text_run.text_range.start;
+                std::sync::Arc::make_mut(&mut text_run.whitespace_collapse_table)
+                    .append(whitespace_collapse_table, run_offset);
                 return;
             }
         }
@@ -365,10 +382,56 @@ impl InlineFormattingContextBuilder {
                     self.shared_inline_styles(),
                     new_range,
                     selection_range,
+                    composition_range,
+                    misspelled_ranges,
+                    highlighted_ranges,
+                    whitespace_collapse_table,
                 ),
             ))));
     }
 
+    /// Translate the highlighted ranges of the DOM text node of `info`, which are offsets in
+    /// `text`, to offsets in the text of this inline formatting context, where `text` starts
+    /// at `text_offset` after its white space has been collapsed.
+    fn highlighted_ranges_for_text(
+        &self,
+        text: &str,
+        info: &NodeAndStyleInfo,
+        text_offset: usize,
+    ) -> Vec<(ServoRange<ByteIndex>, HighlightKind)> {
+        let white_space_collapse = info.style.clone_white_space_collapse();
+        let collapsed_offset = |offset: usize| -> usize {
+            WhitespaceCollapse::new(
+                text[..offset].chars(),
+                white_space_collapse,
+                self.last_inline_box_ended_with_collapsible_white_space,
+            )
+            .map(char::len_utf8)
+            .sum()
+        };
+
+        info.get_highlighted_ranges()
+            .into_iter()
+            .filter_map(|(range, kind)| {
+                let (start, end) = (range.begin().0 as usize, range.end().0 as usize);
+                if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                    return None;
+                }
+                let start = text_offset + collapsed_offset(start);
+                let end = text_offset + collapsed_offset(end);
+                (start < end).then(|| {
+                    (
+                        ServoRange::new(
+                            ByteIndex(start as isize),
+                            ByteIndex((end - start) as isize),
+                        ),
+                        kind,
+                    )
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn enter_display_contents(&mut self, shared_inline_styles: SharedInlineStyles) {
         self.shared_inline_styles_stack.push(shared_inline_styles);
     }
@@ -6,6 +6,7 @@ use app_units::Au;
 use bitflags::bitflags;
 use fonts::{ByteIndex, FontMetrics, GlyphStore};
 use itertools::Either;
+use layout_api::HighlightKind;
 use range::Range;
 use style::Zero;
 use style::computed_values::position::T as Position;
@@ -19,6 +20,7 @@ use unicode_bidi::{BidiInfo, Level};
 use webrender_api::FontInstanceKey;
 
 use super::inline_box::{InlineBoxContainerState, InlineBoxIdentifier, InlineBoxTreePathToken};
+use super::text_run::WhitespaceCollapseTable;
 use super::{InlineFormattingContextLayout, LineBlockSizes, SharedInlineStyles};
 use crate::cell::ArcRefCell;
 use crate::fragment_tree::{BaseFragmentInfo, BoxFragment, Fragment, TextFragment};
@@ -573,6 +575,11 @@ impl LineItemLayout<'_, '_> {
                 glyphs: text_item.text,
                 justification_adjustment: self.justification_adjustment,
                 selection_range: text_item.selection_range,
+                composition_range: text_item.composition_range,
+                misspelled_ranges: text_item.misspelled_ranges,
+                highlighted_ranges: text_item.highlighted_ranges,
+                text_offset: text_item.text_offset,
+                whitespace_collapse_table: text_item.whitespace_collapse_table,
             })),
             content_rect,
         ));
@@ -766,6 +773,13 @@ pub(super) struct TextRunLineItem {
     /// The BiDi level of this [`TextRunLineItem`] to enable reordering.
     pub bidi_level: Level,
     pub selection_range: Option<Range<ByteIndex>>,
+    pub composition_range: Option<Range<ByteIndex>>,
+    pub misspelled_ranges: Vec<Range<ByteIndex>>,
+    pub highlighted_ranges: Vec<(Range<ByteIndex>, HighlightKind)>,
+    /// The UTF-8 offset of the first glyph of this item within the text of its
+    /// originating [`super::TextRun`].
+    pub text_offset: ByteIndex,
+    pub whitespace_collapse_table: std::sync::Arc<WhitespaceCollapseTable>,
 }
 
 impl TextRunLineItem {
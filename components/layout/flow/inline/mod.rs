@@ -72,6 +72,7 @@ pub mod construct;
 pub mod inline_box;
 pub mod line;
 mod line_breaker;
+pub mod ruby;
 pub mod text_run;
 
 use std::cell::{OnceCell, RefCell};
@@ -83,6 +84,7 @@ use bitflags::bitflags;
 use construct::InlineFormattingContextBuilder;
 use fonts::{ByteIndex, FontMetrics, GlyphStore};
 use inline_box::{InlineBox, InlineBoxContainerState, InlineBoxIdentifier, InlineBoxes};
+use layout_api::HighlightKind;
 use layout_api::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
 use line::{
     AbsolutelyPositionedLineItem, AtomicLineItem, FloatLineItem, LineItem, LineItemLayout,
@@ -95,6 +97,7 @@ use script::layout_dom::ServoLayoutNode;
 use servo_arc::Arc;
 use style::Zero;
 use style::computed_values::text_wrap_mode::T as TextWrapMode;
+use style::computed_values::text_wrap_style::T as TextWrapStyle;
 use style::computed_values::vertical_align::T as VerticalAlign;
 use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
 use style::context::{QuirksMode, SharedStyleContext};
@@ -741,9 +744,51 @@ pub(super) struct InlineFormattingContextLayout<'layout_data> {
     /// are laying out. This is used to propagate baselines to the ancestors of
     /// `display: inline-block` elements and table content.
     baselines: Baselines,
+
+    /// A limit on the inline size available for line breaking, used to re-break lines for
+    /// `text-wrap-style: balance` and `text-wrap-style: pretty`.
+    line_break_inline_size_limit: Option<LineBreakInlineSizeLimit>,
+
+    /// Information about the lines that have been laid out so far.
+    line_breaking_summary: LineBreakingSummary,
+}
+
+/// A limit on the inline size available for line breaking. Lines are broken as if the
+/// containing block were narrower, but are still aligned within its full inline size.
+#[derive(Clone, Copy)]
+struct LineBreakInlineSizeLimit {
+    /// The index of the first line that this limit applies to.
+    first_line: usize,
+    inline_size: Au,
+}
+
+/// A summary of the lines produced by laying out an [`InlineFormattingContext`], used to
+/// decide how to re-break them for `text-wrap-style`.
+#[derive(Clone, Copy, Default)]
+struct LineBreakingSummary {
+    line_count: usize,
+    /// The inline size of the content of the last line, excluding trailing whitespace.
+    last_line_inline_size: Au,
 }
 
+/// The maximum number of lines that `text-wrap-style: balance` applies to. Balancing many
+/// lines is expensive and of little visual benefit, so, like other engines, longer
+/// content is laid out normally.
+const MAXIMUM_LINES_TO_BALANCE: usize = 6;
+
 impl InlineFormattingContextLayout<'_> {
+    /// The inline size that the content of the current line needs to fit into, when
+    /// there are no floats to avoid.
+    fn line_break_inline_size(&self) -> Au {
+        let inline_size = self.containing_block.size.inline;
+        match self.line_break_inline_size_limit {
+            Some(limit) if self.line_breaking_summary.line_count >= limit.first_line => {
+                inline_size.min(limit.inline_size)
+            },
+            _ => inline_size,
+        }
+    }
+
     fn current_inline_container_state(&self) -> &InlineContainerState {
         match self.inline_box_state_stack.last() {
             Some(inline_box_state) => &inline_box_state.base,
@@ -901,6 +946,9 @@ impl InlineFormattingContextLayout<'_> {
     /// [`InlineFormattingContextLayout`] preparing it for laying out a new line.
     fn finish_current_line_and_reset(&mut self, last_line_or_forced_line_break: bool) {
         let whitespace_trimmed = self.current_line.trim_trailing_whitespace();
+        let line_inline_size = self.current_line.inline_position -
+            self.current_line.start_position.inline -
+            whitespace_trimmed;
         let (inline_start_position, justification_adjustment) = self
             .calculate_current_line_inline_start_and_justification_adjustment(
                 whitespace_trimmed,
@@ -977,6 +1025,9 @@ impl InlineFormattingContextLayout<'_> {
             return;
         }
 
+        self.line_breaking_summary.line_count += 1;
+        self.line_breaking_summary.last_line_inline_size = line_inline_size;
+
         let baseline = baseline_offset + block_start_position;
         self.baselines.first.get_or_insert(baseline);
         self.baselines.last = Some(baseline);
@@ -1242,7 +1293,7 @@ impl InlineFormattingContextLayout<'_> {
                 .size
         } else {
             LogicalVec2 {
-                inline: self.containing_block.size.inline,
+                inline: self.line_break_inline_size(),
                 block: MAX_AU,
             }
         };
@@ -1275,7 +1326,7 @@ impl InlineFormattingContextLayout<'_> {
 
         // If the potential line is larger than the containing block we do not even need to consider
         // floats. We definitely have to do a linebreak.
-        if potential_line_size.inline > self.containing_block.size.inline {
+        if potential_line_size.inline > self.line_break_inline_size() {
             return true;
         }
 
@@ -1410,6 +1461,13 @@ impl InlineFormattingContextLayout<'_> {
                 if *inline_box_identifier == current_inline_box_identifier &&
                     line_item.can_merge(ifc_font_info.key, bidi_level) =>
             {
+                let line_item_start =
+                    line_item.text_offset + ByteIndex(text_run.text_range.start as isize);
+                line_item.highlighted_ranges.extend(highlighted_ranges_in(
+                    text_run,
+                    &range,
+                    line_item_start,
+                ));
                 line_item.text.push(glyph_store);
                 return;
             },
@@ -1443,6 +1501,24 @@ impl InlineFormattingContextLayout<'_> {
             None
         };
 
+        let composition_range = text_run
+            .composition_range
+            .map(|composition| composition.intersect(&range))
+            .filter(|intersection| !intersection.is_empty())
+            .map(|intersection| {
+                Range::new(intersection.begin() - range.begin(), intersection.length())
+            });
+
+        let misspelled_ranges = text_run
+            .misspelled_ranges
+            .iter()
+            .map(|misspelling| misspelling.intersect(&range))
+            .filter(|intersection| !intersection.is_empty())
+            .map(|intersection| {
+                Range::new(intersection.begin() - range.begin(), intersection.length())
+            })
+            .collect();
+
         self.push_line_item_to_unbreakable_segment(LineItem::TextRun(
             current_inline_box_identifier,
             TextRunLineItem {
@@ -1453,6 +1529,11 @@ impl InlineFormattingContextLayout<'_> {
                 font_key: ifc_font_info.key,
                 bidi_level,
                 selection_range,
+                composition_range,
+                misspelled_ranges,
+                highlighted_ranges: highlighted_ranges_in(text_run, &range, range.begin()),
+                text_offset: range.begin() - ByteIndex(text_run.text_range.start as isize),
+                whitespace_collapse_table: text_run.whitespace_collapse_table.clone(),
             },
         ));
     }
@@ -1711,6 +1792,108 @@ impl InlineFormattingContext {
         sequential_layout_state: Option<&mut SequentialLayoutState>,
         collapsible_with_parent_start_margin: CollapsibleWithParentStartMargin,
     ) -> CacheableLayoutResult {
+        // Re-breaking lines requires laying them out more than once, which isn't possible
+        // when they are placed among floats, as that modifies the `SequentialLayoutState`.
+        let line_break_inline_size_limit = match sequential_layout_state {
+            Some(_) => None,
+            None => self
+                .line_break_inline_size_limit_for_text_wrap_style(layout_context, containing_block),
+        };
+
+        self.layout_with_line_break_inline_size_limit(
+            layout_context,
+            positioning_context,
+            containing_block,
+            sequential_layout_state,
+            collapsible_with_parent_start_margin,
+            line_break_inline_size_limit,
+        )
+        .0
+    }
+
+    /// Determine the [`LineBreakInlineSizeLimit`] to use for `text-wrap-style`, if any.
+    /// This lays out the lines without a limit first and then, if they should be
+    /// re-broken, searches for the narrowest limit that does not produce more lines:
+    ///
+    ///  - For `balance`, the limit applies to all lines, which evens out their lengths.
+    ///  - For `pretty`, the limit only applies to the last two lines and is only used when
+    ///    the last line is very short, which avoids leaving a single word on its own line.
+    ///
+    /// See <https://drafts.csswg.org/css-text-4/#text-wrap-style>.
+    fn line_break_inline_size_limit_for_text_wrap_style(
+        &self,
+        layout_context: &LayoutContext,
+        containing_block: &ContainingBlock,
+    ) -> Option<LineBreakInlineSizeLimit> {
+        let style_text = containing_block.style.get_inherited_text();
+        if style_text.text_wrap_mode != TextWrapMode::Wrap ||
+            !matches!(
+                style_text.text_wrap_style,
+                TextWrapStyle::Balance | TextWrapStyle::Pretty
+            )
+        {
+            return None;
+        }
+
+        let lay_out_lines = |line_break_inline_size_limit| {
+            self.layout_with_line_break_inline_size_limit(
+                layout_context,
+                &mut PositioningContext::default(),
+                containing_block,
+                None,
+                CollapsibleWithParentStartMargin(false),
+                line_break_inline_size_limit,
+            )
+            .1
+        };
+
+        let available_inline_size = containing_block.size.inline;
+        let summary = lay_out_lines(None);
+        let first_line = match style_text.text_wrap_style {
+            TextWrapStyle::Balance
+                if (2..=MAXIMUM_LINES_TO_BALANCE).contains(&summary.line_count) =>
+            {
+                0
+            },
+            TextWrapStyle::Pretty
+                if summary.line_count >= 2 &&
+                    summary.last_line_inline_size < available_inline_size / 3 =>
+            {
+                summary.line_count - 2
+            },
+            _ => return None,
+        };
+
+        let mut too_narrow = Au::zero();
+        let mut wide_enough = available_inline_size;
+        while wide_enough - too_narrow > Au::from_px(1) {
+            let inline_size = (too_narrow + wide_enough) / 2;
+            let limit = LineBreakInlineSizeLimit {
+                first_line,
+                inline_size,
+            };
+            if lay_out_lines(Some(limit)).line_count > summary.line_count {
+                too_narrow = inline_size;
+            } else {
+                wide_enough = inline_size;
+            }
+        }
+
+        Some(LineBreakInlineSizeLimit {
+            first_line,
+            inline_size: wide_enough,
+        })
+    }
+
+    fn layout_with_line_break_inline_size_limit(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        sequential_layout_state: Option<&mut SequentialLayoutState>,
+        collapsible_with_parent_start_margin: CollapsibleWithParentStartMargin,
+        line_break_inline_size_limit: Option<LineBreakInlineSizeLimit>,
+    ) -> (CacheableLayoutResult, LineBreakingSummary) {
         let first_line_inline_start = if self.has_first_formatted_line {
             containing_block
                 .style
@@ -1773,6 +1956,8 @@ impl InlineFormattingContext {
             white_space_collapse: style_text.white_space_collapse,
             text_wrap_mode: style_text.text_wrap_mode,
             baselines: Baselines::default(),
+            line_break_inline_size_limit,
+            line_breaking_summary: LineBreakingSummary::default(),
         };
 
         // FIXME(pcwalton): This assumes that margins never collapse through inline formatting
@@ -1826,7 +2011,7 @@ impl InlineFormattingContext {
             content_block_size.is_zero() &&
             collapsible_with_parent_start_margin.0;
 
-        CacheableLayoutResult {
+        let result = CacheableLayoutResult {
             fragments: layout.fragments,
             content_block_size,
             collapsible_margins_in_children,
@@ -1834,7 +2019,8 @@ impl InlineFormattingContext {
             depends_on_block_constraints: layout.depends_on_block_constraints,
             content_inline_size_for_table: None,
             specific_layout_info: None,
-        }
+        };
+        (result, layout.line_breaking_summary)
     }
 
     fn next_character_prevents_soft_wrap_opportunity(&self, index: usize) -> bool {
@@ -2214,6 +2400,27 @@ impl FloatBox {
     }
 }
 
+/// The highlighted ranges of `text_run` that intersect `range`, relative to `origin`, which
+/// is the start of the text of the line item that shows `range`.
+fn highlighted_ranges_in(
+    text_run: &TextRun,
+    range: &Range<ByteIndex>,
+    origin: ByteIndex,
+) -> Vec<(Range<ByteIndex>, HighlightKind)> {
+    text_run
+        .highlighted_ranges
+        .iter()
+        .map(|(highlight, kind)| (highlight.intersect(range), *kind))
+        .filter(|(intersection, _)| !intersection.is_empty())
+        .map(|(intersection, kind)| {
+            (
+                Range::new(intersection.begin() - origin, intersection.length()),
+                kind,
+            )
+        })
+        .collect()
+}
+
 fn place_pending_floats(ifc: &mut InlineFormattingContextLayout, line_items: &mut [LineItem]) {
     for item in line_items.iter_mut() {
         if let LineItem::Float(_, float_line_item) = item {
@@ -0,0 +1,339 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Layout of `display: ruby` elements. See <https://drafts.csswg.org/css-ruby/>.
+//!
+//! The contents of a ruby container are paired into ruby segments, each made of a ruby
+//! base and the ruby annotation that follows it. Every segment is placed into the
+//! inline formatting context of the ruby container as an atomic inline, which allows
+//! lines to break between segments, but not within them. Within a segment, the base
+//! and the annotation are centered along the inline axis and the annotation is stacked
+//! over or under the base according to `ruby-position`. The segment is aligned in the
+//! line using the baseline of its base, so the annotation contributes to the height of
+//! the line like any other inline content.
+//!
+//! Annotations that are wider than their base widen the segment rather than overhanging
+//! adjacent content. Overhang is optional and left to the UA by CSS Ruby and overhanging
+//! is only appropriate next to characters that can be overlapped by annotations, such as
+//! kana, which are only known once the segment has been placed on a line.
+
+use std::borrow::Cow;
+
+use app_units::Au;
+use malloc_size_of_derive::MallocSizeOf;
+use style::Zero;
+use style::computed_values::display::T as StyloDisplay;
+use style::selector_parser::PseudoElement;
+use style::str::char_is_whitespace;
+use style::values::specified::text::RubyPosition;
+
+use crate::cell::ArcRefCell;
+use crate::context::LayoutContext;
+use crate::dom::{BoxSlot, LayoutBox};
+use crate::dom_traversal::{Contents, NodeAndStyleInfo, TraversalHandler};
+use crate::flow::construct::BlockContainerBuilder;
+use crate::flow::{BlockFormattingContext, IndependentFloatOrAtomicLayoutResult};
+use crate::formatting_contexts::{
+    Baselines, IndependentFormattingContext, IndependentFormattingContextContents,
+};
+use crate::fragment_tree::{CollapsedBlockMargins, Fragment};
+use crate::geom::{LogicalRect, LogicalVec2};
+use crate::layout_box_base::{CacheableLayoutResult, LayoutBoxBase};
+use crate::positioned::PositioningContext;
+use crate::sizing::{ComputeInlineContentSizes, InlineContentSizesResult};
+use crate::style_ext::{DisplayGeneratingBox, DisplayInside};
+use crate::{ConstraintSpace, ContainingBlock, IndefiniteContainingBlock, PropagatedBoxTreeData};
+
+/// A ruby base paired with its ruby annotation, laid out as a single atomic inline.
+#[derive(Debug, MallocSizeOf)]
+pub(crate) struct RubySegment {
+    /// An anonymous block containing the ruby base content of this segment.
+    base: Box<IndependentFormattingContext>,
+    /// The ruby annotation of this segment, if it has one. A ruby base that isn't
+    /// followed by an annotation forms a segment without one.
+    annotation: Option<Box<IndependentFormattingContext>>,
+}
+
+impl RubySegment {
+    /// Construct the [`IndependentFormattingContext`] for each ruby segment of the ruby
+    /// container described by `info`, whose children have been collected into `segments`.
+    pub(crate) fn construct_segments(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<'_>,
+        segments: Vec<RubySegmentContents<'_>>,
+        propagated_data: PropagatedBoxTreeData,
+    ) -> Vec<IndependentFormattingContext> {
+        let anonymous_info = info
+            .pseudo(context, PseudoElement::ServoAnonymousBox)
+            .expect("Should never fail to create anonymous box");
+
+        segments
+            .into_iter()
+            .map(|segment| {
+                let mut builder =
+                    BlockContainerBuilder::new(context, &anonymous_info, propagated_data);
+                for content in segment.base {
+                    match content {
+                        RubyBaseContent::Text(info, text) => builder.handle_text(&info, text),
+                        RubyBaseContent::Element {
+                            info,
+                            display,
+                            contents,
+                            box_slot,
+                        } => builder.handle_element(&info, display, contents, box_slot),
+                    }
+                }
+                let base = IndependentFormattingContext {
+                    base: LayoutBoxBase::new(
+                        (&anonymous_info).into(),
+                        anonymous_info.style.clone(),
+                    ),
+                    contents: IndependentFormattingContextContents::Flow(
+                        BlockFormattingContext::from_block_container(builder.finish()),
+                    ),
+                };
+
+                let annotation = segment.annotation.map(|annotation| {
+                    let annotation_context = IndependentFormattingContext::construct(
+                        context,
+                        &annotation.info,
+                        DisplayInside::FlowRoot {
+                            is_list_item: false,
+                        },
+                        annotation.contents,
+                        propagated_data,
+                    );
+                    // The annotation is laid out as part of its segment, so there is no
+                    // inline-level box to store for it.
+                    annotation.box_slot.set(LayoutBox::InlineLevel(Vec::new()));
+                    Box::new(annotation_context)
+                });
+
+                IndependentFormattingContext {
+                    base: LayoutBoxBase::new(
+                        (&anonymous_info).into(),
+                        anonymous_info.style.clone(),
+                    ),
+                    contents: IndependentFormattingContextContents::Ruby(RubySegment {
+                        base: Box::new(base),
+                        annotation,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block_for_children: &ContainingBlock,
+    ) -> CacheableLayoutResult {
+        let lay_out = |context: &IndependentFormattingContext,
+                       positioning_context: &mut PositioningContext| {
+            context.layout_float_or_atomic_inline(
+                layout_context,
+                positioning_context,
+                containing_block_for_children,
+            )
+        };
+        let base = lay_out(&self.base, positioning_context);
+        let annotation = self
+            .annotation
+            .as_ref()
+            .map(|annotation| lay_out(annotation, positioning_context));
+
+        let annotation_is_over = matches!(
+            containing_block_for_children
+                .style
+                .get_inherited_text()
+                .ruby_position,
+            RubyPosition::Over | RubyPosition::AlternateOver
+        );
+        let ordered_results = match annotation {
+            Some(annotation) if annotation_is_over => vec![(annotation, false), (base, true)],
+            Some(annotation) => vec![(base, true), (annotation, false)],
+            None => vec![(base, true)],
+        };
+
+        let writing_mode = containing_block_for_children.style.writing_mode;
+        let mut fragments = Vec::with_capacity(ordered_results.len());
+        let mut baselines = Baselines::default();
+        let mut block_position = Au::zero();
+        for (result, is_base) in ordered_results {
+            let IndependentFloatOrAtomicLayoutResult {
+                mut fragment,
+                baselines: result_baselines,
+                pbm_sums,
+            } = result;
+
+            // Center the box along the inline axis of the segment.
+            let content_size = fragment.content_rect.size.to_logical(writing_mode);
+            let margin_box_inline_size = content_size.inline + pbm_sums.inline_sum();
+            let start_corner = LogicalVec2 {
+                inline: (containing_block_for_children.size.inline - margin_box_inline_size) / 2 +
+                    pbm_sums.inline_start,
+                block: block_position + pbm_sums.block_start,
+            };
+            fragment.content_rect = LogicalRect {
+                start_corner,
+                size: content_size,
+            }
+            .as_physical(Some(containing_block_for_children));
+
+            if is_base {
+                baselines = result_baselines.offset(start_corner.block);
+            }
+            block_position += content_size.block + pbm_sums.block_sum();
+            fragments.push(Fragment::Box(ArcRefCell::new(fragment)));
+        }
+
+        CacheableLayoutResult {
+            fragments,
+            content_block_size: block_position,
+            content_inline_size_for_table: None,
+            baselines,
+            depends_on_block_constraints: false,
+            specific_layout_info: None,
+            collapsible_margins_in_children: CollapsedBlockMargins::zero(),
+        }
+    }
+}
+
+impl ComputeInlineContentSizes for RubySegment {
+    fn compute_inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        constraint_space: &ConstraintSpace,
+    ) -> InlineContentSizesResult {
+        // The base and the annotation are stacked, so the segment is as wide as the
+        // wider of the two.
+        let containing_block: IndefiniteContainingBlock = constraint_space.into();
+        let outer_inline_content_sizes = |context: &IndependentFormattingContext| {
+            context.outer_inline_content_sizes(
+                layout_context,
+                &containing_block,
+                &LogicalVec2::zero(),
+                false, /* auto_block_size_stretches_to_containing_block */
+            )
+        };
+
+        let mut result = outer_inline_content_sizes(&self.base);
+        if let Some(annotation) = &self.annotation {
+            let annotation_result = outer_inline_content_sizes(annotation);
+            result.sizes.max_assign(annotation_result.sizes);
+            result.depends_on_block_constraints |= annotation_result.depends_on_block_constraints;
+        }
+        result
+    }
+}
+
+/// Content of a ruby base, collected while traversing the ruby container.
+enum RubyBaseContent<'dom> {
+    Text(NodeAndStyleInfo<'dom>, Cow<'dom, str>),
+    Element {
+        info: NodeAndStyleInfo<'dom>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    },
+}
+
+/// A ruby annotation element, collected while traversing the ruby container.
+struct RubyAnnotationContent<'dom> {
+    info: NodeAndStyleInfo<'dom>,
+    contents: Contents,
+    box_slot: BoxSlot<'dom>,
+}
+
+/// The children of a ruby container that make up a single [`RubySegment`].
+#[derive(Default)]
+pub(crate) struct RubySegmentContents<'dom> {
+    base: Vec<RubyBaseContent<'dom>>,
+    annotation: Option<RubyAnnotationContent<'dom>>,
+}
+
+/// A [`TraversalHandler`] that pairs the children of a ruby container into segments.
+///
+/// This follows the pairing in <https://drafts.csswg.org/css-ruby/#box-fixup>
+/// for a single level of annotations: consecutive base content forms a ruby base and
+/// each annotation closes the segment of the base before it. White space between
+/// segments is discarded.
+#[derive(Default)]
+pub(crate) struct RubySegmentsBuilder<'dom> {
+    segments: Vec<RubySegmentContents<'dom>>,
+}
+
+impl<'dom> RubySegmentsBuilder<'dom> {
+    pub(crate) fn finish(self) -> Vec<RubySegmentContents<'dom>> {
+        self.segments
+    }
+
+    /// Get the segment that new base content should be added to, starting a new one if
+    /// the current segment already has an annotation.
+    fn segment_for_base_content(&mut self) -> &mut RubySegmentContents<'dom> {
+        if self
+            .segments
+            .last()
+            .is_none_or(|segment| segment.annotation.is_some())
+        {
+            self.segments.push(RubySegmentContents::default());
+        }
+        self.segments.last_mut().unwrap()
+    }
+}
+
+impl<'dom> TraversalHandler<'dom> for RubySegmentsBuilder<'dom> {
+    fn handle_text(&mut self, info: &NodeAndStyleInfo<'dom>, text: Cow<'dom, str>) {
+        if text.chars().all(char_is_whitespace) &&
+            self.segments
+                .last()
+                .is_none_or(|segment| segment.annotation.is_some())
+        {
+            return;
+        }
+        self.segment_for_base_content()
+            .base
+            .push(RubyBaseContent::Text(info.clone(), text));
+    }
+
+    fn handle_element(
+        &mut self,
+        info: &NodeAndStyleInfo<'dom>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        if !matches!(
+            info.style.get_box().display,
+            StyloDisplay::RubyText | StyloDisplay::RubyTextContainer
+        ) {
+            self.segment_for_base_content()
+                .base
+                .push(RubyBaseContent::Element {
+                    info: info.clone(),
+                    display,
+                    contents,
+                    box_slot,
+                });
+            return;
+        }
+
+        let annotation = RubyAnnotationContent {
+            info: info.clone(),
+            contents,
+            box_slot,
+        };
+        match self.segments.last_mut() {
+            Some(segment) if segment.annotation.is_none() => {
+                segment.annotation = Some(annotation);
+            },
+            // An annotation without a preceding base gets an empty base.
+            _ => self.segments.push(RubySegmentContents {
+                base: Vec::new(),
+                annotation: Some(annotation),
+            }),
+        }
+    }
+}
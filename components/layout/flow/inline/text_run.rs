@@ -11,6 +11,7 @@ use fonts::{
     FontContext, FontRef, GlyphRun, LAST_RESORT_GLYPH_ADVANCE, ShapingFlags, ShapingOptions,
 };
 use fonts_traits::ByteIndex;
+use layout_api::HighlightKind;
 use log::warn;
 use malloc_size_of_derive::MallocSizeOf;
 use range::Range as ServoRange;
@@ -18,6 +19,7 @@ use servo_arc::Arc;
 use style::computed_values::text_rendering::T as TextRendering;
 use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
 use style::computed_values::word_break::T as WordBreak;
+use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 use style::str::char_is_whitespace;
 use style::values::computed::OverflowWrap;
@@ -25,6 +27,7 @@ use unicode_bidi::{BidiInfo, Level};
 use unicode_script::Script;
 use xi_unicode::linebreak_property;
 
+use super::construct::WhitespaceCollapse;
 use super::line_breaker::LineBreaker;
 use super::{FontKeyAndMetrics, InlineFormattingContextLayout, SharedInlineStyles};
 use crate::fragment_tree::BaseFragmentInfo;
@@ -346,14 +349,110 @@ pub(crate) struct TextRun {
     /// The selection range for the DOM text node that originated this [`TextRun`]. This
     /// comes directly from the DOM.
     pub selection_range: Option<ServoRange<ByteIndex>>,
+
+    /// The range of the text that an input method is composing in the DOM text node that
+    /// originated this [`TextRun`]. This comes directly from the DOM.
+    pub composition_range: Option<ServoRange<ByteIndex>>,
+
+    /// The ranges of the words that the spellchecker has flagged as misspelled in the DOM
+    /// text node that originated this [`TextRun`]. This comes directly from the DOM.
+    pub misspelled_ranges: Vec<ServoRange<ByteIndex>>,
+
+    /// The highlighted ranges of the text of this [`TextRun`], in the order in which they
+    /// are painted. A [`TextRun`] can span several DOM text nodes, so these are collected
+    /// from all of them and translated to offsets in the text of the
+    /// [`super::InlineFormattingContext`].
+    pub highlighted_ranges: Vec<(ServoRange<ByteIndex>, HighlightKind)>,
+
+    /// The table that maps offsets in the text of this [`TextRun`] back to offsets in the
+    /// text of the DOM nodes that it originates from.
+    #[conditional_malloc_size_of]
+    pub whitespace_collapse_table: std::sync::Arc<WhitespaceCollapseTable>,
+}
+
+/// A table that maps UTF-8 offsets in the text of a [`TextRun`], where white space has
+/// already been collapsed, back to UTF-8 offsets in the text of the DOM node that it
+/// originates from. Each entry is the pair of offsets at which a stretch of text that
+/// white space collapsing left untouched starts. Characters that are replaced by a
+/// space keep their length, as only ASCII white space is ever replaced.
+#[derive(Clone, Debug, Default, MallocSizeOf)]
+pub struct WhitespaceCollapseTable(Vec<(usize, usize)>);
+
+impl WhitespaceCollapseTable {
+    pub fn new(
+        text: &str,
+        white_space_collapse: WhiteSpaceCollapse,
+        remove_collapsible_white_space_at_start: bool,
+    ) -> Self {
+        let mut collapsed = WhitespaceCollapse::new(
+            text.chars(),
+            white_space_collapse,
+            remove_collapsible_white_space_at_start,
+        )
+        .peekable();
+
+        // The collapsed text is the original text with some white space removed and other
+        // white space replaced by a space, so walk both to find the removed stretches.
+        let mut entries = Vec::new();
+        let mut collapsed_offset = 0;
+        let mut inside_removed_text = true;
+        for (dom_offset, character) in text.char_indices() {
+            let kept = collapsed.peek().is_some_and(|collapsed_character| {
+                *collapsed_character == character ||
+                    (collapsed_character.is_ascii_whitespace() &&
+                        character.is_ascii_whitespace())
+            });
+            if !kept {
+                inside_removed_text = true;
+                continue;
+            }
+            if inside_removed_text {
+                entries.push((collapsed_offset, dom_offset));
+                inside_removed_text = false;
+            }
+            collapsed_offset += collapsed.next().map_or(0, char::len_utf8);
+        }
+        Self(entries)
+    }
+
+    /// Add the entries of `other`, the table for text that starts at `offset` in the text
+    /// of the [`TextRun`] that owns this table.
+    pub(super) fn append(&mut self, other: WhitespaceCollapseTable, offset: usize) {
+        self.0.extend(
+            other
+                .0
+                .into_iter()
+                .map(|(run_offset, dom_offset)| (run_offset + offset, dom_offset)),
+        );
+    }
+
+    /// Map `offset`, a UTF-8 offset in the text of the [`TextRun`] that owns this table, to
+    /// a UTF-8 offset in the text of the DOM node that it originates from.
+    pub fn dom_offset(&self, offset: ByteIndex) -> ByteIndex {
+        let offset = offset.0 as usize;
+        let index = self
+            .0
+            .partition_point(|(run_offset, _)| *run_offset <= offset);
+        match index.checked_sub(1).map(|index| self.0[index]) {
+            Some((run_offset, dom_offset)) => {
+                ByteIndex((dom_offset + offset - run_offset) as isize)
+            },
+            None => ByteIndex(offset as isize),
+        }
+    }
 }
 
 impl TextRun {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         base_fragment_info: BaseFragmentInfo,
         inline_styles: SharedInlineStyles,
         text_range: Range<usize>,
         selection_range: Option<ServoRange<ByteIndex>>,
+        composition_range: Option<ServoRange<ByteIndex>>,
+        misspelled_ranges: Vec<ServoRange<ByteIndex>>,
+        highlighted_ranges: Vec<(ServoRange<ByteIndex>, HighlightKind)>,
+        whitespace_collapse_table: WhitespaceCollapseTable,
     ) -> Self {
         Self {
             base_fragment_info,
@@ -361,6 +460,10 @@ impl TextRun {
             text_range,
             shaped_text: Vec::new(),
             selection_range,
+            composition_range,
+            misspelled_ranges,
+            highlighted_ranges,
+            whitespace_collapse_table: std::sync::Arc::new(whitespace_collapse_table),
         }
     }
 
@@ -418,6 +521,9 @@ impl TextRun {
                 if segment.bidi_level.is_rtl() {
                     flags.insert(ShapingFlags::RTL_FLAG);
                 }
+                if glyphs_are_upright(parent_style.writing_mode, segment.script) {
+                    flags.insert(ShapingFlags::VERTICAL_FLAG);
+                }
                 let shaping_options = ShapingOptions {
                     letter_spacing,
                     word_spacing,
@@ -658,3 +764,27 @@ where
         Some((character, self.next_character))
     }
 }
+
+/// Whether glyphs of the given script are set upright, rather than rotated sideways, in
+/// the given writing mode. See <https://drafts.csswg.org/css-writing-modes/#text-orientation>.
+fn glyphs_are_upright(writing_mode: WritingMode, script: Script) -> bool {
+    if !writing_mode.is_vertical() || writing_mode.is_sideways() {
+        return false;
+    }
+    if writing_mode.is_upright() {
+        return true;
+    }
+
+    // With `text-orientation: mixed` only typographic characters with a `Vertical_Orientation`
+    // of `U` or `Tu` are upright. Text runs are segmented by script and not by orientation,
+    // so this is approximated by setting the scripts that are written vertically upright.
+    matches!(
+        script,
+        Script::Han |
+            Script::Hiragana |
+            Script::Katakana |
+            Script::Hangul |
+            Script::Bopomofo |
+            Script::Yi
+    )
+}
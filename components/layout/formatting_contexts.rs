@@ -15,6 +15,7 @@ use crate::context::LayoutContext;
 use crate::dom_traversal::{Contents, NodeAndStyleInfo};
 use crate::flexbox::FlexContainer;
 use crate::flow::BlockFormattingContext;
+use crate::flow::inline::ruby::RubySegment;
 use crate::fragment_tree::{BaseFragmentInfo, FragmentFlags};
 use crate::geom::LazySize;
 use crate::layout_box_base::{
@@ -44,6 +45,7 @@ pub(crate) enum IndependentFormattingContextContents {
     Flex(FlexContainer),
     Grid(TaffyContainer),
     Table(Table),
+    Ruby(RubySegment),
     // Other layout modes go here
 }
 
@@ -89,6 +91,17 @@ impl IndependentFormattingContext {
                             ),
                         )
                     },
+                    // TODO: A ruby container that is not inline-level, for instance because it
+                    // is floated, should contain a single anonymous inline ruby container.
+                    DisplayInside::Ruby => IndependentFormattingContextContents::Flow(
+                        BlockFormattingContext::construct(
+                            context,
+                            node_and_style_info,
+                            non_replaced_contents,
+                            propagated_data,
+                            false, /* is_list_item */
+                        ),
+                    ),
                     DisplayInside::Grid => {
                         IndependentFormattingContextContents::Grid(TaffyContainer::construct(
                             context,
@@ -235,6 +248,9 @@ impl IndependentFormattingContext {
             IndependentFormattingContextContents::Table(table) => {
                 table.repair_style(context, new_style)
             },
+            // Ruby segments are anonymous boxes, so their style only changes when they
+            // are rebuilt.
+            IndependentFormattingContextContents::Ruby(..) => {},
         }
     }
 
@@ -292,6 +308,11 @@ impl IndependentFormattingContext {
                 containing_block,
                 depends_on_block_constraints,
             ),
+            IndependentFormattingContextContents::Ruby(ruby_segment) => ruby_segment.layout(
+                layout_context,
+                positioning_context,
+                containing_block_for_children,
+            ),
         }
     }
 
@@ -360,6 +381,7 @@ impl IndependentFormattingContext {
             IndependentFormattingContextContents::Flex(fc) => fc.layout_style(),
             IndependentFormattingContextContents::Grid(fc) => fc.layout_style(),
             IndependentFormattingContextContents::Table(fc) => fc.layout_style(None),
+            IndependentFormattingContextContents::Ruby(_) => LayoutStyle::Default(&self.base.style),
         }
     }
 
@@ -400,6 +422,9 @@ impl ComputeInlineContentSizes for IndependentFormattingContextContents {
             Self::Table(inner) => {
                 inner.compute_inline_content_sizes(layout_context, constraint_space)
             },
+            Self::Ruby(inner) => {
+                inner.compute_inline_content_sizes(layout_context, constraint_space)
+            },
         }
     }
 }
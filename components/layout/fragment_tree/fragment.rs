@@ -8,7 +8,8 @@ use app_units::Au;
 use base::id::PipelineId;
 use base::print_tree::PrintTree;
 use euclid::{Point2D, Rect, Size2D, UnknownUnit};
-use fonts::{ByteIndex, FontMetrics, GlyphStore};
+use fonts::{ByteIndex, FontMetrics, GlyphInfo, GlyphStore};
+use layout_api::HighlightKind;
 use malloc_size_of_derive::MallocSizeOf;
 use range::Range as ServoRange;
 use servo_arc::Arc as ServoArc;
@@ -22,6 +23,7 @@ use super::{
 };
 use crate::cell::ArcRefCell;
 use crate::flow::inline::SharedInlineStyles;
+use crate::flow::inline::text_run::WhitespaceCollapseTable;
 use crate::geom::{LogicalSides, PhysicalPoint, PhysicalRect};
 use crate::style_ext::ComputedValuesExt;
 
@@ -74,6 +76,23 @@ pub(crate) struct TextFragment {
     /// Extra space to add for each justification opportunity.
     pub justification_adjustment: Au,
     pub selection_range: Option<ServoRange<ByteIndex>>,
+    /// The range of the glyphs of this fragment that show text that an input method is
+    /// composing, which is painted with an underline.
+    pub composition_range: Option<ServoRange<ByteIndex>>,
+    /// The ranges of the glyphs of this fragment that show words which the spellchecker
+    /// has flagged as misspelled, which are painted with a wavy underline.
+    pub misspelled_ranges: Vec<ServoRange<ByteIndex>>,
+    /// The ranges of the glyphs of this fragment that show highlighted text, in the order
+    /// in which they are painted, with the kind of each highlight.
+    pub highlighted_ranges: Vec<(ServoRange<ByteIndex>, HighlightKind)>,
+
+    /// The UTF-8 offset of the first glyph of this fragment within the text of the
+    /// [`TextRun`](crate::flow::inline::text_run::TextRun) that it originates from.
+    pub text_offset: ByteIndex,
+    /// The table that maps offsets in the text of the originating text run back to
+    /// offsets in the text of its DOM node, undoing white space collapsing.
+    #[conditional_malloc_size_of]
+    pub whitespace_collapse_table: Arc<WhitespaceCollapseTable>,
 }
 
 #[derive(MallocSizeOf)]
@@ -144,6 +163,19 @@ impl Fragment {
         self.base().and_then(|base| base.tag)
     }
 
+    /// An identifier for this fragment, which is the same for every clone of it for as
+    /// long as it's alive.
+    pub(crate) fn identity(&self) -> usize {
+        match self {
+            Fragment::Box(fragment) | Fragment::Float(fragment) => fragment.address(),
+            Fragment::Positioning(fragment) => fragment.address(),
+            Fragment::AbsoluteOrFixedPositioned(fragment) => fragment.address(),
+            Fragment::Text(fragment) => fragment.address(),
+            Fragment::Image(fragment) => fragment.address(),
+            Fragment::IFrame(fragment) => fragment.address(),
+        }
+    }
+
     pub fn print(&self, tree: &mut PrintTree) {
         match self {
             Fragment::Box(fragment) => fragment.borrow().print(tree),
@@ -342,6 +374,81 @@ impl TextFragment {
     pub fn has_selection(&self) -> bool {
         self.selection_range.is_some()
     }
+
+    /// The offset in the glyphs of this fragment at which the caret of a text control is
+    /// shown: the end of the text that an input method is composing, or otherwise the
+    /// end of the selection.
+    pub(crate) fn caret_offset(&self) -> Option<ByteIndex> {
+        self.composition_range
+            .or(self.selection_range)
+            .map(|range| range.end())
+    }
+
+    /// The inline advance of the glyphs of this fragment that come before `offset`.
+    pub(crate) fn inline_advance_to(&self, offset: ByteIndex) -> Au {
+        let mut remaining = offset;
+        let mut advance = Au::zero();
+        for glyph_store in self.glyphs.iter() {
+            let range = ServoRange::new(ByteIndex(0), remaining.min(glyph_store.len()));
+            remaining = remaining - range.length();
+            advance += glyph_store.advance_for_byte_range(&range, self.justification_adjustment);
+        }
+        advance
+    }
+
+    /// Find the UTF-8 offset in the text of this fragment's DOM node of the caret
+    /// position closest to `point`, which is relative to the origin of this fragment's
+    /// rectangle. Glyphs are measured along the inline axis, so this works in both
+    /// horizontal and vertical writing modes, and a point within a glyph resolves to
+    /// its nearest edge, taking the direction of the text into account.
+    pub(crate) fn text_offset_for_point(&self, point: PhysicalPoint<Au>) -> ByteIndex {
+        let inline_position = if self.inline_styles.style.borrow().writing_mode.is_vertical() {
+            point.y
+        } else {
+            point.x
+        };
+
+        // Collect the caret positions at the edges of every glyph, in visual order, as
+        // pairs of inline advance and text offset.
+        let mut advance = Au::zero();
+        let mut edges = Vec::new();
+        let mut offset_in_fragment = ByteIndex(0);
+        for glyph_store in self.glyphs.iter() {
+            let is_rtl = glyph_store.is_rtl();
+            let store_length = glyph_store.len();
+            if is_rtl {
+                edges.push((advance, offset_in_fragment + store_length));
+            }
+            if store_length > ByteIndex(0) {
+                let range = ServoRange::new(ByteIndex(0), store_length);
+                for glyph in glyph_store.iter_glyphs_for_byte_range(&range) {
+                    let (GlyphInfo::Simple(_, byte_index) | GlyphInfo::Detail(_, byte_index, _)) =
+                        glyph;
+                    if !is_rtl {
+                        edges.push((advance, offset_in_fragment + byte_index));
+                    }
+                    advance += glyph.advance();
+                    if glyph.char_is_word_separator() {
+                        advance += self.justification_adjustment;
+                    }
+                    if is_rtl {
+                        edges.push((advance, offset_in_fragment + byte_index));
+                    }
+                }
+            }
+            if !is_rtl {
+                edges.push((advance, offset_in_fragment + store_length));
+            }
+            offset_in_fragment = offset_in_fragment + store_length;
+        }
+
+        let offset_in_fragment = edges
+            .into_iter()
+            .min_by_key(|(edge_advance, _)| (*edge_advance - inline_position).abs())
+            .map_or(ByteIndex(0), |(_, offset)| offset);
+        self.whitespace_collapse_table
+            .dom_offset(self.text_offset + offset_in_fragment)
+    }
 }
 
 impl ImageFragment {
@@ -553,10 +553,17 @@ impl LogicalRect<Au> {
             containing_block.style.writing_mode
         });
         let (x, y, width, height) = if mode.is_vertical() {
-            // TODO: Bottom-to-top writing modes are not supported.
+            // TODO: Right-to-left block flow (`vertical-rl`) is not supported, as the block
+            // size of the containing block is not always known at this point.
+            let y = match containing_block {
+                Some(containing_block) if !mode.is_inline_tb() => {
+                    containing_block.size.inline - self.max_inline_position()
+                },
+                _ => self.start_corner.inline,
+            };
             (
                 self.start_corner.block,
-                self.start_corner.inline,
+                y,
                 self.size.block,
                 self.size.inline,
             )
@@ -634,10 +641,14 @@ pub(crate) trait ToLogicalWithContainingBlock<LogicalType> {
 impl ToLogicalWithContainingBlock<LogicalVec2<Au>> for PhysicalPoint<Au> {
     fn to_logical(&self, containing_block: &ContainingBlock) -> LogicalVec2<Au> {
         let writing_mode = containing_block.style.writing_mode;
-        // TODO: Bottom-to-top and right-to-left vertical writing modes are not supported yet.
+        // TODO: Right-to-left vertical writing modes are not supported yet.
         if writing_mode.is_vertical() {
             LogicalVec2 {
-                inline: self.y,
+                inline: if writing_mode.is_inline_tb() {
+                    self.y
+                } else {
+                    containing_block.size.inline - self.y
+                },
                 block: self.x,
             }
         } else {
@@ -662,11 +673,15 @@ impl ToLogicalWithContainingBlock<LogicalRect<Au>> for PhysicalRect<Au> {
 
         let writing_mode = containing_block.style.writing_mode;
         if writing_mode.is_vertical() {
-            // TODO: Bottom-to-top and right-to-left vertical writing modes are not supported yet.
+            // TODO: Right-to-left vertical writing modes are not supported yet.
             inline = self.size.height;
             block = self.size.width;
             block_start = self.origin.x;
-            inline_start = self.origin.y;
+            if writing_mode.is_inline_tb() {
+                inline_start = self.origin.y;
+            } else {
+                inline_start = containing_block.size.inline - (self.origin.y + self.size.height);
+            }
         } else {
             inline = self.size.width;
             block = self.size.height;
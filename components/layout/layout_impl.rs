@@ -10,20 +10,23 @@ use std::fmt::Debug;
 use std::process;
 use std::rc::Rc;
 use std::sync::{Arc, LazyLock};
+use std::time::Instant;
 
 use app_units::Au;
 use base::Epoch;
 use base::id::{PipelineId, WebViewId};
 use bitflags::bitflags;
 use compositing_traits::CrossProcessCompositorApi;
-use compositing_traits::display_list::ScrollType;
-use embedder_traits::{Theme, UntrustedNodeAddress, ViewportDetails};
+use compositing_traits::display_list::{ScrollAnimation, ScrollType};
+use embedder_traits::{
+    FrameStageTimings, Theme, UntrustedNodeAddress, UserAgentStylesheetOverride, ViewportDetails,
+};
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
 use euclid::{Point2D, Scale, Size2D};
 use fnv::FnvHashMap;
 use fonts::{FontContext, FontContextWebFontMethods};
 use fonts_traits::StylesheetWebFontLoadFinishedCallback;
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use ipc_channel::ipc::IpcSender;
 use layout_api::{
     IFrameSizes, Layout, LayoutConfig, LayoutDamage, LayoutFactory, NodesFromPointQueryType,
@@ -79,11 +82,13 @@ use webrender_api::units::{DevicePixel, DevicePoint, LayoutSize, LayoutVector2D}
 use webrender_api::{ExternalScrollId, HitTestFlags};
 
 use crate::context::{CachedImageOrError, ImageResolver, LayoutContext};
-use crate::display_list::{DisplayListBuilder, StackingContextTree};
+use crate::display_list::{CompositorAnimationSampler, DisplayListBuilder, StackingContextTree};
 use crate::query::{
-    get_the_text_steps, process_client_rect_request, process_content_box_request,
-    process_content_boxes_request, process_node_scroll_area_request, process_offset_parent_query,
-    process_resolved_font_style_query, process_resolved_style_request, process_text_index_request,
+    get_the_rendered_text_of_range, get_the_text_steps, process_caret_position_query,
+    process_client_rect_request, process_content_box_request, process_content_boxes_request,
+    process_node_scroll_area_request, process_offset_parent_query,
+    process_resolved_font_style_query, process_resolved_style_request,
+    process_text_cursor_rect_query, process_text_index_request,
 };
 use crate::traversal::{RecalcStyle, compute_damage_and_repair_style};
 use crate::{BoxTree, FragmentTree};
@@ -140,6 +145,16 @@ pub struct LayoutThread {
     /// Whether or not user agent stylesheets have been added to the Stylist or not.
     have_added_user_agent_stylesheets: bool,
 
+    /// The stylesheet that the embedder applies at the user agent origin, if any.
+    user_agent_stylesheet_override: Option<ParsedUserAgentStylesheetOverride>,
+
+    /// The stylesheets that the embedder applies at the user origin.
+    user_stylesheets: Vec<DocumentStyleSheet>,
+
+    /// The stylesheet that hides the elements matched by the element hiding rules of the
+    /// content blocking filter lists, if any apply to this document.
+    content_blocking_stylesheet: Option<DocumentStyleSheet>,
+
     /// Is this the first reflow in this LayoutThread?
     have_ever_generated_display_list: Cell<bool>,
 
@@ -156,6 +171,11 @@ pub struct LayoutThread {
     /// to preserve scroll offsets from the old tree to the new one.
     need_new_stacking_context_tree: Cell<bool>,
 
+    /// The nodes whose boxes and fragments were restyled without being laid out again
+    /// since the last display list was built. When the stacking context tree is reused,
+    /// only their fragments are painted again in the next display list.
+    nodes_needing_repaint: RefCell<FxHashSet<OpaqueNode>>,
+
     /// The box tree.
     box_tree: RefCell<Option<Arc<BoxTree>>>,
 
@@ -277,6 +297,19 @@ impl Layout for LayoutThread {
         get_the_text_steps(node)
     }
 
+    #[servo_tracing::instrument(skip_all)]
+    fn query_rendered_text_of_range(
+        &self,
+        root: layout_api::TrustedNodeAddress,
+        start: (layout_api::TrustedNodeAddress, usize),
+        end: (layout_api::TrustedNodeAddress, usize),
+    ) -> String {
+        let root = unsafe { ServoLayoutNode::new(&root) };
+        let start = (unsafe { ServoLayoutNode::new(&start.0) }, start.1);
+        let end = (unsafe { ServoLayoutNode::new(&end.0) }, end.1);
+        get_the_rendered_text_of_range(root, start, end)
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn query_nodes_from_point(
         &self,
@@ -300,6 +333,39 @@ impl Layout for LayoutThread {
         results.iter().map(|result| result.node).collect()
     }
 
+    #[servo_tracing::instrument(skip_all)]
+    fn query_caret_position(
+        &self,
+        point: UntypedPoint2D<f32>,
+    ) -> Option<(UntrustedNodeAddress, usize)> {
+        // The point we get is not relative to the entire WebRender scene, but to this
+        // particular pipeline, so we need to tell WebRender about that.
+        let flags = HitTestFlags::POINT_RELATIVE_TO_PIPELINE_VIEWPORT;
+        let client_point = DevicePoint::from_untyped(point);
+        let result = self
+            .compositor_api
+            .hit_test(Some(self.id.into()), client_point, flags)
+            .into_iter()
+            .next()?;
+
+        let to_au = |point: Point2D<f32, CSSPixel>| {
+            UntypedPoint2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y))
+        };
+        let offset = process_caret_position_query(
+            self.fragment_tree.borrow().clone(),
+            OpaqueNode(result.node.0 as usize),
+            to_au(result.point_relative_to_item),
+            to_au(result.point_relative_to_initial_containing_block),
+        );
+        Some((result.node, offset.unwrap_or_default()))
+    }
+
+    #[servo_tracing::instrument(skip_all)]
+    fn query_text_cursor_rect(&self, node: TrustedNodeAddress) -> Option<UntypedRect<Au>> {
+        let node = unsafe { ServoLayoutNode::new(&node) };
+        process_text_cursor_rect_query(self.fragment_tree.borrow().clone(), node.opaque())
+    }
+
     #[servo_tracing::instrument(skip_all)]
     fn query_offset_parent(&self, node: TrustedNodeAddress) -> OffsetParentResponse {
         let node = unsafe { ServoLayoutNode::new(&node) };
@@ -438,6 +504,30 @@ impl Layout for LayoutThread {
         self.stylist.set_quirks_mode(quirks_mode);
     }
 
+    fn set_user_agent_stylesheet_override(
+        &mut self,
+        stylesheet_override: Option<UserAgentStylesheetOverride>,
+    ) {
+        self.remove_user_agent_stylesheets();
+        if let Some(old_override) = self.user_agent_stylesheet_override.take() {
+            self.font_context
+                .remove_all_web_fonts_from_stylesheet(&old_override.stylesheet);
+        }
+
+        self.user_agent_stylesheet_override =
+            stylesheet_override.map(ParsedUserAgentStylesheetOverride::new);
+    }
+
+    fn set_user_stylesheets(&mut self, user_stylesheets: Vec<String>) {
+        self.remove_user_agent_stylesheets();
+        for old_stylesheet in self.user_stylesheets.drain(..) {
+            self.font_context
+                .remove_all_web_fonts_from_stylesheet(&old_stylesheet);
+        }
+
+        self.user_stylesheets = parse_user_stylesheets(user_stylesheets);
+    }
+
     fn reflow(&mut self, reflow_request: ReflowRequest) -> Option<ReflowResult> {
         time_profile!(
             profile_time::ProfilerCategory::Layout,
@@ -517,9 +607,17 @@ impl LayoutThread {
             image_cache: config.image_cache,
             font_context: config.font_context,
             have_added_user_agent_stylesheets: false,
+            user_agent_stylesheet_override: config
+                .user_agent_stylesheet_override
+                .map(ParsedUserAgentStylesheetOverride::new),
+            user_stylesheets: parse_user_stylesheets(config.user_stylesheets),
+            content_blocking_stylesheet: config.content_blocking_stylesheet.map(|contents| {
+                parse_embedder_stylesheet(&contents, "content-blocking.css", Origin::UserAgent)
+            }),
             have_ever_generated_display_list: Cell::new(false),
             need_new_display_list: Cell::new(false),
             need_new_stacking_context_tree: Cell::new(false),
+            nodes_needing_repaint: Default::default(),
             box_tree: Default::default(),
             fragment_tree: Default::default(),
             stacking_context_tree: Default::default(),
@@ -639,10 +737,10 @@ impl LayoutThread {
         self.maybe_print_reflow_event(&reflow_request);
 
         if self.can_skip_reflow_request_entirely(&reflow_request) {
-            if let ReflowGoal::UpdateScrollNode(external_scroll_id, offset) =
+            if let ReflowGoal::UpdateScrollNode(external_scroll_id, offset, animation) =
                 reflow_request.reflow_goal
             {
-                self.set_scroll_offset_from_script(external_scroll_id, offset);
+                self.set_scroll_offset_from_script(external_scroll_id, offset, animation);
             }
             return None;
         }
@@ -664,19 +762,33 @@ impl LayoutThread {
             animation_timeline_value: reflow_request.animation_timeline_value,
         });
 
+        let mut frame_timings = FrameStageTimings {
+            script: reflow_request.script_time,
+            ..Default::default()
+        };
         let (damage, iframe_sizes) = self.restyle_and_build_trees(
             &mut reflow_request,
             document,
             root_element,
             &image_resolver,
+            &mut frame_timings,
         );
+
+        let start = Instant::now();
         self.calculate_overflow(damage);
+        frame_timings.layout += start.elapsed();
+
+        let start = Instant::now();
         self.build_stacking_context_tree(&reflow_request, damage);
-        let built_display_list = self.build_display_list(&reflow_request, damage, &image_resolver);
+        frame_timings.display_list_build += start.elapsed();
+
+        let built_display_list =
+            self.build_display_list(&reflow_request, damage, &image_resolver, frame_timings);
 
-        if let ReflowGoal::UpdateScrollNode(external_scroll_id, offset) = reflow_request.reflow_goal
+        if let ReflowGoal::UpdateScrollNode(external_scroll_id, offset, animation) =
+            reflow_request.reflow_goal
         {
-            self.set_scroll_offset_from_script(external_scroll_id, offset);
+            self.set_scroll_offset_from_script(external_scroll_id, offset, animation);
         }
 
         let pending_images = std::mem::take(&mut *image_resolver.pending_images.lock());
@@ -710,33 +822,78 @@ impl LayoutThread {
         (viewport_changed && had_used_viewport_units) || theme_changed
     }
 
+    /// The stylesheets that are applied at the user agent and user origins of this layout's
+    /// document, in the order in which they are added to the [`Stylist`].
+    fn user_agent_stylesheets(&self) -> Vec<DocumentStyleSheet> {
+        let ua_stylesheets = &*UA_STYLESHEETS;
+
+        // The default user agent stylesheet, `user-agent.css`, comes first.
+        let Some((default_stylesheet, other_stylesheets)) =
+            ua_stylesheets.user_or_user_agent_stylesheets.split_first()
+        else {
+            unreachable!("There should always be a default user agent stylesheet");
+        };
+
+        let mut stylesheets = Vec::new();
+        match &self.user_agent_stylesheet_override {
+            Some(stylesheet_override) if stylesheet_override.replaces_default => {
+                stylesheets.push(stylesheet_override.stylesheet.clone());
+            },
+            Some(stylesheet_override) => {
+                stylesheets.push(default_stylesheet.clone());
+                stylesheets.push(stylesheet_override.stylesheet.clone());
+            },
+            None => stylesheets.push(default_stylesheet.clone()),
+        }
+        stylesheets.extend(other_stylesheets.iter().cloned());
+        stylesheets.extend(self.user_stylesheets.iter().cloned());
+
+        if self.stylist.quirks_mode() == QuirksMode::Quirks {
+            stylesheets.push(ua_stylesheets.quirks_mode_stylesheet.clone());
+        }
+
+        // Element hiding rules come last, so that their `!important` declarations win over
+        // those of the other user agent stylesheets.
+        if let Some(stylesheet) = &self.content_blocking_stylesheet {
+            stylesheets.push(stylesheet.clone());
+        }
+        stylesheets
+    }
+
+    /// Remove all user agent and user stylesheets from the [`Stylist`], so that they are
+    /// added again in the right order on the next reflow.
+    fn remove_user_agent_stylesheets(&mut self) {
+        if !self.have_added_user_agent_stylesheets {
+            return;
+        }
+        let guard = UA_STYLESHEETS.shared_lock.read();
+        for stylesheet in self.user_agent_stylesheets() {
+            self.stylist.remove_stylesheet(stylesheet, &guard);
+        }
+        self.have_added_user_agent_stylesheets = false;
+    }
+
     fn prepare_stylist_for_reflow<'dom>(
         &mut self,
         reflow_request: &ReflowRequest,
         document: ServoLayoutDocument<'dom>,
         root_element: ServoLayoutElement<'dom>,
         guards: &StylesheetGuards,
-        ua_stylesheets: &UserAgentStylesheets,
         snapshot_map: &SnapshotMap,
     ) {
         if !self.have_added_user_agent_stylesheets {
-            for stylesheet in &ua_stylesheets.user_or_user_agent_stylesheets {
+            for stylesheet in self.user_agent_stylesheets() {
+                self.load_all_web_fonts_from_stylesheet_with_guard(&stylesheet, guards.ua_or_user);
                 self.stylist
-                    .append_stylesheet(stylesheet.clone(), guards.ua_or_user);
-                self.load_all_web_fonts_from_stylesheet_with_guard(stylesheet, guards.ua_or_user);
+                    .append_stylesheet(stylesheet, guards.ua_or_user);
             }
+            self.have_added_user_agent_stylesheets = true;
 
-            if self.stylist.quirks_mode() == QuirksMode::Quirks {
-                self.stylist.append_stylesheet(
-                    ua_stylesheets.quirks_mode_stylesheet.clone(),
-                    guards.ua_or_user,
-                );
-                self.load_all_web_fonts_from_stylesheet_with_guard(
-                    &ua_stylesheets.quirks_mode_stylesheet,
-                    guards.ua_or_user,
-                );
+            // The user agent stylesheets are only added again after the document has been
+            // styled when the embedder changed them, which affects every element.
+            if let Some(mut data) = root_element.mutate_data() {
+                data.hint.insert(RestyleHint::restyle_subtree());
             }
-            self.have_added_user_agent_stylesheets = true;
         }
 
         if reflow_request.stylesheets_changed() {
@@ -758,6 +915,7 @@ impl LayoutThread {
         document: ServoLayoutDocument<'_>,
         root_element: ServoLayoutElement<'_>,
         image_resolver: &Arc<ImageResolver>,
+        frame_timings: &mut FrameStageTimings,
     ) -> (RestyleDamage, IFrameSizes) {
         let mut snapshot_map = SnapshotMap::new();
         let _snapshot_setter = match reflow_request.restyle.as_mut() {
@@ -767,8 +925,7 @@ impl LayoutThread {
 
         let document_shared_lock = document.style_shared_lock();
         let author_guard = document_shared_lock.read();
-        let ua_stylesheets = &*UA_STYLESHEETS;
-        let ua_or_user_guard = ua_stylesheets.shared_lock.read();
+        let ua_or_user_guard = UA_STYLESHEETS.shared_lock.read();
         let rayon_pool = STYLE_THREAD_POOL.lock();
         let rayon_pool = rayon_pool.pool();
         let rayon_pool = rayon_pool.as_ref();
@@ -789,7 +946,6 @@ impl LayoutThread {
             document,
             root_element,
             &guards,
-            ua_stylesheets,
             &snapshot_map,
         );
 
@@ -814,6 +970,7 @@ impl LayoutThread {
             iframe_sizes: Mutex::default(),
             use_rayon: rayon_pool.is_some(),
             image_resolver: image_resolver.clone(),
+            position_try_rules: Default::default(),
         };
 
         let restyle = reflow_request
@@ -821,6 +978,7 @@ impl LayoutThread {
             .as_ref()
             .expect("Should not get here if there is not restyle.");
 
+        let style_start = Instant::now();
         let recalc_style_traversal;
         let dirty_root;
         {
@@ -842,6 +1000,7 @@ impl LayoutThread {
 
             if !token.should_traverse() {
                 layout_context.style_context.stylist.rule_tree().maybe_gc();
+                frame_timings.style += style_start.elapsed();
                 return (RestyleDamage::empty(), IFrameSizes::default());
             }
 
@@ -855,14 +1014,18 @@ impl LayoutThread {
         let damage = compute_damage_and_repair_style(
             &layout_context.style_context,
             root_node,
+            dirty_root,
             damage_from_environment,
+            &mut self.nodes_needing_repaint.borrow_mut(),
         );
+        frame_timings.style += style_start.elapsed();
 
         if !damage.contains(RestyleDamage::RELAYOUT) {
             layout_context.style_context.stylist.rule_tree().maybe_gc();
             return (damage, IFrameSizes::default());
         }
 
+        let layout_start = Instant::now();
         let mut box_tree = self.box_tree.borrow_mut();
         let box_tree = &mut *box_tree;
         let layout_damage: LayoutDamage = damage.into();
@@ -896,6 +1059,7 @@ impl LayoutThread {
         });
 
         *self.fragment_tree.borrow_mut() = Some(fragment_tree);
+        frame_timings.layout += layout_start.elapsed();
 
         // Changes to layout require us to generate a new stacking context tree and display
         // list the next time one is requested.
@@ -970,6 +1134,18 @@ impl LayoutThread {
             .as_ref()
             .map(|tree| tree.compositor_info.scroll_tree.scroll_offsets());
 
+        // When animations are run by the compositor, it samples them on the real time
+        // animation timeline, which the test timeline does not follow.
+        let compositor_animation_sampler = (pref!(layout_compositor_animations_enabled) &&
+            !pref!(layout_animations_test_enabled))
+        .then(|| {
+            CompositorAnimationSampler::new(
+                reflow_request.animations.clone(),
+                reflow_request.animation_timeline_value,
+                self.id.into(),
+            )
+        });
+
         // Build the StackingContextTree. This turns the `FragmentTree` into a
         // tree of fragments in CSS painting order and also creates all
         // applicable spatial and clip nodes.
@@ -978,6 +1154,7 @@ impl LayoutThread {
             viewport_size,
             self.id.into(),
             !self.have_ever_generated_display_list.get(),
+            compositor_animation_sampler,
             &self.debug,
         );
 
@@ -1014,14 +1191,15 @@ impl LayoutThread {
         }
     }
 
-    /// Build the display list for the current layout and send it to the renderer. If no display
-    /// list is built, returns false.
+    /// Build the display list for the current layout and send it to the renderer, along with
+    /// the time spent producing it. If no display list is built, returns false.
     #[servo_tracing::instrument(name = "Display List Construction", skip_all)]
     fn build_display_list(
         &self,
         reflow_request: &ReflowRequest,
         damage: RestyleDamage,
         image_resolver: &Arc<ImageResolver>,
+        mut frame_timings: FrameStageTimings,
     ) -> bool {
         if !ReflowPhases::necessary(&reflow_request.reflow_goal)
             .contains(ReflowPhases::DisplayListConstruction)
@@ -1044,19 +1222,29 @@ impl LayoutThread {
             return false;
         }
 
+        let start = Instant::now();
         let mut epoch = self.epoch.get();
         epoch.next();
         self.epoch.set(epoch);
         stacking_context_tree.compositor_info.epoch = epoch.into();
 
+        let rayon_pool = STYLE_THREAD_POOL.lock();
+        let rayon_pool = rayon_pool.pool();
+        let rayon_pool = rayon_pool.as_ref();
         let built_display_list = DisplayListBuilder::build(
             reflow_request,
             stacking_context_tree,
             fragment_tree,
             image_resolver.clone(),
             self.device().device_pixel_ratio(),
+            &self.nodes_needing_repaint.borrow(),
+            rayon_pool,
             &self.debug,
         );
+        self.nodes_needing_repaint.borrow_mut().clear();
+
+        frame_timings.display_list_build += start.elapsed();
+        stacking_context_tree.compositor_info.frame_timings = frame_timings;
         self.compositor_api.send_display_list(
             self.webview_id,
             &stacking_context_tree.compositor_info,
@@ -1076,10 +1264,14 @@ impl LayoutThread {
         true
     }
 
+    /// Set the scroll offset of a scroll node on behalf of script. Layout moves the node to
+    /// its new offset right away, even if the renderer animates the scroll, so that script
+    /// observes the final offset of a smooth scroll as soon as it has started.
     fn set_scroll_offset_from_script(
         &self,
         external_scroll_id: ExternalScrollId,
         offset: LayoutVector2D,
+        animation: ScrollAnimation,
     ) {
         let mut stacking_context_tree = self.stacking_context_tree.borrow_mut();
         let Some(stacking_context_tree) = stacking_context_tree.as_mut() else {
@@ -1100,6 +1292,7 @@ impl LayoutThread {
                 self.id.into(),
                 offset,
                 external_scroll_id,
+                animation,
             );
         }
     }
@@ -1230,6 +1423,59 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
     })
 }
 
+/// A [`UserAgentStylesheetOverride`] of the embedder, parsed into a stylesheet that shares
+/// the lock of the default user agent stylesheets.
+struct ParsedUserAgentStylesheetOverride {
+    stylesheet: DocumentStyleSheet,
+    /// Whether this stylesheet replaces the default user agent stylesheet.
+    replaces_default: bool,
+}
+
+impl ParsedUserAgentStylesheetOverride {
+    fn new(stylesheet_override: UserAgentStylesheetOverride) -> Self {
+        let (contents, replaces_default) = match stylesheet_override {
+            UserAgentStylesheetOverride::Extend(contents) => (contents, false),
+            UserAgentStylesheetOverride::Replace(contents) => (contents, true),
+        };
+        Self {
+            stylesheet: parse_embedder_stylesheet(
+                &contents,
+                "user-agent-override.css",
+                Origin::UserAgent,
+            ),
+            replaces_default,
+        }
+    }
+}
+
+/// Parse the stylesheets that the embedder applies at the user origin, in order.
+fn parse_user_stylesheets(user_stylesheets: Vec<String>) -> Vec<DocumentStyleSheet> {
+    user_stylesheets
+        .iter()
+        .enumerate()
+        .map(|(index, contents)| {
+            parse_embedder_stylesheet(contents, &format!("user-{index}.css"), Origin::User)
+        })
+        .collect()
+}
+
+/// Parse a stylesheet that the embedder applies at the user agent or user origin.
+fn parse_embedder_stylesheet(contents: &str, filename: &str, origin: Origin) -> DocumentStyleSheet {
+    let url = Url::parse(&format!("chrome://resources/{filename}")).unwrap();
+    DocumentStyleSheet(ServoArc::new(Stylesheet::from_bytes(
+        contents.as_bytes(),
+        url.into(),
+        None,
+        None,
+        origin,
+        MediaList::empty(),
+        GLOBAL_STYLE_DATA.shared_lock.clone(),
+        None,
+        Some(&RustLogReporter),
+        QuirksMode::NoQuirks,
+    )))
+}
+
 static UA_STYLESHEETS: LazyLock<UserAgentStylesheets> =
     LazyLock::new(|| match get_ua_stylesheets() {
         Ok(stylesheets) => stylesheets,
@@ -1433,7 +1679,7 @@ impl ReflowPhases {
                 Self::StackingContextTreeConstruction | Self::DisplayListConstruction
             },
             ReflowGoal::LayoutQuery(query) => match query {
-                QueryMsg::NodesFromPointQuery => {
+                QueryMsg::CaretPositionQuery | QueryMsg::NodesFromPointQuery => {
                     Self::StackingContextTreeConstruction | Self::DisplayListConstruction
                 },
                 QueryMsg::ResolvedStyleQuery | QueryMsg::ScrollingAreaOrOffsetQuery => {
@@ -1445,7 +1691,9 @@ impl ReflowPhases {
                 QueryMsg::ElementInnerOuterTextQuery |
                 QueryMsg::InnerWindowDimensionsQuery |
                 QueryMsg::OffsetParentQuery |
+                QueryMsg::RenderedTextOfRangeQuery |
                 QueryMsg::ResolvedFontStyleQuery |
+                QueryMsg::TextCursorRectQuery |
                 QueryMsg::TextIndexQuery |
                 QueryMsg::StyleQuery => Self::empty(),
             },
@@ -7,6 +7,7 @@
 //! Layout. Performs layout on the DOM, builds display lists and sends them to be
 //! painted.
 
+mod anchor_positioning;
 mod cell;
 pub mod context;
 pub mod display_list;
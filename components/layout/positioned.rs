@@ -14,6 +14,9 @@ use style::logical_geometry::{Direction, WritingMode};
 use style::properties::ComputedValues;
 use style::values::specified::align::AlignFlags;
 
+use crate::anchor_positioning::{
+    AnchorMap, PositionTryOption, ResolvedInsets, uses_anchor_positioning,
+};
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::dom_traversal::{Contents, NodeAndStyleInfo};
@@ -385,6 +388,26 @@ impl HoistedAbsolutelyPositionedBox {
         containing_block: &DefiniteContainingBlock,
         containing_block_padding: PhysicalSides<Au>,
     ) {
+        let anchors = if boxes.iter().any(|hoisted_box| {
+            uses_anchor_positioning(
+                hoisted_box
+                    .absolutely_positioned_box
+                    .borrow()
+                    .context
+                    .style(),
+            )
+        }) {
+            AnchorMap::collect(
+                fragments,
+                containing_block
+                    .size
+                    .to_physical_size(containing_block.style.writing_mode),
+                containing_block_padding,
+            )
+        } else {
+            AnchorMap::default()
+        };
+
         if layout_context.use_rayon {
             let mut new_fragments = Vec::new();
             let mut new_hoisted_boxes = Vec::new();
@@ -398,6 +421,7 @@ impl HoistedAbsolutelyPositionedBox {
                         &mut new_hoisted_boxes,
                         containing_block,
                         containing_block_padding,
+                        &anchors,
                     );
 
                     hoisted_box.fragment.borrow_mut().fragment = Some(new_fragment.clone());
@@ -415,6 +439,7 @@ impl HoistedAbsolutelyPositionedBox {
                     for_nearest_containing_block_for_all_descendants,
                     containing_block,
                     containing_block_padding,
+                    &anchors,
                 );
 
                 box_.fragment.borrow_mut().fragment = Some(new_fragment.clone());
@@ -429,7 +454,86 @@ impl HoistedAbsolutelyPositionedBox {
         hoisted_absolutes_from_children: &mut Vec<HoistedAbsolutelyPositionedBox>,
         containing_block: &DefiniteContainingBlock,
         containing_block_padding: PhysicalSides<Au>,
+        anchors: &AnchorMap,
     ) -> Fragment {
+        let style = self
+            .absolutely_positioned_box
+            .borrow()
+            .context
+            .style()
+            .clone();
+        let containing_block_writing_mode = containing_block.style.writing_mode;
+        let containing_block_size = containing_block
+            .size
+            .to_physical_size(containing_block_writing_mode);
+        let containing_block_rect =
+            PhysicalRect::new(PhysicalPoint::origin(), containing_block_size);
+
+        // Lay out the box with each of its `position-try-fallbacks` in turn, stopping at the
+        // first one which doesn't overflow the containing block. If none of them fit, the
+        // box is laid out using its base style.
+        // <https://drafts.csswg.org/css-anchor-position-1/#fallback-apply>
+        let mut chosen_layout = None;
+        for option in PositionTryOption::all_for_style(&style, layout_context) {
+            let insets = ResolvedInsets::new(
+                &style,
+                &option,
+                anchors,
+                containing_block_size,
+                containing_block_writing_mode,
+            );
+            let (new_fragment, positioning_context) = self.layout_with_box_offsets(
+                layout_context,
+                containing_block,
+                containing_block_padding,
+                insets.box_offsets(containing_block_writing_mode),
+            );
+            let fits = containing_block_rect.contains_rect(&new_fragment.margin_rect());
+            if fits || chosen_layout.is_none() {
+                chosen_layout = Some((new_fragment, positioning_context));
+            }
+            if fits {
+                break;
+            }
+        }
+        let (mut new_fragment, mut positioning_context) =
+            chosen_layout.expect("Should always try to lay out the base style");
+
+        // This is an absolutely positioned element, which means it also establishes a
+        // containing block for absolutes. We lay out any absolutely positioned children
+        // here and pass the rest to `hoisted_absolutes_from_children.`
+        positioning_context.layout_collected_children(layout_context, &mut new_fragment);
+
+        // Any hoisted boxes that remain in this positioning context are going to be hoisted
+        // up above this absolutely positioned box. These will necessarily be fixed position
+        // elements, because absolutely positioned elements form containing blocks for all
+        // other elements. If any of them have a static start position though, we need to
+        // adjust it to account for the start corner of this absolute.
+        positioning_context.adjust_static_position_of_hoisted_fragments_with_offset(
+            &new_fragment.content_rect.origin.to_vector(),
+            PositioningContextLength::zero(),
+        );
+
+        hoisted_absolutes_from_children.extend(positioning_context.absolutes);
+
+        let fragment = Fragment::Box(ArcRefCell::new(new_fragment));
+        self.absolutely_positioned_box
+            .borrow()
+            .context
+            .base
+            .set_fragment(fragment.clone());
+        fragment
+    }
+
+    /// Lay out this box using the given (possibly anchor-resolved) box offsets, returning
+    /// the new fragment and the [`PositioningContext`] holding its hoisted descendants.
+    fn layout_with_box_offsets(
+        &self,
+        layout_context: &LayoutContext,
+        containing_block: &DefiniteContainingBlock,
+        containing_block_padding: PhysicalSides<Au>,
+        box_offset: LogicalSides<LengthPercentageOrAuto<'_>>,
+    ) -> (BoxFragment, PositioningContext) {
         let cbis = containing_block.size.inline;
         let cbbs = containing_block.size.block;
         let containing_block_writing_mode = containing_block.style.writing_mode;
@@ -457,8 +561,6 @@ impl HoistedAbsolutelyPositionedBox {
         static_position_rect.size = static_position_rect.size.max(PhysicalSize::zero());
         let static_position_rect = static_position_rect.to_logical(containing_block);
 
-        let box_offset = style.box_offsets(containing_block.style.writing_mode);
-
         // When the "static-position rect" doesn't come into play, we do not do any alignment
         // in the inline axis.
         let inline_box_offsets = box_offset.inline_sides();
@@ -627,7 +729,7 @@ impl HoistedAbsolutelyPositionedBox {
             },
             size: content_size,
         };
-        let mut new_fragment = BoxFragment::new(
+        let new_fragment = BoxFragment::new(
             context.base_fragment_info(),
             style,
             fragments,
@@ -638,26 +740,7 @@ impl HoistedAbsolutelyPositionedBox {
             specific_layout_info,
         );
 
-        // This is an absolutely positioned element, which means it also establishes a
-        // containing block for absolutes. We lay out any absolutely positioned children
-        // here and pass the rest to `hoisted_absolutes_from_children.`
-        positioning_context.layout_collected_children(layout_context, &mut new_fragment);
-
-        // Any hoisted boxes that remain in this positioning context are going to be hoisted
-        // up above this absolutely positioned box. These will necessarily be fixed position
-        // elements, because absolutely positioned elements form containing blocks for all
-        // other elements. If any of them have a static start position though, we need to
-        // adjust it to account for the start corner of this absolute.
-        positioning_context.adjust_static_position_of_hoisted_fragments_with_offset(
-            &new_fragment.content_rect.origin.to_vector(),
-            PositioningContextLength::zero(),
-        );
-
-        hoisted_absolutes_from_children.extend(positioning_context.absolutes);
-
-        let fragment = Fragment::Box(ArcRefCell::new(new_fragment));
-        context.base.set_fragment(fragment.clone());
-        fragment
+        (new_fragment, positioning_context)
     }
 }
 
@@ -14,6 +14,7 @@ use layout_api::{LayoutElementType, LayoutNodeType, OffsetParentResponse};
 use script::layout_dom::ServoLayoutNode;
 use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
+use style::Zero;
 use style::computed_values::display::T as Display;
 use style::computed_values::position::T as Position;
 use style::computed_values::visibility::T as Visibility;
@@ -44,6 +45,7 @@ use crate::flow::inline::construct::{TextTransformation, WhitespaceCollapse, cap
 use crate::fragment_tree::{
     BoxFragment, Fragment, FragmentFlags, FragmentTree, SpecificLayoutInfo,
 };
+use crate::geom::{PhysicalPoint, PhysicalRect};
 use crate::taffy::SpecificTaffyGridInfo;
 
 pub fn process_content_box_request(node: ServoLayoutNode<'_>) -> Option<Rect<Au>> {
@@ -580,19 +582,35 @@ pub fn get_the_text_steps(node: ServoLayoutNode<'_>) -> String {
     // Step 1: If element is not being rendered or if the user agent is a non-CSS user agent, then
     // return element's descendant text content.
     // This is taken care of in HTMLElemnent code
+    get_the_text_steps_with_state(node, RenderedTextCollectionState::default())
+}
 
-    // Step 2: Let results be a new empty list.
-    let mut results = Vec::new();
-    let mut max_req_line_break_count = 0;
+/// The rendered text of the part of the subtree of `root` between `start` and `end`, which
+/// are DOM range boundary points within that subtree, collected the same way as the
+/// `innerText` of `root`. This does a single traversal of the subtree up to `end`.
+pub fn get_the_rendered_text_of_range<'dom>(
+    root: ServoLayoutNode<'dom>,
+    start: (ServoLayoutNode<'dom>, usize),
+    end: (ServoLayoutNode<'dom>, usize),
+) -> String {
+    let state = RenderedTextCollectionState {
+        range: Some(RenderedTextRange { start, end }),
+        in_range: false,
+        ..Default::default()
+    };
+    get_the_text_steps_with_state(root, state)
+}
 
+fn get_the_text_steps_with_state<'dom>(
+    node: ServoLayoutNode<'dom>,
+    mut state: RenderedTextCollectionState<'dom>,
+) -> String {
+    // Step 2: Let results be a new empty list.
     // Step 3: For each child node node of element:
-    let mut state = Default::default();
-    for child in node.dom_children() {
-        // Step 1: Let current be the list resulting in running the rendered text collection steps with node.
-        let mut current = rendered_text_collection_steps(child, &mut state);
-        // Step 2: For each item item in current, append item to results.
-        results.append(&mut current);
-    }
+    // Step 1: Let current be the list resulting in running the rendered text collection steps with node.
+    // Step 2: For each item item in current, append item to results.
+    let results = rendered_text_collection_steps_for_children(node, &mut state);
+    let mut max_req_line_break_count = 0;
 
     let mut output = Vec::new();
     for item in results {
@@ -631,8 +649,16 @@ enum InnerOrOuterTextItem {
     RequiredLineBreakCount(usize),
 }
 
+/// A DOM range, whose boundary points are a node and an offset into it: an offset in
+/// UTF-16 code units for text nodes and an index into the children of other nodes.
+#[derive(Clone, Copy)]
+struct RenderedTextRange<'dom> {
+    start: (ServoLayoutNode<'dom>, usize),
+    end: (ServoLayoutNode<'dom>, usize),
+}
+
 #[derive(Clone)]
-struct RenderedTextCollectionState {
+struct RenderedTextCollectionState<'dom> {
     /// Used to make sure we don't add a `\n` before the first row
     first_table_row: bool,
     /// Used to make sure we don't add a `\t` before the first column
@@ -648,9 +674,16 @@ struct RenderedTextCollectionState {
     /// Is set to true when we're rendering the children of TableCell/TableCaption elements, that way we render
     /// everything inside those as normal, while omitting everything that's in a Table but NOT in a Cell/Caption
     within_table_content: bool,
+    /// The range to collect the text of, if only part of the subtree is collected.
+    range: Option<RenderedTextRange<'dom>>,
+    /// Whether the traversal is between the start and the end of `range`, so that the items
+    /// it produces are collected.
+    in_range: bool,
+    /// Whether the traversal has passed the end of `range`, so that it can stop.
+    past_end_of_range: bool,
 }
 
-impl Default for RenderedTextCollectionState {
+impl Default for RenderedTextCollectionState<'_> {
     fn default() -> Self {
         RenderedTextCollectionState {
             first_table_row: true,
@@ -659,25 +692,136 @@ impl Default for RenderedTextCollectionState {
             did_truncate_trailing_white_space: false,
             within_table: false,
             within_table_content: false,
+            range: None,
+            in_range: true,
+            past_end_of_range: false,
         }
     }
 }
 
+impl<'dom> RenderedTextCollectionState<'dom> {
+    /// Update the range state for the traversal reaching the boundary point at `offset`
+    /// within `node`.
+    fn reach_boundary_point(&mut self, node: ServoLayoutNode<'dom>, offset: usize) {
+        let Some(range) = self.range else {
+            return;
+        };
+        if range.start == (node, offset) {
+            self.in_range = true;
+        }
+        if range.end == (node, offset) {
+            self.in_range = false;
+            self.past_end_of_range = true;
+        }
+    }
+
+    /// Update the range state for the traversal skipping the subtree of `node`, which
+    /// may contain the boundary points of the range.
+    fn skip_subtree(&mut self, node: ServoLayoutNode<'dom>) {
+        let Some(range) = self.range else {
+            return;
+        };
+        let is_inclusive_ancestor_of = |mut descendant: ServoLayoutNode<'dom>| loop {
+            if descendant == node {
+                return true;
+            }
+            match descendant.parent_node() {
+                Some(parent) => descendant = parent,
+                None => return false,
+            }
+        };
+        if is_inclusive_ancestor_of(range.start.0) {
+            self.in_range = true;
+        }
+        if is_inclusive_ancestor_of(range.end.0) {
+            self.in_range = false;
+            self.past_end_of_range = true;
+        }
+    }
+
+    /// Update the range state for the traversal reaching the text node `node` and return
+    /// the part of its text that is in range, as UTF-16 offsets, or `None` if none of it is.
+    fn text_in_range(&mut self, node: ServoLayoutNode<'dom>) -> Option<(usize, Option<usize>)> {
+        let Some(range) = self.range else {
+            return Some((0, None));
+        };
+        let mut start = 0;
+        if range.start.0 == node {
+            self.in_range = true;
+            start = range.start.1;
+        }
+        if !self.in_range {
+            return None;
+        }
+        let mut end = None;
+        if range.end.0 == node {
+            self.in_range = false;
+            self.past_end_of_range = true;
+            end = Some(range.end.1);
+        }
+        Some((start, end))
+    }
+}
+
+/// Slice `text` between the UTF-16 offsets `start` and `end`, if any.
+fn slice_text_at_utf16_offsets(text: &str, start: usize, end: Option<usize>) -> &str {
+    let byte_index = |utf16_offset: usize| {
+        let mut utf16_length = 0;
+        text.char_indices()
+            .find(|(_, character)| {
+                let found = utf16_length >= utf16_offset;
+                utf16_length += character.len_utf16();
+                found
+            })
+            .map_or(text.len(), |(index, _)| index)
+    };
+    let start = byte_index(start);
+    let end = end.map_or(text.len(), byte_index).max(start);
+    &text[start..end]
+}
+
+/// Run the rendered text collection steps for each child of `node` and concatenate their
+/// items, keeping track of the boundary points between the children.
+fn rendered_text_collection_steps_for_children<'dom>(
+    node: ServoLayoutNode<'dom>,
+    state: &mut RenderedTextCollectionState<'dom>,
+) -> Vec<InnerOrOuterTextItem> {
+    let mut items = vec![];
+    let mut offset = 0;
+    for child in node.dom_children() {
+        state.reach_boundary_point(node, offset);
+        if state.past_end_of_range {
+            return items;
+        }
+        items.append(&mut rendered_text_collection_steps(child, state));
+        offset += 1;
+    }
+    state.reach_boundary_point(node, offset);
+    items
+}
+
 /// <https://html.spec.whatwg.org/multipage/#rendered-text-collection-steps>
-fn rendered_text_collection_steps(
-    node: ServoLayoutNode<'_>,
-    state: &mut RenderedTextCollectionState,
+fn rendered_text_collection_steps<'dom>(
+    node: ServoLayoutNode<'dom>,
+    state: &mut RenderedTextCollectionState<'dom>,
 ) -> Vec<InnerOrOuterTextItem> {
     // Step 1. Let items be the result of running the rendered text collection
     // steps with each child node of node in tree order,
     // and then concatenating the results to a single list.
     let mut items = vec![];
+    if state.past_end_of_range {
+        return items;
+    }
     if !node.is_connected() || !(node.is_element() || node.is_text_node()) {
+        state.skip_subtree(node);
         return items;
     }
 
     match node.type_id() {
         LayoutNodeType::Text => {
+            let Some((start, end)) = state.text_in_range(node) else {
+                return items;
+            };
             if let Some(element) = node.parent_node() {
                 match element.type_id() {
                     // Any text contained in these elements must be ignored.
@@ -751,6 +895,7 @@ fn rendered_text_collection_steps(
                 }
 
                 let text_content = node.to_threadsafe().node_text_content();
+                let text_content = slice_text_at_utf16_offsets(&text_content, start, end);
 
                 let white_space_collapse = style.clone_white_space_collapse();
                 let preserve_whitespace = white_space_collapse == WhiteSpaceCollapseValue::Preserve;
@@ -826,8 +971,9 @@ fn rendered_text_collection_steps(
             } else {
                 // If we don't have a parent element then there's no style data available,
                 // in this (pretty unlikely) case we just return the Text fragment as is.
+                let text_content = node.to_threadsafe().node_text_content();
                 items.push(InnerOrOuterTextItem::Text(
-                    node.to_threadsafe().node_text_content().into(),
+                    slice_text_at_utf16_offsets(&text_content, start, end).into(),
                 ));
             }
         },
@@ -836,17 +982,22 @@ fn rendered_text_collection_steps(
             // LF code point to items.
             state.did_truncate_trailing_white_space = false;
             state.may_start_with_whitespace = true;
-            items.push(InnerOrOuterTextItem::Text(String::from("\u{000A}")));
+            if state.in_range {
+                items.push(InnerOrOuterTextItem::Text(String::from("\u{000A}")));
+            }
+            state.skip_subtree(node);
         },
         _ => {
             // First we need to gather some infos to setup the various flags
             // before rendering the child nodes
             let Some(style_data) = node.style_data() else {
+                state.skip_subtree(node);
                 return items;
             };
 
             let element_data = style_data.element_data.borrow();
             let Some(style) = element_data.styles.get_primary() else {
+                state.skip_subtree(node);
                 return items;
             };
             let inherited_box = style.get_inherited_box();
@@ -855,10 +1006,7 @@ fn rendered_text_collection_steps(
                 // If the element is not visible then we'll immediatly render all children,
                 // skipping all other processing.
                 // We can't just stop here since a child can override a parents visibility.
-                for child in node.dom_children() {
-                    items.append(&mut rendered_text_collection_steps(child, state));
-                }
-                return items;
+                return rendered_text_collection_steps_for_children(node, state);
             }
 
             let style_box = style.get_box();
@@ -882,7 +1030,7 @@ fn rendered_text_collection_steps(
                 // enclosing 'table-row' box, then append a string containing
                 // a single U+0009 TAB code point to items.
                 Display::TableCell => {
-                    if !state.first_table_cell {
+                    if !state.first_table_cell && state.in_range {
                         items.push(InnerOrOuterTextItem::Text(String::from(
                             "\u{0009}", /* tab */
                         )));
@@ -897,7 +1045,7 @@ fn rendered_text_collection_steps(
                 // ancestor 'table' box, then append a string containing a single U+000A
                 // LF code point to items.
                 Display::TableRow => {
-                    if !state.first_table_row {
+                    if !state.first_table_row && state.in_range {
                         items.push(InnerOrOuterTextItem::Text(String::from(
                             "\u{000A}", /* Line Feed */
                         )));
@@ -969,13 +1117,14 @@ fn rendered_text_collection_steps(
                         state.did_truncate_trailing_white_space = false;
                     };
                     state.may_start_with_whitespace = false;
+                    state.skip_subtree(node);
                 },
                 _ => {
                     // Now we can finally iterate over all children, appending whatever
                     // they produce to items.
-                    for child in node.dom_children() {
-                        items.append(&mut rendered_text_collection_steps(child, state));
-                    }
+                    items.append(&mut rendered_text_collection_steps_for_children(
+                        node, state,
+                    ));
                 },
             }
 
@@ -1011,6 +1160,93 @@ pub fn process_text_index_request(_node: OpaqueNode, _point: Point2D<Au>) -> Opt
     None
 }
 
+/// Find the UTF-8 offset of the caret position closest to a hit test point within the
+/// text of `node`, which was the node hit. `point_in_item` is the point relative to the
+/// hit test item, which takes into account any transforms and scroll offsets, while
+/// `point_in_initial_containing_block` is used to choose between the fragments of `node`
+/// when more than one of them could contain `point_in_item`, for instance when text
+/// wraps onto several lines.
+pub fn process_caret_position_query(
+    fragment_tree: Option<Rc<FragmentTree>>,
+    node: OpaqueNode,
+    point_in_item: Point2D<Au>,
+    point_in_initial_containing_block: Point2D<Au>,
+) -> Option<usize> {
+    let mut text_fragments = Vec::new();
+    fragment_tree?.find(|fragment, _, containing_block| {
+        if let Fragment::Text(text_fragment) = fragment {
+            let borrowed_text_fragment = text_fragment.borrow();
+            if borrowed_text_fragment
+                .base
+                .tag
+                .is_some_and(|tag| tag.node == node)
+            {
+                let rect = borrowed_text_fragment
+                    .rect
+                    .translate(containing_block.origin.to_vector());
+                text_fragments.push((text_fragment.clone(), rect));
+            }
+        }
+        None::<()>
+    });
+
+    let point_is_within_item = |rect: &PhysicalRect<Au>| {
+        point_in_item.x >= Au::zero() &&
+            point_in_item.y >= Au::zero() &&
+            point_in_item.x <= rect.size.width &&
+            point_in_item.y <= rect.size.height
+    };
+    let distance_to_point = |rect: &PhysicalRect<Au>| {
+        let point = point_in_initial_containing_block;
+        let inline_distance = (rect.min_x() - point.x).max(point.x - rect.max_x());
+        let block_distance = (rect.min_y() - point.y).max(point.y - rect.max_y());
+        inline_distance.max(Au::zero()) + block_distance.max(Au::zero())
+    };
+
+    let (text_fragment, rect) = text_fragments
+        .iter()
+        .min_by_key(|(_, rect)| (!point_is_within_item(rect), distance_to_point(rect)))?;
+    let point_in_fragment = if point_is_within_item(rect) {
+        point_in_item
+    } else {
+        point_in_initial_containing_block - rect.origin.to_vector().to_untyped()
+    };
+
+    let offset = text_fragment
+        .borrow()
+        .text_offset_for_point(PhysicalPoint::from_untyped(point_in_fragment));
+    Some(offset.0.max(0) as usize)
+}
+
+/// Find the rectangle of the caret in the text of the text control `node`, relative to the
+/// initial containing block. This is where the caret is painted for the selection of the
+/// control, or at the end of the text that an input method is composing in it.
+pub fn process_text_cursor_rect_query(
+    fragment_tree: Option<Rc<FragmentTree>>,
+    node: OpaqueNode,
+) -> Option<Rect<Au>> {
+    fragment_tree?.find(|fragment, _, containing_block| {
+        let Fragment::Text(text_fragment) = fragment else {
+            return None;
+        };
+        let text_fragment = text_fragment.borrow();
+        if text_fragment.base.tag.is_none_or(|tag| tag.node != node) {
+            return None;
+        }
+        let offset = text_fragment.caret_offset()?;
+        let rect = text_fragment
+            .rect
+            .translate(containing_block.origin.to_vector());
+        Some(Rect::new(
+            Point2D::new(
+                rect.origin.x + text_fragment.inline_advance_to(offset),
+                rect.origin.y,
+            ),
+            Size2D::new(Au::zero(), rect.size.height),
+        ))
+    })
+}
+
 pub fn process_resolved_font_style_query<'dom, E>(
     context: &SharedStyleContext,
     node: E,
@@ -6,6 +6,7 @@ use app_units::Au;
 use malloc_size_of_derive::MallocSizeOf;
 use style::Zero;
 use style::color::AbsoluteColor;
+use style::computed_values::_servo_top_layer::T as TopLayer;
 use style::computed_values::direction::T as Direction;
 use style::computed_values::isolation::T as ComputedIsolation;
 use style::computed_values::mix_blend_mode::T as ComputedMixBlendMode;
@@ -122,6 +123,8 @@ pub(crate) enum DisplayInside {
     Flex,
     Grid,
     Table,
+    // <https://drafts.csswg.org/css-ruby/#ruby-display>
+    Ruby,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -365,6 +368,7 @@ pub(crate) trait ComputedValuesExt {
         writing_mode: WritingMode,
     ) -> bool;
     fn is_inline_box(&self, fragment_flags: FragmentFlags) -> bool;
+    fn is_in_top_layer(&self) -> bool;
     fn overflow_direction(&self) -> OverflowDirection;
     fn to_bidi_level(&self) -> Level;
 }
@@ -375,11 +379,18 @@ impl ComputedValuesExt for ComputedValues {
             match inset {
                 Inset::LengthPercentage(v) => LengthPercentageOrAuto::LengthPercentage(v),
                 Inset::Auto => LengthPercentageOrAuto::Auto,
-                Inset::AnchorFunction(_) => unreachable!("anchor() should be disabled"),
-                Inset::AnchorSizeFunction(_) => unreachable!("anchor-size() should be disabled"),
-                Inset::AnchorContainingCalcFunction(_) => {
-                    unreachable!("anchor() and anchor-size() should be disabled")
-                },
+                // Anchor functions are resolved when laying out absolutely positioned boxes
+                // (see `crate::anchor_positioning`). Anywhere else they are invalid, so they
+                // resolve to their fallback, or to `auto` when there isn't one.
+                Inset::AnchorFunction(function) => function
+                    .fallback
+                    .as_ref()
+                    .map_or(LengthPercentageOrAuto::Auto, convert),
+                Inset::AnchorSizeFunction(function) => function
+                    .fallback
+                    .as_ref()
+                    .map_or(LengthPercentageOrAuto::Auto, convert),
+                Inset::AnchorContainingCalcFunction(_) => LengthPercentageOrAuto::Auto,
             }
         }
         let position = self.get_position();
@@ -527,6 +538,15 @@ impl ComputedValuesExt for ComputedValues {
                 .intersects(FragmentFlags::IS_REPLACED | FragmentFlags::IS_TEXT_CONTROL)
     }
 
+    /// Whether the box is in the [top layer], like fullscreen elements and modal dialogs,
+    /// which the user agent stylesheet expresses with the internal `-servo-top-layer`
+    /// property.
+    ///
+    /// [top layer]: https://drafts.csswg.org/css-position-4/#top-layer
+    fn is_in_top_layer(&self) -> bool {
+        self.get_box().clone__servo_top_layer() == TopLayer::Top
+    }
+
     /// Returns true if this is a transformable element.
     fn is_transformable(&self, fragment_flags: FragmentFlags) -> bool {
         // "A transformable element is an element in one of these categories:
@@ -692,6 +712,12 @@ impl ComputedValuesExt for ComputedValues {
 
     /// Returns true if this fragment establishes a new stacking context and false otherwise.
     fn establishes_stacking_context(&self, fragment_flags: FragmentFlags) -> bool {
+        // From <https://drafts.csswg.org/css-position-4/#top-layer>:
+        // > Boxes in the top layer generate a stacking context.
+        if self.is_in_top_layer() {
+            return true;
+        }
+
         // From <https://www.w3.org/TR/css-will-change/#valdef-will-change-custom-ident>:
         // > If any non-initial value of a property would create a stacking context on the element,
         // > specifying that property in will-change must create a stacking context on the element.
@@ -758,6 +784,13 @@ impl ComputedValuesExt for ComputedValues {
             return true;
         }
 
+        // From <https://drafts.fxtf.org/filter-effects-2/#BackdropFilterProperty>
+        // > A computed value of other than none results in the creation of both a stacking
+        // > context and a Containing Block for absolute and fixed position descendants
+        if !effects.backdrop_filter.0.is_empty() {
+            return true;
+        }
+
         // From <https://www.w3.org/TR/compositing-1/#mix-blend-mode>
         // > Applying a blendmode other than `normal` to the element must establish a new stacking context
         // Note `will-change: mix-blend-mode` is handled above by `STACKING_CONTEXT_UNCONDITIONAL`.
@@ -862,6 +895,7 @@ impl ComputedValuesExt for ComputedValues {
         // > a document root element in the current browsing context.
         if !fragment_flags.contains(FragmentFlags::IS_ROOT_ELEMENT) &&
             (!self.get_effects().filter.0.is_empty() ||
+                !self.get_effects().backdrop_filter.0.is_empty() ||
                 will_change_bits.intersects(WillChangeBits::FIXPOS_CB_NON_SVG))
         {
             return true;
@@ -1242,6 +1276,16 @@ impl From<stylo::Display> for Display {
             stylo::DisplayOutside::None if inside == stylo::DisplayInside::Contents => {
                 return Display::Contents;
             },
+            // The internal ruby boxes are laid out as inline boxes. Within a ruby container,
+            // they are paired into ruby segments during box tree construction.
+            stylo::DisplayOutside::InternalRuby => {
+                return Display::GeneratingBox(DisplayGeneratingBox::OutsideInside {
+                    outside: DisplayOutside::Inline,
+                    inside: DisplayInside::Flow {
+                        is_list_item: false,
+                    },
+                });
+            },
             stylo::DisplayOutside::None => return Display::None,
         };
 
@@ -1254,6 +1298,7 @@ impl From<stylo::Display> for Display {
             },
             stylo::DisplayInside::Flex => DisplayInside::Flex,
             stylo::DisplayInside::Grid => DisplayInside::Grid,
+            stylo::DisplayInside::Ruby => DisplayInside::Ruby,
 
             // These should not be values of DisplayInside, but oh well
             stylo::DisplayInside::None => return Display::None,
@@ -1266,7 +1311,13 @@ impl From<stylo::Display> for Display {
             stylo::DisplayInside::TableHeaderGroup |
             stylo::DisplayInside::TableFooterGroup |
             stylo::DisplayInside::TableRow |
-            stylo::DisplayInside::TableCell => unreachable!("Internal DisplayInside found"),
+            stylo::DisplayInside::TableCell |
+            stylo::DisplayInside::RubyBase |
+            stylo::DisplayInside::RubyBaseContainer |
+            stylo::DisplayInside::RubyText |
+            stylo::DisplayInside::RubyTextContainer => {
+                unreachable!("Internal DisplayInside found")
+            },
         };
         Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { outside, inside })
     }
@@ -3,7 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 mod text {
+    use fonts::ByteIndex;
     use layout::flow::inline::construct::WhitespaceCollapse;
+    use layout::flow::inline::text_run::WhitespaceCollapseTable;
     use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
 
     #[test]
@@ -60,4 +62,43 @@ mod text {
         let output = collapse("\n        ", WhiteSpaceCollapse::Collapse, false);
         assert_eq!(output, " ");
     }
+
+    #[test]
+    fn test_whitespace_collapse_table() {
+        let dom_offsets = |input: &str, white_space_collapse, trim_beginning_white_space| {
+            let collapsed_length = WhitespaceCollapse::new(
+                input.chars(),
+                white_space_collapse,
+                trim_beginning_white_space,
+            )
+            .map(char::len_utf8)
+            .sum::<usize>();
+            let table = WhitespaceCollapseTable::new(
+                input,
+                white_space_collapse,
+                trim_beginning_white_space,
+            );
+            (0..=collapsed_length)
+                .map(|offset| table.dom_offset(ByteIndex(offset as isize)).0)
+                .collect::<Vec<_>>()
+        };
+
+        let offsets = dom_offsets("H W", WhiteSpaceCollapse::Collapse, false);
+        assert_eq!(offsets, vec![0, 1, 2, 3]);
+
+        let offsets = dom_offsets(" W", WhiteSpaceCollapse::Collapse, true);
+        assert_eq!(offsets, vec![1, 2]);
+
+        let offsets = dom_offsets("\n   H  \n \t  W", WhiteSpaceCollapse::Collapse, false);
+        assert_eq!(offsets, vec![0, 4, 5, 12, 13]);
+
+        let offsets = dom_offsets("Hello \n World", WhiteSpaceCollapse::PreserveBreaks, true);
+        assert_eq!(offsets, vec![0, 1, 2, 3, 4, 5, 8, 9, 10, 11, 12, 13]);
+
+        let offsets = dom_offsets("\n  H\t W", WhiteSpaceCollapse::Preserve, false);
+        assert_eq!(offsets, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+
+        let offsets = dom_offsets("\u{e9}  \u{e9}", WhiteSpaceCollapse::Collapse, false);
+        assert_eq!(offsets, vec![0, 1, 2, 4, 5, 6]);
+    }
 }
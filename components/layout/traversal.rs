@@ -2,12 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use fxhash::FxHashSet;
 use layout_api::LayoutDamage;
 use layout_api::wrapper_traits::LayoutNode;
 use script::layout_dom::ServoLayoutNode;
 use style::context::{SharedStyleContext, StyleContext};
 use style::data::ElementData;
-use style::dom::{NodeInfo, TElement, TNode};
+use style::dom::{NodeInfo, OpaqueNode, TElement, TNode};
 use style::selector_parser::RestyleDamage;
 use style::traversal::{DomTraversal, PerLevelTraversalData, recalc_style_at};
 use style::values::computed::Display;
@@ -93,19 +94,94 @@ where
     }
 }
 
+/// Compute the damage of the DOM tree rooted at `node` and update the style of the boxes
+/// and fragments that are preserved. The nodes whose boxes and fragments had their style
+/// updated are added to `repaired_nodes`, as their fragments need to be repainted.
+///
+/// Only the subtree of `dirty_root`, which contains every node restyled since the last
+/// layout, can have gained damage. Unless the environment damages the entire tree, only
+/// that subtree is traversed and its damage is then propagated up to `node`, so that a
+/// style change confined to a subtree doesn't require visiting the rest of the document.
 #[servo_tracing::instrument(skip_all)]
 pub(crate) fn compute_damage_and_repair_style(
     context: &SharedStyleContext,
     node: ServoLayoutNode<'_>,
+    dirty_root: ServoLayoutNode<'_>,
     damage_from_environment: RestyleDamage,
+    repaired_nodes: &mut FxHashSet<OpaqueNode>,
 ) -> RestyleDamage {
-    compute_damage_and_repair_style_inner(context, node, damage_from_environment)
+    if !damage_from_environment.is_empty() || dirty_root == node {
+        return compute_damage_and_repair_style_inner(
+            context,
+            node,
+            damage_from_environment,
+            repaired_nodes,
+        );
+    }
+
+    let damage_from_dirty_root = compute_damage_and_repair_style_inner(
+        context,
+        dirty_root,
+        RestyleDamage::empty(),
+        repaired_nodes,
+    );
+    propagate_damage_to_ancestors(dirty_root, damage_from_dirty_root)
+}
+
+/// Propagate the damage that the subtree of `dirty_root` reports to its parent up through
+/// the ancestors of `dirty_root`, the same way that [`compute_damage_and_repair_style_inner`]
+/// does when traversing them, and return the damage of the root of the tree. The ancestors
+/// weren't restyled, so this only marks them as needing to recollect their box tree
+/// children or to be laid out again.
+fn propagate_damage_to_ancestors(
+    dirty_root: ServoLayoutNode<'_>,
+    damage_from_dirty_root: RestyleDamage,
+) -> RestyleDamage {
+    let mut damage_from_child = damage_from_dirty_root;
+    let mut child = dirty_root;
+    while let Some(parent) = child.traversal_parent() {
+        let parent = parent.as_node();
+        let Some(style_data) = parent.style_data() else {
+            break;
+        };
+
+        let element_damage = {
+            let mut element_data = style_data.element_data.borrow_mut();
+            let is_display_none = element_data
+                .styles
+                .primary
+                .as_ref()
+                .is_some_and(|style| style.get_box().display == Display::None);
+            if is_display_none {
+                // The descendants of this element don't affect layout.
+                damage_from_child = RestyleDamage::empty();
+            }
+            if damage_from_child.contains(LayoutDamage::recollect_box_tree_children()) {
+                element_data
+                    .damage
+                    .insert(LayoutDamage::recollect_box_tree_children());
+            }
+            element_data.damage
+        };
+
+        let damage_for_parent = element_damage | (damage_from_child & RestyleDamage::RELAYOUT);
+        if element_damage != RestyleDamage::reconstruct() &&
+            damage_for_parent.contains(RestyleDamage::RELAYOUT)
+        {
+            parent.clear_fragment_layout_cache();
+        }
+
+        damage_from_child = damage_for_parent;
+        child = parent;
+    }
+    damage_from_child
 }
 
 pub(crate) fn compute_damage_and_repair_style_inner(
     context: &SharedStyleContext,
     node: ServoLayoutNode<'_>,
     damage_from_parent: RestyleDamage,
+    repaired_nodes: &mut FxHashSet<OpaqueNode>,
 ) -> RestyleDamage {
     let mut element_damage;
     let original_element_damage;
@@ -132,8 +208,12 @@ pub(crate) fn compute_damage_and_repair_style_inner(
     let mut damage_from_children = RestyleDamage::empty();
     for child in iter_child_nodes(node) {
         if child.is_element() {
-            damage_from_children |=
-                compute_damage_and_repair_style_inner(context, child, damage_for_children);
+            damage_from_children |= compute_damage_and_repair_style_inner(
+                context,
+                child,
+                damage_for_children,
+                repaired_nodes,
+            );
         }
     }
 
@@ -164,6 +244,7 @@ pub(crate) fn compute_damage_and_repair_style_inner(
     let element_layout_damage: LayoutDamage = element_damage.into();
     if !element_layout_damage.has_box_damage() && !original_element_damage.is_empty() {
         node.repair_style(context);
+        repaired_nodes.insert(node.opaque());
     }
 
     damage_for_parent
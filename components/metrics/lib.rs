@@ -96,6 +96,10 @@ pub struct ProgressiveWebMetrics {
     ///
     /// See <https://w3c.github.io/paint-timing/#first-contentful-paint>
     first_contentful_paint: Cell<Option<CrossProcessInstant>>,
+    /// The paint of the largest image or text of a particular document so far.
+    ///
+    /// See <https://w3c.github.io/largest-contentful-paint/>
+    largest_contentful_paint: Cell<Option<CrossProcessInstant>>,
     #[ignore_malloc_size_of = "can't measure channels"]
     time_profiler_chan: ProfilerChan,
     url: ServoUrl,
@@ -153,6 +157,7 @@ impl ProgressiveWebMetrics {
             time_to_interactive: Cell::new(None),
             first_paint: Cell::new(None),
             first_contentful_paint: Cell::new(None),
+            largest_contentful_paint: Cell::new(None),
             time_profiler_chan,
             url,
         }
@@ -194,6 +199,14 @@ impl ProgressiveWebMetrics {
         self.first_contentful_paint.get()
     }
 
+    pub fn largest_contentful_paint(&self) -> Option<CrossProcessInstant> {
+        self.largest_contentful_paint.get()
+    }
+
+    pub fn set_largest_contentful_paint(&self, paint_time: CrossProcessInstant) {
+        self.largest_contentful_paint.set(Some(paint_time));
+    }
+
     pub fn main_thread_available(&self) -> Option<CrossProcessInstant> {
         self.main_thread_available.get()
     }
@@ -0,0 +1,601 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The caches of the [Cache Storage API](https://w3c.github.io/ServiceWorker/#cache-objects)
+//! of every origin, which the storage thread persists and counts towards the usage of the
+//! origins.
+
+use std::collections::HashMap;
+
+use http::header::VARY;
+use http::{HeaderMap, Method};
+use malloc_size_of_derive::MallocSizeOf;
+use net_traits::storage_thread::{
+    CacheBatchError, CacheEntry, CacheId, CacheQueryOptions, CachedRequest,
+};
+use serde::{Deserialize, Serialize};
+use url::Position;
+
+/// <https://w3c.github.io/ServiceWorker/#dfn-cache-batch-operation>
+pub(crate) enum CacheBatchOperation {
+    /// Removes the entries whose request matches the given one.
+    Delete(CachedRequest, CacheQueryOptions),
+    /// Stores an entry, replacing the entries whose request matches its request.
+    Put(CacheEntry),
+}
+
+/// A cache, which is a <https://w3c.github.io/ServiceWorker/#dfn-request-response-list>.
+#[derive(Deserialize, MallocSizeOf, Serialize)]
+struct Cache {
+    id: CacheId,
+    name: String,
+    entries: Vec<CacheEntry>,
+}
+
+/// The caches of every origin, keyed by the serialization of the origin.
+///
+/// Unlike in the specification, deleting a cache drops its entries right away, after which
+/// the `Cache` objects for it behave as an empty cache that cannot be written to.
+#[derive(Default, Deserialize, MallocSizeOf, Serialize)]
+pub(crate) struct CacheStorage {
+    /// The <https://w3c.github.io/ServiceWorker/#dfn-relevant-name-to-cache-map> of every
+    /// origin, whose caches are in creation order.
+    origins: HashMap<String, Vec<Cache>>,
+    /// The id of the next cache to be created.
+    next_cache_id: u64,
+}
+
+impl CacheStorage {
+    fn caches(&self, origin: &str) -> &[Cache] {
+        self.origins.get(origin).map_or(&[], Vec::as_slice)
+    }
+
+    fn cache(&self, origin: &str, id: CacheId) -> Option<&Cache> {
+        self.caches(origin).iter().find(|cache| cache.id == id)
+    }
+
+    fn cache_mut(&mut self, origin: &str, id: CacheId) -> Option<&mut Cache> {
+        self.origins
+            .get_mut(origin)?
+            .iter_mut()
+            .find(|cache| cache.id == id)
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-open>
+    pub(crate) fn open(&mut self, origin: &str, name: String) -> CacheId {
+        let caches = self.origins.entry(origin.to_owned()).or_default();
+        if let Some(cache) = caches.iter().find(|cache| cache.name == name) {
+            return cache.id;
+        }
+        let id = CacheId(self.next_cache_id);
+        self.next_cache_id += 1;
+        caches.push(Cache {
+            id,
+            name,
+            entries: vec![],
+        });
+        id
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-has>
+    pub(crate) fn has(&self, origin: &str, name: &str) -> bool {
+        self.caches(origin).iter().any(|cache| cache.name == name)
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-delete>
+    pub(crate) fn delete(&mut self, origin: &str, name: &str) -> bool {
+        let Some(caches) = self.origins.get_mut(origin) else {
+            return false;
+        };
+        let length = caches.len();
+        caches.retain(|cache| cache.name != name);
+        let deleted = caches.len() != length;
+        if caches.is_empty() {
+            self.origins.remove(origin);
+        }
+        deleted
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-keys>
+    pub(crate) fn keys(&self, origin: &str) -> Vec<String> {
+        self.caches(origin)
+            .iter()
+            .map(|cache| cache.name.clone())
+            .collect()
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-match>
+    pub(crate) fn match_in_caches(
+        &self,
+        origin: &str,
+        cache_name: Option<&str>,
+        request: &CachedRequest,
+        options: CacheQueryOptions,
+    ) -> Option<CacheEntry> {
+        self.caches(origin)
+            .iter()
+            .filter(|cache| cache_name.is_none_or(|name| cache.name == name))
+            .find_map(|cache| query_cache(&cache.entries, request, options).next())
+            .cloned()
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-matchall>, and
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-keys> with the responses.
+    pub(crate) fn match_all(
+        &self,
+        origin: &str,
+        id: CacheId,
+        request: Option<&CachedRequest>,
+        options: CacheQueryOptions,
+    ) -> Vec<&CacheEntry> {
+        let Some(cache) = self.cache(origin, id) else {
+            return vec![];
+        };
+        match request {
+            Some(request) => query_cache(&cache.entries, request, options).collect(),
+            None => cache.entries.iter().collect(),
+        }
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#batch-cache-operations>
+    ///
+    /// Returns whether an entry was deleted or stored, or fails without changing the cache
+    /// when it would have to hold more than `quota` bytes across the caches of the origin.
+    pub(crate) fn batch(
+        &mut self,
+        origin: &str,
+        id: CacheId,
+        operations: Vec<CacheBatchOperation>,
+        quota: u64,
+    ) -> Result<bool, CacheBatchError> {
+        let usage_before = self.usage(origin);
+        let Some(cache) = self.cache_mut(origin, id) else {
+            return Ok(false);
+        };
+
+        // Step 1 to 3.
+        let mut entries = cache.entries.clone();
+        let mut added_items: Vec<(CachedRequest, HeaderMap)> = vec![];
+        let mut changed = false;
+
+        // Step 4.3.
+        for operation in operations {
+            let (request, options) = match &operation {
+                CacheBatchOperation::Delete(request, options) => (request, *options),
+                CacheBatchOperation::Put(entry) => (&entry.request, CacheQueryOptions::default()),
+            };
+
+            // Step 4.3.2.
+            if added_items.iter().any(|(added_request, added_headers)| {
+                request_matches_cached_item(request, added_request, Some(added_headers), options)
+            }) {
+                return Err(CacheBatchError::InvalidState);
+            }
+
+            // Step 4.3.3 to 4.3.5, removing the matching entries.
+            let length = entries.len();
+            entries.retain(|entry| {
+                !request_matches_cached_item(
+                    request,
+                    &entry.request,
+                    Some(&entry.response.headers),
+                    options,
+                )
+            });
+            changed |= entries.len() != length;
+
+            // Step 4.3.6.
+            if let CacheBatchOperation::Put(entry) = operation {
+                added_items.push((entry.request.clone(), entry.response.headers.clone()));
+                entries.push(entry);
+                changed = true;
+            }
+        }
+
+        let usage_after = usage_before - entries_size(&cache.entries) + entries_size(&entries);
+        if usage_after > usage_before && usage_after > quota {
+            // Step 4.4, which restores the cache as it was.
+            return Err(CacheBatchError::QuotaExceeded);
+        }
+        cache.entries = entries;
+        Ok(changed)
+    }
+
+    /// The number of bytes used by the caches of an origin.
+    pub(crate) fn usage(&self, origin: &str) -> u64 {
+        self.caches(origin)
+            .iter()
+            .map(|cache| entries_size(&cache.entries))
+            .sum()
+    }
+
+    /// The number of bytes used by the caches of every origin that has some.
+    pub(crate) fn usage_by_origin(&self) -> impl Iterator<Item = (&String, u64)> {
+        self.origins
+            .keys()
+            .map(|origin| (origin, self.usage(origin)))
+    }
+
+    /// Deletes all the caches of an origin, and returns whether it had any.
+    pub(crate) fn clear_origin(&mut self, origin: &str) -> bool {
+        self.origins.remove(origin).is_some()
+    }
+}
+
+/// The number of bytes the entries take up, counting their URLs, headers and bodies.
+fn entries_size(entries: &[CacheEntry]) -> u64 {
+    fn headers_size(headers: &HeaderMap) -> usize {
+        headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum()
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let request = &entry.request;
+            let response = &entry.response;
+            (request.url.as_str().len() +
+                headers_size(&request.headers) +
+                response.url.as_ref().map_or(0, |url| url.as_str().len()) +
+                response.status_text.len() +
+                headers_size(&response.headers) +
+                response.body.len()) as u64
+        })
+        .sum()
+}
+
+/// <https://w3c.github.io/ServiceWorker/#query-cache>
+fn query_cache<'a, 'b>(
+    entries: &'a [CacheEntry],
+    request: &'b CachedRequest,
+    options: CacheQueryOptions,
+) -> impl Iterator<Item = &'a CacheEntry> {
+    entries.iter().filter(move |entry| {
+        request_matches_cached_item(
+            request,
+            &entry.request,
+            Some(&entry.response.headers),
+            options,
+        )
+    })
+}
+
+/// <https://w3c.github.io/ServiceWorker/#request-matches-cached-item>, given the headers of
+/// the cached response, if there is one.
+fn request_matches_cached_item(
+    request_query: &CachedRequest,
+    request: &CachedRequest,
+    response_headers: Option<&HeaderMap>,
+    options: CacheQueryOptions,
+) -> bool {
+    // Step 1.
+    if !options.ignore_method && request_query.method != Method::GET {
+        return false;
+    }
+
+    // Step 2 to 5, comparing the URLs without their fragment, and without their query when
+    // ignoring it.
+    let end = if options.ignore_search {
+        Position::AfterPath
+    } else {
+        Position::AfterQuery
+    };
+    if request_query.url.as_url()[..end] != request.url.as_url()[..end] {
+        return false;
+    }
+
+    // Step 6.
+    let Some(response_headers) = response_headers else {
+        return true;
+    };
+    if options.ignore_vary {
+        return true;
+    }
+
+    // Step 7 and 8.
+    response_headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|field_name| !field_name.is_empty())
+        .all(|field_name| {
+            field_name != "*" &&
+                combined_value(&request.headers, field_name) ==
+                    combined_value(&request_query.headers, field_name)
+        })
+}
+
+/// The <https://fetch.spec.whatwg.org/#concept-header-list-combine> value of a header.
+fn combined_value(headers: &HeaderMap, name: &str) -> Option<Vec<u8>> {
+    let mut values = headers.get_all(name).iter();
+    let mut combined = values.next()?.as_bytes().to_vec();
+    for value in values {
+        combined.extend_from_slice(b", ");
+        combined.extend_from_slice(value.as_bytes());
+    }
+    Some(combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+    use net_traits::storage_thread::{CachedResponse, CachedResponseType};
+    use servo_url::ServoUrl;
+
+    use super::*;
+
+    const ORIGIN: &str = "https://example.com";
+
+    fn request(url: &str) -> CachedRequest {
+        CachedRequest {
+            url: ServoUrl::parse(url).unwrap(),
+            method: Method::GET,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    fn entry(url: &str, body: &[u8]) -> CacheEntry {
+        CacheEntry {
+            request: request(url),
+            response: CachedResponse {
+                response_type: CachedResponseType::Basic,
+                url: Some(ServoUrl::parse(url).unwrap()),
+                status: 200,
+                status_text: b"OK".to_vec(),
+                headers: HeaderMap::new(),
+                body: body.to_vec(),
+            },
+        }
+    }
+
+    fn put(storage: &mut CacheStorage, id: CacheId, entry: CacheEntry) {
+        storage
+            .batch(ORIGIN, id, vec![CacheBatchOperation::Put(entry)], u64::MAX)
+            .unwrap();
+    }
+
+    fn bodies(entries: Vec<&CacheEntry>) -> Vec<&[u8]> {
+        entries
+            .into_iter()
+            .map(|entry| &entry.response.body[..])
+            .collect()
+    }
+
+    #[test]
+    fn test_open_has_delete_and_keys() {
+        let mut storage = CacheStorage::default();
+        let first = storage.open(ORIGIN, "v1".to_owned());
+        let second = storage.open(ORIGIN, "v2".to_owned());
+        assert_ne!(first, second);
+        assert_eq!(storage.open(ORIGIN, "v1".to_owned()), first);
+        assert!(storage.has(ORIGIN, "v1"));
+        assert!(!storage.has("https://other.com", "v1"));
+        assert_eq!(storage.keys(ORIGIN), ["v1", "v2"]);
+
+        assert!(storage.delete(ORIGIN, "v1"));
+        assert!(!storage.delete(ORIGIN, "v1"));
+        assert_eq!(storage.keys(ORIGIN), ["v2"]);
+        // A cache opened again under a deleted name is a new cache.
+        assert_ne!(storage.open(ORIGIN, "v1".to_owned()), first);
+    }
+
+    #[test]
+    fn test_put_replaces_matching_entries() {
+        let mut storage = CacheStorage::default();
+        let id = storage.open(ORIGIN, "v1".to_owned());
+        put(&mut storage, id, entry("https://example.com/a", b"first"));
+        put(&mut storage, id, entry("https://example.com/b", b"b"));
+        put(
+            &mut storage,
+            id,
+            entry("https://example.com/a#fragment", b"second"),
+        );
+
+        let all = storage.match_all(ORIGIN, id, None, CacheQueryOptions::default());
+        assert_eq!(bodies(all), [&b"b"[..], b"second"]);
+    }
+
+    #[test]
+    fn test_match_options() {
+        let mut storage = CacheStorage::default();
+        let id = storage.open(ORIGIN, "v1".to_owned());
+        put(&mut storage, id, entry("https://example.com/a?x=1", b"a"));
+
+        let query = request("https://example.com/a?x=2");
+        let options = CacheQueryOptions::default();
+        assert!(
+            storage
+                .match_all(ORIGIN, id, Some(&query), options)
+                .is_empty()
+        );
+        let options = CacheQueryOptions {
+            ignore_search: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            storage.match_all(ORIGIN, id, Some(&query), options).len(),
+            1
+        );
+
+        let mut query = request("https://example.com/a?x=1");
+        query.method = Method::POST;
+        let options = CacheQueryOptions::default();
+        assert!(
+            storage
+                .match_all(ORIGIN, id, Some(&query), options)
+                .is_empty()
+        );
+        let options = CacheQueryOptions {
+            ignore_method: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            storage.match_all(ORIGIN, id, Some(&query), options).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_vary() {
+        let mut storage = CacheStorage::default();
+        let id = storage.open(ORIGIN, "v1".to_owned());
+        let mut vary_entry = entry("https://example.com/a", b"a");
+        vary_entry
+            .request
+            .headers
+            .insert("accept-language", HeaderValue::from_static("fr"));
+        vary_entry
+            .response
+            .headers
+            .insert(VARY, HeaderValue::from_static("Accept-Language"));
+        put(&mut storage, id, vary_entry);
+
+        let mut query = request("https://example.com/a");
+        let options = CacheQueryOptions::default();
+        assert!(
+            storage
+                .match_all(ORIGIN, id, Some(&query), options)
+                .is_empty()
+        );
+        query
+            .headers
+            .insert("accept-language", HeaderValue::from_static("fr"));
+        assert_eq!(
+            storage.match_all(ORIGIN, id, Some(&query), options).len(),
+            1
+        );
+        query
+            .headers
+            .insert("accept-language", HeaderValue::from_static("en"));
+        assert!(
+            storage
+                .match_all(ORIGIN, id, Some(&query), options)
+                .is_empty()
+        );
+        let options = CacheQueryOptions {
+            ignore_vary: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            storage.match_all(ORIGIN, id, Some(&query), options).len(),
+            1
+        );
+
+        // A response that varies on `*` matches no request.
+        let mut star_entry = entry("https://example.com/b", b"b");
+        star_entry
+            .response
+            .headers
+            .insert(VARY, HeaderValue::from_static("*"));
+        put(&mut storage, id, star_entry);
+        let query = request("https://example.com/b");
+        let options = CacheQueryOptions::default();
+        assert!(
+            storage
+                .match_all(ORIGIN, id, Some(&query), options)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_match_in_caches() {
+        let mut storage = CacheStorage::default();
+        let first = storage.open(ORIGIN, "v1".to_owned());
+        let second = storage.open(ORIGIN, "v2".to_owned());
+        put(
+            &mut storage,
+            second,
+            entry("https://example.com/a", b"second"),
+        );
+        put(
+            &mut storage,
+            first,
+            entry("https://example.com/a", b"first"),
+        );
+
+        let query = request("https://example.com/a");
+        let options = CacheQueryOptions::default();
+        let matched = |cache_name| {
+            storage
+                .match_in_caches(ORIGIN, cache_name, &query, options)
+                .map(|entry| entry.response.body)
+        };
+        // The caches are searched in creation order.
+        assert_eq!(matched(None), Some(b"first".to_vec()));
+        assert_eq!(matched(Some("v2")), Some(b"second".to_vec()));
+        assert_eq!(matched(Some("v3")), None);
+    }
+
+    #[test]
+    fn test_batch_is_atomic() {
+        let mut storage = CacheStorage::default();
+        let id = storage.open(ORIGIN, "v1".to_owned());
+        put(&mut storage, id, entry("https://example.com/a", b"a"));
+
+        // Putting the same request twice in a batch fails, without deleting anything.
+        let result = storage.batch(
+            ORIGIN,
+            id,
+            vec![
+                CacheBatchOperation::Delete(
+                    request("https://example.com/a"),
+                    CacheQueryOptions::default(),
+                ),
+                CacheBatchOperation::Put(entry("https://example.com/b", b"b")),
+                CacheBatchOperation::Put(entry("https://example.com/b", b"b")),
+            ],
+            u64::MAX,
+        );
+        assert!(matches!(result, Err(CacheBatchError::InvalidState)));
+        let all = storage.match_all(ORIGIN, id, None, CacheQueryOptions::default());
+        assert_eq!(bodies(all), [&b"a"[..]]);
+
+        let delete = |url| {
+            vec![CacheBatchOperation::Delete(
+                request(url),
+                CacheQueryOptions::default(),
+            )]
+        };
+        assert!(matches!(
+            storage.batch(ORIGIN, id, delete("https://example.com/a"), u64::MAX),
+            Ok(true)
+        ));
+        assert!(matches!(
+            storage.batch(ORIGIN, id, delete("https://example.com/a"), u64::MAX),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn test_quota() {
+        let mut storage = CacheStorage::default();
+        let id = storage.open(ORIGIN, "v1".to_owned());
+        put(&mut storage, id, entry("https://example.com/a", b"a"));
+        let usage = storage.usage(ORIGIN);
+        assert!(usage > 0);
+
+        let result = storage.batch(
+            ORIGIN,
+            id,
+            vec![CacheBatchOperation::Put(entry(
+                "https://example.com/b",
+                &[0; 1024],
+            ))],
+            usage + 1000,
+        );
+        assert!(matches!(result, Err(CacheBatchError::QuotaExceeded)));
+        assert_eq!(storage.usage(ORIGIN), usage);
+
+        // Replacing an entry by a smaller one is allowed, even over the quota.
+        put(&mut storage, id, entry("https://example.com/a", b""));
+        assert!(storage.usage(ORIGIN) < usage);
+
+        assert!(storage.clear_origin(ORIGIN));
+        assert_eq!(storage.usage(ORIGIN), 0);
+        assert!(storage.keys(ORIGIN).is_empty());
+    }
+}
@@ -4,6 +4,7 @@
 
 use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 
 use futures::Future;
@@ -15,6 +16,7 @@ use hyper::rt::Executor;
 use hyper_rustls::HttpsConnector as HyperRustlsHttpsConnector;
 use hyper_util::client::legacy::Client;
 use hyper_util::client::legacy::connect::HttpConnector as HyperHttpConnector;
+use hyper_util::rt::TokioIo;
 use log::warn;
 use rustls::client::WebPkiServerVerifier;
 use rustls::{ClientConfig, RootCertStore};
@@ -23,27 +25,36 @@ use tower_service::Service;
 
 use crate::async_runtime::HANDLE;
 use crate::hosts::replace_host;
+use crate::speculative_connections::{SpeculativeConnections, SpeculativeResolver};
 
 pub const BUF_SIZE: usize = 32768;
 
+type InnerHttpConnector = HyperHttpConnector<SpeculativeResolver>;
+
 #[derive(Clone)]
 pub struct ServoHttpConnector {
-    inner: HyperHttpConnector,
+    inner: InnerHttpConnector,
+    speculative_connections: SpeculativeConnections,
 }
 
 impl ServoHttpConnector {
-    fn new() -> ServoHttpConnector {
-        let mut inner = HyperHttpConnector::new();
+    fn new(speculative_connections: SpeculativeConnections) -> ServoHttpConnector {
+        let mut inner = HyperHttpConnector::new_with_resolver(SpeculativeResolver::new(
+            speculative_connections.clone(),
+        ));
         inner.enforce_http(false);
         inner.set_happy_eyeballs_timeout(None);
-        ServoHttpConnector { inner }
+        ServoHttpConnector {
+            inner,
+            speculative_connections,
+        }
     }
 }
 
 impl Service<Destination> for ServoHttpConnector {
-    type Response = <HyperHttpConnector as Service<Destination>>::Response;
-    type Error = <HyperHttpConnector as Service<Destination>>::Error;
-    type Future = <HyperHttpConnector as Service<Destination>>::Future;
+    type Response = <InnerHttpConnector as Service<Destination>>::Response;
+    type Error = <InnerHttpConnector as Service<Destination>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
 
     fn call(&mut self, dest: Destination) -> Self::Future {
         // Perform host replacement when making the actual TCP connection.
@@ -68,7 +79,21 @@ impl Service<Destination> for ServoHttpConnector {
             }
         }
 
-        self.inner.call(new_dest)
+        // Use a socket opened by `<link rel=preconnect>`, if there is one.
+        if let Some(host) = new_dest.host() {
+            let port = new_dest.port_u16().unwrap_or(match new_dest.scheme_str() {
+                Some("https") | Some("wss") => 443,
+                _ => 80,
+            });
+            if let Some(socket) = self
+                .speculative_connections
+                .take_preconnected_socket(host, port)
+            {
+                return Box::pin(std::future::ready(Ok(TokioIo::new(socket))));
+            }
+        }
+
+        Box::pin(self.inner.call(new_dest))
     }
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -268,13 +293,16 @@ impl rustls::client::danger::ServerCertVerifier for CertificateVerificationOverr
 
 pub type BoxedBody = BoxBody<Bytes, hyper::Error>;
 
-pub fn create_http_client(tls_config: TlsConfig) -> Client<Connector, BoxedBody> {
+pub fn create_http_client(
+    tls_config: TlsConfig,
+    speculative_connections: SpeculativeConnections,
+) -> Client<Connector, BoxedBody> {
     let connector = hyper_rustls::HttpsConnectorBuilder::new()
         .with_tls_config(tls_config)
         .https_or_http()
         .enable_http1()
         .enable_http2()
-        .wrap_connector(ServoHttpConnector::new());
+        .wrap_connector(ServoHttpConnector::new(speculative_connections));
 
     Client::builder(TokioExecutor {})
         .http1_title_case_headers(true)
@@ -0,0 +1,546 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A content blocker for tracking protection, which evaluates requests against filter
+//! lists in the Adblock Plus format, such as EasyList and EasyPrivacy, and computes the
+//! element hiding stylesheet of pages.
+//!
+//! <https://help.adblockplus.org/hc/en-us/articles/360062733293>
+
+use std::collections::HashMap;
+
+use content_security_policy::Destination;
+use log::debug;
+use net_traits::pub_domains::reg_suffix;
+use net_traits::request::{Origin, Request, RequestMode};
+use servo_url::ServoUrl;
+
+/// The kind of resource that a request fetches, as named by the type options of filters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ResourceType {
+    Document,
+    Font,
+    Image,
+    Media,
+    Object,
+    Other,
+    Ping,
+    Script,
+    Stylesheet,
+    Subdocument,
+    WebSocket,
+    XmlHttpRequest,
+}
+
+impl ResourceType {
+    fn from_option(option: &str) -> Option<ResourceType> {
+        Some(match option {
+            "document" => ResourceType::Document,
+            "font" => ResourceType::Font,
+            "image" => ResourceType::Image,
+            "media" => ResourceType::Media,
+            "object" => ResourceType::Object,
+            "other" => ResourceType::Other,
+            "ping" => ResourceType::Ping,
+            "script" => ResourceType::Script,
+            "stylesheet" | "css" => ResourceType::Stylesheet,
+            "subdocument" | "frame" => ResourceType::Subdocument,
+            "websocket" => ResourceType::WebSocket,
+            "xmlhttprequest" | "xhr" => ResourceType::XmlHttpRequest,
+            _ => return None,
+        })
+    }
+
+    fn of_request(request: &Request) -> ResourceType {
+        if matches!(request.mode, RequestMode::WebSocket { .. }) {
+            return ResourceType::WebSocket;
+        }
+        match request.destination {
+            Destination::Document => ResourceType::Document,
+            Destination::Frame | Destination::IFrame => ResourceType::Subdocument,
+            Destination::Font => ResourceType::Font,
+            Destination::Image => ResourceType::Image,
+            Destination::Audio | Destination::Video | Destination::Track => ResourceType::Media,
+            Destination::Embed | Destination::Object => ResourceType::Object,
+            Destination::Report => ResourceType::Ping,
+            Destination::Script |
+            Destination::Worker |
+            Destination::SharedWorker |
+            Destination::ServiceWorker => ResourceType::Script,
+            Destination::Style => ResourceType::Stylesheet,
+            Destination::None => ResourceType::XmlHttpRequest,
+            _ => ResourceType::Other,
+        }
+    }
+}
+
+/// The domains that a filter is restricted to, from its `domain` option or, for element
+/// hiding rules, from the part before the `##` separator.
+#[derive(Debug, Default)]
+struct DomainRestrictions {
+    included: Vec<String>,
+    excluded: Vec<String>,
+}
+
+impl DomainRestrictions {
+    fn parse<'a>(domains: impl Iterator<Item = &'a str>) -> DomainRestrictions {
+        let mut restrictions = DomainRestrictions::default();
+        for domain in domains.map(str::trim).filter(|domain| !domain.is_empty()) {
+            match domain.strip_prefix('~') {
+                Some(domain) => restrictions.excluded.push(domain.to_ascii_lowercase()),
+                None => restrictions.included.push(domain.to_ascii_lowercase()),
+            }
+        }
+        restrictions
+    }
+
+    fn applies_to(&self, host: Option<&str>) -> bool {
+        let is_on = |domain: &String| {
+            host.is_some_and(|host| {
+                host == domain ||
+                    (host.ends_with(domain.as_str()) &&
+                        host[..host.len() - domain.len()].ends_with('.'))
+            })
+        };
+        if self.excluded.iter().any(is_on) {
+            return false;
+        }
+        self.included.is_empty() || self.included.iter().any(is_on)
+    }
+}
+
+/// Where the pattern of a network filter has to match in the URL.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Anchor {
+    /// The pattern may match anywhere.
+    None,
+    /// The pattern starts with `|` and has to match at the start of the URL.
+    Start,
+    /// The pattern starts with `||` and has to match at the start of the host or of one
+    /// of its subdomains.
+    Domain,
+}
+
+/// A compiled network filter, which blocks requests or, for exception filters, allows them.
+#[derive(Debug)]
+struct NetworkFilter {
+    /// The parts of the pattern between its `*` wildcards. A `^` in a part matches a
+    /// separator character or the end of the URL.
+    parts: Vec<String>,
+    anchor: Anchor,
+    /// Whether the pattern ends with `|` and has to match at the end of the URL.
+    anchored_at_end: bool,
+    match_case: bool,
+    /// `Some(true)` for `third-party`, `Some(false)` for `~third-party`.
+    third_party: Option<bool>,
+    included_types: Vec<ResourceType>,
+    excluded_types: Vec<ResourceType>,
+    domains: DomainRestrictions,
+}
+
+/// Returns whether the character separates the parts of a URL, in the sense of the `^`
+/// placeholder.
+fn is_separator(byte: u8) -> bool {
+    !(byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.' | b'%'))
+}
+
+impl NetworkFilter {
+    /// Parse the filter from a line of a filter list, without its leading `@@`. Returns
+    /// `None` for filters that use syntax or options that are not supported, which are
+    /// ignored rather than risking to block too much.
+    fn parse(filter: &str) -> Option<NetworkFilter> {
+        let (pattern, options) = match filter.rfind('$') {
+            Some(index) if !filter[index + 1..].contains('/') => {
+                (&filter[..index], Some(&filter[index + 1..]))
+            },
+            _ => (filter, None),
+        };
+
+        // Regular expression filters are not supported.
+        if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+            return None;
+        }
+
+        let mut network_filter = NetworkFilter {
+            parts: Vec::new(),
+            anchor: Anchor::None,
+            anchored_at_end: false,
+            match_case: false,
+            third_party: None,
+            included_types: Vec::new(),
+            excluded_types: Vec::new(),
+            domains: DomainRestrictions::default(),
+        };
+
+        for option in options.into_iter().flat_map(|options| options.split(',')) {
+            let option = option.trim().to_ascii_lowercase();
+            let (negated, name) = match option.strip_prefix('~') {
+                Some(name) => (true, name),
+                None => (false, option.as_str()),
+            };
+            match name {
+                "third-party" | "3p" => network_filter.third_party = Some(!negated),
+                "first-party" | "1p" => network_filter.third_party = Some(negated),
+                "match-case" => network_filter.match_case = true,
+                _ if name.starts_with("domain=") => {
+                    network_filter.domains =
+                        DomainRestrictions::parse(name["domain=".len()..].split('|'));
+                },
+                _ => match ResourceType::from_option(name) {
+                    Some(resource_type) if negated => {
+                        network_filter.excluded_types.push(resource_type)
+                    },
+                    Some(resource_type) => network_filter.included_types.push(resource_type),
+                    // Options such as `popup`, `csp` or `redirect` change what the filter
+                    // does rather than what it applies to.
+                    None => return None,
+                },
+            }
+        }
+
+        let mut pattern = pattern;
+        if let Some(rest) = pattern.strip_prefix("||") {
+            network_filter.anchor = Anchor::Domain;
+            pattern = rest;
+        } else if let Some(rest) = pattern.strip_prefix('|') {
+            network_filter.anchor = Anchor::Start;
+            pattern = rest;
+        }
+        if let Some(rest) = pattern.strip_suffix('|') {
+            network_filter.anchored_at_end = true;
+            pattern = rest;
+        }
+        // Leading and trailing wildcards are implied unless the pattern is anchored.
+        if pattern.starts_with('*') {
+            network_filter.anchor = Anchor::None;
+        }
+        if pattern.ends_with('*') {
+            network_filter.anchored_at_end = false;
+        }
+
+        let pattern = if network_filter.match_case {
+            pattern.to_owned()
+        } else {
+            pattern.to_ascii_lowercase()
+        };
+        network_filter.parts = pattern
+            .split('*')
+            .filter(|part| !part.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Some(network_filter)
+    }
+
+    /// The longest token of the pattern that every matching URL contains as a whole, used
+    /// to only evaluate the filter against URLs that contain it.
+    fn token(&self) -> Option<String> {
+        let last_part = self.parts.len().saturating_sub(1);
+        let mut best: Option<&str> = None;
+        for (part_index, part) in self.parts.iter().enumerate() {
+            let bytes = part.as_bytes();
+            let mut start = 0;
+            while start < bytes.len() {
+                if !bytes[start].is_ascii_alphanumeric() {
+                    start += 1;
+                    continue;
+                }
+                let mut end = start;
+                while end < bytes.len() && bytes[end].is_ascii_alphanumeric() {
+                    end += 1;
+                }
+                // A token that touches a wildcard may be the prefix or suffix of a longer
+                // token of the URL.
+                let bounded_at_start =
+                    start > 0 || (part_index == 0 && self.anchor != Anchor::None);
+                let bounded_at_end =
+                    end < bytes.len() || (part_index == last_part && self.anchored_at_end);
+                if bounded_at_start &&
+                    bounded_at_end &&
+                    end - start >= 2 &&
+                    best.is_none_or(|best| best.len() < end - start)
+                {
+                    best = Some(&part[start..end]);
+                }
+                start = end;
+            }
+        }
+        best.map(str::to_ascii_lowercase)
+    }
+
+    fn matches(&self, context: &RequestContext) -> bool {
+        if self
+            .third_party
+            .is_some_and(|third_party| third_party != context.third_party)
+        {
+            return false;
+        }
+        if !self.included_types.is_empty() && !self.included_types.contains(&context.resource_type)
+        {
+            return false;
+        }
+        if self.excluded_types.contains(&context.resource_type) {
+            return false;
+        }
+        // Documents are only affected by filters that explicitly target them.
+        if context.resource_type == ResourceType::Document &&
+            !self.included_types.contains(&ResourceType::Document)
+        {
+            return false;
+        }
+        if !self.domains.applies_to(context.source_host.as_deref()) {
+            return false;
+        }
+
+        let url = if self.match_case {
+            context.url.as_bytes()
+        } else {
+            context.lowercase_url.as_bytes()
+        };
+        match self.anchor {
+            Anchor::None => self.matches_from(url, 0, false),
+            Anchor::Start => self.matches_from(url, 0, true),
+            Anchor::Domain => {
+                let (host_start, host_end) = context.host_range;
+                (host_start..host_end)
+                    .filter(|&index| index == host_start || url[index - 1] == b'.')
+                    .any(|index| self.matches_from(url, index, true))
+            },
+        }
+    }
+
+    /// Match the parts of the pattern in order, starting at `position`. If `anchored`, the
+    /// first part has to match exactly at `position`.
+    fn matches_from(&self, url: &[u8], mut position: usize, anchored: bool) -> bool {
+        let last_part = self.parts.len().saturating_sub(1);
+        for (part_index, part) in self.parts.iter().enumerate() {
+            let part = part.as_bytes();
+            let must_end_at_end = part_index == last_part && self.anchored_at_end;
+            if part_index == 0 && anchored {
+                match match_part_at(part, url, position) {
+                    Some(end) if !must_end_at_end || end == url.len() => position = end,
+                    _ => return false,
+                }
+                continue;
+            }
+            let found = (position..=url.len()).find_map(|start| {
+                match_part_at(part, url, start).filter(|&end| !must_end_at_end || end == url.len())
+            });
+            match found {
+                Some(end) => position = end,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Match a part of a pattern at the given position of the URL, returning where the match
+/// ends.
+fn match_part_at(part: &[u8], url: &[u8], position: usize) -> Option<usize> {
+    let mut index = position;
+    for (part_index, &byte) in part.iter().enumerate() {
+        if byte == b'^' {
+            if index == url.len() {
+                // The separator placeholder also matches the end of the URL.
+                return part[part_index..]
+                    .iter()
+                    .all(|&byte| byte == b'^')
+                    .then_some(index);
+            }
+            if !is_separator(url[index]) {
+                return None;
+            }
+        } else if url.get(index) != Some(&byte) {
+            return None;
+        }
+        index += 1;
+    }
+    Some(index)
+}
+
+/// The URL tokens that index the filters of a [`FilterSet`].
+fn url_tokens(url: &str) -> impl Iterator<Item = &str> {
+    url.split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|token| token.len() >= 2)
+}
+
+/// A set of network filters, indexed by the tokens that URLs have to contain for them to
+/// match.
+#[derive(Debug, Default)]
+struct FilterSet {
+    filters: Vec<NetworkFilter>,
+    by_token: HashMap<String, Vec<usize>>,
+    /// The filters without a token, which are evaluated against every URL.
+    untokenized: Vec<usize>,
+}
+
+impl FilterSet {
+    fn add(&mut self, filter: NetworkFilter) {
+        let index = self.filters.len();
+        match filter.token() {
+            Some(token) => self.by_token.entry(token).or_default().push(index),
+            None => self.untokenized.push(index),
+        }
+        self.filters.push(filter);
+    }
+
+    fn matches(&self, context: &RequestContext) -> bool {
+        self.untokenized
+            .iter()
+            .chain(
+                url_tokens(&context.lowercase_url)
+                    .filter_map(|token| self.by_token.get(token))
+                    .flatten(),
+            )
+            .any(|&index| self.filters[index].matches(context))
+    }
+}
+
+/// An element hiding rule, which hides the elements matching a selector.
+#[derive(Debug)]
+struct ElementHidingRule {
+    selector: String,
+    domains: DomainRestrictions,
+}
+
+/// What the filters are evaluated against for a request.
+struct RequestContext {
+    url: String,
+    lowercase_url: String,
+    /// The byte range of the host in the URL.
+    host_range: (usize, usize),
+    /// The host of the origin that made the request.
+    source_host: Option<String>,
+    resource_type: ResourceType,
+    third_party: bool,
+}
+
+impl RequestContext {
+    fn new(url: &ServoUrl, source_host: Option<String>, resource_type: ResourceType) -> Self {
+        let url_string = url.as_str().to_owned();
+        let host = url.host_str().unwrap_or_default();
+        let host_start = url_string.find(host).unwrap_or(0);
+        let third_party = match &source_host {
+            Some(source_host) => reg_suffix(source_host) != reg_suffix(host),
+            None => false,
+        };
+        RequestContext {
+            lowercase_url: url_string.to_ascii_lowercase(),
+            host_range: (host_start, host_start + host.len()),
+            url: url_string,
+            source_host,
+            resource_type,
+            third_party,
+        }
+    }
+}
+
+/// The compiled filters of the filter lists that the embedder has enabled.
+#[derive(Debug, Default)]
+pub struct ContentBlocker {
+    blocking_filters: FilterSet,
+    exception_filters: FilterSet,
+    hiding_rules: Vec<ElementHidingRule>,
+    hiding_exceptions: Vec<ElementHidingRule>,
+}
+
+impl ContentBlocker {
+    /// Compile the given filter lists, which are the contents of files in the Adblock Plus
+    /// format.
+    pub fn new(filter_lists: &[String]) -> ContentBlocker {
+        let mut content_blocker = ContentBlocker::default();
+        for line in filter_lists.iter().flat_map(|list| list.lines()) {
+            content_blocker.add_filter(line.trim());
+        }
+        debug!(
+            "Content blocker has {} blocking, {} exception and {} element hiding filters",
+            content_blocker.blocking_filters.filters.len(),
+            content_blocker.exception_filters.filters.len(),
+            content_blocker.hiding_rules.len(),
+        );
+        content_blocker
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocking_filters.filters.is_empty() && self.hiding_rules.is_empty()
+    }
+
+    fn add_filter(&mut self, line: &str) {
+        // Skip empty lines, comments and the `[Adblock Plus 2.0]` header.
+        if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+            return;
+        }
+
+        if let Some((domains, selector)) = line.split_once("#@#") {
+            self.hiding_exceptions.push(ElementHidingRule {
+                selector: selector.to_owned(),
+                domains: DomainRestrictions::parse(domains.split(',')),
+            });
+            return;
+        }
+        if let Some((domains, selector)) = line.split_once("##") {
+            self.hiding_rules.push(ElementHidingRule {
+                selector: selector.to_owned(),
+                domains: DomainRestrictions::parse(domains.split(',')),
+            });
+            return;
+        }
+        // Extended CSS, snippet and scriptlet filters are not supported.
+        if line.contains("#?#") || line.contains("#$#") || line.contains("#%#") {
+            return;
+        }
+
+        match line.strip_prefix("@@") {
+            Some(filter) => {
+                if let Some(filter) = NetworkFilter::parse(filter) {
+                    self.exception_filters.add(filter);
+                }
+            },
+            None => {
+                if let Some(filter) = NetworkFilter::parse(line) {
+                    self.blocking_filters.add(filter);
+                }
+            },
+        }
+    }
+
+    /// Returns whether the request matches a blocking filter and no exception filter.
+    pub fn should_block_request(&self, request: &Request) -> bool {
+        if self.blocking_filters.filters.is_empty() {
+            return false;
+        }
+        let url = request.current_url();
+        if !matches!(url.scheme(), "http" | "https" | "ws" | "wss") {
+            return false;
+        }
+        let source_host = match &request.origin {
+            Origin::Origin(origin) => origin.host().map(ToString::to_string),
+            Origin::Client => None,
+        };
+        let context = RequestContext::new(&url, source_host, ResourceType::of_request(request));
+        self.blocking_filters.matches(&context) && !self.exception_filters.matches(&context)
+    }
+
+    /// The stylesheet that hides the elements matched by the element hiding rules that apply
+    /// to the document at the given URL, if any.
+    pub fn element_hiding_stylesheet(&self, url: &ServoUrl) -> Option<String> {
+        let host = url.host_str();
+        let is_excepted = |selector: &str| {
+            self.hiding_exceptions.iter().any(|exception| {
+                exception.selector == selector && exception.domains.applies_to(host)
+            })
+        };
+        let mut stylesheet = String::new();
+        for rule in &self.hiding_rules {
+            if !rule.domains.applies_to(host) || is_excepted(&rule.selector) {
+                continue;
+            }
+            // Each selector gets its own rule, so that a selector that is not supported does
+            // not invalidate the others.
+            stylesheet.push_str(&rule.selector);
+            stylesheet.push_str(" { display: none !important; }\n");
+        }
+        (!stylesheet.is_empty()).then_some(stylesheet)
+    }
+}
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use net_traits::ProcessRequestBodyChunkLength;
 use net_traits::request::Request;
 
 /// <https://fetch.spec.whatwg.org/#fetch-params>
@@ -9,10 +10,15 @@ use net_traits::request::Request;
 pub struct FetchParams {
     /// <https://fetch.spec.whatwg.org/#concept-request>
     pub request: Request,
+    /// <https://fetch.spec.whatwg.org/#fetch-params-process-request-body>
+    pub process_request_body_chunk_length: Option<ProcessRequestBodyChunkLength>,
 }
 
 impl FetchParams {
     pub fn new(request: Request) -> FetchParams {
-        FetchParams { request }
+        FetchParams {
+            request,
+            process_request_body_chunk_length: None,
+        }
     }
 }
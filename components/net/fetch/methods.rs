@@ -10,8 +10,9 @@ use base64::Engine as _;
 use base64::engine::general_purpose;
 use content_security_policy as csp;
 use crossbeam_channel::Sender;
-use devtools_traits::DevtoolsControlMsg;
-use embedder_traits::resources::{self, Resource};
+use devtools_traits::{
+    ConsoleMessageBuilder, DevtoolsControlMsg, LogLevel, ScriptToDevtoolsControlMsg,
+};
 use headers::{AccessControlExposeHeaders, ContentType, HeaderMapExt};
 use http::header::{self, HeaderMap, HeaderName, RANGE};
 use http::{HeaderValue, Method, StatusCode};
@@ -35,6 +36,7 @@ use net_traits::{
 use rustls_pki_types::CertificateDer;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
+use servo_config::pref;
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use tokio::sync::mpsc::{UnboundedReceiver as TokioReceiver, UnboundedSender as TokioSender};
 
@@ -110,6 +112,8 @@ pub async fn fetch_with_cors_cache(
 ) {
     // Step 8: Let fetchParams be a new fetch params whose request is request
     let mut fetch_params = FetchParams::new(request);
+    fetch_params.process_request_body_chunk_length =
+        target.process_request_body_chunk_length(&fetch_params.request);
     let request = &mut fetch_params.request;
 
     // Step 9: If request’s window is "client", then set request’s window to request’s client, if
@@ -246,6 +250,28 @@ fn should_response_be_blocked_by_csp(
         .unwrap_or((csp::CheckResult::Allowed, Vec::new()))
 }
 
+/// Surface a failed integrity check in the console of the document that issued the request.
+fn report_integrity_failure_to_devtools(request: &Request, context: &FetchContext) {
+    let (Some(pipeline_id), Some(devtools_chan)) = (request.pipeline_id, &context.devtools_chan)
+    else {
+        return;
+    };
+    let url = request.current_url();
+    let mut message = ConsoleMessageBuilder::new(LogLevel::Error, url.to_string(), 0, 0);
+    message.add_argument(
+        format!(
+            "None of the digests in the 'integrity' metadata match the resource '{url}'; \
+             the resource has been blocked."
+        )
+        .into(),
+    );
+    let msg = ScriptToDevtoolsControlMsg::ConsoleAPI(pipeline_id, message.finish(), None);
+    let _ = devtools_chan
+        .lock()
+        .unwrap()
+        .send(DevtoolsControlMsg::FromScript(msg));
+}
+
 /// [Main fetch](https://fetch.spec.whatwg.org/#concept-main-fetch)
 pub async fn main_fetch(
     fetch_params: &mut FetchParams,
@@ -356,11 +382,23 @@ pub async fn main_fetch(
             "Blocked as mixed content".into(),
         )));
     }
+    context.state.note_navigation_request(request);
+    if response.is_none() && context.state.should_block_content(request) {
+        debug!(
+            "Request to {} blocked by content blocker",
+            request.current_url()
+        );
+        response = Some(Response::network_error(NetworkError::Internal(
+            "Blocked by content blocker".into(),
+        )));
+    }
 
     // Step 8: If request’s referrer policy is the empty string, then set request’s referrer policy
     // to request’s policy container’s referrer policy.
     if request.referrer_policy == ReferrerPolicy::EmptyString {
-        request.referrer_policy = policy_container.get_referrer_policy();
+        request.referrer_policy = policy_container.get_referrer_policy_or(
+            ReferrerPolicy::from_token(&pref!(network_http_default_referrer_policy)),
+        );
     }
 
     let referrer_url = match mem::replace(&mut request.referrer, Referrer::NoReferrer) {
@@ -666,6 +704,7 @@ pub async fn main_fetch(
         if response.termination_reason.is_none() &&
             !is_response_integrity_valid(integrity_metadata, &response)
         {
+            report_integrity_failure_to_devtools(request, context);
             Response::network_error(NetworkError::Internal(
                 "Subresource integrity validation failed".into(),
             ))
@@ -798,17 +837,6 @@ fn create_blank_reply(url: ServoUrl, timing_type: ResourceTimingType) -> Respons
     response
 }
 
-fn create_about_memory(url: ServoUrl, timing_type: ResourceTimingType) -> Response {
-    let mut response = Response::new(url, ResourceFetchTiming::new(timing_type));
-    response
-        .headers
-        .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
-    *response.body.lock().unwrap() =
-        ResponseBody::Done(resources::read_bytes(Resource::AboutMemoryHTML));
-    response.status = HttpStatus::default();
-    response
-}
-
 /// Handle a request from the user interface to ignore validation errors for a certificate.
 fn handle_allowcert_request(request: &mut Request, context: &FetchContext) -> io::Result<()> {
     let error = |string| Err(io::Error::new(io::ErrorKind::Other, string));
@@ -868,7 +896,15 @@ async fn scheme_fetch(
     let scheme = url.scheme();
     match scheme {
         "about" if url.path() == "blank" => create_blank_reply(url, request.timing_type()),
-        "about" if url.path() == "memory" => create_about_memory(url, request.timing_type()),
+        "about" if url.path() == "memory" => {
+            context.protocols.internal_pages().load(request, context)
+        },
+        // Internal pages are only loaded as documents, by navigations that the constellation
+        // only allows for the user and for other internal pages. Web content cannot fetch them.
+        "servo" if request.mode != RequestMode::Navigate => Response::network_error(
+            NetworkError::Internal("Internal pages can only be navigated to".into()),
+        ),
+        "servo" => context.protocols.internal_pages().load(request, context),
 
         "chrome" if url.path() == "allowcert" => {
             if let Err(error) = handle_allowcert_request(request, context) {
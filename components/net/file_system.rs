@@ -0,0 +1,493 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The entries of the file systems that script has handles to: the
+//! [origin private file system](https://fs.spec.whatwg.org/#origin-private-file-system)
+//! of every origin, and the files that the user picked with a file picker.
+//!
+//! TODO: Count the origin private file system towards the storage usage and quota of
+//! the origin.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use net_traits::filemanager_thread::{
+    FileOrigin, FileSystemEntry, FileSystemError, FileSystemHandleKind, FileSystemLocator,
+    FileSystemMsg, FileSystemOpenMode, FileSystemResult, FileSystemRoot, OpenFileOperation,
+    is_valid_file_name,
+};
+use uuid::Uuid;
+
+use crate::indexeddb::engines::SanitizedName;
+
+/// The extension of the swap files that writable streams write to.
+const SWAP_FILE_EXTENSION: &str = "crswap";
+
+/// An entry that the user picked with a file picker, which only the origin that showed
+/// the picker has access to.
+struct PickedEntry {
+    origin: FileOrigin,
+    path: PathBuf,
+}
+
+/// <https://fs.spec.whatwg.org/#file-entry-lock>
+enum FileLock {
+    /// Taken by writable streams, the number of which is counted.
+    Shared(usize),
+    /// Taken by a sync access handle.
+    Exclusive,
+}
+
+/// A file that was opened by a writable stream or a sync access handle.
+struct OpenFile {
+    origin: FileOrigin,
+    /// The path of the file entry.
+    path: PathBuf,
+    /// The file that is read and written, which is the swap file for writable streams.
+    file: File,
+    /// The path of the swap file of a writable stream.
+    swap_path: Option<PathBuf>,
+}
+
+pub(crate) struct FileSystemManager {
+    /// The directory with the origin private file systems of all origins.
+    base_dir: PathBuf,
+    picked_entries: RwLock<HashMap<Uuid, PickedEntry>>,
+    open_files: Mutex<HashMap<Uuid, OpenFile>>,
+    locks: Mutex<HashMap<PathBuf, FileLock>>,
+}
+
+impl FileSystemManager {
+    pub(crate) fn new(base_dir: PathBuf) -> FileSystemManager {
+        FileSystemManager {
+            base_dir,
+            picked_entries: Default::default(),
+            open_files: Default::default(),
+            locks: Default::default(),
+        }
+    }
+
+    pub(crate) fn handle(&self, msg: FileSystemMsg, origin: FileOrigin) {
+        match msg {
+            FileSystemMsg::GetChild {
+                parent,
+                name,
+                kind,
+                create,
+                sender,
+            } => {
+                let _ = sender.send(self.get_child(&origin, parent, name, kind, create));
+            },
+            FileSystemMsg::Entries(locator, sender) => {
+                let _ = sender.send(self.entries(&origin, &locator));
+            },
+            FileSystemMsg::RemoveEntry {
+                parent,
+                name,
+                recursive,
+                sender,
+            } => {
+                let _ = sender.send(self.remove_entry(&origin, &parent, &name, recursive));
+            },
+            FileSystemMsg::GetFile(..) => {
+                unreachable!("Snapshots of files are created by the file manager store")
+            },
+            FileSystemMsg::Open(locator, mode, sender) => {
+                let _ = sender.send(self.open(origin, &locator, mode));
+            },
+            FileSystemMsg::OpenFile(id, operation) => {
+                self.handle_open_file_operation(&origin, id, operation);
+            },
+        }
+    }
+
+    /// Registers an entry that the user picked for `origin`. Picking the same entry again
+    /// gives the same locator, so that handles to it are the same entry.
+    pub(crate) fn add_picked_entry(
+        &self,
+        origin: &str,
+        path: PathBuf,
+        kind: FileSystemHandleKind,
+    ) -> FileSystemEntry {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut picked_entries = self.picked_entries.write().unwrap();
+        let id = picked_entries
+            .iter()
+            .find(|(_, entry)| entry.origin == origin && entry.path == path)
+            .map(|(id, _)| *id)
+            .unwrap_or_else(|| {
+                let id = Uuid::new_v4();
+                picked_entries.insert(
+                    id,
+                    PickedEntry {
+                        origin: origin.to_owned(),
+                        path,
+                    },
+                );
+                id
+            });
+        FileSystemEntry {
+            locator: FileSystemLocator {
+                root: FileSystemRoot::Picked(id),
+                path: vec![],
+            },
+            kind,
+            name,
+        }
+    }
+
+    /// The path of the file located by `locator`, which must be a file.
+    pub(crate) fn file_path(
+        &self,
+        origin: &str,
+        locator: &FileSystemLocator,
+    ) -> FileSystemResult<PathBuf> {
+        let path = self.resolve(origin, locator)?;
+        check_kind(&path, FileSystemHandleKind::File)?;
+        Ok(path)
+    }
+
+    /// <https://fs.spec.whatwg.org/#locating-an-entry>
+    fn resolve(&self, origin: &str, locator: &FileSystemLocator) -> FileSystemResult<PathBuf> {
+        let mut path = match locator.root {
+            FileSystemRoot::OriginPrivate => {
+                let root = self
+                    .base_dir
+                    .join(SanitizedName::new(origin.to_owned()).to_string());
+                fs::create_dir_all(&root).map_err(io_error)?;
+                root
+            },
+            FileSystemRoot::Picked(id) => match self.picked_entries.read().unwrap().get(&id) {
+                Some(entry) if entry.origin == origin => entry.path.clone(),
+                _ => return Err(FileSystemError::NotFound),
+            },
+        };
+        for name in &locator.path {
+            // Names are validated by script, but a compromised content process must not be
+            // able to leave the root.
+            if !is_valid_file_name(name) {
+                return Err(FileSystemError::NotFound);
+            }
+            path.push(name);
+        }
+        Ok(path)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getfilehandle> and
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getdirectoryhandle>
+    fn get_child(
+        &self,
+        origin: &str,
+        parent: FileSystemLocator,
+        name: String,
+        kind: FileSystemHandleKind,
+        create: bool,
+    ) -> FileSystemResult<FileSystemEntry> {
+        if !is_valid_file_name(&name) {
+            return Err(FileSystemError::NotFound);
+        }
+        let parent_path = self.resolve(origin, &parent)?;
+        check_kind(&parent_path, FileSystemHandleKind::Directory)?;
+
+        let path = parent_path.join(&name);
+        match entry_kind(&path)? {
+            Some(existing_kind) if existing_kind != kind => {
+                return Err(FileSystemError::TypeMismatch);
+            },
+            Some(_) => {},
+            None if !create => return Err(FileSystemError::NotFound),
+            None => match kind {
+                FileSystemHandleKind::File => {
+                    File::create_new(&path).map_err(io_error)?;
+                },
+                FileSystemHandleKind::Directory => fs::create_dir(&path).map_err(io_error)?,
+            },
+        }
+
+        Ok(FileSystemEntry {
+            locator: parent.child(name.clone()),
+            kind,
+            name,
+        })
+    }
+
+    /// <https://fs.spec.whatwg.org/#filesystemdirectoryhandle-asynchronous-iterator-prototype>
+    fn entries(
+        &self,
+        origin: &str,
+        locator: &FileSystemLocator,
+    ) -> FileSystemResult<Vec<FileSystemEntry>> {
+        let path = self.resolve(origin, locator)?;
+        check_kind(&path, FileSystemHandleKind::Directory)?;
+
+        let mut entries = vec![];
+        for dir_entry in fs::read_dir(&path).map_err(io_error)? {
+            let dir_entry = dir_entry.map_err(io_error)?;
+            let Some(name) = dir_entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            if is_swap_file(&dir_entry.path()) {
+                continue;
+            }
+            let Some(kind) = entry_kind(&dir_entry.path())? else {
+                continue;
+            };
+            entries.push(FileSystemEntry {
+                locator: locator.child(name.clone()),
+                kind,
+                name,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-removeentry>
+    fn remove_entry(
+        &self,
+        origin: &str,
+        parent: &FileSystemLocator,
+        name: &str,
+        recursive: bool,
+    ) -> FileSystemResult<()> {
+        if !is_valid_file_name(name) {
+            return Err(FileSystemError::NotFound);
+        }
+        let parent_path = self.resolve(origin, parent)?;
+        check_kind(&parent_path, FileSystemHandleKind::Directory)?;
+
+        let path = parent_path.join(name);
+        let locks = self.locks.lock().unwrap();
+        if locks
+            .keys()
+            .any(|locked_path| locked_path.starts_with(&path))
+        {
+            return Err(FileSystemError::NoModificationAllowed);
+        }
+        match entry_kind(&path)? {
+            None => Err(FileSystemError::NotFound),
+            Some(FileSystemHandleKind::File) => fs::remove_file(&path).map_err(io_error),
+            Some(FileSystemHandleKind::Directory) if recursive => {
+                fs::remove_dir_all(&path).map_err(io_error)
+            },
+            Some(FileSystemHandleKind::Directory) => {
+                let is_empty = fs::read_dir(&path).map_err(io_error)?.next().is_none();
+                if !is_empty {
+                    return Err(FileSystemError::InvalidModification);
+                }
+                fs::remove_dir(&path).map_err(io_error)
+            },
+        }
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createwritable> and
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createsyncaccesshandle>
+    fn open(
+        &self,
+        origin: FileOrigin,
+        locator: &FileSystemLocator,
+        mode: FileSystemOpenMode,
+    ) -> FileSystemResult<Uuid> {
+        let path = self.file_path(&origin, locator)?;
+        let id = Uuid::new_v4();
+
+        // Let lockResult be the result of taking a lock with "shared" (for writable
+        // streams) or "exclusive" (for sync access handles) on entry.
+        self.take_lock(&path, matches!(mode, FileSystemOpenMode::SyncAccess))?;
+
+        let opened = match mode {
+            FileSystemOpenMode::Writable { keep_existing_data } => {
+                let swap_path = swap_file_path(&path, id);
+                let result = if keep_existing_data {
+                    fs::copy(&path, &swap_path).map(|_| ())
+                } else {
+                    File::create(&swap_path).map(|_| ())
+                };
+                result
+                    .and_then(|_| OpenOptions::new().read(true).write(true).open(&swap_path))
+                    .map(|file| (file, Some(swap_path)))
+            },
+            FileSystemOpenMode::SyncAccess => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .map(|file| (file, None)),
+        };
+        let (file, swap_path) = match opened {
+            Ok(opened) => opened,
+            Err(error) => {
+                self.release_lock(&path);
+                return Err(io_error(error));
+            },
+        };
+
+        self.open_files.lock().unwrap().insert(
+            id,
+            OpenFile {
+                origin,
+                path,
+                file,
+                swap_path,
+            },
+        );
+        Ok(id)
+    }
+
+    fn handle_open_file_operation(&self, origin: &str, id: Uuid, operation: OpenFileOperation) {
+        let mut open_files = self.open_files.lock().unwrap();
+        let open_file = open_files
+            .get_mut(&id)
+            .filter(|open_file| open_file.origin == origin);
+        match operation {
+            OpenFileOperation::Read { at, len, sender } => {
+                let _ = sender.send(with_open_file(open_file, |file| {
+                    let mut data = vec![];
+                    file.seek(SeekFrom::Start(at))?;
+                    file.take(len).read_to_end(&mut data)?;
+                    Ok(data)
+                }));
+            },
+            OpenFileOperation::Write { at, data, sender } => {
+                let _ = sender.send(with_open_file(open_file, |file| {
+                    file.seek(SeekFrom::Start(at))?;
+                    file.write_all(&data)?;
+                    Ok(data.len() as u64)
+                }));
+            },
+            OpenFileOperation::Truncate(size, sender) => {
+                let _ = sender.send(with_open_file(open_file, |file| file.set_len(size)));
+            },
+            OpenFileOperation::GetSize(sender) => {
+                let _ = sender.send(with_open_file(open_file, |file| {
+                    file.metadata().map(|metadata| metadata.len())
+                }));
+            },
+            OpenFileOperation::Flush(sender) => {
+                let _ = sender.send(with_open_file(open_file, |file| file.sync_all()));
+            },
+            OpenFileOperation::Close(sender) => {
+                if open_file.is_none() {
+                    let _ = sender.send(Err(FileSystemError::InvalidState));
+                    return;
+                }
+                let open_file = open_files.remove(&id).expect("Checked above");
+                drop(open_files);
+                let _ = sender.send(self.close(open_file));
+            },
+            OpenFileOperation::Abort => {
+                if open_file.is_none() {
+                    return;
+                }
+                let open_file = open_files.remove(&id).expect("Checked above");
+                drop(open_files);
+                if let Some(swap_path) = &open_file.swap_path {
+                    let _ = fs::remove_file(swap_path);
+                }
+                self.release_lock(&open_file.path);
+            },
+        }
+    }
+
+    /// Closes an open file, atomically replacing the file entry with the swap file of a
+    /// writable stream, and releases the lock on it.
+    fn close(&self, open_file: OpenFile) -> FileSystemResult<()> {
+        let OpenFile {
+            path,
+            file,
+            swap_path,
+            ..
+        } = open_file;
+        let result = file.sync_all().and_then(|_| match &swap_path {
+            Some(swap_path) => fs::rename(swap_path, &path),
+            None => Ok(()),
+        });
+        if result.is_err() {
+            if let Some(swap_path) = &swap_path {
+                let _ = fs::remove_file(swap_path);
+            }
+        }
+        self.release_lock(&path);
+        result.map_err(io_error)
+    }
+
+    /// <https://fs.spec.whatwg.org/#file-entry-lock-take>
+    fn take_lock(&self, path: &Path, exclusive: bool) -> FileSystemResult<()> {
+        let mut locks = self.locks.lock().unwrap();
+        match (locks.get_mut(path), exclusive) {
+            (None, true) => {
+                locks.insert(path.to_path_buf(), FileLock::Exclusive);
+            },
+            (None, false) => {
+                locks.insert(path.to_path_buf(), FileLock::Shared(1));
+            },
+            (Some(FileLock::Shared(count)), false) => *count += 1,
+            (Some(_), _) => return Err(FileSystemError::NoModificationAllowed),
+        }
+        Ok(())
+    }
+
+    /// <https://fs.spec.whatwg.org/#file-entry-lock-release>
+    fn release_lock(&self, path: &Path) {
+        let mut locks = self.locks.lock().unwrap();
+        if let Some(FileLock::Shared(count)) = locks.get_mut(path) {
+            if *count > 1 {
+                *count -= 1;
+                return;
+            }
+        }
+        locks.remove(path);
+    }
+}
+
+fn with_open_file<T>(
+    open_file: Option<&mut OpenFile>,
+    operation: impl FnOnce(&mut File) -> io::Result<T>,
+) -> FileSystemResult<T> {
+    let open_file = open_file.ok_or(FileSystemError::InvalidState)?;
+    operation(&mut open_file.file).map_err(io_error)
+}
+
+/// The kind of the entry at `path`, if it exists.
+fn entry_kind(path: &Path) -> FileSystemResult<Option<FileSystemHandleKind>> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Ok(Some(FileSystemHandleKind::Directory)),
+        Ok(_) => Ok(Some(FileSystemHandleKind::File)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(io_error(error)),
+    }
+}
+
+fn check_kind(path: &Path, kind: FileSystemHandleKind) -> FileSystemResult<()> {
+    match entry_kind(path)? {
+        None => Err(FileSystemError::NotFound),
+        Some(existing_kind) if existing_kind != kind => Err(FileSystemError::TypeMismatch),
+        Some(_) => Ok(()),
+    }
+}
+
+fn swap_file_path(path: &Path, id: Uuid) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.{id}.{SWAP_FILE_EXTENSION}"))
+}
+
+fn is_swap_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == SWAP_FILE_EXTENSION)
+}
+
+fn io_error(error: io::Error) -> FileSystemError {
+    match error.kind() {
+        ErrorKind::NotFound => FileSystemError::NotFound,
+        ErrorKind::AlreadyExists => FileSystemError::InvalidModification,
+        _ => FileSystemError::Io(error.to_string()),
+    }
+}
@@ -19,8 +19,9 @@ use log::warn;
 use mime::{self, Mime};
 use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError};
 use net_traits::filemanager_thread::{
-    FileManagerResult, FileManagerThreadError, FileManagerThreadMsg, FileOrigin, FileTokenCheck,
-    ReadFileProgress, RelativePos, SelectedFile,
+    FileManagerResult, FileManagerThreadError, FileManagerThreadMsg, FileOrigin, FileSystemError,
+    FileSystemHandleKind, FileSystemMsg, FileSystemResult, FileTokenCheck, ReadFileProgress,
+    RelativePos, SelectedFile,
 };
 use net_traits::http_percent_encode;
 use net_traits::response::{Response, ResponseBody};
@@ -31,6 +32,7 @@ use url::Url;
 use uuid::Uuid;
 
 use crate::fetch::methods::{CancellationListener, Data, RangeRequestBounds};
+use crate::file_system::FileSystemManager;
 use crate::protocols::get_range_request_bounds;
 use crate::resource_thread::CoreResourceThreadPool;
 
@@ -78,6 +80,7 @@ enum FileImpl {
 pub struct FileManager {
     embedder_proxy: EmbedderProxy,
     store: Arc<FileManagerStore>,
+    file_system: Arc<FileSystemManager>,
     thread_pool: Weak<CoreResourceThreadPool>,
 }
 
@@ -85,10 +88,18 @@ impl FileManager {
     pub fn new(
         embedder_proxy: EmbedderProxy,
         pool_handle: Weak<CoreResourceThreadPool>,
+        config_dir: Option<&Path>,
     ) -> FileManager {
+        let mut file_system_dir = PathBuf::new();
+        if let Some(config_dir) = config_dir {
+            file_system_dir.push(config_dir);
+        }
+        file_system_dir.push("FileSystem");
+
         FileManager {
             embedder_proxy,
             store: Arc::new(FileManagerStore::new()),
+            file_system: Arc::new(FileSystemManager::new(file_system_dir)),
             thread_pool: pool_handle,
         }
     }
@@ -192,6 +203,44 @@ impl FileManager {
                         );
                     });
             },
+            FileManagerThreadMsg::SelectDirectory(webview_id, sender, origin, opt_test_path) => {
+                let store = self.store.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            store.select_directory(
+                                webview_id,
+                                sender,
+                                origin,
+                                opt_test_path,
+                                embedder,
+                            );
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to select a directory after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::SelectDroppedFiles(webview_id, sender, origin) => {
+                let store = self.store.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            store.select_dropped_files(webview_id, sender, origin, embedder);
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to select dropped files after CoreResourceManager has exited."
+                        );
+                    });
+            },
             FileManagerThreadMsg::ReadFile(sender, id, origin) => {
                 self.read_file(sender, id, origin);
             },
@@ -210,6 +259,102 @@ impl FileManager {
             FileManagerThreadMsg::ActivateBlobURL(id, sender, origin) => {
                 let _ = sender.send(self.store.set_blob_url_validity(true, &id, &origin));
             },
+            FileManagerThreadMsg::ShowOpenFilePicker(
+                webview_id,
+                multiple,
+                filter,
+                sender,
+                origin,
+            ) => {
+                let store = self.store.clone();
+                let file_system = self.file_system.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            let paths = store.query_files_from_embedder(webview_id, filter, multiple, embedder);
+                            let result = match paths {
+                                Some(paths) if !paths.is_empty() => Ok(paths
+                                    .into_iter()
+                                    .map(|path| {
+                                        file_system.add_picked_entry(&origin, path, FileSystemHandleKind::File)
+                                    })
+                                    .collect()),
+                                _ => Err(FileSystemError::Abort),
+                            };
+                            let _ = sender.send(result);
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to show a file picker after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::ShowSaveFilePicker(
+                webview_id,
+                suggested_name,
+                filter,
+                sender,
+                origin,
+            ) => {
+                let store = self.store.clone();
+                let file_system = self.file_system.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            let result = store
+                                .query_file_to_save_from_embedder(webview_id, filter, suggested_name, embedder)
+                                .map(|path| {
+                                    file_system.add_picked_entry(&origin, path, FileSystemHandleKind::File)
+                                });
+                            let _ = sender.send(result);
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to show a file picker after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::FileSystem(FileSystemMsg::GetFile(locator, sender), origin) => {
+                let store = self.store.clone();
+                let file_system = self.file_system.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            let result =
+                                file_system.file_path(&origin, &locator).and_then(|path| {
+                                    store
+                                        .create_entry(&path, &origin)
+                                        .map_err(|error| FileSystemError::Io(format!("{error:?}")))
+                                });
+                            let _ = sender.send(result);
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to get a file after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::FileSystem(msg, origin) => {
+                let file_system = self.file_system.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || file_system.handle(msg, origin));
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to access the file system after CoreResourceManager has exited."
+                        );
+                    });
+            },
         }
     }
 
@@ -599,6 +744,38 @@ impl FileManagerStore {
         }
     }
 
+    /// Asks the embedder for the path of a file to save, which is created if it does not
+    /// exist.
+    fn query_file_to_save_from_embedder(
+        &self,
+        webview_id: WebViewId,
+        patterns: Vec<FilterPattern>,
+        suggested_name: Option<String>,
+        embedder_proxy: EmbedderProxy,
+    ) -> FileSystemResult<PathBuf> {
+        let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        embedder_proxy.send(EmbedderMsg::SelectFileToSave(
+            webview_id,
+            patterns,
+            suggested_name,
+            ipc_sender,
+        ));
+        let path = match ipc_receiver.recv() {
+            Ok(Some(path)) => path,
+            Ok(None) => return Err(FileSystemError::Abort),
+            Err(e) => {
+                warn!("Failed to receive file to save from embedder ({:?}).", e);
+                return Err(FileSystemError::Abort);
+            },
+        };
+        File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| FileSystemError::Io(e.to_string()))?;
+        Ok(path)
+    }
+
     fn select_file(
         &self,
         webview_id: WebViewId,
@@ -676,6 +853,111 @@ impl FileManagerStore {
         }
     }
 
+    fn select_directory(
+        &self,
+        webview_id: WebViewId,
+        sender: IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        origin: FileOrigin,
+        opt_test_path: Option<PathBuf>,
+        embedder_proxy: EmbedderProxy,
+    ) {
+        // Check if the select_files preference is enabled
+        // to ensure process-level security against compromised script;
+        // Then try applying opt_test_path directly for testing convenience
+        let opt_directory = if pref!(dom_testing_html_input_element_select_files_enabled) {
+            opt_test_path
+        } else {
+            let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+            embedder_proxy.send(EmbedderMsg::SelectDirectory(webview_id, ipc_sender));
+            ipc_receiver.recv().unwrap_or_else(|e| {
+                warn!("Failed to receive directory from embedder ({:?}).", e);
+                None
+            })
+        };
+
+        match opt_directory {
+            Some(directory) => {
+                let _ = sender.send(self.create_directory_entries(&directory, &origin));
+            },
+            None => {
+                let _ = sender.send(Err(FileManagerThreadError::UserCancelled));
+            },
+        }
+    }
+
+    fn select_dropped_files(
+        &self,
+        webview_id: WebViewId,
+        sender: IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        origin: FileOrigin,
+        embedder_proxy: EmbedderProxy,
+    ) {
+        // The paths come from the embedder rather than from script, so that a compromised
+        // content process can only read files that the user dropped onto the webview.
+        let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        embedder_proxy.send(EmbedderMsg::GetDroppedFiles(webview_id, ipc_sender));
+        let paths = ipc_receiver.recv().unwrap_or_else(|e| {
+            warn!("Failed to receive dropped files from embedder ({:?}).", e);
+            vec![]
+        });
+
+        let paths: Vec<_> = paths.iter().filter(|path| path.is_file()).collect();
+        if paths.is_empty() {
+            let _ = sender.send(Err(FileManagerThreadError::UserCancelled));
+            return;
+        }
+        let result = paths
+            .into_iter()
+            .map(|path| self.create_entry(path, &origin))
+            .collect();
+        let _ = sender.send(result);
+    }
+
+    /// Create an entry for every file below `directory`. The relative path of each entry
+    /// starts with the name of `directory`, as described in
+    /// <https://wicg.github.io/entries-api/#html-forms>.
+    fn create_directory_entries(
+        &self,
+        directory: &Path,
+        origin: &str,
+    ) -> Result<Vec<SelectedFile>, FileManagerThreadError> {
+        let to_error = |e: std::io::Error| FileManagerThreadError::FileSystemError(e.to_string());
+        let root = directory.file_name().map(PathBuf::from).unwrap_or_default();
+
+        let mut entries = vec![];
+        let mut pending_directories = vec![directory.to_path_buf()];
+        while let Some(current_directory) = pending_directories.pop() {
+            let mut children = std::fs::read_dir(&current_directory)
+                .map_err(to_error)?
+                .map(|child| {
+                    let child = child.map_err(to_error)?;
+                    Ok((child.path(), child.file_type().map_err(to_error)?))
+                })
+                .collect::<Result<Vec<_>, FileManagerThreadError>>()?;
+            children.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (path, file_type) in children {
+                // Symbolic links to directories are not followed, to avoid cycles.
+                if file_type.is_dir() {
+                    pending_directories.push(path);
+                    continue;
+                }
+                if !path.is_file() {
+                    continue;
+                }
+                let mut entry = self.create_entry(&path, origin)?;
+                let relative_path = root.join(path.strip_prefix(directory).unwrap_or(&path));
+                entry.relative_path = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
     fn create_entry(
         &self,
         file_path: &Path,
@@ -726,6 +1008,7 @@ impl FileManagerStore {
             modified,
             size: file_size,
             type_string,
+            relative_path: String::new(),
         })
     }
 
@@ -34,7 +34,7 @@ use http_body_util::{BodyExt, Full};
 use hyper::Response as HyperResponse;
 use hyper::body::{Bytes, Frame};
 use hyper::ext::ReasonPhrase;
-use hyper::header::{HeaderName, TRANSFER_ENCODING};
+use hyper::header::HeaderName;
 use hyper_serde::Serde;
 use hyper_util::client::legacy::Client;
 use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
@@ -42,7 +42,7 @@ use ipc_channel::router::ROUTER;
 use log::{debug, error, info, log_enabled, warn};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use net_traits::http_status::HttpStatus;
-use net_traits::pub_domains::reg_suffix;
+use net_traits::pub_domains::{Site, reg_suffix};
 use net_traits::request::Origin::Origin as SpecificOrigin;
 use net_traits::request::{
     BodyChunkRequest, BodyChunkResponse, CacheMode, CredentialsMode, Destination, Initiator,
@@ -53,12 +53,15 @@ use net_traits::request::{
 };
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
 use net_traits::{
-    CookieSource, DOCUMENT_ACCEPT_HEADER_VALUE, FetchMetadata, NetworkError, RedirectEndValue,
-    RedirectStartValue, ReferrerPolicy, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
+    CookieSource, CustomResponseMediator, DOCUMENT_ACCEPT_HEADER_VALUE, FetchMetadata,
+    NetworkError, ProcessRequestBodyChunkLength, RedirectEndValue, RedirectStartValue,
+    ReferrerPolicy, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
+    ServiceWorkerResponse,
 };
 use profile_traits::mem::{Report, ReportKind};
 use profile_traits::path;
 use servo_arc::Arc;
+use servo_config::pref;
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use tokio::sync::mpsc::{
     Receiver as TokioReceiver, Sender as TokioSender, UnboundedReceiver, UnboundedSender, channel,
@@ -68,6 +71,7 @@ use tokio_stream::wrappers::ReceiverStream;
 
 use crate::async_runtime::HANDLE;
 use crate::connector::{CertificateErrorOverrideManager, Connector};
+use crate::content_blocker::ContentBlocker;
 use crate::cookie::ServoCookie;
 use crate::cookie_storage::CookieStorage;
 use crate::decoder::Decoder;
@@ -78,6 +82,7 @@ use crate::fetch::methods::{Data, DoneChannel, FetchContext, Target, main_fetch}
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
 use crate::resource_thread::{AuthCache, AuthCacheEntry};
+use crate::speculative_connections::SpeculativeConnections;
 
 /// The various states an entry of the HttpCache can be in.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -92,9 +97,29 @@ pub enum HttpCacheEntryState {
 
 type HttpCacheState = Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>;
 
+/// Which cookies the documents of a pipeline have access to, among the cookies of a URL.
+#[derive(Clone, Debug)]
+pub(crate) enum CookiePartition {
+    /// The cookies that are shared by the documents of every site.
+    Unpartitioned,
+    /// The cookies that are only shared by the documents below a top-level document of
+    /// the given site.
+    Partitioned(Site),
+    /// Third-party cookies are blocked.
+    Blocked,
+}
+
 pub struct HttpState {
     pub hsts_list: RwLock<HstsList>,
     pub cookie_jar: RwLock<CookieStorage>,
+    /// The cookies set in third-party contexts, by the site of their top-level document.
+    pub partitioned_cookie_jars: RwLock<HashMap<Site, CookieStorage>>,
+    /// The site of the top-level document of the documents loaded by each pipeline.
+    pub top_level_sites: RwLock<HashMap<PipelineId, Site>>,
+    /// The pairs of top-level site and embedded site for which the user granted documents
+    /// of the embedded site access to their unpartitioned cookies, with the Storage Access
+    /// API, when nested in documents of the top-level site.
+    pub storage_access_grants: RwLock<HashSet<(Site, Site)>>,
     pub http_cache: RwLock<HttpCache>,
     /// A map of cache key to entry state,
     /// reflecting whether the cache entry is ready to read from,
@@ -104,7 +129,14 @@ pub struct HttpState {
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
     pub client: Client<Connector, crate::connector::BoxedBody>,
     pub override_manager: CertificateErrorOverrideManager,
+    pub speculative_connections: SpeculativeConnections,
     pub embedder_proxy: Mutex<EmbedderProxy>,
+    /// The filters of the content blocking filter lists enabled by the embedder.
+    pub content_blocker: RwLock<ContentBlocker>,
+    /// The channels to the service worker managers of the origins that registered one, used
+    /// to let service workers handle the requests in their scope.
+    pub service_worker_mediators:
+        RwLock<HashMap<ImmutableOrigin, IpcSender<CustomResponseMediator>>>,
 }
 
 impl HttpState {
@@ -147,6 +179,107 @@ impl HttpState {
         ));
         ipc_receiver.recv().ok()?
     }
+
+    /// Remember the top-level site of the documents that a navigation request loads, by
+    /// which the cookies of their requests are partitioned.
+    pub(crate) fn note_navigation_request(&self, request: &Request) {
+        let Some(pipeline_id) = request.pipeline_id else {
+            return;
+        };
+        if request.mode != RequestMode::Navigate {
+            return;
+        }
+        let top_level_site = match request.destination {
+            Destination::Document => Site::for_url(&request.current_url()),
+            _ => request.top_level_origin.clone().and_then(Site::for_origin),
+        };
+        let mut top_level_sites = self.top_level_sites.write().unwrap();
+        match top_level_site {
+            Some(top_level_site) => top_level_sites.insert(pipeline_id, top_level_site),
+            None => top_level_sites.remove(&pipeline_id),
+        };
+    }
+
+    /// The cookie partition of the documents of a pipeline for the cookies of a URL. The
+    /// cookies of a URL that is not of the same site as the top-level document are
+    /// partitioned by the site of the top-level document, or blocked if the user asked for
+    /// third-party cookies to be blocked, unless the site of the URL was granted storage
+    /// access below the top-level site.
+    pub(crate) fn cookie_partition(
+        &self,
+        pipeline_id: Option<PipelineId>,
+        url: &ServoUrl,
+    ) -> CookiePartition {
+        let Some(pipeline_id) = pipeline_id else {
+            return CookiePartition::Unpartitioned;
+        };
+        let Some(top_level_site) = self
+            .top_level_sites
+            .read()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+        else {
+            return CookiePartition::Unpartitioned;
+        };
+        let site = Site::for_url(url);
+        if site.as_ref() == Some(&top_level_site) ||
+            site.is_some_and(|site| {
+                self.storage_access_grants
+                    .read()
+                    .unwrap()
+                    .contains(&(top_level_site.clone(), site))
+            })
+        {
+            return CookiePartition::Unpartitioned;
+        }
+        if pref!(network_http_block_third_party_cookies) {
+            return CookiePartition::Blocked;
+        }
+        CookiePartition::Partitioned(top_level_site)
+    }
+
+    /// Run `callback` with the cookie jar of a partition, unless its cookies are blocked.
+    pub(crate) fn with_cookie_jar<T>(
+        &self,
+        partition: CookiePartition,
+        callback: impl FnOnce(&mut CookieStorage) -> T,
+    ) -> Option<T> {
+        match partition {
+            CookiePartition::Unpartitioned => Some(callback(&mut self.cookie_jar.write().unwrap())),
+            CookiePartition::Partitioned(top_level_site) => {
+                let mut cookie_jars = self.partitioned_cookie_jars.write().unwrap();
+                let cookie_jar = cookie_jars
+                    .entry(top_level_site)
+                    .or_insert_with(|| CookieStorage::new(150));
+                Some(callback(cookie_jar))
+            },
+            CookiePartition::Blocked => None,
+        }
+    }
+
+    /// Returns whether the request is blocked by the content blocking filter lists, letting
+    /// the embedder know about it so that it can count the blocked requests of the page.
+    pub(crate) fn should_block_content(&self, request: &Request) -> bool {
+        if !self
+            .content_blocker
+            .read()
+            .unwrap()
+            .should_block_request(request)
+        {
+            return false;
+        }
+        if let Some(webview_id) = request.target_webview_id {
+            self.embedder_proxy
+                .lock()
+                .unwrap()
+                .send(EmbedderMsg::ContentBlocked(
+                    webview_id,
+                    request.current_url(),
+                ));
+        }
+        true
+    }
 }
 
 /// Step 13 of <https://fetch.spec.whatwg.org/#concept-fetch>.
@@ -354,11 +487,17 @@ pub fn determine_requests_referrer(
 fn set_request_cookies(
     url: &ServoUrl,
     headers: &mut HeaderMap,
-    cookie_jar: &RwLock<CookieStorage>,
+    state: &HttpState,
+    pipeline_id: Option<PipelineId>,
 ) {
-    let mut cookie_jar = cookie_jar.write().unwrap();
-    cookie_jar.remove_expired_cookies_for_url(url);
-    if let Some(cookie_list) = cookie_jar.cookies_for_url(url, CookieSource::HTTP) {
+    let partition = state.cookie_partition(pipeline_id, url);
+    let cookie_list = state
+        .with_cookie_jar(partition, |cookie_jar| {
+            cookie_jar.remove_expired_cookies_for_url(url);
+            cookie_jar.cookies_for_url(url, CookieSource::HTTP)
+        })
+        .flatten();
+    if let Some(cookie_list) = cookie_list {
         headers.insert(
             header::COOKIE,
             HeaderValue::from_bytes(cookie_list.as_bytes()).unwrap(),
@@ -366,8 +505,7 @@ fn set_request_cookies(
     }
 }
 
-fn set_cookie_for_url(cookie_jar: &RwLock<CookieStorage>, request: &ServoUrl, cookie_val: &str) {
-    let mut cookie_jar = cookie_jar.write().unwrap();
+fn set_cookie_for_url(cookie_jar: &mut CookieStorage, request: &ServoUrl, cookie_val: &str) {
     let source = CookieSource::HTTP;
 
     if let Some(cookie) = ServoCookie::from_cookie_string(cookie_val.into(), request, source) {
@@ -378,13 +516,17 @@ fn set_cookie_for_url(cookie_jar: &RwLock<CookieStorage>, request: &ServoUrl, co
 fn set_cookies_from_headers(
     url: &ServoUrl,
     headers: &HeaderMap,
-    cookie_jar: &RwLock<CookieStorage>,
+    state: &HttpState,
+    pipeline_id: Option<PipelineId>,
 ) {
-    for cookie in headers.get_all(header::SET_COOKIE) {
-        if let Ok(cookie_str) = std::str::from_utf8(cookie.as_bytes()) {
-            set_cookie_for_url(cookie_jar, url, cookie_str);
+    let partition = state.cookie_partition(pipeline_id, url);
+    state.with_cookie_jar(partition, |cookie_jar| {
+        for cookie in headers.get_all(header::SET_COOKIE) {
+            if let Ok(cookie_str) = std::str::from_utf8(cookie.as_bytes()) {
+                set_cookie_for_url(cookie_jar, url, cookie_str);
+            }
         }
-    }
+    });
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -535,6 +677,7 @@ async fn obtain_response(
     request_headers: &mut HeaderMap,
     body: Option<StdArc<Mutex<IpcSender<BodyChunkRequest>>>>,
     source_is_null: bool,
+    process_request_body_chunk_length: Option<ProcessRequestBodyChunkLength>,
     pipeline_id: &Option<PipelineId>,
     request_id: Option<&str>,
     is_xhr: bool,
@@ -559,9 +702,8 @@ async fn obtain_response(
         let request = if let Some(chunk_requester) = body {
             let (sink, stream) = if source_is_null {
                 // Step 4.2 of https://fetch.spec.whatwg.org/#concept-http-network-fetch
-                // TODO: this should not be set for HTTP/2(currently not supported?).
-                headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
-
+                // The body is streamed without a `Content-Length`, which hyper transmits with
+                // the chunked transfer coding over HTTP/1.1, and as DATA frames over HTTP/2.
                 let (sender, receiver) = channel(1);
                 (BodySink::Chunked(sender), BodyStream::Chunked(receiver))
             } else {
@@ -613,11 +755,20 @@ async fn obtain_response(
                     };
 
                     devtools_bytes.lock().unwrap().extend_from_slice(&bytes);
+                    let length = bytes.len();
 
                     // Step 5.1.2.2, transmit chunk over the network,
                     // currently implemented by sending the bytes to the fetch worker.
                     sink.transmit_bytes(bytes);
 
+                    // Step 5.1.2.3 of https://fetch.spec.whatwg.org/#http-network-fetch,
+                    // the "process request body chunk length" steps of fetchParams.
+                    if let Some(process_request_body_chunk_length) =
+                        process_request_body_chunk_length.as_ref()
+                    {
+                        process_request_body_chunk_length(length);
+                    }
+
                     // Step 5.1.2.3
                     // Request the next chunk.
                     let _ = chunk_requester2
@@ -766,6 +917,52 @@ async fn obtain_response(
     }
 }
 
+/// [Handle fetch](https://w3c.github.io/ServiceWorker/#handle-fetch)
+///
+/// Clients are not tracked yet, so a request is handled by the active service worker whose
+/// scope matches the URL of the request itself, rather than by the service worker that
+/// controls the client that made it.
+fn handle_fetch(request: &Request, context: &FetchContext) -> Option<Response> {
+    // Service workers fetch their own scripts, and whatever they fetch from a `FetchEvent`,
+    // with a service-workers mode of "none", so these never get here.
+    let url = request.current_url();
+    let mediator = context
+        .state
+        .service_worker_mediators
+        .read()
+        .unwrap()
+        .get(&url.origin())?
+        .clone();
+
+    let (response_chan, receiver) = ipc::channel().ok()?;
+    mediator
+        .send(CustomResponseMediator {
+            response_chan,
+            load_url: url.clone(),
+            method: request.method.clone(),
+            headers: request.headers.clone(),
+            destination: request.destination,
+        })
+        .ok()?;
+
+    // TODO: use done_chan, like the request interceptor should, rather than blocking.
+    match receiver.recv().ok()? {
+        ServiceWorkerResponse::Fallback => None,
+        ServiceWorkerResponse::Custom(custom_response) => {
+            let timing = context.timing.lock().unwrap().clone();
+            let mut response = Response::new(url, timing);
+            response.headers = custom_response.headers;
+            let (status_code, status_text) = custom_response.raw_status;
+            response.status = HttpStatus::new(status_code, status_text.into_bytes());
+            *response.body.lock().unwrap() = ResponseBody::Done(custom_response.body);
+            Some(response)
+        },
+        ServiceWorkerResponse::NetworkError => Some(Response::network_error(
+            NetworkError::Internal("Service worker responded with an error".into()),
+        )),
+    }
+}
+
 /// [HTTP fetch](https://fetch.spec.whatwg.org#http-fetch)
 #[async_recursion]
 #[allow(clippy::too_many_arguments)]
@@ -790,8 +987,9 @@ pub async fn http_fetch(
 
     // Step 3
     if request.service_workers_mode == ServiceWorkersMode::All {
-        // TODO: Substep 1
+        // Substep 1
         // Set response to the result of invoking handle fetch for request.
+        response = handle_fetch(request, context);
 
         // Substep 2
         if let Some(ref res) = response {
@@ -1369,7 +1567,8 @@ async fn http_network_or_cache_fetch(
         set_request_cookies(
             &current_url,
             &mut http_request.headers,
-            &context.state.cookie_jar,
+            &context.state,
+            http_request.pipeline_id,
         );
         // Substep 2
         if !http_request.headers.contains_key(header::AUTHORIZATION) {
@@ -1853,6 +2052,8 @@ async fn http_network_fetch(
 ) -> Response {
     let mut response_end_timer = ResponseEndTimer(Some(context.timing.clone()));
 
+    let process_request_body_chunk_length = fetch_params.process_request_body_chunk_length.clone();
+
     // Step 1: Let request be fetchParams’s request.
     let request = &mut fetch_params.request;
 
@@ -1911,6 +2112,7 @@ async fn http_network_fetch(
             .as_ref()
             .map(|body| body.source_is_null())
             .unwrap_or(false),
+        process_request_body_chunk_length,
         &request.pipeline_id,
         request_id.as_deref(),
         is_xhr,
@@ -2113,7 +2315,7 @@ async fn http_network_fetch(
     // TODO this step isn't possible yet
     // Step 15
     if credentials_flag {
-        set_cookies_from_headers(&url, &response.headers, &context.state.cookie_jar);
+        set_cookies_from_headers(&url, &response.headers, &context.state, request.pipeline_id);
     }
     context
         .state
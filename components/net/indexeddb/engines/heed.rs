@@ -1,35 +1,701 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use heed::types::*;
-use heed::{Database, Env, EnvOpenOptions};
+use heed::{Database, Env, EnvFlags, EnvOpenOptions, MdbError, RoTxn, RwTxn};
 use log::warn;
 use net_traits::indexeddb_thread::{
-    AsyncOperation, AsyncReadOnlyOperation, AsyncReadWriteOperation, IndexedDBTxnMode,
+    AsyncOperation, AsyncOperationResult, AsyncReadOnlyOperation, AsyncReadWriteOperation,
+    BackendError, BackendResult, IndexMetadata, IndexedDBCursorDirection, IndexedDBKeyRange,
+    IndexedDBKeyType, IndexedDBRecord, IndexedDBTxnDurability, IndexedDBTxnMode, IterationParams,
+    KeyPath, ObjectStoreMetadata,
 };
+use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
-use super::{KvsEngine, KvsTransaction, SanitizedName};
+use super::key_encoding::{
+    ByteBounds, after_index_entries, decode_index_entry, decode_key, encode_index_entry,
+    encode_key, index_range_bounds, key_range_bounds, max_lower_bound, min_upper_bound,
+};
+use super::{KvsEngine, KvsTransaction};
 use crate::resource_thread::CoreResourceThreadPool;
 
 type HeedDatabase = Database<Bytes, Bytes>;
 
-// A simple store that also has a key generator that can be used if no key
-// is provided for the stored objects
+// Holds the version of the database and the metadata of its object stores
+const METADATA_DATABASE: &str = "servo.metadata";
+// Holds the undo entries of transactions that have not committed yet
+const JOURNAL_DATABASE: &str = "servo.journal";
+
+const VERSION_KEY: &[u8] = b"version";
+const NEXT_ID_KEY: &[u8] = b"next_id";
+const STORE_KEY_PREFIX: &[u8] = b"store:";
+// The first key after all keys starting with `STORE_KEY_PREFIX`
+const STORE_KEY_END: &[u8] = b"store;";
+
+// https://w3c.github.io/IndexedDB/#key-generator
+const MAX_GENERATED_KEY: f64 = 9007199254740992.0;
+
+// How an object store is described in the metadata database
+#[derive(Clone, Deserialize, Serialize)]
+struct StoredObjectStore {
+    id: u64,
+    metadata: ObjectStoreMetadata,
+    index_ids: Vec<(String, u64)>,
+    // https://w3c.github.io/IndexedDB/#key-generator-current-number
+    key_generator: Option<f64>,
+}
+
+// How a record is stored in the database of its object store, along with its keys
+// in each index, so its index entries can be removed with it
+#[derive(Deserialize, Serialize)]
+struct StoredRecord {
+    value: Vec<u8>,
+    index_entries: Vec<(String, Vec<Vec<u8>>)>,
+}
+
+// Restores a key of a database to what it was before a transaction wrote to it
+#[derive(Deserialize, Serialize)]
+struct UndoEntry {
+    database: String,
+    key: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+// A named database of the environment, the name is needed to journal writes to it
+#[derive(Clone)]
+struct Table {
+    name: String,
+    database: HeedDatabase,
+}
+
+#[derive(Clone)]
+struct Index {
+    entries: Table,
+    metadata: IndexMetadata,
+}
+
 #[derive(Clone)]
 struct Store {
-    inner: HeedDatabase,
-    // https://www.w3.org/TR/IndexedDB-2/#key-generator
-    key_generator: Option<u64>,
+    records: Table,
+    indexes: HashMap<String, Index>,
+    metadata: ObjectStoreMetadata,
+}
+
+#[derive(Debug)]
+enum OperationError {
+    // https://w3c.github.io/IndexedDB/#constrainterror
+    Constraint,
+    Heed(heed::Error),
+    Serialization(bincode::Error),
+    Corrupted,
+}
+
+impl From<heed::Error> for OperationError {
+    fn from(error: heed::Error) -> Self {
+        OperationError::Heed(error)
+    }
+}
+
+impl From<bincode::Error> for OperationError {
+    fn from(error: bincode::Error) -> Self {
+        OperationError::Serialization(error)
+    }
+}
+
+impl From<OperationError> for BackendError {
+    fn from(error: OperationError) -> Self {
+        match error {
+            OperationError::Constraint => BackendError::Constraint,
+            OperationError::Heed(heed::Error::Mdb(MdbError::MapFull)) => {
+                BackendError::QuotaExceeded
+            },
+            OperationError::Heed(error) => BackendError::DbErr(error.to_string()),
+            OperationError::Serialization(error) => BackendError::DbErr(error.to_string()),
+            OperationError::Corrupted => BackendError::DbErr("Corrupted record".to_owned()),
+        }
+    }
+}
+
+type OperationResult<T> = Result<T, OperationError>;
+
+// Writes to the databases of the environment on behalf of a transaction, journaling
+// how to undo each write, so that the writes can be reverted if the transaction aborts
+// or has not committed when the process exits.
+struct JournaledWriter {
+    journal: HeedDatabase,
+    serial_number: u64,
+    next_entry: u32,
+}
+
+impl JournaledWriter {
+    fn new(journal: HeedDatabase, txn: &RoTxn, serial_number: u64) -> heed::Result<Self> {
+        // A transaction can run its requests in several batches, so continue after
+        // the entries of the previous ones.
+        let bounds = journal_bounds(Some(serial_number));
+        let next_entry = match journal.rev_range(txn, &as_slices(&bounds))?.next() {
+            Some(entry) => {
+                let (key, _) = entry?;
+                key.get(8..12)
+                    .and_then(|sequence| sequence.try_into().ok())
+                    .map_or(0, |sequence| u32::from_be_bytes(sequence) + 1)
+            },
+            None => 0,
+        };
+        Ok(Self {
+            journal,
+            serial_number,
+            next_entry,
+        })
+    }
+
+    fn put(
+        &mut self,
+        wtxn: &mut RwTxn,
+        table: &Table,
+        key: &[u8],
+        value: &[u8],
+    ) -> OperationResult<()> {
+        let previous = table.database.get(wtxn, key)?.map(<[u8]>::to_vec);
+        self.record(wtxn, table, key, previous)?;
+        table.database.put(wtxn, key, value)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, wtxn: &mut RwTxn, table: &Table, key: &[u8]) -> OperationResult<()> {
+        let Some(previous) = table.database.get(wtxn, key)?.map(<[u8]>::to_vec) else {
+            return Ok(());
+        };
+        self.record(wtxn, table, key, Some(previous))?;
+        table.database.delete(wtxn, key)?;
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        wtxn: &mut RwTxn,
+        table: &Table,
+        key: &[u8],
+        previous: Option<Vec<u8>>,
+    ) -> OperationResult<()> {
+        let mut entry_key = self.serial_number.to_be_bytes().to_vec();
+        entry_key.extend_from_slice(&self.next_entry.to_be_bytes());
+        self.next_entry += 1;
+        let entry = UndoEntry {
+            database: table.name.clone(),
+            key: key.to_vec(),
+            previous,
+        };
+        self.journal
+            .put(wtxn, &entry_key, &bincode::serialize(&entry)?)?;
+        Ok(())
+    }
+}
+
+// The bounds of the journal entries of a transaction, or of all transactions
+fn journal_bounds(serial_number: Option<u64>) -> ByteBounds {
+    match serial_number {
+        Some(serial_number) => (
+            Bound::Included(serial_number.to_be_bytes().to_vec()),
+            match serial_number.checked_add(1) {
+                Some(next) => Bound::Excluded(next.to_be_bytes().to_vec()),
+                None => Bound::Unbounded,
+            },
+        ),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    }
+}
+
+fn as_slices(bounds: &ByteBounds) -> (Bound<&[u8]>, Bound<&[u8]>) {
+    (
+        bounds.0.as_ref().map(Vec::as_slice),
+        bounds.1.as_ref().map(Vec::as_slice),
+    )
+}
+
+type Entries<'t> = Box<dyn Iterator<Item = heed::Result<(&'t [u8], &'t [u8])>> + 't>;
+
+fn entries_in<'t>(
+    txn: &'t RoTxn,
+    table: &Table,
+    bounds: &ByteBounds,
+    reverse: bool,
+) -> heed::Result<Entries<'t>> {
+    let bounds = as_slices(bounds);
+    if reverse {
+        Ok(Box::new(table.database.rev_range(txn, &bounds)?))
+    } else {
+        Ok(Box::new(table.database.range(txn, &bounds)?))
+    }
+}
+
+// Either the records of an object store, or the entries of one of its indexes
+enum Source<'a> {
+    Store(&'a Store),
+    Index(&'a Store, &'a Index),
+}
+
+impl<'a> Source<'a> {
+    fn new(store: &'a Store, index: Option<&str>) -> OperationResult<Self> {
+        match index {
+            None => Ok(Source::Store(store)),
+            Some(name) => store
+                .indexes
+                .get(name)
+                .map(|index| Source::Index(store, index))
+                .ok_or(OperationError::Corrupted),
+        }
+    }
+
+    fn table(&self) -> &Table {
+        match self {
+            Source::Store(store) => &store.records,
+            Source::Index(_, index) => &index.entries,
+        }
+    }
+
+    fn bounds(&self, range: &IndexedDBKeyRange) -> ByteBounds {
+        match self {
+            Source::Store(_) => key_range_bounds(range),
+            Source::Index(..) => index_range_bounds(range),
+        }
+    }
+
+    // Returns the key and primary key of an entry
+    fn decode(&self, entry_key: &[u8]) -> OperationResult<(IndexedDBKeyType, IndexedDBKeyType)> {
+        match self {
+            Source::Store(_) => {
+                let (key, _) = decode_key(entry_key).ok_or(OperationError::Corrupted)?;
+                Ok((key.clone(), key))
+            },
+            Source::Index(..) => decode_index_entry(entry_key).ok_or(OperationError::Corrupted),
+        }
+    }
+
+    // Returns the value of the record an entry refers to
+    fn value(&self, txn: &RoTxn, entry_value: &[u8]) -> OperationResult<Vec<u8>> {
+        let record = match self {
+            Source::Store(_) => entry_value,
+            Source::Index(store, _) => store
+                .records
+                .database
+                .get(txn, entry_value)?
+                .ok_or(OperationError::Corrupted)?,
+        };
+        let record: StoredRecord = bincode::deserialize(record)?;
+        Ok(record.value)
+    }
+
+    fn record(
+        &self,
+        txn: &RoTxn,
+        entry_key: &[u8],
+        entry_value: &[u8],
+    ) -> OperationResult<IndexedDBRecord> {
+        let (key, primary_key) = self.decode(entry_key)?;
+        Ok(IndexedDBRecord {
+            key,
+            primary_key,
+            value: Some(self.value(txn, entry_value)?),
+        })
+    }
+}
+
+fn read_operation(
+    txn: &RoTxn,
+    store: &Store,
+    operation: AsyncReadOnlyOperation,
+) -> OperationResult<AsyncOperationResult> {
+    match operation {
+        AsyncReadOnlyOperation::GetItem(range, index) => {
+            let source = Source::new(store, index.as_deref())?;
+            let entry = entries_in(txn, source.table(), &source.bounds(&range), false)?.next();
+            match entry {
+                Some(entry) => {
+                    let (key, value) = entry?;
+                    Ok(AsyncOperationResult::Value(Some(
+                        source.record(txn, key, value)?,
+                    )))
+                },
+                None => Ok(AsyncOperationResult::Value(None)),
+            }
+        },
+        AsyncReadOnlyOperation::GetKey(range, index) => {
+            let source = Source::new(store, index.as_deref())?;
+            let entry = entries_in(txn, source.table(), &source.bounds(&range), false)?.next();
+            match entry {
+                Some(entry) => {
+                    let (key, _) = entry?;
+                    let (_, primary_key) = source.decode(key)?;
+                    Ok(AsyncOperationResult::Key(Some(primary_key)))
+                },
+                None => Ok(AsyncOperationResult::Key(None)),
+            }
+        },
+        AsyncReadOnlyOperation::GetAll(range, count, index) => {
+            let source = Source::new(store, index.as_deref())?;
+            let mut values = Vec::new();
+            for entry in
+                entries_in(txn, source.table(), &source.bounds(&range), false)?.take(limit(count))
+            {
+                let (key, value) = entry?;
+                values.push(source.record(txn, key, value)?);
+            }
+            Ok(AsyncOperationResult::Values(values))
+        },
+        AsyncReadOnlyOperation::GetAllKeys(range, count, index) => {
+            let source = Source::new(store, index.as_deref())?;
+            let mut keys = Vec::new();
+            for entry in
+                entries_in(txn, source.table(), &source.bounds(&range), false)?.take(limit(count))
+            {
+                let (key, _) = entry?;
+                keys.push(source.decode(key)?.1);
+            }
+            Ok(AsyncOperationResult::Keys(keys))
+        },
+        AsyncReadOnlyOperation::Count(range, index) => {
+            let source = Source::new(store, index.as_deref())?;
+            let mut count = 0;
+            for entry in entries_in(txn, source.table(), &source.bounds(&range), false)? {
+                entry?;
+                count += 1;
+            }
+            Ok(AsyncOperationResult::Count(count))
+        },
+        AsyncReadOnlyOperation::Iterate(params) => {
+            Ok(AsyncOperationResult::Record(iterate(txn, store, params)?))
+        },
+    }
+}
+
+// A count of 0 or no count means there is no limit
+fn limit(count: Option<u32>) -> usize {
+    match count {
+        None | Some(0) => usize::MAX,
+        Some(count) => count as usize,
+    }
+}
+
+// https://w3c.github.io/IndexedDB/#iterate-a-cursor
+fn iterate(
+    txn: &RoTxn,
+    store: &Store,
+    params: IterationParams,
+) -> OperationResult<Option<IndexedDBRecord>> {
+    let source = Source::new(store, params.index.as_deref())?;
+    let is_index = matches!(source, Source::Index(..));
+    let forward = matches!(
+        params.direction,
+        IndexedDBCursorDirection::Next | IndexedDBCursorDirection::NextUnique
+    );
+    let unique = matches!(
+        params.direction,
+        IndexedDBCursorDirection::NextUnique | IndexedDBCursorDirection::PrevUnique
+    );
+
+    // Narrow the range of the source down to the records the cursor can move to,
+    // following the conditions on the found record in the steps of the spec.
+    let (mut lower, mut upper) = source.bounds(&params.range);
+    if forward {
+        if let Some(key) = &params.key {
+            let bound = match (&params.primary_key, is_index) {
+                (Some(primary_key), true) => encode_index_entry(key, primary_key),
+                _ => encode_key(key),
+            };
+            lower = max_lower_bound(lower, Bound::Included(bound));
+        }
+        if let Some(position) = &params.position {
+            let bound = match (&params.object_store_position, is_index && !unique) {
+                (Some(object_store_position), true) => {
+                    Bound::Excluded(encode_index_entry(position, object_store_position))
+                },
+                _ if is_index => Bound::Included(after_index_entries(position)),
+                _ => Bound::Excluded(encode_key(position)),
+            };
+            lower = max_lower_bound(lower, bound);
+        }
+    } else {
+        if let Some(key) = &params.key {
+            let bound = match (&params.primary_key, is_index) {
+                (Some(primary_key), true) => Bound::Included(encode_index_entry(key, primary_key)),
+                (None, true) => Bound::Excluded(after_index_entries(key)),
+                _ => Bound::Included(encode_key(key)),
+            };
+            upper = min_upper_bound(upper, bound);
+        }
+        if let Some(position) = &params.position {
+            let bound = match (&params.object_store_position, is_index && !unique) {
+                (Some(object_store_position), true) => {
+                    encode_index_entry(position, object_store_position)
+                },
+                _ => encode_key(position),
+            };
+            upper = min_upper_bound(upper, Bound::Excluded(bound));
+        }
+    }
+
+    let mut remaining = params.count.max(1);
+    let mut previous_key = None;
+    for entry in entries_in(txn, source.table(), &(lower, upper), !forward)? {
+        let (entry_key, entry_value) = entry?;
+        let (key, primary_key) = source.decode(entry_key)?;
+        if unique && previous_key.as_ref() == Some(&key) {
+            continue;
+        }
+        previous_key = Some(key.clone());
+        remaining -= 1;
+        if remaining > 0 {
+            continue;
+        }
+
+        // Moving backwards through unique keys finds the last entry for a key,
+        // but the cursor has to be on the first one, with the lowest primary key.
+        let (primary_key, entry_value) = if unique && !forward && is_index {
+            let bounds = (
+                Bound::Included(encode_key(&key)),
+                Bound::Excluded(after_index_entries(&key)),
+            );
+            match entries_in(txn, source.table(), &bounds, false)?.next() {
+                Some(first) => {
+                    let (first_key, first_value) = first?;
+                    (source.decode(first_key)?.1, first_value)
+                },
+                None => (primary_key, entry_value),
+            }
+        } else {
+            (primary_key, entry_value)
+        };
+
+        let value = if params.key_only {
+            None
+        } else {
+            Some(source.value(txn, entry_value)?)
+        };
+        return Ok(Some(IndexedDBRecord {
+            key,
+            primary_key,
+            value,
+        }));
+    }
+    Ok(None)
+}
+
+fn store_key(store_name: &str) -> Vec<u8> {
+    let mut key = STORE_KEY_PREFIX.to_vec();
+    key.extend_from_slice(store_name.as_bytes());
+    key
+}
+
+fn read_stored_store(
+    txn: &RoTxn,
+    metadata: &Table,
+    store_name: &str,
+) -> OperationResult<StoredObjectStore> {
+    let bytes = metadata
+        .database
+        .get(txn, &store_key(store_name))?
+        .ok_or(OperationError::Corrupted)?;
+    Ok(bincode::deserialize(bytes)?)
+}
+
+fn write_operation(
+    wtxn: &mut RwTxn,
+    writer: &mut JournaledWriter,
+    metadata: &Table,
+    store: &Store,
+    operation: AsyncReadWriteOperation,
+) -> OperationResult<AsyncOperationResult> {
+    match operation {
+        AsyncReadWriteOperation::PutItem(key, value, overwrite, index_keys) => {
+            let mut stored_store = read_stored_store(wtxn, metadata, &store.metadata.name)?;
+            let key = match key {
+                Some(key) => {
+                    // https://w3c.github.io/IndexedDB/#possibly-update-the-key-generator
+                    if let (Some(current), IndexedDBKeyType::Number(number)) =
+                        (stored_store.key_generator, &key)
+                    {
+                        let value = number.min(MAX_GENERATED_KEY).floor();
+                        if value >= current {
+                            stored_store.key_generator = Some(value + 1.0);
+                            writer.put(
+                                wtxn,
+                                metadata,
+                                &store_key(&store.metadata.name),
+                                &bincode::serialize(&stored_store)?,
+                            )?;
+                        }
+                    }
+                    key
+                },
+                None => {
+                    // https://w3c.github.io/IndexedDB/#generate-a-key
+                    let current = stored_store
+                        .key_generator
+                        .ok_or(OperationError::Constraint)?;
+                    if current > MAX_GENERATED_KEY {
+                        return Err(OperationError::Constraint);
+                    }
+                    stored_store.key_generator = Some(current + 1.0);
+                    writer.put(
+                        wtxn,
+                        metadata,
+                        &store_key(&store.metadata.name),
+                        &bincode::serialize(&stored_store)?,
+                    )?;
+                    IndexedDBKeyType::Number(current)
+                },
+            };
+
+            let encoded_key = encode_key(&key);
+            if !overwrite && store.records.database.get(wtxn, &encoded_key)?.is_some() {
+                return Err(OperationError::Constraint);
+            }
+
+            // https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store
+            // Step 5. If any index with the unique flag set already has a record with the
+            // same key for another record, the operation fails.
+            for (index_name, keys) in &index_keys {
+                let Some(index) = store.indexes.get(index_name) else {
+                    continue;
+                };
+                if !index.metadata.unique {
+                    continue;
+                }
+                for index_key in keys {
+                    let bounds = (
+                        Bound::Included(encode_key(index_key)),
+                        Bound::Excluded(after_index_entries(index_key)),
+                    );
+                    for entry in entries_in(wtxn, &index.entries, &bounds, false)? {
+                        let (entry_key, _) = entry?;
+                        let (_, primary_key) =
+                            decode_index_entry(entry_key).ok_or(OperationError::Corrupted)?;
+                        if primary_key != key {
+                            return Err(OperationError::Constraint);
+                        }
+                    }
+                }
+            }
+
+            remove_record(wtxn, writer, store, &encoded_key)?;
+
+            let mut index_entries = Vec::new();
+            for (index_name, keys) in index_keys {
+                let Some(index) = store.indexes.get(&index_name) else {
+                    continue;
+                };
+                let mut encoded_keys = Vec::new();
+                for index_key in &keys {
+                    let entry_key = encode_index_entry(index_key, &key);
+                    writer.put(wtxn, &index.entries, &entry_key, &encoded_key)?;
+                    encoded_keys.push(encode_key(index_key));
+                }
+                index_entries.push((index_name, encoded_keys));
+            }
+            let record = StoredRecord {
+                value,
+                index_entries,
+            };
+            writer.put(
+                wtxn,
+                &store.records,
+                &encoded_key,
+                &bincode::serialize(&record)?,
+            )?;
+            Ok(AsyncOperationResult::Key(Some(key)))
+        },
+        AsyncReadWriteOperation::RemoveItem(range) => {
+            let keys = record_keys(wtxn, store, &key_range_bounds(&range))?;
+            for key in keys {
+                remove_record(wtxn, writer, store, &key)?;
+            }
+            Ok(AsyncOperationResult::Undefined)
+        },
+        AsyncReadWriteOperation::Clear => {
+            let keys = record_keys(wtxn, store, &(Bound::Unbounded, Bound::Unbounded))?;
+            for key in keys {
+                remove_record(wtxn, writer, store, &key)?;
+            }
+            Ok(AsyncOperationResult::Undefined)
+        },
+    }
+}
+
+fn record_keys(txn: &RoTxn, store: &Store, bounds: &ByteBounds) -> OperationResult<Vec<Vec<u8>>> {
+    let mut keys = Vec::new();
+    for entry in entries_in(txn, &store.records, bounds, false)? {
+        let (key, _) = entry?;
+        keys.push(key.to_vec());
+    }
+    Ok(keys)
+}
+
+// Removes a record and its index entries, if it exists
+fn remove_record(
+    wtxn: &mut RwTxn,
+    writer: &mut JournaledWriter,
+    store: &Store,
+    encoded_key: &[u8],
+) -> OperationResult<()> {
+    let Some(bytes) = store.records.database.get(wtxn, encoded_key)? else {
+        return Ok(());
+    };
+    let record: StoredRecord = bincode::deserialize(bytes)?;
+    for (index_name, keys) in &record.index_entries {
+        let Some(index) = store.indexes.get(index_name) else {
+            continue;
+        };
+        for index_key in keys {
+            let mut entry_key = index_key.clone();
+            entry_key.extend_from_slice(encoded_key);
+            writer.delete(wtxn, &index.entries, &entry_key)?;
+        }
+    }
+    writer.delete(wtxn, &store.records, encoded_key)
+}
+
+// Restores what the journal entries in `bounds` overwrote, in reverse order, and
+// removes the entries.
+fn roll_back(
+    env: &Env,
+    wtxn: &mut RwTxn,
+    journal: HeedDatabase,
+    bounds: &ByteBounds,
+) -> heed::Result<()> {
+    let mut entries = Vec::new();
+    for entry in journal.rev_range(wtxn, &as_slices(bounds))? {
+        let (_, entry) = entry?;
+        match bincode::deserialize::<UndoEntry>(entry) {
+            Ok(entry) => entries.push(entry),
+            Err(error) => warn!("Skipping corrupted idb journal entry: {error}"),
+        }
+    }
+    for entry in entries {
+        let Some(database) = env.open_database::<Bytes, Bytes>(wtxn, Some(&entry.database))? else {
+            continue;
+        };
+        match entry.previous {
+            Some(previous) => database.put(wtxn, &entry.key, &previous)?,
+            None => {
+                database.delete(wtxn, &entry.key)?;
+            },
+        }
+    }
+    journal.delete_range(wtxn, &as_slices(bounds))?;
+    Ok(())
 }
 
 pub struct HeedEngine {
     heed_env: Arc<Env>,
-    open_stores: Arc<RwLock<HashMap<SanitizedName, Store>>>,
+    metadata: Table,
+    journal: HeedDatabase,
+    open_stores: Arc<RwLock<HashMap<String, Store>>>,
     read_pool: Arc<CoreResourceThreadPool>,
     write_pool: Arc<CoreResourceThreadPool>,
 }
@@ -39,6 +705,7 @@ impl HeedEngine {
         base_dir: &Path,
         db_file_name: &Path,
         thread_pool: Arc<CoreResourceThreadPool>,
+        map_size: usize,
     ) -> Self {
         let mut db_dir = PathBuf::new();
         db_dir.push(base_dir);
@@ -46,72 +713,368 @@ impl HeedEngine {
 
         std::fs::create_dir_all(&db_dir).expect("Could not create OS directory for idb");
         // FIXME:(arihant2math) gracefully handle errors like hitting max dbs
+        // Writes are not flushed to disk on every LMDB commit, instead committing an
+        // IndexedDB transaction flushes them unless it asked for relaxed durability.
         #[allow(unsafe_code)]
         let env = unsafe {
             EnvOpenOptions::new()
                 .max_dbs(1024)
+                .map_size(map_size)
+                .flags(EnvFlags::NO_SYNC)
                 .open(db_dir)
                 .expect("Failed to open db_dir")
         };
+
+        let mut wtxn = env.write_txn().expect("Could not create idb store writer");
+        let metadata: HeedDatabase = env
+            .create_database(&mut wtxn, Some(METADATA_DATABASE))
+            .expect("Could not create idb metadata");
+        let journal: HeedDatabase = env
+            .create_database(&mut wtxn, Some(JOURNAL_DATABASE))
+            .expect("Could not create idb journal");
+        // Transactions that were still running when the process exited did not commit.
+        roll_back(&env, &mut wtxn, journal, &journal_bounds(None))
+            .expect("Could not roll back unfinished idb transactions");
+        wtxn.commit().expect("Failed to commit transaction");
+        env.force_sync().expect("Failed to sync idb");
+
+        let metadata = Table {
+            name: METADATA_DATABASE.to_owned(),
+            database: metadata,
+        };
+        let open_stores = Self::load_stores(&env, &metadata).expect("Could not load idb stores");
+
         Self {
             heed_env: Arc::new(env),
-            open_stores: Arc::new(RwLock::new(HashMap::new())),
+            metadata,
+            journal,
+            open_stores: Arc::new(RwLock::new(open_stores)),
             read_pool: thread_pool.clone(),
             write_pool: thread_pool,
         }
     }
+
+    fn load_stores(env: &Env, metadata: &Table) -> OperationResult<HashMap<String, Store>> {
+        let open_table = |rtxn: &RoTxn, name: String| -> heed::Result<Option<Table>> {
+            Ok(env
+                .open_database::<Bytes, Bytes>(rtxn, Some(&name))?
+                .map(|database| Table { name, database }))
+        };
+
+        let rtxn = env.read_txn()?;
+        let mut stores = HashMap::new();
+        let bounds = (
+            Bound::Included(STORE_KEY_PREFIX),
+            Bound::Excluded(STORE_KEY_END),
+        );
+        for entry in metadata.database.range(&rtxn, &bounds)? {
+            let (_, bytes) = entry?;
+            let stored: StoredObjectStore = bincode::deserialize(bytes)?;
+            let Some(records) = open_table(&rtxn, format!("store.{}", stored.id))? else {
+                warn!("Missing database for idb store {}", stored.metadata.name);
+                continue;
+            };
+            let mut indexes = HashMap::new();
+            for (name, id) in &stored.index_ids {
+                let Some(metadata) = stored
+                    .metadata
+                    .indexes
+                    .iter()
+                    .find(|index| &index.name == name)
+                else {
+                    continue;
+                };
+                let Some(entries) = open_table(&rtxn, format!("index.{id}"))? else {
+                    warn!("Missing database for idb index {name}");
+                    continue;
+                };
+                indexes.insert(
+                    name.clone(),
+                    Index {
+                        entries,
+                        metadata: metadata.clone(),
+                    },
+                );
+            }
+            stores.insert(
+                stored.metadata.name.clone(),
+                Store {
+                    records,
+                    indexes,
+                    metadata: stored.metadata,
+                },
+            );
+        }
+        // Database handles opened in a transaction are only kept if it commits
+        rtxn.commit()?;
+        Ok(stores)
+    }
+
+    fn next_id(&self, wtxn: &mut RwTxn) -> heed::Result<u64> {
+        let id = self
+            .metadata
+            .database
+            .get(wtxn, NEXT_ID_KEY)?
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or_default();
+        self.metadata
+            .database
+            .put(wtxn, NEXT_ID_KEY, &(id + 1).to_be_bytes())?;
+        Ok(id)
+    }
+
+    fn write_stored_store(
+        &self,
+        wtxn: &mut RwTxn,
+        stored: &StoredObjectStore,
+    ) -> OperationResult<()> {
+        self.metadata.database.put(
+            wtxn,
+            &store_key(&stored.metadata.name),
+            &bincode::serialize(stored)?,
+        )?;
+        Ok(())
+    }
 }
 
 impl KvsEngine for HeedEngine {
     type Error = heed::Error;
 
-    fn create_store(&self, store_name: SanitizedName, auto_increment: bool) -> heed::Result<()> {
+    fn create_store(
+        &self,
+        store_name: &str,
+        key_path: Option<KeyPath>,
+        auto_increment: bool,
+    ) -> heed::Result<()> {
         let mut write_txn = self.heed_env.write_txn()?;
         let _ = self.heed_env.clear_stale_readers();
-        let new_store: HeedDatabase = self
-            .heed_env
-            .create_database(&mut write_txn, Some(&*store_name.to_string()))?;
+        let id = self.next_id(&mut write_txn)?;
+        let name = format!("store.{id}");
+        let database: HeedDatabase = self.heed_env.create_database(&mut write_txn, Some(&name))?;
 
-        write_txn.commit().expect("Failed to commit transaction");
-
-        let key_generator = { if auto_increment { Some(0) } else { None } };
+        let metadata = ObjectStoreMetadata {
+            name: store_name.to_owned(),
+            key_path,
+            auto_increment,
+            indexes: Vec::new(),
+        };
+        let stored = StoredObjectStore {
+            id,
+            metadata: metadata.clone(),
+            index_ids: Vec::new(),
+            // https://w3c.github.io/IndexedDB/#key-generator
+            // A key generator has a current number, initially 1.
+            key_generator: auto_increment.then_some(1.0),
+        };
+        self.metadata.database.put(
+            &mut write_txn,
+            &store_key(store_name),
+            &bincode::serialize(&stored).map_err(|error| heed::Error::Encoding(error))?,
+        )?;
+        write_txn.commit()?;
 
         let store = Store {
-            inner: new_store,
-            key_generator,
+            records: Table { name, database },
+            indexes: HashMap::new(),
+            metadata,
         };
-
         self.open_stores
             .write()
             .expect("Could not acquire lock on stores")
-            .insert(store_name, store);
+            .insert(store_name.to_owned(), store);
         Ok(())
     }
 
-    fn delete_store(&self, store_name: SanitizedName) -> heed::Result<()> {
-        // TODO: Actually delete store instead of just clearing it
+    fn delete_store(&self, store_name: &str) -> heed::Result<()> {
+        let mut open_stores = self.open_stores.write().unwrap();
+        let Some(store) = open_stores.remove(store_name) else {
+            return Ok(());
+        };
+        // TODO: Actually delete the databases of the store instead of just clearing
+        // them, as they count towards the maximum number of databases.
         let mut write_txn = self.heed_env.write_txn()?;
-        let store: HeedDatabase = self
+        store.records.database.clear(&mut write_txn)?;
+        for index in store.indexes.values() {
+            index.entries.database.clear(&mut write_txn)?;
+        }
+        self.metadata
+            .database
+            .delete(&mut write_txn, &store_key(store_name))?;
+        write_txn.commit()
+    }
+
+    fn object_store(&self, store_name: &str) -> Option<ObjectStoreMetadata> {
+        self.open_stores
+            .read()
+            .expect("Could not acquire read lock on stores")
+            .get(store_name)
+            .map(|store| store.metadata.clone())
+    }
+
+    fn object_store_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .open_stores
+            .read()
+            .expect("Could not acquire read lock on stores")
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn all_records(&self, store_name: &str) -> BackendResult<Vec<(IndexedDBKeyType, Vec<u8>)>> {
+        let stores = self
+            .open_stores
+            .read()
+            .expect("Could not acquire read lock on stores");
+        let store = stores
+            .get(store_name)
+            .ok_or_else(|| BackendError::DbErr("No such object store".to_owned()))?;
+        let rtxn = self
             .heed_env
-            .create_database(&mut write_txn, Some(&*store_name.to_string()))?;
-        store.clear(&mut write_txn)?;
-        write_txn.commit().expect("Failed to commit transaction");
+            .read_txn()
+            .map_err(|error| BackendError::from(OperationError::from(error)))?;
+        let records = (|| {
+            let mut records = Vec::new();
+            let bounds = (Bound::Unbounded, Bound::Unbounded);
+            for entry in entries_in(&rtxn, &store.records, &bounds, false)? {
+                let (key, bytes) = entry?;
+                let (key, _) = decode_key(key).ok_or(OperationError::Corrupted)?;
+                let record: StoredRecord = bincode::deserialize(bytes)?;
+                records.push((key, record.value));
+            }
+            Ok::<_, OperationError>(records)
+        })()?;
+        Ok(records)
+    }
 
+    fn create_index(
+        &self,
+        store_name: &str,
+        index: IndexMetadata,
+        keys: Vec<(IndexedDBKeyType, Vec<IndexedDBKeyType>)>,
+    ) -> BackendResult<()> {
         let mut open_stores = self.open_stores.write().unwrap();
-        open_stores.retain(|key, _| key != &store_name);
+        let store = open_stores
+            .get_mut(store_name)
+            .ok_or_else(|| BackendError::DbErr("No such object store".to_owned()))?;
+        if store.indexes.contains_key(&index.name) {
+            return Err(BackendError::Constraint);
+        }
+
+        // https://w3c.github.io/IndexedDB/#create-an-index
+        // If the index is unique and existing records would violate that, creating
+        // the index fails with a "ConstraintError".
+        let mut entries = BTreeMap::new();
+        let mut unique_keys = HashMap::new();
+        for (primary_key, index_keys) in &keys {
+            let encoded_primary_key = encode_key(primary_key);
+            for index_key in index_keys {
+                if index.unique {
+                    let other = unique_keys
+                        .entry(encode_key(index_key))
+                        .or_insert_with(|| encoded_primary_key.clone());
+                    if *other != encoded_primary_key {
+                        return Err(BackendError::Constraint);
+                    }
+                }
+                entries.insert(
+                    encode_index_entry(index_key, primary_key),
+                    encoded_primary_key.clone(),
+                );
+            }
+        }
+
+        let result = (|| {
+            let mut write_txn = self.heed_env.write_txn()?;
+            let id = self.next_id(&mut write_txn)?;
+            let name = format!("index.{id}");
+            let database: HeedDatabase =
+                self.heed_env.create_database(&mut write_txn, Some(&name))?;
+            for (entry_key, primary_key) in &entries {
+                database.put(&mut write_txn, entry_key, primary_key)?;
+            }
+
+            // Remember the keys of each record in the index, to remove its entries with it
+            for (primary_key, index_keys) in &keys {
+                let encoded_primary_key = encode_key(primary_key);
+                let Some(bytes) = store
+                    .records
+                    .database
+                    .get(&write_txn, &encoded_primary_key)?
+                else {
+                    continue;
+                };
+                let mut record: StoredRecord = bincode::deserialize(bytes)?;
+                record
+                    .index_entries
+                    .retain(|(index_name, _)| index_name != &index.name);
+                record.index_entries.push((
+                    index.name.clone(),
+                    index_keys.iter().map(encode_key).collect(),
+                ));
+                store.records.database.put(
+                    &mut write_txn,
+                    &encoded_primary_key,
+                    &bincode::serialize(&record)?,
+                )?;
+            }
+
+            let mut stored = read_stored_store(&write_txn, &self.metadata, store_name)?;
+            stored.metadata.indexes.push(index.clone());
+            stored.index_ids.push((index.name.clone(), id));
+            self.write_stored_store(&mut write_txn, &stored)?;
+            write_txn.commit()?;
+            Ok::<_, OperationError>(Table { name, database })
+        })();
+
+        let entries = result?;
+        store.metadata.indexes.push(index.clone());
+        store.indexes.insert(
+            index.name.clone(),
+            Index {
+                entries,
+                metadata: index,
+            },
+        );
         Ok(())
     }
 
-    fn close_store(&self, store_name: SanitizedName) -> heed::Result<()> {
-        // FIXME: (arihant2math) unused
-        // FIXME:(arihant2math) return error if no store ...
+    fn delete_index(&self, store_name: &str, index_name: &str) -> heed::Result<()> {
         let mut open_stores = self.open_stores.write().unwrap();
-        open_stores.retain(|key, _| key != &store_name);
-        Ok(())
+        let Some(store) = open_stores.get_mut(store_name) else {
+            return Ok(());
+        };
+        let Some(index) = store.indexes.remove(index_name) else {
+            return Ok(());
+        };
+        store
+            .metadata
+            .indexes
+            .retain(|index| index.name != index_name);
+
+        let mut write_txn = self.heed_env.write_txn()?;
+        index.entries.database.clear(&mut write_txn)?;
+        if let Ok(mut stored) = read_stored_store(&write_txn, &self.metadata, store_name) {
+            stored
+                .metadata
+                .indexes
+                .retain(|index| index.name != index_name);
+            stored.index_ids.retain(|(name, _)| name != index_name);
+            let bytes =
+                bincode::serialize(&stored).map_err(|error| heed::Error::Encoding(error))?;
+            self.metadata
+                .database
+                .put(&mut write_txn, &store_key(store_name), &bytes)?;
+        }
+        write_txn.commit()
     }
 
     // Starts a transaction, processes all operations for that transaction,
-    // and commits the changes.
+    // and journals the changes, so they can be reverted until the transaction
+    // commits.
     fn process_transaction(
         &self,
         transaction: KvsTransaction,
@@ -124,48 +1087,32 @@ impl KvsEngine for HeedEngine {
         // so that the rest of the threads can work in parallel with read txns.
         let heed_env = self.heed_env.clone();
         let stores = self.open_stores.clone();
+        let metadata = self.metadata.clone();
+        let journal = self.journal;
 
         let (tx, rx) = oneshot::channel();
         if let IndexedDBTxnMode::Readonly = transaction.mode {
             self.read_pool.spawn(move || {
                 let env = heed_env;
                 let rtxn = env.read_txn().expect("Could not create idb store reader");
+                let stores = stores
+                    .read()
+                    .expect("Could not acquire read lock on stores");
                 for request in transaction.requests {
-                    match request.operation {
-                        AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetItem(key)) => {
-                            let key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .read()
-                                .expect("Could not acquire read lock on stores");
-                            let store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            let result = store.inner.get(&rtxn, &key).expect("Could not get item");
-
-                            if let Some(blob) = result {
-                                let _ = request.sender.send(Some(blob.to_vec()));
-                            } else {
-                                let _ = request.sender.send(None);
-                            }
-                        },
-                        AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Count(key)) => {
-                            let _key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .read()
-                                .expect("Could not acquire read lock on stores");
-                            let _store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            // FIXME:(arihant2math) Return count with sender
+                    let result = match (stores.get(&request.store_name), request.operation) {
+                        (Some(store), AsyncOperation::ReadOnly(operation)) => {
+                            read_operation(&rtxn, store, operation).map_err(BackendError::from)
                         },
-                        AsyncOperation::ReadWrite(..) => {
+                        (_, AsyncOperation::ReadWrite(..)) => {
                             // We cannot reach this, as checks are made earlier so that
                             // no modifying requests are executed on readonly transactions
                             unreachable!(
                                 "Cannot execute modifying request with readonly transactions"
                             );
                         },
-                    }
+                        (None, _) => Err(BackendError::DbErr("No such object store".to_owned())),
+                    };
+                    let _ = request.sender.send(result);
                 }
 
                 if tx.send(None).is_err() {
@@ -179,92 +1126,109 @@ impl KvsEngine for HeedEngine {
                 // Acquiring a writer will block the thread if another `readwrite` transaction is active
                 let env = heed_env;
                 let mut wtxn = env.write_txn().expect("Could not create idb store writer");
+                let stores = stores
+                    .read()
+                    .expect("Could not acquire read lock on stores");
+                let mut writer = JournaledWriter::new(journal, &wtxn, transaction.serial_number)
+                    .expect("Could not read idb journal");
                 for request in transaction.requests {
-                    match request.operation {
-                        AsyncOperation::ReadWrite(AsyncReadWriteOperation::PutItem(
-                            key,
-                            value,
-                            overwrite,
-                        )) => {
-                            let key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .write()
-                                .expect("Could not acquire write lock on stores");
-                            let store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            if overwrite {
-                                let result =
-                                    store.inner.put(&mut wtxn, &key, &value).ok().and(Some(key));
-                                request.sender.send(result).unwrap();
-                            } else if store
-                                .inner
-                                .get(&wtxn, &key)
-                                .expect("Could not get item")
-                                .is_none()
-                            {
-                                let result =
-                                    store.inner.put(&mut wtxn, &key, &value).ok().and(Some(key));
-                                let _ = request.sender.send(result);
-                            } else {
-                                let _ = request.sender.send(None);
-                            }
-                        },
-                        AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetItem(key)) => {
-                            let key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .read()
-                                .expect("Could not acquire write lock on stores");
-                            let store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            let result = store.inner.get(&wtxn, &key).expect("Could not get item");
-
-                            if let Some(blob) = result {
-                                let _ = request.sender.send(Some(blob.to_vec()));
-                            } else {
-                                let _ = request.sender.send(None);
-                            }
+                    let Some(store) = stores.get(&request.store_name) else {
+                        let _ = request
+                            .sender
+                            .send(Err(BackendError::DbErr("No such object store".to_owned())));
+                        continue;
+                    };
+                    // Each request runs in a nested transaction, so that a failing request
+                    // leaves nothing behind.
+                    let mut nested = env
+                        .nested_write_txn(&mut wtxn)
+                        .expect("Could not create nested idb store writer");
+                    let result = match request.operation {
+                        AsyncOperation::ReadOnly(operation) => {
+                            read_operation(&nested, store, operation)
                         },
-                        AsyncOperation::ReadWrite(AsyncReadWriteOperation::RemoveItem(key)) => {
-                            let key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .write()
-                                .expect("Could not acquire write lock on stores");
-                            let store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            let result = store.inner.delete(&mut wtxn, &key).ok().and(Some(key));
-                            let _ = request.sender.send(result);
+                        AsyncOperation::ReadWrite(operation) => {
+                            write_operation(&mut nested, &mut writer, &metadata, store, operation)
                         },
-                        AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Count(key)) => {
-                            let _key: Vec<u8> = bincode::serialize(&key).unwrap();
-                            let stores = stores
-                                .read()
-                                .expect("Could not acquire read lock on stores");
-                            let _store = stores
-                                .get(&request.store_name)
-                                .expect("Could not get store");
-                            // FIXME:(arihant2math) Return count with sender
+                    };
+                    let result = match result {
+                        Ok(result) => nested
+                            .commit()
+                            .map(|_| result)
+                            .map_err(OperationError::from),
+                        Err(error) => {
+                            nested.abort();
+                            Err(error)
                         },
-                    }
+                    };
+                    let _ = request.sender.send(result.map_err(BackendError::from));
                 }
 
                 wtxn.commit().expect("Failed to commit to database");
+
+                if tx.send(None).is_err() {
+                    warn!("IDBTransaction's execution channel is dropped");
+                };
             })
         }
         rx
     }
 
-    fn has_key_generator(&self, store_name: SanitizedName) -> bool {
-        let has_generator = self
-            .open_stores
-            .read()
-            .expect("Could not acquire read lock on stores")
-            .get(&store_name)
-            .expect("Store not found")
-            .key_generator
-            .is_some();
-        has_generator
+    fn commit_transaction(
+        &self,
+        serial_number: u64,
+        durability: IndexedDBTxnDurability,
+    ) -> BackendResult<()> {
+        let result = (|| {
+            let mut write_txn = self.heed_env.write_txn()?;
+            self.journal.delete_range(
+                &mut write_txn,
+                &as_slices(&journal_bounds(Some(serial_number))),
+            )?;
+            write_txn.commit()?;
+            if durability != IndexedDBTxnDurability::Relaxed {
+                self.heed_env.force_sync()?;
+            }
+            Ok::<_, OperationError>(())
+        })();
+        result.map_err(BackendError::from)
+    }
+
+    fn abort_transaction(&self, serial_number: u64) -> BackendResult<()> {
+        let result = (|| {
+            let mut write_txn = self.heed_env.write_txn()?;
+            roll_back(
+                &self.heed_env,
+                &mut write_txn,
+                self.journal,
+                &journal_bounds(Some(serial_number)),
+            )?;
+            write_txn.commit()?;
+            Ok::<_, OperationError>(())
+        })();
+        result.map_err(BackendError::from)
+    }
+
+    fn version(&self) -> u64 {
+        let Ok(rtxn) = self.heed_env.read_txn() else {
+            return 0;
+        };
+        self.metadata
+            .database
+            .get(&rtxn, VERSION_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or_default()
+    }
+
+    fn set_version(&self, version: u64) -> heed::Result<()> {
+        let mut write_txn = self.heed_env.write_txn()?;
+        self.metadata
+            .database
+            .put(&mut write_txn, VERSION_KEY, &version.to_be_bytes())?;
+        write_txn.commit()?;
+        self.heed_env.force_sync()
     }
 }
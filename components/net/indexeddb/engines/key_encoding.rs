@@ -0,0 +1,494 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An encoding of keys into bytes that preserves the order of keys, so that the byte-wise
+//! ordering of the backing store matches <https://w3c.github.io/IndexedDB/#compare-two-keys>
+//! and key ranges can be looked up directly.
+//!
+//! Every key starts with a tag byte for its type, with higher tags for types that sort
+//! higher. Numbers and dates are followed by 8 bytes, strings, binary keys and arrays are
+//! a sequence of items ending with a zero byte. The encoding is prefix-free, so keys can
+//! be concatenated, which is used for the entries of indexes.
+
+use std::ops::Bound;
+
+use net_traits::indexeddb_thread::{IndexedDBKeyRange, IndexedDBKeyType};
+
+const NUMBER_TAG: u8 = 0x10;
+const DATE_TAG: u8 = 0x20;
+const STRING_TAG: u8 = 0x30;
+const BINARY_TAG: u8 = 0x40;
+const ARRAY_TAG: u8 = 0x50;
+const END: u8 = 0x00;
+const ITEM: u8 = 0x01;
+
+/// A byte that is greater than the first byte of any encoded key. Appending it to an
+/// encoded key gives a bound that is above all index entries for that key.
+const AFTER_ALL_KEYS: u8 = 0xFF;
+
+pub(crate) fn encode_key(key: &IndexedDBKeyType) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    encode_key_into(key, &mut bytes);
+    bytes
+}
+
+fn encode_key_into(key: &IndexedDBKeyType, bytes: &mut Vec<u8>) {
+    match key {
+        IndexedDBKeyType::Number(number) => {
+            bytes.push(NUMBER_TAG);
+            encode_number(*number, bytes);
+        },
+        IndexedDBKeyType::Date(time) => {
+            bytes.push(DATE_TAG);
+            encode_number(*time, bytes);
+        },
+        IndexedDBKeyType::String(string) => {
+            bytes.push(STRING_TAG);
+            for code_unit in string.encode_utf16() {
+                bytes.push(ITEM);
+                bytes.extend_from_slice(&code_unit.to_be_bytes());
+            }
+            bytes.push(END);
+        },
+        IndexedDBKeyType::Binary(binary) => {
+            bytes.push(BINARY_TAG);
+            for byte in binary {
+                bytes.push(ITEM);
+                bytes.push(*byte);
+            }
+            bytes.push(END);
+        },
+        IndexedDBKeyType::Array(array) => {
+            bytes.push(ARRAY_TAG);
+            for key in array {
+                encode_key_into(key, bytes);
+            }
+            bytes.push(END);
+        },
+    }
+}
+
+fn encode_number(number: f64, bytes: &mut Vec<u8>) {
+    // -0 and 0 are the same key.
+    let number = if number == 0.0 { 0.0 } else { number };
+    // Flip the sign bit of positive numbers, and all bits of negative numbers, so the
+    // unsigned big-endian representation sorts like the numbers.
+    let bits = number.to_bits();
+    let bits = if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits ^ (1 << 63)
+    };
+    bytes.extend_from_slice(&bits.to_be_bytes());
+}
+
+fn decode_number(bytes: &[u8]) -> Option<f64> {
+    let bits = u64::from_be_bytes(bytes.get(..8)?.try_into().ok()?);
+    let bits = if bits >> 63 == 1 {
+        bits ^ (1 << 63)
+    } else {
+        !bits
+    };
+    Some(f64::from_bits(bits))
+}
+
+/// Decodes a key, returning it with the number of bytes it took up.
+pub(crate) fn decode_key(bytes: &[u8]) -> Option<(IndexedDBKeyType, usize)> {
+    let (tag, rest) = bytes.split_first()?;
+    match *tag {
+        NUMBER_TAG => Some((IndexedDBKeyType::Number(decode_number(rest)?), 9)),
+        DATE_TAG => Some((IndexedDBKeyType::Date(decode_number(rest)?), 9)),
+        STRING_TAG => {
+            let mut code_units = Vec::new();
+            let mut offset = 0;
+            loop {
+                match *rest.get(offset)? {
+                    END => break,
+                    ITEM => {
+                        let code_unit = rest.get(offset + 1..offset + 3)?;
+                        code_units.push(u16::from_be_bytes([code_unit[0], code_unit[1]]));
+                        offset += 3;
+                    },
+                    _ => return None,
+                }
+            }
+            let string = String::from_utf16_lossy(&code_units);
+            Some((IndexedDBKeyType::String(string), offset + 2))
+        },
+        BINARY_TAG => {
+            let mut binary = Vec::new();
+            let mut offset = 0;
+            loop {
+                match *rest.get(offset)? {
+                    END => break,
+                    ITEM => {
+                        binary.push(*rest.get(offset + 1)?);
+                        offset += 2;
+                    },
+                    _ => return None,
+                }
+            }
+            Some((IndexedDBKeyType::Binary(binary), offset + 2))
+        },
+        ARRAY_TAG => {
+            let mut array = Vec::new();
+            let mut offset = 0;
+            while *rest.get(offset)? != END {
+                let (key, length) = decode_key(&rest[offset..])?;
+                array.push(key);
+                offset += length;
+            }
+            Some((IndexedDBKeyType::Array(array), offset + 2))
+        },
+        _ => None,
+    }
+}
+
+/// The key of an entry of an index, which orders entries by their key in the index and
+/// then by the primary key of their record.
+pub(crate) fn encode_index_entry(
+    key: &IndexedDBKeyType,
+    primary_key: &IndexedDBKeyType,
+) -> Vec<u8> {
+    let mut bytes = encode_key(key);
+    encode_key_into(primary_key, &mut bytes);
+    bytes
+}
+
+/// Decodes the key and primary key of an entry of an index.
+pub(crate) fn decode_index_entry(bytes: &[u8]) -> Option<(IndexedDBKeyType, IndexedDBKeyType)> {
+    let (key, length) = decode_key(bytes)?;
+    let (primary_key, _) = decode_key(&bytes[length..])?;
+    Some((key, primary_key))
+}
+
+/// The bound of the entries of an index that sorts just after all entries for `key`.
+pub(crate) fn after_index_entries(key: &IndexedDBKeyType) -> Vec<u8> {
+    let mut bytes = encode_key(key);
+    bytes.push(AFTER_ALL_KEYS);
+    bytes
+}
+
+pub(crate) type ByteBounds = (Bound<Vec<u8>>, Bound<Vec<u8>>);
+
+/// The bounds of the encoded keys of the records of an object store that are in `range`.
+pub(crate) fn key_range_bounds(range: &IndexedDBKeyRange) -> ByteBounds {
+    let bound = |key: &Option<IndexedDBKeyType>, open: bool| match key {
+        None => Bound::Unbounded,
+        Some(key) if open => Bound::Excluded(encode_key(key)),
+        Some(key) => Bound::Included(encode_key(key)),
+    };
+    (
+        bound(&range.lower, range.lower_open),
+        bound(&range.upper, range.upper_open),
+    )
+}
+
+/// The bounds of the entries of an index whose key is in `range`.
+pub(crate) fn index_range_bounds(range: &IndexedDBKeyRange) -> ByteBounds {
+    let lower = match &range.lower {
+        None => Bound::Unbounded,
+        Some(key) if range.lower_open => Bound::Included(after_index_entries(key)),
+        Some(key) => Bound::Included(encode_key(key)),
+    };
+    let upper = match &range.upper {
+        None => Bound::Unbounded,
+        Some(key) if range.upper_open => Bound::Excluded(encode_key(key)),
+        Some(key) => Bound::Excluded(after_index_entries(key)),
+    };
+    (lower, upper)
+}
+
+/// Returns the higher of two lower bounds.
+pub(crate) fn max_lower_bound(a: Bound<Vec<u8>>, b: Bound<Vec<u8>>) -> Bound<Vec<u8>> {
+    match (&a, &b) {
+        (Bound::Unbounded, _) => b,
+        (_, Bound::Unbounded) => a,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => {
+            if x > y || (x == y && matches!(a, Bound::Excluded(_))) {
+                a
+            } else {
+                b
+            }
+        },
+    }
+}
+
+/// Returns the lower of two upper bounds.
+pub(crate) fn min_upper_bound(a: Bound<Vec<u8>>, b: Bound<Vec<u8>>) -> Bound<Vec<u8>> {
+    match (&a, &b) {
+        (Bound::Unbounded, _) => b,
+        (_, Bound::Unbounded) => a,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => {
+            if x < y || (x == y && matches!(a, Bound::Excluded(_))) {
+                a
+            } else {
+                b
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeBounds;
+
+    use super::*;
+
+    fn number(number: f64) -> IndexedDBKeyType {
+        IndexedDBKeyType::Number(number)
+    }
+
+    fn string(string: &str) -> IndexedDBKeyType {
+        IndexedDBKeyType::String(string.to_owned())
+    }
+
+    fn array(keys: &[IndexedDBKeyType]) -> IndexedDBKeyType {
+        IndexedDBKeyType::Array(keys.to_vec())
+    }
+
+    /// Keys of every type, including the edge cases of each encoding.
+    fn sample_keys() -> Vec<IndexedDBKeyType> {
+        vec![
+            number(f64::NEG_INFINITY),
+            number(f64::MIN),
+            number(-1.5),
+            number(-f64::MIN_POSITIVE),
+            number(-0.0),
+            number(0.0),
+            number(f64::from_bits(1)),
+            number(1.0),
+            number(2.0),
+            number(f64::MAX),
+            number(f64::INFINITY),
+            IndexedDBKeyType::Date(-1.0),
+            IndexedDBKeyType::Date(0.0),
+            IndexedDBKeyType::Date(1_700_000_000_000.0),
+            string(""),
+            string("\0"),
+            string("a"),
+            string("a\0"),
+            string("ab"),
+            string("abc"),
+            string("b"),
+            string("\u{1F600}"),
+            string("\u{FFFF}"),
+            IndexedDBKeyType::Binary(vec![]),
+            IndexedDBKeyType::Binary(vec![0]),
+            IndexedDBKeyType::Binary(vec![0, 0]),
+            IndexedDBKeyType::Binary(vec![1]),
+            IndexedDBKeyType::Binary(vec![255]),
+            array(&[]),
+            array(&[number(1.0)]),
+            array(&[number(1.0), number(2.0)]),
+            array(&[number(1.0), string("a")]),
+            array(&[string("a")]),
+            array(&[array(&[])]),
+            array(&[array(&[number(1.0)])]),
+            array(&[array(&[number(1.0)]), number(2.0)]),
+            array(&[array(&[number(1.0), number(2.0)])]),
+            array(&[array(&[array(&[])])]),
+        ]
+    }
+
+    #[test]
+    fn test_keys_round_trip() {
+        for key in sample_keys() {
+            let bytes = encode_key(&key);
+            let (decoded, length) = decode_key(&bytes).expect("Should decode an encoded key");
+            assert_eq!(decoded, key);
+            assert_eq!(
+                length,
+                bytes.len(),
+                "{key:?} should take up all of its bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn test_byte_order_matches_key_order() {
+        let keys = sample_keys();
+        for a in keys.iter() {
+            for b in keys.iter() {
+                assert_eq!(
+                    encode_key(a).cmp(&encode_key(b)),
+                    a.cmp(b),
+                    "Comparing {a:?} with {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_keys_are_sorted() {
+        // Checks the expectations above against the key comparison itself, so that the byte
+        // order isn't only compared with itself.
+        let keys = sample_keys();
+        for pair in keys.windows(2) {
+            assert!(
+                pair[0] <= pair[1],
+                "{:?} should not sort after {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_negative_zero_is_zero() {
+        assert_eq!(encode_key(&number(-0.0)), encode_key(&number(0.0)));
+        assert_eq!(
+            encode_key(&IndexedDBKeyType::Date(-0.0)),
+            encode_key(&IndexedDBKeyType::Date(0.0))
+        );
+        assert!(encode_key(&number(-f64::MIN_POSITIVE)) < encode_key(&number(-0.0)));
+    }
+
+    #[test]
+    fn test_prefixes_sort_first() {
+        assert!(encode_key(&string("")) < encode_key(&string("a")));
+        assert!(encode_key(&string("ab")) < encode_key(&string("abc")));
+        assert!(encode_key(&string("a")) < encode_key(&string("a\0")));
+        assert!(
+            encode_key(&IndexedDBKeyType::Binary(vec![0])) <
+                encode_key(&IndexedDBKeyType::Binary(vec![0, 0]))
+        );
+        assert!(
+            encode_key(&array(&[number(1.0)])) < encode_key(&array(&[number(1.0), number(0.0)]))
+        );
+        assert!(
+            encode_key(&array(&[array(&[number(1.0)])])) <
+                encode_key(&array(&[array(&[number(1.0)]), number(0.0)]))
+        );
+    }
+
+    #[test]
+    fn test_strings_compare_by_code_units() {
+        // U+1F600 is encoded as the surrogate pair D83D DE00, which sorts before U+FFFF even
+        // though its code point is higher.
+        assert!(encode_key(&string("\u{1F600}")) < encode_key(&string("\u{FFFF}")));
+    }
+
+    #[test]
+    fn test_truncated_keys_do_not_decode() {
+        for key in sample_keys() {
+            let bytes = encode_key(&key);
+            for length in 0..bytes.len() {
+                assert!(
+                    decode_key(&bytes[..length]).is_none(),
+                    "{key:?} truncated to {length} bytes should not decode"
+                );
+            }
+        }
+        assert!(decode_key(&[0x60]).is_none());
+    }
+
+    #[test]
+    fn test_index_entries() {
+        let keys = sample_keys();
+        for key in keys.iter() {
+            for primary_key in [number(1.0), string("a"), array(&[number(1.0)])] {
+                let entry = encode_index_entry(key, &primary_key);
+                assert_eq!(
+                    decode_index_entry(&entry),
+                    Some((key.clone(), primary_key.clone()))
+                );
+                assert!(entry.starts_with(&encode_key(key)));
+                assert!(entry < after_index_entries(key));
+            }
+        }
+
+        // Entries are ordered by their index key first, and then by their primary key.
+        let entries: Vec<_> = [
+            (number(1.0), string("b")),
+            (number(1.0), string("c")),
+            (number(2.0), string("a")),
+            (string("a"), number(3.0)),
+            (array(&[number(1.0)]), number(0.0)),
+            (array(&[number(1.0), number(2.0)]), number(0.0)),
+        ]
+        .iter()
+        .map(|(key, primary_key)| encode_index_entry(key, primary_key))
+        .collect();
+        assert!(entries.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    fn ranges() -> Vec<IndexedDBKeyRange> {
+        let mut ranges = vec![IndexedDBKeyRange::unbounded()];
+        for open in [false, true] {
+            ranges.push(IndexedDBKeyRange::lower_bound(string("ab"), open));
+            ranges.push(IndexedDBKeyRange::upper_bound(array(&[number(1.0)]), open));
+            ranges.push(IndexedDBKeyRange {
+                lower: Some(number(-0.0)),
+                upper: Some(string("a")),
+                lower_open: open,
+                upper_open: !open,
+            });
+        }
+        ranges.push(IndexedDBKeyRange::from(string("a")));
+        ranges
+    }
+
+    #[test]
+    fn test_key_range_bounds() {
+        for range in ranges() {
+            let bounds = key_range_bounds(&range);
+            for key in sample_keys() {
+                assert_eq!(
+                    bounds.contains(&encode_key(&key)),
+                    range.contains(&key),
+                    "{key:?} in {range:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_range_bounds() {
+        let primary_keys = [number(f64::NEG_INFINITY), string(""), array(&[array(&[])])];
+        for range in ranges() {
+            let bounds = index_range_bounds(&range);
+            for key in sample_keys() {
+                for primary_key in primary_keys.iter() {
+                    assert_eq!(
+                        bounds.contains(&encode_index_entry(&key, primary_key)),
+                        range.contains(&key),
+                        "{key:?} with primary key {primary_key:?} in {range:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_combining_bounds() {
+        let low = encode_key(&number(1.0));
+        let high = encode_key(&number(2.0));
+
+        assert_eq!(
+            max_lower_bound(Bound::Unbounded, Bound::Included(low.clone())),
+            Bound::Included(low.clone())
+        );
+        assert_eq!(
+            max_lower_bound(Bound::Included(low.clone()), Bound::Excluded(high.clone())),
+            Bound::Excluded(high.clone())
+        );
+        assert_eq!(
+            max_lower_bound(Bound::Included(low.clone()), Bound::Excluded(low.clone())),
+            Bound::Excluded(low.clone())
+        );
+        assert_eq!(
+            min_upper_bound(Bound::Included(high.clone()), Bound::Unbounded),
+            Bound::Included(high.clone())
+        );
+        assert_eq!(
+            min_upper_bound(Bound::Included(low.clone()), Bound::Excluded(high.clone())),
+            Bound::Included(low.clone())
+        );
+        assert_eq!(
+            min_upper_bound(Bound::Excluded(high.clone()), Bound::Included(high.clone())),
+            Bound::Excluded(high)
+        );
+    }
+}
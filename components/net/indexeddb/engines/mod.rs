@@ -5,12 +5,16 @@
 use std::collections::VecDeque;
 
 use ipc_channel::ipc::IpcSender;
-use net_traits::indexeddb_thread::{AsyncOperation, IndexedDBTxnMode};
+use net_traits::indexeddb_thread::{
+    AsyncOperation, AsyncOperationResult, BackendResult, IndexMetadata, IndexedDBKeyType,
+    IndexedDBTxnDurability, IndexedDBTxnMode, KeyPath, ObjectStoreMetadata,
+};
 use tokio::sync::oneshot;
 
 pub use self::heed::HeedEngine;
 
 mod heed;
+mod key_encoding;
 
 #[derive(Eq, Hash, PartialEq)]
 pub struct SanitizedName {
@@ -46,13 +50,15 @@ impl std::fmt::Display for SanitizedName {
 }
 
 pub struct KvsOperation {
-    pub sender: IpcSender<Option<Vec<u8>>>,
-    pub store_name: SanitizedName,
+    pub sender: IpcSender<BackendResult<AsyncOperationResult>>,
+    pub store_name: String,
     pub operation: AsyncOperation,
 }
 
 pub struct KvsTransaction {
     pub mode: IndexedDBTxnMode,
+    // The serial number of the transaction, used to revert its changes if it aborts
+    pub serial_number: u64,
     pub requests: VecDeque<KvsOperation>,
 }
 
@@ -61,19 +67,44 @@ pub trait KvsEngine {
 
     fn create_store(
         &self,
-        store_name: SanitizedName,
+        store_name: &str,
+        key_path: Option<KeyPath>,
         auto_increment: bool,
     ) -> Result<(), Self::Error>;
 
-    fn delete_store(&self, store_name: SanitizedName) -> Result<(), Self::Error>;
+    fn delete_store(&self, store_name: &str) -> Result<(), Self::Error>;
 
-    #[expect(dead_code)]
-    fn close_store(&self, store_name: SanitizedName) -> Result<(), Self::Error>;
+    fn object_store(&self, store_name: &str) -> Option<ObjectStoreMetadata>;
+
+    fn object_store_names(&self) -> Vec<String>;
+
+    fn all_records(&self, store_name: &str) -> BackendResult<Vec<(IndexedDBKeyType, Vec<u8>)>>;
+
+    fn create_index(
+        &self,
+        store_name: &str,
+        index: IndexMetadata,
+        keys: Vec<(IndexedDBKeyType, Vec<IndexedDBKeyType>)>,
+    ) -> BackendResult<()>;
+
+    fn delete_index(&self, store_name: &str, index_name: &str) -> Result<(), Self::Error>;
 
     fn process_transaction(
         &self,
         transaction: KvsTransaction,
     ) -> oneshot::Receiver<Option<Vec<u8>>>;
 
-    fn has_key_generator(&self, store_name: SanitizedName) -> bool;
+    // Makes the changes of a transaction permanent
+    fn commit_transaction(
+        &self,
+        serial_number: u64,
+        durability: IndexedDBTxnDurability,
+    ) -> BackendResult<()>;
+
+    // Reverts the changes of a transaction
+    fn abort_transaction(&self, serial_number: u64) -> BackendResult<()>;
+
+    fn version(&self) -> u64;
+
+    fn set_version(&self, version: u64) -> Result<(), Self::Error>;
 }
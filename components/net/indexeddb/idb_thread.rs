@@ -4,16 +4,19 @@
 use std::borrow::ToOwned;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
+use std::time::SystemTime;
 
 use ipc_channel::ipc::{self, IpcError, IpcReceiver, IpcSender};
 use log::{debug, warn};
 use net_traits::indexeddb_thread::{
-    AsyncOperation, IndexedDBThreadMsg, IndexedDBTxnMode, SyncOperation,
+    AsyncOperation, AsyncOperationResult, AsyncReadWriteOperation, BackendError, BackendResult,
+    IndexedDBOriginUsage, IndexedDBThreadMsg, IndexedDBTxnMode, KeyPath, SyncOperation,
 };
 use servo_config::pref;
+use servo_url::ServoUrl;
 use servo_url::origin::ImmutableOrigin;
 
 use crate::indexeddb::engines::{
@@ -23,6 +26,11 @@ use crate::resource_thread::CoreResourceThreadPool;
 
 pub trait IndexedDBThreadFactory {
     fn new(config_dir: Option<PathBuf>) -> Self;
+
+    /// Create an IndexedDB thread whose databases live in `dir`, which should be backed by
+    /// memory, only for as long as the thread does. Whatever a previous process left there
+    /// is removed first. Without a directory, opening a database fails.
+    fn new_ephemeral(dir: Option<PathBuf>) -> Self;
 }
 
 impl IndexedDBThreadFactory for IpcSender<IndexedDBThreadMsg> {
@@ -38,7 +46,23 @@ impl IndexedDBThreadFactory for IpcSender<IndexedDBThreadMsg> {
         thread::Builder::new()
             .name("IndexedDBManager".to_owned())
             .spawn(move || {
-                IndexedDBManager::new(port, idb_base_dir).start();
+                IndexedDBManager::new(port, Some(idb_base_dir), false).start();
+            })
+            .expect("Thread spawning failed");
+
+        chan
+    }
+
+    fn new_ephemeral(dir: Option<PathBuf>) -> IpcSender<IndexedDBThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+
+        thread::Builder::new()
+            .name("IndexedDBManager".to_owned())
+            .spawn(move || {
+                let mut manager = IndexedDBManager::new(port, dir, true);
+                manager.delete_all();
+                manager.start();
+                manager.delete_all();
             })
             .expect("Thread spawning failed");
 
@@ -56,17 +80,52 @@ impl IndexedDBDescription {
     // Converts the database description to a folder name where all
     // data for this database is stored
     fn as_path(&self) -> PathBuf {
-        let mut path = PathBuf::new();
+        let mut path = origin_path(&self.origin);
 
-        let sanitized_origin = SanitizedName::new(self.origin.ascii_serialization());
         let sanitized_name = SanitizedName::new(self.name.clone());
-        path.push(sanitized_origin.to_string());
         path.push(sanitized_name.to_string());
 
         path
     }
 }
 
+// The folder where the databases of an origin are stored
+fn origin_path(origin: &ImmutableOrigin) -> PathBuf {
+    let sanitized_origin = SanitizedName::new(origin.ascii_serialization());
+    PathBuf::from(sanitized_origin.to_string())
+}
+
+// The storage quota of an origin, in bytes
+fn origin_quota() -> u64 {
+    pref!(dom_indexeddb_origin_quota_mb).max(1) as u64 * 1024 * 1024
+}
+
+// The file in the folder of an origin that holds the serialization of the origin
+const ORIGIN_FILE_NAME: &str = "origin";
+
+// The metadata of the files of the databases of an origin
+fn database_files(origin_dir: &Path) -> impl Iterator<Item = std::fs::Metadata> {
+    std::fs::read_dir(origin_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|database| std::fs::metadata(database.path().join("data.mdb")).ok())
+}
+
+// The disk space used by the databases of an origin
+fn origin_usage(origin_dir: &Path) -> u64 {
+    database_files(origin_dir)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// When one of the databases of an origin was last written to
+fn origin_last_modified(origin_dir: &Path) -> Option<SystemTime> {
+    database_files(origin_dir)
+        .filter_map(|metadata| metadata.modified().ok())
+        .max()
+}
+
 struct IndexedDBEnvironment<E: KvsEngine> {
     engine: E,
     version: u64,
@@ -76,10 +135,10 @@ struct IndexedDBEnvironment<E: KvsEngine> {
 }
 
 impl<E: KvsEngine> IndexedDBEnvironment<E> {
-    fn new(engine: E, version: u64) -> IndexedDBEnvironment<E> {
+    fn new(engine: E) -> IndexedDBEnvironment<E> {
         IndexedDBEnvironment {
+            version: engine.version(),
             engine,
-            version,
 
             transactions: HashMap::new(),
             serial_number_counter: 0,
@@ -88,8 +147,8 @@ impl<E: KvsEngine> IndexedDBEnvironment<E> {
 
     fn queue_operation(
         &mut self,
-        sender: IpcSender<Option<Vec<u8>>>,
-        store_name: SanitizedName,
+        sender: IpcSender<BackendResult<AsyncOperationResult>>,
+        store_name: String,
         serial_number: u64,
         mode: IndexedDBTxnMode,
         operation: AsyncOperation,
@@ -99,6 +158,7 @@ impl<E: KvsEngine> IndexedDBEnvironment<E> {
             .or_insert_with(|| KvsTransaction {
                 requests: VecDeque::new(),
                 mode,
+                serial_number,
             })
             .requests
             .push_back(KvsOperation {
@@ -125,17 +185,16 @@ impl<E: KvsEngine> IndexedDBEnvironment<E> {
         }
     }
 
-    fn has_key_generator(&self, store_name: SanitizedName) -> bool {
-        self.engine.has_key_generator(store_name)
-    }
-
     fn create_object_store(
         &mut self,
         sender: IpcSender<Result<(), ()>>,
-        store_name: SanitizedName,
+        store_name: String,
+        key_path: Option<KeyPath>,
         auto_increment: bool,
     ) {
-        let result = self.engine.create_store(store_name, auto_increment);
+        let result = self
+            .engine
+            .create_store(&store_name, key_path, auto_increment);
 
         if result.is_ok() {
             let _ = sender.send(Ok(()));
@@ -144,12 +203,8 @@ impl<E: KvsEngine> IndexedDBEnvironment<E> {
         }
     }
 
-    fn delete_object_store(
-        &mut self,
-        sender: IpcSender<Result<(), ()>>,
-        store_name: SanitizedName,
-    ) {
-        let result = self.engine.delete_store(store_name);
+    fn delete_object_store(&mut self, sender: IpcSender<Result<(), ()>>, store_name: String) {
+        let result = self.engine.delete_store(&store_name);
 
         if result.is_ok() {
             let _ = sender.send(Ok(()));
@@ -161,13 +216,20 @@ impl<E: KvsEngine> IndexedDBEnvironment<E> {
 
 struct IndexedDBManager {
     port: IpcReceiver<IndexedDBThreadMsg>,
-    idb_base_dir: PathBuf,
+    /// Where the databases are stored, no database can be opened without it.
+    idb_base_dir: Option<PathBuf>,
+    /// Whether the databases are deleted when this thread exits.
+    ephemeral: bool,
     databases: HashMap<IndexedDBDescription, IndexedDBEnvironment<HeedEngine>>,
     thread_pool: Arc<CoreResourceThreadPool>,
 }
 
 impl IndexedDBManager {
-    fn new(port: IpcReceiver<IndexedDBThreadMsg>, idb_base_dir: PathBuf) -> IndexedDBManager {
+    fn new(
+        port: IpcReceiver<IndexedDBThreadMsg>,
+        idb_base_dir: Option<PathBuf>,
+        ephemeral: bool,
+    ) -> IndexedDBManager {
         debug!("New indexedDBManager");
 
         let thread_count = thread::available_parallelism()
@@ -177,6 +239,7 @@ impl IndexedDBManager {
         IndexedDBManager {
             port,
             idb_base_dir,
+            ephemeral,
             databases: HashMap::new(),
             thread_pool: Arc::new(CoreResourceThreadPool::new(
                 thread_count,
@@ -219,7 +282,18 @@ impl IndexedDBManager {
                     mode,
                     operation,
                 ) => {
-                    let store_name = SanitizedName::new(store_name);
+                    // https://w3c.github.io/IndexedDB/#quota-exceeded
+                    // Writes fail once the databases of the origin use up its quota.
+                    if matches!(
+                        operation,
+                        AsyncOperation::ReadWrite(AsyncReadWriteOperation::PutItem(..))
+                    ) && self
+                        .origin_dir(&origin)
+                        .is_some_and(|origin_dir| origin_usage(&origin_dir) >= origin_quota())
+                    {
+                        let _ = sender.send(Err(BackendError::QuotaExceeded));
+                        continue;
+                    }
                     if let Some(db) = self.get_database_mut(origin, db_name) {
                         // Queues an operation for a transaction without starting it
                         db.queue_operation(sender, store_name, txn, mode, operation);
@@ -232,6 +306,44 @@ impl IndexedDBManager {
         }
     }
 
+    /// Close the databases of every origin and remove their files.
+    fn delete_all(&mut self) {
+        self.databases.clear();
+        let Some(idb_base_dir) = &self.idb_base_dir else {
+            return;
+        };
+        if let Err(error) = std::fs::remove_dir_all(idb_base_dir) {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                warn!("Could not delete the IndexedDB databases: {error}");
+            }
+        }
+    }
+
+    /// The disk space used by the databases of every origin, found from the origin files
+    /// that are written next to the databases of each origin.
+    fn usage(&self) -> Vec<IndexedDBOriginUsage> {
+        let Some(Ok(origin_dirs)) = self.idb_base_dir.as_ref().map(std::fs::read_dir) else {
+            return vec![];
+        };
+        origin_dirs
+            .flatten()
+            .filter_map(|origin_dir| {
+                let origin_dir = origin_dir.path();
+                let origin = std::fs::read_to_string(origin_dir.join(ORIGIN_FILE_NAME)).ok()?;
+                let origin = ServoUrl::parse(origin.trim()).ok()?.origin();
+                Some(IndexedDBOriginUsage {
+                    origin,
+                    usage: origin_usage(&origin_dir),
+                    last_modified: origin_last_modified(&origin_dir),
+                })
+            })
+            .collect()
+    }
+
+    fn origin_dir(&self, origin: &ImmutableOrigin) -> Option<PathBuf> {
+        Some(self.idb_base_dir.as_ref()?.join(origin_path(origin)))
+    }
+
     fn get_database(
         &self,
         origin: ImmutableOrigin,
@@ -270,29 +382,50 @@ impl IndexedDBManager {
                 }
                 let _ = sender.send(Ok(()));
             },
-            SyncOperation::OpenDatabase(sender, origin, db_name, version) => {
+            SyncOperation::OpenDatabase(sender, origin, db_name, _version) => {
                 let idb_description = IndexedDBDescription {
                     origin,
                     name: db_name,
                 };
 
-                let idb_base_dir = self.idb_base_dir.as_path();
+                let Some(idb_base_dir) = self.idb_base_dir.as_deref() else {
+                    let _ = sender.send(Err(BackendError::DbErr(
+                        "IndexedDB is not available".to_owned(),
+                    )));
+                    return;
+                };
 
                 match self.databases.entry(idb_description.clone()) {
                     Entry::Vacant(e) => {
-                        let db = IndexedDBEnvironment::new(
-                            HeedEngine::new(
-                                idb_base_dir,
-                                &idb_description.as_path(),
-                                self.thread_pool.clone(),
-                            ),
-                            version.unwrap_or(0),
-                        );
-                        let _ = sender.send(db.version);
+                        // The size of the memory map limits how large the database can
+                        // grow, LMDB requires it to be a multiple of the page size.
+                        let map_size = origin_quota().next_multiple_of(64 * 1024) as usize;
+                        // A new database has version 0, the version it is opened with
+                        // is set by its upgrade transaction.
+                        let db = IndexedDBEnvironment::new(HeedEngine::new(
+                            idb_base_dir,
+                            &idb_description.as_path(),
+                            self.thread_pool.clone(),
+                            map_size,
+                        ));
+                        // Remember which origin the databases belong to, as the
+                        // sanitized folder name cannot be turned back into it.
+                        let origin_file = idb_base_dir
+                            .join(origin_path(&idb_description.origin))
+                            .join(ORIGIN_FILE_NAME);
+                        if !origin_file.exists() {
+                            if let Err(error) = std::fs::write(
+                                &origin_file,
+                                idb_description.origin.ascii_serialization(),
+                            ) {
+                                warn!("Could not write the origin of the database: {error}");
+                            }
+                        }
+                        let _ = sender.send(Ok(db.version));
                         e.insert(db);
                     },
                     Entry::Occupied(db) => {
-                        let _ = sender.send(db.get().version);
+                        let _ = sender.send(Ok(db.get().version));
                     },
                 }
             },
@@ -305,29 +438,82 @@ impl IndexedDBManager {
 
                 // FIXME:(rasviitanen) Possible security issue?
                 // FIXME:(arihant2math) using remove_dir_all with arbitrary input ...
-                let mut db_dir = self.idb_base_dir.clone();
-                db_dir.push(idb_description.as_path());
+                let Some(idb_base_dir) = &self.idb_base_dir else {
+                    let _ = sender.send(Err(()));
+                    return;
+                };
+                let db_dir = idb_base_dir.join(idb_description.as_path());
                 if std::fs::remove_dir_all(&db_dir).is_err() {
                     let _ = sender.send(Err(()));
                 } else {
                     let _ = sender.send(Ok(()));
                 }
             },
-            SyncOperation::HasKeyGenerator(sender, origin, db_name, store_name) => {
-                let store_name = SanitizedName::new(store_name);
+            SyncOperation::GetObjectStore(sender, origin, db_name, store_name) => {
+                let result = self
+                    .get_database(origin, db_name)
+                    .and_then(|db| db.engine.object_store(&store_name));
+                let _ = sender.send(result);
+            },
+            SyncOperation::ObjectStoreNames(sender, origin, db_name) => {
                 let result = self
                     .get_database(origin, db_name)
-                    .map(|db| db.has_key_generator(store_name))
-                    .expect("No Database");
-                sender.send(result).expect("Could not send generator info");
+                    .map(|db| db.engine.object_store_names())
+                    .unwrap_or_default();
+                let _ = sender.send(result);
+            },
+            SyncOperation::Commit(sender, origin, db_name, txn, durability) => {
+                let result = match self.get_database_mut(origin, db_name) {
+                    Some(db) => {
+                        // Run the requests that are still queued before committing
+                        db.start_transaction(txn, None);
+                        db.engine.commit_transaction(txn, durability)
+                    },
+                    None => Err(BackendError::DbErr("No database".to_owned())),
+                };
+                let _ = sender.send(result);
             },
-            SyncOperation::Commit(sender, _origin, _db_name, _txn) => {
-                // FIXME:(arihant2math) This does nothing at the moment
-                sender.send(Err(())).expect("Could not send commit status");
+            SyncOperation::Abort(sender, origin, db_name, txn) => {
+                let result = match self.get_database_mut(origin, db_name) {
+                    Some(db) => {
+                        // Requests that have not run yet are dropped, with their results
+                        db.transactions.remove(&txn);
+                        db.engine.abort_transaction(txn)
+                    },
+                    None => Err(BackendError::DbErr("No database".to_owned())),
+                };
+                let _ = sender.send(result);
+            },
+            SyncOperation::GetAllRecords(sender, origin, db_name, store_name) => {
+                let result = match self.get_database(origin, db_name) {
+                    Some(db) => db.engine.all_records(&store_name),
+                    None => Err(BackendError::DbErr("No database".to_owned())),
+                };
+                let _ = sender.send(result);
+            },
+            SyncOperation::CreateIndex(sender, origin, db_name, store_name, index, keys) => {
+                let result = match self.get_database(origin, db_name) {
+                    Some(db) => db.engine.create_index(&store_name, index, keys),
+                    None => Err(BackendError::DbErr("No database".to_owned())),
+                };
+                let _ = sender.send(result);
+            },
+            SyncOperation::DeleteIndex(sender, origin, db_name, store_name, index_name) => {
+                let result = match self.get_database(origin, db_name) {
+                    Some(db) => db
+                        .engine
+                        .delete_index(&store_name, &index_name)
+                        .map_err(|_| ()),
+                    None => Err(()),
+                };
+                let _ = sender.send(result);
             },
             SyncOperation::UpgradeVersion(sender, origin, db_name, _txn, version) => {
                 if let Some(db) = self.get_database_mut(origin, db_name) {
                     if version > db.version {
+                        if let Err(error) = db.engine.set_version(version) {
+                            warn!("Could not store the version of the database: {error:?}");
+                        }
                         db.version = version;
                     }
                     // erroring out if the version is not upgraded can be and non-replicable
@@ -341,15 +527,14 @@ impl IndexedDBManager {
                 origin,
                 db_name,
                 store_name,
+                key_path,
                 auto_increment,
             ) => {
-                let store_name = SanitizedName::new(store_name);
                 if let Some(db) = self.get_database_mut(origin, db_name) {
-                    db.create_object_store(sender, store_name, auto_increment);
+                    db.create_object_store(sender, store_name, key_path, auto_increment);
                 }
             },
             SyncOperation::DeleteObjectStore(sender, origin, db_name, store_name) => {
-                let store_name = SanitizedName::new(store_name);
                 if let Some(db) = self.get_database_mut(origin, db_name) {
                     db.delete_object_store(sender, store_name);
                 }
@@ -370,8 +555,30 @@ impl IndexedDBManager {
                     let _ = sender.send(db.serial_number_counter);
                 }
             },
+            SyncOperation::Usage(sender) => {
+                let _ = sender.send(self.usage());
+            },
+            SyncOperation::DeleteOrigin(sender, origin) => {
+                // Close the databases of the origin before removing their files
+                self.databases
+                    .retain(|description, _| description.origin != origin);
+                if let Some(origin_dir) = self.origin_dir(&origin) {
+                    if let Err(error) = std::fs::remove_dir_all(&origin_dir) {
+                        if error.kind() != std::io::ErrorKind::NotFound {
+                            warn!("Could not delete the databases of {origin:?}: {error}");
+                        }
+                    }
+                }
+                let _ = sender.send(());
+            },
+            SyncOperation::DeleteAll(sender) => {
+                self.delete_all();
+                let _ = sender.send(());
+            },
             SyncOperation::Exit(sender) => {
-                // FIXME:(rasviitanen) Nothing to do?
+                if self.ephemeral {
+                    self.delete_all();
+                }
                 let _ = sender.send(());
             },
         }
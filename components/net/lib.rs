@@ -5,10 +5,13 @@
 #![deny(unsafe_code)]
 
 pub mod async_runtime;
+mod cache_storage;
 pub mod connector;
+pub mod content_blocker;
 pub mod cookie;
 pub mod cookie_storage;
 mod decoder;
+mod file_system;
 pub mod filemanager_thread;
 mod hosts;
 pub mod hsts;
@@ -20,6 +23,7 @@ pub mod local_directory_listing;
 pub mod protocols;
 pub mod request_interceptor;
 pub mod resource_thread;
+pub mod speculative_connections;
 mod storage_thread;
 pub mod subresource_integrity;
 mod websocket_loader;
@@ -21,13 +21,16 @@ use crate::fetch::methods::{DoneChannel, FetchContext, RangeRequestBounds};
 mod blob;
 mod data;
 mod file;
+mod servo;
 
 use blob::BlobProtocolHander;
 use data::DataProtocolHander;
 use file::FileProtocolHander;
+pub use servo::{InternalPage, InternalPageRegistry};
 
-// The set of schemes that can't be registered.
-static FORBIDDEN_SCHEMES: [&str; 4] = ["http", "https", "chrome", "about"];
+// The set of schemes that can't be registered. The pages of the `servo` scheme are
+// registered with `register_internal_page` instead.
+static FORBIDDEN_SCHEMES: [&str; 5] = ["http", "https", "chrome", "about", "servo"];
 
 pub trait ProtocolHandler: Send + Sync {
     /// Triggers the load of a resource for this protocol and returns a future
@@ -62,17 +65,22 @@ pub trait ProtocolHandler: Send + Sync {
 #[derive(Default)]
 pub struct ProtocolRegistry {
     pub(crate) handlers: HashMap<String, Box<dyn ProtocolHandler>>, // Maps scheme -> handler
+    pub(crate) internal_pages: InternalPageRegistry,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum ProtocolRegisterError {
     ForbiddenScheme,
     SchemeAlreadyRegistered,
+    PageAlreadyRegistered,
 }
 
 impl ProtocolRegistry {
     pub fn with_internal_protocols() -> Self {
-        let mut registry = Self::default();
+        let mut registry = Self {
+            internal_pages: InternalPageRegistry::with_builtin_pages(),
+            ..Default::default()
+        };
         // We just created a new registry, and know that we aren't using
         // any forbidden schemes, so this should never panic.
         registry
@@ -110,7 +118,21 @@ impl ProtocolRegistry {
         self.handlers.get(scheme).map(|e| e.as_ref())
     }
 
+    /// Register the page generated for `servo:<name>`.
+    pub fn register_internal_page(
+        &mut self,
+        name: &str,
+        page: impl InternalPage + 'static,
+    ) -> Result<(), ProtocolRegisterError> {
+        self.internal_pages.register(name, page)
+    }
+
+    pub fn internal_pages(&self) -> &InternalPageRegistry {
+        &self.internal_pages
+    }
+
     pub fn merge(&mut self, mut other: ProtocolRegistry) {
+        self.internal_pages.merge(other.internal_pages);
         for (scheme, handler) in other.handlers.drain() {
             if FORBIDDEN_SCHEMES.contains(&scheme.as_str()) {
                 error!("Protocol handler for '{scheme}' is not allowed to be registered.");
@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `servo:` scheme, whose pages are built into the browser rather than loaded from a
+//! server. Each page is generated by the [`InternalPage`] registered for the path of the URL,
+//! e.g. `servo:version`, with access to the state of the network stack through the
+//! [`FetchContext`] of the load. Pages that need data from the embedder, such as its history,
+//! hold on to whatever handle to that data they need when they are registered.
+//!
+//! Loading a page never changes any state. Pages change state, e.g. remove an entry of a
+//! list, through [`InternalPage::perform_action`], which the page itself triggers with
+//! `navigator.servo.performAction()`, an API that web content has no access to.
+//!
+//! The pages of Servo itself are registered by [`ProtocolRegistry::with_internal_protocols`],
+//! and embedders add their own with [`ProtocolRegistry::register_internal_page`].
+//!
+//! [`ProtocolRegistry::with_internal_protocols`]: super::ProtocolRegistry::with_internal_protocols
+//! [`ProtocolRegistry::register_internal_page`]: super::ProtocolRegistry::register_internal_page
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use embedder_traits::resources::{self, Resource};
+use headers::{ContentType, HeaderMapExt};
+use log::error;
+use net_traits::ResourceFetchTiming;
+use net_traits::http_status::HttpStatus;
+use net_traits::request::Request;
+use net_traits::response::{Response, ResponseBody};
+use servo_url::ServoUrl;
+
+use super::ProtocolRegisterError;
+use crate::fetch::methods::FetchContext;
+
+pub trait InternalPage: Send + Sync {
+    /// Generate the HTML of the page for a load of `url`. Returns an error message if the
+    /// page could not be generated.
+    fn generate(&self, url: &ServoUrl, context: &FetchContext) -> Result<String, String>;
+
+    /// Perform an `action` on `value`, e.g. removing an entry of a list, as requested by a
+    /// document of the page. Pages without actions ignore it.
+    fn perform_action(&self, _action: &str, _value: &str) {}
+}
+
+impl<F> InternalPage for F
+where
+    F: Fn(&ServoUrl, &FetchContext) -> Result<String, String> + Send + Sync,
+{
+    fn generate(&self, url: &ServoUrl, context: &FetchContext) -> Result<String, String> {
+        self(url, context)
+    }
+}
+
+#[derive(Default)]
+pub struct InternalPageRegistry {
+    pages: HashMap<String, Box<dyn InternalPage>>, // Maps path -> page
+}
+
+impl InternalPageRegistry {
+    /// The registry of the pages of Servo itself.
+    pub(crate) fn with_builtin_pages() -> Self {
+        let mut registry = Self::default();
+        registry
+            .register("memory", memory_page)
+            .expect("Infallible");
+        registry
+            .register("version", version_page)
+            .expect("Infallible");
+        registry
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        page: impl InternalPage + 'static,
+    ) -> Result<(), ProtocolRegisterError> {
+        if let Entry::Vacant(entry) = self.pages.entry(name.into()) {
+            entry.insert(Box::new(page));
+            Ok(())
+        } else {
+            error!("Internal page 'servo:{name}' is already registered.");
+            Err(ProtocolRegisterError::PageAlreadyRegistered)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn InternalPage> {
+        self.pages.get(name).map(|page| page.as_ref())
+    }
+
+    /// Perform an action requested by a document at `url`, which is a page of this registry.
+    pub(crate) fn perform_action(&self, url: &ServoUrl, action: &str, value: &str) {
+        if url.scheme() != "servo" {
+            return;
+        }
+        match self.get(url.path()) {
+            Some(page) => page.perform_action(action, value),
+            None => error!("Action {action} requested by an unknown internal page: {url}"),
+        }
+    }
+
+    /// Add the pages of `other` which are not registered yet.
+    pub(crate) fn merge(&mut self, mut other: InternalPageRegistry) {
+        for (name, page) in other.pages.drain() {
+            self.pages.entry(name).or_insert(page);
+        }
+    }
+
+    /// Load the page of the `servo:` URL of `request`.
+    pub(crate) fn load(&self, request: &Request, context: &FetchContext) -> Response {
+        let url = request.current_url();
+        let Some(page) = self.get(url.path()) else {
+            return Response::network_internal_error("Unknown internal page");
+        };
+        let html = match page.generate(&url, context) {
+            Ok(html) => html,
+            Err(message) => return Response::network_internal_error(message),
+        };
+        let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+        response
+            .headers
+            .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
+        *response.body.lock().unwrap() = ResponseBody::Done(html.into_bytes());
+        response.status = HttpStatus::default();
+        response
+    }
+}
+
+/// The memory report page, which is also available as `about:memory`.
+fn memory_page(_: &ServoUrl, _: &FetchContext) -> Result<String, String> {
+    Ok(resources::read_string(Resource::AboutMemoryHTML))
+}
+
+fn version_page(_: &ServoUrl, context: &FetchContext) -> Result<String, String> {
+    Ok(format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Version</title>\
+         <style>body {{ font-family: sans-serif; margin: 1em 2em; }} \
+         th {{ text-align: left; padding-right: 2em; }}</style></head><body>\
+         <h1>Servo {version}</h1><table>\
+         <tr><th>User agent</th><td>{user_agent}</td></tr>\
+         <tr><th>Operating system</th><td>{os} ({arch})</td></tr>\
+         </table></body></html>",
+        version = env!("CARGO_PKG_VERSION"),
+        user_agent = context
+            .user_agent
+            .replace('&', "&amp;")
+            .replace('<', "&lt;"),
+        os = std::env::consts::OS,
+        arch = std::env::consts::ARCH,
+    ))
+}
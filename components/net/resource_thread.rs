@@ -11,9 +11,10 @@ use std::io::prelude::*;
 use std::io::{self, BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock, Weak};
-use std::thread;
 use std::time::Duration;
+use std::{process, thread};
 
+use base::id::PipelineId;
 use cookie::Cookie;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
@@ -24,14 +25,14 @@ use log::{debug, trace, warn};
 use net_traits::blob_url_store::parse_blob_url;
 use net_traits::filemanager_thread::FileTokenCheck;
 use net_traits::indexeddb_thread::IndexedDBThreadMsg;
-use net_traits::pub_domains::public_suffix_list_size_of;
+use net_traits::pub_domains::{Site, public_suffix_list_size_of};
 use net_traits::request::{Destination, RequestBuilder, RequestId};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::{
-    CookieSource, CoreResourceMsg, CoreResourceThread, CustomResponseMediator, DiscardFetch,
-    FetchChannels, FetchTaskTarget, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
-    WebSocketDomAction, WebSocketNetworkEvent,
+    CookieSource, CoreResourceMsg, CoreResourceThread, DiscardFetch, FetchChannels,
+    FetchTaskTarget, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
+    SpeculativeConnectionKind, WebSocketDomAction, WebSocketNetworkEvent,
 };
 use profile_traits::mem::{
     ProcessReports, ProfilerChan as MemProfilerChan, Report, ReportKind, ReportsChan,
@@ -42,12 +43,13 @@ use profile_traits::time::ProfilerChan;
 use rustls::RootCertStore;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
-use servo_url::{ImmutableOrigin, ServoUrl};
+use servo_url::ServoUrl;
 
 use crate::async_runtime::HANDLE;
 use crate::connector::{
     CACertificates, CertificateErrorOverrideManager, create_http_client, create_tls_config,
 };
+use crate::content_blocker::ContentBlocker;
 use crate::cookie::ServoCookie;
 use crate::cookie_storage::CookieStorage;
 use crate::fetch::cors_cache::CorsCache;
@@ -60,6 +62,7 @@ use crate::http_loader::{HttpState, http_redirect_fetch};
 use crate::indexeddb::idb_thread::IndexedDBThreadFactory;
 use crate::protocols::ProtocolRegistry;
 use crate::request_interceptor::RequestInterceptor;
+use crate::speculative_connections::SpeculativeConnections;
 use crate::storage_thread::StorageThreadFactory;
 use crate::websocket_loader;
 
@@ -108,13 +111,38 @@ pub fn new_resource_threads(
     );
     let idb: IpcSender<IndexedDBThreadMsg> = IndexedDBThreadFactory::new(config_dir.clone());
     let storage: IpcSender<StorageThreadMsg> =
-        StorageThreadFactory::new(config_dir, mem_profiler_chan);
+        StorageThreadFactory::new(config_dir, mem_profiler_chan.clone(), idb.clone());
+
+    // Private browsing gets its own storage partition, which never touches the disk. Local
+    // and session storage are kept in memory, and as the database engine needs a backing
+    // file, the IndexedDB databases live in a memory backed file system. The directory is
+    // removed when the partition is discarded and when the IndexedDB thread starts and exits.
+    // Where there is no such file system, IndexedDB is not available in private browsing.
+    let private_idb_dir =
+        memory_backed_dir().map(|dir| dir.join(format!("servo-private-{}", process::id())));
+    let private_idb: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new_ephemeral(private_idb_dir);
+    let private_storage: IpcSender<StorageThreadMsg> =
+        StorageThreadFactory::new(None, mem_profiler_chan, private_idb.clone());
     (
-        ResourceThreads::new(public_core, storage.clone(), idb.clone()),
-        ResourceThreads::new(private_core, storage, idb),
+        ResourceThreads::new(public_core, storage, idb),
+        ResourceThreads::new(private_core, private_storage, private_idb),
     )
 }
 
+/// A directory whose files are kept in memory, if there is one.
+fn memory_backed_dir() -> Option<PathBuf> {
+    // `/dev/shm` is a tmpfs mount wherever it exists.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let dir = Path::new("/dev/shm");
+        if dir.is_dir() {
+            return Some(dir.to_owned());
+        }
+    }
+    None
+}
+
 /// Create a CoreResourceThread
 #[allow(clippy::too_many_arguments)]
 pub fn new_core_resource_thread(
@@ -140,6 +168,7 @@ pub fn new_core_resource_thread(
                 embedder_proxy.clone(),
                 ca_certificates.clone(),
                 ignore_certificate_errors,
+                config_dir.as_deref(),
             );
 
             let mut channel_manager = ResourceChannelManager {
@@ -194,6 +223,7 @@ fn create_http_states(
     }
 
     let override_manager = CertificateErrorOverrideManager::new();
+    let speculative_connections = SpeculativeConnections::default();
     let http_state = HttpState {
         hsts_list: RwLock::new(hsts_list),
         cookie_jar: RwLock::new(cookie_jar),
@@ -201,16 +231,26 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
-        client: create_http_client(create_tls_config(
-            ca_certificates.clone(),
-            ignore_certificate_errors,
-            override_manager.clone(),
-        )),
+        client: create_http_client(
+            create_tls_config(
+                ca_certificates.clone(),
+                ignore_certificate_errors,
+                override_manager.clone(),
+            ),
+            speculative_connections.clone(),
+        ),
         override_manager,
+        speculative_connections,
         embedder_proxy: Mutex::new(embedder_proxy.clone()),
+        partitioned_cookie_jars: Default::default(),
+        top_level_sites: Default::default(),
+        storage_access_grants: Default::default(),
+        content_blocker: Default::default(),
+        service_worker_mediators: Default::default(),
     };
 
     let override_manager = CertificateErrorOverrideManager::new();
+    let speculative_connections = SpeculativeConnections::default();
     let private_http_state = HttpState {
         hsts_list: RwLock::new(HstsList::default()),
         cookie_jar: RwLock::new(CookieStorage::new(150)),
@@ -218,13 +258,22 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(HttpCache::default()),
         http_cache_state: Mutex::new(HashMap::new()),
-        client: create_http_client(create_tls_config(
-            ca_certificates,
-            ignore_certificate_errors,
-            override_manager.clone(),
-        )),
+        client: create_http_client(
+            create_tls_config(
+                ca_certificates,
+                ignore_certificate_errors,
+                override_manager.clone(),
+            ),
+            speculative_connections.clone(),
+        ),
         override_manager,
+        speculative_connections,
         embedder_proxy: Mutex::new(embedder_proxy),
+        partitioned_cookie_jars: Default::default(),
+        top_level_sites: Default::default(),
+        storage_access_grants: Default::default(),
+        content_blocker: Default::default(),
+        service_worker_mediators: Default::default(),
     };
 
     (Arc::new(http_state), Arc::new(private_http_state))
@@ -406,29 +455,60 @@ impl ResourceChannelManager {
                     protocols,
                 )
             },
-            CoreResourceMsg::SetCookieForUrl(request, cookie, source) => self
-                .resource_manager
-                .set_cookie_for_url(&request, cookie.into_inner().to_owned(), source, http_state),
-            CoreResourceMsg::SetCookiesForUrl(request, cookies, source) => {
+            CoreResourceMsg::SetCookieForUrl(request, cookie, source) => {
+                self.resource_manager.set_cookie_for_url(
+                    &request,
+                    None,
+                    cookie.into_inner().to_owned(),
+                    source,
+                    http_state,
+                )
+            },
+            CoreResourceMsg::SetCookiesForUrl(request, pipeline_id, cookies, source) => {
                 for cookie in cookies {
                     self.resource_manager.set_cookie_for_url(
                         &request,
+                        Some(pipeline_id),
                         cookie.into_inner(),
                         source,
                         http_state,
                     );
                 }
             },
-            CoreResourceMsg::GetCookiesForUrl(url, consumer, source) => {
-                let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-                cookie_jar.remove_expired_cookies_for_url(&url);
-                consumer
-                    .send(cookie_jar.cookies_for_url(&url, source))
-                    .unwrap();
+            CoreResourceMsg::GetCookiesForUrl(url, pipeline_id, consumer, source) => {
+                let partition = http_state.cookie_partition(Some(pipeline_id), &url);
+                let cookies = http_state
+                    .with_cookie_jar(partition, |cookie_jar| {
+                        cookie_jar.remove_expired_cookies_for_url(&url);
+                        cookie_jar.cookies_for_url(&url, source)
+                    })
+                    .flatten();
+                consumer.send(cookies).unwrap();
+            },
+            CoreResourceMsg::GrantStorageAccess(top_level_origin, embedded_origin) => {
+                if let (Some(top_level_site), Some(embedded_site)) = (
+                    Site::for_origin(top_level_origin),
+                    Site::for_origin(embedded_origin),
+                ) {
+                    http_state
+                        .storage_access_grants
+                        .write()
+                        .unwrap()
+                        .insert((top_level_site, embedded_site));
+                }
+            },
+            CoreResourceMsg::PipelineExited(pipeline_id) => {
+                http_state
+                    .top_level_sites
+                    .write()
+                    .unwrap()
+                    .remove(&pipeline_id);
             },
             CoreResourceMsg::NetworkMediator(mediator_chan, origin) => {
-                self.resource_manager
-                    .sw_managers
+                http_state
+                    .service_worker_mediators
+                    .write()
+                    .unwrap()
                     .insert(origin, mediator_chan);
             },
             CoreResourceMsg::GetCookiesDataForUrl(url, consumer, source) => {
@@ -456,9 +536,58 @@ impl ResourceChannelManager {
                     history_states.remove(&history_state);
                 }
             },
+            CoreResourceMsg::GetActiveFetchCount(sender) => {
+                // Fetches hold on to their cancellation listener until they are done.
+                let count = self
+                    .cancellation_listeners
+                    .values()
+                    .filter(|listener| listener.strong_count() > 0)
+                    .count();
+                let _ = sender.send(count);
+            },
             CoreResourceMsg::ClearCache => {
                 http_state.http_cache.write().unwrap().clear();
             },
+            CoreResourceMsg::DiscardState => {
+                http_state.http_cache.write().unwrap().clear();
+                *http_state.cookie_jar.write().unwrap() = CookieStorage::new(150);
+                http_state.partitioned_cookie_jars.write().unwrap().clear();
+                http_state.storage_access_grants.write().unwrap().clear();
+                *http_state.auth_cache.write().unwrap() = AuthCache::default();
+                *http_state.hsts_list.write().unwrap() = HstsList::default();
+                http_state.history_states.write().unwrap().clear();
+            },
+            CoreResourceMsg::SetContentBlockingFilterLists(filter_lists) => {
+                *http_state.content_blocker.write().unwrap() = ContentBlocker::new(&filter_lists);
+            },
+            CoreResourceMsg::GetContentBlockingStylesheet(url, sender) => {
+                let stylesheet = http_state
+                    .content_blocker
+                    .read()
+                    .unwrap()
+                    .element_hiding_stylesheet(&url);
+                let _ = sender.send(stylesheet);
+            },
+            CoreResourceMsg::InternalPageAction(url, action, value) => {
+                protocols
+                    .internal_pages()
+                    .perform_action(&url, &action, &value);
+            },
+            CoreResourceMsg::SpeculativeConnection(url, kind) => {
+                let Some(host) = url.host_str() else {
+                    return true;
+                };
+                match kind {
+                    SpeculativeConnectionKind::DnsPrefetch => {
+                        http_state.speculative_connections.prefetch_dns(host)
+                    },
+                    SpeculativeConnectionKind::Preconnect => {
+                        if let Some(port) = url.port_or_known_default() {
+                            http_state.speculative_connections.preconnect(host, port);
+                        }
+                    },
+                }
+            },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
@@ -552,7 +681,6 @@ pub struct AuthCache {
 
 pub struct CoreResourceManager {
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
-    sw_managers: HashMap<ImmutableOrigin, IpcSender<CustomResponseMediator>>,
     filemanager: FileManager,
     request_interceptor: RequestInterceptor,
     thread_pool: Arc<CoreResourceThreadPool>,
@@ -697,6 +825,7 @@ impl CoreResourceManager {
         embedder_proxy: EmbedderProxy,
         ca_certificates: CACertificates,
         ignore_certificate_errors: bool,
+        config_dir: Option<&Path>,
     ) -> CoreResourceManager {
         let num_threads = thread::available_parallelism()
             .map(|i| i.get())
@@ -706,8 +835,11 @@ impl CoreResourceManager {
         let pool_handle = Arc::new(pool);
         CoreResourceManager {
             devtools_sender,
-            sw_managers: Default::default(),
-            filemanager: FileManager::new(embedder_proxy.clone(), Arc::downgrade(&pool_handle)),
+            filemanager: FileManager::new(
+                embedder_proxy.clone(),
+                Arc::downgrade(&pool_handle),
+                config_dir,
+            ),
             request_interceptor: RequestInterceptor::new(embedder_proxy),
             thread_pool: pool_handle,
             ca_certificates,
@@ -728,13 +860,16 @@ impl CoreResourceManager {
     fn set_cookie_for_url(
         &mut self,
         request: &ServoUrl,
+        pipeline_id: Option<PipelineId>,
         cookie: Cookie<'static>,
         source: CookieSource,
         http_state: &Arc<HttpState>,
     ) {
         if let Some(cookie) = ServoCookie::new_wrapped(cookie, request, source) {
-            let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-            cookie_jar.push(cookie, request, source)
+            let partition = http_state.cookie_partition(pipeline_id, request);
+            http_state.with_cookie_jar(partition, |cookie_jar| {
+                cookie_jar.push(cookie, request, source)
+            });
         }
     }
 
@@ -785,7 +920,6 @@ impl CoreResourceManager {
             // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
             // todo load context / mimesniff in fetch
             // todo referrer policy?
-            // todo service worker stuff
             let context = FetchContext {
                 state: http_state,
                 user_agent: servo_config::pref!(user_agent),
@@ -803,6 +937,8 @@ impl CoreResourceManager {
                     let response = Response::from_init(res_init, timing_type);
 
                     let mut fetch_params = FetchParams::new(request);
+                    fetch_params.process_request_body_chunk_length =
+                        sender.process_request_body_chunk_length(&fetch_params.request);
                     http_redirect_fetch(
                         &mut fetch_params,
                         &mut CorsCache::default(),
@@ -0,0 +1,211 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Work done ahead of the requests that are expected to need it, for
+//! `<link rel=dns-prefetch>`, `<link rel=preconnect>` and the hosts that the HTML
+//! parser discovers before it fetches anything from them.
+//!
+//! Resolved hosts are kept in a small cache that the HTTP connector consults before
+//! resolving a host itself, and preconnected sockets are kept around for a short while,
+//! until the connector needs a new connection to their host. TLS handshakes are still
+//! made when the connection is first used.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper_util::client::legacy::connect::dns::{GaiResolver, Name};
+use log::debug;
+use tokio::net::{TcpStream, lookup_host};
+use tower_service::Service;
+
+use crate::async_runtime::HANDLE;
+use crate::hosts::replace_host;
+
+/// How long the resolved addresses of a host are used before resolving it again.
+const RESOLVED_HOST_LIFETIME: Duration = Duration::from_secs(60);
+
+/// How long a preconnected socket waits for a request to use it before being closed.
+const PRECONNECTED_SOCKET_LIFETIME: Duration = Duration::from_secs(10);
+
+/// The maximum number of hosts whose resolved addresses are cached.
+const MAX_RESOLVED_HOSTS: usize = 256;
+
+/// The maximum number of preconnected sockets waiting to be used, across all hosts.
+const MAX_PRECONNECTED_SOCKETS: usize = 16;
+
+#[derive(Default)]
+struct SpeculativeConnectionsInner {
+    resolved_hosts: HashMap<String, (Instant, Vec<SocketAddr>)>,
+    preconnected_sockets: HashMap<(String, u16), Vec<(Instant, TcpStream)>>,
+}
+
+impl SpeculativeConnectionsInner {
+    fn remove_expired_entries(&mut self, now: Instant) {
+        self.resolved_hosts.retain(|_, (resolved_at, _)| {
+            now.duration_since(*resolved_at) < RESOLVED_HOST_LIFETIME
+        });
+        self.preconnected_sockets.retain(|_, sockets| {
+            sockets.retain(|(connected_at, _)| {
+                now.duration_since(*connected_at) < PRECONNECTED_SOCKET_LIFETIME
+            });
+            !sockets.is_empty()
+        });
+    }
+
+    fn preconnected_socket_count(&self) -> usize {
+        self.preconnected_sockets.values().map(Vec::len).sum()
+    }
+}
+
+/// The hosts resolved and the sockets connected ahead of time for an `HttpState`.
+#[derive(Clone, Default)]
+pub struct SpeculativeConnections(Arc<Mutex<SpeculativeConnectionsInner>>);
+
+impl SpeculativeConnections {
+    /// Resolve `host` in the background, so that connecting to it later does not have
+    /// to wait for DNS.
+    pub fn prefetch_dns(&self, host: &str) {
+        let host = replace_host(host).into_owned();
+        if self.resolved_addresses(&host).is_some() {
+            return;
+        }
+
+        let this = self.clone();
+        HANDLE.spawn(async move {
+            this.resolve(host).await;
+        });
+    }
+
+    /// Resolve `host` and open a socket to it in the background, so that the next
+    /// request to `host` on `port` does not have to wait for DNS nor for the TCP
+    /// handshake.
+    pub fn preconnect(&self, host: &str, port: u16) {
+        let host = replace_host(host).into_owned();
+        {
+            let mut inner = self.0.lock().unwrap();
+            inner.remove_expired_entries(Instant::now());
+            if inner.preconnected_socket_count() >= MAX_PRECONNECTED_SOCKETS ||
+                inner
+                    .preconnected_sockets
+                    .contains_key(&(host.clone(), port))
+            {
+                return;
+            }
+        }
+
+        let this = self.clone();
+        HANDLE.spawn(async move {
+            let Some(addresses) = this.resolve(host.clone()).await else {
+                return;
+            };
+            let addresses: Vec<_> = addresses
+                .into_iter()
+                .map(|address| SocketAddr::new(address.ip(), port))
+                .collect();
+            match TcpStream::connect(&*addresses).await {
+                Ok(socket) => {
+                    let mut inner = this.0.lock().unwrap();
+                    if inner.preconnected_socket_count() < MAX_PRECONNECTED_SOCKETS {
+                        inner
+                            .preconnected_sockets
+                            .entry((host, port))
+                            .or_default()
+                            .push((Instant::now(), socket));
+                    }
+                },
+                Err(error) => debug!("Could not preconnect to {host}:{port}: {error}"),
+            }
+        });
+    }
+
+    async fn resolve(&self, host: String) -> Option<Vec<SocketAddr>> {
+        if let Some(addresses) = self.resolved_addresses(&host) {
+            return Some(addresses);
+        }
+
+        let addresses: Vec<_> = match lookup_host((host.as_str(), 0)).await {
+            Ok(addresses) => addresses.collect(),
+            Err(error) => {
+                debug!("Could not resolve {host} ahead of time: {error}");
+                return None;
+            },
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        inner.remove_expired_entries(Instant::now());
+        if inner.resolved_hosts.len() < MAX_RESOLVED_HOSTS {
+            inner
+                .resolved_hosts
+                .insert(host, (Instant::now(), addresses.clone()));
+        }
+        Some(addresses)
+    }
+
+    fn resolved_addresses(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let inner = self.0.lock().unwrap();
+        let (resolved_at, addresses) = inner.resolved_hosts.get(host)?;
+        (resolved_at.elapsed() < RESOLVED_HOST_LIFETIME).then(|| addresses.clone())
+    }
+
+    /// Take a socket that was preconnected to `host` on `port` and has not been used yet.
+    pub(crate) fn take_preconnected_socket(&self, host: &str, port: u16) -> Option<TcpStream> {
+        let mut inner = self.0.lock().unwrap();
+        inner.remove_expired_entries(Instant::now());
+        let key = (host.to_owned(), port);
+        let sockets = inner.preconnected_sockets.get_mut(&key)?;
+        let (_, socket) = sockets.pop()?;
+        if sockets.is_empty() {
+            inner.preconnected_sockets.remove(&key);
+        }
+        Some(socket)
+    }
+}
+
+/// A DNS resolver for the HTTP connector, which uses the addresses of the hosts that
+/// were resolved ahead of time when they are available.
+#[derive(Clone)]
+pub(crate) struct SpeculativeResolver {
+    speculative_connections: SpeculativeConnections,
+    inner: GaiResolver,
+}
+
+impl SpeculativeResolver {
+    pub(crate) fn new(speculative_connections: SpeculativeConnections) -> Self {
+        Self {
+            speculative_connections,
+            inner: GaiResolver::new(),
+        }
+    }
+}
+
+impl Service<Name> for SpeculativeResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        if let Some(addresses) = self
+            .speculative_connections
+            .resolved_addresses(name.as_str())
+        {
+            return Box::pin(std::future::ready(Ok(addresses.into_iter())));
+        }
+
+        let resolving = self.inner.call(name);
+        Box::pin(async move {
+            let addresses = resolving.await?;
+            Ok(addresses.collect::<Vec<_>>().into_iter())
+        })
+    }
+}
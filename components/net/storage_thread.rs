@@ -6,23 +6,49 @@ use std::borrow::ToOwned;
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use base::id::WebViewId;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use log::warn;
 use malloc_size_of::MallocSizeOf;
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::indexeddb_thread::{IndexedDBOriginUsage, IndexedDBThreadMsg, SyncOperation};
+use net_traits::storage_thread::{
+    CacheBatchError, CacheId, StorageEstimate, StorageKey, StorageThreadMsg, StorageType,
+};
 use profile_traits::mem::{
     ProcessReports, ProfilerChan as MemProfilerChan, Report, ReportKind, perform_memory_report,
 };
 use profile_traits::path;
-use servo_url::ServoUrl;
+use serde::{Deserialize, Serialize};
+use servo_config::pref;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
+use crate::cache_storage::{CacheBatchOperation, CacheStorage};
 use crate::resource_thread;
 
 const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
+/// How often writes may trigger a check of the storage usage of all origins.
+const STORAGE_PRESSURE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The number of bytes an origin may use, across local storage, Cache Storage and IndexedDB.
+fn origin_quota() -> u64 {
+    let indexeddb_quota = pref!(dom_indexeddb_origin_quota_mb).max(0) as u64 * 1024 * 1024;
+    (indexeddb_quota + QUOTA_SIZE_LIMIT as u64).min(total_quota())
+}
+
+/// The number of bytes all origins together may use before the storage is under pressure.
+fn total_quota() -> u64 {
+    pref!(dom_storage_total_quota_mb).max(0) as u64 * 1024 * 1024
+}
+
 pub trait StorageThreadFactory {
-    fn new(config_dir: Option<PathBuf>, mem_profiler_chan: MemProfilerChan) -> Self;
+    fn new(
+        config_dir: Option<PathBuf>,
+        mem_profiler_chan: MemProfilerChan,
+        idb_thread: IpcSender<IndexedDBThreadMsg>,
+    ) -> Self;
 }
 
 impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
@@ -30,6 +56,7 @@ impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
     fn new(
         config_dir: Option<PathBuf>,
         mem_profiler_chan: MemProfilerChan,
+        idb_thread: IpcSender<IndexedDBThreadMsg>,
     ) -> IpcSender<StorageThreadMsg> {
         let (chan, port) = ipc::channel().unwrap();
         let chan2 = chan.clone();
@@ -37,7 +64,7 @@ impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
             .name("StorageManager".to_owned())
             .spawn(move || {
                 mem_profiler_chan.run_with_memory_reporting(
-                    || StorageManager::new(port, config_dir).start(),
+                    || StorageManager::new(port, config_dir, idb_thread).start(),
                     String::from("storage-reporter"),
                     chan2,
                     StorageThreadMsg::CollectMemoryReport,
@@ -50,23 +77,57 @@ impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
 
 type OriginEntry = (usize, BTreeMap<String, String>);
 
+/// What the quota manager keeps track of for an origin that has stored data.
+#[derive(Default, Deserialize, Serialize)]
+struct OriginRecord {
+    /// Whether the <https://storage.spec.whatwg.org/#bucket-mode> of the origin is
+    /// "persistent", in which case its data is not evicted under storage pressure.
+    persistent: bool,
+    /// When the local or session storage of the origin was last modified.
+    last_modified: Option<SystemTime>,
+}
+
 struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<WebViewId, HashMap<String, OriginEntry>>,
     local_data: HashMap<String, OriginEntry>,
+    /// The Cache Storage of every origin.
+    cache_storage: CacheStorage,
+    /// The origins that have stored data, keyed like `local_data`.
+    origins: HashMap<String, OriginRecord>,
+    /// The IndexedDB thread, whose databases count towards the usage of the origins.
+    idb_thread: IpcSender<IndexedDBThreadMsg>,
+    /// When the storage usage of all origins was last checked against the total quota.
+    last_pressure_check: Option<Instant>,
     config_dir: Option<PathBuf>,
 }
 
 impl StorageManager {
-    fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
+    fn new(
+        port: IpcReceiver<StorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+        idb_thread: IpcSender<IndexedDBThreadMsg>,
+    ) -> StorageManager {
         let mut local_data = HashMap::new();
+        let mut cache_storage = CacheStorage::default();
+        let mut origins = HashMap::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            resource_thread::read_json_from_file(
+                &mut cache_storage,
+                config_dir,
+                "cache_storage.json",
+            );
+            resource_thread::read_json_from_file(&mut origins, config_dir, "storage_origins.json");
         }
         StorageManager {
             port,
             session_data: HashMap::new(),
             local_data,
+            cache_storage,
+            origins,
+            idb_thread,
+            last_pressure_check: None,
             config_dir,
         }
     }
@@ -74,30 +135,48 @@ impl StorageManager {
 
 impl StorageManager {
     fn start(&mut self) {
+        self.evict_under_pressure();
         loop {
             match self.port.recv().unwrap() {
-                StorageThreadMsg::Length(sender, storage_type, webview_id, url) => {
-                    self.length(sender, storage_type, webview_id, url)
+                StorageThreadMsg::Length(sender, storage_type, webview_id, storage_key) => {
+                    self.length(sender, storage_type, webview_id, storage_key)
                 },
-                StorageThreadMsg::Key(sender, storage_type, webview_id, url, index) => {
-                    self.key(sender, storage_type, webview_id, url, index)
+                StorageThreadMsg::Key(sender, storage_type, webview_id, storage_key, index) => {
+                    self.key(sender, storage_type, webview_id, storage_key, index)
                 },
-                StorageThreadMsg::Keys(sender, storage_type, webview_id, url) => {
-                    self.keys(sender, storage_type, webview_id, url)
+                StorageThreadMsg::Keys(sender, storage_type, webview_id, storage_key) => {
+                    self.keys(sender, storage_type, webview_id, storage_key)
                 },
-                StorageThreadMsg::SetItem(sender, storage_type, webview_id, url, name, value) => {
-                    self.set_item(sender, storage_type, webview_id, url, name, value);
+                StorageThreadMsg::SetItem(
+                    sender,
+                    storage_type,
+                    webview_id,
+                    storage_key,
+                    name,
+                    value,
+                ) => {
+                    self.note_modified(&storage_key.serialize());
+                    self.set_item(sender, storage_type, webview_id, storage_key, name, value);
+                    self.maybe_evict_under_pressure();
                     self.save_state()
                 },
-                StorageThreadMsg::GetItem(sender, storage_type, webview_id, url, name) => {
-                    self.request_item(sender, storage_type, webview_id, url, name)
+                StorageThreadMsg::GetItem(sender, storage_type, webview_id, storage_key, name) => {
+                    self.request_item(sender, storage_type, webview_id, storage_key, name)
                 },
-                StorageThreadMsg::RemoveItem(sender, storage_type, webview_id, url, name) => {
-                    self.remove_item(sender, storage_type, webview_id, url, name);
+                StorageThreadMsg::RemoveItem(
+                    sender,
+                    storage_type,
+                    webview_id,
+                    storage_key,
+                    name,
+                ) => {
+                    self.note_modified(&storage_key.serialize());
+                    self.remove_item(sender, storage_type, webview_id, storage_key, name);
                     self.save_state()
                 },
-                StorageThreadMsg::Clear(sender, storage_type, webview_id, url) => {
-                    self.clear(sender, storage_type, webview_id, url);
+                StorageThreadMsg::Clear(sender, storage_type, webview_id, storage_key) => {
+                    self.note_modified(&storage_key.serialize());
+                    self.clear(sender, storage_type, webview_id, storage_key);
                     self.save_state()
                 },
                 StorageThreadMsg::Clone {
@@ -112,6 +191,138 @@ impl StorageManager {
                     let reports = self.collect_memory_reports();
                     sender.send(ProcessReports::new(reports));
                 },
+                StorageThreadMsg::Estimate(sender, origin) => {
+                    let _ = sender.send(self.estimate(&origin));
+                },
+                StorageThreadMsg::Persist(sender, origin) => {
+                    self.origins
+                        .entry(origin.ascii_serialization())
+                        .or_default()
+                        .persistent = true;
+                    self.save_state();
+                    let _ = sender.send(true);
+                },
+                StorageThreadMsg::Persisted(sender, origin) => {
+                    let _ = sender.send(self.is_persistent(&origin.ascii_serialization()));
+                },
+                StorageThreadMsg::CacheStorageOpen(sender, origin, name) => {
+                    let origin = origin.ascii_serialization();
+                    let _ = sender.send(self.cache_storage.open(&origin, name));
+                },
+                StorageThreadMsg::CacheStorageHas(sender, origin, name) => {
+                    let _ =
+                        sender.send(self.cache_storage.has(&origin.ascii_serialization(), &name));
+                },
+                StorageThreadMsg::CacheStorageDelete(sender, origin, name) => {
+                    let origin = origin.ascii_serialization();
+                    let deleted = self.cache_storage.delete(&origin, &name);
+                    if deleted {
+                        self.note_modified(&origin);
+                        self.save_cache_storage();
+                    }
+                    let _ = sender.send(deleted);
+                },
+                StorageThreadMsg::CacheStorageKeys(sender, origin) => {
+                    let _ = sender.send(self.cache_storage.keys(&origin.ascii_serialization()));
+                },
+                StorageThreadMsg::CacheStorageMatch {
+                    sender,
+                    origin,
+                    cache_name,
+                    request,
+                    options,
+                } => {
+                    let _ = sender.send(self.cache_storage.match_in_caches(
+                        &origin.ascii_serialization(),
+                        cache_name.as_deref(),
+                        &request,
+                        options,
+                    ));
+                },
+                StorageThreadMsg::CacheMatchAll {
+                    sender,
+                    origin,
+                    cache,
+                    request,
+                    options,
+                } => {
+                    let entries = self.cache_storage.match_all(
+                        &origin.ascii_serialization(),
+                        cache,
+                        request.as_ref(),
+                        options,
+                    );
+                    let _ = sender.send(entries.into_iter().cloned().collect());
+                },
+                StorageThreadMsg::CacheKeys {
+                    sender,
+                    origin,
+                    cache,
+                    request,
+                    options,
+                } => {
+                    let entries = self.cache_storage.match_all(
+                        &origin.ascii_serialization(),
+                        cache,
+                        request.as_ref(),
+                        options,
+                    );
+                    let _ = sender.send(
+                        entries
+                            .into_iter()
+                            .map(|entry| entry.request.clone())
+                            .collect(),
+                    );
+                },
+                StorageThreadMsg::CacheMatch {
+                    sender,
+                    origin,
+                    cache,
+                    request,
+                    options,
+                } => {
+                    let entry = self
+                        .cache_storage
+                        .match_all(
+                            &origin.ascii_serialization(),
+                            cache,
+                            Some(&request),
+                            options,
+                        )
+                        .into_iter()
+                        .next()
+                        .cloned();
+                    let _ = sender.send(entry);
+                },
+                StorageThreadMsg::CachePut {
+                    sender,
+                    origin,
+                    cache,
+                    entries,
+                } => {
+                    let operations = entries.into_iter().map(CacheBatchOperation::Put).collect();
+                    let result = self.cache_batch(&origin, cache, operations);
+                    let _ = sender.send(result.map(|_| ()));
+                },
+                StorageThreadMsg::CacheDelete {
+                    sender,
+                    origin,
+                    cache,
+                    request,
+                    options,
+                } => {
+                    let operations = vec![CacheBatchOperation::Delete(request, options)];
+                    let _ = sender.send(self.cache_batch(&origin, cache, operations));
+                },
+                StorageThreadMsg::ClearSiteData {
+                    sender,
+                    origin,
+                    since,
+                } => {
+                    self.clear_site_data(origin, since);
+                    self.save_state();
+                    let _ = sender.send(());
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -135,6 +346,12 @@ impl StorageManager {
                 kind: ReportKind::ExplicitJemallocHeapSize,
                 size: self.session_data.size_of(ops),
             });
+
+            reports.push(Report {
+                path: path!["storage", "cache"],
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size: self.cache_storage.size_of(ops),
+            });
         });
         reports
     }
@@ -142,6 +359,206 @@ impl StorageManager {
     fn save_state(&self) {
         if let Some(ref config_dir) = self.config_dir {
             resource_thread::write_json_to_file(&self.local_data, config_dir, "local_data.json");
+            resource_thread::write_json_to_file(&self.origins, config_dir, "storage_origins.json");
+        }
+    }
+
+    /// Writes the Cache Storage of every origin to disk, which is done separately from the
+    /// other storage data as it holds whole responses.
+    fn save_cache_storage(&self) {
+        if let Some(ref config_dir) = self.config_dir {
+            resource_thread::write_json_to_file(
+                &self.cache_storage,
+                config_dir,
+                "cache_storage.json",
+            );
+        }
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#batch-cache-operations>, with the responses
+    /// that are stored counting towards the quota of the origin.
+    fn cache_batch(
+        &mut self,
+        origin: &ImmutableOrigin,
+        cache: CacheId,
+        operations: Vec<CacheBatchOperation>,
+    ) -> Result<bool, CacheBatchError> {
+        let origin = origin.ascii_serialization();
+        let other_usage = self.estimate_for(&origin).usage - self.cache_storage.usage(&origin);
+        let quota = origin_quota().saturating_sub(other_usage);
+        let result = self.cache_storage.batch(&origin, cache, operations, quota);
+        if let Ok(true) = result {
+            self.note_modified(&origin);
+            self.maybe_evict_under_pressure();
+            self.save_cache_storage();
+            self.save_state();
+        }
+        result
+    }
+
+    fn note_modified(&mut self, origin: &str) {
+        self.origins
+            .entry(origin.to_owned())
+            .or_default()
+            .last_modified = Some(SystemTime::now());
+    }
+
+    fn is_persistent(&self, origin: &str) -> bool {
+        self.origins
+            .get(origin)
+            .is_some_and(|record| record.persistent)
+    }
+
+    /// The disk space used by the IndexedDB databases of every origin.
+    fn indexeddb_usage(&self) -> Vec<IndexedDBOriginUsage> {
+        let Ok((sender, receiver)) = ipc::channel() else {
+            return vec![];
+        };
+        // The IndexedDB thread is not running when IndexedDB is disabled.
+        if self
+            .idb_thread
+            .send(IndexedDBThreadMsg::Sync(SyncOperation::Usage(sender)))
+            .is_err()
+        {
+            return vec![];
+        }
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// The number of bytes used by every origin that has stored data, with when its data
+    /// was last modified.
+    fn origin_usage(&self) -> HashMap<String, (u64, Option<SystemTime>)> {
+        let mut usage: HashMap<String, (u64, Option<SystemTime>)> = self
+            .origins
+            .iter()
+            .map(|(origin, record)| (origin.clone(), (0, record.last_modified)))
+            .collect();
+        for (origin, (total, _)) in &self.local_data {
+            usage.entry(origin.clone()).or_default().0 += *total as u64;
+        }
+        for (origin, cache_usage) in self.cache_storage.usage_by_origin() {
+            usage.entry(origin.clone()).or_default().0 += cache_usage;
+        }
+        for indexeddb_usage in self.indexeddb_usage() {
+            let entry = usage
+                .entry(indexeddb_usage.origin.ascii_serialization())
+                .or_default();
+            entry.0 += indexeddb_usage.usage;
+            entry.1 = entry.1.max(indexeddb_usage.last_modified);
+        }
+        usage
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-estimate>
+    fn estimate(&self, origin: &ImmutableOrigin) -> StorageEstimate {
+        self.estimate_for(&origin.ascii_serialization())
+    }
+
+    fn estimate_for(&self, origin: &str) -> StorageEstimate {
+        let local_usage = self
+            .local_data
+            .get(origin)
+            .map_or(0, |&(total, _)| total as u64);
+        let indexeddb_usage: u64 = self
+            .indexeddb_usage()
+            .into_iter()
+            .filter(|usage| usage.origin.ascii_serialization() == origin)
+            .map(|usage| usage.usage)
+            .sum();
+        StorageEstimate {
+            usage: local_usage + self.cache_storage.usage(origin) + indexeddb_usage,
+            quota: origin_quota(),
+        }
+    }
+
+    /// Runs [`Self::evict_under_pressure`], unless the storage usage was checked recently,
+    /// as it measures the databases of every origin on disk.
+    fn maybe_evict_under_pressure(&mut self) {
+        if self
+            .last_pressure_check
+            .is_some_and(|last_check| last_check.elapsed() < STORAGE_PRESSURE_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.evict_under_pressure();
+    }
+
+    /// <https://storage.spec.whatwg.org/#storage-pressure>
+    ///
+    /// When all origins together use more than the total quota, the data of the least
+    /// recently modified origins whose storage is not persistent is cleared until they
+    /// no longer do.
+    fn evict_under_pressure(&mut self) {
+        self.last_pressure_check = Some(Instant::now());
+
+        let mut usage: Vec<_> = self.origin_usage().into_iter().collect();
+        let mut total_usage: u64 = usage.iter().map(|(_, (bytes, _))| bytes).sum();
+        if total_usage <= total_quota() {
+            return;
+        }
+
+        usage.retain(|(origin, _)| !self.is_persistent(origin));
+        usage.sort_by_key(|(_, (_, last_modified))| *last_modified);
+        for (origin, (bytes, _)) in usage {
+            if total_usage <= total_quota() {
+                break;
+            }
+            warn!("Evicting the storage of {origin} as the storage is under pressure");
+            self.clear_origin(&origin);
+            total_usage = total_usage.saturating_sub(bytes);
+        }
+        self.save_state();
+    }
+
+    /// Clears the data of the origins, or of the given origin, which was modified since
+    /// the given time.
+    fn clear_site_data(&mut self, origin: Option<ImmutableOrigin>, since: Option<SystemTime>) {
+        let origin = origin.map(|origin| origin.ascii_serialization());
+        let origins_to_clear: Vec<String> = self
+            .origin_usage()
+            .into_iter()
+            .filter(|(key, (_, last_modified))| {
+                origin
+                    .as_ref()
+                    .is_none_or(|origin| StorageKey::is_for_origin(key, origin)) &&
+                    since.is_none_or(|since| {
+                        last_modified.is_some_and(|last_modified| last_modified >= since)
+                    })
+            })
+            .map(|(key, _)| key)
+            .collect();
+        for origin in origins_to_clear {
+            self.clear_origin(&origin);
+        }
+    }
+
+    /// Clears the local storage, session storage, Cache Storage and IndexedDB databases of an
+    /// origin.
+    fn clear_origin(&mut self, origin: &str) {
+        self.local_data.remove(origin);
+        if self.cache_storage.clear_origin(origin) {
+            self.save_cache_storage();
+        }
+        for origin_map in self.session_data.values_mut() {
+            origin_map.remove(origin);
+        }
+        self.origins.remove(origin);
+
+        let Ok(url) = ServoUrl::parse(origin) else {
+            return;
+        };
+        let Ok((sender, receiver)) = ipc::channel() else {
+            return;
+        };
+        if self
+            .idb_thread
+            .send(IndexedDBThreadMsg::Sync(SyncOperation::DeleteOrigin(
+                sender,
+                url.origin(),
+            )))
+            .is_ok()
+        {
+            let _ = receiver.recv();
         }
     }
 
@@ -197,9 +614,9 @@ impl StorageManager {
         sender: IpcSender<usize>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data(storage_type, webview_id, &origin);
         sender
             .send(data.map_or(0, |(_, entry)| entry.len()))
@@ -211,10 +628,10 @@ impl StorageManager {
         sender: IpcSender<Option<String>>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
         index: u32,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data(storage_type, webview_id, &origin);
         let key = data
             .and_then(|(_, entry)| entry.keys().nth(index as usize))
@@ -227,9 +644,9 @@ impl StorageManager {
         sender: IpcSender<Vec<String>>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data(storage_type, webview_id, &origin);
         let keys = data.map_or(vec![], |(_, entry)| entry.keys().cloned().collect());
 
@@ -245,11 +662,11 @@ impl StorageManager {
         sender: IpcSender<Result<(bool, Option<String>), ()>>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
         name: String,
         value: String,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
 
         let (this_storage_size, other_storage_size) = {
             let local_data = self.select_data(StorageType::Local, webview_id, &origin);
@@ -294,10 +711,10 @@ impl StorageManager {
         sender: IpcSender<Option<String>>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
         name: String,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data(storage_type, webview_id, &origin);
         sender
             .send(data.and_then(|(_, entry)| entry.get(&name)).cloned())
@@ -310,10 +727,10 @@ impl StorageManager {
         sender: IpcSender<Option<String>>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
         name: String,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data_mut(storage_type, webview_id, &origin);
         let old_value = data.and_then(|&mut (ref mut total, ref mut entry)| {
             entry.remove(&name).inspect(|old| {
@@ -328,9 +745,9 @@ impl StorageManager {
         sender: IpcSender<bool>,
         storage_type: StorageType,
         webview_id: WebViewId,
-        url: ServoUrl,
+        storage_key: StorageKey,
     ) {
-        let origin = self.origin_as_string(url);
+        let origin = storage_key.serialize();
         let data = self.select_data_mut(storage_type, webview_id, &origin);
         sender
             .send(data.is_some_and(|&mut (ref mut total, ref mut entry)| {
@@ -354,8 +771,4 @@ impl StorageManager {
         self.session_data
             .insert(dest_webview_id, dest_origin_entries);
     }
-
-    fn origin_as_string(&self, url: ServoUrl) -> String {
-        url.origin().ascii_serialization()
-    }
 }
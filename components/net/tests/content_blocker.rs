@@ -0,0 +1,282 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use base::id::TEST_WEBVIEW_ID;
+use content_security_policy::Destination;
+use net::content_blocker::ContentBlocker;
+use net_traits::request::{Referrer, RequestBuilder};
+use servo_url::ServoUrl;
+
+fn content_blocker(filter_list: &str) -> ContentBlocker {
+    ContentBlocker::new(&[filter_list.to_owned()])
+}
+
+fn is_blocked(
+    content_blocker: &ContentBlocker,
+    url: &str,
+    source: &str,
+    destination: Destination,
+) -> bool {
+    let url = ServoUrl::parse(url).unwrap();
+    let source = ServoUrl::parse(source).unwrap();
+    let request = RequestBuilder::new(Some(TEST_WEBVIEW_ID), url, Referrer::NoReferrer)
+        .origin(source.origin())
+        .destination(destination)
+        .build();
+    content_blocker.should_block_request(&request)
+}
+
+fn is_script_blocked(content_blocker: &ContentBlocker, url: &str) -> bool {
+    is_blocked(
+        content_blocker,
+        url,
+        "https://page.test/",
+        Destination::Script,
+    )
+}
+
+#[test]
+fn test_content_blocker_skips_comments_and_unsupported_filters() {
+    let content_blocker = content_blocker(
+        "[Adblock Plus 2.0]\n\
+         ! ||comment.test^\n\
+         /banner[0-9]+/\n\
+         ||popup.test^$popup\n\
+         ##.ad\n",
+    );
+    assert!(!content_blocker.is_empty());
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://comment.test/a.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/banner1.js"
+    ));
+    assert!(!is_script_blocked(&content_blocker, "https://popup.test/"));
+
+    assert!(self::content_blocker("! Only a comment\n\n").is_empty());
+}
+
+#[test]
+fn test_content_blocker_domain_anchor() {
+    let content_blocker = content_blocker("||ads.example.com^");
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://ads.example.com/ad.js"
+    ));
+    assert!(is_script_blocked(
+        &content_blocker,
+        "http://cdn.ads.example.com/ad.js"
+    ));
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://ads.example.com:8443/ad.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://notads.example.com/ad.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://ads.example.community/ad.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/?ref=ads.example.com"
+    ));
+}
+
+#[test]
+fn test_content_blocker_start_and_end_anchors() {
+    let content_blocker = content_blocker("|https://track.\n.gif|\n/pixel/*/count");
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://track.test/t.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/?u=https://track.test/"
+    ));
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://page.test/a.gif"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/a.gif?b"
+    ));
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://page.test/pixel/42/count"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/pixel/count"
+    ));
+}
+
+#[test]
+fn test_content_blocker_match_case() {
+    let content_blocker = content_blocker("/Banner.js$match-case\n/popup.js");
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://page.test/Banner.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://page.test/banner.js"
+    ));
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://page.test/PopUp.js"
+    ));
+}
+
+#[test]
+fn test_content_blocker_exception_filters() {
+    let content_blocker = content_blocker("||ads.test^\n@@||ads.test/allowed/");
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://ads.test/ad.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "https://ads.test/allowed/ad.js"
+    ));
+}
+
+#[test]
+fn test_content_blocker_third_party_option() {
+    let content_blocker = content_blocker("||tracker.test^$third-party");
+    assert!(is_blocked(
+        &content_blocker,
+        "https://tracker.test/t.js",
+        "https://page.test/",
+        Destination::Script,
+    ));
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://tracker.test/t.js",
+        "https://www.tracker.test/",
+        Destination::Script,
+    ));
+}
+
+#[test]
+fn test_content_blocker_type_options() {
+    let content_blocker = content_blocker("||scripts.test^$script\n||media.test^$~image");
+    assert!(is_blocked(
+        &content_blocker,
+        "https://scripts.test/a.js",
+        "https://page.test/",
+        Destination::Script,
+    ));
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://scripts.test/a.png",
+        "https://page.test/",
+        Destination::Image,
+    ));
+    assert!(is_blocked(
+        &content_blocker,
+        "https://media.test/a.mp4",
+        "https://page.test/",
+        Destination::Video,
+    ));
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://media.test/a.png",
+        "https://page.test/",
+        Destination::Image,
+    ));
+}
+
+#[test]
+fn test_content_blocker_documents_need_document_option() {
+    let content_blocker = content_blocker("||ads.test^\n||tracker.test^$document");
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://ads.test/",
+        "https://page.test/",
+        Destination::Document,
+    ));
+    assert!(is_blocked(
+        &content_blocker,
+        "https://tracker.test/",
+        "https://page.test/",
+        Destination::Document,
+    ));
+}
+
+#[test]
+fn test_content_blocker_domain_option() {
+    let content_blocker = content_blocker("/ad.js$domain=news.test|~sports.news.test");
+    assert!(is_blocked(
+        &content_blocker,
+        "https://cdn.test/ad.js",
+        "https://www.news.test/",
+        Destination::Script,
+    ));
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://cdn.test/ad.js",
+        "https://sports.news.test/",
+        Destination::Script,
+    ));
+    assert!(!is_blocked(
+        &content_blocker,
+        "https://cdn.test/ad.js",
+        "https://page.test/",
+        Destination::Script,
+    ));
+}
+
+#[test]
+fn test_content_blocker_only_blocks_network_schemes() {
+    let content_blocker = content_blocker("ad.js");
+    assert!(is_script_blocked(
+        &content_blocker,
+        "https://page.test/ad.js"
+    ));
+    assert!(!is_script_blocked(
+        &content_blocker,
+        "data:text/javascript,ad.js"
+    ));
+}
+
+#[test]
+fn test_content_blocker_element_hiding_stylesheet() {
+    let content_blocker = content_blocker(
+        "##.ad\n\
+         news.test##.banner\n\
+         ~sports.news.test##.sidebar-ad\n\
+         shop.test#@#.ad\n",
+    );
+    let stylesheet = |url: &str| {
+        content_blocker
+            .element_hiding_stylesheet(&ServoUrl::parse(url).unwrap())
+            .unwrap_or_default()
+    };
+
+    let news = stylesheet("https://www.news.test/");
+    assert!(news.contains(".ad { display: none !important; }"));
+    assert!(news.contains(".banner { display: none !important; }"));
+    assert!(news.contains(".sidebar-ad { display: none !important; }"));
+
+    let sports = stylesheet("https://sports.news.test/");
+    assert!(sports.contains(".banner"));
+    assert!(!sports.contains(".sidebar-ad"));
+
+    let shop = stylesheet("https://shop.test/");
+    assert!(!shop.contains(".ad {"));
+    assert!(!shop.contains(".banner"));
+    assert!(shop.contains(".sidebar-ad"));
+
+    assert!(
+        self::content_blocker("||ads.test^")
+            .element_hiding_stylesheet(&ServoUrl::parse("https://page.test/").unwrap())
+            .is_none()
+    );
+}
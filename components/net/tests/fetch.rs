@@ -142,6 +142,29 @@ fn test_fetch_aboutblank() {
     assert_eq!(*resp_body, ResponseBody::Done(vec![]));
 }
 
+#[test]
+fn test_fetch_internal_page_only_for_navigations() {
+    let url = ServoUrl::parse("servo:version").unwrap();
+    let request = RequestBuilder::new(Some(TEST_WEBVIEW_ID), url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .build();
+    assert!(fetch(request, None).is_network_error());
+
+    let request = RequestBuilder::new(Some(TEST_WEBVIEW_ID), url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .mode(RequestMode::Navigate)
+        .destination(Destination::Document)
+        .build();
+    let fetch_response = fetch(request, None);
+    assert!(!fetch_response.is_network_error());
+    match *fetch_response.actual_response().body.lock().unwrap() {
+        ResponseBody::Done(ref body) => {
+            assert!(String::from_utf8_lossy(body).contains("<h1>Servo "))
+        },
+        _ => panic!("Expected the generated page"),
+    }
+}
+
 #[test]
 fn test_fetch_blob() {
     use net_traits::blob_url_store::BlobBuf;
@@ -732,6 +755,7 @@ fn test_fetch_with_hsts() {
         filemanager: Arc::new(Mutex::new(FileManager::new(
             embedder_proxy.clone(),
             Weak::new(),
+            None,
         ))),
         file_token: FileTokenCheck::NotRequired,
         request_interceptor: Arc::new(Mutex::new(RequestInterceptor::new(embedder_proxy))),
@@ -792,6 +816,7 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         filemanager: Arc::new(Mutex::new(FileManager::new(
             embedder_proxy.clone(),
             Weak::new(),
+            None,
         ))),
         file_token: FileTokenCheck::NotRequired,
         request_interceptor: Arc::new(Mutex::new(RequestInterceptor::new(embedder_proxy))),
@@ -858,6 +883,7 @@ fn test_fetch_self_signed() {
         filemanager: Arc::new(Mutex::new(FileManager::new(
             embedder_proxy.clone(),
             Weak::new(),
+            None,
         ))),
         file_token: FileTokenCheck::NotRequired,
         request_interceptor: Arc::new(Mutex::new(RequestInterceptor::new(embedder_proxy))),
@@ -1414,6 +1440,7 @@ fn test_fetch_request_intercepted() {
         filemanager: Arc::new(Mutex::new(FileManager::new(
             embedder_proxy.clone(),
             Weak::new(),
+            None,
         ))),
         file_token: FileTokenCheck::NotRequired,
         request_interceptor: Arc::new(Mutex::new(RequestInterceptor::new(embedder_proxy))),
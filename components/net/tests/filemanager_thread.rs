@@ -28,7 +28,7 @@ fn test_filemanager() {
 
     let pool = CoreResourceThreadPool::new(1, "CoreResourceTestPool".to_string());
     let pool_handle = Arc::new(pool);
-    let filemanager = FileManager::new(create_embedder_proxy(), Arc::downgrade(&pool_handle));
+    let filemanager = FileManager::new(create_embedder_proxy(), Arc::downgrade(&pool_handle), None);
 
     // Try to open a dummy file "components/net/tests/test.jpeg" in tree
     let mut handler = File::open("tests/test.jpeg").expect("test.jpeg is stolen");
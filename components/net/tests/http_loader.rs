@@ -33,11 +33,12 @@ use hyper::body::{Body, Bytes, Incoming};
 use hyper::{Request as HyperRequest, Response as HyperResponse};
 use net::cookie::ServoCookie;
 use net::cookie_storage::CookieStorage;
-use net::fetch::methods::{self};
+use net::fetch::methods::{self, FetchContext};
 use net::http_loader::{determine_requests_referrer, serialize_origin};
 use net::resource_thread::AuthCacheEntry;
 use net::test::{DECODER_BUFFER_SIZE, replace_host_table};
 use net_traits::http_status::HttpStatus;
+use net_traits::pub_domains::Site;
 use net_traits::request::{
     CredentialsMode, Destination, Referrer, Request, RequestBuilder, RequestMode,
     create_request_body_with_content,
@@ -684,6 +685,108 @@ fn test_load_sets_cookies_in_the_resource_manager_when_it_get_set_cookie_header_
     );
 }
 
+/// Fetch a URL that sets a cookie from a document nested in a top-level document of
+/// `https://top.test`, after granting storage access to the site of the URL below the
+/// given top-level site, if any.
+fn set_cookie_in_third_party_context(
+    storage_access_top_level_site: Option<&str>,
+) -> (ServoUrl, FetchContext) {
+    let handler =
+        move |_: HyperRequest<Incoming>,
+              response: &mut HyperResponse<BoxBody<Bytes, hyper::Error>>| {
+            response.headers_mut().insert(
+                header::SET_COOKIE,
+                HeaderValue::from_static("mozillaIs=theBest"),
+            );
+            *response.body_mut() = make_body(b"Yay!".to_vec());
+        };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    let top_level_site = Site::for_url(&ServoUrl::parse("https://top.test/").unwrap()).unwrap();
+    context
+        .state
+        .top_level_sites
+        .write()
+        .unwrap()
+        .insert(TEST_PIPELINE_ID, top_level_site);
+    if let Some(storage_access_top_level_site) = storage_access_top_level_site {
+        let storage_access_top_level_site =
+            Site::for_url(&ServoUrl::parse(storage_access_top_level_site).unwrap()).unwrap();
+        context
+            .state
+            .storage_access_grants
+            .write()
+            .unwrap()
+            .insert((storage_access_top_level_site, Site::for_url(&url).unwrap()));
+    }
+
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Script)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .credentials_mode(CredentialsMode::Include)
+        .build();
+
+    let response = fetch_with_context(request, &mut context);
+
+    let _ = server.close();
+
+    assert!(
+        response
+            .internal_response
+            .unwrap()
+            .status
+            .code()
+            .is_success()
+    );
+    (url, context)
+}
+
+fn partitioned_cookie_for_url(context: &FetchContext, url: &ServoUrl) -> Option<String> {
+    let top_level_site = Site::for_url(&ServoUrl::parse("https://top.test/").unwrap()).unwrap();
+    context
+        .state
+        .partitioned_cookie_jars
+        .write()
+        .unwrap()
+        .get_mut(&top_level_site)?
+        .cookies_for_url(url, CookieSource::HTTP)
+}
+
+#[test]
+fn test_third_party_cookies_are_partitioned_by_top_level_site() {
+    let (url, context) = set_cookie_in_third_party_context(None);
+    assert_cookie_for_domain(&context.state.cookie_jar, url.as_str(), None);
+    assert_eq!(
+        partitioned_cookie_for_url(&context, &url).as_deref(),
+        Some("mozillaIs=theBest")
+    );
+}
+
+#[test]
+fn test_storage_access_grant_unpartitions_cookies_below_top_level_site() {
+    let (url, context) = set_cookie_in_third_party_context(Some("https://top.test/"));
+    assert_cookie_for_domain(
+        &context.state.cookie_jar,
+        url.as_str(),
+        Some("mozillaIs=theBest"),
+    );
+    assert_eq!(partitioned_cookie_for_url(&context, &url), None);
+}
+
+#[test]
+fn test_storage_access_grant_does_not_apply_below_other_top_level_sites() {
+    let (url, context) = set_cookie_in_third_party_context(Some("https://other.test/"));
+    assert_cookie_for_domain(&context.state.cookie_jar, url.as_str(), None);
+    assert_eq!(
+        partitioned_cookie_for_url(&context, &url).as_deref(),
+        Some("mozillaIs=theBest")
+    );
+}
+
 #[test]
 fn test_load_sets_requests_cookies_header_for_url_by_getting_cookies_from_the_resource_manager() {
     let handler =
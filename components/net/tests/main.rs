@@ -5,6 +5,7 @@
 #![cfg(test)]
 #![allow(dead_code)]
 
+mod content_blocker;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
@@ -44,6 +45,7 @@ use net::filemanager_thread::FileManager;
 use net::protocols::ProtocolRegistry;
 use net::request_interceptor::RequestInterceptor;
 use net::resource_thread::CoreResourceThreadPool;
+use net::speculative_connections::SpeculativeConnections;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
 use net_traits::request::Request;
@@ -146,6 +148,7 @@ fn create_http_state(fc: Option<EmbedderProxy>) -> HttpState {
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
     let override_manager = net::connector::CertificateErrorOverrideManager::new();
+    let speculative_connections = SpeculativeConnections::default();
     HttpState {
         hsts_list: RwLock::new(net::hsts::HstsList::default()),
         cookie_jar: RwLock::new(net::cookie_storage::CookieStorage::new(150)),
@@ -153,13 +156,22 @@ fn create_http_state(fc: Option<EmbedderProxy>) -> HttpState {
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(net::http_cache::HttpCache::default()),
         http_cache_state: Mutex::new(HashMap::new()),
-        client: create_http_client(create_tls_config(
-            net::connector::CACertificates::Default,
-            false, /* ignore_certificate_errors */
-            override_manager.clone(),
-        )),
+        client: create_http_client(
+            create_tls_config(
+                net::connector::CACertificates::Default,
+                false, /* ignore_certificate_errors */
+                override_manager.clone(),
+            ),
+            speculative_connections.clone(),
+        ),
         override_manager,
+        speculative_connections,
         embedder_proxy: Mutex::new(fc.unwrap_or_else(|| create_embedder_proxy())),
+        partitioned_cookie_jars: Default::default(),
+        top_level_sites: Default::default(),
+        storage_access_grants: Default::default(),
+        content_blocker: Default::default(),
+        service_worker_mediators: Default::default(),
     }
 }
 
@@ -177,6 +189,7 @@ fn new_fetch_context(
         filemanager: Arc::new(Mutex::new(FileManager::new(
             sender.clone(),
             pool_handle.unwrap_or_else(|| Weak::new()),
+            None,
         ))),
         file_token: FileTokenCheck::NotRequired,
         request_interceptor: Arc::new(Mutex::new(RequestInterceptor::new(sender))),
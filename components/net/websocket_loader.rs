@@ -47,7 +47,7 @@ use crate::fetch::methods::{
     should_request_be_blocked_due_to_a_bad_port,
 };
 use crate::hosts::replace_host;
-use crate::http_loader::HttpState;
+use crate::http_loader::{CookiePartition, HttpState};
 /// Create a tungstenite Request object for the initial HTTP request.
 /// This request contains `Origin`, `Sec-WebSocket-Protocol`, `Authorization`,
 /// and `Cookie` headers as appropriate.
@@ -58,6 +58,7 @@ fn create_request(
     origin: &str,
     protocols: &[String],
     http_state: &HttpState,
+    cookie_partition: &CookiePartition,
 ) -> WebSocketResult<Request> {
     let mut builder = Request::get(resource_url.as_str());
     let headers = builder.headers_mut().unwrap();
@@ -83,9 +84,13 @@ fn create_request(
         headers.insert("Sec-WebSocket-Protocol", HeaderValue::from_str(&protocols)?);
     }
 
-    let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-    cookie_jar.remove_expired_cookies_for_url(resource_url);
-    if let Some(cookie_list) = cookie_jar.cookies_for_url(resource_url, CookieSource::HTTP) {
+    let cookie_list = http_state
+        .with_cookie_jar(cookie_partition.clone(), |cookie_jar| {
+            cookie_jar.remove_expired_cookies_for_url(resource_url);
+            cookie_jar.cookies_for_url(resource_url, CookieSource::HTTP)
+        })
+        .flatten();
+    if let Some(cookie_list) = cookie_list {
         headers.insert("Cookie", HeaderValue::from_str(&cookie_list)?);
     }
 
@@ -114,6 +119,7 @@ fn process_ws_response(
     response: &Response,
     resource_url: &ServoUrl,
     protocols: &[String],
+    cookie_partition: CookiePartition,
 ) -> Result<Option<String>, Error> {
     trace!("processing websocket http response for {}", resource_url);
     let mut protocol_in_use = None;
@@ -127,17 +133,18 @@ fn process_ws_response(
         protocol_in_use = Some(protocol_name.to_string());
     }
 
-    let mut jar = http_state.cookie_jar.write().unwrap();
-    // TODO(eijebong): Replace thise once typed headers settled on a cookie impl
-    for cookie in response.headers().get_all(header::SET_COOKIE) {
-        if let Ok(s) = std::str::from_utf8(cookie.as_bytes()) {
-            if let Some(cookie) =
-                ServoCookie::from_cookie_string(s.into(), resource_url, CookieSource::HTTP)
-            {
-                jar.push(cookie, resource_url, CookieSource::HTTP);
+    http_state.with_cookie_jar(cookie_partition, |jar| {
+        // TODO(eijebong): Replace thise once typed headers settled on a cookie impl
+        for cookie in response.headers().get_all(header::SET_COOKIE) {
+            if let Ok(s) = std::str::from_utf8(cookie.as_bytes()) {
+                if let Some(cookie) =
+                    ServoCookie::from_cookie_string(s.into(), resource_url, CookieSource::HTTP)
+                {
+                    jar.push(cookie, resource_url, CookieSource::HTTP);
+                }
             }
         }
-    }
+    });
 
     http_state
         .hsts_list
@@ -300,6 +307,7 @@ async fn start_websocket(
     client: Request,
     tls_config: TlsConfig,
     dom_action_receiver: IpcReceiver<WebSocketDomAction>,
+    cookie_partition: CookiePartition,
 ) -> Result<(), Error> {
     trace!("starting WS connection to {}", url);
 
@@ -331,7 +339,8 @@ async fn start_websocket(
     let (stream, response) =
         client_async_tls_with_connector_and_config(client, socket, Some(connector), None).await?;
 
-    let protocol_in_use = process_ws_response(&http_state, &response, &url, &protocols)?;
+    let protocol_in_use =
+        process_ws_response(&http_state, &response, &url, &protocols, cookie_partition)?;
 
     if !initiated_close.load(Ordering::SeqCst) {
         if resource_event_sender
@@ -405,11 +414,13 @@ fn connect(
         }
     }
 
+    let cookie_partition = http_state.cookie_partition(request.pipeline_id, &req_url);
     let client = match create_request(
         &req_url,
         &req_origin.ascii_serialization(),
         &protocols,
         &http_state,
+        &cookie_partition,
     ) {
         Ok(c) => c,
         Err(e) => return Err(e.to_string()),
@@ -432,6 +443,7 @@ fn connect(
             client,
             tls_config,
             dom_action_receiver,
+            cookie_partition,
         )
         .map_err(move |e| {
             warn!("Failed to establish a WebSocket connection: {:?}", e);
@@ -16,24 +16,32 @@ use jstraceable_derive::JSTraceable;
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
 pub(crate) struct AnimationTimeline {
     current_value: f64,
+    /// The value of the timeline when it was created, which is the zero time of the
+    /// timeline as exposed to script.
+    origin: f64,
 }
 
 impl AnimationTimeline {
     /// Creates a new "normal" timeline, i.e., a "Current" mode timer.
     #[inline]
     pub(crate) fn new() -> Self {
+        let current_value = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
         Self {
-            current_value: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs_f64(),
+            current_value,
+            origin: current_value,
         }
     }
 
     /// Creates a new "test mode" timeline, with initial time 0.
     #[inline]
     pub(crate) fn new_for_testing() -> Self {
-        Self { current_value: 0. }
+        Self {
+            current_value: 0.,
+            origin: 0.,
+        }
     }
 
     /// Returns the current value of the timeline in seconds.
@@ -41,6 +49,11 @@ impl AnimationTimeline {
         self.current_value
     }
 
+    /// Returns the value of the timeline when it was created, in seconds.
+    pub(crate) fn origin(&self) -> f64 {
+        self.origin
+    }
+
     /// Updates the value of the `AnimationTimeline` to the current clock time.
     pub(crate) fn update(&mut self) {
         self.current_value = SystemTime::now()
@@ -20,6 +20,9 @@ use style::animation::{
 use style::dom::OpaqueNode;
 use style::selector_parser::PseudoElement;
 
+use crate::dom::animation::{
+    Animation as DOMAnimation, AnimationKey, AnimationSource, AnimationTiming,
+};
 use crate::dom::animationevent::AnimationEvent;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AnimationEventBinding::AnimationEventInit;
@@ -30,6 +33,9 @@ use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::NoTrace;
+use crate::dom::cssanimation::CSSAnimation;
+use crate::dom::csstransition::CSSTransition;
+use crate::dom::element::Element;
 use crate::dom::event::Event;
 use crate::dom::node::{Node, NodeDamage, NodeTraits, from_untrusted_node_address};
 use crate::dom::transitionevent::TransitionEvent;
@@ -57,6 +63,11 @@ pub(crate) struct Animations {
     /// This is used to prevent marking animations dirty when the timeline
     /// has not changed.
     timeline_value_at_last_dirty: Cell<f64>,
+
+    /// The objects exposed to script for the animations and transitions of this document,
+    /// so that script gets the same object every time that it asks for one of them.
+    #[ignore_malloc_size_of = "AnimationKey contains types defined in style"]
+    dom_animations: DomRefCell<FxHashMap<NoTrace<AnimationKey>, Dom<DOMAnimation>>>,
 }
 
 impl Animations {
@@ -67,6 +78,7 @@ impl Animations {
             rooted_nodes: Default::default(),
             pending_events: Default::default(),
             timeline_value_at_last_dirty: Cell::new(0.0),
+            dom_animations: Default::default(),
         }
     }
 
@@ -74,6 +86,7 @@ impl Animations {
         self.sets.sets.write().clear();
         self.rooted_nodes.borrow_mut().clear();
         self.pending_events.borrow_mut().clear();
+        self.dom_animations.borrow_mut().clear();
     }
 
     pub(crate) fn animations_present(&self) -> bool {
@@ -373,6 +386,178 @@ impl Animations {
         self.rooted_nodes.borrow_mut().retain(|node, _| {
             nodes.contains(&node.0) || pending_events.iter().any(|event| event.node == node.0)
         });
+        self.dom_animations
+            .borrow_mut()
+            .retain(|key, _| sets.contains_key(&key.0.set_key));
+    }
+
+    /// The timing of the animation or transition identified by `key` at the time `now`,
+    /// or `None` if it does not exist anymore.
+    pub(crate) fn timing(&self, key: &AnimationKey, now: f64) -> Option<AnimationTiming> {
+        let sets = self.sets.sets.read();
+        let set = sets.get(&key.set_key)?;
+        match &key.source {
+            AnimationSource::CSSAnimation(name) => {
+                let animation = set
+                    .animations
+                    .iter()
+                    .find(|animation| animation.name == *name)?;
+                let iteration_index = match animation.iteration_state {
+                    KeyframesIterationState::Finite(current, _) |
+                    KeyframesIterationState::Infinite(current) => current,
+                };
+                // `started_at` is the time at which the current iteration started.
+                let current_time = match animation.state {
+                    AnimationState::Paused(progress) => {
+                        animation.delay + (iteration_index + progress) * animation.duration
+                    },
+                    _ => {
+                        animation.delay +
+                            iteration_index * animation.duration +
+                            (now - animation.started_at)
+                    },
+                };
+                Some(AnimationTiming {
+                    state: animation.state.clone(),
+                    start_time: animation.started_at -
+                        animation.delay -
+                        iteration_index * animation.duration,
+                    current_time,
+                })
+            },
+            AnimationSource::CSSTransition(property) => {
+                let transition = set.transitions.iter().rev().find(|transition| {
+                    transition.property_animation.property_id().name() == property
+                })?;
+                Some(AnimationTiming {
+                    state: transition.state.clone(),
+                    start_time: transition.start_time - transition.delay,
+                    current_time: transition.delay + (now - transition.start_time),
+                })
+            },
+        }
+    }
+
+    /// Cancel the animation or transition identified by `key`. Events are sent for it
+    /// after the next restyle, which also stops its effect on style.
+    pub(crate) fn cancel(&self, key: &AnimationKey) {
+        {
+            let mut sets = self.sets.sets.write();
+            let Some(set) = sets.get_mut(&key.set_key) else {
+                return;
+            };
+            match &key.source {
+                AnimationSource::CSSAnimation(name) => {
+                    for animation in set
+                        .animations
+                        .iter_mut()
+                        .filter(|animation| animation.name == *name)
+                    {
+                        animation.state = AnimationState::Canceled;
+                    }
+                },
+                AnimationSource::CSSTransition(property) => {
+                    for transition in set.transitions.iter_mut().filter(|transition| {
+                        transition.property_animation.property_id().name() == property
+                    }) {
+                        transition.state = AnimationState::Canceled;
+                    }
+                },
+            }
+        }
+
+        if let Some(node) = self.rooted_nodes.borrow().get(&NoTrace(key.set_key.node)) {
+            node.dirty(NodeDamage::Style);
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animatable-getanimations>
+    ///
+    /// Returns the objects exposed to script for the relevant animations and transitions
+    /// of `elements`, which must be in tree order, in composite order: transitions come
+    /// before animations and each of them are sorted by the tree order of their elements.
+    pub(crate) fn get_animations(
+        &self,
+        elements: impl Iterator<Item = DomRoot<Element>>,
+        window: &Window,
+        can_gc: CanGc,
+    ) -> Vec<DomRoot<DOMAnimation>> {
+        let is_relevant = |state: &AnimationState| {
+            matches!(
+                state,
+                AnimationState::Pending | AnimationState::Running | AnimationState::Paused(_)
+            )
+        };
+
+        let mut transition_keys = Vec::new();
+        let mut animation_keys = Vec::new();
+        {
+            let sets = self.sets.sets.read();
+            for element in elements {
+                let node = element.upcast::<Node>().to_opaque();
+                for set_key in [
+                    AnimationSetKey::new_for_non_pseudo(node),
+                    AnimationSetKey::new_for_pseudo(node, PseudoElement::Before),
+                    AnimationSetKey::new_for_pseudo(node, PseudoElement::After),
+                ] {
+                    let Some(set) = sets.get(&set_key) else {
+                        continue;
+                    };
+                    transition_keys.extend(
+                        set.transitions
+                            .iter()
+                            .filter(|transition| is_relevant(&transition.state))
+                            .map(|transition| AnimationKey {
+                                set_key: set_key.clone(),
+                                source: AnimationSource::CSSTransition(
+                                    transition.property_animation.property_id().name().into(),
+                                ),
+                            }),
+                    );
+                    animation_keys.extend(
+                        set.animations
+                            .iter()
+                            .filter(|animation| is_relevant(&animation.state))
+                            .map(|animation| AnimationKey {
+                                set_key: set_key.clone(),
+                                source: AnimationSource::CSSAnimation(animation.name.clone()),
+                            }),
+                    );
+                }
+            }
+        }
+
+        transition_keys
+            .into_iter()
+            .chain(animation_keys)
+            .map(|key| self.dom_animation(key, window, can_gc))
+            .collect()
+    }
+
+    /// The object exposed to script for the animation or transition identified by `key`,
+    /// creating it if script never asked for it before.
+    fn dom_animation(
+        &self,
+        key: AnimationKey,
+        window: &Window,
+        can_gc: CanGc,
+    ) -> DomRoot<DOMAnimation> {
+        if let Some(animation) = self.dom_animations.borrow().get(&NoTrace(key.clone())) {
+            return DomRoot::from_ref(&**animation);
+        }
+
+        let animation = match key.source {
+            AnimationSource::CSSAnimation(_) => {
+                DomRoot::upcast(CSSAnimation::new(window, key.clone(), can_gc))
+            },
+            AnimationSource::CSSTransition(_) => {
+                DomRoot::upcast(CSSTransition::new(window, key.clone(), can_gc))
+            },
+        };
+        self.dom_animations
+            .borrow_mut()
+            .insert(NoTrace(key), Dom::from_ref(&*animation));
+        animation
     }
 
     fn add_transition_event(
@@ -399,6 +584,18 @@ impl Animations {
         }
         .abs();
 
+        // `start_time` is the time at which the delay phase of the transition ends.
+        let scheduled_event_time = match event_type {
+            TransitionOrAnimationEventType::TransitionRun => {
+                transition.start_time - transition.delay
+            },
+            TransitionOrAnimationEventType::TransitionStart => transition.start_time,
+            TransitionOrAnimationEventType::TransitionEnd => {
+                transition.start_time + transition.property_animation.duration
+            },
+            _ => now,
+        };
+
         self.pending_events
             .borrow_mut()
             .push(TransitionOrAnimationEvent {
@@ -406,6 +603,7 @@ impl Animations {
                 event_type,
                 node: key.node,
                 pseudo_element: key.pseudo_element,
+                scheduled_event_time,
                 property_or_animation_name: transition
                     .property_animation
                     .property_id()
@@ -452,6 +650,16 @@ impl Animations {
         }
         .abs();
 
+        // `started_at` is the time at which the current iteration of the animation started.
+        let scheduled_event_time = match event_type {
+            TransitionOrAnimationEventType::AnimationStart |
+            TransitionOrAnimationEventType::AnimationIteration => animation.started_at,
+            TransitionOrAnimationEventType::AnimationEnd => {
+                animation.started_at + animation.current_iteration_duration()
+            },
+            _ => now,
+        };
+
         self.pending_events
             .borrow_mut()
             .push(TransitionOrAnimationEvent {
@@ -459,6 +667,7 @@ impl Animations {
                 event_type,
                 node: key.node,
                 pseudo_element: key.pseudo_element,
+                scheduled_event_time,
                 property_or_animation_name: animation.name.to_string(),
                 elapsed_time,
             });
@@ -472,7 +681,7 @@ impl Animations {
         //
         // Take all of the events here, in case sending one of these events
         // triggers adding new events by forcing a layout.
-        let events = std::mem::take(&mut *self.pending_events.borrow_mut());
+        let mut events = std::mem::take(&mut *self.pending_events.borrow_mut());
         if events.is_empty() {
             return;
         }
@@ -485,7 +694,18 @@ impl Animations {
         // >    2. Within events with equal scheduled event times, sort by their composite
         // >       order.
         //
-        // TODO: Sorting of animation events isn't done yet.
+        // All events are scheduled at a resolved time. The composite order of transitions
+        // is lower than that of animations and otherwise the events are in the order in
+        // which their animations were processed, which is close enough to tree order.
+        events.sort_by(|a, b| {
+            a.scheduled_event_time
+                .total_cmp(&b.scheduled_event_time)
+                .then_with(|| {
+                    b.event_type
+                        .is_transition_event()
+                        .cmp(&a.event_type.is_transition_event())
+                })
+        });
 
         // 7. Dispatch each of the events in events to dispatch at their corresponding
         // target using the order established in the previous step.
@@ -613,6 +833,9 @@ pub(crate) struct TransitionOrAnimationEvent {
     /// The pseudo element for this transition or animation, if applicable.
     #[no_trace]
     pub(crate) pseudo_element: Option<PseudoElement>,
+    /// The time on the animation timeline at which the event was scheduled to occur,
+    /// which determines the order in which pending events are dispatched.
+    pub(crate) scheduled_event_time: f64,
     /// The name of the property that is transitioning (in the case of a transition)
     /// or the name of the animation (in the case of an animation).
     pub(crate) property_or_animation_name: String,
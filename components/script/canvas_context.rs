@@ -17,8 +17,8 @@ use crate::dom::node::{Node, NodeDamage};
 #[cfg(feature = "webgpu")]
 use crate::dom::types::GPUCanvasContext;
 use crate::dom::types::{
-    CanvasRenderingContext2D, OffscreenCanvas, OffscreenCanvasRenderingContext2D,
-    WebGL2RenderingContext, WebGLRenderingContext,
+    CanvasRenderingContext2D, ImageBitmapRenderingContext, OffscreenCanvas,
+    OffscreenCanvasRenderingContext2D, WebGL2RenderingContext, WebGLRenderingContext,
 };
 
 pub(crate) trait LayoutCanvasRenderingContextHelpers {
@@ -60,14 +60,24 @@ pub(crate) trait CanvasContext {
     }
 
     fn mark_as_dirty(&self) {
-        if let Some(HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(canvas)) = &self.canvas()
-        {
-            canvas.upcast::<Node>().dirty(NodeDamage::Other);
+        match &self.canvas() {
+            Some(HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(canvas)) => {
+                canvas.upcast::<Node>().dirty(NodeDamage::Other);
+            },
+            Some(HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(canvas)) => {
+                canvas.mark_as_dirty();
+            },
+            None => {},
         }
     }
 
     fn update_rendering(&self) {}
 
+    /// The image that presents the bitmap of the context, if it is presented with one.
+    fn image_key(&self) -> Option<ImageKey> {
+        None
+    }
+
     fn onscreen(&self) -> bool {
         let Some(canvas) = self.canvas() else {
             return false;
@@ -113,6 +123,7 @@ impl CanvasHelpers for HTMLCanvasElementOrOffscreenCanvas {
 pub(crate) enum RenderingContext {
     Placeholder(Dom<OffscreenCanvas>),
     Context2d(Dom<CanvasRenderingContext2D>),
+    BitmapRenderer(Dom<ImageBitmapRenderingContext>),
     WebGL(Dom<WebGLRenderingContext>),
     WebGL2(Dom<WebGL2RenderingContext>),
     #[cfg(feature = "webgpu")]
@@ -128,6 +139,7 @@ impl CanvasContext for RenderingContext {
         match self {
             RenderingContext::Placeholder(offscreen_canvas) => offscreen_canvas.context()?.canvas(),
             RenderingContext::Context2d(context) => context.canvas(),
+            RenderingContext::BitmapRenderer(context) => context.canvas(),
             RenderingContext::WebGL(context) => context.canvas(),
             RenderingContext::WebGL2(context) => context.canvas(),
             #[cfg(feature = "webgpu")]
@@ -143,6 +155,7 @@ impl CanvasContext for RenderingContext {
                 }
             },
             RenderingContext::Context2d(context) => context.resize(),
+            RenderingContext::BitmapRenderer(context) => context.resize(),
             RenderingContext::WebGL(context) => context.resize(),
             RenderingContext::WebGL2(context) => context.resize(),
             #[cfg(feature = "webgpu")]
@@ -158,6 +171,7 @@ impl CanvasContext for RenderingContext {
                 }
             },
             RenderingContext::Context2d(context) => context.reset_bitmap(),
+            RenderingContext::BitmapRenderer(context) => context.reset_bitmap(),
             RenderingContext::WebGL(context) => context.reset_bitmap(),
             RenderingContext::WebGL2(context) => context.reset_bitmap(),
             #[cfg(feature = "webgpu")]
@@ -171,6 +185,7 @@ impl CanvasContext for RenderingContext {
                 offscreen_canvas.context()?.get_image_data()
             },
             RenderingContext::Context2d(context) => context.get_image_data(),
+            RenderingContext::BitmapRenderer(context) => context.get_image_data(),
             RenderingContext::WebGL(context) => context.get_image_data(),
             RenderingContext::WebGL2(context) => context.get_image_data(),
             #[cfg(feature = "webgpu")]
@@ -184,6 +199,7 @@ impl CanvasContext for RenderingContext {
                 .context()
                 .is_none_or(|context| context.origin_is_clean()),
             RenderingContext::Context2d(context) => context.origin_is_clean(),
+            RenderingContext::BitmapRenderer(context) => context.origin_is_clean(),
             RenderingContext::WebGL(context) => context.origin_is_clean(),
             RenderingContext::WebGL2(context) => context.origin_is_clean(),
             #[cfg(feature = "webgpu")]
@@ -198,6 +214,7 @@ impl CanvasContext for RenderingContext {
                 .map(|context| context.size())
                 .unwrap_or_default(),
             RenderingContext::Context2d(context) => context.size(),
+            RenderingContext::BitmapRenderer(context) => context.size(),
             RenderingContext::WebGL(context) => context.size(),
             RenderingContext::WebGL2(context) => context.size(),
             #[cfg(feature = "webgpu")]
@@ -213,6 +230,7 @@ impl CanvasContext for RenderingContext {
                 }
             },
             RenderingContext::Context2d(context) => context.mark_as_dirty(),
+            RenderingContext::BitmapRenderer(context) => context.mark_as_dirty(),
             RenderingContext::WebGL(context) => context.mark_as_dirty(),
             RenderingContext::WebGL2(context) => context.mark_as_dirty(),
             #[cfg(feature = "webgpu")]
@@ -228,6 +246,7 @@ impl CanvasContext for RenderingContext {
                 }
             },
             RenderingContext::Context2d(context) => context.update_rendering(),
+            RenderingContext::BitmapRenderer(context) => context.update_rendering(),
             RenderingContext::WebGL(context) => context.update_rendering(),
             RenderingContext::WebGL2(context) => context.update_rendering(),
             #[cfg(feature = "webgpu")]
@@ -241,6 +260,7 @@ impl CanvasContext for RenderingContext {
                 .context()
                 .is_some_and(|context| context.onscreen()),
             RenderingContext::Context2d(context) => context.onscreen(),
+            RenderingContext::BitmapRenderer(context) => context.onscreen(),
             RenderingContext::WebGL(context) => context.onscreen(),
             RenderingContext::WebGL2(context) => context.onscreen(),
             #[cfg(feature = "webgpu")]
@@ -254,8 +274,9 @@ impl CanvasContext for RenderingContext {
 #[derive(Clone, JSTraceable, MallocSizeOf)]
 pub(crate) enum OffscreenRenderingContext {
     Context2d(Dom<OffscreenCanvasRenderingContext2D>),
-    //WebGL(Dom<WebGLRenderingContext>),
-    //WebGL2(Dom<WebGL2RenderingContext>),
+    BitmapRenderer(Dom<ImageBitmapRenderingContext>),
+    WebGL(Dom<WebGLRenderingContext>),
+    WebGL2(Dom<WebGL2RenderingContext>),
     //#[cfg(feature = "webgpu")]
     //WebGPU(Dom<GPUCanvasContext>),
     Detached,
@@ -269,6 +290,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn canvas(&self) -> Option<HTMLCanvasElementOrOffscreenCanvas> {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.canvas(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.canvas(),
+            OffscreenRenderingContext::WebGL(context) => context.canvas(),
+            OffscreenRenderingContext::WebGL2(context) => context.canvas(),
             OffscreenRenderingContext::Detached => None,
         }
     }
@@ -276,6 +300,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn resize(&self) {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.resize(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.resize(),
+            OffscreenRenderingContext::WebGL(context) => context.resize(),
+            OffscreenRenderingContext::WebGL2(context) => context.resize(),
             OffscreenRenderingContext::Detached => {},
         }
     }
@@ -283,6 +310,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn reset_bitmap(&self) {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.reset_bitmap(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.reset_bitmap(),
+            OffscreenRenderingContext::WebGL(context) => context.reset_bitmap(),
+            OffscreenRenderingContext::WebGL2(context) => context.reset_bitmap(),
             OffscreenRenderingContext::Detached => {},
         }
     }
@@ -290,6 +320,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn get_image_data(&self) -> Option<Snapshot> {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.get_image_data(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.get_image_data(),
+            OffscreenRenderingContext::WebGL(context) => context.get_image_data(),
+            OffscreenRenderingContext::WebGL2(context) => context.get_image_data(),
             OffscreenRenderingContext::Detached => None,
         }
     }
@@ -297,6 +330,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn origin_is_clean(&self) -> bool {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.origin_is_clean(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.origin_is_clean(),
+            OffscreenRenderingContext::WebGL(context) => context.origin_is_clean(),
+            OffscreenRenderingContext::WebGL2(context) => context.origin_is_clean(),
             OffscreenRenderingContext::Detached => true,
         }
     }
@@ -304,6 +340,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn size(&self) -> Size2D<u32> {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.size(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.size(),
+            OffscreenRenderingContext::WebGL(context) => context.size(),
+            OffscreenRenderingContext::WebGL2(context) => context.size(),
             OffscreenRenderingContext::Detached => Size2D::default(),
         }
     }
@@ -311,6 +350,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn mark_as_dirty(&self) {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.mark_as_dirty(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.mark_as_dirty(),
+            OffscreenRenderingContext::WebGL(context) => context.mark_as_dirty(),
+            OffscreenRenderingContext::WebGL2(context) => context.mark_as_dirty(),
             OffscreenRenderingContext::Detached => {},
         }
     }
@@ -318,6 +360,9 @@ impl CanvasContext for OffscreenRenderingContext {
     fn update_rendering(&self) {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.update_rendering(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.update_rendering(),
+            OffscreenRenderingContext::WebGL(context) => context.update_rendering(),
+            OffscreenRenderingContext::WebGL2(context) => context.update_rendering(),
             OffscreenRenderingContext::Detached => {},
         }
     }
@@ -325,7 +370,20 @@ impl CanvasContext for OffscreenRenderingContext {
     fn onscreen(&self) -> bool {
         match self {
             OffscreenRenderingContext::Context2d(context) => context.onscreen(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.onscreen(),
+            OffscreenRenderingContext::WebGL(context) => context.onscreen(),
+            OffscreenRenderingContext::WebGL2(context) => context.onscreen(),
             OffscreenRenderingContext::Detached => false,
         }
     }
+
+    fn image_key(&self) -> Option<ImageKey> {
+        match self {
+            OffscreenRenderingContext::Context2d(context) => context.image_key(),
+            OffscreenRenderingContext::BitmapRenderer(context) => context.image_key(),
+            OffscreenRenderingContext::WebGL(context) => context.image_key(),
+            OffscreenRenderingContext::WebGL2(context) => context.image_key(),
+            OffscreenRenderingContext::Detached => None,
+        }
+    }
 }
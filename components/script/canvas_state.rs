@@ -8,13 +8,14 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use canvas_traits::canvas::{
-    Canvas2dMsg, CanvasId, CanvasMsg, CompositionOrBlending, Direction, FillOrStrokeStyle,
-    FillRule, LineCapStyle, LineJoinStyle, LinearGradientStyle, Path, RadialGradientStyle,
-    RepetitionStyle, TextAlign, TextBaseline, TextMetrics as CanvasTextMetrics,
+    Canvas2dMsg, CanvasFilter, CanvasId, CanvasMsg, CompositionOrBlending, ConicGradientStyle,
+    Direction, FillOrStrokeStyle, FillRule, LineCapStyle, LineJoinStyle, LinearGradientStyle, Path,
+    RadialGradientStyle, RepetitionStyle, TextAlign, TextBaseline,
+    TextMetrics as CanvasTextMetrics,
 };
 use constellation_traits::ScriptToConstellationMessage;
 use cssparser::color::clamp_unit_f32;
-use cssparser::{Parser, ParserInput};
+use cssparser::{ParseError, Parser, ParserInput, Token, match_ignore_ascii_case};
 use euclid::default::{Point2D, Rect, Size2D, Transform2D};
 use euclid::vec2;
 use ipc_channel::ipc::{self, IpcSender};
@@ -36,14 +37,17 @@ use style_traits::{CssWriter, ParsingMode};
 use url::Url;
 use webrender_api::ImageKey;
 
-use crate::canvas_context::{OffscreenRenderingContext, RenderingContext};
+use crate::canvas_context::{CanvasContext, OffscreenRenderingContext, RenderingContext};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
     CanvasDirection, CanvasFillRule, CanvasImageSource, CanvasLineCap, CanvasLineJoin,
     CanvasTextAlign, CanvasTextBaseline, ImageDataMethods,
 };
 use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
-use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrCanvasGradientOrCanvasPattern, UnrestrictedDoubleOrDOMPointInit,
+    UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
@@ -109,6 +113,7 @@ pub(crate) struct CanvasContextState {
     shadow_blur: f64,
     #[no_trace]
     shadow_color: AbsoluteColor,
+    filter: DOMString,
     #[no_trace]
     font_style: Option<Font>,
     #[no_trace]
@@ -138,6 +143,7 @@ impl CanvasContextState {
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: AbsoluteColor::TRANSPARENT_BLACK,
+            filter: DOMString::from("none"),
             font_style: None,
             text_align: Default::default(),
             text_baseline: Default::default(),
@@ -651,6 +657,19 @@ impl CanvasState {
                         smoothing_enabled,
                     ));
                 },
+                OffscreenRenderingContext::BitmapRenderer(_) |
+                OffscreenRenderingContext::WebGL(_) |
+                OffscreenRenderingContext::WebGL2(_) => {
+                    let Some(snapshot) = context.get_image_data() else {
+                        return Err(Error::InvalidState);
+                    };
+                    self.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+                        snapshot.as_ipc(),
+                        dest_rect,
+                        source_rect,
+                        smoothing_enabled,
+                    ));
+                },
                 OffscreenRenderingContext::Detached => return Err(Error::InvalidState),
             }
         } else {
@@ -720,6 +739,19 @@ impl CanvasState {
                                 source_rect,
                                 smoothing_enabled,
                             )),
+                        OffscreenRenderingContext::BitmapRenderer(_) |
+                        OffscreenRenderingContext::WebGL(_) |
+                        OffscreenRenderingContext::WebGL2(_) => {
+                            let Some(snapshot) = context.get_image_data() else {
+                                return Err(Error::InvalidState);
+                            };
+                            self.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+                                snapshot.as_ipc(),
+                                dest_rect,
+                                source_rect,
+                                smoothing_enabled,
+                            ));
+                        },
                         OffscreenRenderingContext::Detached => return Err(Error::InvalidState),
                     }
                 },
@@ -1125,6 +1157,22 @@ impl CanvasState {
         ))
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+    pub(crate) fn create_conic_gradient(
+        &self,
+        global: &GlobalScope,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> DomRoot<CanvasGradient> {
+        CanvasGradient::new(
+            global,
+            CanvasGradientStyle::Conic(ConicGradientStyle::new(*start_angle, *x, *y, Vec::new())),
+            can_gc,
+        )
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern>
     pub(crate) fn create_pattern(
         &self,
@@ -1282,12 +1330,7 @@ impl CanvasState {
             )
         }
 
-        let is_rtl = match self.state.borrow().direction {
-            Direction::Ltr => false,
-            Direction::Rtl => true,
-            Direction::Inherit => false, // TODO: resolve direction wrt to canvas element
-        };
-
+        let is_rtl = self.is_rtl(canvas);
         let style = self.state.borrow().fill_style.to_fill_or_stroke_style();
         self.send_canvas_2d_msg(Canvas2dMsg::FillText(
             text.into(),
@@ -1316,7 +1359,8 @@ impl CanvasState {
         }
 
         let (sender, receiver) = ipc::channel::<CanvasTextMetrics>().unwrap();
-        self.send_canvas_2d_msg(Canvas2dMsg::MeasureText(text.into(), sender));
+        let is_rtl = self.is_rtl(canvas);
+        self.send_canvas_2d_msg(Canvas2dMsg::MeasureText(text.into(), is_rtl, sender));
         let metrics = receiver.recv().unwrap();
 
         TextMetrics::new(
@@ -1438,6 +1482,46 @@ impl CanvasState {
         self.state.borrow_mut().direction = direction;
     }
 
+    /// Whether text is drawn right-to-left, resolving the `inherit` direction against
+    /// the directionality of the canvas element. Without a canvas element, as for
+    /// offscreen canvases, `inherit` resolves to left-to-right.
+    /// <https://html.spec.whatwg.org/multipage/#text-preparation-algorithm>
+    fn is_rtl(&self, canvas: Option<&HTMLCanvasElement>) -> bool {
+        match self.state.borrow().direction {
+            Direction::Ltr => false,
+            Direction::Rtl => true,
+            Direction::Inherit => {
+                canvas.is_some_and(|canvas| canvas.upcast::<Element>().directionality() == "rtl")
+            },
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    pub(crate) fn filter(&self) -> DOMString {
+        self.state.borrow().filter.clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    pub(crate) fn set_filter(
+        &self,
+        canvas: Option<&HTMLCanvasElement>,
+        value: DOMString,
+        can_gc: CanGc,
+    ) {
+        // On setting, if the value is "none", the filter is cleared. Otherwise the value
+        // is parsed as a <filter-value-list>; values that fail to parse are ignored.
+        let filter = if &*value == "none" {
+            Vec::new()
+        } else {
+            match parse_filter(canvas, &value, can_gc) {
+                Ok(filter) => filter,
+                Err(()) => return,
+            }
+        };
+        self.state.borrow_mut().filter = value;
+        self.send_canvas_2d_msg(Canvas2dMsg::SetFilter(filter));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-linewidth
     pub(crate) fn line_width(&self) -> f64 {
         self.state.borrow().line_width
@@ -2012,6 +2096,34 @@ impl CanvasState {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    pub(crate) fn round_rect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        // Step 1. If any of x, y, w, or h are infinite or NaN, then return.
+        if ![x, y, width, height].iter().all(|val| val.is_finite()) {
+            return Ok(());
+        }
+        let Some(radii) = normalize_round_rect_radii(radii)? else {
+            return Ok(());
+        };
+
+        let rect = Rect::new(
+            Point2D::new(x as f32, y as f32),
+            Size2D::new(width as f32, height as f32),
+        );
+        self.send_canvas_2d_msg(Canvas2dMsg::RoundRect(
+            rect,
+            radii.map(|radius| radius.cast()),
+        ));
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     pub(crate) fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         if !(cpx.is_finite() && cpy.is_finite() && x.is_finite() && y.is_finite()) {
@@ -2177,6 +2289,257 @@ pub(crate) fn parse_color(
     }
 }
 
+/// Steps 2 to 7 of <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>, which
+/// turn the `radii` argument into the upper left, upper right, lower right and lower left
+/// corner radii. Returns `None` if any of the radii is infinite or NaN.
+pub(crate) fn normalize_round_rect_radii(
+    radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+) -> Fallible<Option<[Point2D<f64>; 4]>> {
+    use UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence as Radii;
+
+    // Step 2. If radii is an unrestricted double or DOMPointInit, then set radii to « radii ».
+    let radii = match radii {
+        Radii::UnrestrictedDouble(radius) => {
+            vec![UnrestrictedDoubleOrDOMPointInit::UnrestrictedDouble(radius)]
+        },
+        Radii::DOMPointInit(radius) => vec![UnrestrictedDoubleOrDOMPointInit::DOMPointInit(radius)],
+        Radii::UnrestrictedDoubleOrDOMPointInitSequence(radii) => radii,
+    };
+
+    // Step 3. If radii is not a list of size one, two, three, or four, then throw a
+    // RangeError.
+    if !(1..=4).contains(&radii.len()) {
+        return Err(Error::Range(
+            "roundRect() expects one to four radii".to_owned(),
+        ));
+    }
+
+    // Step 4. Let normalizedRadii be an empty list.
+    // Step 5. For each radius of radii:
+    let mut normalized_radii = Vec::with_capacity(4);
+    for radius in radii {
+        let (x, y) = match radius {
+            UnrestrictedDoubleOrDOMPointInit::UnrestrictedDouble(radius) => (radius, radius),
+            UnrestrictedDoubleOrDOMPointInit::DOMPointInit(radius) => (radius.x, radius.y),
+        };
+        // Step 5.1.1 / 5.2.1. If any of the radii is infinite or NaN, then return.
+        if !x.is_finite() || !y.is_finite() {
+            return Ok(None);
+        }
+        // Step 5.1.2 / 5.2.2. If any of the radii is negative, then throw a RangeError.
+        if x < 0. || y < 0. {
+            return Err(Error::Range(
+                "roundRect() radii must not be negative".to_owned(),
+            ));
+        }
+        // Step 5.1.3 / 5.2.3. Otherwise, append the radius to normalizedRadii.
+        normalized_radii.push(Point2D::new(x, y));
+    }
+
+    // Step 6. Let upperLeft, upperRight, lowerRight, and lowerLeft be null.
+    // Step 7. Assign the corners according to the size of normalizedRadii.
+    Ok(Some(match normalized_radii[..] {
+        [radius] => [radius; 4],
+        [upper_left_lower_right, upper_right_lower_left] => [
+            upper_left_lower_right,
+            upper_right_lower_left,
+            upper_left_lower_right,
+            upper_right_lower_left,
+        ],
+        [upper_left, upper_right_lower_left, lower_right] => [
+            upper_left,
+            upper_right_lower_left,
+            lower_right,
+            upper_right_lower_left,
+        ],
+        [upper_left, upper_right, lower_right, lower_left] => {
+            [upper_left, upper_right, lower_right, lower_left]
+        },
+        _ => unreachable!("Should have checked the number of radii"),
+    }))
+}
+
+/// Parse a `<filter-value-list>` for the `filter` attribute of 2D contexts. This supports
+/// the filter functions, with absolute lengths only; `url()` references are not supported.
+/// <https://drafts.fxtf.org/filter-effects/#typedef-filter-value-list>
+fn parse_filter(
+    canvas: Option<&HTMLCanvasElement>,
+    string: &str,
+    can_gc: CanGc,
+) -> Result<Vec<CanvasFilter>, ()> {
+    let mut input = ParserInput::new(string);
+    let mut parser = Parser::new(&mut input);
+    let mut filters = Vec::new();
+    while !parser.is_exhausted() {
+        let name = parser.expect_function().map_err(|_| ())?.clone();
+        let filter = parser
+            .parse_nested_block(|parser| parse_filter_function(canvas, &name, parser, can_gc))
+            .map_err(|_| ())?;
+        filters.push(filter);
+    }
+    if filters.is_empty() {
+        return Err(());
+    }
+    Ok(filters)
+}
+
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+fn parse_filter_function<'i>(
+    canvas: Option<&HTMLCanvasElement>,
+    name: &str,
+    parser: &mut Parser<'i, '_>,
+    can_gc: CanGc,
+) -> Result<CanvasFilter, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let filter = match_ignore_ascii_case! { name,
+        "blur" => CanvasFilter::Blur(if parser.is_exhausted() {
+            0.
+        } else {
+            parse_filter_length(parser, false)?
+        }),
+        "brightness" => CanvasFilter::Brightness(parse_filter_amount(parser)?),
+        "contrast" => CanvasFilter::Contrast(parse_filter_amount(parser)?),
+        "drop-shadow" => parse_drop_shadow(canvas, parser, can_gc)?,
+        "grayscale" => CanvasFilter::Grayscale(parse_filter_amount(parser)?),
+        "hue-rotate" => CanvasFilter::HueRotate(if parser.is_exhausted() {
+            0.
+        } else {
+            parse_filter_angle(parser)?
+        }),
+        "invert" => CanvasFilter::Invert(parse_filter_amount(parser)?),
+        "opacity" => CanvasFilter::Opacity(parse_filter_amount(parser)?),
+        "saturate" => CanvasFilter::Saturate(parse_filter_amount(parser)?),
+        "sepia" => CanvasFilter::Sepia(parse_filter_amount(parser)?),
+        _ => return Err(location.new_custom_error(())),
+    };
+    Ok(filter)
+}
+
+/// Parse an optional non-negative `<number>` or `<percentage>`, which defaults to 1.
+fn parse_filter_amount<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i, ()>> {
+    if parser.is_exhausted() {
+        return Ok(1.);
+    }
+    let location = parser.current_source_location();
+    let amount = match *parser.next()? {
+        Token::Number { value, .. } => value,
+        Token::Percentage { unit_value, .. } => unit_value,
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    if amount < 0. {
+        return Err(location.new_custom_error(()));
+    }
+    Ok(amount as f64)
+}
+
+/// Parse an absolute `<length>`, in CSS pixels.
+fn parse_filter_length<'i>(
+    parser: &mut Parser<'i, '_>,
+    allow_negative: bool,
+) -> Result<f64, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let length = match *parser.next()? {
+        Token::Number { value, .. } if value == 0. => 0.,
+        Token::Dimension {
+            value, ref unit, ..
+        } => {
+            let pixels_per_unit = match_ignore_ascii_case! { &**unit,
+                "px" => 1.,
+                "in" => 96.,
+                "cm" => 96. / 2.54,
+                "mm" => 96. / 25.4,
+                "q" => 96. / 101.6,
+                "pt" => 96. / 72.,
+                "pc" => 16.,
+                _ => return Err(location.new_custom_error(())),
+            };
+            value as f64 * pixels_per_unit
+        },
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    if !allow_negative && length < 0. {
+        return Err(location.new_custom_error(()));
+    }
+    Ok(length)
+}
+
+/// Parse an `<angle>`, in degrees.
+fn parse_filter_angle<'i>(parser: &mut Parser<'i, '_>) -> Result<f64, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    match *parser.next()? {
+        Token::Number { value, .. } if value == 0. => Ok(0.),
+        Token::Dimension {
+            value, ref unit, ..
+        } => {
+            let degrees_per_unit = match_ignore_ascii_case! { &**unit,
+                "deg" => 1.,
+                "grad" => 0.9,
+                "rad" => 180. / std::f64::consts::PI,
+                "turn" => 360.,
+                _ => return Err(location.new_custom_error(())),
+            };
+            Ok(value as f64 * degrees_per_unit)
+        },
+        ref token => Err(location.new_unexpected_token_error(token.clone())),
+    }
+}
+
+/// Parse the arguments of `drop-shadow()`: `[ <color>? && <length>{2,3} ]`.
+fn parse_drop_shadow<'i>(
+    canvas: Option<&HTMLCanvasElement>,
+    parser: &mut Parser<'i, '_>,
+    can_gc: CanGc,
+) -> Result<CanvasFilter, ParseError<'i, ()>> {
+    let location = parser.current_source_location();
+    let mut lengths = Vec::new();
+    let mut color = None;
+    while !parser.is_exhausted() {
+        if let Ok(length) = parser.try_parse(|parser| parse_filter_length(parser, true)) {
+            // The lengths must be contiguous.
+            if color.is_some() && !lengths.is_empty() {
+                return Err(location.new_custom_error(()));
+            }
+            lengths.push(length);
+            continue;
+        }
+
+        // A color is a single component value: a keyword, a hash or a function.
+        if color.is_some() {
+            return Err(location.new_custom_error(()));
+        }
+        let start = parser.position();
+        let is_function = matches!(parser.next()?, Token::Function(_));
+        if is_function {
+            parser.parse_nested_block(|parser| -> Result<(), ParseError<'i, ()>> {
+                while parser.next().is_ok() {}
+                Ok(())
+            })?;
+        }
+        color = Some(
+            parse_color(canvas, parser.slice_from(start), can_gc)
+                .map_err(|_| location.new_custom_error(()))?,
+        );
+    }
+
+    let (offset_x, offset_y, blur) = match lengths[..] {
+        [offset_x, offset_y] => (offset_x, offset_y, 0.),
+        [offset_x, offset_y, blur] if blur >= 0. => (offset_x, offset_y, blur),
+        _ => return Err(location.new_custom_error(())),
+    };
+    // The color defaults to currentcolor.
+    let color = match color {
+        Some(color) => color,
+        None => parse_color(canvas, "currentcolor", can_gc)
+            .map_err(|_| location.new_custom_error(()))?,
+    };
+    Ok(CanvasFilter::DropShadow {
+        offset_x,
+        offset_y,
+        blur,
+        color,
+    })
+}
+
 // Used by drawImage to determine if a source or destination rectangle is valid
 // Origin coordinates and size cannot be negative. Size has to be greater than zero
 pub(crate) fn is_rect_valid(rect: Rect<f64>) -> bool {
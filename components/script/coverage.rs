@@ -0,0 +1,252 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Collection of code coverage information, used by tooling that wants to know which
+//! parts of a page's scripts and stylesheets were actually used during a session, for
+//! instance in order to trim unused assets.
+//!
+//! Script coverage is gathered with SpiderMonkey's PC count profiling, which counts the
+//! number of times each bytecode of each script is executed. This is runtime-wide, so
+//! the scripts of every document running in the same script thread are reported.
+//!
+//! Stylesheet coverage is computed when the report is taken, by checking whether each
+//! style rule of the document's author stylesheets matches any element in the document.
+//! Nested style rules are matched with their parent selectors substituted for `&`, and
+//! rules for pseudo-elements are considered used when their originating element exists.
+
+use std::ptr::NonNull;
+
+use cssparser::ToCss;
+use js::jsapi::{
+    GetPCCountScriptCount, GetPCCountScriptSummary, PurgePCCounts, StartPCCountProfiling,
+    StopPCCountProfiling,
+};
+use selectors::parser::{Selector, SelectorList};
+use serde::Serialize;
+use serde_json::Value;
+use style::selector_parser::SelectorImpl;
+use style::shared_lock::SharedRwLockReadGuard;
+use style::stylesheets::{CssRule, CssRules};
+
+use crate::dom::bindings::codegen::Bindings::StyleSheetBinding::StyleSheetMethods;
+use crate::dom::bindings::conversions::jsstring_to_str;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::stylesheet::StyleSheet;
+use crate::realms::enter_realm;
+use crate::script_runtime::JSContext;
+
+/// A coverage report for a single document, which is serialized to JSON when handed
+/// to the embedder.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CoverageReport {
+    url: String,
+    /// The per-script summaries produced by SpiderMonkey, which include the script
+    /// `file`, `line`, function `name` and execution `totals`.
+    scripts: Vec<Value>,
+    stylesheets: Vec<StylesheetCoverage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StylesheetCoverage {
+    /// The URL of the stylesheet, or `None` for inline stylesheets.
+    href: Option<String>,
+    rules: Vec<StyleRuleCoverage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StyleRuleCoverage {
+    selector_text: String,
+    used: bool,
+}
+
+/// Start counting script execution in the JavaScript runtime of `cx`. Any counts from
+/// a previous session which were not collected are discarded.
+#[allow(unsafe_code)]
+pub(crate) fn start_coverage(cx: JSContext) {
+    unsafe {
+        PurgePCCounts(*cx);
+        StartPCCountProfiling(*cx);
+    }
+}
+
+/// Stop counting script execution and produce a [`CoverageReport`] for `document`.
+pub(crate) fn take_coverage(document: &Document) -> CoverageReport {
+    CoverageReport {
+        url: document.url().to_string(),
+        scripts: collect_script_coverage(document),
+        stylesheets: collect_stylesheet_coverage(document),
+    }
+}
+
+#[allow(unsafe_code)]
+fn collect_script_coverage(document: &Document) -> Vec<Value> {
+    let window = document.window();
+    let _ac = enter_realm(window);
+    let cx = window.get_cx();
+
+    unsafe {
+        StopPCCountProfiling(*cx);
+        let scripts = (0..GetPCCountScriptCount(*cx))
+            .filter_map(|index| {
+                let summary = NonNull::new(GetPCCountScriptSummary(*cx, index))?;
+                let summary = String::from(jsstring_to_str(*cx, summary));
+                serde_json::from_str(&summary).ok()
+            })
+            .collect();
+        PurgePCCounts(*cx);
+        scripts
+    }
+}
+
+fn collect_stylesheet_coverage(document: &Document) -> Vec<StylesheetCoverage> {
+    (0..document.stylesheet_count())
+        .filter_map(|index| document.stylesheet_at(index))
+        .map(|stylesheet| {
+            let shared_lock = stylesheet.shared_lock();
+            let guard = shared_lock.read();
+            let mut rules = Vec::new();
+            collect_style_rules(
+                document,
+                stylesheet
+                    .style_stylesheet()
+                    .contents
+                    .rules
+                    .read_with(&guard),
+                None,
+                &guard,
+                &mut rules,
+            );
+            StylesheetCoverage {
+                href: stylesheet
+                    .upcast::<StyleSheet>()
+                    .GetHref()
+                    .map(String::from),
+                rules,
+            }
+        })
+        .collect()
+}
+
+fn collect_style_rules(
+    document: &Document,
+    css_rules: &CssRules,
+    parent_selectors: Option<&SelectorList<SelectorImpl>>,
+    guard: &SharedRwLockReadGuard,
+    output: &mut Vec<StyleRuleCoverage>,
+) {
+    for rule in css_rules.0.iter() {
+        let (child_rules, selectors) = match rule {
+            CssRule::Style(lock) => {
+                let style_rule = lock.read_with(guard);
+                let selectors = match parent_selectors {
+                    Some(parent_selectors) => style_rule
+                        .selectors
+                        .replace_parent_selector(parent_selectors),
+                    None => style_rule.selectors.clone(),
+                };
+                output.push(StyleRuleCoverage {
+                    selector_text: style_rule.selectors.to_css_string(),
+                    used: selectors
+                        .slice()
+                        .iter()
+                        .any(|selector| selector_matches_any_element(document, selector)),
+                });
+                match style_rule.rules {
+                    Some(ref rules) => (rules.clone(), Some(selectors)),
+                    None => continue,
+                }
+            },
+            CssRule::Media(rule) => (rule.rules.clone(), None),
+            CssRule::Supports(rule) => (rule.rules.clone(), None),
+            CssRule::LayerBlock(rule) => (rule.rules.clone(), None),
+            CssRule::Container(rule) => (rule.rules.clone(), None),
+            CssRule::Scope(rule) => (rule.rules.clone(), None),
+            _ => continue,
+        };
+        collect_style_rules(
+            document,
+            child_rules.read_with(guard),
+            selectors.as_ref().or(parent_selectors),
+            guard,
+            output,
+        );
+    }
+}
+
+/// Whether `selector` matches any element of `document`. Selectors with a pseudo-element
+/// are matched against the element the pseudo-element would originate from, since
+/// `querySelector()` never matches pseudo-elements.
+fn selector_matches_any_element(document: &Document, selector: &Selector<SelectorImpl>) -> bool {
+    let mut selector_text = selector.to_css_string();
+    if selector.has_pseudo_element() {
+        selector_text = originating_element_selector(&selector_text);
+    }
+    matches!(
+        document
+            .upcast::<Node>()
+            .query_selector(DOMString::from(selector_text)),
+        Ok(Some(_))
+    )
+}
+
+/// Remove the pseudo-element, and anything following it, from the serialization of a
+/// selector, leaving a selector for the originating element.
+fn originating_element_selector(selector_text: &str) -> String {
+    let mut nesting = 0;
+    let mut quote = None;
+    let mut characters = selector_text.char_indices().peekable();
+    let mut end = selector_text.len();
+    while let Some((index, character)) = characters.next() {
+        match (quote, character) {
+            (Some(_), '\\') => {
+                characters.next();
+            },
+            (Some(open), _) if open == character => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') => quote = Some(character),
+            (None, '(' | '[') => nesting += 1,
+            (None, ')' | ']') => nesting -= 1,
+            (None, ':') if nesting == 0 && characters.peek().is_some_and(|(_, c)| *c == ':') => {
+                end = index;
+                break;
+            },
+            _ => {},
+        }
+    }
+
+    // A pseudo-element which is not preceded by a compound selector applies to every
+    // element matched by the rest of the selector.
+    let mut originating = selector_text[..end].to_owned();
+    if originating.trim_end().is_empty() || originating.ends_with([' ', '>', '+', '~']) {
+        originating.push('*');
+    }
+    originating
+}
+
+#[cfg(test)]
+mod tests {
+    use super::originating_element_selector;
+
+    #[test]
+    fn test_originating_element_selector() {
+        assert_eq!(originating_element_selector("p::before"), "p");
+        assert_eq!(originating_element_selector("::selection"), "*");
+        assert_eq!(originating_element_selector("div > ::marker"), "div > *");
+        assert_eq!(originating_element_selector("div ::after"), "div *");
+        assert_eq!(
+            originating_element_selector("a[title=\"::\"]::after:hover"),
+            "a[title=\"::\"]"
+        );
+        assert_eq!(
+            originating_element_selector(":is(p, li)::first-line"),
+            ":is(p, li)"
+        );
+    }
+}
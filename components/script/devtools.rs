@@ -17,6 +17,7 @@ use js::rust::ToString;
 use servo_config::pref;
 use uuid::Uuid;
 
+use crate::coverage;
 use crate::document_collection::DocumentCollection;
 use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding::CSSRuleListMethods;
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
@@ -556,3 +557,20 @@ pub(crate) fn handle_highlight_dom_node(
         window.Document().highlight_dom_node(node.as_deref());
     }
 }
+
+pub(crate) fn handle_start_coverage(documents: &DocumentCollection, id: PipelineId) {
+    if let Some(window) = documents.find_window(id) {
+        coverage::start_coverage(window.get_cx());
+    }
+}
+
+pub(crate) fn handle_take_coverage(
+    documents: &DocumentCollection,
+    id: PipelineId,
+    reply: IpcSender<Option<String>>,
+) {
+    let report = documents
+        .find_document(id)
+        .and_then(|document| serde_json::to_string(&coverage::take_coverage(&document)).ok());
+    reply.send(report).unwrap();
+}
@@ -42,6 +42,7 @@ impl LoadBlocker {
     /// Mark the document's load event as blocked on this new load.
     pub(crate) fn new(doc: &Document, load: LoadType) -> LoadBlocker {
         doc.loader_mut().add_blocking_load(load.clone());
+        doc.update_load_progress();
         LoadBlocker {
             doc: Dom::from_ref(doc),
             load: Some(load),
@@ -79,6 +80,10 @@ pub(crate) struct DocumentLoader {
     #[no_trace]
     resource_threads: ResourceThreads,
     blocking_loads: Vec<LoadType>,
+    /// The number of blocking loads that were added, and the number of those that finished,
+    /// from which the progress of the load of the document is estimated.
+    added_loads: usize,
+    finished_loads: usize,
     events_inhibited: bool,
     cancellers: Vec<FetchCanceller>,
 }
@@ -93,10 +98,12 @@ impl DocumentLoader {
         initial_load: Option<ServoUrl>,
     ) -> DocumentLoader {
         debug!("Initial blocking load {:?}.", initial_load);
-        let initial_loads = initial_load.into_iter().map(LoadType::PageSource).collect();
+        let initial_loads: Vec<_> = initial_load.into_iter().map(LoadType::PageSource).collect();
 
         DocumentLoader {
             resource_threads,
+            added_loads: initial_loads.len(),
+            finished_loads: 0,
             blocking_loads: initial_loads,
             events_inhibited: false,
             cancellers: Vec::new(),
@@ -118,6 +125,7 @@ impl DocumentLoader {
             self.blocking_loads.len()
         );
         self.blocking_loads.push(load);
+        self.added_loads += 1;
     }
 
     /// Initiate a new fetch given a response callback.
@@ -155,11 +163,17 @@ impl DocumentLoader {
         match idx {
             Some(i) => {
                 self.blocking_loads.remove(i);
+                self.finished_loads += 1;
             },
             None => warn!("unknown completed load {:?}", load),
         }
     }
 
+    /// The number of blocking loads that were added so far, and how many of them finished.
+    pub(crate) fn load_counts(&self) -> (usize, usize) {
+        (self.added_loads, self.finished_loads)
+    }
+
     pub(crate) fn is_blocked(&self) -> bool {
         // TODO: Ensure that we report blocked if parsing is still ongoing.
         !self.blocking_loads.is_empty()
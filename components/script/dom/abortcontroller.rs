@@ -24,7 +24,7 @@ pub(crate) struct AbortController {
 
 impl AbortController {
     /// <https://dom.spec.whatwg.org/#dom-abortcontroller-abortcontroller>
-    fn new_inherited(signal: &AbortSignal) -> AbortController {
+    pub(crate) fn new_inherited(signal: &AbortSignal) -> AbortController {
         // Note: continuation of the constructor steps.
 
         // Set this’s signal to signal.
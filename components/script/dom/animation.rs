@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use style::animation::{AnimationSetKey, AnimationState};
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    AnimationMethods, AnimationPlayState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::DomGlobal;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+
+/// The CSS animation or transition that an [`Animation`] stands for.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum AnimationSource {
+    /// The CSS animation with the given `animation-name`.
+    CSSAnimation(Atom),
+    /// The CSS transition of the property with the given name.
+    CSSTransition(String),
+}
+
+/// Identifies the CSS animation or transition that an [`Animation`] stands for among
+/// the animations of its document.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct AnimationKey {
+    /// The element or pseudo-element that is animated.
+    pub(crate) set_key: AnimationSetKey,
+    pub(crate) source: AnimationSource,
+}
+
+/// The timing of the CSS animation or transition that an [`Animation`] stands for, in
+/// seconds on the animation timeline of its document.
+pub(crate) struct AnimationTiming {
+    pub(crate) state: AnimationState,
+    pub(crate) start_time: f64,
+    pub(crate) current_time: f64,
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#the-animation-interface>
+///
+/// Only the animations that CSS creates are exposed to script for now, as [`CSSAnimation`]
+/// and [`CSSTransition`] objects, which reflect the state that style keeps for them.
+///
+/// [`CSSAnimation`]: crate::dom::cssanimation::CSSAnimation
+/// [`CSSTransition`]: crate::dom::csstransition::CSSTransition
+#[dom_struct]
+pub(crate) struct Animation {
+    eventtarget: EventTarget,
+    id: DomRefCell<DOMString>,
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in style"]
+    key: AnimationKey,
+}
+
+impl Animation {
+    pub(crate) fn new_inherited(key: AnimationKey) -> Self {
+        Self {
+            eventtarget: EventTarget::new_inherited(),
+            id: Default::default(),
+            key,
+        }
+    }
+
+    pub(crate) fn key(&self) -> &AnimationKey {
+        &self.key
+    }
+
+    fn timing(&self) -> Option<AnimationTiming> {
+        let document = self.global().as_window().Document();
+        let now = document.current_animation_timeline_value();
+        document.animations().timing(&self.key, now)
+    }
+
+    /// Converts a time on the animation timeline into a time relative to the start of
+    /// the timeline of the document, in milliseconds.
+    fn timeline_time_in_milliseconds(&self, time: f64) -> f64 {
+        let document = self.global().as_window().Document();
+        (time - document.animation_timeline_origin()) * 1000.
+    }
+}
+
+impl AnimationMethods<crate::DomTypeHolder> for Animation {
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-id
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-id
+    fn SetId(&self, id: DOMString) {
+        *self.id.borrow_mut() = id;
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-starttime
+    fn GetStartTime(&self) -> Option<f64> {
+        let timing = self.timing()?;
+        Some(self.timeline_time_in_milliseconds(timing.start_time))
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-currenttime
+    fn GetCurrentTime(&self) -> Option<f64> {
+        let timing = self.timing()?;
+        Some(timing.current_time * 1000.)
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-playstate
+    fn PlayState(&self) -> AnimationPlayState {
+        match self.timing().map(|timing| timing.state) {
+            Some(AnimationState::Pending | AnimationState::Running) => AnimationPlayState::Running,
+            Some(AnimationState::Paused(_)) => AnimationPlayState::Paused,
+            Some(AnimationState::Finished) => AnimationPlayState::Finished,
+            Some(AnimationState::Canceled) | None => AnimationPlayState::Idle,
+        }
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-pending
+    fn Pending(&self) -> bool {
+        // Animations created by CSS start playing as soon as style creates them.
+        false
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-cancel
+    fn Cancel(&self) {
+        self.global()
+            .as_window()
+            .Document()
+            .animations()
+            .cancel(&self.key);
+    }
+}
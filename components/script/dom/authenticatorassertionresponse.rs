@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::typedarray::{ArrayBuffer, ArrayBufferU8};
+
+use crate::dom::authenticatorresponse::AuthenticatorResponse;
+use crate::dom::bindings::buffer_source::HeapBufferSource;
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAssertionResponseBinding::AuthenticatorAssertionResponseMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/webauthn/#iface-authenticatorassertionresponse>
+#[dom_struct]
+pub(crate) struct AuthenticatorAssertionResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    authenticator_data: HeapBufferSource<ArrayBufferU8>,
+    #[ignore_malloc_size_of = "mozjs"]
+    signature: HeapBufferSource<ArrayBufferU8>,
+    /// Not initialized when the credential has no user handle.
+    #[ignore_malloc_size_of = "mozjs"]
+    user_handle: HeapBufferSource<ArrayBufferU8>,
+}
+
+impl AuthenticatorAssertionResponse {
+    fn new_inherited() -> AuthenticatorAssertionResponse {
+        AuthenticatorAssertionResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            authenticator_data: HeapBufferSource::default(),
+            signature: HeapBufferSource::default(),
+            user_handle: HeapBufferSource::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        authenticator_data: &[u8],
+        signature: &[u8],
+        user_handle: Option<&[u8]>,
+        can_gc: CanGc,
+    ) -> DomRoot<AuthenticatorAssertionResponse> {
+        let response = reflect_dom_object(
+            Box::new(AuthenticatorAssertionResponse::new_inherited()),
+            global,
+            can_gc,
+        );
+        let cx = GlobalScope::get_cx();
+        response
+            .response
+            .init_client_data_json(client_data_json, can_gc);
+        response
+            .authenticator_data
+            .set_data(cx, authenticator_data, can_gc)
+            .expect("Failed to set authenticator data of authenticator response.");
+        response
+            .signature
+            .set_data(cx, signature, can_gc)
+            .expect("Failed to set signature of authenticator response.");
+        if let Some(user_handle) = user_handle {
+            response
+                .user_handle
+                .set_data(cx, user_handle, can_gc)
+                .expect("Failed to set user handle of authenticator response.");
+        }
+        response
+    }
+}
+
+impl AuthenticatorAssertionResponseMethods<crate::DomTypeHolder>
+    for AuthenticatorAssertionResponse
+{
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-authenticatordata>
+    fn AuthenticatorData(&self, _cx: JSContext) -> ArrayBuffer {
+        self.authenticator_data
+            .get_typed_array()
+            .expect("Failed to get authenticator data of authenticator response.")
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-signature>
+    fn Signature(&self, _cx: JSContext) -> ArrayBuffer {
+        self.signature
+            .get_typed_array()
+            .expect("Failed to get signature of authenticator response.")
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-userhandle>
+    fn GetUserHandle(&self, _cx: JSContext) -> Option<ArrayBuffer> {
+        if !self.user_handle.is_initialized() {
+            return None;
+        }
+        self.user_handle.get_typed_array().ok()
+    }
+}
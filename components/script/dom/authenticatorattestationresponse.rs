@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::typedarray::{ArrayBuffer, ArrayBufferU8};
+
+use crate::dom::authenticatorresponse::AuthenticatorResponse;
+use crate::dom::bindings::buffer_source::HeapBufferSource;
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAttestationResponseBinding::AuthenticatorAttestationResponseMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/webauthn/#iface-authenticatorattestationresponse>
+#[dom_struct]
+pub(crate) struct AuthenticatorAttestationResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    attestation_object: HeapBufferSource<ArrayBufferU8>,
+    transports: Vec<DOMString>,
+}
+
+impl AuthenticatorAttestationResponse {
+    fn new_inherited(transports: Vec<DOMString>) -> AuthenticatorAttestationResponse {
+        AuthenticatorAttestationResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            attestation_object: HeapBufferSource::default(),
+            transports,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        attestation_object: &[u8],
+        transports: Vec<DOMString>,
+        can_gc: CanGc,
+    ) -> DomRoot<AuthenticatorAttestationResponse> {
+        let response = reflect_dom_object(
+            Box::new(AuthenticatorAttestationResponse::new_inherited(transports)),
+            global,
+            can_gc,
+        );
+        response
+            .response
+            .init_client_data_json(client_data_json, can_gc);
+        response
+            .attestation_object
+            .set_data(GlobalScope::get_cx(), attestation_object, can_gc)
+            .expect("Failed to set attestation object of authenticator response.");
+        response
+    }
+}
+
+impl AuthenticatorAttestationResponseMethods<crate::DomTypeHolder>
+    for AuthenticatorAttestationResponse
+{
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-attestationobject>
+    fn AttestationObject(&self, _cx: JSContext) -> ArrayBuffer {
+        self.attestation_object
+            .get_typed_array()
+            .expect("Failed to get attestation object of authenticator response.")
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-gettransports>
+    fn GetTransports(&self) -> Vec<DOMString> {
+        self.transports.clone()
+    }
+}
@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::typedarray::{ArrayBuffer, ArrayBufferU8};
+
+use crate::dom::bindings::buffer_source::HeapBufferSource;
+use crate::dom::bindings::codegen::Bindings::AuthenticatorResponseBinding::AuthenticatorResponseMethods;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/webauthn/#iface-authenticatorresponse>
+#[dom_struct]
+pub(crate) struct AuthenticatorResponse {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "mozjs"]
+    client_data_json: HeapBufferSource<ArrayBufferU8>,
+}
+
+impl AuthenticatorResponse {
+    pub(crate) fn new_inherited() -> AuthenticatorResponse {
+        AuthenticatorResponse {
+            reflector_: Reflector::new(),
+            client_data_json: HeapBufferSource::default(),
+        }
+    }
+
+    /// Set the client data, once the response has been reflected.
+    pub(crate) fn init_client_data_json(&self, client_data_json: &[u8], can_gc: CanGc) {
+        self.client_data_json
+            .set_data(GlobalScope::get_cx(), client_data_json, can_gc)
+            .expect("Failed to set client data of authenticator response.");
+    }
+}
+
+impl AuthenticatorResponseMethods<crate::DomTypeHolder> for AuthenticatorResponse {
+    /// <https://w3c.github.io/webauthn/#dom-authenticatorresponse-clientdatajson>
+    fn ClientDataJSON(&self, _cx: JSContext) -> ArrayBuffer {
+        self.client_data_json
+            .get_typed_array()
+            .expect("Failed to get client data of authenticator response.")
+    }
+}
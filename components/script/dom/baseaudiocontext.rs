@@ -109,6 +109,8 @@ pub(crate) struct BaseAudioContext {
     /// been "closed()".
     state: Cell<AudioContextState>,
     channel_count: u32,
+    /// Whether this is the context of an `OfflineAudioContext`, which renders into a buffer.
+    offline: bool,
 }
 
 impl BaseAudioContext {
@@ -117,10 +119,10 @@ impl BaseAudioContext {
         options: BaseAudioContextOptions,
         pipeline_id: PipelineId,
     ) -> Fallible<BaseAudioContext> {
-        let (sample_rate, channel_count) = match options {
-            BaseAudioContextOptions::AudioContext(ref opt) => (opt.sample_rate, 2),
+        let (sample_rate, channel_count, offline) = match options {
+            BaseAudioContextOptions::AudioContext(ref opt) => (opt.sample_rate, 2, false),
             BaseAudioContextOptions::OfflineAudioContext(ref opt) => {
-                (opt.sample_rate, opt.channels)
+                (opt.sample_rate, opt.channels, true)
             },
         };
 
@@ -141,12 +143,13 @@ impl BaseAudioContext {
             sample_rate,
             state: Cell::new(AudioContextState::Suspended),
             channel_count: channel_count.into(),
+            offline,
         })
     }
 
     /// Tells whether this is an OfflineAudioContext or not.
     pub(crate) fn is_offline(&self) -> bool {
-        false
+        self.offline
     }
 
     pub(crate) fn audio_context_impl(&self) -> Arc<Mutex<AudioContext>> {
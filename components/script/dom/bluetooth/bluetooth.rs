@@ -222,6 +222,7 @@ impl Bluetooth {
 
         let option = RequestDeviceoptions::new(
             self.global().as_window().webview_id(),
+            self.global().origin().immutable().ascii_serialization(),
             BluetoothScanfilterSequence::new(uuid_filters),
             ServiceUUIDSequence::new(optional_services_uuids),
         );
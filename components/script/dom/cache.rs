@@ -0,0 +1,772 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use http::header::VARY;
+use http::{HeaderMap, Method};
+use hyper_serde::Serde;
+use js::rust::HandleValue;
+use net_traits::http_status::HttpStatus;
+use net_traits::request::{Referrer, RequestBuilder};
+use net_traits::storage_thread::{
+    self, CacheBatchError, CacheEntry, CacheId, CachedRequest, CachedResponse, CachedResponseType,
+    StorageThreadMsg,
+};
+use servo_url::ServoUrl;
+
+use crate::body::BodyMixin;
+use crate::conversions::Convert;
+use crate::dom::bindings::codegen::Bindings::CacheBinding::{CacheMethods, CacheQueryOptions};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{
+    RequestInfo, RequestInit, RequestMethods,
+};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::{
+    ResponseMethods, ResponseType as DOMResponseType,
+};
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::cachestorage::send_to_storage_thread;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::headers::Guard;
+use crate::dom::promise::{Promise, wait_for_all};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::fetch;
+use crate::realms::{InRealm, enter_realm};
+use crate::routed_promise::RoutedPromiseListener;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/ServiceWorker/#cache-interface>
+///
+/// The entries of the cache are kept by the storage thread, so this only identifies it.
+#[dom_struct]
+pub(crate) struct Cache {
+    reflector_: Reflector,
+    /// The <https://w3c.github.io/ServiceWorker/#dfn-request-response-list> that this
+    /// object represents.
+    #[no_trace]
+    id: CacheId,
+}
+
+impl Cache {
+    fn new_inherited(id: CacheId) -> Cache {
+        Cache {
+            reflector_: Reflector::new(),
+            id,
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, id: CacheId, can_gc: CanGc) -> DomRoot<Cache> {
+        reflect_dom_object(Box::new(Cache::new_inherited(id)), global, can_gc)
+    }
+
+    /// Steps of <https://w3c.github.io/ServiceWorker/#dom-cache-put> and
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-addall> that read the whole bodies of
+    /// the responses, and then store them with their requests as a single batch of put
+    /// operations.
+    fn put_all(
+        &self,
+        promise: &Rc<Promise>,
+        items: Vec<(CachedRequest, DomRoot<Response>)>,
+        realm: InRealm,
+        can_gc: CanGc,
+    ) {
+        let global = self.global();
+        let mut entries = vec![];
+        let mut bodies = vec![];
+        for (request, response) in items {
+            entries.push(CacheEntry {
+                request,
+                response: cached_response(&response, can_gc),
+            });
+            bodies.push(response.body());
+        }
+
+        let remaining_bodies = bodies.iter().flatten().count();
+        let pending = Rc::new(RefCell::new(PendingPut {
+            cache: Trusted::new(self),
+            promise: promise.clone(),
+            entries,
+            remaining_bodies,
+            failed: false,
+        }));
+        if remaining_bodies == 0 {
+            pending.borrow_mut().store(can_gc);
+            return;
+        }
+
+        for (index, body) in bodies.into_iter().enumerate() {
+            let Some(body) = body else {
+                continue;
+            };
+            let reader = match body.acquire_default_reader(can_gc) {
+                Ok(reader) => reader,
+                Err(error) => {
+                    pending.borrow_mut().fail(error, can_gc);
+                    return;
+                },
+            };
+            let success_pending = pending.clone();
+            let failure_pending = pending.clone();
+            reader.read_all_bytes(
+                GlobalScope::get_cx(),
+                &global,
+                Rc::new(move |bytes: &[u8]| {
+                    success_pending.borrow_mut().body_read(index, bytes, can_gc);
+                }),
+                Rc::new(move |_cx, _error| {
+                    failure_pending.borrow_mut().fail(
+                        Error::Type("Failed to read the body of the response".to_owned()),
+                        can_gc,
+                    );
+                }),
+                realm,
+                can_gc,
+            );
+        }
+    }
+}
+
+impl CacheMethods<crate::DomTypeHolder> for Cache {
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-match>
+    fn Match(
+        &self,
+        request: RequestInfo,
+        options: &CacheQueryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+        let request = match cached_request_query(&global, request, can_gc) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 2.1. Let p be the result of running the algorithm specified in
+        // matchAll(request, options) method with request and options.
+        // Step 2.3. If p[0] is undefined, resolve promise with undefined, otherwise with
+        // p[0].
+        let options = options.convert();
+        let cache = self.id;
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheMatch {
+                sender,
+                origin,
+                cache,
+                request,
+                options,
+            },
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-matchall>
+    fn MatchAll(
+        &self,
+        request: Option<RequestInfo>,
+        options: &CacheQueryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 1. Let r be null.
+        // Step 2. If the optional argument request is not omitted, set r to its request.
+        let request = match request
+            .map(|request| cached_request_query(&global, request, can_gc))
+            .transpose()
+        {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 5.1. Let responses be an empty list.
+        // Step 5.2. If the optional argument request is omitted, add the responses of all
+        // the entries of the cache, otherwise those of the entries matching request.
+        // Step 5.4. Resolve promise with a frozen array of new Response objects for them.
+        let options = options.convert();
+        let cache = self.id;
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheMatchAll {
+                sender,
+                origin,
+                cache,
+                request,
+                options,
+            },
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-add>
+    fn Add(&self, request: RequestInfo, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1. Let requests be an array containing only request.
+        // Step 2. Let responseArrayPromise be the result of running the algorithm specified
+        // in addAll(requests) passing requests as the argument.
+        // Step 3. Return the result of reacting to responseArrayPromise with a fulfillment
+        // handler that returns undefined.
+        self.AddAll(vec![request], comp, can_gc)
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-addall>
+    fn AddAll(&self, requests: Vec<RequestInfo>, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 1. Let responsePromises be an empty list.
+        // Step 2. Let requestList be an empty list.
+        // Step 3. For each request whose type is Request in requests, if its method is not
+        // `GET`, return a promise rejected with a TypeError.
+        // Step 4. For each request in requests, let r be the associated request of the
+        // result of invoking the initial value of Request as constructor with request, and
+        // return a promise rejected with a TypeError if its URL's scheme is not "http" or
+        // "https".
+        let mut request_list = vec![];
+        for request in requests {
+            match request_to_store(&global, request, can_gc) {
+                Ok(request) => request_list.push(request),
+                Err(error) => {
+                    promise.reject_error(error, can_gc);
+                    return promise;
+                },
+            }
+        }
+
+        // Step 4.5. Add r to requestList, and fetch it.
+        let (cached_requests, request_objects): (Vec<_>, Vec<_>) = request_list.into_iter().unzip();
+        let response_promises = request_objects
+            .into_iter()
+            .map(|request| {
+                fetch::Fetch(
+                    &global,
+                    RequestInfo::Request(request),
+                    RequestInit::empty(),
+                    comp,
+                    can_gc,
+                )
+            })
+            .collect();
+
+        // Step 5. Let p be the result of getting a promise to wait for all of
+        // responsePromises.
+        // Step 6. Return the result of reacting to p with a fulfillment handler that, when
+        // called with argument responses, stores them as a batch of put operations.
+        let trusted_cache = Trusted::new(self);
+        let success_promise = promise.clone();
+        let success_steps = Rc::new(move |responses: Vec<HandleValue>| {
+            let cache = trusted_cache.root();
+            let global = cache.global();
+            let mut items = vec![];
+            for (request, response) in cached_requests.iter().zip(responses) {
+                let response = match response_to_store(response, can_gc) {
+                    Ok(response) => response,
+                    Err(error) => {
+                        success_promise.reject_error(error, can_gc);
+                        return;
+                    },
+                };
+                items.push((request.clone(), response));
+            }
+            let realm = enter_realm(&*global);
+            cache.put_all(&success_promise, items, InRealm::Entered(&realm), can_gc);
+        });
+        let failure_promise = promise.clone();
+        let failure_steps = Rc::new(move |reason: HandleValue| {
+            failure_promise.reject(GlobalScope::get_cx(), reason, can_gc);
+        });
+        wait_for_all(
+            GlobalScope::get_cx(),
+            &global,
+            response_promises,
+            success_steps,
+            failure_steps,
+            comp,
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-put>
+    fn Put(
+        &self,
+        request: RequestInfo,
+        response: &Response,
+        comp: InRealm,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 1 to 4. Let innerRequest be the request of request, and return a promise
+        // rejected with a TypeError if its URL's scheme is not "http" or "https", or if its
+        // method is not `GET`.
+        let request = match request_to_store(&global, request, can_gc) {
+            Ok((request, _)) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 5. Let innerResponse be response's response.
+        // Step 6. If innerResponse's status is 206, return a promise rejected with a
+        // TypeError.
+        // Step 7. If innerResponse's header list contains a header named `Vary` whose
+        // values contain `*`, return a promise rejected with a TypeError.
+        if response.Status() == 206 {
+            promise.reject_error(
+                Error::Type("Partial responses cannot be stored".to_owned()),
+                can_gc,
+            );
+            return promise;
+        }
+        if varies_on_everything(&response.Headers(can_gc).get_headers_list()) {
+            promise.reject_error(
+                Error::Type("Responses that vary on `*` cannot be stored".to_owned()),
+                can_gc,
+            );
+            return promise;
+        }
+
+        // Step 8. If innerResponse's body is disturbed or locked, return a promise rejected
+        // with a TypeError.
+        if response.is_disturbed() || response.is_locked() {
+            promise.reject_error(
+                Error::Type("The body of the response is already used".to_owned()),
+                can_gc,
+            );
+            return promise;
+        }
+
+        // Step 9 to 14. Read all the bytes of the body, then run the put operation.
+        self.put_all(
+            &promise,
+            vec![(request, DomRoot::from_ref(response))],
+            comp,
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-delete>
+    fn Delete(
+        &self,
+        request: RequestInfo,
+        options: &CacheQueryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 1 to 3. Let r be the request of request, and resolve with false when its
+        // method is not `GET` and options' ignoreMethod is false.
+        let request = match cached_request_query(&global, request, can_gc) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 4 to 9. Run a batch of a single delete operation for r, and resolve with
+        // whether it removed any entry.
+        let options = options.convert();
+        let cache = self.id;
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheDelete {
+                sender,
+                origin,
+                cache,
+                request,
+                options,
+            },
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-keys>
+    fn Keys(
+        &self,
+        request: Option<RequestInfo>,
+        options: &CacheQueryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 1. Let r be null.
+        // Step 2. If the optional argument request is not omitted, set r to its request.
+        let request = match request
+            .map(|request| cached_request_query(&global, request, can_gc))
+            .transpose()
+        {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 5.1. Let requests be an empty list.
+        // Step 5.2. If the optional argument request is omitted, add the requests of all
+        // the entries of the cache, otherwise those of the entries matching request.
+        // Step 5.4. Resolve promise with a frozen array of new Request objects for them.
+        let options = options.convert();
+        let cache = self.id;
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheKeys {
+                sender,
+                origin,
+                cache,
+                request,
+                options,
+            },
+            can_gc,
+        );
+        promise
+    }
+}
+
+impl RoutedPromiseListener<Option<CacheEntry>> for Cache {
+    fn handle_response(&self, entry: Option<CacheEntry>, promise: &Rc<Promise>, can_gc: CanGc) {
+        resolve_with_cache_entry(&self.global(), promise, entry, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<Vec<CacheEntry>> for Cache {
+    fn handle_response(&self, entries: Vec<CacheEntry>, promise: &Rc<Promise>, can_gc: CanGc) {
+        let global = self.global();
+        let responses: Vec<_> = entries
+            .into_iter()
+            .map(|entry| response_from_cache(&global, entry.response, can_gc))
+            .collect();
+        promise.resolve_native(&responses, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<Vec<CachedRequest>> for Cache {
+    fn handle_response(&self, requests: Vec<CachedRequest>, promise: &Rc<Promise>, can_gc: CanGc) {
+        let global = self.global();
+        let requests: Vec<_> = requests
+            .into_iter()
+            .map(|request| request_from_cache(&global, request, can_gc))
+            .collect();
+        promise.resolve_native(&requests, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<Result<(), CacheBatchError>> for Cache {
+    fn handle_response(
+        &self,
+        result: Result<(), CacheBatchError>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match result {
+            Ok(()) => promise.resolve_native(&(), can_gc),
+            Err(error) => promise.reject_error(error.convert(), can_gc),
+        }
+    }
+}
+
+impl RoutedPromiseListener<Result<bool, CacheBatchError>> for Cache {
+    fn handle_response(
+        &self,
+        result: Result<bool, CacheBatchError>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match result {
+            Ok(deleted) => promise.resolve_native(&deleted, can_gc),
+            Err(error) => promise.reject_error(error.convert(), can_gc),
+        }
+    }
+}
+
+/// The entries of a batch of put operations, whose response bodies are being read.
+struct PendingPut {
+    cache: Trusted<Cache>,
+    promise: Rc<Promise>,
+    entries: Vec<CacheEntry>,
+    remaining_bodies: usize,
+    failed: bool,
+}
+
+impl PendingPut {
+    fn body_read(&mut self, index: usize, bytes: &[u8], can_gc: CanGc) {
+        if self.failed {
+            return;
+        }
+        self.entries[index].response.body = bytes.to_vec();
+        self.remaining_bodies -= 1;
+        if self.remaining_bodies == 0 {
+            self.store(can_gc);
+        }
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#batch-cache-operations>, run by the storage
+    /// thread once all the bodies are read.
+    fn store(&mut self, can_gc: CanGc) {
+        let cache = self.cache.root();
+        let id = cache.id;
+        let entries = mem::take(&mut self.entries);
+        send_to_storage_thread(
+            &*cache,
+            &self.promise,
+            |sender, origin| StorageThreadMsg::CachePut {
+                sender,
+                origin,
+                cache: id,
+                entries,
+            },
+            can_gc,
+        );
+    }
+
+    fn fail(&mut self, error: Error, can_gc: CanGc) {
+        if !self.failed {
+            self.failed = true;
+            self.promise.reject_error(error, can_gc);
+        }
+    }
+}
+
+/// Returns the request object of `request`, invoking the initial value of `Request` as
+/// constructor when it is a string.
+fn request_object(
+    global: &GlobalScope,
+    request: RequestInfo,
+    can_gc: CanGc,
+) -> Fallible<DomRoot<Request>> {
+    match request {
+        RequestInfo::Request(request) => Ok(request),
+        RequestInfo::USVString(url) => Request::Constructor(
+            global,
+            None,
+            can_gc,
+            RequestInfo::USVString(url),
+            RequestInit::empty(),
+        ),
+    }
+}
+
+/// The request to match the entries of caches against, for `request`.
+pub(crate) fn cached_request_query(
+    global: &GlobalScope,
+    request: RequestInfo,
+    can_gc: CanGc,
+) -> Fallible<CachedRequest> {
+    Ok(cached_request(
+        &request_object(global, request, can_gc)?,
+        can_gc,
+    ))
+}
+
+/// The request to store in a cache for `request`, along with its request object, which
+/// fails when it is not a `GET` request of an HTTP(S) URL.
+fn request_to_store(
+    global: &GlobalScope,
+    request: RequestInfo,
+    can_gc: CanGc,
+) -> Fallible<(CachedRequest, DomRoot<Request>)> {
+    let request = request_object(global, request, can_gc)?;
+    let cached_request = cached_request(&request, can_gc);
+    if !matches!(cached_request.url.scheme(), "http" | "https") {
+        return Err(Error::Type(
+            "Only requests of HTTP(S) URLs can be stored".to_owned(),
+        ));
+    }
+    if cached_request.method != Method::GET {
+        return Err(Error::Type("Only GET requests can be stored".to_owned()));
+    }
+    Ok((cached_request, request))
+}
+
+/// The response of a fetch in <https://w3c.github.io/ServiceWorker/#dom-cache-addall>,
+/// which fails when it is not one that can be stored.
+#[allow(unsafe_code)]
+fn response_to_store(response: HandleValue, can_gc: CanGc) -> Fallible<DomRoot<Response>> {
+    let response = unsafe { root_from_handlevalue::<Response>(response, *GlobalScope::get_cx()) }
+        .map_err(|_| {
+        Error::Type("Fetching the request did not return a response".to_owned())
+    })?;
+
+    // If response's type is "error", or response's status is not an ok status or is 206,
+    // reject with a TypeError.
+    if !response.Ok() || response.Status() == 206 {
+        return Err(Error::Type(format!(
+            "Responses with a status of {} cannot be stored",
+            response.Status()
+        )));
+    }
+
+    // If response's header list contains a header named `Vary` whose values contain `*`,
+    // reject with a TypeError.
+    if varies_on_everything(&response.Headers(can_gc).get_headers_list()) {
+        return Err(Error::Type(
+            "Responses that vary on `*` cannot be stored".to_owned(),
+        ));
+    }
+    Ok(response)
+}
+
+fn cached_request(request: &Request, can_gc: CanGc) -> CachedRequest {
+    let net_request = request.get_request();
+    let mut url = net_request.url();
+    url.set_fragment(None);
+    CachedRequest {
+        url,
+        method: net_request.method,
+        headers: request.Headers(can_gc).get_headers_list(),
+    }
+}
+
+/// The response to store in a cache for `response`, whose body is read separately.
+fn cached_response(response: &Response, can_gc: CanGc) -> CachedResponse {
+    CachedResponse {
+        response_type: response.Type().convert(),
+        url: ServoUrl::parse(&response.Url()).ok(),
+        status: response.Status(),
+        status_text: response.StatusText().to_vec(),
+        headers: response.Headers(can_gc).get_headers_list(),
+        body: vec![],
+    }
+}
+
+/// Creates a `Request` object for a request stored in a cache, whose headers are immutable.
+fn request_from_cache(
+    global: &GlobalScope,
+    request: CachedRequest,
+    can_gc: CanGc,
+) -> DomRoot<Request> {
+    let net_request = RequestBuilder::new(global.webview_id(), request.url, Referrer::NoReferrer)
+        .method(request.method)
+        .headers(request.headers.clone())
+        .origin(global.origin().immutable().clone())
+        .build();
+    let request_object = Request::from_net_request(global, None, net_request, can_gc);
+    let headers = request_object.Headers(can_gc);
+    headers.set_headers(request.headers);
+    headers.set_guard(Guard::Immutable);
+    request_object
+}
+
+/// Creates a `Response` object for a response stored in a cache, whose headers are
+/// immutable.
+fn response_from_cache(
+    global: &GlobalScope,
+    response: CachedResponse,
+    can_gc: CanGc,
+) -> DomRoot<Response> {
+    let response_object = Response::new(global, can_gc);
+    response_object.set_status(&HttpStatus::new_raw(response.status, response.status_text));
+    response_object.set_headers(Some(Serde(response.headers)), can_gc);
+    response_object.Headers(can_gc).set_guard(Guard::Immutable);
+    if let Some(url) = response.url {
+        response_object.set_final_url(url);
+    }
+    if !response.body.is_empty() {
+        response_object.stream_chunk(response.body, can_gc);
+    }
+    response_object.finish(can_gc);
+    // Setting the type last, as it clears the members that opaque responses do not expose.
+    response_object.set_type(response.response_type.convert(), can_gc);
+    response_object
+}
+
+/// Resolves the promise of a `match()` with a new `Response` object for the response of the
+/// matched entry, or with undefined when no entry matched.
+pub(crate) fn resolve_with_cache_entry(
+    global: &GlobalScope,
+    promise: &Rc<Promise>,
+    entry: Option<CacheEntry>,
+    can_gc: CanGc,
+) {
+    match entry {
+        Some(entry) => {
+            let response = response_from_cache(global, entry.response, can_gc);
+            promise.resolve_native(&response, can_gc);
+        },
+        None => promise.resolve_native(&(), can_gc),
+    }
+}
+
+/// Whether the `Vary` header of a response contains `*`.
+fn varies_on_everything(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|field_name| field_name.trim() == "*")
+}
+
+impl Convert<storage_thread::CacheQueryOptions> for &CacheQueryOptions {
+    fn convert(self) -> storage_thread::CacheQueryOptions {
+        storage_thread::CacheQueryOptions {
+            ignore_search: self.ignoreSearch,
+            ignore_method: self.ignoreMethod,
+            ignore_vary: self.ignoreVary,
+        }
+    }
+}
+
+impl Convert<CachedResponseType> for DOMResponseType {
+    fn convert(self) -> CachedResponseType {
+        match self {
+            DOMResponseType::Basic => CachedResponseType::Basic,
+            DOMResponseType::Cors => CachedResponseType::Cors,
+            DOMResponseType::Default => CachedResponseType::Default,
+            DOMResponseType::Error => CachedResponseType::Error,
+            DOMResponseType::Opaque => CachedResponseType::Opaque,
+            DOMResponseType::Opaqueredirect => CachedResponseType::OpaqueRedirect,
+        }
+    }
+}
+
+impl Convert<DOMResponseType> for CachedResponseType {
+    fn convert(self) -> DOMResponseType {
+        match self {
+            CachedResponseType::Basic => DOMResponseType::Basic,
+            CachedResponseType::Cors => DOMResponseType::Cors,
+            CachedResponseType::Default => DOMResponseType::Default,
+            CachedResponseType::Error => DOMResponseType::Error,
+            CachedResponseType::Opaque => DOMResponseType::Opaque,
+            CachedResponseType::OpaqueRedirect => DOMResponseType::Opaqueredirect,
+        }
+    }
+}
+
+impl Convert<Error> for CacheBatchError {
+    fn convert(self) -> Error {
+        match self {
+            CacheBatchError::InvalidState => Error::InvalidState,
+            CacheBatchError::QuotaExceeded => Error::QuotaExceeded,
+        }
+    }
+}
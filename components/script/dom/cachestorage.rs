@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::IpcSend;
+use net_traits::storage_thread::{CacheEntry, CacheId, StorageThreadMsg};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use servo_url::ImmutableOrigin;
+
+use crate::conversions::Convert;
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding::{
+    CacheStorageMethods, MultiCacheQueryOptions,
+};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{DomGlobal, DomObject, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cache::{Cache, cached_request_query, resolve_with_cache_entry};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::storagemanager::local_storage_shelf_origin;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/ServiceWorker/#cachestorage-interface>
+///
+/// The caches of origins are kept by the storage thread, which persists them along with the
+/// other storage of the origins.
+#[dom_struct]
+pub(crate) struct CacheStorage {
+    reflector_: Reflector,
+}
+
+impl CacheStorage {
+    fn new_inherited() -> CacheStorage {
+        CacheStorage {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<CacheStorage> {
+        reflect_dom_object(Box::new(CacheStorage::new_inherited()), global, can_gc)
+    }
+}
+
+impl CacheStorageMethods<crate::DomTypeHolder> for CacheStorage {
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-match>
+    fn Match(
+        &self,
+        request: RequestInfo,
+        options: &MultiCacheQueryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+        let request = match cached_request_query(&global, request, can_gc) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 1. If options["cacheName"] exists, then run these substeps in parallel:
+        // Step 1.1. For each cacheJob in the relevant name to cache map, if options["cacheName"]
+        // matches its key, resolve promise with the result of running the algorithm of
+        // Cache's match(request, options) on a new Cache object for it, or with undefined.
+        // Step 2. Else, for each cache in the relevant name to cache map, resolve promise with
+        // the first response matched by Cache's match(request, options) on it, or with
+        // undefined.
+        let cache_name = options.cacheName.as_ref().map(|name| name.to_string());
+        let options = (&options.parent).convert();
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheStorageMatch {
+                sender,
+                origin,
+                cache_name,
+                request,
+                options,
+            },
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-has>
+    fn Has(&self, cache_name: DOMString, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 2.1. For each key → value of the relevant name to cache map, if cacheName
+        // matches key, resolve promise with true and abort these steps.
+        // Step 2.2. Resolve promise with false.
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheStorageHas(sender, origin, cache_name.into()),
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-open>
+    fn Open(&self, cache_name: DOMString, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 2.1. For each key → value of the relevant name to cache map, if cacheName
+        // matches key, resolve promise with a new Cache object that represents value and
+        // abort these steps.
+        // Step 2.2. Let cache be a new request response list.
+        // Step 2.3. Set the relevant name to cache map[cacheName] to cache.
+        // Step 2.4. Resolve promise with a new Cache object that represents cache.
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| StorageThreadMsg::CacheStorageOpen(sender, origin, cache_name.into()),
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-delete>
+    fn Delete(&self, cache_name: DOMString, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 1. Let cacheExists be the result of running the algorithm specified in has()
+        // with cacheName.
+        // Step 3.1. If cacheExists is false, resolve with false.
+        // Step 3.2. Remove the relevant name to cache map[cacheName] and resolve with true.
+        send_to_storage_thread(
+            self,
+            &promise,
+            |sender, origin| {
+                StorageThreadMsg::CacheStorageDelete(sender, origin, cache_name.into())
+            },
+            can_gc,
+        );
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cachestorage-keys>
+    fn Keys(&self, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 2.1. Let keys be a new list.
+        // Step 2.2. For each key → value of the relevant name to cache map, in order, append
+        // key to keys.
+        // Step 2.3. Resolve promise with keys.
+        send_to_storage_thread(self, &promise, StorageThreadMsg::CacheStorageKeys, can_gc);
+        promise
+    }
+}
+
+impl RoutedPromiseListener<Option<CacheEntry>> for CacheStorage {
+    fn handle_response(&self, entry: Option<CacheEntry>, promise: &Rc<Promise>, can_gc: CanGc) {
+        resolve_with_cache_entry(&self.global(), promise, entry, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<bool> for CacheStorage {
+    fn handle_response(&self, result: bool, promise: &Rc<Promise>, can_gc: CanGc) {
+        promise.resolve_native(&result, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<CacheId> for CacheStorage {
+    fn handle_response(&self, id: CacheId, promise: &Rc<Promise>, can_gc: CanGc) {
+        let cache = Cache::new(&self.global(), id, can_gc);
+        promise.resolve_native(&cache, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<Vec<String>> for CacheStorage {
+    fn handle_response(&self, names: Vec<String>, promise: &Rc<Promise>, can_gc: CanGc) {
+        let names: Vec<DOMString> = names.into_iter().map(DOMString::from).collect();
+        promise.resolve_native(&names, can_gc);
+    }
+}
+
+/// Sends a message about the Cache Storage of the origin of the listener to the storage
+/// thread, whose reply settles the promise.
+///
+/// Contexts with an opaque origin have no Cache Storage, so the promise is rejected with a
+/// SecurityError for them.
+pub(crate) fn send_to_storage_thread<T, R>(
+    listener: &T,
+    promise: &Rc<Promise>,
+    message: impl FnOnce(IpcSender<R>, ImmutableOrigin) -> StorageThreadMsg,
+    can_gc: CanGc,
+) where
+    R: Serialize + DeserializeOwned + Send + 'static,
+    T: RoutedPromiseListener<R> + DomObject + 'static,
+{
+    let global = listener.global();
+    let Some(origin) = local_storage_shelf_origin(&global) else {
+        promise.reject_error(Error::Security, can_gc);
+        return;
+    };
+
+    let sender = route_promise(
+        promise,
+        listener,
+        global.task_manager().dom_manipulation_task_source(),
+    );
+    if global
+        .resource_threads()
+        .send(message(sender, origin))
+        .is_err()
+    {
+        promise.reject_error(Error::Operation, can_gc);
+    }
+}
@@ -3,7 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use canvas_traits::canvas::{
-    CanvasGradientStop, FillOrStrokeStyle, LinearGradientStyle, RadialGradientStyle,
+    CanvasGradientStop, ConicGradientStyle, FillOrStrokeStyle, LinearGradientStyle,
+    RadialGradientStyle,
 };
 use dom_struct::dom_struct;
 
@@ -31,6 +32,7 @@ pub(crate) struct CanvasGradient {
 pub(crate) enum CanvasGradientStyle {
     Linear(#[no_trace] LinearGradientStyle),
     Radial(#[no_trace] RadialGradientStyle),
+    Conic(#[no_trace] ConicGradientStyle),
 }
 
 impl CanvasGradient {
@@ -103,6 +105,9 @@ impl ToFillOrStrokeStyle for &CanvasGradient {
                     gradient_stops,
                 ))
             },
+            CanvasGradientStyle::Conic(ref gradient) => FillOrStrokeStyle::ConicGradient(
+                ConicGradientStyle::new(gradient.angle, gradient.x, gradient.y, gradient_stops),
+            ),
         }
     }
 }
@@ -21,6 +21,7 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
 use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
 use crate::dom::bindings::codegen::UnionTypes::{
     HTMLCanvasElementOrOffscreenCanvas, StringOrCanvasGradientOrCanvasPattern,
+    UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
 };
 use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::num::Finite;
@@ -38,14 +39,24 @@ use crate::dom::path2d::Path2D;
 use crate::dom::textmetrics::TextMetrics;
 use crate::script_runtime::CanGc;
 
+/// Closes a canvas of the canvas paint thread when the context that draws in it is dropped.
 #[derive(JSTraceable, MallocSizeOf)]
-struct DroppableCanvasRenderingContext2D {
+pub(crate) struct DroppableCanvasRenderingContext2D {
     #[no_trace]
     ipc_sender: IpcSender<CanvasMsg>,
     #[no_trace]
     canvas_id: CanvasId,
 }
 
+impl DroppableCanvasRenderingContext2D {
+    pub(crate) fn new(canvas_state: &CanvasState) -> DroppableCanvasRenderingContext2D {
+        DroppableCanvasRenderingContext2D {
+            ipc_sender: canvas_state.get_ipc_renderer().clone(),
+            canvas_id: canvas_state.get_canvas_id(),
+        }
+    }
+}
+
 impl Drop for DroppableCanvasRenderingContext2D {
     fn drop(&mut self) {
         if let Err(err) = self.ipc_sender.send(CanvasMsg::Close(self.canvas_id)) {
@@ -72,16 +83,12 @@ impl CanvasRenderingContext2D {
     ) -> CanvasRenderingContext2D {
         let canvas_state =
             CanvasState::new(global, Size2D::new(size.width as u64, size.height as u64));
-        let ipc_sender = canvas_state.get_ipc_renderer().clone();
-        let canvas_id = canvas_state.get_canvas_id();
+        let droppable = DroppableCanvasRenderingContext2D::new(&canvas_state);
         CanvasRenderingContext2D {
             reflector_: Reflector::new(),
             canvas,
             canvas_state,
-            droppable: DroppableCanvasRenderingContext2D {
-                ipc_sender,
-                canvas_id,
-            },
+            droppable,
         }
     }
 
@@ -120,6 +127,14 @@ impl CanvasRenderingContext2D {
     pub(crate) fn send_canvas_2d_msg(&self, msg: Canvas2dMsg) {
         self.canvas_state.send_canvas_2d_msg(msg)
     }
+
+    /// The canvas state only marks a canvas element as dirty after it draws an image, so do
+    /// it for an OffscreenCanvas here.
+    fn mark_offscreen_canvas_as_dirty(&self) {
+        if let HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(canvas) = &self.canvas {
+            canvas.mark_as_dirty();
+        }
+    }
 }
 
 impl LayoutCanvasRenderingContextHelpers for LayoutDom<'_, CanvasRenderingContext2D> {
@@ -149,6 +164,10 @@ impl CanvasContext for CanvasRenderingContext2D {
         self.canvas_state.update_rendering();
     }
 
+    fn image_key(&self) -> Option<ImageKey> {
+        Some(self.canvas_state.image_key())
+    }
+
     fn resize(&self) {
         self.set_canvas_bitmap_dimensions(self.size().cast())
     }
@@ -174,9 +193,12 @@ impl CanvasContext for CanvasRenderingContext2D {
     }
 
     fn mark_as_dirty(&self) {
-        if let Some(canvas) = self.canvas.canvas() {
-            canvas.upcast::<Node>().dirty(NodeDamage::Other);
-            canvas.owner_document().add_dirty_2d_canvas(self);
+        match &self.canvas {
+            HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(canvas) => {
+                canvas.upcast::<Node>().dirty(NodeDamage::Other);
+                canvas.owner_document().add_dirty_2d_canvas(self);
+            },
+            HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(canvas) => canvas.mark_as_dirty(),
         }
     }
 }
@@ -417,7 +439,9 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
     fn DrawImage(&self, image: CanvasImageSource, dx: f64, dy: f64) -> ErrorResult {
         self.canvas_state
-            .draw_image(self.canvas.canvas().as_deref(), image, dx, dy)
+            .draw_image(self.canvas.canvas().as_deref(), image, dx, dy)?;
+        self.mark_offscreen_canvas_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
@@ -430,7 +454,9 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
         dh: f64,
     ) -> ErrorResult {
         self.canvas_state
-            .draw_image_(self.canvas.canvas().as_deref(), image, dx, dy, dw, dh)
+            .draw_image_(self.canvas.canvas().as_deref(), image, dx, dy, dw, dh)?;
+        self.mark_offscreen_canvas_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
@@ -457,7 +483,9 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
             dy,
             dw,
             dh,
-        )
+        )?;
+        self.mark_offscreen_canvas_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-moveto
@@ -475,6 +503,18 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
         self.canvas_state.rect(x, y, width, height)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn RoundRect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        self.canvas_state.round_rect(x, y, width, height, radii)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         self.canvas_state.quadratic_curve_to(cpx, cpy, x, y)
@@ -632,6 +672,18 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
             .create_radial_gradient(&self.global(), x0, y0, r0, x1, y1, r1, can_gc)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+    fn CreateConicGradient(
+        &self,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> DomRoot<CanvasGradient> {
+        self.canvas_state
+            .create_conic_gradient(&self.global(), start_angle, x, y, can_gc)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     fn CreatePattern(
         &self,
@@ -743,4 +795,15 @@ impl CanvasRenderingContext2DMethods<crate::DomTypeHolder> for CanvasRenderingCo
         self.canvas_state
             .set_shadow_color(self.canvas.canvas().as_deref(), value, can_gc)
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    fn Filter(&self) -> DOMString {
+        self.canvas_state.filter()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    fn SetFilter(&self, value: DOMString, can_gc: CanGc) {
+        self.canvas_state
+            .set_filter(self.canvas.canvas().as_deref(), value, can_gc)
+    }
 }
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::CaretPositionBinding::CaretPositionMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrect::DOMRect;
+use crate::dom::node::{Node, NodeTraits};
+use crate::dom::range::Range;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://drafts.csswg.org/cssom-view/#caretposition>
+#[dom_struct]
+pub(crate) struct CaretPosition {
+    reflector_: Reflector,
+    /// <https://drafts.csswg.org/cssom-view/#caret-node>
+    offset_node: Dom<Node>,
+    /// <https://drafts.csswg.org/cssom-view/#caret-offset>
+    offset: u32,
+}
+
+impl CaretPosition {
+    fn new_inherited(offset_node: &Node, offset: u32) -> CaretPosition {
+        CaretPosition {
+            reflector_: Reflector::new(),
+            offset_node: Dom::from_ref(offset_node),
+            offset,
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        offset_node: &Node,
+        offset: u32,
+        can_gc: CanGc,
+    ) -> DomRoot<CaretPosition> {
+        reflect_dom_object(
+            Box::new(CaretPosition::new_inherited(offset_node, offset)),
+            window,
+            can_gc,
+        )
+    }
+}
+
+impl CaretPositionMethods<crate::DomTypeHolder> for CaretPosition {
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-offsetnode>
+    fn OffsetNode(&self) -> DomRoot<Node> {
+        DomRoot::from_ref(&*self.offset_node)
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-offset>
+    fn Offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-getclientrect>
+    fn GetClientRect(&self, can_gc: CanGc) -> Option<DomRoot<DOMRect>> {
+        // Step 1. If caret range is null, return null.
+        // Step 2. Let caretRect be a DOMRect object whose values are the result of
+        // invoking getBoundingClientRect() on the caret range.
+        let range = Range::new(
+            &self.offset_node.owner_doc(),
+            &self.offset_node,
+            self.offset,
+            &self.offset_node,
+            self.offset,
+            can_gc,
+        );
+        Some(range.GetBoundingClientRect(can_gc))
+    }
+}
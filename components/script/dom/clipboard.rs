@@ -5,26 +5,47 @@
 use std::rc::Rc;
 use std::str::FromStr;
 
+use base::id::WebViewId;
 use constellation_traits::BlobImpl;
 use data_url::mime::Mime;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{ClipboardContents, ClipboardImage, EmbedderMsg};
+use euclid::default::Size2D;
+use html5ever::local_name;
+use ipc_channel::ipc::{self, IpcSender};
 use js::rust::HandleValue as SafeHandleValue;
+use pixels::{
+    CorsStatus, EncodedImageType, PixelFormat, Snapshot, SnapshotAlphaMode, SnapshotPixelFormat,
+};
+use script_traits::DocumentActivity;
 
+use crate::document_loader::DocumentLoader;
+use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::ClipboardBinding::{
-    ClipboardMethods, PresentationStyle,
+    ClipboardItemMethods, ClipboardMethods, PresentationStyle,
+};
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
 };
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::TrustedPromise;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::blob::Blob;
-use crate::dom::clipboarditem::Representation;
+use crate::dom::clipboarditem::{ClipboardItem, Representation, blob_from_representation_data};
+use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
+use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::promise::Promise;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::permissions::{descriptor_permission_state, prompt_user_from_embedder};
+use crate::dom::promise::{Promise, wait_for_all};
 use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::servoparser::ServoParser;
 use crate::dom::window::Window;
 use crate::realms::{InRealm, enter_realm};
 use crate::routed_promise::{RoutedPromiseListener, route_promise};
@@ -88,9 +109,172 @@ impl Clipboard {
     pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<Clipboard> {
         reflect_dom_object(Box::new(Clipboard::new_inherited()), global, can_gc)
     }
+
+    /// Whether the document of this clipboard is the one that the user is interacting with.
+    /// Only that document may access the clipboard, so that documents in the background,
+    /// like those of hidden tabs or of frames without focus, can't snoop on its contents or
+    /// overwrite them.
+    fn is_document_focused(&self) -> bool {
+        let document = self.global().as_window().Document();
+        document.is_fully_active() && !document.Hidden() && document.HasFocus()
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#check-clipboard-read-permission>
+    fn check_clipboard_read_permission(&self) -> bool {
+        let global = self.global();
+        let window = global.as_window();
+
+        if !self.is_document_focused() {
+            return false;
+        }
+
+        // Step 1 Let hasGesture be true if the relevant global object of this has transient
+        // activation, false otherwise.
+        // Step 3 Return false.
+        if !window.has_transient_activation() {
+            return false;
+        }
+
+        // Step 2 If hasGesture then,
+        // Step 2.1 Return true if the current script is running as a result of user
+        // interaction with a "Paste" element created by the user agent or operating system.
+        // NOTE: Instead of a "Paste" element, the "clipboard-read" permission is used. The
+        // embedder asks the user for it the first time, and the answer is remembered for
+        // the rest of the session, or longer if the user asks to remember it, like other
+        // permissions.
+        match descriptor_permission_state(PermissionName::Clipboard_read, Some(&global)) {
+            PermissionState::Granted => true,
+            PermissionState::Denied => false,
+            PermissionState::Prompt => {
+                prompt_user_from_embedder(PermissionName::Clipboard_read, &global) ==
+                    PermissionState::Granted
+            },
+        }
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#check-clipboard-write-permission>
+    fn check_clipboard_write_permission(&self) -> bool {
+        // Step 1 Let hasGesture be true if the relevant global object of this has transient
+        // activation, false otherwise.
+        // NOTE: Like other browsers, writing only requires the document to have focus, as
+        // pages often write to the clipboard after an asynchronous operation.
+        if !self.is_document_focused() {
+            return false;
+        }
+
+        // Step 2 Return true if the permission state of "clipboard-write" is "granted".
+        descriptor_permission_state(PermissionName::Clipboard_write, Some(&self.global())) ==
+            PermissionState::Granted
+    }
 }
 
 impl ClipboardMethods<crate::DomTypeHolder> for Clipboard {
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-read>
+    fn Read(&self, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1 Let realm be this's relevant realm.
+        let global = self.global();
+
+        // Step 2 Let p be a new promise in realm.
+        let p = Promise::new(&global, can_gc);
+
+        // Step 3 Run the following steps in parallel:
+
+        // Step 3.1 Let r be the result of running check clipboard read permission.
+        // Step 3.2 If r is false, then:
+        // Step 3.2.1 Queue a global task on the permission task source, given realm’s global object,
+        // to reject p with "NotAllowedError" DOMException in realm.
+        // Step 3.2.2 Abort these steps.
+        // NOTE: The permission is checked synchronously, so p can be rejected right away.
+        if !self.check_clipboard_read_permission() {
+            p.reject_error(Error::NotAllowed, can_gc);
+            return p;
+        }
+
+        // Step 3.3 Let data be a copy of the system clipboard data.
+        let window = global.as_window();
+        let text = get_system_clipboard_contents(window, EmbedderMsg::GetClipboardText);
+        let html = get_system_clipboard_contents(window, EmbedderMsg::GetClipboardHtml);
+        let image = get_system_clipboard_image_as_png(window);
+
+        // Step 3.4 Queue a global task on the clipboard task source,
+        // given realm’s global object, to perform the below steps:
+        let trusted_promise = TrustedPromise::new(p.clone());
+        global.task_manager().clipboard_task_source().queue(
+            task!(resolve_clipboard_read: move || {
+                let promise = trusted_promise.root();
+                let global = promise.global();
+                let window = global.as_window();
+
+                // Step 3.4.1 Let clipboardItems be an empty sequence<ClipboardItem>.
+                let mut clipboard_items = Vec::new();
+
+                // Step 3.4.2 For each systemClipboardItem in data:
+                // NOTE: The embedder only exposes a single item of the system clipboard.
+
+                // Step 3.4.2.1 Let clipboardItem be a new ClipboardItem.
+                let mut representations = Vec::new();
+
+                // Step 3.4.2.2 For each systemClipboardRepresentation in systemClipboardItem:
+                // NOTE: HTML may come from any other application, so it is sanitized before
+                // it is exposed to the page.
+                let html = html.map(|html| sanitize_html(window, html, CanGc::note()));
+                for (mime_type, data) in [
+                    ("text/plain", text.map(String::into_bytes)),
+                    ("text/html", html.map(String::into_bytes)),
+                    ("image/png", image),
+                ] {
+                    let Some(data) = data else {
+                        continue;
+                    };
+
+                    // Step 3.4.2.2.1 Let mimeType be the result of running the well-known mime
+                    // type from os specific format algorithm given systemClipboardRepresentation’s name.
+                    // Step 3.4.2.2.2 If mimeType is null, continue this loop.
+                    // Note: This is done by the embedder, so we just convert the format to a MIME
+                    let mime = Mime::from_str(mime_type).unwrap();
+
+                    // Step 3.4.2.2.3 Let representation be a new representation.
+                    // Step 3.4.2.2.4 Set representation’s MIME type to mimeType.
+                    // Step 3.4.2.2.5 Set representation’s isCustom to false.
+                    // Step 3.4.2.2.6 Set representation’s data to systemClipboardRepresentation’s data.
+                    let blob = Blob::new(
+                        &global,
+                        BlobImpl::new_from_bytes(data, mime_type.into()),
+                        CanGc::note(),
+                    );
+                    let representation = Representation {
+                        mime_type: mime,
+                        is_custom: false,
+                        data: Promise::new_resolved(
+                            &global,
+                            GlobalScope::get_cx(),
+                            blob,
+                            CanGc::note(),
+                        ),
+                    };
+
+                    // Step 3.4.2.2.7 Append representation to clipboardItem’s list of representations.
+                    representations.push(representation);
+                }
+
+                // Step 3.4.2.3 If clipboardItem’s list of representations size is greater than 0,
+                // append clipboardItem to clipboardItems.
+                if !representations.is_empty() {
+                    clipboard_items.push(ClipboardItem::new_with_representations(
+                        window,
+                        representations,
+                        CanGc::note(),
+                    ));
+                }
+
+                // Step 3.4.3 Resolve p with clipboardItems.
+                promise.resolve_native(&clipboard_items, CanGc::note());
+            }),
+        );
+
+        p
+    }
+
     /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext>
     fn ReadText(&self, can_gc: CanGc) -> Rc<Promise> {
         // Step 1 Let realm be this's relevant realm.
@@ -101,11 +285,16 @@ impl ClipboardMethods<crate::DomTypeHolder> for Clipboard {
 
         // Step 3 Run the following steps in parallel:
 
-        // TODO Step 3.1 Let r be the result of running check clipboard read permission.
+        // Step 3.1 Let r be the result of running check clipboard read permission.
         // Step 3.2 If r is false, then:
         // Step 3.2.1 Queue a global task on the permission task source, given realm’s global object,
         // to reject p with "NotAllowedError" DOMException in realm.
         // Step 3.2.2 Abort these steps.
+        // NOTE: The permission is checked synchronously, so p can be rejected right away.
+        if !self.check_clipboard_read_permission() {
+            p.reject_error(Error::NotAllowed, can_gc);
+            return p;
+        }
 
         // Step 3.3 Let data be a copy of the system clipboard data.
         let window = global.as_window();
@@ -127,12 +316,16 @@ impl ClipboardMethods<crate::DomTypeHolder> for Clipboard {
 
         // Step 3 Run the following steps in parallel:
 
-        // TODO write permission could be removed from spec
         // Step 3.1 Let r be the result of running check clipboard write permission.
         // Step 3.2 If r is false, then:
         // Step 3.2.1 Queue a global task on the permission task source, given realm’s global object,
         // to reject p with "NotAllowedError" DOMException in realm.
         // Step 3.2.2 Abort these steps.
+        // NOTE: The permission is checked synchronously, so p can be rejected right away.
+        if !self.check_clipboard_write_permission() {
+            p.reject_error(Error::NotAllowed, can_gc);
+            return p;
+        }
 
         let trusted_promise = TrustedPromise::new(p.clone());
         let bytes = Vec::from(data);
@@ -172,6 +365,127 @@ impl ClipboardMethods<crate::DomTypeHolder> for Clipboard {
         // Step 3.4 Return p.
         p
     }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-write>
+    fn Write(&self, data: Vec<DomRoot<ClipboardItem>>, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1 Let realm be this's relevant realm.
+        let global = self.global();
+
+        // Step 2 Let p be a new promise in realm.
+        let p = Promise::new(&global, can_gc);
+
+        // Step 3 Run the following steps in parallel:
+
+        // Step 3.1 Let r be the result of running check clipboard write permission.
+        // Step 3.2 If r is false, then:
+        // Step 3.2.1 Queue a global task on the permission task source, given realm’s global object,
+        // to reject p with "NotAllowedError" DOMException in realm.
+        // Step 3.2.2 Abort these steps.
+        // NOTE: The permission is checked synchronously, so p can be rejected right away.
+        if !self.check_clipboard_write_permission() {
+            p.reject_error(Error::NotAllowed, can_gc);
+            return p;
+        }
+
+        // Step 3.3 Queue a global task on the clipboard task source,
+        // given realm’s global object, to perform the below steps:
+        // NOTE: The steps run synchronously, as the data of the representations is only
+        // available once their promises settle, which happens in a later task anyway.
+
+        // Step 3.3.1 Let itemList be an empty sequence<Blob>.
+        // Step 3.3.2 Let dataList be a sequence<ClipboardItem>.
+        // Step 3.3.3 If data’s size is greater than 1, and the current operating system does
+        // not support multiple native clipboard items on the system clipboard, then add
+        // data[0] to dataList, else, set dataList to data.
+        // NOTE: The embedder only supports a single item on the system clipboard.
+        let clipboard_item = data.first();
+
+        // Step 3.3.4 For each clipboardItem in dataList:
+        // Step 3.3.4.1 For each representation in clipboardItem’s list of representations:
+        // Step 3.3.4.1.1 Let representationDataPromise be the representation’s data.
+        // Step 3.3.4.1.2 React to representationDataPromise:
+        // NOTE: Web custom formats aren't supported by the embedder, so they are skipped.
+        let (mime_types, promises): (Vec<_>, Vec<_>) = clipboard_item
+            .map(|clipboard_item| clipboard_item.well_known_representations())
+            .unwrap_or_default()
+            .into_iter()
+            .unzip();
+        let option = clipboard_item.map_or(PresentationStyle::Unspecified, |clipboard_item| {
+            clipboard_item.PresentationStyle()
+        });
+
+        // Writing no representations clears the system clipboard.
+        if promises.is_empty() {
+            write_blobs_and_option_to_the_clipboard(global.as_window(), Vec::new(), option);
+            p.resolve_native(&(), can_gc);
+            return p;
+        }
+
+        let success_promise = p.clone();
+        let success_steps = Rc::new(move |values: Vec<SafeHandleValue>| {
+            let global = success_promise.global();
+            let mut item_list = Vec::new();
+            for (mime_type, value) in mime_types.iter().zip(values) {
+                // If representationDataPromise was fulfilled with value v, then:
+                // If v is a DOMString, then follow the below steps:
+                // Let dataAsBytes be the result of UTF-8 encoding v.
+                // Let blobData be a Blob created using dataAsBytes with its type set to
+                // representation’s MIME type.
+                // Add blobData to itemList.
+                // If v is a Blob, then add v to itemList.
+                let Some(blob) = blob_from_representation_data(
+                    GlobalScope::get_cx(),
+                    &global,
+                    value,
+                    mime_type,
+                    can_gc,
+                ) else {
+                    success_promise.reject_error(Error::NotAllowed, can_gc);
+                    return;
+                };
+
+                // Step 3.3.4.1.3 For each blob in itemList:
+                // Step 3.3.4.1.3.1 Let type be the blob’s type.
+                // Step 3.3.4.1.3.2 If type is not in the mandatory data types or optional
+                // data types list, then reject p with "NotAllowedError" DOMException in
+                // realm and abort these steps.
+                if !is_supported_clipboard_type(&blob.type_string()) {
+                    success_promise.reject_error(Error::NotAllowed, can_gc);
+                    return;
+                }
+                item_list.push(blob);
+            }
+
+            // Step 3.3.4.2 Let option be clipboardItem’s presentation style.
+            // Step 3.3.4.3 Write blobs and option to the clipboard with itemList and option.
+            write_blobs_and_option_to_the_clipboard(global.as_window(), item_list, option);
+
+            // Step 3.3.5 Resolve p.
+            success_promise.resolve_native(&(), can_gc);
+        });
+
+        // If representationDataPromise was rejected, then:
+        // Reject p with "NotAllowedError" DOMException in realm.
+        // Abort these steps.
+        let failure_promise = p.clone();
+        let failure_steps = Rc::new(move |_reason: SafeHandleValue| {
+            failure_promise.reject_error(Error::NotAllowed, can_gc);
+        });
+
+        let realm = enter_realm(&*global);
+        wait_for_all(
+            GlobalScope::get_cx(),
+            &global,
+            promises,
+            success_steps,
+            failure_steps,
+            InRealm::Entered(&realm),
+            can_gc,
+        );
+
+        // Step 4 Return p.
+        p
+    }
 }
 
 impl RoutedPromiseListener<Result<String, String>> for Clipboard {
@@ -239,6 +553,23 @@ impl RoutedPromiseListener<Result<String, String>> for Clipboard {
     }
 }
 
+/// The essences of the MIME types that can be written to the system clipboard, which are
+/// the mandatory data types of
+/// <https://w3c.github.io/clipboard-apis/#mandatory-data-types-x>.
+const SUPPORTED_CLIPBOARD_TYPES: [&str; 3] = ["text/plain", "text/html", "image/png"];
+
+/// Whether the MIME type `type_` is one of [`SUPPORTED_CLIPBOARD_TYPES`].
+fn is_supported_clipboard_type(type_: &str) -> bool {
+    clipboard_type_essence(type_)
+        .is_some_and(|essence| SUPPORTED_CLIPBOARD_TYPES.contains(&&*essence))
+}
+
+/// The essence of the MIME type `type_`, or `None` if it isn't a valid MIME type.
+fn clipboard_type_essence(type_: &str) -> Option<String> {
+    let mime = Mime::from_str(type_).ok()?;
+    Some(format!("{}/{}", mime.type_, mime.subtype))
+}
+
 /// <https://w3c.github.io/clipboard-apis/#write-blobs-and-option-to-the-clipboard>
 fn write_blobs_and_option_to_the_clipboard(
     window: &Window,
@@ -247,10 +578,15 @@ fn write_blobs_and_option_to_the_clipboard(
 ) {
     // TODO Step 1 Let webCustomFormats be a sequence<Blob>.
 
+    let mut contents = ClipboardContents::default();
+
     // Step 2 For each item in items:
     for item in items {
-        // TODO support more formats than just text/plain
         // Step 2.1 Let formatString be the result of running os specific well-known format given item’s type.
+        // NOTE: The embedder maps the formats of ClipboardContents to native formats.
+        let Some(essence) = clipboard_type_essence(&item.type_string()) else {
+            continue;
+        };
 
         // Step 2.2 If formatString is empty then follow the below steps:
 
@@ -265,18 +601,177 @@ fn write_blobs_and_option_to_the_clipboard(
         // Step 2.2.8 Append webCustomFormat to webCustomFormats.
 
         // Step 2.3 Let payload be the result of UTF-8 decoding item’s underlying byte sequence.
-        // Step 2.4 Insert payload and presentationStyle into the system clipboard
-        // using formatString as the native clipboard format.
-        window.send_to_embedder(EmbedderMsg::SetClipboardText(
-            window.webview_id(),
-            String::from_utf8(
-                item.get_bytes()
-                    .expect("No bytes found for Blob created by caller"),
-            )
-            .expect("DOMString contained invalid bytes"),
-        ));
+        // NOTE: Images are decoded to pixels instead, which is what the embedder expects.
+        let Ok(bytes) = item.get_bytes() else {
+            continue;
+        };
+        match &*essence {
+            "text/plain" => contents.text = Some(String::from_utf8_lossy(&bytes).into_owned()),
+            "text/html" => contents.html = Some(String::from_utf8_lossy(&bytes).into_owned()),
+            "image/png" => contents.image = decode_clipboard_image(&bytes),
+            _ => {},
+        }
     }
 
+    // Step 2.4 Insert payload and presentationStyle into the system clipboard
+    // using formatString as the native clipboard format.
+    // NOTE: All formats are written at once, as writing a format replaces the previous
+    // contents of the system clipboard.
+    window.send_to_embedder(EmbedderMsg::SetClipboardContents(
+        window.webview_id(),
+        contents,
+    ));
+
     // TODO Step 3 Write web custom formats given webCustomFormats.
     // Needs support to arbitrary formats inside arboard
 }
+
+/// Get the image contents of the system clipboard from the embedder, encoded as PNG, returning
+/// `None` if the clipboard doesn't hold an image.
+pub(crate) fn get_system_clipboard_image_as_png(window: &Window) -> Option<Vec<u8>> {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    window.send_to_embedder(EmbedderMsg::GetClipboardImage(window.webview_id(), sender));
+    let image = receiver.recv().ok()?.ok()?;
+    if image.rgba_pixels.len() != image.width as usize * image.height as usize * 4 {
+        warn!("Embedder returned a clipboard image with an invalid size");
+        return None;
+    }
+
+    let mut snapshot = Snapshot::from_vec(
+        Size2D::new(image.width, image.height),
+        SnapshotPixelFormat::RGBA,
+        SnapshotAlphaMode::Transparent {
+            premultiplied: false,
+        },
+        image.rgba_pixels,
+    );
+    let mut png = Vec::new();
+    snapshot
+        .encode_for_mime_type(&EncodedImageType::Png, None, &mut png)
+        .ok()?;
+    Some(png)
+}
+
+/// Decode PNG data written to the clipboard by web content into the pixels of the image,
+/// returning `None` if it isn't a valid image.
+pub(crate) fn decode_clipboard_image(png: &[u8]) -> Option<ClipboardImage> {
+    let image = pixels::load_from_memory(png, CorsStatus::Safe)?;
+    let frame = image.first_frame();
+    let mut rgba_pixels = frame.bytes.to_vec();
+    if image.format == PixelFormat::BGRA8 {
+        pixels::rgba8_byte_swap_colors_inplace(&mut rgba_pixels);
+    }
+    Some(ClipboardImage {
+        width: frame.width,
+        height: frame.height,
+        rgba_pixels,
+    })
+}
+
+/// Get the contents of the system clipboard in one format from the embedder, returning
+/// `None` if the clipboard doesn't hold contents in that format.
+pub(crate) fn get_system_clipboard_contents(
+    window: &Window,
+    message: fn(WebViewId, IpcSender<Result<String, String>>) -> EmbedderMsg,
+) -> Option<String> {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    window.send_to_embedder(message(window.webview_id(), sender));
+    receiver.recv().ok()?.ok()
+}
+
+/// Elements that are removed from HTML read from the clipboard, along with their contents,
+/// because they could run script or load resources when the HTML is inserted in a page.
+fn is_unsafe_clipboard_element(element: &Element) -> bool {
+    matches!(
+        *element.local_name(),
+        local_name!("base") |
+            local_name!("embed") |
+            local_name!("frame") |
+            local_name!("frameset") |
+            local_name!("iframe") |
+            local_name!("link") |
+            local_name!("meta") |
+            local_name!("object") |
+            local_name!("script")
+    )
+}
+
+const JAVASCRIPT_SCHEME: &str = "javascript:";
+
+/// Whether `attribute` of an element could run script, either because it is an event
+/// handler or because it holds a `javascript:` URL.
+fn is_unsafe_clipboard_attribute(attribute: &Attr) -> bool {
+    if attribute.local_name().starts_with("on") {
+        return true;
+    }
+    matches!(
+        *attribute.local_name(),
+        local_name!("action") |
+            local_name!("formaction") |
+            local_name!("href") |
+            local_name!("src")
+    ) && attribute
+        .value()
+        .trim_start()
+        .get(..JAVASCRIPT_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(JAVASCRIPT_SCHEME))
+}
+
+/// Sanitize HTML from the system clipboard before exposing it to web content, by parsing it
+/// into an inert document and removing everything that could run script from it.
+pub(crate) fn sanitize_html(window: &Window, html: String, can_gc: CanGc) -> String {
+    let url = window.get_url();
+    let document = window.Document();
+    let inert_document = Document::new(
+        window,
+        HasBrowsingContext::No,
+        Some(url.clone()),
+        document.origin().clone(),
+        IsHTMLDocument::HTMLDocument,
+        Some("text/html".parse().expect("text/html is a MIME type")),
+        None,
+        DocumentActivity::Inactive,
+        DocumentSource::FromParser,
+        DocumentLoader::new(&document.loader()),
+        None,
+        None,
+        Default::default(),
+        false,
+        false,
+        Some(document.insecure_requests_policy()),
+        document.has_trustworthy_ancestor_or_current_origin(),
+        can_gc,
+    );
+    ServoParser::parse_html_document(&inert_document, Some(DOMString::from(html)), url, can_gc);
+
+    let elements: Vec<_> = inert_document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .collect();
+    for element in elements {
+        if is_unsafe_clipboard_element(&element) {
+            element.upcast::<Node>().remove_self(can_gc);
+            continue;
+        }
+
+        let unsafe_attributes: Vec<_> = element
+            .attrs()
+            .iter()
+            .filter(|attribute| is_unsafe_clipboard_attribute(attribute))
+            .map(|attribute| attribute.name().clone())
+            .collect();
+        for name in unsafe_attributes {
+            element.remove_attribute_by_name(&name, can_gc);
+        }
+    }
+
+    inert_document
+        .GetBody()
+        .map(|body| {
+            body.upcast::<Node>()
+                .fragment_serialization_algorithm(false, can_gc)
+                .into()
+        })
+        .unwrap_or_default()
+}
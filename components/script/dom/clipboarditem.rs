@@ -6,22 +6,33 @@ use std::ops::Deref;
 use std::rc::Rc;
 use std::str::FromStr;
 
+use constellation_traits::BlobImpl;
 use data_url::mime::Mime;
 use dom_struct::dom_struct;
-use js::rust::{HandleObject, MutableHandleValue};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use script_bindings::conversions::StringificationBehavior;
 use script_bindings::record::Record;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ClipboardBinding::{
     ClipboardItemMethods, ClipboardItemOptions, PresentationStyle,
 };
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, root_from_handlevalue,
+};
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::frozenarray::CachedFrozenArray;
-use crate::dom::bindings::reflector::{Reflector, reflect_dom_object_with_proto};
+use crate::dom::bindings::reflector::{
+    DomGlobal, Reflector, reflect_dom_object, reflect_dom_object_with_proto,
+};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::Blob;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
 use crate::dom::window::Window;
+use crate::realms::{InRealm, enter_realm};
 use crate::script_runtime::{CanGc, JSContext};
 
 /// <https://w3c.github.io/clipboard-apis/#web-custom-format>
@@ -38,6 +49,80 @@ pub(super) struct Representation {
     pub data: Rc<Promise>,
 }
 
+/// The fulfillment handler for the reacting to representationDataPromise part of
+/// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct RepresentationDataPromiseFulfillmentHandler {
+    #[ignore_malloc_size_of = "Rc are hard"]
+    promise: Rc<Promise>,
+    #[no_trace]
+    #[ignore_malloc_size_of = "Extern type"]
+    mime_type: Mime,
+}
+
+impl Callback for RepresentationDataPromiseFulfillmentHandler {
+    /// The fulfillment case of Step 7.2.2 of
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>.
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm, can_gc: CanGc) {
+        match blob_from_representation_data(cx, &self.promise.global(), v, &self.mime_type, can_gc)
+        {
+            // Resolve p with blobData.
+            Some(blob_data) => self.promise.resolve_native(&blob_data, can_gc),
+            None => self.promise.reject_error(Error::NotFound, can_gc),
+        }
+    }
+}
+
+/// Convert the value `v` that the data of a representation with `mime_type` was fulfilled
+/// with to a [`Blob`], returning `None` if it is neither a DOMString nor a Blob.
+#[allow(unsafe_code)]
+pub(super) fn blob_from_representation_data(
+    cx: JSContext,
+    global: &GlobalScope,
+    v: HandleValue,
+    mime_type: &Mime,
+    can_gc: CanGc,
+) -> Option<DomRoot<Blob>> {
+    // If v is a DOMString, then follow the below steps:
+    if v.get().is_string() {
+        let Ok(ConversionResult::Success(string)) =
+            (unsafe { DOMString::from_jsval(*cx, v, StringificationBehavior::Default) })
+        else {
+            return None;
+        };
+
+        // Let dataAsBytes be the result of UTF-8 encoding v.
+        // Let blobData be a Blob created using dataAsBytes with its type set to mimeType,
+        // serialized.
+        return Some(Blob::new(
+            global,
+            BlobImpl::new_from_bytes(Vec::from(string), mime_type.to_string()),
+            can_gc,
+        ));
+    }
+
+    // If v is a Blob, then follow the below steps:
+    // Resolve p with v.
+    unsafe { root_from_handlevalue::<Blob>(v, *cx) }.ok()
+}
+
+/// The rejection handler for the reacting to representationDataPromise part of
+/// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct RepresentationDataPromiseRejectionHandler {
+    #[ignore_malloc_size_of = "Rc are hard"]
+    promise: Rc<Promise>,
+}
+
+impl Callback for RepresentationDataPromiseRejectionHandler {
+    /// The rejection case of Step 7.2.2 of
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>.
+    fn callback(&self, _cx: JSContext, _v: HandleValue, _realm: InRealm, can_gc: CanGc) {
+        // Reject p with "NotFoundError" DOMException in realm.
+        self.promise.reject_error(Error::NotFound, can_gc);
+    }
+}
+
 #[dom_struct]
 pub(crate) struct ClipboardItem {
     reflector_: Reflector,
@@ -65,6 +150,34 @@ impl ClipboardItem {
             can_gc,
         )
     }
+
+    /// Create a [`ClipboardItem`] holding the given representations of an item of the system
+    /// clipboard.
+    pub(super) fn new_with_representations(
+        window: &Window,
+        representations: Vec<Representation>,
+        can_gc: CanGc,
+    ) -> DomRoot<ClipboardItem> {
+        let clipboard_item = ClipboardItem::new_inherited();
+        *clipboard_item.representations.borrow_mut() = representations;
+        reflect_dom_object(Box::new(clipboard_item), window, can_gc)
+    }
+
+    /// The MIME types and data promises of the representations of this item that aren't
+    /// web custom formats.
+    pub(super) fn well_known_representations(&self) -> Vec<(Mime, Rc<Promise>)> {
+        self.representations
+            .borrow()
+            .iter()
+            .filter(|representation| !representation.is_custom)
+            .map(|representation| {
+                (
+                    representation.mime_type.clone(),
+                    representation.data.clone(),
+                )
+            })
+            .collect()
+    }
 }
 
 impl ClipboardItemMethods<crate::DomTypeHolder> for ClipboardItem {
@@ -178,4 +291,65 @@ impl ClipboardItemMethods<crate::DomTypeHolder> for ClipboardItem {
             can_gc,
         );
     }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>
+    fn GetType(&self, type_: DOMString, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1 Let realm be this’s relevant realm.
+        let global = self.global();
+
+        // Step 6 Let p be a new promise in realm.
+        let p = Promise::new(&global, can_gc);
+
+        // Step 2 Let isCustom be false.
+        // Step 3 If type starts with `"web "` prefix, then:
+        // Step 3.1 Remove `"web "` prefix and assign the remaining string to type.
+        // Step 3.2 Set isCustom to true.
+        let (type_, is_custom) = match type_.strip_prefix(CUSTOM_FORMAT_PREFIX) {
+            None => (type_.str(), false),
+            Some(stripped) => (stripped, true),
+        };
+
+        // Step 4 Let mimeType be the result of parsing a MIME type given type.
+        // Step 5 If mimeType is failure, then throw a TypeError.
+        let Ok(mime_type) = Mime::from_str(type_) else {
+            p.reject_error(Error::Type(String::from("Invalid mime type")), can_gc);
+            return p;
+        };
+
+        // Step 7 For each representation in this's clipboard item's list of representations:
+        // Step 7.1 If representation’s MIME type is mimeType and representation’s isCustom is
+        // isCustom, then:
+        let representations = self.representations.borrow();
+        let Some(representation) = representations.iter().find(|representation| {
+            representation.mime_type == mime_type && representation.is_custom == is_custom
+        }) else {
+            // Step 8 Reject p with "NotFoundError" DOMException in realm.
+            // Step 9 Return p.
+            p.reject_error(Error::NotFound, can_gc);
+            return p;
+        };
+
+        // Step 7.2.1 Let representationDataPromise be the representation’s data.
+        // Step 7.2.2 React to representationDataPromise:
+        let fulfillment_handler = Box::new(RepresentationDataPromiseFulfillmentHandler {
+            promise: p.clone(),
+            mime_type,
+        });
+        let rejection_handler =
+            Box::new(RepresentationDataPromiseRejectionHandler { promise: p.clone() });
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(fulfillment_handler),
+            Some(rejection_handler),
+            can_gc,
+        );
+        let realm = enter_realm(&*global);
+        let comp = InRealm::Entered(&realm);
+        representation
+            .data
+            .append_native_handler(&handler, comp, can_gc);
+
+        // Step 7.2.3 Return p.
+        p
+    }
 }
@@ -0,0 +1,687 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::{mem, ptr, thread};
+
+use dom_struct::dom_struct;
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::{Compression, Crc, Decompress, FlushDecompress, Status};
+use js::jsapi::{IsArrayBufferObject, JS_IsArrayBufferViewObject, JSObject};
+use js::jsval::ObjectValue;
+use js::rust::{HandleObject, HandleValue as SafeHandleValue};
+use js::typedarray::{ArrayBuffer, ArrayBufferView, Uint8};
+
+use crate::dom::bindings::buffer_source::create_buffer_source;
+use crate::dom::bindings::codegen::Bindings::CompressionStreamBinding::{
+    CompressionFormat, CompressionStreamMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::transformstream::TransformStream;
+use crate::dom::transformstreamdefaultcontroller::{
+    NativeTransformer, TransformStreamDefaultController,
+};
+use crate::dom::writablestream::WritableStream;
+use crate::realms::enter_realm;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CompressionDirection {
+    Compress,
+    Decompress,
+}
+
+/// The size by which the output buffer of a decompression grows when it is full.
+const OUTPUT_BUFFER_SIZE: usize = 32 * 1024;
+
+/// The threads on which the chunks of all the compression streams of this process are
+/// compressed and decompressed, so that large chunks do not block script.
+static COMPRESSION_THREAD_POOL: LazyLock<rayon::ThreadPool> = LazyLock::new(|| {
+    let num_threads = thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .min(4);
+    rayon::ThreadPoolBuilder::new()
+        .thread_name(|index| format!("Compression#{index}"))
+        .num_threads(num_threads)
+        .build()
+        .expect("Failed to create the compression thread pool")
+});
+
+trait Coder: Send {
+    /// Compress or decompress `chunk`, returning the output that is available.
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// Finish the stream, returning the rest of the output.
+    fn finish(&mut self) -> io::Result<Vec<u8>>;
+}
+
+macro_rules! impl_coder_for_writer {
+    ($($writer:ident),*) => {$(
+        impl Coder for $writer<Vec<u8>> {
+            fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+                self.write_all(chunk)?;
+                Ok(mem::take(self.get_mut()))
+            }
+
+            fn finish(&mut self) -> io::Result<Vec<u8>> {
+                self.try_finish()?;
+                Ok(mem::take(self.get_mut()))
+            }
+        }
+    )*};
+}
+
+impl_coder_for_writer!(DeflateEncoder, GzEncoder, ZlibEncoder);
+
+/// The length of the gzip trailer, made of the CRC-32 and the size of the uncompressed data.
+const GZIP_TRAILER_LENGTH: usize = 8;
+
+/// Where the decompression of the "gzip" format is in the framing around the deflate data,
+/// <https://www.rfc-editor.org/rfc/rfc1952#section-2.3>.
+enum GzipFraming {
+    /// The bytes of the header seen so far.
+    Header(Vec<u8>),
+    /// The deflate data of the member.
+    Data,
+    /// The bytes of the trailer seen so far.
+    Trailer(Vec<u8>),
+}
+
+/// The length of the gzip header at the start of `bytes`, or `None` if it does not end
+/// within them.
+fn gzip_header_length(bytes: &[u8]) -> io::Result<Option<usize>> {
+    const FHCRC: u8 = 1 << 1;
+    const FEXTRA: u8 = 1 << 2;
+    const FNAME: u8 = 1 << 3;
+    const FCOMMENT: u8 = 1 << 4;
+    const RESERVED: u8 = 0b1110_0000;
+
+    // The magic number, and the deflate compression method.
+    let invalid_header = || io::Error::new(io::ErrorKind::InvalidData, "Invalid gzip header");
+    if bytes
+        .iter()
+        .zip([0x1f, 0x8b, 8])
+        .any(|(byte, expected)| *byte != expected)
+    {
+        return Err(invalid_header());
+    }
+    if bytes.len() < 10 {
+        return Ok(None);
+    }
+    let flags = bytes[3];
+    if flags & RESERVED != 0 {
+        return Err(invalid_header());
+    }
+
+    let mut length = 10;
+    if flags & FEXTRA != 0 {
+        let Some(extra_length) = bytes.get(length..length + 2) else {
+            return Ok(None);
+        };
+        length += 2 + u16::from_le_bytes([extra_length[0], extra_length[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag == 0 {
+            continue;
+        }
+        // A zero-terminated string.
+        let Some(string_length) = bytes
+            .get(length..)
+            .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+        else {
+            return Ok(None);
+        };
+        length += string_length + 1;
+    }
+    if flags & FHCRC != 0 {
+        length += 2;
+    }
+    Ok((bytes.len() >= length).then_some(length))
+}
+
+/// The decompression of the "deflate", "deflate-raw" and "gzip" formats, which unlike the
+/// writers of flate2 notices when the compressed data ends too early or is followed by more
+/// data.
+struct Inflater {
+    decompress: Decompress,
+    /// The framing of the "gzip" format, or `None` for the other formats.
+    gzip: Option<GzipFraming>,
+    /// The CRC-32 and size of the output, checked against the gzip trailer.
+    crc: Crc,
+    finished: bool,
+}
+
+impl Inflater {
+    fn new(zlib_header: bool) -> Inflater {
+        Inflater {
+            decompress: Decompress::new(zlib_header),
+            gzip: None,
+            crc: Crc::new(),
+            finished: false,
+        }
+    }
+
+    fn new_gzip() -> Inflater {
+        Inflater {
+            gzip: Some(GzipFraming::Header(Vec::new())),
+            ..Inflater::new(false)
+        }
+    }
+
+    fn run(&mut self, input: &[u8], flush: FlushDecompress) -> io::Result<Vec<u8>> {
+        if let Some(GzipFraming::Header(header)) = &mut self.gzip {
+            header.extend_from_slice(input);
+            let Some(header_length) = gzip_header_length(header)? else {
+                return Ok(Vec::new());
+            };
+            let data = header.split_off(header_length);
+            self.gzip = Some(GzipFraming::Data);
+            return self.run(&data, flush);
+        }
+
+        let mut output = Vec::new();
+        let mut input = input;
+        if !self.finished && matches!(self.gzip, None | Some(GzipFraming::Data)) {
+            input = self.inflate(input, &mut output, flush)?;
+        }
+
+        if let Some(GzipFraming::Trailer(trailer)) = &mut self.gzip {
+            let (trailer_bytes, rest) =
+                input.split_at((GZIP_TRAILER_LENGTH - trailer.len()).min(input.len()));
+            trailer.extend_from_slice(trailer_bytes);
+            input = rest;
+            if trailer.len() == GZIP_TRAILER_LENGTH {
+                let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+                let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+                if crc != self.crc.sum() || size != self.crc.amount() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "The gzip trailer does not match the decompressed data",
+                    ));
+                }
+                self.finished = true;
+            }
+        }
+
+        if self.finished && !input.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The compressed data is followed by more data",
+            ));
+        }
+        Ok(output)
+    }
+
+    /// Decompress the deflate data at the start of `input` into `output`, returning the
+    /// input that follows the end of the deflate data, if it was reached.
+    fn inflate<'input>(
+        &mut self,
+        mut input: &'input [u8],
+        output: &mut Vec<u8>,
+        flush: FlushDecompress,
+    ) -> io::Result<&'input [u8]> {
+        loop {
+            output.reserve(OUTPUT_BUFFER_SIZE);
+            let total_in = self.decompress.total_in();
+            let total_out = self.decompress.total_out();
+            let output_start = output.len();
+            let status = self
+                .decompress
+                .decompress_vec(input, output, flush)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            input = &input[(self.decompress.total_in() - total_in) as usize..];
+            self.crc.update(&output[output_start..]);
+            if status == Status::StreamEnd {
+                match self.gzip {
+                    Some(_) => self.gzip = Some(GzipFraming::Trailer(Vec::new())),
+                    None => self.finished = true,
+                }
+                return Ok(input);
+            }
+
+            // Stop once all the input was used, unless the output buffer was filled up
+            // and there is more output to take.
+            let made_progress =
+                self.decompress.total_in() != total_in || self.decompress.total_out() != total_out;
+            if !made_progress || (input.is_empty() && output.len() < output.capacity()) {
+                return Ok(input);
+            }
+        }
+    }
+}
+
+impl Coder for Inflater {
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        self.run(chunk, FlushDecompress::None)
+    }
+
+    fn finish(&mut self) -> io::Result<Vec<u8>> {
+        let output = self.run(&[], FlushDecompress::Finish)?;
+        if !self.finished {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The compressed data ended unexpectedly",
+            ));
+        }
+        Ok(output)
+    }
+}
+
+fn new_coder(format: CompressionFormat, direction: CompressionDirection) -> Box<dyn Coder> {
+    let level = Compression::default();
+    match (direction, format) {
+        (CompressionDirection::Compress, CompressionFormat::Deflate) => {
+            Box::new(ZlibEncoder::new(Vec::new(), level))
+        },
+        (CompressionDirection::Compress, CompressionFormat::Deflate_raw) => {
+            Box::new(DeflateEncoder::new(Vec::new(), level))
+        },
+        (CompressionDirection::Compress, CompressionFormat::Gzip) => {
+            Box::new(GzEncoder::new(Vec::new(), level))
+        },
+        (CompressionDirection::Decompress, CompressionFormat::Deflate) => {
+            Box::new(Inflater::new(true))
+        },
+        (CompressionDirection::Decompress, CompressionFormat::Deflate_raw) => {
+            Box::new(Inflater::new(false))
+        },
+        (CompressionDirection::Decompress, CompressionFormat::Gzip) => {
+            Box::new(Inflater::new_gzip())
+        },
+    }
+}
+
+/// The transform and flush algorithms of a `CompressionStream` or a `DecompressionStream`,
+/// which compress or decompress the chunks with flate2 on the compression thread pool and
+/// enqueue the output from a task.
+///
+/// The stream does not run the transform algorithm again before the promise it returned is
+/// settled, so the chunks of a stream are never worked on by two threads at once.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub(crate) struct CompressionTransformer {
+    /// <https://compression.spec.whatwg.org/#compressionstream-context>
+    #[no_trace]
+    #[ignore_malloc_size_of = "The state of flate2 is not measured"]
+    context: Arc<Mutex<Box<dyn Coder>>>,
+}
+
+impl CompressionTransformer {
+    pub(crate) fn new(
+        format: CompressionFormat,
+        direction: CompressionDirection,
+    ) -> CompressionTransformer {
+        CompressionTransformer {
+            context: Arc::new(Mutex::new(new_coder(format, direction))),
+        }
+    }
+
+    /// <https://compression.spec.whatwg.org/#compress-and-enqueue-a-chunk> and
+    /// <https://compression.spec.whatwg.org/#decompress-and-enqueue-a-chunk>
+    pub(crate) fn transform(
+        &self,
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        controller: &TransformStreamDefaultController,
+        chunk: SafeHandleValue,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(global, can_gc);
+
+        // Step 1. If chunk is not a BufferSource type, then throw a TypeError.
+        let Some(bytes) = buffer_source_bytes(cx, chunk) else {
+            promise.reject_error(
+                Error::Type("The chunk is not a BufferSource".into()),
+                can_gc,
+            );
+            return promise;
+        };
+
+        // Step 2. Let buffer be the result of compressing chunk with cs’s format and context.
+        self.run_in_background(global, controller, &promise, move |coder| {
+            coder.write_chunk(&bytes)
+        });
+        promise
+    }
+
+    /// <https://compression.spec.whatwg.org/#compress-flush-and-enqueue> and
+    /// <https://compression.spec.whatwg.org/#decompress-flush-and-enqueue>
+    pub(crate) fn flush(
+        &self,
+        global: &GlobalScope,
+        controller: &TransformStreamDefaultController,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(global, can_gc);
+
+        // Step 1. Let buffer be the result of compressing an empty input with cs’s format and
+        // context, with the finish flag.
+        // Note: for decompression, this is an error if the end of the compressed data was not
+        // reached.
+        self.run_in_background(global, controller, &promise, |coder| coder.finish());
+        promise
+    }
+
+    /// Run `work` with the context on the compression thread pool, then enqueue its output
+    /// and settle `promise` in a task.
+    fn run_in_background(
+        &self,
+        global: &GlobalScope,
+        controller: &TransformStreamDefaultController,
+        promise: &Rc<Promise>,
+        work: impl FnOnce(&mut dyn Coder) -> io::Result<Vec<u8>> + Send + 'static,
+    ) {
+        let context = self.context.clone();
+        let controller = Trusted::new(controller);
+        let promise = TrustedPromise::new(promise.clone());
+        let task_source = global
+            .task_manager()
+            .dom_manipulation_task_source()
+            .to_sendable();
+        COMPRESSION_THREAD_POOL.spawn(move || {
+            let buffer = work(&mut **context.lock().unwrap());
+            task_source.queue(task!(enqueue_compressed_chunk: move || {
+                enqueue_output(&controller.root(), &promise.root(), buffer, CanGc::note());
+            }));
+        });
+    }
+}
+
+/// The bytes of `chunk`, if it is a `BufferSource`.
+#[allow(unsafe_code)]
+fn buffer_source_bytes(cx: SafeJSContext, chunk: SafeHandleValue) -> Option<Vec<u8>> {
+    if !chunk.is_object() {
+        return None;
+    }
+    rooted!(in(*cx) let object = chunk.to_object());
+    unsafe {
+        if IsArrayBufferObject(*object) {
+            return ArrayBuffer::from(*object)
+                .ok()
+                .map(|buffer| buffer.to_vec());
+        }
+        if JS_IsArrayBufferViewObject(*object) {
+            return ArrayBufferView::from(*object)
+                .ok()
+                .map(|view| view.to_vec());
+        }
+    }
+    None
+}
+
+fn enqueue_output(
+    controller: &TransformStreamDefaultController,
+    promise: &Promise,
+    buffer: io::Result<Vec<u8>>,
+    can_gc: CanGc,
+) {
+    // If the (de)compression fails, throw a TypeError.
+    let buffer = match buffer {
+        Ok(buffer) => buffer,
+        Err(error) => return promise.reject_error(Error::Type(error.to_string()), can_gc),
+    };
+
+    // Step 3. If buffer is empty, return.
+    if buffer.is_empty() {
+        return promise.resolve_native(&(), can_gc);
+    }
+
+    // Step 4. Split buffer into one or more non-empty pieces and convert them into
+    // Uint8Arrays.
+    let global = controller.global();
+    let cx = GlobalScope::get_cx();
+    let _ac = enter_realm(&*global);
+    rooted!(in(*cx) let mut array = ptr::null_mut::<JSObject>());
+    if create_buffer_source::<Uint8>(cx, &buffer, array.handle_mut(), can_gc).is_err() {
+        return promise.reject_error(Error::JSFailed, can_gc);
+    }
+
+    // Step 5. For each Uint8Array array, enqueue array in cs’s transform.
+    rooted!(in(*cx) let chunk = ObjectValue(array.get()));
+    match controller.enqueue(cx, &global, chunk.handle(), can_gc) {
+        Ok(()) => promise.resolve_native(&(), can_gc),
+        Err(error) => promise.reject_error(error, can_gc),
+    }
+}
+
+/// <https://compression.spec.whatwg.org/#compressionstream>
+#[dom_struct]
+pub(crate) struct CompressionStream {
+    reflector_: Reflector,
+
+    /// <https://streams.spec.whatwg.org/#generictransformstream-transform>
+    transform: Dom<TransformStream>,
+}
+
+impl CompressionStream {
+    fn new_inherited(transform: &TransformStream) -> CompressionStream {
+        CompressionStream {
+            reflector_: Reflector::new(),
+            transform: Dom::from_ref(transform),
+        }
+    }
+
+    fn new_with_proto(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        transform: &TransformStream,
+        can_gc: CanGc,
+    ) -> DomRoot<CompressionStream> {
+        reflect_dom_object_with_proto(
+            Box::new(CompressionStream::new_inherited(transform)),
+            global,
+            proto,
+            can_gc,
+        )
+    }
+}
+
+impl CompressionStreamMethods<crate::DomTypeHolder> for CompressionStream {
+    /// <https://compression.spec.whatwg.org/#dom-compressionstream-compressionstream>
+    fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        format: CompressionFormat,
+    ) -> Fallible<DomRoot<CompressionStream>> {
+        // Step 1. If format is unsupported in CompressionStream, then throw a TypeError.
+        // Note: this is done by the bindings.
+
+        // Step 2. Set this’s format to format.
+        // Step 3. Let transformAlgorithm be an algorithm which takes a chunk argument and
+        // runs the compress and enqueue a chunk algorithm with this and chunk.
+        // Step 4. Let flushAlgorithm be an algorithm which takes no argument and runs the
+        // compress flush and enqueue algorithm with this.
+        let transformer = CompressionTransformer::new(format, CompressionDirection::Compress);
+
+        // Step 5. Set this’s transform to a new TransformStream.
+        let transform = TransformStream::new_with_proto(global, None, can_gc);
+
+        // Step 6. Set up this’s transform with transformAlgorithm set to transformAlgorithm
+        // and flushAlgorithm set to flushAlgorithm.
+        transform.set_up(
+            GlobalScope::get_cx(),
+            global,
+            NativeTransformer::Compression(transformer),
+            can_gc,
+        )?;
+
+        Ok(CompressionStream::new_with_proto(
+            global, proto, &transform, can_gc,
+        ))
+    }
+
+    /// <https://streams.spec.whatwg.org/#dom-generictransformstream-readable>
+    fn Readable(&self) -> DomRoot<ReadableStream> {
+        self.transform.get_readable()
+    }
+
+    /// <https://streams.spec.whatwg.org/#dom-generictransformstream-writable>
+    fn Writable(&self) -> DomRoot<WritableStream> {
+        self.transform.get_writable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compress(format: CompressionFormat, data: &[u8]) -> Vec<u8> {
+        let mut coder = new_coder(format, CompressionDirection::Compress);
+        let mut output = coder.write_chunk(data).unwrap();
+        output.extend(coder.finish().unwrap());
+        output
+    }
+
+    #[test]
+    fn test_inflater_decompresses_chunks() {
+        let data = b"Hello, Hello, Hello, Hello, compressed world!".repeat(100);
+        for (format, zlib_header) in [
+            (CompressionFormat::Deflate, true),
+            (CompressionFormat::Deflate_raw, false),
+        ] {
+            let compressed = compress(format, &data);
+            let mut inflater = Inflater::new(zlib_header);
+            let mut output = Vec::new();
+            for chunk in compressed.chunks(7) {
+                output.extend(inflater.write_chunk(chunk).unwrap());
+            }
+            output.extend(inflater.finish().unwrap());
+            assert_eq!(output, data);
+        }
+    }
+
+    #[test]
+    fn test_inflater_large_output() {
+        let data = vec![b'a'; 4 * OUTPUT_BUFFER_SIZE + 1];
+        let compressed = compress(CompressionFormat::Deflate, &data);
+        let mut inflater = Inflater::new(true);
+        let mut output = inflater.write_chunk(&compressed).unwrap();
+        output.extend(inflater.finish().unwrap());
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn test_inflater_truncated_input() {
+        let compressed = compress(CompressionFormat::Deflate, b"Some data that gets cut off");
+        let mut inflater = Inflater::new(true);
+        assert!(
+            inflater
+                .write_chunk(&compressed[..compressed.len() - 3])
+                .is_ok()
+        );
+        let error = inflater.finish().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut inflater = Inflater::new(false);
+        assert_eq!(
+            inflater.finish().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_inflater_trailing_data() {
+        let mut compressed = compress(CompressionFormat::Deflate_raw, b"Some data");
+        compressed.push(0);
+        let mut inflater = Inflater::new(false);
+        let error = inflater.write_chunk(&compressed).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // The trailing data is also noticed when it comes in a later chunk.
+        let compressed = compress(CompressionFormat::Deflate, b"Some data");
+        let mut inflater = Inflater::new(true);
+        assert_eq!(inflater.write_chunk(&compressed).unwrap(), b"Some data");
+        let error = inflater.write_chunk(b"more").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_gzip_inflater_decompresses_chunks() {
+        let data = b"Hello, Hello, Hello, Hello, compressed world!".repeat(100);
+        let compressed = compress(CompressionFormat::Gzip, &data);
+        for chunk_size in [1, 7, compressed.len()] {
+            let mut inflater = Inflater::new_gzip();
+            let mut output = Vec::new();
+            for chunk in compressed.chunks(chunk_size) {
+                output.extend(inflater.write_chunk(chunk).unwrap());
+            }
+            output.extend(inflater.finish().unwrap());
+            assert_eq!(output, data);
+        }
+    }
+
+    #[test]
+    fn test_gzip_header_length() {
+        let header = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        assert_eq!(gzip_header_length(&header[..4]).unwrap(), None);
+        assert_eq!(gzip_header_length(&header).unwrap(), Some(10));
+
+        // FEXTRA with two bytes of extra data, FNAME, FCOMMENT and FHCRC.
+        let mut header = vec![0x1f, 0x8b, 8, 0b0001_1110, 0, 0, 0, 0, 0, 0xff, 2, 0, 1, 2];
+        header.extend(b"name\0comment\0");
+        header.extend([0, 0]);
+        assert_eq!(
+            gzip_header_length(&header[..header.len() - 1]).unwrap(),
+            None
+        );
+        assert_eq!(gzip_header_length(&header).unwrap(), Some(header.len()));
+
+        assert!(gzip_header_length(&[0x1f, 0x8c]).is_err());
+        assert!(gzip_header_length(&[0x1f, 0x8b, 8, 0b1000_0000, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_gzip_inflater_truncated_input() {
+        let compressed = compress(CompressionFormat::Gzip, b"Some data that gets cut off");
+
+        // Cut off in the header, in the deflate data and in the trailer.
+        for length in [5, compressed.len() - 12, compressed.len() - 3] {
+            let mut inflater = Inflater::new_gzip();
+            assert!(inflater.write_chunk(&compressed[..length]).is_ok());
+            let error = inflater.finish().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn test_gzip_inflater_trailing_data() {
+        let mut compressed = compress(CompressionFormat::Gzip, b"Some data");
+        compressed.push(0);
+        let mut inflater = Inflater::new_gzip();
+        let error = inflater.write_chunk(&compressed).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let compressed = compress(CompressionFormat::Gzip, b"Some data");
+        let mut inflater = Inflater::new_gzip();
+        assert_eq!(inflater.write_chunk(&compressed).unwrap(), b"Some data");
+        let error = inflater.write_chunk(b"more").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_gzip_inflater_corrupt_trailer() {
+        let mut compressed = compress(CompressionFormat::Gzip, b"Some data");
+        let crc_start = compressed.len() - 8;
+        compressed[crc_start] ^= 1;
+        let mut inflater = Inflater::new_gzip();
+        let error = inflater.write_chunk(&compressed).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_inflater_invalid_data() {
+        let mut inflater = Inflater::new(true);
+        let error = inflater.write_chunk(b"not compressed data").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
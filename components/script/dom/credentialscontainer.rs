@@ -0,0 +1,519 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use aws_lc_rs::digest;
+use base64::Engine;
+use dom_struct::dom_struct;
+use embedder_traits::{
+    AuthenticatorAssertion, AuthenticatorAttestation, EmbedderMsg,
+    PublicKeyCredentialAssertionParameters, PublicKeyCredentialCreationParameters,
+    UserVerificationRequirement, WebAuthnError,
+};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use servo_url::ImmutableOrigin;
+use url::Host;
+
+use crate::dom::authenticatorassertionresponse::AuthenticatorAssertionResponse;
+use crate::dom::authenticatorattestationresponse::AuthenticatorAttestationResponse;
+use crate::dom::bindings::codegen::Bindings::CredentialsContainerBinding::{
+    CredentialCreationOptions, CredentialRequestOptions, CredentialsContainerMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::{
+    PublicKeyCredentialCreationOptions, PublicKeyCredentialDescriptor,
+    PublicKeyCredentialRequestOptions,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::credential::Credential;
+use crate::dom::document::get_registrable_domain_suffix_of_or_is_equal_to;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::publickeycredential::PublicKeyCredential;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// The COSE identifiers of ES256 and RS256, which are used when the relying party does not
+/// give any algorithms.
+/// <https://w3c.github.io/webauthn/#dom-publickeycredentialcreationoptions-pubkeycredparams>
+const DEFAULT_ALGORITHMS: [i32; 2] = [-7, -257];
+
+/// <https://w3c.github.io/webappsec-credential-management/#credentialscontainer>
+///
+/// Only public key credentials are supported. They are created and used by the
+/// authenticators of the embedder, such as the passkey store of the platform.
+#[dom_struct]
+pub(crate) struct CredentialsContainer {
+    reflector_: Reflector,
+}
+
+impl CredentialsContainer {
+    fn new_inherited() -> CredentialsContainer {
+        CredentialsContainer {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<CredentialsContainer> {
+        reflect_dom_object(
+            Box::new(CredentialsContainer::new_inherited()),
+            global,
+            can_gc,
+        )
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-createCredential>
+    fn create_public_key_credential(
+        &self,
+        options: &PublicKeyCredentialCreationOptions,
+        promise: &Rc<Promise>,
+    ) -> Result<(), Error> {
+        let global = self.global();
+        let window = global.as_window();
+
+        // Step 2. If sameOriginWithAncestors is false, throw a "NotAllowedError".
+        if !is_same_origin_with_its_ancestors(window) {
+            return Err(Error::NotAllowed);
+        }
+
+        // Step 5. If the length of options.user.id is not between 1 and 64 bytes
+        // (inclusive) then throw a TypeError.
+        let user_id = buffer_source_bytes(&options.user.id);
+        if user_id.is_empty() || user_id.len() > 64 {
+            return Err(Error::Type(
+                "The user id has to be between 1 and 64 bytes long".to_owned(),
+            ));
+        }
+
+        // Steps 6-8. Validate the RP ID against the origin.
+        let origin = global.origin().immutable().clone();
+        let rp_id = relying_party_id(&origin, options.rp.id.as_deref())?;
+
+        // Steps 9-10. Build credTypesAndPubKeyAlgs from the public-key algorithms that were
+        // given, or the default ones if none were.
+        let algorithms = if options.pubKeyCredParams.is_empty() {
+            DEFAULT_ALGORITHMS.to_vec()
+        } else {
+            let algorithms: Vec<i32> = options
+                .pubKeyCredParams
+                .iter()
+                .filter(|parameters| &*parameters.type_ == "public-key")
+                .map(|parameters| parameters.alg)
+                .collect();
+            // Step 10. If credTypesAndPubKeyAlgs is empty, throw a "NotSupportedError".
+            if algorithms.is_empty() {
+                return Err(Error::NotSupported);
+            }
+            algorithms
+        };
+
+        // Steps 12-15. Let clientDataJSON be the JSON-compatible serialization of the
+        // client data and clientDataHash its hash.
+        let client_data_json =
+            collected_client_data("webauthn.create", &options.challenge, &origin);
+        let client_data_hash = digest::digest(&digest::SHA256, &client_data_json)
+            .as_ref()
+            .to_vec();
+
+        let (require_resident_key, user_verification) = match &options.authenticatorSelection {
+            Some(selection) => (
+                selection.residentKey.as_deref() == Some("required") ||
+                    selection.requireResidentKey,
+                user_verification_requirement(&selection.userVerification),
+            ),
+            None => (false, UserVerificationRequirement::Preferred),
+        };
+
+        let parameters = PublicKeyCredentialCreationParameters {
+            origin,
+            rp_id,
+            rp_name: options.rp.parent.name.to_string(),
+            user_id,
+            user_name: options.user.parent.name.to_string(),
+            user_display_name: options.user.displayName.to_string(),
+            client_data_hash,
+            algorithms,
+            exclude_credentials: credential_ids(&options.excludeCredentials),
+            require_resident_key,
+            user_verification,
+        };
+
+        // Step 20. Start the authenticatorMakeCredential operation on the authenticators of
+        // the embedder, the result is handled by `handle_attestation`.
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let task_source = global
+            .task_manager()
+            .dom_manipulation_task_source()
+            .to_sendable();
+        let mut pending = Some((
+            Trusted::new(self),
+            TrustedPromise::new(promise.clone()),
+            client_data_json,
+        ));
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |message| {
+                let Some((container, promise, client_data_json)) = pending.take() else {
+                    return;
+                };
+                let result = message.unwrap_or(Err(WebAuthnError::NotAllowed));
+                task_source.queue(task!(public_key_credential_created: move || {
+                    container.root().handle_attestation(
+                        result,
+                        &client_data_json,
+                        &promise.root(),
+                        CanGc::note(),
+                    );
+                }));
+            }),
+        );
+        global.send_to_embedder(EmbedderMsg::CreatePublicKeyCredential(
+            window.webview_id(),
+            parameters,
+            sender,
+        ));
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-getAssertion>
+    fn get_public_key_credential(
+        &self,
+        options: &PublicKeyCredentialRequestOptions,
+        promise: &Rc<Promise>,
+    ) -> Result<(), Error> {
+        let global = self.global();
+        let window = global.as_window();
+
+        // Step 1. If sameOriginWithAncestors is false, throw a "NotAllowedError".
+        if !is_same_origin_with_its_ancestors(window) {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 5-8. Validate the RP ID against the origin.
+        let origin = global.origin().immutable().clone();
+        let rp_id = relying_party_id(&origin, options.rpId.as_ref().map(|id| id.0.as_str()))?;
+
+        // Steps 9-12. Let clientDataJSON be the JSON-compatible serialization of the client
+        // data and clientDataHash its hash.
+        let client_data_json = collected_client_data("webauthn.get", &options.challenge, &origin);
+        let client_data_hash = digest::digest(&digest::SHA256, &client_data_json)
+            .as_ref()
+            .to_vec();
+
+        let parameters = PublicKeyCredentialAssertionParameters {
+            origin,
+            rp_id,
+            client_data_hash,
+            allow_credentials: credential_ids(&options.allowCredentials),
+            user_verification: user_verification_requirement(&options.userVerification),
+        };
+
+        // Step 18. Start the authenticatorGetAssertion operation on the authenticators of the
+        // embedder, the result is handled by `handle_assertion`.
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let task_source = global
+            .task_manager()
+            .dom_manipulation_task_source()
+            .to_sendable();
+        let mut pending = Some((
+            Trusted::new(self),
+            TrustedPromise::new(promise.clone()),
+            client_data_json,
+        ));
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |message| {
+                let Some((container, promise, client_data_json)) = pending.take() else {
+                    return;
+                };
+                let result = message.unwrap_or(Err(WebAuthnError::NotAllowed));
+                task_source.queue(task!(public_key_credential_asserted: move || {
+                    container.root().handle_assertion(
+                        result,
+                        &client_data_json,
+                        &promise.root(),
+                        CanGc::note(),
+                    );
+                }));
+            }),
+        );
+        global.send_to_embedder(EmbedderMsg::GetPublicKeyCredential(
+            window.webview_id(),
+            parameters,
+            sender,
+        ));
+        Ok(())
+    }
+
+    /// Step 22 of <https://w3c.github.io/webauthn/#sctn-createCredential>, which constructs
+    /// the new credential once an authenticator has created it.
+    fn handle_attestation(
+        &self,
+        result: Result<AuthenticatorAttestation, WebAuthnError>,
+        client_data_json: &[u8],
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        let attestation = match result {
+            Ok(attestation) => attestation,
+            Err(error) => return promise.reject_error(webauthn_error(error), can_gc),
+        };
+        let global = self.global();
+        let response = AuthenticatorAttestationResponse::new(
+            &global,
+            client_data_json,
+            &attestation.attestation_object,
+            attestation
+                .transports
+                .into_iter()
+                .map(DOMString::from)
+                .collect(),
+            can_gc,
+        );
+        let credential = PublicKeyCredential::new(
+            &global,
+            &attestation.credential_id,
+            response.upcast(),
+            attestation.platform_attachment,
+            can_gc,
+        );
+        promise.resolve_native(&credential, can_gc);
+    }
+
+    /// Step 20 of <https://w3c.github.io/webauthn/#sctn-getAssertion>, which constructs the
+    /// credential once an authenticator has signed the challenge with it.
+    fn handle_assertion(
+        &self,
+        result: Result<AuthenticatorAssertion, WebAuthnError>,
+        client_data_json: &[u8],
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        let assertion = match result {
+            Ok(assertion) => assertion,
+            Err(error) => return promise.reject_error(webauthn_error(error), can_gc),
+        };
+        let global = self.global();
+        let response = AuthenticatorAssertionResponse::new(
+            &global,
+            client_data_json,
+            &assertion.authenticator_data,
+            &assertion.signature,
+            assertion.user_handle.as_deref(),
+            can_gc,
+        );
+        let credential = PublicKeyCredential::new(
+            &global,
+            &assertion.credential_id,
+            response.upcast(),
+            assertion.platform_attachment,
+            can_gc,
+        );
+        promise.resolve_native(&credential, can_gc);
+    }
+}
+
+impl CredentialsContainerMethods<crate::DomTypeHolder> for CredentialsContainer {
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-get>
+    fn Get(
+        &self,
+        options: &RootedTraceableBox<CredentialRequestOptions>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. If the relevant global object's associated Document is not fully active,
+        // then return a promise rejected with an "InvalidStateError".
+        if !global.as_window().Document().is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 3. If options.signal is aborted, then return a promise rejected with
+        // options.signal's abort reason.
+        if options
+            .signal
+            .as_ref()
+            .is_some_and(|signal| signal.aborted())
+        {
+            promise.reject_error(Error::Abort, can_gc);
+            return promise;
+        }
+
+        // Step 4. Let interfaces be the set of options' relevant credential interface
+        // objects. Public key credentials are the only ones that are supported.
+        // <https://w3c.github.io/webappsec-credential-management/#algorithm-request>
+        let Some(public_key) = options.publicKey.as_ref() else {
+            promise.reject_error(Error::NotSupported, can_gc);
+            return promise;
+        };
+        if let Err(error) = self.get_public_key_credential(public_key, &promise) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-store>
+    fn Store(&self, _credential: &Credential, can_gc: CanGc) -> Rc<Promise> {
+        // Public key credentials can't be stored, they are stored by the authenticator that
+        // creates them.
+        // <https://w3c.github.io/webauthn/#sctn-storeCredential>
+        let promise = Promise::new(&self.global(), can_gc);
+        promise.reject_error(Error::NotSupported, can_gc);
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-create>
+    fn Create(
+        &self,
+        options: &RootedTraceableBox<CredentialCreationOptions>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. If the relevant global object's associated Document is not fully active,
+        // then return a promise rejected with an "InvalidStateError".
+        if !global.as_window().Document().is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 3. If options.signal is aborted, then return a promise rejected with
+        // options.signal's abort reason.
+        if options
+            .signal
+            .as_ref()
+            .is_some_and(|signal| signal.aborted())
+        {
+            promise.reject_error(Error::Abort, can_gc);
+            return promise;
+        }
+
+        // Step 4. Let interfaces be the set of options' relevant credential interface
+        // objects. Public key credentials are the only ones that are supported.
+        // <https://w3c.github.io/webappsec-credential-management/#algorithm-create>
+        let Some(public_key) = options.publicKey.as_ref() else {
+            promise.reject_error(Error::NotSupported, can_gc);
+            return promise;
+        };
+        if let Err(error) = self.create_public_key_credential(public_key, &promise) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-preventsilentaccess>
+    fn PreventSilentAccess(&self, can_gc: CanGc) -> Rc<Promise> {
+        // Public key credentials are never used without mediation, so there is nothing to
+        // prevent.
+        let promise = Promise::new(&self.global(), can_gc);
+        promise.resolve_native(&(), can_gc);
+        promise
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#same-origin-with-its-ancestors>
+fn is_same_origin_with_its_ancestors(window: &Window) -> bool {
+    let window_proxy = window.window_proxy();
+    let mut ancestor = window_proxy.parent();
+    while let Some(window_proxy) = ancestor {
+        // Documents of other origins are in other script threads.
+        match window_proxy.document() {
+            Some(document) if document.origin().same_origin(window.origin()) => {},
+            _ => return false,
+        }
+        ancestor = window_proxy.parent();
+    }
+    true
+}
+
+/// Validate the RP ID that was given against the origin, or default to the effective
+/// domain of the origin, as in steps 6-8 of
+/// <https://w3c.github.io/webauthn/#sctn-createCredential>.
+fn relying_party_id(origin: &ImmutableOrigin, rp_id: Option<&str>) -> Result<String, Error> {
+    // Step 6. If callerOrigin is an opaque origin, throw a "NotAllowedError".
+    if !origin.is_tuple() {
+        return Err(Error::NotAllowed);
+    }
+
+    // Step 7. Let effectiveDomain be the callerOrigin's effective domain. If
+    // effectiveDomain is not a valid domain, then throw a "SecurityError".
+    let effective_domain = match origin.host() {
+        Some(host @ Host::Domain(_)) => host.clone(),
+        _ => return Err(Error::Security),
+    };
+
+    // Step 8. If options.rp.id is present, and is not a registrable domain suffix of and
+    // is not equal to effectiveDomain, throw a "SecurityError". Otherwise set it to
+    // effectiveDomain.
+    match rp_id {
+        Some(rp_id) => get_registrable_domain_suffix_of_or_is_equal_to(rp_id, effective_domain)
+            .map(|host| host.to_string())
+            .ok_or(Error::Security),
+        None => Ok(effective_domain.to_string()),
+    }
+}
+
+/// The JSON-compatible serialization of the client data,
+/// <https://w3c.github.io/webauthn/#clientdatajson-serialization>.
+fn collected_client_data(
+    client_data_type: &str,
+    challenge: &ArrayBufferViewOrArrayBuffer,
+    origin: &ImmutableOrigin,
+) -> Vec<u8> {
+    let challenge =
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(buffer_source_bytes(challenge));
+    let origin =
+        serde_json::to_string(&origin.ascii_serialization()).expect("Failed to serialize origin.");
+    // Cross-origin calls are not allowed, so crossOrigin is always false.
+    format!(
+        r#"{{"type":"{client_data_type}","challenge":"{challenge}","origin":{origin},"crossOrigin":false}}"#
+    )
+    .into_bytes()
+}
+
+fn buffer_source_bytes(buffer_source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match buffer_source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.to_vec(),
+    }
+}
+
+/// The ids of the given credentials whose type is "public-key".
+fn credential_ids(descriptors: &[PublicKeyCredentialDescriptor]) -> Vec<Vec<u8>> {
+    descriptors
+        .iter()
+        .filter(|descriptor| &*descriptor.type_ == "public-key")
+        .map(|descriptor| buffer_source_bytes(&descriptor.id))
+        .collect()
+}
+
+/// <https://w3c.github.io/webauthn/#enumdef-userverificationrequirement>
+fn user_verification_requirement(user_verification: &DOMString) -> UserVerificationRequirement {
+    // Unknown values are ignored, which means the default of "preferred".
+    match &**user_verification {
+        "required" => UserVerificationRequirement::Required,
+        "discouraged" => UserVerificationRequirement::Discouraged,
+        _ => UserVerificationRequirement::Preferred,
+    }
+}
+
+fn webauthn_error(error: WebAuthnError) -> Error {
+    match error {
+        WebAuthnError::NotAllowed => Error::NotAllowed,
+        WebAuthnError::InvalidState => Error::InvalidState,
+        WebAuthnError::NotSupported => Error::NotSupported,
+    }
+}
@@ -16,15 +16,18 @@ use content_security_policy::{
 use http::header::{HeaderMap, HeaderValue, ValueIter};
 use hyper_serde::Serde;
 use js::rust::describe_scripted_caller;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::csppolicyviolationreport::CSPViolationReportBuilder;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::node::{Node, NodeTraits};
 use crate::dom::window::Window;
+use crate::dom::windowproxy::WindowProxy;
 use crate::security_manager::CSPViolationReportTask;
 
 pub(crate) trait CspReporting {
@@ -61,6 +64,7 @@ pub(crate) trait CspReporting {
         sink_group: &str,
         source: &str,
     ) -> bool;
+    fn is_framing_blocked_by_frame_ancestors(&self, window: &Window) -> bool;
 }
 
 impl CspReporting for Option<CspList> {
@@ -196,6 +200,117 @@ impl CspReporting for Option<CspList> {
 
         allowed_by_csp == CheckResult::Blocked
     }
+
+    /// <https://w3c.github.io/webappsec-csp/#frame-ancestors-navigation-response>
+    fn is_framing_blocked_by_frame_ancestors(&self, window: &Window) -> bool {
+        let Some(csp_list) = self else {
+            return false;
+        };
+        let window_proxy = window.window_proxy();
+        // Step 1. If navigable is not a child navigable, return "Allowed".
+        if window_proxy.parent().is_none() {
+            return false;
+        }
+        let global = window.as_global_scope();
+        let self_origin = global.origin().immutable().clone();
+        let document = window.Document();
+        let mut blocked = false;
+        for policy in csp_list.0.iter() {
+            let Some(directive) = policy
+                .directive_set
+                .iter()
+                .find(|directive| directive.name == "frame-ancestors")
+            else {
+                continue;
+            };
+            // Step 2. Let current be navigable.
+            // Step 3. While current has a parent:
+            let mut current = window_proxy.parent();
+            let mut depth = 0;
+            while let Some(ancestor) = current {
+                // Step 3.2. Let origin be the result of executing the URL parser on the
+                // ASCII serialization of parent’s active document’s origin.
+                let origin = ancestor_origin(&document, ancestor, depth);
+                // Step 3.3. If § 6.7.2.5 Does url match source list in origin with redirect
+                // count? returns Does Not Match when executed upon origin, this directive’s
+                // value, self’s policy’s self-origin, and 0, return "Blocked".
+                if !does_origin_match_source_list(&origin, &directive.value, &self_origin) {
+                    let report = CSPViolationReportBuilder::default()
+                        .resource(origin.ascii_serialization())
+                        .effective_directive(directive.name.clone())
+                        .original_policy(policy.to_string())
+                        .report_only(policy.disposition == PolicyDisposition::Report)
+                        .build(global);
+                    let task = CSPViolationReportTask::new(
+                        Trusted::new(global),
+                        Trusted::new(document.upcast()),
+                        report,
+                        policy.clone(),
+                    );
+                    global
+                        .task_manager()
+                        .dom_manipulation_task_source()
+                        .queue(task);
+                    if policy.disposition == PolicyDisposition::Enforce {
+                        blocked = true;
+                    }
+                    break;
+                }
+                // Step 3.4. Set current to parent.
+                current = ancestor.parent();
+                depth += 1;
+            }
+        }
+        // Step 4. Return "Allowed".
+        blocked
+    }
+}
+
+/// The origin of the ancestor at the given depth, where the parent is at depth 0. Ancestors
+/// living in another script thread do not expose their document here, so use the origin the
+/// constellation recorded for them when `document` was loaded.
+fn ancestor_origin(document: &Document, ancestor: &WindowProxy, depth: usize) -> ImmutableOrigin {
+    ancestor
+        .document()
+        .map(|ancestor_document| ancestor_document.origin().immutable().clone())
+        .or_else(|| document.ancestor_origin(depth))
+        .unwrap_or_else(ImmutableOrigin::new_opaque)
+}
+
+/// <https://w3c.github.io/webappsec-csp/#match-url-to-source-list>
+///
+/// The source matching of the CSP crate is only reachable through its request checks, so this
+/// runs the pre-request check of a `frame-src` directive with the same source list, which
+/// matches the request URL against it with the request origin as self-origin.
+fn does_origin_match_source_list(
+    origin: &ImmutableOrigin,
+    source_list: &[String],
+    self_origin: &ImmutableOrigin,
+) -> bool {
+    // An opaque origin serializes to "null", which is not a URL that any expression matches.
+    if !origin.is_tuple() {
+        return false;
+    }
+    let Ok(url) = ServoUrl::parse(&origin.ascii_serialization()) else {
+        return false;
+    };
+    let csp_list = CspList::parse(
+        &format!("frame-src {}", source_list.join(" ")),
+        PolicySource::Header,
+        PolicyDisposition::Enforce,
+    );
+    let request = Request {
+        url: url.into_url(),
+        origin: self_origin.clone().into_url_origin(),
+        redirect_count: 0,
+        destination: Destination::IFrame,
+        initiator: Initiator::None,
+        nonce: "".to_owned(),
+        integrity_metadata: "".to_owned(),
+        parser_metadata: ParserMetadata::None,
+    };
+    let (result, _) = csp_list.should_request_be_blocked(&request);
+    result == CheckResult::Allowed
 }
 
 pub(crate) struct SourcePosition {
@@ -214,7 +329,7 @@ pub(crate) trait GlobalCspReporting {
 }
 
 #[allow(unsafe_code)]
-fn compute_scripted_caller_source_position() -> SourcePosition {
+pub(crate) fn compute_scripted_caller_source_position() -> SourcePosition {
     let scripted_caller =
         unsafe { describe_scripted_caller(*GlobalScope::get_cx()) }.unwrap_or_default();
 
@@ -340,3 +455,83 @@ pub(crate) fn parse_csp_list_from_metadata(headers: &Option<Serde<HeaderMap>>) -
         PolicyDisposition::Report,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use servo_url::{ImmutableOrigin, ServoUrl};
+
+    use super::does_origin_match_source_list;
+
+    fn origin(url: &str) -> ImmutableOrigin {
+        ServoUrl::parse(url).unwrap().origin()
+    }
+
+    fn matches(url: &str, source_list: &[&str], self_url: &str) -> bool {
+        let source_list: Vec<String> = source_list.iter().map(|s| s.to_string()).collect();
+        does_origin_match_source_list(&origin(url), &source_list, &origin(self_url))
+    }
+
+    #[test]
+    fn test_frame_ancestors_self() {
+        let self_url = "https://example.com/";
+        assert!(matches("https://example.com/", &["'self'"], self_url));
+        assert!(matches(
+            "http://example.com/",
+            &["'self'"],
+            "http://example.com/"
+        ));
+        assert!(matches(
+            "https://example.com/",
+            &["'self'"],
+            "http://example.com/"
+        ));
+        assert!(!matches("https://example.com:8443/", &["'self'"], self_url));
+        assert!(!matches(
+            "https://example.com/",
+            &["'self'"],
+            "https://example.com:8443/"
+        ));
+        assert!(!matches("https://other.com/", &["'self'"], self_url));
+    }
+
+    #[test]
+    fn test_frame_ancestors_host_sources() {
+        let self_url = "https://example.com/";
+        assert!(matches(
+            "https://other.com/",
+            &["https://other.com"],
+            self_url
+        ));
+        assert!(matches("https://a.other.com/", &["*.other.com"], self_url));
+        assert!(matches(
+            "https://other.com:8443/",
+            &["other.com:*"],
+            self_url
+        ));
+        assert!(!matches(
+            "https://other.com:8443/",
+            &["other.com"],
+            self_url
+        ));
+        assert!(matches("https://other.com/", &["https:"], self_url));
+        assert!(matches("https://other.com/", &["*"], self_url));
+        assert!(!matches(
+            "https://other.com/",
+            &["https://third.com"],
+            self_url
+        ));
+    }
+
+    #[test]
+    fn test_frame_ancestors_none_and_opaque() {
+        let self_url = "https://example.com/";
+        assert!(!matches("https://example.com/", &["'none'"], self_url));
+        assert!(!matches("https://example.com/", &[], self_url));
+        let opaque = ImmutableOrigin::new_opaque();
+        assert!(!does_origin_match_source_list(
+            &opaque,
+            &["*".to_owned()],
+            &origin(self_url)
+        ));
+    }
+}
@@ -2,38 +2,37 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use malloc_size_of_derive::MallocSizeOf;
 use net_traits::request::Referrer;
 use serde::Serialize;
 use servo_url::ServoUrl;
 
 use crate::conversions::Convert;
-use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBody;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
-use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::ReportBody;
 use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::{
     SecurityPolicyViolationEventDisposition, SecurityPolicyViolationEventInit,
 };
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::reportingobserver::ReportingObserver;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, MallocSizeOf, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct SecurityPolicyViolationReport {
-    sample: Option<String>,
+    pub(crate) sample: Option<String>,
     #[serde(rename = "blockedURL")]
-    blocked_url: String,
-    referrer: String,
-    status_code: u16,
+    pub(crate) blocked_url: String,
+    pub(crate) referrer: String,
+    pub(crate) status_code: u16,
     #[serde(rename = "documentURL")]
-    document_url: String,
-    source_file: String,
-    violated_directive: String,
-    effective_directive: String,
-    line_number: u32,
-    column_number: u32,
-    original_policy: String,
+    pub(crate) document_url: String,
+    pub(crate) source_file: String,
+    pub(crate) violated_directive: String,
+    pub(crate) effective_directive: String,
+    pub(crate) line_number: u32,
+    pub(crate) column_number: u32,
+    pub(crate) original_policy: String,
     #[serde(serialize_with = "serialize_disposition")]
-    disposition: SecurityPolicyViolationEventDisposition,
+    pub(crate) disposition: SecurityPolicyViolationEventDisposition,
 }
 
 #[derive(Serialize)]
@@ -80,28 +79,6 @@ impl Convert<SecurityPolicyViolationEventInit> for SecurityPolicyViolationReport
     }
 }
 
-impl Convert<CSPViolationReportBody> for SecurityPolicyViolationReport {
-    fn convert(self) -> CSPViolationReportBody {
-        CSPViolationReportBody {
-            sample: self.sample.map(|s| s.into()),
-            blockedURL: Some(self.blocked_url.into()),
-            // TODO(37328): Why does /content-security-policy/reporting-api/
-            // report-to-directive-allowed-in-meta.https.sub.html expect this to be
-            // empty, yet the spec expects us to copy referrer from SecurityPolicyViolationReport
-            referrer: Some("".to_owned().into()),
-            statusCode: self.status_code,
-            documentURL: self.document_url.into(),
-            sourceFile: Some(self.source_file.into()),
-            effectiveDirective: self.effective_directive.into(),
-            lineNumber: Some(self.line_number),
-            columnNumber: Some(self.column_number),
-            originalPolicy: self.original_policy.into(),
-            disposition: self.disposition,
-            parent: ReportBody::empty(),
-        }
-    }
-}
-
 /// <https://www.w3.org/TR/CSP/#deprecated-serialize-violation>
 impl From<SecurityPolicyViolationReport> for CSPReportUriViolationReportBody {
     fn from(value: SecurityPolicyViolationReport) -> Self {
@@ -0,0 +1,104 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBodyMethods;
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::csppolicyviolationreport::SecurityPolicyViolationReport;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/webappsec-csp/#cspviolationreportbody>
+#[dom_struct]
+pub(crate) struct CSPViolationReportBody {
+    report_body: ReportBody,
+    #[no_trace]
+    report: SecurityPolicyViolationReport,
+}
+
+impl CSPViolationReportBody {
+    fn new_inherited(report: SecurityPolicyViolationReport) -> CSPViolationReportBody {
+        CSPViolationReportBody {
+            report_body: ReportBody::new_inherited(),
+            report,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        report: SecurityPolicyViolationReport,
+        can_gc: CanGc,
+    ) -> DomRoot<CSPViolationReportBody> {
+        reflect_dom_object(
+            Box::new(CSPViolationReportBody::new_inherited(report)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl CSPViolationReportBodyMethods<crate::DomTypeHolder> for CSPViolationReportBody {
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-documenturl>
+    fn DocumentURL(&self) -> USVString {
+        USVString(self.report.document_url.clone())
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-referrer>
+    fn GetReferrer(&self) -> Option<USVString> {
+        // TODO(37328): Why does /content-security-policy/reporting-api/
+        // report-to-directive-allowed-in-meta.https.sub.html expect this to be
+        // empty, yet the spec expects us to copy referrer from SecurityPolicyViolationReport
+        Some(USVString::default())
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-blockedurl>
+    fn GetBlockedURL(&self) -> Option<USVString> {
+        Some(USVString(self.report.blocked_url.clone()))
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-effectivedirective>
+    fn EffectiveDirective(&self) -> DOMString {
+        self.report.effective_directive.clone().into()
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-originalpolicy>
+    fn OriginalPolicy(&self) -> DOMString {
+        self.report.original_policy.clone().into()
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sourcefile>
+    fn GetSourceFile(&self) -> Option<USVString> {
+        Some(USVString(self.report.source_file.clone()))
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sample>
+    fn GetSample(&self) -> Option<DOMString> {
+        self.report.sample.clone().map(DOMString::from)
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-disposition>
+    fn Disposition(&self) -> SecurityPolicyViolationEventDisposition {
+        self.report.disposition
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-statuscode>
+    fn StatusCode(&self) -> u16 {
+        self.report.status_code
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-linenumber>
+    fn GetLineNumber(&self) -> Option<u32> {
+        Some(self.report.line_number)
+    }
+
+    /// <https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-columnnumber>
+    fn GetColumnNumber(&self) -> Option<u32> {
+        Some(self.report.column_number)
+    }
+}
@@ -16,6 +16,7 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::highlightregistry::HighlightRegistry;
 use crate::dom::window::Window;
 use crate::dom::worklet::Worklet;
 
@@ -81,4 +82,9 @@ impl CSSMethods<crate::DomTypeHolder> for CSS {
     fn PaintWorklet(win: &Window) -> DomRoot<Worklet> {
         win.paint_worklet()
     }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-css-highlights>
+    fn Highlights(win: &Window) -> DomRoot<HighlightRegistry> {
+        win.highlight_registry()
+    }
 }
@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::animation::{Animation, AnimationKey, AnimationSource};
+use crate::dom::bindings::codegen::Bindings::CSSAnimationBinding::CSSAnimationMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+#[dom_struct]
+pub(crate) struct CSSAnimation {
+    animation: Animation,
+}
+
+impl CSSAnimation {
+    pub(crate) fn new(window: &Window, key: AnimationKey, can_gc: CanGc) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self {
+                animation: Animation::new_inherited(key),
+            }),
+            window,
+            can_gc,
+        )
+    }
+}
+
+impl CSSAnimationMethods<crate::DomTypeHolder> for CSSAnimation {
+    // https://drafts.csswg.org/css-animations-2/#dom-cssanimation-animationname
+    fn AnimationName(&self) -> DOMString {
+        match &self.animation.key().source {
+            AnimationSource::CSSAnimation(name) => DOMString::from(&**name),
+            AnimationSource::CSSTransition(_) => unreachable!("CSSAnimation for a transition"),
+        }
+    }
+}
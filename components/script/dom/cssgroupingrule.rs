@@ -4,7 +4,6 @@
 
 use dom_struct::dom_struct;
 use style::shared_lock::SharedRwLock;
-use style::stylesheets::{CssRuleType, CssRuleTypes};
 
 use crate::dom::bindings::codegen::Bindings::CSSGroupingRuleBinding::CSSGroupingRuleMethods;
 use crate::dom::bindings::error::{ErrorResult, Fallible};
@@ -16,6 +15,7 @@ use crate::dom::cssconditionrule::CSSConditionRule;
 use crate::dom::csslayerblockrule::CSSLayerBlockRule;
 use crate::dom::cssrule::CSSRule;
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::cssscoperule::CSSScopeRule;
 use crate::dom::cssstylerule::CSSStyleRule;
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::script_runtime::CanGc;
@@ -41,6 +41,8 @@ impl CSSGroupingRule {
                 rule.clone_rules()
             } else if let Some(rule) = self.downcast::<CSSLayerBlockRule>() {
                 rule.clone_rules()
+            } else if let Some(rule) = self.downcast::<CSSScopeRule>() {
+                rule.clone_rules()
             } else if let Some(rule) = self.downcast::<CSSStyleRule>() {
                 rule.ensure_rules()
             } else {
@@ -49,6 +51,7 @@ impl CSSGroupingRule {
             CSSRuleList::new(
                 self.global().as_window(),
                 parent_stylesheet,
+                Some(self.upcast()),
                 RulesSource::Rules(rules),
                 can_gc,
             )
@@ -73,13 +76,8 @@ impl CSSGroupingRuleMethods<crate::DomTypeHolder> for CSSGroupingRule {
 
     // https://drafts.csswg.org/cssom/#dom-cssgroupingrule-insertrule
     fn InsertRule(&self, rule: DOMString, index: u32, can_gc: CanGc) -> Fallible<u32> {
-        // TODO: this should accumulate the rule types of all ancestors.
-        let rule_type = self.cssrule.as_specific().ty();
-        let containing_rule_types = CssRuleTypes::from(rule_type);
-        let parse_relative_rule_type = match rule_type {
-            CssRuleType::Style | CssRuleType::Scope => Some(rule_type),
-            _ => None,
-        };
+        let (containing_rule_types, parse_relative_rule_type) =
+            self.cssrule.containing_rule_types();
         self.rulelist(can_gc).insert_rule(
             &rule,
             index,
@@ -67,6 +67,7 @@ impl CSSKeyframesRule {
             CSSRuleList::new(
                 self.global().as_window(),
                 parent_stylesheet,
+                Some(self.upcast()),
                 RulesSource::Keyframes(self.keyframesrule.clone()),
                 can_gc,
             )
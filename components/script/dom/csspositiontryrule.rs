@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::{CssRuleType, PositionTryRule};
+
+use crate::dom::bindings::codegen::Bindings::CSSPositionTryRuleBinding::CSSPositionTryRuleMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
+use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+#[dom_struct]
+pub(crate) struct CSSPositionTryRule {
+    cssrule: CSSRule,
+    #[ignore_malloc_size_of = "Arc"]
+    #[no_trace]
+    positiontryrule: Arc<Locked<PositionTryRule>>,
+    style_decl: MutNullableDom<CSSStyleDeclaration>,
+}
+
+impl CSSPositionTryRule {
+    pub(crate) fn new_inherited(
+        parent_stylesheet: &CSSStyleSheet,
+        positiontryrule: Arc<Locked<PositionTryRule>>,
+    ) -> Self {
+        Self {
+            cssrule: CSSRule::new_inherited(parent_stylesheet),
+            positiontryrule,
+            style_decl: Default::default(),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        window: &Window,
+        parent_stylesheet: &CSSStyleSheet,
+        positiontryrule: Arc<Locked<PositionTryRule>>,
+        can_gc: CanGc,
+    ) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(Self::new_inherited(parent_stylesheet, positiontryrule)),
+            window,
+            can_gc,
+        )
+    }
+}
+
+impl SpecificCSSRule for CSSPositionTryRule {
+    fn ty(&self) -> CssRuleType {
+        CssRuleType::PositionTry
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        self.positiontryrule
+            .read_with(&guard)
+            .to_css_string(&guard)
+            .into()
+    }
+}
+
+impl CSSPositionTryRuleMethods<crate::DomTypeHolder> for CSSPositionTryRule {
+    /// <https://drafts.csswg.org/css-anchor-position-1/#dom-csspositiontryrule-name>
+    fn Name(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        DOMString::from(self.positiontryrule.read_with(&guard).name.0.to_string())
+    }
+
+    /// <https://drafts.csswg.org/css-anchor-position-1/#dom-csspositiontryrule-style>
+    fn Style(&self, can_gc: CanGc) -> DomRoot<CSSStyleDeclaration> {
+        self.style_decl.or_init(|| {
+            let guard = self.cssrule.shared_lock().read();
+            CSSStyleDeclaration::new(
+                self.global().as_window(),
+                CSSStyleOwner::CSSRule(
+                    Dom::from_ref(self.upcast()),
+                    self.positiontryrule.read_with(&guard).block.clone(),
+                ),
+                None,
+                CSSModificationAccess::ReadWrite,
+                can_gc,
+            )
+        })
+    }
+}
@@ -6,12 +6,12 @@ use std::cell::Cell;
 
 use dom_struct::dom_struct;
 use style::shared_lock::SharedRwLock;
-use style::stylesheets::{CssRule as StyleCssRule, CssRuleType};
+use style::stylesheets::{CssRule as StyleCssRule, CssRuleType, CssRuleTypes};
 
 use crate::dom::bindings::codegen::Bindings::CSSRuleBinding::CSSRuleMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::Reflector;
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssfontfacerule::CSSFontFaceRule;
 use crate::dom::cssimportrule::CSSImportRule;
@@ -22,6 +22,8 @@ use crate::dom::csslayerstatementrule::CSSLayerStatementRule;
 use crate::dom::cssmediarule::CSSMediaRule;
 use crate::dom::cssnamespacerule::CSSNamespaceRule;
 use crate::dom::cssnesteddeclarations::CSSNestedDeclarations;
+use crate::dom::csspositiontryrule::CSSPositionTryRule;
+use crate::dom::cssscoperule::CSSScopeRule;
 use crate::dom::cssstylerule::CSSStyleRule;
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::csssupportsrule::CSSSupportsRule;
@@ -37,6 +39,9 @@ pub(crate) struct CSSRule {
     /// We keep parent_stylesheet in that case because insertRule needs it
     /// for the stylesheet’s base URL and namespace prefixes.
     parent_stylesheet_removed: Cell<bool>,
+
+    /// <https://drafts.csswg.org/cssom/#concept-css-rule-parent-css-rule>
+    parent_rule: MutNullableDom<CSSRule>,
 }
 
 impl CSSRule {
@@ -46,6 +51,7 @@ impl CSSRule {
             reflector_: Reflector::new(),
             parent_stylesheet: Dom::from_ref(parent_stylesheet),
             parent_stylesheet_removed: Cell::new(false),
+            parent_rule: MutNullableDom::new(None),
         }
     }
 
@@ -72,6 +78,10 @@ impl CSSRule {
             rule as &dyn SpecificCSSRule
         } else if let Some(rule) = self.downcast::<CSSNestedDeclarations>() {
             rule as &dyn SpecificCSSRule
+        } else if let Some(rule) = self.downcast::<CSSScopeRule>() {
+            rule as &dyn SpecificCSSRule
+        } else if let Some(rule) = self.downcast::<CSSPositionTryRule>() {
+            rule as &dyn SpecificCSSRule
         } else {
             unreachable!()
         }
@@ -125,9 +135,16 @@ impl CSSRule {
             StyleCssRule::FontPaletteValues(_) => unimplemented!(), // TODO
             StyleCssRule::Property(_) => unimplemented!(),          // TODO
             StyleCssRule::Margin(_) => unimplemented!(),            // TODO
-            StyleCssRule::Scope(_) => unimplemented!(),             // TODO
-            StyleCssRule::StartingStyle(_) => unimplemented!(),     // TODO
-            StyleCssRule::PositionTry(_) => unimplemented!(),       // TODO
+            StyleCssRule::Scope(s) => {
+                DomRoot::upcast(CSSScopeRule::new(window, parent_stylesheet, s, can_gc))
+            },
+            StyleCssRule::StartingStyle(_) => unimplemented!(), // TODO
+            StyleCssRule::PositionTry(s) => DomRoot::upcast(CSSPositionTryRule::new(
+                window,
+                parent_stylesheet,
+                s,
+                can_gc,
+            )),
             StyleCssRule::NestedDeclarations(s) => DomRoot::upcast(CSSNestedDeclarations::new(
                 window,
                 parent_stylesheet,
@@ -140,7 +157,7 @@ impl CSSRule {
     /// Sets owner sheet/rule to null
     pub(crate) fn detach(&self) {
         self.deparent();
-        // should set parent rule to None when we add parent rule support
+        self.parent_rule.set(None);
     }
 
     /// Sets owner sheet to null (and does the same for all children)
@@ -156,6 +173,34 @@ impl CSSRule {
         &self.parent_stylesheet
     }
 
+    pub(crate) fn parent_rule(&self) -> Option<DomRoot<CSSRule>> {
+        self.parent_rule.get()
+    }
+
+    pub(crate) fn set_parent_rule(&self, parent_rule: Option<&CSSRule>) {
+        self.parent_rule.set(parent_rule);
+    }
+
+    /// The types of this rule and of all of its ancestor rules, along with the type of
+    /// the nearest of them that relative selectors in nested rules are resolved against,
+    /// which is needed to parse rules inserted into it.
+    pub(crate) fn containing_rule_types(&self) -> (CssRuleTypes, Option<CssRuleType>) {
+        let mut containing_rule_types = CssRuleTypes::default();
+        let mut parse_relative_rule_type = None;
+        let mut rule = Some(DomRoot::from_ref(self));
+        while let Some(current) = rule {
+            let rule_type = current.as_specific().ty();
+            containing_rule_types.insert(rule_type);
+            if parse_relative_rule_type.is_none() &&
+                matches!(rule_type, CssRuleType::Style | CssRuleType::Scope)
+            {
+                parse_relative_rule_type = Some(rule_type);
+            }
+            rule = current.parent_rule();
+        }
+        (containing_rule_types, parse_relative_rule_type)
+    }
+
     pub(crate) fn shared_lock(&self) -> &SharedRwLock {
         &self.parent_stylesheet.style_stylesheet().shared_lock
     }
@@ -179,6 +224,11 @@ impl CSSRuleMethods<crate::DomTypeHolder> for CSSRule {
         }
     }
 
+    // https://drafts.csswg.org/cssom/#dom-cssrule-parentrule
+    fn GetParentRule(&self) -> Option<DomRoot<CSSRule>> {
+        self.parent_rule.get()
+    }
+
     // https://drafts.csswg.org/cssom/#dom-cssrule-csstext
     fn CssText(&self) -> DOMString {
         self.as_specific().get_css()
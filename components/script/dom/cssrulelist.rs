@@ -43,6 +43,9 @@ impl Convert<Error> for RulesMutateError {
 pub(crate) struct CSSRuleList {
     reflector_: Reflector,
     parent_stylesheet: Dom<CSSStyleSheet>,
+    /// The rule containing this list, which becomes the `parentRule` of its items, or
+    /// `None` for the top-level rules of a stylesheet.
+    parent_rule: Option<Dom<CSSRule>>,
     #[ignore_malloc_size_of = "Arc"]
     rules: RulesSource,
     dom_rules: DomRefCell<Vec<MutNullableDom<CSSRule>>>,
@@ -57,6 +60,7 @@ impl CSSRuleList {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn new_inherited(
         parent_stylesheet: &CSSStyleSheet,
+        parent_rule: Option<&CSSRule>,
         rules: RulesSource,
     ) -> CSSRuleList {
         let guard = parent_stylesheet.shared_lock().read();
@@ -78,6 +82,7 @@ impl CSSRuleList {
         CSSRuleList {
             reflector_: Reflector::new(),
             parent_stylesheet: Dom::from_ref(parent_stylesheet),
+            parent_rule: parent_rule.map(Dom::from_ref),
             rules,
             dom_rules: DomRefCell::new(dom_rules),
         }
@@ -87,11 +92,16 @@ impl CSSRuleList {
     pub(crate) fn new(
         window: &Window,
         parent_stylesheet: &CSSStyleSheet,
+        parent_rule: Option<&CSSRule>,
         rules: RulesSource,
         can_gc: CanGc,
     ) -> DomRoot<CSSRuleList> {
         reflect_dom_object(
-            Box::new(CSSRuleList::new_inherited(parent_stylesheet, rules)),
+            Box::new(CSSRuleList::new_inherited(
+                parent_stylesheet,
+                parent_rule,
+                rules,
+            )),
             window,
             can_gc,
         )
@@ -145,6 +155,7 @@ impl CSSRuleList {
 
         let parent_stylesheet = &*self.parent_stylesheet;
         let dom_rule = CSSRule::new_specific(window, parent_stylesheet, new_rule, can_gc);
+        dom_rule.set_parent_rule(self.parent_rule.as_deref());
         self.dom_rules
             .borrow_mut()
             .insert(index, MutNullableDom::new(Some(&*dom_rule)));
@@ -196,7 +207,7 @@ impl CSSRuleList {
             rule.or_init(|| {
                 let parent_stylesheet = &self.parent_stylesheet;
                 let lock = parent_stylesheet.shared_lock();
-                match self.rules {
+                let dom_rule = match self.rules {
                     RulesSource::Rules(ref rules) => {
                         let rule = {
                             let guard = lock.read();
@@ -221,7 +232,9 @@ impl CSSRuleList {
                             can_gc,
                         ))
                     },
-                }
+                };
+                dom_rule.set_parent_rule(self.parent_rule.as_deref());
+                dom_rule
             })
         })
     }
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cssparser::ToCss;
+use dom_struct::dom_struct;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::{CssRuleType, CssRules, ScopeRule};
+
+use crate::dom::bindings::codegen::Bindings::CSSScopeRuleBinding::CSSScopeRuleMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssgroupingrule::CSSGroupingRule;
+use crate::dom::cssrule::SpecificCSSRule;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+#[dom_struct]
+pub(crate) struct CSSScopeRule {
+    cssgroupingrule: CSSGroupingRule,
+    #[ignore_malloc_size_of = "Arc"]
+    #[no_trace]
+    scoperule: Arc<ScopeRule>,
+}
+
+impl CSSScopeRule {
+    fn new_inherited(parent_stylesheet: &CSSStyleSheet, scoperule: Arc<ScopeRule>) -> Self {
+        CSSScopeRule {
+            cssgroupingrule: CSSGroupingRule::new_inherited(parent_stylesheet),
+            scoperule,
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        window: &Window,
+        parent_stylesheet: &CSSStyleSheet,
+        scoperule: Arc<ScopeRule>,
+        can_gc: CanGc,
+    ) -> DomRoot<CSSScopeRule> {
+        reflect_dom_object(
+            Box::new(CSSScopeRule::new_inherited(parent_stylesheet, scoperule)),
+            window,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn clone_rules(&self) -> Arc<Locked<CssRules>> {
+        self.scoperule.rules.clone()
+    }
+}
+
+impl SpecificCSSRule for CSSScopeRule {
+    fn ty(&self) -> CssRuleType {
+        CssRuleType::Scope
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssgroupingrule.shared_lock().read();
+        self.scoperule.to_css_string(&guard).into()
+    }
+}
+
+impl CSSScopeRuleMethods<crate::DomTypeHolder> for CSSScopeRule {
+    /// <https://drafts.csswg.org/css-cascade-6/#dom-cssscoperule-start>
+    fn GetStart(&self) -> Option<DOMString> {
+        self.scoperule
+            .bounds
+            .start
+            .as_ref()
+            .map(|selectors| DOMString::from_string(selectors.to_css_string()))
+    }
+
+    /// <https://drafts.csswg.org/css-cascade-6/#dom-cssscoperule-end>
+    fn GetEnd(&self) -> Option<DOMString> {
+        self.scoperule
+            .bounds
+            .end
+            .as_ref()
+            .map(|selectors| DOMString::from_string(selectors.to_css_string()))
+    }
+}
@@ -77,6 +77,16 @@ impl CSSStyleOwner {
                 let shared_lock = document.style_shared_lock();
                 let mut attr = el.style_attribute().borrow_mut().take();
                 let result = if attr.is_some() {
+                    // The declaration block may be shared with other elements that have
+                    // the same `style` attribute, so copy it before mutating it.
+                    if !attr.as_ref().unwrap().is_unique() {
+                        let pdb = attr
+                            .as_ref()
+                            .unwrap()
+                            .read_with(&shared_lock.read())
+                            .clone();
+                        attr = Some(Arc::new(shared_lock.wrap(pdb)));
+                    }
                     let lock = attr.as_ref().unwrap();
                     let mut guard = shared_lock.write();
                     let pdb = lock.write_with(&mut guard);
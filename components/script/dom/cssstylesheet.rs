@@ -124,6 +124,7 @@ impl CSSStyleSheet {
             CSSRuleList::new(
                 self.global().as_window(),
                 self,
+                None,
                 RulesSource::Rules(rules),
                 can_gc,
             )
@@ -88,6 +88,18 @@ impl DataTransfer {
     pub(crate) fn data_store(&self) -> Option<Ref<DragDataStore>> {
         Ref::filter_map(self.data_store.borrow(), |data_store| data_store.as_ref()).ok()
     }
+
+    /// Set the `dropEffect` attribute as the user agent does before dispatching a drag
+    /// event, regardless of the mode of the drag data store.
+    pub(crate) fn set_drop_effect(&self, value: &str) {
+        *self.drop_effect.borrow_mut() = DOMString::from(value);
+    }
+
+    /// Set the `effectAllowed` attribute as the user agent does before dispatching a drag
+    /// event, regardless of the mode of the drag data store.
+    pub(crate) fn set_effect_allowed(&self, value: &str) {
+        *self.effect_allowed.borrow_mut() = DOMString::from(value);
+    }
 }
 
 impl DataTransferMethods<crate::DomTypeHolder> for DataTransfer {
@@ -131,7 +143,7 @@ impl DataTransferMethods<crate::DomTypeHolder> for DataTransfer {
             .is_some_and(|data_store| data_store.mode() == Mode::ReadWrite) &&
             VALID_EFFECTS_ALLOWED.contains(&value.as_ref())
         {
-            *self.drop_effect.borrow_mut() = value;
+            *self.effect_allowed.borrow_mut() = value;
         }
     }
 
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::CompressionStreamBinding::CompressionFormat;
+use crate::dom::bindings::codegen::Bindings::DecompressionStreamBinding::DecompressionStreamMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::compressionstream::{CompressionDirection, CompressionTransformer};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::transformstream::TransformStream;
+use crate::dom::transformstreamdefaultcontroller::NativeTransformer;
+use crate::dom::writablestream::WritableStream;
+use crate::script_runtime::CanGc;
+
+/// <https://compression.spec.whatwg.org/#decompressionstream>
+#[dom_struct]
+pub(crate) struct DecompressionStream {
+    reflector_: Reflector,
+
+    /// <https://streams.spec.whatwg.org/#generictransformstream-transform>
+    transform: Dom<TransformStream>,
+}
+
+impl DecompressionStream {
+    fn new_inherited(transform: &TransformStream) -> DecompressionStream {
+        DecompressionStream {
+            reflector_: Reflector::new(),
+            transform: Dom::from_ref(transform),
+        }
+    }
+
+    fn new_with_proto(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        transform: &TransformStream,
+        can_gc: CanGc,
+    ) -> DomRoot<DecompressionStream> {
+        reflect_dom_object_with_proto(
+            Box::new(DecompressionStream::new_inherited(transform)),
+            global,
+            proto,
+            can_gc,
+        )
+    }
+}
+
+impl DecompressionStreamMethods<crate::DomTypeHolder> for DecompressionStream {
+    /// <https://compression.spec.whatwg.org/#dom-decompressionstream-decompressionstream>
+    fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        format: CompressionFormat,
+    ) -> Fallible<DomRoot<DecompressionStream>> {
+        // Step 1. If format is unsupported in DecompressionStream, then throw a TypeError.
+        // Note: this is done by the bindings.
+
+        // Step 2. Set this’s format to format.
+        // Step 3. Let transformAlgorithm be an algorithm which takes a chunk argument and
+        // runs the decompress and enqueue a chunk algorithm with this and chunk.
+        // Step 4. Let flushAlgorithm be an algorithm which takes no argument and runs the
+        // decompress flush and enqueue algorithm with this.
+        let transformer = CompressionTransformer::new(format, CompressionDirection::Decompress);
+
+        // Step 5. Set this’s transform to a new TransformStream.
+        let transform = TransformStream::new_with_proto(global, None, can_gc);
+
+        // Step 6. Set up this’s transform with transformAlgorithm set to transformAlgorithm
+        // and flushAlgorithm set to flushAlgorithm.
+        transform.set_up(
+            GlobalScope::get_cx(),
+            global,
+            NativeTransformer::Compression(transformer),
+            can_gc,
+        )?;
+
+        Ok(DecompressionStream::new_with_proto(
+            global, proto, &transform, can_gc,
+        ))
+    }
+
+    /// <https://streams.spec.whatwg.org/#dom-generictransformstream-readable>
+    fn Readable(&self) -> DomRoot<ReadableStream> {
+        self.transform.get_readable()
+    }
+
+    /// <https://streams.spec.whatwg.org/#dom-generictransformstream-writable>
+    fn Writable(&self) -> DomRoot<WritableStream> {
+        self.transform.get_writable()
+    }
+}
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread::{self, JoinHandle};
 
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
@@ -50,6 +50,7 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::reportingendpoint::ReportingEndpoint;
+use crate::dom::webglrenderingcontext::WebGLCommandSender;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::identityhub::IdentityHub;
 use crate::dom::worker::{TrustedWorkerAddress, Worker};
@@ -91,6 +92,31 @@ impl Drop for AutoWorkerReset<'_> {
     }
 }
 
+/// The number of dedicated worker threads running in this process.
+static RUNNING_DEDICATED_WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a dedicated worker thread as running in [`RUNNING_DEDICATED_WORKERS`] for as long
+/// as it is alive.
+struct RunningDedicatedWorker;
+
+impl RunningDedicatedWorker {
+    fn new() -> Self {
+        RUNNING_DEDICATED_WORKERS.fetch_add(1, Ordering::Relaxed);
+        RunningDedicatedWorker
+    }
+}
+
+impl Drop for RunningDedicatedWorker {
+    fn drop(&mut self) {
+        RUNNING_DEDICATED_WORKERS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The number of dedicated worker threads running in this process.
+pub(crate) fn running_dedicated_workers() -> usize {
+    RUNNING_DEDICATED_WORKERS.load(Ordering::Relaxed)
+}
+
 /// Messages sent from the owning global.
 pub(crate) enum DedicatedWorkerControlMsg {
     /// Shutdown the worker.
@@ -192,6 +218,8 @@ pub(crate) struct DedicatedWorkerGlobalScope {
     #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
     image_cache: Arc<dyn ImageCache>,
+    #[ignore_malloc_size_of = "Channels are hard"]
+    webgl_chan: Option<WebGLCommandSender>,
     #[no_trace]
     browsing_context: Option<BrowsingContextId>,
     /// A receiver of control messages,
@@ -258,6 +286,7 @@ impl DedicatedWorkerGlobalScope {
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        webgl_chan: Option<WebGLCommandSender>,
         browsing_context: Option<BrowsingContextId>,
         #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
         control_receiver: Receiver<DedicatedWorkerControlMsg>,
@@ -281,6 +310,7 @@ impl DedicatedWorkerGlobalScope {
             parent_event_loop_sender,
             worker: DomRefCell::new(None),
             image_cache,
+            webgl_chan,
             browsing_context,
             control_receiver,
         }
@@ -299,6 +329,7 @@ impl DedicatedWorkerGlobalScope {
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        webgl_chan: Option<WebGLCommandSender>,
         browsing_context: Option<BrowsingContextId>,
         #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
         control_receiver: Receiver<DedicatedWorkerControlMsg>,
@@ -317,6 +348,7 @@ impl DedicatedWorkerGlobalScope {
             receiver,
             closing,
             image_cache,
+            webgl_chan,
             browsing_context,
             #[cfg(feature = "webgpu")]
             gpu_id_hub,
@@ -346,6 +378,7 @@ impl DedicatedWorkerGlobalScope {
         worker_type: WorkerType,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        webgl_chan: Option<WebGLCommandSender>,
         browsing_context: Option<BrowsingContextId>,
         #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
         control_receiver: Receiver<DedicatedWorkerControlMsg>,
@@ -367,6 +400,7 @@ impl DedicatedWorkerGlobalScope {
             .name(format!("WW:{}", worker_url.debug_compact()))
             .spawn(move || {
                 thread_state::initialize(ThreadState::SCRIPT | ThreadState::IN_WORKER);
+                let _running_worker = RunningDedicatedWorker::new();
 
                 if let Some(webview_id) = webview_id {
                     WebViewId::install(webview_id);
@@ -446,6 +480,7 @@ impl DedicatedWorkerGlobalScope {
                     receiver,
                     closing,
                     image_cache,
+                    webgl_chan,
                     browsing_context,
                     #[cfg(feature = "webgpu")]
                     gpu_id_hub,
@@ -575,6 +610,10 @@ impl DedicatedWorkerGlobalScope {
         self.image_cache.clone()
     }
 
+    pub(crate) fn webgl_chan(&self) -> Option<WebGLCommandSender> {
+        self.webgl_chan.clone()
+    }
+
     pub(crate) fn event_loop_sender(&self) -> Option<ScriptEventLoopSender> {
         Some(ScriptEventLoopSender::DedicatedWorker {
             sender: self.own_sender.clone(),
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use malloc_size_of_derive::MallocSizeOf;
+use serde::Serialize;
+
+use crate::dom::bindings::codegen::Bindings::DeprecationReportBodyBinding::DeprecationReportBodyMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::script_runtime::CanGc;
+
+/// The use of a deprecated feature, which is reported with the "deprecation" type.
+///
+/// <https://wicg.github.io/deprecation-reporting/#deprecation-report>
+#[derive(Clone, Debug, MallocSizeOf, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeprecationReport {
+    /// A name identifying the deprecated feature.
+    pub(crate) id: String,
+    /// A human-readable description of the deprecation.
+    pub(crate) message: String,
+    pub(crate) source_file: Option<String>,
+    pub(crate) line_number: Option<u32>,
+    pub(crate) column_number: Option<u32>,
+}
+
+/// <https://wicg.github.io/deprecation-reporting/#deprecationreportbody>
+#[dom_struct]
+pub(crate) struct DeprecationReportBody {
+    report_body: ReportBody,
+    #[no_trace]
+    report: DeprecationReport,
+}
+
+impl DeprecationReportBody {
+    fn new_inherited(report: DeprecationReport) -> DeprecationReportBody {
+        DeprecationReportBody {
+            report_body: ReportBody::new_inherited(),
+            report,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        report: DeprecationReport,
+        can_gc: CanGc,
+    ) -> DomRoot<DeprecationReportBody> {
+        reflect_dom_object(
+            Box::new(DeprecationReportBody::new_inherited(report)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl DeprecationReportBodyMethods<crate::DomTypeHolder> for DeprecationReportBody {
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-id>
+    fn Id(&self) -> DOMString {
+        self.report.id.clone().into()
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-message>
+    fn Message(&self) -> DOMString {
+        self.report.message.clone().into()
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-sourcefile>
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.report.source_file.clone().map(DOMString::from)
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-linenumber>
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.report.line_number
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-columnnumber>
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.report.column_number
+    }
+}
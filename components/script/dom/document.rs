@@ -15,27 +15,32 @@ use std::str::FromStr;
 use std::sync::{LazyLock, Mutex};
 use std::time::{Duration, Instant};
 
+use app_units::Au;
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::WebViewId;
+use base::id::{OffscreenCanvasId, WebViewId};
 use canvas_traits::canvas::CanvasId;
 use canvas_traits::webgl::{self, WebGLContextId, WebGLMsg};
 use chrono::Local;
-use constellation_traits::{NavigationHistoryBehavior, ScriptToConstellationMessage};
+use compositing_traits::{IntersectionObservationTarget, NavigationTiming};
+use constellation_traits::{
+    IntersectionObservationChange, NavigationHistoryBehavior, ScriptToConstellationMessage,
+    SequentialFocusDirection,
+};
 use content_security_policy::{CspList, PolicyDisposition};
 use cookie::Cookie;
-use cssparser::match_ignore_ascii_case;
 use data_url::mime::Mime;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
 use embedder_traits::{
-    AllowOrDeny, AnimationState, CompositorHitTestResult, ContextMenuResult, EditingActionEvent,
-    EmbedderMsg, FocusSequenceNumber, ImeEvent, InputEvent, LoadStatus, MouseButton,
-    MouseButtonAction, MouseButtonEvent, ScrollEvent, TouchEvent, TouchEventType, TouchId,
-    UntrustedNodeAddress, WheelEvent,
+    AllowOrDeny, AnimationState, ClipboardContents, CompositorHitTestResult, ContextMenuResult,
+    EditingActionEvent, EmbedderMsg, ExternalDragEvent, FindInPageRequest, FindInPageResult,
+    FocusSequenceNumber, ImeEvent, InputEvent, LoadProgress, LoadStatus, MouseButton,
+    MouseButtonAction, MouseButtonEvent, PaintMetrics, ScrollEvent, TouchEvent, TouchEventType,
+    TouchId, UntrustedNodeAddress, WheelEvent,
 };
 use encoding_rs::{Encoding, UTF_8};
 use euclid::Point2D;
-use euclid::default::{Rect, Size2D};
+use euclid::default::{Rect, Size2D, Vector2D};
 use fnv::FnvHashMap;
 use html5ever::{LocalName, Namespace, QualName, local_name, ns};
 use hyper_serde::Serde;
@@ -43,18 +48,24 @@ use ipc_channel::ipc;
 use js::rust::{HandleObject, HandleValue};
 use keyboard_types::{Code, Key, KeyState, Modifiers};
 use layout_api::{
-    PendingRestyle, ReflowGoal, RestyleReason, TrustedNodeAddress, node_id_from_scroll_id,
+    HighlightKind, PendingRestyle, ReflowGoal, RestyleReason, TrustedNodeAddress,
+    node_id_from_scroll_id,
 };
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use metrics::{InteractiveFlag, InteractiveWindow, ProgressiveWebMetrics};
 use net_traits::CookieSource::NonHTTP;
 use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
 use net_traits::policy_container::PolicyContainer;
-use net_traits::pub_domains::is_pub_domain;
+use net_traits::pub_domains::{Site, is_pub_domain};
 use net_traits::request::{InsecureRequestsPolicy, RequestBuilder};
 use net_traits::response::HttpsState;
-use net_traits::{FetchResponseListener, IpcSend, ReferrerPolicy};
+use net_traits::{
+    CoreResourceMsg, FetchResponseListener, IpcSend, ReferrerPolicy, SpeculativeConnectionKind,
+};
 use percent_encoding::percent_decode;
 use profile_traits::ipc as profile_ipc;
+use profile_traits::mem::{Report, ReportKind};
+use profile_traits::path;
 use profile_traits::time::TimerMetadataFrameType;
 use regex::bytes::Regex;
 use script_bindings::interfaces::DocumentHelpers;
@@ -65,31 +76,35 @@ use servo_media::{ClientContextId, ServoMedia};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::attr::AttrValue;
 use style::context::QuirksMode;
+use style::dom::OpaqueNode;
 use style::invalidation::element::restyle_hints::RestyleHint;
+use style::properties::{PropertyDeclarationBlock, parse_style_attribute};
 use style::selector_parser::Snapshot;
-use style::shared_lock::SharedRwLock as StyleSharedRwLock;
+use style::shared_lock::{Locked, SharedRwLock as StyleSharedRwLock};
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
-use style::stylesheets::{Origin, OriginSet, Stylesheet};
+use style::stylesheets::{CssRuleType, Origin, OriginSet, Stylesheet, UrlExtraData};
 use style_traits::CSSPixel;
 use stylo_atoms::Atom;
 use url::Host;
 use uuid::Uuid;
 #[cfg(feature = "webgpu")]
 use webgpu_traits::WebGPUContextId;
-use webrender_api::units::DeviceIntRect;
+use webrender_api::units::{DeviceIntRect, DeviceIntSize};
 
 use crate::animation_timeline::AnimationTimeline;
 use crate::animations::Animations;
 use crate::canvas_context::CanvasContext as _;
 use crate::document_loader::{DocumentLoader, LoadType};
+use crate::dom::animation::Animation;
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEvent_Binding::BeforeUnloadEventMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
-    DocumentMethods, DocumentReadyState, DocumentVisibilityState, NamedPropertyValue,
+    CaretPositionFromPointOptions, DocumentMethods, DocumentReadyState, DocumentVisibilityState,
+    NamedPropertyValue,
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElement_Binding::HTMLIFrameElementMethods;
@@ -98,8 +113,8 @@ use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTex
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::Navigator_Binding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
-use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionName;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -125,7 +140,13 @@ use crate::dom::bindings::trace::{HashMapTracedValues, NoTrace};
 use crate::dom::bindings::weakref::WeakRef;
 use crate::dom::bindings::xmlname::matches_name_production;
 use crate::dom::canvasrenderingcontext2d::CanvasRenderingContext2D;
+use crate::dom::caretposition::CaretPosition;
 use crate::dom::cdatasection::CDATASection;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::clipboard::{
+    decode_clipboard_image, get_system_clipboard_contents, get_system_clipboard_image_as_png,
+    sanitize_html,
+};
 use crate::dom::clipboardevent::{ClipboardEvent, ClipboardEventType};
 use crate::dom::comment::Comment;
 use crate::dom::compositionevent::CompositionEvent;
@@ -150,7 +171,9 @@ use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
+use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
@@ -159,6 +182,7 @@ use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
@@ -175,10 +199,13 @@ use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancepainttiming::PerformancePaintTiming;
+use crate::dom::pictureinpictureevent::PictureInPictureEvent;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
 use crate::dom::pointerevent::{PointerEvent, PointerId};
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::reportingobserver::ReportingObserver;
 use crate::dom::resizeobserver::{ResizeObservationDepth, ResizeObserver};
 use crate::dom::selection::Selection;
 use crate::dom::servoparser::ServoParser;
@@ -201,26 +228,51 @@ use crate::dom::wheelevent::WheelEvent as DomWheelEvent;
 use crate::dom::window::Window;
 use crate::dom::windowproxy::WindowProxy;
 use crate::dom::xpathevaluator::XPathEvaluator;
+use crate::drag_and_drop::DragAndDrop;
 use crate::drag_data_store::{DragDataStore, Kind, Mode};
 use crate::fetch::FetchCanceller;
+use crate::find_in_page::FindInPage;
+use crate::focus_navigation;
 use crate::iframe_collection::IFrameCollection;
 use crate::image_animation::ImageAnimationManager;
 use crate::messaging::{CommonScriptMsg, MainThreadScriptMsg};
 use crate::mime::{APPLICATION, CHARSET, MimeExt};
 use crate::network_listener::{NetworkListener, PreInvoke};
 use crate::realms::{AlreadyInRealm, InRealm, enter_realm};
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
 use crate::script_runtime::{CanGc, ScriptThreadEventCategory};
 use crate::script_thread::{ScriptThread, with_script_thread};
 use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::TaskSourceName;
+use crate::text_highlights::TextHighlights;
 use crate::timers::OneshotTimerCallback;
+use crate::{editing, favicon};
 
 pub(crate) enum TouchEventResult {
     Processed(bool),
     Forwarded,
 }
 
+/// The interval between the runs of the animation frame callbacks of documents in
+/// throttled windows, or `None` if they are paused.
+pub(crate) fn throttled_animation_frame_interval() -> Option<Duration> {
+    let rate = pref!(dom_throttled_animation_frame_rate);
+    (rate > 0).then(|| Duration::from_secs(1) / rate.min(u32::MAX as i64) as u32)
+}
+
+/// The maximum number of hosts that the parser discovers in a document and that are
+/// resolved ahead of time.
+const MAX_SPECULATIVELY_RESOLVED_HOSTS: usize = 32;
+
+/// The maximum number of parsed `style` attributes that a document keeps to share them
+/// between elements.
+const MAX_CACHED_STYLE_ATTRIBUTES: usize = 256;
+
+/// `style` attributes longer than this are not worth keeping around, since they are
+/// unlikely to be repeated verbatim.
+const MAX_CACHED_STYLE_ATTRIBUTE_LENGTH: usize = 1024;
+
 #[derive(Clone, Copy, PartialEq)]
 pub(crate) enum FireMouseEventType {
     Move,
@@ -306,6 +358,10 @@ pub(crate) struct Document {
     is_html_document: bool,
     #[no_trace]
     activity: Cell<DocumentActivity>,
+    /// Whether this document is frozen, because its `WebView` has been hidden for a long
+    /// time. None of the tasks of a frozen document run.
+    /// <https://wicg.github.io/page-lifecycle/#sec-lifecycle-states>
+    frozen: Cell<bool>,
     #[no_trace]
     url: DomRefCell<ServoUrl>,
     #[ignore_malloc_size_of = "defined in selectors"]
@@ -337,6 +393,12 @@ pub(crate) struct Document {
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
+    /// Whether a `<link rel=icon>` was found in this document, in which case `/favicon.ico`
+    /// is not loaded for it.
+    favicon_link_seen: Cell<bool>,
+    /// The progress of the load of this document, as last sent to the embedder.
+    #[no_trace]
+    load_progress: Cell<LoadProgress>,
     /// The state of this document's focus transaction.
     focus_transaction: DomRefCell<Option<FocusTransaction>>,
     /// The element that currently has the document focus context.
@@ -348,6 +410,9 @@ pub(crate) struct Document {
     /// context's focus chain (not considering system focus). Permanently `true`
     /// for a top-level document.
     has_focus: Cell<bool>,
+    /// Whether the most recent user interaction with this document was with a pointing
+    /// device rather than the keyboard. This is used by the `:focus-visible` heuristics.
+    last_interaction_was_pointer: Cell<bool>,
     /// The script element that is currently executing.
     current_script: MutNullableDom<HTMLScriptElement>,
     /// <https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script>
@@ -374,6 +439,9 @@ pub(crate) struct Document {
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
     /// sending needless `ChangeRunningAnimationsState` messages to the compositor.
     running_animation_callbacks: Cell<bool>,
+    /// The timestamp of the frame for which the animation frame callbacks last ran.
+    #[no_trace]
+    last_animation_frame_timestamp: Cell<Option<CrossProcessInstant>>,
     /// Tracks all outstanding loads related to this document.
     loader: DomRefCell<DocumentLoader>,
     /// The current active HTML parser, to allow resuming after interruptions.
@@ -426,10 +494,12 @@ pub(crate) struct Document {
     /// <https://html.spec.whatwg.org/multipage/#concept-document-policy-container>
     #[no_trace]
     policy_container: DomRefCell<PolicyContainer>,
+    /// The time, position and click count of the last click, used to detect double and
+    /// triple clicks.
     /// <https://w3c.github.io/uievents/#event-type-dblclick>
     #[ignore_malloc_size_of = "Defined in std"]
     #[no_trace]
-    last_click_info: DomRefCell<Option<(Instant, Point2D<f32, CSSPixel>)>>,
+    last_click_info: DomRefCell<Option<(Instant, Point2D<f32, CSSPixel>, i32)>>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-destructive-writes-counter>
     ignore_destructive_writes_counter: Cell<u32>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-opens-during-unload-counter>
@@ -447,6 +517,10 @@ pub(crate) struct Document {
     dom_count: Cell<u32>,
     /// Entry node for fullscreen.
     fullscreen_element: MutNullableDom<Element>,
+    /// <https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement>
+    picture_in_picture_element: MutNullableDom<Element>,
+    /// The window that shows the picture-in-picture element, if any.
+    picture_in_picture_window: MutNullableDom<PictureInPictureWindow>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -463,7 +537,12 @@ pub(crate) struct Document {
     throw_on_dynamic_markup_insertion_counter: Cell<u64>,
     /// <https://html.spec.whatwg.org/multipage/#page-showing>
     page_showing: Cell<bool>,
-    /// Whether the document is salvageable.
+    /// <https://html.spec.whatwg.org/multipage/#concept-document-salvageable>
+    ///
+    /// A document that is still salvageable once it is unloaded stays in the back-forward
+    /// cache. It stops being salvageable when it has `beforeunload` or `unload` listeners,
+    /// when loads or a parser are aborted, when it has WebSockets that are connecting or
+    /// open, or when any of its child documents stops being salvageable.
     salvageable: Cell<bool>,
     /// Whether the document was aborted with an active parser
     active_parser_was_aborted: Cell<bool>,
@@ -495,6 +574,10 @@ pub(crate) struct Document {
     /// List of all WebGL context IDs that need flushing.
     dirty_webgl_contexts:
         DomRefCell<HashMapTracedValues<WebGLContextId, Dom<WebGLRenderingContext>>>,
+    /// The placeholder canvas elements whose OffscreenCanvas was transferred to another
+    /// global, by the id under which they get its frames.
+    placeholder_canvases:
+        DomRefCell<HashMapTracedValues<OffscreenCanvasId, WeakRef<HTMLCanvasElement>>>,
     /// List of all WebGPU contexts.
     #[cfg(feature = "webgpu")]
     #[ignore_malloc_size_of = "Rc are hard"]
@@ -508,6 +591,17 @@ pub(crate) struct Document {
     animations: DomRefCell<Animations>,
     /// Image Animation Manager for this Document
     image_animation_manager: DomRefCell<ImageAnimationManager>,
+    /// The drag-and-drop state of this Document.
+    /// <https://html.spec.whatwg.org/multipage/#dnd>
+    drag_and_drop: DragAndDrop,
+    /// The state of finding text in the page of this Document for the embedder.
+    find_in_page: FindInPage,
+    /// The text of this Document that is highlighted, other than the selection.
+    text_highlights: TextHighlights,
+    /// The dialogs of this Document that are shown as modal dialogs, in the order in which
+    /// they were shown. The last of them blocks the rest of the document.
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    modal_dialogs: DomRefCell<Vec<Dom<HTMLDialogElement>>>,
     /// The nearest inclusive ancestors to all the nodes that require a restyle.
     dirty_root: MutNullableDom<Element>,
     /// <https://html.spec.whatwg.org/multipage/#will-declaratively-refresh>
@@ -543,6 +637,16 @@ pub(crate) struct Document {
     inherited_insecure_requests_policy: Cell<Option<InsecureRequestsPolicy>>,
     //// <https://w3c.github.io/webappsec-mixed-content/#categorize-settings-object>
     has_trustworthy_ancestor_origin: Cell<bool>,
+    /// The origin of the top-level document this document is nested in, if any. Cookies and
+    /// storage of documents that are cross-site to it are partitioned by its site.
+    #[no_trace]
+    top_level_origin: DomRefCell<Option<ImmutableOrigin>>,
+    /// The origins of the documents this document is nested in, starting with the parent,
+    /// as known to the constellation when the document was loaded.
+    #[no_trace]
+    ancestor_origins: DomRefCell<Vec<ImmutableOrigin>>,
+    /// <https://privacycg.github.io/storage-access/#environment-has-storage-access>
+    has_storage_access: Cell<bool>,
     /// <https://w3c.github.io/IntersectionObserver/#document-intersectionobservertaskqueued>
     intersection_observer_task_queued: Cell<bool>,
     /// Active intersection observers that should be processed by this document in
@@ -557,11 +661,30 @@ pub(crate) struct Document {
     /// The lifetime of an intersection observer is specified at
     /// <https://github.com/w3c/IntersectionObserver/issues/525>.
     intersection_observers: DomRefCell<Vec<Dom<IntersectionObserver>>>,
+    /// The counter for the identifiers of intersection observer registrations, which are
+    /// used to refer to them in the targets tracked by the renderer.
+    next_intersection_observation_id: Cell<u64>,
+    /// The intersection observation targets that were last sent to the renderer, which
+    /// tracks them while scrolling.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in compositing_traits"]
+    renderer_intersection_observation_targets: DomRefCell<Vec<IntersectionObservationTarget>>,
     /// The active keyboard modifiers for the WebView. This is updated when receiving any input event.
     #[no_trace]
     active_keyboard_modifiers: Cell<Modifiers>,
     /// The node that is currently highlighted by the devtools
     highlighted_dom_node: MutNullableDom<Node>,
+    /// The hosts that the parser discovered in this document and that were resolved
+    /// ahead of time.
+    speculatively_resolved_hosts: DomRefCell<HashSet<String>>,
+    /// The declaration blocks parsed from the `style` attributes of the elements of this
+    /// document, keyed by the base URL and the value of the attribute. They are shared
+    /// between the elements with identical `style` attributes, and copied before one of
+    /// them is mutated through CSSOM.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Arc"]
+    style_attribute_cache:
+        DomRefCell<HashMap<(ServoUrl, String), Arc<Locked<PropertyDeclarationBlock>>>>,
 }
 
 #[allow(non_snake_case)]
@@ -723,6 +846,52 @@ impl Document {
         self.activity.get() == DocumentActivity::FullyActive
     }
 
+    pub(crate) fn is_frozen(&self) -> bool {
+        self.frozen.get()
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#freeze-steps>
+    pub(crate) fn freeze(&self, can_gc: CanGc) {
+        if self.frozen.get() || !self.is_fully_active() {
+            return;
+        }
+
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("freeze"), can_gc);
+        self.frozen.set(true);
+        self.window().as_global_scope().suspend();
+        ServoMedia::get().suspend(&self.media_client_context_id());
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#resume-steps>
+    pub(crate) fn resume_from_freeze(&self) {
+        if !self.frozen.get() {
+            return;
+        }
+
+        self.frozen.set(false);
+        self.window().as_global_scope().resume();
+        ServoMedia::get().resume(&self.media_client_context_id());
+
+        // Firing the event from a task also wakes up the event loop to run the tasks that
+        // were held back while this document was frozen.
+        let document = Trusted::new(self);
+        self.owner_global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task!(fire_resume_event: move || {
+                let document = document.root();
+                document
+                    .upcast::<EventTarget>()
+                    .fire_event(Atom::from("resume"), CanGc::note());
+            }));
+    }
+
+    fn media_client_context_id(&self) -> ClientContextId {
+        let pipeline_id = self.window().pipeline_id();
+        ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get())
+    }
+
     pub(crate) fn is_active(&self) -> bool {
         self.activity.get() != DocumentActivity::Inactive
     }
@@ -737,9 +906,17 @@ impl Document {
         // Set the document's activity level, reflow if necessary, and suspend or resume timers.
         self.activity.set(activity);
         let media = ServoMedia::get();
-        let pipeline_id = self.window().pipeline_id();
-        let client_context_id =
-            ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+        let client_context_id = self.media_client_context_id();
+
+        // A document that was frozen by the page lifecycle API while it was fully active is
+        // thawed here when it is put into or restored from the back-forward cache, so that
+        // its global is suspended and resumed by its activity alone, and so that it does
+        // not stay frozen once it is restored. This is why `resume_from_freeze` no longer
+        // checks that the document is fully active.
+        if self.frozen.replace(false) {
+            self.window().as_global_scope().resume();
+            media.resume(&client_context_id);
+        }
 
         if activity != DocumentActivity::FullyActive {
             self.window().suspend(can_gc);
@@ -913,6 +1090,7 @@ impl Document {
 
         if old_mode != new_mode {
             self.window.layout_mut().set_quirks_mode(new_mode);
+            self.style_attribute_cache.borrow_mut().clear();
         }
     }
 
@@ -1075,6 +1253,89 @@ impl Document {
         })
     }
 
+    /// Load the icon of a `<link rel=icon>` of this document for the embedder, if this is a
+    /// top-level document.
+    pub(crate) fn load_favicon(&self, url: ServoUrl) {
+        if !self.window.is_top_level() {
+            return;
+        }
+        self.favicon_link_seen.set(true);
+        favicon::load_favicon(self, url);
+    }
+
+    /// Load `/favicon.ico` for the embedder if this is a top-level HTTP(S) document without
+    /// a `<link rel=icon>`.
+    fn load_default_favicon(&self) {
+        let url = self.url();
+        if !self.window.is_top_level() ||
+            self.favicon_link_seen.get() ||
+            !matches!(url.scheme(), "http" | "https")
+        {
+            return;
+        }
+        if let Ok(favicon_url) = url.join("/favicon.ico") {
+            favicon::load_favicon(self, favicon_url);
+        }
+    }
+
+    /// Note the size of the page of this document, from the `Content-Length` of its response.
+    pub(crate) fn set_expected_page_bytes(&self, length: Option<u64>) {
+        let mut progress = self.load_progress.get();
+        progress.bytes_expected = length;
+        self.load_progress.set(progress);
+    }
+
+    /// Note that `length` more bytes of the page of this document were received.
+    pub(crate) fn note_page_bytes_received(&self, length: usize) {
+        let mut progress = self.load_progress.get();
+        progress.bytes_received += length as u64;
+        self.load_progress.set(progress);
+        self.update_load_progress();
+    }
+
+    /// Estimate the progress of the load of this document from its blocking loads and the
+    /// bytes received of its page, and send it to the embedder if this is a top-level document
+    /// whose load event was not fired yet and the estimate grew by at least a percent.
+    pub(crate) fn update_load_progress(&self) {
+        if !self.window.is_top_level() {
+            return;
+        }
+        let loader = self.loader.borrow();
+        if loader.events_inhibited() {
+            return;
+        }
+        let mut progress = self.load_progress.get();
+        (progress.resources_requested, progress.resources_loaded) = loader.load_counts();
+        drop(loader);
+        if progress.resources_requested == 0 {
+            return;
+        }
+
+        // Until the page finished loading, the part of it that was received counts as a
+        // part of a loaded resource.
+        let page_part = match progress.bytes_expected {
+            Some(expected) if progress.bytes_received < expected => {
+                progress.bytes_received as f32 / expected as f32
+            },
+            _ => 0.,
+        };
+        let estimate = ((progress.resources_loaded as f32 + page_part) /
+            progress.resources_requested as f32)
+            .min(1.);
+        let last_estimate = progress.estimated_progress;
+        let grew = estimate >= last_estimate + 0.01 || (estimate == 1. && last_estimate < 1.);
+        if grew {
+            progress.estimated_progress = estimate;
+        }
+        self.load_progress.set(progress);
+        if grew {
+            self.send_to_embedder(EmbedderMsg::NotifyLoadProgressChanged(
+                self.webview_id(),
+                progress,
+            ));
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#current-document-readiness
     pub(crate) fn set_ready_state(&self, state: DocumentReadyState, can_gc: CanGc) {
         match state {
@@ -1096,7 +1357,10 @@ impl Document {
                 }
                 update_with_current_instant(&self.dom_complete);
             },
-            DocumentReadyState::Interactive => update_with_current_instant(&self.dom_interactive),
+            DocumentReadyState::Interactive => {
+                update_with_current_instant(&self.dom_interactive);
+                self.send_navigation_timing_to_renderer();
+            },
         };
 
         self.ready_state.set(state);
@@ -1152,6 +1416,17 @@ impl Document {
         });
     }
 
+    /// Whether a newly focused `element` should match `:focus-visible`, following the
+    /// heuristics suggested in <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>:
+    ///
+    ///  - Elements that support keyboard input, such as text fields, always indicate focus.
+    ///  - Otherwise, focus is only indicated if it did not result from interacting with a
+    ///    pointing device. This includes focus moved by script in response to the keyboard
+    ///    or before the user interacted with the page at all.
+    fn should_focus_be_visible(&self, element: &Element) -> bool {
+        element.input_method_type().is_some() || !self.last_interaction_was_pointer.get()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#focus-fixup-rule>
     pub(crate) fn perform_focus_fixup_rule(&self, not_focusable: &Element, can_gc: CanGc) {
         // Return if `not_focusable` is not the designated focused area of the
@@ -1329,19 +1604,20 @@ impl Document {
         }
 
         if old_focused_filtered != new_focused_filtered {
+            update_focus_within_state(
+                old_focused_filtered.map(|element| &**element),
+                new_focused_filtered.map(|element| &**element),
+            );
+
             if let Some(elem) = &new_focused_filtered {
                 elem.set_focus_state(true);
+                elem.set_focus_visible_state(self.should_focus_be_visible(elem));
                 let node = elem.upcast::<Node>();
                 // FIXME: pass appropriate relatedTarget
                 self.fire_focus_event(FocusEventType::Focus, node.upcast(), None, can_gc);
 
                 // Notify the embedder to display an input method.
                 if let Some(kind) = elem.input_method_type() {
-                    let rect = elem.upcast::<Node>().bounding_content_box_or_zero(can_gc);
-                    let rect = Rect::new(
-                        Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
-                        Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
-                    );
                     let (text, multiline) = if let Some(input) = elem.downcast::<HTMLInputElement>()
                     {
                         (
@@ -1367,7 +1643,7 @@ impl Document {
                         kind,
                         text,
                         multiline,
-                        DeviceIntRect::from_untyped(&rect.to_box2d()),
+                        self.ime_cursor_area(elem, can_gc),
                     ));
                 }
             }
@@ -1529,6 +1805,7 @@ impl Document {
         );
 
         let node = unsafe { node::from_untrusted_node_address(hit_test_result.node) };
+        let node = self.hit_node_ignoring_inert(node);
         let Some(el) = node
             .inclusive_ancestors(ShadowIncluding::Yes)
             .filter_map(DomRoot::downcast::<Element>)
@@ -1540,21 +1817,43 @@ impl Document {
         let node = el.upcast::<Node>();
         debug!("{:?} on {:?}", event.action, node.debug_str());
         // Prevent click event if form control element is disabled.
+        if let MouseButtonAction::Down = event.action {
+            self.last_interaction_was_pointer.set(true);
+
+            // <https://html.spec.whatwg.org/multipage/#activation-triggering-input-event>
+            self.window.notify_activation();
+        }
+
         if let MouseButtonAction::Click = event.action {
             // The click event is filtered by the disabled state.
             if el.is_actually_disabled() {
                 return;
             }
 
+            // Releasing the mouse button at the end of a drag does not click.
+            if self.drag_and_drop.take_suppressed_click() {
+                return;
+            }
+
             self.begin_focus_transaction();
-            // Try to focus `el`. If it's not focusable, focus the document
-            // instead.
+            // Try to focus `el`, or the editing host that it is part of. If it's not
+            // focusable, focus the document instead.
             self.request_focus(None, FocusInitiator::Local, can_gc);
-            self.request_focus(Some(&*el), FocusInitiator::Local, can_gc);
+            let editing_host = editing::editing_host(node);
+            self.request_focus(
+                Some(editing_host.as_deref().unwrap_or(&*el)),
+                FocusInitiator::Local,
+                can_gc,
+            );
         }
 
+        let click_count = match event.action {
+            MouseButtonAction::Click => self.update_click_count(hit_test_result.point_in_viewport),
+            MouseButtonAction::Down | MouseButtonAction::Up => 1,
+        };
         let dom_event = DomRoot::upcast::<Event>(MouseEvent::for_platform_mouse_event(
             event,
+            click_count,
             input_event.pressed_mouse_buttons,
             &self.window,
             hit_test_result,
@@ -1577,12 +1876,34 @@ impl Document {
 
                 let target = node.upcast();
                 dom_event.fire(target, can_gc);
+
+                if let MouseButton::Left = event.button {
+                    self.drag_and_drop.handle_mouse_down(
+                        self,
+                        node,
+                        &dom_event,
+                        hit_test_result.point_in_viewport,
+                        can_gc,
+                    );
+                }
             },
             MouseButtonAction::Up => {
                 if let Some(a) = activatable {
                     a.exit_formal_activation_state();
                 }
 
+                // Releasing the mouse button during a drag drops the dragged data instead.
+                if let MouseButton::Left = event.button {
+                    if self.drag_and_drop.handle_mouse_up(
+                        self,
+                        hit_test_result,
+                        input_event,
+                        can_gc,
+                    ) {
+                        return;
+                    }
+                }
+
                 let target = node.upcast();
                 dom_event.fire(target, can_gc);
             },
@@ -1592,7 +1913,15 @@ impl Document {
             if self.focus_transaction.borrow().is_some() {
                 self.commit_focus_transaction(FocusInitiator::Local, can_gc);
             }
-            self.maybe_fire_dblclick(node, hit_test_result, input_event, can_gc);
+
+            self.update_selection_for_click(
+                node,
+                hit_test_result.point_in_viewport,
+                input_event.active_keyboard_modifiers,
+                click_count,
+                can_gc,
+            );
+            self.maybe_fire_dblclick(node, hit_test_result, input_event, click_count, can_gc);
         }
 
         // When the contextmenu event is triggered by right mouse button
@@ -1649,31 +1978,56 @@ impl Document {
 
         // if the event was not canceled, notify the embedder to show the context menu
         if event.status() == EventStatus::NotCanceled {
+            // When the caret of the focused text control is in a misspelled word, the
+            // menu offers the replacements that the spellchecker suggests for it.
+            let focused = self.get_focused_element();
+            let spelling = focused.as_deref().and_then(|element| {
+                if let Some(input) = element.downcast::<HTMLInputElement>() {
+                    input.spelling_suggestions()
+                } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+                    textarea.spelling_suggestions()
+                } else {
+                    None
+                }
+            });
+            let (title, items) = match spelling {
+                Some((word, suggestions)) => (Some(word), suggestions),
+                None => (None, vec![]),
+            };
+
             let (sender, receiver) =
                 ipc::channel::<ContextMenuResult>().expect("Failed to create IPC channel.");
             self.send_to_embedder(EmbedderMsg::ShowContextMenu(
                 self.webview_id(),
                 sender,
-                None,
-                vec![],
+                title,
+                items.clone(),
             ));
-            let _ = receiver.recv().unwrap();
+            let result = receiver.recv().unwrap();
+
+            if let (ContextMenuResult::Selected(index), Some(element)) = (result, focused) {
+                let Some(replacement) = items.get(index) else {
+                    return;
+                };
+                if let Some(input) = element.downcast::<HTMLInputElement>() {
+                    input.replace_misspelled_word(replacement, can_gc);
+                } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+                    textarea.replace_misspelled_word(replacement, can_gc);
+                }
+            }
         };
     }
 
-    fn maybe_fire_dblclick(
-        &self,
-        target: &Node,
-        hit_test_result: &CompositorHitTestResult,
-        input_event: &ConstellationInputEvent,
-        can_gc: CanGc,
-    ) {
-        // https://w3c.github.io/uievents/#event-type-dblclick
+    /// Count a click at `point_in_viewport` and return its click count: 1 for a single
+    /// click, 2 for a double click and 3 for a triple click. A click is part of the
+    /// sequence of the previous click if it follows it within a certain time and distance.
+    /// <https://w3c.github.io/uievents/#dom-uievent-detail>
+    fn update_click_count(&self, point_in_viewport: Point2D<f32, CSSPixel>) -> i32 {
         let now = Instant::now();
-        let point_in_viewport = hit_test_result.point_in_viewport;
-        let opt = self.last_click_info.borrow_mut().take();
+        let last_click_info = self.last_click_info.borrow_mut().take();
 
-        if let Some((last_time, last_pos)) = opt {
+        let mut click_count = 1;
+        if let Some((last_time, last_pos, last_click_count)) = last_click_info {
             let DBL_CLICK_TIMEOUT =
                 Duration::from_millis(pref!(dom_document_dblclick_timeout) as u64);
             let DBL_CLICK_DIST_THRESHOLD = pref!(dom_document_dblclick_dist) as u64;
@@ -1682,41 +2036,96 @@ impl Document {
             let line = point_in_viewport - last_pos;
             let dist = (line.dot(line) as f64).sqrt();
 
+            // A fourth click starts a new sequence of clicks.
             if now.duration_since(last_time) < DBL_CLICK_TIMEOUT &&
-                dist < DBL_CLICK_DIST_THRESHOLD as f64
+                dist < DBL_CLICK_DIST_THRESHOLD as f64 &&
+                last_click_count < 3
             {
-                // A double click has occurred if this click is within a certain time and dist. of previous click.
-                let click_count = 2;
+                click_count = last_click_count + 1;
+            }
+        }
 
-                let event = MouseEvent::new(
-                    &self.window,
-                    DOMString::from("dblclick"),
-                    EventBubbles::Bubbles,
-                    EventCancelable::Cancelable,
-                    Some(&self.window),
-                    click_count,
-                    point_in_viewport.to_i32(),
-                    point_in_viewport.to_i32(),
-                    hit_test_result
-                        .point_relative_to_initial_containing_block
-                        .to_i32(),
-                    input_event.active_keyboard_modifiers,
-                    0i16,
-                    input_event.pressed_mouse_buttons,
-                    None,
-                    None,
-                    can_gc,
-                );
-                event.upcast::<Event>().fire(target.upcast(), can_gc);
+        // Update last_click_info with the time, position and count of the click.
+        *self.last_click_info.borrow_mut() = Some((now, point_in_viewport, click_count));
+        click_count
+    }
 
-                // When a double click occurs, self.last_click_info is left as None so that a
-                // third sequential click will not cause another double click.
-                return;
-            }
+    /// Update the selection of the document for a click at `point_in_viewport` on `node`.
+    /// A click collapses the selection at the clicked position, or extends the selection
+    /// to it if Shift is held, a double click selects the clicked word and a triple click
+    /// selects the paragraph around it. Text controls update their own selection when they
+    /// handle the click.
+    fn update_selection_for_click(
+        &self,
+        node: &Node,
+        point_in_viewport: Point2D<f32, CSSPixel>,
+        modifiers: Modifiers,
+        click_count: i32,
+        can_gc: CanGc,
+    ) {
+        if node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .any(|ancestor| {
+                ancestor.is::<HTMLInputElement>() || ancestor.is::<HTMLTextAreaElement>()
+            })
+        {
+            return;
         }
+        let Some((caret_node, offset)) = self.caret_position_from_point(
+            Finite::wrap(point_in_viewport.x as f64),
+            Finite::wrap(point_in_viewport.y as f64),
+            &[],
+            can_gc,
+        ) else {
+            return;
+        };
+        let Some(selection) = self.GetSelection(can_gc) else {
+            return;
+        };
+        match click_count {
+            2 => selection.select_word(&caret_node, offset, can_gc),
+            3 => selection.select_paragraph(&caret_node, can_gc),
+            _ if modifiers.contains(Modifiers::SHIFT) && selection.RangeCount() > 0 => {
+                let _ = selection.Extend(&caret_node, offset, can_gc);
+            },
+            _ => {
+                let _ = selection.Collapse(Some(&caret_node), offset, can_gc);
+            },
+        }
+    }
 
-        // Update last_click_info with the time and position of the click.
-        *self.last_click_info.borrow_mut() = Some((now, point_in_viewport));
+    fn maybe_fire_dblclick(
+        &self,
+        target: &Node,
+        hit_test_result: &CompositorHitTestResult,
+        input_event: &ConstellationInputEvent,
+        click_count: i32,
+        can_gc: CanGc,
+    ) {
+        // https://w3c.github.io/uievents/#event-type-dblclick
+        if click_count == 2 {
+            let point_in_viewport = hit_test_result.point_in_viewport;
+            let event = MouseEvent::new(
+                &self.window,
+                DOMString::from("dblclick"),
+                EventBubbles::Bubbles,
+                EventCancelable::Cancelable,
+                Some(&self.window),
+                click_count,
+                point_in_viewport.to_i32(),
+                point_in_viewport.to_i32(),
+                hit_test_result
+                    .point_relative_to_initial_containing_block
+                    .to_i32(),
+                input_event.active_keyboard_modifiers,
+                0i16,
+                input_event.pressed_mouse_buttons,
+                None,
+                None,
+                can_gc,
+            );
+            event.upcast::<Event>().fire(target.upcast(), can_gc);
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1798,6 +2207,15 @@ impl Document {
             return false;
         }
 
+        // Step 3 If the event was not canceled, copy the selected contents, if any, to the
+        // clipboard. Text controls copy their own selection when handling the event.
+        let focused_text_control = self.get_focused_element().is_some_and(|element| {
+            element.is::<HTMLInputElement>() || element.is::<HTMLTextAreaElement>()
+        });
+        if !e.DefaultPrevented() && e.Type().str() == "copy" && !focused_text_control {
+            self.copy_selection_to_clipboard(can_gc);
+        }
+
         // Step 4 If the event was canceled, then
         if e.DefaultPrevented() {
             match e.Type().str() {
@@ -1864,15 +2282,18 @@ impl Document {
                 drag_data_store.set_mode(Mode::ReadWrite);
             },
             ClipboardEventType::Paste => {
-                let (sender, receiver) = ipc::channel().unwrap();
-                self.window
-                    .send_to_constellation(ScriptToConstellationMessage::ForwardToEmbedder(
-                        EmbedderMsg::GetClipboardText(self.window.webview_id(), sender),
-                    ));
-                let text_contents = receiver
-                    .recv()
-                    .map(Result::unwrap_or_default)
-                    .unwrap_or_default();
+                // Only expose the clipboard to the document that the user is pasting into,
+                // so that documents without focus can't snoop on its contents.
+                let (text_contents, html_contents, image_contents) = if self.HasFocus() {
+                    (
+                        get_system_clipboard_contents(&self.window, EmbedderMsg::GetClipboardText)
+                            .unwrap_or_default(),
+                        get_system_clipboard_contents(&self.window, EmbedderMsg::GetClipboardHtml),
+                        get_system_clipboard_image_as_png(&self.window),
+                    )
+                } else {
+                    (String::new(), None, None)
+                };
 
                 // Step 7.1.1
                 drag_data_store.set_mode(Mode::ReadOnly);
@@ -1885,8 +2306,24 @@ impl Document {
                     let type_ = DOMString::from("text/plain");
                     let _ = drag_data_store.add(Kind::Text { data, type_ });
 
-                    // Step 7.1.2.1.2 TODO If clipboard-part represents file references, then for each file reference
-                    // Step 7.1.2.1.3 TODO If clipboard-part contains HTML- or XHTML-formatted text then
+                    // Step 7.1.2.1.2 If clipboard-part represents file references, then for each file reference
+                    // NOTE: An image on the clipboard is exposed as a file, like other browsers do.
+                    if let Some(bytes) = image_contents {
+                        let _ = drag_data_store.add(Kind::File {
+                            bytes,
+                            name: DOMString::from("image.png"),
+                            type_: String::from("image/png"),
+                        });
+                    }
+
+                    // Step 7.1.2.1.3 If clipboard-part contains HTML- or XHTML-formatted text then
+                    // HTML may come from any other application, so it is sanitized before it
+                    // is exposed to the page.
+                    if let Some(html) = html_contents {
+                        let data = DOMString::from(sanitize_html(&self.window, html, can_gc));
+                        let type_ = DOMString::from("text/html");
+                        let _ = drag_data_store.add(Kind::Text { data, type_ });
+                    }
 
                     // Step 7.1.3 Update clipboard-event-data’s files to match clipboard-event-data’s items
                     // Step 7.1.4 Update clipboard-event-data’s types to match clipboard-event-data’s items
@@ -1926,31 +2363,64 @@ impl Document {
         self.fire_clipboard_event(&clipboardchange_event, ClipboardEventType::Change, can_gc);
     }
 
+    /// Copy the contents of the selection of this document, if any, to the clipboard, both
+    /// as plain text and as HTML.
+    fn copy_selection_to_clipboard(&self, can_gc: CanGc) {
+        let Some(range) = self
+            .GetSelection(can_gc)
+            .and_then(|selection| selection.GetRangeAt(0).ok())
+        else {
+            return;
+        };
+        if range.collapsed() {
+            return;
+        }
+
+        let mut drag_data_store = DragDataStore::new();
+        drag_data_store.set_mode(Mode::ReadWrite);
+        drag_data_store.set_data(DOMString::from("text/plain"), range.clipboard_text(can_gc));
+        if let Some(html) = range.html_fragment(can_gc) {
+            drag_data_store.set_data(DOMString::from("text/html"), html);
+        }
+        self.write_content_to_the_clipboard(&drag_data_store);
+        self.fire_clipboardchange_event(can_gc);
+    }
+
     /// <https://www.w3.org/TR/clipboard-apis/#write-content-to-the-clipboard>
     fn write_content_to_the_clipboard(&self, drag_data_store: &DragDataStore) {
         // Step 1
         if drag_data_store.list_len() > 0 {
             // Step 1.1 Clear the clipboard.
-            self.send_to_embedder(EmbedderMsg::ClearClipboard(self.webview_id()));
+            // NOTE: The new contents replace the previous ones when they are sent to the
+            // embedder all at once below.
+            let mut contents = ClipboardContents::default();
             // Step 1.2
             for item in drag_data_store.iter_item_list() {
                 match item {
-                    Kind::Text { data, .. } => {
+                    Kind::Text { data, type_ } => {
                         // Step 1.2.1.1 Ensure encoding is correct per OS and locale conventions
                         // Step 1.2.1.2 Normalize line endings according to platform conventions
                         // Step 1.2.1.3
-                        self.send_to_embedder(EmbedderMsg::SetClipboardText(
-                            self.webview_id(),
-                            data.to_string(),
-                        ));
+                        if type_.eq("text/plain") {
+                            contents.text = Some(data.to_string());
+                        } else if type_.eq("text/html") {
+                            contents.html = Some(data.to_string());
+                        }
                     },
-                    Kind::File { .. } => {
+                    Kind::File { bytes, type_, .. } => {
                         // Step 1.2.2 If data is of a type listed in the mandatory data types list, then
                         // Step 1.2.2.1 Place part on clipboard with the appropriate OS clipboard format description
                         // Step 1.2.3 Else this is left to the implementation
+                        if type_ == "image/png" {
+                            contents.image = decode_clipboard_image(bytes);
+                        }
                     },
                 }
             }
+            self.send_to_embedder(EmbedderMsg::SetClipboardContents(
+                self.webview_id(),
+                contents,
+            ));
         } else {
             // Step 2.1
             if drag_data_store.clear_was_called {
@@ -1975,6 +2445,7 @@ impl Document {
         };
 
         let node = unsafe { node::from_untrusted_node_address(hit_test_result.node) };
+        let node = self.hit_node_ignoring_inert(node);
         let Some(new_target) = node
             .inclusive_ancestors(ShadowIncluding::No)
             .filter_map(DomRoot::downcast::<Element>)
@@ -1983,6 +2454,14 @@ impl Document {
             return;
         };
 
+        // Moving the mouse during a drag fires drag events instead of mouse events.
+        if self
+            .drag_and_drop
+            .handle_mouse_move(self, &node, hit_test_result, input_event, can_gc)
+        {
+            return;
+        }
+
         let target_has_changed = prev_mouse_over_target
             .get()
             .as_ref()
@@ -2088,12 +2567,37 @@ impl Document {
         }
     }
 
+    /// Handle a drag from outside of the webview, such as a drag of files from the file
+    /// manager of the operating system, which fires drag events at the page.
     #[allow(unsafe_code)]
+    pub(crate) fn handle_external_drag_event(
+        &self,
+        event: &ExternalDragEvent,
+        input_event: &ConstellationInputEvent,
+        can_gc: CanGc,
+    ) {
+        let hit_test_result = input_event.hit_test_result.as_ref();
+        let node = hit_test_result.map(|hit_test_result| unsafe {
+            node::from_untrusted_node_address(hit_test_result.node)
+        });
+        self.drag_and_drop.handle_external_drag_event(
+            self,
+            event,
+            node.as_deref(),
+            hit_test_result,
+            input_event,
+            can_gc,
+        );
+    }
+
     pub(crate) fn handle_mouse_leave_event(
         &self,
         input_event: &ConstellationInputEvent,
         can_gc: CanGc,
     ) {
+        // A drag that leaves the webview is handed to the embedder.
+        self.drag_and_drop.handle_mouse_leave(self, can_gc);
+
         // Ignore all incoming events without a hit test.
         let Some(hit_test_result) = &input_event.hit_test_result else {
             return;
@@ -2195,6 +2699,7 @@ impl Document {
         };
 
         let node = unsafe { node::from_untrusted_node_address(hit_test_result.node) };
+        let node = self.hit_node_ignoring_inert(node);
         let Some(el) = node
             .inclusive_ancestors(ShadowIncluding::No)
             .filter_map(DomRoot::downcast::<Element>)
@@ -2260,6 +2765,7 @@ impl Document {
         };
 
         let node = unsafe { node::from_untrusted_node_address(hit_test_result.node) };
+        let node = self.hit_node_ignoring_inert(node);
         let Some(el) = node
             .inclusive_ancestors(ShadowIncluding::No)
             .filter_map(DomRoot::downcast::<Element>)
@@ -2304,6 +2810,11 @@ impl Document {
                 }
             },
             TouchEventType::Up | TouchEventType::Cancel => {
+                // <https://html.spec.whatwg.org/multipage/#activation-triggering-input-event>
+                if matches!(event.event_type, TouchEventType::Up) {
+                    self.window.notify_activation();
+                }
+
                 // Remove an existing touch point
                 let mut active_touch_points = self.active_touch_points.borrow_mut();
                 match active_touch_points
@@ -2419,9 +2930,40 @@ impl Document {
         keyboard_event: ::embedder_traits::KeyboardEvent,
         can_gc: CanGc,
     ) {
+        // Escape cancels a drag instead of reaching the page.
+        if keyboard_event.event.state == KeyState::Down &&
+            keyboard_event.event.key == Key::Escape &&
+            self.drag_and_drop.cancel(self, can_gc)
+        {
+            return;
+        }
+
         let focused = self.get_focused_element();
         let body = self.GetBody();
 
+        // Any keyboard interaction that isn't a keyboard shortcut makes the focused
+        // element indicate focus, as the user is likely navigating with the keyboard.
+        if keyboard_event.event.state == KeyState::Down &&
+            !matches!(
+                keyboard_event.event.key,
+                Key::Alt | Key::Control | Key::Meta | Key::Shift
+            ) &&
+            !keyboard_event
+                .event
+                .modifiers
+                .intersects(Modifiers::ALT | Modifiers::CONTROL | Modifiers::META)
+        {
+            self.last_interaction_was_pointer.set(false);
+            if let Some(focused) = &focused {
+                focused.set_focus_visible_state(true);
+            }
+
+            // <https://html.spec.whatwg.org/multipage/#activation-triggering-input-event>
+            if keyboard_event.event.key != Key::Escape {
+                self.window.notify_activation();
+            }
+        }
+
         let target = match (&focused, &body) {
             (Some(focused), _) => focused.upcast(),
             (&None, Some(body)) => body.upcast(),
@@ -2482,6 +3024,32 @@ impl Document {
         }
 
         if cancel_state == EventDefault::Allowed {
+            // Escape requests to close the modal dialog that blocks the document.
+            // <https://html.spec.whatwg.org/multipage/#close-requests>
+            if keyboard_event.event.key == Key::Escape &&
+                keyboard_event.event.state == KeyState::Down
+            {
+                if let Some(dialog) = self.blocking_modal_dialog() {
+                    dialog.request_close(can_gc);
+                    return;
+                }
+            }
+
+            // Keys pressed in an editing host edit its contents.
+            if keyboard_event.event.state == KeyState::Down && !keyboard_event.event.is_composing {
+                if let Some(host) = focused
+                    .as_deref()
+                    .and_then(|focused| editing::editing_host(focused.upcast()))
+                {
+                    editing::handle_keydown(
+                        &host,
+                        &keyboard_event.event.key,
+                        keyboard_event.event.modifiers,
+                        can_gc,
+                    );
+                }
+            }
+
             let msg = EmbedderMsg::Keyboard(self.webview_id(), keyboard_event.clone());
             self.send_to_embedder(msg);
 
@@ -2498,9 +3066,84 @@ impl Document {
                         .fire_synthetic_pointer_event_not_trusted(DOMString::from("click"), can_gc);
                 }
             }
+
+            // Tab moves focus to the next element in the sequential focus navigation order
+            // and Shift+Tab to the previous one.
+            if keyboard_event.event.key == Key::Tab &&
+                keyboard_event.event.state == KeyState::Down &&
+                !keyboard_event
+                    .event
+                    .modifiers
+                    .intersects(Modifiers::ALT | Modifiers::CONTROL | Modifiers::META)
+            {
+                let direction = if keyboard_event.event.modifiers.contains(Modifiers::SHIFT) {
+                    SequentialFocusDirection::Backward
+                } else {
+                    SequentialFocusDirection::Forward
+                };
+                focus_navigation::move_sequential_focus(
+                    self,
+                    direction,
+                    focused.as_deref(),
+                    can_gc,
+                );
+            }
         }
     }
 
+    /// The area next to which the input method for `element` should show its candidate
+    /// window, relative to the viewport: the caret of a text control, or the whole element
+    /// if it doesn't have a caret.
+    fn ime_cursor_area(&self, element: &Element, can_gc: CanGc) -> DeviceIntRect {
+        let node = element.upcast::<Node>();
+        let rect = self
+            .window
+            .text_cursor_rect_query(node, can_gc)
+            .unwrap_or_else(|| node.bounding_content_box_or_zero(can_gc));
+        let scroll_offset = self.window.scroll_offset(can_gc);
+        let rect = Rect::new(
+            Point2D::new(
+                rect.origin.x.to_px() - scroll_offset.x as i32,
+                rect.origin.y.to_px() - scroll_offset.y as i32,
+            ),
+            Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+        );
+        DeviceIntRect::from_untyped(&rect.to_box2d())
+    }
+
+    /// Tell the embedder where the caret of the focused text control `element` is, so that
+    /// the input method shows the text it is composing next to it.
+    pub(crate) fn update_ime_cursor_area(&self, element: &Element, can_gc: CanGc) {
+        if element.input_method_type().is_none() {
+            return;
+        }
+        let area = self.ime_cursor_area(element, can_gc);
+        self.send_to_embedder(EmbedderMsg::UpdateIMECursorArea(self.webview_id(), area));
+    }
+
+    /// Ask the spellchecker of the embedder for the UTF-8 byte ranges of the misspelled
+    /// words in `text`.
+    pub(crate) fn check_spelling(&self, text: String) -> Vec<std::ops::Range<usize>> {
+        if text.is_empty() {
+            return Vec::new();
+        }
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.send_to_embedder(EmbedderMsg::CheckSpelling(self.webview_id(), text, sender));
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// Ask the spellchecker of the embedder for the suggested replacements for the
+    /// misspelled `word`, for instance to offer them in a context menu.
+    pub(crate) fn spelling_suggestions(&self, word: String) -> Vec<String> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.send_to_embedder(EmbedderMsg::GetSpellingSuggestions(
+            self.webview_id(),
+            word,
+            sender,
+        ));
+        receiver.recv().unwrap_or_default()
+    }
+
     pub(crate) fn dispatch_ime_event(&self, event: ImeEvent, can_gc: CanGc) {
         let composition_event = match event {
             ImeEvent::Dismissed => {
@@ -2632,6 +3275,27 @@ impl Document {
         !self.animation_frame_list.borrow().is_empty()
     }
 
+    /// Whether the animation frame callbacks of this `Document` should run for the frame
+    /// starting at `frame_timestamp`. The callbacks of documents in throttled windows only
+    /// run at the throttled animation frame rate, if at all.
+    pub(crate) fn animation_frame_callbacks_due(
+        &self,
+        frame_timestamp: CrossProcessInstant,
+    ) -> bool {
+        if !self.window.throttled() {
+            return true;
+        }
+
+        let Some(interval) = throttled_animation_frame_interval() else {
+            return false;
+        };
+        self.last_animation_frame_timestamp
+            .get()
+            .is_none_or(|last_timestamp| {
+                (frame_timestamp - last_timestamp).unsigned_abs() >= interval
+            })
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-requestanimationframe>
     pub(crate) fn request_animation_frame(&self, callback: AnimationFrameCallback) -> u32 {
         let ident = self.animation_frame_ident.get() + 1;
@@ -2669,11 +3333,20 @@ impl Document {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
-    pub(crate) fn run_the_animation_frame_callbacks(&self, can_gc: CanGc) {
+    pub(crate) fn run_the_animation_frame_callbacks(
+        &self,
+        frame_timestamp: CrossProcessInstant,
+        can_gc: CanGc,
+    ) {
         let _realm = enter_realm(self);
 
         self.running_animation_callbacks.set(true);
-        let timing = self.global().performance().Now();
+        self.last_animation_frame_timestamp
+            .set(Some(frame_timestamp));
+        let timing = self
+            .global()
+            .performance()
+            .to_dom_high_res_time_stamp(frame_timestamp);
 
         let num_callbacks = self.animation_frame_list.borrow().len();
         for _ in 0..num_callbacks {
@@ -2726,6 +3399,7 @@ impl Document {
         .into_callback();
         self.loader_mut()
             .fetch_async_with_callback(load, request, callback);
+        self.update_load_progress();
     }
 
     pub(crate) fn fetch_background<Listener: FetchResponseListener + PreInvoke + Send + 'static>(
@@ -2748,12 +3422,64 @@ impl Document {
         self.loader_mut().fetch_async_background(request, callback);
     }
 
+    /// Ask the resource thread to resolve the host of `url`, or to also connect to it,
+    /// ahead of the requests that are expected to be made to it.
+    pub(crate) fn speculatively_connect(&self, url: &ServoUrl, kind: SpeculativeConnectionKind) {
+        if !pref!(network_speculative_connections_enabled) ||
+            !matches!(url.scheme(), "http" | "https") ||
+            url.host_str().is_none()
+        {
+            return;
+        }
+
+        // Connections that end up unused cost data, so only resolve hosts when the user
+        // asked for data usage to be reduced.
+        if kind == SpeculativeConnectionKind::Preconnect && pref!(network_save_data) {
+            return;
+        }
+
+        let _ = self
+            .window
+            .as_global_scope()
+            .resource_threads()
+            .send(CoreResourceMsg::SpeculativeConnection(url.clone(), kind));
+    }
+
+    /// Resolve the host of a URL that the parser discovered ahead of the fetch of the
+    /// resource that it refers to, unless this document already did so for that host or
+    /// for too many hosts.
+    pub(crate) fn prefetch_dns_for_discovered_url(&self, url: &ServoUrl) {
+        // Unlike `<link rel=dns-prefetch>`, these lookups were not asked for by the page.
+        if pref!(network_save_data) {
+            return;
+        }
+
+        let Some(host) = url.host_str() else {
+            return;
+        };
+        if self.url().host_str() == Some(host) {
+            return;
+        }
+
+        {
+            let mut resolved_hosts = self.speculatively_resolved_hosts.borrow_mut();
+            if resolved_hosts.len() >= MAX_SPECULATIVELY_RESOLVED_HOSTS ||
+                !resolved_hosts.insert(host.to_owned())
+            {
+                return;
+            }
+        }
+
+        self.speculatively_connect(url, SpeculativeConnectionKind::DnsPrefetch);
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-end
     // https://html.spec.whatwg.org/multipage/#delay-the-load-event
     pub(crate) fn finish_load(&self, load: LoadType, can_gc: CanGc) {
         // This does not delay the load event anymore.
         debug!("Document got finish_load: {:?}", load);
         self.loader.borrow_mut().finish_load(&load);
+        self.update_load_progress();
 
         match load {
             LoadType::Stylesheet(_) => {
@@ -2911,6 +3637,10 @@ impl Document {
         }
         // TODO: Step 8, decrease the event loop's termination nesting level by 1.
 
+        // https://w3c.github.io/picture-in-picture/#page-visibility
+        // A video of a document that is unloaded cannot stay in picture-in-picture.
+        self.exit_picture_in_picture(true, can_gc);
+
         // Step 13
         if !recursive_flag {
             // `unload` might cause futher modifications to the DOM so collecting here prevents
@@ -2929,6 +3659,11 @@ impl Document {
         let global_scope = self.window.as_global_scope();
         // Step 10, 14
         // https://html.spec.whatwg.org/multipage/#unloading-document-cleanup-steps
+        // Step 2 of clean-up steps: make the WebSockets of the window disappear, which keeps
+        // documents with open connections out of the back-forward cache.
+        if global_scope.make_websockets_disappear() {
+            self.salvageable.set(false);
+        }
         if !self.salvageable.get() {
             // Step 1 of clean-up steps.
             global_scope.close_event_sources();
@@ -3001,9 +3736,11 @@ impl Document {
 
                 debug!("About to dispatch load for {:?}", document.url());
                 window.dispatch_event_with_target_override(&event, CanGc::note());
+                document.load_default_favicon();
 
                 // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
                 update_with_current_instant(&document.load_event_end);
+                document.send_navigation_timing_to_renderer();
 
                 if let Some(fragment) = document.url().fragment() {
                     document.check_and_scroll_fragment(fragment, CanGc::note());
@@ -3255,6 +3992,13 @@ impl Document {
                 let document = document.root();
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"), CanGc::note());
                 update_with_current_instant(&document.dom_content_loaded_event_end);
+                document.send_navigation_timing_to_renderer();
+                if document.window().is_top_level() {
+                    document.send_to_embedder(EmbedderMsg::NotifyLoadStatusChanged(
+                        document.webview_id(),
+                        LoadStatus::DomContentLoaded,
+                    ));
+                }
                 })
             );
 
@@ -3277,7 +4021,11 @@ impl Document {
             if let Some(document) = iframe.GetContentDocument() {
                 // TODO: abort the active documents of every child browsing context.
                 document.abort(can_gc);
-                // TODO: salvageable flag.
+                // If document's salvageable state is false, set this document's salvageable
+                // state to false too.
+                if !document.salvageable() {
+                    self.salvageable.set(false);
+                }
             }
         }
 
@@ -3341,6 +4089,27 @@ impl Document {
         self.dom_interactive.get()
     }
 
+    /// Tell the renderer about the navigation timing of a top-level document, which it
+    /// reports along with the paint timing of the frame that screenshots are taken of.
+    fn send_navigation_timing_to_renderer(&self) {
+        if !self.window.is_top_level() {
+            return;
+        }
+        let Some(navigation_start) = self.interactive_time.borrow().navigation_start() else {
+            return;
+        };
+        self.window.compositor_api().update_navigation_timing(
+            self.webview_id(),
+            self.window.pipeline_id(),
+            NavigationTiming {
+                navigation_start,
+                dom_interactive: self.dom_interactive.get(),
+                dom_content_loaded_event_end: self.dom_content_loaded_event_end.get(),
+                load_event_end: self.load_event_end.get(),
+            },
+        );
+    }
+
     pub(crate) fn set_navigation_start(&self, navigation_start: CrossProcessInstant) {
         self.interactive_time
             .borrow_mut()
@@ -3531,6 +4300,31 @@ impl Document {
             .or_insert_with(|| Dom::from_ref(context));
     }
 
+    /// Keep a placeholder canvas element under a new id, which lets the frames of its
+    /// OffscreenCanvas reach it from another global.
+    pub(crate) fn register_placeholder_canvas(
+        &self,
+        canvas: &HTMLCanvasElement,
+    ) -> OffscreenCanvasId {
+        let canvas_id = OffscreenCanvasId::new();
+        self.placeholder_canvases
+            .borrow_mut()
+            .insert(canvas_id, WeakRef::new(canvas));
+        canvas_id
+    }
+
+    pub(crate) fn placeholder_canvas(
+        &self,
+        canvas_id: OffscreenCanvasId,
+    ) -> Option<DomRoot<HTMLCanvasElement>> {
+        let mut placeholder_canvases = self.placeholder_canvases.borrow_mut();
+        let canvas = placeholder_canvases.get(&canvas_id)?.root();
+        if canvas.is_none() {
+            placeholder_canvases.remove(&canvas_id);
+        }
+        canvas
+    }
+
     #[cfg(feature = "webgpu")]
     pub(crate) fn webgpu_contexts(&self) -> WebGPUContextsMap {
         self.webgpu_contexts.clone()
@@ -3578,9 +4372,91 @@ impl Document {
             receiver.recv().unwrap();
         }
 
+        // Changes to the DOM can move the boundary points of highlighted ranges.
+        if self.restyle_reason().needs_restyle() {
+            self.update_text_highlights();
+        }
+
         self.window().reflow(ReflowGoal::UpdateTheRendering, can_gc)
     }
 
+    /// Resolve the ranges of the highlights of the CSS Custom Highlight API and the matches
+    /// of finding text in the page to the highlighted text that layout paints.
+    pub(crate) fn update_text_highlights(&self) {
+        let mut highlights = self
+            .window
+            .highlight_registry_if_created()
+            .map(|registry| registry.highlighted_ranges())
+            .unwrap_or_default();
+        highlights.extend(self.find_in_page.highlighted_ranges());
+        self.text_highlights.update(&highlights);
+    }
+
+    /// Find text in this Document for the embedder, highlighting the matches.
+    pub(crate) fn find_in_page(
+        &self,
+        request: FindInPageRequest,
+        can_gc: CanGc,
+    ) -> FindInPageResult {
+        self.find_in_page.find(self, request, can_gc)
+    }
+
+    /// Stop finding text in this Document, which removes the highlights of the matches.
+    pub(crate) fn stop_finding_in_page(&self) {
+        self.find_in_page.stop(self)
+    }
+
+    /// Show `dialog` as a modal dialog on top of the other modal dialogs of this Document,
+    /// which blocks the rest of the document.
+    pub(crate) fn add_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        let mut modal_dialogs = self.modal_dialogs.borrow_mut();
+        modal_dialogs.retain(|modal_dialog| &**modal_dialog != dialog);
+        modal_dialogs.push(Dom::from_ref(dialog));
+    }
+
+    /// Stop showing `dialog` as a modal dialog of this Document.
+    pub(crate) fn remove_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        self.modal_dialogs
+            .borrow_mut()
+            .retain(|modal_dialog| &**modal_dialog != dialog);
+    }
+
+    /// The modal dialog that blocks this Document, if any.
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    pub(crate) fn blocking_modal_dialog(&self) -> Option<DomRoot<HTMLDialogElement>> {
+        self.modal_dialogs
+            .borrow()
+            .last()
+            .map(|dialog| DomRoot::from_ref(&**dialog))
+    }
+
+    /// Whether `node` is inert because this Document is blocked by a modal dialog that
+    /// doesn't contain it.
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    pub(crate) fn is_blocked_by_modal_dialog(&self, node: &Node) -> bool {
+        self.blocking_modal_dialog().is_some_and(|dialog| {
+            !dialog
+                .upcast::<Node>()
+                .is_shadow_including_inclusive_ancestor_of(node)
+        })
+    }
+
+    /// The node that the user hits when the hit test of an input event finds `node`, as inert
+    /// nodes can't be hit. That is the blocking modal dialog for the nodes it blocks, as if
+    /// its backdrop was hit, and the closest ancestor that isn't inert for the nodes of an
+    /// inert subtree. The `[inert]` rule of the user agent stylesheet already keeps layout
+    /// from hitting most of those, but it can't reach into shadow trees.
+    fn hit_node_ignoring_inert(&self, node: DomRoot<Node>) -> DomRoot<Node> {
+        if self.is_blocked_by_modal_dialog(&node) {
+            if let Some(dialog) = self.blocking_modal_dialog() {
+                return DomRoot::from_ref(dialog.upcast());
+            }
+        }
+        node.inclusive_ancestors(ShadowIncluding::Yes)
+            .find(|ancestor| !ancestor.is_inert())
+            .unwrap_or(node)
+    }
+
     pub(crate) fn id_map(&self) -> Ref<HashMapTracedValues<Atom, Vec<Dom<Element>>>> {
         self.id_map.borrow()
     }
@@ -3725,15 +4601,50 @@ impl Document {
             .retain(|observer| *observer != intersection_observer)
     }
 
+    /// Return a new identifier for an intersection observer registration.
+    pub(crate) fn next_intersection_observation_id(&self) -> u64 {
+        let id = self.next_intersection_observation_id.get();
+        self.next_intersection_observation_id.set(id + 1);
+        id
+    }
+
     /// <https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo>
     pub(crate) fn update_intersection_observer_steps(
         &self,
         time: CrossProcessInstant,
         can_gc: CanGc,
     ) {
-        // Step 1-2
-        for intersection_observer in &*self.intersection_observers.borrow() {
-            self.update_single_intersection_observer_steps(intersection_observer, time, can_gc);
+        let mut renderer_targets = Vec::new();
+        if !self.intersection_observers.borrow().is_empty() {
+            let scroll_offset = self.window.scroll_offset(can_gc);
+            let viewport_scroll_offset = Vector2D::new(
+                Au::from_f32_px(scroll_offset.x),
+                Au::from_f32_px(scroll_offset.y),
+            );
+
+            // Step 1-2
+            for intersection_observer in &*self.intersection_observers.borrow() {
+                self.update_single_intersection_observer_steps(
+                    intersection_observer,
+                    time,
+                    viewport_scroll_offset,
+                    &mut renderer_targets,
+                    can_gc,
+                );
+            }
+        }
+
+        // Let the renderer track the targets that it can while it scrolls, so that their
+        // entries are queued without waiting for script to update the rendering.
+        if *self.renderer_intersection_observation_targets.borrow() != renderer_targets {
+            self.window
+                .compositor_api()
+                .update_intersection_observation_targets(
+                    self.webview_id(),
+                    self.window.pipeline_id(),
+                    renderer_targets.clone(),
+                );
+            *self.renderer_intersection_observation_targets.borrow_mut() = renderer_targets;
         }
     }
 
@@ -3742,6 +4653,8 @@ impl Document {
         &self,
         intersection_observer: &IntersectionObserver,
         time: CrossProcessInstant,
+        viewport_scroll_offset: Vector2D<Au>,
+        renderer_targets: &mut Vec<IntersectionObservationTarget>,
         can_gc: CanGc,
     ) {
         // Step 1
@@ -3755,10 +4668,43 @@ impl Document {
             self,
             time,
             root_bounds,
+            viewport_scroll_offset,
+            renderer_targets,
             can_gc,
         );
     }
 
+    /// Queue entries for the intersection observer targets whose intersection with the
+    /// viewport changed while the renderer scrolled this document.
+    pub(crate) fn handle_renderer_intersection_observation_changes(
+        &self,
+        changes: Vec<IntersectionObservationChange>,
+        can_gc: CanGc,
+    ) {
+        let time = CrossProcessInstant::now();
+        rooted_vec!(let observers <- self.intersection_observers.clone().take().into_iter());
+        for change in changes {
+            if !observers.iter().any(|observer| {
+                observer
+                    .handle_renderer_intersection_observation_change(self, time, &change, can_gc)
+            }) {
+                continue;
+            }
+
+            // Keep the renderer's copy of the target in sync, so that it isn't sent again
+            // only because the renderer reported its new state first.
+            if let Some(target) = self
+                .renderer_intersection_observation_targets
+                .borrow_mut()
+                .iter_mut()
+                .find(|target| target.id == change.id)
+            {
+                target.threshold_index = change.threshold_index;
+                target.is_intersecting = change.is_intersecting;
+            }
+        }
+    }
+
     /// <https://w3c.github.io/IntersectionObserver/#notify-intersection-observers-algo>
     pub(crate) fn notify_intersection_observers(&self, can_gc: CanGc) {
         // Step 1
@@ -3818,11 +4764,39 @@ impl Document {
             ProgressiveWebMetricType::FirstContentfulPaint => {
                 metrics.set_first_contentful_paint(metric_value, first_reflow)
             },
+            ProgressiveWebMetricType::LargestContentfulPaint => {
+                metrics.set_largest_contentful_paint(metric_value)
+            },
             ProgressiveWebMetricType::TimeToInteractive => {
                 unreachable!("Unexpected non-paint metric.")
             },
         }
 
+        if self.window.is_top_level() {
+            let since_navigation_start = |time: Option<CrossProcessInstant>| -> Option<Duration> {
+                (time? - metrics.navigation_start()?).try_into().ok()
+            };
+            let paint_metrics = PaintMetrics {
+                first_paint: since_navigation_start(metrics.first_paint()),
+                first_contentful_paint: since_navigation_start(metrics.first_contentful_paint()),
+                largest_contentful_paint: since_navigation_start(
+                    metrics.largest_contentful_paint(),
+                ),
+            };
+            self.send_to_embedder(EmbedderMsg::NotifyPaintMetricsChanged(
+                self.webview_id(),
+                paint_metrics,
+            ));
+        }
+
+        // Largest contentful paint entries are not exposed to the page yet.
+        if matches!(
+            metric_type,
+            ProgressiveWebMetricType::LargestContentfulPaint
+        ) {
+            return;
+        }
+
         let entry = PerformancePaintTiming::new(
             self.window.as_global_scope(),
             metric_type,
@@ -3939,6 +4913,10 @@ pub(crate) trait LayoutDocumentHelpers<'dom> {
     fn shadow_roots(self) -> Vec<LayoutDom<'dom, ShadowRoot>>;
     fn shadow_roots_styles_changed(self) -> bool;
     fn flush_shadow_roots_stylesheets(self);
+    fn highlights_for_layout(
+        self,
+        node: OpaqueNode,
+    ) -> Vec<(std::ops::Range<usize>, HighlightKind)>;
 }
 
 #[allow(unsafe_code)]
@@ -3983,12 +4961,20 @@ impl<'dom> LayoutDocumentHelpers<'dom> for LayoutDom<'dom, Document> {
     fn flush_shadow_roots_stylesheets(self) {
         (*self.unsafe_get()).flush_shadow_roots_stylesheets()
     }
+
+    #[inline]
+    fn highlights_for_layout(
+        self,
+        node: OpaqueNode,
+    ) -> Vec<(std::ops::Range<usize>, HighlightKind)> {
+        self.unsafe_get().text_highlights.ranges_for_layout(node)
+    }
 }
 
 // https://html.spec.whatwg.org/multipage/#is-a-registrable-domain-suffix-of-or-is-equal-to
 // The spec says to return a bool, we actually return an Option<Host> containing
 // the parsed host in the successful case, to avoid having to re-parse the host.
-fn get_registrable_domain_suffix_of_or_is_equal_to(
+pub(crate) fn get_registrable_domain_suffix_of_or_is_equal_to(
     host_suffix_string: &str,
     original_host: Host,
 ) -> Option<Host> {
@@ -4123,6 +5109,7 @@ impl Document {
             encoding: Cell::new(encoding),
             is_html_document: is_html_document == IsHTMLDocument::HTMLDocument,
             activity: Cell::new(activity),
+            frozen: Cell::new(false),
             tag_map: DomRefCell::new(HashMapTracedValues::new()),
             tagns_map: DomRefCell::new(HashMapTracedValues::new()),
             classes_map: DomRefCell::new(HashMapTracedValues::new()),
@@ -4151,10 +5138,13 @@ impl Document {
             stylesheet_list: MutNullableDom::new(None),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
+            favicon_link_seen: Cell::new(false),
+            load_progress: Default::default(),
             focus_transaction: DomRefCell::new(None),
             focused: Default::default(),
             focus_sequence: Cell::new(FocusSequenceNumber::default()),
             has_focus: Cell::new(has_focus),
+            last_interaction_was_pointer: Cell::new(false),
             current_script: Default::default(),
             pending_parsing_blocking_script: Default::default(),
             script_blocking_stylesheets_count: Cell::new(0u32),
@@ -4165,6 +5155,7 @@ impl Document {
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(VecDeque::new()),
             running_animation_callbacks: Cell::new(false),
+            last_animation_frame_timestamp: Default::default(),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
             base_element: Default::default(),
@@ -4192,6 +5183,8 @@ impl Document {
             spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
             fullscreen_element: MutNullableDom::new(None),
+            picture_in_picture_element: MutNullableDom::new(None),
+            picture_in_picture_window: MutNullableDom::new(None),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::default()),
@@ -4211,6 +5204,7 @@ impl Document {
             media_controls: DomRefCell::new(HashMap::new()),
             dirty_2d_contexts: DomRefCell::new(HashMapTracedValues::new()),
             dirty_webgl_contexts: DomRefCell::new(HashMapTracedValues::new()),
+            placeholder_canvases: DomRefCell::new(HashMapTracedValues::new()),
             #[cfg(feature = "webgpu")]
             webgpu_contexts: Rc::new(RefCell::new(HashMapTracedValues::new())),
             selection: MutNullableDom::new(None),
@@ -4221,6 +5215,10 @@ impl Document {
             },
             animations: DomRefCell::new(Animations::new()),
             image_animation_manager: DomRefCell::new(ImageAnimationManager::default()),
+            drag_and_drop: Default::default(),
+            find_in_page: Default::default(),
+            text_highlights: Default::default(),
+            modal_dialogs: Default::default(),
             dirty_root: Default::default(),
             declarative_refresh: Default::default(),
             pending_input_events: Default::default(),
@@ -4233,10 +5231,17 @@ impl Document {
             allow_declarative_shadow_roots: Cell::new(allow_declarative_shadow_roots),
             inherited_insecure_requests_policy: Cell::new(inherited_insecure_requests_policy),
             has_trustworthy_ancestor_origin: Cell::new(has_trustworthy_ancestor_origin),
+            top_level_origin: Default::default(),
+            ancestor_origins: Default::default(),
+            has_storage_access: Cell::new(false),
             intersection_observer_task_queued: Cell::new(false),
             intersection_observers: Default::default(),
+            next_intersection_observation_id: Cell::new(0),
+            renderer_intersection_observation_targets: Default::default(),
             active_keyboard_modifiers: Cell::new(Modifiers::empty()),
             highlighted_dom_node: Default::default(),
+            speculatively_resolved_hosts: Default::default(),
+            style_attribute_cache: Default::default(),
         }
     }
 
@@ -4297,6 +5302,11 @@ impl Document {
         pending_compositor_events.push(event);
     }
 
+    /// Whether there are compositor events waiting for the next `update_the_rendering` task.
+    pub(crate) fn has_pending_input_events(&self) -> bool {
+        !self.pending_input_events.borrow().is_empty()
+    }
+
     /// Get pending compositor events, for processing within an `update_the_rendering` task.
     pub(crate) fn take_pending_input_events(&self) -> Vec<ConstellationInputEvent> {
         // Reset the mouse event index.
@@ -4522,6 +5532,65 @@ impl Document {
         &self.style_shared_lock
     }
 
+    /// Measure the memory used by the nodes of this document, including the style and
+    /// layout data attached to them, for the memory profiler.
+    #[allow(unsafe_code)]
+    pub(crate) fn dom_tree_memory_report(&self, ops: &mut MallocSizeOfOps) -> Report {
+        // `DomRoot` does not measure what it points to, so measure the node itself. Each node
+        // is allocated as a box of its concrete type, which starts with the `Node`, so the
+        // size of that allocation covers the fields of the concrete type too.
+        let size = self
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::Yes)
+            .map(|node| {
+                let allocation_size = unsafe { ops.malloc_size_of(&*node as *const Node) };
+                allocation_size + (*node).size_of(ops)
+            })
+            .sum();
+        Report {
+            path: path![format!("url({})", self.url()), "dom-tree"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size,
+        }
+    }
+
+    /// Parse the value of the `style` attribute of an element of this document. The
+    /// declaration block is shared with the other elements whose `style` attribute has
+    /// the same value, so it must be copied before being mutated.
+    pub(crate) fn parse_style_attribute(
+        &self,
+        source: &str,
+    ) -> Arc<Locked<PropertyDeclarationBlock>> {
+        let base_url = self.base_url();
+        let parse = || {
+            Arc::new(self.style_shared_lock.wrap(parse_style_attribute(
+                source,
+                &UrlExtraData(base_url.get_arc()),
+                self.window.css_error_reporter(),
+                self.quirks_mode(),
+                CssRuleType::Style,
+            )))
+        };
+        if source.len() > MAX_CACHED_STYLE_ATTRIBUTE_LENGTH {
+            return parse();
+        }
+
+        let mut cache = self.style_attribute_cache.borrow_mut();
+        let key = (base_url.clone(), source.to_owned());
+        if let Some(block) = cache.get(&key) {
+            return block.clone();
+        }
+
+        if cache.len() >= MAX_CACHED_STYLE_ATTRIBUTES {
+            // Forget the blocks that no element uses anymore.
+            cache.retain(|_, block| !block.is_unique());
+            if cache.len() >= MAX_CACHED_STYLE_ATTRIBUTES {
+                return parse();
+            }
+        }
+        cache.entry(key).or_insert_with(parse).clone()
+    }
+
     /// Flushes the stylesheet list, and returns whether any stylesheet changed.
     pub(crate) fn flush_stylesheets_for_reflow(&self) -> bool {
         // NOTE(emilio): The invalidation machinery is used on the replicated
@@ -4661,7 +5730,11 @@ impl Document {
     }
 
     pub(crate) fn get_referrer_policy(&self) -> ReferrerPolicy {
-        self.policy_container.borrow().get_referrer_policy()
+        self.policy_container
+            .borrow()
+            .get_referrer_policy_or(ReferrerPolicy::from_token(&pref!(
+                network_http_default_referrer_policy
+            )))
     }
 
     pub(crate) fn set_target_element(&self, node: Option<&Element>) {
@@ -4732,10 +5805,10 @@ impl Document {
             // For reftests we just take over the current window,
             // and don't try to really enter fullscreen.
             info!("Tests don't really enter fullscreen.");
-        } else {
-            // TODO fullscreen is supported
-            // TODO This algorithm is allowed to request fullscreen.
-            warn!("Fullscreen not supported yet");
+        } else if !self.window.has_transient_activation() {
+            // This algorithm is allowed to request fullscreen only if the relevant global
+            // object has transient activation.
+            error = true;
         }
 
         // Step 5 Parallel start
@@ -4811,6 +5884,148 @@ impl Document {
         self.fullscreen_element.set(element);
     }
 
+    /// Make `container`, the container of a nested browsing context whose document entered
+    /// or exited fullscreen, the fullscreen element of this Document or stop it being one.
+    /// <https://fullscreen.spec.whatwg.org/#dom-element-requestfullscreen>
+    pub(crate) fn set_fullscreen_state_of_container(
+        &self,
+        container: &Element,
+        fullscreen: bool,
+        can_gc: CanGc,
+    ) {
+        let fullscreen_element = self.fullscreen_element.get();
+        if fullscreen {
+            if fullscreen_element.as_deref() == Some(container) {
+                return;
+            }
+            if let Some(fullscreen_element) = fullscreen_element {
+                fullscreen_element.set_fullscreen_state(false);
+            }
+            container.set_fullscreen_state(true);
+            self.set_fullscreen_element(Some(container));
+        } else {
+            if fullscreen_element.as_deref() != Some(container) {
+                return;
+            }
+            container.set_fullscreen_state(false);
+            self.set_fullscreen_element(None);
+        }
+
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("fullscreenchange"), can_gc);
+        self.notify_container_of_fullscreen_state(fullscreen);
+    }
+
+    /// Tell the document that contains the browsing context of this Document, if any, that
+    /// this Document entered or exited fullscreen, so that the container of the browsing
+    /// context fills the viewport along with the fullscreen element of this Document.
+    pub(crate) fn notify_container_of_fullscreen_state(&self, fullscreen: bool) {
+        if self.window.is_top_level() {
+            return;
+        }
+        self.window.send_to_constellation(
+            ScriptToConstellationMessage::SetFullscreenStateOfContainer(fullscreen),
+        );
+    }
+
+    pub(crate) fn picture_in_picture_element(&self) -> Option<DomRoot<Element>> {
+        self.picture_in_picture_element.get()
+    }
+
+    pub(crate) fn picture_in_picture_window(&self) -> Option<DomRoot<PictureInPictureWindow>> {
+        self.picture_in_picture_window.get()
+    }
+
+    /// Make the given video the picture-in-picture element, once the embedder has opened a
+    /// window of the given size for it.
+    /// <https://w3c.github.io/picture-in-picture/#request-pip>
+    pub(crate) fn enter_picture_in_picture(
+        &self,
+        video: &Element,
+        size: DeviceIntSize,
+        can_gc: CanGc,
+    ) -> DomRoot<PictureInPictureWindow> {
+        // Step 8. If pictureInPictureElement is set, exit it first. The window of the
+        // embedder is reused for the new video, so it is not told about this.
+        if self
+            .picture_in_picture_element
+            .get()
+            .is_some_and(|element| &*element != video)
+        {
+            self.exit_picture_in_picture(false, can_gc);
+        }
+
+        // Step 9. Set pictureInPictureElement to video.
+        self.picture_in_picture_element.set(Some(video));
+        let picture_in_picture_window = PictureInPictureWindow::new(&self.window, size, can_gc);
+        self.picture_in_picture_window
+            .set(Some(&picture_in_picture_window));
+        if let Some(media_element) = video.downcast::<HTMLMediaElement>() {
+            media_element.set_picture_in_picture(Some(self.webview_id()));
+        }
+
+        // Step 12. Queue a task to fire an event named enterpictureinpicture, with its
+        // bubbles attribute initialized to true.
+        let event = PictureInPictureEvent::new(
+            &self.window,
+            Atom::from("enterpictureinpicture"),
+            true,
+            false,
+            &picture_in_picture_window,
+            can_gc,
+        );
+        event.upcast::<Event>().fire(video.upcast(), can_gc);
+
+        picture_in_picture_window
+    }
+
+    /// Exit picture-in-picture, if any element of this document is in it. The embedder
+    /// is only notified when this is not a response to it closing the window.
+    /// <https://w3c.github.io/picture-in-picture/#exit-picture-in-picture-algorithm>
+    pub(crate) fn exit_picture_in_picture(&self, notify_embedder: bool, can_gc: CanGc) {
+        let Some(element) = self.picture_in_picture_element.get() else {
+            return;
+        };
+
+        // Step 2. Close the associated Picture-in-Picture window.
+        if let Some(media_element) = element.downcast::<HTMLMediaElement>() {
+            media_element.set_picture_in_picture(None);
+        }
+        if notify_embedder {
+            self.window
+                .send_to_constellation(ScriptToConstellationMessage::ExitPictureInPicture);
+        }
+        let picture_in_picture_window = self.picture_in_picture_window.get();
+        if let Some(picture_in_picture_window) = &picture_in_picture_window {
+            picture_in_picture_window.close();
+        }
+
+        // Step 3. Unset pictureInPictureElement.
+        self.picture_in_picture_element.set(None);
+        self.picture_in_picture_window.set(None);
+
+        // Step 4. Queue a task to fire an event named leavepictureinpicture, with its
+        // bubbles attribute initialized to true.
+        if let Some(picture_in_picture_window) = picture_in_picture_window {
+            let event = PictureInPictureEvent::new(
+                &self.window,
+                Atom::from("leavepictureinpicture"),
+                true,
+                false,
+                &picture_in_picture_window,
+                can_gc,
+            );
+            event.upcast::<Event>().fire(element.upcast(), can_gc);
+        }
+    }
+
+    /// Called when the embedder resizes the picture-in-picture window.
+    pub(crate) fn picture_in_picture_window_resized(&self, size: DeviceIntSize, can_gc: CanGc) {
+        if let Some(picture_in_picture_window) = self.picture_in_picture_window.get() {
+            picture_in_picture_window.resize(size, can_gc);
+        }
+    }
+
     pub(crate) fn get_allow_fullscreen(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#allowed-to-use
         match self.browsing_context() {
@@ -4870,6 +6085,76 @@ impl Document {
         self.shadow_roots_styles_changed.set(false);
     }
 
+    /// The shared implementation of `caretPositionFromPoint` and `caretRangeFromPoint`,
+    /// returning the caret node and caret offset for the given point.
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint>
+    pub(crate) fn caret_position_from_point(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        shadow_roots: &[DomRoot<ShadowRoot>],
+        can_gc: CanGc,
+    ) -> Option<(DomRoot<Node>, u32)> {
+        // Step 1. If there is no viewport associated with the document, return null.
+        if !self.has_browsing_context {
+            return None;
+        }
+
+        // Step 2. If either argument is negative, x is greater than the viewport width
+        // excluding the size of a rendered scroll bar (if any), or y is greater than the
+        // viewport height excluding the size of a rendered scroll bar (if any), return null.
+        let (x, y) = (*x as f32, *y as f32);
+        let viewport = self.window.viewport_details().size;
+        if x < 0.0 || y < 0.0 || x > viewport.width || y > viewport.height {
+            return None;
+        }
+
+        // Step 3. If at the coordinates x,y in the viewport no text insertion point
+        // indicator would have been inserted when applying the same processing model as
+        // for a pointing device, return null.
+        // Step 4. Otherwise, let caretPosition be a tuple consisting of a caretPositionNode
+        // (a node) and a caretPositionOffset (a non-negative integer) for the position
+        // where the text insertion point indicator would have been inserted.
+        let (mut node, utf8_offset) = self
+            .window
+            .caret_position_query(Point2D::new(x, y), can_gc)?;
+        // Layout reports offsets into the UTF-8 text of the node, while DOM offsets are
+        // in UTF-16 code units.
+        let mut offset = node
+            .downcast::<CharacterData>()
+            .map_or(0, |character_data| {
+                character_data
+                    .data()
+                    .char_indices()
+                    .take_while(|(index, _)| *index < utf8_offset)
+                    .map(|(_, character)| character.len_utf16() as u32)
+                    .sum()
+            });
+
+        // Step 5. Let startNode be the caretPositionNode of the caretPosition, and let
+        // startOffset be the caretPositionOffset of the caretPosition.
+        // Step 6. While startNode is a node, startNode's root is a shadow root, and
+        // startNode's root is not a shadow-including inclusive ancestor of any of
+        // options["shadowRoots"], change startOffset to index of startNode's root's host,
+        // and startNode to the host's parent.
+        while let Some(shadow_root) = node.containing_shadow_root() {
+            if shadow_roots.iter().any(|root| {
+                root.upcast::<Node>()
+                    .is_shadow_including_inclusive_ancestor_of(shadow_root.upcast())
+            }) {
+                break;
+            }
+            let host = shadow_root.Host();
+            offset = host.upcast::<Node>().index();
+            node = host.upcast::<Node>().GetParentNode()?;
+        }
+
+        // Step 7. Return a CaretPosition with its offset node set to startNode and its
+        // offset set to startOffset.
+        Some((node, offset))
+    }
+
     pub(crate) fn stylesheet_count(&self) -> usize {
         self.stylesheets.borrow().len()
     }
@@ -4983,6 +6268,12 @@ impl Document {
         self.animation_timeline.borrow().current_value()
     }
 
+    /// The value of the animation timeline when this document was created, in seconds,
+    /// which is the zero time of its timeline as exposed to script.
+    pub(crate) fn animation_timeline_origin(&self) -> f64 {
+        self.animation_timeline.borrow().origin()
+    }
+
     pub(crate) fn animations(&self) -> Ref<Animations> {
         self.animations.borrow()
     }
@@ -5219,6 +6510,32 @@ impl Document {
         self.has_trustworthy_ancestor_origin.get()
     }
 
+    pub(crate) fn top_level_origin(&self) -> Option<ImmutableOrigin> {
+        self.top_level_origin.borrow().clone()
+    }
+
+    pub(crate) fn set_top_level_origin(&self, origin: Option<ImmutableOrigin>) {
+        *self.top_level_origin.borrow_mut() = origin;
+    }
+
+    /// The origin of the ancestor document at the given depth, where the parent is at depth 0.
+    pub(crate) fn ancestor_origin(&self, depth: usize) -> Option<ImmutableOrigin> {
+        self.ancestor_origins.borrow().get(depth).cloned()
+    }
+
+    pub(crate) fn set_ancestor_origins(&self, origins: Vec<ImmutableOrigin>) {
+        *self.ancestor_origins.borrow_mut() = origins;
+    }
+
+    /// Whether this document is nested in a top-level document of a different site, in
+    /// which case its cookies and storage are partitioned.
+    fn is_third_party(&self) -> bool {
+        self.top_level_origin().is_some_and(|top_level_origin| {
+            Site::for_origin(top_level_origin) !=
+                Site::for_origin(self.origin().immutable().clone())
+        })
+    }
+
     pub fn has_trustworthy_ancestor_or_current_origin(&self) -> bool {
         self.has_trustworthy_ancestor_origin.get() ||
             self.origin().immutable().is_potentially_trustworthy()
@@ -5267,11 +6584,48 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         ))
     }
 
-    // https://w3c.github.io/editing/ActiveDocuments/execCommand.html#querycommandsupported()
-    fn QueryCommandSupported(&self, _command: DOMString) -> bool {
+    // https://w3c.github.io/editing/docs/execCommand/#execcommand()
+    fn ExecCommand(
+        &self,
+        command_id: DOMString,
+        _show_ui: bool,
+        value: DOMString,
+        can_gc: CanGc,
+    ) -> bool {
+        editing::exec_command(self, &command_id, value, can_gc)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()
+    fn QueryCommandEnabled(&self, command_id: DOMString, can_gc: CanGc) -> bool {
+        editing::is_command_enabled(self, &command_id, can_gc)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandindeterm()
+    fn QueryCommandIndeterm(&self, _command_id: DOMString) -> bool {
         false
     }
 
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandstate()
+    fn QueryCommandState(&self, command_id: DOMString, can_gc: CanGc) -> bool {
+        editing::command_state(self, &command_id, can_gc)
+    }
+
+    // https://w3c.github.io/editing/ActiveDocuments/execCommand.html#querycommandsupported()
+    fn QueryCommandSupported(&self, command_id: DOMString) -> bool {
+        editing::is_command_supported(&command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandvalue()
+    fn QueryCommandValue(&self, _command_id: DOMString) -> DOMString {
+        DOMString::new()
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-documentorshadowroot-getanimations
+    fn GetAnimations(&self, can_gc: CanGc) -> Vec<DomRoot<Animation>> {
+        self.document_or_shadow_root
+            .get_animations(self.upcast(), can_gc)
+    }
+
     // https://drafts.csswg.org/cssom/#dom-document-stylesheets
     fn StyleSheets(&self, can_gc: CanGc) -> DomRoot<StyleSheetList> {
         self.stylesheet_list.or_init(|| {
@@ -5359,6 +6713,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             return Err(Error::Security);
         }
 
+        ReportingObserver::report_deprecation(
+            self.window.as_global_scope(),
+            "DocumentDomainSetter",
+            "Setting document.domain is deprecated, as it relaxes the same-origin policy.",
+        );
+
         // TODO: Step 2. "If this Document object's active sandboxing
         // flag set has its sandboxed document.domain browsing context
         // flag set, then throw a "SecurityError" DOMException."
@@ -6149,7 +7509,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             .window
             .as_global_scope()
             .resource_threads()
-            .send(GetCookiesForUrl(url, tx, NonHTTP));
+            .send(GetCookiesForUrl(
+                url,
+                self.window.pipeline_id(),
+                tx,
+                NonHTTP,
+            ));
         let cookies = rx.recv().unwrap();
         Ok(cookies.map_or(DOMString::new(), DOMString::from))
     }
@@ -6174,7 +7539,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             .window
             .as_global_scope()
             .resource_threads()
-            .send(SetCookiesForUrl(self.url(), cookies, NonHTTP));
+            .send(SetCookiesForUrl(
+                self.url(),
+                self.window.pipeline_id(),
+                cookies,
+                NonHTTP,
+            ));
         Ok(())
     }
 
@@ -6357,6 +7727,30 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         SetOnreadystatechange
     );
 
+    /// <https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint>
+    fn CaretPositionFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        options: &CaretPositionFromPointOptions,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<CaretPosition>> {
+        let (node, offset) = self.caret_position_from_point(x, y, &options.shadowRoots, can_gc)?;
+        Some(CaretPosition::new(&self.window, &node, offset, can_gc))
+    }
+
+    /// A non-standard version of `caretPositionFromPoint` which returns a collapsed
+    /// [`Range`], as supported by WebKit and Blink.
+    fn CaretRangeFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<Range>> {
+        let (node, offset) = self.caret_position_from_point(x, y, &[], can_gc)?;
+        Some(Range::new(self, &node, offset, &node, offset, can_gc))
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     fn ElementFromPoint(
         &self,
@@ -6365,6 +7759,7 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         can_gc: CanGc,
     ) -> Option<DomRoot<Element>> {
         self.document_or_shadow_root.element_from_point(
+            self.upcast(),
             x,
             y,
             self.GetDocumentElement(),
@@ -6381,6 +7776,7 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         can_gc: CanGc,
     ) -> Vec<DomRoot<Element>> {
         self.document_or_shadow_root.elements_from_point(
+            self.upcast(),
             x,
             y,
             self.GetDocumentElement(),
@@ -6589,6 +7985,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         Ok(())
     }
 
+    // https://wicg.github.io/page-lifecycle/#dom-document-onfreeze
+    event_handler!(freeze, GetOnfreeze, SetOnfreeze);
+
+    // https://wicg.github.io/page-lifecycle/#dom-document-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
     // https://fullscreen.spec.whatwg.org/#handler-document-onfullscreenerror
     event_handler!(fullscreenerror, GetOnfullscreenerror, SetOnfullscreenerror);
 
@@ -6620,6 +8022,110 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         self.exit_fullscreen(can_gc)
     }
 
+    // https://w3c.github.io/picture-in-picture/#dom-document-pictureinpictureenabled
+    fn PictureInPictureEnabled(&self) -> bool {
+        self.has_browsing_context
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement
+    fn GetPictureInPictureElement(&self) -> Option<DomRoot<Element>> {
+        // TODO ShadowRoot
+        self.picture_in_picture_element.get()
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-document-exitpictureinpicture
+    fn ExitPictureInPicture(&self, can_gc: CanGc) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
+        // Step 1. If pictureInPictureElement is null, throw a InvalidStateError.
+        if self.picture_in_picture_element.get().is_none() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+        // Step 2. Run the exit Picture-in-Picture algorithm.
+        self.exit_picture_in_picture(true, can_gc);
+        promise.resolve_native(&(), can_gc);
+        promise
+    }
+
+    /// <https://privacycg.github.io/storage-access/#dom-document-hasstorageaccess>
+    fn HasStorageAccess(&self, can_gc: CanGc) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
+
+        // Step 3. If doc is not fully active, then reject p with an "InvalidStateError"
+        // DOMException and return p.
+        if !self.is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 4. If doc's origin is an opaque origin, then resolve p with false and return p.
+        if !self.origin().is_tuple() {
+            promise.resolve_native(&false, can_gc);
+            return promise;
+        }
+
+        // Step 5-6. If global is not a secure context, or doc is the top-level document, or
+        // doc is same-site with the top-level document, resolve p with true. Such documents
+        // are never partitioned.
+        // NOTE: Non-secure contexts are not partitioned differently from secure ones, so
+        // there is no need to special-case them here.
+        // Step 7. Resolve p with global's has storage access.
+        let has_access = !self.is_third_party() || self.has_storage_access.get();
+        promise.resolve_native(&has_access, can_gc);
+        promise
+    }
+
+    /// <https://privacycg.github.io/storage-access/#dom-document-requeststorageaccess>
+    fn RequestStorageAccess(&self, can_gc: CanGc) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
+
+        // Step 4. If doc is not fully active, then reject p with an "InvalidStateError"
+        // DOMException and return p.
+        if !self.is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 5. If global's has storage access is true, resolve p with undefined and return p.
+        // Step 9. If doc's browsing context is a top-level browsing context, or doc is
+        // same-site with the top-level document, resolve p with undefined and return p.
+        if self.has_storage_access.get() || !self.is_third_party() {
+            promise.resolve_native(&(), can_gc);
+            return promise;
+        }
+
+        // Step 6. If doc's origin is an opaque origin, reject p with a "NotAllowedError"
+        // DOMException and return p.
+        if !self.origin().is_tuple() {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return promise;
+        }
+
+        // Step 13. Let permissionState be the result of requesting permission to use
+        // "storage-access". The constellation asks the user, which requires transient
+        // activation, and grants the access for the sites of this document and of the
+        // top-level document as it knows them.
+        // The answer is handled by `handle_response`, without blocking the event loop while
+        // the user decides.
+        let sender = route_promise(
+            &promise,
+            self,
+            self.owner_global()
+                .task_manager()
+                .dom_manipulation_task_source(),
+        );
+        self.window.as_global_scope().send_to_constellation(
+            ScriptToConstellationMessage::RequestStorageAccess(
+                self.window.has_transient_activation(),
+                sender,
+            ),
+        );
+        promise
+    }
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -6711,16 +8217,38 @@ fn update_with_current_instant(marker: &Cell<Option<CrossProcessInstant>>) {
 
 /// <https://w3c.github.io/webappsec-referrer-policy/#determine-policy-for-token>
 pub(crate) fn determine_policy_for_token(token: &str) -> ReferrerPolicy {
-    match_ignore_ascii_case! { token,
-        "never" | "no-referrer" => ReferrerPolicy::NoReferrer,
-        "no-referrer-when-downgrade" => ReferrerPolicy::NoReferrerWhenDowngrade,
-        "origin" => ReferrerPolicy::Origin,
-        "same-origin" => ReferrerPolicy::SameOrigin,
-        "strict-origin" => ReferrerPolicy::StrictOrigin,
-        "default" | "strict-origin-when-cross-origin" => ReferrerPolicy::StrictOriginWhenCrossOrigin,
-        "origin-when-cross-origin" => ReferrerPolicy::OriginWhenCrossOrigin,
-        "always" | "unsafe-url" => ReferrerPolicy::UnsafeUrl,
-        _ => ReferrerPolicy::EmptyString,
+    ReferrerPolicy::from_token(token)
+}
+
+/// Update the `:focus-within` state of the shadow-including ancestors of the old and new
+/// focused elements. Only the ancestors that are not shared by both elements change state,
+/// so that moving focus between siblings does not invalidate the style of the entire
+/// ancestor chain.
+fn update_focus_within_state(old_focused: Option<&Element>, new_focused: Option<&Element>) {
+    let inclusive_ancestors = |element: Option<&Element>| -> Vec<DomRoot<Element>> {
+        element
+            .into_iter()
+            .flat_map(|element| {
+                element
+                    .upcast::<Node>()
+                    .inclusive_ancestors(ShadowIncluding::Yes)
+            })
+            .filter_map(DomRoot::downcast::<Element>)
+            .collect()
+    };
+
+    let new_ancestors = inclusive_ancestors(new_focused);
+    for element in inclusive_ancestors(old_focused) {
+        if new_ancestors.contains(&element) {
+            break;
+        }
+        element.set_focus_within_state(false);
+    }
+    for element in new_ancestors {
+        if element.focus_within_state() {
+            break;
+        }
+        element.set_focus_within_state(true);
     }
 }
 
@@ -6895,3 +8423,19 @@ impl DocumentHelpers for Document {
         Document::ensure_safe_to_run_script_or_layout(self)
     }
 }
+
+impl RoutedPromiseListener<bool> for Document {
+    /// The end of <https://privacycg.github.io/storage-access/#dom-document-requeststorageaccess>,
+    /// once the constellation knows whether storage access was granted.
+    fn handle_response(&self, granted: bool, promise: &Rc<Promise>, can_gc: CanGc) {
+        // Step 14. If permissionState is "granted", set global's has storage access to true,
+        // and resolve p with undefined.
+        // Step 15. Otherwise, reject p with a "NotAllowedError" DOMException.
+        if !granted {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return;
+        }
+        self.has_storage_access.set(true);
+        promise.resolve_native(&(), can_gc);
+    }
+}
@@ -16,15 +16,18 @@ use style::stylesheets::{Stylesheet, StylesheetContents};
 use stylo_atoms::Atom;
 
 use super::bindings::trace::HashMapTracedValues;
+use crate::dom::animation::Animation;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::Node_Binding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{self, Node, VecPreOrderInsertionHelper};
+use crate::dom::node::{self, Node, ShadowIncluding, VecPreOrderInsertionHelper};
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
@@ -110,10 +113,32 @@ impl DocumentOrShadowRoot {
             .query_nodes_from_point(*client_point, query_type)
     }
 
+    /// The element on whose behalf the node at `address` was hit, retargeted against
+    /// `context`, which is the document or shadow root that is queried. Text is hit on
+    /// behalf of its parent element, or the host of its parent shadow root.
     #[allow(unsafe_code)]
+    fn element_for_hit(address: UntrustedNodeAddress, context: &Node) -> Option<DomRoot<Element>> {
+        let node = unsafe { node::from_untrusted_node_address(address) };
+        let element = match DomRoot::downcast::<Element>(node.clone()) {
+            Some(element) => element,
+            None => {
+                let parent_node = node.GetParentNode()?;
+                match parent_node.downcast::<ShadowRoot>() {
+                    Some(shadow_root) => shadow_root.Host(),
+                    None => DomRoot::downcast::<Element>(parent_node)?,
+                }
+            },
+        };
+
+        // Return the result of running the retargeting algorithm with the original result
+        // as input and context object.
+        DomRoot::downcast::<Element>(element.upcast::<EventTarget>().retarget(context.upcast()))
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     pub(crate) fn element_from_point(
         &self,
+        context: &Node,
         x: Finite<f64>,
         y: Finite<f64>,
         document_element: Option<DomRoot<Element>>,
@@ -137,31 +162,15 @@ impl DocumentOrShadowRoot {
             .nodes_from_point(point, NodesFromPointQueryType::Topmost, can_gc)
             .first()
         {
-            Some(address) => {
-                let node = unsafe { node::from_untrusted_node_address(*address) };
-                let parent_node = node.GetParentNode().unwrap();
-                let shadow_host = parent_node
-                    .downcast::<ShadowRoot>()
-                    .map(ShadowRootMethods::Host);
-                let element_ref = node
-                    .downcast::<Element>()
-                    .or(shadow_host.as_deref())
-                    .unwrap_or_else(|| {
-                        parent_node
-                            .downcast::<Element>()
-                            .expect("Hit node should have an element or shadowroot parent")
-                    });
-
-                Some(DomRoot::from_ref(element_ref))
-            },
+            Some(address) => Self::element_for_hit(*address, context).or(document_element),
             None => document_element,
         }
     }
 
-    #[allow(unsafe_code)]
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
     pub(crate) fn elements_from_point(
         &self,
+        context: &Node,
         x: Finite<f64>,
         y: Finite<f64>,
         document_element: Option<DomRoot<Element>>,
@@ -183,18 +192,23 @@ impl DocumentOrShadowRoot {
         }
 
         // Step 1 and Step 3
+        // Hit test results are in paint order, topmost first. Several items of the same
+        // element, such as its background and its text, can be hit at the same point, but
+        // each element is only listed once, at its topmost position.
         let nodes = self.nodes_from_point(point, NodesFromPointQueryType::All, can_gc);
-        let mut elements: Vec<DomRoot<Element>> = nodes
-            .iter()
-            .flat_map(|&untrusted_node_address| {
-                let node = unsafe { node::from_untrusted_node_address(untrusted_node_address) };
-                DomRoot::downcast::<Element>(node)
-            })
-            .collect();
+        let mut elements: Vec<DomRoot<Element>> = Vec::with_capacity(nodes.len());
+        for address in nodes {
+            let Some(element) = Self::element_for_hit(address, context) else {
+                continue;
+            };
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
 
         // Step 4
         if let Some(root_element) = document_element {
-            if elements.last() != Some(&root_element) {
+            if !elements.contains(&root_element) {
                 elements.push(root_element);
             }
         }
@@ -204,6 +218,20 @@ impl DocumentOrShadowRoot {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-document-activeelement
+    /// <https://drafts.csswg.org/web-animations-1/#dom-documentorshadowroot-getanimations>
+    pub(crate) fn get_animations(&self, root: &Node, can_gc: CanGc) -> Vec<DomRoot<Animation>> {
+        // Make sure that style has created the animations and transitions that are due.
+        self.window.layout_reflow(QueryMsg::StyleQuery, can_gc);
+
+        let elements = root
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>);
+        self.window
+            .Document()
+            .animations()
+            .get_animations(elements, &self.window, can_gc)
+    }
+
     pub(crate) fn get_active_element(
         &self,
         focused_element: Option<DomRoot<Element>>,
@@ -16,14 +16,14 @@ use cssparser::{Parser as CssParser, ParserInput as CssParserInput, match_ignore
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
 use embedder_traits::InputMethodType;
-use euclid::default::{Rect, Size2D};
+use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D, Vector2D};
 use html5ever::serialize::TraversalScope;
 use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use html5ever::{LocalName, Namespace, Prefix, QualName, local_name, namespace_prefix, ns};
 use js::jsapi::Heap;
 use js::jsval::JSVal;
 use js::rust::HandleObject;
-use layout_api::LayoutDamage;
+use layout_api::{LayoutDamage, QueryMsg};
 use net_traits::ReferrerPolicy;
 use net_traits::request::CorsSettings;
 use selectors::Element as SelectorsElement;
@@ -43,7 +43,6 @@ use style::properties::longhands::{
 };
 use style::properties::{
     ComputedValues, Importance, PropertyDeclaration, PropertyDeclarationBlock,
-    parse_style_attribute,
 };
 use style::rule_tree::CascadeLevel;
 use style::selector_parser::{
@@ -53,8 +52,9 @@ use style::selector_parser::{
 use style::shared_lock::{Locked, SharedRwLock};
 use style::stylesheets::layer_rule::LayerOrder;
 use style::stylesheets::{CssRuleType, Origin as CssOrigin, UrlExtraData};
-use style::values::computed::Overflow;
+use style::values::computed::{Length, NonNegativeLengthPercentageOrAuto, Overflow};
 use style::values::generics::NonNegative;
+use style::values::generics::length::GenericLengthPercentageOrAuto as LengthPercentageOrAuto;
 use style::values::generics::position::PreferredRatio;
 use style::values::generics::ratio::Ratio;
 use style::values::{AtomIdent, AtomString, CSSFloat, computed, specified};
@@ -68,12 +68,14 @@ use xml5ever::serialize::TraversalScope::{
 
 use crate::conversions::Convert;
 use crate::dom::activation::Activatable;
+use crate::dom::animation::Animation;
 use crate::dom::attr::{Attr, AttrHelpersForLayout, is_relevant_attribute};
 use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut};
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::GetAnimationsOptions;
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::{
-    ElementMethods, GetHTMLOptions, ShadowRootInit,
+    ElementMethods, GetHTMLOptions, ScrollLogicalPosition, ShadowRootInit,
 };
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
@@ -85,7 +87,8 @@ use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     ScrollBehavior, ScrollToOptions, WindowMethods,
 };
 use crate::dom::bindings::codegen::UnionTypes::{
-    NodeOrString, TrustedHTMLOrNullIsEmptyString, TrustedHTMLOrString, TrustedScriptURLOrUSVString,
+    BooleanOrScrollIntoViewOptions, NodeOrString, TrustedHTMLOrNullIsEmptyString,
+    TrustedHTMLOrString, TrustedScriptURLOrUSVString,
 };
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::domname::{
@@ -162,6 +165,7 @@ use crate::dom::trustedhtml::TrustedHTML;
 use crate::dom::validation::Validatable;
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::{VirtualMethods, vtable_for};
+use crate::editing::{EditingHistory, is_editing_host};
 use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
@@ -756,6 +760,45 @@ impl Element {
         true
     }
 
+    // https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking
+    pub(crate) fn is_spellcheck_enabled(&self) -> bool {
+        let name = &html5ever::local_name!("spellcheck");
+        if self.has_attribute(name) {
+            match_ignore_ascii_case! { &*self.get_string_attribute(name),
+                "true" | "" => return true,
+                "false" => return false,
+                _ => {},
+            }
+        }
+        if let Some(parent) = self.upcast::<Node>().GetParentNode() {
+            if let Some(elem) = parent.downcast::<Element>() {
+                return elem.is_spellcheck_enabled();
+            }
+        }
+        // Editable text is checked by default.
+        true
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    pub(crate) fn is_draggable(&self) -> bool {
+        let name = &html5ever::local_name!("draggable");
+        match_ignore_ascii_case! { &*self.get_string_attribute(name),
+            "true" => return true,
+            "false" => return false,
+            _ => {},
+        }
+        // Images and hyperlinks with an href are draggable by default.
+        match self.upcast::<Node>().type_id() {
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLImageElement,
+            )) => true,
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLAnchorElement,
+            )) => self.has_attribute(&local_name!("href")),
+            _ => false,
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-directionality
     pub(crate) fn directionality(&self) -> String {
         self.downcast::<HTMLElement>()
@@ -830,6 +873,12 @@ impl Element {
             .retain(|reg_obs| *reg_obs.observer != *observer)
     }
 
+    /// Run `f` with the undo and redo history of this element as an editing host.
+    /// Lazily initialize the raredata if it does not exist.
+    pub(crate) fn with_editing_history<R>(&self, f: impl FnOnce(&mut EditingHistory) -> R) -> R {
+        f(&mut self.ensure_rare_data().editing_history)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#matches-the-environment>
     pub(crate) fn matches_environment(&self, media_query: &str) -> bool {
         let document = self.owner_document();
@@ -1741,10 +1790,10 @@ impl Element {
     }
 
     pub(crate) fn is_focusable_area(&self) -> bool {
-        if self.is_actually_disabled() {
+        let node = self.upcast::<Node>();
+        if self.is_actually_disabled() || node.is_inert() {
             return false;
         }
-        let node = self.upcast::<Node>();
         if node.get_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE) {
             return true;
         }
@@ -2302,7 +2351,6 @@ impl Element {
                     attr.swap_value(&mut value);
                     block
                 } else {
-                    let win = self.owner_window();
                     let source = &**attr.value();
                     let global = &self.owner_global();
                     // However, if the Should element's inline behavior be blocked by
@@ -2320,13 +2368,7 @@ impl Element {
                     {
                         return;
                     }
-                    Arc::new(doc.style_shared_lock().wrap(parse_style_attribute(
-                        source,
-                        &UrlExtraData(doc.base_url().get_arc()),
-                        win.css_error_reporter(),
-                        doc.quirks_mode(),
-                        CssRuleType::Style,
-                    )))
+                    doc.parse_style_attribute(source)
                 };
 
                 Some(block)
@@ -2495,6 +2537,152 @@ impl Element {
         win.scroll_node(node, x, y, behavior, can_gc);
     }
 
+    /// Scroll the scroll containers of this element and the viewport by the smallest amount
+    /// that brings its border box, expanded by its `scroll-margin`, into view. This is what
+    /// happens when an element gets focus.
+    pub(crate) fn scroll_into_view_if_needed(&self, can_gc: CanGc) {
+        self.scroll_into_view(
+            ScrollBehavior::Auto,
+            ScrollLogicalPosition::Nearest,
+            ScrollLogicalPosition::Nearest,
+            can_gc,
+        );
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+    pub(crate) fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+        can_gc: CanGc,
+    ) {
+        let node = self.upcast::<Node>();
+        let (Some(style), Some(border_box)) =
+            (self.style(can_gc), node.bounding_content_box(can_gc))
+        else {
+            return;
+        };
+
+        // Layout geometry doesn't take scroll offsets into account, so the target starts out
+        // relative to the unscrolled document and is moved by every scroll container that it
+        // is scrolled within on the way up.
+        let margin = style.get_margin();
+        let mut target = Rect::new(
+            Point2D::new(
+                border_box.origin.x.to_f64_px(),
+                border_box.origin.y.to_f64_px(),
+            ),
+            Size2D::new(
+                border_box.size.width.to_f64_px(),
+                border_box.size.height.to_f64_px(),
+            ),
+        )
+        .outer_rect(SideOffsets2D::new(
+            margin.scroll_margin_top.px() as f64,
+            margin.scroll_margin_right.px() as f64,
+            margin.scroll_margin_bottom.px() as f64,
+            margin.scroll_margin_left.px() as f64,
+        ));
+
+        // Step 1. For each ancestor element or viewport that establishes a scrolling box, in
+        // order of innermost to outermost scrolling box:
+        let ancestors = node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>);
+        for ancestor in ancestors {
+            // The root element scrolls the viewport, which is handled below.
+            if *ancestor.root_element() == *ancestor {
+                break;
+            }
+            if !ancestor.has_scrolling_box(can_gc) {
+                continue;
+            }
+            let (Some(container_style), Some(container_box)) = (
+                ancestor.style(can_gc),
+                ancestor.upcast::<Node>().bounding_content_box(can_gc),
+            ) else {
+                continue;
+            };
+
+            // Scroll offsets are relative to the padding box of the scroll container.
+            let padding_box_origin = Vector2D::new(
+                container_box.origin.x.to_f64_px() + ancestor.ClientLeft(can_gc) as f64,
+                container_box.origin.y.to_f64_px() + ancestor.ClientTop(can_gc) as f64,
+            );
+            let scrollport = Rect::new(
+                Point2D::new(ancestor.ScrollLeft(can_gc), ancestor.ScrollTop(can_gc)),
+                Size2D::new(
+                    ancestor.ClientWidth(can_gc) as f64,
+                    ancestor.ClientHeight(can_gc) as f64,
+                ),
+            );
+
+            // Step 1.2. Let position be the scroll position resulting from running the steps
+            // to determine the scroll-into-view position of target with behavior as the
+            // scroll behavior, block as the block flow position, inline as the inline base
+            // direction position and element as the scrolling box.
+            let position = scroll_into_view_position(
+                &target.translate(-padding_box_origin),
+                &scrollport,
+                &container_style,
+                block,
+                inline,
+            );
+
+            // Step 1.3. If position is not the same as scrolling box’s current scroll
+            // position, or scrolling box has an ongoing smooth scroll, perform a scroll of
+            // scrolling box to position, element as the associated element and behavior as
+            // the scroll behavior.
+            if position != scrollport.origin {
+                ancestor.scroll(position.x, position.y, behavior, can_gc);
+            }
+
+            // Layout knows about the final offset of a smooth scroll right away, so the
+            // target can be positioned within the next scroll container immediately.
+            let scroll_offset =
+                Vector2D::new(ancestor.ScrollLeft(can_gc), ancestor.ScrollTop(can_gc));
+            target = target.translate(-scroll_offset);
+        }
+
+        // The viewport takes its `scroll-padding` from the root element.
+        let document = node.owner_doc();
+        let Some(root_style) = document
+            .GetDocumentElement()
+            .and_then(|root_element| root_element.style(can_gc))
+        else {
+            return;
+        };
+        let window = document.window();
+        let scroll_offset = window.scroll_offset(can_gc);
+        let viewport = Rect::new(
+            Point2D::new(scroll_offset.x as f64, scroll_offset.y as f64),
+            Size2D::new(window.InnerWidth() as f64, window.InnerHeight() as f64),
+        );
+        let position = scroll_into_view_position(&target, &viewport, &root_style, block, inline);
+        if position != viewport.origin {
+            window.scroll(position.x, position.y, behavior, can_gc);
+        }
+    }
+
+    /// The [focus delegate] of this element when it is a shadow host whose shadow root
+    /// delegates focus, which is the first focusable area in its shadow tree.
+    ///
+    /// [focus delegate]: https://html.spec.whatwg.org/multipage/#focus-delegate
+    pub(crate) fn focus_delegate(&self) -> Option<DomRoot<Element>> {
+        let shadow_root = self
+            .shadow_root()
+            .filter(|shadow_root| shadow_root.DelegatesFocus())?;
+        shadow_root
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| {
+                element.is_focusable_area() && !element.upcast::<Node>().is_display_none()
+            })
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#fragment-parsing-algorithm-steps>
     pub(crate) fn parse_fragment(
         &self,
@@ -2619,11 +2807,11 @@ impl Element {
                 HTMLElementTypeId::HTMLAnchorElement,
             )) => element.has_attribute(&local_name!("href")),
 
-            //TODO focusable if editing host
             //TODO focusable if "sorting interface th elements"
             _ => {
-                // Draggable elements are focusable.
-                element.get_string_attribute(&local_name!("draggable")) == "true"
+                // Draggable elements and editing hosts are focusable.
+                element.get_string_attribute(&local_name!("draggable")) == "true" ||
+                    is_editing_host(element)
             },
         }
     }
@@ -3056,6 +3244,61 @@ impl ElementMethods<crate::DomTypeHolder> for Element {
         )
     }
 
+    // https://drafts.csswg.org/web-animations-1/#dom-animatable-getanimations
+    fn GetAnimations(
+        &self,
+        options: &GetAnimationsOptions,
+        can_gc: CanGc,
+    ) -> Vec<DomRoot<Animation>> {
+        // Make sure that style has created the animations and transitions that are due.
+        let window = self.owner_window();
+        window.layout_reflow(QueryMsg::StyleQuery, can_gc);
+
+        let node = self.upcast::<Node>();
+        let elements: Vec<_> = if options.subtree {
+            node.traverse_preorder(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<Element>)
+                .collect()
+        } else {
+            vec![DomRoot::from_ref(self)]
+        };
+        self.owner_document()
+            .animations()
+            .get_animations(elements.into_iter(), &window, can_gc)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions, can_gc: CanGc) {
+        // Steps 1-3. Determine behavior, block and inline from arg.
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::Boolean(true) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::Start,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::Boolean(false) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::End,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+        };
+
+        // Step 4. If the element does not have any associated box, or is not available to
+        // user-agent features, then return.
+        if !self.has_css_layout_box(can_gc) {
+            return;
+        }
+
+        // Step 5. Scroll the element into view with behavior, block, inline, and container.
+        self.scroll_into_view(behavior, block, inline, can_gc);
+
+        // TODO: Step 6. Optionally perform some other action that brings the element to the
+        // user’s attention.
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions, can_gc: CanGc) {
         // Step 1
@@ -4175,9 +4418,10 @@ impl VirtualMethods for Element {
         let node = self.upcast::<Node>();
         let doc = node.owner_doc();
         match attr.local_name() {
-            &local_name!("tabindex") | &local_name!("draggable") | &local_name!("hidden") => {
-                self.update_sequentially_focusable_status(can_gc)
-            },
+            &local_name!("tabindex") |
+            &local_name!("draggable") |
+            &local_name!("hidden") |
+            &local_name!("contenteditable") => self.update_sequentially_focusable_status(can_gc),
             &local_name!("style") => self.update_style_attribute(attr, mutation),
             &local_name!("id") => {
                 *self.id_attribute.borrow_mut() = mutation.new_value(attr).and_then(|value| {
@@ -4896,6 +5140,17 @@ impl Element {
         false
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#user-validity>
+    pub(crate) fn set_user_validity(&self, user_validity: bool, can_gc: CanGc) {
+        if let Some(validatable) = self.as_maybe_validatable() {
+            validatable
+                .validity_state()
+                .set_user_validity(user_validity, can_gc);
+        } else if let Some(internals) = self.get_element_internals() {
+            internals.set_user_validity(user_validity, can_gc);
+        }
+    }
+
     pub(crate) fn is_instance_validatable(&self) -> bool {
         if let Some(validatable) = self.as_maybe_validatable() {
             return validatable.is_instance_validatable();
@@ -4977,9 +5232,26 @@ impl Element {
 
     pub(crate) fn set_focus_state(&self, value: bool) {
         self.set_state(ElementState::FOCUS, value);
+        if !value {
+            self.set_state(ElementState::FOCUSRING, false);
+        }
         self.upcast::<Node>().dirty(NodeDamage::Other);
     }
 
+    /// <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>
+    pub(crate) fn set_focus_visible_state(&self, value: bool) {
+        self.set_state(ElementState::FOCUSRING, value && self.focus_state());
+    }
+
+    pub(crate) fn focus_within_state(&self) -> bool {
+        self.state.get().contains(ElementState::FOCUS_WITHIN)
+    }
+
+    /// <https://drafts.csswg.org/selectors-4/#the-focus-within-pseudo>
+    pub(crate) fn set_focus_within_state(&self, value: bool) {
+        self.set_state(ElementState::FOCUS_WITHIN, value);
+    }
+
     pub(crate) fn hover_state(&self) -> bool {
         self.state.get().contains(ElementState::HOVER)
     }
@@ -5031,6 +5303,10 @@ impl Element {
         self.set_state(ElementState::FULLSCREEN, value)
     }
 
+    pub(crate) fn set_modal_state(&self, value: bool) {
+        self.set_state(ElementState::MODAL, value)
+    }
+
     /// <https://dom.spec.whatwg.org/#connected>
     pub(crate) fn is_connected(&self) -> bool {
         self.upcast::<Node>().is_connected()
@@ -5213,8 +5489,12 @@ impl TaskOnce for ElementPerformFullscreenEnter {
 
         // TODO Step 7.2-4
         // Step 7.5
+        if let Some(fullscreen_element) = document.GetFullscreenElement() {
+            fullscreen_element.set_fullscreen_state(false);
+        }
         element.set_fullscreen_state(true);
         document.set_fullscreen_element(Some(&element));
+        document.notify_container_of_fullscreen_state(true);
 
         // Step 7.6
         document
@@ -5249,6 +5529,7 @@ impl TaskOnce for ElementPerformFullscreenExit {
         // Step 9.6
         element.set_fullscreen_state(false);
         document.set_fullscreen_element(None);
+        document.notify_container_of_fullscreen_state(false);
 
         // Step 9.8
         document
@@ -5321,3 +5602,83 @@ pub(crate) fn cors_setting_for_element(element: &Element) -> Option<CorsSettings
         _ => unreachable!(),
     })
 }
+
+/// <https://drafts.csswg.org/cssom-view/#determine-the-scroll-into-view-position>
+///
+/// The scroll position that aligns `target` with `scrollport` as requested by `block` and
+/// `inline`, where `scrollport` is the area currently shown by a scroll container whose
+/// style is `container_style`. Both are relative to the origin of the scrollable content.
+fn scroll_into_view_position(
+    target: &Rect<f64>,
+    scrollport: &Rect<f64>,
+    container_style: &ComputedValues,
+    block: ScrollLogicalPosition,
+    inline: ScrollLogicalPosition,
+) -> Point2D<f64> {
+    // Targets are aligned with the optimal viewing region of the scrollport, which excludes
+    // its `scroll-padding`. Percentages are relative to the size of the scrollport.
+    let padding = container_style.get_padding();
+    let resolve = |value: &NonNegativeLengthPercentageOrAuto, basis: f64| match value {
+        // `auto` leaves the padding to the user agent, which doesn't add any.
+        LengthPercentageOrAuto::Auto => 0.0,
+        LengthPercentageOrAuto::LengthPercentage(value) => {
+            value.0.resolve(Length::new(basis as f32)).px() as f64
+        },
+    };
+    let scroll_padding = SideOffsets2D::new(
+        resolve(&padding.scroll_padding_top, scrollport.height()),
+        resolve(&padding.scroll_padding_right, scrollport.width()),
+        resolve(&padding.scroll_padding_bottom, scrollport.height()),
+        resolve(&padding.scroll_padding_left, scrollport.width()),
+    );
+    let optimal_viewing_region = scrollport.inner_rect(scroll_padding);
+
+    let align = |position: ScrollLogicalPosition,
+                 target_start: f64,
+                 target_end: f64,
+                 region_start: f64,
+                 region_size: f64| {
+        let region_end = region_start + region_size;
+        let target_size = target_end - target_start;
+        match position {
+            ScrollLogicalPosition::Start => target_start,
+            ScrollLogicalPosition::End => target_end - region_size,
+            ScrollLogicalPosition::Center => (target_start + target_end - region_size) / 2.0,
+            // If the target is entirely inside the region or covers all of it, don't
+            // scroll. Otherwise, align the edge of the target that is outside of the region
+            // with the region, unless the target is larger than the region, in which case
+            // the opposite edge is aligned.
+            ScrollLogicalPosition::Nearest => {
+                if (target_start >= region_start && target_end <= region_end) ||
+                    (target_start <= region_start && target_end >= region_end)
+                {
+                    region_start
+                } else if (target_start < region_start) == (target_size <= region_size) {
+                    target_start
+                } else {
+                    target_end - region_size
+                }
+            },
+        }
+    };
+
+    // TODO: Take the writing mode of the scroll container into account, which determines
+    // the axes and directions that `block` and `inline` refer to.
+    let region_position = Point2D::new(
+        align(
+            inline,
+            target.min_x(),
+            target.max_x(),
+            optimal_viewing_region.min_x(),
+            optimal_viewing_region.width(),
+        ),
+        align(
+            block,
+            target.min_y(),
+            target.max_y(),
+            optimal_viewing_region.min_y(),
+            optimal_viewing_region.height(),
+        ),
+    );
+    region_position - Vector2D::new(scroll_padding.left, scroll_padding.top)
+}
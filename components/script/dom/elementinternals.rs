@@ -186,6 +186,14 @@ impl ElementInternals {
             self.is_instance_validatable() &&
             !self.satisfies_constraints()
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#user-validity>
+    pub(crate) fn set_user_validity(&self, user_validity: bool, can_gc: CanGc) {
+        if self.is_target_form_associated() {
+            self.validity_state()
+                .set_user_validity(user_validity, can_gc);
+        }
+    }
 }
 
 impl ElementInternalsMethods<crate::DomTypeHolder> for ElementInternals {
@@ -388,4 +396,17 @@ impl Validatable for ElementInternals {
             !self.as_element().disabled_state() &&
             !is_barred_by_datalist_ancestor(self.target_element.upcast::<Node>())
     }
+
+    /// <https://html.spec.whatwg.org/multipage#face-validation-message>
+    fn validation_message(&self) -> DOMString {
+        self.validation_message.borrow().clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage#face-validation-anchor>
+    fn validation_anchor(&self) -> DomRoot<Element> {
+        match self.validation_anchor.get() {
+            Some(anchor) => DomRoot::upcast(anchor),
+            None => DomRoot::from_ref(self.as_element()),
+        }
+    }
 }
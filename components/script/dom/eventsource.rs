@@ -17,6 +17,7 @@ use ipc_channel::router::ROUTER;
 use js::conversions::ToJSValConvertible;
 use js::jsval::UndefinedValue;
 use js::rust::HandleObject;
+use log::warn;
 use mime::{self, Mime};
 use net_traits::request::{CacheMode, CorsSettings, Destination, RequestBuilder, RequestId};
 use net_traits::{
@@ -50,6 +51,14 @@ use crate::timers::OneshotTimerCallback;
 
 const DEFAULT_RECONNECTION_TIME: Duration = Duration::from_millis(5000);
 
+/// The longest that the exponential backoff of failed reconnection attempts makes the wait
+/// before reconnecting, unless the reconnection time of the stream is longer.
+const MAX_RECONNECTION_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The most text of an event that is kept while it is being received, after which the
+/// connection is failed rather than growing the buffers without bounds.
+const MAX_BUFFERED_EVENT_SIZE: usize = 16 * 1024 * 1024;
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 struct GenerationId(u32);
 
@@ -98,6 +107,8 @@ pub(crate) struct EventSource {
     request: DomRefCell<Option<RequestBuilder>>,
     last_event_id: DomRefCell<DOMString>,
     reconnection_time: Cell<Duration>,
+    /// The number of attempts to reestablish the connection since it was last announced.
+    reconnection_attempts: Cell<u32>,
     generation_id: Cell<GenerationId>,
 
     ready_state: Cell<ReadyState>,
@@ -108,26 +119,189 @@ pub(crate) struct EventSource {
 enum ParserState {
     Field,
     Comment,
+    /// After the colon of a field, where a space is ignored.
+    ValueStart,
     Value,
     Eol,
 }
 
+/// What the `EventSource` has to do for the lines of an event stream.
+#[derive(Debug, PartialEq)]
+enum EventStreamItem {
+    /// Set the last event ID, at the end of an event.
+    LastEventId(String),
+    /// Dispatch a `MessageEvent` with a type and data.
+    Message { event_type: String, data: String },
+    /// Set the reconnection time.
+    ReconnectionTime(Duration),
+}
+
+/// <https://html.spec.whatwg.org/multipage/#event-stream-interpretation>
+struct EventStreamParser {
+    /// Whether nothing of the current stream was parsed yet, so that a leading byte order
+    /// mark is ignored.
+    at_stream_start: bool,
+    /// Whether the last character parsed was a CR, so that a LF following it, possibly in the
+    /// next chunk, is part of the same line ending.
+    after_carriage_return: bool,
+    state: ParserState,
+    field: String,
+    value: String,
+    event_type: String,
+    data: String,
+    last_event_id: String,
+}
+
+impl EventStreamParser {
+    fn new() -> EventStreamParser {
+        EventStreamParser {
+            at_stream_start: true,
+            after_carriage_return: false,
+            state: ParserState::Eol,
+            field: String::new(),
+            value: String::new(),
+            event_type: String::new(),
+            data: String::new(),
+            last_event_id: String::new(),
+        }
+    }
+
+    /// Start parsing a new stream, after the connection was (re)established, which discards
+    /// the event that was pending when the previous stream ended.
+    fn reset_stream(&mut self) {
+        self.at_stream_start = true;
+        self.after_carriage_return = false;
+        self.state = ParserState::Eol;
+        self.field.clear();
+        self.value.clear();
+        self.event_type.clear();
+        self.data.clear();
+    }
+
+    /// The size of the text of the event being received.
+    fn buffered_size(&self) -> usize {
+        self.field.len() + self.value.len() + self.event_type.len() + self.data.len()
+    }
+
+    /// Parse the next characters of the stream, returning what to do for the lines they end.
+    fn parse(&mut self, stream: Chars) -> Vec<EventStreamItem> {
+        let mut items = Vec::new();
+        let mut stream = stream.peekable();
+
+        // One leading U+FEFF BYTE ORDER MARK character must be ignored if any are present.
+        if self.at_stream_start {
+            if stream.peek().is_none() {
+                return items;
+            }
+            self.at_stream_start = false;
+            stream.next_if_eq(&'\u{FEFF}');
+        }
+
+        while let Some(ch) = stream.next() {
+            // A CR LF pair is a single line ending, even when it is split across chunks.
+            let after_carriage_return = mem::replace(&mut self.after_carriage_return, ch == '\r');
+            if ch == '\n' && after_carriage_return {
+                continue;
+            }
+
+            match (ch, &self.state) {
+                (':', &ParserState::Eol) => self.state = ParserState::Comment,
+                (':', &ParserState::Field) => self.state = ParserState::ValueStart,
+                // A single leading space of the value is removed, even when it is in the next
+                // chunk.
+                (' ', &ParserState::ValueStart) => self.state = ParserState::Value,
+
+                (
+                    '\n' | '\r',
+                    &ParserState::Field | &ParserState::ValueStart | &ParserState::Value,
+                ) => {
+                    self.state = ParserState::Eol;
+                    self.process_field(&mut items);
+                },
+                ('\n' | '\r', &ParserState::Eol) => self.dispatch_event(&mut items),
+                ('\n' | '\r', &ParserState::Comment) => self.state = ParserState::Eol,
+
+                (_, &ParserState::Field) => self.field.push(ch),
+                (_, &ParserState::ValueStart) => {
+                    self.state = ParserState::Value;
+                    self.value.push(ch);
+                },
+                (_, &ParserState::Value) => self.value.push(ch),
+                (_, &ParserState::Eol) => {
+                    self.state = ParserState::Field;
+                    self.field.push(ch);
+                },
+                (_, &ParserState::Comment) => (),
+            }
+        }
+        items
+    }
+
+    // https://html.spec.whatwg.org/multipage/#processField
+    fn process_field(&mut self, items: &mut Vec<EventStreamItem>) {
+        match &*self.field {
+            "event" => mem::swap(&mut self.event_type, &mut self.value),
+            "data" => {
+                self.data.push_str(&self.value);
+                self.data.push('\n');
+            },
+            "id" if !self.value.contains('\0') => {
+                mem::swap(&mut self.last_event_id, &mut self.value);
+            },
+            "retry" if self.value.bytes().all(|byte| byte.is_ascii_digit()) => {
+                if let Ok(time) = u64::from_str(&self.value) {
+                    items.push(EventStreamItem::ReconnectionTime(Duration::from_millis(
+                        time,
+                    )));
+                }
+            },
+            _ => (),
+        }
+
+        self.field.clear();
+        self.value.clear();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dispatchMessage
+    fn dispatch_event(&mut self, items: &mut Vec<EventStreamItem>) {
+        // Step 1
+        items.push(EventStreamItem::LastEventId(self.last_event_id.clone()));
+        // Step 2
+        if self.data.is_empty() {
+            self.event_type.clear();
+            return;
+        }
+        // Step 3
+        if self.data.ends_with('\n') {
+            self.data.pop();
+        }
+        // Step 6
+        let event_type = if self.event_type.is_empty() {
+            "message".to_owned()
+        } else {
+            mem::take(&mut self.event_type)
+        };
+        // Steps 4-5 and 7
+        items.push(EventStreamItem::Message {
+            event_type,
+            data: mem::take(&mut self.data),
+        });
+    }
+}
+
 struct EventSourceContext {
     incomplete_utf8: Option<utf8::Incomplete>,
+    parser: EventStreamParser,
+    /// Whether the connection was announced for the current response, whose body is the
+    /// stream being parsed.
+    announced: bool,
 
     event_source: Trusted<EventSource>,
     gen_id: GenerationId,
     action_sender: ipc::IpcSender<FetchResponseMsg>,
 
-    parser_state: ParserState,
-    field: String,
-    value: String,
     origin: String,
 
-    event_type: String,
-    data: String,
-    last_event_id: String,
-
     resource_timing: ResourceFetchTiming,
 }
 
@@ -145,6 +319,7 @@ impl EventSourceContext {
                 let event_source = event_source.root();
                 if event_source.ready_state.get() != ReadyState::Closed {
                     event_source.ready_state.set(ReadyState::Open);
+                    event_source.reconnection_attempts.set(0);
                     event_source.upcast::<EventTarget>().fire_event(atom!("open"), CanGc::note());
                 }
             }),
@@ -187,10 +362,15 @@ impl EventSourceContext {
                 event_source.upcast::<EventTarget>().fire_event(atom!("error"), CanGc::note());
 
                 // Step 2.
-                let duration = event_source.reconnection_time.get();
+                let reconnection_time = event_source.reconnection_time.get();
 
-                // Step 3.
-                // TODO: Optionally wait some more.
+                // Step 3. Optionally, wait some more. In particular, if the previous attempt
+                // failed, then user agents might introduce an exponential backoff delay.
+                let attempts = event_source.reconnection_attempts.get();
+                event_source.reconnection_attempts.set(attempts.saturating_add(1));
+                let duration = reconnection_time
+                    .saturating_mul(1 << attempts.min(16))
+                    .min(reconnection_time.max(MAX_RECONNECTION_BACKOFF));
 
                 // Steps 4-5.
                 let callback = OneshotTimerCallback::EventSourceTimeout(
@@ -204,70 +384,22 @@ impl EventSourceContext {
         );
     }
 
-    // https://html.spec.whatwg.org/multipage/#processField
-    fn process_field(&mut self) {
-        match &*self.field {
-            "event" => mem::swap(&mut self.event_type, &mut self.value),
-            "data" => {
-                self.data.push_str(&self.value);
-                self.data.push('\n');
-            },
-            "id" if !self.value.contains('\0') => {
-                mem::swap(&mut self.last_event_id, &mut self.value);
-            },
-            "retry" => {
-                if let Ok(time) = u64::from_str(&self.value) {
-                    self.event_source
-                        .root()
-                        .reconnection_time
-                        .set(Duration::from_millis(time));
-                }
-            },
-            _ => (),
-        }
-
-        self.field.clear();
-        self.value.clear();
-    }
-
     // https://html.spec.whatwg.org/multipage/#dispatchMessage
     #[allow(unsafe_code)]
-    fn dispatch_event(&mut self, can_gc: CanGc) {
+    fn dispatch_event(&self, event_type: String, data: String, can_gc: CanGc) {
         let event_source = self.event_source.root();
-        // Step 1
-        *event_source.last_event_id.borrow_mut() = DOMString::from(self.last_event_id.clone());
-        // Step 2
-        if self.data.is_empty() {
-            self.data.clear();
-            self.event_type.clear();
-            return;
-        }
-        // Step 3
-        if let Some(last) = self.data.pop() {
-            if last != '\n' {
-                self.data.push(last);
-            }
-        }
-        // Step 6
-        let type_ = if !self.event_type.is_empty() {
-            Atom::from(self.event_type.clone())
-        } else {
-            atom!("message")
-        };
+        let type_ = Atom::from(event_type);
         // Steps 4-5
         let event = {
             let _ac = enter_realm(&*event_source);
-            rooted!(in(*GlobalScope::get_cx()) let mut data = UndefinedValue());
-            unsafe {
-                self.data
-                    .to_jsval(*GlobalScope::get_cx(), data.handle_mut())
-            };
+            rooted!(in(*GlobalScope::get_cx()) let mut data_value = UndefinedValue());
+            unsafe { data.to_jsval(*GlobalScope::get_cx(), data_value.handle_mut()) };
             MessageEvent::new(
                 &event_source.global(),
                 type_,
                 false,
                 false,
-                data.handle(),
+                data_value.handle(),
                 DOMString::from(self.origin.clone()),
                 None,
                 event_source.last_event_id.borrow().clone(),
@@ -275,9 +407,6 @@ impl EventSourceContext {
                 can_gc,
             )
         };
-        // Step 7
-        self.event_type.clear();
-        self.data.clear();
 
         // Step 8.
         let global = event_source.global();
@@ -293,67 +422,35 @@ impl EventSourceContext {
         );
     }
 
+    /// Start parsing a new stream, after the connection was (re)established.
+    fn reset_stream(&mut self) {
+        self.incomplete_utf8 = None;
+        self.parser.reset_stream();
+    }
+
     // https://html.spec.whatwg.org/multipage/#event-stream-interpretation
     fn parse(&mut self, stream: Chars, can_gc: CanGc) {
-        let mut stream = stream.peekable();
-
-        while let Some(ch) = stream.next() {
-            match (ch, &self.parser_state) {
-                (':', &ParserState::Eol) => self.parser_state = ParserState::Comment,
-                (':', &ParserState::Field) => {
-                    self.parser_state = ParserState::Value;
-                    if let Some(&' ') = stream.peek() {
-                        stream.next();
-                    }
-                },
-
-                ('\n', &ParserState::Value) => {
-                    self.parser_state = ParserState::Eol;
-                    self.process_field();
-                },
-                ('\r', &ParserState::Value) => {
-                    if let Some(&'\n') = stream.peek() {
-                        continue;
-                    }
-                    self.parser_state = ParserState::Eol;
-                    self.process_field();
-                },
-
-                ('\n', &ParserState::Field) => {
-                    self.parser_state = ParserState::Eol;
-                    self.process_field();
+        for item in self.parser.parse(stream) {
+            match item {
+                EventStreamItem::LastEventId(last_event_id) => {
+                    *self.event_source.root().last_event_id.borrow_mut() =
+                        DOMString::from(last_event_id);
                 },
-                ('\r', &ParserState::Field) => {
-                    if let Some(&'\n') = stream.peek() {
-                        continue;
-                    }
-                    self.parser_state = ParserState::Eol;
-                    self.process_field();
-                },
-
-                ('\n', &ParserState::Eol) => self.dispatch_event(can_gc),
-                ('\r', &ParserState::Eol) => {
-                    if let Some(&'\n') = stream.peek() {
-                        continue;
-                    }
-                    self.dispatch_event(can_gc);
+                EventStreamItem::Message { event_type, data } => {
+                    self.dispatch_event(event_type, data, can_gc)
                 },
-
-                ('\n', &ParserState::Comment) => self.parser_state = ParserState::Eol,
-                ('\r', &ParserState::Comment) => {
-                    if let Some(&'\n') = stream.peek() {
-                        continue;
-                    }
-                    self.parser_state = ParserState::Eol;
+                EventStreamItem::ReconnectionTime(time) => {
+                    self.event_source.root().reconnection_time.set(time)
                 },
+            }
+        }
 
-                (_, &ParserState::Field) => self.field.push(ch),
-                (_, &ParserState::Value) => self.value.push(ch),
-                (_, &ParserState::Eol) => {
-                    self.parser_state = ParserState::Field;
-                    self.field.push(ch);
-                },
-                (_, &ParserState::Comment) => (),
+        if self.parser.buffered_size() > MAX_BUFFERED_EVENT_SIZE {
+            warn!("Failing the EventSource connection, as an event exceeds the maximum size");
+            self.reset_stream();
+            let event_source = self.event_source.root();
+            if self.gen_id == event_source.generation_id.get() {
+                event_source.cancel();
             }
         }
     }
@@ -369,6 +466,7 @@ impl FetchResponseListener for EventSourceContext {
     }
 
     fn process_response(&mut self, _: RequestId, metadata: Result<FetchMetadata, NetworkError>) {
+        self.announced = false;
         match metadata {
             Ok(fm) => {
                 let meta = match fm {
@@ -394,6 +492,8 @@ impl FetchResponseListener for EventSourceContext {
                 }
                 self.origin = meta.final_url.origin().ascii_serialization();
                 // Step 15.4 announce the connection and interpret res's body line by line.
+                self.reset_stream();
+                self.announced = true;
                 self.announce_the_connection();
             },
             Err(_) => {
@@ -457,8 +557,15 @@ impl FetchResponseListener for EventSourceContext {
         if self.incomplete_utf8.take().is_some() {
             self.parse("\u{FFFD}".chars(), CanGc::note());
         }
-        if response.is_ok() {
-            self.reestablish_the_connection();
+        match response {
+            Ok(_) => self.reestablish_the_connection(),
+            // The fetch was aborted, because the connection was closed or failed.
+            Err(NetworkError::LoadCancelled) => {},
+            // The connection was lost after it was established, which is reestablished like
+            // at the end of the body. A network error before that was handled with the
+            // response.
+            Err(_) if self.announced => self.reestablish_the_connection(),
+            Err(_) => {},
         }
     }
 
@@ -504,6 +611,7 @@ impl EventSource {
             request: DomRefCell::new(None),
             last_event_id: DomRefCell::new(DOMString::from("")),
             reconnection_time: Cell::new(DEFAULT_RECONNECTION_TIME),
+            reconnection_attempts: Cell::new(0),
             generation_id: Cell::new(GenerationId(0)),
 
             ready_state: Cell::new(ReadyState::Connecting),
@@ -626,19 +734,15 @@ impl EventSourceMethods<crate::DomTypeHolder> for EventSource {
         let (action_sender, action_receiver) = ipc::channel().unwrap();
         let context = EventSourceContext {
             incomplete_utf8: None,
+            parser: EventStreamParser::new(),
+            announced: false,
 
             event_source: Trusted::new(&ev),
             gen_id: ev.generation_id.get(),
             action_sender: action_sender.clone(),
 
-            parser_state: ParserState::Eol,
-            field: String::new(),
-            value: String::new(),
             origin: String::new(),
 
-            event_type: String::new(),
-            data: String::new(),
-            last_event_id: String::new(),
             resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
         };
         let mut listener = NetworkListener {
@@ -716,14 +820,20 @@ impl EventSourceTimeoutCallback {
         }
         // Step 5.2
         let mut request = event_source.request();
-        // Step 5.3
-        if !event_source.last_event_id.borrow().is_empty() {
+        // Step 5.3 If the EventSource object's last event ID string is not the empty string,
+        // set (`Last-Event-ID`, last event ID string, encoded as UTF-8) in request's header
+        // list.
+        let last_event_id = event_source.last_event_id.borrow().clone();
+        if !last_event_id.is_empty() {
             //TODO(eijebong): Change this once typed header support custom values
-            request.headers.insert(
-                HeaderName::from_static("last-event-id"),
-                HeaderValue::from_str(&String::from(event_source.last_event_id.borrow().clone()))
-                    .unwrap(),
-            );
+            match HeaderValue::from_bytes(last_event_id.as_bytes()) {
+                Ok(value) => {
+                    request
+                        .headers
+                        .insert(HeaderName::from_static("last-event-id"), value);
+                },
+                Err(_) => warn!("Not sending a Last-Event-ID that is not a valid header value"),
+            }
         }
         // Step 5.4
         global
@@ -735,3 +845,146 @@ impl EventSourceTimeoutCallback {
             .unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_chunks(chunks: &[&str]) -> Vec<EventStreamItem> {
+        let mut parser = EventStreamParser::new();
+        chunks
+            .iter()
+            .flat_map(|chunk| parser.parse(chunk.chars()))
+            .collect()
+    }
+
+    fn message(event_type: &str, data: &str) -> EventStreamItem {
+        EventStreamItem::Message {
+            event_type: event_type.to_owned(),
+            data: data.to_owned(),
+        }
+    }
+
+    fn messages(items: Vec<EventStreamItem>) -> Vec<EventStreamItem> {
+        items
+            .into_iter()
+            .filter(|item| matches!(item, EventStreamItem::Message { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn test_line_endings() {
+        for stream in [
+            "data: a\n\ndata: b\n\n",
+            "data: a\r\rdata: b\r\r",
+            "data: a\r\n\r\ndata: b\r\n\r\n",
+            "data: a\r\n\ndata: b\n\r\n",
+        ] {
+            assert_eq!(
+                messages(parse_chunks(&[stream])),
+                vec![message("message", "a"), message("message", "b")],
+                "{stream:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_carriage_return_line_feed_split_across_chunks() {
+        assert_eq!(
+            messages(parse_chunks(&["data: a\r", "\ndata: b\r", "\n\r", "\n"])),
+            vec![message("message", "a\nb")]
+        );
+        // A CR at the end of a chunk followed by a CR is two line endings.
+        assert_eq!(
+            messages(parse_chunks(&["data: a\r", "\r"])),
+            vec![message("message", "a")]
+        );
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        assert_eq!(
+            messages(parse_chunks(&["\u{FEFF}data: a\n\n"])),
+            vec![message("message", "a")]
+        );
+        // The BOM is ignored when it arrives after empty chunks.
+        assert_eq!(
+            messages(parse_chunks(&["", "\u{FEFF}", "data: a\n\n"])),
+            vec![message("message", "a")]
+        );
+        // Only one leading BOM is ignored, so the field of the next one is unknown.
+        assert_eq!(
+            messages(parse_chunks(&["\u{FEFF}\u{FEFF}data: a\n\ndata: b\n\n"])),
+            vec![message("message", "b")]
+        );
+        // A BOM which does not start the stream is part of the field name.
+        assert_eq!(
+            messages(parse_chunks(&["data: a\n\n\u{FEFF}data: b\n\n"])),
+            vec![message("message", "a")]
+        );
+    }
+
+    #[test]
+    fn test_space_after_colon() {
+        assert_eq!(
+            messages(parse_chunks(&["data:", " a\ndata:b\ndata:  c\n\n"])),
+            vec![message("message", "a\nb\n c")]
+        );
+    }
+
+    #[test]
+    fn test_event_type_and_last_event_id() {
+        assert_eq!(
+            parse_chunks(&["event: ping\nid: 1\ndata: a\n\n: comment\n\ndata: b\n\n"]),
+            vec![
+                EventStreamItem::LastEventId("1".to_owned()),
+                message("ping", "a"),
+                EventStreamItem::LastEventId("1".to_owned()),
+                EventStreamItem::LastEventId("1".to_owned()),
+                message("message", "b"),
+            ]
+        );
+        // An ID containing NULL is ignored.
+        assert_eq!(
+            parse_chunks(&["id: 1\0\n\n"]),
+            vec![EventStreamItem::LastEventId(String::new())]
+        );
+    }
+
+    #[test]
+    fn test_retry() {
+        assert_eq!(
+            parse_chunks(&["retry: 1000\nretry:250\n"]),
+            vec![
+                EventStreamItem::ReconnectionTime(Duration::from_millis(1000)),
+                EventStreamItem::ReconnectionTime(Duration::from_millis(250)),
+            ]
+        );
+        for stream in [
+            "retry: -1\n",
+            "retry: 1.5\n",
+            "retry: 1e3\n",
+            "retry: +10\n",
+            "retry:  10\n",
+            "retry: 10 \n",
+            "retry: ١٠\n",
+            "retry: 99999999999999999999999\n",
+        ] {
+            assert!(parse_chunks(&[stream]).is_empty(), "{stream:?}");
+        }
+    }
+
+    #[test]
+    fn test_reset_stream_discards_pending_event() {
+        let mut parser = EventStreamParser::new();
+        assert!(parser.parse("id: 1\ndata: a\n".chars()).is_empty());
+        parser.reset_stream();
+        assert_eq!(
+            parser.parse("\u{FEFF}data: b\n\n".chars()),
+            vec![
+                EventStreamItem::LastEventId("1".to_owned()),
+                message("message", "b")
+            ]
+        );
+    }
+}
@@ -26,6 +26,7 @@ use style::str::HTML_SPACE_CHARACTERS;
 use stylo_atoms::Atom;
 
 use crate::conversions::Convert;
+use crate::dom::abortsignal::AbortAlgorithm;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::{CallbackContainer, CallbackFunction, ExceptionHandling};
 use crate::dom::bindings::cell::DomRefCell;
@@ -52,7 +53,7 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{
     DomGlobal, DomObject, Reflector, reflect_dom_object_with_proto,
 };
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::HashMapTracedValues;
 use crate::dom::csp::{CspReporting, InlineCheckType};
@@ -346,6 +347,25 @@ impl std::cmp::PartialEq for EventListenerEntry {
     }
 }
 
+/// An event listener added with a signal, removed when that signal is aborted.
+/// <https://dom.spec.whatwg.org/#add-an-event-listener>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) struct RemovableDomEventListener {
+    event_target: Dom<EventTarget>,
+    #[no_trace]
+    ty: Atom,
+    #[ignore_malloc_size_of = "Rc"]
+    entry: Rc<RefCell<EventListenerEntry>>,
+}
+
+impl RemovableDomEventListener {
+    /// <https://dom.spec.whatwg.org/#remove-an-event-listener>
+    pub(crate) fn remove(&self) {
+        self.event_target.remove_listener(&self.ty, &self.entry);
+    }
+}
+
 #[derive(Clone, JSTraceable, MallocSizeOf)]
 /// A mix of potentially uncompiled and compiled event listeners of the same type.
 pub(crate) struct EventListeners(
@@ -839,8 +859,19 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+
+        // If listener’s signal is not null and is aborted, then return.
+        if options
+            .signal
+            .as_ref()
+            .is_some_and(|signal| signal.aborted())
+        {
+            return;
+        }
+
+        let ty = Atom::from(ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entries = match handlers.entry(Atom::from(ty)) {
+        let entries = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -859,7 +890,21 @@ impl EventTarget {
         }));
 
         if !entries.contains(&new_entry) {
-            entries.push(new_entry);
+            entries.push(new_entry.clone());
+        }
+
+        // If listener’s signal is not null, then add the following abort steps to it:
+        // Remove an event listener with eventTarget and listener.
+        if let Some(signal) = options.signal.as_ref() {
+            let cx = GlobalScope::get_cx();
+            rooted!(in(*cx) let abort_algorithm = AbortAlgorithm::DomEventListener(
+                RemovableDomEventListener {
+                    event_target: Dom::from_ref(self),
+                    ty,
+                    entry: new_entry,
+                }
+            ));
+            signal.add(&abort_algorithm);
         }
     }
 
@@ -1017,6 +1062,7 @@ impl Convert<AddEventListenerOptions> for AddEventListenerOptionsOrBoolean {
                 parent: EventListenerOptions { capture },
                 once: false,
                 passive: None,
+                signal: None,
             },
         }
     }
@@ -0,0 +1,303 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use http::StatusCode;
+use ipc_channel::ipc::IpcSender;
+use js::rust::{HandleObject, HandleValue};
+use net_traits::{CustomResponse, ServiceWorkerResponse};
+use stylo_atoms::Atom;
+
+use crate::body::BodyMixin;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::ExtendableEventBinding::ExtendableEvent_Binding::ExtendableEventMethods;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding::{
+    FetchEventInit, FetchEventMethods,
+};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::realms::{InRealm, enter_realm};
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/ServiceWorker/#fetchevent-interface>
+#[dom_struct]
+pub(crate) struct FetchEvent {
+    event: ExtendableEvent,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-request>
+    request: Dom<Request>,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-clientid>
+    client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-resultingclientid>
+    resulting_client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#fetchevent-respond-with-entered-flag>
+    respond_with_entered: Cell<bool>,
+    /// The promise passed to `respondWith()`, which settles the response of the request.
+    #[ignore_malloc_size_of = "Rc is hard"]
+    response_promise: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl FetchEvent {
+    fn new_inherited(
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+    ) -> FetchEvent {
+        FetchEvent {
+            event: ExtendableEvent::new_inherited(),
+            request: Dom::from_ref(request),
+            client_id,
+            resulting_client_id,
+            respond_with_entered: Cell::new(false),
+            response_promise: DomRefCell::new(None),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        request: &Request,
+        can_gc: CanGc,
+    ) -> DomRoot<FetchEvent> {
+        Self::new_with_proto(
+            global,
+            None,
+            atom!("fetch"),
+            false,
+            true,
+            request,
+            DOMString::new(),
+            DOMString::new(),
+            can_gc,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_proto(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        request: &Request,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+        can_gc: CanGc,
+    ) -> DomRoot<FetchEvent> {
+        let event = reflect_dom_object_with_proto(
+            Box::new(FetchEvent::new_inherited(
+                request,
+                client_id,
+                resulting_client_id,
+            )),
+            global,
+            proto,
+            can_gc,
+        );
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+
+    /// Report the outcome of this event, once it has been dispatched, to the fetch that is
+    /// waiting for it, following the steps of
+    /// <https://w3c.github.io/ServiceWorker/#handle-fetch> that run after dispatching.
+    pub(crate) fn report_response(
+        &self,
+        response_chan: IpcSender<ServiceWorkerResponse>,
+        can_gc: CanGc,
+    ) {
+        let Some(promise) = self.response_promise.borrow().clone() else {
+            // If eventCanceledFlag is set, then set handleFetchFailed.
+            let response = if self.upcast::<Event>().DefaultPrevented() {
+                ServiceWorkerResponse::NetworkError
+            } else {
+                ServiceWorkerResponse::Fallback
+            };
+            let _ = response_chan.send(response);
+            return;
+        };
+
+        let global = self.global();
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(RespondWithFulfillmentHandler {
+                response_chan: response_chan.clone(),
+            })),
+            Some(Box::new(RespondWithRejectionHandler { response_chan })),
+            can_gc,
+        );
+        let realm = enter_realm(&*global);
+        promise.append_native_handler(&handler, InRealm::Entered(&realm), can_gc);
+    }
+}
+
+impl FetchEventMethods<crate::DomTypeHolder> for FetchEvent {
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-fetchevent>
+    fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        type_: DOMString,
+        init: &FetchEventInit,
+    ) -> Fallible<DomRoot<FetchEvent>> {
+        Ok(FetchEvent::new_with_proto(
+            worker.upcast(),
+            proto,
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.request,
+            init.clientId.clone(),
+            init.resultingClientId.clone(),
+            can_gc,
+        ))
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-request>
+    fn Request(&self) -> DomRoot<Request> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-clientid>
+    fn ClientId(&self) -> DOMString {
+        self.client_id.clone()
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-resultingclientid>
+    fn ResultingClientId(&self) -> DOMString {
+        self.resulting_client_id.clone()
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith>
+    fn RespondWith(&self, response: &Rc<Promise>) -> ErrorResult {
+        let event = self.upcast::<Event>();
+
+        // Step 1. If event’s dispatch flag is unset, throw an "InvalidStateError" DOMException.
+        // Step 2. If event’s respond-with entered flag is set, throw an "InvalidStateError"
+        // DOMException.
+        if !event.dispatching() || self.respond_with_entered.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // TODO: Step 3. Add lifetime promise r to event.
+
+        // Step 4. Set event’s stop propagation flag and stop immediate propagation flag.
+        event.StopImmediatePropagation();
+
+        // Step 5. Set event’s respond-with entered flag.
+        self.respond_with_entered.set(true);
+
+        // Step 6. Set event’s wait to respond flag.
+        // Step 8. Upon fulfillment or rejection of r, the response is reported by
+        // `report_response`, once the event has been dispatched.
+        *self.response_promise.borrow_mut() = Some(response.clone());
+        Ok(())
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-event-istrusted>
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
+
+/// The fulfillment steps of the promise passed to
+/// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith>.
+#[derive(JSTraceable, MallocSizeOf)]
+struct RespondWithFulfillmentHandler {
+    #[no_trace]
+    #[ignore_malloc_size_of = "Channels are hard"]
+    response_chan: IpcSender<ServiceWorkerResponse>,
+}
+
+impl Callback for RespondWithFulfillmentHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: JSContext, v: HandleValue, realm: InRealm, can_gc: CanGc) {
+        // If response is not a Response object, or is disturbed or locked, then set the
+        // respond-with error flag.
+        let response = match unsafe { root_from_handlevalue::<Response>(v, *cx) } {
+            Ok(response) if !response.is_disturbed() && !response.is_locked() => response,
+            _ => {
+                let _ = self.response_chan.send(ServiceWorkerResponse::NetworkError);
+                return;
+            },
+        };
+        let Ok(status_code) = StatusCode::from_u16(response.Status()) else {
+            // Network errors have a status of 0.
+            let _ = self.response_chan.send(ServiceWorkerResponse::NetworkError);
+            return;
+        };
+        let status_text = String::from_utf8_lossy(&response.StatusText()).into_owned();
+        let headers = response.Headers(can_gc).get_headers_list();
+
+        let Some(stream) = response.body() else {
+            let custom_response =
+                CustomResponse::new(headers, (status_code, status_text), Vec::new());
+            let _ = self
+                .response_chan
+                .send(ServiceWorkerResponse::Custom(custom_response));
+            return;
+        };
+        let reader = match stream.acquire_default_reader(can_gc) {
+            Ok(reader) => reader,
+            Err(_) => {
+                let _ = self.response_chan.send(ServiceWorkerResponse::NetworkError);
+                return;
+            },
+        };
+
+        // Read the whole body, since responses from service workers are not streamed yet.
+        let success_chan = self.response_chan.clone();
+        let failure_chan = self.response_chan.clone();
+        reader.read_all_bytes(
+            cx,
+            &response.global(),
+            Rc::new(move |bytes: &[u8]| {
+                let custom_response = CustomResponse::new(
+                    headers.clone(),
+                    (status_code, status_text.clone()),
+                    bytes.to_vec(),
+                );
+                let _ = success_chan.send(ServiceWorkerResponse::Custom(custom_response));
+            }),
+            Rc::new(move |_cx, _error| {
+                let _ = failure_chan.send(ServiceWorkerResponse::NetworkError);
+            }),
+            realm,
+            can_gc,
+        );
+    }
+}
+
+/// The rejection steps of the promise passed to
+/// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith>.
+#[derive(JSTraceable, MallocSizeOf)]
+struct RespondWithRejectionHandler {
+    #[no_trace]
+    #[ignore_malloc_size_of = "Channels are hard"]
+    response_chan: IpcSender<ServiceWorkerResponse>,
+}
+
+impl Callback for RespondWithRejectionHandler {
+    fn callback(&self, _cx: JSContext, _v: HandleValue, _realm: InRealm, _can_gc: CanGc) {
+        // Set the respond-with error flag.
+        let _ = self.response_chan.send(ServiceWorkerResponse::NetworkError);
+    }
+}
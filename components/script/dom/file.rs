@@ -14,13 +14,11 @@ use crate::dom::bindings::codegen::Bindings::FileBinding;
 use crate::dom::bindings::codegen::Bindings::FileBinding::FileMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferOrArrayBufferViewOrBlobOrString;
 use crate::dom::bindings::error::{Error, Fallible};
-use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::{Blob, blob_parts_to_bytes, normalize_type_string};
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -28,16 +26,24 @@ pub(crate) struct File {
     blob: Blob,
     name: DOMString,
     modified: SystemTime,
+    /// <https://wicg.github.io/entries-api/#dom-file-webkitrelativepath>
+    relative_path: DOMString,
 }
 
 impl File {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
-    fn new_inherited(blob_impl: &BlobImpl, name: DOMString, modified: Option<SystemTime>) -> File {
+    fn new_inherited(
+        blob_impl: &BlobImpl,
+        name: DOMString,
+        modified: Option<SystemTime>,
+        relative_path: DOMString,
+    ) -> File {
         File {
             blob: Blob::new_inherited(blob_impl),
             name,
             // https://w3c.github.io/FileAPI/#dfn-lastModified
             modified: modified.unwrap_or_else(SystemTime::now),
+            relative_path,
         }
     }
 
@@ -48,7 +54,15 @@ impl File {
         modified: Option<SystemTime>,
         can_gc: CanGc,
     ) -> DomRoot<File> {
-        Self::new_with_proto(global, None, blob_impl, name, modified, can_gc)
+        Self::new_with_proto(
+            global,
+            None,
+            blob_impl,
+            name,
+            modified,
+            DOMString::new(),
+            can_gc,
+        )
     }
 
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
@@ -58,10 +72,16 @@ impl File {
         blob_impl: BlobImpl,
         name: DOMString,
         modified: Option<SystemTime>,
+        relative_path: DOMString,
         can_gc: CanGc,
     ) -> DomRoot<File> {
         let file = reflect_dom_object_with_proto(
-            Box::new(File::new_inherited(&blob_impl, name, modified)),
+            Box::new(File::new_inherited(
+                &blob_impl,
+                name,
+                modified,
+                relative_path,
+            )),
             global,
             proto,
             can_gc,
@@ -72,7 +92,7 @@ impl File {
 
     // Construct from selected file message from file manager thread
     pub(crate) fn new_from_selected(
-        window: &Window,
+        global: &GlobalScope,
         selected: SelectedFile,
         can_gc: CanGc,
     ) -> DomRoot<File> {
@@ -83,8 +103,9 @@ impl File {
                 .expect("File name encoding error"),
         );
 
-        File::new(
-            window.upcast(),
+        File::new_with_proto(
+            global,
+            None,
             BlobImpl::new_from_file(
                 selected.id,
                 selected.filename,
@@ -93,6 +114,7 @@ impl File {
             ),
             name,
             Some(selected.modified),
+            DOMString::from(selected.relative_path),
             can_gc,
         )
     }
@@ -112,6 +134,10 @@ impl File {
     pub(crate) fn get_modified(&self) -> SystemTime {
         self.modified
     }
+
+    pub(crate) fn relative_path(&self) -> &DOMString {
+        &self.relative_path
+    }
 }
 
 impl FileMethods<crate::DomTypeHolder> for File {
@@ -143,6 +169,7 @@ impl FileMethods<crate::DomTypeHolder> for File {
             BlobImpl::new_from_bytes(bytes, type_string),
             filename,
             modified,
+            DOMString::new(),
             can_gc,
         ))
     }
@@ -159,4 +186,9 @@ impl FileMethods<crate::DomTypeHolder> for File {
         (OffsetDateTime::from(self.modified) - OffsetDateTime::UNIX_EPOCH).whole_milliseconds()
             as i64
     }
+
+    /// <https://wicg.github.io/entries-api/#dom-file-webkitrelativepath>
+    fn WebkitRelativePath(&self) -> USVString {
+        USVString(self.relative_path.to_string())
+    }
 }
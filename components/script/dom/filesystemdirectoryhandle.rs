@@ -0,0 +1,209 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use net_traits::filemanager_thread::{
+    FileSystemEntry, FileSystemHandleKind, FileSystemMsg, FileSystemResult, is_valid_file_name,
+};
+
+use crate::dom::bindings::codegen::Bindings::FileSystemDirectoryHandleBinding::{
+    FileSystemDirectoryHandleMethods, FileSystemGetDirectoryOptions, FileSystemGetFileOptions,
+    FileSystemRemoveOptions,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::filesystemhandle::{FileSystemHandle, file_system_error};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::CanGc;
+
+/// <https://fs.spec.whatwg.org/#filesystemdirectoryhandle>
+#[dom_struct]
+pub(crate) struct FileSystemDirectoryHandle {
+    filesystemhandle: FileSystemHandle,
+}
+
+impl FileSystemDirectoryHandle {
+    fn new_inherited(entry: FileSystemEntry) -> FileSystemDirectoryHandle {
+        FileSystemDirectoryHandle {
+            filesystemhandle: FileSystemHandle::new_inherited(entry),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        entry: FileSystemEntry,
+        can_gc: CanGc,
+    ) -> DomRoot<FileSystemDirectoryHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemDirectoryHandle::new_inherited(entry)),
+            global,
+            can_gc,
+        )
+    }
+
+    /// The steps shared by `getFileHandle()` and `getDirectoryHandle()`.
+    fn get_child(
+        &self,
+        name: USVString,
+        kind: FileSystemHandleKind,
+        create: bool,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // If name is not a valid file name, queue a storage task with global to reject
+        // result with a TypeError and abort these steps.
+        if !is_valid_file_name(&name.0) {
+            promise.reject_error(
+                Error::Type(format!("{:?} is not a valid file name", name.0)),
+                can_gc,
+            );
+            return promise;
+        }
+
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        let handle = self.upcast::<FileSystemHandle>();
+        let msg = FileSystemMsg::GetChild {
+            parent: handle.locator().clone(),
+            name: name.0,
+            kind,
+            create,
+            sender,
+        };
+        if let Err(error) = handle.send_to_file_manager(msg) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+}
+
+impl FileSystemDirectoryHandleMethods<crate::DomTypeHolder> for FileSystemDirectoryHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getfilehandle>
+    fn GetFileHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetFileOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        self.get_child(name, FileSystemHandleKind::File, options.create, can_gc)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getdirectoryhandle>
+    fn GetDirectoryHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetDirectoryOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        self.get_child(
+            name,
+            FileSystemHandleKind::Directory,
+            options.create,
+            can_gc,
+        )
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-removeentry>
+    fn RemoveEntry(
+        &self,
+        name: USVString,
+        options: &FileSystemRemoveOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // If name is not a valid file name, queue a storage task with global to reject
+        // result with a TypeError and abort these steps.
+        if !is_valid_file_name(&name.0) {
+            promise.reject_error(
+                Error::Type(format!("{:?} is not a valid file name", name.0)),
+                can_gc,
+            );
+            return promise;
+        }
+
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        let handle = self.upcast::<FileSystemHandle>();
+        let msg = FileSystemMsg::RemoveEntry {
+            parent: handle.locator().clone(),
+            name: name.0,
+            recursive: options.recursive,
+            sender,
+        };
+        if let Err(error) = handle.send_to_file_manager(msg) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-resolve>
+    fn Resolve(&self, possible_descendant: &FileSystemHandle, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+        let locator = self.upcast::<FileSystemHandle>().locator();
+        let descendant_locator = possible_descendant.locator();
+
+        // Let path be the result of resolving possibleDescendant's locator relative to
+        // this's locator. Resolve result with path, which is null if possibleDescendant
+        // is not a descendant of this.
+        let path = (locator.root == descendant_locator.root &&
+            descendant_locator.path.starts_with(&locator.path))
+        .then(|| {
+            descendant_locator.path[locator.path.len()..]
+                .iter()
+                .cloned()
+                .map(USVString)
+                .collect::<Vec<_>>()
+        });
+        promise.resolve_native(&path, can_gc);
+        promise
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<FileSystemEntry>> for FileSystemDirectoryHandle {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<FileSystemEntry>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(entry) => {
+                let handle = FileSystemHandle::new_for_entry(&self.global(), entry, can_gc);
+                promise.resolve_native(&handle, can_gc);
+            },
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<()>> for FileSystemDirectoryHandle {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<()>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(()) => promise.resolve_native(&(), can_gc),
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
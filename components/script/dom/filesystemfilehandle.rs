@@ -0,0 +1,162 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use net_traits::filemanager_thread::{
+    FileSystemEntry, FileSystemMsg, FileSystemOpenMode, FileSystemResult, SelectedFile,
+};
+use uuid::Uuid;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemFileHandleBinding::{
+    FileSystemCreateWritableOptions, FileSystemFileHandleMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::file::File;
+use crate::dom::filesystemhandle::{FileSystemHandle, file_system_error};
+use crate::dom::filesystemsyncaccesshandle::FileSystemSyncAccessHandle;
+use crate::dom::filesystemwritablefilestream::FileSystemWritableFileStream;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::CanGc;
+
+/// <https://fs.spec.whatwg.org/#filesystemfilehandle>
+#[dom_struct]
+pub(crate) struct FileSystemFileHandle {
+    filesystemhandle: FileSystemHandle,
+}
+
+impl FileSystemFileHandle {
+    fn new_inherited(entry: FileSystemEntry) -> FileSystemFileHandle {
+        FileSystemFileHandle {
+            filesystemhandle: FileSystemHandle::new_inherited(entry),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        entry: FileSystemEntry,
+        can_gc: CanGc,
+    ) -> DomRoot<FileSystemFileHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemFileHandle::new_inherited(entry)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl FileSystemFileHandleMethods<crate::DomTypeHolder> for FileSystemFileHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-getfile>
+    fn GetFile(&self, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        let handle = self.upcast::<FileSystemHandle>();
+        if let Err(error) =
+            handle.send_to_file_manager(FileSystemMsg::GetFile(handle.locator().clone(), sender))
+        {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createwritable>
+    fn CreateWritable(
+        &self,
+        options: &FileSystemCreateWritableOptions,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        let handle = self.upcast::<FileSystemHandle>();
+        let mode = FileSystemOpenMode::Writable {
+            keep_existing_data: options.keepExistingData,
+        };
+        if let Err(error) =
+            handle.send_to_file_manager(FileSystemMsg::Open(handle.locator().clone(), mode, sender))
+        {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createsyncaccesshandle>
+    fn CreateSyncAccessHandle(&self, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Sync access handles are only exposed to dedicated workers, which may block on
+        // the file manager.
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let handle = self.upcast::<FileSystemHandle>();
+        let msg = FileSystemMsg::Open(
+            handle.locator().clone(),
+            FileSystemOpenMode::SyncAccess,
+            sender,
+        );
+        if let Err(error) = handle.send_to_file_manager(msg) {
+            promise.reject_error(error, can_gc);
+            return promise;
+        }
+        match receiver.recv() {
+            Ok(Ok(id)) => {
+                let access_handle = FileSystemSyncAccessHandle::new(&global, id, can_gc);
+                promise.resolve_native(&access_handle, can_gc);
+            },
+            Ok(Err(error)) => promise.reject_error(file_system_error(error), can_gc),
+            Err(_) => promise.reject_error(Error::Operation, can_gc),
+        }
+        promise
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<SelectedFile>> for FileSystemFileHandle {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<SelectedFile>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(selected) => {
+                let file = File::new_from_selected(&self.global(), selected, can_gc);
+                promise.resolve_native(&file, can_gc);
+            },
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<Uuid>> for FileSystemFileHandle {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<Uuid>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(id) => {
+                let stream = FileSystemWritableFileStream::new(&self.global(), id, can_gc);
+                promise.resolve_native(&stream, can_gc);
+            },
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
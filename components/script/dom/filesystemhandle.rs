@@ -0,0 +1,199 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::{
+    self, FileManagerThreadMsg, FileOrigin, FileSystemEntry, FileSystemError, FileSystemLocator,
+    FileSystemMsg, OpenFileOperation,
+};
+use net_traits::{CoreResourceMsg, IpcSend, ResourceThreads};
+use uuid::Uuid;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::{
+    FileSystemHandleKind, FileSystemHandleMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::filesystemdirectoryhandle::FileSystemDirectoryHandle;
+use crate::dom::filesystemfilehandle::FileSystemFileHandle;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::CanGc;
+
+/// <https://fs.spec.whatwg.org/#filesystemhandle>
+///
+/// The entries that handles locate live in the file manager of the resource thread.
+#[dom_struct]
+pub(crate) struct FileSystemHandle {
+    reflector_: Reflector,
+    /// <https://fs.spec.whatwg.org/#filesystemhandle-locator>
+    #[no_trace]
+    locator: FileSystemLocator,
+    #[no_trace]
+    kind: filemanager_thread::FileSystemHandleKind,
+    name: String,
+}
+
+impl FileSystemHandle {
+    pub(crate) fn new_inherited(entry: FileSystemEntry) -> FileSystemHandle {
+        FileSystemHandle {
+            reflector_: Reflector::new(),
+            locator: entry.locator,
+            kind: entry.kind,
+            name: entry.name,
+        }
+    }
+
+    /// Creates a `FileSystemFileHandle` or a `FileSystemDirectoryHandle` for `entry`,
+    /// depending on its kind.
+    pub(crate) fn new_for_entry(
+        global: &GlobalScope,
+        entry: FileSystemEntry,
+        can_gc: CanGc,
+    ) -> DomRoot<FileSystemHandle> {
+        match entry.kind {
+            filemanager_thread::FileSystemHandleKind::File => {
+                DomRoot::upcast(FileSystemFileHandle::new(global, entry, can_gc))
+            },
+            filemanager_thread::FileSystemHandleKind::Directory => {
+                DomRoot::upcast(FileSystemDirectoryHandle::new(global, entry, can_gc))
+            },
+        }
+    }
+
+    pub(crate) fn locator(&self) -> &FileSystemLocator {
+        &self.locator
+    }
+
+    /// Sends a message about the entry of this handle, or a child of it, to the file
+    /// manager.
+    pub(crate) fn send_to_file_manager(&self, msg: FileSystemMsg) -> Fallible<()> {
+        let global = self.global();
+        send_to_file_manager(
+            &global,
+            FileManagerThreadMsg::FileSystem(msg, file_system_origin(&global)),
+        )
+    }
+}
+
+impl FileSystemHandleMethods<crate::DomTypeHolder> for FileSystemHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-kind>
+    fn Kind(&self) -> FileSystemHandleKind {
+        match self.kind {
+            filemanager_thread::FileSystemHandleKind::File => FileSystemHandleKind::File,
+            filemanager_thread::FileSystemHandleKind::Directory => FileSystemHandleKind::Directory,
+        }
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-name>
+    fn Name(&self) -> USVString {
+        USVString(self.name.clone())
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-issameentry>
+    fn IsSameEntry(&self, other: &FileSystemHandle, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Enqueue the following steps to the file system queue:
+        // If this's locator is the same locator as other's locator, resolve result with
+        // true. Otherwise resolve result with false.
+        let is_same_entry = self.kind == other.kind && self.locator == other.locator;
+        promise.resolve_native(&is_same_entry, can_gc);
+        promise
+    }
+}
+
+/// A file opened by a `FileSystemWritableFileStream` or a `FileSystemSyncAccessHandle`,
+/// which is aborted when it is garbage collected before being closed, so that its lock is
+/// released.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) struct OpenFile {
+    #[no_trace]
+    id: Uuid,
+    origin: FileOrigin,
+    #[no_trace]
+    #[ignore_malloc_size_of = "Channels are hard"]
+    resource_threads: ResourceThreads,
+    closed: Cell<bool>,
+}
+
+impl OpenFile {
+    pub(crate) fn new(global: &GlobalScope, id: Uuid) -> OpenFile {
+        OpenFile {
+            id,
+            origin: file_system_origin(global),
+            resource_threads: global.resource_threads().clone(),
+            closed: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// Sends an operation on the file to the file manager. Closing or aborting the file
+    /// marks it as closed.
+    pub(crate) fn send(&self, operation: OpenFileOperation) -> Fallible<()> {
+        if matches!(
+            operation,
+            OpenFileOperation::Close(..) | OpenFileOperation::Abort
+        ) {
+            self.closed.set(true);
+        }
+        self.resource_threads
+            .send(CoreResourceMsg::ToFileManager(
+                FileManagerThreadMsg::FileSystem(
+                    FileSystemMsg::OpenFile(self.id, operation),
+                    self.origin.clone(),
+                ),
+            ))
+            .map_err(|_| Error::Operation)
+    }
+}
+
+impl Drop for OpenFile {
+    fn drop(&mut self) {
+        if !self.is_closed() {
+            let _ = self.send(OpenFileOperation::Abort);
+        }
+    }
+}
+
+/// The origin that the file manager uses to check access to entries. This matches the
+/// origin of the files that are created from them.
+pub(crate) fn file_system_origin(global: &GlobalScope) -> FileOrigin {
+    get_blob_origin(&global.get_url())
+}
+
+pub(crate) fn send_to_file_manager(
+    global: &GlobalScope,
+    msg: FileManagerThreadMsg,
+) -> Fallible<()> {
+    global
+        .resource_threads()
+        .send(CoreResourceMsg::ToFileManager(msg))
+        .map_err(|_| Error::Operation)
+}
+
+/// The exception that a failed file system operation rejects with.
+pub(crate) fn file_system_error(error: FileSystemError) -> Error {
+    match error {
+        FileSystemError::NotFound => Error::NotFound,
+        FileSystemError::TypeMismatch => Error::TypeMismatch,
+        FileSystemError::InvalidModification => Error::InvalidModification,
+        FileSystemError::NoModificationAllowed => Error::NoModificationAllowed,
+        FileSystemError::InvalidState => Error::InvalidState,
+        FileSystemError::Abort => Error::Abort,
+        FileSystemError::Io(message) => {
+            warn!("File system operation failed: {}", message);
+            Error::Operation
+        },
+    }
+}
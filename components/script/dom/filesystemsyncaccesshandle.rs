@@ -0,0 +1,164 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcSender};
+use net_traits::filemanager_thread::{FileSystemResult, OpenFileOperation};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemSyncAccessHandleBinding::{
+    FileSystemReadWriteOptions, FileSystemSyncAccessHandleMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemhandle::{OpenFile, file_system_error};
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle>
+///
+/// Sync access handles are only exposed to dedicated workers, so their operations block
+/// on the file manager.
+#[dom_struct]
+pub(crate) struct FileSystemSyncAccessHandle {
+    reflector_: Reflector,
+    /// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle-file>, whose closing is
+    /// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle-state>.
+    file: OpenFile,
+}
+
+impl FileSystemSyncAccessHandle {
+    fn new_inherited(global: &GlobalScope, id: Uuid) -> FileSystemSyncAccessHandle {
+        FileSystemSyncAccessHandle {
+            reflector_: Reflector::new(),
+            file: OpenFile::new(global, id),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        id: Uuid,
+        can_gc: CanGc,
+    ) -> DomRoot<FileSystemSyncAccessHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemSyncAccessHandle::new_inherited(global, id)),
+            global,
+            can_gc,
+        )
+    }
+
+    /// Performs an operation on the file and waits for its result.
+    fn perform<T>(
+        &self,
+        operation: impl FnOnce(IpcSender<FileSystemResult<T>>) -> OpenFileOperation,
+    ) -> Fallible<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        // If this's [[state]] is "closed", throw an "InvalidStateError" DOMException.
+        if self.file.is_closed() {
+            return Err(Error::InvalidState);
+        }
+
+        let (sender, receiver) = ipc::channel().map_err(|_| Error::Operation)?;
+        self.file.send(operation(sender))?;
+        receiver
+            .recv()
+            .map_err(|_| Error::Operation)?
+            .map_err(file_system_error)
+    }
+}
+
+impl FileSystemSyncAccessHandleMethods<crate::DomTypeHolder> for FileSystemSyncAccessHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-read>
+    #[allow(unsafe_code)]
+    fn Read(
+        &self,
+        buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        // Let bufferSize be buffer's byte length.
+        let buffer_size = match &buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.len(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.len(),
+        };
+
+        // Let readStart be options["at"] if options["at"] exists; otherwise 0.
+        // Let readEnd be readStart + bufferSize.
+        // Let bytes be a byte sequence containing the bytes from readStart to readEnd of
+        // fileContents, or to the end of fileContents if it is shorter.
+        let at = options.at.unwrap_or(0);
+        let bytes = self.perform(|sender| OpenFileOperation::Read {
+            at,
+            len: buffer_size as u64,
+            sender,
+        })?;
+
+        // Set the contents of buffer to bytes, and return the number of bytes read.
+        let destination = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(mut view) => unsafe {
+                view.as_mut_slice().as_mut_ptr()
+            },
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(mut buffer) => unsafe {
+                buffer.as_mut_slice().as_mut_ptr()
+            },
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), destination, bytes.len());
+        }
+        Ok(bytes.len() as u64)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-write>
+    fn Write(
+        &self,
+        buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        // Let writePosition be options["at"] if options["at"] exists; otherwise 0.
+        let at = options.at.unwrap_or(0);
+        let data = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.to_vec(),
+        };
+
+        // If writePosition is larger than oldSize, the file is extended with null bytes
+        // before the write.
+        self.perform(|sender| OpenFileOperation::Write { at, data, sender })
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-truncate>
+    fn Truncate(&self, new_size: u64) -> ErrorResult {
+        self.perform(|sender| OpenFileOperation::Truncate(new_size, sender))
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-getsize>
+    fn GetSize(&self) -> Fallible<u64> {
+        self.perform(OpenFileOperation::GetSize)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-flush>
+    fn Flush(&self) -> ErrorResult {
+        self.perform(OpenFileOperation::Flush)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-close>
+    fn Close(&self) {
+        // If this's [[state]] is "closed", return.
+        if self.file.is_closed() {
+            return;
+        }
+
+        // Set this's [[state]] to "closed".
+        // Release the lock on this's [[file]].
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        if self.file.send(OpenFileOperation::Close(sender)).is_ok() {
+            let _ = receiver.recv();
+        }
+    }
+}
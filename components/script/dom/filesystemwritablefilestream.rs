@@ -0,0 +1,337 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::conversions::{FromJSValConvertible, ToJSValConvertible};
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue as SafeHandleValue;
+use net_traits::filemanager_thread::{FileSystemResult, OpenFileOperation};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemWritableFileStreamBinding::{
+    FileSystemWritableFileStreamMethods, FileSystemWriteChunkType, WriteCommandType, WriteParams,
+};
+use crate::dom::bindings::codegen::Bindings::QueuingStrategyBinding::QueuingStrategy;
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBufferOrBlobOrUSVString;
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::countqueuingstrategy::extract_size_algorithm;
+use crate::dom::filesystemhandle::{OpenFile, file_system_error};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::writablestream::WritableStream;
+use crate::dom::writablestreamdefaultcontroller::{
+    UnderlyingSinkType, WritableStreamDefaultController,
+};
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+/// <https://fs.spec.whatwg.org/#filesystemwritablefilestream>
+///
+/// Writes go to a swap file in the file manager, which replaces the file when the stream
+/// is closed.
+#[dom_struct]
+pub(crate) struct FileSystemWritableFileStream {
+    writablestream: WritableStream,
+    /// <https://fs.spec.whatwg.org/#filesystemwritablefilestream-file>
+    file: OpenFile,
+    /// <https://fs.spec.whatwg.org/#filesystemwritablefilestream-seekoffset>
+    seek_offset: Cell<u64>,
+}
+
+impl FileSystemWritableFileStream {
+    fn new_inherited(global: &GlobalScope, id: Uuid) -> FileSystemWritableFileStream {
+        FileSystemWritableFileStream {
+            writablestream: WritableStream::new_inherited(),
+            file: OpenFile::new(global, id),
+            seek_offset: Cell::new(0),
+        }
+    }
+
+    /// <https://fs.spec.whatwg.org/#create-a-new-filesystemwritablefilestream>
+    pub(crate) fn new(
+        global: &GlobalScope,
+        id: Uuid,
+        can_gc: CanGc,
+    ) -> DomRoot<FileSystemWritableFileStream> {
+        let stream = reflect_dom_object(
+            Box::new(FileSystemWritableFileStream::new_inherited(global, id)),
+            global,
+            can_gc,
+        );
+
+        // Let highWaterMark be 1.
+        // Let sizeAlgorithm be an algorithm that returns 1.
+        let size_algorithm = extract_size_algorithm(&QueuingStrategy::default(), can_gc);
+
+        // Set up stream with writeAlgorithm set to writeAlgorithm, closeAlgorithm set to
+        // closeAlgorithm, abortAlgorithm set to abortAlgorithm, highWaterMark set to
+        // highWaterMark, and sizeAlgorithm set to sizeAlgorithm.
+        let controller = WritableStreamDefaultController::new(
+            global,
+            UnderlyingSinkType::FileSystemWritable(Dom::from_ref(&*stream)),
+            1.0,
+            size_algorithm,
+            can_gc,
+        );
+        controller
+            .setup(GlobalScope::get_cx(), global, stream.upcast(), can_gc)
+            .expect("Setup of a file system writable stream cannot fail");
+        stream
+    }
+
+    /// Sends an operation on the swap file to the file manager, whose result settles the
+    /// returned promise.
+    fn perform<R>(
+        &self,
+        global: &GlobalScope,
+        operation: impl FnOnce(IpcSender<R>) -> OpenFileOperation,
+        can_gc: CanGc,
+    ) -> Rc<Promise>
+    where
+        R: Serialize + DeserializeOwned + Send + 'static,
+        FileSystemWritableFileStream: RoutedPromiseListener<R>,
+    {
+        let promise = Promise::new(global, can_gc);
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        if let Err(error) = self.file.send(operation(sender)) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// Writes `data` at the seek offset, and advances the seek offset past it.
+    fn write_data(&self, global: &GlobalScope, data: Vec<u8>, can_gc: CanGc) -> Rc<Promise> {
+        let at = self.seek_offset.get();
+        self.seek_offset.set(at + data.len() as u64);
+        self.perform(
+            global,
+            |sender| OpenFileOperation::Write { at, data, sender },
+            can_gc,
+        )
+    }
+
+    /// <https://fs.spec.whatwg.org/#write-a-chunk>
+    #[allow(unsafe_code)]
+    pub(crate) fn write_algorithm(
+        &self,
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        chunk: SafeHandleValue,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        // Let input be the result of converting chunk to a FileSystemWriteChunkType. If
+        // this throws an exception, then return a promise rejected with that exception.
+        let input = match unsafe { FileSystemWriteChunkType::from_jsval(*cx, chunk, ()) } {
+            Ok(ConversionResult::Success(input)) => input,
+            Ok(ConversionResult::Failure(error)) => {
+                return rejected_promise(global, Error::Type(error.into_owned()), can_gc);
+            },
+            Err(()) => return rejected_promise(global, Error::JSFailed, can_gc),
+        };
+
+        let data = match input {
+            FileSystemWriteChunkType::ArrayBufferView(view) => Ok(view.to_vec()),
+            FileSystemWriteChunkType::ArrayBuffer(buffer) => Ok(buffer.to_vec()),
+            FileSystemWriteChunkType::Blob(blob) => {
+                blob.get_bytes().map_err(|_| Error::NotReadable)
+            },
+            FileSystemWriteChunkType::USVString(string) => Ok(string.0.into_bytes()),
+            FileSystemWriteChunkType::WriteParams(params) => {
+                return self.write_params(global, &params, can_gc);
+            },
+        };
+        match data {
+            Ok(data) => self.write_data(global, data, can_gc),
+            Err(error) => rejected_promise(global, error, can_gc),
+        }
+    }
+
+    /// The steps of <https://fs.spec.whatwg.org/#write-a-chunk> for a `WriteParams` input.
+    fn write_params(
+        &self,
+        global: &GlobalScope,
+        params: &WriteParams,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        match params.type_ {
+            WriteCommandType::Write => {
+                // If data is undefined, reject p with a SyntaxError.
+                let Some(Some(data)) = &params.data else {
+                    return rejected_promise(global, Error::Syntax, can_gc);
+                };
+                let data = match data {
+                    ArrayBufferViewOrArrayBufferOrBlobOrUSVString::ArrayBufferView(view) => {
+                        Ok(view.to_vec())
+                    },
+                    ArrayBufferViewOrArrayBufferOrBlobOrUSVString::ArrayBuffer(buffer) => {
+                        Ok(buffer.to_vec())
+                    },
+                    ArrayBufferViewOrArrayBufferOrBlobOrUSVString::Blob(blob) => {
+                        blob.get_bytes().map_err(|_| Error::NotReadable)
+                    },
+                    ArrayBufferViewOrArrayBufferOrBlobOrUSVString::USVString(string) => {
+                        Ok(string.0.clone().into_bytes())
+                    },
+                };
+                // If input["position"] exists and is non-null, set stream's [[seekOffset]]
+                // to input["position"].
+                if let Some(Some(position)) = params.position {
+                    self.seek_offset.set(position);
+                }
+                match data {
+                    Ok(data) => self.write_data(global, data, can_gc),
+                    Err(error) => rejected_promise(global, error, can_gc),
+                }
+            },
+            WriteCommandType::Seek => {
+                // If chunk["position"] is undefined, reject p with a SyntaxError.
+                let Some(Some(position)) = params.position else {
+                    return rejected_promise(global, Error::Syntax, can_gc);
+                };
+                // Set stream's [[seekOffset]] to chunk["position"].
+                self.seek_offset.set(position);
+                Promise::new_resolved(global, GlobalScope::get_cx(), (), can_gc)
+            },
+            WriteCommandType::Truncate => {
+                // If chunk["size"] is undefined, reject p with a SyntaxError.
+                let Some(Some(new_size)) = params.size else {
+                    return rejected_promise(global, Error::Syntax, can_gc);
+                };
+                // If stream's [[seekOffset]] is bigger than newSize, set it to newSize.
+                if self.seek_offset.get() > new_size {
+                    self.seek_offset.set(new_size);
+                }
+                self.perform(
+                    global,
+                    |sender| OpenFileOperation::Truncate(new_size, sender),
+                    can_gc,
+                )
+            },
+        }
+    }
+
+    /// The closeAlgorithm of
+    /// <https://fs.spec.whatwg.org/#create-a-new-filesystemwritablefilestream>, which
+    /// replaces the contents of the file with those of the swap file.
+    pub(crate) fn close_algorithm(&self, global: &GlobalScope, can_gc: CanGc) -> Rc<Promise> {
+        self.perform(global, OpenFileOperation::Close, can_gc)
+    }
+
+    /// The abortAlgorithm of
+    /// <https://fs.spec.whatwg.org/#create-a-new-filesystemwritablefilestream>, which
+    /// discards the swap file.
+    pub(crate) fn abort_algorithm(&self, global: &GlobalScope, can_gc: CanGc) -> Rc<Promise> {
+        let _ = self.file.send(OpenFileOperation::Abort);
+        Promise::new_resolved(global, GlobalScope::get_cx(), (), can_gc)
+    }
+
+    /// Writes a chunk with a writer for this stream, as the methods of this interface do.
+    #[allow(unsafe_code)]
+    fn write_with_writer(&self, chunk: FileSystemWriteChunkType, can_gc: CanGc) -> Rc<Promise> {
+        let cx = GlobalScope::get_cx();
+        let global = self.global();
+
+        // Let writer be the result of getting a writer for this.
+        let writer = match self
+            .upcast::<WritableStream>()
+            .aquire_default_writer(cx, &global, can_gc)
+        {
+            Ok(writer) => writer,
+            Err(error) => return rejected_promise(&global, error, can_gc),
+        };
+
+        // Let result be the result of writing a chunk to writer given data.
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        unsafe { chunk.to_jsval(*cx, value.handle_mut()) };
+        let result = writer.write(cx, &global, value.handle(), can_gc);
+
+        // Release writer.
+        writer.release(cx, &global, can_gc);
+
+        // Return result.
+        result
+    }
+}
+
+impl FileSystemWritableFileStreamMethods<crate::DomTypeHolder> for FileSystemWritableFileStream {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemwritablefilestream-write>
+    fn Write(&self, data: FileSystemWriteChunkType, can_gc: CanGc) -> Rc<Promise> {
+        self.write_with_writer(data, can_gc)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemwritablefilestream-seek>
+    fn Seek(&self, position: u64, can_gc: CanGc) -> Rc<Promise> {
+        // Let result be the result of writing a chunk to writer given
+        // «[ "type" → "seek", "position" → position ]».
+        let params = RootedTraceableBox::new(WriteParams {
+            type_: WriteCommandType::Seek,
+            size: None,
+            position: Some(Some(position)),
+            data: None,
+        });
+        self.write_with_writer(FileSystemWriteChunkType::WriteParams(params), can_gc)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemwritablefilestream-truncate>
+    fn Truncate(&self, size: u64, can_gc: CanGc) -> Rc<Promise> {
+        // Let result be the result of writing a chunk to writer given
+        // «[ "type" → "truncate", "size" → size ]».
+        let params = RootedTraceableBox::new(WriteParams {
+            type_: WriteCommandType::Truncate,
+            size: Some(Some(size)),
+            position: None,
+            data: None,
+        });
+        self.write_with_writer(FileSystemWriteChunkType::WriteParams(params), can_gc)
+    }
+}
+
+fn rejected_promise(global: &GlobalScope, error: Error, can_gc: CanGc) -> Rc<Promise> {
+    let promise = Promise::new(global, can_gc);
+    promise.reject_error(error, can_gc);
+    promise
+}
+
+impl RoutedPromiseListener<FileSystemResult<u64>> for FileSystemWritableFileStream {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<u64>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(_) => promise.resolve_native(&(), can_gc),
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<()>> for FileSystemWritableFileStream {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<()>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(()) => promise.resolve_native(&(), can_gc),
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
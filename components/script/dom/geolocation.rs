@@ -0,0 +1,389 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, GeolocationPosition as Position, GeolocationWatchId};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use malloc_size_of_derive::MallocSizeOf;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::{
+    GeolocationMethods, PositionCallback, PositionErrorCallback, PositionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::GeolocationPositionErrorConstants;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::geolocationposition::GeolocationPosition;
+use crate::dom::geolocationpositionerror::GeolocationPositionError;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{descriptor_permission_state, prompt_user_from_embedder};
+use crate::script_runtime::CanGc;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+
+/// A position that was acquired before, with the time that it was acquired at in
+/// milliseconds since the Unix epoch.
+#[derive(Clone, Copy, MallocSizeOf)]
+struct CachedPosition {
+    position: Position,
+    timestamp: u64,
+}
+
+/// A call of `getCurrentPosition()` or `watchPosition()` that is waiting for a position.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PositionRequest {
+    #[ignore_malloc_size_of = "Rc"]
+    success_callback: Rc<PositionCallback>,
+    #[ignore_malloc_size_of = "Rc"]
+    error_callback: Option<Rc<PositionErrorCallback>>,
+    /// Whether this request comes from `watchPosition()`, in which case its id is the
+    /// watch id and it lasts until it is cleared.
+    is_watch: bool,
+    /// The timer that reports a timeout if no position is acquired in time. Only the first
+    /// position of a watch is subject to the timeout.
+    timeout: Option<OneshotTimerHandle>,
+}
+
+/// <https://w3c.github.io/geolocation/#geolocation_interface>
+#[dom_struct]
+pub(crate) struct Geolocation {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/geolocation/#dfn-cachedposition>
+    #[no_trace]
+    cached_position: Cell<Option<CachedPosition>>,
+    /// The requests that are waiting for a position, by their id. The ids of the requests
+    /// of `watchPosition()` are the <https://w3c.github.io/geolocation/#dfn-watchids>.
+    requests: DomRefCell<HashMap<i32, PositionRequest>>,
+    next_request_id: Cell<i32>,
+}
+
+impl Geolocation {
+    fn new_inherited() -> Geolocation {
+        Geolocation {
+            reflector_: Reflector::new(),
+            cached_position: Cell::new(None),
+            requests: Default::default(),
+            next_request_id: Cell::new(1),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<Geolocation> {
+        reflect_dom_object(Box::new(Geolocation::new_inherited()), global, can_gc)
+    }
+
+    /// Register a request for a position with its callbacks, returning its id.
+    fn add_request(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        is_watch: bool,
+    ) -> i32 {
+        let id = self.next_request_id.get();
+        self.next_request_id.set(id.checked_add(1).unwrap_or(1));
+        self.requests.borrow_mut().insert(
+            id,
+            PositionRequest {
+                success_callback,
+                error_callback,
+                is_watch,
+                timeout: None,
+            },
+        );
+        id
+    }
+
+    /// <https://w3c.github.io/geolocation/#dfn-request-a-position>
+    fn request_a_position(&self, id: i32, options: &PositionOptions) {
+        let global = self.global();
+        let is_watch = self
+            .requests
+            .borrow()
+            .get(&id)
+            .is_some_and(|request| request.is_watch);
+
+        // Steps 3-8. Check that the document is allowed to use the "geolocation" feature in
+        // a secure context, and request permission to use it.
+        // TODO: Step 5. Wait for a hidden document to become visible first.
+        let mut permission =
+            descriptor_permission_state(PermissionName::Geolocation, Some(&global));
+        if permission == PermissionState::Prompt {
+            permission = prompt_user_from_embedder(PermissionName::Geolocation, &global);
+        }
+        if permission != PermissionState::Granted {
+            self.queue_result(
+                id,
+                Err((
+                    GeolocationPositionErrorConstants::PERMISSION_DENIED,
+                    "User denied Geolocation".to_owned(),
+                )),
+            );
+            return;
+        }
+
+        // https://w3c.github.io/geolocation/#dfn-acquire-a-position
+        // Step 4. If the cached position is not older than the maximum age, use it instead
+        // of acquiring a new position. A watch still goes on to watch for new positions.
+        if let Some(cached_position) = self.cached_position.get() {
+            let age = now_in_milliseconds().saturating_sub(cached_position.timestamp);
+            if options.maximumAge > 0 && age <= options.maximumAge as u64 {
+                self.queue_result(id, Ok(cached_position));
+                if !is_watch {
+                    return;
+                }
+            }
+        }
+
+        // Step 6. Try to acquire a position within the timeout.
+        if options.timeout != u32::MAX {
+            let callback = OneshotTimerCallback::GeolocationTimeout(GeolocationTimeoutCallback {
+                geolocation: Trusted::new(self),
+                request_id: id,
+            });
+            let timeout =
+                global.schedule_callback(callback, Duration::from_millis(options.timeout as u64));
+            if let Some(request) = self.requests.borrow_mut().get_mut(&id) {
+                request.timeout = Some(timeout);
+            }
+        }
+
+        let Some(webview_id) = global.webview_id() else {
+            return;
+        };
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let geolocation = Trusted::new(self);
+        let task_source = global
+            .task_manager()
+            .geolocation_task_source()
+            .to_sendable();
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |message| {
+                let Ok(result) = message else {
+                    return;
+                };
+                let geolocation = geolocation.clone();
+                task_source.queue(task!(geolocation_position_acquired: move || {
+                    let geolocation = geolocation.root();
+                    // Step 8. Set the cached position to the acquired position.
+                    let result = result
+                        .map(|position| {
+                            let cached_position = CachedPosition {
+                                position,
+                                timestamp: now_in_milliseconds(),
+                            };
+                            geolocation.cached_position.set(Some(cached_position));
+                            cached_position
+                        })
+                        .map_err(|message| {
+                            (GeolocationPositionErrorConstants::POSITION_UNAVAILABLE, message)
+                        });
+                    geolocation.handle_result(id, result, CanGc::note());
+                }));
+            }),
+        );
+
+        let message = if is_watch {
+            let watch_id = GeolocationWatchId(global.pipeline_id(), id);
+            EmbedderMsg::WatchGeolocationPosition(
+                webview_id,
+                watch_id,
+                options.enableHighAccuracy,
+                sender,
+            )
+        } else {
+            EmbedderMsg::RequestGeolocationPosition(webview_id, options.enableHighAccuracy, sender)
+        };
+        global.send_to_embedder(message);
+    }
+
+    /// Queue a task on the geolocation task source to report the result of the request with
+    /// the given id.
+    fn queue_result(&self, id: i32, result: Result<CachedPosition, (u16, String)>) {
+        let geolocation = Trusted::new(self);
+        self.global()
+            .task_manager()
+            .geolocation_task_source()
+            .queue(task!(geolocation_report_result: move || {
+                geolocation.root().handle_result(id, result, CanGc::note());
+            }));
+    }
+
+    /// Invoke the success callback of the request with the given id with a position, or
+    /// <https://w3c.github.io/geolocation/#dfn-call-back-with-error>. Requests of
+    /// `getCurrentPosition()` are done after that, and so are watches that were denied
+    /// permission.
+    fn handle_result(&self, id: i32, result: Result<CachedPosition, (u16, String)>, can_gc: CanGc) {
+        let (success_callback, error_callback) = {
+            let mut requests = self.requests.borrow_mut();
+            // The request timed out already, or its watch was cleared.
+            let Some(request) = requests.get_mut(&id) else {
+                return;
+            };
+            if let Some(timeout) = request.timeout.take() {
+                self.global().unschedule_callback(timeout);
+            }
+            let callbacks = (
+                request.success_callback.clone(),
+                request.error_callback.clone(),
+            );
+            let is_done = !request.is_watch ||
+                matches!(
+                    result,
+                    Err((GeolocationPositionErrorConstants::PERMISSION_DENIED, _))
+                );
+            if is_done {
+                requests.remove(&id);
+            }
+            callbacks
+        };
+
+        let global = self.global();
+        match result {
+            Ok(cached_position) => {
+                let position = GeolocationPosition::new(
+                    &global,
+                    cached_position.position,
+                    cached_position.timestamp,
+                    can_gc,
+                );
+                let _ = success_callback.Call__(&position, ExceptionHandling::Report, can_gc);
+            },
+            Err((code, message)) => {
+                // Step 1. If callback is null, return.
+                let Some(error_callback) = error_callback else {
+                    return;
+                };
+                // Step 2. Let error be a newly created GeolocationPositionError instance whose
+                // code attribute is initialized to code.
+                let error =
+                    GeolocationPositionError::new(&global, code, DOMString::from(message), can_gc);
+                // Step 3. Invoke callback with « error » and "report".
+                let _ = error_callback.Call__(&error, ExceptionHandling::Report, can_gc);
+            },
+        }
+    }
+
+    /// The document of this [`Geolocation`] is not fully active, so report that the
+    /// position is unavailable instead of requesting it.
+    fn report_document_not_fully_active(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+    ) {
+        let id = self.add_request(success_callback, error_callback, false);
+        self.queue_result(
+            id,
+            Err((
+                GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                "Document is not fully active".to_owned(),
+            )),
+        );
+    }
+}
+
+fn now_in_milliseconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl GeolocationMethods<crate::DomTypeHolder> for Geolocation {
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-getcurrentposition>
+    fn GetCurrentPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) {
+        // Step 1. If this's relevant global object's associated Document is not fully
+        // active, call back with error POSITION_UNAVAILABLE.
+        if !self.global().as_window().Document().is_fully_active() {
+            return self.report_document_not_fully_active(success_callback, error_callback);
+        }
+
+        // Step 2. Request a position passing this, successCallback, errorCallback, and
+        // options.
+        let id = self.add_request(success_callback, error_callback, false);
+        self.request_a_position(id, options);
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-watchposition>
+    fn WatchPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) -> i32 {
+        // Step 1. If this's relevant global object's associated Document is not fully
+        // active, call back with error POSITION_UNAVAILABLE and return 0.
+        if !self.global().as_window().Document().is_fully_active() {
+            self.report_document_not_fully_active(success_callback, error_callback);
+            return 0;
+        }
+
+        // Steps 2-5. Generate a watch id, add it to the watch ids, request a position
+        // passing it, and return it.
+        let watch_id = self.add_request(success_callback, error_callback, true);
+        self.request_a_position(watch_id, options);
+        watch_id
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-clearwatch>
+    fn ClearWatch(&self, watch_id: i32) {
+        // Step 1. Remove watchId from this's [[watchIDs]].
+        let request = {
+            let mut requests = self.requests.borrow_mut();
+            if !requests
+                .get(&watch_id)
+                .is_some_and(|request| request.is_watch)
+            {
+                return;
+            }
+            requests.remove(&watch_id)
+        };
+        let global = self.global();
+        if let Some(timeout) = request.and_then(|request| request.timeout) {
+            global.unschedule_callback(timeout);
+        }
+
+        // Tell the geolocation provider that it can stop watching the position.
+        if let Some(webview_id) = global.webview_id() {
+            let watch_id = GeolocationWatchId(global.pipeline_id(), watch_id);
+            global.send_to_embedder(EmbedderMsg::ClearGeolocationWatch(webview_id, watch_id));
+        }
+    }
+}
+
+/// Reports a timeout to a request of a [`Geolocation`] that did not get a position in time.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) struct GeolocationTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    geolocation: Trusted<Geolocation>,
+    request_id: i32,
+}
+
+impl GeolocationTimeoutCallback {
+    pub(crate) fn invoke(self, can_gc: CanGc) {
+        let error = (
+            GeolocationPositionErrorConstants::TIMEOUT,
+            "Timeout expired".to_owned(),
+        );
+        self.geolocation
+            .root()
+            .handle_result(self.request_id, Err(error), can_gc);
+    }
+}
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition;
+
+use crate::dom::bindings::codegen::Bindings::GeolocationCoordinatesBinding::GeolocationCoordinatesMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/geolocation/#coordinates_interface>
+#[dom_struct]
+pub(crate) struct GeolocationCoordinates {
+    reflector_: Reflector,
+    #[no_trace]
+    position: GeolocationPosition,
+}
+
+impl GeolocationCoordinates {
+    fn new_inherited(position: GeolocationPosition) -> GeolocationCoordinates {
+        GeolocationCoordinates {
+            reflector_: Reflector::new(),
+            position,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        position: GeolocationPosition,
+        can_gc: CanGc,
+    ) -> DomRoot<GeolocationCoordinates> {
+        reflect_dom_object(
+            Box::new(GeolocationCoordinates::new_inherited(position)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+/// The providers of positions are not trusted to only report finite values, so values that
+/// are not finite are reported as zero, or as unknown if they are optional.
+fn finite_or_zero(value: f64) -> Finite<f64> {
+    Finite::new(value).unwrap_or_else(|| Finite::wrap(0.))
+}
+
+impl GeolocationCoordinatesMethods<crate::DomTypeHolder> for GeolocationCoordinates {
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-accuracy>
+    fn Accuracy(&self) -> Finite<f64> {
+        finite_or_zero(self.position.accuracy)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-latitude>
+    fn Latitude(&self) -> Finite<f64> {
+        finite_or_zero(self.position.latitude)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-longitude>
+    fn Longitude(&self) -> Finite<f64> {
+        finite_or_zero(self.position.longitude)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-altitude>
+    fn GetAltitude(&self) -> Option<Finite<f64>> {
+        self.position.altitude.and_then(Finite::new)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-altitudeaccuracy>
+    fn GetAltitudeAccuracy(&self) -> Option<Finite<f64>> {
+        self.position.altitude_accuracy.and_then(Finite::new)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-heading>
+    fn GetHeading(&self) -> Option<Finite<f64>> {
+        self.position.heading.and_then(Finite::new)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-speed>
+    fn GetSpeed(&self) -> Option<Finite<f64>> {
+        self.position.speed.and_then(Finite::new)
+    }
+}
@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition as Position;
+
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionBinding::GeolocationPositionMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::geolocationcoordinates::GeolocationCoordinates;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/geolocation/#position_interface>
+#[dom_struct]
+pub(crate) struct GeolocationPosition {
+    reflector_: Reflector,
+    coords: Dom<GeolocationCoordinates>,
+    /// When the position was acquired, in milliseconds since the Unix epoch.
+    timestamp: u64,
+}
+
+impl GeolocationPosition {
+    fn new_inherited(coords: &GeolocationCoordinates, timestamp: u64) -> GeolocationPosition {
+        GeolocationPosition {
+            reflector_: Reflector::new(),
+            coords: Dom::from_ref(coords),
+            timestamp,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        position: Position,
+        timestamp: u64,
+        can_gc: CanGc,
+    ) -> DomRoot<GeolocationPosition> {
+        let coords = GeolocationCoordinates::new(global, position, can_gc);
+        reflect_dom_object(
+            Box::new(GeolocationPosition::new_inherited(&coords, timestamp)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl GeolocationPositionMethods<crate::DomTypeHolder> for GeolocationPosition {
+    /// <https://w3c.github.io/geolocation/#dom-geolocationposition-coords>
+    fn Coords(&self) -> DomRoot<GeolocationCoordinates> {
+        DomRoot::from_ref(&self.coords)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationposition-timestamp>
+    fn Timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
@@ -25,7 +25,7 @@ use content_security_policy::CspList;
 use crossbeam_channel::Sender;
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, PermissionFeature};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::{IsWrapper, UnwrapObjectDynamic};
@@ -68,15 +68,12 @@ use super::bindings::error::Fallible;
 use super::bindings::trace::{HashMapTracedValues, RootedTraceableBox};
 use super::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use super::transformstream::CrossRealmTransform;
+use crate::conversions::Convert;
 use crate::dom::bindings::cell::{DomRefCell, RefMut};
 use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSource_Binding::EventSourceMethods;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::NotificationBinding::NotificationPermissionCallback;
-use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
-    PermissionName, PermissionState,
-};
-use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::Report;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
@@ -108,18 +105,26 @@ use crate::dom::messageport::MessagePort;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
 use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
+use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{CrossRealmTransformReadable, ReadableStream};
+use crate::dom::report::ReportData;
+use crate::dom::reportingendpoint::{
+    REPORT_DELIVERY_DELAY, ReportDeliveryCallback, ReportingEndpoint,
+};
 use crate::dom::reportingobserver::ReportingObserver;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::dom::trustedtypepolicyfactory::TrustedTypePolicyFactory;
 use crate::dom::types::MessageEvent;
 use crate::dom::underlyingsourcecontainer::UnderlyingSourceType;
+use crate::dom::webglrenderingcontext::WebGLCommandSender;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::gpudevice::GPUDevice;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::identityhub::IdentityHub;
+use crate::dom::websocket::WebSocket;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
@@ -191,6 +196,9 @@ pub(crate) struct GlobalScope {
     eventtarget: EventTarget,
     crypto: MutNullableDom<Crypto>,
 
+    /// <https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler>
+    scheduler: MutNullableDom<Scheduler>,
+
     /// A [`TaskManager`] for this [`GlobalScope`].
     task_manager: OnceCell<TaskManager>,
 
@@ -252,6 +260,10 @@ pub(crate) struct GlobalScope {
     /// <https://html.spec.whatwg.org/multipage/#in-error-reporting-mode>
     in_error_reporting_mode: Cell<bool>,
 
+    /// The identifiers of the deprecated features whose use has been reported, which are
+    /// only reported once per global.
+    reported_deprecations: DomRefCell<HashSet<String>>,
+
     /// Associated resource threads for use by DOM objects like XMLHttpRequest,
     /// including resource_thread, filemanager_thread and storage_thread
     #[no_trace]
@@ -273,8 +285,9 @@ pub(crate) struct GlobalScope {
     #[no_trace]
     top_level_creation_url: Option<ServoUrl>,
 
-    /// A map for storing the previous permission state read results.
-    permission_state_invocation_results: DomRefCell<HashMap<PermissionName, PermissionState>>,
+    /// Vector storing references of all PermissionStatus objects, which are updated when the
+    /// decision of the user about their permission changes.
+    permission_status_tracker: DOMTracker<PermissionStatus>,
 
     /// The microtask queue associated with this global.
     ///
@@ -292,6 +305,9 @@ pub(crate) struct GlobalScope {
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
 
+    /// Vector storing references of all WebSockets.
+    websocket_tracker: DOMTracker<WebSocket>,
+
     /// Storage for watching rejected promises waiting for some client to
     /// consume their rejection.
     /// Promises in this list have been rejected in the last turn of the
@@ -745,6 +761,7 @@ impl GlobalScope {
             blob_state: Default::default(),
             eventtarget: EventTarget::new_inherited(),
             crypto: Default::default(),
+            scheduler: Default::default(),
             registration_map: DomRefCell::new(HashMapTracedValues::new()),
             worker_map: DomRefCell::new(HashMapTracedValues::new()),
             pipeline_id,
@@ -757,15 +774,17 @@ impl GlobalScope {
             time_profiler_chan,
             script_to_constellation_chan,
             in_error_reporting_mode: Default::default(),
+            reported_deprecations: Default::default(),
             resource_threads,
             timers: OnceCell::default(),
             origin,
             creation_url,
             top_level_creation_url,
-            permission_state_invocation_results: Default::default(),
+            permission_status_tracker: DOMTracker::new(),
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            websocket_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
             #[cfg(feature = "webgpu")]
@@ -818,37 +837,38 @@ impl GlobalScope {
         scope: &ServoUrl,
         registration_id: ServiceWorkerRegistrationId,
         installing_worker: Option<ServiceWorkerId>,
-        _waiting_worker: Option<ServiceWorkerId>,
-        _active_worker: Option<ServiceWorkerId>,
+        waiting_worker: Option<ServiceWorkerId>,
+        active_worker: Option<ServiceWorkerId>,
         can_gc: CanGc,
     ) -> DomRoot<ServiceWorkerRegistration> {
         // Step 1
         let mut registrations = self.registration_map.borrow_mut();
 
-        if let Some(registration) = registrations.get(&registration_id) {
-            // Step 3
-            return DomRoot::from_ref(&**registration);
-        }
-
-        // Step 2.1 -> 2.5
-        let new_registration =
-            ServiceWorkerRegistration::new(self, scope.clone(), registration_id, can_gc);
-
-        // Step 2.6
-        if let Some(worker_id) = installing_worker {
-            let worker = self.get_serviceworker(script_url, scope, worker_id, can_gc);
-            new_registration.set_installing(&worker);
-        }
-
-        // TODO: 2.7 (waiting worker)
+        let registration = match registrations.get(&registration_id) {
+            Some(registration) => DomRoot::from_ref(&**registration),
+            None => {
+                // Step 2.1 -> 2.5
+                let new_registration =
+                    ServiceWorkerRegistration::new(self, scope.clone(), registration_id, can_gc);
 
-        // TODO: 2.8 (active worker)
+                // Step 2.9
+                registrations.insert(registration_id, Dom::from_ref(&*new_registration));
+                new_registration
+            },
+        };
 
-        // Step 2.9
-        registrations.insert(registration_id, Dom::from_ref(&*new_registration));
+        // Step 2.6 -> 2.8
+        // Note: this also runs for an existing registration, since the manager does not
+        // queue the tasks of <https://w3c.github.io/ServiceWorker/#update-registration-state>.
+        let get_worker = |worker_id| self.get_serviceworker(script_url, scope, worker_id, can_gc);
+        registration.set_workers(
+            installing_worker.map(&get_worker).as_deref(),
+            waiting_worker.map(&get_worker).as_deref(),
+            active_worker.map(&get_worker).as_deref(),
+        );
 
         // Step 3
-        new_registration
+        registration
     }
 
     /// <https://w3c.github.io/ServiceWorker/#get-the-service-worker-object>
@@ -2192,10 +2212,19 @@ impl GlobalScope {
         }
     }
 
-    pub(crate) fn permission_state_invocation_results(
-        &self,
-    ) -> &DomRefCell<HashMap<PermissionName, PermissionState>> {
-        &self.permission_state_invocation_results
+    pub(crate) fn track_permission_status(&self, permission_status: &PermissionStatus) {
+        self.permission_status_tracker.track(permission_status);
+    }
+
+    /// The decision of the user about the given permission of the origin of this global
+    /// changed, so run the update steps of the PermissionStatus objects for it.
+    pub(crate) fn permission_decision_changed(&self, feature: PermissionFeature) {
+        self.permission_status_tracker
+            .for_each(|permission_status: DomRoot<PermissionStatus>| {
+                if permission_status.get_query().convert() == feature {
+                    permission_status.run_the_update_steps();
+                }
+            });
     }
 
     pub(crate) fn track_worker(
@@ -2219,6 +2248,21 @@ impl GlobalScope {
         self.event_source_tracker.track(event_source);
     }
 
+    pub(crate) fn track_websocket(&self, websocket: &WebSocket) {
+        self.websocket_tracker.track(websocket);
+    }
+
+    /// Make all WebSockets of this global disappear, returning whether any of them had a
+    /// connection that was being established or open.
+    pub(crate) fn make_websockets_disappear(&self) -> bool {
+        let mut affected_any_websocket = false;
+        self.websocket_tracker
+            .for_each(|websocket: DomRoot<WebSocket>| {
+                affected_any_websocket |= websocket.make_disappear();
+            });
+        affected_any_websocket
+    }
+
     pub(crate) fn close_event_sources(&self) -> bool {
         let mut canceled_any_fetch = false;
         self.event_source_tracker
@@ -2357,6 +2401,10 @@ impl GlobalScope {
         self.crypto.or_init(|| Crypto::new(self, can_gc))
     }
 
+    pub(crate) fn scheduler(&self, can_gc: CanGc) -> DomRoot<Scheduler> {
+        self.scheduler.or_init(|| Scheduler::new(self, can_gc))
+    }
+
     pub(crate) fn live_devtools_updates(&self) -> bool {
         self.devtools_wants_updates.get()
     }
@@ -2490,6 +2538,16 @@ impl GlobalScope {
         unreachable!();
     }
 
+    pub(crate) fn webgl_chan(&self) -> Option<WebGLCommandSender> {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.webgl_chan();
+        }
+        if let Some(worker) = self.downcast::<DedicatedWorkerGlobalScope>() {
+            return worker.webgl_chan();
+        }
+        None
+    }
+
     /// Schedule a [`TimerEventRequest`] on this [`GlobalScope`]'s [`timers::TimerScheduler`].
     /// Every Worker has its own scheduler, which handles events in the Worker event loop,
     /// but `Window`s use a shared scheduler associated with their [`ScriptThread`].
@@ -2911,6 +2969,10 @@ impl GlobalScope {
         self.timers().slow_down();
     }
 
+    pub(crate) fn slow_down_timers_intensively(&self) {
+        self.timers().slow_down_intensively();
+    }
+
     pub(crate) fn speed_up_timers(&self) {
         self.timers().speed_up();
     }
@@ -3364,26 +3426,77 @@ impl GlobalScope {
         unreachable!();
     }
 
-    pub(crate) fn append_report(&self, report: Report) {
+    /// Append `report` to the reports to deliver to the endpoints, which are delivered in a
+    /// batch shortly after the first of them is appended.
+    pub(crate) fn append_report(&self, report: ReportData) {
+        let was_empty = self.with_reports(|reports| {
+            reports.push(report);
+            reports.len() == 1
+        });
+        if was_empty {
+            let callback = OneshotTimerCallback::ReportDelivery(ReportDeliveryCallback {
+                global: Trusted::new(self),
+            });
+            self.schedule_callback(callback, REPORT_DELIVERY_DELAY);
+        }
+    }
+
+    /// Take the reports to deliver to the endpoints.
+    pub(crate) fn take_reports(&self) -> Vec<ReportData> {
+        self.with_reports(std::mem::take)
+    }
+
+    fn with_reports<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
         if let Some(window) = self.downcast::<Window>() {
-            return window.append_report(report);
+            return window.with_reports(f);
         }
         if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
-            return worker.append_report(report);
+            return worker.with_reports(f);
         }
         unreachable!();
     }
 
-    pub(crate) fn buffered_reports(&self) -> Vec<Report> {
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-report-buffer>
+    pub(crate) fn with_report_buffer<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
         if let Some(window) = self.downcast::<Window>() {
-            return window.buffered_reports();
+            return window.with_report_buffer(f);
         }
         if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
-            return worker.buffered_reports();
+            return worker.with_report_buffer(f);
         }
         unreachable!();
     }
 
+    pub(crate) fn buffered_reports(&self) -> Vec<ReportData> {
+        self.with_report_buffer(|report_buffer| report_buffer.clone())
+    }
+
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-endpoints>
+    pub(crate) fn with_reporting_endpoints<R>(
+        &self,
+        f: impl FnOnce(&mut Vec<ReportingEndpoint>) -> R,
+    ) -> R {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.with_reporting_endpoints(f);
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.with_reporting_endpoints(f);
+        }
+        unreachable!();
+    }
+
+    pub(crate) fn reporting_endpoints(&self) -> Vec<ReportingEndpoint> {
+        self.with_reporting_endpoints(|endpoints| endpoints.clone())
+    }
+
+    /// Record the use of the deprecated feature `id`, and return whether it is the first use of
+    /// the feature in this global.
+    pub(crate) fn note_deprecated_feature_use(&self, id: &str) -> bool {
+        self.reported_deprecations
+            .borrow_mut()
+            .insert(id.to_owned())
+    }
+
     pub(crate) fn import_map(&self) -> Ref<'_, ImportMap> {
         self.import_map.borrow()
     }
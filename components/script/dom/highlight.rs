@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use indexmap::IndexSet;
+use js::rust::HandleObject;
+
+use crate::dom::abstractrange::AbstractRange;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HighlightBinding::{HighlightMethods, HighlightType};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::like::Setlike;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://drafts.csswg.org/css-highlight-api-1/#highlight>
+#[dom_struct]
+pub(crate) struct Highlight {
+    reflector_: Reflector,
+    #[custom_trace]
+    ranges: DomRefCell<IndexSet<DomRoot<AbstractRange>>>,
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-priority>
+    priority: Cell<i32>,
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-type>
+    type_: Cell<HighlightType>,
+}
+
+impl Highlight {
+    fn new_inherited(ranges: &[&AbstractRange]) -> Highlight {
+        Highlight {
+            reflector_: Reflector::new(),
+            ranges: DomRefCell::new(
+                ranges
+                    .iter()
+                    .map(|range| DomRoot::from_ref(*range))
+                    .collect(),
+            ),
+            priority: Cell::new(0),
+            type_: Cell::new(HighlightType::Highlight),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        ranges: &[&AbstractRange],
+        can_gc: CanGc,
+    ) -> DomRoot<Highlight> {
+        reflect_dom_object_with_proto(
+            Box::new(Highlight::new_inherited(ranges)),
+            window,
+            proto,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn priority(&self) -> i32 {
+        self.priority.get()
+    }
+
+    pub(crate) fn ranges(&self) -> Vec<DomRoot<AbstractRange>> {
+        self.ranges.borrow().iter().cloned().collect()
+    }
+
+    /// The ranges of this highlight changed, so the highlighted text has to be updated if
+    /// it is registered.
+    fn ranges_changed(&self) {
+        self.global()
+            .as_window()
+            .Document()
+            .update_text_highlights();
+    }
+}
+
+impl HighlightMethods<crate::DomTypeHolder> for Highlight {
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-highlight>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        initial_ranges: &[&AbstractRange],
+    ) -> Fallible<DomRoot<Highlight>> {
+        Ok(Highlight::new(window, proto, initial_ranges, can_gc))
+    }
+
+    fn Size(&self) -> u32 {
+        self.ranges.size()
+    }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-priority>
+    fn Priority(&self) -> i32 {
+        self.priority.get()
+    }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-priority>
+    fn SetPriority(&self, priority: i32) {
+        self.priority.set(priority);
+        self.ranges_changed();
+    }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-type>
+    fn Type(&self) -> HighlightType {
+        self.type_.get()
+    }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-type>
+    fn SetType(&self, type_: HighlightType) {
+        self.type_.set(type_);
+    }
+}
+
+impl Setlike for Highlight {
+    type Key = DomRoot<AbstractRange>;
+
+    fn get_index(&self, index: u32) -> Option<Self::Key> {
+        self.ranges.get_index(index)
+    }
+
+    fn size(&self) -> u32 {
+        self.ranges.size()
+    }
+
+    fn add(&self, key: Self::Key) {
+        self.ranges.add(key);
+        self.ranges_changed();
+    }
+
+    fn has(&self, key: Self::Key) -> bool {
+        self.ranges.has(key)
+    }
+
+    fn clear(&self) {
+        self.ranges.clear();
+        self.ranges_changed();
+    }
+
+    fn delete(&self, key: Self::Key) -> bool {
+        let deleted = self.ranges.delete(key);
+        if deleted {
+            self.ranges_changed();
+        }
+        deleted
+    }
+}
@@ -0,0 +1,112 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use indexmap::IndexMap;
+use layout_api::HighlightKind;
+
+use crate::dom::abstractrange::AbstractRange;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HighlightRegistryBinding::HighlightRegistryMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::like::Maplike;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::highlight::Highlight;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://drafts.csswg.org/css-highlight-api-1/#highlight-registry>
+#[dom_struct]
+pub(crate) struct HighlightRegistry {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    #[custom_trace]
+    highlights: DomRefCell<IndexMap<DOMString, DomRoot<Highlight>>>,
+}
+
+impl HighlightRegistry {
+    fn new_inherited(window: &Window) -> HighlightRegistry {
+        HighlightRegistry {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            highlights: DomRefCell::new(IndexMap::new()),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<HighlightRegistry> {
+        reflect_dom_object(
+            Box::new(HighlightRegistry::new_inherited(window)),
+            window,
+            can_gc,
+        )
+    }
+
+    /// The ranges of the registered highlights, in the order in which they are painted:
+    /// highlights with a higher priority are painted over those with a lower one, and of
+    /// highlights with the same priority, the one that was registered last is painted on
+    /// top.
+    ///
+    /// TODO: Paint highlights with the styles of their `::highlight()` pseudo-elements,
+    /// which the style system doesn't support yet, rather than all alike.
+    pub(crate) fn highlighted_ranges(&self) -> Vec<(DomRoot<AbstractRange>, HighlightKind)> {
+        let mut highlights: Vec<_> = self.highlights.borrow().values().cloned().collect();
+        highlights.sort_by_key(|highlight| highlight.priority());
+        highlights
+            .iter()
+            .flat_map(|highlight| highlight.ranges())
+            .map(|range| (range, HighlightKind::Custom))
+            .collect()
+    }
+
+    fn highlights_changed(&self) {
+        self.window.Document().update_text_highlights();
+    }
+}
+
+impl HighlightRegistryMethods<crate::DomTypeHolder> for HighlightRegistry {
+    fn Size(&self) -> u32 {
+        self.highlights.size()
+    }
+}
+
+impl Maplike for HighlightRegistry {
+    type Key = DOMString;
+    type Value = DomRoot<Highlight>;
+
+    fn get_index(&self, index: u32) -> Option<(Self::Key, Self::Value)> {
+        self.highlights.get_index(index)
+    }
+
+    fn get(&self, key: Self::Key) -> Option<Self::Value> {
+        self.highlights.get(key)
+    }
+
+    fn size(&self) -> u32 {
+        self.highlights.size()
+    }
+
+    fn set(&self, key: Self::Key, value: Self::Value) {
+        self.highlights.set(key, value);
+        self.highlights_changed();
+    }
+
+    fn has(&self, key: Self::Key) -> bool {
+        self.highlights.has(key)
+    }
+
+    fn clear(&self) {
+        self.highlights.clear();
+        self.highlights_changed();
+    }
+
+    fn delete(&self, key: Self::Key) -> bool {
+        let deleted = self.highlights.delete(key);
+        if deleted {
+            self.highlights_changed();
+        }
+        deleted
+    }
+}
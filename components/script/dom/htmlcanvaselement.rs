@@ -23,6 +23,7 @@ use script_bindings::weakref::WeakRef;
 use servo_media::streams::MediaStreamType;
 use servo_media::streams::registry::MediaStreamId;
 use style::attr::AttrValue;
+use webrender_api::ImageKey;
 
 use super::node::NodeDamage;
 pub(crate) use crate::canvas_context::*;
@@ -33,6 +34,7 @@ use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::HTMLCanvasElementBinding::{
     BlobCallback, HTMLCanvasElementMethods, RenderingContext as RootedRenderingContext,
 };
+use crate::dom::bindings::codegen::Bindings::ImageBitmapRenderingContextBinding::ImageBitmapRenderingContextSettings;
 use crate::dom::bindings::codegen::Bindings::MediaStreamBinding::MediaStreamMethods;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
 use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
@@ -51,6 +53,7 @@ use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
 #[cfg(not(feature = "webgpu"))]
 use crate::dom::gpucanvascontext::GPUCanvasContext;
 use crate::dom::htmlelement::HTMLElement;
+use crate::dom::imagebitmaprenderingcontext::ImageBitmapRenderingContext;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
 use crate::dom::node::{Node, NodeTraits};
@@ -78,6 +81,11 @@ pub(crate) struct HTMLCanvasElement {
     callback_id: Cell<u32>,
     #[ignore_malloc_size_of = "not implemented for webidl callbacks"]
     blob_callbacks: RefCell<HashMap<u32, Rc<BlobCallback>>>,
+
+    /// The image of the last frame that the OffscreenCanvas of this placeholder canvas
+    /// element presented in it.
+    #[no_trace]
+    placeholder_image: Cell<Option<ImageKey>>,
 }
 
 impl HTMLCanvasElement {
@@ -91,6 +99,7 @@ impl HTMLCanvasElement {
             context_mode: DomRefCell::new(None),
             callback_id: Cell::new(0),
             blob_callbacks: RefCell::new(HashMap::new()),
+            placeholder_image: Cell::new(None),
         }
     }
 
@@ -135,6 +144,24 @@ impl HTMLCanvasElement {
         }
     }
 
+    /// Present a frame of the OffscreenCanvas of this placeholder canvas element.
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
+    pub(crate) fn update_placeholder(
+        &self,
+        image_key: Option<ImageKey>,
+        size: Size2D<u32>,
+        can_gc: CanGc,
+    ) {
+        self.placeholder_image.set(image_key);
+        if self.Width() != size.width {
+            self.set_natural_width(size.width, can_gc);
+        }
+        if self.Height() != size.height {
+            self.set_natural_height(size.height, can_gc);
+        }
+        self.upcast::<Node>().dirty(NodeDamage::Other);
+    }
+
     pub(crate) fn set_natural_width(&self, value: u32, can_gc: CanGc) {
         let value = if value > UNSIGNED_LONG_MAX {
             DEFAULT_WIDTH
@@ -164,11 +191,15 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<'_, HTMLCanvasElement> {
                 Some(RenderingContext::Context2d(context)) => {
                     context.to_layout().canvas_data_source()
                 },
+                Some(RenderingContext::BitmapRenderer(context)) => {
+                    context.to_layout().canvas_data_source()
+                },
                 Some(RenderingContext::WebGL(context)) => context.to_layout().canvas_data_source(),
                 Some(RenderingContext::WebGL2(context)) => context.to_layout().canvas_data_source(),
                 #[cfg(feature = "webgpu")]
                 Some(RenderingContext::WebGPU(context)) => context.to_layout().canvas_data_source(),
-                Some(RenderingContext::Placeholder(_)) | None => None,
+                Some(RenderingContext::Placeholder(_)) => self.unsafe_get().placeholder_image.get(),
+                None => None,
             }
         };
 
@@ -224,7 +255,7 @@ impl HTMLCanvasElement {
         let attrs = Self::get_gl_attributes(cx, options)?;
         let canvas = HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(self));
         let context = WebGLRenderingContext::new(
-            &window,
+            window.as_global_scope(),
             &canvas,
             WebGLVersion::WebGL1,
             size,
@@ -255,11 +286,33 @@ impl HTMLCanvasElement {
         let size = self.get_size();
         let attrs = Self::get_gl_attributes(cx, options)?;
         let canvas = HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(self));
-        let context = WebGL2RenderingContext::new(&window, &canvas, size, attrs, can_gc)?;
+        let context =
+            WebGL2RenderingContext::new(window.as_global_scope(), &canvas, size, attrs, can_gc)?;
         *self.context_mode.borrow_mut() = Some(RenderingContext::WebGL2(Dom::from_ref(&*context)));
         Some(context)
     }
 
+    fn get_or_init_bitmaprenderer_context(
+        &self,
+        cx: JSContext,
+        options: HandleValue,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<ImageBitmapRenderingContext>> {
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                RenderingContext::BitmapRenderer(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let settings = Self::get_bitmaprenderer_settings(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(DomRoot::from_ref(self));
+        let context =
+            ImageBitmapRenderingContext::new(&self.owner_global(), &canvas, settings.alpha, can_gc);
+        *self.context_mode.borrow_mut() =
+            Some(RenderingContext::BitmapRenderer(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
     #[cfg(not(feature = "webgpu"))]
     fn get_or_init_webgpu_context(&self) -> Option<DomRoot<GPUCanvasContext>> {
         None
@@ -299,7 +352,10 @@ impl HTMLCanvasElement {
     }
 
     #[allow(unsafe_code)]
-    fn get_gl_attributes(cx: JSContext, options: HandleValue) -> Option<GLContextAttributes> {
+    pub(crate) fn get_gl_attributes(
+        cx: JSContext,
+        options: HandleValue,
+    ) -> Option<GLContextAttributes> {
         unsafe {
             match WebGLContextAttributes::new(cx, options) {
                 Ok(ConversionResult::Success(attrs)) => Some(attrs.convert()),
@@ -315,6 +371,26 @@ impl HTMLCanvasElement {
         }
     }
 
+    #[allow(unsafe_code)]
+    pub(crate) fn get_bitmaprenderer_settings(
+        cx: JSContext,
+        options: HandleValue,
+    ) -> Option<ImageBitmapRenderingContextSettings> {
+        unsafe {
+            match ImageBitmapRenderingContextSettings::new(cx, options) {
+                Ok(ConversionResult::Success(settings)) => Some(settings),
+                Ok(ConversionResult::Failure(error)) => {
+                    throw_type_error(*cx, &error);
+                    None
+                },
+                _ => {
+                    debug!("Unexpected error on conversion of ImageBitmapRenderingContextSettings");
+                    None
+                },
+            }
+        }
+    }
+
     pub(crate) fn is_valid(&self) -> bool {
         self.Height() != 0 && self.Width() != 0
     }
@@ -410,6 +486,9 @@ impl HTMLCanvasElementMethods<crate::DomTypeHolder> for HTMLCanvasElement {
             "2d" => self
                 .get_or_init_2d_context(can_gc)
                 .map(RootedRenderingContext::CanvasRenderingContext2D),
+            "bitmaprenderer" => self
+                .get_or_init_bitmaprenderer_context(cx, options, can_gc)
+                .map(RootedRenderingContext::ImageBitmapRenderingContext),
             "webgl" | "experimental-webgl" => self
                 .get_or_init_webgl_context(cx, options, can_gc)
                 .map(RootedRenderingContext::WebGLRenderingContext),
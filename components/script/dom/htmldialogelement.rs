@@ -2,26 +2,37 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
+
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix, local_name, ns};
 use js::rust::HandleObject;
+use stylo_atoms::Atom;
 
+use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::document::{Document, FocusInitiator};
+use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{Node, NodeTraits};
+use crate::dom::node::{Node, NodeTraits, ShadowIncluding, UnbindContext};
+use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
 pub(crate) struct HTMLDialogElement {
     htmlelement: HTMLElement,
     return_value: DomRefCell<DOMString>,
+    /// <https://html.spec.whatwg.org/multipage/#is-modal>
+    is_modal: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#previously-focused-element>
+    previously_focused_element: MutNullableDom<Element>,
 }
 
 impl HTMLDialogElement {
@@ -33,6 +44,8 @@ impl HTMLDialogElement {
         HTMLDialogElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             return_value: DomRefCell::new(DOMString::new()),
+            is_modal: Cell::new(false),
+            previously_focused_element: Default::default(),
         }
     }
 
@@ -53,6 +66,122 @@ impl HTMLDialogElement {
             can_gc,
         )
     }
+
+    /// Stop showing this dialog as a modal dialog, which stops it from blocking its
+    /// document and removes it from the top layer, which the `:modal` rule of the user
+    /// agent stylesheet puts it in.
+    fn stop_being_modal(&self) {
+        if !self.is_modal.replace(false) {
+            return;
+        }
+        self.upcast::<Element>().set_modal_state(false);
+        self.owner_document().remove_modal_dialog(self);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dialog-focusing-steps>
+    fn run_the_dialog_focusing_steps(&self, can_gc: CanGc) {
+        let element = self.upcast::<Element>();
+        let is_focus_candidate = |element: &Element| {
+            element.is_focusable_area() && !element.upcast::<Node>().is_display_none()
+        };
+
+        // Steps 1-3. The control is the dialog itself if it has the autofocus attribute,
+        // otherwise the first of its descendants with the autofocus attribute, or the first
+        // of them that can be focused at all.
+        let control =
+            if element.has_attribute(&local_name!("autofocus")) && is_focus_candidate(element) {
+                Some(DomRoot::from_ref(element))
+            } else {
+                let descendants = || {
+                    self.upcast::<Node>()
+                        .traverse_preorder(ShadowIncluding::No)
+                        .skip(1)
+                        .filter_map(DomRoot::downcast::<Element>)
+                        .filter(|element| is_focus_candidate(element))
+                };
+                descendants()
+                    .find(|element| element.has_attribute(&local_name!("autofocus")))
+                    .or_else(|| descendants().next())
+            };
+
+        // Step 4. Run the focusing steps for control. A modal dialog without anything to
+        // focus takes focus away from the rest of the document, which is inert.
+        let document = self.owner_document();
+        match control {
+            Some(control) => document.request_focus(Some(&control), FocusInitiator::Local, can_gc),
+            None if self.is_modal.get() => {
+                document.request_focus(None, FocusInitiator::Local, can_gc)
+            },
+            None => {},
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#close-the-dialog>
+    fn close_the_dialog(&self, result: Option<DOMString>, can_gc: CanGc) {
+        let element = self.upcast::<Element>();
+        let target = self.upcast::<EventTarget>();
+
+        // Step 1. If subject does not have an open attribute, then return.
+        if !element.has_attribute(&local_name!("open")) {
+            return;
+        }
+
+        // Step 4. Let wasModal be the value of subject's is modal flag.
+        let was_modal = self.is_modal.get();
+
+        // Step 3. Remove subject's open attribute.
+        element.remove_attribute(&ns!(), &local_name!("open"), can_gc);
+
+        // Step 5. Set the is modal flag of subject to false, which removes it from the
+        // top layer.
+        self.stop_being_modal();
+
+        // Step 6. If result is not null, then set the returnValue attribute to result.
+        if let Some(new_value) = result {
+            *self.return_value.borrow_mut() = new_value;
+        }
+
+        // Step 8. If subject's previously focused element is not null, then restore focus to
+        // it if focus is within subject or subject was modal.
+        if let Some(previously_focused_element) = self.previously_focused_element.take() {
+            let document = self.owner_document();
+            let focus_is_within_dialog = document.get_focused_element().is_some_and(|focused| {
+                self.upcast::<Node>()
+                    .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+            });
+            if focus_is_within_dialog || was_modal {
+                document.request_focus(
+                    Some(&previously_focused_element),
+                    FocusInitiator::Local,
+                    can_gc,
+                );
+            }
+        }
+
+        // Step 9. Queue a task to fire an event named close at subject.
+        self.owner_global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(target, atom!("close"));
+    }
+
+    /// Handle a close request, like the user pressing Escape while this dialog is the
+    /// modal dialog that blocks its document: fire a cancelable `cancel` event at the
+    /// dialog and close it unless the event is canceled.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#close-requests>
+    pub(crate) fn request_close(&self, can_gc: CanGc) {
+        if !self.upcast::<Element>().has_attribute(&local_name!("open")) {
+            return;
+        }
+
+        let event = self
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(Atom::from("cancel"), can_gc);
+        if !event.DefaultPrevented() {
+            self.close_the_dialog(None, can_gc);
+        }
+    }
 }
 
 impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
@@ -74,20 +203,27 @@ impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-dialog-show>
-    fn Show(&self, can_gc: CanGc) {
+    fn Show(&self, can_gc: CanGc) -> ErrorResult {
         let element = self.upcast::<Element>();
 
-        // Step 1 TODO: Check is modal flag is false
+        // Step 1. If this has an open attribute and the is modal flag of this is false, then
+        // return.
         if element.has_attribute(&local_name!("open")) {
-            return;
-        }
+            if !self.is_modal.get() {
+                return Ok(());
+            }
 
-        // TODO: Step 2 If this has an open attribute, then throw an "InvalidStateError" DOMException.
+            // Step 2. If this has an open attribute, then throw an "InvalidStateError"
+            // DOMException.
+            return Err(Error::InvalidState);
+        }
 
         // Step 3
         element.set_bool_attribute(&local_name!("open"), true, can_gc);
 
-        // TODO: Step 4 Set this's previously focused element to the focused element.
+        // Step 4. Set this's previously focused element to the focused element.
+        self.previously_focused_element
+            .set(self.owner_document().get_focused_element().as_deref());
 
         // TODO: Step 5 Let hideUntil be the result of running topmost popover ancestor given this, null, and false.
 
@@ -95,33 +231,79 @@ impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
 
         // TODO: Step 7 Run hide all popovers until given hideUntil, false, and true.
 
-        // TODO(Issue #32702): Step 8 Run the dialog focusing steps given this.
+        // Step 8. Run the dialog focusing steps given this.
+        self.run_the_dialog_focusing_steps(can_gc);
+        Ok(())
     }
 
-    // https://html.spec.whatwg.org/multipage/#dom-dialog-close
-    fn Close(&self, return_value: Option<DOMString>, can_gc: CanGc) {
+    /// <https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal>
+    fn ShowModal(&self, can_gc: CanGc) -> ErrorResult {
         let element = self.upcast::<Element>();
-        let target = self.upcast::<EventTarget>();
 
-        // Step 1 & 2
-        if element
-            .remove_attribute(&ns!(), &local_name!("open"), can_gc)
-            .is_none()
-        {
-            return;
+        // Step 1. If this has an open attribute and the is modal flag of this is true, then
+        // return.
+        if element.has_attribute(&local_name!("open")) {
+            if self.is_modal.get() {
+                return Ok(());
+            }
+
+            // Step 2. If this has an open attribute, then throw an "InvalidStateError"
+            // DOMException.
+            return Err(Error::InvalidState);
         }
 
-        // Step 3
-        if let Some(new_value) = return_value {
-            *self.return_value.borrow_mut() = new_value;
+        // Step 3. If this is not connected, then throw an "InvalidStateError" DOMException.
+        if !element.is_connected() {
+            return Err(Error::InvalidState);
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 6. Add an open attribute to this, whose value is the empty string.
+        element.set_bool_attribute(&local_name!("open"), true, can_gc);
 
-        // Step 5
-        self.owner_global()
-            .task_manager()
-            .dom_manipulation_task_source()
-            .queue_simple_event(target, atom!("close"));
+        // Steps 7-9. Set the is modal flag of this to true, let this's node document be
+        // blocked by the modal dialog this, and add this to the top layer.
+        self.is_modal.set(true);
+        element.set_modal_state(true);
+        let document = self.owner_document();
+        document.add_modal_dialog(self);
+
+        // Step 11. Set this's previously focused element to the focused element.
+        self.previously_focused_element
+            .set(document.get_focused_element().as_deref());
+
+        // Step 14. Run the dialog focusing steps given this.
+        self.run_the_dialog_focusing_steps(can_gc);
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-dialog-close>
+    fn Close(&self, return_value: Option<DOMString>, can_gc: CanGc) {
+        // Step 1. If returnValue is not given, then set it to null.
+        // Step 2. Close the dialog this with returnValue.
+        self.close_the_dialog(return_value, can_gc);
+    }
+}
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation, can_gc: CanGc) {
+        self.super_type()
+            .unwrap()
+            .attribute_mutated(attr, mutation, can_gc);
+
+        // A modal dialog whose open attribute is removed directly stops being modal.
+        if attr.local_name() == &local_name!("open") && mutation.is_removal() {
+            self.stop_being_modal();
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-dialog-element:html-element-removing-steps>
+    fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
+        self.super_type().unwrap().unbind_from_tree(context, can_gc);
+
+        self.stop_being_modal();
     }
 }
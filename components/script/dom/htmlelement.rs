@@ -52,6 +52,7 @@ use crate::dom::node::{BindContext, Node, NodeTraits, ShadowIncluding, UnbindCon
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::editing::{ContentEditableState, editing_host};
 use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 
@@ -188,6 +189,11 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_setter!(SetHidden, "hidden");
 
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    make_bool_getter!(Inert, "inert");
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    make_bool_setter!(SetInert, "inert");
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!(NoOnload);
 
@@ -422,7 +428,31 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
         // TODO: Mark the element as locked for focus and run the focusing steps.
         // https://html.spec.whatwg.org/multipage/#focusing-steps
         let document = self.owner_document();
-        document.request_focus(Some(self.upcast()), FocusInitiator::Local, can_gc);
+        let element = self.as_element();
+        let delegates_focus = element
+            .shadow_root()
+            .is_some_and(|shadow_root| shadow_root.DelegatesFocus());
+        let target = if delegates_focus {
+            // A shadow host that delegates focus keeps focus where it is if it's already
+            // within its shadow tree.
+            if document.get_focused_element().is_some_and(|focused| {
+                self.upcast::<Node>()
+                    .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+            }) {
+                return;
+            }
+            match element.focus_delegate() {
+                Some(delegate) => delegate,
+                None => return,
+            }
+        } else {
+            DomRoot::from_ref(element)
+        };
+
+        document.request_focus(Some(&target), FocusInitiator::Local, can_gc);
+        if document.get_focused_element().as_ref() == Some(&target) {
+            target.scroll_into_view_if_needed(can_gc);
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-blur
@@ -575,25 +605,68 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
         );
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn Spellcheck(&self) -> bool {
+        self.as_element().is_spellcheck_enabled()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn SetSpellcheck(&self, value: bool, can_gc: CanGc) {
+        self.as_element().set_string_attribute(
+            &html5ever::local_name!("spellcheck"),
+            match value {
+                true => DOMString::from("true"),
+                false => DOMString::from("false"),
+            },
+            can_gc,
+        );
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn Draggable(&self) -> bool {
+        self.as_element().is_draggable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn SetDraggable(&self, value: bool, can_gc: CanGc) {
+        self.as_element().set_string_attribute(
+            &html5ever::local_name!("draggable"),
+            match value {
+                true => DOMString::from("true"),
+                false => DOMString::from("false"),
+            },
+            can_gc,
+        );
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-contenteditable
     fn ContentEditable(&self) -> DOMString {
-        // TODO: https://github.com/servo/servo/issues/12776
-        self.as_element()
-            .get_attribute(&ns!(), &local_name!("contenteditable"))
-            .map(|attr| DOMString::from(&**attr.value()))
-            .unwrap_or_else(|| DOMString::from("inherit"))
+        DOMString::from(ContentEditableState::for_element(self.as_element()).as_str())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-contenteditable
-    fn SetContentEditable(&self, _: DOMString) {
-        // TODO: https://github.com/servo/servo/issues/12776
-        warn!("The contentEditable attribute is not implemented yet");
+    fn SetContentEditable(&self, value: DOMString, can_gc: CanGc) -> ErrorResult {
+        let value = value.to_ascii_lowercase();
+        match value.as_str() {
+            "inherit" => {
+                self.as_element()
+                    .remove_attribute(&ns!(), &local_name!("contenteditable"), can_gc);
+            },
+            "true" | "false" | "plaintext-only" => {
+                self.as_element().set_string_attribute(
+                    &local_name!("contenteditable"),
+                    DOMString::from(value),
+                    can_gc,
+                );
+            },
+            _ => return Err(Error::Syntax),
+        }
+        Ok(())
     }
 
-    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    // https://html.spec.whatwg.org/multipage/#dom-iscontenteditable
     fn IsContentEditable(&self) -> bool {
-        // TODO: https://github.com/servo/servo/issues/12776
-        false
+        editing_host(self.upcast()).is_some()
     }
     /// <https://html.spec.whatwg.org/multipage#dom-attachinternals>
     fn AttachInternals(&self, can_gc: CanGc) -> Fallible<DomRoot<ElementInternals>> {
@@ -1154,6 +1227,18 @@ impl VirtualMethods for HTMLElement {
                     },
                 }
             },
+            // An inert subtree can't contain the focused area of the document.
+            (&local_name!("inert"), AttributeMutation::Set(_)) => {
+                let document = self.owner_document();
+                if let Some(focused) = document.get_focused_element() {
+                    if self
+                        .upcast::<Node>()
+                        .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+                    {
+                        document.perform_focus_fixup_rule(&focused, can_gc);
+                    }
+                }
+            },
             (&local_name!("nonce"), mutation) => match mutation {
                 AttributeMutation::Set(_) => {
                     let nonce = &**attr.value();
@@ -76,6 +76,7 @@ use crate::dom::node::{
 use crate::dom::nodelist::{NodeList, RadioListMode};
 use crate::dom::radionodelist::RadioNodeList;
 use crate::dom::submitevent::SubmitEvent;
+use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
 use crate::links::{LinkRelations, get_element_target};
@@ -1053,29 +1054,39 @@ impl HTMLFormElement {
     /// Interactively validate the constraints of form elements
     /// <https://html.spec.whatwg.org/multipage/#interactively-validate-the-constraints>
     fn interactive_validation(&self, can_gc: CanGc) -> Result<(), ()> {
+        // The user tried to submit the form, so from now on the controls of the form match
+        // :user-valid or :user-invalid.
+        let controls: Vec<_> = self
+            .controls
+            .borrow()
+            .iter()
+            .map(|control| control.as_rooted())
+            .collect();
+        for control in controls {
+            control.set_user_validity(true, can_gc);
+        }
+
         // Step 1-2
         let unhandled_invalid_controls = match self.static_validation(can_gc) {
             Ok(()) => return Ok(()),
             Err(err) => err,
         };
 
-        // Step 3
-        let mut first = true;
-
+        // Step 3: Report the problems with the constraints of at least one of the elements
+        // given in unhandled invalid controls to the user.
         for elem in unhandled_invalid_controls {
             if let Some(validatable) = elem.as_maybe_validatable() {
-                println!("Validation error: {}", validatable.validation_message());
+                validatable.report_problems(can_gc);
+                break;
             }
-            if first {
-                if let Some(html_elem) = elem.downcast::<HTMLElement>() {
-                    html_elem.Focus(can_gc);
-                    first = false;
+            if let Some(internals) = elem.get_element_internals() {
+                if internals.is_invalid() {
+                    internals.report_problems(can_gc);
+                    break;
                 }
             }
         }
 
-        // If it's form-associated and has a validation anchor, point the
-        //  user there instead of the element itself.
         // Step 4
         Err(())
     }
@@ -1312,6 +1323,12 @@ impl HTMLFormElement {
                 },
                 _ => {},
             }
+
+            // https://html.spec.whatwg.org/multipage/#user-validity
+            // Resetting a control also forgets that the user interacted with it.
+            if let Some(element) = child.downcast::<Element>() {
+                element.set_user_validity(false, can_gc);
+            }
         }
         self.marked_for_reset.set(false);
     }
@@ -1872,36 +1889,42 @@ pub(crate) fn encode_multipart_form_data(
 
         // TODO(eijebong): Everthing related to content-disposition it to redo once typed headers
         // are capable of it.
+        let name = escape_multipart_name(&entry.name);
         match entry.value {
             FormDatumValue::String(ref s) => {
-                let content_disposition = format!("form-data; name=\"{}\"", entry.name);
+                let content_disposition = format!("form-data; name=\"{}\"", name);
                 let mut bytes =
                     format!("Content-Disposition: {}\r\n\r\n{}", content_disposition, s)
                         .into_bytes();
                 result.append(&mut bytes);
             },
             FormDatumValue::File(ref f) => {
+                // Like other browsers, submit files that were selected through a directory
+                // with their path relative to that directory.
+                let filename = if f.relative_path().is_empty() {
+                    f.name()
+                } else {
+                    f.relative_path()
+                };
                 let charset = encoding.name();
                 let extra = if charset.to_lowercase() == "utf-8" {
-                    format!(
-                        "filename=\"{}\"",
-                        String::from_utf8(f.name().as_bytes().into()).unwrap()
-                    )
+                    format!("filename=\"{}\"", escape_multipart_name(filename))
                 } else {
                     format!(
                         "filename*=\"{}\"''{}",
                         charset,
-                        http_percent_encode(f.name().as_bytes())
+                        http_percent_encode(filename.as_bytes())
                     )
                 };
 
-                let content_disposition = format!("form-data; name=\"{}\"; {}", entry.name, extra);
+                let content_disposition = format!("form-data; name=\"{}\"; {}", name, extra);
                 // https://tools.ietf.org/html/rfc7578#section-4.4
+                // Files without a type are sent as application/octet-stream.
                 let content_type: Mime = f
                     .upcast::<Blob>()
                     .Type()
                     .parse()
-                    .unwrap_or(mime::TEXT_PLAIN);
+                    .unwrap_or(mime::APPLICATION_OCTET_STREAM);
                 let mut type_bytes = format!(
                     "Content-Disposition: {}\r\ncontent-type: {}\r\n\r\n",
                     content_disposition, content_type
@@ -1922,6 +1945,16 @@ pub(crate) fn encode_multipart_form_data(
     result
 }
 
+/// Escape a field name or file name for use in a quoted `Content-Disposition` parameter, by
+/// replacing LF with `%0A`, CR with `%0D` and `"` with `%22`.
+///
+/// <https://html.spec.whatwg.org/multipage/#multipart-form-data>
+fn escape_multipart_name(name: &str) -> String {
+    name.replace('\n', "%0A")
+        .replace('\r', "%0D")
+        .replace('"', "%22")
+}
+
 // https://tools.ietf.org/html/rfc7578#section-4.1
 pub(crate) fn generate_boundary() -> String {
     let i1 = random::<u32>();
@@ -243,6 +243,8 @@ impl HTMLIFrameElement {
                     load_data,
                     viewport_details,
                     theme: window.theme(),
+                    user_agent_stylesheet_override: window.user_agent_stylesheet_override(),
+                    user_stylesheets: window.user_stylesheets(),
                 };
 
                 self.pipeline_id.set(Some(new_pipeline_id));
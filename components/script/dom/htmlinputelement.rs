@@ -11,9 +11,11 @@ use std::ptr::NonNull;
 use std::str::FromStr;
 use std::{f64, ptr};
 
+use constellation_traits::BlobImpl;
 use dom_struct::dom_struct;
 use embedder_traits::{
-    EmbedderMsg, FilterPattern, FormControl as EmbedderFormControl, InputMethodType, RgbColor,
+    DateTimePickerType, DateTimePickerValues, EmbedderMsg, FilterPattern,
+    FormControl as EmbedderFormControl, InputMethodType, RgbColor,
 };
 use encoding_rs::Encoding;
 use euclid::{Point2D, Rect, Size2D};
@@ -48,7 +50,9 @@ use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::FileListBinding::FileListMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
+use crate::dom::bindings::codegen::Bindings::UIEventBinding::UIEventMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomGlobal;
@@ -57,6 +61,7 @@ use crate::dom::bindings::str::{DOMString, FromInputValueString, ToInputValueStr
 use crate::dom::clipboardevent::ClipboardEvent;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
@@ -79,6 +84,7 @@ use crate::dom::node::{
 use crate::dom::nodelist::NodeList;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
+use crate::dom::uievent::UIEvent;
 use crate::dom::validation::{Validatable, is_barred_by_datalist_ancestor};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
@@ -89,8 +95,8 @@ use crate::textinput::KeyReaction::{
 };
 use crate::textinput::Lines::Single;
 use crate::textinput::{
-    Direction, SelectionDirection, TextInput, UTF8Bytes, UTF16CodeUnits,
-    handle_text_clipboard_action,
+    Direction, Selection, SelectionDirection, TextInput, UTF8Bytes, UTF16CodeUnits,
+    handle_text_clipboard_action, handle_text_drop_action,
 };
 
 const DEFAULT_SUBMIT_VALUE: &str = "Submit";
@@ -216,6 +222,17 @@ impl InputType {
         self.is_textual() || *self == InputType::Password
     }
 
+    /// The text that is rendered in place of an empty date or time input, so that
+    /// users typing into the field without a picker know which format is expected.
+    fn format_hint(&self) -> &'static str {
+        match *self {
+            InputType::Date => "yyyy-mm-dd",
+            InputType::Time => "--:--",
+            InputType::DatetimeLocal => "yyyy-mm-ddT--:--",
+            _ => "",
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#has-a-periodic-domain
     fn has_periodic_domain(&self) -> bool {
         *self == InputType::Time
@@ -1127,6 +1144,8 @@ pub(crate) trait LayoutHTMLInputElementHelpers<'dom> {
     fn value_for_layout(self) -> Cow<'dom, str>;
     fn size_for_layout(self) -> u32;
     fn selection_for_layout(self) -> Option<Range<usize>>;
+    fn composition_for_layout(self) -> Option<Range<usize>>;
+    fn misspellings_for_layout(self) -> Vec<Range<usize>>;
 }
 
 #[allow(unsafe_code)]
@@ -1159,6 +1178,25 @@ impl<'dom> LayoutDom<'dom, HTMLInputElement> {
                 .sorted_selection_offsets_range()
         }
     }
+
+    fn textinput_composition_range(self) -> Option<Range<UTF8Bytes>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .composition_range()
+        }
+    }
+
+    fn textinput_misspellings(self) -> Vec<Range<UTF8Bytes>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .misspellings()
+                .to_vec()
+        }
+    }
 }
 
 impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElement> {
@@ -1212,6 +1250,16 @@ impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElem
             InputType::Color => {
                 unreachable!("Input type color is explicitly not rendered as text");
             },
+            InputType::Date | InputType::Time | InputType::DatetimeLocal => {
+                let text = self.get_raw_textinput_value();
+                if !text.is_empty() {
+                    text.into()
+                } else if !self.placeholder().is_empty() {
+                    self.placeholder().into()
+                } else {
+                    self.input_type().format_hint().into()
+                }
+            },
             _ => {
                 let text = self.get_raw_textinput_value();
                 if !text.is_empty() {
@@ -1252,6 +1300,24 @@ impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElem
             _ => None,
         }
     }
+
+    fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !self.upcast::<Element>().focus_state() || !self.input_type().is_textual() {
+            return None;
+        }
+        self.textinput_composition_range()
+            .map(UTF8Bytes::unwrap_range)
+    }
+
+    fn misspellings_for_layout(self) -> Vec<Range<usize>> {
+        if !self.input_type().is_textual() {
+            return Vec::new();
+        }
+        self.textinput_misspellings()
+            .into_iter()
+            .map(UTF8Bytes::unwrap_range)
+            .collect()
+    }
 }
 
 impl TextControlElement for HTMLInputElement {
@@ -1636,6 +1702,21 @@ impl HTMLInputElementMethods<crate::DomTypeHolder> for HTMLInputElement {
     // https://html.spec.whatwg.org/multipage/#dom-input-multiple
     make_bool_setter!(SetMultiple, "multiple");
 
+    /// <https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory>
+    fn Webkitdirectory(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("webkitdirectory"))
+    }
+
+    /// <https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory>
+    fn SetWebkitdirectory(&self, value: bool, can_gc: CanGc) {
+        self.upcast::<Element>().set_bool_attribute(
+            &LocalName::from("webkitdirectory"),
+            value,
+            can_gc,
+        );
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-input-pattern
     make_getter!(Pattern, "pattern");
 
@@ -1911,25 +1992,31 @@ impl HTMLInputElement {
                 // Step 5.2-5.7
                 let name = self.Name();
 
-                match self.GetFiles() {
-                    Some(fl) => {
-                        for f in fl.iter_files() {
-                            datums.push(FormDatum {
-                                ty: ty.clone(),
-                                name: name.clone(),
-                                value: FormDatumValue::File(DomRoot::from_ref(f)),
-                            });
-                        }
-                    },
-                    None => {
+                if let Some(fl) = self.GetFiles() {
+                    for f in fl.iter_files() {
                         datums.push(FormDatum {
-                            // XXX(izgzhen): Spec says 'application/octet-stream' as the type,
-                            // but this is _type_ of element rather than content right?
                             ty: ty.clone(),
                             name: name.clone(),
-                            value: FormDatumValue::String(DOMString::from("")),
-                        })
-                    },
+                            value: FormDatumValue::File(DomRoot::from_ref(f)),
+                        });
+                    }
+                }
+
+                // If there are no selected files, then create an entry with name and a new File
+                // object with an empty name, application/octet-stream as type, and an empty body.
+                if datums.is_empty() {
+                    let file = File::new(
+                        &self.global(),
+                        BlobImpl::new_from_bytes(vec![], "application/octet-stream".to_owned()),
+                        DOMString::new(),
+                        None,
+                        CanGc::note(),
+                    );
+                    datums.push(FormDatum {
+                        ty: ty.clone(),
+                        name: name.clone(),
+                        value: FormDatumValue::File(file),
+                    });
                 }
 
                 return datums;
@@ -2024,6 +2111,56 @@ impl HTMLInputElement {
         el.set_placeholder_shown_state(has_placeholder && !has_value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking
+    fn is_spellchecked(&self) -> bool {
+        matches!(self.input_type(), InputType::Text | InputType::Search) &&
+            self.is_mutable() &&
+            self.upcast::<Element>().is_spellcheck_enabled()
+    }
+
+    /// Ask the spellchecker for the misspelled words in the value of this element, so that
+    /// they are marked when it is painted.
+    fn update_misspellings(&self, typing: bool) {
+        let misspellings = if self.is_spellchecked() {
+            let value = self.textinput.borrow().get_content();
+            self.owner_document()
+                .check_spelling(value.into())
+                .into_iter()
+                .map(|range| UTF8Bytes(range.start)..UTF8Bytes(range.end))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.textinput
+            .borrow_mut()
+            .set_misspellings(misspellings, typing);
+        self.upcast::<Node>().dirty(NodeDamage::Other);
+    }
+
+    /// The misspelled word at the caret of this element and the replacements that the
+    /// spellchecker suggests for it, which are offered in its context menu.
+    pub(crate) fn spelling_suggestions(&self) -> Option<(String, Vec<String>)> {
+        let word = self.textinput.borrow().misspelled_word()?;
+        let suggestions = self.owner_document().spelling_suggestions(word.clone());
+        Some((word, suggestions))
+    }
+
+    /// Replace the misspelled word at the caret of this element with `replacement`, which
+    /// the user picked from the suggestions of the spellchecker.
+    pub(crate) fn replace_misspelled_word(&self, replacement: &str, can_gc: CanGc) {
+        let action = self
+            .textinput
+            .borrow_mut()
+            .replace_misspelled_word(replacement);
+        if let DispatchInput = action {
+            self.value_dirty.set(true);
+            self.update_placeholder_shown_state();
+            self.update_misspellings(false);
+            self.upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("input"), can_gc);
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file)
     // Select files by invoking UI or by passed in argument
     fn select_files(&self, opt_test_paths: Option<Vec<DOMString>>, can_gc: CanGc) {
@@ -2038,7 +2175,30 @@ impl HTMLInputElement {
         let filter = filter_from_accept(&self.Accept());
         let target = self.upcast::<EventTarget>();
 
-        if self.Multiple() {
+        if self.Webkitdirectory() {
+            // https://wicg.github.io/entries-api/#html-forms
+            // The user selects a directory, whose files are all selected.
+            let opt_test_path = opt_test_paths
+                .and_then(|paths| paths.first().map(|path| PathBuf::from(path.to_string())));
+
+            let (chan, recv) =
+                profile_traits::ipc::channel(self.global().time_profiler_chan().clone())
+                    .expect("Error initializing channel");
+            let msg =
+                FileManagerThreadMsg::SelectDirectory(webview_id, chan, origin, opt_test_path);
+            resource_threads
+                .send(CoreResourceMsg::ToFileManager(msg))
+                .unwrap();
+
+            match recv.recv().expect("IpcSender side error") {
+                Ok(selected_files) => {
+                    for selected in selected_files {
+                        files.push(File::new_from_selected(window.upcast(), selected, can_gc));
+                    }
+                },
+                Err(err) => error = Some(err),
+            };
+        } else if self.Multiple() {
             let opt_test_paths = opt_test_paths.map(|paths| {
                 paths
                     .iter()
@@ -2058,7 +2218,7 @@ impl HTMLInputElement {
             match recv.recv().expect("IpcSender side error") {
                 Ok(selected_files) => {
                     for selected in selected_files {
-                        files.push(File::new_from_selected(&window, selected, can_gc));
+                        files.push(File::new_from_selected(window.upcast(), selected, can_gc));
                     }
                 },
                 Err(err) => error = Some(err),
@@ -2086,7 +2246,7 @@ impl HTMLInputElement {
 
             match recv.recv().expect("IpcSender side error") {
                 Ok(selected) => {
-                    files.push(File::new_from_selected(&window, selected, can_gc));
+                    files.push(File::new_from_selected(window.upcast(), selected, can_gc));
                 },
                 Err(err) => error = Some(err),
             };
@@ -2098,11 +2258,97 @@ impl HTMLInputElement {
             let filelist = FileList::new(&window, files, can_gc);
             self.filelist.set(Some(&filelist));
 
+            self.upcast::<Element>().set_user_validity(true, can_gc);
             target.fire_bubbling_event(atom!("input"), can_gc);
             target.fire_bubbling_event(atom!("change"), can_gc);
         }
     }
 
+    /// Whether dropping data with plain text or with files onto this element does something
+    /// by default, so that a drag over it can be accepted without help from script.
+    pub(crate) fn accepts_drop(&self, has_text: bool, has_files: bool) -> bool {
+        if !self.is_mutable() {
+            return false;
+        }
+        match self.input_type() {
+            InputType::File => has_files,
+            input_type => has_text && input_type.is_textual_or_password(),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#drop-processing>
+    fn handle_drop(&self, event: &DragEvent, can_gc: CanGc) {
+        if !self.is_mutable() {
+            return;
+        }
+        if self.input_type() == InputType::File {
+            self.select_dropped_files(can_gc);
+            return;
+        }
+        if !self.input_type().is_textual_or_password() {
+            return;
+        }
+
+        // Insert the text where it was dropped.
+        let index = event
+            .upcast::<MouseEvent>()
+            .point_in_target()
+            .and_then(|point_in_target| {
+                self.owner_window().text_index_query(
+                    self.upcast::<Node>(),
+                    point_in_target.to_untyped(),
+                    can_gc,
+                )
+            });
+        if handle_text_drop_action(&self.textinput, event, index) {
+            self.value_dirty.set(true);
+            self.upcast::<Node>().dirty(NodeDamage::Other);
+            self.upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("input"), can_gc);
+        }
+    }
+
+    /// Select the files that the user dropped onto this element from the operating system.
+    pub(crate) fn select_dropped_files(&self, can_gc: CanGc) {
+        if self.input_type() != InputType::File || !self.is_mutable() {
+            return;
+        }
+
+        let window = self.owner_window();
+        let origin = get_blob_origin(&window.get_url());
+        let (chan, recv) = profile_traits::ipc::channel(self.global().time_profiler_chan().clone())
+            .expect("Error initializing channel");
+        let msg = FileManagerThreadMsg::SelectDroppedFiles(window.webview_id(), chan, origin);
+        window
+            .as_global_scope()
+            .resource_threads()
+            .send(CoreResourceMsg::ToFileManager(msg))
+            .unwrap();
+
+        let selected_files = match recv.recv().expect("IpcSender side error") {
+            Ok(selected_files) => selected_files,
+            Err(err) => {
+                debug!("Input file drop error: {:?}", err);
+                return;
+            },
+        };
+
+        // Only the first file is kept if the element does not accept multiple files.
+        let limit = if self.Multiple() { usize::MAX } else { 1 };
+        let files = selected_files
+            .into_iter()
+            .take(limit)
+            .map(|selected| File::new_from_selected(window.upcast(), selected, can_gc))
+            .collect();
+        let filelist = FileList::new(&window, files, can_gc);
+        self.filelist.set(Some(&filelist));
+        self.upcast::<Element>().set_user_validity(true, can_gc);
+
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"), can_gc);
+        target.fire_bubbling_event(atom!("change"), can_gc);
+    }
+
     // https://html.spec.whatwg.org/multipage/#value-sanitization-algorithm
     fn sanitize_value(&self, value: &mut DOMString) {
         // if sanitization_flag is false, we are setting content attributes
@@ -2476,40 +2722,92 @@ impl HTMLInputElement {
 
         // Step 6. Otherwise, the user agent should show the relevant user interface for selecting a value for element,
         // in the way it normally would when the user interacts with the control.
-        if self.input_type() == InputType::Color {
-            let (ipc_sender, ipc_receiver) =
-                ipc::channel::<Option<RgbColor>>().expect("Failed to create IPC channel!");
-            let document = self.owner_document();
-            let rect = self.upcast::<Node>().bounding_content_box_or_zero(can_gc);
-            let rect = Rect::new(
-                Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
-                Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
-            );
-            let current_value = self.Value();
-            let current_color = RgbColor {
-                red: u8::from_str_radix(&current_value[1..3], 16).unwrap(),
-                green: u8::from_str_radix(&current_value[3..5], 16).unwrap(),
-                blue: u8::from_str_radix(&current_value[5..7], 16).unwrap(),
-            };
-            document.send_to_embedder(EmbedderMsg::ShowFormControl(
-                document.webview_id(),
-                DeviceIntRect::from_untyped(&rect.to_box2d()),
-                EmbedderFormControl::ColorPicker(current_color, ipc_sender),
-            ));
-
-            let Ok(response) = ipc_receiver.recv() else {
-                log::error!("Failed to receive response");
-                return;
-            };
+        let date_time_picker_type = match self.input_type() {
+            InputType::Color => None,
+            InputType::Date => Some(DateTimePickerType::Date),
+            InputType::Time => Some(DateTimePickerType::Time),
+            InputType::DatetimeLocal => Some(DateTimePickerType::DatetimeLocal),
+            _ => return,
+        };
 
-            if let Some(selected_color) = response {
-                let formatted_color = format!(
-                    "#{:0>2x}{:0>2x}{:0>2x}",
-                    selected_color.red, selected_color.green, selected_color.blue
-                );
-                let _ = self.SetValue(formatted_color.into(), can_gc);
-            }
+        let document = self.owner_document();
+        let rect = self.upcast::<Node>().bounding_content_box_or_zero(can_gc);
+        let rect = Rect::new(
+            Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+            Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+        );
+        let position = DeviceIntRect::from_untyped(&rect.to_box2d());
+        let current_value = self.Value();
+
+        let new_value = match date_time_picker_type {
+            None => {
+                let (ipc_sender, ipc_receiver) =
+                    ipc::channel::<Option<RgbColor>>().expect("Failed to create IPC channel!");
+                let current_color = RgbColor {
+                    red: u8::from_str_radix(&current_value[1..3], 16).unwrap(),
+                    green: u8::from_str_radix(&current_value[3..5], 16).unwrap(),
+                    blue: u8::from_str_radix(&current_value[5..7], 16).unwrap(),
+                };
+                document.send_to_embedder(EmbedderMsg::ShowFormControl(
+                    document.webview_id(),
+                    position,
+                    EmbedderFormControl::ColorPicker(current_color, ipc_sender),
+                ));
+
+                let Ok(response) = ipc_receiver.recv() else {
+                    log::error!("Failed to receive response");
+                    return;
+                };
+
+                response.map(|selected_color| {
+                    DOMString::from(format!(
+                        "#{:0>2x}{:0>2x}{:0>2x}",
+                        selected_color.red, selected_color.green, selected_color.blue
+                    ))
+                })
+            },
+            Some(picker_type) => {
+                let (ipc_sender, ipc_receiver) =
+                    ipc::channel::<Option<String>>().expect("Failed to create IPC channel!");
+                let to_string = |value: f64| self.convert_number_to_string(value).map(String::from);
+                let values = DateTimePickerValues {
+                    value: current_value.to_string(),
+                    min: self.minimum().and_then(to_string),
+                    max: self.maximum().and_then(to_string),
+                };
+                document.send_to_embedder(EmbedderMsg::ShowFormControl(
+                    document.webview_id(),
+                    position,
+                    EmbedderFormControl::DateTimePicker(picker_type, values, ipc_sender),
+                ));
+
+                let Ok(response) = ipc_receiver.recv() else {
+                    log::error!("Failed to receive response");
+                    return;
+                };
+
+                // Ignore values that the embedder did not serialize correctly, rather than
+                // letting the value sanitization algorithm clear the element.
+                response.map(DOMString::from).filter(|value| {
+                    value.is_empty() || self.convert_string_to_number(value).is_some()
+                })
+            },
+        };
+
+        let Some(new_value) = new_value else {
+            return;
+        };
+        if new_value == current_value {
+            return;
         }
+        let _ = self.SetValue(new_value, can_gc);
+        self.upcast::<Element>().set_user_validity(true, can_gc);
+
+        // https://html.spec.whatwg.org/multipage/#common-input-element-events
+        // The user committed a new value through the picker, so fire both events at once.
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"), can_gc);
+        target.fire_bubbling_event(atom!("change"), can_gc);
     }
 }
 
@@ -2820,14 +3118,27 @@ impl VirtualMethods for HTMLInputElement {
                             can_gc,
                         );
                         // Position the caret at the click position or at the end of the current
-                        // value.
+                        // value. Shift+click extends the selection to that position instead.
                         let edit_point_index = match index {
                             Some(i) => i,
                             None => self.textinput.borrow().char_count(),
                         };
-                        self.textinput
-                            .borrow_mut()
-                            .set_edit_point_index(edit_point_index);
+                        let select = if mouse_event.ShiftKey() {
+                            Selection::Selected
+                        } else {
+                            Selection::NotSelected
+                        };
+                        let mut textinput = self.textinput.borrow_mut();
+                        textinput.move_edit_point_to_index(edit_point_index, select);
+
+                        // A double click selects the clicked word, and a triple click
+                        // selects the whole value.
+                        match mouse_event.upcast::<UIEvent>().Detail() {
+                            2 => textinput.select_word(),
+                            3 => textinput.select_all(),
+                            _ => {},
+                        }
+                        drop(textinput);
                         // trigger redraw
                         self.upcast::<Node>().dirty(NodeDamage::Other);
                         event.PreventDefault();
@@ -2860,7 +3171,7 @@ impl VirtualMethods for HTMLInputElement {
                         }
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
-                        self.upcast::<Node>().dirty(NodeDamage::Other);
+                        self.update_misspellings(true);
                         event.mark_as_handled();
                     },
                     RedrawSelection => {
@@ -2870,6 +3181,19 @@ impl VirtualMethods for HTMLInputElement {
                     Nothing => (),
                 }
             }
+        } else if event.type_() == atom!("focus") && self.input_type().is_textual_or_password() {
+            self.update_misspellings(false);
+        } else if &*event.type_() == "blur" &&
+            event.IsTrusted() &&
+            self.input_type().is_textual_or_password()
+        {
+            // https://html.spec.whatwg.org/multipage/#user-validity
+            // The user is done editing the value once the control loses focus.
+            if self.value_dirty.get() && !self.textinput.borrow().was_last_change_by_set_content() {
+                self.upcast::<Element>().set_user_validity(true, can_gc);
+            }
+            // The word that was being typed is finished now, so it can be marked as well.
+            self.update_misspellings(false);
         } else if event.type_() == atom!("keypress") &&
             !event.DefaultPrevented() &&
             self.input_type().is_textual_or_password()
@@ -2883,25 +3207,46 @@ impl VirtualMethods for HTMLInputElement {
             self.input_type().is_textual_or_password()
         {
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::Other);
+                let action = if event.type_() == atom!("compositionstart") {
+                    self.textinput.borrow_mut().handle_compositionstart()
                 } else if event.type_() == atom!("compositionupdate") {
-                    let _ = self
-                        .textinput
+                    self.textinput
                         .borrow_mut()
-                        .handle_compositionupdate(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::Other);
+                        .handle_compositionupdate(compositionevent)
+                } else {
+                    self.textinput
+                        .borrow_mut()
+                        .handle_compositionend(compositionevent)
+                };
+                if let DispatchInput = action {
+                    if event.IsTrusted() {
+                        self.owner_global()
+                            .task_manager()
+                            .user_interaction_task_source()
+                            .queue_event(
+                                self.upcast(),
+                                atom!("input"),
+                                EventBubbles::Bubbles,
+                                EventCancelable::NotCancelable,
+                            );
+                    }
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
+                    self.update_misspellings(true);
                 }
+                self.upcast::<Node>().dirty(NodeDamage::Other);
+                self.owner_document()
+                    .update_ime_cursor_area(self.upcast(), can_gc);
                 event.mark_as_handled();
             }
         } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
             if !event.DefaultPrevented() {
                 handle_text_clipboard_action(self, &self.textinput, clipboard_event, can_gc);
             }
+        } else if let Some(drag_event) = event.downcast::<DragEvent>() {
+            if &*event.type_() == "drop" && !event.DefaultPrevented() {
+                self.handle_drop(drag_event, can_gc);
+            }
         }
 
         self.value_changed(can_gc);
@@ -3037,6 +3382,9 @@ impl Activatable for HTMLInputElement {
             // https://html.spec.whatwg.org/multipage/#radio-button-state-(type=radio):input-activation-behavior
             // https://html.spec.whatwg.org/multipage/#color-state-(type=color):input-activation-behavior
             InputType::Checkbox | InputType::Radio | InputType::Color => true,
+            // Activating a date or time input shows its picker, while the text field itself
+            // remains editable as a fallback.
+            InputType::Date | InputType::Time | InputType::DatetimeLocal => self.is_mutable(),
             _ => false,
         }
     }
@@ -3191,6 +3539,9 @@ impl Activatable for HTMLInputElement {
                     return;
                 }
 
+                // https://html.spec.whatwg.org/multipage/#user-validity
+                self.upcast::<Element>().set_user_validity(true, can_gc);
+
                 let target = self.upcast::<EventTarget>();
 
                 // Step 2: Fire an event named input at the element with the bubbles and composed
@@ -3204,7 +3555,7 @@ impl Activatable for HTMLInputElement {
             // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file):input-activation-behavior
             InputType::File => self.select_files(None, can_gc),
             // https://html.spec.whatwg.org/multipage/#color-state-(type=color):input-activation-behavior
-            InputType::Color => {
+            InputType::Color | InputType::Date | InputType::Time | InputType::DatetimeLocal => {
                 self.show_the_picker_if_applicable(can_gc);
             },
             _ => (),
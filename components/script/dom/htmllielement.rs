@@ -3,16 +3,20 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use html5ever::{LocalName, Prefix, local_name};
+use html5ever::{LocalName, Prefix, local_name, ns};
 use js::rust::HandleObject;
-use style::attr::AttrValue;
+use style::attr::{AttrValue, parse_integer};
 
+use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLLIElementBinding::HTMLLIElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
+use crate::dom::element::Element;
 use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlolistelement::HTMLOListElement;
 use crate::dom::node::Node;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
@@ -48,6 +52,43 @@ impl HTMLLIElement {
             can_gc,
         )
     }
+
+    /// The number that the marker of this list item shows, if it is an item of an ordered
+    /// list. The `start` and `reversed` attributes of the list and the `value` attributes of
+    /// its items are taken into account.
+    /// <https://html.spec.whatwg.org/multipage/#ordinal-value>
+    pub(crate) fn ordinal_value(&self) -> Option<i32> {
+        let owner = self.upcast::<Node>().GetParentElement()?;
+        owner.downcast::<HTMLOListElement>()?;
+
+        let integer_attribute = |element: &Element, name: &LocalName| {
+            element
+                .get_attribute(&ns!(), name)
+                .and_then(|attribute| parse_integer(attribute.Value().chars()).ok())
+        };
+        let items: Vec<_> = owner
+            .upcast::<Node>()
+            .children()
+            .filter_map(DomRoot::downcast::<HTMLLIElement>)
+            .collect();
+        let reversed = owner.has_attribute(&local_name!("reversed"));
+        let mut numbering = match integer_attribute(&owner, &local_name!("start")) {
+            Some(start) => start,
+            None if reversed => items.len() as i32,
+            None => 1,
+        };
+        let increment = if reversed { -1 } else { 1 };
+        for item in items {
+            if let Some(value) = integer_attribute(item.upcast(), &local_name!("value")) {
+                numbering = value;
+            }
+            if &*item == self {
+                return Some(numbering);
+            }
+            numbering += increment;
+        }
+        None
+    }
 }
 
 impl HTMLLIElementMethods<crate::DomTypeHolder> for HTMLLIElement {
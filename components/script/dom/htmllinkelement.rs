@@ -21,7 +21,7 @@ use net_traits::request::{
 };
 use net_traits::{
     FetchMetadata, FetchResponseListener, NetworkError, ReferrerPolicy, ResourceFetchTiming,
-    ResourceTimingType,
+    ResourceTimingType, SpeculativeConnectionKind,
 };
 use servo_arc::Arc;
 use servo_url::{ImmutableOrigin, ServoUrl};
@@ -280,6 +280,11 @@ impl VirtualMethods for HTMLLinkElement {
                     self.fetch_and_process_prefetch_link(&attr.value());
                 }
 
+                // https://html.spec.whatwg.org/multipage/#link-type-dns-prefetch
+                // https://html.spec.whatwg.org/multipage/#link-type-preconnect
+                // When the href attribute of the link element is changed.
+                self.handle_speculative_connection_url(&attr.value());
+
                 // https://html.spec.whatwg.org/multipage/#link-type-preload
                 // When the href attribute of the link element of an external resource link
                 // that is already browsing-context connected is changed.
@@ -401,6 +406,8 @@ impl VirtualMethods for HTMLLinkElement {
                     self.fetch_and_process_prefetch_link(&href);
                 }
 
+                self.handle_speculative_connection_url(&href);
+
                 if relations.contains(LinkRelations::PRELOAD) {
                     self.handle_preload_url();
                 }
@@ -516,6 +523,38 @@ impl HTMLLinkElement {
         document.fetch_background(request, fetch_context);
     }
 
+    /// The processing steps for [`rel="dns-prefetch"`](https://html.spec.whatwg.org/multipage/#link-type-dns-prefetch)
+    /// and [`rel="preconnect"`](https://html.spec.whatwg.org/multipage/#link-type-preconnect)
+    fn handle_speculative_connection_url(&self, href: &str) {
+        let relations = self.relations.get();
+        let kind = if relations.contains(LinkRelations::PRECONNECT) {
+            SpeculativeConnectionKind::Preconnect
+        } else if relations.contains(LinkRelations::DNS_PREFETCH) {
+            SpeculativeConnectionKind::DnsPrefetch
+        } else {
+            return;
+        };
+
+        // Step 1. If el's href attribute's value is the empty string, then return.
+        if href.is_empty() {
+            return;
+        }
+
+        // Step 2. Let url be the result of encoding-parsing a URL given el's href attribute's
+        // value, relative to el's node document.
+        // Step 3. If url is failure, then return.
+        let document = self.owner_document();
+        let Ok(url) = document.base_url().join(href) else {
+            return;
+        };
+
+        // Step 4. Let origin be url's origin.
+        // Step 5. If origin is an opaque origin, then return.
+        // Step 6. The user agent should resolve the host of origin, or obtain a connection
+        // to it.
+        document.speculatively_connect(&url, kind);
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#concept-link-obtain>
     fn handle_stylesheet_url(&self, href: &str) {
         let document = self.owner_document();
@@ -594,6 +633,7 @@ impl HTMLLinkElement {
                 if window.is_top_level() {
                     let msg = EmbedderMsg::NewFavicon(document.webview_id(), url.clone());
                     window.send_to_embedder(msg);
+                    document.load_favicon(url);
                 }
             },
             Err(e) => debug!("Parsing url {} failed: {}", href, e),
@@ -948,12 +988,13 @@ impl LinkProcessingOptions {
 }
 
 /// <https://html.spec.whatwg.org/multipage/#translate-a-preload-destination>
-fn translate_a_preload_destination(potential_destination: &str) -> Destination {
+pub(crate) fn translate_a_preload_destination(potential_destination: &str) -> Destination {
     match potential_destination {
         "fetch" => Destination::None,
         "font" => Destination::Font,
         "image" => Destination::Image,
         "script" => Destination::Script,
+        "style" => Destination::Style,
         "track" => Destination::Track,
         _ => Destination::None,
     }
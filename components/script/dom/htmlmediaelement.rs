@@ -9,6 +9,7 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{f64, mem};
 
+use base::id::WebViewId;
 use compositing_traits::{CrossProcessCompositorApi, ImageUpdate, SerializableImageData};
 use dom_struct::dom_struct;
 use embedder_traits::{MediaPositionState, MediaSessionEvent, MediaSessionPlaybackState};
@@ -22,6 +23,7 @@ use ipc_channel::router::ROUTER;
 use js::jsapi::JSAutoRealm;
 use layout_api::MediaFrame;
 use media::{GLPlayerMsg, GLPlayerMsgForward, WindowGLContext};
+use net_traits::blob_url_store::parse_blob_url;
 use net_traits::request::{Destination, RequestId};
 use net_traits::{
     FetchMetadata, FetchResponseListener, FilteredMetadata, Metadata, NetworkError,
@@ -60,7 +62,7 @@ use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, T
 use crate::dom::bindings::codegen::Bindings::URLBinding::URLMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
 use crate::dom::bindings::codegen::UnionTypes::{
-    MediaStreamOrBlob, VideoTrackOrAudioTrackOrTextTrack,
+    BlobOrMediaSource, MediaStreamOrMediaSourceOrBlob, VideoTrackOrAudioTrackOrTextTrack,
 };
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
@@ -86,6 +88,7 @@ use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::mediaerror::MediaError;
 use crate::dom::mediafragmentparser::MediaFragmentParser;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::node::{Node, NodeDamage, NodeTraits, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
@@ -168,6 +171,8 @@ pub(crate) struct MediaFrameRenderer {
     current_frame_holder: Option<FrameHolder>,
     /// <https://html.spec.whatwg.org/multipage/#poster-frame>
     poster_frame: Option<MediaFrame>,
+    /// The `WebView` whose picture-in-picture window shows the frames of this video.
+    picture_in_picture: Option<WebViewId>,
 }
 
 impl MediaFrameRenderer {
@@ -180,6 +185,7 @@ impl MediaFrameRenderer {
             very_old_frame: None,
             current_frame_holder: None,
             poster_frame: None,
+            picture_in_picture: None,
         }
     }
 
@@ -192,6 +198,50 @@ impl MediaFrameRenderer {
             })
         });
     }
+
+    /// Start or stop showing the frames of the video in the picture-in-picture window of
+    /// the given `WebView`.
+    fn set_picture_in_picture(&mut self, webview_id: Option<WebViewId>) {
+        let Some(webview_id) = webview_id else {
+            if let Some(webview_id) = self.picture_in_picture.take() {
+                self.compositor_api
+                    .set_picture_in_picture_image(webview_id, None);
+            }
+            return;
+        };
+
+        self.picture_in_picture = Some(webview_id);
+        let Some(current_frame) = self.current_frame else {
+            return;
+        };
+        self.compositor_api
+            .set_picture_in_picture_image(webview_id, Some(current_frame.image_key));
+
+        // Send the current frame again, so that the window does not stay empty until the
+        // next one, which never comes for a paused video.
+        let Some(frame) = self
+            .current_frame_holder
+            .as_ref()
+            .map(|holder| holder.get_frame())
+        else {
+            return;
+        };
+        if frame.is_gl_texture() {
+            return;
+        }
+        let descriptor = ImageDescriptor::new(
+            frame.get_width(),
+            frame.get_height(),
+            ImageFormat::BGRA8,
+            ImageDescriptorFlags::empty(),
+        );
+        self.compositor_api
+            .update_images(smallvec::smallvec![ImageUpdate::UpdateImage(
+                current_frame.image_key,
+                descriptor,
+                SerializableImageData::Raw(IpcSharedMemory::from_bytes(&frame.get_data())),
+            )]);
+    }
 }
 
 impl VideoFrameRenderer for MediaFrameRenderer {
@@ -263,6 +313,10 @@ impl VideoFrameRenderer for MediaFrameRenderer {
                     .get_or_insert_with(|| FrameHolder::new(frame.clone()))
                     .set(frame);
 
+                if let Some(webview_id) = self.picture_in_picture {
+                    self.compositor_api
+                        .set_picture_in_picture_image(webview_id, Some(new_image_key));
+                }
                 updates.push(ImageUpdate::AddImage(new_image_key, descriptor, image_data));
             },
             None => {
@@ -295,6 +349,10 @@ impl VideoFrameRenderer for MediaFrameRenderer {
 
                 self.current_frame_holder = Some(FrameHolder::new(frame));
 
+                if let Some(webview_id) = self.picture_in_picture {
+                    self.compositor_api
+                        .set_picture_in_picture_image(webview_id, Some(image_key));
+                }
                 updates.push(ImageUpdate::AddImage(image_key, descriptor, image_data));
             },
         }
@@ -306,17 +364,21 @@ impl VideoFrameRenderer for MediaFrameRenderer {
 #[derive(JSTraceable, MallocSizeOf)]
 enum SrcObject {
     MediaStream(Dom<MediaStream>),
+    MediaSource(Dom<MediaSource>),
     Blob(Dom<Blob>),
 }
 
-impl From<MediaStreamOrBlob> for SrcObject {
+impl From<MediaStreamOrMediaSourceOrBlob> for SrcObject {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
-    fn from(src_object: MediaStreamOrBlob) -> SrcObject {
+    fn from(src_object: MediaStreamOrMediaSourceOrBlob) -> SrcObject {
         match src_object {
-            MediaStreamOrBlob::Blob(blob) => SrcObject::Blob(Dom::from_ref(&*blob)),
-            MediaStreamOrBlob::MediaStream(stream) => {
+            MediaStreamOrMediaSourceOrBlob::Blob(blob) => SrcObject::Blob(Dom::from_ref(&*blob)),
+            MediaStreamOrMediaSourceOrBlob::MediaStream(stream) => {
                 SrcObject::MediaStream(Dom::from_ref(&*stream))
             },
+            MediaStreamOrMediaSourceOrBlob::MediaSource(media_source) => {
+                SrcObject::MediaSource(Dom::from_ref(&*media_source))
+            },
         }
     }
 }
@@ -435,6 +497,9 @@ pub(crate) struct HTMLMediaElement {
     /// the access to the "privileged" document.servoGetMediaControls(id) API by
     /// keeping a whitelist of media controls identifiers.
     media_controls_id: DomRefCell<Option<String>>,
+    /// The `MediaSource` attached as the media provider object, if any.
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    media_source: MutNullableDom<MediaSource>,
     droppable: DroppableHtmlMediaElement,
 }
 
@@ -504,6 +569,7 @@ impl HTMLMediaElement {
             next_timeupdate_event: Cell::new(Instant::now() + Duration::from_millis(250)),
             current_fetch_context: DomRefCell::new(None),
             media_controls_id: DomRefCell::new(None),
+            media_source: Default::default(),
             droppable: DroppableHtmlMediaElement::new(
                 Cell::new(0),
                 document.window().get_player_context(),
@@ -962,6 +1028,17 @@ impl HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#concept-media-load-resource
     fn resource_fetch_algorithm(&self, resource: Resource) {
+        // https://w3c.github.io/media-source/#dom-url-createobjecturl
+        // A media source object URL resolves to its MediaSource, which is attached rather
+        // than fetched.
+        let resource = match resource {
+            Resource::Url(url) => match self.media_source_for_url(&url) {
+                Some(media_source) => Resource::MediaSource(media_source),
+                None => Resource::Url(url),
+            },
+            resource => resource,
+        };
+
         if let Err(e) = self.setup_media_player(&resource) {
             eprintln!("Setup media player error {:?}", e);
             self.queue_dedicated_media_source_failure_steps();
@@ -1014,7 +1091,10 @@ impl HTMLMediaElement {
                 if let Some(ref src_object) = *self.src_object.borrow() {
                     match src_object {
                         SrcObject::Blob(blob) => {
-                            let blob_url = URL::CreateObjectURL(&self.global(), blob);
+                            let blob_url = URL::CreateObjectURL(
+                                &self.global(),
+                                BlobOrMediaSource::Blob(DomRoot::from_ref(blob)),
+                            );
                             *self.blob_url.borrow_mut() =
                                 Some(ServoUrl::parse(&blob_url).expect("infallible"));
                             self.fetch_request(None, None);
@@ -1036,12 +1116,97 @@ impl HTMLMediaElement {
                                 }
                             }
                         },
+                        SrcObject::MediaSource(media_source) => {
+                            self.attach_media_source(media_source);
+                        },
                     }
                 }
             },
+            Resource::MediaSource(media_source) => self.attach_media_source(&media_source),
+        }
+    }
+
+    /// Returns the `MediaSource` that a media source object URL was created for.
+    fn media_source_for_url(&self, url: &ServoUrl) -> Option<DomRoot<MediaSource>> {
+        if url.scheme() != "blob" {
+            return None;
+        }
+        let (id, _) = parse_blob_url(url).ok()?;
+        self.owner_window().media_source_for_url(&id)
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    fn attach_media_source(&self, media_source: &MediaSource) {
+        // If readyState is NOT set to "closed", run the "dedicated media source failure
+        // steps" and abort.
+        if !media_source.attach(self) {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+
+        // The media player pulls the data appended to the source buffers from the media
+        // source, as it asks for it.
+        self.media_source.set(Some(media_source));
+    }
+
+    /// Pushes data appended to the source buffers of the attached media source to the
+    /// media player. The data is dropped if there is no player.
+    pub(crate) fn push_media_source_data(&self, data: Vec<u8>) -> Result<(), PlayerError> {
+        match *self.player.borrow() {
+            Some(ref player) => player.lock().unwrap().push_data(data),
+            None => Ok(()),
+        }
+    }
+
+    /// Signals the media player that the attached media source has all of its data.
+    pub(crate) fn media_source_end_of_stream(&self) {
+        if let Some(ref player) = *self.player.borrow() {
+            if let Err(e) = player.lock().unwrap().end_of_stream() {
+                warn!("Could not signal EOS to player {:?}", e);
+            }
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#durationChange>, run by the duration
+    /// change algorithm of the attached media source.
+    pub(crate) fn media_source_duration_changed(&self, duration: f64) {
+        let previous_duration = self.duration.replace(duration);
+        if previous_duration != duration {
+            self.owner_global()
+                .task_manager()
+                .media_element_task_source()
+                .queue_simple_event(self.upcast(), atom!("durationchange"));
         }
     }
 
+    /// Runs the steps of the media data processing steps list for a network or decode
+    /// error, as the end of stream algorithm of the attached media source does.
+    ///
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub(crate) fn media_source_error(&self, code: u16, can_gc: CanGc) {
+        // If the HTMLMediaElement.readyState attribute equals HAVE_NOTHING, run the
+        // "If the media data cannot be fetched at all..." steps, or the "If the media data
+        // can be fetched but is found by inspection to be in an unsupported format..."
+        // steps, which are both the dedicated media source failure steps.
+        if self.ready_state.get() == ReadyState::HaveNothing {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+
+        if self.in_error_state() {
+            return;
+        }
+
+        // Otherwise run the "If the connection is interrupted after some media data has
+        // been received..." steps, or the "If the media data is corrupted" steps.
+        self.error
+            .set(Some(&*MediaError::new(&self.owner_window(), code, can_gc)));
+        self.network_state.set(NetworkState::Idle);
+        self.delay_load_event(false, can_gc);
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("error"), can_gc);
+    }
+
     /// Queues a task to run the [dedicated media source failure steps][steps].
     ///
     /// [steps]: https://html.spec.whatwg.org/multipage/#dedicated-media-source-failure-steps
@@ -1168,7 +1333,9 @@ impl HTMLMediaElement {
             }
 
             // Step 6.3.
-            // FIXME(nox): Detach MediaSource media provider object.
+            if let Some(media_source) = self.media_source.take() {
+                media_source.detach();
+            }
 
             // Step 6.4.
             self.AudioTracks().clear();
@@ -1414,13 +1581,16 @@ impl HTMLMediaElement {
             Resource::Object => {
                 if let Some(ref src_object) = *self.src_object.borrow() {
                     match src_object {
-                        SrcObject::MediaStream(_) => StreamType::Stream,
+                        // TODO: Seeking within media sources is not supported yet, since
+                        // their data is pushed by the page rather than fetched.
+                        SrcObject::MediaStream(_) | SrcObject::MediaSource(_) => StreamType::Stream,
                         _ => StreamType::Seekable,
                     }
                 } else {
                     return Err(());
                 }
             },
+            Resource::MediaSource(_) => StreamType::Stream,
             _ => StreamType::Seekable,
         };
 
@@ -1789,17 +1959,20 @@ impl HTMLMediaElement {
         self.playback_position.set(0.);
 
         // Step 4.
-        let previous_duration = self.duration.get();
-        if let Some(duration) = metadata.duration {
-            self.duration.set(duration.as_secs() as f64);
-        } else {
-            self.duration.set(f64::INFINITY);
-        }
-        if previous_duration != self.duration.get() {
-            self.owner_global()
-                .task_manager()
-                .media_element_task_source()
-                .queue_simple_event(self.upcast(), atom!("durationchange"));
+        // The duration of a media source is set by its duration change algorithm.
+        if self.media_source.get().is_none() {
+            let previous_duration = self.duration.get();
+            if let Some(duration) = metadata.duration {
+                self.duration.set(duration.as_secs() as f64);
+            } else {
+                self.duration.set(f64::INFINITY);
+            }
+            if previous_duration != self.duration.get() {
+                self.owner_global()
+                    .task_manager()
+                    .media_element_task_source()
+                    .queue_simple_event(self.upcast(), atom!("durationchange"));
+            }
         }
 
         // Step 5.
@@ -1864,6 +2037,11 @@ impl HTMLMediaElement {
     }
 
     fn playback_need_data(&self) {
+        if let Some(media_source) = self.media_source.get() {
+            media_source.player_needs_data();
+            return;
+        }
+
         // The player needs more data.
         // If we already have a valid fetch request, we do nothing.
         // Otherwise, if we have no request and the previous request was
@@ -1903,6 +2081,11 @@ impl HTMLMediaElement {
     fn playback_enough_data(&self) {
         self.change_ready_state(ReadyState::HaveEnoughData);
 
+        if let Some(media_source) = self.media_source.get() {
+            media_source.player_has_enough_data();
+            return;
+        }
+
         // The player has enough data and it is asking us to stop pushing
         // bytes, so we cancel the ongoing fetch request iff we are able
         // to restart it from where we left. Otherwise, we continue the
@@ -2087,6 +2270,15 @@ impl HTMLMediaElement {
             .map(|holder| holder.get_frame())
     }
 
+    /// Start or stop showing the frames of this video in the picture-in-picture window of
+    /// the given `WebView`.
+    pub(crate) fn set_picture_in_picture(&self, webview_id: Option<WebViewId>) {
+        self.video_renderer
+            .lock()
+            .unwrap()
+            .set_picture_in_picture(webview_id);
+    }
+
     /// Gets the current frame of the video element to present, if any.
     /// <https://html.spec.whatwg.org/multipage/#the-video-element:the-video-element-7>
     pub(crate) fn get_current_frame_to_present(&self) -> Option<MediaFrame> {
@@ -2268,19 +2460,24 @@ impl HTMLMediaElementMethods<crate::DomTypeHolder> for HTMLMediaElement {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
-    fn GetSrcObject(&self) -> Option<MediaStreamOrBlob> {
+    fn GetSrcObject(&self) -> Option<MediaStreamOrMediaSourceOrBlob> {
         (*self.src_object.borrow())
             .as_ref()
             .map(|src_object| match src_object {
-                SrcObject::Blob(blob) => MediaStreamOrBlob::Blob(DomRoot::from_ref(blob)),
+                SrcObject::Blob(blob) => {
+                    MediaStreamOrMediaSourceOrBlob::Blob(DomRoot::from_ref(blob))
+                },
                 SrcObject::MediaStream(stream) => {
-                    MediaStreamOrBlob::MediaStream(DomRoot::from_ref(stream))
+                    MediaStreamOrMediaSourceOrBlob::MediaStream(DomRoot::from_ref(stream))
+                },
+                SrcObject::MediaSource(media_source) => {
+                    MediaStreamOrMediaSourceOrBlob::MediaSource(DomRoot::from_ref(media_source))
                 },
             })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
-    fn SetSrcObject(&self, value: Option<MediaStreamOrBlob>, can_gc: CanGc) {
+    fn SetSrcObject(&self, value: Option<MediaStreamOrMediaSourceOrBlob>, can_gc: CanGc) {
         *self.src_object.borrow_mut() = value.map(|value| value.into());
         self.media_element_load_algorithm(can_gc);
     }
@@ -2531,6 +2728,12 @@ impl HTMLMediaElementMethods<crate::DomTypeHolder> for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-seekable
     fn Seekable(&self) -> DomRoot<TimeRanges> {
+        // https://w3c.github.io/media-source/#htmlmediaelement-extensions
+        if let Some(media_source) = self.media_source.get() {
+            let seekable = media_source.seekable(&media_source.buffered());
+            return TimeRanges::new(self.global().as_window(), seekable, CanGc::note());
+        }
+
         let mut seekable = TimeRangesContainer::default();
         if let Some(ref player) = *self.player.borrow() {
             if let Ok(ranges) = player.lock().unwrap().seekable() {
@@ -2544,6 +2747,15 @@ impl HTMLMediaElementMethods<crate::DomTypeHolder> for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-buffered
     fn Buffered(&self) -> DomRoot<TimeRanges> {
+        // https://w3c.github.io/media-source/#htmlmediaelement-extensions
+        if let Some(media_source) = self.media_source.get() {
+            return TimeRanges::new(
+                self.global().as_window(),
+                media_source.buffered(),
+                CanGc::note(),
+            );
+        }
+
         let mut buffered = TimeRangesContainer::default();
         if let Some(ref player) = *self.player.borrow() {
             if let Ok(ranges) = player.lock().unwrap().buffered() {
@@ -2735,6 +2947,8 @@ impl MicrotaskRunnable for MediaElementMicrotask {
 enum Resource {
     Object,
     Url(ServoUrl),
+    /// A `MediaSource` that a media source object URL was created for.
+    MediaSource(DomRoot<MediaSource>),
 }
 
 #[derive(Debug, MallocSizeOf, PartialEq)]
@@ -79,6 +79,7 @@ use crate::realms::enter_realm;
 use crate::script_module::{
     ImportMap, ModuleOwner, ScriptFetchOptions, fetch_external_module_script,
     fetch_inline_module_script, parse_an_import_map_string, register_import_map,
+    resolve_a_module_integrity_metadata,
 };
 use crate::script_runtime::CanGc;
 use crate::task_source::{SendableTaskSource, TaskSourceName};
@@ -942,7 +943,15 @@ impl HTMLScriptElement {
                     }
                 },
                 ScriptType::Module => {
-                    // Step 31.11. Fetch an external module script graph.
+                    // Step 31.11.1. If el does not have an integrity attribute, then set options's
+                    // integrity metadata to the result of resolving a module integrity metadata
+                    // with url and settings object.
+                    let mut options = options;
+                    if !element.has_attribute(&local_name!("integrity")) {
+                        options.integrity_metadata =
+                            resolve_a_module_integrity_metadata(&url, &self.global());
+                    }
+                    // Step 31.11.2. Fetch an external module script graph.
                     fetch_external_module_script(
                         ModuleOwner::Window(Trusted::new(self)),
                         url.clone(),
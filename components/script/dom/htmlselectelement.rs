@@ -339,7 +339,9 @@ impl HTMLSelectElement {
             .or_else(|| self.list_of_options().next())
     }
 
-    pub(crate) fn show_menu(&self, can_gc: CanGc) -> Option<usize> {
+    /// Ask the embedder to show a popup menu for this element and return the indices
+    /// of the options chosen by the user, or `None` if the menu was dismissed.
+    pub(crate) fn show_menu(&self, can_gc: CanGc) -> Option<Vec<usize>> {
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
 
         // Collect list of optgroups and options
@@ -383,13 +385,23 @@ impl HTMLSelectElement {
             Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
         );
 
-        let selected_index = self.list_of_options().position(|option| option.Selected());
+        let selected_indices = self
+            .list_of_options()
+            .enumerate()
+            .filter(|(_, option)| option.Selected())
+            .map(|(index, _)| index)
+            .collect();
 
         let document = self.owner_document();
         document.send_to_embedder(EmbedderMsg::ShowFormControl(
             document.webview_id(),
             DeviceIntRect::from_untyped(&rect.to_box2d()),
-            EmbedderFormControl::SelectElement(options, selected_index, ipc_sender),
+            EmbedderFormControl::SelectElement(
+                options,
+                selected_indices,
+                self.Multiple(),
+                ipc_sender,
+            ),
         ));
 
         let Ok(response) = ipc_receiver.recv() else {
@@ -411,7 +423,8 @@ impl HTMLSelectElement {
             .queue(task!(send_select_update_notification: move || {
                 let this = this.root();
 
-                // TODO: Step 1. Set the select element's user validity to true.
+                // Step 1. Set the select element's user validity to true.
+                this.upcast::<Element>().set_user_validity(true, CanGc::note());
 
                 // Step 2. Fire an event named input at the select element, with the bubbles and composed
                 // attributes initialized to true.
@@ -782,13 +795,34 @@ impl Activatable for HTMLSelectElement {
     }
 
     fn activation_behavior(&self, _event: &Event, _target: &EventTarget, can_gc: CanGc) {
-        let Some(selected_value) = self.show_menu(can_gc) else {
+        let Some(selected_values) = self.show_menu(can_gc) else {
             // The user did not select a value
             return;
         };
 
-        self.SetSelectedIndex(selected_value as i32, can_gc);
-        self.send_update_notifications();
+        if !self.Multiple() {
+            let Some(selected_value) = selected_values.first() else {
+                return;
+            };
+            self.SetSelectedIndex(*selected_value as i32, can_gc);
+            self.send_update_notifications();
+            return;
+        }
+
+        let mut selection_did_change = false;
+        for (index, option) in self.list_of_options().enumerate() {
+            let selected = selected_values.contains(&index);
+            if option.Selected() != selected {
+                selection_did_change = true;
+                option.set_selectedness(selected);
+                option.set_dirtiness(true);
+            }
+        }
+
+        if selection_did_change {
+            self.update_shadow_tree(can_gc);
+            self.send_update_notifications();
+        }
     }
 }
 
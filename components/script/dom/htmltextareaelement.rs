@@ -26,8 +26,10 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::clipboardevent::ClipboardEvent;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
@@ -44,7 +46,7 @@ use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 use crate::textinput::{
     Direction, KeyReaction, Lines, SelectionDirection, TextInput, UTF8Bytes, UTF16CodeUnits,
-    handle_text_clipboard_action,
+    handle_text_clipboard_action, handle_text_drop_action,
 };
 
 #[dom_struct]
@@ -64,6 +66,8 @@ pub(crate) struct HTMLTextAreaElement {
 pub(crate) trait LayoutHTMLTextAreaElementHelpers {
     fn value_for_layout(self) -> String;
     fn selection_for_layout(self) -> Option<Range<usize>>;
+    fn composition_for_layout(self) -> Option<Range<usize>>;
+    fn misspellings_for_layout(self) -> Vec<Range<usize>>;
     fn get_cols(self) -> u32;
     fn get_rows(self) -> u32;
 }
@@ -88,6 +92,25 @@ impl<'dom> LayoutDom<'dom, HTMLTextAreaElement> {
         }
     }
 
+    fn textinput_composition_range(self) -> Option<Range<UTF8Bytes>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .composition_range()
+        }
+    }
+
+    fn textinput_misspellings(self) -> Vec<Range<UTF8Bytes>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .misspellings()
+                .to_vec()
+        }
+    }
+
     fn placeholder(self) -> &'dom str {
         unsafe { self.unsafe_get().placeholder.borrow_for_layout() }
     }
@@ -114,6 +137,21 @@ impl LayoutHTMLTextAreaElementHelpers for LayoutDom<'_, HTMLTextAreaElement> {
         ))
     }
 
+    fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !self.upcast::<Element>().focus_state() {
+            return None;
+        }
+        self.textinput_composition_range()
+            .map(UTF8Bytes::unwrap_range)
+    }
+
+    fn misspellings_for_layout(self) -> Vec<Range<usize>> {
+        self.textinput_misspellings()
+            .into_iter()
+            .map(UTF8Bytes::unwrap_range)
+            .collect()
+    }
+
     fn get_cols(self) -> u32 {
         self.upcast::<Element>()
             .get_attr_for_layout(&ns!(), &local_name!("cols"))
@@ -204,11 +242,59 @@ impl HTMLTextAreaElement {
     }
 
     // https://html.spec.whatwg.org/multipage/#concept-fe-mutable
-    fn is_mutable(&self) -> bool {
+    pub(crate) fn is_mutable(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#the-textarea-element%3Aconcept-fe-mutable
         // https://html.spec.whatwg.org/multipage/#the-readonly-attribute:concept-fe-mutable
         !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
     }
+
+    // https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking
+    fn is_spellchecked(&self) -> bool {
+        self.is_mutable() && self.upcast::<Element>().is_spellcheck_enabled()
+    }
+
+    /// Ask the spellchecker for the misspelled words in the value of this element, so that
+    /// they are marked when it is painted.
+    fn update_misspellings(&self, typing: bool) {
+        let misspellings = if self.is_spellchecked() {
+            let value = self.textinput.borrow().get_content();
+            self.owner_document()
+                .check_spelling(value.into())
+                .into_iter()
+                .map(|range| UTF8Bytes(range.start)..UTF8Bytes(range.end))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.textinput
+            .borrow_mut()
+            .set_misspellings(misspellings, typing);
+        self.upcast::<Node>().dirty(NodeDamage::Other);
+    }
+
+    /// The misspelled word at the caret of this element and the replacements that the
+    /// spellchecker suggests for it, which are offered in its context menu.
+    pub(crate) fn spelling_suggestions(&self) -> Option<(String, Vec<String>)> {
+        let word = self.textinput.borrow().misspelled_word()?;
+        let suggestions = self.owner_document().spelling_suggestions(word.clone());
+        Some((word, suggestions))
+    }
+
+    /// Replace the misspelled word at the caret of this element with `replacement`, which
+    /// the user picked from the suggestions of the spellchecker.
+    pub(crate) fn replace_misspelled_word(&self, replacement: &str, can_gc: CanGc) {
+        let action = self
+            .textinput
+            .borrow_mut()
+            .replace_misspelled_word(replacement);
+        if let KeyReaction::DispatchInput = action {
+            self.value_dirty.set(true);
+            self.update_placeholder_shown_state();
+            self.update_misspellings(false);
+            self.upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("input"), can_gc);
+        }
+    }
 }
 
 impl TextControlElement for HTMLTextAreaElement {
@@ -656,7 +742,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                         }
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
-                        self.upcast::<Node>().dirty(NodeDamage::Other);
+                        self.update_misspellings(true);
                         event.mark_as_handled();
                     },
                     KeyReaction::RedrawSelection => {
@@ -666,6 +752,16 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::Nothing => (),
                 }
             }
+        } else if event.type_() == atom!("focus") {
+            self.update_misspellings(false);
+        } else if &*event.type_() == "blur" && event.IsTrusted() {
+            // https://html.spec.whatwg.org/multipage/#user-validity
+            // The user is done editing the value once the control loses focus.
+            if self.value_dirty.get() && !self.textinput.borrow().was_last_change_by_set_content() {
+                self.upcast::<Element>().set_user_validity(true, can_gc);
+            }
+            // The word that was being typed is finished now, so it can be marked as well.
+            self.update_misspellings(false);
         } else if event.type_() == atom!("keypress") && !event.DefaultPrevented() {
             // keypress should be deprecated and replaced by beforeinput.
             // keypress was supposed to fire "blur" and "focus" events
@@ -675,25 +771,56 @@ impl VirtualMethods for HTMLTextAreaElement {
             event.type_() == atom!("compositionend")
         {
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::Other);
+                let action = if event.type_() == atom!("compositionstart") {
+                    self.textinput.borrow_mut().handle_compositionstart()
                 } else if event.type_() == atom!("compositionupdate") {
-                    let _ = self
-                        .textinput
+                    self.textinput
+                        .borrow_mut()
+                        .handle_compositionupdate(compositionevent)
+                } else {
+                    self.textinput
                         .borrow_mut()
-                        .handle_compositionupdate(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::Other);
+                        .handle_compositionend(compositionevent)
+                };
+                if let KeyReaction::DispatchInput = action {
+                    if event.IsTrusted() {
+                        self.owner_global()
+                            .task_manager()
+                            .user_interaction_task_source()
+                            .queue_event(
+                                self.upcast(),
+                                atom!("input"),
+                                EventBubbles::Bubbles,
+                                EventCancelable::NotCancelable,
+                            );
+                    }
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
+                    self.update_misspellings(true);
                 }
+                self.upcast::<Node>().dirty(NodeDamage::Other);
+                self.owner_document()
+                    .update_ime_cursor_area(self.upcast(), can_gc);
                 event.mark_as_handled();
             }
         } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
             if !event.DefaultPrevented() {
                 handle_text_clipboard_action(self, &self.textinput, clipboard_event, CanGc::note());
             }
+        } else if let Some(drag_event) = event.downcast::<DragEvent>() {
+            // <https://html.spec.whatwg.org/multipage/#drop-processing>
+            if &*event.type_() == "drop" &&
+                !event.DefaultPrevented() &&
+                self.is_mutable() &&
+                handle_text_drop_action(&self.textinput, drag_event, None)
+            {
+                self.value_dirty.set(true);
+                self.update_placeholder_shown_state();
+                self.update_misspellings(true);
+                self.upcast::<Node>().dirty(NodeDamage::Other);
+                self.upcast::<EventTarget>()
+                    .fire_bubbling_event(atom!("input"), can_gc);
+            }
         }
 
         self.validity_state()
@@ -3,8 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use constellation_traits::ScriptToConstellationMessage;
 use dom_struct::dom_struct;
 use euclid::default::Size2D;
 use html5ever::{LocalName, Prefix, local_name, ns};
@@ -23,11 +25,13 @@ use pixels::{Snapshot, SnapshotAlphaMode, SnapshotPixelFormat};
 use servo_media::player::video::VideoFrame;
 use servo_url::ServoUrl;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use webrender_api::units::DeviceIntSize;
 
 use crate::document_loader::{LoadBlocker, LoadType};
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::HTMLVideoElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomGlobal;
@@ -38,11 +42,14 @@ use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlmediaelement::{HTMLMediaElement, NetworkState, ReadyState};
-use crate::dom::node::{Node, NodeTraits};
+use crate::dom::node::{Node, NodeTraits, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::FetchCanceller;
 use crate::network_listener::{self, PreInvoke, ResourceTimingListener};
+use crate::realms::{AlreadyInRealm, InRealm};
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -317,6 +324,45 @@ impl HTMLVideoElement {
     pub(crate) fn is_network_state_empty(&self) -> bool {
         self.htmlmediaelement.network_state() == NetworkState::Empty
     }
+
+    /// Whether this video is the picture-in-picture element of its document.
+    fn is_picture_in_picture_element(&self) -> bool {
+        self.owner_document()
+            .picture_in_picture_element()
+            .is_some_and(|element| &*element == self.upcast::<Element>())
+    }
+
+    /// Steps 1 to 6 of <https://w3c.github.io/picture-in-picture/#request-pip>
+    fn check_picture_in_picture_request(&self) -> ErrorResult {
+        // Step 2. If the document is not allowed to use the policy-controlled feature named
+        // "picture-in-picture", throw a SecurityError.
+        let document = self.owner_document();
+        if !document.is_fully_active() {
+            return Err(Error::Security);
+        }
+
+        // Step 3. If the readyState attribute is HAVE_NOTHING, throw a InvalidStateError.
+        // Step 4. If video has no video track, throw a InvalidStateError.
+        if self.VideoWidth() == 0 || self.VideoHeight() == 0 {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5. If the disablePictureInPicture attribute is present, throw a
+        // InvalidStateError.
+        if self.DisablePictureInPicture() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 6. If pictureInPictureElement is null and the relevant global object of
+        // this does not have transient activation, throw a NotAllowedError.
+        if document.picture_in_picture_element().is_none() &&
+            !self.owner_window().has_transient_activation()
+        {
+            return Err(Error::NotAllowed);
+        }
+
+        Ok(())
+    }
 }
 
 impl HTMLVideoElementMethods<crate::DomTypeHolder> for HTMLVideoElement {
@@ -345,6 +391,94 @@ impl HTMLVideoElementMethods<crate::DomTypeHolder> for HTMLVideoElement {
     // For testing purposes only. This is not an event from
     // https://html.spec.whatwg.org/multipage/#dom-video-poster
     event_handler!(postershown, GetOnpostershown, SetOnpostershown);
+
+    /// <https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-requestpictureinpicture>
+    fn RequestPictureInPicture(&self, can_gc: CanGc) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
+        if let Err(error) = self.check_picture_in_picture_request() {
+            promise.reject_error(error, can_gc);
+            return promise;
+        }
+
+        // Step 7. If video is pictureInPictureElement, abort these steps.
+        if self.is_picture_in_picture_element() {
+            if let Some(window) = self.owner_document().picture_in_picture_window() {
+                promise.resolve_native(&window, can_gc);
+                return promise;
+            }
+        }
+
+        // The embedder opens a window for the video, or declines to, in parallel.
+        let video_size = DeviceIntSize::new(self.VideoWidth() as i32, self.VideoHeight() as i32);
+        let global = self.owner_global();
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().media_element_task_source(),
+        );
+        self.owner_window().send_to_constellation(
+            ScriptToConstellationMessage::RequestPictureInPicture(video_size, sender),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onenterpictureinpicture
+    event_handler!(
+        enterpictureinpicture,
+        GetOnenterpictureinpicture,
+        SetOnenterpictureinpicture
+    );
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onleavepictureinpicture
+    event_handler!(
+        leavepictureinpicture,
+        GetOnleavepictureinpicture,
+        SetOnleavepictureinpicture
+    );
+
+    /// <https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture>
+    fn DisablePictureInPicture(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("disablepictureinpicture"))
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture>
+    fn SetDisablePictureInPicture(&self, value: bool) {
+        self.upcast::<Element>().set_bool_attribute(
+            &LocalName::from("disablepictureinpicture"),
+            value,
+            CanGc::note(),
+        );
+    }
+}
+
+impl RoutedPromiseListener<Option<DeviceIntSize>> for HTMLVideoElement {
+    /// Steps 8 onwards of <https://w3c.github.io/picture-in-picture/#request-pip>, once the
+    /// embedder has opened a window of the given size, if it did.
+    fn handle_response(
+        &self,
+        window_size: Option<DeviceIntSize>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        let Some(window_size) = window_size else {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return;
+        };
+
+        // The video may have left its document while the window was being opened.
+        let document = self.owner_document();
+        if !self.upcast::<Node>().is_connected() {
+            self.owner_window()
+                .send_to_constellation(ScriptToConstellationMessage::ExitPictureInPicture);
+            promise.reject_error(Error::InvalidState, can_gc);
+            return;
+        }
+
+        let window = document.enter_picture_in_picture(self.upcast(), window_size, can_gc);
+        promise.resolve_native(&window, can_gc);
+    }
 }
 
 impl VirtualMethods for HTMLVideoElement {
@@ -377,6 +511,15 @@ impl VirtualMethods for HTMLVideoElement {
                 .parse_plain_attribute(name, value),
         }
     }
+
+    fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
+        self.super_type().unwrap().unbind_from_tree(context, can_gc);
+
+        // https://w3c.github.io/picture-in-picture/#remove-video
+        if context.tree_connected && self.is_picture_in_picture_element() {
+            self.owner_document().exit_picture_in_picture(true, can_gc);
+        }
+    }
 }
 
 struct PosterFrameFetchContext {
@@ -0,0 +1,561 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::{HandleValue, MutableHandleValue};
+use net_traits::indexeddb_thread::{
+    AsyncOperation, AsyncReadOnlyOperation, AsyncReadWriteOperation, IndexedDBCursorDirection,
+    IndexedDBKeyRange, IndexedDBKeyType, IndexedDBRecord, IterationParams,
+};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::{
+    IDBCursorDirection, IDBCursorMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::structuredclone;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursorwithvalue::IDBCursorWithValue;
+use crate::dom::idbindex::IDBIndex;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbrequest::IDBRequest;
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{ExtractionResult, convert_value_to_key, extract_key, key_type_to_jsval};
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+#[dom_struct]
+pub struct IDBCursor {
+    reflector_: Reflector,
+    /// The object store of the cursor, which is its source if it does not iterate
+    /// over an index, see <https://w3c.github.io/IndexedDB/#cursor-effective-object-store>.
+    object_store: Dom<IDBObjectStore>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-source>, if it is an index.
+    index: Option<Dom<IDBIndex>>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-direction>
+    direction: IDBCursorDirection,
+    /// <https://w3c.github.io/IndexedDB/#cursor-range>
+    #[no_trace]
+    range: IndexedDBKeyRange,
+    /// <https://w3c.github.io/IndexedDB/#cursor-position>
+    #[no_trace]
+    position: DomRefCell<Option<IndexedDBKeyType>>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-object-store-position>
+    #[no_trace]
+    object_store_position: DomRefCell<Option<IndexedDBKeyType>>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-key>
+    #[ignore_malloc_size_of = "mozjs"]
+    key: Heap<JSVal>,
+    /// The primary key of the record of the cursor, see
+    /// <https://w3c.github.io/IndexedDB/#cursor-effective-key>.
+    #[ignore_malloc_size_of = "mozjs"]
+    primary_key: Heap<JSVal>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-value>
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Heap<JSVal>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-got-value-flag>
+    got_value: Cell<bool>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-key-only-flag>
+    key_only: bool,
+    /// <https://w3c.github.io/IndexedDB/#cursor-request>
+    request: MutNullableDom<IDBRequest>,
+}
+
+impl IDBCursor {
+    pub fn new_inherited(
+        object_store: &IDBObjectStore,
+        index: Option<&IDBIndex>,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+        key_only: bool,
+    ) -> IDBCursor {
+        IDBCursor {
+            reflector_: Reflector::new(),
+            object_store: Dom::from_ref(object_store),
+            index: index.map(Dom::from_ref),
+            direction,
+            range,
+            position: Default::default(),
+            object_store_position: Default::default(),
+            key: Heap::default(),
+            primary_key: Heap::default(),
+            value: Heap::default(),
+            got_value: Cell::new(false),
+            key_only,
+            request: Default::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        object_store: &IDBObjectStore,
+        index: Option<&IDBIndex>,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+        can_gc: CanGc,
+    ) -> DomRoot<IDBCursor> {
+        reflect_dom_object(
+            Box::new(IDBCursor::new_inherited(
+                object_store,
+                index,
+                direction,
+                range,
+                true,
+            )),
+            global,
+            can_gc,
+        )
+    }
+
+    /// Opens a cursor on an object store or index, the steps shared by `openCursor()` and
+    /// `openKeyCursor()` after their checks, see
+    /// <https://w3c.github.io/IndexedDB/#dom-idbobjectstore-opencursor>.
+    pub fn open(
+        source: IDBObjectStoreOrIDBIndex,
+        range: IndexedDBKeyRange,
+        direction: IDBCursorDirection,
+        key_only: bool,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        let (object_store, index, request_source) = match source {
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(store) => (
+                store.clone(),
+                None,
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store),
+            ),
+            IDBObjectStoreOrIDBIndex::IDBIndex(index) => (
+                index.object_store(),
+                Some(index.clone()),
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index),
+            ),
+        };
+        let global = object_store.global();
+
+        // Step 6: Let cursor be a new cursor with its source handle set to this, undefined
+        // position, direction set to direction, got value flag set to false, undefined key
+        // and value, range set to range, and key only flag set to keyOnly.
+        let cursor = if key_only {
+            IDBCursor::new(
+                &global,
+                &object_store,
+                index.as_deref(),
+                direction,
+                range,
+                can_gc,
+            )
+        } else {
+            DomRoot::upcast(IDBCursorWithValue::new(
+                &global,
+                &object_store,
+                index.as_deref(),
+                direction,
+                range,
+                can_gc,
+            ))
+        };
+
+        // Step 7: Let operation be an algorithm to run iterate a cursor with the current
+        // Realm record and cursor.
+        let operation = AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Iterate(
+            cursor.params(None, None, 1),
+        ));
+
+        // Step 8: Let request be the result of asynchronously executing a request with this
+        // and operation.
+        let request = IDBRequest::execute_async(request_source, operation, None, can_gc)?;
+
+        // Step 9: Set cursor's request to request.
+        cursor.request.set(Some(&request));
+        request.set_cursor(&cursor);
+
+        // Step 10: Return request.
+        Ok(request)
+    }
+
+    pub fn object_store(&self) -> DomRoot<IDBObjectStore> {
+        self.object_store.as_rooted()
+    }
+
+    pub fn value(&self) -> JSVal {
+        self.value.get()
+    }
+
+    fn transaction(&self) -> DomRoot<IDBTransaction> {
+        self.object_store
+            .transaction()
+            .expect("No transaction in Object Store")
+    }
+
+    fn source(&self) -> IDBObjectStoreOrIDBIndexOrIDBCursor {
+        match &self.index {
+            Some(index) => IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index.as_rooted()),
+            None => IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(self.object_store()),
+        }
+    }
+
+    fn is_forward(&self) -> bool {
+        matches!(
+            self.direction,
+            IDBCursorDirection::Next | IDBCursorDirection::Nextunique
+        )
+    }
+
+    /// Whether the source of the cursor or its object store has been deleted.
+    fn is_source_deleted(&self) -> bool {
+        match &self.index {
+            Some(index) => self.object_store.index_metadata(index.get_name()).is_none(),
+            None => false,
+        }
+    }
+
+    /// The arguments to <https://w3c.github.io/IndexedDB/#iterate-a-cursor>, starting from
+    /// the current position of the cursor.
+    fn params(
+        &self,
+        key: Option<IndexedDBKeyType>,
+        primary_key: Option<IndexedDBKeyType>,
+        count: u32,
+    ) -> IterationParams {
+        IterationParams {
+            index: self.index.as_ref().map(|index| index.get_name().to_owned()),
+            range: self.range.clone(),
+            direction: match self.direction {
+                IDBCursorDirection::Next => IndexedDBCursorDirection::Next,
+                IDBCursorDirection::Nextunique => IndexedDBCursorDirection::NextUnique,
+                IDBCursorDirection::Prev => IndexedDBCursorDirection::Prev,
+                IDBCursorDirection::Prevunique => IndexedDBCursorDirection::PrevUnique,
+            },
+            key,
+            primary_key,
+            position: self.position.borrow().clone(),
+            object_store_position: self.object_store_position.borrow().clone(),
+            count,
+            key_only: self.key_only,
+        }
+    }
+
+    /// Moves the cursor with the request it was opened with, see the last steps of
+    /// <https://w3c.github.io/IndexedDB/#dom-idbcursor-continue>.
+    fn iterate(&self, params: IterationParams) -> Fallible<()> {
+        let request = self.request.get().expect("Cursor has no request");
+
+        // Set this's got value flag to false.
+        self.got_value.set(false);
+
+        // Set request's processed flag to false, and its done flag to false.
+        request.set_ready_state_pending();
+
+        // Run asynchronously execute a request with this's source handle, operation,
+        // and request.
+        IDBRequest::execute_async(
+            self.source(),
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Iterate(params)),
+            Some(request),
+            CanGc::note(),
+        )
+        .map(|_| ())
+    }
+
+    /// Sets the position, key and value of the cursor to those of the record found by
+    /// iterating it, see the last steps of <https://w3c.github.io/IndexedDB/#iterate-a-cursor>.
+    /// Returns false if the cursor reached the end of its range.
+    pub fn set_iteration_result(
+        &self,
+        cx: SafeJSContext,
+        record: Option<IndexedDBRecord>,
+    ) -> Fallible<bool> {
+        let Some(record) = record else {
+            // Set cursor's value to undefined.
+            // Set cursor's key to undefined.
+            // Set cursor's position to undefined.
+            // Set cursor's object store position to undefined.
+            *self.position.borrow_mut() = None;
+            *self.object_store_position.borrow_mut() = None;
+            self.key.set(UndefinedValue());
+            self.primary_key.set(UndefinedValue());
+            self.value.set(UndefinedValue());
+            return Ok(false);
+        };
+
+        rooted!(in(*cx) let mut key = UndefinedValue());
+        key_type_to_jsval(cx, &record.key, key.handle_mut());
+        self.key.set(key.get());
+
+        rooted!(in(*cx) let mut primary_key = UndefinedValue());
+        key_type_to_jsval(cx, &record.primary_key, primary_key.handle_mut());
+        self.primary_key.set(primary_key.get());
+
+        if let Some(value) = record.value {
+            rooted!(in(*cx) let mut deserialized = UndefinedValue());
+            self.object_store.deserialize_value(
+                cx,
+                value,
+                &record.primary_key,
+                deserialized.handle_mut(),
+            )?;
+            self.value.set(deserialized.get());
+        }
+
+        *self.position.borrow_mut() = Some(record.key);
+        *self.object_store_position.borrow_mut() = Some(record.primary_key);
+
+        // Set cursor's got value flag to true.
+        self.got_value.set(true);
+        Ok(true)
+    }
+
+    /// The checks shared by the methods that move the cursor.
+    fn check_movable(&self) -> Fallible<()> {
+        // If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        if !self.transaction().is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // If this's source or effective object store has been deleted, throw an
+        // "InvalidStateError" DOMException.
+        if self.is_source_deleted() {
+            return Err(Error::InvalidState);
+        }
+
+        // If this's got value flag is false, indicating that the cursor is being iterated
+        // or has iterated past its end, throw an "InvalidStateError" DOMException.
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+
+        Ok(())
+    }
+
+    /// The checks shared by `update()` and `delete()`, returning the effective key of the
+    /// cursor.
+    fn check_writable(&self) -> Fallible<IndexedDBKeyType> {
+        // If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        // If transaction is a read-only transaction, throw a "ReadOnlyError" DOMException.
+        // If this's source or effective object store has been deleted, throw an
+        // "InvalidStateError" DOMException.
+        self.object_store.check_writable()?;
+        if self.is_source_deleted() {
+            return Err(Error::InvalidState);
+        }
+
+        // If this's got value flag is false, indicating that the cursor is being iterated
+        // or has iterated past its end, throw an "InvalidStateError" DOMException.
+        // If this's key only flag is true, throw an "InvalidStateError" DOMException.
+        if !self.got_value.get() || self.key_only {
+            return Err(Error::InvalidState);
+        }
+
+        self.object_store_position
+            .borrow()
+            .clone()
+            .ok_or(Error::InvalidState)
+    }
+}
+
+impl IDBCursorMethods<crate::DomTypeHolder> for IDBCursor {
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-source
+    fn Source(&self) -> IDBObjectStoreOrIDBIndex {
+        match &self.index {
+            Some(index) => IDBObjectStoreOrIDBIndex::IDBIndex(index.as_rooted()),
+            None => IDBObjectStoreOrIDBIndex::IDBObjectStore(self.object_store()),
+        }
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-direction
+    fn Direction(&self) -> IDBCursorDirection {
+        self.direction
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-key
+    fn Key(&self, _cx: SafeJSContext, mut retval: MutableHandleValue) {
+        retval.set(self.key.get());
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-primarykey
+    fn PrimaryKey(&self, _cx: SafeJSContext, mut retval: MutableHandleValue) {
+        retval.set(self.primary_key.get());
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-request
+    fn Request(&self) -> DomRoot<IDBRequest> {
+        self.request.get().expect("Cursor has no request")
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-advance
+    fn Advance(&self, count: u32) -> Fallible<()> {
+        // Step 1: If count is 0 (zero), throw a TypeError.
+        if count == 0 {
+            return Err(Error::Type("Count must be greater than zero".to_owned()));
+        }
+
+        // Steps 2-5
+        self.check_movable()?;
+
+        // Steps 6-9
+        self.iterate(self.params(None, None, count))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-continue
+    fn Continue(&self, cx: SafeJSContext, key: HandleValue) -> Fallible<()> {
+        // Steps 1-4
+        self.check_movable()?;
+
+        // Step 5: If key is given, then:
+        let key = if key.is_undefined() {
+            None
+        } else {
+            // Step 5.1: Let r be the result of converting a value to a key with key.
+            // Rethrow any exceptions.
+            // Step 5.2: If r is invalid, throw a "DataError" DOMException.
+            let key = convert_value_to_key(cx, key, None)?;
+
+            // Step 5.3: If key is less than or equal to this's position and this's
+            // direction is "next" or "nextunique", then throw a "DataError" DOMException.
+            // Step 5.4: If key is greater than or equal to this's position and this's
+            // direction is "prev" or "prevunique", then throw a "DataError" DOMException.
+            if let Some(position) = &*self.position.borrow() {
+                if (self.is_forward() && key <= *position) ||
+                    (!self.is_forward() && key >= *position)
+                {
+                    return Err(Error::Data);
+                }
+            }
+            Some(key)
+        };
+
+        // Steps 6-9
+        self.iterate(self.params(key, None, 1))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-continueprimarykey
+    fn ContinuePrimaryKey(
+        &self,
+        cx: SafeJSContext,
+        key: HandleValue,
+        primary_key: HandleValue,
+    ) -> Fallible<()> {
+        // Step 1: Let transaction be this's transaction.
+        // Step 2: If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        if !self.transaction().is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 3: If this's source or effective object store has been deleted, throw an
+        // "InvalidStateError" DOMException.
+        if self.is_source_deleted() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4: If this's source is not an index throw an "InvalidAccessError" DOMException.
+        if self.index.is_none() {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 5: If this's direction is not "next" or "prev", throw an
+        // "InvalidAccessError" DOMException.
+        if !matches!(
+            self.direction,
+            IDBCursorDirection::Next | IDBCursorDirection::Prev
+        ) {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 6: If this's got value flag is false, indicating that the cursor is being
+        // iterated or has iterated past its end, throw an "InvalidStateError" DOMException.
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 7-8: Let r be the result of converting a value to a key with key.
+        let key = convert_value_to_key(cx, key, None)?;
+
+        // Steps 9-10: Let r be the result of converting a value to a key with primaryKey.
+        let primary_key = convert_value_to_key(cx, primary_key, None)?;
+
+        // Steps 11-14: The key and primary key have to be after the position and object
+        // store position of the cursor, in its direction.
+        if let (Some(position), Some(object_store_position)) = (
+            &*self.position.borrow(),
+            &*self.object_store_position.borrow(),
+        ) {
+            let is_behind = if self.is_forward() {
+                key < *position || (key == *position && primary_key <= *object_store_position)
+            } else {
+                key > *position || (key == *position && primary_key >= *object_store_position)
+            };
+            if is_behind {
+                return Err(Error::Data);
+            }
+        }
+
+        // Steps 15-18
+        self.iterate(self.params(Some(key), Some(primary_key), 1))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-update
+    fn Update(&self, cx: SafeJSContext, value: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-6
+        let effective_key = self.check_writable()?;
+
+        // Steps 7-8: Let clone be a clone of value.
+        let serialized_value = structuredclone::write(cx, value, None)?;
+
+        // Step 9: If this's effective object store uses in-line keys, then:
+        if let Some(key_path) = self.object_store.key_path() {
+            // Let kpk be the result of extracting a key from a value using a key path with
+            // clone and the key path of this's effective object store.
+            // If kpk is failure, invalid, or not equal to this's effective key, throw a
+            // "DataError" DOMException.
+            match extract_key(cx, value, &key_path)? {
+                ExtractionResult::Key(key) if key == effective_key => {},
+                _ => return Err(Error::Data),
+            }
+        }
+
+        let index_keys = self.object_store.index_keys(cx, value)?;
+
+        // Step 10: Let operation be an algorithm to run store a record into an object store
+        // with this's effective object store, clone, this's effective key, and false.
+        // Step 11: Return the result of asynchronously executing a request with this and
+        // operation.
+        IDBRequest::execute_async(
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(DomRoot::from_ref(self)),
+            AsyncOperation::ReadWrite(AsyncReadWriteOperation::PutItem(
+                Some(effective_key),
+                serialized_value.serialized,
+                true,
+                index_keys,
+            )),
+            None,
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-delete
+    fn Delete(&self) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-6
+        let effective_key = self.check_writable()?;
+
+        // Step 7: Let operation be an algorithm to run delete records from an object store
+        // with this's effective object store and this's effective key.
+        // Step 8: Return the result of asynchronously executing a request with this and
+        // operation.
+        IDBRequest::execute_async(
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(DomRoot::from_ref(self)),
+            AsyncOperation::ReadWrite(AsyncReadWriteOperation::RemoveItem(
+                IndexedDBKeyRange::from(effective_key),
+            )),
+            None,
+            CanGc::note(),
+        )
+    }
+}
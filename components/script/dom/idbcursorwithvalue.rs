@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::MutableHandleValue;
+use net_traits::indexeddb_thread::IndexedDBKeyRange;
+
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBCursorWithValueBinding::IDBCursorWithValueMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbindex::IDBIndex;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+#[dom_struct]
+pub struct IDBCursorWithValue {
+    cursor: IDBCursor,
+}
+
+impl IDBCursorWithValue {
+    fn new_inherited(
+        object_store: &IDBObjectStore,
+        index: Option<&IDBIndex>,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+    ) -> IDBCursorWithValue {
+        IDBCursorWithValue {
+            cursor: IDBCursor::new_inherited(object_store, index, direction, range, false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        object_store: &IDBObjectStore,
+        index: Option<&IDBIndex>,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+        can_gc: CanGc,
+    ) -> DomRoot<IDBCursorWithValue> {
+        reflect_dom_object(
+            Box::new(IDBCursorWithValue::new_inherited(
+                object_store,
+                index,
+                direction,
+                range,
+            )),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl IDBCursorWithValueMethods<crate::DomTypeHolder> for IDBCursorWithValue {
+    // https://w3c.github.io/IndexedDB/#dom-idbcursorwithvalue-value
+    fn Value(&self, _cx: SafeJSContext, mut retval: MutableHandleValue) {
+        retval.set(self.cursor.value());
+    }
+}
@@ -7,7 +7,7 @@ use std::cell::Cell;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use net_traits::IpcSend;
-use net_traits::indexeddb_thread::{IndexedDBThreadMsg, SyncOperation};
+use net_traits::indexeddb_thread::{IndexedDBThreadMsg, ObjectStoreMetadata, SyncOperation};
 use profile_traits::ipc;
 use stylo_atoms::Atom;
 
@@ -50,12 +50,16 @@ pub struct IDBDatabase {
 }
 
 impl IDBDatabase {
-    pub fn new_inherited(name: DOMString, version: u64) -> IDBDatabase {
+    pub fn new_inherited(
+        name: DOMString,
+        version: u64,
+        object_store_names: Vec<DOMString>,
+    ) -> IDBDatabase {
         IDBDatabase {
             eventtarget: EventTarget::new_inherited(),
             name,
             version: Cell::new(version),
-            object_store_names: Default::default(),
+            object_store_names: DomRefCell::new(object_store_names),
 
             upgrade_transaction: Default::default(),
             closing: Cell::new(false),
@@ -68,8 +72,31 @@ impl IDBDatabase {
         version: u64,
         can_gc: CanGc,
     ) -> DomRoot<IDBDatabase> {
+        // Load the names of the object stores that were created by earlier connections
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let operation = SyncOperation::ObjectStoreNames(
+            sender,
+            global.origin().immutable().clone(),
+            name.to_string(),
+        );
+        global
+            .resource_threads()
+            .sender()
+            .send(IndexedDBThreadMsg::Sync(operation))
+            .unwrap();
+        let object_store_names = receiver
+            .recv()
+            .unwrap_or_default()
+            .into_iter()
+            .map(DOMString::from)
+            .collect();
+
         reflect_dom_object(
-            Box::new(IDBDatabase::new_inherited(name, version)),
+            Box::new(IDBDatabase::new_inherited(
+                name,
+                version,
+                object_store_names,
+            )),
             global,
             can_gc,
         )
@@ -84,11 +111,33 @@ impl IDBDatabase {
     }
 
     pub fn object_stores(&self) -> DomRoot<DOMStringList> {
-        DOMStringList::new(
-            &self.global(),
-            self.object_store_names.borrow().clone(),
-            CanGc::note(),
-        )
+        let mut names = self.object_store_names.borrow().clone();
+        names.sort();
+        DOMStringList::new(&self.global(), names, CanGc::note())
+    }
+
+    pub fn has_object_store(&self, name: &str) -> bool {
+        self.object_store_names
+            .borrow()
+            .iter()
+            .any(|store_name| **store_name == *name)
+    }
+
+    /// Gets the key path, key generator and indexes of an object store from the idb thread.
+    pub fn object_store_metadata(&self, name: &str) -> Option<ObjectStoreMetadata> {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        let operation = SyncOperation::GetObjectStore(
+            sender,
+            self.global().origin().immutable().clone(),
+            self.name.to_string(),
+            name.to_owned(),
+        );
+
+        self.get_idb_thread()
+            .send(IndexedDBThreadMsg::Sync(operation))
+            .unwrap();
+
+        receiver.recv().ok().flatten()
     }
 
     pub fn version(&self) -> u64 {
@@ -144,38 +193,58 @@ impl IDBDatabaseMethods<crate::DomTypeHolder> for IDBDatabase {
         &self,
         store_names: StringOrStringSequence,
         mode: IDBTransactionMode,
-        _options: &IDBTransactionOptions,
+        options: &IDBTransactionOptions,
     ) -> Fallible<DomRoot<IDBTransaction>> {
-        // FIXIME:(arihant2math) use options
-        // Step 1: Check if upgrade transaction is running
-        // FIXME:(rasviitanen)
+        // Step 1: If a live upgrade transaction is associated with the connection,
+        // throw an "InvalidStateError" DOMException.
+        if self
+            .upgrade_transaction
+            .get()
+            .is_some_and(|transaction| !transaction.is_finished())
+        {
+            return Err(Error::InvalidState);
+        }
 
         // Step 2: if close flag is set, throw error
         if self.closing.get() {
             return Err(Error::InvalidState);
         }
 
-        // Step 3
-        Ok(match store_names {
-            StringOrStringSequence::String(name) => IDBTransaction::new(
-                &self.global(),
-                self,
-                mode,
-                &DOMStringList::new(&self.global(), vec![name], CanGc::note()),
-                CanGc::note(),
-            ),
-            StringOrStringSequence::StringSequence(sequence) => {
-                // FIXME:(rasviitanen) Remove eventual duplicated names
-                // from the sequence
-                IDBTransaction::new(
-                    &self.global(),
-                    self,
-                    mode,
-                    &DOMStringList::new(&self.global(), sequence, CanGc::note()),
-                    CanGc::note(),
-                )
-            },
-        })
+        // Step 3: Let scope be the set of unique strings in storeNames if it is a sequence,
+        // or a set containing one string equal to storeNames otherwise.
+        let mut scope = match store_names {
+            StringOrStringSequence::String(name) => vec![name],
+            StringOrStringSequence::StringSequence(sequence) => sequence,
+        };
+        scope.sort();
+        scope.dedup();
+
+        // Step 4: If any string in scope is not the name of an object store in the
+        // connected database, throw a "NotFoundError" DOMException.
+        if scope.iter().any(|name| !self.has_object_store(name)) {
+            return Err(Error::NotFound);
+        }
+
+        // Step 5: If scope is empty, throw an "InvalidAccessError" DOMException.
+        if scope.is_empty() {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 6: If mode is not "readonly" or "readwrite", throw a TypeError.
+        if mode == IDBTransactionMode::Versionchange {
+            return Err(Error::Type("Invalid transaction mode".to_owned()));
+        }
+
+        // Step 7-9: Let transaction be a newly created transaction with this connection,
+        // mode, options' durability member, and the set of object stores named in scope.
+        Ok(IDBTransaction::new(
+            &self.global(),
+            self,
+            mode,
+            options.durability,
+            &DOMStringList::new(&self.global(), scope, CanGc::note()),
+            CanGc::note(),
+        ))
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbdatabase-createobjectstore
@@ -235,11 +304,16 @@ impl IDBDatabaseMethods<crate::DomTypeHolder> for IDBDatabase {
         }
 
         // Step 9
+        let key_path = key_path.map(IDBObjectStore::convert_key_path);
         let object_store = IDBObjectStore::new(
             &self.global(),
             self.name.clone(),
-            name.clone(),
-            Some(options),
+            ObjectStoreMetadata {
+                name: name.to_string(),
+                key_path: key_path.clone(),
+                auto_increment,
+                indexes: vec![],
+            },
             CanGc::note(),
         );
         object_store.set_transaction(&upgrade_transaction);
@@ -251,6 +325,7 @@ impl IDBDatabaseMethods<crate::DomTypeHolder> for IDBDatabase {
             self.global().origin().immutable().clone(),
             self.name.to_string(),
             name.to_string(),
+            key_path,
             auto_increment,
         );
 
@@ -268,6 +343,7 @@ impl IDBDatabaseMethods<crate::DomTypeHolder> for IDBDatabase {
         };
 
         self.object_store_names.borrow_mut().push(name);
+        upgrade_transaction.set_store_handle(&object_store);
         Ok(object_store)
     }
 
@@ -340,12 +416,7 @@ impl IDBDatabaseMethods<crate::DomTypeHolder> for IDBDatabase {
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbdatabase-objectstorenames
     fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
-        // FIXME: (arihant2math) Sort the list of names, as per spec
-        DOMStringList::new(
-            &self.global(),
-            self.object_store_names.borrow().clone(),
-            CanGc::note(),
-        )
+        self.object_stores()
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbdatabase-close
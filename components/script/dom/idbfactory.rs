@@ -2,7 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cmp::Ordering;
+
 use dom_struct::dom_struct;
+use js::rust::HandleValue;
 use servo_url::origin::ImmutableOrigin;
 
 use crate::dom::bindings::codegen::Bindings::IDBFactoryBinding::IDBFactoryMethods;
@@ -12,7 +15,8 @@ use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::idbopendbrequest::IDBOpenDBRequest;
-use crate::script_runtime::CanGc;
+use crate::indexed_db::convert_value_to_key;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
 #[dom_struct]
 pub struct IDBFactory {
@@ -89,9 +93,22 @@ impl IDBFactoryMethods<crate::DomTypeHolder> for IDBFactory {
     // fn Databases(&self) -> Rc<Promise> {
     //     unimplemented!();
     // }
-    //
-    // // https://www.w3.org/TR/IndexedDB-2/#dom-idbfactory-cmp
-    // fn Cmp(&self, _cx: SafeJSContext, _first: HandleValue, _second: HandleValue) -> i16 {
-    //     unimplemented!();
-    // }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-cmp
+    fn Cmp(&self, cx: SafeJSContext, first: HandleValue, second: HandleValue) -> Fallible<i16> {
+        // Step 1-2: Let a be the result of converting a value to a key with first.
+        // Rethrow any exceptions. If a is invalid, throw a "DataError" DOMException.
+        let first = convert_value_to_key(cx, first, None)?;
+
+        // Step 3-4: Let b be the result of converting a value to a key with second.
+        // Rethrow any exceptions. If b is invalid, throw a "DataError" DOMException.
+        let second = convert_value_to_key(cx, second, None)?;
+
+        // Step 5: Return the results of comparing two keys with a and b.
+        Ok(match first.cmp(&second) {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        })
+    }
 }
@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::{HandleValue, MutableHandleValue};
+use net_traits::indexeddb_thread::{AsyncOperation, AsyncReadOnlyOperation, IndexMetadata};
+
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBIndexBinding::IDBIndexMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbrequest::IDBRequest;
+use crate::indexed_db::{convert_value_to_key_range, key_path_to_jsval};
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+#[dom_struct]
+pub struct IDBIndex {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/IndexedDB/#index-handle-object-store-handle>
+    object_store: Dom<IDBObjectStore>,
+    #[no_trace]
+    metadata: IndexMetadata,
+}
+
+impl IDBIndex {
+    pub fn new_inherited(object_store: &IDBObjectStore, metadata: IndexMetadata) -> IDBIndex {
+        IDBIndex {
+            reflector_: Reflector::new(),
+            object_store: Dom::from_ref(object_store),
+            metadata,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        object_store: &IDBObjectStore,
+        metadata: IndexMetadata,
+        can_gc: CanGc,
+    ) -> DomRoot<IDBIndex> {
+        reflect_dom_object(
+            Box::new(IDBIndex::new_inherited(object_store, metadata)),
+            global,
+            can_gc,
+        )
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.metadata.name
+    }
+
+    pub fn object_store(&self) -> DomRoot<IDBObjectStore> {
+        self.object_store.as_rooted()
+    }
+
+    /// The checks on the transaction, store and index of the methods that read the index.
+    fn check_readable(&self) -> Fallible<()> {
+        // If index or index's object store has been deleted, throw an "InvalidStateError"
+        // DOMException.
+        if self
+            .object_store
+            .index_metadata(&self.metadata.name)
+            .is_none()
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        self.object_store.check_readable().map(|_| ())
+    }
+
+    fn execute(&self, operation: AsyncOperation, can_gc: CanGc) -> Fallible<DomRoot<IDBRequest>> {
+        IDBRequest::execute_async(
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(DomRoot::from_ref(self)),
+            operation,
+            None,
+            can_gc,
+        )
+    }
+}
+
+impl IDBIndexMethods<crate::DomTypeHolder> for IDBIndex {
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.metadata.name.clone())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-objectstore
+    fn ObjectStore(&self) -> DomRoot<IDBObjectStore> {
+        self.object_store()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-keypath
+    fn KeyPath(&self, cx: SafeJSContext, retval: MutableHandleValue) {
+        key_path_to_jsval(cx, Some(&self.metadata.key_path), retval);
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-multientry
+    fn MultiEntry(&self) -> bool {
+        self.metadata.multi_entry
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-unique
+    fn Unique(&self) -> bool {
+        self.metadata.unique
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-get
+    fn Get(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5: Let range be the result of converting a value to a key range with query
+        // and true. Rethrow any exceptions.
+        let range = convert_value_to_key_range(cx, query, true)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetItem(
+                range,
+                Some(self.metadata.name.clone()),
+            )),
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getkey
+    fn GetKey(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, true)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetKey(
+                range,
+                Some(self.metadata.name.clone()),
+            )),
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getall
+    fn GetAll(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetAll(
+                range,
+                count,
+                Some(self.metadata.name.clone()),
+            )),
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getallkeys
+    fn GetAllKeys(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetAllKeys(
+                range,
+                count,
+                Some(self.metadata.name.clone()),
+            )),
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-count
+    fn Count(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Count(
+                range,
+                Some(self.metadata.name.clone()),
+            )),
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-opencursor
+    fn OpenCursor(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-10
+        IDBCursor::open(
+            IDBObjectStoreOrIDBIndex::IDBIndex(DomRoot::from_ref(self)),
+            range,
+            direction,
+            false,
+            CanGc::note(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-openkeycursor
+    fn OpenKeyCursor(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-10
+        IDBCursor::open(
+            IDBObjectStoreOrIDBIndex::IDBIndex(DomRoot::from_ref(self)),
+            range,
+            direction,
+            true,
+            CanGc::note(),
+        )
+    }
+}
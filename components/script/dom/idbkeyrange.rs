@@ -0,0 +1,190 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsval::UndefinedValue;
+use js::rust::{HandleValue, MutableHandleValue};
+use net_traits::indexeddb_thread::{IndexedDBKeyRange, IndexedDBKeyType};
+
+use crate::dom::bindings::codegen::Bindings::IDBKeyRangeBinding::IDBKeyRangeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::indexed_db::{convert_value_to_key, key_type_to_jsval};
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+#[dom_struct]
+pub struct IDBKeyRange {
+    reflector_: Reflector,
+    #[no_trace]
+    inner: IndexedDBKeyRange,
+}
+
+impl IDBKeyRange {
+    pub fn new_inherited(inner: IndexedDBKeyRange) -> IDBKeyRange {
+        IDBKeyRange {
+            reflector_: Reflector::new(),
+            inner,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        inner: IndexedDBKeyRange,
+        can_gc: CanGc,
+    ) -> DomRoot<IDBKeyRange> {
+        reflect_dom_object(Box::new(IDBKeyRange::new_inherited(inner)), global, can_gc)
+    }
+
+    pub fn inner(&self) -> &IndexedDBKeyRange {
+        &self.inner
+    }
+
+    fn bound_to_jsval(
+        cx: SafeJSContext,
+        bound: &Option<IndexedDBKeyType>,
+        mut retval: MutableHandleValue,
+    ) {
+        match bound {
+            Some(key) => key_type_to_jsval(cx, key, retval),
+            None => retval.set(UndefinedValue()),
+        }
+    }
+}
+
+impl IDBKeyRangeMethods<crate::DomTypeHolder> for IDBKeyRange {
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-lower
+    fn Lower(&self, cx: SafeJSContext, retval: MutableHandleValue) {
+        IDBKeyRange::bound_to_jsval(cx, &self.inner.lower, retval)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upper
+    fn Upper(&self, cx: SafeJSContext, retval: MutableHandleValue) {
+        IDBKeyRange::bound_to_jsval(cx, &self.inner.upper, retval)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-loweropen
+    fn LowerOpen(&self) -> bool {
+        self.inner.lower_open
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upperopen
+    fn UpperOpen(&self) -> bool {
+        self.inner.upper_open
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-only
+    fn Only(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        value: HandleValue,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        // Step 1. Let key be the result of converting a value to a key with value.
+        // Rethrow any exceptions.
+        // Step 2. If key is invalid, throw a "DataError" DOMException.
+        let key = convert_value_to_key(cx, value, None)?;
+
+        // Step 3. Create and return a new key range containing only key.
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange::from(key),
+            CanGc::note(),
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-lowerbound
+    fn LowerBound(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        lower: HandleValue,
+        open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        // Step 1. Let lowerKey be the result of converting a value to a key with lower.
+        // Rethrow any exceptions.
+        // Step 2. If lowerKey is invalid, throw a "DataError" DOMException.
+        let lower_key = convert_value_to_key(cx, lower, None)?;
+
+        // Step 3. Create and return a new key range with lower bound set to lowerKey,
+        // lower open flag set to open, upper bound set to null, and upper open flag set
+        // to true.
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange::lower_bound(lower_key, open),
+            CanGc::note(),
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upperbound
+    fn UpperBound(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        upper: HandleValue,
+        open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        // Step 1. Let upperKey be the result of converting a value to a key with upper.
+        // Rethrow any exceptions.
+        // Step 2. If upperKey is invalid, throw a "DataError" DOMException.
+        let upper_key = convert_value_to_key(cx, upper, None)?;
+
+        // Step 3. Create and return a new key range with lower bound set to null, lower
+        // open flag set to true, upper bound set to upperKey, and upper open flag set
+        // to open.
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange::upper_bound(upper_key, open),
+            CanGc::note(),
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-bound
+    fn Bound(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        lower: HandleValue,
+        upper: HandleValue,
+        lower_open: bool,
+        upper_open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        // Step 1. Let lowerKey be the result of converting a value to a key with lower.
+        // Rethrow any exceptions.
+        // Step 2. If lowerKey is invalid, throw a "DataError" DOMException.
+        let lower_key = convert_value_to_key(cx, lower, None)?;
+
+        // Step 3. Let upperKey be the result of converting a value to a key with upper.
+        // Rethrow any exceptions.
+        // Step 4. If upperKey is invalid, throw a "DataError" DOMException.
+        let upper_key = convert_value_to_key(cx, upper, None)?;
+
+        // Step 5. If lowerKey is greater than upperKey, throw a "DataError" DOMException.
+        if lower_key > upper_key {
+            return Err(Error::Data);
+        }
+
+        // Step 6. Create and return a new key range with lower bound set to lowerKey,
+        // lower open flag set to lowerOpen, upper bound set to upperKey and upper open
+        // flag set to upperOpen.
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange {
+                lower: Some(lower_key),
+                upper: Some(upper_key),
+                lower_open,
+                upper_open,
+            },
+            CanGc::note(),
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-includes
+    fn Includes(&self, cx: SafeJSContext, key: HandleValue) -> Fallible<bool> {
+        // Step 1. Let k be the result of converting a value to a key with key.
+        // Rethrow any exceptions.
+        // Step 2. If k is invalid, throw a "DataError" DOMException.
+        let key = convert_value_to_key(cx, key, None)?;
+
+        // Step 3. Return true if k is in this range, and false otherwise.
+        Ok(self.inner.contains(&key))
+    }
+}
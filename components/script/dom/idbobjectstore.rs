@@ -2,90 +2,70 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::ptr;
+use std::collections::HashMap;
 
+use constellation_traits::StructuredSerializedData;
 use dom_struct::dom_struct;
-use js::conversions::ToJSValConvertible;
-use js::jsapi::{
-    ESClass, GetBuiltinClass, IsArrayBufferObject, JS_DeleteUCProperty,
-    JS_GetOwnUCPropertyDescriptor, JS_GetStringLength, JS_IsArrayBufferViewObject, JSObject,
-    ObjectOpResult, ObjectOpResult_SpecialCodes, PropertyDescriptor,
-};
 use js::jsval::UndefinedValue;
 use js::rust::{HandleValue, MutableHandleValue};
-use log::error;
 use net_traits::IpcSend;
 use net_traits::indexeddb_thread::{
-    AsyncOperation, AsyncReadOnlyOperation, AsyncReadWriteOperation, IndexedDBKeyType,
-    IndexedDBThreadMsg, SyncOperation,
+    AsyncOperation, AsyncReadOnlyOperation, AsyncReadWriteOperation, IndexKeys, IndexMetadata,
+    IndexedDBKeyType, IndexedDBThreadMsg, KeyPath, ObjectStoreMetadata, SyncOperation,
 };
 use profile_traits::ipc;
 
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBObjectStoreParameters;
-use crate::dom::bindings::codegen::Bindings::IDBObjectStoreBinding::IDBObjectStoreMethods;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBObjectStoreBinding::{
+    IDBIndexParameters, IDBObjectStoreMethods,
+};
 use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor,
+};
 // We need to alias this name, otherwise test-tidy complains at &String reference.
 use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence as StrOrStringSequence;
-use crate::dom::bindings::conversions::jsstring_to_str;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::domexception::DOMErrorName;
 use crate::dom::domstringlist::DOMStringList;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbindex::IDBIndex;
 use crate::dom::idbrequest::IDBRequest;
 use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{
+    ExtractionResult, can_inject_key, convert_value_to_key, convert_value_to_key_range,
+    extract_index_keys, extract_key, inject_key, key_path_to_jsval,
+};
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
-#[derive(JSTraceable, MallocSizeOf)]
-pub enum KeyPath {
-    String(DOMString),
-    StringSequence(Vec<DOMString>),
-}
-
 #[dom_struct]
 pub struct IDBObjectStore {
     reflector_: Reflector,
-    name: DomRefCell<DOMString>,
-    key_path: Option<KeyPath>,
-    index_names: DomRoot<DOMStringList>,
+    /// The name, key path, key generator and indexes of the store.
+    #[no_trace]
+    metadata: DomRefCell<ObjectStoreMetadata>,
     transaction: MutNullableDom<IDBTransaction>,
-    auto_increment: bool,
+    /// <https://w3c.github.io/IndexedDB/#object-store-handle-index-set>
+    index_handles: DomRefCell<HashMap<String, Dom<IDBIndex>>>,
 
     // We store the db name in the object store to be able to find the correct
-    // store in the idb thread when checking if we have a key generator
+    // store in the idb thread
     db_name: DOMString,
 }
 
 impl IDBObjectStore {
-    pub fn new_inherited(
-        global: &GlobalScope,
-        db_name: DOMString,
-        name: DOMString,
-        options: Option<&IDBObjectStoreParameters>,
-        can_gc: CanGc,
-    ) -> IDBObjectStore {
-        let key_path: Option<KeyPath> = match options {
-            Some(options) => options.keyPath.as_ref().map(|path| match path {
-                StrOrStringSequence::String(inner) => KeyPath::String(inner.clone()),
-                StrOrStringSequence::StringSequence(inner) => {
-                    KeyPath::StringSequence(inner.clone())
-                },
-            }),
-            None => None,
-        };
-
+    pub fn new_inherited(db_name: DOMString, metadata: ObjectStoreMetadata) -> IDBObjectStore {
         IDBObjectStore {
             reflector_: Reflector::new(),
-            name: DomRefCell::new(name),
-            key_path,
-
-            index_names: DOMStringList::new(global, Vec::new(), can_gc),
+            metadata: DomRefCell::new(metadata),
             transaction: Default::default(),
-            // FIXME:(arihant2math)
-            auto_increment: false,
+            index_handles: Default::default(),
 
             db_name,
         }
@@ -94,21 +74,18 @@ impl IDBObjectStore {
     pub fn new(
         global: &GlobalScope,
         db_name: DOMString,
-        name: DOMString,
-        options: Option<&IDBObjectStoreParameters>,
+        metadata: ObjectStoreMetadata,
         can_gc: CanGc,
     ) -> DomRoot<IDBObjectStore> {
         reflect_dom_object(
-            Box::new(IDBObjectStore::new_inherited(
-                global, db_name, name, options, can_gc,
-            )),
+            Box::new(IDBObjectStore::new_inherited(db_name, metadata)),
             global,
             can_gc,
         )
     }
 
     pub fn get_name(&self) -> DOMString {
-        self.name.borrow().clone()
+        DOMString::from(self.metadata.borrow().name.clone())
     }
 
     pub fn set_transaction(&self, transaction: &IDBTransaction) {
@@ -119,6 +96,19 @@ impl IDBObjectStore {
         self.transaction.get()
     }
 
+    pub fn key_path(&self) -> Option<KeyPath> {
+        self.metadata.borrow().key_path.clone()
+    }
+
+    pub fn index_metadata(&self, name: &str) -> Option<IndexMetadata> {
+        self.metadata
+            .borrow()
+            .indexes
+            .iter()
+            .find(|index| index.name == name)
+            .cloned()
+    }
+
     // https://www.w3.org/TR/IndexedDB-2/#valid-key-path
     pub fn is_valid_key_path(key_path: &StrOrStringSequence) -> bool {
         fn is_identifier(_s: &str) -> bool {
@@ -142,248 +132,100 @@ impl IDBObjectStore {
         }
     }
 
-    #[allow(unsafe_code)]
-    // https://www.w3.org/TR/IndexedDB-2/#convert-value-to-key
-    fn convert_value_to_key(
-        cx: SafeJSContext,
-        input: HandleValue,
-        seen: Option<Vec<HandleValue>>,
-    ) -> Result<IndexedDBKeyType, Error> {
-        // Step 1: If seen was not given, then let seen be a new empty set.
-        let _seen = seen.unwrap_or_default();
-
-        // Step 2: If seen contains input, then return invalid.
-        // FIXME:(arihant2math) implement this
-        // Check if we have seen this key
-        // Does not currently work with HandleValue,
-        // as it does not implement PartialEq
-
-        // Step 3
-        // FIXME:(arihant2math) Accept buffer, array and date as well
-        if input.is_number() {
-            // FIXME:(arihant2math) check for NaN
-            return Ok(IndexedDBKeyType::Number(input.to_number()));
-        }
-
-        if input.is_string() {
-            let string_ptr = std::ptr::NonNull::new(input.to_string()).unwrap();
-            let key = unsafe { jsstring_to_str(*cx, string_ptr).str().to_string() };
-            return Ok(IndexedDBKeyType::String(key));
+    pub fn convert_key_path(key_path: &StrOrStringSequence) -> KeyPath {
+        match key_path {
+            StrOrStringSequence::String(path) => KeyPath::String(path.to_string()),
+            StrOrStringSequence::StringSequence(paths) => {
+                KeyPath::Sequence(paths.iter().map(|path| path.to_string()).collect())
+            },
         }
+    }
 
-        if input.is_object() {
-            rooted!(in(*cx) let object = input.to_object());
-            unsafe {
-                let mut built_in_class = ESClass::Other;
-
-                if !GetBuiltinClass(*cx, object.handle().into(), &mut built_in_class) {
-                    return Err(Error::Data);
-                }
+    // https://w3c.github.io/IndexedDB/#object-store-handle-object-store
+    // Whether the object store was deleted in the versionchange transaction.
+    fn is_deleted(&self, transaction: &IDBTransaction) -> bool {
+        !transaction
+            .get_db()
+            .has_object_store(&self.metadata.borrow().name)
+    }
 
-                if let ESClass::Date = built_in_class {
-                    // FIXME:(arihant2math) implement it the correct way
-                    let key =
-                        structuredclone::write(cx, input, None).expect("Could not serialize key");
-                    return Ok(IndexedDBKeyType::Date(key.serialized.clone()));
-                }
+    /// The checks on the transaction and store of the methods that read the store,
+    /// returning the transaction.
+    pub fn check_readable(&self) -> Fallible<DomRoot<IDBTransaction>> {
+        // Let transaction be this's transaction.
+        let transaction = self
+            .transaction
+            .get()
+            .expect("No transaction in Object Store");
 
-                if IsArrayBufferObject(*object) || JS_IsArrayBufferViewObject(*object) {
-                    // FIXME:(arihant2math)
-                    error!("Array buffers as keys is currently unsupported");
-                    return Err(Error::NotSupported);
-                }
+        // If store has been deleted, throw an "InvalidStateError" DOMException.
+        if self.is_deleted(&transaction) {
+            return Err(Error::InvalidState);
+        }
 
-                if let ESClass::Array = built_in_class {
-                    // FIXME:(arihant2math)
-                    unimplemented!("Arrays as keys is currently unsupported");
-                }
-            }
+        // If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
         }
 
-        Err(Error::Data)
+        Ok(transaction)
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#evaluate-a-key-path-on-a-value
-    #[allow(unsafe_code)]
-    fn evaluate_key_path_on_value(
-        cx: SafeJSContext,
-        value: HandleValue,
-        mut return_val: MutableHandleValue,
-        key_path: &KeyPath,
-    ) {
-        // The implementation is translated from gecko:
-        // https://github.com/mozilla/gecko-dev/blob/master/dom/indexedDB/KeyPath.cpp
-        return_val.set(*value);
-
-        rooted!(in(*cx) let mut target_object = ptr::null_mut::<JSObject>());
-        rooted!(in(*cx) let mut current_val = *value);
-        rooted!(in(*cx) let mut object = ptr::null_mut::<JSObject>());
+    /// The checks of the methods that change the store, which fail for read-only
+    /// transactions as well.
+    pub fn check_writable(&self) -> Fallible<DomRoot<IDBTransaction>> {
+        let transaction = self.check_readable()?;
 
-        let mut target_object_prop_name: Option<String> = None;
-
-        match key_path {
-            KeyPath::String(path) => {
-                // Step 3
-                let path_as_string = path.to_string();
-                let mut tokenizer = path_as_string.split('.').peekable();
-
-                while let Some(token) = tokenizer.next() {
-                    if target_object.get().is_null() {
-                        if token == "length" &&
-                            tokenizer.peek().is_none() &&
-                            current_val.is_string()
-                        {
-                            rooted!(in(*cx) let input_val = current_val.to_string());
-                            unsafe {
-                                let string_len = JS_GetStringLength(*input_val) as u64;
-                                string_len.to_jsval(*cx, return_val);
-                            }
-                            break;
-                        }
-
-                        if !current_val.is_object() {
-                            // FIXME:(rasviitanen) Return a proper error
-                            return;
-                        }
-
-                        object.handle_mut().set(current_val.to_object());
-                        rooted!(in(*cx) let mut desc = PropertyDescriptor::default());
-                        rooted!(in(*cx) let mut intermediate = UndefinedValue());
-
-                        // So rust says that this value is never read, but it is.
-                        #[allow(unused)]
-                        let mut has_prop = false;
-
-                        unsafe {
-                            let prop_name_as_utf16: Vec<u16> = token.encode_utf16().collect();
-                            let mut is_descriptor_none: bool = false;
-                            let ok = JS_GetOwnUCPropertyDescriptor(
-                                *cx,
-                                object.handle().into(),
-                                prop_name_as_utf16.as_ptr(),
-                                prop_name_as_utf16.len(),
-                                desc.handle_mut().into(),
-                                &mut is_descriptor_none,
-                            );
-
-                            if !ok {
-                                // FIXME:(arihant2math) Handle this
-                                return;
-                            }
-
-                            if desc.hasWritable_() || desc.hasValue_() {
-                                intermediate.handle_mut().set(desc.handle().value_);
-                                has_prop = true;
-                            } else {
-                                // If we get here it means the object doesn't have the property or the
-                                // property is available throuch a getter. We don't want to call any
-                                // getters to avoid potential re-entrancy.
-                                // The blob object is special since its properties are available
-                                // only through getters but we still want to support them for key
-                                // extraction. So they need to be handled manually.
-                                unimplemented!("Blob tokens are not yet supported");
-                            }
-                        }
-
-                        if has_prop {
-                            // Treat undefined as an error
-                            if intermediate.is_undefined() {
-                                // FIXME:(rasviitanen) Throw/return error
-                                return;
-                            }
-
-                            if tokenizer.peek().is_some() {
-                                // ...and walk to it if there are more steps...
-                                current_val.handle_mut().set(*intermediate);
-                            } else {
-                                // ...otherwise use it as key
-                                return_val.set(*intermediate);
-                            }
-                        } else {
-                            target_object.handle_mut().set(*object);
-                            target_object_prop_name = Some(token.to_string());
-                        }
-                    }
-
-                    if !target_object.get().is_null() {
-                        // We have started inserting new objects or are about to just insert
-                        // the first one.
-                        // FIXME:(rasviitanen) Implement this piece
-                        unimplemented!("keyPath tokens that requires insertion are not supported.");
-                    }
-                } // All tokens processed
-
-                if !target_object.get().is_null() {
-                    // If this fails, we lose, and the web page sees a magical property
-                    // appear on the object :-(
-                    unsafe {
-                        let prop_name_as_utf16: Vec<u16> =
-                            target_object_prop_name.unwrap().encode_utf16().collect();
-                        #[allow(clippy::cast_enum_truncation)]
-                        let mut succeeded = ObjectOpResult {
-                            code_: ObjectOpResult_SpecialCodes::Uninitialized as usize,
-                        };
-                        if !JS_DeleteUCProperty(
-                            *cx,
-                            target_object.handle().into(),
-                            prop_name_as_utf16.as_ptr(),
-                            prop_name_as_utf16.len(),
-                            &mut succeeded,
-                        ) {
-                            // FIXME:(rasviitanen) Throw/return error
-                            // return;
-                        }
-                    }
-                }
-            },
-            KeyPath::StringSequence(_) => {
-                unimplemented!("String sequence keyPath is currently unsupported");
-            },
+        // If transaction is a read-only transaction, throw a "ReadOnlyError" DOMException.
+        if let IDBTransactionMode::Readonly = transaction.get_mode() {
+            return Err(Error::ReadOnly);
         }
-    }
-
-    fn has_key_generator(&self) -> bool {
-        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
-
-        let operation = SyncOperation::HasKeyGenerator(
-            sender,
-            self.global().origin().immutable().clone(),
-            self.db_name.to_string(),
-            self.name.borrow().to_string(),
-        );
 
-        self.global()
-            .resource_threads()
-            .sender()
-            .send(IndexedDBThreadMsg::Sync(operation))
-            .unwrap();
+        Ok(transaction)
+    }
 
-        receiver.recv().unwrap()
+    /// The keys of a value in each index of the store, which are stored with its record.
+    pub fn index_keys(&self, cx: SafeJSContext, value: HandleValue) -> Fallible<IndexKeys> {
+        self.metadata
+            .borrow()
+            .indexes
+            .iter()
+            .map(|index| Ok((index.name.clone(), extract_index_keys(cx, value, index)?)))
+            .collect()
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#extract-a-key-from-a-value-using-a-key-path
-    fn extract_key(
+    /// Deserializes the value of a record of this store. Keys that were generated by the key
+    /// generator are injected into the value, see step 2 of
+    /// <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>.
+    pub fn deserialize_value(
+        &self,
         cx: SafeJSContext,
-        input: HandleValue,
-        key_path: &KeyPath,
-        multi_entry: Option<bool>,
-    ) -> Result<IndexedDBKeyType, Error> {
-        // Step 1: Evaluate key path
-        // FIXME:(rasviitanen) Do this propertly
-        rooted!(in(*cx) let mut r = UndefinedValue());
-        IDBObjectStore::evaluate_key_path_on_value(cx, input, r.handle_mut(), key_path);
-
-        if let Some(_multi_entry) = multi_entry {
-            // FIXME:(rasviitanen) handle multi_entry cases
-            unimplemented!("multiEntry keys are not yet supported");
-        } else {
-            IDBObjectStore::convert_value_to_key(cx, r.handle(), None)
+        value: Vec<u8>,
+        primary_key: &IndexedDBKeyType,
+        rval: MutableHandleValue,
+    ) -> Fallible<()> {
+        let data = StructuredSerializedData {
+            serialized: value,
+            ..Default::default()
+        };
+        structuredclone::read(&self.global(), data, rval)?;
+
+        let metadata = self.metadata.borrow();
+        if let (true, Some(KeyPath::String(key_path))) =
+            (metadata.auto_increment, &metadata.key_path)
+        {
+            inject_key(cx, rval.handle(), primary_key, key_path);
         }
+        Ok(())
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#object-store-in-line-keys
-    fn uses_inline_keys(&self) -> bool {
-        self.key_path.is_some()
+    fn execute(&self, operation: AsyncOperation, can_gc: CanGc) -> Fallible<DomRoot<IDBRequest>> {
+        IDBRequest::execute_async(
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(DomRoot::from_ref(self)),
+            operation,
+            None,
+            can_gc,
+        )
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-put
@@ -396,71 +238,64 @@ impl IDBObjectStore {
         can_gc: CanGc,
     ) -> Fallible<DomRoot<IDBRequest>> {
         // Step 1: Let transaction be this object store handle's transaction.
-        let transaction = self
-            .transaction
-            .get()
-            .expect("No transaction in Object Store");
-
         // Step 2: Let store be this object store handle's object store.
-        // This is resolved in the `execute_async` function.
-
         // Step 3: If store has been deleted, throw an "InvalidStateError" DOMException.
-        // FIXME:(rasviitanen)
-
-        // Step 4-5: If transaction is not active, throw a "TransactionInactiveError" DOMException.
-        if !transaction.is_active() {
-            return Err(Error::TransactionInactive);
-        }
-
+        // Step 4: If transaction is not active, throw a "TransactionInactiveError" DOMException.
         // Step 5: If transaction is a read-only transaction, throw a "ReadOnlyError" DOMException.
-        if let IDBTransactionMode::Readonly = transaction.get_mode() {
-            return Err(Error::ReadOnly);
-        }
+        self.check_writable()?;
+
+        let key_path = self.key_path();
+        let has_key_generator = self.metadata.borrow().auto_increment;
 
         // Step 6: If store uses in-line keys and key was given, throw a "DataError" DOMException.
-        if !key.is_undefined() && self.uses_inline_keys() {
+        if !key.is_undefined() && key_path.is_some() {
             return Err(Error::Data);
         }
 
         // Step 7: If store uses out-of-line keys and has no key generator
         // and key was not given, throw a "DataError" DOMException.
-        if !self.uses_inline_keys() && !self.has_key_generator() && key.is_undefined() {
+        if key_path.is_none() && !has_key_generator && key.is_undefined() {
             return Err(Error::Data);
         }
 
         // Step 8: If key was given, then: convert a value to a key with key
-        let serialized_key: IndexedDBKeyType;
-
+        let mut serialized_key = None;
         if !key.is_undefined() {
-            serialized_key = IDBObjectStore::convert_value_to_key(cx, key, None)?;
-        } else {
-            // Step 11: We should use in-line keys instead
-            if let Ok(kpk) = IDBObjectStore::extract_key(
-                cx,
-                value,
-                self.key_path.as_ref().expect("No key path"),
-                None,
-            ) {
-                serialized_key = kpk;
-            } else {
-                // FIXME:(rasviitanen)
-                // Check if store has a key generator
-                // Check if we can inject a key
-                return Err(Error::Data);
+            serialized_key = Some(convert_value_to_key(cx, key, None)?);
+        }
+
+        // Step 9-10: Let clone be a clone of value.
+        let serialized_value = structuredclone::write(cx, value, None)?;
+
+        // Step 11: If store uses in-line keys, then:
+        if let Some(key_path) = &key_path {
+            match extract_key(cx, value, key_path)? {
+                ExtractionResult::Key(key) => serialized_key = Some(key),
+                // If kpk is invalid, throw a "DataError" DOMException.
+                ExtractionResult::Invalid => return Err(Error::Data),
+                // Otherwise, if store has no key generator, or the key generator can not
+                // inject a key into the value, throw a "DataError" DOMException.
+                ExtractionResult::Failure => match key_path {
+                    KeyPath::String(path)
+                        if has_key_generator && can_inject_key(cx, value, path) => {},
+                    _ => return Err(Error::Data),
+                },
             }
         }
 
-        let serialized_value =
-            structuredclone::write(cx, value, None).expect("Could not serialize value");
+        let index_keys = self.index_keys(cx, value)?;
 
-        IDBRequest::execute_async(
-            self,
+        // Step 12: Let operation be an algorithm to run store a record into an object
+        // store with store, clone, key, and no-overwrite flag.
+        // Step 13: Return the result of asynchronously executing a request with handle
+        // and operation.
+        self.execute(
             AsyncOperation::ReadWrite(AsyncReadWriteOperation::PutItem(
                 serialized_key,
                 serialized_value.serialized,
                 overwrite,
+                index_keys,
             )),
-            None,
             can_gc,
         )
     }
@@ -489,115 +324,417 @@ impl IDBObjectStoreMethods<crate::DomTypeHolder> for IDBObjectStore {
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-delete
     fn Delete(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
-        let serialized_query = IDBObjectStore::convert_value_to_key(cx, query, None);
-        serialized_query.and_then(|q| {
-            IDBRequest::execute_async(
-                self,
-                AsyncOperation::ReadWrite(AsyncReadWriteOperation::RemoveItem(q)),
-                None,
-                CanGc::note(),
-            )
-        })
+        // Steps 1-6
+        self.check_writable()?;
+
+        // Step 7: Let range be the result of converting a value to a key range with query
+        // and true. Rethrow any exceptions.
+        let range = convert_value_to_key_range(cx, query, true)?;
+
+        // Steps 8-9
+        self.execute(
+            AsyncOperation::ReadWrite(AsyncReadWriteOperation::RemoveItem(range)),
+            CanGc::note(),
+        )
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-clear
     fn Clear(&self) -> Fallible<DomRoot<IDBRequest>> {
-        unimplemented!();
+        // Steps 1-5
+        self.check_writable()?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadWrite(AsyncReadWriteOperation::Clear),
+            CanGc::note(),
+        )
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-get
     fn Get(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
-        let serialized_query = IDBObjectStore::convert_value_to_key(cx, query, None);
-        serialized_query.and_then(|q| {
-            IDBRequest::execute_async(
-                self,
-                AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetItem(q)),
-                None,
-                CanGc::note(),
-            )
-        })
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5: Let range be the result of converting a value to a key range with query
+        // and true. Rethrow any exceptions.
+        let range = convert_value_to_key_range(cx, query, true)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetItem(range, None)),
+            CanGc::note(),
+        )
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-getkey
-    // fn GetKey(&self, _cx: SafeJSContext, _query: HandleValue) -> DomRoot<IDBRequest> {
-    //     unimplemented!();
-    // }
+    fn GetKey(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, true)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetKey(range, None)),
+            CanGc::note(),
+        )
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-getall
-    // fn GetAll(
-    //     &self,
-    //     _cx: SafeJSContext,
-    //     _query: HandleValue,
-    //     _count: Option<u32>,
-    // ) -> DomRoot<IDBRequest> {
-    //     unimplemented!();
-    // }
+    fn GetAll(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetAll(range, count, None)),
+            CanGc::note(),
+        )
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-getallkeys
-    // fn GetAllKeys(
-    //     &self,
-    //     _cx: SafeJSContext,
-    //     _query: HandleValue,
-    //     _count: Option<u32>,
-    // ) -> DomRoot<IDBRequest> {
-    //     unimplemented!();
-    // }
+    fn GetAllKeys(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::GetAllKeys(range, count, None)),
+            CanGc::note(),
+        )
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-count
     fn Count(&self, cx: SafeJSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
-        // Step 1
-        let transaction = self.transaction.get().expect("Could not get transaction");
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-7
+        self.execute(
+            AsyncOperation::ReadOnly(AsyncReadOnlyOperation::Count(range, None)),
+            CanGc::note(),
+        )
+    }
 
-        // Step 2
-        // FIXME(arihant2math): investigate further
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-opencursor
+    fn OpenCursor(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-10
+        IDBCursor::open(
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(DomRoot::from_ref(self)),
+            range,
+            direction,
+            false,
+            CanGc::note(),
+        )
+    }
 
-        // Step 3
-        // FIXME(arihant2math): Cannot tell if store has been deleted
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-openkeycursor
+    fn OpenKeyCursor(
+        &self,
+        cx: SafeJSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 1-4
+        self.check_readable()?;
+
+        // Step 5
+        let range = convert_value_to_key_range(cx, query, false)?;
+
+        // Steps 6-10
+        IDBCursor::open(
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(DomRoot::from_ref(self)),
+            range,
+            direction,
+            true,
+            CanGc::note(),
+        )
+    }
 
-        // Step 4
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-index
+    fn Index(&self, name: DOMString) -> Fallible<DomRoot<IDBIndex>> {
+        // Step 1: Let transaction be this's transaction.
+        let transaction = self
+            .transaction
+            .get()
+            .expect("No transaction in Object Store");
+
+        // Step 3: If store has been deleted, or if transaction has finished, throw an
+        // "InvalidStateError" DOMException.
+        if self.is_deleted(&transaction) || transaction.is_finished() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4: Let index be the index named name in this's index set if one exists,
+        // or throw a "NotFoundError" DOMException otherwise.
+        let Some(metadata) = self.index_metadata(&name) else {
+            return Err(Error::NotFound);
+        };
+
+        // Step 5: Return an index handle associated with index and this.
+        let mut index_handles = self.index_handles.borrow_mut();
+        let index = index_handles.entry(name.to_string()).or_insert_with(|| {
+            let index = IDBIndex::new(&self.global(), self, metadata, CanGc::note());
+            Dom::from_ref(&*index)
+        });
+        Ok(DomRoot::from_ref(&**index))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-createindex
+    fn CreateIndex(
+        &self,
+        cx: SafeJSContext,
+        name: DOMString,
+        key_path: StrOrStringSequence,
+        options: &IDBIndexParameters,
+    ) -> Fallible<DomRoot<IDBIndex>> {
+        // Step 1: Let transaction be this's transaction.
+        let transaction = self
+            .transaction
+            .get()
+            .expect("No transaction in Object Store");
+
+        // Step 3: If transaction is not an upgrade transaction, throw an
+        // "InvalidStateError" DOMException.
+        if transaction.get_mode() != IDBTransactionMode::Versionchange {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4: If store has been deleted, throw an "InvalidStateError" DOMException.
+        if self.is_deleted(&transaction) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5: If transaction is not active, throw a "TransactionInactiveError" DOMException.
         if !transaction.is_active() {
             return Err(Error::TransactionInactive);
         }
 
-        // Step 5
-        let _serialized_query = IDBObjectStore::convert_value_to_key(cx, query, None);
+        // Step 6: If an index named name already exists in store, throw a
+        // "ConstraintError" DOMException.
+        if self.index_metadata(&name).is_some() {
+            return Err(Error::Constraint);
+        }
+
+        // Step 7: If keyPath is not a valid key path, throw a "SyntaxError" DOMException.
+        if !IDBObjectStore::is_valid_key_path(&key_path) {
+            return Err(Error::Syntax);
+        }
 
-        // Step 6
-        // match serialized_query {
-        //     Ok(q) => IDBRequest::execute_async(&*self, AsyncOperation::Count(q), None),
-        //     Err(e) => Err(e),
-        // }
-        Err(Error::NotSupported)
+        // Step 8-9: If keyPath is a sequence and multiEntry is true, throw an
+        // "InvalidAccessError" DOMException.
+        if let StrOrStringSequence::StringSequence(_) = key_path {
+            if options.multiEntry {
+                return Err(Error::InvalidAccess);
+            }
+        }
+
+        // Step 10: Let index be a new index in store.
+        let metadata = IndexMetadata {
+            name: name.to_string(),
+            key_path: IDBObjectStore::convert_key_path(&key_path),
+            unique: options.unique,
+            multi_entry: options.multiEntry,
+        };
+
+        // The index is populated with the records that are already in the store.
+        // The key paths are evaluated on the values here, as the idb thread can not
+        // deserialize them.
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let operation = SyncOperation::GetAllRecords(
+            sender,
+            global.origin().immutable().clone(),
+            self.db_name.to_string(),
+            self.metadata.borrow().name.clone(),
+        );
+        global
+            .resource_threads()
+            .sender()
+            .send(IndexedDBThreadMsg::Sync(operation))
+            .unwrap();
+        let records = receiver
+            .recv()
+            .expect("Could not receive the records of the object store")
+            .map_err(|_| Error::InvalidState)?;
+
+        let mut keys = Vec::with_capacity(records.len());
+        for (primary_key, value) in records {
+            rooted!(in(*cx) let mut deserialized = UndefinedValue());
+            self.deserialize_value(cx, value, &primary_key, deserialized.handle_mut())?;
+            keys.push((
+                primary_key,
+                extract_index_keys(cx, deserialized.handle(), &metadata)?,
+            ));
+        }
+
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let operation = SyncOperation::CreateIndex(
+            sender,
+            global.origin().immutable().clone(),
+            self.db_name.to_string(),
+            self.metadata.borrow().name.clone(),
+            metadata.clone(),
+            keys,
+        );
+        global
+            .resource_threads()
+            .sender()
+            .send(IndexedDBThreadMsg::Sync(operation))
+            .unwrap();
+
+        // If the records of the store violate the unique flag of the index, the index is
+        // not created and the upgrade transaction is aborted with a "ConstraintError".
+        match receiver
+            .recv()
+            .expect("Could not receive index creation status")
+        {
+            Ok(()) => {},
+            Err(_) => transaction.abort(Some(DOMErrorName::ConstraintError)),
+        }
+
+        self.metadata.borrow_mut().indexes.push(metadata.clone());
+
+        // Step 11: Add index to this's index set.
+        // Step 12: Return a new index handle associated with index and this.
+        let index = IDBIndex::new(&global, self, metadata, CanGc::note());
+        self.index_handles
+            .borrow_mut()
+            .insert(name.to_string(), Dom::from_ref(&*index));
+        Ok(index)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-deleteindex
+    fn DeleteIndex(&self, name: DOMString) -> Fallible<()> {
+        // Step 1: Let transaction be this's transaction.
+        let transaction = self
+            .transaction
+            .get()
+            .expect("No transaction in Object Store");
+
+        // Step 3: If transaction is not an upgrade transaction, throw an
+        // "InvalidStateError" DOMException.
+        if transaction.get_mode() != IDBTransactionMode::Versionchange {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4: If store has been deleted, throw an "InvalidStateError" DOMException.
+        if self.is_deleted(&transaction) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5: If transaction is not active, throw a "TransactionInactiveError" DOMException.
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 6: Let index be the index named name in store if one exists, or throw a
+        // "NotFoundError" DOMException otherwise.
+        if self.index_metadata(&name).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        // Step 7: Remove index from this's index set.
+        self.metadata
+            .borrow_mut()
+            .indexes
+            .retain(|index| *index.name != *name);
+        self.index_handles.borrow_mut().remove(&*name);
+
+        // Step 8: Destroy index.
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let operation = SyncOperation::DeleteIndex(
+            sender,
+            global.origin().immutable().clone(),
+            self.db_name.to_string(),
+            self.metadata.borrow().name.clone(),
+            name.to_string(),
+        );
+        global
+            .resource_threads()
+            .sender()
+            .send(IndexedDBThreadMsg::Sync(operation))
+            .unwrap();
+
+        if receiver
+            .recv()
+            .expect("Could not receive index deletion status")
+            .is_err()
+        {
+            warn!("Index deletion failed in idb thread");
+        }
+        Ok(())
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-name
     fn Name(&self) -> DOMString {
-        self.name.borrow().clone()
+        self.get_name()
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-setname
     fn SetName(&self, value: DOMString) {
-        *self.name.borrow_mut() = value;
+        self.metadata.borrow_mut().name = value.to_string();
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-keypath
-    // fn KeyPath(&self, _cx: SafeJSContext, _val: MutableHandleValue) {
-    //     unimplemented!();
-    // }
+    fn KeyPath(&self, cx: SafeJSContext, retval: MutableHandleValue) {
+        key_path_to_jsval(cx, self.metadata.borrow().key_path.as_ref(), retval);
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-indexnames
-    // fn IndexNames(&self) -> DomRoot<DOMStringList> {
-    //     unimplemented!();
-    // }
+    fn IndexNames(&self) -> DomRoot<DOMStringList> {
+        let mut names: Vec<DOMString> = self
+            .metadata
+            .borrow()
+            .indexes
+            .iter()
+            .map(|index| DOMString::from(index.name.clone()))
+            .collect();
+        names.sort();
+        DOMStringList::new(&self.global(), names, CanGc::note())
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-transaction
-    // fn Transaction(&self) -> DomRoot<IDBTransaction> {
-    //     unimplemented!();
-    // }
+    fn Transaction(&self) -> DomRoot<IDBTransaction> {
+        self.transaction
+            .get()
+            .expect("No transaction in Object Store")
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbobjectstore-autoincrement
     fn AutoIncrement(&self) -> bool {
-        // FIXME(arihant2math): This is wrong
-        self.auto_increment
+        self.metadata.borrow().auto_increment
     }
 }
@@ -7,10 +7,11 @@ use ipc_channel::router::ROUTER;
 use js::jsval::UndefinedValue;
 use js::rust::HandleValue;
 use net_traits::IpcSend;
-use net_traits::indexeddb_thread::{IndexedDBThreadMsg, SyncOperation};
+use net_traits::indexeddb_thread::{BackendResult, IndexedDBThreadMsg, SyncOperation};
 use profile_traits::ipc;
 use stylo_atoms::Atom;
 
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBTransactionDurability;
 use crate::dom::bindings::codegen::Bindings::IDBOpenDBRequestBinding::IDBOpenDBRequestMethods;
 use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
 use crate::dom::bindings::error::{Error, Fallible};
@@ -19,6 +20,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::domexception::DOMErrorName;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::idbdatabase::IDBDatabase;
@@ -40,9 +42,15 @@ impl OpenRequestListener {
         &self,
         name: String,
         request_version: Option<u64>,
-        db_version: u64,
+        db_version: BackendResult<u64>,
         can_gc: CanGc,
     ) -> (Fallible<DomRoot<IDBDatabase>>, bool) {
+        // The database could not be opened, as when IndexedDB is unavailable in private
+        // browsing.
+        let Ok(db_version) = db_version else {
+            return (Err(Error::InvalidState), false);
+        };
+
         // Step 5-6
         let request_version = match request_version {
             Some(v) => v,
@@ -149,9 +157,11 @@ impl IDBOpenDBRequest {
             &global,
             connection,
             IDBTransactionMode::Versionchange,
+            IDBTransactionDurability::Default,
             &connection.object_stores(),
             can_gc,
         );
+        transaction.set_open_request(self);
 
         // Step 3
         connection.set_transaction(&transaction);
@@ -208,10 +218,10 @@ impl IDBOpenDBRequest {
                 // Step 8.6
                 txn.set_active_flag(false);
 
-                // Implementation specific: we fire the success on db here
-                // to make sure the success event occurs after the upgrade event.
-                txn.wait();
-                this.dispatch_success(&conn);
+                // The transaction commits once the requests placed in the
+                // upgradeneeded handlers have finished, and then dispatches
+                // the success event of this request.
+                txn.maybe_commit();
             }),
         );
 
@@ -224,7 +234,35 @@ impl IDBOpenDBRequest {
     }
 
     pub fn set_error(&self, error: Error, can_gc: CanGc) {
-        self.idbrequest.set_error(error, can_gc);
+        // FIXME:(rasviitanen) Support all error types
+        match error {
+            Error::Version => self
+                .idbrequest
+                .set_error(Some(DOMErrorName::VersionError), can_gc),
+            Error::InvalidState => self
+                .idbrequest
+                .set_error(Some(DOMErrorName::InvalidStateError), can_gc),
+            _ => {},
+        }
+    }
+
+    /// Fails the request because its upgrade transaction was aborted, see step 10.7 of
+    /// <https://w3c.github.io/IndexedDB/#open-a-database-connection>.
+    pub fn dispatch_abort(&self) {
+        self.idbrequest.set_ready_state_done();
+        self.set_result(HandleValue::undefined());
+        self.idbrequest
+            .set_error(Some(DOMErrorName::AbortError), CanGc::note());
+
+        let global = self.global();
+        let event = Event::new(
+            &global,
+            Atom::from("error"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            CanGc::note(),
+        );
+        event.fire(self.upcast(), CanGc::note());
     }
 
     pub fn open_database(&self, name: DOMString, version: Option<u64>) {
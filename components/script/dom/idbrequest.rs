@@ -4,33 +4,41 @@
 
 use std::cell::Cell;
 
-use constellation_traits::StructuredSerializedData;
 use dom_struct::dom_struct;
 use ipc_channel::router::ROUTER;
-use js::jsapi::Heap;
-use js::jsval::{JSVal, UndefinedValue};
-use js::rust::HandleValue;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSPROP_ENUMERATE, NewArrayObject1};
+use js::jsval::{DoubleValue, JSVal, NullValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::JS_DefineElement;
+use js::rust::{HandleValue, MutableHandleValue};
 use net_traits::IpcSend;
-use net_traits::indexeddb_thread::{AsyncOperation, IndexedDBThreadMsg, IndexedDBTxnMode};
+use net_traits::indexeddb_thread::{
+    AsyncOperation, AsyncOperationResult, BackendResult, IndexedDBKeyType, IndexedDBThreadMsg,
+    IndexedDBTxnMode,
+};
 use profile_traits::ipc;
 use stylo_atoms::Atom;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::IDBRequestBinding::{
     IDBRequestMethods, IDBRequestReadyState,
 };
 use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::codegen::UnionTypes::IDBObjectStoreOrIDBIndexOrIDBCursor;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::domexception::{DOMErrorName, DOMException};
-use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbindex::IDBIndex;
 use crate::dom::idbobjectstore::IDBObjectStore;
-use crate::dom::idbtransaction::IDBTransaction;
+use crate::dom::idbtransaction::{IDBTransaction, backend_error_name};
+use crate::indexed_db::key_type_to_jsval;
 use crate::realms::enter_realm;
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
@@ -40,83 +48,115 @@ struct RequestListener {
 }
 
 impl RequestListener {
-    fn handle_async_request_finished(&self, result: Option<Vec<u8>>) {
+    fn handle_async_request_finished(&self, result: BackendResult<AsyncOperationResult>) {
         let request = self.request.root();
         let global = request.global();
         let cx = GlobalScope::get_cx();
 
+        let transaction = request
+            .transaction
+            .get()
+            .expect("Request unexpectedly has no transaction");
+
+        // The requests of an aborted transaction already failed with an "AbortError".
+        if transaction.is_finished() {
+            return;
+        }
+        transaction.request_finished();
+
         request.set_ready_state_done();
 
         let _ac = enter_realm(&*request);
         rooted!(in(*cx) let mut answer = UndefinedValue());
 
-        if let Some(serialized_data) = result {
-            let data = StructuredSerializedData {
-                serialized: serialized_data,
-                ..Default::default()
-            };
-
-            if structuredclone::read(&global, data, answer.handle_mut()).is_err() {
-                warn!("Error reading structuredclone data");
-            }
-
-            request.set_result(answer.handle());
-
-            let transaction = request
-                .transaction
-                .get()
-                .expect("Request unexpectedly has no transaction");
-
-            let event = Event::new(
-                &global,
-                Atom::from("success"),
-                EventBubbles::DoesNotBubble,
-                EventCancelable::NotCancelable,
-                CanGc::note(),
-            );
-
-            transaction.set_active_flag(true);
-            event
-                .upcast::<Event>()
-                .fire(request.upcast(), CanGc::note());
-            transaction.set_active_flag(false);
-        } else {
-            request.set_result(answer.handle());
-
-            // FIXME:(rasviitanen)
-            // Set the error of request to result
-
-            let transaction = request
-                .transaction
-                .get()
-                .expect("Request has no transaction");
-
-            let event = Event::new(
-                &global,
-                Atom::from("error"),
-                EventBubbles::Bubbles,
-                EventCancelable::Cancelable,
-                CanGc::note(),
-            );
-
-            transaction.set_active_flag(true);
-            event
-                .upcast::<Event>()
-                .fire(request.upcast(), CanGc::note());
-            transaction.set_active_flag(false);
+        let result = result
+            .map_err(|error| backend_error_name(&error))
+            .and_then(|result| {
+                request
+                    .convert_result(cx, result, answer.handle_mut())
+                    .map_err(|_| DOMErrorName::DataCloneError)
+            });
+
+        match result {
+            Ok(()) => {
+                request.set_result(answer.handle());
+
+                // https://w3c.github.io/IndexedDB/#fire-a-success-event
+                let event = Event::new(
+                    &global,
+                    Atom::from("success"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    CanGc::note(),
+                );
+
+                transaction.set_active_flag(true);
+                event
+                    .upcast::<Event>()
+                    .fire(request.upcast(), CanGc::note());
+                transaction.set_active_flag(false);
+            },
+            Err(error) => {
+                request.set_result(HandleValue::undefined());
+                request.set_error(Some(error), CanGc::note());
+
+                // https://w3c.github.io/IndexedDB/#fire-an-error-event
+                let event = Event::new(
+                    &global,
+                    Atom::from("error"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::Cancelable,
+                    CanGc::note(),
+                );
+
+                transaction.set_active_flag(true);
+                let status = event
+                    .upcast::<Event>()
+                    .fire(request.upcast(), CanGc::note());
+                transaction.set_active_flag(false);
+
+                // If the event was not canceled, run abort a transaction with transaction
+                // and request's error.
+                if let EventStatus::NotCanceled = status {
+                    transaction.abort(Some(error));
+                }
+            },
         }
+
+        transaction.maybe_commit();
     }
 }
 
+/// <https://w3c.github.io/IndexedDB/#request-source>
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+#[derive(JSTraceable, MallocSizeOf)]
+enum RequestSource {
+    ObjectStore(Dom<IDBObjectStore>),
+    Index(Dom<IDBIndex>),
+    Cursor(Dom<IDBCursor>),
+}
+
 #[dom_struct]
 pub struct IDBRequest {
     eventtarget: EventTarget,
     #[ignore_malloc_size_of = "mozjs"]
     result: Heap<JSVal>,
     error: MutNullableDom<DOMException>,
-    source: MutNullableDom<IDBObjectStore>,
+    source: DomRefCell<Option<RequestSource>>,
     transaction: MutNullableDom<IDBTransaction>,
     ready_state: Cell<IDBRequestReadyState>,
+    /// The cursor that is iterated by this request, if it was returned by
+    /// `openCursor()` or `openKeyCursor()`.
+    cursor: MutNullableDom<IDBCursor>,
+}
+
+/// The object store that requests placed against a source operate on.
+fn source_object_store(source: &IDBObjectStoreOrIDBIndexOrIDBCursor) -> DomRoot<IDBObjectStore> {
+    match source {
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store) => store.clone(),
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index) => index.object_store(),
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(cursor) => cursor.object_store(),
+    }
 }
 
 impl IDBRequest {
@@ -129,6 +169,7 @@ impl IDBRequest {
             source: Default::default(),
             transaction: Default::default(),
             ready_state: Cell::new(IDBRequestReadyState::Pending),
+            cursor: Default::default(),
         }
     }
 
@@ -136,42 +177,169 @@ impl IDBRequest {
         reflect_dom_object(Box::new(IDBRequest::new_inherited()), global, can_gc)
     }
 
-    pub fn set_source(&self, source: Option<&IDBObjectStore>) {
-        self.source.set(source);
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub fn set_source(&self, source: &IDBObjectStoreOrIDBIndexOrIDBCursor) {
+        *self.source.borrow_mut() = Some(match source {
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store) => {
+                RequestSource::ObjectStore(Dom::from_ref(&**store))
+            },
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index) => {
+                RequestSource::Index(Dom::from_ref(&**index))
+            },
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(cursor) => {
+                RequestSource::Cursor(Dom::from_ref(&**cursor))
+            },
+        });
+    }
+
+    pub fn set_cursor(&self, cursor: &IDBCursor) {
+        self.cursor.set(Some(cursor));
     }
 
     pub fn set_ready_state_done(&self) {
         self.ready_state.set(IDBRequestReadyState::Done);
     }
 
+    pub fn set_ready_state_pending(&self) {
+        self.ready_state.set(IDBRequestReadyState::Pending);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.ready_state.get() == IDBRequestReadyState::Pending
+    }
+
     pub fn set_result(&self, result: HandleValue) {
         self.result.set(result.get());
     }
 
-    pub fn set_error(&self, error: Error, can_gc: CanGc) {
-        // FIXME:(rasviitanen) Support all error types
-        if let Error::Version = error {
-            self.error.set(Some(&DOMException::new(
-                &self.global(),
-                DOMErrorName::VersionError,
-                can_gc,
-            )));
-        }
+    pub fn set_error(&self, error: Option<DOMErrorName>, can_gc: CanGc) {
+        self.error.set(
+            error
+                .map(|error| DOMException::new(&self.global(), error, can_gc))
+                .as_deref(),
+        );
     }
 
     pub fn set_transaction(&self, transaction: &IDBTransaction) {
         self.transaction.set(Some(transaction));
     }
 
+    /// The object store of the source of the request.
+    fn object_store(&self) -> Option<DomRoot<IDBObjectStore>> {
+        self.source.borrow().as_ref().map(|source| match source {
+            RequestSource::ObjectStore(store) => store.as_rooted(),
+            RequestSource::Index(index) => index.object_store(),
+            RequestSource::Cursor(cursor) => cursor.object_store(),
+        })
+    }
+
+    /// Converts the result of an operation to the value of the `result` attribute.
+    #[allow(unsafe_code)]
+    fn convert_result(
+        &self,
+        cx: SafeJSContext,
+        result: AsyncOperationResult,
+        mut rval: MutableHandleValue,
+    ) -> Fallible<()> {
+        let object_store = self.object_store().expect("Request has no source");
+        match result {
+            AsyncOperationResult::Value(None) |
+            AsyncOperationResult::Key(None) |
+            AsyncOperationResult::Undefined => rval.set(UndefinedValue()),
+            AsyncOperationResult::Value(Some(record)) => {
+                if let Some(value) = record.value {
+                    object_store.deserialize_value(cx, value, &record.primary_key, rval)?;
+                }
+            },
+            AsyncOperationResult::Key(Some(key)) => key_type_to_jsval(cx, &key, rval),
+            AsyncOperationResult::Values(records) => unsafe {
+                rooted!(in(*cx) let array = NewArrayObject1(*cx, records.len()));
+                for (index, record) in records.into_iter().enumerate() {
+                    rooted!(in(*cx) let mut value = UndefinedValue());
+                    if let Some(serialized) = record.value {
+                        object_store.deserialize_value(
+                            cx,
+                            serialized,
+                            &record.primary_key,
+                            value.handle_mut(),
+                        )?;
+                    }
+                    if !JS_DefineElement(
+                        *cx,
+                        array.handle(),
+                        index as u32,
+                        value.handle(),
+                        JSPROP_ENUMERATE as u32,
+                    ) {
+                        return Err(Error::JSFailed);
+                    }
+                }
+                rval.set(ObjectValue(array.get()));
+            },
+            // A list of keys converts to the same value as an array key.
+            AsyncOperationResult::Keys(keys) => {
+                key_type_to_jsval(cx, &IndexedDBKeyType::Array(keys), rval)
+            },
+            AsyncOperationResult::Count(count) => rval.set(DoubleValue(count as f64)),
+            // https://w3c.github.io/IndexedDB/#iterate-a-cursor
+            AsyncOperationResult::Record(record) => {
+                let cursor = self.cursor.get().expect("Request has no cursor");
+                if cursor.set_iteration_result(cx, record)? {
+                    unsafe { cursor.to_jsval(*cx, rval) };
+                } else {
+                    rval.set(NullValue());
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Fails the request because its transaction was aborted, see step 5 of
+    /// <https://w3c.github.io/IndexedDB/#abort-a-transaction>.
+    pub fn abort(&self) {
+        // Set request's processed flag to true.
+        self.set_ready_state_done();
+
+        // Queue a database task to run these steps:
+        let this = Trusted::new(self);
+        self.global()
+            .task_manager()
+            .database_access_task_source()
+            .queue(task!(abort_request: move || {
+                let this = this.root();
+                let global = this.global();
+
+                // Set request's result to undefined.
+                this.set_result(HandleValue::undefined());
+
+                // Set request's error to a newly created "AbortError" DOMException.
+                this.set_error(Some(DOMErrorName::AbortError), CanGc::note());
+
+                // Fire an event named error at request with its bubbles and cancelable
+                // attributes initialized to true.
+                let event = Event::new(
+                    &global,
+                    Atom::from("error"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::Cancelable,
+                    CanGc::note(),
+                );
+                event.fire(this.upcast(), CanGc::note());
+            }));
+    }
+
     // https://www.w3.org/TR/IndexedDB-2/#asynchronously-execute-a-request
     pub fn execute_async(
-        source: &IDBObjectStore,
+        source: IDBObjectStoreOrIDBIndexOrIDBCursor,
         operation: AsyncOperation,
         request: Option<DomRoot<IDBRequest>>,
         can_gc: CanGc,
     ) -> Fallible<DomRoot<IDBRequest>> {
         // Step 1: Let transaction be the transaction associated with source.
-        let transaction = source.transaction().expect("Store has no transaction");
+        let object_store = source_object_store(&source);
+        let transaction = object_store
+            .transaction()
+            .expect("Store has no transaction");
         let global = transaction.global();
 
         // Step 2: Assert: transaction is active.
@@ -182,7 +350,7 @@ impl IDBRequest {
         // Step 3: If request was not given, let request be a new request with source as source.
         let request = request.unwrap_or_else(|| {
             let new_request = IDBRequest::new(&global, can_gc);
-            new_request.set_source(Some(source));
+            new_request.set_source(&source);
             new_request.set_transaction(&transaction);
             new_request
         });
@@ -198,9 +366,10 @@ impl IDBRequest {
             IDBTransactionMode::Versionchange => IndexedDBTxnMode::Versionchange,
         };
 
-        let (sender, receiver) =
-            ipc::channel::<std::option::Option<Vec<u8>>>(global.time_profiler_chan().clone())
-                .unwrap();
+        let (sender, receiver) = ipc::channel::<BackendResult<AsyncOperationResult>>(
+            global.time_profiler_chan().clone(),
+        )
+        .unwrap();
 
         let response_listener = RequestListener {
             request: Trusted::new(&request),
@@ -230,7 +399,7 @@ impl IDBRequest {
                 sender,
                 global.origin().immutable().clone(),
                 transaction.get_db_name().to_string(),
-                source.get_name().to_string(),
+                object_store.get_name().to_string(),
                 transaction.get_serial_number(),
                 transaction_mode,
                 operation,
@@ -254,8 +423,18 @@ impl IDBRequestMethods<crate::DomTypeHolder> for IDBRequest {
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbrequest-source
-    fn GetSource(&self) -> Option<DomRoot<IDBObjectStore>> {
-        self.source.get()
+    fn GetSource(&self) -> Option<IDBObjectStoreOrIDBIndexOrIDBCursor> {
+        self.source.borrow().as_ref().map(|source| match source {
+            RequestSource::ObjectStore(store) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store.as_rooted())
+            },
+            RequestSource::Index(index) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index.as_rooted())
+            },
+            RequestSource::Cursor(cursor) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(cursor.as_rooted())
+            },
+        })
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbrequest-transaction
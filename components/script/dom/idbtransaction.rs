@@ -8,12 +8,15 @@ use std::collections::HashMap;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use net_traits::IpcSend;
-use net_traits::indexeddb_thread::{IndexedDBThreadMsg, SyncOperation};
+use net_traits::indexeddb_thread::{
+    BackendError, IndexedDBThreadMsg, IndexedDBTxnDurability, SyncOperation,
+};
 use profile_traits::ipc;
 use stylo_atoms::Atom;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DOMStringListBinding::DOMStringListMethods;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBTransactionDurability;
 use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::{
     IDBTransactionMethods, IDBTransactionMode,
 };
@@ -23,13 +26,14 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::domexception::DOMException;
+use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::domstringlist::DOMStringList;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::idbdatabase::IDBDatabase;
 use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbopendbrequest::IDBOpenDBRequest;
 use crate::dom::idbrequest::IDBRequest;
 use crate::script_runtime::CanGc;
 
@@ -38,6 +42,7 @@ pub struct IDBTransaction {
     eventtarget: EventTarget,
     object_store_names: Dom<DOMStringList>,
     mode: IDBTransactionMode,
+    durability: IDBTransactionDurability,
     db: Dom<IDBDatabase>,
     error: MutNullableDom<DOMException>,
 
@@ -48,6 +53,12 @@ pub struct IDBTransaction {
     active: Cell<bool>,
     // https://www.w3.org/TR/IndexedDB-2/#transaction-finish
     finished: Cell<bool>,
+    // https://w3c.github.io/IndexedDB/#transaction-committing
+    committing: Cell<bool>,
+    // The number of requests that have not received their result yet
+    pending_requests: Cell<usize>,
+    // The request that opened the connection, if this is its upgrade transaction
+    open_request: MutNullableDom<IDBOpenDBRequest>,
     // An unique identifier, used to commit and revert this transaction
     // FIXME:(rasviitanen) Replace this with a channel
     serial_number: u64,
@@ -57,6 +68,7 @@ impl IDBTransaction {
     fn new_inherited(
         connection: &IDBDatabase,
         mode: IDBTransactionMode,
+        durability: IDBTransactionDurability,
         scope: &DOMStringList,
         serial_number: u64,
     ) -> IDBTransaction {
@@ -64,6 +76,7 @@ impl IDBTransaction {
             eventtarget: EventTarget::new_inherited(),
             object_store_names: Dom::from_ref(scope),
             mode,
+            durability,
             db: Dom::from_ref(connection),
             error: Default::default(),
 
@@ -71,6 +84,9 @@ impl IDBTransaction {
             requests: Default::default(),
             active: Cell::new(true),
             finished: Cell::new(false),
+            committing: Cell::new(false),
+            pending_requests: Cell::new(0),
+            open_request: Default::default(),
             serial_number,
         }
     }
@@ -79,20 +95,39 @@ impl IDBTransaction {
         global: &GlobalScope,
         connection: &IDBDatabase,
         mode: IDBTransactionMode,
+        durability: IDBTransactionDurability,
         scope: &DOMStringList,
         can_gc: CanGc,
     ) -> DomRoot<IDBTransaction> {
         let serial_number = IDBTransaction::register_new(global, connection.get_name());
-        reflect_dom_object(
+        let transaction = reflect_dom_object(
             Box::new(IDBTransaction::new_inherited(
                 connection,
                 mode,
+                durability,
                 scope,
                 serial_number,
             )),
             global,
             can_gc,
-        )
+        );
+
+        // https://w3c.github.io/IndexedDB/#transaction-lifetime
+        // The transaction is deactivated when control returns to the event loop, and
+        // commits automatically once all of its requests have finished. Upgrade
+        // transactions are handled by the open request instead.
+        if mode != IDBTransactionMode::Versionchange {
+            let this = Trusted::new(&*transaction);
+            global.task_manager().database_access_task_source().queue(
+                task!(deactivate_transaction: move || {
+                    let this = this.root();
+                    this.set_active_flag(false);
+                    this.maybe_commit();
+                }),
+            );
+        }
+
+        transaction
     }
 
     // Registers a new transaction in the idb thread, and gets an unique serial number in return.
@@ -139,13 +174,33 @@ impl IDBTransaction {
     }
 
     pub fn set_active_flag(&self, status: bool) {
-        self.active.set(status)
+        // A transaction that is committing can not receive new requests.
+        self.active
+            .set(status && !self.committing.get() && !self.finished.get())
     }
 
     pub fn is_active(&self) -> bool {
         self.active.get()
     }
 
+    pub fn is_finished(&self) -> bool {
+        self.finished.get()
+    }
+
+    pub fn get_db(&self) -> DomRoot<IDBDatabase> {
+        self.db.as_rooted()
+    }
+
+    pub fn set_open_request(&self, request: &IDBOpenDBRequest) {
+        self.open_request.set(Some(request));
+    }
+
+    pub fn set_store_handle(&self, store: &IDBObjectStore) {
+        self.store_handles
+            .borrow_mut()
+            .insert(store.get_name().to_string(), Dom::from_ref(store));
+    }
+
     pub fn get_mode(&self) -> IDBTransactionMode {
         self.mode
     }
@@ -160,6 +215,137 @@ impl IDBTransaction {
 
     pub fn add_request(&self, request: &IDBRequest) {
         self.requests.borrow_mut().push(Dom::from_ref(request));
+        self.pending_requests.set(self.pending_requests.get() + 1);
+    }
+
+    pub fn request_finished(&self) {
+        self.pending_requests
+            .set(self.pending_requests.get().saturating_sub(1));
+    }
+
+    /// Commits the transaction once it can not receive new requests and all of its
+    /// requests have finished, see <https://w3c.github.io/IndexedDB/#transaction-commit>.
+    pub fn maybe_commit(&self) {
+        if self.finished.get() || self.active.get() || self.pending_requests.get() > 0 {
+            return;
+        }
+        self.commit();
+    }
+
+    // https://w3c.github.io/IndexedDB/#commit-a-transaction
+    fn commit(&self) {
+        // Step 1: Set transaction's state to committing.
+        self.committing.set(true);
+        self.active.set(false);
+
+        // Step 2: Run the following steps in parallel:
+        // Step 2.1: Wait until every item in transaction's request list is processed.
+        // Step 2.2: If transaction's state is no longer committing, then terminate these steps.
+        // Step 2.3: Attempt to write any outstanding changes made by transaction to the database,
+        // considering transaction's durability hint.
+        let durability = match self.durability {
+            IDBTransactionDurability::Default => IndexedDBTxnDurability::Default,
+            IDBTransactionDurability::Strict => IndexedDBTxnDurability::Strict,
+            IDBTransactionDurability::Relaxed => IndexedDBTxnDurability::Relaxed,
+        };
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        let commit_operation = SyncOperation::Commit(
+            sender,
+            self.global().origin().immutable().clone(),
+            self.db.get_name().to_string(),
+            self.serial_number,
+            durability,
+        );
+
+        self.get_idb_thread()
+            .send(IndexedDBThreadMsg::Sync(commit_operation))
+            .unwrap();
+
+        match receiver.recv() {
+            // Step 2.5: Queue a database task to run these steps:
+            // Set transaction's state to finished, and fire an event named complete.
+            Ok(Ok(())) => {
+                self.finished.set(true);
+                self.dispatch_complete();
+            },
+            // Step 2.4: If an error occurs while writing the changes to the database, then
+            // run abort a transaction with transaction and an appropriate type for the error,
+            // for example "QuotaExceededError" or "UnknownError" DOMException, and terminate
+            // these steps.
+            Ok(Err(error)) => self.abort(Some(backend_error_name(&error))),
+            Err(_) => self.abort(Some(DOMErrorName::UnknownError)),
+        }
+    }
+
+    // https://w3c.github.io/IndexedDB/#abort-a-transaction
+    pub fn abort(&self, error: Option<DOMErrorName>) {
+        if self.finished.get() {
+            return;
+        }
+
+        // Step 1: All the changes made to the database by the transaction are reverted.
+        // FIXME: Object stores and indexes created or deleted by an upgrade transaction
+        // are not restored yet, only their records are.
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        let abort_operation = SyncOperation::Abort(
+            sender,
+            self.global().origin().immutable().clone(),
+            self.db.get_name().to_string(),
+            self.serial_number,
+        );
+        self.get_idb_thread()
+            .send(IndexedDBThreadMsg::Sync(abort_operation))
+            .unwrap();
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            warn!("Could not revert the changes of an aborted IDBTransaction");
+        }
+
+        // Step 3: Set transaction's state to finished.
+        self.active.set(false);
+        self.finished.set(true);
+
+        // Step 4: If error is not null, set transaction's error to error.
+        if let Some(error) = error {
+            self.error.set(Some(&DOMException::new(
+                &self.global(),
+                error,
+                CanGc::note(),
+            )));
+        }
+
+        // Step 5: For each request of transaction's request list, abort the steps to
+        // asynchronously execute a request for request, set request's processed flag to
+        // true, and queue a database task to fail the request with an "AbortError".
+        for request in self.requests.borrow().iter() {
+            if request.is_pending() {
+                request.abort();
+            }
+        }
+
+        // Step 6: Queue a database task to fire an event named abort at transaction.
+        let this = Trusted::new(self);
+        self.global()
+            .task_manager()
+            .database_access_task_source()
+            .queue(task!(send_abort_notification: move || {
+                let this = this.root();
+                let global = this.global();
+                let event = Event::new(
+                    &global,
+                    Atom::from("abort"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::NotCancelable,
+                    CanGc::note()
+                );
+                event.fire(this.upcast(), CanGc::note());
+
+                // If transaction is an upgrade transaction, the open request fails
+                // with an "AbortError", see
+                // https://w3c.github.io/IndexedDB/#run-an-upgrade-transaction
+                if let Some(open_request) = this.open_request.get() {
+                    open_request.dispatch_abort();
+                }
+            }));
     }
 
     pub fn upgrade_db_version(&self, version: u64) {
@@ -197,6 +383,13 @@ impl IDBTransaction {
                     CanGc::note()
                 );
                 event.fire(this.upcast(), CanGc::note());
+
+                // The open request of an upgrade transaction succeeds once the
+                // transaction completes, see step 10 of
+                // https://w3c.github.io/IndexedDB/#open-a-database-connection
+                if let Some(open_request) = this.open_request.get() {
+                    open_request.dispatch_success(&this.db);
+                }
             }),
         );
     }
@@ -206,6 +399,16 @@ impl IDBTransaction {
     }
 }
 
+/// The name of the DOMException that a request or transaction fails with when an
+/// operation fails in the idb thread.
+pub fn backend_error_name(error: &BackendError) -> DOMErrorName {
+    match error {
+        BackendError::Constraint => DOMErrorName::ConstraintError,
+        BackendError::QuotaExceeded => DOMErrorName::QuotaExceededError,
+        BackendError::DbErr(_) => DOMErrorName::UnknownError,
+    }
+}
+
 impl IDBTransactionMethods<crate::DomTypeHolder> for IDBTransaction {
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbtransaction-db
     fn Db(&self) -> DomRoot<IDBDatabase> {
@@ -219,78 +422,71 @@ impl IDBTransactionMethods<crate::DomTypeHolder> for IDBTransaction {
             return Err(Error::InvalidState);
         }
 
-        // Step 2: Check that the object store exists
-        if !self.object_store_names.Contains(name.clone()) {
+        // Step 2: Check that the object store exists. The scope of an upgrade
+        // transaction includes the object stores it creates.
+        let in_scope = match self.mode {
+            IDBTransactionMode::Versionchange => self.db.has_object_store(&name),
+            _ => self.object_store_names.Contains(name.clone()),
+        };
+        if !in_scope {
             return Err(Error::NotFound);
         }
 
         // Step 3: Each call to this method on the same
         // IDBTransaction instance with the same name
         // returns the same IDBObjectStore instance.
-        let mut store_handles = self.store_handles.borrow_mut();
-        let store = store_handles.entry(name.to_string()).or_insert_with(|| {
-            let store = IDBObjectStore::new(
-                &self.global(),
-                self.db.get_name(),
-                name,
-                None,
-                CanGc::note(),
-            );
-            store.set_transaction(self);
-            Dom::from_ref(&*store)
-        });
+        if let Some(store) = self.store_handles.borrow().get(&*name) {
+            return Ok(DomRoot::from_ref(&**store));
+        }
+
+        let Some(metadata) = self.db.object_store_metadata(&name) else {
+            return Err(Error::NotFound);
+        };
+        let store =
+            IDBObjectStore::new(&self.global(), self.db.get_name(), metadata, CanGc::note());
+        store.set_transaction(self);
+        self.set_store_handle(&store);
 
-        Ok(DomRoot::from_ref(&*store))
+        Ok(store)
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#commit-transaction
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-commit
     fn Commit(&self) -> Fallible<()> {
-        // Step 1
-        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
-        let start_operation = SyncOperation::Commit(
-            sender,
-            self.global().origin().immutable().clone(),
-            self.db.get_name().to_string(),
-            self.serial_number,
-        );
-
-        self.get_idb_thread()
-            .send(IndexedDBThreadMsg::Sync(start_operation))
-            .unwrap();
-
-        let result = receiver.recv().unwrap();
-
-        // Step 2
-        if let Err(_result) = result {
-            // FIXME:(rasviitanen) also support Unknown error
-            return Err(Error::QuotaExceeded);
+        // Step 1: If this's state is not active, then throw an "InvalidStateError" DOMException.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
         }
 
-        // Step 3
-        // FIXME:(rasviitanen) https://www.w3.org/TR/IndexedDB-2/#commit-a-transaction
-
-        // Steps 3.1 and 3.3
-        self.dispatch_complete();
+        // Step 2: Run commit a transaction with this. The transaction can not receive
+        // new requests, and is committed once its pending requests have finished.
+        self.committing.set(true);
+        self.active.set(false);
+        self.maybe_commit();
 
         Ok(())
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#dom-idbtransaction-abort
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-abort
     fn Abort(&self) -> Fallible<()> {
-        // FIXME:(rasviitanen)
-        // This only sets the flags, and does not abort the transaction
-        // see https://www.w3.org/TR/IndexedDB-2/#abort-a-transaction
-        if self.finished.get() {
+        // Step 1: If this's state is committing or finished, then throw an
+        // "InvalidStateError" DOMException.
+        if self.finished.get() || self.committing.get() {
             return Err(Error::InvalidState);
         }
 
+        // Step 2: Set this's state to inactive and run abort a transaction with this
+        // and null.
         self.active.set(false);
+        self.abort(None);
 
         Ok(())
     }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbtransaction-objectstorenames
     fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
+        if self.mode == IDBTransactionMode::Versionchange {
+            return self.db.object_stores();
+        }
         self.object_store_names.as_rooted()
     }
 
@@ -299,11 +495,10 @@ impl IDBTransactionMethods<crate::DomTypeHolder> for IDBTransaction {
         self.mode
     }
 
-    // https://www.w3.org/TR/IndexedDB-2/#dom-idbtransaction-mode
-    // fn Durability(&self) -> IDBTransactionDurability {
-    //     // FIXME:(arihant2math) Durability is not implemented at all
-    //     unimplemented!();
-    // }
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-durability
+    fn Durability(&self) -> IDBTransactionDurability {
+        self.durability
+    }
 
     // https://www.w3.org/TR/IndexedDB-2/#dom-idbtransaction-error
     fn GetError(&self) -> Option<DomRoot<DOMException>> {
@@ -67,6 +67,11 @@ impl ImageBitmap {
         self.bitmap_data.borrow()
     }
 
+    /// Take the bitmap data, which detaches this bitmap.
+    pub(crate) fn take_bitmap_data(&self) -> Option<Snapshot> {
+        self.bitmap_data.borrow_mut().take()
+    }
+
     pub(crate) fn origin_is_clean(&self) -> bool {
         self.origin_clean.get()
     }
@@ -0,0 +1,215 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use canvas_traits::canvas::{Canvas2dMsg, CanvasId};
+use dom_struct::dom_struct;
+use euclid::default::Rect;
+use pixels::{Snapshot, SnapshotAlphaMode};
+use webrender_api::ImageKey;
+
+use crate::canvas_context::{CanvasContext, CanvasHelpers, LayoutCanvasRenderingContextHelpers};
+use crate::canvas_state::CanvasState;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapRenderingContextBinding::ImageBitmapRenderingContextMethods;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{DomRoot, LayoutDom};
+use crate::dom::canvasrenderingcontext2d::DroppableCanvasRenderingContext2D;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagebitmap::ImageBitmap;
+use crate::script_runtime::CanGc;
+
+/// <https://html.spec.whatwg.org/multipage/#imagebitmaprenderingcontext>
+#[dom_struct]
+pub(crate) struct ImageBitmapRenderingContext {
+    reflector_: Reflector,
+    canvas: HTMLCanvasElementOrOffscreenCanvas,
+    /// The bitmap of the last [`ImageBitmap`] transferred into this context, or `None`
+    /// when its [bitmap mode](https://html.spec.whatwg.org/multipage/#concept-imagebitmaprenderingcontext-bitmap-mode)
+    /// is blank.
+    #[no_trace]
+    bitmap: DomRefCell<Option<Snapshot>>,
+    origin_clean: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#concept-imagebitmaprenderingcontext-alpha>
+    alpha: bool,
+    /// The canvas of the canvas paint thread in which the bitmap is presented.
+    canvas_state: CanvasState,
+    droppable: DroppableCanvasRenderingContext2D,
+}
+
+impl ImageBitmapRenderingContext {
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn new_inherited(
+        global: &GlobalScope,
+        canvas: HTMLCanvasElementOrOffscreenCanvas,
+        alpha: bool,
+    ) -> ImageBitmapRenderingContext {
+        let canvas_state = CanvasState::new(global, canvas.size().cast());
+        let droppable = DroppableCanvasRenderingContext2D::new(&canvas_state);
+        ImageBitmapRenderingContext {
+            reflector_: Reflector::new(),
+            canvas,
+            bitmap: DomRefCell::new(None),
+            origin_clean: Cell::new(true),
+            alpha,
+            canvas_state,
+            droppable,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        canvas: &HTMLCanvasElementOrOffscreenCanvas,
+        alpha: bool,
+        can_gc: CanGc,
+    ) -> DomRoot<ImageBitmapRenderingContext> {
+        reflect_dom_object(
+            Box::new(ImageBitmapRenderingContext::new_inherited(
+                global,
+                canvas.clone(),
+                alpha,
+            )),
+            global,
+            can_gc,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#set-an-imagebitmaprenderingcontext's-output-bitmap>
+    fn set_output_bitmap(&self, bitmap: Option<&ImageBitmap>) {
+        match bitmap {
+            // Step 1. If a bitmap was not provided, then set the bitmap mode to blank,
+            // the output bitmap to transparent black and the origin-clean flag to true.
+            None => {
+                *self.bitmap.borrow_mut() = None;
+                self.origin_clean.set(true);
+            },
+            // Step 2. Otherwise, set the bitmap mode to valid, the output bitmap to the
+            // bitmap data of the bitmap and the origin-clean flag to the one of the bitmap.
+            Some(bitmap) => {
+                *self.bitmap.borrow_mut() = bitmap.take_bitmap_data();
+                self.origin_clean.set(bitmap.origin_is_clean());
+            },
+        }
+        self.present();
+    }
+
+    /// Draw the output bitmap, scaled to the size of the canvas, in the canvas of the
+    /// canvas paint thread.
+    fn present(&self) {
+        self.canvas_state.reset_bitmap();
+        if let Some(mut bitmap) = self.bitmap.borrow().clone() {
+            // The output bitmap is opaque black where it is transparent if alpha is false.
+            if !self.alpha {
+                let format = bitmap.format();
+                bitmap.transform(SnapshotAlphaMode::Opaque, format);
+            }
+            let source_rect = Rect::from_size(bitmap.size().to_f64());
+            let dest_rect = Rect::from_size(self.size().to_f64());
+            self.canvas_state.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+                bitmap.as_ipc(),
+                dest_rect,
+                source_rect,
+                true,
+            ));
+        }
+        if let HTMLCanvasElementOrOffscreenCanvas::HTMLCanvasElement(_) = self.canvas {
+            self.canvas_state.update_rendering();
+        }
+        self.mark_as_dirty();
+    }
+}
+
+impl LayoutCanvasRenderingContextHelpers for LayoutDom<'_, ImageBitmapRenderingContext> {
+    fn canvas_data_source(self) -> Option<ImageKey> {
+        let canvas_state = &self.unsafe_get().canvas_state;
+
+        if canvas_state.is_paintable() {
+            Some(canvas_state.image_key())
+        } else {
+            None
+        }
+    }
+}
+
+impl CanvasContext for ImageBitmapRenderingContext {
+    type ID = CanvasId;
+
+    fn context_id(&self) -> Self::ID {
+        self.canvas_state.get_canvas_id()
+    }
+
+    fn canvas(&self) -> Option<HTMLCanvasElementOrOffscreenCanvas> {
+        Some(self.canvas.clone())
+    }
+
+    fn resize(&self) {
+        self.canvas_state.set_bitmap_dimensions(self.size().cast());
+        self.present();
+    }
+
+    fn reset_bitmap(&self) {
+        self.set_output_bitmap(None);
+    }
+
+    fn get_image_data(&self) -> Option<Snapshot> {
+        let size = self.size();
+        if size.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.bitmap
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| Snapshot::cleared(size)),
+        )
+    }
+
+    fn origin_is_clean(&self) -> bool {
+        self.origin_clean.get()
+    }
+
+    fn update_rendering(&self) {
+        self.canvas_state.update_rendering();
+    }
+
+    fn image_key(&self) -> Option<ImageKey> {
+        Some(self.canvas_state.image_key())
+    }
+}
+
+impl ImageBitmapRenderingContextMethods<crate::DomTypeHolder> for ImageBitmapRenderingContext {
+    /// <https://html.spec.whatwg.org/multipage/#dom-imagebitmaprenderingcontext-canvas>
+    fn Canvas(&self) -> HTMLCanvasElementOrOffscreenCanvas {
+        self.canvas.clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-imagebitmaprenderingcontext-transferfromimagebitmap>
+    fn TransferFromImageBitmap(&self, image_bitmap: Option<&ImageBitmap>) -> ErrorResult {
+        // Step 2. If imageBitmap is null, then run the steps to set an
+        // ImageBitmapRenderingContext's output bitmap, with bitmapContext as the context
+        // argument and no bitmap argument, then return.
+        let Some(image_bitmap) = image_bitmap else {
+            self.set_output_bitmap(None);
+            return Ok(());
+        };
+
+        // Step 3. If the value of imageBitmap's [[Detached]] internal slot is set to true,
+        // then throw an "InvalidStateError" DOMException.
+        if image_bitmap.is_detached() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4. Run the steps to set an ImageBitmapRenderingContext's output bitmap,
+        // with the context argument equal to bitmapContext, and the bitmap argument
+        // referring to imageBitmap's underlying bitmap data.
+        // Step 5. Set the value of imageBitmap's [[Detached]] internal slot to true.
+        // Step 6. Unset imageBitmap's bitmap data.
+        self.set_output_bitmap(Some(image_bitmap));
+        Ok(())
+    }
+}
@@ -20,11 +20,12 @@ pub(crate) struct InputEvent {
     uievent: UIEvent,
     data: Option<DOMString>,
     is_composing: bool,
+    input_type: DOMString,
 }
 
 impl InputEvent {
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    pub(crate) fn new(
         window: &Window,
         proto: Option<HandleObject>,
         type_: DOMString,
@@ -34,6 +35,7 @@ impl InputEvent {
         detail: i32,
         data: Option<DOMString>,
         is_composing: bool,
+        input_type: DOMString,
         can_gc: CanGc,
     ) -> DomRoot<InputEvent> {
         let ev = reflect_dom_object_with_proto(
@@ -41,6 +43,7 @@ impl InputEvent {
                 uievent: UIEvent::new_inherited(),
                 data,
                 is_composing,
+                input_type,
             }),
             window,
             proto,
@@ -71,6 +74,7 @@ impl InputEventMethods<crate::DomTypeHolder> for InputEvent {
             init.parent.detail,
             init.data.clone(),
             init.isComposing,
+            init.inputType.clone(),
             can_gc,
         );
         Ok(event)
@@ -86,6 +90,11 @@ impl InputEventMethods<crate::DomTypeHolder> for InputEvent {
         self.is_composing
     }
 
+    // https://w3c.github.io/input-events/#dom-inputevent-inputtype
+    fn InputType(&self) -> DOMString {
+        self.input_type.clone()
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     fn IsTrusted(&self) -> bool {
         self.uievent.IsTrusted()
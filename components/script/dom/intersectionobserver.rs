@@ -8,15 +8,18 @@ use std::time::Duration;
 
 use app_units::Au;
 use base::cross_process_instant::CrossProcessInstant;
+use compositing_traits::IntersectionObservationTarget;
+use constellation_traits::IntersectionObservationChange;
 use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
-use euclid::default::{Rect, Size2D};
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use js::rust::{HandleObject, MutableHandleValue};
 use style::context::QuirksMode;
 use style::parser::{Parse, ParserContext};
 use style::stylesheets::{CssRuleType, Origin};
 use style_traits::{ParsingMode, ToCss};
 use url::Url;
+use webrender_api::units::LayoutRect;
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
@@ -252,6 +255,16 @@ impl IntersectionObserver {
         }
     }
 
+    /// Whether the intersection root of this observer is the viewport of `document`, either
+    /// explicitly or as the implicit root of an observer in a top-level document.
+    fn root_is_viewport_of(&self, document: &Document) -> bool {
+        match &self.root {
+            None => *self.owner_doc == *document && document.window().is_top_level(),
+            Some(ElementOrDocument::Document(root)) => **root == *document,
+            Some(ElementOrDocument::Element(_)) => false,
+        }
+    }
+
     /// <https://w3c.github.io/IntersectionObserver/#observe-target-element>
     fn observe_target_element(&self, target: &Element) {
         // Step 1
@@ -482,6 +495,7 @@ impl IntersectionObserver {
         document: &Document,
         target: &Element,
         maybe_root_bounds: Option<Rect<Au>>,
+        viewport_scroll_offset: Vector2D<Au>,
     ) -> IntersectionObservationOutput {
         // Step 5
         // > If the intersection root is not the implicit root, and target is not in
@@ -511,7 +525,14 @@ impl IntersectionObserver {
             return IntersectionObservationOutput::default_skipped();
         }
         let root_bounds = maybe_root_bounds.unwrap();
-        let target_rect = maybe_target_rect.unwrap();
+        let mut target_rect = maybe_target_rect.unwrap();
+
+        // The bounding box is in the coordinate space of the document, while the root
+        // intersection rectangle of a document is in the coordinate space of its viewport.
+        // TODO: Map the bounding box through the scroll containers of its ancestors.
+        if self.maybe_element_root().is_none() {
+            target_rect = target_rect.translate(-viewport_scroll_offset);
+        }
 
         // TODO(stevennovaryo): we should probably also consider adding visibity check, ideally
         //                      it would require new query from LayoutThread.
@@ -580,11 +601,16 @@ impl IntersectionObserver {
     }
 
     /// Step 2.2.1-2.2.21 of <https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo>
+    ///
+    /// The targets whose intersection with the viewport the renderer can track while
+    /// scrolling are appended to `renderer_targets`.
     pub(crate) fn update_intersection_observations_steps(
         &self,
         document: &Document,
         time: CrossProcessInstant,
         root_bounds: Option<Rect<Au>>,
+        viewport_scroll_offset: Vector2D<Au>,
+        renderer_targets: &mut Vec<IntersectionObservationTarget>,
         can_gc: CanGc,
     ) {
         for target in &*self.observation_targets.borrow() {
@@ -606,8 +632,12 @@ impl IntersectionObserver {
             registration.last_update_time.set(time);
 
             // step 4-14
-            let intersection_output =
-                self.maybe_compute_intersection_output(document, target, root_bounds);
+            let intersection_output = self.maybe_compute_intersection_output(
+                document,
+                target,
+                root_bounds,
+                viewport_scroll_offset,
+            );
 
             // Step 15-17
             // > 15. Let previousThresholdIndex be the registration’s previousThresholdIndex property.
@@ -656,8 +686,90 @@ impl IntersectionObserver {
             registration
                 .previous_is_visible
                 .set(intersection_output.is_visible);
+
+            // The renderer can only track targets of the viewport of their own document,
+            // and it doesn't know about the delay between updates.
+            if root_bounds.is_some() &&
+                self.delay.get() <= 0 &&
+                self.root_is_viewport_of(document) &&
+                *target.owner_document() == *document
+            {
+                if let Some(target_rect) = target.upcast::<Node>().bounding_content_box_no_reflow()
+                {
+                    renderer_targets.push(IntersectionObservationTarget {
+                        id: registration.id,
+                        rect: au_rect_to_layout_rect(target_rect),
+                        root_bounds: au_rect_to_layout_rect(intersection_output.root_bounds),
+                        thresholds: self
+                            .thresholds
+                            .borrow()
+                            .iter()
+                            .map(|threshold| **threshold)
+                            .collect(),
+                        threshold_index: intersection_output.threshold_index,
+                        is_intersecting: intersection_output.is_intersecting,
+                    });
+                }
+            }
         }
     }
+
+    /// Queue an entry for a target whose intersection with the viewport changed while the
+    /// renderer scrolled, if the change was not already observed. Returns false if `change`
+    /// is not about one of the targets of this observer.
+    pub(crate) fn handle_renderer_intersection_observation_change(
+        &self,
+        document: &Document,
+        time: CrossProcessInstant,
+        change: &IntersectionObservationChange,
+        can_gc: CanGc,
+    ) -> bool {
+        let Some(target) = self
+            .observation_targets
+            .borrow()
+            .iter()
+            .find(|target| {
+                target
+                    .get_intersection_observer_registration(self)
+                    .is_some_and(|registration| registration.id == change.id)
+            })
+            .map(|target| target.as_rooted())
+        else {
+            return false;
+        };
+        let registration = target.get_intersection_observer_registration(self).unwrap();
+        registration.last_update_time.set(time);
+
+        // Script may already have observed the change itself, when it updated the rendering
+        // in the meantime.
+        if change.threshold_index == registration.previous_threshold_index.get() &&
+            change.is_intersecting == registration.previous_is_intersecting.get()
+        {
+            return true;
+        }
+        registration
+            .previous_threshold_index
+            .set(change.threshold_index);
+        registration
+            .previous_is_intersecting
+            .set(change.is_intersecting);
+        let is_visible = registration.previous_is_visible.get();
+        drop(registration);
+
+        self.queue_an_intersectionobserverentry(
+            document,
+            time,
+            layout_rect_to_au_rect(change.root_bounds),
+            layout_rect_to_au_rect(change.target_rect),
+            layout_rect_to_au_rect(change.intersection_rect),
+            change.is_intersecting,
+            is_visible,
+            change.intersection_ratio,
+            &target,
+            can_gc,
+        );
+        true
+    }
 }
 
 impl IntersectionObserverMethods<crate::DomTypeHolder> for IntersectionObserver {
@@ -771,6 +883,9 @@ impl IntersectionObserverMethods<crate::DomTypeHolder> for IntersectionObserver
 #[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
 pub(crate) struct IntersectionObserverRegistration {
     pub(crate) observer: Dom<IntersectionObserver>,
+    /// Identifies this registration in the intersection observation targets that are sent
+    /// to the renderer.
+    pub(crate) id: u64,
     pub(crate) previous_threshold_index: Cell<i32>,
     pub(crate) previous_is_intersecting: Cell<bool>,
     #[no_trace]
@@ -787,6 +902,7 @@ impl IntersectionObserverRegistration {
     pub(crate) fn new_initial(observer: &IntersectionObserver) -> Self {
         IntersectionObserverRegistration {
             observer: Dom::from_ref(observer),
+            id: observer.owner_doc.next_intersection_observation_id(),
             previous_threshold_index: Cell::new(-1),
             previous_is_intersecting: Cell::new(false),
             last_update_time: Cell::new(CrossProcessInstant::epoch()),
@@ -795,6 +911,23 @@ impl IntersectionObserverRegistration {
     }
 }
 
+fn au_rect_to_layout_rect(rect: Rect<Au>) -> LayoutRect {
+    LayoutRect::from_origin_and_size(
+        Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()).cast_unit(),
+        Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()).cast_unit(),
+    )
+}
+
+fn layout_rect_to_au_rect(rect: LayoutRect) -> Rect<Au> {
+    Rect::new(
+        Point2D::new(Au::from_f32_px(rect.min.x), Au::from_f32_px(rect.min.y)),
+        Size2D::new(
+            Au::from_f32_px(rect.width()),
+            Au::from_f32_px(rect.height()),
+        ),
+    )
+}
+
 /// <https://w3c.github.io/IntersectionObserver/#parse-a-margin>
 fn parse_a_margin(value: Option<&DOMString>) -> Result<IntersectionObserverRootMargin, ()> {
     // <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverinit-rootmargin> &&
@@ -156,6 +156,10 @@ impl KeyboardEvent {
     pub(crate) fn modifiers(&self) -> Modifiers {
         self.modifiers.get()
     }
+
+    pub(crate) fn is_composing(&self) -> bool {
+        self.is_composing.get()
+    }
 }
 
 impl KeyboardEventMethods<crate::DomTypeHolder> for KeyboardEvent {
@@ -107,6 +107,7 @@ impl MediaQueryListMethods<crate::DomTypeHolder> for MediaQueryList {
                 parent: EventListenerOptions { capture: false },
                 once: false,
                 passive: None,
+                signal: None,
             },
         );
     }
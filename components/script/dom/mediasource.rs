@@ -0,0 +1,715 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_media::player::PlayerError;
+use servo_media::{ServoMedia, SupportsMediaType};
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementConstants::{
+    MEDIA_ERR_DECODE, MEDIA_ERR_NETWORK,
+};
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::sourcebuffer::SourceBuffer;
+use crate::dom::sourcebufferlist::SourceBufferList;
+use crate::dom::timeranges::TimeRangesContainer;
+use crate::dom::window::Window;
+use crate::media_source::{self, ByteStreamFormat};
+use crate::script_runtime::CanGc;
+
+/// The number of bytes that may wait to be pushed to the media player, past which
+/// appending to a `SourceBuffer` throws a `QuotaExceededError`.
+const MAX_PENDING_BYTES: usize = 64 * 1024 * 1024;
+
+/// The number of track IDs set aside for the tracks of each `SourceBuffer`, once their
+/// segments are muxed into a single stream.
+const TRACKS_PER_SOURCE_BUFFER: u64 = 16;
+
+/// <https://w3c.github.io/media-source/#mediasource>
+///
+/// The media player of the attached media element reads a single byte stream, so the
+/// segments appended to all the source buffers are queued here, in the order in which
+/// they were appended, and pushed whenever the player asks for data.
+#[dom_struct]
+pub(crate) struct MediaSource {
+    eventtarget: EventTarget,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers>
+    source_buffers: Dom<SourceBufferList>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers>
+    active_source_buffers: Dom<SourceBufferList>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-readystate>
+    ready_state: Cell<ReadyState>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-duration>
+    duration: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#live-seekable-range>
+    live_seekable_range: Cell<Option<(f64, f64)>>,
+    /// The media element that this media source is attached to, if any.
+    media_element: MutNullableDom<HTMLMediaElement>,
+    /// The segments that wait to be pushed to the media player.
+    pending_data: DomRefCell<VecDeque<Vec<u8>>>,
+    /// The number of bytes of `pending_data`.
+    pending_bytes: Cell<usize>,
+    /// Whether the media player asked for data since it last had enough of it.
+    player_wants_data: Cell<bool>,
+    /// The merged initialization segment of the source buffers that was last queued.
+    queued_initialization_segment: DomRefCell<Option<Vec<u8>>>,
+    /// Whether the end of the stream must be signalled to the media player once the
+    /// pending data has been pushed.
+    end_of_stream_pending: Cell<bool>,
+    /// The first track ID of the next source buffer to be added.
+    next_track_base: Cell<u64>,
+}
+
+impl MediaSource {
+    fn new_inherited(
+        source_buffers: &SourceBufferList,
+        active_source_buffers: &SourceBufferList,
+    ) -> MediaSource {
+        MediaSource {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: Dom::from_ref(source_buffers),
+            active_source_buffers: Dom::from_ref(active_source_buffers),
+            ready_state: Cell::new(ReadyState::Closed),
+            duration: Cell::new(f64::NAN),
+            live_seekable_range: Cell::new(None),
+            media_element: Default::default(),
+            pending_data: Default::default(),
+            pending_bytes: Cell::new(0),
+            player_wants_data: Cell::new(false),
+            queued_initialization_segment: Default::default(),
+            end_of_stream_pending: Cell::new(false),
+            next_track_base: Cell::new(1),
+        }
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<MediaSource> {
+        let source_buffers = SourceBufferList::new(window, can_gc);
+        let active_source_buffers = SourceBufferList::new(window, can_gc);
+        reflect_dom_object_with_proto(
+            Box::new(MediaSource::new_inherited(
+                &source_buffers,
+                &active_source_buffers,
+            )),
+            window,
+            proto,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn ready_state(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    pub(crate) fn duration(&self) -> f64 {
+        self.duration.get()
+    }
+
+    pub(crate) fn media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.media_element.get()
+    }
+
+    fn queue_event(&self, name: &str) {
+        self.global()
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), Atom::from(name));
+    }
+
+    fn set_ready_state(&self, ready_state: ReadyState) {
+        self.ready_state.set(ready_state);
+        match ready_state {
+            ReadyState::Open => self.queue_event("sourceopen"),
+            ReadyState::Ended => self.queue_event("sourceended"),
+            ReadyState::Closed => self.queue_event("sourceclose"),
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    ///
+    /// Returns false if this media source cannot be attached, in which case the media
+    /// element runs the dedicated media source failure steps.
+    pub(crate) fn attach(&self, media_element: &HTMLMediaElement) -> bool {
+        // If readyState is NOT set to "closed", run the "dedicated media source failure
+        // steps" and abort.
+        if self.ready_state.get() != ReadyState::Closed {
+            return false;
+        }
+
+        // Set the readyState attribute to "open", and queue a task to fire an event
+        // named sourceopen at the MediaSource.
+        self.media_element.set(Some(media_element));
+        self.set_ready_state(ReadyState::Open);
+        true
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-detach>
+    pub(crate) fn detach(&self) {
+        // Set the readyState attribute to "closed".
+        // Update duration to NaN.
+        self.duration.set(f64::NAN);
+        self.live_seekable_range.set(None);
+
+        // Remove all the SourceBuffer objects from activeSourceBuffers and from
+        // sourceBuffers, queueing a removesourcebuffer event for each list.
+        self.active_source_buffers.clear();
+        for source_buffer in self.source_buffers.source_buffers() {
+            source_buffer.remove_from_parent();
+        }
+        self.source_buffers.clear();
+
+        // Queue a task to fire an event named sourceclose at the MediaSource.
+        self.set_ready_state(ReadyState::Closed);
+
+        self.media_element.set(None);
+        self.pending_data.borrow_mut().clear();
+        self.pending_bytes.set(0);
+        self.player_wants_data.set(false);
+        *self.queued_initialization_segment.borrow_mut() = None;
+        self.end_of_stream_pending.set(false);
+    }
+
+    /// Sets the readyState to "open" if it is "ended", as appending or removing data does.
+    pub(crate) fn reopen_if_ended(&self) {
+        if self.ready_state.get() == ReadyState::Ended {
+            // The media player cannot take data after the end of its stream, so this
+            // only takes effect if it was not signalled yet.
+            self.end_of_stream_pending.set(false);
+            self.set_ready_state(ReadyState::Open);
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-full-flag>
+    pub(crate) fn is_buffer_full(&self) -> bool {
+        self.pending_bytes.get() >= MAX_PENDING_BYTES
+    }
+
+    /// The presentation end time of the buffered ranges of all the source buffers.
+    fn highest_end_time(&self) -> f64 {
+        self.source_buffers
+            .source_buffers()
+            .iter()
+            .filter_map(|source_buffer| source_buffer.highest_end_time())
+            .fold(0., f64::max)
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    fn change_duration(&self, new_duration: f64) {
+        // If the current value of duration is equal to new duration, then return.
+        if self.duration.get() == new_duration {
+            return;
+        }
+
+        // If new duration is less than the highest end time of the buffered ranges of
+        // all the source buffers, then update new duration to equal it.
+        let new_duration = new_duration.max(self.highest_end_time());
+
+        // Update duration to new duration.
+        self.duration.set(new_duration);
+
+        // Update the media duration to new duration and run the HTMLMediaElement
+        // duration change algorithm.
+        if let Some(media_element) = self.media_element.get() {
+            media_element.media_source_duration_changed(new_duration);
+        }
+    }
+
+    /// Runs the duration change algorithm if the media duration is still unknown, with
+    /// the duration of an initialization segment if it declares one.
+    pub(crate) fn set_initial_duration(&self, duration: Option<f64>) {
+        if self.duration.get().is_nan() {
+            self.change_duration(duration.unwrap_or(f64::INFINITY));
+        }
+    }
+
+    /// Runs the duration change algorithm if coded frames were appended past the current
+    /// duration.
+    pub(crate) fn extend_duration(&self, end: f64) {
+        if end > self.duration.get() {
+            self.change_duration(end);
+        }
+    }
+
+    /// Marks a source buffer as active once it received the tracks of its first
+    /// initialization segment.
+    pub(crate) fn activate_source_buffer(&self, source_buffer: &SourceBuffer) {
+        if !self.active_source_buffers.contains(source_buffer) {
+            self.active_source_buffers.add(source_buffer);
+        }
+    }
+
+    /// Queues the initialization segments of the source buffers for the media player,
+    /// merged into one, once all of them received one.
+    pub(crate) fn initialization_segment_received(&self) {
+        let source_buffers = self.source_buffers.source_buffers();
+        let Some(format) = source_buffers.first().map(|buffer| buffer.format()) else {
+            return;
+        };
+        let Some(segments) = source_buffers
+            .iter()
+            .map(|source_buffer| source_buffer.initialization_segment())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        let merged = match media_source::merge_initialization_segments(format, &segments) {
+            Ok(merged) => merged,
+            Err(error) => {
+                warn!("Could not merge initialization segments: {:?}", error);
+                self.end_of_stream(Some(EndOfStreamError::Decode), CanGc::note());
+                return;
+            },
+        };
+
+        // An identical initialization segment does not need to reach the player again.
+        let mut queued = self.queued_initialization_segment.borrow_mut();
+        if queued.as_ref() == Some(&merged) {
+            return;
+        }
+        let first = queued.is_none();
+        *queued = Some(merged.clone());
+        drop(queued);
+
+        // The first one goes before any media segment that was appended to a source
+        // buffer while waiting for the initialization segments of the others.
+        self.pending_bytes
+            .set(self.pending_bytes.get() + merged.len());
+        if first {
+            self.pending_data.borrow_mut().push_front(merged);
+        } else {
+            self.pending_data.borrow_mut().push_back(merged);
+        }
+        self.push_pending_data();
+    }
+
+    /// Queues the bytes of a media segment for the media player.
+    pub(crate) fn queue_media_segment(&self, bytes: Vec<u8>) {
+        self.pending_bytes
+            .set(self.pending_bytes.get() + bytes.len());
+        self.pending_data.borrow_mut().push_back(bytes);
+        self.push_pending_data();
+    }
+
+    /// Called when the media player asks for more data.
+    pub(crate) fn player_needs_data(&self) {
+        self.player_wants_data.set(true);
+        self.push_pending_data();
+    }
+
+    /// Called when the media player has enough data for now.
+    pub(crate) fn player_has_enough_data(&self) {
+        self.player_wants_data.set(false);
+    }
+
+    /// Pushes the pending data to the media player for as long as it wants it, and then
+    /// signals the end of the stream if it was requested.
+    fn push_pending_data(&self) {
+        let Some(media_element) = self.media_element.get() else {
+            return;
+        };
+        // Nothing can be pushed before the initialization segment.
+        if self.queued_initialization_segment.borrow().is_none() {
+            return;
+        }
+
+        while self.player_wants_data.get() {
+            let Some(data) = self.pending_data.borrow_mut().pop_front() else {
+                break;
+            };
+            self.pending_bytes
+                .set(self.pending_bytes.get() - data.len());
+            match media_element.push_media_source_data(data) {
+                Ok(()) => {},
+                // The data was still taken, but the player wants no more for now.
+                Err(PlayerError::EnoughData) => self.player_wants_data.set(false),
+                Err(error) => {
+                    warn!("Could not push media source data to player {:?}", error);
+                    return;
+                },
+            }
+        }
+
+        if self.end_of_stream_pending.get() && self.pending_data.borrow().is_empty() {
+            self.end_of_stream_pending.set(false);
+            media_element.media_source_end_of_stream();
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub(crate) fn end_of_stream(&self, error: Option<EndOfStreamError>, can_gc: CanGc) {
+        // Change the readyState attribute value to "ended".
+        // Queue a task to fire an event named sourceended at the MediaSource.
+        self.set_ready_state(ReadyState::Ended);
+
+        let Some(media_element) = self.media_element.get() else {
+            return;
+        };
+        match error {
+            // Run the duration change algorithm with new duration set to the largest
+            // track buffer ranges end time across all the track buffers in sourceBuffers.
+            // Notify the media element that it now has all of the media data.
+            None => {
+                self.change_duration(self.highest_end_time());
+                self.end_of_stream_pending.set(true);
+                self.push_pending_data();
+            },
+            Some(EndOfStreamError::Network) => {
+                media_element.media_source_error(MEDIA_ERR_NETWORK, can_gc)
+            },
+            Some(EndOfStreamError::Decode) => {
+                media_element.media_source_error(MEDIA_ERR_DECODE, can_gc)
+            },
+        }
+    }
+
+    /// The track IDs set aside for the tracks of a new source buffer.
+    fn allocate_track_base(&self) -> u64 {
+        let track_base = self.next_track_base.get();
+        self.next_track_base
+            .set(track_base + TRACKS_PER_SOURCE_BUFFER);
+        track_base
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-htmlmediaelement-buffered>
+    pub(crate) fn buffered(&self) -> TimeRangesContainer {
+        // If activeSourceBuffers.length equals 0 then return an empty TimeRanges object.
+        let active_source_buffers = self.active_source_buffers.source_buffers();
+        if active_source_buffers.is_empty() {
+            return TimeRangesContainer::default();
+        }
+
+        // Let active ranges be the ranges returned by buffered for each SourceBuffer
+        // object in activeSourceBuffers.
+        // Let highest end time be the largest range end time in the active ranges.
+        let active_ranges: Vec<_> = active_source_buffers
+            .iter()
+            .map(|source_buffer| source_buffer.buffered_ranges())
+            .collect();
+        let highest_end_time = active_ranges
+            .iter()
+            .filter(|ranges| !ranges.is_empty())
+            .filter_map(|ranges| ranges.end(ranges.len() - 1).ok())
+            .fold(0., f64::max);
+
+        // Let intersection ranges equal a TimeRanges object containing a single range
+        // from 0 to highest end time.
+        let mut intersection_ranges = TimeRangesContainer::default();
+        let _ = intersection_ranges.add(0., highest_end_time);
+
+        // For each SourceBuffer object in activeSourceBuffers run the following steps:
+        for mut source_ranges in active_ranges {
+            // If readyState is "ended", then set the end time on the last range in source
+            // ranges to highest end time.
+            if self.ready_state.get() == ReadyState::Ended && !source_ranges.is_empty() {
+                let last = source_ranges.len() - 1;
+                if let Ok(start) = source_ranges.start(last) {
+                    let _ = source_ranges.add(start, highest_end_time);
+                }
+            }
+
+            // Let new intersection ranges equal the intersection between the
+            // intersection ranges and the source ranges.
+            intersection_ranges = intersection_ranges.intersection(&source_ranges);
+        }
+        intersection_ranges
+    }
+
+    /// <https://w3c.github.io/media-source/#htmlmediaelement-extensions>, for `seekable`.
+    pub(crate) fn seekable(&self, buffered: &TimeRangesContainer) -> TimeRangesContainer {
+        let mut seekable = TimeRangesContainer::default();
+        let duration = self.duration.get();
+
+        // If duration equals NaN, then return an empty TimeRanges object.
+        if duration.is_nan() {
+            return seekable;
+        }
+
+        // If duration equals positive Infinity:
+        if duration == f64::INFINITY {
+            let buffered_range = (!buffered.is_empty()).then(|| {
+                (
+                    buffered.start(0).unwrap_or(0.),
+                    buffered.end(buffered.len() - 1).unwrap_or(0.),
+                )
+            });
+            match (self.live_seekable_range.get(), buffered_range) {
+                // If live seekable range is not empty, return a single range spanning
+                // the union of live seekable range and buffered.
+                (Some((start, end)), Some((buffered_start, buffered_end))) => {
+                    let _ = seekable.add(start.min(buffered_start), end.max(buffered_end));
+                },
+                (Some((start, end)), None) => {
+                    let _ = seekable.add(start, end);
+                },
+                // If the HTMLMediaElement.buffered attribute returns an empty TimeRanges
+                // object, then return an empty TimeRanges object.
+                (None, None) => {},
+                // Return a single range with a start time of 0 and an end time equal to
+                // the highest end time reported by the HTMLMediaElement.buffered
+                // attribute.
+                (None, Some((_, buffered_end))) => {
+                    let _ = seekable.add(0., buffered_end);
+                },
+            }
+            return seekable;
+        }
+
+        // Otherwise, return a single range with a start time of 0 and an end time equal
+        // to duration.
+        let _ = seekable.add(0., duration);
+        seekable
+    }
+
+    /// Whether any source buffer has its updating attribute set.
+    fn any_source_buffer_updating(&self) -> bool {
+        self.source_buffers
+            .source_buffers()
+            .iter()
+            .any(|source_buffer| source_buffer.is_updating())
+    }
+}
+
+impl MediaSourceMethods<crate::DomTypeHolder> for MediaSource {
+    /// <https://w3c.github.io/media-source/#dom-mediasource-constructor>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<MediaSource>> {
+        Ok(MediaSource::new_with_proto(window, proto, can_gc))
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers>
+    fn SourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.source_buffers)
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers>
+    fn ActiveSourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.active_source_buffers)
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-readystate>
+    fn ReadyState(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-duration>
+    fn Duration(&self) -> f64 {
+        // If the readyState attribute is "closed" then return NaN and abort these steps.
+        if self.ready_state.get() == ReadyState::Closed {
+            return f64::NAN;
+        }
+        self.duration.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-duration>
+    fn SetDuration(&self, value: f64) -> ErrorResult {
+        // If the value being set is negative or NaN then throw a TypeError exception.
+        if value < 0. || value.is_nan() {
+            return Err(Error::Type("duration must be a non-negative number".into()));
+        }
+
+        // If the readyState attribute is not "open" then throw an InvalidStateError
+        // exception.
+        // If the updating attribute equals true on any SourceBuffer in sourceBuffers, then
+        // throw an InvalidStateError exception.
+        if self.ready_state.get() != ReadyState::Open || self.any_source_buffer_updating() {
+            return Err(Error::InvalidState);
+        }
+
+        // If new duration is less than the highest presentation timestamp of any buffered
+        // coded frames for all SourceBuffer objects in sourceBuffers, then throw an
+        // InvalidStateError exception.
+        if value < self.highest_end_time() {
+            return Err(Error::InvalidState);
+        }
+
+        // Run the duration change algorithm with new duration set to the value being
+        // assigned to this attribute.
+        self.change_duration(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceopen
+    event_handler!(sourceopen, GetOnsourceopen, SetOnsourceopen);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceended
+    event_handler!(sourceended, GetOnsourceended, SetOnsourceended);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceclose
+    event_handler!(sourceclose, GetOnsourceclose, SetOnsourceclose);
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-canconstructindedicatedworker>
+    fn CanConstructInDedicatedWorker(_window: &Window) -> bool {
+        false
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-addsourcebuffer>
+    fn AddSourceBuffer(&self, type_: DOMString, can_gc: CanGc) -> Fallible<DomRoot<SourceBuffer>> {
+        // If type is an empty string then throw a TypeError exception.
+        if type_.is_empty() {
+            return Err(Error::Type("type must not be empty".into()));
+        }
+
+        // If type contains a MIME type that is not supported or contains a MIME type that
+        // is not supported with the types specified for the other SourceBuffer objects in
+        // sourceBuffers, then throw a NotSupportedError exception.
+        let global = self.global();
+        let window = global.as_window();
+        if !MediaSource::IsTypeSupported(window, type_.clone()) {
+            return Err(Error::NotSupported);
+        }
+        let format = ByteStreamFormat::for_mime_type(&type_).ok_or(Error::NotSupported)?;
+
+        // If the user agent can't handle any more SourceBuffer objects or if creating a
+        // SourceBuffer based on type would result in an unsupported SourceBuffer
+        // configuration, then throw a QuotaExceededError exception.
+        //
+        // The segments of all source buffers are muxed into one stream, which needs them
+        // to share a byte stream format, and whose tracks are fixed once it started.
+        let source_buffers = self.source_buffers.source_buffers();
+        if source_buffers
+            .iter()
+            .any(|buffer| buffer.format() != format) ||
+            source_buffers.len() as u64 >= TRACKS_PER_SOURCE_BUFFER ||
+            self.queued_initialization_segment.borrow().is_some()
+        {
+            return Err(Error::QuotaExceeded);
+        }
+
+        // If the readyState attribute is not in the "open" state then throw an
+        // InvalidStateError exception.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        // Let buffer be a new instance of a ManagedSourceBuffer if this is a
+        // ManagedMediaSource, or a SourceBuffer otherwise, with their respective
+        // associated resources.
+        let source_buffer =
+            SourceBuffer::new(window, self, format, self.allocate_track_base(), can_gc);
+
+        // Add buffer to this's sourceBuffers.
+        // Queue a task to fire an event named addsourcebuffer at this's sourceBuffers.
+        self.source_buffers.add(&source_buffer);
+
+        // Return buffer.
+        Ok(source_buffer)
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer>
+    fn RemoveSourceBuffer(&self, source_buffer: &SourceBuffer) -> ErrorResult {
+        // If sourceBuffer specifies an object that is not in sourceBuffers then throw a
+        // NotFoundError exception and abort these steps.
+        if !self.source_buffers.contains(source_buffer) {
+            return Err(Error::NotFound);
+        }
+
+        // If the sourceBuffer.updating attribute equals true, then abort the buffer
+        // append algorithm, which queues abort and updateend events.
+        source_buffer.abort_buffer_append();
+
+        // If sourceBuffer is in activeSourceBuffers, then remove sourceBuffer from
+        // activeSourceBuffers and queue a task to fire an event named removesourcebuffer
+        // at the SourceBufferList returned by activeSourceBuffers.
+        self.active_source_buffers.remove(source_buffer);
+
+        // Remove sourceBuffer from sourceBuffers and queue a task to fire an event named
+        // removesourcebuffer at the SourceBufferList returned by sourceBuffers.
+        self.source_buffers.remove(source_buffer);
+
+        // Destroy all resources for sourceBuffer.
+        source_buffer.remove_from_parent();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-endofstream>
+    fn EndOfStream(&self, error: Option<EndOfStreamError>, can_gc: CanGc) -> ErrorResult {
+        // If the readyState attribute is not in the "open" state then throw an
+        // InvalidStateError exception and abort these steps.
+        // If the updating attribute equals true on any SourceBuffer in sourceBuffers,
+        // then throw an InvalidStateError exception and abort these steps.
+        if self.ready_state.get() != ReadyState::Open || self.any_source_buffer_updating() {
+            return Err(Error::InvalidState);
+        }
+
+        // Run the end of stream algorithm with the error parameter set to error.
+        self.end_of_stream(error, can_gc);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-setliveseekablerange>
+    fn SetLiveSeekableRange(&self, start: Finite<f64>, end: Finite<f64>) -> ErrorResult {
+        let (start, end) = (*start, *end);
+
+        // If the readyState attribute is not "open" then throw an InvalidStateError
+        // exception and abort these steps.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        // If start is negative or greater than end, then throw a TypeError exception and
+        // abort these steps.
+        if start < 0. || start > end {
+            return Err(Error::Type("invalid live seekable range".into()));
+        }
+
+        // Set live seekable range to be a new normalized TimeRanges object containing a
+        // single range whose start position is start and end position is end.
+        self.live_seekable_range.set(Some((start, end)));
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-clearliveseekablerange>
+    fn ClearLiveSeekableRange(&self) -> ErrorResult {
+        // If the readyState attribute is not "open" then throw an InvalidStateError
+        // exception and abort these steps.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        // If live seekable range contains a range, then set live seekable range to be a
+        // new empty TimeRanges object.
+        self.live_seekable_range.set(None);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-mediasource-istypesupported>
+    fn IsTypeSupported(_window: &Window, type_: DOMString) -> bool {
+        // If type is an empty string, then return false.
+        // If type does not contain a valid MIME type string, then return false.
+        // If type contains a media type or media subtype that the MediaSource does not
+        // support, then return false.
+        if ByteStreamFormat::for_mime_type(&type_).is_none() {
+            return false;
+        }
+
+        // If type contains a codec that the MediaSource does not support, then return
+        // false.
+        !matches!(
+            ServoMedia::get().can_play_type(&type_),
+            SupportsMediaType::No
+        )
+    }
+}
@@ -218,6 +218,7 @@ pub(crate) mod abstractworker;
 pub(crate) mod abstractworkerglobalscope;
 pub(crate) mod activation;
 pub(crate) mod analysernode;
+pub(crate) mod animation;
 pub(crate) mod animationevent;
 pub(crate) mod attr;
 pub(crate) mod audiobuffer;
@@ -230,6 +231,9 @@ pub(crate) mod audioparam;
 pub(crate) mod audioscheduledsourcenode;
 pub(crate) mod audiotrack;
 pub(crate) mod audiotracklist;
+pub(crate) mod authenticatorassertionresponse;
+pub(crate) mod authenticatorattestationresponse;
+pub(crate) mod authenticatorresponse;
 pub(crate) mod baseaudiocontext;
 pub(crate) mod beforeunloadevent;
 pub(crate) mod bindings;
@@ -242,10 +246,13 @@ pub(crate) mod bluetooth;
 pub(crate) use self::bluetooth::*;
 pub(crate) mod broadcastchannel;
 pub(crate) mod bytelengthqueuingstrategy;
+pub(crate) mod cache;
+pub(crate) mod cachestorage;
 pub(crate) mod canvasgradient;
 pub(crate) mod canvaspattern;
 #[allow(dead_code)]
 pub(crate) mod canvasrenderingcontext2d;
+pub(crate) mod caretposition;
 pub(crate) mod cdatasection;
 pub(crate) mod channelmergernode;
 pub(crate) mod channelsplitternode;
@@ -257,15 +264,20 @@ pub(crate) mod clipboarditem;
 pub(crate) mod closeevent;
 pub(crate) mod comment;
 pub(crate) mod compositionevent;
+pub(crate) mod compressionstream;
 pub(crate) mod console;
 pub(crate) mod constantsourcenode;
 pub(crate) mod countqueuingstrategy;
 mod create;
+pub(crate) mod credential;
+pub(crate) mod credentialscontainer;
 pub(crate) mod crypto;
 pub(crate) mod cryptokey;
 pub(crate) mod csp;
 pub(crate) mod csppolicyviolationreport;
+pub(crate) mod cspviolationreportbody;
 pub(crate) mod css;
+pub(crate) mod cssanimation;
 pub(crate) mod cssconditionrule;
 pub(crate) mod cssfontfacerule;
 pub(crate) mod cssgroupingrule;
@@ -277,21 +289,26 @@ pub(crate) mod csslayerstatementrule;
 pub(crate) mod cssmediarule;
 pub(crate) mod cssnamespacerule;
 pub(crate) mod cssnesteddeclarations;
+pub(crate) mod csspositiontryrule;
 pub(crate) mod cssrule;
 pub(crate) mod cssrulelist;
+pub(crate) mod cssscoperule;
 pub(crate) mod cssstyledeclaration;
 pub(crate) mod cssstylerule;
 pub(crate) mod cssstylesheet;
 pub(crate) mod cssstylevalue;
 pub(crate) mod csssupportsrule;
+pub(crate) mod csstransition;
 pub(crate) mod customelementregistry;
 pub(crate) mod customevent;
 pub(crate) mod datatransfer;
 pub(crate) mod datatransferitem;
 pub(crate) mod datatransferitemlist;
+pub(crate) mod decompressionstream;
 pub(crate) mod dedicatedworkerglobalscope;
 pub(crate) mod defaultteereadrequest;
 pub(crate) mod defaultteeunderlyingsource;
+pub(crate) mod deprecationreportbody;
 pub(crate) mod dissimilaroriginlocation;
 pub(crate) mod dissimilaroriginwindow;
 #[allow(dead_code)]
@@ -313,6 +330,7 @@ pub(crate) mod domrectreadonly;
 pub(crate) mod domstringlist;
 pub(crate) mod domstringmap;
 pub(crate) mod domtokenlist;
+pub(crate) mod dragevent;
 pub(crate) mod dynamicmoduleowner;
 #[allow(dead_code)]
 pub(crate) mod element;
@@ -323,10 +341,16 @@ pub(crate) mod eventsource;
 pub(crate) mod eventtarget;
 pub(crate) mod extendableevent;
 pub(crate) mod extendablemessageevent;
+pub(crate) mod fetchevent;
 pub(crate) mod file;
 pub(crate) mod filelist;
 pub(crate) mod filereader;
 pub(crate) mod filereadersync;
+pub(crate) mod filesystemdirectoryhandle;
+pub(crate) mod filesystemfilehandle;
+pub(crate) mod filesystemhandle;
+pub(crate) mod filesystemsyncaccesshandle;
+pub(crate) mod filesystemwritablefilestream;
 pub(crate) mod focusevent;
 pub(crate) mod fontface;
 pub(crate) mod fontfaceset;
@@ -339,10 +363,16 @@ pub(crate) mod gamepadbuttonlist;
 pub(crate) mod gamepadevent;
 pub(crate) mod gamepadhapticactuator;
 pub(crate) mod gamepadpose;
+pub(crate) mod geolocation;
+pub(crate) mod geolocationcoordinates;
+pub(crate) mod geolocationposition;
+pub(crate) mod geolocationpositionerror;
 #[allow(dead_code)]
 pub(crate) mod globalscope;
 pub(crate) mod hashchangeevent;
 pub(crate) mod headers;
+pub(crate) mod highlight;
+pub(crate) mod highlightregistry;
 pub(crate) mod history;
 pub(crate) mod htmlanchorelement;
 pub(crate) mod htmlareaelement;
@@ -420,8 +450,12 @@ pub(crate) mod htmltrackelement;
 pub(crate) mod htmlulistelement;
 pub(crate) mod htmlunknownelement;
 pub(crate) mod htmlvideoelement;
+pub(crate) mod idbcursor;
+pub(crate) mod idbcursorwithvalue;
 pub(crate) mod idbdatabase;
 pub(crate) mod idbfactory;
+pub(crate) mod idbindex;
+pub(crate) mod idbkeyrange;
 pub(crate) mod idbobjectstore;
 pub(crate) mod idbopendbrequest;
 pub(crate) mod idbrequest;
@@ -429,6 +463,7 @@ pub(crate) mod idbtransaction;
 pub(crate) mod idbversionchangeevent;
 pub(crate) mod iirfilternode;
 pub(crate) mod imagebitmap;
+pub(crate) mod imagebitmaprenderingcontext;
 pub(crate) mod imagedata;
 pub(crate) mod inputevent;
 pub(crate) mod intersectionobserver;
@@ -446,6 +481,7 @@ pub(crate) mod mediametadata;
 pub(crate) mod mediaquerylist;
 pub(crate) mod mediaquerylistevent;
 pub(crate) mod mediasession;
+pub(crate) mod mediasource;
 pub(crate) mod mediastream;
 pub(crate) mod mediastreamaudiodestinationnode;
 pub(crate) mod mediastreamaudiosourcenode;
@@ -495,6 +531,8 @@ pub(crate) mod performancepainttiming;
 pub(crate) mod performanceresourcetiming;
 pub(crate) mod permissions;
 pub(crate) mod permissionstatus;
+pub(crate) mod pictureinpictureevent;
+pub(crate) mod pictureinpicturewindow;
 pub(crate) mod plugin;
 pub(crate) mod pluginarray;
 #[allow(dead_code)]
@@ -506,6 +544,9 @@ pub(crate) mod progressevent;
 pub(crate) mod promise;
 pub(crate) mod promisenativehandler;
 pub(crate) mod promiserejectionevent;
+pub(crate) mod publickeycredential;
+pub(crate) mod pushmanager;
+pub(crate) mod pushsubscription;
 pub(crate) mod radionodelist;
 pub(crate) mod range;
 pub(crate) mod raredata;
@@ -517,6 +558,8 @@ pub(crate) mod readablestreambyobrequest;
 pub(crate) mod readablestreamdefaultcontroller;
 pub(crate) mod readablestreamdefaultreader;
 pub(crate) mod readablestreamgenericreader;
+pub(crate) mod report;
+pub(crate) mod reportbody;
 pub(crate) mod reportingendpoint;
 pub(crate) mod reportingobserver;
 pub(crate) mod request;
@@ -535,6 +578,7 @@ pub(crate) mod rtcrtpsender;
 pub(crate) mod rtcrtptransceiver;
 pub(crate) mod rtcsessiondescription;
 pub(crate) mod rtctrackevent;
+pub(crate) mod scheduler;
 pub(crate) mod screen;
 pub(crate) mod securitypolicyviolationevent;
 pub(crate) mod selection;
@@ -548,10 +592,13 @@ pub(crate) mod servointernals;
 #[allow(dead_code)]
 pub(crate) mod servoparser;
 pub(crate) mod shadowroot;
+pub(crate) mod sourcebuffer;
+pub(crate) mod sourcebufferlist;
 pub(crate) mod staticrange;
 pub(crate) mod stereopannernode;
 pub(crate) mod storage;
 pub(crate) mod storageevent;
+pub(crate) mod storagemanager;
 pub(crate) mod stylepropertymapreadonly;
 pub(crate) mod stylesheet;
 pub(crate) mod stylesheetlist;
@@ -561,6 +608,9 @@ pub(crate) mod svgelement;
 pub(crate) mod svggraphicselement;
 pub(crate) mod svgimageelement;
 pub(crate) mod svgsvgelement;
+pub(crate) mod taskcontroller;
+pub(crate) mod taskprioritychangeevent;
+pub(crate) mod tasksignal;
 #[cfg(feature = "testbinding")]
 pub(crate) mod testbinding;
 #[cfg(feature = "testbinding")]
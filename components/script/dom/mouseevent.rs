@@ -224,6 +224,7 @@ impl MouseEvent {
     /// Create a [MouseEvent] triggered by the embedder
     pub(crate) fn for_platform_mouse_event(
         event: embedder_traits::MouseButtonEvent,
+        click_count: i32,
         pressed_mouse_buttons: u16,
         window: &Window,
         hit_test_result: &CompositorHitTestResult,
@@ -241,7 +242,6 @@ impl MouseEvent {
             .point_relative_to_initial_containing_block
             .to_i32();
 
-        let click_count = 1;
         let mouse_event = MouseEvent::new(
             window,
             mouse_event_type_string.into(),
@@ -20,8 +20,10 @@ use crate::dom::bindings::utils::to_frozen_array;
 #[cfg(feature = "bluetooth")]
 use crate::dom::bluetooth::Bluetooth;
 use crate::dom::clipboard::Clipboard;
+use crate::dom::credentialscontainer::CredentialsContainer;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gamepadevent::GamepadEventType;
+use crate::dom::geolocation::Geolocation;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
@@ -30,6 +32,7 @@ use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
 use crate::dom::servointernals::ServoInternals;
+use crate::dom::storagemanager::StorageManager;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::gpu::GPU;
 use crate::dom::window::Window;
@@ -59,6 +62,9 @@ pub(crate) struct Navigator {
     permissions: MutNullableDom<Permissions>,
     mediasession: MutNullableDom<MediaSession>,
     clipboard: MutNullableDom<Clipboard>,
+    geolocation: MutNullableDom<Geolocation>,
+    credentials: MutNullableDom<CredentialsContainer>,
+    storage: MutNullableDom<StorageManager>,
     #[cfg(feature = "webgpu")]
     gpu: MutNullableDom<GPU>,
     /// <https://www.w3.org/TR/gamepad/#dfn-hasgamepadgesture>
@@ -82,6 +88,9 @@ impl Navigator {
             permissions: Default::default(),
             mediasession: Default::default(),
             clipboard: Default::default(),
+            geolocation: Default::default(),
+            credentials: Default::default(),
+            storage: Default::default(),
             #[cfg(feature = "webgpu")]
             gpu: Default::default(),
             has_gamepad_gesture: Cell::new(false),
@@ -98,8 +107,14 @@ impl Navigator {
         self.xr.get()
     }
 
-    pub(crate) fn get_gamepad(&self, index: usize) -> Option<DomRoot<Gamepad>> {
-        self.gamepads.borrow().get(index).and_then(|g| g.get())
+    /// The gamepad of this navigator for the system gamepad with the given id, if it is
+    /// connected.
+    pub(crate) fn gamepad_with_id(&self, gamepad_id: u32) -> Option<DomRoot<Gamepad>> {
+        self.gamepads
+            .borrow()
+            .iter()
+            .filter_map(|g| g.get())
+            .find(|gamepad| gamepad.gamepad_id() == gamepad_id)
     }
 
     pub(crate) fn set_gamepad(&self, index: usize, gamepad: &Gamepad, can_gc: CanGc) {
@@ -136,7 +151,7 @@ impl Navigator {
     fn shrink_gamepads_list(&self) {
         let mut gamepad_list = self.gamepads.borrow_mut();
         for i in (0..gamepad_list.len()).rev() {
-            if gamepad_list.get(i).is_none() {
+            if gamepad_list[i].get().is_none() {
                 gamepad_list.remove(i);
             } else {
                 break;
@@ -287,6 +302,12 @@ impl NavigatorMethods<crate::DomTypeHolder> for Navigator {
             .or_init(|| MediaDevices::new(&self.global(), CanGc::note()))
     }
 
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-navigator-credentials>
+    fn Credentials(&self) -> DomRoot<CredentialsContainer> {
+        self.credentials
+            .or_init(|| CredentialsContainer::new(&self.global(), CanGc::note()))
+    }
+
     /// <https://w3c.github.io/mediasession/#dom-navigator-mediasession>
     fn MediaSession(&self) -> DomRoot<MediaSession> {
         self.mediasession.or_init(|| {
@@ -303,6 +324,12 @@ impl NavigatorMethods<crate::DomTypeHolder> for Navigator {
         })
     }
 
+    /// <https://storage.spec.whatwg.org/#dom-navigatorstorage-storage>
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage
+            .or_init(|| StorageManager::new(&self.global(), CanGc::note()))
+    }
+
     // https://gpuweb.github.io/gpuweb/#dom-navigator-gpu
     #[cfg(feature = "webgpu")]
     fn Gpu(&self) -> DomRoot<GPU> {
@@ -320,6 +347,12 @@ impl NavigatorMethods<crate::DomTypeHolder> for Navigator {
             .or_init(|| Clipboard::new(&self.global(), CanGc::note()))
     }
 
+    /// <https://w3c.github.io/geolocation/#navigator_interface>
+    fn Geolocation(&self) -> DomRoot<Geolocation> {
+        self.geolocation
+            .or_init(|| Geolocation::new(&self.global(), CanGc::note()))
+    }
+
     /// <https://servo.org/internal-no-spec>
     fn Servo(&self) -> DomRoot<ServoInternals> {
         self.servo_internals
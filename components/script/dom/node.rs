@@ -26,8 +26,8 @@ use js::jsapi::JSObject;
 use js::rust::HandleObject;
 use keyboard_types::Modifiers;
 use layout_api::{
-    GenericLayoutData, HTMLCanvasData, HTMLMediaData, LayoutElementType, LayoutNodeType, QueryMsg,
-    SVGSVGData, StyleData, TrustedNodeAddress,
+    GenericLayoutData, HTMLCanvasData, HTMLMediaData, HighlightKind, LayoutElementType,
+    LayoutNodeType, QueryMsg, SVGSVGData, StyleData, TrustedNodeAddress,
 };
 use libc::{self, c_void, uintptr_t};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
@@ -89,7 +89,9 @@ use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::characterdata::{CharacterData, LayoutCharacterDataHelpers};
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::customelementregistry::{CallbackReaction, try_upgrade_element};
-use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
+use crate::dom::document::{
+    Document, DocumentSource, HasBrowsingContext, IsHTMLDocument, LayoutDocumentHelpers,
+};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documenttype::DocumentType;
 use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator, SelectorWrapper};
@@ -891,6 +893,23 @@ impl Node {
             .any(|ancestor| &*ancestor == self)
     }
 
+    /// Whether this node is inert, because it is in the subtree of an element with an `inert`
+    /// attribute, including through shadow roots, or because its document is blocked by a
+    /// modal dialog that doesn't contain it. Inert nodes can't be focused, hit by input
+    /// events, found in the page or exposed to assistive technologies.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#inert>
+    pub(crate) fn is_inert(&self) -> bool {
+        self.inclusive_ancestors(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<HTMLElement>)
+            .any(|element| {
+                element
+                    .upcast::<Element>()
+                    .has_attribute(&local_name!("inert"))
+            }) ||
+            self.owner_doc().is_blocked_by_modal_dialog(self)
+    }
+
     pub(crate) fn following_siblings(&self) -> impl Iterator<Item = DomRoot<Node>> + use<> {
         SimpleNodeIterator {
             current: self.GetNextSibling(),
@@ -1657,6 +1676,9 @@ pub(crate) trait LayoutNodeHelpers<'dom> {
     fn is_text_input(&self) -> bool;
     fn text_content(self) -> Cow<'dom, str>;
     fn selection(self) -> Option<Range<usize>>;
+    fn composition(self) -> Option<Range<usize>>;
+    fn misspellings(self) -> Vec<Range<usize>>;
+    fn highlights(self) -> Vec<(Range<usize>, HighlightKind)>;
     fn image_url(self) -> Option<ServoUrl>;
     fn image_density(self) -> Option<f64>;
     fn image_data(self) -> Option<(Option<Image>, Option<ImageMetadata>)>;
@@ -1876,6 +1898,42 @@ impl<'dom> LayoutNodeHelpers<'dom> for LayoutDom<'dom, Node> {
         None
     }
 
+    fn composition(self) -> Option<Range<usize>> {
+        if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
+            return area.composition_for_layout();
+        }
+
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return input.composition_for_layout();
+        }
+
+        None
+    }
+
+    fn misspellings(self) -> Vec<Range<usize>> {
+        if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
+            return area.misspellings_for_layout();
+        }
+
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return input.misspellings_for_layout();
+        }
+
+        Vec::new()
+    }
+
+    fn highlights(self) -> Vec<(Range<usize>, HighlightKind)> {
+        if !matches!(
+            self.type_id_for_layout(),
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(_))
+        ) {
+            return Vec::new();
+        }
+
+        self.owner_doc_for_layout()
+            .highlights_for_layout(self.opaque())
+    }
+
     fn image_url(self) -> Option<ServoUrl> {
         self.downcast::<HTMLImageElement>()
             .expect("not an image!")
@@ -2762,21 +2820,15 @@ impl Node {
         parent.owner_doc().remove_script_and_layout_blocker();
     }
 
-    /// Ensure that for styles, we clone the already-parsed property declaration block.
-    /// This does two things:
+    /// Ensure that for styles, we share the already-parsed property declaration block,
+    /// which is copied before either element mutates it. This does two things:
     /// 1. it uses the same fast-path as CSSStyleDeclaration
     /// 2. it also avoids the CSP checks when cloning (it shouldn't run any when cloning
     ///    existing valid attributes)
     fn compute_attribute_value_with_style_fast_path(attr: &Dom<Attr>, elem: &Element) -> AttrValue {
         if *attr.local_name() == local_name!("style") {
             if let Some(ref pdb) = *elem.style_attribute().borrow() {
-                let document = elem.owner_document();
-                let shared_lock = document.style_shared_lock();
-                let new_pdb = pdb.read_with(&shared_lock.read()).clone();
-                return AttrValue::Declaration(
-                    (**attr.value()).to_owned(),
-                    Arc::new(shared_lock.wrap(new_pdb)),
-                );
+                return AttrValue::Declaration((**attr.value()).to_owned(), pdb.clone());
             }
         }
 
@@ -186,9 +186,11 @@ impl OfflineAudioContextMethods<crate::DomTypeHolder> for OfflineAudioContext {
                         .chunks(this.length as usize)
                         .map(|channel| channel.to_vec())
                         .collect();
-                    // it can end up being empty if the task failed
-                    if processed_audio.len() != this.length as usize {
-                        processed_audio.resize(this.length as usize, Vec::new())
+                    // it can end up being empty or short if the task failed,
+                    // in which case the missing frames are silent.
+                    processed_audio.resize(this.channel_count as usize, Vec::new());
+                    for channel in processed_audio.iter_mut() {
+                        channel.resize(this.length as usize, 0.);
                     }
                     let buffer = AudioBuffer::new(
                         this.global().as_window(),
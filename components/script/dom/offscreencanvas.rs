@@ -7,7 +7,10 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use base::id::{OffscreenCanvasId, OffscreenCanvasIndex};
-use constellation_traits::{BlobImpl, TransferableOffscreenCanvas};
+use canvas_traits::webgl::WebGLVersion;
+use constellation_traits::{
+    BlobImpl, OffscreenCanvasPlaceholder, ScriptToConstellationMessage, TransferableOffscreenCanvas,
+};
 use dom_struct::dom_struct;
 use euclid::default::Size2D;
 use js::rust::{HandleObject, HandleValue};
@@ -20,9 +23,10 @@ use crate::dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{
     ImageEncodeOptions, OffscreenCanvasMethods,
     OffscreenRenderingContext as RootedOffscreenRenderingContext,
 };
+use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanvas;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
-use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object_with_proto};
+use crate::dom::bindings::reflector::{DomGlobal, DomObject, reflect_dom_object_with_proto};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone::StructuredData;
@@ -32,8 +36,12 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::imagebitmap::ImageBitmap;
+use crate::dom::imagebitmaprenderingcontext::ImageBitmapRenderingContext;
+use crate::dom::node::NodeTraits;
 use crate::dom::offscreencanvasrenderingcontext2d::OffscreenCanvasRenderingContext2D;
 use crate::dom::promise::Promise;
+use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::{CanGc, JSContext};
 
@@ -52,6 +60,14 @@ pub(crate) struct OffscreenCanvas {
 
     /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
     placeholder: Option<WeakRef<HTMLCanvasElement>>,
+
+    /// The placeholder canvas element of this canvas when it was transferred from the
+    /// global of the element.
+    #[no_trace]
+    remote_placeholder: Cell<Option<OffscreenCanvasPlaceholder>>,
+
+    /// Whether a task to present the bitmap in the placeholder canvas element is queued.
+    commit_pending: Cell<bool>,
 }
 
 impl OffscreenCanvas {
@@ -66,6 +82,8 @@ impl OffscreenCanvas {
             height: Cell::new(height),
             context: DomRefCell::new(None),
             placeholder,
+            remote_placeholder: Cell::new(None),
+            commit_pending: Cell::new(false),
         }
     }
 
@@ -140,11 +158,128 @@ impl OffscreenCanvas {
         Some(context)
     }
 
+    pub(crate) fn get_or_init_bitmaprenderer_context(
+        &self,
+        cx: JSContext,
+        options: HandleValue,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<ImageBitmapRenderingContext>> {
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                OffscreenRenderingContext::BitmapRenderer(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let settings = HTMLCanvasElement::get_bitmaprenderer_settings(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(self));
+        let context =
+            ImageBitmapRenderingContext::new(&self.global(), &canvas, settings.alpha, can_gc);
+        *self.context.borrow_mut() = Some(OffscreenRenderingContext::BitmapRenderer(
+            Dom::from_ref(&*context),
+        ));
+        Some(context)
+    }
+
+    pub(crate) fn get_or_init_webgl_context(
+        &self,
+        cx: JSContext,
+        options: HandleValue,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<WebGLRenderingContext>> {
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                OffscreenRenderingContext::WebGL(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let attrs = HTMLCanvasElement::get_gl_attributes(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(self));
+        let context = WebGLRenderingContext::new(
+            &self.global(),
+            &canvas,
+            WebGLVersion::WebGL1,
+            self.get_size(),
+            attrs,
+            can_gc,
+        )?;
+        *self.context.borrow_mut() =
+            Some(OffscreenRenderingContext::WebGL(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
+    pub(crate) fn get_or_init_webgl2_context(
+        &self,
+        cx: JSContext,
+        options: HandleValue,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<WebGL2RenderingContext>> {
+        if !WebGL2RenderingContext::is_webgl2_enabled(cx, self.global().reflector().get_jsobject())
+        {
+            return None;
+        }
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                OffscreenRenderingContext::WebGL2(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let attrs = HTMLCanvasElement::get_gl_attributes(cx, options)?;
+        let canvas = HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(DomRoot::from_ref(self));
+        let context =
+            WebGL2RenderingContext::new(&self.global(), &canvas, self.get_size(), attrs, can_gc)?;
+        *self.context.borrow_mut() =
+            Some(OffscreenRenderingContext::WebGL2(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
     pub(crate) fn placeholder(&self) -> Option<DomRoot<HTMLCanvasElement>> {
         self.placeholder
             .as_ref()
             .and_then(|placeholder| placeholder.root())
     }
+
+    fn has_placeholder(&self) -> bool {
+        self.placeholder.is_some() || self.remote_placeholder.get().is_some()
+    }
+
+    /// Queue a task to present the bitmap in the placeholder canvas element, if there is
+    /// one, which is done once for all the drawing of a task.
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
+    pub(crate) fn mark_as_dirty(&self) {
+        if !self.has_placeholder() || self.commit_pending.replace(true) {
+            return;
+        }
+
+        let this = Trusted::new(self);
+        self.global().task_manager().rendering_task_source().queue(
+            task!(commit_offscreen_canvas: move || {
+                this.root().commit(CanGc::note());
+            }),
+        );
+    }
+
+    /// Update the rendering of the context and present its image in the placeholder
+    /// canvas element, in this global or through the constellation in the one of the
+    /// element.
+    fn commit(&self, can_gc: CanGc) {
+        self.commit_pending.set(false);
+
+        let Some(context) = self.context() else {
+            return;
+        };
+        context.update_rendering();
+        let image_key = context.image_key();
+        drop(context);
+        let size = self.get_size();
+
+        if let Some(canvas) = self.placeholder() {
+            canvas.update_placeholder(image_key, size, can_gc);
+        } else if let Some(placeholder) = self.remote_placeholder.get() {
+            let _ = self.global().script_to_constellation_chan().send(
+                ScriptToConstellationMessage::UpdatePlaceholderCanvas(placeholder, image_key, size),
+            );
+        }
+    }
 }
 
 impl Transferable for OffscreenCanvas {
@@ -167,11 +302,6 @@ impl Transferable for OffscreenCanvas {
             return Err(Error::InvalidState);
         }
 
-        // TODO(#37882): Allow to transfer with a placeholder canvas element.
-        if self.placeholder.is_some() {
-            return Err(Error::InvalidState);
-        }
-
         // Step 2. Set value's context mode to detached.
         *self.context.borrow_mut() = Some(OffscreenRenderingContext::Detached);
 
@@ -189,10 +319,23 @@ impl Transferable for OffscreenCanvas {
         // TODO(#37918) Step 7. Set dataHolder.[[Language]] to language and
         // dataHolder.[[Direction]] to direction.
 
-        // TODO(#37882) Step 8. Set dataHolder.[[PlaceholderCanvas]] to be a
-        // weak reference to value's placeholder canvas element, if value has
-        // one, or null if it does not.
-        let transferred = TransferableOffscreenCanvas { width, height };
+        // Step 8. Set dataHolder.[[PlaceholderCanvas]] to be a weak reference to
+        // value's placeholder canvas element, if value has one, or null if it
+        // does not.
+        // NOTE: The document of the canvas element keeps a weak reference to it,
+        // under an id that lets the frames of the canvas reach it from another global.
+        let placeholder = match self.placeholder() {
+            Some(canvas) => Some(OffscreenCanvasPlaceholder {
+                pipeline_id: canvas.owner_global().pipeline_id(),
+                canvas_id: canvas.owner_document().register_placeholder_canvas(&canvas),
+            }),
+            None => self.remote_placeholder.get(),
+        };
+        let transferred = TransferableOffscreenCanvas {
+            width,
+            height,
+            placeholder,
+        };
 
         Ok((OffscreenCanvasId::new(), transferred))
     }
@@ -211,18 +354,20 @@ impl Transferable for OffscreenCanvas {
         // dataHolder.[[Language]] and its inherited direction to
         // dataHolder.[[Direction]].
 
-        // TODO(#37882) Step 3. If dataHolder.[[PlaceholderCanvas]] is not null,
-        // set value's placeholder canvas element to
-        // dataHolder.[[PlaceholderCanvas]] (while maintaining the weak
-        // reference semantics).
-        Ok(OffscreenCanvas::new(
+        let canvas = OffscreenCanvas::new(
             owner,
             None,
             transferred.width,
             transferred.height,
             None,
             CanGc::note(),
-        ))
+        );
+
+        // Step 3. If dataHolder.[[PlaceholderCanvas]] is not null, set value's
+        // placeholder canvas element to dataHolder.[[PlaceholderCanvas]] (while
+        // maintaining the weak reference semantics).
+        canvas.remote_placeholder.set(transferred.placeholder);
+        Ok(canvas)
     }
 
     fn serialized_storage<'a>(
@@ -252,9 +397,9 @@ impl OffscreenCanvasMethods<crate::DomTypeHolder> for OffscreenCanvas {
     /// <https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-getcontext>
     fn GetContext(
         &self,
-        _cx: JSContext,
+        cx: JSContext,
         id: DOMString,
-        _options: HandleValue,
+        options: HandleValue,
         can_gc: CanGc,
     ) -> Fallible<Option<RootedOffscreenRenderingContext>> {
         // Step 3. Throw an "InvalidStateError" DOMException if the
@@ -267,12 +412,15 @@ impl OffscreenCanvasMethods<crate::DomTypeHolder> for OffscreenCanvas {
             "2d" => Ok(self
                 .get_or_init_2d_context(can_gc)
                 .map(RootedOffscreenRenderingContext::OffscreenCanvasRenderingContext2D)),
-            /*"webgl" | "experimental-webgl" => self
-                .get_or_init_webgl_context(cx, options)
-                .map(OffscreenRenderingContext::WebGLRenderingContext),
-            "webgl2" | "experimental-webgl2" => self
-                .get_or_init_webgl2_context(cx, options)
-                .map(OffscreenRenderingContext::WebGL2RenderingContext),*/
+            "bitmaprenderer" => Ok(self
+                .get_or_init_bitmaprenderer_context(cx, options, can_gc)
+                .map(RootedOffscreenRenderingContext::ImageBitmapRenderingContext)),
+            "webgl" | "experimental-webgl" => Ok(self
+                .get_or_init_webgl_context(cx, options, can_gc)
+                .map(RootedOffscreenRenderingContext::WebGLRenderingContext)),
+            "webgl2" | "experimental-webgl2" => Ok(self
+                .get_or_init_webgl2_context(cx, options, can_gc)
+                .map(RootedOffscreenRenderingContext::WebGL2RenderingContext)),
             _ => Err(Error::Type(String::from(
                 "Unrecognized OffscreenCanvas context type",
             ))),
@@ -8,6 +8,7 @@ use crate::dom::bindings::codegen::UnionTypes::HTMLCanvasElementOrOffscreenCanva
 use canvas_traits::canvas::Canvas2dMsg;
 use dom_struct::dom_struct;
 use pixels::Snapshot;
+use webrender_api::ImageKey;
 
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
     CanvasDirection, CanvasFillRule, CanvasImageSource, CanvasLineCap, CanvasLineJoin,
@@ -15,7 +16,10 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasRenderingContext2DBinding::OffscreenCanvasRenderingContext2DMethods;
-use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrCanvasGradientOrCanvasPattern,
+    UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+};
 use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object;
@@ -95,6 +99,18 @@ impl CanvasContext for OffscreenCanvasRenderingContext2D {
     fn origin_is_clean(&self) -> bool {
         self.context.origin_is_clean()
     }
+
+    fn mark_as_dirty(&self) {
+        self.context.mark_as_dirty()
+    }
+
+    fn update_rendering(&self) {
+        self.context.update_rendering()
+    }
+
+    fn image_key(&self) -> Option<ImageKey> {
+        self.context.image_key()
+    }
 }
 
 impl OffscreenCanvasRenderingContext2DMethods<crate::DomTypeHolder>
@@ -163,6 +179,16 @@ impl OffscreenCanvasRenderingContext2DMethods<crate::DomTypeHolder>
         self.context.SetShadowColor(value, can_gc)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    fn Filter(&self) -> DOMString {
+        self.context.Filter()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+    fn SetFilter(&self, value: DOMString, can_gc: CanGc) {
+        self.context.SetFilter(value, can_gc)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-strokestyle
     fn StrokeStyle(&self) -> StringOrCanvasGradientOrCanvasPattern {
         self.context.StrokeStyle()
@@ -210,6 +236,17 @@ impl OffscreenCanvasRenderingContext2DMethods<crate::DomTypeHolder>
             .CreateRadialGradient(x0, y0, r0, x1, y1, r1, can_gc)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+    fn CreateConicGradient(
+        &self,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> DomRoot<CanvasGradient> {
+        self.context.CreateConicGradient(start_angle, x, y, can_gc)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     fn CreatePattern(
         &self,
@@ -569,6 +606,18 @@ impl OffscreenCanvasRenderingContext2DMethods<crate::DomTypeHolder>
         self.context.Rect(x, y, width, height)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn RoundRect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        self.context.RoundRect(x, y, width, height, radii)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         self.context.QuadraticCurveTo(cpx, cpy, x, y)
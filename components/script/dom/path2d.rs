@@ -9,10 +9,14 @@ use dom_struct::dom_struct;
 use js::rust::HandleObject;
 use script_bindings::str::DOMString;
 
+use crate::canvas_state::normalize_round_rect_radii;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::Path2DMethods;
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::reflector::{Reflector, reflect_dom_object_with_proto};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::dommatrixreadonly::dommatrix2dinit_to_matrix;
 use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
@@ -52,10 +56,58 @@ impl Path2D {
 
 impl Path2DMethods<crate::DomTypeHolder> for Path2D {
     /// <https://html.spec.whatwg.org/multipage/#dom-path2d-addpath>
-    fn AddPath(&self, other: &Path2D) {
-        let other = other.segments();
+    fn AddPath(&self, other: &Path2D, transform: &DOMMatrix2DInit) -> ErrorResult {
+        // Step 1. If the Path2D object path has no subpaths, then return.
+        let mut other = other.segments();
+        if other.0.elements().is_empty() {
+            return Ok(());
+        }
+
+        // Step 2. Let matrix be the result of creating a DOMMatrix from the 2D dictionary
+        // transform.
+        let matrix = dommatrix2dinit_to_matrix(transform)?;
+
+        // Step 3. If one or more of matrix's m11 element, m12 element, m21 element,
+        // m22 element, m41 element, or m42 element are infinite or NaN, then return.
+        if !matrix.to_array().iter().all(|value| value.is_finite()) {
+            return Ok(());
+        }
+
+        // Step 4. Create a copy of all the subpaths in path. Let c be this copy.
+        // Step 5. Transform all the coordinates and lines in c by the transform matrix matrix.
+        other.transform(matrix);
+
+        // Step 6. Let (x, y) be the last point in the last subpath of c.
+        let last_point = other.last_point();
+
         // Step 7. Add all the subpaths in c to a.
-        self.path.borrow_mut().0.extend(other.0);
+        let mut path = self.path.borrow_mut();
+        path.0.extend(other.0);
+
+        // Step 8. Create a new subpath in a with (x, y) as the only point in the subpath.
+        if let Some(point) = last_point {
+            path.move_to(point.x, point.y);
+        }
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn RoundRect(
+        &self,
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        // Step 1. If any of x, y, w, or h are infinite or NaN, then return.
+        if ![x, y, w, h].iter().all(|value| value.is_finite()) {
+            return Ok(());
+        }
+        if let Some(radii) = normalize_round_rect_radii(radii)? {
+            self.path.borrow_mut().round_rect(x, y, w, h, radii);
+        }
+        Ok(())
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-closepath>
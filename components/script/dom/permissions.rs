@@ -4,8 +4,9 @@
 
 use std::rc::Rc;
 
+use constellation_traits::ScriptToConstellationMessage;
 use dom_struct::dom_struct;
-use embedder_traits::{self, AllowOrDeny, EmbedderMsg, PermissionFeature};
+use embedder_traits::{self, AllowOrDeny, EmbedderMsg, PermissionDecision, PermissionFeature};
 use ipc_channel::ipc;
 use js::conversions::ConversionResult;
 use js::jsapi::JSObject;
@@ -140,11 +141,7 @@ impl Permissions {
 
                     Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name);
+                        set_permission_store_entry(&self.global(), root_desc.name, None, false);
 
                         // (Revoke) Step 4.
                         Bluetooth::permission_revoke(&bluetooth_desc, &result, can_gc)
@@ -172,11 +169,7 @@ impl Permissions {
 
                     Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name);
+                        set_permission_store_entry(&self.global(), root_desc.name, None, false);
 
                         // (Revoke) Step 4.
                         Permissions::permission_revoke(&root_desc, &status, can_gc);
@@ -270,11 +263,7 @@ impl PermissionAlgorithm for Permissions {
                 // https://w3c.github.io/permissions/#request-permission-to-use (Step 3 - 4)
                 let permission_name = status.get_query();
                 let globalscope = GlobalScope::current().expect("No current global object");
-                let state = prompt_user_from_embedder(permission_name, &globalscope);
-                globalscope
-                    .permission_state_invocation_results()
-                    .borrow_mut()
-                    .insert(permission_name, state);
+                prompt_user_from_embedder(permission_name, &globalscope);
             },
 
             // Step 2.
@@ -331,21 +320,77 @@ pub(crate) fn descriptor_permission_state(
     //
     // TODO: We aren't making a key based on the descriptor, but on the descriptor's name. This really
     // only matters for WebBluetooth, which adds more fields to the descriptor beyond the name.
-    if let Some(entry) = global_scope
-        .permission_state_invocation_results()
-        .borrow()
-        .get(&feature)
-    {
-        return *entry;
+    if let Some(entry) = get_permission_store_entry(&global_scope, feature) {
+        return entry;
     }
 
     // Step 8. Return the PermissionState enum value that represents the permission state
     // of feature, taking into account any permission state constraints for descriptor's
     // name.
-    PermissionState::Prompt
+    match feature {
+        // <https://w3c.github.io/clipboard-apis/#clipboard-write-permission>
+        // Writing to the clipboard is allowed for documents that the user interacts with.
+        PermissionName::Clipboard_write => PermissionState::Granted,
+        _ => PermissionState::Prompt,
+    }
+}
+
+/// <https://w3c.github.io/permissions/#dfn-get-a-permission-store-entry>
+///
+/// The permission store is kept by the constellation, so that decisions are shared by all
+/// documents of an origin and can outlive the session if the user asked to remember them.
+fn get_permission_store_entry(
+    global_scope: &GlobalScope,
+    feature: PermissionName,
+) -> Option<PermissionState> {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    let message = ScriptToConstellationMessage::GetPermissionDecision(
+        global_scope.origin().immutable().clone(),
+        feature.convert(),
+        sender,
+    );
+    global_scope
+        .script_to_constellation_chan()
+        .send(message)
+        .ok()?;
+
+    match receiver.recv().ok()?? {
+        AllowOrDeny::Allow => Some(PermissionState::Granted),
+        AllowOrDeny::Deny => Some(PermissionState::Denied),
+    }
 }
 
-fn prompt_user_from_embedder(name: PermissionName, global_scope: &GlobalScope) -> PermissionState {
+/// <https://w3c.github.io/permissions/#dfn-set-a-permission-store-entry>
+///
+/// Setting the entry to "prompt" removes it. If `remember` is true, the entry is kept in the
+/// profile and applies to later sessions too.
+fn set_permission_store_entry(
+    global_scope: &GlobalScope,
+    feature: PermissionName,
+    state: Option<PermissionState>,
+    remember: bool,
+) {
+    let decision = match state {
+        Some(PermissionState::Granted) => Some(AllowOrDeny::Allow),
+        Some(PermissionState::Denied) => Some(AllowOrDeny::Deny),
+        Some(PermissionState::Prompt) | None => None,
+    };
+    let _ = global_scope.script_to_constellation_chan().send(
+        ScriptToConstellationMessage::SetPermissionDecision(
+            global_scope.origin().immutable().clone(),
+            feature.convert(),
+            decision,
+            remember,
+        ),
+    );
+}
+
+/// Ask the user whether the origin of the given global may use the given feature, and
+/// store the answer in the permission store, returning the new permission state.
+pub(crate) fn prompt_user_from_embedder(
+    name: PermissionName,
+    global_scope: &GlobalScope,
+) -> PermissionState {
     let Some(webview_id) = global_scope.webview_id() else {
         warn!("Requesting permissions from non-webview-associated global scope");
         return PermissionState::Denied;
@@ -353,21 +398,33 @@ fn prompt_user_from_embedder(name: PermissionName, global_scope: &GlobalScope) -
     let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
     global_scope.send_to_embedder(EmbedderMsg::PromptPermission(
         webview_id,
+        global_scope.origin().immutable().clone(),
         name.convert(),
         sender,
     ));
 
-    match receiver.recv() {
-        Ok(AllowOrDeny::Allow) => PermissionState::Granted,
-        Ok(AllowOrDeny::Deny) => PermissionState::Denied,
+    let (state, remember) = match receiver.recv() {
+        Ok(PermissionDecision {
+            allow_or_deny,
+            remember,
+        }) => {
+            let state = match allow_or_deny {
+                AllowOrDeny::Allow => PermissionState::Granted,
+                AllowOrDeny::Deny => PermissionState::Denied,
+            };
+            (state, remember)
+        },
         Err(e) => {
             warn!(
                 "Failed to receive permission state from embedder ({:?}).",
                 e
             );
-            PermissionState::Denied
+            return PermissionState::Denied;
         },
-    }
+    };
+
+    set_permission_store_entry(global_scope, name, Some(state), remember);
+    state
 }
 
 impl Convert<PermissionFeature> for PermissionName {
@@ -384,6 +441,9 @@ impl Convert<PermissionFeature> for PermissionName {
             PermissionName::Background_sync => PermissionFeature::BackgroundSync,
             PermissionName::Bluetooth => PermissionFeature::Bluetooth,
             PermissionName::Persistent_storage => PermissionFeature::PersistentStorage,
+            PermissionName::Clipboard_read => PermissionFeature::ClipboardRead,
+            PermissionName::Clipboard_write => PermissionFeature::ClipboardWrite,
+            PermissionName::Storage_access => PermissionFeature::StorageAccess,
         }
     }
 }
@@ -9,10 +9,13 @@ use dom_struct::dom_struct;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
     PermissionDescriptor, PermissionName, PermissionState, PermissionStatusMethods,
 };
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::descriptor_permission_state;
 use crate::script_runtime::CanGc;
 
 // https://w3c.github.io/permissions/#permissionstatus
@@ -37,11 +40,13 @@ impl PermissionStatus {
         query: &PermissionDescriptor,
         can_gc: CanGc,
     ) -> DomRoot<PermissionStatus> {
-        reflect_dom_object(
+        let permission_status = reflect_dom_object(
             Box::new(PermissionStatus::new_inherited(query.name)),
             global,
             can_gc,
-        )
+        );
+        global.track_permission_status(&permission_status);
+        permission_status
     }
 
     pub(crate) fn set_state(&self, state: PermissionState) {
@@ -51,6 +56,31 @@ impl PermissionStatus {
     pub(crate) fn get_query(&self) -> PermissionName {
         self.query.get()
     }
+
+    /// <https://w3c.github.io/permissions/#dfn-permissionstatus-update-steps>
+    ///
+    /// Run when the decision of the user about the permission of this status changes.
+    pub(crate) fn run_the_update_steps(&self) {
+        let this = Trusted::new(self);
+        self.global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task!(permission_status_update_steps: move || {
+                let this = this.root();
+
+                // Step 2. Let originalState be status's state.
+                // Step 3. Run the permission query algorithm with status's query and status.
+                let new_state =
+                    descriptor_permission_state(this.get_query(), Some(&this.global()));
+
+                // Step 4. If originalState is not equal to status's state, fire an event
+                // named change at status.
+                if this.state.replace(new_state) != new_state {
+                    this.upcast::<EventTarget>()
+                        .fire_event(atom!("change"), CanGc::note());
+                }
+            }));
+    }
 }
 
 impl PermissionStatusMethods<crate::DomTypeHolder> for PermissionStatus {
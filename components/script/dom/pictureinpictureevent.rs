@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PictureInPictureEventBinding;
+use crate::dom::bindings::codegen::Bindings::PictureInPictureEventBinding::PictureInPictureEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/picture-in-picture/#pictureinpictureevent>
+#[dom_struct]
+pub(crate) struct PictureInPictureEvent {
+    event: Event,
+    picture_in_picture_window: Dom<PictureInPictureWindow>,
+}
+
+impl PictureInPictureEvent {
+    fn new_inherited(picture_in_picture_window: &PictureInPictureWindow) -> PictureInPictureEvent {
+        PictureInPictureEvent {
+            event: Event::new_inherited(),
+            picture_in_picture_window: Dom::from_ref(picture_in_picture_window),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        picture_in_picture_window: &PictureInPictureWindow,
+        can_gc: CanGc,
+    ) -> DomRoot<PictureInPictureEvent> {
+        Self::new_with_proto(
+            window,
+            None,
+            type_,
+            bubbles,
+            cancelable,
+            picture_in_picture_window,
+            can_gc,
+        )
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        picture_in_picture_window: &PictureInPictureWindow,
+        can_gc: CanGc,
+    ) -> DomRoot<PictureInPictureEvent> {
+        let event = reflect_dom_object_with_proto(
+            Box::new(PictureInPictureEvent::new_inherited(
+                picture_in_picture_window,
+            )),
+            window,
+            proto,
+            can_gc,
+        );
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+}
+
+impl PictureInPictureEventMethods<crate::DomTypeHolder> for PictureInPictureEvent {
+    /// <https://w3c.github.io/picture-in-picture/#dom-pictureinpictureevent-pictureinpictureevent>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        type_: DOMString,
+        init: &PictureInPictureEventBinding::PictureInPictureEventInit,
+    ) -> Fallible<DomRoot<PictureInPictureEvent>> {
+        Ok(PictureInPictureEvent::new_with_proto(
+            window,
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.pictureInPictureWindow,
+            can_gc,
+        ))
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#dom-pictureinpictureevent-pictureinpicturewindow>
+    fn PictureInPictureWindow(&self) -> DomRoot<PictureInPictureWindow> {
+        DomRoot::from_ref(&*self.picture_in_picture_window)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
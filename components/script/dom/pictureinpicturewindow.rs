@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use webrender_api::units::DeviceIntSize;
+
+use crate::dom::bindings::codegen::Bindings::PictureInPictureWindowBinding::PictureInPictureWindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/picture-in-picture/#pictureinpicturewindow>
+#[dom_struct]
+pub(crate) struct PictureInPictureWindow {
+    eventtarget: EventTarget,
+    /// The size of the window, or zero once it has been closed.
+    #[no_trace]
+    size: Cell<DeviceIntSize>,
+}
+
+impl PictureInPictureWindow {
+    fn new_inherited(size: DeviceIntSize) -> PictureInPictureWindow {
+        PictureInPictureWindow {
+            eventtarget: EventTarget::new_inherited(),
+            size: Cell::new(size),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        size: DeviceIntSize,
+        can_gc: CanGc,
+    ) -> DomRoot<PictureInPictureWindow> {
+        reflect_dom_object(
+            Box::new(PictureInPictureWindow::new_inherited(size)),
+            window,
+            can_gc,
+        )
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#ref-for-dom-pictureinpicturewindow-onresize>
+    pub(crate) fn resize(&self, size: DeviceIntSize, can_gc: CanGc) {
+        if self.size.get() == size {
+            return;
+        }
+        self.size.set(size);
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("resize"), can_gc);
+    }
+
+    /// Called when the window is closed. Its width and height are zero from then on.
+    pub(crate) fn close(&self) {
+        self.size.set(DeviceIntSize::zero());
+    }
+}
+
+impl PictureInPictureWindowMethods<crate::DomTypeHolder> for PictureInPictureWindow {
+    /// <https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-width>
+    fn Width(&self) -> i32 {
+        self.size.get().width
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-height>
+    fn Height(&self) -> i32 {
+        self.size.get().height
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-onresize
+    event_handler!(resize, GetOnresize, SetOnresize);
+}
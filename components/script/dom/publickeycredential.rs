@@ -0,0 +1,115 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use base64::Engine;
+use dom_struct::dom_struct;
+use js::typedarray::{ArrayBuffer, ArrayBufferU8};
+
+use crate::dom::authenticatorresponse::AuthenticatorResponse;
+use crate::dom::bindings::buffer_source::HeapBufferSource;
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::{
+    AuthenticationExtensionsClientOutputs, PublicKeyCredentialMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::credential::Credential;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/webauthn/#iface-pkcredential>
+#[dom_struct]
+pub(crate) struct PublicKeyCredential {
+    credential: Credential,
+    #[ignore_malloc_size_of = "mozjs"]
+    raw_id: HeapBufferSource<ArrayBufferU8>,
+    response: Dom<AuthenticatorResponse>,
+    /// Whether the authenticator is built into the device.
+    platform_attachment: bool,
+}
+
+impl PublicKeyCredential {
+    fn new_inherited(
+        id: USVString,
+        response: &AuthenticatorResponse,
+        platform_attachment: bool,
+    ) -> PublicKeyCredential {
+        PublicKeyCredential {
+            credential: Credential::new_inherited(id, DOMString::from("public-key")),
+            raw_id: HeapBufferSource::default(),
+            response: Dom::from_ref(response),
+            platform_attachment,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        raw_id: &[u8],
+        response: &AuthenticatorResponse,
+        platform_attachment: bool,
+        can_gc: CanGc,
+    ) -> DomRoot<PublicKeyCredential> {
+        // The id is the base64url encoding of the raw id.
+        // <https://w3c.github.io/webauthn/#dom-publickeycredential-identifier-slot>
+        let id = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw_id);
+        let credential = reflect_dom_object(
+            Box::new(PublicKeyCredential::new_inherited(
+                USVString(id),
+                response,
+                platform_attachment,
+            )),
+            global,
+            can_gc,
+        );
+        credential
+            .raw_id
+            .set_data(GlobalScope::get_cx(), raw_id, can_gc)
+            .expect("Failed to set raw id of public key credential.");
+        credential
+    }
+}
+
+impl PublicKeyCredentialMethods<crate::DomTypeHolder> for PublicKeyCredential {
+    /// <https://w3c.github.io/webauthn/#dom-publickeycredential-rawid>
+    fn RawId(&self, _cx: JSContext) -> ArrayBuffer {
+        self.raw_id
+            .get_typed_array()
+            .expect("Failed to get raw id of public key credential.")
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-publickeycredential-response>
+    fn Response(&self) -> DomRoot<AuthenticatorResponse> {
+        DomRoot::from_ref(&*self.response)
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-publickeycredential-authenticatorattachment>
+    fn GetAuthenticatorAttachment(&self) -> Option<DOMString> {
+        Some(DOMString::from(if self.platform_attachment {
+            "platform"
+        } else {
+            "cross-platform"
+        }))
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-publickeycredential-getclientextensionresults>
+    fn GetClientExtensionResults(&self) -> AuthenticationExtensionsClientOutputs {
+        // No extensions are supported.
+        AuthenticationExtensionsClientOutputs::empty()
+    }
+
+    /// <https://w3c.github.io/webauthn/#dom-publickeycredential-isuserverifyingplatformauthenticatoravailable>
+    fn IsUserVerifyingPlatformAuthenticatorAvailable(
+        global: &GlobalScope,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        // Whether the embedder has a platform authenticator is not known up front, so
+        // relying parties should not expect one, but can still try to create a credential.
+        let promise = Promise::new(global, can_gc);
+        promise.resolve_native(&false, can_gc);
+        promise
+    }
+}
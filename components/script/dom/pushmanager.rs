@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use base64::Engine;
+use dom_struct::dom_struct;
+use embedder_traits::{self, EmbedderMsg, PushSubscriptionError};
+
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionStatus_Binding::PermissionStatusMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionDescriptor, PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::PushManagerBinding::{
+    PushManagerMethods, PushSubscriptionOptionsInit,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBufferOrString;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{PermissionAlgorithm, Permissions, descriptor_permission_state};
+use crate::dom::permissionstatus::PermissionStatus;
+use crate::dom::promise::Promise;
+use crate::dom::pushsubscription::PushSubscription;
+use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/push-api/#pushmanager-interface>
+///
+/// Servo has no push service of its own, so subscriptions are created by the push service
+/// of the embedder, if it has one.
+#[dom_struct]
+pub(crate) struct PushManager {
+    reflector_: Reflector,
+    registration: Dom<ServiceWorkerRegistration>,
+}
+
+impl PushManager {
+    fn new_inherited(registration: &ServiceWorkerRegistration) -> PushManager {
+        PushManager {
+            reflector_: Reflector::new(),
+            registration: Dom::from_ref(registration),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        registration: &ServiceWorkerRegistration,
+        can_gc: CanGc,
+    ) -> DomRoot<PushManager> {
+        reflect_dom_object(
+            Box::new(PushManager::new_inherited(registration)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl PushManagerMethods<crate::DomTypeHolder> for PushManager {
+    /// <https://w3c.github.io/push-api/#dom-pushmanager-subscribe>
+    fn Subscribe(
+        &self,
+        options: &RootedTraceableBox<PushSubscriptionOptionsInit>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. If the options argument has a userVisibleOnly value set to false and the
+        // user agent requires it to be true, reject promise with a "NotAllowedError"
+        // DOMException and terminate these steps.
+        // Every push message has to be shown to the user as a notification.
+        if !options.userVisibleOnly {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return promise;
+        }
+
+        // Step 3. If the options argument includes a non-null value for the
+        // applicationServerKey attribute, run the following substeps:
+        let application_server_key = match options.applicationServerKey.as_ref() {
+            Some(key) => match application_server_key_bytes(key) {
+                Ok(key) => Some(key),
+                Err(error) => {
+                    promise.reject_error(error, can_gc);
+                    return promise;
+                },
+            },
+            None => None,
+        };
+
+        // Step 4. Let registration be this's associated service worker registration.
+        // Step 5. If registration's active worker is null, reject promise with an
+        // "InvalidStateError" DOMException and terminate these steps.
+        if !self.registration.is_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 6. Let permission be request permission to use "push".
+        // Step 7. If permission is "denied", reject promise with a "NotAllowedError"
+        // DOMException and terminate these steps.
+        if request_push_permission(&global, can_gc) != PermissionState::Granted {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return promise;
+        }
+
+        // Step 8. If the Service Worker is already subscribed, run the following substeps:
+        // Step 9. Attempt to create a push subscription with options.
+        // Both are up to the push service of the embedder, the result is handled by
+        // `handle_response`.
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        global.send_to_embedder(EmbedderMsg::SubscribeToPush(
+            self.registration.scope().clone(),
+            application_server_key,
+            sender,
+        ));
+
+        promise
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-pushmanager-getsubscription>
+    fn GetSubscription(&self, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. Run the following substeps in parallel:
+        // Step 2.1. Retrieve the push subscription associated with the service worker
+        // registration.
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        global.send_to_embedder(EmbedderMsg::GetPushSubscription(
+            self.registration.scope().clone(),
+            sender,
+        ));
+
+        promise
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-pushmanager-permissionstate>
+    fn PermissionState(
+        &self,
+        _options: &RootedTraceableBox<PushSubscriptionOptionsInit>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. Retrieve the push permission status of the requesting webapp.
+        // Step 4. Resolve promise with a PermissionState providing the push permission
+        // status.
+        let state = descriptor_permission_state(PermissionName::Push, Some(&global));
+        promise.resolve_native(&state, can_gc);
+        promise
+    }
+}
+
+impl RoutedPromiseListener<Result<embedder_traits::PushSubscription, PushSubscriptionError>>
+    for PushManager
+{
+    fn handle_response(
+        &self,
+        response: Result<embedder_traits::PushSubscription, PushSubscriptionError>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            // Step 9. ... resolve promise with a PushSubscription providing the details of
+            // the new push subscription.
+            Ok(subscription) => {
+                let subscription = PushSubscription::new(
+                    &self.global(),
+                    self.registration.scope().clone(),
+                    subscription,
+                    can_gc,
+                );
+                promise.resolve_native(&subscription, can_gc);
+            },
+            Err(PushSubscriptionError::NotAllowed) => {
+                promise.reject_error(Error::NotAllowed, can_gc)
+            },
+            // Step 9. If an error occurs during the course of creating the push subscription,
+            // reject promise with an "AbortError" DOMException.
+            Err(PushSubscriptionError::PushServiceUnavailable) => {
+                promise.reject_error(Error::Abort, can_gc)
+            },
+        }
+    }
+}
+
+impl RoutedPromiseListener<Option<embedder_traits::PushSubscription>> for PushManager {
+    fn handle_response(
+        &self,
+        response: Option<embedder_traits::PushSubscription>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        // Step 2.2. If there is no such push subscription, resolve promise with null.
+        // Step 2.4. Resolve promise with a PushSubscription providing the details of the
+        // retrieved push subscription.
+        let subscription = response.map(|subscription| {
+            PushSubscription::new(
+                &self.global(),
+                self.registration.scope().clone(),
+                subscription,
+                can_gc,
+            )
+        });
+        promise.resolve_native(&subscription, can_gc);
+    }
+}
+
+/// Step 3 of <https://w3c.github.io/push-api/#dom-pushmanager-subscribe>, which gets the
+/// bytes of the applicationServerKey option.
+fn application_server_key_bytes(
+    key: &ArrayBufferViewOrArrayBufferOrString,
+) -> Result<Vec<u8>, Error> {
+    let bytes = match key {
+        // Step 3.1. If the applicationServerKey is provided as a DOMString, set its value
+        // to an ArrayBuffer containing the sequence of octets that result from decoding
+        // applicationServerKey using the base64url encoding.
+        // Step 3.2. If decoding fails, reject promise with an "InvalidCharacterError"
+        // DOMException and terminate these steps.
+        ArrayBufferViewOrArrayBufferOrString::String(key) => {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(key.trim_end_matches('='))
+                .map_err(|_| Error::InvalidCharacter)?
+        },
+        ArrayBufferViewOrArrayBufferOrString::ArrayBufferView(key) => key.to_vec(),
+        ArrayBufferViewOrArrayBufferOrString::ArrayBuffer(key) => key.to_vec(),
+    };
+
+    // Step 3.3. Ensure that applicationServerKey describes a valid point on the P-256
+    // curve. If its value is invalid, reject promise with an "InvalidAccessError"
+    // DOMException and terminate these steps.
+    // Only the uncompressed form of the point is checked for, the push service validates
+    // the point itself.
+    if bytes.len() != 65 || bytes[0] != 0x04 {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(bytes)
+}
+
+/// <https://w3c.github.io/permissions/#dfn-request-permission-to-use> for "push".
+fn request_push_permission(global: &GlobalScope, can_gc: CanGc) -> PermissionState {
+    let cx = GlobalScope::get_cx();
+    let promise = &Promise::new(global, can_gc);
+    let descriptor = PermissionDescriptor {
+        name: PermissionName::Push,
+    };
+    let status = PermissionStatus::new(global, &descriptor, can_gc);
+    Permissions::permission_request(cx, promise, &descriptor, &status);
+    status.State()
+}
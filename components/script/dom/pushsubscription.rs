@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::ptr;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::{self, EmbedderMsg};
+use js::jsapi::JSObject;
+use js::typedarray::{ArrayBuffer, ArrayBufferU8};
+use servo_url::ServoUrl;
+
+use crate::dom::bindings::buffer_source::create_buffer_source;
+use crate::dom::bindings::codegen::Bindings::PushSubscriptionBinding::{
+    PushEncryptionKeyName, PushSubscriptionMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/push-api/#pushsubscription-interface>
+#[dom_struct]
+pub(crate) struct PushSubscription {
+    reflector_: Reflector,
+    /// The scope of the service worker registration this subscription belongs to.
+    #[no_trace]
+    scope: ServoUrl,
+    #[no_trace]
+    subscription: embedder_traits::PushSubscription,
+}
+
+impl PushSubscription {
+    fn new_inherited(
+        scope: ServoUrl,
+        subscription: embedder_traits::PushSubscription,
+    ) -> PushSubscription {
+        PushSubscription {
+            reflector_: Reflector::new(),
+            scope,
+            subscription,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        scope: ServoUrl,
+        subscription: embedder_traits::PushSubscription,
+        can_gc: CanGc,
+    ) -> DomRoot<PushSubscription> {
+        reflect_dom_object(
+            Box::new(PushSubscription::new_inherited(scope, subscription)),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl PushSubscriptionMethods<crate::DomTypeHolder> for PushSubscription {
+    /// <https://w3c.github.io/push-api/#dom-pushsubscription-endpoint>
+    fn Endpoint(&self) -> USVString {
+        USVString(self.subscription.endpoint.as_str().to_owned())
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-pushsubscription-expirationtime>
+    fn GetExpirationTime(&self) -> Option<u64> {
+        self.subscription.expiration_time
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-pushsubscription-getkey>
+    fn GetKey(
+        &self,
+        cx: JSContext,
+        name: PushEncryptionKeyName,
+        can_gc: CanGc,
+    ) -> Fallible<Option<ArrayBuffer>> {
+        let key = match name {
+            PushEncryptionKeyName::P256dh => &self.subscription.p256dh,
+            PushEncryptionKeyName::Auth => &self.subscription.auth,
+        };
+        rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+        create_buffer_source::<ArrayBufferU8>(cx, key, array_buffer.handle_mut(), can_gc)
+            .map(Some)
+            .map_err(|_| Error::JSFailed)
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-pushsubscription-unsubscribe>
+    fn Unsubscribe(&self, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1. Let p be a new promise.
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 2. Run the following steps in parallel:
+        // The push service of the embedder deactivates the subscription and reports whether
+        // there was one, in `handle_response`.
+        let sender = route_promise(
+            &promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        global.send_to_embedder(EmbedderMsg::UnsubscribeFromPush(self.scope.clone(), sender));
+
+        // Step 3. Return p.
+        promise
+    }
+}
+
+impl RoutedPromiseListener<bool> for PushSubscription {
+    fn handle_response(&self, unsubscribed: bool, promise: &Rc<Promise>, can_gc: CanGc) {
+        // Step 2.3. Resolve p with true if the subscription was deactivated, or false if
+        // there was no subscription to deactivate.
+        promise.resolve_native(&unsubscribed, can_gc);
+    }
+}
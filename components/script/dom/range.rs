@@ -7,6 +7,7 @@ use std::cmp::{Ordering, PartialOrd};
 use std::iter;
 
 use dom_struct::dom_struct;
+use html5ever::{local_name, ns};
 use js::jsapi::JSTracer;
 use js::rust::HandleObject;
 
@@ -14,6 +15,7 @@ use crate::dom::abstractrange::{AbstractRange, BoundaryPoint, bp_position};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AbstractRangeBinding::AbstractRangeMethods;
 use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeListBinding::NodeListMethods;
 use crate::dom::bindings::codegen::Bindings::RangeBinding::{RangeConstants, RangeMethods};
@@ -32,7 +34,10 @@ use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domrect::DOMRect;
 use crate::dom::domrectlist::DOMRectList;
 use crate::dom::element::Element;
+use crate::dom::htmllielement::HTMLLIElement;
+use crate::dom::htmlolistelement::HTMLOListElement;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
+use crate::dom::htmlulistelement::HTMLUListElement;
 use crate::dom::node::{Node, NodeTraits, ShadowIncluding, UnbindContext};
 use crate::dom::selection::Selection;
 use crate::dom::text::Text;
@@ -327,6 +332,146 @@ impl Range {
         self.abstract_range().Collapsed()
     }
 
+    /// The text of this range as it is rendered, with the line breaks and the white space
+    /// processing that layout applies to its contents, the way `innerText` does for an
+    /// element. Text that isn't rendered falls back to the text content of the range.
+    pub(crate) fn rendered_text(&self, can_gc: CanGc) -> DOMString {
+        let mut start = (self.start_container(), self.start_offset());
+        let mut end = (self.end_container(), self.end_offset());
+
+        // Layout collects the text of the children of an element, so a range that spans the
+        // whole document is limited to the document element.
+        let ancestor = self.CommonAncestorContainer();
+        let root = match ancestor.downcast::<Document>() {
+            Some(document) => {
+                let Some(document_element) = document.GetDocumentElement() else {
+                    return DOMString::new();
+                };
+                let document_element = DomRoot::upcast::<Node>(document_element);
+                if start.0.is::<Document>() {
+                    start = (document_element.clone(), 0);
+                }
+                if end.0.is::<Document>() {
+                    end = (document_element.clone(), document_element.len());
+                }
+                document_element
+            },
+            None => match ancestor
+                .inclusive_ancestors(ShadowIncluding::No)
+                .find(|node| node.is::<Element>())
+            {
+                Some(root) => root,
+                None => return self.Stringifier(),
+            },
+        };
+
+        let is_rendered = root.is_connected() &&
+            root.downcast::<Element>()
+                .is_some_and(|element| element.has_css_layout_box(can_gc));
+        if !is_rendered {
+            return self.Stringifier();
+        }
+
+        root.owner_window()
+            .rendered_text_of_range_query(&root, (&start.0, start.1), (&end.0, end.1), can_gc)
+            .into()
+    }
+
+    /// The text of this range as it is copied to the clipboard as plain text. This is the
+    /// [rendered text](Self::rendered_text) of the range, with every list item that starts
+    /// inside of the range on a line of its own that begins with the marker of the item, so
+    /// that the numbering of ordered lists, which isn't part of the rendered text, is kept.
+    pub(crate) fn clipboard_text(&self, can_gc: CanGc) -> DOMString {
+        let (start, start_offset) = (self.start_container(), self.start_offset());
+        let (end, end_offset) = (self.end_container(), self.end_offset());
+        let list_items: Vec<_> = self
+            .CommonAncestorContainer()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLLIElement>)
+            .filter(|item| {
+                let item = item.upcast::<Node>();
+                bp_position(item, 0, &start, start_offset) == Some(Ordering::Greater) &&
+                    bp_position(item, 0, &end, end_offset) == Some(Ordering::Less)
+            })
+            .collect();
+        if list_items.is_empty() {
+            return self.rendered_text(can_gc);
+        }
+
+        // Split the range at the start of every list item, and join the rendered text of
+        // the pieces with the markers of the items.
+        let document = start.owner_doc();
+        let mut boundaries = vec![(start, start_offset)];
+        boundaries.extend(
+            list_items
+                .iter()
+                .map(|item| (DomRoot::from_ref(item.upcast::<Node>()), 0)),
+        );
+        boundaries.push((end, end_offset));
+
+        let mut text = String::new();
+        for (index, piece) in boundaries.windows(2).enumerate() {
+            if let Some(item) = index.checked_sub(1).map(|index| &list_items[index]) {
+                if !text.is_empty() {
+                    text.truncate(text.trim_end_matches('\n').len());
+                    text.push('\n');
+                }
+                text.push_str(&list_item_marker(item));
+            }
+            let ((piece_start, piece_start_offset), (piece_end, piece_end_offset)) =
+                (&piece[0], &piece[1]);
+            let piece = Range::new(
+                &document,
+                piece_start,
+                *piece_start_offset,
+                piece_end,
+                *piece_end_offset,
+                can_gc,
+            );
+            text.push_str(piece.rendered_text(can_gc).trim_start_matches('\n'));
+        }
+        DOMString::from(text)
+    }
+
+    /// The contents of this range serialized as a well-formed HTML fragment, as copied to
+    /// the clipboard alongside [`Self::clipboard_text`]. The contents are wrapped in copies
+    /// of the ancestors that they need to keep their meaning, like the formatting of the
+    /// selected text or the list and the table that the selected items and rows are part
+    /// of. A copied ordered list starts at the number of its first selected item.
+    pub(crate) fn html_fragment(&self, can_gc: CanGc) -> Option<DOMString> {
+        let fragment = self.CloneContents(can_gc).ok()?;
+        let fragment = fragment.upcast::<Node>();
+
+        let context = self
+            .CommonAncestorContainer()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .skip_while(|node| !node.is::<Element>())
+            .map_while(DomRoot::downcast::<Element>)
+            .take_while(|element| is_serialization_context(element));
+        for element in context {
+            let wrapper = element.upcast::<Node>().CloneNode(false, can_gc).ok()?;
+            if let Some(list) = element.downcast::<HTMLOListElement>() {
+                let first_item = list
+                    .upcast::<Node>()
+                    .children()
+                    .filter_map(DomRoot::downcast::<HTMLLIElement>)
+                    .find(|item| self.IntersectsNode(item.upcast()));
+                if let Some(ordinal) = first_item.and_then(|item| item.ordinal_value()) {
+                    wrapper.downcast::<Element>()?.set_string_attribute(
+                        &local_name!("start"),
+                        DOMString::from(ordinal.to_string()),
+                        can_gc,
+                    );
+                }
+            }
+            // Appending the fragment moves its children into the wrapper.
+            wrapper.AppendChild(fragment, can_gc).ok()?;
+            fragment.AppendChild(&wrapper, can_gc).ok()?;
+        }
+
+        Some(fragment.fragment_serialization_algorithm(false, can_gc))
+    }
+
     fn client_rects(
         &self,
         can_gc: CanGc,
@@ -392,6 +537,66 @@ impl Range {
     }
 }
 
+/// The marker of a list item in the plain text of a range, indented by the nesting level
+/// of its list.
+fn list_item_marker(item: &HTMLLIElement) -> String {
+    let depth = item
+        .upcast::<Node>()
+        .ancestors()
+        .filter(|ancestor| ancestor.is::<HTMLOListElement>() || ancestor.is::<HTMLUListElement>())
+        .count();
+    let indent = "    ".repeat(depth.saturating_sub(1));
+    match item.ordinal_value() {
+        Some(ordinal) => format!("{indent}{ordinal}. "),
+        None => format!("{indent}\u{2022} "),
+    }
+}
+
+/// Whether `element` is kept around the serialized contents of a range that are inside of
+/// it: the structure of lists and tables, which their items and rows need, and inline
+/// formatting, which the text in it needs.
+fn is_serialization_context(element: &Element) -> bool {
+    if *element.namespace() != ns!(html) {
+        return false;
+    }
+    matches!(
+        *element.local_name(),
+        local_name!("ol") |
+            local_name!("ul") |
+            local_name!("menu") |
+            local_name!("dl") |
+            local_name!("table") |
+            local_name!("thead") |
+            local_name!("tbody") |
+            local_name!("tfoot") |
+            local_name!("tr") |
+            local_name!("pre") |
+            local_name!("a") |
+            local_name!("abbr") |
+            local_name!("b") |
+            local_name!("cite") |
+            local_name!("code") |
+            local_name!("del") |
+            local_name!("em") |
+            local_name!("font") |
+            local_name!("i") |
+            local_name!("ins") |
+            local_name!("kbd") |
+            local_name!("mark") |
+            local_name!("q") |
+            local_name!("s") |
+            local_name!("samp") |
+            local_name!("small") |
+            local_name!("span") |
+            local_name!("strike") |
+            local_name!("strong") |
+            local_name!("sub") |
+            local_name!("sup") |
+            local_name!("u") |
+            local_name!("var")
+    )
+}
+
 enum StartOrEnd {
     Start,
     End,
@@ -22,6 +22,7 @@ use crate::dom::nodelist::NodeList;
 use crate::dom::range::WeakRangeVec;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::window::LayoutValue;
+use crate::editing::EditingHistory;
 
 //XXX(ferjm) Ideally merge NodeRareData and ElementRareData so they share
 //           storage.
@@ -91,4 +92,7 @@ pub(crate) struct ElementRareData {
 
     /// <https://drafts.csswg.org/css-shadow-parts/#dom-element-part>
     pub(crate) part: MutNullableDom<DOMTokenList>,
+
+    /// The undo and redo history of this element if it is an editing host.
+    pub(crate) editing_history: EditingHistory,
 }
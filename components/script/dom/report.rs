@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use malloc_size_of_derive::MallocSizeOf;
+use script_bindings::str::DOMString;
+
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::ReportMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::csppolicyviolationreport::SecurityPolicyViolationReport;
+use crate::dom::cspviolationreportbody::CSPViolationReportBody;
+use crate::dom::deprecationreportbody::{DeprecationReport, DeprecationReportBody};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::script_runtime::CanGc;
+
+/// A queued report, which is turned into a [`Report`] when it is given to a
+/// `ReportingObserver`.
+///
+/// <https://w3c.github.io/reporting/#concept-reports>
+#[derive(Clone, MallocSizeOf)]
+pub(crate) struct ReportData {
+    /// <https://w3c.github.io/reporting/#report-type>
+    pub(crate) type_: DOMString,
+    /// <https://w3c.github.io/reporting/#report-url>
+    pub(crate) url: DOMString,
+    /// <https://w3c.github.io/reporting/#report-body>
+    pub(crate) body: Option<ReportBodyData>,
+    /// <https://w3c.github.io/reporting/#report-destination>
+    pub(crate) destination: DOMString,
+    /// <https://w3c.github.io/reporting/#report-timestamp>, in milliseconds since the Unix
+    /// epoch.
+    pub(crate) timestamp: f64,
+    /// <https://w3c.github.io/reporting/#report-attempts>
+    pub(crate) attempts: u32,
+}
+
+/// The data of the body of each type of report.
+#[derive(Clone, MallocSizeOf)]
+pub(crate) enum ReportBodyData {
+    CspViolation(SecurityPolicyViolationReport),
+    Deprecation(DeprecationReport),
+}
+
+/// <https://w3c.github.io/reporting/#report>
+#[dom_struct]
+pub(crate) struct Report {
+    reflector_: Reflector,
+    type_: DOMString,
+    url: DOMString,
+    body: Option<Dom<ReportBody>>,
+}
+
+impl Report {
+    fn new_inherited(report: &ReportData, body: Option<&ReportBody>) -> Report {
+        Report {
+            reflector_: Reflector::new(),
+            type_: report.type_.clone(),
+            url: report.url.clone(),
+            body: body.map(Dom::from_ref),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, report: &ReportData, can_gc: CanGc) -> DomRoot<Report> {
+        let body: Option<DomRoot<ReportBody>> = report.body.as_ref().map(|body| match body {
+            ReportBodyData::CspViolation(violation) => DomRoot::upcast(
+                CSPViolationReportBody::new(global, violation.clone(), can_gc),
+            ),
+            ReportBodyData::Deprecation(deprecation) => DomRoot::upcast(
+                DeprecationReportBody::new(global, deprecation.clone(), can_gc),
+            ),
+        });
+        reflect_dom_object(
+            Box::new(Report::new_inherited(report, body.as_deref())),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl ReportMethods<crate::DomTypeHolder> for Report {
+    /// <https://w3c.github.io/reporting/#dom-report-type>
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-report-url>
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-report-body>
+    fn GetBody(&self) -> Option<DomRoot<ReportBody>> {
+        self.body.as_deref().map(DomRoot::from_ref)
+    }
+}
@@ -0,0 +1,21 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::reflector::Reflector;
+
+/// <https://w3c.github.io/reporting/#reportbody>
+#[dom_struct]
+pub(crate) struct ReportBody {
+    reflector_: Reflector,
+}
+
+impl ReportBody {
+    pub(crate) fn new_inherited() -> ReportBody {
+        ReportBody {
+            reflector_: Reflector::new(),
+        }
+    }
+}
@@ -4,7 +4,9 @@
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use constellation_traits::CrashReport;
 use headers::{ContentType, HeaderMapExt};
 use http::HeaderMap;
 use hyper_serde::Serde;
@@ -18,21 +20,30 @@ use net_traits::{
 };
 use script_bindings::str::DOMString;
 use serde::Serialize;
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 
-use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBody;
-use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::Report;
 use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::csp::Violation;
-use crate::dom::csppolicyviolationreport::serialize_disposition;
+use crate::dom::csppolicyviolationreport::{SecurityPolicyViolationReport, serialize_disposition};
+use crate::dom::deprecationreportbody::DeprecationReport;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::report::{ReportBodyData, ReportData};
+use crate::dom::reportingobserver::ReportingObserver;
 use crate::fetch::create_a_potential_cors_request;
 use crate::network_listener::{PreInvoke, ResourceTimingListener, submit_timing};
 use crate::script_runtime::CanGc;
 
+/// The time for which the reports generated by a global are collected before they are
+/// delivered together.
+pub(crate) const REPORT_DELIVERY_DELAY: Duration = Duration::from_secs(1);
+
+/// The number of attempts to deliver a report after which it is dropped.
+const MAX_REPORT_DELIVERY_ATTEMPTS: u32 = 3;
+
 /// <https://w3c.github.io/reporting/#endpoint>
 #[derive(Clone, Eq, Hash, MallocSizeOf, PartialEq)]
 pub(crate) struct ReportingEndpoint {
@@ -106,50 +117,86 @@ impl ReportingEndpoint {
         }
         Some(endpoints)
     }
+
+    /// The report to deliver to the "default" endpoint of `endpoints` if the pipeline of the
+    /// document at `url` crashes.
+    ///
+    /// <https://wicg.github.io/crash-reporting/#crash-report>
+    pub(crate) fn crash_report(
+        endpoints: &[ReportingEndpoint],
+        url: &ServoUrl,
+        origin: ImmutableOrigin,
+    ) -> Option<CrashReport> {
+        let endpoint = endpoints
+            .iter()
+            .find(|endpoint| endpoint.name.str() == "default")?;
+        let report = SerializedReport {
+            age: 0,
+            type_: "crash".to_owned(),
+            url: ReportingObserver::strip_url_for_reports(url.clone()),
+            user_agent: pref!(user_agent),
+            // The reason of the crash is not known when the report is generated.
+            body: Some(SerializedReportBody::Crash(CrashReportBody {})),
+        };
+        Some(CrashReport {
+            endpoint: endpoint.url.clone(),
+            origin,
+            body: serde_json::to_string(&[report]).ok()?,
+        })
+    }
+}
+
+/// The result of an attempt to deliver reports to an endpoint.
+///
+/// <https://w3c.github.io/reporting/#try-delivery>
+pub(crate) enum DeliveryResult {
+    Success,
+    RemoveEndpoint,
+    Failure,
 }
 
 pub(crate) trait SendReportsToEndpoints {
     /// <https://w3c.github.io/reporting/#send-reports>
-    fn send_reports_to_endpoints(&self, reports: Vec<Report>, endpoints: Vec<ReportingEndpoint>);
+    fn send_reports_to_endpoints(&self, reports: Vec<ReportData>);
     /// <https://w3c.github.io/reporting/#try-delivery>
-    fn attempt_to_deliver_reports_to_endpoints(
+    fn attempt_to_deliver_reports_to_endpoint(
         &self,
-        endpoint: &ServoUrl,
+        endpoint: &ReportingEndpoint,
         origin: ImmutableOrigin,
-        reports: &[&Report],
+        reports: Vec<ReportData>,
+    );
+    /// Steps 3.3.2 to 3.3.4 of <https://w3c.github.io/reporting/#send-reports>, which run once
+    /// the result of the attempt to deliver `reports` to the endpoint named `endpoint_name`
+    /// is known.
+    fn process_delivery_result(
+        &self,
+        endpoint_name: &DOMString,
+        result: DeliveryResult,
+        reports: Vec<ReportData>,
     );
     /// <https://w3c.github.io/reporting/#serialize-a-list-of-reports-to-json>
-    fn serialize_list_of_reports(reports: &[&Report]) -> Option<RequestBody>;
+    fn serialize_list_of_reports(reports: &mut [ReportData]) -> Option<RequestBody>;
 }
 
 impl SendReportsToEndpoints for GlobalScope {
-    fn send_reports_to_endpoints(
-        &self,
-        mut reports: Vec<Report>,
-        endpoints: Vec<ReportingEndpoint>,
-    ) {
+    fn send_reports_to_endpoints(&self, reports: Vec<ReportData>) {
+        let endpoints = self.reporting_endpoints();
         // Step 1. Let endpoint map be an empty map of endpoint objects to lists of report objects.
-        let mut endpoint_map: HashMap<&ReportingEndpoint, Vec<Report>> = HashMap::new();
+        let mut endpoint_map: HashMap<&ReportingEndpoint, Vec<ReportData>> = HashMap::new();
         // Step 2. For each report in reports:
-        reports.retain(|report| {
+        for report in reports {
             // Step 2.1. If there exists an endpoint (endpoint) in context’s endpoints
             // list whose name is report’s destination:
             if let Some(endpoint) = endpoints.iter().find(|e| e.name == report.destination) {
                 // Step 2.1.1. Append report to endpoint map’s list of reports for endpoint.
-                endpoint_map
-                    .entry(endpoint)
-                    .or_default()
-                    .push(report.clone());
-                true
-            } else {
-                // Step 2.1.2. Otherwise, remove report from reports.
-                false
+                endpoint_map.entry(endpoint).or_default().push(report);
             }
-        });
+            // Step 2.1.2. Otherwise, remove report from reports.
+        }
         // Step 3. For each (endpoint, report list) pair in endpoint map:
-        for (endpoint, report_list) in endpoint_map.iter() {
+        for (endpoint, report_list) in endpoint_map {
             // Step 3.1. Let origin map be an empty map of origins to lists of report objects.
-            let mut origin_map: HashMap<ImmutableOrigin, Vec<&Report>> = HashMap::new();
+            let mut origin_map: HashMap<ImmutableOrigin, Vec<ReportData>> = HashMap::new();
             // Step 3.2. For each report in report list:
             for report in report_list {
                 let Ok(url) = ServoUrl::parse(&report.url) else {
@@ -162,36 +209,24 @@ impl SendReportsToEndpoints for GlobalScope {
             }
             // Step 3.3. For each (origin, per-origin reports) pair in origin map,
             // execute the following steps asynchronously:
-            for (origin, origin_report_list) in origin_map.iter() {
+            for (origin, origin_report_list) in origin_map {
                 // Step 3.3.1. Let result be the result of executing
-                // § 3.5.2 Attempt to deliver reports to endpoint on endpoint, origin, and per-origin reports.
-                self.attempt_to_deliver_reports_to_endpoints(
-                    &endpoint.url,
-                    origin.clone(),
-                    origin_report_list,
-                );
-                // Step 3.3.2. If result is "Failure":
-                // TODO(37238)
-                // Step 3.3.2.1. Increment endpoint’s failures.
-                // TODO(37238)
-                // Step 3.3.3. If result is "Remove Endpoint":
-                // TODO(37238)
-                // Step 3.3.3.1 Remove endpoint from context’s endpoints list.
-                // TODO(37238)
-                // Step 3.3.4. Remove each report from reports.
-                // TODO(37238)
+                // § 3.5.2 Attempt to deliver reports to endpoint on endpoint, origin, and per-origin reports.
+                // Note: The remaining steps run in `process_delivery_result` once the response
+                // to the request has been received.
+                self.attempt_to_deliver_reports_to_endpoint(endpoint, origin, origin_report_list);
             }
         }
     }
 
-    fn attempt_to_deliver_reports_to_endpoints(
+    fn attempt_to_deliver_reports_to_endpoint(
         &self,
-        endpoint: &ServoUrl,
+        endpoint: &ReportingEndpoint,
         origin: ImmutableOrigin,
-        reports: &[&Report],
+        mut reports: Vec<ReportData>,
     ) {
         // Step 1. Let body be the result of executing serialize a list of reports to JSON on reports.
-        let request_body = Self::serialize_list_of_reports(reports);
+        let request_body = Self::serialize_list_of_reports(&mut reports);
         // Step 2. Let request be a new request with the following properties [FETCH]:
         let mut headers = HeaderMap::with_capacity(1);
         headers.typed_insert(ContentType::from(
@@ -199,7 +234,7 @@ impl SendReportsToEndpoints for GlobalScope {
         ));
         let request = create_a_potential_cors_request(
             None,
-            endpoint.clone(),
+            endpoint.url.clone(),
             Destination::Report,
             None,
             None,
@@ -216,51 +251,108 @@ impl SendReportsToEndpoints for GlobalScope {
         .unsafe_request(true)
         .headers(headers);
         // Step 3. Queue a task to fetch request.
+        // Step 4. Wait for a response (response).
+        // Note: Steps 5 to 7 are in `ReportDeliveryFetchListener::process_response_eof`.
         self.fetch(
             request,
-            Arc::new(Mutex::new(CSPReportEndpointFetchListener {
-                endpoint: endpoint.clone(),
+            Arc::new(Mutex::new(ReportDeliveryFetchListener {
+                endpoint_name: endpoint.name.clone(),
+                endpoint_url: endpoint.url.clone(),
+                reports,
+                status: None,
                 global: Trusted::new(self),
                 resource_timing: ResourceFetchTiming::new(ResourceTimingType::None),
             })),
             self.task_manager().networking_task_source().into(),
         );
-        // Step 4. Wait for a response (response).
-        // TODO(37238)
-        // Step 5. If response’s status is an OK status (200-299), return "Success".
-        // TODO(37238)
-        // Step 6. If response’s status is 410 Gone [RFC9110], return "Remove Endpoint".
-        // TODO(37238)
-        // Step 7. Return "Failure".
-        // TODO(37238)
     }
 
-    fn serialize_list_of_reports(reports: &[&Report]) -> Option<RequestBody> {
+    fn process_delivery_result(
+        &self,
+        endpoint_name: &DOMString,
+        result: DeliveryResult,
+        reports: Vec<ReportData>,
+    ) {
+        match result {
+            DeliveryResult::Success => {},
+            // Step 3.3.2. If result is "Failure":
+            DeliveryResult::Failure => {
+                // Step 3.3.2.1. Increment endpoint’s failures.
+                self.with_reporting_endpoints(|endpoints| {
+                    if let Some(endpoint) = endpoints.iter_mut().find(|e| e.name == *endpoint_name)
+                    {
+                        endpoint.failures += 1;
+                    }
+                });
+                // The reports are queued again to retry their delivery with the next batch,
+                // unless delivering them has been attempted too many times already.
+                for report in reports {
+                    if report.attempts < MAX_REPORT_DELIVERY_ATTEMPTS {
+                        self.append_report(report);
+                    }
+                }
+            },
+            // Step 3.3.3. If result is "Remove Endpoint":
+            DeliveryResult::RemoveEndpoint => {
+                // Step 3.3.3.1 Remove endpoint from context’s endpoints list.
+                self.with_reporting_endpoints(|endpoints| {
+                    endpoints.retain(|endpoint| endpoint.name != *endpoint_name)
+                });
+            },
+        }
+        // Step 3.3.4. Remove each report from reports.
+        // Note: The reports were removed when they were taken for delivery.
+    }
+
+    fn serialize_list_of_reports(reports: &mut [ReportData]) -> Option<RequestBody> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as f64;
+        let user_agent = pref!(user_agent);
         // Step 1. Let collection be an empty list.
         // Step 2. For each report in reports:
-        let report_body: Vec<SerializedReport> = reports
-            .iter()
-            // Step 2.1. Let data be a map with the following key/value pairs:
-            .map(|r| SerializedReport {
-                // TODO(37238)
-                age: 0,
-                type_: r.type_.to_string(),
-                url: r.url.to_string(),
-                user_agent: "".to_owned(),
-                body: r.body.clone().map(|b| b.into()),
+        let collection: Vec<SerializedReport> = reports
+            .iter_mut()
+            .map(|report| {
+                // Step 2.1. Let data be a map with the following key/value pairs:
+                let data = SerializedReport {
+                    age: (now - report.timestamp).max(0.) as u64,
+                    type_: report.type_.to_string(),
+                    url: report.url.to_string(),
+                    user_agent: user_agent.clone(),
+                    body: report.body.clone().map(SerializedReportBody::from),
+                };
+                // Step 2.2. Increment report’s attempts.
+                report.attempts += 1;
+                // Step 2.3. Append data to collection.
+                data
             })
-            // Step 2.2. Increment report’s attempts.
-            // TODO(37238)
-            // Step 2.3. Append data to collection.
             .collect();
         // Step 3. Return the byte sequence resulting from executing serialize an
         // Infra value to JSON bytes on collection.
         Some(create_request_body_with_content(
-            &serde_json::to_string(&report_body).unwrap_or("".to_owned()),
+            &serde_json::to_string(&collection).unwrap_or("".to_owned()),
         ))
     }
 }
 
+/// Delivers the reports queued in a global once the reports generated in the meantime have
+/// been collected, so that they are sent to each endpoint in a single request.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) struct ReportDeliveryCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    pub(crate) global: Trusted<GlobalScope>,
+}
+
+impl ReportDeliveryCallback {
+    pub(crate) fn invoke(self) {
+        let global = self.global.root();
+        let reports = global.take_reports();
+        global.send_reports_to_endpoints(reports);
+    }
+}
+
 #[derive(Serialize)]
 struct SerializedReport {
     age: u64,
@@ -268,7 +360,28 @@ struct SerializedReport {
     type_: String,
     url: String,
     user_agent: String,
-    body: Option<CSPReportingEndpointBody>,
+    body: Option<SerializedReportBody>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SerializedReportBody {
+    CspViolation(CSPReportingEndpointBody),
+    Deprecation(DeprecationReport),
+    Crash(CrashReportBody),
+}
+
+/// <https://wicg.github.io/crash-reporting/#crashreportbody>
+#[derive(Serialize)]
+struct CrashReportBody {}
+
+impl From<ReportBodyData> for SerializedReportBody {
+    fn from(value: ReportBodyData) -> Self {
+        match value {
+            ReportBodyData::CspViolation(violation) => Self::CspViolation(violation.into()),
+            ReportBodyData::Deprecation(deprecation) => Self::Deprecation(deprecation),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -290,34 +403,43 @@ pub(crate) struct CSPReportingEndpointBody {
     disposition: SecurityPolicyViolationEventDisposition,
 }
 
-impl From<CSPViolationReportBody> for CSPReportingEndpointBody {
-    fn from(value: CSPViolationReportBody) -> Self {
+impl From<SecurityPolicyViolationReport> for CSPReportingEndpointBody {
+    fn from(value: SecurityPolicyViolationReport) -> Self {
         CSPReportingEndpointBody {
-            sample: value.sample.map(|s| s.to_string()),
-            blocked_url: value.blockedURL.map(|s| s.to_string()),
-            referrer: value.referrer.map(|s| s.to_string()),
-            status_code: value.statusCode,
-            document_url: value.documentURL.to_string(),
-            source_file: value.sourceFile.map(|s| s.to_string()),
-            effective_directive: value.effectiveDirective.to_string(),
-            line_number: value.lineNumber,
-            column_number: value.columnNumber,
-            original_policy: value.originalPolicy.into(),
+            sample: value.sample,
+            blocked_url: Some(value.blocked_url),
+            // TODO(37328): Why does /content-security-policy/reporting-api/
+            // report-to-directive-allowed-in-meta.https.sub.html expect this to be
+            // empty, yet the spec expects us to copy referrer from SecurityPolicyViolationReport
+            referrer: Some("".to_owned()),
+            status_code: value.status_code,
+            document_url: value.document_url,
+            source_file: Some(value.source_file),
+            effective_directive: value.effective_directive,
+            line_number: Some(value.line_number),
+            column_number: Some(value.column_number),
+            original_policy: value.original_policy,
             disposition: value.disposition,
         }
     }
 }
 
-struct CSPReportEndpointFetchListener {
+struct ReportDeliveryFetchListener {
+    /// The name of the endpoint that the reports are delivered to.
+    endpoint_name: DOMString,
     /// Endpoint URL of this request.
-    endpoint: ServoUrl,
+    endpoint_url: ServoUrl,
+    /// The reports delivered by this request.
+    reports: Vec<ReportData>,
+    /// The status of the response, once it has been received.
+    status: Option<u16>,
     /// Timing data for this resource.
     resource_timing: ResourceFetchTiming,
-    /// The global object fetching the report uri violation
+    /// The global object delivering the reports
     global: Trusted<GlobalScope>,
 }
 
-impl FetchResponseListener for CSPReportEndpointFetchListener {
+impl FetchResponseListener for ReportDeliveryFetchListener {
     fn process_request_body(&mut self, _: RequestId) {}
 
     fn process_request_eof(&mut self, _: RequestId) {}
@@ -327,7 +449,9 @@ impl FetchResponseListener for CSPReportEndpointFetchListener {
         _: RequestId,
         fetch_metadata: Result<FetchMetadata, NetworkError>,
     ) {
-        _ = fetch_metadata;
+        self.status = fetch_metadata
+            .ok()
+            .map(|metadata| metadata.metadata().status.raw_code());
     }
 
     fn process_response_chunk(&mut self, _: RequestId, chunk: Vec<u8>) {
@@ -339,7 +463,19 @@ impl FetchResponseListener for CSPReportEndpointFetchListener {
         _: RequestId,
         response: Result<ResourceFetchTiming, NetworkError>,
     ) {
-        _ = response;
+        let result = match (response, self.status) {
+            // Step 5. If response’s status is an OK status (200-299), return "Success".
+            (Ok(_), Some(200..=299)) => DeliveryResult::Success,
+            // Step 6. If response’s status is 410 Gone [RFC9110], return "Remove Endpoint".
+            (Ok(_), Some(410)) => DeliveryResult::RemoveEndpoint,
+            // Step 7. Return "Failure".
+            _ => DeliveryResult::Failure,
+        };
+        self.global.root().process_delivery_result(
+            &self.endpoint_name,
+            result,
+            std::mem::take(&mut self.reports),
+        );
     }
 
     fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
@@ -357,9 +493,9 @@ impl FetchResponseListener for CSPReportEndpointFetchListener {
     fn process_csp_violations(&mut self, _request_id: RequestId, _violations: Vec<Violation>) {}
 }
 
-impl ResourceTimingListener for CSPReportEndpointFetchListener {
+impl ResourceTimingListener for ReportDeliveryFetchListener {
     fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
-        (InitiatorType::Other, self.endpoint.clone())
+        (InitiatorType::Other, self.endpoint_url.clone())
     }
 
     fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
@@ -367,7 +503,7 @@ impl ResourceTimingListener for CSPReportEndpointFetchListener {
     }
 }
 
-impl PreInvoke for CSPReportEndpointFetchListener {
+impl PreInvoke for ReportDeliveryFetchListener {
     fn should_invoke(&self) -> bool {
         true
     }
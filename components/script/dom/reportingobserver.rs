@@ -13,18 +13,21 @@ use servo_url::ServoUrl;
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBody;
 use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::{
-    Report, ReportList, ReportingObserverCallback, ReportingObserverMethods,
-    ReportingObserverOptions,
+    ReportList, ReportingObserverCallback, ReportingObserverMethods, ReportingObserverOptions,
 };
-use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::csp::compute_scripted_caller_source_position;
+use crate::dom::deprecationreportbody::DeprecationReport;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::{Report, ReportBodyData, ReportData};
 use crate::script_runtime::CanGc;
 
+/// The maximum number of reports of each type in the report buffer of a global.
+const MAX_BUFFERED_REPORTS_PER_TYPE: usize = 100;
+
 #[dom_struct]
 pub(crate) struct ReportingObserver {
     reflector_: Reflector,
@@ -33,7 +36,8 @@ pub(crate) struct ReportingObserver {
     callback: Rc<ReportingObserverCallback>,
     buffered: RefCell<bool>,
     types: DomRefCell<Vec<DOMString>>,
-    report_queue: DomRefCell<Vec<Report>>,
+    #[no_trace]
+    report_queue: DomRefCell<Vec<ReportData>>,
 }
 
 impl ReportingObserver {
@@ -66,16 +70,18 @@ impl ReportingObserver {
         )
     }
 
-    fn report_is_visible_to_reporting_observers(report: &Report) -> bool {
+    fn report_is_visible_to_reporting_observers(report: &ReportData) -> bool {
         match report.type_.str() {
             // https://w3c.github.io/webappsec-csp/#reporting
             "csp-violation" => true,
+            // https://wicg.github.io/deprecation-reporting/#deprecation-report
+            "deprecation" => true,
             _ => false,
         }
     }
 
     /// <https://w3c.github.io/reporting/#add-report>
-    fn add_report_to_observer(&self, report: &Report) {
+    fn add_report_to_observer(&self, report: &ReportData) {
         // Step 1. If report’s type is not visible to ReportingObservers, return.
         if !Self::report_is_visible_to_reporting_observers(report) {
             return;
@@ -87,16 +93,9 @@ impl ReportingObserver {
         }
         // Step 3. Create a new Report r with type initialized to report’s type,
         // url initialized to report’s url, and body initialized to report’s body.
-        let report = Report {
-            type_: report.type_.clone(),
-            url: report.url.clone(),
-            body: report.body.clone(),
-            destination: report.destination.clone(),
-            attempts: report.attempts,
-            timestamp: report.timestamp,
-        };
+        // Note: The `Report` objects are created when they are given to the observer.
         // Step 4. Append r to observer’s report queue.
-        self.report_queue.borrow_mut().push(report);
+        self.report_queue.borrow_mut().push(report.clone());
         // Step 5. If the size of observer’s report queue is 1:
         if self.report_queue.borrow().len() == 1 {
             // Step 5.1. Let global be observer’s relevant global object.
@@ -115,19 +114,33 @@ impl ReportingObserver {
     }
 
     /// <https://w3c.github.io/reporting/#notify-observers>
-    pub(crate) fn notify_reporting_observers_on_scope(global: &GlobalScope, report: &Report) {
+    pub(crate) fn notify_reporting_observers_on_scope(global: &GlobalScope, report: &ReportData) {
         // Step 1. For each ReportingObserver observer registered with scope,
         // execute § 4.3 Add report to observer on report and observer.
         for observer in global.registered_reporting_observers().iter() {
             observer.add_report_to_observer(report);
         }
-        // Step 2. Append report to scope’s report buffer.
-        global.append_report(report.clone());
-        // Step 3. Let type be report’s type.
-        // TODO(37328)
-        // Step 4. If scope’s report buffer now contains more than 100 reports with
-        // type equal to type, remove the earliest item with type equal to type in the report buffer.
-        // TODO(37328)
+        global.with_report_buffer(|report_buffer| {
+            // Step 2. Append report to scope’s report buffer.
+            report_buffer.push(report.clone());
+            // Step 3. Let type be report’s type.
+            let type_ = &report.type_;
+            // Step 4. If scope’s report buffer now contains more than 100 reports with
+            // type equal to type, remove the earliest item with type equal to type in the report buffer.
+            if report_buffer
+                .iter()
+                .filter(|report| report.type_ == *type_)
+                .count() >
+                MAX_BUFFERED_REPORTS_PER_TYPE
+            {
+                if let Some(index) = report_buffer
+                    .iter()
+                    .position(|report| report.type_ == *type_)
+                {
+                    report_buffer.remove(index);
+                }
+            }
+        });
     }
 
     /// <https://w3c.github.io/reporting/#invoke-observers>
@@ -140,7 +153,7 @@ impl ReportingObserver {
             }
             // Step 1.2. Let reports be a copy of observer’s report queue
             // Step 1.3. Empty observer’s report queue
-            let reports = std::mem::take(&mut *observer.report_queue.borrow_mut());
+            let reports = observer.take_report_queue(CanGc::note());
             // Step 1.4. Invoke observer’s callback with « reports, observer » and "report",
             // and with observer as the callback this value.
             let _ = observer.callback.Call_(
@@ -153,14 +166,23 @@ impl ReportingObserver {
         }
     }
 
+    /// Empty the report queue, and return the `Report` objects for the reports in it.
+    fn take_report_queue(&self, can_gc: CanGc) -> ReportList {
+        let global = self.global();
+        std::mem::take(&mut *self.report_queue.borrow_mut())
+            .iter()
+            .map(|report| Report::new(&global, report, can_gc))
+            .collect()
+    }
+
     /// <https://w3c.github.io/reporting/#generate-a-report>
     fn generate_a_report(
         global: &GlobalScope,
         type_: DOMString,
         url: Option<ServoUrl>,
-        body: Option<CSPViolationReportBody>,
+        body: Option<ReportBodyData>,
         destination: DOMString,
-    ) -> Report {
+    ) -> ReportData {
         // Step 2. If url was not provided by the caller, let url be settings’s creation URL.
         let url = url.unwrap_or(global.creation_url().clone());
         // Step 3. Set url’s username to the empty string, and its password to null.
@@ -169,17 +191,15 @@ impl ReportingObserver {
         let url = Self::strip_url_for_reports(url).into();
         // Step 1. Let report be a new report object with its values initialized as follows:
         // Step 5. Return report.
-        Report {
+        ReportData {
             type_,
             url,
             body,
             destination,
-            timestamp: Finite::wrap(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as f64,
-            ),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as f64,
             attempts: 0,
         }
     }
@@ -188,7 +208,7 @@ impl ReportingObserver {
     pub(crate) fn generate_and_queue_a_report(
         global: &GlobalScope,
         type_: DOMString,
-        body: Option<CSPViolationReportBody>,
+        body: Option<ReportBodyData>,
         destination: DOMString,
     ) {
         // Step 1. Let settings be context’s relevant settings object.
@@ -203,6 +223,31 @@ impl ReportingObserver {
         global.append_report(report);
     }
 
+    /// Report the use of the deprecated feature `id` to the observers and to the "default"
+    /// endpoint, once per global.
+    ///
+    /// <https://wicg.github.io/deprecation-reporting/#deprecation-report>
+    pub(crate) fn report_deprecation(global: &GlobalScope, id: &str, message: &str) {
+        if !global.note_deprecated_feature_use(id) {
+            return;
+        }
+        let source_position = compute_scripted_caller_source_position();
+        let has_source_file = !source_position.source_file.is_empty();
+        let body = DeprecationReport {
+            id: id.to_owned(),
+            message: message.to_owned(),
+            source_file: has_source_file.then_some(source_position.source_file),
+            line_number: has_source_file.then_some(source_position.line_number),
+            column_number: has_source_file.then_some(source_position.column_number),
+        };
+        Self::generate_and_queue_a_report(
+            global,
+            "deprecation".into(),
+            Some(ReportBodyData::Deprecation(body)),
+            "default".into(),
+        );
+    }
+
     /// <https://w3c.github.io/webappsec-csp/#strip-url-for-use-in-reports>
     pub(crate) fn strip_url_for_reports(mut url: ServoUrl) -> String {
         let scheme = url.scheme();
@@ -269,10 +314,10 @@ impl ReportingObserverMethods<crate::DomTypeHolder> for ReportingObserver {
     }
 
     /// <https://w3c.github.io/reporting/#dom-reportingobserver-takerecords>
-    fn TakeRecords(&self) -> ReportList {
+    fn TakeRecords(&self, can_gc: CanGc) -> ReportList {
         // Step 1. Let reports be a copy of this’s report queue.
         // Step 2. Empty this’s report queue.
         // Step 3. Return reports.
-        std::mem::take(&mut *self.report_queue.borrow_mut())
+        self.take_report_queue(can_gc)
     }
 }
@@ -23,11 +23,12 @@ use servo_url::ServoUrl;
 
 use crate::body::{BodyMixin, BodyType, Extractable, consume_body};
 use crate::conversions::Convert;
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::{
-    ReferrerPolicy, RequestCache, RequestCredentials, RequestDestination, RequestInfo, RequestInit,
-    RequestMethods, RequestMode, RequestRedirect,
+    ReferrerPolicy, RequestCache, RequestCredentials, RequestDestination, RequestDuplex,
+    RequestInfo, RequestInit, RequestMethods, RequestMode, RequestRedirect,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
@@ -47,6 +48,8 @@ pub(crate) struct Request {
     request: DomRefCell<NetTraitsRequest>,
     body_stream: MutNullableDom<ReadableStream>,
     headers: MutNullableDom<Headers>,
+    /// <https://fetch.spec.whatwg.org/#request-signal>
+    signal: MutNullableDom<AbortSignal>,
 }
 
 impl Request {
@@ -56,6 +59,7 @@ impl Request {
             request: DomRefCell::new(net_request_from_global(global, url)),
             body_stream: MutNullableDom::new(None),
             headers: Default::default(),
+            signal: Default::default(),
         }
     }
 
@@ -73,7 +77,7 @@ impl Request {
         )
     }
 
-    fn from_net_request(
+    pub(crate) fn from_net_request(
         global: &GlobalScope,
         proto: Option<HandleObject>,
         net_request: NetTraitsRequest,
@@ -175,7 +179,8 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
         // Step 3
         let base_url = global.api_base_url();
 
-        // Step 4 TODO: "Let signal be null."
+        // Step 4
+        let mut signal: Option<DomRoot<AbortSignal>> = None;
 
         match input {
             // Step 5
@@ -205,7 +210,8 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
                 }
                 // Step 6.1
                 temporary_request = input_request.request.borrow().clone();
-                // Step 6.2 TODO: "Set signal to input's signal."
+                // Step 6.2
+                signal = Some(input_request.Signal(can_gc));
             },
         }
 
@@ -251,12 +257,14 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
             init.cache.is_some() ||
             init.credentials.is_some() ||
             init.integrity.is_some() ||
+            init.duplex.is_some() ||
             init.headers.is_some() ||
             init.method.is_some() ||
             init.mode.is_some() ||
             init.redirect.is_some() ||
             init.referrer.is_some() ||
             init.referrerPolicy.is_some() ||
+            init.signal.is_some() ||
             !init.window.handle().is_undefined()
         {
             // Step 13.1
@@ -374,14 +382,22 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
             request.method = method;
         }
 
-        // Step 26 TODO: "If init["signal"] exists..."
+        // Step 26
+        if let Some(init_signal) = init.signal.as_ref() {
+            signal = init_signal.clone();
+        }
         // Step 27 TODO: "If init["priority"] exists..."
 
         // Step 28
         let r = Request::from_net_request(global, proto, request, can_gc);
 
-        // Step 29 TODO: "Set this's signal to new AbortSignal object..."
-        // Step 30 TODO: "If signal is not null..."
+        // Step 29-30: "Let signals be « signal » if signal is non-null; otherwise « ».
+        // Set this’s signal to the result of creating a dependent abort signal from signals."
+        let signals = signal.into_iter().collect();
+        r.signal
+            .set(Some(&AbortSignal::create_dependent_abort_signal(
+                signals, global, can_gc,
+            )));
 
         // Step 31
         // "or_init" looks unclear here, but it always enters the block since r
@@ -514,9 +530,34 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
 
         // Step 38 is done earlier
 
-        // Step 39 "TODO if body is non-null and body's source is null..."
-        // This looks like where we need to set the use-preflight flag
-        // if the request has a body and nothing else has set the flag.
+        // Step 39. If inputOrInitBody is non-null and inputOrInitBody’s source is null, then:
+        if input_body
+            .as_ref()
+            .is_some_and(|body| body.source_is_null())
+        {
+            // Step 39.1. If initBody is non-null and init["duplex"] does not exist,
+            // then throw a TypeError.
+            if matches!(init.body, Some(Some(_))) && init.duplex.is_none() {
+                return Err(Error::Type(
+                    "The duplex member must be specified for a request with a streaming body"
+                        .to_string(),
+                ));
+            }
+            let mut request = r.request.borrow_mut();
+            // Step 39.2. If this’s request’s mode is neither "same-origin" nor "cors",
+            // then throw a TypeError.
+            if !matches!(
+                request.mode,
+                NetTraitsRequestMode::SameOrigin | NetTraitsRequestMode::CorsMode
+            ) {
+                return Err(Error::Type(
+                    "A request with a streaming body must use the same-origin or cors mode"
+                        .to_string(),
+                ));
+            }
+            // Step 39.3. Set this’s request’s use-CORS-preflight flag.
+            request.use_cors_preflight = true;
+        }
 
         // Step 40 is done earlier
 
@@ -597,6 +638,20 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    /// <https://fetch.spec.whatwg.org/#dom-request-signal>
+    fn Signal(&self, can_gc: CanGc) -> DomRoot<AbortSignal> {
+        // Requests created from a net request, e.g. for a service worker fetch event,
+        // get a signal that is never aborted.
+        self.signal
+            .or_init(|| AbortSignal::new_with_proto(&self.global(), None, can_gc))
+    }
+
+    // https://fetch.spec.whatwg.org/#dom-request-duplex
+    fn Duplex(&self) -> RequestDuplex {
+        // Responses are only processed once the request body has been fully transmitted.
+        RequestDuplex::Half
+    }
+
     /// <https://fetch.spec.whatwg.org/#dom-body-body>
     fn GetBody(&self) -> Option<DomRoot<ReadableStream>> {
         self.body()
@@ -618,7 +673,16 @@ impl RequestMethods<crate::DomTypeHolder> for Request {
         }
 
         // Step 2
-        Request::clone_from(self, can_gc)
+        let cloned_request = Request::clone_from(self, can_gc)?;
+
+        // Step 3-4
+        let cloned_signal = AbortSignal::create_dependent_abort_signal(
+            vec![self.Signal(can_gc)],
+            &self.global(),
+            can_gc,
+        );
+        cloned_request.signal.set(Some(&cloned_signal));
+        Ok(cloned_request)
     }
 
     // https://fetch.spec.whatwg.org/#dom-body-text
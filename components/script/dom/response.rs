@@ -95,6 +95,15 @@ impl Response {
             body.error_native(error, can_gc);
         }
     }
+
+    /// Error the body stream with `reason`, e.g. the abort reason of a fetch, if it is readable.
+    pub(crate) fn error_stream_with_reason(&self, reason: HandleValue, can_gc: CanGc) {
+        if let Some(body) = self.body_stream.get() {
+            if body.is_readable() {
+                body.error(reason, can_gc);
+            }
+        }
+    }
 }
 
 impl BodyMixin for Response {
@@ -0,0 +1,426 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use dom_struct::dom_struct;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::{
+    SchedulerMethods, SchedulerPostTaskCallback, SchedulerPostTaskOptions, TaskPriority,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::tasksignal::TaskSignal;
+use crate::realms::{InRealm, enter_realm};
+use crate::script_runtime::{CanGc, JSContext};
+use crate::timers::OneshotTimerCallback;
+
+/// Where a scheduled task gets its priority from: either the priority given to
+/// `postTask()`, or a [`TaskSignal`] whose priority can change while the task is queued.
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+enum TaskPrioritySource {
+    Static(TaskPriority),
+    Dynamic(Dom<TaskSignal>),
+}
+
+impl TaskPrioritySource {
+    fn priority(&self) -> TaskPriority {
+        match self {
+            TaskPrioritySource::Static(priority) => *priority,
+            TaskPrioritySource::Dynamic(signal) => signal.priority(),
+        }
+    }
+}
+
+/// <https://wicg.github.io/scheduling-apis/#scheduling-state>
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct SchedulingState {
+    /// <https://wicg.github.io/scheduling-apis/#scheduling-state-abort-source>
+    abort_source: Option<Dom<AbortSignal>>,
+    /// <https://wicg.github.io/scheduling-apis/#scheduling-state-priority-source>
+    priority_source: TaskPrioritySource,
+}
+
+/// A task posted with `postTask()`, or the continuation of a `yield()`, together with its
+/// <https://wicg.github.io/scheduling-apis/#task-handle>.
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+#[derive(JSTraceable, MallocSizeOf)]
+struct ScheduledTask {
+    /// Identifies the task for its abort algorithm and its delay timer.
+    id: u64,
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-task-enqueue-order>,
+    /// only set once the task is runnable, which is after its delay has elapsed.
+    enqueue_order: Option<u64>,
+    state: SchedulingState,
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-task-queue-is-continuation>
+    is_continuation: bool,
+    /// The callback of a `postTask()` task, `None` for a continuation.
+    #[ignore_malloc_size_of = "Rc"]
+    callback: Option<Rc<SchedulerPostTaskCallback>>,
+    /// <https://wicg.github.io/scheduling-apis/#task-handle-result>
+    #[ignore_malloc_size_of = "Rc"]
+    result: Rc<Promise>,
+}
+
+impl ScheduledTask {
+    /// The effective priority of the task, with continuations running before other
+    /// tasks of the same priority.
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-task-queue-effective-priority>
+    fn effective_priority(&self) -> u8 {
+        let rank = match self.state.priority_source.priority() {
+            TaskPriority::Background => 0,
+            TaskPriority::User_visible => 2,
+            TaskPriority::User_blocking => 4,
+        };
+        rank + self.is_continuation as u8
+    }
+}
+
+/// <https://wicg.github.io/scheduling-apis/#scheduler>
+#[dom_struct]
+pub(crate) struct Scheduler {
+    reflector_: Reflector,
+
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-next-enqueue-order>
+    next_enqueue_order: Cell<u64>,
+
+    /// The tasks of all <https://wicg.github.io/scheduling-apis/#scheduler-static-priority-task-queue-map>
+    /// and <https://wicg.github.io/scheduling-apis/#scheduler-dynamic-priority-task-queue-map>,
+    /// including the delayed ones that are not runnable yet.
+    tasks: DomRefCell<Vec<ScheduledTask>>,
+
+    /// The scheduling state of the task that is currently running, inherited by `yield()`.
+    /// <https://wicg.github.io/scheduling-apis/#event-loop-current-scheduling-state>
+    current_scheduling_state: DomRefCell<Option<SchedulingState>>,
+}
+
+impl Scheduler {
+    fn new_inherited() -> Scheduler {
+        Scheduler {
+            reflector_: Reflector::new(),
+            next_enqueue_order: Cell::new(1),
+            tasks: Default::default(),
+            current_scheduling_state: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<Scheduler> {
+        reflect_dom_object(Box::new(Scheduler::new_inherited()), global, can_gc)
+    }
+
+    fn next_enqueue_order(&self) -> u64 {
+        let order = self.next_enqueue_order.get();
+        self.next_enqueue_order.set(order + 1);
+        order
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#schedule-a-task-to-invoke-an-algorithm>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn schedule_task(
+        &self,
+        state: SchedulingState,
+        is_continuation: bool,
+        callback: Option<Rc<SchedulerPostTaskCallback>>,
+        result: &Rc<Promise>,
+        delay: u64,
+    ) {
+        let id = self.next_enqueue_order();
+        let priority = state.priority_source.priority();
+
+        // If signal is not null, then add handle’s abort steps to signal.
+        if let Some(signal) = &state.abort_source {
+            signal.add(&AbortAlgorithm::PostedTask(Dom::from_ref(self), id));
+        }
+
+        // If the priority comes from a signal, update the task when it changes.
+        if let TaskPrioritySource::Dynamic(signal) = &state.priority_source {
+            signal.add_priority_change_algorithm(self);
+        }
+
+        let runnable = delay == 0;
+        self.tasks.borrow_mut().push(ScheduledTask {
+            id,
+            enqueue_order: runnable.then_some(id),
+            state,
+            is_continuation,
+            callback,
+            result: result.clone(),
+        });
+
+        if runnable {
+            self.queue_run_task(priority);
+            return;
+        }
+
+        // Run steps after a timeout given global, "scheduler-postTask", and delay,
+        // to queue the task.
+        let callback =
+            OneshotTimerCallback::SchedulerPostTaskDelay(SchedulerPostTaskDelayCallback {
+                scheduler: Trusted::new(self),
+                id,
+            });
+        self.global()
+            .schedule_callback(callback, Duration::from_millis(delay));
+    }
+
+    /// Make a delayed task runnable, once its delay has elapsed.
+    fn enqueue_delayed_task(&self, id: u64) {
+        let priority = {
+            let mut tasks = self.tasks.borrow_mut();
+            // The task was aborted in the meantime.
+            let Some(task) = tasks.iter_mut().find(|task| task.id == id) else {
+                return;
+            };
+            task.enqueue_order = Some(self.next_enqueue_order());
+            task.state.priority_source.priority()
+        };
+        self.queue_run_task(priority);
+    }
+
+    /// Queue a task on the task source matching the priority, that runs the next
+    /// scheduled task. The scheduled task picked when it runs is the one with the highest
+    /// effective priority at that time, so the event loop only needs to know about the
+    /// priority to choose between the posted task sources and other task sources.
+    fn queue_run_task(&self, priority: TaskPriority) {
+        let global = self.global();
+        let task_manager = global.task_manager();
+        let task_source = match priority {
+            TaskPriority::Background => task_manager.background_posted_task_task_source(),
+            TaskPriority::User_blocking | TaskPriority::User_visible => {
+                task_manager.posted_task_task_source()
+            },
+        };
+        let scheduler = Trusted::new(self);
+        task_source.queue(task!(run_scheduled_task: move || {
+            scheduler.root().run_next_task(CanGc::note());
+        }));
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-select-the-next-scheduler-task-queue-from-all-schedulers>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn take_next_task(&self) -> Option<ScheduledTask> {
+        let mut tasks = self.tasks.borrow_mut();
+        let index = tasks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, task)| task.enqueue_order.map(|order| (index, task, order)))
+            .max_by(|(_, task_a, order_a), (_, task_b, order_b)| {
+                task_a
+                    .effective_priority()
+                    .cmp(&task_b.effective_priority())
+                    .then(order_b.cmp(order_a))
+            })
+            .map(|(index, _, _)| index)?;
+        Some(tasks.remove(index))
+    }
+
+    /// Run the runnable task with the highest priority, if any.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn run_next_task(&self, can_gc: CanGc) {
+        // Other run tasks may have run this scheduler's tasks already, or they were aborted.
+        let Some(task) = self.take_next_task() else {
+            return;
+        };
+
+        let global = self.global();
+        let _ac = enter_realm(&*global);
+        let cx = GlobalScope::get_cx();
+
+        // Set event loop’s current scheduling state to state.
+        let previous_state = self
+            .current_scheduling_state
+            .replace(Some(task.state.clone()));
+
+        match task.callback {
+            // Let callbackResult be the result of invoking callback with « » and "rethrow".
+            Some(callback) => {
+                rooted!(in(*cx) let mut callback_result = UndefinedValue());
+                match callback.Call__(
+                    callback_result.handle_mut(),
+                    ExceptionHandling::Rethrow,
+                    can_gc,
+                ) {
+                    // Otherwise, resolve result with callbackResult.
+                    Ok(()) => task.result.resolve(cx, callback_result.handle(), can_gc),
+                    // If that threw an exception, then reject result with that.
+                    Err(error) => task.result.reject_error(error, can_gc),
+                }
+            },
+            // The continuation of a `yield()`: resolve result.
+            None => task.result.resolve_native(&(), can_gc),
+        }
+
+        // Set event loop’s current scheduling state to null.
+        *self.current_scheduling_state.borrow_mut() = previous_state;
+    }
+
+    /// The abort steps of the task handle of the task with the given id.
+    /// <https://wicg.github.io/scheduling-apis/#task-handle-abort-steps>
+    pub(crate) fn abort_task(&self, cx: JSContext, id: u64, reason: HandleValue, can_gc: CanGc) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            let Some(index) = tasks.iter().position(|task| task.id == id) else {
+                return;
+            };
+            // Remove task from handle’s queue.
+            tasks.remove(index)
+        };
+
+        // Reject handle’s result promise with signal’s abort reason.
+        task.result.reject(cx, reason, can_gc);
+    }
+
+    /// The priority change algorithm a scheduler adds to the [`TaskSignal`] of its tasks.
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-task-queue-priority>
+    pub(crate) fn task_signal_priority_changed(&self, signal: &TaskSignal) {
+        // The runnable tasks using the signal as their priority source move to the
+        // task queue of the new priority, so queue a run task at that priority for them.
+        let moved_tasks = self
+            .tasks
+            .borrow()
+            .iter()
+            .filter(|task| task.enqueue_order.is_some())
+            .filter(|task| match &task.state.priority_source {
+                TaskPrioritySource::Dynamic(source) => *source == signal,
+                TaskPrioritySource::Static(_) => false,
+            })
+            .count();
+        for _ in 0..moved_tasks {
+            self.queue_run_task(signal.priority());
+        }
+    }
+}
+
+impl SchedulerMethods<crate::DomTypeHolder> for Scheduler {
+    /// <https://wicg.github.io/scheduling-apis/#dom-scheduler-posttask>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn PostTask(
+        &self,
+        callback: Rc<SchedulerPostTaskCallback>,
+        options: &SchedulerPostTaskOptions,
+        comp: InRealm,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        // Let result be a new promise.
+        let result = Promise::new_in_current_realm(comp, can_gc);
+
+        // Let signal be options["signal"] if options["signal"] exists, or otherwise null.
+        let signal = options.signal.as_deref();
+
+        // If signal is not null and it is aborted, then reject result with signal’s abort
+        // reason and return result.
+        if let Some(signal) = signal {
+            if signal.aborted() {
+                let cx = GlobalScope::get_cx();
+                rooted!(in(*cx) let mut reason = UndefinedValue());
+                signal.Reason(cx, reason.handle_mut());
+                result.reject(cx, reason.handle(), can_gc);
+                return result;
+            }
+        }
+
+        // If options["priority"] exists, then set state’s priority source to the result of
+        // creating a fixed priority unabortable task signal given options["priority"].
+        // Otherwise if signal is not null and implements the TaskSignal interface,
+        // then set state’s priority source to signal.
+        // If state’s priority source is null, then set state’s priority source to the result
+        // of creating a fixed priority unabortable task signal given "user-visible".
+        let priority_source = match (
+            options.priority,
+            signal.and_then(|signal| signal.downcast::<TaskSignal>()),
+        ) {
+            (Some(priority), _) => TaskPrioritySource::Static(priority),
+            (None, Some(task_signal)) => TaskPrioritySource::Dynamic(Dom::from_ref(task_signal)),
+            (None, None) => TaskPrioritySource::Static(TaskPriority::User_visible),
+        };
+
+        // Let state be a new scheduling state. Set state’s abort source to signal.
+        let state = SchedulingState {
+            abort_source: signal.map(Dom::from_ref),
+            priority_source,
+        };
+
+        // Let delay be options["delay"].
+        // If delay is greater than 0, then run steps after a timeout, otherwise run
+        // enqueueSteps.
+        self.schedule_task(state, false, Some(callback), &result, options.delay);
+
+        // Return result.
+        result
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#dom-scheduler-yield>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn Yield(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        // Let result be a new promise.
+        let result = Promise::new_in_current_realm(comp, can_gc);
+
+        // Let inheritedState be the scheduler’s relevant agent’s event loop’s current
+        // scheduling state.
+        let inherited_state = self.current_scheduling_state.borrow().clone();
+
+        // Let abortSource be inheritedState’s abort source if inheritedState is not null,
+        // or otherwise null.
+        let abort_source = inherited_state
+            .as_ref()
+            .and_then(|state| state.abort_source.as_ref().map(|signal| signal.as_rooted()));
+
+        // If abortSource is not null and abortSource is aborted, then reject result with
+        // abortSource’s abort reason and return result.
+        if let Some(signal) = &abort_source {
+            if signal.aborted() {
+                let cx = GlobalScope::get_cx();
+                rooted!(in(*cx) let mut reason = UndefinedValue());
+                signal.Reason(cx, reason.handle_mut());
+                result.reject(cx, reason.handle(), can_gc);
+                return result;
+            }
+        }
+
+        // Let prioritySource be inheritedState’s priority source if inheritedState is not
+        // null, or otherwise null.
+        // If prioritySource is null, then set prioritySource to the result of creating a
+        // fixed priority unabortable task signal given "user-visible".
+        let state = inherited_state.unwrap_or(SchedulingState {
+            abort_source: None,
+            priority_source: TaskPrioritySource::Static(TaskPriority::User_visible),
+        });
+
+        // Schedule a task to invoke an algorithm given the scheduler, the continuation
+        // queue selected for prioritySource, and the following step: resolve result.
+        self.schedule_task(state, true, None, &result, 0);
+
+        // Return result.
+        result
+    }
+}
+
+/// Makes a task posted with a delay runnable, once the delay has elapsed.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) struct SchedulerPostTaskDelayCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    scheduler: Trusted<Scheduler>,
+    id: u64,
+}
+
+impl SchedulerPostTaskDelayCallback {
+    /// <https://wicg.github.io/scheduling-apis/#schedule-a-posttask-task>
+    pub(crate) fn invoke(self) {
+        self.scheduler.root().enqueue_delayed_task(self.id);
+    }
+}
@@ -5,7 +5,10 @@
 use std::cell::Cell;
 
 use dom_struct::dom_struct;
+use style::values::specified::box_::DisplayOutside;
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
 use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
@@ -15,10 +18,14 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
+use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
-use crate::dom::node::{Node, NodeTraits};
+use crate::dom::node::{Node, NodeTraits, ShadowIncluding};
 use crate::dom::range::Range;
+use crate::dom::text::Text;
+use crate::editing;
 use crate::script_runtime::CanGc;
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
@@ -105,6 +112,62 @@ impl Selection {
     fn is_same_root(&self, node: &Node) -> bool {
         &*node.GetRootNode(&GetRootNodeOptions::empty()) == self.document.upcast::<Node>()
     }
+
+    /// Select the word around the boundary point `(node, offset)`, as double clicking on it
+    /// does. A point that isn't in a text node collapses the selection to it instead.
+    pub(crate) fn select_word(&self, node: &Node, offset: u32, can_gc: CanGc) {
+        let word = node
+            .downcast::<Text>()
+            .and_then(|text| word_around(&text.upcast::<CharacterData>().Data(), offset));
+        let _ = match word {
+            Some((start, end)) => self.SetBaseAndExtent(node, start, node, end, can_gc),
+            None => self.Collapse(Some(node), offset, can_gc),
+        };
+    }
+
+    /// Select the contents of the paragraph that `node` is part of, as triple clicking on it
+    /// does. The paragraph is the nearest block-level ancestor of `node`, but never extends
+    /// past the editing host of `node`.
+    pub(crate) fn select_paragraph(&self, node: &Node, can_gc: CanGc) {
+        let editing_host = editing::editing_host(node);
+        let paragraph = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter(|ancestor| ancestor.is::<Element>())
+            .find(|ancestor| {
+                editing_host
+                    .as_deref()
+                    .is_some_and(|host| host.upcast::<Node>() == &**ancestor) ||
+                    ancestor.style(can_gc).is_some_and(|style| {
+                        style.get_box().display.outside() == DisplayOutside::Block
+                    })
+            });
+        if let Some(paragraph) = paragraph {
+            let _ = self.SelectAllChildren(&paragraph, can_gc);
+        }
+    }
+}
+
+/// The UTF-16 offsets of the start and the end of the word of `text` around the UTF-16
+/// `offset`. A word that ends at `offset` is preferred over the white space or punctuation
+/// that follows it.
+fn word_around(text: &str, offset: u32) -> Option<(u32, u32)> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for word in text.split_word_bounds() {
+        let end = start + word.encode_utf16().count() as u32;
+        words.push((start, end, word.chars().any(char::is_alphanumeric)));
+        start = end;
+    }
+
+    let mut position = words
+        .iter()
+        .position(|&(_, end, _)| offset < end)
+        .or(words.len().checked_sub(1))?;
+    if position > 0 && words[position].0 == offset && !words[position].2 && words[position - 1].2 {
+        position -= 1;
+    }
+    let (start, end, _) = words[position];
+    Some((start, end))
 }
 
 impl SelectionMethods<crate::DomTypeHolder> for Selection {
@@ -517,10 +580,10 @@ impl SelectionMethods<crate::DomTypeHolder> for Selection {
     fn Stringifier(&self) -> DOMString {
         // The spec as of Jan 31 2020 just says
         // "See W3C bug 10583." for this method.
-        // Stringifying the range seems at least approximately right
-        // and passes the non-style-dependent case in the WPT tests.
+        // Like other browsers, return the text of the range as it is rendered, which
+        // matches stringifying the range for text that isn't affected by style.
         if let Some(range) = self.range.get() {
-            range.Stringifier()
+            range.rendered_text(CanGc::note())
         } else {
             DOMString::from("")
         }
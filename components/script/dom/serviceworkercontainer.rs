@@ -6,11 +6,12 @@ use std::default::Default;
 use std::rc::Rc;
 
 use constellation_traits::{
-    Job, JobError, JobResult, JobResultValue, JobType, ScriptToConstellationMessage,
+    Job, JobError, JobResult, JobResultValue, JobType, ScopeThings, ScriptToConstellationMessage,
 };
 use dom_struct::dom_struct;
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
+use servo_url::ServoUrl;
 
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerContainerBinding::{
     RegistrationOptions, ServiceWorkerContainerMethods,
@@ -148,64 +149,86 @@ impl ServiceWorkerContainerMethods<crate::DomTypeHolder> for ServiceWorkerContai
             return promise;
         }
 
-        // Setup the callback for reject/resolve of the promise,
-        // from steps running "in-parallel" from here in the serviceworker manager.
-        let mut handler = RegisterJobResultHandler {
-            trusted_promise: Some(TrustedPromise::new(promise.clone())),
-            task_source: global.task_manager().dom_manipulation_task_source().into(),
-        };
-
-        let (job_result_sender, job_result_receiver) = ipc::channel().expect("ipc channel failure");
-
-        ROUTER.add_typed_route(
-            job_result_receiver,
-            Box::new(move |message| match message {
-                Ok(msg) => handler.handle(msg),
-                Err(err) => warn!("Error receiving a JobResult: {:?}", err),
-            }),
-        );
-
         let scope_things =
             ServiceWorkerRegistration::create_scope_things(&global, script_url.clone());
 
-        // B: Step 8 - 13
-        let job = Job::create_job(
+        // B: Step 8 - 14
+        schedule_job(
+            &global,
+            &promise,
             JobType::Register,
             scope,
             script_url,
-            job_result_sender,
             self.client.creation_url(),
             Some(scope_things),
         );
 
-        // B: Step 14: schedule job.
-        let _ = global
-            .script_to_constellation_chan()
-            .send(ScriptToConstellationMessage::ScheduleJob(job));
-
         // A: Step 7
         promise
     }
 }
 
-/// Callback for resolve/reject job promise for Register.
+/// <https://w3c.github.io/ServiceWorker/#create-job-algorithm>
+/// and <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>,
+/// with the job promise settled from the steps running in the serviceworker manager.
+pub(crate) fn schedule_job(
+    global: &GlobalScope,
+    promise: &Rc<Promise>,
+    job_type: JobType,
+    scope_url: ServoUrl,
+    script_url: ServoUrl,
+    referrer: ServoUrl,
+    scope_things: Option<ScopeThings>,
+) {
+    // Setup the callback for reject/resolve of the promise,
+    // from steps running "in-parallel" from here in the serviceworker manager.
+    let mut handler = JobResultHandler {
+        trusted_promise: Some(TrustedPromise::new(promise.clone())),
+        task_source: global.task_manager().dom_manipulation_task_source().into(),
+    };
+
+    let (job_result_sender, job_result_receiver) = ipc::channel().expect("ipc channel failure");
+
+    ROUTER.add_typed_route(
+        job_result_receiver,
+        Box::new(move |message| match message {
+            Ok(msg) => handler.handle(msg),
+            Err(err) => warn!("Error receiving a JobResult: {:?}", err),
+        }),
+    );
+
+    let job = Job::create_job(
+        job_type,
+        scope_url,
+        script_url,
+        job_result_sender,
+        referrer,
+        scope_things,
+    );
+
+    let _ = global
+        .script_to_constellation_chan()
+        .send(ScriptToConstellationMessage::ScheduleJob(job));
+}
+
+/// Callback for resolve/reject job promise for Register, Update and Unregister.
 /// <https://w3c.github.io/ServiceWorker/#register>
-struct RegisterJobResultHandler {
+struct JobResultHandler {
     trusted_promise: Option<TrustedPromise>,
     task_source: SendableTaskSource,
 }
 
-impl RegisterJobResultHandler {
+impl JobResultHandler {
     /// <https://w3c.github.io/ServiceWorker/#reject-job-promise>
     /// <https://w3c.github.io/ServiceWorker/#resolve-job-promise>
-    /// Handle a result to either resolve or reject the register job promise.
-    pub(crate) fn handle(&mut self, result: JobResult) {
+    /// Handle a result to either resolve or reject the job promise.
+    fn handle(&mut self, result: JobResult) {
         match result {
             JobResult::RejectPromise(error) => {
                 let promise = self
                     .trusted_promise
                     .take()
-                    .expect("No promise to resolve for SW Register job.");
+                    .expect("No promise to resolve for SW job.");
 
                 // Step 1
                 self.task_source
@@ -232,7 +255,7 @@ impl RegisterJobResultHandler {
                 let promise = self
                     .trusted_promise
                     .take()
-                    .expect("No promise to resolve for SW Register job.");
+                    .expect("No promise to resolve for SW job.");
 
                 // Step 1
                 self.task_source.queue(task!(resolve_promise: move || {
@@ -241,26 +264,32 @@ impl RegisterJobResultHandler {
                     let _ac = enter_realm(&*global);
 
                     // Step 1.1
-                    let JobResultValue::Registration {
-                        id,
-                        installing_worker,
-                        waiting_worker,
-                        active_worker,
-                    } = value;
-
-                    // Step 1.2 (Job type is "register").
-                    let registration = global.get_serviceworker_registration(
-                        &job.script_url,
-                        &job.scope_url,
-                        id,
-                        installing_worker,
-                        waiting_worker,
-                        active_worker,
-                        CanGc::note()
-                    );
+                    match value {
+                        JobResultValue::Registration {
+                            id,
+                            installing_worker,
+                            waiting_worker,
+                            active_worker,
+                        } => {
+                            // Step 1.2 (Job type is "register" or "update").
+                            let registration = global.get_serviceworker_registration(
+                                &job.script_url,
+                                &job.scope_url,
+                                id,
+                                installing_worker,
+                                waiting_worker,
+                                active_worker,
+                                CanGc::note()
+                            );
 
-                    // Step 1.4
-                    promise.resolve_native(&*registration, CanGc::note());
+                            // Step 1.4
+                            promise.resolve_native(&*registration, CanGc::note());
+                        },
+                        JobResultValue::Unregistered(unregistered) => {
+                            // Step 1.3 (Job type is "unregister").
+                            promise.resolve_native(&unregistered, CanGc::note());
+                        },
+                    }
                 }));
 
                 // TODO: step 2, handle equivalent jobs.
@@ -7,7 +7,7 @@ use std::sync::atomic::AtomicBool;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use base::id::PipelineId;
+use base::id::{PipelineId, ServiceWorkerId};
 use constellation_traits::{
     ScopeThings, ServiceWorkerMsg, WorkerGlobalScopeInit, WorkerScriptLoadOrigin,
 };
@@ -20,16 +20,19 @@ use js::jsapi::{JS_AddInterruptCallback, JSContext};
 use js::jsval::UndefinedValue;
 use net_traits::request::{
     CredentialsMode, Destination, InsecureRequestsPolicy, ParserMetadata, Referrer, RequestBuilder,
+    RequestMode, ServiceWorkersMode,
 };
 use net_traits::{CustomResponseMediator, IpcSend};
 use servo_config::pref;
 use servo_rand::random;
 use servo_url::ServoUrl;
 use style::thread_state::{self, ThreadState};
+use stylo_atoms::Atom;
 
 use crate::devtools;
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::abstractworkerglobalscope::{WorkerEventLoopMethods, run_worker_event_loop};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
@@ -44,7 +47,10 @@ use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::extendableevent::ExtendableEvent;
 use crate::dom::extendablemessageevent::ExtendableMessageEvent;
+use crate::dom::fetchevent::FetchEvent;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::headers::Guard;
+use crate::dom::request::Request;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::identityhub::IdentityHub;
 use crate::dom::worker::TrustedWorkerAddress;
@@ -62,6 +68,9 @@ pub(crate) enum ServiceWorkerScriptMsg {
     CommonWorker(WorkerScriptMsg),
     /// Message to request a custom response by the service worker
     Response(CustomResponseMediator),
+    /// The worker has become the active worker of its registration.
+    /// <https://w3c.github.io/ServiceWorker/#activation-algorithm>
+    Activate,
     /// Wake-up call from the task queue.
     WakeUp,
 }
@@ -286,6 +295,7 @@ impl ServiceWorkerGlobalScope {
         devtools_receiver: IpcReceiver<DevtoolScriptControlMsg>,
         swmanager_sender: IpcSender<ServiceWorkerMsg>,
         scope_url: ServoUrl,
+        worker_id: ServiceWorkerId,
         control_receiver: Receiver<ServiceWorkerControlMsg>,
         context_sender: Sender<ThreadSafeJSContext>,
         closing: Arc<AtomicBool>,
@@ -348,6 +358,7 @@ impl ServiceWorkerGlobalScope {
 
                 let request = RequestBuilder::new(None, script_url, referrer)
                     .destination(Destination::ServiceWorker)
+                    .service_workers_mode(ServiceWorkersMode::None)
                     .credentials_mode(CredentialsMode::Include)
                     .parser_metadata(ParserMetadata::NotParserInserted)
                     .use_url_credentials(true)
@@ -364,6 +375,13 @@ impl ServiceWorkerGlobalScope {
                 ) {
                     Err(_) => {
                         println!("error loading script {}", serialized_worker_url);
+                        let _ = global
+                            .swmanager_sender
+                            .send(ServiceWorkerMsg::InstallFinished(
+                                global.scope_url.clone(),
+                                worker_id,
+                                false,
+                            ));
                         scope.clear_js_runtime();
                         return;
                     },
@@ -386,9 +404,18 @@ impl ServiceWorkerGlobalScope {
                         CanGc::note(),
                     );
                     scope.execute_script(DOMString::from(source), CanGc::note());
-                    global.dispatch_activate(CanGc::note(), InRealm::entered(&realm));
+                    global.dispatch_install(CanGc::note(), InRealm::entered(&realm));
                 }
 
+                // The registration moves this worker to waiting, and then activates it.
+                let _ = global
+                    .swmanager_sender
+                    .send(ServiceWorkerMsg::InstallFinished(
+                        global.scope_url.clone(),
+                        worker_id,
+                        true,
+                    ));
+
                 let reporter_name = format!("service-worker-reporter-{}", random::<u64>());
                 scope
                     .upcast::<GlobalScope>()
@@ -468,13 +495,10 @@ impl ServiceWorkerGlobalScope {
             CommonWorker(WorkerScriptMsg::Common(msg)) => {
                 self.upcast::<WorkerGlobalScope>().process_event(msg);
             },
-            Response(mediator) => {
-                // TODO XXXcreativcoder This will eventually use a FetchEvent interface to fire event
-                // when we have the Request and Response dom api's implemented
-                // https://w3c.github.io/ServiceWorker/#fetchevent-interface
-                self.upcast::<EventTarget>()
-                    .fire_event(atom!("fetch"), can_gc);
-                let _ = mediator.response_chan.send(None);
+            Response(mediator) => self.dispatch_fetch(mediator, can_gc),
+            Activate => {
+                let realm = enter_realm(self);
+                self.dispatch_activate(can_gc, InRealm::entered(&realm));
             },
             WakeUp => {},
         }
@@ -484,11 +508,47 @@ impl ServiceWorkerGlobalScope {
         ScriptEventLoopSender::ServiceWorker(self.own_sender.clone())
     }
 
+    fn dispatch_install(&self, can_gc: CanGc, _realm: InRealm) {
+        let event = ExtendableEvent::new(self, Atom::from("install"), false, false, can_gc);
+        let event = (*event).upcast::<Event>();
+        self.upcast::<EventTarget>().dispatch_event(event, can_gc);
+    }
+
     fn dispatch_activate(&self, can_gc: CanGc, _realm: InRealm) {
         let event = ExtendableEvent::new(self, atom!("activate"), false, false, can_gc);
         let event = (*event).upcast::<Event>();
         self.upcast::<EventTarget>().dispatch_event(event, can_gc);
     }
+
+    /// Steps of <https://w3c.github.io/ServiceWorker/#handle-fetch> that fire a `FetchEvent`
+    /// for a request intercepted by the resource thread.
+    fn dispatch_fetch(&self, mediator: CustomResponseMediator, can_gc: CanGc) {
+        let global = self.upcast::<GlobalScope>();
+        let _realm = enter_realm(global);
+
+        let mode = if mediator.destination == Destination::Document {
+            RequestMode::Navigate
+        } else {
+            RequestMode::NoCors
+        };
+        let net_request = RequestBuilder::new(None, mediator.load_url, Referrer::NoReferrer)
+            .method(mediator.method)
+            .headers(mediator.headers.clone())
+            .destination(mediator.destination)
+            .mode(mode)
+            .origin(global.origin().immutable().clone())
+            .build();
+        let request = Request::from_net_request(global, None, net_request, can_gc);
+        let headers = request.Headers(can_gc);
+        headers.set_headers(mediator.headers);
+        headers.set_guard(Guard::Immutable);
+
+        let event = FetchEvent::new(global, &request, can_gc);
+        event
+            .upcast::<Event>()
+            .fire(self.upcast::<EventTarget>(), can_gc);
+        event.report_response(mediator.response_chan, can_gc);
+    }
 }
 
 #[allow(unsafe_code)]
@@ -509,4 +569,13 @@ impl ServiceWorkerGlobalScopeMethods<crate::DomTypeHolder> for ServiceWorkerGlob
 
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessageerror
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-oninstall
+    event_handler!(install, GetOninstall, SetOninstall);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onactivate
+    event_handler!(activate, GetOnactivate, SetOnactivate);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onfetch
+    event_handler!(fetch, GetOnfetch, SetOnfetch);
 }
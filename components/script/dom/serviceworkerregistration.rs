@@ -3,9 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::rc::Rc;
 
 use base::id::ServiceWorkerRegistrationId;
-use constellation_traits::{ScopeThings, WorkerScriptLoadOrigin};
+use constellation_traits::{JobType, ScopeThings, WorkerScriptLoadOrigin};
 use devtools_traits::WorkerId;
 use dom_struct::dom_struct;
 use net_traits::request::Referrer;
@@ -16,14 +17,19 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::{
     ServiceWorkerRegistrationMethods, ServiceWorkerUpdateViaCache,
 };
+use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{ByteString, USVString};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationpreloadmanager::NavigationPreloadManager;
+use crate::dom::promise::Promise;
+use crate::dom::pushmanager::PushManager;
 use crate::dom::serviceworker::ServiceWorker;
+use crate::dom::serviceworkercontainer::schedule_job;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
+use crate::realms::InRealm;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -33,6 +39,7 @@ pub(crate) struct ServiceWorkerRegistration {
     installing: DomRefCell<Option<Dom<ServiceWorker>>>,
     waiting: DomRefCell<Option<Dom<ServiceWorker>>>,
     navigation_preload: MutNullableDom<NavigationPreloadManager>,
+    push_manager: MutNullableDom<PushManager>,
     #[no_trace]
     scope: ServoUrl,
     navigation_preload_enabled: Cell<bool>,
@@ -54,6 +61,7 @@ impl ServiceWorkerRegistration {
             installing: DomRefCell::new(None),
             waiting: DomRefCell::new(None),
             navigation_preload: MutNullableDom::new(None),
+            push_manager: MutNullableDom::new(None),
             scope,
             navigation_preload_enabled: Cell::new(false),
             navigation_preload_header_value: DomRefCell::new(None),
@@ -80,13 +88,24 @@ impl ServiceWorkerRegistration {
         )
     }
 
+    pub(crate) fn scope(&self) -> &ServoUrl {
+        &self.scope
+    }
+
     /// Does this registration have an active worker?
     pub(crate) fn is_active(&self) -> bool {
         self.active.borrow().is_some()
     }
 
-    pub(crate) fn set_installing(&self, worker: &ServiceWorker) {
-        *self.installing.borrow_mut() = Some(Dom::from_ref(worker));
+    pub(crate) fn set_workers(
+        &self,
+        installing: Option<&ServiceWorker>,
+        waiting: Option<&ServiceWorker>,
+        active: Option<&ServiceWorker>,
+    ) {
+        *self.installing.borrow_mut() = installing.map(Dom::from_ref);
+        *self.waiting.borrow_mut() = waiting.map(Dom::from_ref);
+        *self.active.borrow_mut() = active.map(Dom::from_ref);
     }
 
     pub(crate) fn get_navigation_preload_header_value(&self) -> Option<ByteString> {
@@ -207,4 +226,64 @@ impl ServiceWorkerRegistrationMethods<crate::DomTypeHolder> for ServiceWorkerReg
         self.navigation_preload
             .or_init(|| NavigationPreloadManager::new(&self.global(), self, CanGc::note()))
     }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-serviceworkerregistration-update>
+    fn Update(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 1 and 2.
+        let Some(newest_worker) = self.get_newest_worker() else {
+            // Step 3.
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        };
+
+        // TODO: Step 4, reject when called from the installing worker itself.
+
+        // Step 5 and 6.
+        let global = self.global();
+        let script_url = newest_worker.get_script_url();
+        let scope_things =
+            ServiceWorkerRegistration::create_scope_things(&global, script_url.clone());
+        schedule_job(
+            &global,
+            &promise,
+            JobType::Update,
+            self.scope.clone(),
+            script_url,
+            global.creation_url().clone(),
+            Some(scope_things),
+        );
+
+        // Step 7.
+        promise
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-serviceworkerregistration-unregister>
+    fn Unregister(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        // Step 2.
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 3 and 4.
+        // Note: the script url of an unregister job is null, the scope is passed instead.
+        let global = self.global();
+        schedule_job(
+            &global,
+            &promise,
+            JobType::Unregister,
+            self.scope.clone(),
+            self.scope.clone(),
+            global.creation_url().clone(),
+            None,
+        );
+
+        // Step 5.
+        promise
+    }
+
+    /// <https://w3c.github.io/push-api/#dom-serviceworkerregistration-pushmanager>
+    fn PushManager(&self) -> DomRoot<PushManager> {
+        self.push_manager
+            .or_init(|| PushManager::new(&self.global(), self, CanGc::note()))
+    }
 }
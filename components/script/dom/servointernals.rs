@@ -4,17 +4,22 @@
 
 use std::rc::Rc;
 
-use constellation_traits::ScriptToConstellationMessage;
+use constellation_traits::{RuntimeServicesReport, ScriptToConstellationMessage};
 use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
 use js::rust::HandleObject;
+use net_traits::CoreResourceMsg;
 use profile_traits::mem::MemoryReportResult;
 use script_bindings::interfaces::ServoInternalsHelpers;
 use script_bindings::script_runtime::JSContext;
+use serde_json::json;
+use servo_config::prefs::{self, PrefValue, Preferences};
 
 use crate::dom::bindings::codegen::Bindings::ServoInternalsBinding::ServoInternalsMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::realms::{AlreadyInRealm, InRealm};
@@ -41,6 +46,20 @@ impl ServoInternals {
 impl ServoInternalsMethods<crate::DomTypeHolder> for ServoInternals {
     /// <https://servo.org/internal-no-spec>
     fn ReportMemory(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        self.request_memory_report(ScriptToConstellationMessage::ReportMemory, comp, can_gc)
+    }
+
+    /// <https://servo.org/internal-no-spec>
+    fn GarbageCollectAndReportMemory(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        self.request_memory_report(
+            ScriptToConstellationMessage::CollectGarbageAndReportMemory,
+            comp,
+            can_gc,
+        )
+    }
+
+    /// <https://servo.org/internal-no-spec>
+    fn ReportRuntimeServices(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
         let global = &self.global();
         let promise = Promise::new_in_current_realm(comp, can_gc);
         let task_source = global.task_manager().dom_manipulation_task_source();
@@ -48,13 +67,134 @@ impl ServoInternalsMethods<crate::DomTypeHolder> for ServoInternals {
 
         let script_to_constellation_chan = global.script_to_constellation_chan();
         if script_to_constellation_chan
-            .send(ScriptToConstellationMessage::ReportMemory(sender))
+            .send(ScriptToConstellationMessage::ReportRuntimeServices(sender))
             .is_err()
         {
             promise.reject_error(Error::Operation, can_gc);
         }
         promise
     }
+
+    /// <https://servo.org/internal-no-spec>
+    fn Preferences(&self) -> DOMString {
+        let preferences = prefs::get();
+        let default_preferences = Preferences::default();
+        let descriptions: Vec<_> = Preferences::NAMES
+            .iter()
+            .map(|name| {
+                let value = preferences.get_value(name);
+                json!({
+                    "name": name,
+                    "type": type_name(&value),
+                    "value": display_value(&value),
+                    "defaultValue": display_value(&default_preferences.get_value(name)),
+                })
+            })
+            .collect();
+        DOMString::from(serde_json::Value::Array(descriptions).to_string())
+    }
+
+    /// <https://servo.org/internal-no-spec>
+    fn SetPreference(&self, name: DOMString, value: DOMString) -> ErrorResult {
+        let name = String::from(name);
+        if !Preferences::NAMES.contains(&name.as_str()) {
+            return Err(Error::Type(format!("Unknown preference: {name}")));
+        }
+        let Some(value) = parse_value(&prefs::get().get_value(&name), &value) else {
+            return Err(Error::Type(format!(
+                "Invalid value for preference {name}: {value}"
+            )));
+        };
+        self.change_preferences(vec![(name, value)]);
+        Ok(())
+    }
+
+    /// <https://servo.org/internal-no-spec>
+    fn ResetPreference(&self, name: DOMString) -> ErrorResult {
+        let name = String::from(name);
+        if !Preferences::NAMES.contains(&name.as_str()) {
+            return Err(Error::Type(format!("Unknown preference: {name}")));
+        }
+        let value = Preferences::default().get_value(&name);
+        self.change_preferences(vec![(name, value)]);
+        Ok(())
+    }
+
+    /// <https://servo.org/internal-no-spec>
+    fn PerformAction(&self, action: DOMString, value: DOMString) {
+        let global = self.global();
+        let _ = global
+            .resource_threads()
+            .send(CoreResourceMsg::InternalPageAction(
+                global.get_url(),
+                action.into(),
+                value.into(),
+            ));
+    }
+}
+
+impl ServoInternals {
+    fn request_memory_report(
+        &self,
+        message: fn(IpcSender<MemoryReportResult>) -> ScriptToConstellationMessage,
+        comp: InRealm,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let global = &self.global();
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+        let task_source = global.task_manager().dom_manipulation_task_source();
+        let sender = route_promise(&promise, self, task_source);
+
+        let script_to_constellation_chan = global.script_to_constellation_chan();
+        if script_to_constellation_chan.send(message(sender)).is_err() {
+            promise.reject_error(Error::Operation, can_gc);
+        }
+        promise
+    }
+
+    /// Apply the changed preferences to this process right away, so that they are reflected
+    /// by [`ServoInternalsMethods::Preferences`], and let the constellation pass them on to
+    /// all other processes.
+    fn change_preferences(&self, values: Vec<(String, PrefValue)>) {
+        prefs::set_values(&values);
+        let _ = self
+            .global()
+            .script_to_constellation_chan()
+            .send(ScriptToConstellationMessage::PreferencesChanged(values));
+    }
+}
+
+fn type_name(value: &PrefValue) -> &'static str {
+    match value {
+        PrefValue::Float(_) => "float",
+        PrefValue::Int(_) => "int",
+        PrefValue::Str(_) => "string",
+        PrefValue::Bool(_) => "bool",
+        PrefValue::Array(_) => "array",
+    }
+}
+
+fn display_value(value: &PrefValue) -> String {
+    match value {
+        PrefValue::Float(value) => value.to_string(),
+        PrefValue::Int(value) => value.to_string(),
+        PrefValue::Str(value) => value.clone(),
+        PrefValue::Bool(value) => value.to_string(),
+        PrefValue::Array(values) => itertools::join(values.iter().map(display_value), ","),
+    }
+}
+
+/// Parse `input` as a new value for a preference whose current value is `current_value`,
+/// which decides the type of the new value. Returns `None` if `input` cannot be parsed as a
+/// value of that type. Array preferences cannot be edited.
+fn parse_value(current_value: &PrefValue, input: &str) -> Option<PrefValue> {
+    match current_value {
+        PrefValue::Float(_) => input.parse::<f64>().ok().map(PrefValue::Float),
+        PrefValue::Int(_) => input.parse::<i64>().ok().map(PrefValue::Int),
+        PrefValue::Str(_) => Some(PrefValue::Str(input.to_owned())),
+        PrefValue::Bool(_) => input.parse::<bool>().ok().map(PrefValue::Bool),
+        PrefValue::Array(_) => None,
+    }
 }
 
 impl RoutedPromiseListener<MemoryReportResult> for ServoInternals {
@@ -65,15 +205,34 @@ impl RoutedPromiseListener<MemoryReportResult> for ServoInternals {
     }
 }
 
+impl RoutedPromiseListener<RuntimeServicesReport> for ServoInternals {
+    fn handle_response(
+        &self,
+        response: RuntimeServicesReport,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        let stringified = serde_json::to_string(&response).unwrap_or_else(|_| {
+            "{ error: \"failed to create runtime services report\"}".to_owned()
+        });
+        promise.resolve_native(&stringified, can_gc);
+    }
+}
+
 impl ServoInternalsHelpers for ServoInternals {
-    /// The navigator.servo api is only exposed to about: pages except about:blank
+    /// The navigator.servo api is only exposed to about: pages except about:blank, and to
+    /// the internal pages served under the servo: scheme, like servo:memory.
     #[allow(unsafe_code)]
     fn is_servo_internal(cx: JSContext, _global: HandleObject) -> bool {
         unsafe {
             let in_realm_proof = AlreadyInRealm::assert_for_cx(cx);
             let global_scope = GlobalScope::from_context(*cx, InRealm::Already(&in_realm_proof));
             let url = global_scope.get_url();
-            url.scheme() == "about" && url.as_str() != "about:blank"
+            match url.scheme() {
+                "about" => url.as_str() != "about:blank",
+                "servo" => true,
+                _ => false,
+            }
         }
     }
 }
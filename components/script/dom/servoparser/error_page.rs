@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The pages shown in place of pages that failed to load, which describe why and can load the
+//! page again. The embedder can supply its own page for top-level documents.
+
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use embedder_traits::{EmbedderMsg, LoadError};
+use ipc_channel::ipc;
+use net_traits::NetworkError;
+use servo_url::ServoUrl;
+
+use crate::dom::document::Document;
+
+const ERROR_PAGE_STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 50em; padding: 0 1em; }
+.button { display: inline-block; padding: 0.4em 1.2em; border: 1px solid #888;
+          border-radius: 4px; background: #eee; color: black; text-decoration: none;
+          font: inherit; cursor: pointer; }
+pre { overflow: auto; max-height: 30em; padding: 0.5em; background: #f4f4f4;
+      white-space: pre-wrap; word-break: break-all; }
+";
+
+/// A script that shows the SHA-256 fingerprint of the certificate in `#certificate`, and
+/// asks the network stack to accept the certificate when `#accept` is clicked.
+const CERTIFICATE_SCRIPT: &str = "
+const certificate = document.getElementById('certificate');
+const bytes = Uint8Array.from(atob(certificate.dataset.bytes), c => c.charCodeAt(0));
+crypto.subtle.digest('SHA-256', bytes).then(digest => {
+    document.getElementById('fingerprint').textContent = Array.from(new Uint8Array(digest))
+        .map(byte => byte.toString(16).padStart(2, '0').toUpperCase()).join(':');
+}, () => {});
+document.getElementById('accept').onclick = () => {
+    const xhr = new XMLHttpRequest();
+    xhr.open('POST', 'chrome:allowcert');
+    xhr.onloadend = () => location.reload();
+    xhr.send(certificate.dataset.secret + '&' + certificate.dataset.unpadded);
+};
+";
+
+/// The [`LoadError`] to describe on the error page for a network error, if the error is one
+/// that the page is shown for.
+pub(crate) fn load_error_for_network_error(error: &NetworkError) -> Option<LoadError> {
+    Some(match error {
+        NetworkError::ResolveFailed(details) => LoadError::ResolveFailed(details.clone()),
+        NetworkError::ConnectionRefused(details) => LoadError::ConnectionRefused(details.clone()),
+        NetworkError::SslValidation(reason, certificate) => {
+            LoadError::Certificate(reason.clone(), certificate.clone())
+        },
+        NetworkError::Internal(details) => LoadError::Network(details.clone()),
+        NetworkError::LoadCancelled | NetworkError::Crash(_) => return None,
+    })
+}
+
+/// The HTML of the page to show in `document` for a page at `url` that failed to load, which is
+/// supplied by the embedder for top-level documents if it wants to.
+pub(crate) fn error_page(document: &Document, url: &ServoUrl, error: LoadError) -> String {
+    let window = document.window();
+    if window.is_top_level() {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        window.send_to_embedder(EmbedderMsg::GetErrorPage(
+            document.webview_id(),
+            url.clone(),
+            error.clone(),
+            sender,
+        ));
+        if let Ok(Some(page)) = receiver.recv() {
+            return page;
+        }
+    }
+    generate_error_page(url, &error)
+}
+
+fn generate_error_page(url: &ServoUrl, error: &LoadError) -> String {
+    let host = escape_html(url.host_str().unwrap_or(url.as_str()));
+    let (title, description, details) = match error {
+        LoadError::ResolveFailed(details) => (
+            "Server not found".to_owned(),
+            format!(
+                "The address of <strong>{host}</strong> could not be found. Check that the \
+                 address is spelled correctly and that you are connected to the network."
+            ),
+            details.clone(),
+        ),
+        LoadError::ConnectionRefused(details) => (
+            "Unable to connect".to_owned(),
+            format!(
+                "<strong>{host}</strong> refused the connection. The server may be down or \
+                 not accepting connections on this port."
+            ),
+            details.clone(),
+        ),
+        LoadError::Certificate(reason, _) => (
+            "This connection is not secure".to_owned(),
+            format!(
+                "The certificate of <strong>{host}</strong> could not be validated, so this \
+                 may not be the site that it claims to be. Attackers could be trying to steal \
+                 information that you send to it."
+            ),
+            reason.clone(),
+        ),
+        LoadError::HttpStatus(code, reason) => (
+            format!("{code} {reason}"),
+            format!(
+                "<strong>{host}</strong> responded with the error status \
+                 <strong>{code} {reason}</strong> and an empty page.",
+                reason = escape_html(reason)
+            ),
+            format!("HTTP status {code} {reason}"),
+        ),
+        LoadError::Network(details) => (
+            "Unable to load this page".to_owned(),
+            format!("An error occurred while loading the page from <strong>{host}</strong>."),
+            details.clone(),
+        ),
+    };
+
+    let certificate = match error {
+        LoadError::Certificate(_, certificate) => certificate_section(certificate),
+        _ => String::new(),
+    };
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{ERROR_PAGE_STYLE}</style></head><body><h1>{title}</h1><p>{description}</p>\
+         <p><a class=\"button\" href=\"{url}\">Try again</a></p>\
+         <details><summary>Details</summary><pre>{url}\n{details}</pre></details>\
+         {certificate}</body></html>",
+        title = escape_html(&title),
+        url = escape_html(url.as_str()),
+        details = escape_html(&details),
+    )
+}
+
+/// The details of a certificate that failed validation, and a button to accept it anyway.
+fn certificate_section(certificate: &[u8]) -> String {
+    let padded = general_purpose::STANDARD.encode(certificate);
+    let pem: Vec<_> = padded
+        .as_bytes()
+        .chunks(64)
+        .map(|line| String::from_utf8_lossy(line))
+        .collect();
+    format!(
+        "<details id=\"certificate\" data-bytes=\"{padded}\" data-unpadded=\"{unpadded}\" \
+         data-secret=\"{secret}\"><summary>Certificate</summary>\
+         <p>SHA-256 fingerprint: <code id=\"fingerprint\"></code></p>\
+         <pre>-----BEGIN CERTIFICATE-----\n{pem}\n-----END CERTIFICATE-----</pre>\
+         <p><button class=\"button\" id=\"accept\">Accept the risk and continue</button></p>\
+         </details><script>{CERTIFICATE_SCRIPT}</script>",
+        unpadded = general_purpose::STANDARD_NO_PAD.encode(certificate),
+        secret = *net_traits::PRIVILEGED_SECRET,
+        pem = pem.join("\n"),
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -7,12 +7,12 @@ use std::cell::Cell;
 
 use base::cross_process_instant::CrossProcessInstant;
 use base::id::PipelineId;
-use base64::Engine as _;
-use base64::engine::general_purpose;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
+use embedder_traits::LoadError;
 use embedder_traits::resources::{self, Resource};
 use encoding_rs::Encoding;
+use headers::{ContentLength, HeaderMapExt};
 use html5ever::buffer_queue::BufferQueue;
 use html5ever::tendril::fmt::UTF8;
 use html5ever::tendril::{ByteTendril, StrTendril, TendrilSink};
@@ -21,6 +21,7 @@ use html5ever::{Attribute, ExpandedName, LocalName, QualName, local_name, ns};
 use hyper_serde::Serde;
 use markup5ever::TokenizerResult;
 use mime::{self, Mime};
+use net_traits::http_status::HttpStatus;
 use net_traits::policy_container::PolicyContainer;
 use net_traits::request::RequestId;
 use net_traits::{
@@ -56,7 +57,7 @@ use crate::dom::bindings::settings_stack::is_execution_stack_empty;
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::characterdata::CharacterData;
 use crate::dom::comment::Comment;
-use crate::dom::csp::{GlobalCspReporting, Violation, parse_csp_list_from_metadata};
+use crate::dom::csp::{CspReporting, GlobalCspReporting, Violation, parse_csp_list_from_metadata};
 use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documenttype::DocumentType;
@@ -80,6 +81,7 @@ use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 
 mod async_html;
+mod error_page;
 mod html;
 mod prefetch;
 mod xml;
@@ -842,6 +844,11 @@ pub(crate) struct ParserContext {
     resource_timing: ResourceFetchTiming,
     /// pushed entry index
     pushed_entry_index: Option<usize>,
+    /// The HTTP error status of an HTML response, for which an error page is shown if the
+    /// response has an empty body.
+    http_error_status: Option<HttpStatus>,
+    /// Whether any of the body of the response was received.
+    received_body: bool,
 }
 
 impl ParserContext {
@@ -853,6 +860,8 @@ impl ParserContext {
             url,
             resource_timing: ResourceFetchTiming::new(ResourceTimingType::Navigation),
             pushed_entry_index: None,
+            http_error_status: None,
+            received_body: false,
         }
     }
 
@@ -897,6 +906,8 @@ impl FetchResponseListener for ParserContext {
                 match &error {
                     NetworkError::SslValidation(..) |
                     NetworkError::Internal(..) |
+                    NetworkError::ResolveFailed(..) |
+                    NetworkError::ConnectionRefused(..) |
                     NetworkError::Crash(..) => {
                         let mut meta = Metadata::default(self.url.clone());
                         let mime: Option<Mime> = "text/html".parse().ok();
@@ -914,6 +925,22 @@ impl FetchResponseListener for ParserContext {
             .map(Serde::into_inner)
             .map(Into::into);
 
+        let http_error_status = metadata
+            .as_ref()
+            .map(|meta| meta.status.clone())
+            .filter(|status| status.in_range(400..600))
+            .filter(|_| {
+                content_type
+                    .as_ref()
+                    .is_none_or(|mime| mime.type_() == mime::TEXT && mime.subtype() == mime::HTML)
+            });
+
+        let content_length = metadata
+            .as_ref()
+            .and_then(|meta| meta.headers.as_ref())
+            .and_then(|headers| headers.typed_get::<ContentLength>())
+            .map(|length| length.0);
+
         let (csp_list, endpoints_list) = match metadata.as_ref() {
             None => (None, None),
             Some(m) => (
@@ -933,12 +960,33 @@ impl FetchResponseListener for ParserContext {
         let _realm = enter_realm(&*parser.document);
 
         parser.document.set_csp_list(csp_list);
+        parser.document.set_expected_page_bytes(content_length);
         if let Some(endpoints) = endpoints_list {
             parser.document.window().set_endpoints_list(endpoints);
         }
         self.parser = Some(Trusted::new(&*parser));
+        self.http_error_status = http_error_status;
         self.submit_resource_timing();
 
+        // <https://html.spec.whatwg.org/multipage/#populating-a-session-history-entry>
+        // If the navigation response is blocked by the `frame-ancestors` directive,
+        // the response is replaced by a network error.
+        if parser
+            .document
+            .get_csp_list()
+            .is_framing_blocked_by_frame_ancestors(&parser.document.window())
+        {
+            self.is_synthesized_document = true;
+            let page = resources::read_string(Resource::NetErrorHTML);
+            let page = page.replace(
+                "${reason}",
+                "Framing was blocked by the frame-ancestors Content Security Policy directive",
+            );
+            parser.push_string_input_chunk(page);
+            parser.parse_sync(CanGc::note());
+            return;
+        }
+
         let content_type = match content_type {
             Some(ref content_type) => content_type,
             None => {
@@ -982,32 +1030,22 @@ impl FetchResponseListener for ParserContext {
                 parser.tokenizer.set_plaintext_state();
             },
             (mime::TEXT, mime::HTML, _) => match error {
-                Some(NetworkError::SslValidation(reason, bytes)) => {
+                Some(NetworkError::Crash(details)) => {
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::BadCertHTML);
-                    let page = page.replace("${reason}", &reason);
-                    let encoded_bytes = general_purpose::STANDARD_NO_PAD.encode(bytes);
-                    let page = page.replace("${bytes}", encoded_bytes.as_str());
-                    let page =
-                        page.replace("${secret}", &net_traits::PRIVILEGED_SECRET.to_string());
+                    let page = resources::read_string(Resource::CrashHTML);
+                    let page = page.replace("${details}", &details);
                     parser.push_string_input_chunk(page);
                     parser.parse_sync(CanGc::note());
                 },
-                Some(NetworkError::Internal(reason)) => {
+                Some(error) => {
+                    let Some(error) = error_page::load_error_for_network_error(&error) else {
+                        return;
+                    };
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::NetErrorHTML);
-                    let page = page.replace("${reason}", &reason);
+                    let page = error_page::error_page(&parser.document, &self.url, error);
                     parser.push_string_input_chunk(page);
                     parser.parse_sync(CanGc::note());
                 },
-                Some(NetworkError::Crash(details)) => {
-                    self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::CrashHTML);
-                    let page = page.replace("${details}", &details);
-                    parser.push_string_input_chunk(page);
-                    parser.parse_sync(CanGc::note());
-                },
-                Some(_) => {},
                 None => {},
             },
             (mime::TEXT, mime::XML, _) |
@@ -1040,6 +1078,8 @@ impl FetchResponseListener for ParserContext {
             return;
         }
         let _realm = enter_realm(&*parser);
+        self.received_body |= !payload.is_empty();
+        parser.document.note_page_bytes_received(payload.len());
         parser.parse_bytes_chunk(payload, CanGc::note());
     }
 
@@ -1072,6 +1112,19 @@ impl FetchResponseListener for ParserContext {
             .document
             .set_redirect_count(self.resource_timing.redirect_count);
 
+        // Show an error page instead of a blank page for HTTP errors without a body.
+        if let Some(status) = self.http_error_status.take() {
+            if !self.received_body && !self.is_synthesized_document {
+                self.is_synthesized_document = true;
+                let error = LoadError::HttpStatus(
+                    status.raw_code(),
+                    String::from_utf8_lossy(status.message()).into_owned(),
+                );
+                let page = error_page::error_page(&parser.document, &self.url, error);
+                parser.push_string_input_chunk(page);
+            }
+        }
+
         parser.last_chunk_received.set(true);
         if !parser.suspended.get() {
             parser.parse_sync(CanGc::note());
@@ -1260,6 +1313,10 @@ impl TreeSink for Sink {
         attrs: Vec<Attribute>,
         flags: ElementFlags,
     ) -> Dom<Node> {
+        if self.parsing_algorithm == ParsingAlgorithm::Normal && !flags.template {
+            prefetch_dns_for_element(&self.document, &name, &attrs);
+        }
+
         let attrs = attrs
             .into_iter()
             .map(|attr| ElementAttribute::new(attr.name, DOMString::from(String::from(attr.value))))
@@ -1470,6 +1527,37 @@ impl TreeSink for Sink {
 }
 
 /// <https://html.spec.whatwg.org/multipage/#create-an-element-for-the-token>
+/// Resolve ahead of time the hosts of the resources that an element created by the parser
+/// refers to, which are only fetched once the element is inserted into the document.
+fn prefetch_dns_for_element(document: &Document, name: &QualName, attrs: &[Attribute]) {
+    if name.ns != ns!(html) {
+        return;
+    }
+
+    let url_attribute = match name.local {
+        local_name!("link") | local_name!("a") => local_name!("href"),
+        local_name!("img") |
+        local_name!("script") |
+        local_name!("iframe") |
+        local_name!("source") |
+        local_name!("video") |
+        local_name!("audio") |
+        local_name!("embed") => local_name!("src"),
+        _ => return,
+    };
+
+    let Some(value) = attrs
+        .iter()
+        .find(|attr| attr.name.ns == ns!() && attr.name.local == url_attribute)
+        .map(|attr| &attr.value)
+    else {
+        return;
+    };
+    if let Ok(url) = document.base_url().join(value) {
+        document.prefetch_dns_for_discovered_url(&url);
+    }
+}
+
 fn create_element_for_token(
     name: QualName,
     attrs: Vec<ElementAttribute>,
@@ -24,6 +24,8 @@ use servo_url::{ImmutableOrigin, ServoUrl};
 use crate::dom::bindings::reflector::DomGlobal;
 use crate::dom::bindings::trace::{CustomTraceable, JSTraceable};
 use crate::dom::document::{Document, determine_policy_for_token};
+use crate::dom::htmlimageelement::{ImageSource, parse_a_srcset_attribute};
+use crate::dom::htmllinkelement::translate_a_preload_destination;
 use crate::dom::htmlscriptelement::script_fetch_request;
 use crate::fetch::create_a_potential_cors_request;
 use crate::script_module::ScriptFetchOptions;
@@ -66,6 +68,7 @@ impl Tokenizer {
             pipeline_id: global.pipeline_id(),
             webview_id: document.webview_id(),
             base_url: RefCell::new(None),
+            device_pixel_ratio: document.window().device_pixel_ratio().get() as f64,
             document_url: document.url(),
             referrer: global.get_referrer(),
             referrer_policy: document.get_referrer_policy(),
@@ -100,6 +103,7 @@ struct PrefetchSink {
     document_url: ServoUrl,
     #[no_trace]
     base_url: RefCell<Option<ServoUrl>>,
+    device_pixel_ratio: f64,
     #[no_trace]
     referrer: Referrer,
     #[no_trace]
@@ -163,63 +167,40 @@ impl TokenSink for PrefetchSink {
                 TokenSinkResult::RawData(RawKind::ScriptData)
             },
             (TagKind::StartTag, &local_name!("img")) if self.prefetching.get() => {
-                if let Some(url) = self.get_url(tag, local_name!("src")) {
-                    debug!("Prefetch {} {}", tag.name, url);
-                    let request = create_a_potential_cors_request(
-                        Some(self.webview_id),
-                        url,
-                        Destination::Image,
-                        self.get_cors_settings(tag, local_name!("crossorigin")),
-                        None,
-                        self.referrer.clone(),
-                        self.insecure_requests_policy,
-                        self.has_trustworthy_ancestor_origin,
-                        self.policy_container.clone(),
-                    )
-                    .origin(self.origin.clone())
-                    .pipeline_id(Some(self.pipeline_id))
-                    .referrer_policy(self.get_referrer_policy(tag, local_name!("referrerpolicy")));
-
-                    let _ = self
-                        .resource_threads
-                        .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+                let url = self
+                    .get_srcset_url(tag)
+                    .or_else(|| self.get_url(tag, local_name!("src")));
+                if let Some(url) = url {
+                    self.prefetch_resource(tag, url, Destination::Image);
                 }
                 TokenSinkResult::Continue
             },
             (TagKind::StartTag, &local_name!("link")) if self.prefetching.get() => {
                 if let Some(rel) = self.get_attr(tag, local_name!("rel")) {
-                    if rel.value.eq_ignore_ascii_case("stylesheet") {
+                    let has_rel = |keyword: &str| {
+                        rel.value
+                            .split_ascii_whitespace()
+                            .any(|token| token.eq_ignore_ascii_case(keyword))
+                    };
+                    if has_rel("preload") {
+                        // https://html.spec.whatwg.org/multipage/#link-type-preload
+                        let destination = self
+                            .get_attr(tag, local_name!("as"))
+                            .map(|attr| {
+                                translate_a_preload_destination(&attr.value.to_ascii_lowercase())
+                            })
+                            .unwrap_or(Destination::None);
+                        // Preloads without a destination are fetched as-is by the main parser,
+                        // since they could be requests that are not meant to be repeated.
+                        if destination != Destination::None {
+                            if let Some(url) = self.get_url(tag, local_name!("href")) {
+                                self.prefetch_resource(tag, url, destination);
+                            }
+                        }
+                    } else if has_rel("stylesheet") {
+                        // https://html.spec.whatwg.org/multipage/#default-fetch-and-process-the-linked-resource
                         if let Some(url) = self.get_url(tag, local_name!("href")) {
-                            debug!("Prefetch {} {}", tag.name, url);
-                            let cors_setting =
-                                self.get_cors_settings(tag, local_name!("crossorigin"));
-                            let referrer_policy =
-                                self.get_referrer_policy(tag, local_name!("referrerpolicy"));
-                            let integrity_metadata = self
-                                .get_attr(tag, local_name!("integrity"))
-                                .map(|attr| String::from(&attr.value))
-                                .unwrap_or_default();
-
-                            // https://html.spec.whatwg.org/multipage/#default-fetch-and-process-the-linked-resource
-                            let request = create_a_potential_cors_request(
-                                Some(self.webview_id),
-                                url,
-                                Destination::Style,
-                                cors_setting,
-                                None,
-                                self.referrer.clone(),
-                                self.insecure_requests_policy,
-                                self.has_trustworthy_ancestor_origin,
-                                self.policy_container.clone(),
-                            )
-                            .origin(self.origin.clone())
-                            .pipeline_id(Some(self.pipeline_id))
-                            .referrer_policy(referrer_policy)
-                            .integrity_metadata(integrity_metadata);
-
-                            let _ = self
-                                .resource_threads
-                                .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+                            self.prefetch_resource(tag, url, Destination::Style);
                         }
                     }
                 }
@@ -248,6 +229,62 @@ impl TokenSink for PrefetchSink {
 }
 
 impl PrefetchSink {
+    /// Start fetching a resource that is not a script, with the CORS settings, referrer
+    /// policy and integrity metadata of `tag`.
+    fn prefetch_resource(&self, tag: &Tag, url: ServoUrl, destination: Destination) {
+        debug!("Prefetch {} {} as {:?}", tag.name, url, destination);
+        let integrity_metadata = self
+            .get_attr(tag, local_name!("integrity"))
+            .map(|attr| String::from(&attr.value))
+            .unwrap_or_default();
+        let request = create_a_potential_cors_request(
+            Some(self.webview_id),
+            url,
+            destination,
+            self.get_cors_settings(tag, local_name!("crossorigin")),
+            None,
+            self.referrer.clone(),
+            self.insecure_requests_policy,
+            self.has_trustworthy_ancestor_origin,
+            self.policy_container.clone(),
+        )
+        .origin(self.origin.clone())
+        .pipeline_id(Some(self.pipeline_id))
+        .referrer_policy(self.get_referrer_policy(tag, local_name!("referrerpolicy")))
+        .integrity_metadata(integrity_metadata);
+
+        let _ = self
+            .resource_threads
+            .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+    }
+
+    /// The URL of the `srcset` candidate that the image is most likely to select, following
+    /// <https://html.spec.whatwg.org/multipage/#select-an-image-source>. Candidates with width
+    /// descriptors depend on `sizes` and layout, so they are left to the main parser.
+    fn get_srcset_url(&self, tag: &Tag) -> Option<ServoUrl> {
+        let srcset = self.get_attr(tag, local_name!("srcset"))?;
+        let candidates = parse_a_srcset_attribute(&srcset.value);
+        if candidates
+            .iter()
+            .any(|candidate| candidate.descriptor.width.is_some())
+        {
+            return None;
+        }
+        let density = |candidate: &ImageSource| candidate.descriptor.density.unwrap_or(1.);
+        let best_candidate = candidates
+            .iter()
+            .filter(|candidate| density(candidate) >= self.device_pixel_ratio)
+            .min_by(|a, b| density(a).total_cmp(&density(b)))
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .max_by(|a, b| density(a).total_cmp(&density(b)))
+            })?;
+        let base_url = self.base_url.borrow();
+        let base = base_url.as_ref().unwrap_or(&self.document_url);
+        ServoUrl::parse_with_base(Some(base), &best_candidate.url).ok()
+    }
+
     fn get_attr<'a>(&'a self, tag: &'a Tag, name: LocalName) -> Option<&'a Attribute> {
         tag.attrs.iter().find(|attr| attr.name.local == name)
     }
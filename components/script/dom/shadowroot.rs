@@ -17,6 +17,7 @@ use style::stylist::{CascadeData, Stylist};
 use stylo_atoms::Atom;
 
 use crate::conversions::Convert;
+use crate::dom::animation::Animation;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::GetHTMLOptions;
 use crate::dom::bindings::codegen::Bindings::HTMLSlotElementBinding::HTMLSlotElement_Binding::HTMLSlotElementMethods;
@@ -40,7 +41,6 @@ use crate::dom::node::{
     VecPreOrderInsertionHelper,
 };
 use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
-use crate::dom::types::EventTarget;
 use crate::dom::virtualmethods::{VirtualMethods, vtable_for};
 use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
@@ -326,23 +326,14 @@ impl ShadowRootMethods<crate::DomTypeHolder> for ShadowRoot {
         y: Finite<f64>,
         can_gc: CanGc,
     ) -> Option<DomRoot<Element>> {
-        // Return the result of running the retargeting algorithm with context object
-        // and the original result as input.
-        match self.document_or_shadow_root.element_from_point(
+        self.document_or_shadow_root.element_from_point(
+            self.upcast(),
             x,
             y,
             None,
             self.document.has_browsing_context(),
             can_gc,
-        ) {
-            Some(e) => {
-                let retargeted_node = self
-                    .upcast::<EventTarget>()
-                    .retarget(e.upcast::<EventTarget>());
-                retargeted_node.downcast::<Element>().map(DomRoot::from_ref)
-            },
-            None => None,
-        }
+        )
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
@@ -352,22 +343,14 @@ impl ShadowRootMethods<crate::DomTypeHolder> for ShadowRoot {
         y: Finite<f64>,
         can_gc: CanGc,
     ) -> Vec<DomRoot<Element>> {
-        // Return the result of running the retargeting algorithm with context object
-        // and the original result as input
-        let mut elements = Vec::new();
-        for e in self
-            .document_or_shadow_root
-            .elements_from_point(x, y, None, self.document.has_browsing_context(), can_gc)
-            .iter()
-        {
-            let retargeted_node = self
-                .upcast::<EventTarget>()
-                .retarget(e.upcast::<EventTarget>());
-            if let Some(element) = retargeted_node.downcast::<Element>().map(DomRoot::from_ref) {
-                elements.push(element);
-            }
-        }
-        elements
+        self.document_or_shadow_root.elements_from_point(
+            self.upcast(),
+            x,
+            y,
+            None,
+            self.document.has_browsing_context(),
+            can_gc,
+        )
     }
 
     /// <https://dom.spec.whatwg.org/#dom-shadowroot-mode>
@@ -396,6 +379,12 @@ impl ShadowRootMethods<crate::DomTypeHolder> for ShadowRoot {
         host.expect("Trying to get host from a detached shadow root")
     }
 
+    // https://drafts.csswg.org/web-animations-1/#dom-documentorshadowroot-getanimations
+    fn GetAnimations(&self, can_gc: CanGc) -> Vec<DomRoot<Animation>> {
+        self.document_or_shadow_root
+            .get_animations(self.upcast(), can_gc)
+    }
+
     // https://drafts.csswg.org/cssom/#dom-document-stylesheets
     fn StyleSheets(&self) -> DomRoot<StyleSheetList> {
         self.stylesheet_list.or_init(|| {
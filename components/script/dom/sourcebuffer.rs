@@ -0,0 +1,746 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{EndOfStreamError, ReadyState};
+use crate::dom::bindings::codegen::Bindings::SourceBufferBinding::{
+    AppendMode, SourceBufferMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mediasource::MediaSource;
+use crate::dom::timeranges::{TimeRanges, TimeRangesContainer};
+use crate::dom::window::Window;
+use crate::media_source::{
+    ByteStreamFormat, InitializationSegment, MediaSegment, ParseError, Segment, SegmentParser,
+    TrackKind, renumber_tracks,
+};
+use crate::script_runtime::CanGc;
+
+/// The largest gap, in seconds, between two media segments that are considered to be
+/// contiguous in "sequence" mode.
+const DISCONTINUITY_TOLERANCE: f64 = 0.001;
+
+/// <https://w3c.github.io/media-source/#sourcebuffer>
+///
+/// The media player demuxes the segments itself, so coded frames are processed a media
+/// segment at a time: timestamps are offset and buffered ranges are updated for whole
+/// segments.
+#[dom_struct]
+pub(crate) struct SourceBuffer {
+    eventtarget: EventTarget,
+    /// <https://w3c.github.io/media-source/#parent-media-source>, which is unset once
+    /// this source buffer is removed from its `sourceBuffers`.
+    parent_media_source: MutNullableDom<MediaSource>,
+    #[no_trace]
+    format: ByteStreamFormat,
+    /// The segment parser, which holds the
+    /// <https://w3c.github.io/media-source/#sourcebuffer-input-buffer>.
+    #[no_trace]
+    parser: DomRefCell<SegmentParser>,
+    /// The first of the track IDs that the media source set aside for this source buffer.
+    track_base: u64,
+    /// The track IDs of the last initialization segment, whose positions map them to
+    /// the track IDs of the muxed stream.
+    track_ids: DomRefCell<Vec<u64>>,
+    /// The last initialization segment, with its tracks renumbered.
+    initialization_segment: DomRefCell<Option<Vec<u8>>>,
+    /// <https://w3c.github.io/media-source/#first-init-segment-received-flag>
+    first_initialization_segment_received: Cell<bool>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-mode>
+    mode: Cell<AppendMode>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-updating>
+    updating: Cell<bool>,
+    /// Whether the <https://w3c.github.io/media-source/#sourcebuffer-range-removal>
+    /// algorithm is running.
+    removing: Cell<bool>,
+    /// Incremented when the buffer append algorithm is aborted, to cancel its task.
+    append_generation: Cell<u32>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset>
+    timestamp_offset: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart>
+    append_window_start: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend>
+    append_window_end: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#group-start-timestamp>
+    group_start_timestamp: Cell<Option<f64>>,
+    /// <https://w3c.github.io/media-source/#group-end-timestamp>
+    group_end_timestamp: Cell<f64>,
+    /// The presentation end timestamp of the last media segment, which stands for the
+    /// <https://w3c.github.io/media-source/#last-decode-timestamp>.
+    last_segment_end: Cell<Option<f64>>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-buffered>
+    buffered: DomRefCell<TimeRangesContainer>,
+}
+
+impl SourceBuffer {
+    fn new_inherited(
+        parent_media_source: &MediaSource,
+        format: ByteStreamFormat,
+        track_base: u64,
+    ) -> SourceBuffer {
+        SourceBuffer {
+            eventtarget: EventTarget::new_inherited(),
+            parent_media_source: MutNullableDom::new(Some(parent_media_source)),
+            format,
+            parser: DomRefCell::new(format.new_parser()),
+            track_base,
+            track_ids: Default::default(),
+            initialization_segment: Default::default(),
+            first_initialization_segment_received: Cell::new(false),
+            mode: Cell::new(AppendMode::Segments),
+            updating: Cell::new(false),
+            removing: Cell::new(false),
+            append_generation: Cell::new(0),
+            timestamp_offset: Cell::new(0.),
+            append_window_start: Cell::new(0.),
+            append_window_end: Cell::new(f64::INFINITY),
+            group_start_timestamp: Cell::new(None),
+            group_end_timestamp: Cell::new(0.),
+            last_segment_end: Cell::new(None),
+            buffered: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        parent_media_source: &MediaSource,
+        format: ByteStreamFormat,
+        track_base: u64,
+        can_gc: CanGc,
+    ) -> DomRoot<SourceBuffer> {
+        reflect_dom_object(
+            Box::new(SourceBuffer::new_inherited(
+                parent_media_source,
+                format,
+                track_base,
+            )),
+            window,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn format(&self) -> ByteStreamFormat {
+        self.format
+    }
+
+    pub(crate) fn is_updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    pub(crate) fn initialization_segment(&self) -> Option<Vec<u8>> {
+        self.initialization_segment.borrow().clone()
+    }
+
+    pub(crate) fn buffered_ranges(&self) -> TimeRangesContainer {
+        self.buffered.borrow().clone()
+    }
+
+    /// The end time of the last buffered range, if any.
+    pub(crate) fn highest_end_time(&self) -> Option<f64> {
+        let buffered = self.buffered.borrow();
+        if buffered.is_empty() {
+            return None;
+        }
+        buffered.end(buffered.len() - 1).ok()
+    }
+
+    /// Detaches this source buffer from its parent media source, once it was removed
+    /// from its `sourceBuffers`.
+    pub(crate) fn remove_from_parent(&self) {
+        self.parent_media_source.set(None);
+        self.append_generation
+            .set(self.append_generation.get().wrapping_add(1));
+        self.updating.set(false);
+        self.removing.set(false);
+    }
+
+    fn queue_event(&self, name: &str) {
+        self.global()
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), Atom::from(name));
+    }
+
+    /// Returns the parent media source, or throws an `InvalidStateError` if this source
+    /// buffer was removed from it or is updating, as most attributes and methods do.
+    fn parent_if_not_updating(&self) -> Fallible<DomRoot<MediaSource>> {
+        let parent = self.parent_media_source.get().ok_or(Error::InvalidState)?;
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(parent)
+    }
+
+    /// Maps a track ID of the segments of this source buffer to one of the muxed stream.
+    fn map_track_id(&self, track_id: u64) -> u64 {
+        let position = self
+            .track_ids
+            .borrow()
+            .iter()
+            .position(|id| *id == track_id)
+            .unwrap_or(0);
+        self.track_base + position as u64
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-prepare-append>
+    fn prepare_append(&self) -> Fallible<DomRoot<MediaSource>> {
+        // If the SourceBuffer has been removed from the sourceBuffers attribute of the
+        // parent media source then throw an InvalidStateError exception.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception.
+        let media_source = self.parent_if_not_updating()?;
+
+        // If the HTMLMediaElement's error attribute is not null, then throw an
+        // InvalidStateError exception.
+        if media_source
+            .media_element()
+            .is_some_and(|media_element| media_element.GetError().is_some())
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // If the readyState attribute of the parent media source is in the "ended" state
+        // then set it to "open" and queue a task to fire sourceopen.
+        media_source.reopen_if_ended();
+
+        // Run the coded frame eviction algorithm.
+        // The media player owns the coded frames, so only the data that waits to be
+        // pushed to it counts towards the buffer full flag.
+
+        // If the buffer full flag equals true, then throw a QuotaExceededError exception.
+        if media_source.is_buffer_full() {
+            return Err(Error::QuotaExceeded);
+        }
+        Ok(media_source)
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-append>
+    fn buffer_append(&self, bytes: Vec<u8>, can_gc: CanGc) {
+        // Run the segment parser loop algorithm.
+        let segments = self.parser.borrow_mut().append(&bytes);
+        let result = segments.and_then(|segments| {
+            segments.into_iter().try_for_each(|segment| match segment {
+                Segment::Initialization(segment) => self.initialization_segment_received(segment),
+                Segment::Media(segment) => self.process_media_segment(segment),
+            })
+        });
+
+        // If the segment parser loop algorithm in the previous step was aborted, then
+        // abort this algorithm.
+        if let Err(error) = result {
+            warn!("Could not parse appended media data: {:?}", error);
+            self.append_error(can_gc);
+            return;
+        }
+
+        // Set the updating attribute to false.
+        // Queue a task to fire an event named update at this SourceBuffer object.
+        // Queue a task to fire an event named updateend at this SourceBuffer object.
+        self.updating.set(false);
+        self.queue_event("update");
+        self.queue_event("updateend");
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-append-error>
+    fn append_error(&self, can_gc: CanGc) {
+        // Run the reset parser state algorithm.
+        self.reset_parser_state();
+
+        // Set the updating attribute to false.
+        // Queue a task to fire an event named error at this SourceBuffer object.
+        // Queue a task to fire an event named updateend at this SourceBuffer object.
+        self.updating.set(false);
+        self.queue_event("error");
+        self.queue_event("updateend");
+
+        // Run the end of stream algorithm with the error parameter set to "decode".
+        if let Some(media_source) = self.parent_media_source.get() {
+            media_source.end_of_stream(Some(EndOfStreamError::Decode), can_gc);
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-reset-parser-state>
+    fn reset_parser_state(&self) {
+        // Unset the last decode timestamp on all track buffers.
+        self.last_segment_end.set(None);
+
+        // If the mode attribute equals "sequence", then set the group start timestamp to
+        // the group end timestamp.
+        if self.mode.get() == AppendMode::Sequence {
+            self.group_start_timestamp
+                .set(Some(self.group_end_timestamp.get()));
+        }
+
+        // Remove all bytes from the input buffer.
+        // Set append state to WAITING_FOR_SEGMENT.
+        self.parser.borrow_mut().reset();
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-abort-buffer-append>, if the
+    /// buffer append algorithm is running.
+    pub(crate) fn abort_buffer_append(&self) {
+        if !self.updating.get() || self.removing.get() {
+            return;
+        }
+
+        // Abort the buffer append algorithm if it is running.
+        self.append_generation
+            .set(self.append_generation.get().wrapping_add(1));
+
+        // Set the updating attribute to false.
+        // Queue a task to fire an event named abort at this SourceBuffer object.
+        // Queue a task to fire an event named updateend at this SourceBuffer object.
+        self.updating.set(false);
+        self.queue_event("abort");
+        self.queue_event("updateend");
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-init-segment-received>
+    fn initialization_segment_received(
+        &self,
+        mut segment: InitializationSegment,
+    ) -> Result<(), ParseError> {
+        let Some(media_source) = self.parent_media_source.get() else {
+            return Ok(());
+        };
+
+        // Update the duration attribute if it currently equals NaN.
+        media_source.set_initial_duration(segment.duration);
+
+        // If the initialization segment has no audio, video, or text tracks, then run
+        // the append error algorithm and abort these steps.
+        if segment
+            .tracks
+            .iter()
+            .all(|track| track.kind == TrackKind::Other)
+        {
+            return Err(ParseError::Malformed(
+                "initialization segment without audio, video or text tracks",
+            ));
+        }
+
+        // If the first initialization segment received flag is true, then verify that
+        // the number of audio, video, and text tracks match what was in the first
+        // initialization segment.
+        let first = !self.first_initialization_segment_received.get();
+        if !first && self.track_ids.borrow().len() != segment.tracks.len() {
+            return Err(ParseError::Malformed(
+                "initialization segment with different tracks",
+            ));
+        }
+        *self.track_ids.borrow_mut() = segment.tracks.iter().map(|track| track.id).collect();
+
+        renumber_tracks(self.format, &mut segment.bytes, &|track_id| {
+            self.map_track_id(track_id)
+        })?;
+        *self.initialization_segment.borrow_mut() = Some(segment.bytes);
+
+        // If the first initialization segment received flag is false, add this
+        // SourceBuffer to activeSourceBuffers if it has an audio or video track, and set
+        // the first initialization segment received flag to true.
+        if first {
+            self.first_initialization_segment_received.set(true);
+            if segment
+                .tracks
+                .iter()
+                .any(|track| matches!(track.kind, TrackKind::Audio | TrackKind::Video))
+            {
+                media_source.activate_source_buffer(self);
+            }
+        }
+
+        media_source.initialization_segment_received();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-coded-frame-processing>
+    fn process_media_segment(&self, mut segment: MediaSegment) -> Result<(), ParseError> {
+        let Some(media_source) = self.parent_media_source.get() else {
+            return Ok(());
+        };
+        if !self.first_initialization_segment_received.get() {
+            return Err(ParseError::MissingInitializationSegment);
+        }
+
+        if self.mode.get() == AppendMode::Sequence {
+            // A gap from the last media segment starts a new coded frame group, at the
+            // group end timestamp.
+            let start = segment.start + self.timestamp_offset.get();
+            if self
+                .last_segment_end
+                .get()
+                .is_some_and(|last_end| (start - last_end).abs() > DISCONTINUITY_TOLERANCE)
+            {
+                self.group_start_timestamp
+                    .set(Some(self.group_end_timestamp.get()));
+            }
+
+            // If mode equals "sequence" and group start timestamp is set, then set
+            // timestampOffset equal to group start timestamp - presentation timestamp,
+            // and unset group start timestamp.
+            if let Some(group_start_timestamp) = self.group_start_timestamp.take() {
+                self.timestamp_offset
+                    .set(group_start_timestamp - segment.start);
+            }
+        }
+
+        // If timestampOffset is not 0, add it to the timestamps of the coded frames.
+        self.parser
+            .borrow()
+            .apply_timestamp_offset(&mut segment, self.timestamp_offset.get())?;
+        self.last_segment_end.set(Some(segment.end));
+
+        // Coded frames outside of the append window are dropped. Segments that are only
+        // partly in it are kept whole, but only count as buffered within it.
+        let start = segment.start.max(self.append_window_start.get());
+        let end = segment.end.min(self.append_window_end.get());
+        if end <= start {
+            return Ok(());
+        }
+
+        renumber_tracks(self.format, &mut segment.bytes, &|track_id| {
+            self.map_track_id(track_id)
+        })?;
+        let _ = self.buffered.borrow_mut().add(start, end);
+        self.group_end_timestamp
+            .set(self.group_end_timestamp.get().max(segment.end));
+        media_source.queue_media_segment(segment.bytes);
+
+        // If the media segment contains data beyond the current duration, then run the
+        // duration change algorithm with new duration set to the maximum of the current
+        // duration and the group end timestamp.
+        media_source.extend_duration(self.group_end_timestamp.get());
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-range-removal>
+    fn range_removal(&self, start: f64, end: f64) {
+        // Set the updating attribute to true.
+        // Queue a task to fire an event named updatestart at this SourceBuffer object.
+        self.updating.set(true);
+        self.removing.set(true);
+        self.queue_event("updatestart");
+
+        // Return control to the caller and run the rest of the steps asynchronously.
+        let this = Trusted::new(self);
+        let generation = self.append_generation.get();
+        self.global()
+            .task_manager()
+            .media_element_task_source()
+            .queue(task!(source_buffer_range_removal: move || {
+                let this = this.root();
+                if this.append_generation.get() != generation {
+                    return;
+                }
+
+                // Run the coded frame removal algorithm with start and end as the start
+                // and end of the removal range. The media player keeps the coded frames
+                // it was given, so they only stop counting as buffered.
+                this.buffered.borrow_mut().remove(start, end);
+
+                // Set the updating attribute to false.
+                // Queue a task to fire an event named update at this SourceBuffer object.
+                // Queue a task to fire an event named updateend at this SourceBuffer
+                // object.
+                this.updating.set(false);
+                this.removing.set(false);
+                this.queue_event("update");
+                this.queue_event("updateend");
+            }));
+    }
+}
+
+impl SourceBufferMethods<crate::DomTypeHolder> for SourceBuffer {
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-mode>
+    fn Mode(&self) -> AppendMode {
+        self.mode.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-mode>
+    fn SetMode(&self, value: AppendMode) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source, then throw an InvalidStateError exception.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception.
+        let media_source = self.parent_if_not_updating()?;
+
+        // If the readyState attribute of the parent media source is in the "ended" state
+        // then set it to "open" and queue a task to fire sourceopen.
+        media_source.reopen_if_ended();
+
+        // If the append state equals PARSING_MEDIA_SEGMENT, then throw an
+        // InvalidStateError.
+        if self.parser.borrow().is_parsing_media_segment() {
+            return Err(Error::InvalidState);
+        }
+
+        // If the new mode equals "sequence", then set the group start timestamp to the
+        // group end timestamp.
+        if value == AppendMode::Sequence {
+            self.group_start_timestamp
+                .set(Some(self.group_end_timestamp.get()));
+        }
+
+        // Update the attribute to new mode.
+        self.mode.set(value);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-updating>
+    fn Updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-buffered>
+    fn Buffered(&self, can_gc: CanGc) -> Fallible<DomRoot<TimeRanges>> {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source then throw an InvalidStateError exception.
+        if self.parent_media_source.get().is_none() {
+            return Err(Error::InvalidState);
+        }
+        Ok(TimeRanges::new(
+            self.global().as_window(),
+            self.buffered_ranges(),
+            can_gc,
+        ))
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset>
+    fn TimestampOffset(&self) -> Finite<f64> {
+        Finite::wrap(self.timestamp_offset.get())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset>
+    fn SetTimestampOffset(&self, value: Finite<f64>) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source, then throw an InvalidStateError exception.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception.
+        let media_source = self.parent_if_not_updating()?;
+
+        // If the readyState attribute of the parent media source is in the "ended" state
+        // then set it to "open" and queue a task to fire sourceopen.
+        media_source.reopen_if_ended();
+
+        // If the append state equals PARSING_MEDIA_SEGMENT, then throw an
+        // InvalidStateError.
+        if self.parser.borrow().is_parsing_media_segment() {
+            return Err(Error::InvalidState);
+        }
+
+        // Update the attribute to new timestamp offset.
+        // If the mode attribute equals "sequence", then set the group start timestamp to
+        // new timestamp offset.
+        self.timestamp_offset.set(*value);
+        if self.mode.get() == AppendMode::Sequence {
+            self.group_start_timestamp.set(Some(*value));
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart>
+    fn AppendWindowStart(&self) -> Finite<f64> {
+        Finite::wrap(self.append_window_start.get())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart>
+    fn SetAppendWindowStart(&self, value: Finite<f64>) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source, then throw an InvalidStateError exception.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception.
+        self.parent_if_not_updating()?;
+
+        // If the new value is less than 0 or greater than or equal to appendWindowEnd
+        // then throw a TypeError exception.
+        if *value < 0. || *value >= self.append_window_end.get() {
+            return Err(Error::Type("invalid append window start".into()));
+        }
+
+        // Update the attribute to the new value.
+        self.append_window_start.set(*value);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend>
+    fn AppendWindowEnd(&self) -> f64 {
+        self.append_window_end.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend>
+    fn SetAppendWindowEnd(&self, value: f64) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source, then throw an InvalidStateError exception.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception.
+        self.parent_if_not_updating()?;
+
+        // If the new value equals NaN, then throw a TypeError.
+        // If the new value is less than or equal to appendWindowStart then throw a
+        // TypeError exception.
+        if value.is_nan() || value <= self.append_window_start.get() {
+            return Err(Error::Type("invalid append window end".into()));
+        }
+
+        // Update the attribute to the new value.
+        self.append_window_end.set(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdatestart
+    event_handler!(updatestart, GetOnupdatestart, SetOnupdatestart);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdate
+    event_handler!(update, GetOnupdate, SetOnupdate);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdateend
+    event_handler!(updateend, GetOnupdateend, SetOnupdateend);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendbuffer>
+    fn AppendBuffer(&self, data: ArrayBufferViewOrArrayBuffer) -> ErrorResult {
+        // Run the prepare append algorithm.
+        self.prepare_append()?;
+
+        // Add data to the end of the input buffer.
+        let bytes = match data {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.to_vec(),
+        };
+
+        // Set the updating attribute to true.
+        // Queue a task to fire an event named updatestart at this SourceBuffer object.
+        self.updating.set(true);
+        self.queue_event("updatestart");
+
+        // Asynchronously run the buffer append algorithm.
+        let this = Trusted::new(self);
+        let generation = self.append_generation.get();
+        self.global()
+            .task_manager()
+            .media_element_task_source()
+            .queue(task!(source_buffer_append: move || {
+                let this = this.root();
+                if this.append_generation.get() != generation {
+                    return;
+                }
+                this.buffer_append(bytes, CanGc::note());
+            }));
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-abort>
+    fn Abort(&self) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source then throw an InvalidStateError exception and abort these steps.
+        // If the readyState attribute of the parent media source is not in the "open"
+        // state then throw an InvalidStateError exception and abort these steps.
+        let media_source = self.parent_media_source.get().ok_or(Error::InvalidState)?;
+        if media_source.ready_state() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        // If the range removal algorithm is running, then throw an InvalidStateError
+        // exception and abort these steps.
+        if self.removing.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // If the sourceBuffer.updating attribute equals true, then run the abort buffer
+        // append algorithm.
+        self.abort_buffer_append();
+
+        // Run the reset parser state algorithm.
+        self.reset_parser_state();
+
+        // Set appendWindowStart to the presentation start time.
+        // Set appendWindowEnd to positive Infinity.
+        self.append_window_start.set(0.);
+        self.append_window_end.set(f64::INFINITY);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-changetype>
+    fn ChangeType(&self, type_: DOMString) -> ErrorResult {
+        // If type is an empty string then throw a TypeError exception and abort these
+        // steps.
+        if type_.is_empty() {
+            return Err(Error::Type("type must not be empty".into()));
+        }
+
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source, then throw an InvalidStateError exception and abort these steps.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception and abort these steps.
+        let media_source = self.parent_if_not_updating()?;
+
+        // If type contains a MIME type that is not supported or contains a MIME type
+        // that is not supported with the types specified for the other SourceBuffer
+        // objects in the sourceBuffers attribute of the parent media source, then throw
+        // a NotSupportedError exception and abort these steps.
+        //
+        // The segments of all source buffers share a byte stream format once muxed, so
+        // only the codecs can change.
+        if !MediaSource::IsTypeSupported(self.global().as_window(), type_.clone()) ||
+            ByteStreamFormat::for_mime_type(&type_) != Some(self.format)
+        {
+            return Err(Error::NotSupported);
+        }
+
+        // If the readyState attribute of the parent media source is in the "ended" state
+        // then set it to "open" and queue a task to fire sourceopen.
+        media_source.reopen_if_ended();
+
+        // Run the reset parser state algorithm.
+        self.reset_parser_state();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-remove>
+    fn Remove(&self, start: Finite<f64>, end: f64) -> ErrorResult {
+        // If this object has been removed from the sourceBuffers attribute of the parent
+        // media source then throw an InvalidStateError exception and abort these steps.
+        // If the updating attribute equals true, then throw an InvalidStateError
+        // exception and abort these steps.
+        let media_source = self.parent_if_not_updating()?;
+
+        // If duration equals NaN, then throw a TypeError exception and abort these steps.
+        // If start is negative or greater than duration, then throw a TypeError exception
+        // and abort these steps.
+        // If end is less than or equal to start or end equals NaN, then throw a TypeError
+        // exception and abort these steps.
+        let duration = media_source.duration();
+        if duration.is_nan() || *start < 0. || *start > duration || end.is_nan() || end <= *start {
+            return Err(Error::Type("invalid removal range".into()));
+        }
+
+        // If the readyState attribute of the parent media source is in the "ended" state
+        // then set it to "open" and queue a task to fire sourceopen.
+        media_source.reopen_if_ended();
+
+        // Run the range removal algorithm with start and end as the start and end of the
+        // removal range.
+        self.range_removal(*start, end);
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SourceBufferListBinding::SourceBufferListMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::sourcebuffer::SourceBuffer;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/media-source/#sourcebufferlist>
+#[dom_struct]
+pub(crate) struct SourceBufferList {
+    eventtarget: EventTarget,
+    source_buffers: DomRefCell<Vec<Dom<SourceBuffer>>>,
+}
+
+impl SourceBufferList {
+    fn new_inherited() -> SourceBufferList {
+        SourceBufferList {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<SourceBufferList> {
+        reflect_dom_object(Box::new(SourceBufferList::new_inherited()), window, can_gc)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.source_buffers.borrow().len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.source_buffers.borrow().is_empty()
+    }
+
+    pub(crate) fn contains(&self, source_buffer: &SourceBuffer) -> bool {
+        self.source_buffers
+            .borrow()
+            .iter()
+            .any(|buffer| &**buffer == source_buffer)
+    }
+
+    /// A snapshot of the source buffers of this list, in order.
+    pub(crate) fn source_buffers(&self) -> Vec<DomRoot<SourceBuffer>> {
+        self.source_buffers
+            .borrow()
+            .iter()
+            .map(|buffer| DomRoot::from_ref(&**buffer))
+            .collect()
+    }
+
+    /// Appends a source buffer and queues a task to fire an `addsourcebuffer` event.
+    pub(crate) fn add(&self, source_buffer: &SourceBuffer) {
+        self.source_buffers
+            .borrow_mut()
+            .push(Dom::from_ref(source_buffer));
+        self.queue_event(Atom::from("addsourcebuffer"));
+    }
+
+    /// Removes a source buffer and queues a task to fire a `removesourcebuffer` event, if
+    /// this list contains it.
+    pub(crate) fn remove(&self, source_buffer: &SourceBuffer) {
+        let mut source_buffers = self.source_buffers.borrow_mut();
+        let Some(index) = source_buffers
+            .iter()
+            .position(|buffer| &**buffer == source_buffer)
+        else {
+            return;
+        };
+        source_buffers.remove(index);
+        drop(source_buffers);
+        self.queue_event(Atom::from("removesourcebuffer"));
+    }
+
+    /// Removes all source buffers, queueing a single `removesourcebuffer` event.
+    pub(crate) fn clear(&self) {
+        if self.is_empty() {
+            return;
+        }
+        self.source_buffers.borrow_mut().clear();
+        self.queue_event(Atom::from("removesourcebuffer"));
+    }
+
+    fn queue_event(&self, name: Atom) {
+        self.global()
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name);
+    }
+}
+
+impl SourceBufferListMethods<crate::DomTypeHolder> for SourceBufferList {
+    /// <https://w3c.github.io/media-source/#dom-sourcebufferlist-length>
+    fn Length(&self) -> u32 {
+        self.len() as u32
+    }
+
+    /// <https://w3c.github.io/media-source/#dfn-sourcebufferlist-getter>
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<SourceBuffer>> {
+        self.source_buffers
+            .borrow()
+            .get(index as usize)
+            .map(|buffer| DomRoot::from_ref(&**buffer))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onaddsourcebuffer
+    event_handler!(addsourcebuffer, GetOnaddsourcebuffer, SetOnaddsourcebuffer);
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onremovesourcebuffer
+    event_handler!(
+        removesourcebuffer,
+        GetOnremovesourcebuffer,
+        SetOnremovesourcebuffer
+    );
+}
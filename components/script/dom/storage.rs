@@ -7,11 +7,12 @@ use constellation_traits::ScriptToConstellationMessage;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use net_traits::IpcSend;
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::storage_thread::{StorageKey, StorageThreadMsg, StorageType};
 use profile_traits::ipc;
 use servo_url::ServoUrl;
 
 use crate::dom::bindings::codegen::Bindings::StorageBinding::StorageMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
@@ -58,6 +59,13 @@ impl Storage {
         self.global().get_url()
     }
 
+    fn storage_key(&self) -> StorageKey {
+        StorageKey {
+            url: self.get_url(),
+            top_level_origin: self.global().as_window().Document().top_level_origin(),
+        }
+    }
+
     fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
         self.global().resource_threads().sender()
     }
@@ -73,7 +81,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
                 sender,
                 self.storage_type,
                 self.webview_id(),
-                self.get_url(),
+                self.storage_key(),
             ))
             .unwrap();
         receiver.recv().unwrap() as u32
@@ -88,7 +96,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
                 sender,
                 self.storage_type,
                 self.webview_id(),
-                self.get_url(),
+                self.storage_key(),
                 index,
             ))
             .unwrap();
@@ -104,7 +112,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
             sender,
             self.storage_type,
             self.webview_id(),
-            self.get_url(),
+            self.storage_key(),
             name,
         );
         self.get_storage_thread().send(msg).unwrap();
@@ -121,7 +129,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
             sender,
             self.storage_type,
             self.webview_id(),
-            self.get_url(),
+            self.storage_key(),
             name.clone(),
             value.clone(),
         );
@@ -146,7 +154,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
             sender,
             self.storage_type,
             self.webview_id(),
-            self.get_url(),
+            self.storage_key(),
             name.clone(),
         );
         self.get_storage_thread().send(msg).unwrap();
@@ -164,7 +172,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
                 sender,
                 self.storage_type,
                 self.webview_id(),
-                self.get_url(),
+                self.storage_key(),
             ))
             .unwrap();
         if receiver.recv().unwrap() {
@@ -181,7 +189,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
                 sender,
                 self.storage_type,
                 self.webview_id(),
-                self.get_url(),
+                self.storage_key(),
             ))
             .unwrap();
         receiver
@@ -0,0 +1,208 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::IpcSend;
+use net_traits::filemanager_thread::{
+    FileSystemEntry, FileSystemHandleKind, FileSystemLocator, FileSystemRoot,
+};
+use net_traits::storage_thread::{self, StorageThreadMsg};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use servo_url::ImmutableOrigin;
+
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionStatus_Binding::PermissionStatusMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionDescriptor, PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::{
+    StorageEstimate, StorageManagerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemdirectoryhandle::FileSystemDirectoryHandle;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::Permissions;
+use crate::dom::permissionstatus::PermissionStatus;
+use crate::dom::promise::Promise;
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
+use crate::script_runtime::CanGc;
+
+/// <https://storage.spec.whatwg.org/#storagemanager>
+///
+/// The usage, quota and persistence of the storage of origins are kept track of by the
+/// storage thread.
+#[dom_struct]
+pub(crate) struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub(crate) fn new(global: &GlobalScope, can_gc: CanGc) -> DomRoot<StorageManager> {
+        reflect_dom_object(Box::new(StorageManager::new_inherited()), global, can_gc)
+    }
+
+    /// Sends a message to the storage thread, whose reply settles the promise.
+    fn send_to_storage_thread<R>(
+        &self,
+        promise: &Rc<Promise>,
+        message: impl FnOnce(IpcSender<R>, ImmutableOrigin) -> StorageThreadMsg,
+        can_gc: CanGc,
+    ) where
+        R: Serialize + DeserializeOwned + Send + 'static,
+        StorageManager: RoutedPromiseListener<R>,
+    {
+        let global = self.global();
+
+        // Let shelf be the result of running obtain a local storage shelf with this's
+        // relevant settings object. If shelf is failure, then reject promise with a
+        // TypeError.
+        let Some(origin) = local_storage_shelf_origin(&global) else {
+            promise.reject_error(
+                Error::Type("The origin of this context cannot store data".to_owned()),
+                can_gc,
+            );
+            return;
+        };
+
+        let sender = route_promise(
+            promise,
+            self,
+            global.task_manager().dom_manipulation_task_source(),
+        );
+        if global
+            .resource_threads()
+            .send(message(sender, origin))
+            .is_err()
+        {
+            promise.reject_error(Error::Operation, can_gc);
+        }
+    }
+}
+
+impl StorageManagerMethods<crate::DomTypeHolder> for StorageManager {
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persisted>
+    fn Persisted(&self, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 5. Otherwise, run these steps in parallel:
+        // Step 5.1. Let persisted be true if shelf's bucket map["default"]'s mode is
+        // "persistent"; otherwise false.
+        // Step 5.3. Queue a storage task with global to resolve promise with persisted.
+        self.send_to_storage_thread(&promise, StorageThreadMsg::Persisted, can_gc);
+        promise
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persist>
+    fn Persist(&self, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Step 5.1. Let permission be the result of requesting permission to use
+        // "persistent-storage".
+        // Step 5.4. If permission is "granted", then set bucket's mode to "persistent".
+        // Step 5.5. Let persisted be true if bucket's mode is "persistent"; otherwise false.
+        // Step 5.6. Queue a storage task with global to resolve promise with persisted.
+        if request_persistent_storage_permission(&global, can_gc) == PermissionState::Granted {
+            self.send_to_storage_thread(&promise, StorageThreadMsg::Persist, can_gc);
+        } else {
+            self.send_to_storage_thread(&promise, StorageThreadMsg::Persisted, can_gc);
+        }
+        promise
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-estimate>
+    fn Estimate(&self, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 5. Otherwise, run these steps in parallel:
+        // Step 5.1. Let usage be storage usage for shelf.
+        // Step 5.2. Let quota be storage quota for shelf.
+        // Step 5.5. Queue a storage task with global to resolve promise with dictionary.
+        self.send_to_storage_thread(&promise, StorageThreadMsg::Estimate, can_gc);
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-storagemanager-getdirectory>
+    fn GetDirectory(&self, can_gc: CanGc) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global, can_gc);
+
+        // Let map be the result of running obtain a local storage bottle map with
+        // environment and "fileSystem". If map is failure, then reject p with a
+        // SecurityError.
+        if local_storage_shelf_origin(&global).is_none() {
+            promise.reject_error(Error::Security, can_gc);
+            return promise;
+        }
+
+        // Let root be a directory entry whose name is the empty string, which is stored in
+        // map["root"], and resolve p with a new FileSystemDirectoryHandle for it.
+        let root = FileSystemEntry {
+            locator: FileSystemLocator {
+                root: FileSystemRoot::OriginPrivate,
+                path: vec![],
+            },
+            kind: FileSystemHandleKind::Directory,
+            name: String::new(),
+        };
+        let handle = FileSystemDirectoryHandle::new(&global, root, can_gc);
+        promise.resolve_native(&handle, can_gc);
+        promise
+    }
+}
+
+impl RoutedPromiseListener<bool> for StorageManager {
+    fn handle_response(&self, persisted: bool, promise: &Rc<Promise>, can_gc: CanGc) {
+        promise.resolve_native(&persisted, can_gc);
+    }
+}
+
+impl RoutedPromiseListener<storage_thread::StorageEstimate> for StorageManager {
+    fn handle_response(
+        &self,
+        estimate: storage_thread::StorageEstimate,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        // Step 5.3. Let dictionary be a new StorageEstimate dictionary whose usage member
+        // is usage and quota member is quota.
+        let dictionary = StorageEstimate {
+            usage: Some(estimate.usage),
+            quota: Some(estimate.quota),
+        };
+        promise.resolve_native(&dictionary, can_gc);
+    }
+}
+
+/// <https://storage.spec.whatwg.org/#obtain-a-local-storage-shelf>, which fails for
+/// contexts with an opaque origin. Servo keeps a single shelf per origin, so this returns
+/// the origin of the shelf.
+pub(crate) fn local_storage_shelf_origin(global: &GlobalScope) -> Option<ImmutableOrigin> {
+    let origin = global.origin().immutable();
+    origin.is_tuple().then(|| origin.clone())
+}
+
+/// <https://w3c.github.io/permissions/#dfn-request-permission-to-use> for
+/// "persistent-storage".
+fn request_persistent_storage_permission(global: &GlobalScope, can_gc: CanGc) -> PermissionState {
+    let cx = GlobalScope::get_cx();
+    let promise = &Promise::new(global, can_gc);
+    let descriptor = PermissionDescriptor {
+        name: PermissionName::Persistent_storage,
+    };
+    let status = PermissionStatus::new(global, &descriptor, can_gc);
+    Permissions::permission_request(cx, promise, &descriptor, &status);
+    status.State()
+}
@@ -0,0 +1,234 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use stylo_atoms::Atom;
+
+use crate::dom::abortsignal::AbortSignal;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::TaskPriority;
+use crate::dom::bindings::codegen::Bindings::TaskSignalBinding::{
+    TaskSignalAnyInit, TaskSignalMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::TaskPriorityOrTaskSignal;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::scheduler::Scheduler;
+use crate::dom::taskprioritychangeevent::TaskPriorityChangeEvent;
+use crate::script_runtime::CanGc;
+
+/// <https://wicg.github.io/scheduling-apis/#tasksignal>
+#[dom_struct]
+pub(crate) struct TaskSignal {
+    abortsignal: AbortSignal,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-priority>
+    priority: Cell<TaskPriority>,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-priority-changing>
+    priority_changing: Cell<bool>,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-priority-change-algorithms>
+    /// The only algorithm is the one updating the priority of the tasks a [`Scheduler`]
+    /// has queued with this signal, so the schedulers are stored instead.
+    priority_change_algorithms: DomRefCell<Vec<Dom<Scheduler>>>,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-source-signal>
+    source_signal: MutNullableDom<TaskSignal>,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-dependent-signals>
+    dependent_signals: DomRefCell<Vec<Dom<TaskSignal>>>,
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-dependent>
+    dependent: Cell<bool>,
+}
+
+impl TaskSignal {
+    fn new_inherited(priority: TaskPriority) -> TaskSignal {
+        TaskSignal {
+            abortsignal: AbortSignal::new_inherited(),
+            priority: Cell::new(priority),
+            priority_changing: Default::default(),
+            priority_change_algorithms: Default::default(),
+            source_signal: Default::default(),
+            dependent_signals: Default::default(),
+            dependent: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        priority: TaskPriority,
+        can_gc: CanGc,
+    ) -> DomRoot<TaskSignal> {
+        reflect_dom_object(
+            Box::new(TaskSignal::new_inherited(priority)),
+            global,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn priority(&self) -> TaskPriority {
+        self.priority.get()
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-has-fixed-priority>
+    fn has_fixed_priority(&self) -> bool {
+        // A TaskSignal has fixed priority if it is a dependent signal with a null source signal.
+        self.dependent.get() && self.source_signal.get().is_none()
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-add-a-priority-change-algorithm>
+    pub(crate) fn add_priority_change_algorithm(&self, scheduler: &Scheduler) {
+        let mut algorithms = self.priority_change_algorithms.borrow_mut();
+        if !algorithms.iter().any(|algorithm| *algorithm == scheduler) {
+            algorithms.push(Dom::from_ref(scheduler));
+        }
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#tasksignal-signal-priority-change>
+    pub(crate) fn signal_priority_change(
+        &self,
+        priority: TaskPriority,
+        can_gc: CanGc,
+    ) -> ErrorResult {
+        // If signal’s priority changing is true, then throw a "NotAllowedError" DOMException.
+        if self.priority_changing.get() {
+            return Err(Error::NotAllowed);
+        }
+
+        // If signal’s priority equals priority then return.
+        if self.priority.get() == priority {
+            return Ok(());
+        }
+
+        // Set signal’s priority changing to true.
+        self.priority_changing.set(true);
+
+        // Let previousPriority be signal’s priority.
+        let previous_priority = self.priority.get();
+
+        // Set signal’s priority to priority.
+        self.priority.set(priority);
+
+        // For each algorithm of signal’s priority change algorithms, run algorithm.
+        let schedulers: Vec<_> = self
+            .priority_change_algorithms
+            .borrow()
+            .iter()
+            .map(|scheduler| scheduler.as_rooted())
+            .collect();
+        for scheduler in schedulers {
+            scheduler.task_signal_priority_changed(self);
+        }
+
+        // Fire an event named prioritychange at signal using TaskPriorityChangeEvent,
+        // with its previousPriority attribute initialized to previousPriority.
+        let event = TaskPriorityChangeEvent::new(
+            &self.global(),
+            Atom::from("prioritychange"),
+            false,
+            false,
+            previous_priority,
+            can_gc,
+        );
+        event
+            .upcast::<Event>()
+            .fire(self.upcast::<EventTarget>(), can_gc);
+
+        // For each dependentSignal of signal’s dependent signals,
+        // signal priority change on dependentSignal with priority.
+        let dependent_signals: Vec<_> = self
+            .dependent_signals
+            .borrow()
+            .iter()
+            .map(|signal| signal.as_rooted())
+            .collect();
+        for dependent_signal in dependent_signals {
+            let _ = dependent_signal.signal_priority_change(priority, can_gc);
+        }
+
+        // Set signal’s priority changing to false.
+        self.priority_changing.set(false);
+        Ok(())
+    }
+}
+
+impl TaskSignalMethods<crate::DomTypeHolder> for TaskSignal {
+    /// <https://wicg.github.io/scheduling-apis/#dom-tasksignal-any>
+    fn Any(
+        global: &GlobalScope,
+        signals: Vec<DomRoot<AbortSignal>>,
+        init: &TaskSignalAnyInit,
+        can_gc: CanGc,
+    ) -> DomRoot<TaskSignal> {
+        // Let resultSignal be the result of creating a dependent signal
+        // from signals using the TaskSignal interface and the current realm.
+        let result_signal = TaskSignal::new(global, TaskPriority::User_visible, can_gc);
+        result_signal
+            .upcast::<AbortSignal>()
+            .make_dependent_on(&signals);
+
+        // Set resultSignal’s dependent to true.
+        result_signal.dependent.set(true);
+
+        match &init.priority {
+            // If init["priority"] is a TaskPriority, then:
+            // Set resultSignal’s priority to init["priority"].
+            TaskPriorityOrTaskSignal::TaskPriority(priority) => {
+                result_signal.priority.set(*priority);
+            },
+            // Otherwise:
+            TaskPriorityOrTaskSignal::TaskSignal(source_signal) => {
+                // Set resultSignal’s priority to init["priority"]’s priority.
+                result_signal.priority.set(source_signal.priority());
+
+                // If sourceSignal does not have fixed priority, then:
+                if !source_signal.has_fixed_priority() {
+                    // If sourceSignal’s dependent is true, then set sourceSignal to
+                    // sourceSignal’s source signal.
+                    let source_signal = if source_signal.dependent.get() {
+                        source_signal
+                            .source_signal
+                            .get()
+                            .expect("A dependent signal without fixed priority has a source")
+                    } else {
+                        source_signal.clone()
+                    };
+
+                    // Assert: sourceSignal is not dependent.
+                    assert!(!source_signal.dependent.get());
+
+                    // Set resultSignal’s source signal to a weak reference to sourceSignal.
+                    result_signal.source_signal.set(Some(&source_signal));
+
+                    // Append resultSignal to sourceSignal’s dependent signals.
+                    source_signal
+                        .dependent_signals
+                        .borrow_mut()
+                        .push(Dom::from_ref(&*result_signal));
+                }
+            },
+        }
+
+        // Return resultSignal.
+        result_signal
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#dom-tasksignal-priority>
+    fn Priority(&self) -> TaskPriority {
+        // The priority getter steps are to return this’s priority.
+        self.priority.get()
+    }
+
+    // <https://wicg.github.io/scheduling-apis/#dom-tasksignal-onprioritychange>
+    event_handler!(prioritychange, GetOnprioritychange, SetOnprioritychange);
+}
@@ -124,6 +124,46 @@ impl TimeRangesContainer {
 
         Ok(())
     }
+
+    /// Removes the interval from `start` to `end` from these ranges, splitting the range
+    /// that contains it if needed.
+    pub(crate) fn remove(&mut self, start: f64, end: f64) {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        for range in self.ranges.drain(..) {
+            if range.end <= start || range.start >= end {
+                ranges.push(range);
+                continue;
+            }
+            if range.start < start {
+                ranges.push(TimeRange {
+                    start: range.start,
+                    end: start,
+                });
+            }
+            if range.end > end {
+                ranges.push(TimeRange {
+                    start: end,
+                    end: range.end,
+                });
+            }
+        }
+        self.ranges = ranges;
+    }
+
+    /// The ranges that are in both `self` and `other`.
+    pub(crate) fn intersection(&self, other: &TimeRangesContainer) -> TimeRangesContainer {
+        let mut ranges = vec![];
+        for range in &self.ranges {
+            for other_range in &other.ranges {
+                let start = f64::max(range.start, other_range.start);
+                let end = f64::min(range.end, other_range.end);
+                if start < end {
+                    ranges.push(TimeRange { start, end });
+                }
+            }
+        }
+        TimeRangesContainer { ranges }
+    }
 }
 
 #[dom_struct]
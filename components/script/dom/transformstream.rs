@@ -36,6 +36,7 @@ use crate::dom::countqueuingstrategy::{extract_high_water_mark, extract_size_alg
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ReadableStream, create_readable_stream};
+use crate::dom::transformstreamdefaultcontroller::NativeTransformer;
 use crate::dom::types::PromiseNativeHandler;
 use crate::dom::underlyingsourcecontainer::UnderlyingSourceType;
 use crate::dom::writablestream::create_writable_stream;
@@ -536,6 +537,52 @@ impl TransformStream {
         self.backpressure.set(backpressure);
     }
 
+    /// <https://streams.spec.whatwg.org/#transformstream-set-up>
+    pub(crate) fn set_up(
+        &self,
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        transformer: NativeTransformer,
+        can_gc: CanGc,
+    ) -> Fallible<()> {
+        // Let writableHighWaterMark be 1.
+        // Let writableSizeAlgorithm be an algorithm that returns 1.
+        let writable_size_algorithm = extract_size_algorithm(&QueuingStrategy::empty(), can_gc);
+
+        // Let readableHighWaterMark be 0.
+        // Let readableSizeAlgorithm be an algorithm that returns 1.
+        let readable_size_algorithm = extract_size_algorithm(&QueuingStrategy::empty(), can_gc);
+
+        // Let transformAlgorithmWrapper, flushAlgorithmWrapper and cancelAlgorithmWrapper be
+        // the algorithms of transformer.
+        // Note: they are run by the controller, see `NativeTransformer`.
+
+        // Let startPromise be a promise resolved with undefined.
+        let start_promise = Promise::new_resolved(global, cx, (), can_gc);
+
+        // Perform ! InitializeTransformStream(stream, startPromise, writableHighWaterMark,
+        // writableSizeAlgorithm, readableHighWaterMark, readableSizeAlgorithm).
+        self.initialize(
+            cx,
+            global,
+            start_promise,
+            1.0,
+            writable_size_algorithm,
+            0.0,
+            readable_size_algorithm,
+            can_gc,
+        )?;
+
+        // Let controller be a new TransformStreamDefaultController.
+        let controller = TransformStreamDefaultController::new_native(global, transformer, can_gc);
+
+        // Perform ! SetUpTransformStreamDefaultController(stream, controller,
+        // transformAlgorithmWrapper, flushAlgorithmWrapper, cancelAlgorithmWrapper).
+        self.set_up_transform_stream_default_controller(&controller);
+
+        Ok(())
+    }
+
     /// <https://streams.spec.whatwg.org/#set-up-transform-stream-default-controller>
     fn set_up_transform_stream_default_controller(
         &self,
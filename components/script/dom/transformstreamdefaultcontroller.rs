@@ -22,6 +22,7 @@ use crate::dom::bindings::codegen::Bindings::TransformStreamDefaultControllerBin
 use crate::dom::bindings::error::{Error, ErrorToJsval, Fallible};
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::compressionstream::CompressionTransformer;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
@@ -51,6 +52,16 @@ impl Callback for TransformTransformPromiseRejection {
     }
 }
 
+/// The algorithms of a transform stream that is set up by another interface, rather than
+/// constructed from a JS transformer.
+///
+/// <https://streams.spec.whatwg.org/#transformstream-set-up>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub(crate) enum NativeTransformer {
+    /// The transformer of a `CompressionStream` or a `DecompressionStream`.
+    Compression(CompressionTransformer),
+}
+
 /// <https://streams.spec.whatwg.org/#transformstreamdefaultcontroller>
 #[dom_struct]
 pub struct TransformStreamDefaultController {
@@ -72,6 +83,10 @@ pub struct TransformStreamDefaultController {
     #[ignore_malloc_size_of = "mozjs"]
     transform_obj: Heap<*mut JSObject>,
 
+    /// The algorithms of a stream set up by another interface, which are used instead of the
+    /// callbacks of the transformer.
+    native_transformer: DomRefCell<Option<NativeTransformer>>,
+
     /// <https://streams.spec.whatwg.org/#TransformStreamDefaultController-stream>
     stream: MutNullableDom<TransformStream>,
 
@@ -91,6 +106,7 @@ impl TransformStreamDefaultController {
             finish_promise: DomRefCell::new(None),
             stream: MutNullableDom::new(None),
             transform_obj: Default::default(),
+            native_transformer: DomRefCell::new(None),
         }
     }
 
@@ -107,6 +123,18 @@ impl TransformStreamDefaultController {
         )
     }
 
+    /// A controller running the algorithms of `native_transformer`.
+    pub(crate) fn new_native(
+        global: &GlobalScope,
+        native_transformer: NativeTransformer,
+        can_gc: CanGc,
+    ) -> DomRoot<TransformStreamDefaultController> {
+        let controller =
+            TransformStreamDefaultController::new(global, &Transformer::empty(), can_gc);
+        *controller.native_transformer.borrow_mut() = Some(native_transformer);
+        controller
+    }
+
     /// Setting the JS object after the heap has settled down.
     pub(crate) fn set_transform_obj(&self, this_object: SafeHandleObject) {
         self.transform_obj.set(*this_object);
@@ -160,6 +188,11 @@ impl TransformStreamDefaultController {
         chunk: SafeHandleValue,
         can_gc: CanGc,
     ) -> Fallible<Rc<Promise>> {
+        let native_transformer = self.native_transformer.borrow().clone();
+        if let Some(NativeTransformer::Compression(transformer)) = native_transformer {
+            return Ok(transformer.transform(cx, global, self, chunk, can_gc));
+        }
+
         // If transformerDict["transform"] exists, set transformAlgorithm to an algorithm which
         // takes an argument chunk and returns the result of invoking transformerDict["transform"] with argument list
         // « chunk, controller » and callback this value transformer.
@@ -207,6 +240,12 @@ impl TransformStreamDefaultController {
         chunk: SafeHandleValue,
         can_gc: CanGc,
     ) -> Fallible<Rc<Promise>> {
+        // The interfaces setting up a stream do not give it a cancel algorithm, so it is an
+        // algorithm which returns a promise resolved with undefined.
+        if self.native_transformer.borrow().is_some() {
+            return Ok(Promise::new_resolved(global, cx, (), can_gc));
+        }
+
         // If transformerDict["cancel"] exists, set cancelAlgorithm to an algorithm which takes an argument
         // reason and returns the result of invoking transformerDict["cancel"] with argument list « reason »
         // and callback this value transformer.
@@ -241,6 +280,11 @@ impl TransformStreamDefaultController {
         global: &GlobalScope,
         can_gc: CanGc,
     ) -> Fallible<Rc<Promise>> {
+        let native_transformer = self.native_transformer.borrow().clone();
+        if let Some(NativeTransformer::Compression(transformer)) = native_transformer {
+            return Ok(transformer.flush(global, self, can_gc));
+        }
+
         // If transformerDict["flush"] exists, set flushAlgorithm to an algorithm which returns the result of
         // invoking transformerDict["flush"] with argument list « controller » and callback this value transformer.
         let algo = self.flush.borrow().clone();
@@ -357,6 +401,9 @@ impl TransformStreamDefaultController {
 
         // Set controller.[[cancelAlgorithm]] to undefined.
         self.cancel.replace(None);
+
+        // Also drop the algorithms of a native transformer, and the state they hold on to.
+        self.native_transformer.borrow_mut().take();
     }
 
     /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-terminate>
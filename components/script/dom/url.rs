@@ -15,14 +15,16 @@ use uuid::Uuid;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::URLBinding::URLMethods;
+use crate::dom::bindings::codegen::UnionTypes::BlobOrMediaSource;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
-use crate::dom::blob::Blob;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::urlhelper::UrlHelper;
 use crate::dom::urlsearchparams::URLSearchParams;
+use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
 /// <https://url.spec.whatwg.org/#url>
@@ -181,12 +183,25 @@ impl URLMethods<crate::DomTypeHolder> for URL {
     }
 
     /// <https://w3c.github.io/FileAPI/#dfn-createObjectURL>
-    fn CreateObjectURL(global: &GlobalScope, blob: &Blob) -> DOMString {
+    /// <https://w3c.github.io/media-source/#dom-url-createobjecturl>
+    fn CreateObjectURL(global: &GlobalScope, obj: BlobOrMediaSource) -> DOMString {
         // XXX: Second field is an unicode-serialized Origin, it is a temporary workaround
         //      and should not be trusted. See issue https://github.com/servo/servo/issues/11722
         let origin = get_blob_origin(&global.get_url());
 
-        let id = blob.get_blob_url_id();
+        let id = match obj {
+            BlobOrMediaSource::Blob(blob) => blob.get_blob_url_id(),
+            // Media source object URLs never reach the file manager, since the media
+            // element resolves them to their `MediaSource` directly.
+            BlobOrMediaSource::MediaSource(media_source) => {
+                let id = Uuid::new_v4();
+                global
+                    .downcast::<Window>()
+                    .expect("MediaSource is only exposed to Window")
+                    .add_media_source_url(id, &media_source);
+                id
+            },
+        };
 
         DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
     }
@@ -201,6 +216,13 @@ impl URLMethods<crate::DomTypeHolder> for URL {
         if let Ok(url) = ServoUrl::parse(&url) {
             if url.fragment().is_none() && origin == get_blob_origin(&url) {
                 if let Ok((id, _)) = parse_blob_url(&url) {
+                    if global
+                        .downcast::<Window>()
+                        .is_some_and(|window| window.revoke_media_source_url(&id))
+                    {
+                        return;
+                    }
+
                     let resource_threads = global.resource_threads();
                     let (tx, rx) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
                     let msg = FileManagerThreadMsg::RevokeBlobURL(id, origin, tx);
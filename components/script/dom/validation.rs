@@ -1,6 +1,10 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+use embedder_traits::EmbedderMsg;
+use euclid::{Point2D, Rect, Size2D};
+use webrender_api::units::DeviceIntRect;
+
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::inheritance::Castable;
@@ -10,7 +14,7 @@ use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::Node;
+use crate::dom::node::{Node, NodeTraits};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::script_runtime::CanGc;
 
@@ -69,14 +73,7 @@ pub(crate) trait Validatable {
 
         // Step 1.2.
         if !event.DefaultPrevented() {
-            let flags = self.validity_state().invalid_flags();
-            println!(
-                "Validation error: {}",
-                validation_message_for_flags(&self.validity_state(), flags)
-            );
-            if let Some(html_elem) = self.as_element().downcast::<HTMLElement>() {
-                html_elem.Focus(can_gc);
-            }
+            self.report_problems(can_gc);
         }
 
         // Step 1.3.
@@ -92,6 +89,39 @@ pub(crate) trait Validatable {
             DOMString::new()
         }
     }
+
+    /// The element that problems with the constraints of this element are reported at.
+    fn validation_anchor(&self) -> DomRoot<Element> {
+        DomRoot::from_ref(self.as_element())
+    }
+
+    /// Report the problems with the constraints of this element to the user, by focusing
+    /// its validation anchor and asking the embedder to show the validation message there.
+    fn report_problems(&self, can_gc: CanGc) {
+        self.as_element().set_user_validity(true, can_gc);
+
+        let anchor = self.validation_anchor();
+        if let Some(html_elem) = anchor.downcast::<HTMLElement>() {
+            html_elem.Focus(can_gc);
+        }
+
+        let message = self.validation_message();
+        if message.is_empty() {
+            return;
+        }
+
+        let rect = anchor.upcast::<Node>().bounding_content_box_or_zero(can_gc);
+        let rect = Rect::new(
+            Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+            Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+        );
+        let document = anchor.owner_document();
+        document.send_to_embedder(EmbedderMsg::ShowValidationMessage(
+            document.webview_id(),
+            DeviceIntRect::from_untyped(&rect.to_box2d()),
+            message.into(),
+        ));
+    }
 }
 
 /// <https://html.spec.whatwg.org/multipage/#the-datalist-element%3Abarred-from-constraint-validation>
@@ -79,6 +79,8 @@ pub(crate) struct ValidityState {
     element: Dom<Element>,
     custom_error_message: DomRefCell<DOMString>,
     invalid_flags: Cell<ValidationFlags>,
+    /// <https://html.spec.whatwg.org/multipage/#user-validity>
+    user_validity: Cell<bool>,
 }
 
 impl ValidityState {
@@ -88,6 +90,7 @@ impl ValidityState {
             element: Dom::from_ref(element),
             custom_error_message: DomRefCell::new(DOMString::new()),
             invalid_flags: Cell::new(ValidationFlags::empty()),
+            user_validity: Cell::new(false),
         }
     }
 
@@ -147,14 +150,36 @@ impl ValidityState {
         self.invalid_flags.get()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#user-validity>
+    pub(crate) fn user_validity(&self) -> bool {
+        self.user_validity.get()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#user-validity>
+    pub(crate) fn set_user_validity(&self, user_validity: bool, can_gc: CanGc) {
+        if self.user_validity.replace(user_validity) != user_validity {
+            self.update_pseudo_classes(can_gc);
+        }
+    }
+
     pub(crate) fn update_pseudo_classes(&self, can_gc: CanGc) {
         if self.element.is_instance_validatable() {
             let is_valid = self.invalid_flags.get().is_empty();
             self.element.set_state(ElementState::VALID, is_valid);
             self.element.set_state(ElementState::INVALID, !is_valid);
+
+            // https://html.spec.whatwg.org/multipage/#selector-user-valid
+            // https://html.spec.whatwg.org/multipage/#selector-user-invalid
+            let user_validity = self.user_validity.get();
+            self.element
+                .set_state(ElementState::USER_VALID, user_validity && is_valid);
+            self.element
+                .set_state(ElementState::USER_INVALID, user_validity && !is_valid);
         } else {
             self.element.set_state(ElementState::VALID, false);
             self.element.set_state(ElementState::INVALID, false);
+            self.element.set_state(ElementState::USER_VALID, false);
+            self.element.set_state(ElementState::USER_INVALID, false);
         }
 
         if let Some(form_control) = self.element.as_maybe_form_control() {
@@ -71,7 +71,6 @@ use crate::dom::webgltexture::WebGLTexture;
 use crate::dom::webgltransformfeedback::WebGLTransformFeedback;
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
 use crate::dom::webglvertexarrayobject::WebGLVertexArrayObject;
-use crate::dom::window::Window;
 use crate::js::conversions::ToJSValConvertible;
 use crate::script_runtime::{CanGc, JSContext};
 
@@ -130,14 +129,14 @@ struct ReadPixelsSizes {
 
 impl WebGL2RenderingContext {
     fn new_inherited(
-        window: &Window,
+        global: &GlobalScope,
         canvas: &HTMLCanvasElementOrOffscreenCanvas,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
         can_gc: CanGc,
     ) -> Option<WebGL2RenderingContext> {
         let base =
-            WebGLRenderingContext::new(window, canvas, WebGLVersion::WebGL2, size, attrs, can_gc)?;
+            WebGLRenderingContext::new(global, canvas, WebGLVersion::WebGL2, size, attrs, can_gc)?;
 
         let samplers = (0..base.limits().max_combined_texture_image_units)
             .map(|_| Default::default())
@@ -179,14 +178,14 @@ impl WebGL2RenderingContext {
 
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         canvas: &HTMLCanvasElementOrOffscreenCanvas,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
         can_gc: CanGc,
     ) -> Option<DomRoot<WebGL2RenderingContext>> {
-        WebGL2RenderingContext::new_inherited(window, canvas, size, attrs, can_gc)
-            .map(|ctx| reflect_dom_object(Box::new(ctx), window, can_gc))
+        WebGL2RenderingContext::new_inherited(global, canvas, size, attrs, can_gc)
+            .map(|ctx| reflect_dom_object(Box::new(ctx), global, can_gc))
     }
 
     #[allow(unsafe_code)]
@@ -989,6 +988,14 @@ impl CanvasContext for WebGL2RenderingContext {
     fn mark_as_dirty(&self) {
         self.base.mark_as_dirty()
     }
+
+    fn update_rendering(&self) {
+        self.base.update_rendering()
+    }
+
+    fn image_key(&self) -> Option<ImageKey> {
+        self.base.image_key()
+    }
 }
 
 impl WebGL2RenderingContextMethods<crate::DomTypeHolder> for WebGL2RenderingContext {
@@ -1631,7 +1638,14 @@ impl WebGL2RenderingContextMethods<crate::DomTypeHolder> for WebGL2RenderingCont
         }
         let copy_bytes = copy_count * dst_elem_size;
 
-        // TODO(mmatyas): Transform Feedback
+        // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.3
+        if target == constants::TRANSFORM_FEEDBACK_BUFFER &&
+            self.current_transform_feedback
+                .get()
+                .is_some_and(|tf| tf.is_active())
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
 
         let src_byte_offset = src_byte_offset as usize;
         if src_byte_offset + copy_bytes > bound_buffer.capacity() ||
@@ -4345,7 +4359,7 @@ impl WebGL2RenderingContextMethods<crate::DomTypeHolder> for WebGL2RenderingCont
             ));
         let (size, ty, name) = receiver.recv().unwrap();
         Some(WebGLActiveInfo::new(
-            self.base.global().as_window(),
+            &self.base.global(),
             size,
             ty,
             DOMString::from(name),
@@ -7,6 +7,7 @@ use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGL
 
 pub(crate) mod angleinstancedarrays;
 pub(crate) mod extblendminmax;
+pub(crate) mod extcolorbufferfloat;
 pub(crate) mod extcolorbufferhalffloat;
 pub(crate) mod extfragdepth;
 pub(crate) mod extshadertexturelod;
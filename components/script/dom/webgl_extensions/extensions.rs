@@ -22,6 +22,7 @@ use crate::dom::bindings::codegen::Bindings::OESTextureHalfFloatBinding::OESText
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::trace::JSTraceable;
+use crate::dom::extcolorbufferfloat::EXTColorBufferFloat;
 use crate::dom::extcolorbufferhalffloat::EXTColorBufferHalfFloat;
 use crate::dom::oestexturefloat::OESTextureFloat;
 use crate::dom::oestexturehalffloat::OESTextureHalfFloat;
@@ -413,6 +414,7 @@ impl WebGLExtensions {
     fn register_all_extensions(&self) {
         self.register::<ext::angleinstancedarrays::ANGLEInstancedArrays>();
         self.register::<ext::extblendminmax::EXTBlendMinmax>();
+        self.register::<ext::extcolorbufferfloat::EXTColorBufferFloat>();
         self.register::<ext::extcolorbufferhalffloat::EXTColorBufferHalfFloat>();
         self.register::<ext::extfragdepth::EXTFragDepth>();
         self.register::<ext::extshadertexturelod::EXTShaderTextureLod>();
@@ -446,7 +448,9 @@ impl WebGLExtensions {
     }
 
     pub(crate) fn is_float_buffer_renderable(&self) -> bool {
-        self.is_enabled::<WEBGLColorBufferFloat>() || self.is_enabled::<OESTextureFloat>()
+        self.is_enabled::<WEBGLColorBufferFloat>() ||
+            self.is_enabled::<OESTextureFloat>() ||
+            self.is_enabled::<EXTColorBufferFloat>()
     }
 
     pub(crate) fn is_min_glsl_version_satisfied(&self, min_glsl_version: WebGLSLVersion) -> bool {
@@ -454,7 +458,9 @@ impl WebGLExtensions {
     }
 
     pub(crate) fn is_half_float_buffer_renderable(&self) -> bool {
-        self.is_enabled::<EXTColorBufferHalfFloat>() || self.is_enabled::<OESTextureHalfFloat>()
+        self.is_enabled::<EXTColorBufferHalfFloat>() ||
+            self.is_enabled::<OESTextureHalfFloat>() ||
+            self.is_enabled::<EXTColorBufferFloat>()
     }
 
     pub(crate) fn effective_type(&self, type_: u32) -> u32 {
@@ -9,7 +9,7 @@ use crate::dom::bindings::codegen::Bindings::WebGLActiveInfoBinding::WebGLActive
 use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -32,7 +32,7 @@ impl WebGLActiveInfo {
     }
 
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         size: i32,
         ty: u32,
         name: DOMString,
@@ -40,7 +40,7 @@ impl WebGLActiveInfo {
     ) -> DomRoot<WebGLActiveInfo> {
         reflect_dom_object(
             Box::new(WebGLActiveInfo::new_inherited(size, ty, name)),
-            window,
+            global,
             can_gc,
         )
     }
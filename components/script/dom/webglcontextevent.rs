@@ -16,7 +16,7 @@ use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -28,7 +28,7 @@ pub(crate) struct WebGLContextEvent {
 impl WebGLContextEventMethods<crate::DomTypeHolder> for WebGLContextEvent {
     // https://registry.khronos.org/webgl/specs/latest/1.0/#5.15
     fn Constructor(
-        window: &Window,
+        global: &GlobalScope,
         proto: Option<HandleObject>,
         can_gc: CanGc,
         type_: DOMString,
@@ -44,7 +44,7 @@ impl WebGLContextEventMethods<crate::DomTypeHolder> for WebGLContextEvent {
         let cancelable = EventCancelable::from(init.parent.cancelable);
 
         Ok(WebGLContextEvent::new_with_proto(
-            window,
+            global,
             proto,
             Atom::from(type_),
             bubbles,
@@ -74,7 +74,7 @@ impl WebGLContextEvent {
     }
 
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         type_: Atom,
         bubbles: EventBubbles,
         cancelable: EventCancelable,
@@ -82,7 +82,7 @@ impl WebGLContextEvent {
         can_gc: CanGc,
     ) -> DomRoot<WebGLContextEvent> {
         Self::new_with_proto(
-            window,
+            global,
             None,
             type_,
             bubbles,
@@ -93,7 +93,7 @@ impl WebGLContextEvent {
     }
 
     fn new_with_proto(
-        window: &Window,
+        global: &GlobalScope,
         proto: Option<HandleObject>,
         type_: Atom,
         bubbles: EventBubbles,
@@ -103,7 +103,7 @@ impl WebGLContextEvent {
     ) -> DomRoot<WebGLContextEvent> {
         let event = reflect_dom_object_with_proto(
             Box::new(WebGLContextEvent::new_inherited(status_message)),
-            window,
+            global,
             proto,
             can_gc,
         );
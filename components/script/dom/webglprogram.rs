@@ -336,7 +336,7 @@ impl WebGLProgram {
             .get(index as usize)
             .ok_or(WebGLError::InvalidValue)?;
         Ok(WebGLActiveInfo::new(
-            self.global().as_window(),
+            &self.global(),
             data.size.unwrap_or(1),
             data.type_,
             data.name().into(),
@@ -358,7 +358,7 @@ impl WebGLProgram {
             .get(index as usize)
             .ok_or(WebGLError::InvalidValue)?;
         Ok(WebGLActiveInfo::new(
-            self.global().as_window(),
+            &self.global(),
             data.size,
             data.type_,
             data.name.clone().into(),
@@ -463,7 +463,7 @@ impl WebGLProgram {
         let context_id = self.upcast::<WebGLObject>().context().context_id();
 
         Ok(Some(WebGLUniformLocation::new(
-            self.global().as_window(),
+            &self.global(),
             location,
             context_id,
             self.id,
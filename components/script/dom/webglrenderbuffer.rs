@@ -216,6 +216,23 @@ impl WebGLRenderbuffer {
                 }
                 internal_format
             },
+            // https://registry.khronos.org/webgl/extensions/EXT_color_buffer_float/
+            constants::R16F |
+            constants::RG16F |
+            constants::R32F |
+            constants::RG32F |
+            constants::R11F_G11F_B10F => {
+                if webgl_version == WebGLVersion::WebGL1 ||
+                    !self
+                        .upcast()
+                        .context()
+                        .extension_manager()
+                        .is_float_buffer_renderable()
+                {
+                    return Err(WebGLError::InvalidEnum);
+                }
+                internal_format
+            },
             WEBGLColorBufferFloatConstants::RGBA32F_EXT => {
                 if !self
                     .upcast()
@@ -55,6 +55,7 @@ use crate::dom::bindings::reflector::{DomGlobal, DomObject, Reflector, reflect_d
 use crate::dom::bindings::root::{DomOnceCell, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::LayoutCanvasRenderingContextHelpers;
 use crate::dom::node::{Node, NodeDamage, NodeTraits};
 #[cfg(feature = "webxr")]
@@ -216,7 +217,7 @@ pub(crate) struct WebGLRenderingContext {
 
 impl WebGLRenderingContext {
     pub(crate) fn new_inherited(
-        window: &Window,
+        global: &GlobalScope,
         canvas: &HTMLCanvasElementOrOffscreenCanvas,
         webgl_version: WebGLVersion,
         size: Size2D<u32>,
@@ -226,7 +227,7 @@ impl WebGLRenderingContext {
             return Err("WebGL context creation error forced by pref `webgl.testing.context_creation_error`".into());
         }
 
-        let webgl_chan = match window.webgl_chan() {
+        let webgl_chan = match global.webgl_chan() {
             Some(chan) => chan,
             None => return Err("WebGL initialization failed early on".into()),
         };
@@ -283,19 +284,19 @@ impl WebGLRenderingContext {
 
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         canvas: &HTMLCanvasElementOrOffscreenCanvas,
         webgl_version: WebGLVersion,
         size: Size2D<u32>,
         attrs: GLContextAttributes,
         can_gc: CanGc,
     ) -> Option<DomRoot<WebGLRenderingContext>> {
-        match WebGLRenderingContext::new_inherited(window, canvas, webgl_version, size, attrs) {
-            Ok(ctx) => Some(reflect_dom_object(Box::new(ctx), window, can_gc)),
+        match WebGLRenderingContext::new_inherited(global, canvas, webgl_version, size, attrs) {
+            Ok(ctx) => Some(reflect_dom_object(Box::new(ctx), global, can_gc)),
             Err(msg) => {
                 error!("Couldn't create WebGLRenderingContext: {}", msg);
                 let event = WebGLContextEvent::new(
-                    window,
+                    global,
                     atom!("webglcontextcreationerror"),
                     EventBubbles::DoesNotBubble,
                     EventCancelable::Cancelable,
@@ -2029,7 +2030,11 @@ impl CanvasContext for WebGLRenderingContext {
 
         // Dirtying the canvas is unnecessary if we're actively displaying immersive
         // XR content right now.
-        if self.global().as_window().in_immersive_xr_session() {
+        if self
+            .global()
+            .downcast::<Window>()
+            .is_some_and(|window| window.in_immersive_xr_session())
+        {
             return;
         }
 
@@ -2038,9 +2043,28 @@ impl CanvasContext for WebGLRenderingContext {
                 canvas.upcast::<Node>().dirty(NodeDamage::Other);
                 canvas.owner_document().add_dirty_webgl_canvas(self);
             },
-            HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(_) => {},
+            HTMLCanvasElementOrOffscreenCanvas::OffscreenCanvas(ref canvas) => {
+                canvas.mark_as_dirty()
+            },
         }
     }
+
+    /// Swap the buffers of this context, which the document does in a batch for the
+    /// contexts of canvas elements, and block until it is done.
+    fn update_rendering(&self) {
+        let Some(webgl_chan) = self.global().webgl_chan() else {
+            return;
+        };
+        let (sender, receiver) = webgl_channel().unwrap();
+        webgl_chan
+            .send(WebGLMsg::SwapBuffers(vec![self.context_id()], sender, 0))
+            .unwrap();
+        receiver.recv().unwrap();
+    }
+
+    fn image_key(&self) -> Option<ImageKey> {
+        Some(self.webrender_image)
+    }
 }
 
 #[cfg(not(feature = "webgl_backtrace"))]
@@ -3386,18 +3410,22 @@ impl WebGLRenderingContextMethods<crate::DomTypeHolder> for WebGLRenderingContex
         // https://github.com/immersive-web/webxr/issues/862
         let target_matches = target == constants::RENDERBUFFER;
 
-        let pname_matches = matches!(
-            pname,
+        let pname_matches = match pname {
             constants::RENDERBUFFER_WIDTH |
-                constants::RENDERBUFFER_HEIGHT |
-                constants::RENDERBUFFER_INTERNAL_FORMAT |
-                constants::RENDERBUFFER_RED_SIZE |
-                constants::RENDERBUFFER_GREEN_SIZE |
-                constants::RENDERBUFFER_BLUE_SIZE |
-                constants::RENDERBUFFER_ALPHA_SIZE |
-                constants::RENDERBUFFER_DEPTH_SIZE |
-                constants::RENDERBUFFER_STENCIL_SIZE
-        );
+            constants::RENDERBUFFER_HEIGHT |
+            constants::RENDERBUFFER_INTERNAL_FORMAT |
+            constants::RENDERBUFFER_RED_SIZE |
+            constants::RENDERBUFFER_GREEN_SIZE |
+            constants::RENDERBUFFER_BLUE_SIZE |
+            constants::RENDERBUFFER_ALPHA_SIZE |
+            constants::RENDERBUFFER_DEPTH_SIZE |
+            constants::RENDERBUFFER_STENCIL_SIZE => true,
+            // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.5
+            WebGL2RenderingContextConstants::RENDERBUFFER_SAMPLES => {
+                self.webgl_version() == WebGLVersion::WebGL2
+            },
+            _ => false,
+        };
 
         if !target_matches || !pname_matches {
             self.webgl_error(InvalidEnum);
@@ -3550,7 +3578,7 @@ impl WebGLRenderingContextMethods<crate::DomTypeHolder> for WebGLRenderingContex
 
         let (range_min, range_max, precision) = receiver.recv().unwrap();
         Some(WebGLShaderPrecisionFormat::new(
-            self.global().as_window(),
+            &self.global(),
             range_min,
             range_max,
             precision,
@@ -5185,7 +5213,7 @@ pub(crate) enum TexSource {
     BufferOffset(i64),
 }
 
-#[derive(JSTraceable)]
+#[derive(Clone, JSTraceable)]
 pub(crate) struct WebGLCommandSender {
     #[no_trace]
     sender: WebGLChan,
@@ -10,7 +10,7 @@ use dom_struct::dom_struct;
 use crate::dom::bindings::codegen::Bindings::WebGLShaderPrecisionFormatBinding::WebGLShaderPrecisionFormatMethods;
 use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -32,7 +32,7 @@ impl WebGLShaderPrecisionFormat {
     }
 
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         range_min: i32,
         range_max: i32,
         precision: i32,
@@ -42,7 +42,7 @@ impl WebGLShaderPrecisionFormat {
             Box::new(WebGLShaderPrecisionFormat::new_inherited(
                 range_min, range_max, precision,
             )),
-            window,
+            global,
             can_gc,
         )
     }
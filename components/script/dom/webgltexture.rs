@@ -218,17 +218,31 @@ impl WebGLTexture {
             return Err(WebGLError::InvalidOperation);
         }
 
-        if !base_image_info.is_power_of_two() {
-            return Err(WebGLError::InvalidOperation);
+        let context = self.upcast::<WebGLObject>().context();
+        match context.webgl_version() {
+            WebGLVersion::WebGL1 => {
+                if !base_image_info.is_power_of_two() {
+                    return Err(WebGLError::InvalidOperation);
+                }
+            },
+            // WebGL 2 lifts the power-of-two restriction, but mipmaps can only be generated
+            // for formats which are both color-renderable and texture-filterable.
+            // https://registry.khronos.org/OpenGL-Refpages/es3.0/html/glGenerateMipmap.xhtml
+            WebGLVersion::WebGL2 => {
+                if matches!(
+                    base_image_info.internal_format(),
+                    TexFormat::SRGB8 | TexFormat::RGB9E5 | TexFormat::RGB8SNorm
+                ) {
+                    return Err(WebGLError::InvalidOperation);
+                }
+            },
         }
 
         if base_image_info.is_compressed_format() {
             return Err(WebGLError::InvalidOperation);
         }
 
-        self.upcast::<WebGLObject>()
-            .context()
-            .send_command(WebGLCommand::GenerateMipmap(target));
+        context.send_command(WebGLCommand::GenerateMipmap(target));
 
         if self.base_mipmap_level + base_image_info.get_max_mimap_levels() == 0 {
             return Err(WebGLError::InvalidOperation);
@@ -8,7 +8,7 @@ use dom_struct::dom_struct;
 
 use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::window::Window;
+use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
@@ -46,7 +46,7 @@ impl WebGLUniformLocation {
 
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        window: &Window,
+        global: &GlobalScope,
         id: i32,
         context_id: WebGLContextId,
         program_id: WebGLProgramId,
@@ -64,7 +64,7 @@ impl WebGLUniformLocation {
                 size,
                 type_,
             )),
-            window,
+            global,
             can_gc,
         )
     }
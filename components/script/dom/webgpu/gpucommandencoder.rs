@@ -14,7 +14,7 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUCommandBufferDescriptor, GPUCommandEncoderDescriptor, GPUCommandEncoderMethods,
     GPUComputePassDescriptor, GPUExtent3D, GPUImageCopyBuffer, GPUImageCopyTexture,
-    GPURenderPassDescriptor, GPUSize64,
+    GPURenderPassDescriptor, GPUSize32, GPUSize64,
 };
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
@@ -26,6 +26,7 @@ use crate::dom::webgpu::gpubuffer::GPUBuffer;
 use crate::dom::webgpu::gpucommandbuffer::GPUCommandBuffer;
 use crate::dom::webgpu::gpucomputepassencoder::GPUComputePassEncoder;
 use crate::dom::webgpu::gpudevice::GPUDevice;
+use crate::dom::webgpu::gpuqueryset::GPUQuerySet;
 use crate::dom::webgpu::gpurenderpassencoder::GPURenderPassEncoder;
 use crate::script_runtime::CanGc;
 
@@ -138,6 +139,13 @@ impl GPUCommandEncoderMethods<crate::DomTypeHolder> for GPUCommandEncoder {
             command_encoder_id: self.id().0,
             compute_pass_id,
             label: (&descriptor.parent).convert(),
+            timestamp_writes: descriptor.timestampWrites.as_ref().map(|writes| {
+                wgpu_com::PassTimestampWrites {
+                    query_set: writes.querySet.id().0,
+                    beginning_of_pass_write_index: writes.beginningOfPassWriteIndex,
+                    end_of_pass_write_index: writes.endOfPassWriteIndex,
+                }
+            }),
             device_id: self.device.id().0,
         }) {
             warn!("Failed to send WebGPURequest::BeginComputePass {e:?}");
@@ -208,6 +216,14 @@ impl GPUCommandEncoderMethods<crate::DomTypeHolder> for GPUCommandEncoder {
             label: (&descriptor.parent).convert(),
             depth_stencil_attachment,
             color_attachments,
+            timestamp_writes: descriptor.timestampWrites.as_ref().map(|writes| {
+                wgpu_com::PassTimestampWrites {
+                    query_set: writes.querySet.id().0,
+                    beginning_of_pass_write_index: writes.beginningOfPassWriteIndex,
+                    end_of_pass_write_index: writes.endOfPassWriteIndex,
+                }
+            }),
+            occlusion_query_set: descriptor.occlusionQuerySet.as_ref().map(|set| set.id().0),
             device_id: self.device.id().0,
         }) {
             warn!("Failed to send WebGPURequest::BeginRenderPass {e:?}");
@@ -305,6 +321,35 @@ impl GPUCommandEncoderMethods<crate::DomTypeHolder> for GPUCommandEncoder {
         Ok(())
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-resolvequeryset>
+    fn ResolveQuerySet(
+        &self,
+        query_set: &GPUQuerySet,
+        first_query: GPUSize32,
+        query_count: GPUSize32,
+        destination: &GPUBuffer,
+        destination_offset: GPUSize64,
+    ) {
+        // Timestamps are quantized after being resolved, which needs a bind group
+        // over the destination range.
+        let quantization_bind_group_id = query_set
+            .is_timestamp()
+            .then(|| self.global().wgpu_id_hub().create_bind_group_id());
+        self.channel
+            .0
+            .send(WebGPURequest::ResolveQuerySet {
+                command_encoder_id: self.encoder.0,
+                device_id: self.device.id().0,
+                query_set_id: query_set.id().0,
+                first_query,
+                query_count,
+                destination_id: destination.id().0,
+                destination_offset,
+                quantization_bind_group_id,
+            })
+            .expect("Failed to send ResolveQuerySet");
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-finish>
     fn Finish(&self, descriptor: &GPUCommandBufferDescriptor) -> DomRoot<GPUCommandBuffer> {
         self.channel
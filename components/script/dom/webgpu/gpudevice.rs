@@ -11,8 +11,8 @@ use std::rc::Rc;
 use dom_struct::dom_struct;
 use js::jsapi::{Heap, JSObject};
 use webgpu_traits::{
-    PopError, WebGPU, WebGPUComputePipeline, WebGPUComputePipelineResponse, WebGPUDevice,
-    WebGPUPoppedErrorScopeResponse, WebGPUQueue, WebGPURenderPipeline,
+    PopError, TimestampQuantizerIds, WebGPU, WebGPUComputePipeline, WebGPUComputePipelineResponse,
+    WebGPUDevice, WebGPUPoppedErrorScopeResponse, WebGPUQueue, WebGPURenderPipeline,
     WebGPURenderPipelineResponse, WebGPURequest,
 };
 use wgpu_core::id::{BindGroupLayoutId, PipelineLayoutId};
@@ -32,9 +32,9 @@ use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUBindGroupDescriptor, GPUBindGroupLayoutDescriptor, GPUBufferDescriptor,
     GPUCommandEncoderDescriptor, GPUComputePipelineDescriptor, GPUDeviceLostReason,
     GPUDeviceMethods, GPUErrorFilter, GPUPipelineErrorReason, GPUPipelineLayoutDescriptor,
-    GPURenderBundleEncoderDescriptor, GPURenderPipelineDescriptor, GPUSamplerDescriptor,
-    GPUShaderModuleDescriptor, GPUSupportedLimitsMethods, GPUTextureDescriptor, GPUTextureFormat,
-    GPUUncapturedErrorEventInit, GPUVertexStepMode,
+    GPUQuerySetDescriptor, GPURenderBundleEncoderDescriptor, GPURenderPipelineDescriptor,
+    GPUSamplerDescriptor, GPUShaderModuleDescriptor, GPUSupportedLimitsMethods,
+    GPUTextureDescriptor, GPUTextureFormat, GPUUncapturedErrorEventInit, GPUVertexStepMode,
 };
 use crate::dom::bindings::codegen::UnionTypes::GPUPipelineLayoutOrGPUAutoLayoutMode;
 use crate::dom::bindings::error::{Error, Fallible};
@@ -53,6 +53,7 @@ use crate::dom::webgpu::gpubuffer::GPUBuffer;
 use crate::dom::webgpu::gpucommandencoder::GPUCommandEncoder;
 use crate::dom::webgpu::gpucomputepipeline::GPUComputePipeline;
 use crate::dom::webgpu::gpupipelinelayout::GPUPipelineLayout;
+use crate::dom::webgpu::gpuqueryset::GPUQuerySet;
 use crate::dom::webgpu::gpuqueue::GPUQueue;
 use crate::dom::webgpu::gpurenderbundleencoder::GPURenderBundleEncoder;
 use crate::dom::webgpu::gpurenderpipeline::GPURenderPipeline;
@@ -84,6 +85,9 @@ pub(crate) struct GPUDevice {
     #[ignore_malloc_size_of = "promises are hard"]
     lost_promise: DomRefCell<Rc<Promise>>,
     valid: Cell<bool>,
+    /// Whether the WebGPU thread has been asked to set up timestamp quantization
+    /// for this device, which happens along with its first timestamp query set.
+    has_timestamp_quantizer: Cell<bool>,
 }
 
 pub(crate) enum PipelineLayout {
@@ -134,6 +138,7 @@ impl GPUDevice {
             default_queue: Dom::from_ref(queue),
             lost_promise: DomRefCell::new(lost_promise),
             valid: Cell::new(true),
+            has_timestamp_quantizer: Cell::new(false),
         }
     }
 
@@ -232,6 +237,21 @@ impl GPUDevice {
         }
     }
 
+    /// Allocates the ids of the timestamp quantizer of this device,
+    /// or returns `None` if that already happened.
+    pub(crate) fn take_timestamp_quantizer_ids(&self) -> Option<TimestampQuantizerIds> {
+        if self.has_timestamp_quantizer.replace(true) {
+            return None;
+        }
+        let hub = self.global().wgpu_id_hub();
+        Some(TimestampQuantizerIds {
+            shader_module_id: hub.create_shader_module_id(),
+            bind_group_layout_id: hub.create_bind_group_layout_id(),
+            pipeline_layout_id: hub.create_pipeline_layout_id(),
+            pipeline_id: hub.create_compute_pipeline_id(),
+        })
+    }
+
     pub(crate) fn is_lost(&self) -> bool {
         self.lost_promise.borrow().is_fulfilled()
     }
@@ -497,6 +517,11 @@ impl GPUDeviceMethods<crate::DomTypeHolder> for GPUDevice {
         GPUSampler::create(self, descriptor, CanGc::note())
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+    fn CreateQuerySet(&self, descriptor: &GPUQuerySetDescriptor) -> Fallible<DomRoot<GPUQuerySet>> {
+        GPUQuerySet::create(self, descriptor, CanGc::note())
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderpipeline>
     fn CreateRenderPipeline(
         &self,
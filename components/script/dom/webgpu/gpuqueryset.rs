@@ -2,34 +2,183 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-#![allow(dead_code)] // this file is stub
-
 use dom_struct::dom_struct;
+use webgpu_traits::{WebGPU, WebGPUQuerySet, WebGPURequest};
+use wgpu_types::{Features, QuerySetDescriptor, QueryType};
 
-use crate::dom::bindings::codegen::Bindings::WebGPUBinding::GPUQuerySetMethods;
-use crate::dom::bindings::reflector::Reflector;
+use crate::conversions::Convert;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
+    GPUDeviceMethods, GPUQuerySetDescriptor, GPUQuerySetMethods, GPUQueryType,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::webgpu::gpudevice::GPUDevice;
+use crate::script_runtime::CanGc;
 
 #[dom_struct]
 pub(crate) struct GPUQuerySet {
     reflector_: Reflector,
-    // #[ignore_malloc_size_of = "defined in wgpu-types"]
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    #[no_trace]
+    channel: WebGPU,
+    label: DomRefCell<USVString>,
+    #[no_trace]
+    query_set: WebGPUQuerySet,
+    ty: GPUQueryType,
+    count: u32,
+}
+
+impl GPUQuerySet {
+    fn new_inherited(
+        channel: WebGPU,
+        query_set: WebGPUQuerySet,
+        ty: GPUQueryType,
+        count: u32,
+        label: USVString,
+    ) -> Self {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            label: DomRefCell::new(label),
+            query_set,
+            ty,
+            count,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        query_set: WebGPUQuerySet,
+        ty: GPUQueryType,
+        count: u32,
+        label: USVString,
+        can_gc: CanGc,
+    ) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(GPUQuerySet::new_inherited(
+                channel, query_set, ty, count, label,
+            )),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl GPUQuerySet {
+    pub(crate) fn id(&self) -> WebGPUQuerySet {
+        self.query_set
+    }
+
+    pub(crate) fn is_timestamp(&self) -> bool {
+        self.ty == GPUQueryType::Timestamp
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+    pub(crate) fn create(
+        device: &GPUDevice,
+        descriptor: &GPUQuerySetDescriptor,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<GPUQuerySet>> {
+        let ty = match descriptor.type_ {
+            GPUQueryType::Occlusion => QueryType::Occlusion,
+            GPUQueryType::Timestamp => {
+                if !device
+                    .Features()
+                    .wgpu_features()
+                    .contains(Features::TIMESTAMP_QUERY)
+                {
+                    return Err(Error::Type(
+                        "Timestamp queries require the timestamp-query feature".to_owned(),
+                    ));
+                }
+                QueryType::Timestamp
+            },
+            // Pipeline statistics were removed from the WebGPU specification.
+            GPUQueryType::Pipeline_statistics => {
+                return Err(Error::Type(
+                    "Pipeline statistics queries are not supported".to_owned(),
+                ));
+            },
+        };
+
+        let query_set_id = device.global().wgpu_id_hub().create_query_set_id();
+        let timestamp_quantizer = if ty == QueryType::Timestamp {
+            device.take_timestamp_quantizer_ids()
+        } else {
+            None
+        };
+        device
+            .channel()
+            .0
+            .send(WebGPURequest::CreateQuerySet {
+                device_id: device.id().0,
+                queue_id: device.queue_id().0,
+                query_set_id,
+                descriptor: QuerySetDescriptor {
+                    label: (&descriptor.parent).convert(),
+                    ty,
+                    count: descriptor.count,
+                },
+                timestamp_quantizer,
+            })
+            .expect("Failed to create WebGPU query set");
+
+        Ok(GPUQuerySet::new(
+            &device.global(),
+            device.channel().clone(),
+            WebGPUQuerySet(query_set_id),
+            descriptor.type_,
+            descriptor.count,
+            descriptor.parent.label.clone(),
+            can_gc,
+        ))
+    }
 }
 
-// TODO: wgpu does not expose right fields right now
 impl GPUQuerySetMethods<crate::DomTypeHolder> for GPUQuerySet {
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-destroy>
     fn Destroy(&self) {
-        todo!()
+        // wgpu-core cannot destroy a query set ahead of dropping it, so the
+        // underlying resource is released once this object is garbage collected.
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-type>
+    fn Type(&self) -> GPUQueryType {
+        self.ty
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-count>
+    fn Count(&self) -> u32 {
+        self.count
     }
 
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
     fn Label(&self) -> USVString {
-        todo!()
+        self.label.borrow().clone()
     }
 
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
-    fn SetLabel(&self, _value: USVString) {
-        todo!()
+    fn SetLabel(&self, value: USVString) {
+        *self.label.borrow_mut() = value;
+    }
+}
+
+impl Drop for GPUQuerySet {
+    fn drop(&mut self) {
+        if let Err(e) = self
+            .channel
+            .0
+            .send(WebGPURequest::DropQuerySet(self.query_set.0))
+        {
+            warn!(
+                "Failed to send DropQuerySet ({:?}) ({})",
+                self.query_set.0, e
+            );
+        }
     }
 }
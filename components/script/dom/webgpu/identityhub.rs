@@ -5,12 +5,12 @@
 use webgpu_traits::{ComputePass, ComputePassId, RenderPass, RenderPassId};
 use wgpu_core::id::markers::{
     Adapter, BindGroup, BindGroupLayout, Buffer, CommandEncoder, ComputePipeline, Device,
-    PipelineLayout, Queue, RenderBundle, RenderPipeline, Sampler, ShaderModule, Texture,
+    PipelineLayout, QuerySet, Queue, RenderBundle, RenderPipeline, Sampler, ShaderModule, Texture,
     TextureView,
 };
 use wgpu_core::id::{
     AdapterId, BindGroupId, BindGroupLayoutId, BufferId, CommandEncoderId, ComputePipelineId,
-    DeviceId, PipelineLayoutId, QueueId, RenderBundleId, RenderPipelineId, SamplerId,
+    DeviceId, PipelineLayoutId, QuerySetId, QueueId, RenderBundleId, RenderPipelineId, SamplerId,
     ShaderModuleId, TextureId, TextureViewId,
 };
 use wgpu_core::identity::IdentityManager;
@@ -32,6 +32,7 @@ pub(crate) struct IdentityHub {
     samplers: IdentityManager<Sampler>,
     render_pipelines: IdentityManager<RenderPipeline>,
     render_bundles: IdentityManager<RenderBundle>,
+    query_sets: IdentityManager<QuerySet>,
     compute_passes: IdentityManager<ComputePass>,
     render_passes: IdentityManager<RenderPass>,
 }
@@ -54,6 +55,7 @@ impl Default for IdentityHub {
             samplers: IdentityManager::new(),
             render_pipelines: IdentityManager::new(),
             render_bundles: IdentityManager::new(),
+            query_sets: IdentityManager::new(),
             compute_passes: IdentityManager::new(),
             render_passes: IdentityManager::new(),
         }
@@ -181,6 +183,14 @@ impl IdentityHub {
         self.render_bundles.free(id);
     }
 
+    pub(crate) fn create_query_set_id(&self) -> QuerySetId {
+        self.query_sets.process()
+    }
+
+    pub(crate) fn free_query_set_id(&self, id: QuerySetId) {
+        self.query_sets.free(id);
+    }
+
     pub(crate) fn create_compute_pass_id(&self) -> ComputePassId {
         self.compute_passes.process()
     }
@@ -176,6 +176,40 @@ impl WebSocket {
     pub(crate) fn origin(&self) -> ImmutableOrigin {
         self.url.origin()
     }
+
+    /// Make this WebSocket disappear because its document is unloaded, returning whether
+    /// its connection was being established or open.
+    /// <https://websockets.spec.whatwg.org/#make-disappear>
+    pub(crate) fn make_disappear(&self) -> bool {
+        match self.ready_state.get() {
+            WebSocketRequestState::Closing | WebSocketRequestState::Closed => false,
+            // Step 1. If the WebSocket connection is not yet established, fail the
+            // WebSocket connection.
+            WebSocketRequestState::Connecting => {
+                self.ready_state.set(WebSocketRequestState::Closing);
+                fail_the_websocket_connection(
+                    Trusted::new(self),
+                    &self
+                        .global()
+                        .task_manager()
+                        .websocket_task_source()
+                        .to_sendable(),
+                );
+                true
+            },
+            // Step 2. If the WebSocket closing handshake has not yet been started, start the
+            // WebSocket closing handshake, with the status code to use in the WebSocket
+            // Close message being 1001.
+            WebSocketRequestState::Open => {
+                self.ready_state.set(WebSocketRequestState::Closing);
+                let _ = self.sender.send(WebSocketDomAction::Close(
+                    Some(close_code::GOING_AWAY),
+                    None,
+                ));
+                true
+            },
+        }
+    }
 }
 
 impl WebSocketMethods<crate::DomTypeHolder> for WebSocket {
@@ -257,6 +291,7 @@ impl WebSocketMethods<crate::DomTypeHolder> for WebSocket {
 
         // Step 12. Establish a WebSocket connection given urlRecord, protocols, and client.
         let ws = WebSocket::new(global, proto, url_record.clone(), dom_action_sender, can_gc);
+        global.track_websocket(&ws);
         let address = Trusted::new(&*ws);
 
         let request = RequestBuilder::new(global.webview_id(), url_record, Referrer::NoReferrer)
@@ -4,6 +4,8 @@
 
 pub(crate) mod fakexrdevice;
 pub(crate) mod fakexrinputcontroller;
+pub(crate) mod xranchor;
+pub(crate) mod xranchorset;
 pub(crate) mod xrboundedreferencespace;
 pub(crate) mod xrcompositionlayer;
 pub(crate) mod xrcubelayer;
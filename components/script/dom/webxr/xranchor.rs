@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding::XRAnchorMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::window::Window;
+use crate::dom::xrsession::{ApiPose, XRSession};
+use crate::dom::xrspace::XRSpace;
+use crate::script_runtime::CanGc;
+
+#[dom_struct]
+pub(crate) struct XRAnchor {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    space: Dom<XRSpace>,
+    /// <https://immersive-web.github.io/anchors/#xranchor-deleted>
+    deleted: Cell<bool>,
+}
+
+impl XRAnchor {
+    fn new_inherited(session: &XRSession, space: &XRSpace) -> XRAnchor {
+        XRAnchor {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            space: Dom::from_ref(space),
+            deleted: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        session: &XRSession,
+        pose: ApiPose,
+        can_gc: CanGc,
+    ) -> DomRoot<XRAnchor> {
+        let space = XRSpace::new_anchorspace(window.as_global_scope(), session, pose, can_gc);
+        reflect_dom_object(
+            Box::new(XRAnchor::new_inherited(session, &space)),
+            window,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn set_deleted(&self) {
+        self.deleted.set(true);
+    }
+}
+
+impl XRAnchorMethods<crate::DomTypeHolder> for XRAnchor {
+    /// <https://immersive-web.github.io/anchors/#dom-xranchor-anchorspace>
+    fn AnchorSpace(&self) -> Fallible<DomRoot<XRSpace>> {
+        if self.deleted.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(DomRoot::from_ref(&self.space))
+    }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xranchor-delete>
+    fn Delete(&self) {
+        if self.deleted.get() {
+            return;
+        }
+        self.session.delete_anchor(self);
+    }
+}
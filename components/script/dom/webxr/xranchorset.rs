@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use indexmap::IndexSet;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::XRAnchorSetBinding::XRAnchorSetMethods;
+use crate::dom::bindings::like::Setlike;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use crate::dom::xranchor::XRAnchor;
+use crate::script_runtime::CanGc;
+
+/// <https://immersive-web.github.io/anchors/#xr-anchor-set>
+#[dom_struct]
+pub(crate) struct XRAnchorSet {
+    reflector_: Reflector,
+    #[custom_trace]
+    internal: DomRefCell<IndexSet<DomRoot<XRAnchor>>>,
+}
+
+impl XRAnchorSet {
+    fn new_inherited() -> XRAnchorSet {
+        XRAnchorSet {
+            reflector_: Reflector::new(),
+            internal: DomRefCell::new(IndexSet::new()),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<XRAnchorSet> {
+        reflect_dom_object(Box::new(XRAnchorSet::new_inherited()), window, can_gc)
+    }
+
+    pub(crate) fn insert(&self, anchor: &XRAnchor) {
+        self.internal.borrow_mut().insert(DomRoot::from_ref(anchor));
+    }
+
+    pub(crate) fn remove(&self, anchor: &XRAnchor) {
+        self.internal
+            .borrow_mut()
+            .shift_remove(&DomRoot::from_ref(anchor));
+    }
+
+    /// Removes every anchor from the set, returning them.
+    pub(crate) fn take(&self) -> Vec<DomRoot<XRAnchor>> {
+        self.internal.borrow_mut().drain(..).collect()
+    }
+}
+
+impl XRAnchorSetMethods<crate::DomTypeHolder> for XRAnchorSet {
+    fn Size(&self) -> u32 {
+        self.internal.size()
+    }
+}
+
+impl Setlike for XRAnchorSet {
+    type Key = DomRoot<XRAnchor>;
+
+    #[inline(always)]
+    fn get_index(&self, index: u32) -> Option<Self::Key> {
+        self.internal.get_index(index)
+    }
+    #[inline(always)]
+    fn size(&self) -> u32 {
+        self.internal.size()
+    }
+    #[inline(always)]
+    fn add(&self, _key: Self::Key) {
+        unreachable!("readonly");
+    }
+    #[inline(always)]
+    fn has(&self, key: Self::Key) -> bool {
+        self.internal.has(key)
+    }
+    #[inline(always)]
+    fn clear(&self) {
+        unreachable!("readonly");
+    }
+    #[inline(always)]
+    fn delete(&self, _key: Self::Key) -> bool {
+        unreachable!("readonly");
+    }
+}
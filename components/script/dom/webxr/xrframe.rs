@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::rc::Rc;
 
 use dom_struct::dom_struct;
 use js::gc::CustomAutoRooterGuard;
@@ -15,13 +16,16 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::promise::Promise;
 use crate::dom::window::Window;
+use crate::dom::xranchorset::XRAnchorSet;
 use crate::dom::xrhittestresult::XRHitTestResult;
 use crate::dom::xrhittestsource::XRHitTestSource;
 use crate::dom::xrjointpose::XRJointPose;
 use crate::dom::xrjointspace::XRJointSpace;
 use crate::dom::xrpose::XRPose;
 use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::{ApiPose, XRSession};
 use crate::dom::xrspace::XRSpace;
 use crate::dom::xrviewerpose::XRViewerPose;
@@ -202,6 +206,24 @@ impl XRFrameMethods<crate::DomTypeHolder> for XRFrame {
             .collect()
     }
 
+    /// <https://immersive-web.github.io/anchors/#dom-xrframe-trackedanchors>
+    fn TrackedAnchors(&self) -> DomRoot<XRAnchorSet> {
+        self.session.tracked_anchors()
+    }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xrframe-createanchor>
+    fn CreateAnchor(&self, pose: &XRRigidTransform, space: &XRSpace, can_gc: CanGc) -> Rc<Promise> {
+        if !self.active.get() || self.session != space.session() {
+            let p = Promise::new(&self.global(), can_gc);
+            p.reject_error(Error::InvalidState, can_gc);
+            return p;
+        }
+        let native_pose = self
+            .get_pose(space)
+            .map(|space_pose| pose.transform().then(&space_pose));
+        self.session.create_anchor(native_pose, can_gc)
+    }
+
     #[allow(unsafe_code)]
     /// <https://www.w3.org/TR/webxr-hand-input-1/#dom-xrframe-filljointradii>
     fn FillJointRadii(
@@ -2,12 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::rc::Rc;
+
 use dom_struct::dom_struct;
 use webxr_api::HitTestResult;
 
+use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
 use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding::XRHitTestResultMethods;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::promise::Promise;
 use crate::dom::window::Window;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrpose::XRPose;
@@ -57,4 +61,11 @@ impl XRHitTestResultMethods<crate::DomTypeHolder> for XRHitTestResult {
             can_gc,
         ))
     }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xrhittestresult-createanchor>
+    fn CreateAnchor(&self, can_gc: CanGc) -> Rc<Promise> {
+        self.frame
+            .Session()
+            .create_anchor(Some(self.result.space.cast_unit()), can_gc)
+    }
 }
@@ -56,6 +56,8 @@ use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
+use crate::dom::xranchor::XRAnchor;
+use crate::dom::xranchorset::XRAnchorSet;
 use crate::dom::xrboundedreferencespace::XRBoundedReferenceSpace;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrhittestsource::XRHitTestSource;
@@ -114,6 +116,8 @@ pub(crate) struct XRSession {
     #[ignore_malloc_size_of = "promises are hard"]
     update_framerate_promise: DomRefCell<Option<Rc<Promise>>>,
     reference_spaces: DomRefCell<Vec<Dom<XRReferenceSpace>>>,
+    /// <https://immersive-web.github.io/anchors/#xrsession-set-of-tracked-anchors>
+    tracked_anchors: Dom<XRAnchorSet>,
 }
 
 impl XRSession {
@@ -121,6 +125,7 @@ impl XRSession {
         session: Session,
         render_state: &XRRenderState,
         input_sources: &XRInputSourceArray,
+        tracked_anchors: &XRAnchorSet,
         mode: XRSessionMode,
     ) -> XRSession {
         XRSession {
@@ -148,6 +153,7 @@ impl XRSession {
             framerate: Cell::new(0.0),
             update_framerate_promise: DomRefCell::new(None),
             reference_spaces: DomRefCell::new(Vec::new()),
+            tracked_anchors: Dom::from_ref(tracked_anchors),
         }
     }
 
@@ -165,11 +171,13 @@ impl XRSession {
         };
         let render_state = XRRenderState::new(window, 0.1, 1000.0, ivfov, None, Vec::new(), can_gc);
         let input_sources = XRInputSourceArray::new(window, can_gc);
+        let tracked_anchors = XRAnchorSet::new(window, can_gc);
         let ret = reflect_dom_object(
             Box::new(XRSession::new_inherited(
                 session,
                 &render_state,
                 &input_sources,
+                &tracked_anchors,
                 mode,
             )),
             window,
@@ -189,6 +197,51 @@ impl XRSession {
         self.ended.get()
     }
 
+    pub(crate) fn tracked_anchors(&self) -> DomRoot<XRAnchorSet> {
+        DomRoot::from_ref(&*self.tracked_anchors)
+    }
+
+    /// <https://immersive-web.github.io/anchors/#create-new-anchor-object>
+    ///
+    /// `pose` is the pose of the new anchor in native space. Backends do not track
+    /// anchors themselves, so anchors stay fixed relative to the native origin.
+    pub(crate) fn create_anchor(&self, pose: Option<ApiPose>, can_gc: CanGc) -> Rc<Promise> {
+        let p = Promise::new(&self.global(), can_gc);
+
+        if !self
+            .session
+            .borrow()
+            .granted_features()
+            .iter()
+            .any(|f| f == "anchors")
+        {
+            p.reject_error(Error::NotSupported, can_gc);
+            return p;
+        }
+
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState, can_gc);
+            return p;
+        }
+
+        let Some(pose) = pose else {
+            // The pose of the space the anchor was requested relative to is unknown
+            p.reject_error(Error::Operation, can_gc);
+            return p;
+        };
+
+        let anchor = XRAnchor::new(self.global().as_window(), self, pose, can_gc);
+        self.tracked_anchors.insert(&anchor);
+        p.resolve_native(&anchor, can_gc);
+        p
+    }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xranchor-delete>
+    pub(crate) fn delete_anchor(&self, anchor: &XRAnchor) {
+        anchor.set_deleted();
+        self.tracked_anchors.remove(anchor);
+    }
+
     pub(crate) fn is_immersive(&self) -> bool {
         self.mode != XRSessionMode::Inline
     }
@@ -921,6 +974,10 @@ impl XRSessionMethods<crate::DomTypeHolder> for XRSession {
             self.input_sources
                 .remove_input_source(self, InputId(source), can_gc);
         }
+        // https://immersive-web.github.io/anchors/#anchor-updates
+        for anchor in self.tracked_anchors.take() {
+            anchor.set_deleted();
+        }
         p
     }
 
@@ -24,6 +24,10 @@ pub(crate) struct XRSpace {
     input_source: MutNullableDom<XRInputSource>,
     /// If we're an input space, are we an aim space or a grip space?
     is_grip_space: bool,
+    /// If we're an anchor space, the pose of the anchor in native space
+    #[ignore_malloc_size_of = "defined in euclid"]
+    #[no_trace]
+    anchor_pose: Option<ApiPose>,
 }
 
 impl XRSpace {
@@ -33,6 +37,7 @@ impl XRSpace {
             session: Dom::from_ref(session),
             input_source: Default::default(),
             is_grip_space: false,
+            anchor_pose: None,
         }
     }
 
@@ -46,6 +51,7 @@ impl XRSpace {
             session: Dom::from_ref(session),
             input_source: MutNullableDom::new(Some(input)),
             is_grip_space,
+            anchor_pose: None,
         }
     }
 
@@ -63,11 +69,36 @@ impl XRSpace {
         )
     }
 
+    pub(crate) fn new_anchorspace(
+        global: &GlobalScope,
+        session: &XRSession,
+        pose: ApiPose,
+        can_gc: CanGc,
+    ) -> DomRoot<XRSpace> {
+        reflect_dom_object(
+            Box::new(XRSpace {
+                eventtarget: EventTarget::new_inherited(),
+                session: Dom::from_ref(session),
+                input_source: Default::default(),
+                is_grip_space: false,
+                anchor_pose: Some(pose),
+            }),
+            global,
+            can_gc,
+        )
+    }
+
     pub(crate) fn space(&self) -> Space {
         if let Some(rs) = self.downcast::<XRReferenceSpace>() {
             rs.space()
         } else if let Some(j) = self.downcast::<XRJointSpace>() {
             j.space()
+        } else if let Some(pose) = self.anchor_pose {
+            // The local space shares its origin with native space
+            Space {
+                base: BaseSpace::Local,
+                offset: pose.cast_unit(),
+            }
         } else if let Some(source) = self.input_source.get() {
             let base = if self.is_grip_space {
                 BaseSpace::Grip(source.id())
@@ -95,6 +126,9 @@ impl XRSpace {
             reference.get_pose(base_pose)
         } else if let Some(joint) = self.downcast::<XRJointSpace>() {
             joint.get_pose(base_pose)
+        } else if self.anchor_pose.is_some() {
+            // Anchors are static in native space
+            self.anchor_pose
         } else if let Some(source) = self.input_source.get() {
             // XXXManishearth we should be able to request frame information
             // for inputs when necessary instead of always loading it
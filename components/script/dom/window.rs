@@ -22,6 +22,7 @@ use base64::Engine;
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLChan;
 use compositing_traits::CrossProcessCompositorApi;
+use compositing_traits::display_list::ScrollAnimation;
 use constellation_traits::{
     DocumentState, LoadData, LoadOrigin, NavigationHistoryBehavior, ScriptToConstellationChan,
     ScriptToConstellationMessage, StructuredSerializedData, WindowSizeType,
@@ -32,9 +33,9 @@ use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarker
 use dom_struct::dom_struct;
 use embedder_traits::user_content_manager::{UserContentManager, UserScript};
 use embedder_traits::{
-    AlertResponse, ConfirmResponse, EmbedderMsg, GamepadEvent, GamepadSupportedHapticEffects,
-    GamepadUpdateType, PromptResponse, SimpleDialog, Theme, ViewportDetails, WebDriverJSError,
-    WebDriverJSResult,
+    AlertResponse, ConfirmResponse, EmbedderMsg, FilterPattern, GamepadEvent,
+    GamepadSupportedHapticEffects, GamepadUpdateType, PromptResponse, SimpleDialog, Theme,
+    UserAgentStylesheetOverride, ViewportDetails, WebDriverJSError, WebDriverJSResult,
 };
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect, Size2D as UntypedSize2D};
 use euclid::{Point2D, Scale, Size2D, Vector2D};
@@ -59,6 +60,7 @@ use layout_api::{
 use malloc_size_of::MallocSizeOf;
 use media::WindowGLContext;
 use net_traits::ResourceThreads;
+use net_traits::filemanager_thread::{FileManagerThreadMsg, FileSystemEntry, FileSystemResult};
 use net_traits::image_cache::{
     ImageCache, ImageCacheResponseMessage, ImageLoadListener, ImageResponse, PendingImageId,
     PendingImageResponse, RasterizationCompleteResponse,
@@ -87,29 +89,36 @@ use style::stylesheets::UrlExtraData;
 use style_traits::CSSPixel;
 use stylo_atoms::Atom;
 use url::Position;
+use uuid::Uuid;
 use webrender_api::ExternalScrollId;
 use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutPixel};
 
 use super::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
 use super::bindings::trace::HashMapTracedValues;
+use crate::dom::abstractrange::bp_position;
 use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState, NamedPropertyValue,
 };
+use crate::dom::bindings::codegen::Bindings::FileSystemAccessBinding::{
+    FilePickerOptions, OpenFilePickerOptions, SaveFilePickerOptions,
+};
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::History_Binding::HistoryMethods;
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryList_Binding::MediaQueryListMethods;
-use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::Report;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     self, FrameRequestCallback, ScrollBehavior, ScrollToOptions, WindowMethods,
     WindowPostMessageOptions,
 };
-use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
+use crate::dom::bindings::codegen::UnionTypes::{
+    RequestOrUSVString, StringOrFunction, USVStringOrUSVStringSequence,
+};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::num::Finite;
@@ -123,6 +132,7 @@ use crate::dom::bindings::utils::GlobalStaticData;
 use crate::dom::bindings::weakref::DOMTracker;
 #[cfg(feature = "bluetooth")]
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::cachestorage::CacheStorage;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -130,10 +140,13 @@ use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::filesystemfilehandle::FileSystemFileHandle;
+use crate::dom::filesystemhandle::{file_system_error, file_system_origin, send_to_file_manager};
 use crate::dom::gamepad::{Gamepad, contains_user_gesture};
 use crate::dom::gamepadevent::GamepadEventType;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
+use crate::dom::highlightregistry::HighlightRegistry;
 use crate::dom::history::History;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
@@ -142,13 +155,16 @@ use crate::dom::location::Location;
 use crate::dom::medialist::MediaList;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{Node, NodeDamage, NodeTraits, from_untrusted_node_address};
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
-use crate::dom::reportingendpoint::{ReportingEndpoint, SendReportsToEndpoints};
+use crate::dom::report::ReportData;
+use crate::dom::reportingendpoint::ReportingEndpoint;
 use crate::dom::reportingobserver::ReportingObserver;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
 use crate::dom::shadowroot::ShadowRoot;
@@ -163,10 +179,12 @@ use crate::dom::webgpu::identityhub::IdentityHub;
 use crate::dom::windowproxy::{WindowProxy, WindowProxyHandler};
 use crate::dom::worklet::Worklet;
 use crate::dom::workletglobalscope::WorkletGlobalScopeType;
+use crate::find_in_page;
 use crate::layout_image::fetch_image_for_layout;
 use crate::messaging::{MainThreadScriptMsg, ScriptEventLoopReceiver, ScriptEventLoopSender};
 use crate::microtask::MicrotaskQueue;
 use crate::realms::{InRealm, enter_realm};
+use crate::routed_promise::{RoutedPromiseListener, route_promise};
 use crate::script_runtime::{CanGc, JSContext, Runtime};
 use crate::script_thread::ScriptThread;
 use crate::timers::{IsInterval, TimerCallback};
@@ -195,6 +213,10 @@ enum WindowState {
 /// assuming that `<body>` take this long to parse.
 const INITIAL_REFLOW_DELAY: Duration = Duration::from_millis(200);
 
+/// How long a [`Window`] has transient activation after the user interacted with it. See
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>.
+const TRANSIENT_ACTIVATION_DURATION: time::Duration = time::Duration::seconds(5);
+
 /// During loading and parsing, layouts are suppressed to avoid flashing incomplete page
 /// contents.
 ///
@@ -253,10 +275,14 @@ pub(crate) struct Window {
     location: MutNullableDom<Location>,
     history: MutNullableDom<History>,
     indexeddb: MutNullableDom<IDBFactory>,
+    caches: MutNullableDom<CacheStorage>,
     custom_element_registry: MutNullableDom<CustomElementRegistry>,
     performance: MutNullableDom<Performance>,
     #[no_trace]
     navigation_start: Cell<CrossProcessInstant>,
+    /// <https://html.spec.whatwg.org/multipage/#last-activation-timestamp>
+    #[no_trace]
+    last_activation_timestamp: Cell<Option<CrossProcessInstant>>,
     screen: MutNullableDom<Screen>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
@@ -278,6 +304,15 @@ pub(crate) struct Window {
     #[no_trace]
     theme: Cell<Theme>,
 
+    /// The stylesheet that the embedder applies at the user agent origin of the documents
+    /// of this `WebView`, if any.
+    #[no_trace]
+    user_agent_stylesheet_override: DomRefCell<Option<UserAgentStylesheetOverride>>,
+
+    /// The stylesheets that the embedder applies at the user origin of the documents of
+    /// this `WebView`.
+    user_stylesheets: DomRefCell<Vec<String>>,
+
     /// Parent id associated with this page, if any.
     #[no_trace]
     parent_info: Option<PipelineId>,
@@ -355,6 +390,10 @@ pub(crate) struct Window {
     pending_images_for_rasterization:
         DomRefCell<HashMapTracedValues<PendingImageRasterizationKey, Vec<Dom<Node>>>>,
 
+    /// The `MediaSource` objects that object URLs were created for, by the ID of their
+    /// URL. <https://w3c.github.io/media-source/#dom-url-createobjecturl>
+    media_source_urls: DomRefCell<HashMapTracedValues<Uuid, Dom<MediaSource>>>,
+
     /// Directory to store unminified css for this window if unminify-css
     /// opt is enabled.
     unminified_css_dir: DomRefCell<Option<String>>,
@@ -366,6 +405,8 @@ pub(crate) struct Window {
     test_worklet: MutNullableDom<Worklet>,
     /// <https://drafts.css-houdini.org/css-paint-api-1/#paint-worklet>
     paint_worklet: MutNullableDom<Worklet>,
+    /// <https://drafts.csswg.org/css-highlight-api-1/#highlight-registry>
+    highlight_registry: MutNullableDom<HighlightRegistry>,
 
     /// Flag to identify whether mutation observers are present(true)/absent(false)
     exists_mut_observer: Cell<bool>,
@@ -393,6 +434,11 @@ pub(crate) struct Window {
 
     throttled: Cell<bool>,
 
+    /// The time at which the `ScriptThread` started updating the rendering of this window,
+    /// while it is doing so.
+    #[ignore_malloc_size_of = "Defined in std::time"]
+    rendering_update_start: Cell<Option<Instant>>,
+
     /// A shared marker for the validity of any cached layout values. A value of true
     /// indicates that any such values remain valid; any new layout that invalidates
     /// those values will cause the marker to be set to false.
@@ -405,8 +451,13 @@ pub(crate) struct Window {
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-registered-reporting-observer-list>
     reporting_observer_list: DomRefCell<Vec<DomRoot<ReportingObserver>>>,
 
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-report-buffer>
+    #[no_trace]
+    report_buffer: DomRefCell<Vec<ReportData>>,
+
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-reports>
-    report_list: DomRefCell<Vec<Report>>,
+    #[no_trace]
+    report_list: DomRefCell<Vec<ReportData>>,
 
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-endpoints>
     #[no_trace]
@@ -536,27 +587,28 @@ impl Window {
         self.reporting_observer_list.borrow().clone()
     }
 
-    pub(crate) fn append_report(&self, report: Report) {
-        self.report_list.borrow_mut().push(report);
-        let trusted_window = Trusted::new(self);
-        self.upcast::<GlobalScope>()
-            .task_manager()
-            .dom_manipulation_task_source()
-            .queue(task!(send_to_reporting_endpoints: move || {
-                let window = trusted_window.root();
-                let reports = std::mem::take(&mut *window.report_list.borrow_mut());
-                window.upcast::<GlobalScope>().send_reports_to_endpoints(
-                    reports,
-                    window.endpoints_list.borrow().clone(),
-                );
-            }));
+    pub(crate) fn with_reports<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
+        f(&mut self.report_list.borrow_mut())
     }
 
-    pub(crate) fn buffered_reports(&self) -> Vec<Report> {
-        self.report_list.borrow().clone()
+    pub(crate) fn with_report_buffer<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
+        f(&mut self.report_buffer.borrow_mut())
+    }
+
+    pub(crate) fn with_reporting_endpoints<R>(
+        &self,
+        f: impl FnOnce(&mut Vec<ReportingEndpoint>) -> R,
+    ) -> R {
+        f(&mut self.endpoints_list.borrow_mut())
     }
 
     pub(crate) fn set_endpoints_list(&self, endpoints: Vec<ReportingEndpoint>) {
+        let crash_report = ReportingEndpoint::crash_report(
+            &endpoints,
+            &self.get_url(),
+            self.origin().immutable().clone(),
+        );
+        self.send_to_constellation(ScriptToConstellationMessage::SetCrashReport(crash_report));
         *self.endpoints_list.borrow_mut() = endpoints;
     }
 
@@ -604,6 +656,24 @@ impl Window {
         self.webxr_registry.clone()
     }
 
+    pub(crate) fn add_media_source_url(&self, id: Uuid, media_source: &MediaSource) {
+        self.media_source_urls
+            .borrow_mut()
+            .insert(id, Dom::from_ref(media_source));
+    }
+
+    /// Returns the `MediaSource` that the object URL with the given ID was created for.
+    pub(crate) fn media_source_for_url(&self, id: &Uuid) -> Option<DomRoot<MediaSource>> {
+        self.media_source_urls
+            .borrow()
+            .get(id)
+            .map(|media_source| DomRoot::from_ref(&**media_source))
+    }
+
+    pub(crate) fn revoke_media_source_url(&self, id: &Uuid) -> bool {
+        self.media_source_urls.borrow_mut().remove(id).is_some()
+    }
+
     fn new_paint_worklet(&self, can_gc: CanGc) -> DomRoot<Worklet> {
         debug!("Creating new paint worklet.");
         Worklet::new(self, WorkletGlobalScopeType::Paint, can_gc)
@@ -733,10 +803,9 @@ impl Window {
     /// <https://www.w3.org/TR/gamepad/#dfn-gamepadconnected>
     fn handle_gamepad_connect(
         &self,
-        // As the spec actually defines how to set the gamepad index, the GilRs index
-        // is currently unused, though in practice it will almost always be the same.
-        // More infra is currently needed to track gamepads across windows.
-        _index: usize,
+        // The spec defines how to select the gamepad index, so the index of the gamepad
+        // in the system is only used to identify it in later events.
+        index: usize,
         name: String,
         axis_bounds: (f64, f64),
         button_bounds: (f64, f64),
@@ -752,10 +821,20 @@ impl Window {
                 let window = this.root();
 
                 let navigator = window.Navigator();
+                // The embedder may report a gamepad again, for instance when this webview
+                // becomes active, so ignore gamepads that are already connected.
+                if navigator.gamepad_with_id(index as u32).is_some() {
+                    return;
+                }
                 let selected_index = navigator.select_gamepad_index();
+                // Embedders remap the buttons and axes of gamepads to the standard layout
+                // before reporting their inputs, and `Gamepad` normalizes their values by the
+                // reported bounds. The gamepads of WebXR input sources are not reported here:
+                // `XRInputSource` creates them with the "xr-standard" mapping and keeps them out
+                // of `getGamepads()`.
                 let gamepad = Gamepad::new(
                     &window,
-                    selected_index,
+                    index as u32,
                     name,
                     "standard".into(),
                     axis_bounds,
@@ -764,6 +843,7 @@ impl Window {
                     false,
                     CanGc::note(),
                 );
+                gamepad.update_index(selected_index as i32);
                 navigator.set_gamepad(selected_index as usize, &gamepad, CanGc::note());
             }));
     }
@@ -777,10 +857,10 @@ impl Window {
             .queue(task!(gamepad_disconnected: move || {
                 let window = this.root();
                 let navigator = window.Navigator();
-                if let Some(gamepad) = navigator.get_gamepad(index) {
+                if let Some(gamepad) = navigator.gamepad_with_id(index as u32) {
                     if window.Document().is_fully_active() {
                         gamepad.update_connected(false, gamepad.exposed(), CanGc::note());
-                        navigator.remove_gamepad(index);
+                        navigator.remove_gamepad(gamepad.index() as usize);
                     }
                 }
             }));
@@ -795,7 +875,7 @@ impl Window {
                 task!(update_gamepad_state: move || {
                     let window = this.root();
                     let navigator = window.Navigator();
-                    if let Some(gamepad) = navigator.get_gamepad(index) {
+                    if let Some(gamepad) = navigator.gamepad_with_id(index as u32) {
                         let current_time = window.Performance().Now();
                         gamepad.update_timestamp(*current_time);
                         match update_type {
@@ -1145,6 +1225,12 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         })
     }
 
+    // https://w3c.github.io/ServiceWorker/#global-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches
+            .or_init(|| CacheStorage::new(self.upcast::<GlobalScope>(), CanGc::note()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-customelements
     fn CustomElements(&self) -> DomRoot<CustomElementRegistry> {
         self.custom_element_registry
@@ -1173,6 +1259,11 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         self.as_global_scope().crypto(CanGc::note())
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.as_global_scope().scheduler(CanGc::note())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-frameelement
     fn GetFrameElement(&self) -> Option<DomRoot<Element>> {
         // Steps 1-3.
@@ -1795,6 +1886,71 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
             .and_then(|d| d.GetSelection(CanGc::note()))
     }
 
+    /// Non-standard, see <https://developer.mozilla.org/en-US/docs/Web/API/Window/find>.
+    /// Searching in frames and showing a dialog aren't supported.
+    fn Find(
+        &self,
+        string: DOMString,
+        case_sensitive: bool,
+        backwards: bool,
+        wrap_around: bool,
+        whole_word: bool,
+        _search_in_frames: bool,
+        _show_dialog: bool,
+        can_gc: CanGc,
+    ) -> bool {
+        let document = self.Document();
+        let Some(selection) = document.GetSelection(can_gc) else {
+            return false;
+        };
+        let matches: Vec<_> = find_in_page::find_text(&document, &string, case_sensitive, can_gc)
+            .into_iter()
+            .filter(|range| !whole_word || find_in_page::is_whole_word(range))
+            .collect();
+
+        // The search continues from the selection: after its end, or before its start when
+        // searching backwards.
+        let current = selection.GetRangeAt(0).ok();
+        let found = if backwards {
+            matches
+                .iter()
+                .rev()
+                .find(|range| {
+                    current.as_ref().is_none_or(|current| {
+                        bp_position(
+                            &range.end_container(),
+                            range.end_offset(),
+                            &current.start_container(),
+                            current.start_offset(),
+                        ) != Some(cmp::Ordering::Greater)
+                    })
+                })
+                .or(matches.last().filter(|_| wrap_around))
+        } else {
+            matches
+                .iter()
+                .find(|range| {
+                    current.as_ref().is_none_or(|current| {
+                        bp_position(
+                            &range.start_container(),
+                            range.start_offset(),
+                            &current.end_container(),
+                            current.end_offset(),
+                        ) != Some(cmp::Ordering::Less)
+                    })
+                })
+                .or(matches.first().filter(|_| wrap_around))
+        };
+        let Some(found) = found else {
+            return false;
+        };
+
+        selection.RemoveAllRanges();
+        selection.AddRange(found);
+        find_in_page::scroll_range_into_view(found, can_gc);
+        true
+    }
+
     // https://dom.spec.whatwg.org/#dom-window-event
     #[allow(unsafe_code)]
     fn Event(&self, cx: JSContext, rval: MutableHandleValue) {
@@ -1974,6 +2130,110 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         self.trusted_types
             .or_init(|| TrustedTypePolicyFactory::new(self.as_global_scope(), can_gc))
     }
+
+    /// <https://wicg.github.io/file-system-access/#dom-window-showopenfilepicker>
+    fn ShowOpenFilePicker(&self, options: &OpenFilePickerOptions, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(self.as_global_scope(), can_gc);
+        let filters = match self.prepare_file_picker(&options.parent) {
+            Ok(filters) => filters,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Let entries be a list of file system entries that the user picked, and resolve
+        // p with a new FileSystemFileHandle for each of them.
+        let sender = route_promise(
+            &promise,
+            self,
+            self.as_global_scope()
+                .task_manager()
+                .dom_manipulation_task_source(),
+        );
+        let msg = FileManagerThreadMsg::ShowOpenFilePicker(
+            self.webview_id(),
+            options.multiple,
+            filters,
+            sender,
+            file_system_origin(self.as_global_scope()),
+        );
+        if let Err(error) = send_to_file_manager(self.as_global_scope(), msg) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+
+    /// <https://wicg.github.io/file-system-access/#dom-window-showsavefilepicker>
+    fn ShowSaveFilePicker(&self, options: &SaveFilePickerOptions, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(self.as_global_scope(), can_gc);
+        let filters = match self.prepare_file_picker(&options.parent) {
+            Ok(filters) => filters,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Let entry be the file system entry that the user picked, which is created empty
+        // if it does not exist, and resolve p with a new FileSystemFileHandle for it.
+        let sender = route_promise(
+            &promise,
+            self,
+            self.as_global_scope()
+                .task_manager()
+                .dom_manipulation_task_source(),
+        );
+        let suggested_name = options.suggestedName.clone().flatten().map(|name| name.0);
+        let msg = FileManagerThreadMsg::ShowSaveFilePicker(
+            self.webview_id(),
+            suggested_name,
+            filters,
+            sender,
+            file_system_origin(self.as_global_scope()),
+        );
+        if let Err(error) = send_to_file_manager(self.as_global_scope(), msg) {
+            promise.reject_error(error, can_gc);
+        }
+        promise
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<Vec<FileSystemEntry>>> for Window {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<Vec<FileSystemEntry>>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(entries) => {
+                let handles: Vec<_> = entries
+                    .into_iter()
+                    .map(|entry| FileSystemFileHandle::new(self.as_global_scope(), entry, can_gc))
+                    .collect();
+                promise.resolve_native(&handles, can_gc);
+            },
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
+}
+
+impl RoutedPromiseListener<FileSystemResult<FileSystemEntry>> for Window {
+    fn handle_response(
+        &self,
+        response: FileSystemResult<FileSystemEntry>,
+        promise: &Rc<Promise>,
+        can_gc: CanGc,
+    ) {
+        match response {
+            Ok(entry) => {
+                let handle = FileSystemFileHandle::new(self.as_global_scope(), entry, can_gc);
+                promise.resolve_native(&handle, can_gc);
+            },
+            Err(error) => promise.reject_error(file_system_error(error), can_gc),
+        }
+    }
 }
 
 impl Window {
@@ -2042,6 +2302,16 @@ impl Window {
             .or_init(|| self.new_paint_worklet(CanGc::note()))
     }
 
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-css-highlights>
+    pub(crate) fn highlight_registry(&self) -> DomRoot<HighlightRegistry> {
+        self.highlight_registry
+            .or_init(|| HighlightRegistry::new(self, CanGc::note()))
+    }
+
+    pub(crate) fn highlight_registry_if_created(&self) -> Option<DomRoot<HighlightRegistry>> {
+        self.highlight_registry.get()
+    }
+
     pub(crate) fn has_document(&self) -> bool {
         self.document.get().is_some()
     }
@@ -2128,15 +2398,20 @@ impl Window {
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
+        behavior: ScrollBehavior,
         _element: Option<&Element>,
         can_gc: CanGc,
     ) {
         // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
+        // Smooth scrolls are animated by the renderer, while layout moves the node to its
+        // final offset right away.
+        // TODO: Take the computed `scroll-behavior` of the element into account for `auto`.
+        let animation = match behavior {
+            ScrollBehavior::Smooth => ScrollAnimation::Smooth,
+            ScrollBehavior::Auto | ScrollBehavior::Instant => ScrollAnimation::Instant,
+        };
         self.reflow(
-            ReflowGoal::UpdateScrollNode(scroll_id, Vector2D::new(x, y)),
+            ReflowGoal::UpdateScrollNode(scroll_id, Vector2D::new(x, y), animation),
             can_gc,
         );
     }
@@ -2232,6 +2507,11 @@ impl Window {
             node_to_animating_image_map: document.image_animation_manager().node_to_image_map(),
             theme: self.theme.get(),
             highlighted_dom_node: document.highlighted_dom_node().map(|node| node.to_opaque()),
+            script_time: self
+                .rendering_update_start
+                .get()
+                .map(|start| start.elapsed())
+                .unwrap_or_default(),
         };
 
         let Some(results) = self.layout.borrow_mut().reflow(reflow) else {
@@ -2446,6 +2726,36 @@ impl Window {
         self.content_box_query_unchecked(node)
     }
 
+    /// The rectangle of the caret in the text of the text control `node`, relative to the
+    /// initial containing block.
+    pub(crate) fn text_cursor_rect_query(
+        &self,
+        node: &Node,
+        can_gc: CanGc,
+    ) -> Option<UntypedRect<Au>> {
+        self.layout_reflow(QueryMsg::TextCursorRectQuery, can_gc);
+        self.layout
+            .borrow()
+            .query_text_cursor_rect(node.to_trusted_node_address())
+    }
+
+    /// The rendered text of the part of the subtree of `root` between the boundary points
+    /// `start` and `end`, see [`Range::rendered_text`](crate::dom::range::Range::rendered_text).
+    pub(crate) fn rendered_text_of_range_query(
+        &self,
+        root: &Node,
+        start: (&Node, u32),
+        end: (&Node, u32),
+        can_gc: CanGc,
+    ) -> String {
+        self.layout_reflow(QueryMsg::RenderedTextOfRangeQuery, can_gc);
+        self.layout.borrow().query_rendered_text_of_range(
+            root.to_trusted_node_address(),
+            (start.0.to_trusted_node_address(), start.1 as usize),
+            (end.0.to_trusted_node_address(), end.1 as usize),
+        )
+    }
+
     pub(crate) fn content_boxes_query(&self, node: &Node, can_gc: CanGc) -> Vec<UntypedRect<Au>> {
         self.layout_reflow(QueryMsg::ContentBoxes, can_gc);
         self.layout
@@ -2588,6 +2898,20 @@ impl Window {
             .query_text_indext(node.to_opaque(), point_in_node)
     }
 
+    /// Find the node and the UTF-8 offset within its text of the caret position closest
+    /// to `point`, which is relative to the viewport.
+    #[allow(unsafe_code)]
+    pub(crate) fn caret_position_query(
+        &self,
+        point: UntypedPoint2D<f32>,
+        can_gc: CanGc,
+    ) -> Option<(DomRoot<Node>, usize)> {
+        self.layout_reflow(QueryMsg::CaretPositionQuery, can_gc);
+        let (address, offset) = self.layout.borrow().query_caret_position(point)?;
+        let node = unsafe { from_untrusted_node_address(address) };
+        Some((node, offset))
+    }
+
     #[allow(unsafe_code)]
     pub(crate) fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -2730,6 +3054,47 @@ impl Window {
             .add_restyle_reason(RestyleReason::ThemeChanged);
     }
 
+    /// Get the stylesheet that the embedder applies at the user agent origin of the
+    /// documents of this `WebView`, if any.
+    pub(crate) fn user_agent_stylesheet_override(&self) -> Option<UserAgentStylesheetOverride> {
+        self.user_agent_stylesheet_override.borrow().clone()
+    }
+
+    /// Replace the stylesheet that the embedder applies at the user agent origin, restyling
+    /// the document with it on the next reflow.
+    pub(crate) fn handle_user_agent_stylesheet_override(
+        &self,
+        stylesheet_override: Option<UserAgentStylesheetOverride>,
+    ) {
+        if *self.user_agent_stylesheet_override.borrow() == stylesheet_override {
+            return;
+        }
+        self.layout
+            .borrow_mut()
+            .set_user_agent_stylesheet_override(stylesheet_override.clone());
+        *self.user_agent_stylesheet_override.borrow_mut() = stylesheet_override;
+        self.Document().invalidate_stylesheets();
+    }
+
+    /// Get the stylesheets that the embedder applies at the user origin of the documents
+    /// of this `WebView`.
+    pub(crate) fn user_stylesheets(&self) -> Vec<String> {
+        self.user_stylesheets.borrow().clone()
+    }
+
+    /// Replace the stylesheets that the embedder applies at the user origin, restyling the
+    /// document with them on the next reflow.
+    pub(crate) fn handle_user_stylesheets(&self, user_stylesheets: Vec<String>) {
+        if *self.user_stylesheets.borrow() == user_stylesheets {
+            return;
+        }
+        self.layout
+            .borrow_mut()
+            .set_user_stylesheets(user_stylesheets.clone());
+        *self.user_stylesheets.borrow_mut() = user_stylesheets;
+        self.Document().invalidate_stylesheets();
+    }
+
     pub(crate) fn get_url(&self) -> ServoUrl {
         self.Document().url()
     }
@@ -2935,6 +3300,24 @@ impl Window {
         self.throttled.get()
     }
 
+    /// Set whether to run timers at an even more limited rate than throttled ones, because
+    /// the `WebView` of this window has been hidden for a long time.
+    pub(crate) fn set_timers_intensively_throttled(&self, intensively_throttled: bool) {
+        if intensively_throttled {
+            self.as_global_scope().slow_down_timers_intensively();
+        } else if self.throttled() {
+            self.as_global_scope().slow_down_timers();
+        } else {
+            self.as_global_scope().speed_up_timers();
+        }
+    }
+
+    /// Note whether the `ScriptThread` is updating the rendering of this window, so that
+    /// the time spent running script before layout can be reported with the next frame.
+    pub(crate) fn set_updating_the_rendering(&self, updating: bool) {
+        self.rendering_update_start.set(updating.then(Instant::now));
+    }
+
     pub(crate) fn unminified_css_dir(&self) -> Option<String> {
         self.unminified_css_dir.borrow().clone()
     }
@@ -2947,6 +3330,73 @@ impl Window {
         self.navigation_start.set(CrossProcessInstant::now());
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    pub(crate) fn notify_activation(&self) {
+        // TODO: Also update the last activation timestamp of the windows of ancestor
+        // navigables and of same origin descendant navigables.
+        self.last_activation_timestamp
+            .set(Some(CrossProcessInstant::now()));
+    }
+
+    /// The steps shared by `showOpenFilePicker()` and `showSaveFilePicker()`, which check
+    /// that a picker may be shown and return the file name extensions that it accepts.
+    ///
+    /// <https://wicg.github.io/file-system-access/#process-accept-types>
+    fn prepare_file_picker(&self, options: &FilePickerOptions) -> Fallible<Vec<FilterPattern>> {
+        // If environment's origin is an opaque origin, return a promise rejected with a
+        // "SecurityError" DOMException.
+        if !self.origin().immutable().is_tuple() {
+            return Err(Error::Security);
+        }
+
+        // If relevantGlobalObject does not have transient activation, return a promise
+        // rejected with a "SecurityError" DOMException.
+        if !self.has_transient_activation() {
+            return Err(Error::Security);
+        }
+
+        // For each type of options["types"], for each typeSuffixes of type["accept"]'s
+        // values, for each typeSuffix of typeSuffixes: if typeSuffix does not start with
+        // ".", throw a TypeError.
+        let mut filters = vec![];
+        for accept_type in options.types.iter().flatten() {
+            for suffixes in accept_type.accept.iter().flat_map(|accept| accept.values()) {
+                let suffixes = match suffixes {
+                    USVStringOrUSVStringSequence::USVString(suffix) => vec![suffix.clone()],
+                    USVStringOrUSVStringSequence::USVStringSequence(suffixes) => suffixes.clone(),
+                };
+                for suffix in suffixes {
+                    let Some(extension) = suffix.0.strip_prefix('.') else {
+                        return Err(Error::Type(format!(
+                            "{:?} is not a valid file name extension",
+                            suffix.0
+                        )));
+                    };
+                    filters.push(FilterPattern(extension.to_owned()));
+                }
+            }
+        }
+
+        // If accepts options is empty and excludeAcceptAllOption is true, throw a
+        // TypeError.
+        if filters.is_empty() && options.excludeAcceptAllOption {
+            return Err(Error::Type(
+                "The picker would not accept any files".to_owned(),
+            ));
+        }
+
+        Ok(filters)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    pub(crate) fn has_transient_activation(&self) -> bool {
+        self.last_activation_timestamp
+            .get()
+            .is_some_and(|timestamp| {
+                CrossProcessInstant::now() - timestamp < TRANSIENT_ACTIVATION_DURATION
+            })
+    }
+
     pub(crate) fn send_to_embedder(&self, msg: EmbedderMsg) {
         self.send_to_constellation(ScriptToConstellationMessage::ForwardToEmbedder(msg));
     }
@@ -3065,6 +3515,8 @@ impl Window {
         #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
         inherited_secure_context: Option<bool>,
         theme: Theme,
+        user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+        user_stylesheets: Vec<String>,
     ) -> DomRoot<Self> {
         let error_reporter = CSSErrorReporter {
             pipelineid: pipeline_id,
@@ -3103,11 +3555,13 @@ impl Window {
             location: Default::default(),
             history: Default::default(),
             indexeddb: Default::default(),
+            caches: Default::default(),
             custom_element_registry: Default::default(),
             window_proxy: Default::default(),
             document: Default::default(),
             performance: Default::default(),
             navigation_start: Cell::new(navigation_start),
+            last_activation_timestamp: Default::default(),
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
@@ -3137,10 +3591,12 @@ impl Window {
             pending_image_callbacks: Default::default(),
             pending_layout_images: Default::default(),
             pending_images_for_rasterization: Default::default(),
+            media_source_urls: Default::default(),
             unminified_css_dir: Default::default(),
             local_script_source,
             test_worklet: Default::default(),
             paint_worklet: Default::default(),
+            highlight_registry: Default::default(),
             exists_mut_observer: Cell::new(false),
             compositor_api,
             has_sent_idle_message: Cell::new(false),
@@ -3148,11 +3604,15 @@ impl Window {
             user_content_manager,
             player_context,
             throttled: Cell::new(false),
+            rendering_update_start: Default::default(),
             layout_marker: DomRefCell::new(Rc::new(Cell::new(true))),
             current_event: DomRefCell::new(None),
             theme: Cell::new(theme),
+            user_agent_stylesheet_override: DomRefCell::new(user_agent_stylesheet_override),
+            user_stylesheets: DomRefCell::new(user_stylesheets),
             trusted_types: Default::default(),
             reporting_observer_list: Default::default(),
+            report_buffer: Default::default(),
             report_list: Default::default(),
             endpoints_list: Default::default(),
         });
@@ -334,6 +334,8 @@ impl WindowProxy {
             // Use the current `WebView`'s theme initially, but the embedder may
             // change this later.
             theme: window.theme(),
+            user_agent_stylesheet_override: window.user_agent_stylesheet_override(),
+            user_stylesheets: window.user_stylesheets(),
         };
         ScriptThread::process_attach_layout(new_layout_info, document.origin().clone());
         let new_window_proxy = ScriptThread::find_document(response.new_pipeline_id)
@@ -248,6 +248,7 @@ impl WorkerMethods<crate::DomTypeHolder> for Worker {
             worker_options.type_,
             closing.clone(),
             global.image_cache(),
+            global.webgl_chan(),
             browsing_context,
             #[cfg(feature = "webgpu")]
             global.wgpu_id_hub(),
@@ -24,7 +24,7 @@ use net_traits::IpcSend;
 use net_traits::policy_container::PolicyContainer;
 use net_traits::request::{
     CredentialsMode, Destination, InsecureRequestsPolicy, ParserMetadata,
-    RequestBuilder as NetRequestInit,
+    RequestBuilder as NetRequestInit, ServiceWorkersMode,
 };
 use profile_traits::mem::{ProcessReports, perform_memory_report};
 use servo_url::{MutableOrigin, ServoUrl};
@@ -36,7 +36,6 @@ use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
-use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::Report;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
@@ -46,20 +45,23 @@ use crate::dom::bindings::codegen::UnionTypes::{
 };
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible, report_pending_exception};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::AutoEntryScript;
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cachestorage::CacheStorage;
 use crate::dom::crypto::Crypto;
 use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::idbfactory::IDBFactory;
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
-use crate::dom::reportingendpoint::{ReportingEndpoint, SendReportsToEndpoints};
+use crate::dom::report::ReportData;
+use crate::dom::reportingendpoint::ReportingEndpoint;
 use crate::dom::reportingobserver::ReportingObserver;
+use crate::dom::scheduler::Scheduler;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::dom::trustedscripturl::TrustedScriptURL;
 use crate::dom::trustedtypepolicyfactory::TrustedTypePolicyFactory;
 use crate::dom::types::ImageBitmap;
@@ -134,6 +136,7 @@ pub(crate) struct WorkerGlobalScope {
     navigation_start: CrossProcessInstant,
     performance: MutNullableDom<Performance>,
     indexeddb: MutNullableDom<IDBFactory>,
+    caches: MutNullableDom<CacheStorage>,
     trusted_types: MutNullableDom<TrustedTypePolicyFactory>,
 
     /// A [`TimerScheduler`] used to schedule timers for this [`WorkerGlobalScope`].
@@ -147,8 +150,13 @@ pub(crate) struct WorkerGlobalScope {
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-registered-reporting-observer-list>
     reporting_observer_list: DomRefCell<Vec<DomRoot<ReportingObserver>>>,
 
+    /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-report-buffer>
+    #[no_trace]
+    report_buffer: DomRefCell<Vec<ReportData>>,
+
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-reports>
-    report_list: DomRefCell<Vec<Report>>,
+    #[no_trace]
+    report_list: DomRefCell<Vec<ReportData>>,
 
     /// <https://w3c.github.io/reporting/#windoworworkerglobalscope-endpoints>
     #[no_trace]
@@ -207,10 +215,12 @@ impl WorkerGlobalScope {
             navigation_start: CrossProcessInstant::now(),
             performance: Default::default(),
             indexeddb: Default::default(),
+            caches: Default::default(),
             timer_scheduler: RefCell::default(),
             insecure_requests_policy,
             trusted_types: Default::default(),
             reporting_observer_list: Default::default(),
+            report_buffer: Default::default(),
             report_list: Default::default(),
             endpoints_list: Default::default(),
         }
@@ -297,24 +307,19 @@ impl WorkerGlobalScope {
         self.reporting_observer_list.borrow().clone()
     }
 
-    pub(crate) fn append_report(&self, report: Report) {
-        self.report_list.borrow_mut().push(report);
-        let trusted_worker = Trusted::new(self);
-        self.upcast::<GlobalScope>()
-            .task_manager()
-            .dom_manipulation_task_source()
-            .queue(task!(send_to_reporting_endpoints: move || {
-                let worker = trusted_worker.root();
-                let reports = std::mem::take(&mut *worker.report_list.borrow_mut());
-                worker.upcast::<GlobalScope>().send_reports_to_endpoints(
-                    reports,
-                    worker.endpoints_list.borrow().clone(),
-                );
-            }));
+    pub(crate) fn with_reports<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
+        f(&mut self.report_list.borrow_mut())
     }
 
-    pub(crate) fn buffered_reports(&self) -> Vec<Report> {
-        self.report_list.borrow().clone()
+    pub(crate) fn with_report_buffer<R>(&self, f: impl FnOnce(&mut Vec<ReportData>) -> R) -> R {
+        f(&mut self.report_buffer.borrow_mut())
+    }
+
+    pub(crate) fn with_reporting_endpoints<R>(
+        &self,
+        f: impl FnOnce(&mut Vec<ReportingEndpoint>) -> R,
+    ) -> R {
+        f(&mut self.endpoints_list.borrow_mut())
     }
 
     pub(crate) fn set_endpoints_list(&self, endpoints: Option<Vec<ReportingEndpoint>>) {
@@ -351,6 +356,12 @@ impl WorkerGlobalScopeMethods<crate::DomTypeHolder> for WorkerGlobalScope {
         })
     }
 
+    // https://w3c.github.io/ServiceWorker/#global-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.caches
+            .or_init(|| CacheStorage::new(self.upcast::<GlobalScope>(), CanGc::note()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-location
     fn Location(&self) -> DomRoot<WorkerLocation> {
         self.location
@@ -406,6 +417,12 @@ impl WorkerGlobalScopeMethods<crate::DomTypeHolder> for WorkerGlobalScope {
                 global_scope.has_trustworthy_ancestor_or_current_origin(),
             )
             .pipeline_id(Some(self.upcast::<GlobalScope>().pipeline_id()));
+            // Scripts imported by a service worker must not be handled by a service worker.
+            let request = if self.is::<ServiceWorkerGlobalScope>() {
+                request.service_workers_mode(ServiceWorkersMode::None)
+            } else {
+                request
+            };
 
             let (url, source) = match fetch::load_whole_resource(
                 request,
@@ -456,6 +473,11 @@ impl WorkerGlobalScopeMethods<crate::DomTypeHolder> for WorkerGlobalScope {
         self.upcast::<GlobalScope>().crypto(CanGc::note())
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.upcast::<GlobalScope>().scheduler(CanGc::note())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
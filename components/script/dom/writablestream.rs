@@ -168,7 +168,7 @@ pub struct WritableStream {
 impl WritableStream {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     /// <https://streams.spec.whatwg.org/#initialize-writable-stream>
-    fn new_inherited() -> WritableStream {
+    pub(crate) fn new_inherited() -> WritableStream {
         WritableStream {
             reflector_: Reflector::new(),
             backpressure: Default::default(),
@@ -27,7 +27,7 @@ use crate::dom::messageport::MessagePort;
 use crate::dom::promise::Promise;
 use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
 use crate::dom::readablestreamdefaultcontroller::{EnqueuedValue, QueueWithSizes, ValueWithSize};
-use crate::dom::types::{AbortController, AbortSignal};
+use crate::dom::types::{AbortController, AbortSignal, FileSystemWritableFileStream};
 use crate::dom::writablestream::WritableStream;
 use crate::realms::{InRealm, enter_realm};
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
@@ -293,6 +293,8 @@ pub enum UnderlyingSinkType {
     },
     /// Algorithms supporting transform streams are implemented in Rust.
     Transform(Dom<TransformStream>, Rc<Promise>),
+    /// Algorithms of file system writable streams are implemented in Rust.
+    FileSystemWritable(Dom<FileSystemWritableFileStream>),
 }
 
 impl UnderlyingSinkType {
@@ -434,7 +436,7 @@ impl WritableStreamDefaultController {
             } => {
                 backpressure_promise.borrow_mut().take();
             },
-            UnderlyingSinkType::Transform(_, _) => {
+            UnderlyingSinkType::Transform(_, _) | UnderlyingSinkType::FileSystemWritable(_) => {
                 return;
             },
         }
@@ -576,7 +578,7 @@ impl WritableStreamDefaultController {
 
                 Ok(start_promise)
             },
-            UnderlyingSinkType::Transfer { .. } => {
+            UnderlyingSinkType::Transfer { .. } | UnderlyingSinkType::FileSystemWritable(_) => {
                 // Let startAlgorithm be an algorithm that returns undefined.
                 Ok(Promise::new_resolved(global, cx, (), can_gc))
             },
@@ -648,6 +650,9 @@ impl WritableStreamDefaultController {
                     .transform_stream_default_sink_abort_algorithm(cx, global, reason, can_gc)
                     .expect("Transform stream default sink abort algorithm should not fail.")
             },
+            UnderlyingSinkType::FileSystemWritable(stream) => {
+                stream.abort_algorithm(global, can_gc)
+            },
         };
 
         // Perform ! WritableStreamDefaultControllerClearAlgorithms(controller).
@@ -735,6 +740,9 @@ impl WritableStreamDefaultController {
                     .transform_stream_default_sink_write_algorithm(cx, global, chunk, can_gc)
                     .expect("Transform stream default sink write algorithm should not fail.")
             },
+            UnderlyingSinkType::FileSystemWritable(stream) => {
+                stream.write_algorithm(cx, global, chunk, can_gc)
+            },
         }
     }
 
@@ -787,6 +795,9 @@ impl WritableStreamDefaultController {
                     .transform_stream_default_sink_close_algorithm(cx, global, can_gc)
                     .expect("Transform stream default sink close algorithm should not fail.")
             },
+            UnderlyingSinkType::FileSystemWritable(stream) => {
+                stream.close_algorithm(global, can_gc)
+            },
         }
     }
 
@@ -65,6 +65,7 @@ use crate::dom::node::Node;
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::progressevent::ProgressEvent;
 use crate::dom::readablestream::ReadableStream;
+use crate::dom::reportingobserver::ReportingObserver;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
@@ -108,6 +109,12 @@ impl FetchResponseListener for XHRContext {
         // todo
     }
 
+    fn process_request_body_chunk_length(&mut self, _: RequestId, length: usize) {
+        self.xhr
+            .root()
+            .process_request_body_chunk_length(self.gen_id, length, CanGc::note());
+    }
+
     fn process_response(&mut self, _: RequestId, metadata: Result<FetchMetadata, NetworkError>) {
         let xhr = self.xhr.root();
         let rv = xhr.process_headers_available(self.gen_id, metadata, CanGc::note());
@@ -227,6 +234,10 @@ pub(crate) struct XMLHttpRequest {
     #[no_trace]
     request_headers: DomRefCell<HeaderMap>,
     request_body_len: Cell<usize>,
+    /// <https://xhr.spec.whatwg.org/#request-body-transmitted>
+    request_body_transmitted: Cell<u64>,
+    /// When the last `progress` event was fired at the upload object.
+    last_upload_progress: Cell<Option<Instant>>,
     sync: Cell<bool>,
     upload_complete: Cell<bool>,
     upload_listener: Cell<bool>,
@@ -266,6 +277,8 @@ impl XMLHttpRequest {
             request_url: DomRefCell::new(None),
             request_headers: DomRefCell::new(HeaderMap::new()),
             request_body_len: Cell::new(0),
+            request_body_transmitted: Cell::new(0),
+            last_upload_progress: Cell::new(None),
             sync: Cell::new(false),
             upload_complete: Cell::new(false),
             upload_listener: Cell::new(false),
@@ -397,6 +410,14 @@ impl XMLHttpRequestMethods<crate::DomTypeHolder> for XMLHttpRequest {
                         return Err(Error::InvalidAccess);
                     }
                 }
+                if !asynch && self.global().is::<Window>() {
+                    ReportingObserver::report_deprecation(
+                        &self.global(),
+                        "SyncXMLHttpRequestOnMainThread",
+                        "Synchronous XMLHttpRequest on the main thread is deprecated because \
+                         of its detrimental effects to the end user's experience.",
+                    );
+                }
                 // Step 11 - abort existing requests
                 self.terminate_ongoing_fetch();
 
@@ -625,6 +646,8 @@ impl XMLHttpRequestMethods<crate::DomTypeHolder> for XMLHttpRequest {
                 .as_ref()
                 .map_or(0, |e| e.total_bytes.unwrap_or(0)),
         );
+        self.request_body_transmitted.set(0);
+        self.last_upload_progress.set(None);
 
         // Step 5
         // If we dont have data to upload, we dont want to emit events
@@ -1046,6 +1069,41 @@ impl XMLHttpRequest {
         Ok(())
     }
 
+    /// The processRequestBodyChunkLength steps of
+    /// <https://xhr.spec.whatwg.org/#the-send()-method>
+    fn process_request_body_chunk_length(
+        &self,
+        gen_id: GenerationId,
+        length: usize,
+        can_gc: CanGc,
+    ) {
+        if self.generation_id.get() != gen_id || self.upload_complete.get() {
+            return;
+        }
+
+        // Step 1. Increase requestBodyTransmitted by bytesLength.
+        let transmitted = self.request_body_transmitted.get() + length as u64;
+        self.request_body_transmitted.set(transmitted);
+
+        // Step 2. If not roughly 50ms have passed since these steps were last invoked, then
+        // return.
+        let now = Instant::now();
+        if self
+            .last_upload_progress
+            .get()
+            .is_some_and(|last| now - last < Duration::from_millis(50))
+        {
+            return;
+        }
+        self.last_upload_progress.set(Some(now));
+
+        // Step 3. If this’s upload listener flag is set, then fire a progress event named
+        // progress at this’s upload object with requestBodyTransmitted and requestBodyLength.
+        if !self.sync.get() && self.upload_listener.get() {
+            self.dispatch_upload_progress_event(atom!("progress"), Ok(Some(transmitted)), can_gc);
+        }
+    }
+
     fn process_data_available(&self, gen_id: GenerationId, payload: Vec<u8>, can_gc: CanGc) {
         self.process_partial_response(XHRProgress::Loading(gen_id, payload), can_gc);
     }
@@ -0,0 +1,765 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Drag and drop. See <https://html.spec.whatwg.org/multipage/#dnd>.
+//!
+//! A drag starts when the user presses the primary mouse button on a draggable element, a
+//! hyperlink, an image or the selection, and then moves the mouse a few pixels. Until the
+//! button is released, moving the mouse fires `drag`, `dragenter`, `dragover` and
+//! `dragleave` events instead of mouse events, and releasing it fires `drop` at the element
+//! under the mouse, if it accepted the drag, and `dragend` at the source of the drag. When
+//! the mouse leaves the `WebView` during a drag, the dragged data is handed to the embedder,
+//! which can let the user drop it somewhere else.
+//!
+//! Drags that come from outside of the `WebView`, like files dragged from the file manager
+//! of the operating system, fire the same events, except for those fired at the source.
+//! Only the names of dragged files are known to the page: when they are dropped onto an
+//! `<input type=file>`, the element asks the file manager for them.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use embedder_traits::{
+    CompositorHitTestResult, DragData, EmbedderMsg, ExternalDragEvent, ExternalDragEventType,
+};
+use euclid::Point2D;
+use keyboard_types::Modifiers;
+use script_traits::ConstellationInputEvent;
+use style_traits::CSSPixel;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLAnchorElementBinding::HTMLAnchorElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
+use crate::dom::element::Element;
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::drag_data_store::{DragDataStore, Kind, Mode};
+use crate::editing;
+use crate::script_runtime::CanGc;
+
+/// How far the mouse has to move, in CSS pixels, while the button is pressed before a
+/// drag starts.
+const DRAG_THRESHOLD: f32 = 5.0;
+
+/// <https://html.spec.whatwg.org/multipage/#current-drag-operation>
+#[derive(Clone, Copy, Default, JSTraceable, MallocSizeOf, PartialEq)]
+enum DragOperation {
+    #[default]
+    None,
+    Copy,
+    Link,
+    Move,
+}
+
+impl DragOperation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DragOperation::None => "none",
+            DragOperation::Copy => "copy",
+            DragOperation::Link => "link",
+            DragOperation::Move => "move",
+        }
+    }
+
+    /// The operation that the `dropEffect` of a `dragover` or `drop` event that was
+    /// canceled asks for, if the `effectAllowed` of the drag allows it.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn from_effects(effect_allowed: &str, drop_effect: &str) -> DragOperation {
+        let allowed = |effects: [&str; 3]| {
+            effect_allowed == "uninitialized" ||
+                effect_allowed == "all" ||
+                effects.contains(&effect_allowed)
+        };
+        match drop_effect {
+            "copy" if allowed(["copy", "copyLink", "copyMove"]) => DragOperation::Copy,
+            "link" if allowed(["link", "copyLink", "linkMove"]) => DragOperation::Link,
+            "move" if allowed(["move", "copyMove", "linkMove"]) => DragOperation::Move,
+            _ => DragOperation::None,
+        }
+    }
+}
+
+/// Where the mouse is during a drag and which buttons and modifiers are pressed, for the
+/// drag events that are fired there.
+#[derive(Clone, Copy)]
+struct DragPosition {
+    client_point: Point2D<i32, CSSPixel>,
+    page_point: Point2D<i32, CSSPixel>,
+    point_in_target: Point2D<f32, CSSPixel>,
+    modifiers: Modifiers,
+    buttons: u16,
+}
+
+impl DragPosition {
+    fn new(
+        hit_test_result: &CompositorHitTestResult,
+        input_event: &ConstellationInputEvent,
+    ) -> DragPosition {
+        DragPosition {
+            client_point: hit_test_result.point_in_viewport.to_i32(),
+            page_point: hit_test_result
+                .point_relative_to_initial_containing_block
+                .to_i32(),
+            point_in_target: hit_test_result.point_relative_to_item,
+            modifiers: input_event.active_keyboard_modifiers,
+            buttons: input_event.pressed_mouse_buttons,
+        }
+    }
+}
+
+/// The drag-and-drop state of a document.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) struct DragAndDrop {
+    /// The node that the primary mouse button was pressed on, from which a drag starts if
+    /// the mouse moves far enough before the button is released.
+    pending_source: MutNullableDom<Node>,
+
+    /// Where the mouse button was pressed on the `pending_source`.
+    pending_point: Cell<Point2D<f32, CSSPixel>>,
+
+    /// Whether the pending drag is a drag of the selection rather than of an element.
+    pending_selection: Cell<bool>,
+
+    /// The node that the drag in progress started from. This is `None` for drags that come
+    /// from outside of the `WebView`.
+    /// <https://html.spec.whatwg.org/multipage/#source-node>
+    source_node: MutNullableDom<Node>,
+
+    /// <https://html.spec.whatwg.org/multipage/#current-target-element>
+    current_target: MutNullableDom<Element>,
+
+    /// <https://html.spec.whatwg.org/multipage/#current-drag-operation>
+    current_drag_operation: Cell<DragOperation>,
+
+    /// The drag data store of the drag in progress, if there is one.
+    /// <https://html.spec.whatwg.org/multipage/#drag-data-store>
+    #[no_trace]
+    #[ignore_malloc_size_of = "DragDataStore"]
+    data_store: DomRefCell<Option<DragDataStore>>,
+
+    /// The position of the mouse at the last event of the drag in progress.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in keyboard-types"]
+    position: Cell<Option<DragPosition>>,
+
+    /// Whether the `click` that follows the release of the mouse button at the end of a
+    /// drag should not be fired.
+    suppress_click: Cell<bool>,
+}
+
+impl DragAndDrop {
+    /// Whether a drag is in progress over the document.
+    fn in_progress(&self) -> bool {
+        self.data_store.borrow().is_some()
+    }
+
+    /// Handle the press of the primary mouse button on `node`, after the `mousedown` event
+    /// was fired. Unless that event was canceled, a drag may start from `node`.
+    pub(crate) fn handle_mouse_down(
+        &self,
+        document: &Document,
+        node: &Node,
+        mousedown: &Event,
+        point: Point2D<f32, CSSPixel>,
+        can_gc: CanGc,
+    ) {
+        self.suppress_click.set(false);
+        self.pending_source.set(None);
+        if mousedown.DefaultPrevented() {
+            return;
+        }
+
+        // Text controls select their text with the mouse instead.
+        if node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .any(|node| node.is::<HTMLInputElement>() || node.is::<HTMLTextAreaElement>())
+        {
+            return;
+        }
+
+        if let Some(element) = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| element.is_draggable())
+        {
+            self.pending_source.set(Some(element.upcast()));
+            self.pending_selection.set(false);
+        } else if selection_contains_point(document, point, can_gc) {
+            self.pending_source.set(Some(node));
+            self.pending_selection.set(true);
+        } else {
+            return;
+        }
+        self.pending_point.set(point);
+    }
+
+    /// Handle a mouse move over `node`, which may start a drag or continue the drag in
+    /// progress. Returns whether the move is part of a drag, in which case no mouse events
+    /// should be fired for it.
+    pub(crate) fn handle_mouse_move(
+        &self,
+        document: &Document,
+        node: &Node,
+        hit_test_result: &CompositorHitTestResult,
+        input_event: &ConstellationInputEvent,
+        can_gc: CanGc,
+    ) -> bool {
+        let position = DragPosition::new(hit_test_result, input_event);
+        if let Some(source) = self.pending_source.get() {
+            // The button was released somewhere that this document was not told about.
+            if input_event.pressed_mouse_buttons & 1 == 0 {
+                self.pending_source.set(None);
+                return false;
+            }
+            let distance = (hit_test_result.point_in_viewport - self.pending_point.get()).length();
+            if distance < DRAG_THRESHOLD {
+                return false;
+            }
+            self.pending_source.set(None);
+            if !self.start_drag(document, &source, position, can_gc) {
+                return false;
+            }
+        }
+
+        if self.source_node.get().is_none() {
+            return false;
+        }
+        self.position.set(Some(position));
+        if let Some(immediate_user_selection) = immediate_user_selection(node) {
+            self.update_drag(document, &immediate_user_selection, can_gc);
+        }
+        true
+    }
+
+    /// Handle the release of the primary mouse button. Returns whether it ended a drag, in
+    /// which case no `mouseup` and `click` events should be fired for it.
+    pub(crate) fn handle_mouse_up(
+        &self,
+        document: &Document,
+        hit_test_result: &CompositorHitTestResult,
+        input_event: &ConstellationInputEvent,
+        can_gc: CanGc,
+    ) -> bool {
+        self.pending_source.set(None);
+        if self.source_node.get().is_none() {
+            return false;
+        }
+        self.position
+            .set(Some(DragPosition::new(hit_test_result, input_event)));
+        self.drop(document, can_gc);
+        self.suppress_click.set(true);
+        true
+    }
+
+    /// Whether the `click` event that is about to be fired follows the end of a drag and
+    /// should not be fired.
+    pub(crate) fn take_suppressed_click(&self) -> bool {
+        self.suppress_click.replace(false)
+    }
+
+    /// Handle the mouse leaving the `WebView`. A drag that started in this document is
+    /// handed to the embedder, which may let the user drop its data outside of the
+    /// `WebView`.
+    pub(crate) fn handle_mouse_leave(&self, document: &Document, can_gc: CanGc) {
+        self.pending_source.set(None);
+        if self.source_node.get().is_none() {
+            return;
+        }
+        let data = self
+            .data_store
+            .borrow()
+            .as_ref()
+            .map(drag_data)
+            .unwrap_or_default();
+
+        // Whether the data is dropped somewhere else is not known, so as far as the page is
+        // concerned the drag ends without an operation.
+        self.cancel(document, can_gc);
+        document.send_to_embedder(EmbedderMsg::StartDrag(document.webview_id(), data));
+    }
+
+    /// Cancel the drag in progress, if there is one, for instance because the user pressed
+    /// Escape. Returns whether a drag was canceled.
+    pub(crate) fn cancel(&self, document: &Document, can_gc: CanGc) -> bool {
+        self.pending_source.set(None);
+        if !self.in_progress() {
+            return false;
+        }
+        self.current_drag_operation.set(DragOperation::None);
+        if let Some(current_target) = self.current_target.get() {
+            self.fire_dnd_event(document, "dragleave", current_target.upcast(), None, can_gc);
+        }
+        self.end_drag(document, can_gc);
+        true
+    }
+
+    /// Handle a drag from outside of the `WebView` over `node`, which is where the drag is
+    /// if the compositor found it.
+    pub(crate) fn handle_external_drag_event(
+        &self,
+        document: &Document,
+        event: &ExternalDragEvent,
+        node: Option<&Node>,
+        hit_test_result: Option<&CompositorHitTestResult>,
+        input_event: &ConstellationInputEvent,
+        can_gc: CanGc,
+    ) {
+        // A drag that started in this document is not interrupted by another one.
+        if self.source_node.get().is_some() {
+            return;
+        }
+        let (
+            Some(node),
+            Some(hit_test_result),
+            ExternalDragEventType::Over | ExternalDragEventType::Drop,
+        ) = (node, hit_test_result, event.event_type)
+        else {
+            self.cancel(document, can_gc);
+            return;
+        };
+
+        self.position
+            .set(Some(DragPosition::new(hit_test_result, input_event)));
+        // The data that the embedder reports is always that of the whole drag.
+        *self.data_store.borrow_mut() = Some(data_store_for_external_drag(&event.data));
+        if let Some(immediate_user_selection) = immediate_user_selection(node) {
+            self.update_drag(document, &immediate_user_selection, can_gc);
+        }
+        if event.event_type == ExternalDragEventType::Drop {
+            self.drop(document, can_gc);
+        }
+    }
+
+    /// Fill a new drag data store with the data of what is dragged from `source` and fire
+    /// `dragstart` at it. Returns whether the drag started, that is whether `dragstart` was
+    /// not canceled.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn start_drag(
+        &self,
+        document: &Document,
+        source: &Node,
+        position: DragPosition,
+        can_gc: CanGc,
+    ) -> bool {
+        let mut data_store = DragDataStore::new();
+        if self.pending_selection.get() {
+            if let Some(range) = document
+                .GetSelection(can_gc)
+                .and_then(|selection| selection.GetRangeAt(0).ok())
+            {
+                data_store.set_data(DOMString::from("text/plain"), range.clipboard_text(can_gc));
+                if let Some(html) = range.html_fragment(can_gc) {
+                    data_store.set_data(DOMString::from("text/html"), html);
+                }
+            }
+        } else if let Some(url) = dragged_url(source) {
+            data_store.set_data(DOMString::from("text/uri-list"), url.clone());
+            data_store.set_data(DOMString::from("text/plain"), url);
+        }
+
+        *self.data_store.borrow_mut() = Some(data_store);
+        self.source_node.set(Some(source));
+        self.current_target.set(None);
+        self.current_drag_operation.set(DragOperation::None);
+        self.position.set(Some(position));
+
+        let (status, _) = self.fire_dnd_event(document, "dragstart", source.upcast(), None, can_gc);
+        if status == EventStatus::Canceled {
+            self.reset();
+            return false;
+        }
+        true
+    }
+
+    /// The steps of the drag-and-drop processing model that run every time the drag moves,
+    /// with the element under the mouse as the immediate user selection.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn update_drag(&self, document: &Document, immediate_user_selection: &Element, can_gc: CanGc) {
+        // Canceling the `drag` event at the source cancels the drag.
+        if let Some(source) = self.source_node.get() {
+            let (status, _) = self.fire_dnd_event(document, "drag", source.upcast(), None, can_gc);
+            if status == EventStatus::Canceled {
+                self.cancel(document, can_gc);
+                return;
+            }
+        }
+
+        let current_target = self.current_target.get();
+        if current_target.as_deref() != Some(immediate_user_selection) {
+            let (status, _) = self.fire_dnd_event(
+                document,
+                "dragenter",
+                immediate_user_selection.upcast(),
+                current_target.as_deref().map(|target| target.upcast()),
+                can_gc,
+            );
+            let new_target = if status == EventStatus::Canceled ||
+                self.accepts_drop_by_default(immediate_user_selection)
+            {
+                Some(DomRoot::from_ref(immediate_user_selection))
+            } else {
+                // Drags that no element accepted are over the body element.
+                let body = document.GetBody().map(DomRoot::upcast::<Element>);
+                if let Some(body) = &body {
+                    if &**body != immediate_user_selection && current_target.as_ref() != Some(body)
+                    {
+                        self.fire_dnd_event(
+                            document,
+                            "dragenter",
+                            body.upcast(),
+                            current_target.as_deref().map(|target| target.upcast()),
+                            can_gc,
+                        );
+                    }
+                }
+                body
+            };
+
+            if current_target != new_target {
+                if let Some(current_target) = &current_target {
+                    self.fire_dnd_event(
+                        document,
+                        "dragleave",
+                        current_target.upcast(),
+                        new_target.as_deref().map(|target| target.upcast()),
+                        can_gc,
+                    );
+                }
+                self.current_target.set(new_target.as_deref());
+            }
+        }
+
+        let Some(current_target) = self.current_target.get() else {
+            self.current_drag_operation.set(DragOperation::None);
+            return;
+        };
+        let (status, drop_effect) =
+            self.fire_dnd_event(document, "dragover", current_target.upcast(), None, can_gc);
+        let effect_allowed = self.effect_allowed();
+        let operation = if status == EventStatus::Canceled {
+            DragOperation::from_effects(&effect_allowed, &drop_effect)
+        } else if self.accepts_drop_by_default(&current_target) {
+            DragOperation::from_effects(&effect_allowed, self.default_drop_effect(&effect_allowed))
+        } else {
+            DragOperation::None
+        };
+        self.current_drag_operation.set(operation);
+    }
+
+    /// Drop the dragged data onto the current target element, if it accepted the drag, and
+    /// end the drag.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn drop(&self, document: &Document, can_gc: CanGc) {
+        if let Some(current_target) = self.current_target.get() {
+            if self.current_drag_operation.get() == DragOperation::None {
+                self.fire_dnd_event(document, "dragleave", current_target.upcast(), None, can_gc);
+            } else {
+                let (status, drop_effect) =
+                    self.fire_dnd_event(document, "drop", current_target.upcast(), None, can_gc);
+                if status == EventStatus::Canceled {
+                    self.current_drag_operation.set(DragOperation::from_effects(
+                        &self.effect_allowed(),
+                        &drop_effect,
+                    ));
+                } else {
+                    self.insert_into_editing_host(document, &current_target, can_gc);
+                }
+            }
+        }
+        self.end_drag(document, can_gc);
+    }
+
+    /// The default action of a `drop` event that was not canceled in editable content:
+    /// insert the dropped text where it was dropped. Text controls insert it themselves
+    /// when they handle the event, and file inputs select the dropped files.
+    /// <https://html.spec.whatwg.org/multipage/#drop-processing>
+    fn insert_into_editing_host(&self, document: &Document, target: &Element, can_gc: CanGc) {
+        if target.is::<HTMLInputElement>() || target.is::<HTMLTextAreaElement>() {
+            return;
+        }
+        let Some(host) = editing::editing_host(target.upcast()) else {
+            return;
+        };
+        let Some(text) = self
+            .data_store
+            .borrow()
+            .as_ref()
+            .and_then(|data_store| data_store.find_matching_text("text/plain"))
+        else {
+            return;
+        };
+        let Some(position) = self.position.get() else {
+            return;
+        };
+        if let Some((node, offset)) = document.caret_position_from_point(
+            Finite::wrap(position.client_point.x as f64),
+            Finite::wrap(position.client_point.y as f64),
+            &[],
+            can_gc,
+        ) {
+            editing::insert_dropped_text(&host, &node, offset, text.to_string(), can_gc);
+        }
+    }
+
+    /// Fire `dragend` at the source node, if the drag started in this document, and forget
+    /// about the drag.
+    fn end_drag(&self, document: &Document, can_gc: CanGc) {
+        if let Some(source) = self.source_node.get() {
+            self.fire_dnd_event(document, "dragend", source.upcast(), None, can_gc);
+        }
+        self.reset();
+    }
+
+    fn reset(&self) {
+        self.source_node.set(None);
+        self.current_target.set(None);
+        self.current_drag_operation.set(DragOperation::None);
+        *self.data_store.borrow_mut() = None;
+        self.position.set(None);
+    }
+
+    /// The `effectAllowed` of the drag in progress.
+    fn effect_allowed(&self) -> String {
+        self.data_store
+            .borrow()
+            .as_ref()
+            .map(|data_store| data_store.allowed_effects_state().to_owned())
+            .unwrap_or_else(|| String::from("none"))
+    }
+
+    /// The `dropEffect` of `dragenter` and `dragover` events, before script changes it.
+    /// <https://html.spec.whatwg.org/multipage/#dropEffect-initialisation>
+    fn default_drop_effect(&self, effect_allowed: &str) -> &'static str {
+        match effect_allowed {
+            "none" => "none",
+            "copy" | "copyLink" | "copyMove" | "all" => "copy",
+            "link" | "linkMove" => "link",
+            "move" => "move",
+            _ if self
+                .source_node
+                .get()
+                .is_some_and(|source| source.is::<HTMLAnchorElement>()) =>
+            {
+                "link"
+            },
+            _ => "copy",
+        }
+    }
+
+    /// Whether dropping the dragged data onto `target` does something without help from
+    /// script, like inserting dragged text into editable content, so that `target` accepts
+    /// the drag even if it does not cancel `dragenter` and `dragover`.
+    fn accepts_drop_by_default(&self, target: &Element) -> bool {
+        let data_store = self.data_store.borrow();
+        let Some(data_store) = data_store.as_ref() else {
+            return false;
+        };
+        let has_text = data_store.find_matching_text("text/plain").is_some();
+        let has_files = data_store
+            .iter_item_list()
+            .any(|item| matches!(item, Kind::File { .. }));
+
+        if let Some(input) = target.downcast::<HTMLInputElement>() {
+            return input.accepts_drop(has_text, has_files);
+        }
+        if let Some(textarea) = target.downcast::<HTMLTextAreaElement>() {
+            return has_text && textarea.is_mutable();
+        }
+        has_text && editing::editing_host(target.upcast()).is_some()
+    }
+
+    /// Fire a drag event named `type_` at `target`, with a `DataTransfer` for the drag data
+    /// store of the drag in progress. Returns whether the event was canceled and the
+    /// `dropEffect` that it was left with.
+    /// <https://html.spec.whatwg.org/multipage/#fire-a-dnd-event>
+    fn fire_dnd_event(
+        &self,
+        document: &Document,
+        type_: &str,
+        target: &EventTarget,
+        related_target: Option<&EventTarget>,
+        can_gc: CanGc,
+    ) -> (EventStatus, DOMString) {
+        let Some(position) = self.position.get() else {
+            return (EventStatus::NotCanceled, DOMString::from("none"));
+        };
+        let Some(mut data_store) = self.data_store.borrow_mut().take() else {
+            return (EventStatus::NotCanceled, DOMString::from("none"));
+        };
+
+        // Step 2-4. Only `dragstart` can change the data and only `drop` can read it.
+        data_store.set_mode(match type_ {
+            "dragstart" => Mode::ReadWrite,
+            "drop" => Mode::ReadOnly,
+            _ => Mode::Protected,
+        });
+
+        // Step 5-7
+        let effect_allowed = data_store.allowed_effects_state().to_owned();
+        let drop_effect = match type_ {
+            "dragenter" | "dragover" => self.default_drop_effect(&effect_allowed),
+            "drop" | "dragend" => self.current_drag_operation.get().as_str(),
+            _ => "none",
+        };
+        let window = document.window();
+        let shared_data_store = Rc::new(RefCell::new(Some(data_store)));
+        let data_transfer = DataTransfer::new(window, Rc::clone(&shared_data_store), can_gc);
+        data_transfer.set_drop_effect(drop_effect);
+        data_transfer.set_effect_allowed(&effect_allowed);
+
+        // Step 8-9
+        let cancelable = !matches!(type_, "dragleave" | "dragend");
+        let event = DragEvent::new(
+            window,
+            DOMString::from(type_),
+            EventBubbles::Bubbles,
+            EventCancelable::from(cancelable),
+            position.client_point,
+            position.page_point,
+            position.modifiers,
+            position.buttons,
+            related_target,
+            Some(position.point_in_target),
+            Some(&data_transfer),
+            can_gc,
+        );
+        let event = event.upcast::<Event>();
+        event.set_composed(true);
+        let status = event.fire(target, can_gc);
+
+        // Step 10. The `DataTransfer` is no longer associated with the drag data store.
+        if let Some(mut data_store) = shared_data_store.borrow_mut().take() {
+            if type_ == "dragstart" {
+                data_store.set_allowed_effects_state(data_transfer.EffectAllowed().to_string());
+            }
+            data_store.set_mode(Mode::Protected);
+            *self.data_store.borrow_mut() = Some(data_store);
+        }
+        (status, data_transfer.DropEffect())
+    }
+}
+
+/// The element that a drag over `node` is over. Drags over the contents of a text control
+/// are over the text control itself.
+/// <https://html.spec.whatwg.org/multipage/#immediate-user-selection>
+fn immediate_user_selection(node: &Node) -> Option<DomRoot<Element>> {
+    node.inclusive_ancestors(ShadowIncluding::Yes)
+        .find(|node| node.is::<HTMLInputElement>() || node.is::<HTMLTextAreaElement>())
+        .and_then(DomRoot::downcast::<Element>)
+        .or_else(|| {
+            node.inclusive_ancestors(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        })
+}
+
+/// Whether the selection of `document` is not collapsed and contains `point`.
+fn selection_contains_point(
+    document: &Document,
+    point: Point2D<f32, CSSPixel>,
+    can_gc: CanGc,
+) -> bool {
+    let Some(range) = document
+        .GetSelection(can_gc)
+        .and_then(|selection| selection.GetRangeAt(0).ok())
+    else {
+        return false;
+    };
+    if range.collapsed() {
+        return false;
+    }
+    document
+        .caret_position_from_point(
+            Finite::wrap(point.x as f64),
+            Finite::wrap(point.y as f64),
+            &[],
+            can_gc,
+        )
+        .is_some_and(|(node, offset)| range.IsPointInRange(&node, offset).unwrap_or(false))
+}
+
+/// The URL of a dragged hyperlink or image.
+fn dragged_url(source: &Node) -> Option<DOMString> {
+    let url = if let Some(anchor) = source.downcast::<HTMLAnchorElement>() {
+        anchor.Href()
+    } else if let Some(image) = source.downcast::<HTMLImageElement>() {
+        image.CurrentSrc()
+    } else {
+        return None;
+    };
+    (!url.0.is_empty()).then(|| DOMString::from(url.0))
+}
+
+/// The data of a drag that leaves the `WebView`, for the embedder.
+fn drag_data(data_store: &DragDataStore) -> DragData {
+    // The first line of a `text/uri-list` that is not a comment is its URL.
+    let url = data_store
+        .find_matching_text("text/uri-list")
+        .and_then(|uri_list| {
+            uri_list
+                .lines()
+                .find(|line| !line.starts_with('#'))
+                .map(str::to_owned)
+        });
+    DragData {
+        text: data_store
+            .find_matching_text("text/plain")
+            .map(String::from),
+        html: data_store.find_matching_text("text/html").map(String::from),
+        url,
+        files: Vec::new(),
+    }
+}
+
+/// A drag data store with the data of a drag from outside of the `WebView`. Dragged files
+/// are only listed by name, their contents are not read.
+fn data_store_for_external_drag(data: &DragData) -> DragDataStore {
+    let mut data_store = DragDataStore::new();
+    if let Some(text) = &data.text {
+        data_store.set_data(
+            DOMString::from("text/plain"),
+            DOMString::from(text.as_str()),
+        );
+    }
+    if let Some(html) = &data.html {
+        data_store.set_data(DOMString::from("text/html"), DOMString::from(html.as_str()));
+    }
+    if let Some(url) = &data.url {
+        data_store.set_data(
+            DOMString::from("text/uri-list"),
+            DOMString::from(url.as_str()),
+        );
+    }
+    for path in &data.files {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        let _ = data_store.add(Kind::File {
+            bytes: Vec::new(),
+            name: DOMString::from(name.to_string_lossy().into_owned()),
+            type_: mime_guess::from_path(path)
+                .first_or_octet_stream()
+                .to_string(),
+        });
+    }
+    data_store
+}
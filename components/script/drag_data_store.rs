@@ -68,7 +68,7 @@ impl Kind {
 }
 
 /// <https://html.spec.whatwg.org/multipage/#drag-data-store-bitmap>
-#[allow(dead_code)] // TODO this used by DragEvent.
+#[allow(dead_code)] // TODO the drag image is not rendered as drag feedback yet.
 struct Bitmap {
     image: Option<Arc<RasterImage>>,
     x: i32,
@@ -86,13 +86,14 @@ pub(crate) enum Mode {
     Protected,
 }
 
-#[allow(dead_code)] // TODO some fields are used by DragEvent.
 pub(crate) struct DragDataStore {
     /// <https://html.spec.whatwg.org/multipage/#drag-data-store-item-list>
     item_list: IndexMap<u16, Kind>,
     next_item_id: u16,
     /// <https://html.spec.whatwg.org/multipage/#drag-data-store-default-feedback>
+    #[allow(dead_code)] // TODO the default feedback is not shown while dragging yet.
     default_feedback: Option<String>,
+    #[allow(dead_code)] // TODO the drag image is not rendered as drag feedback yet.
     bitmap: Option<Bitmap>,
     mode: Mode,
     /// <https://html.spec.whatwg.org/multipage/#drag-data-store-allowed-effects-state>
@@ -130,6 +131,16 @@ impl DragDataStore {
         self.bitmap = Some(Bitmap { image, x, y });
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#drag-data-store-allowed-effects-state>
+    pub(crate) fn allowed_effects_state(&self) -> &str {
+        &self.allowed_effects_state
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#drag-data-store-allowed-effects-state>
+    pub(crate) fn set_allowed_effects_state(&mut self, state: String) {
+        self.allowed_effects_state = state;
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#concept-datatransfer-types>
     pub(crate) fn types(&self) -> Vec<DOMString> {
         let mut types = Vec::new();
@@ -0,0 +1,1244 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Editing of `contenteditable` elements. See
+//! <https://html.spec.whatwg.org/multipage/#editing-2>,
+//! <https://w3c.github.io/editing/docs/execCommand/> and
+//! <https://w3c.github.io/input-events/>.
+//!
+//! The caret and the selection inside an editing host are the document's `Selection`.
+//! User input and `document.execCommand()` are both turned into an [`EditingCommand`],
+//! which operates on the DOM around the selection: text is inserted into and deleted
+//! from text nodes, and deleting at the edge of a block merges it with its neighbour.
+//! Commands that come from the user are announced with a cancelable `beforeinput` event
+//! and all of them are followed by an `input` event whose `inputType` names the command.
+//!
+//! Every editing host keeps its own [`EditingHistory`] of snapshots of its contents,
+//! which are swapped back in to undo and redo edits. Consecutive typing is coalesced into
+//! a single step, like in other browsers.
+
+use std::cmp::Ordering;
+
+use html5ever::{LocalName, QualName, local_name, ns};
+use keyboard_types::{Key, Modifiers};
+
+use crate::dom::abstractrange::bp_position;
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::codegen::Bindings::TextBinding::TextMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::inputevent::InputEvent;
+use crate::dom::node::{CloneChildrenFlag, Node, NodeTraits, ShadowIncluding};
+use crate::dom::range::Range;
+use crate::dom::text::Text;
+use crate::script_runtime::CanGc;
+
+/// The number of steps that the [`EditingHistory`] of an editing host remembers.
+const MAX_HISTORY_LENGTH: usize = 100;
+
+/// The states of the `contenteditable` attribute.
+/// <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ContentEditableState {
+    True,
+    False,
+    PlaintextOnly,
+    Inherit,
+}
+
+impl ContentEditableState {
+    pub(crate) fn for_element(element: &Element) -> ContentEditableState {
+        let Some(attr) = element.get_attribute(&ns!(), &local_name!("contenteditable")) else {
+            return ContentEditableState::Inherit;
+        };
+        match attr.value().to_ascii_lowercase().as_str() {
+            "" | "true" => ContentEditableState::True,
+            "false" => ContentEditableState::False,
+            "plaintext-only" => ContentEditableState::PlaintextOnly,
+            // The invalid value default is the inherit state.
+            _ => ContentEditableState::Inherit,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ContentEditableState::True => "true",
+            ContentEditableState::False => "false",
+            ContentEditableState::PlaintextOnly => "plaintext-only",
+            ContentEditableState::Inherit => "inherit",
+        }
+    }
+}
+
+/// Returns the editing host of `node`, i.e. the outermost element of the run of editable
+/// ancestors that `node` is part of, if `node` is editable or is an editing host itself.
+/// <https://html.spec.whatwg.org/multipage/#editing-host>
+pub(crate) fn editing_host(node: &Node) -> Option<DomRoot<Element>> {
+    let mut host = None;
+    for element in node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLElement>)
+    {
+        match ContentEditableState::for_element(element.upcast()) {
+            ContentEditableState::True | ContentEditableState::PlaintextOnly => {
+                host = Some(DomRoot::upcast(element))
+            },
+            ContentEditableState::False => break,
+            ContentEditableState::Inherit => {},
+        }
+    }
+    host
+}
+
+/// <https://html.spec.whatwg.org/multipage/#editing-host>
+pub(crate) fn is_editing_host(element: &Element) -> bool {
+    editing_host(element.upcast()).is_some_and(|host| &*host == element)
+}
+
+/// An editing action, named after its `inputType`.
+/// <https://w3c.github.io/input-events/#interface-InputEvent-Attributes>
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum EditingCommand {
+    InsertText(String),
+    InsertFromDrop(String),
+    InsertParagraph,
+    InsertLineBreak,
+    DeleteContentBackward,
+    DeleteContentForward,
+    FormatBold,
+    FormatItalic,
+    HistoryUndo,
+    HistoryRedo,
+}
+
+impl EditingCommand {
+    /// The command for a `document.execCommand()` command name, ignoring commands that are
+    /// not supported.
+    fn for_command_name(command_id: &str, value: &DOMString) -> Option<EditingCommand> {
+        Some(match command_id.to_ascii_lowercase().as_str() {
+            "bold" => EditingCommand::FormatBold,
+            "italic" => EditingCommand::FormatItalic,
+            "inserttext" => EditingCommand::InsertText(value.to_string()),
+            "insertparagraph" => EditingCommand::InsertParagraph,
+            "insertlinebreak" => EditingCommand::InsertLineBreak,
+            "delete" => EditingCommand::DeleteContentBackward,
+            "forwarddelete" => EditingCommand::DeleteContentForward,
+            "undo" => EditingCommand::HistoryUndo,
+            "redo" => EditingCommand::HistoryRedo,
+            _ => return None,
+        })
+    }
+
+    fn input_type(&self) -> &'static str {
+        match self {
+            EditingCommand::InsertText(_) => "insertText",
+            EditingCommand::InsertFromDrop(_) => "insertFromDrop",
+            EditingCommand::InsertParagraph => "insertParagraph",
+            EditingCommand::InsertLineBreak => "insertLineBreak",
+            EditingCommand::DeleteContentBackward => "deleteContentBackward",
+            EditingCommand::DeleteContentForward => "deleteContentForward",
+            EditingCommand::FormatBold => "formatBold",
+            EditingCommand::FormatItalic => "formatItalic",
+            EditingCommand::HistoryUndo => "historyUndo",
+            EditingCommand::HistoryRedo => "historyRedo",
+        }
+    }
+
+    fn data(&self) -> Option<DOMString> {
+        match self {
+            EditingCommand::InsertText(text) => Some(DOMString::from(text.as_str())),
+            _ => None,
+        }
+    }
+
+    /// Commands that only make sense for rich text are not available in editing hosts
+    /// in the plaintext-only state.
+    fn for_host(self, host: &Element) -> Option<EditingCommand> {
+        if ContentEditableState::for_element(host) != ContentEditableState::PlaintextOnly {
+            return Some(self);
+        }
+        match self {
+            EditingCommand::FormatBold | EditingCommand::FormatItalic => None,
+            EditingCommand::InsertParagraph => Some(EditingCommand::InsertLineBreak),
+            command => Some(command),
+        }
+    }
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()>
+pub(crate) fn is_command_supported(command_id: &str) -> bool {
+    command_id.eq_ignore_ascii_case("selectall") ||
+        EditingCommand::for_command_name(command_id, &DOMString::new()).is_some()
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()>
+pub(crate) fn is_command_enabled(document: &Document, command_id: &str, can_gc: CanGc) -> bool {
+    if command_id.eq_ignore_ascii_case("selectall") {
+        return true;
+    }
+    let Some(command) = EditingCommand::for_command_name(command_id, &DOMString::new()) else {
+        return false;
+    };
+    active_editing_host(document, can_gc).is_some_and(|host| command.for_host(&host).is_some())
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandstate()>
+pub(crate) fn command_state(document: &Document, command_id: &str, can_gc: CanGc) -> bool {
+    let style = match EditingCommand::for_command_name(command_id, &DOMString::new()) {
+        Some(EditingCommand::FormatBold) => InlineStyle::Bold,
+        Some(EditingCommand::FormatItalic) => InlineStyle::Italic,
+        _ => return false,
+    };
+    let Some(host) = active_editing_host(document, can_gc) else {
+        return false;
+    };
+    let Some(range) = selection_range(document, can_gc) else {
+        return false;
+    };
+    style
+        .enclosing_element(&range.start_container(), host.upcast())
+        .is_some()
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#execcommand()>
+pub(crate) fn exec_command(
+    document: &Document,
+    command_id: &str,
+    value: DOMString,
+    can_gc: CanGc,
+) -> bool {
+    if command_id.eq_ignore_ascii_case("selectall") {
+        let Some(selection) = document.GetSelection(can_gc) else {
+            return false;
+        };
+        let root = match active_editing_host(document, can_gc) {
+            Some(host) => DomRoot::upcast::<Node>(host),
+            None => match document.GetBody() {
+                Some(body) => DomRoot::upcast(body),
+                None => return false,
+            },
+        };
+        return selection.SelectAllChildren(&root, can_gc).is_ok();
+    }
+
+    let Some(command) = EditingCommand::for_command_name(command_id, &value) else {
+        return false;
+    };
+    let Some(host) = active_editing_host(document, can_gc) else {
+        return false;
+    };
+    let Some(command) = command.for_host(&host) else {
+        return false;
+    };
+
+    // Commands run by script are not announced with a `beforeinput` event.
+    // <https://w3c.github.io/input-events/#event-type-beforeinput>
+    perform_command(&host, command, false, can_gc);
+    true
+}
+
+/// Handle a `keydown` event that was not canceled in an element inside of `host`.
+/// Returns whether the key was used for editing.
+pub(crate) fn handle_keydown(
+    host: &Element,
+    key: &Key,
+    modifiers: Modifiers,
+    can_gc: CanGc,
+) -> bool {
+    let shortcut = modifiers.intersects(Modifiers::CONTROL | Modifiers::META);
+    let shift = modifiers.contains(Modifiers::SHIFT);
+    let command = match key {
+        Key::Character(character) if shortcut => match character.to_ascii_lowercase().as_str() {
+            "z" if shift => EditingCommand::HistoryRedo,
+            "z" => EditingCommand::HistoryUndo,
+            "y" => EditingCommand::HistoryRedo,
+            "b" => EditingCommand::FormatBold,
+            "i" => EditingCommand::FormatItalic,
+            "a" => {
+                let document = host.owner_document();
+                if let Some(selection) = document.GetSelection(can_gc) {
+                    let _ = selection.SelectAllChildren(host.upcast(), can_gc);
+                }
+                return true;
+            },
+            _ => return false,
+        },
+        Key::Character(_) if modifiers.contains(Modifiers::ALT) => return false,
+        Key::Character(character) => EditingCommand::InsertText(character.clone()),
+        Key::Enter if shift => EditingCommand::InsertLineBreak,
+        Key::Enter => EditingCommand::InsertParagraph,
+        Key::Backspace => EditingCommand::DeleteContentBackward,
+        Key::Delete => EditingCommand::DeleteContentForward,
+        Key::ArrowLeft | Key::ArrowRight | Key::Home | Key::End => {
+            move_caret(host, key, shift, can_gc);
+            return true;
+        },
+        _ => return false,
+    };
+    if let Some(command) = command.for_host(host) {
+        perform_command(host, command, true, can_gc);
+    }
+    true
+}
+
+/// Place the caret at the boundary point `(node, offset)`, for instance where the user
+/// clicked, if it is inside of `host`.
+pub(crate) fn place_caret(host: &Element, node: &Node, offset: u32, can_gc: CanGc) {
+    if editing_host(node).is_none_or(|node_host| &*node_host != host) {
+        return;
+    }
+    if let Some(selection) = host.owner_document().GetSelection(can_gc) {
+        let _ = selection.Collapse(Some(node), offset, can_gc);
+    }
+}
+
+/// Insert `text` that the user dropped at the boundary point `(node, offset)` inside of
+/// `host`, as the default action of a `drop` event.
+pub(crate) fn insert_dropped_text(
+    host: &Element,
+    node: &Node,
+    offset: u32,
+    text: String,
+    can_gc: CanGc,
+) {
+    place_caret(host, node, offset, can_gc);
+    if let Some(command) = EditingCommand::InsertFromDrop(text).for_host(host) {
+        perform_command(host, command, true, can_gc);
+    }
+}
+
+/// Run `command` in `host`, recording it in the editing history of the host and firing
+/// `beforeinput` and `input` events around it.
+fn perform_command(host: &Element, command: EditingCommand, fire_beforeinput: bool, can_gc: CanGc) {
+    // <https://w3c.github.io/input-events/#event-type-beforeinput>
+    if fire_beforeinput && !fire_input_event(host, "beforeinput", &command, true, can_gc) {
+        return;
+    }
+
+    let changed = match command {
+        EditingCommand::HistoryUndo => restore_from_history(host, true, can_gc),
+        EditingCommand::HistoryRedo => restore_from_history(host, false, can_gc),
+        _ => {
+            let typing = matches!(command, EditingCommand::InsertText(_));
+            let extends_typing =
+                typing && host.with_editing_history(|history| history.extends_typing());
+            let before = (!extends_typing).then(|| CapturedContents::capture(host, can_gc));
+            let changed = apply_command(host, &command, can_gc);
+            if changed {
+                let after = CapturedContents::capture(host, can_gc);
+                host.with_editing_history(|history| history.record(before, after, typing));
+            }
+            changed
+        },
+    };
+
+    // <https://w3c.github.io/input-events/#event-type-input>
+    if changed {
+        fire_input_event(host, "input", &command, false, can_gc);
+    }
+}
+
+/// Fire an `InputEvent` named `name` for `command` at `host`. Returns `false` if the event
+/// was canceled.
+fn fire_input_event(
+    host: &Element,
+    name: &str,
+    command: &EditingCommand,
+    cancelable: bool,
+    can_gc: CanGc,
+) -> bool {
+    let window = host.owner_window();
+    let event = InputEvent::new(
+        &window,
+        None,
+        DOMString::from(name),
+        true,
+        cancelable,
+        Some(&window),
+        0,
+        command.data(),
+        false,
+        DOMString::from(command.input_type()),
+        can_gc,
+    );
+    let event = event.upcast::<Event>();
+    event.set_composed(true);
+    event.fire(host.upcast(), can_gc) == EventStatus::NotCanceled
+}
+
+fn apply_command(host: &Element, command: &EditingCommand, can_gc: CanGc) -> bool {
+    let Some(range) = caret_range(host, can_gc) else {
+        return false;
+    };
+    match command {
+        EditingCommand::InsertText(text) | EditingCommand::InsertFromDrop(text) => {
+            insert_text(host, &range, text, can_gc)
+        },
+        EditingCommand::InsertParagraph => insert_paragraph(host, &range, can_gc),
+        EditingCommand::InsertLineBreak => insert_line_break(host, &range, can_gc),
+        EditingCommand::DeleteContentBackward => delete(host, &range, false, can_gc),
+        EditingCommand::DeleteContentForward => delete(host, &range, true, can_gc),
+        EditingCommand::FormatBold => toggle_inline_style(host, &range, InlineStyle::Bold, can_gc),
+        EditingCommand::FormatItalic => {
+            toggle_inline_style(host, &range, InlineStyle::Italic, can_gc)
+        },
+        EditingCommand::HistoryUndo | EditingCommand::HistoryRedo => false,
+    }
+}
+
+/// The range of the document's selection, if there is one.
+fn selection_range(document: &Document, can_gc: CanGc) -> Option<DomRoot<Range>> {
+    document.GetSelection(can_gc)?.GetRangeAt(0).ok()
+}
+
+/// The editing host that contains the whole selection of `document`.
+fn active_editing_host(document: &Document, can_gc: CanGc) -> Option<DomRoot<Element>> {
+    let range = selection_range(document, can_gc)?;
+    let host = editing_host(&range.start_container())?;
+    editing_host(&range.end_container())
+        .filter(|end_host| *end_host == host)
+        .map(|_| host)
+}
+
+/// The range of the selection if it is inside of `host`. Otherwise the selection is first
+/// collapsed at the end of `host`, like when an editing host is focused.
+fn caret_range(host: &Element, can_gc: CanGc) -> Option<DomRoot<Range>> {
+    let document = host.owner_document();
+    let host_node = host.upcast::<Node>();
+    if let Some(range) = selection_range(&document, can_gc) {
+        if host_node.is_inclusive_ancestor_of(&range.start_container()) &&
+            host_node.is_inclusive_ancestor_of(&range.end_container())
+        {
+            return Some(range);
+        }
+    }
+    let selection = document.GetSelection(can_gc)?;
+    selection
+        .Collapse(Some(host_node), host_node.children_count(), can_gc)
+        .ok()?;
+    selection.GetRangeAt(0).ok()
+}
+
+fn collapse_selection(document: &Document, node: &Node, offset: u32, can_gc: CanGc) {
+    if let Some(selection) = document.GetSelection(can_gc) {
+        let _ = selection.Collapse(Some(node), offset, can_gc);
+    }
+}
+
+/// Whether `node` is something that the caret moves over: a non-empty text node or a `<br>`.
+fn is_leaf(node: &Node) -> bool {
+    match node.downcast::<Text>() {
+        Some(_) => node.len() > 0,
+        None => node.is::<HTMLBRElement>(),
+    }
+}
+
+/// The leaves inside of `host` that are editable, in tree order.
+fn leaves(host: &Element) -> Vec<DomRoot<Node>> {
+    host.upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter(|node| is_leaf(node))
+        .filter(|node| editing_host(node).is_some_and(|node_host| &*node_host == host))
+        .collect()
+}
+
+/// The boundary points right before and right after `node` in its parent.
+fn boundary_points_around(node: &Node) -> Option<(DomRoot<Node>, u32, u32)> {
+    let parent = node.GetParentNode()?;
+    let index = node.index();
+    Some((parent, index, index + 1))
+}
+
+/// The last leaf of `host` that ends before the boundary point `(node, offset)`.
+fn previous_leaf(host: &Element, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    leaves(host).into_iter().rev().find(|leaf| {
+        boundary_points_around(leaf).is_some_and(|(parent, _, end)| {
+            bp_position(&parent, end, node, offset).is_some_and(|order| order != Ordering::Greater)
+        })
+    })
+}
+
+/// The first leaf of `host` that starts after the boundary point `(node, offset)`.
+fn next_leaf(host: &Element, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    leaves(host).into_iter().find(|leaf| {
+        boundary_points_around(leaf).is_some_and(|(parent, start, _)| {
+            bp_position(&parent, start, node, offset).is_some_and(|order| order != Ordering::Less)
+        })
+    })
+}
+
+/// Whether `node` is an element that starts a new line of its own. This is decided by the
+/// element's name rather than by its style, which is good enough for editing.
+fn is_block(node: &Node) -> bool {
+    let Some(element) = node.downcast::<HTMLElement>() else {
+        return false;
+    };
+    matches!(
+        *element.upcast::<Element>().local_name(),
+        local_name!("address") |
+            local_name!("article") |
+            local_name!("aside") |
+            local_name!("blockquote") |
+            local_name!("dd") |
+            local_name!("div") |
+            local_name!("dl") |
+            local_name!("dt") |
+            local_name!("fieldset") |
+            local_name!("figure") |
+            local_name!("footer") |
+            local_name!("h1") |
+            local_name!("h2") |
+            local_name!("h3") |
+            local_name!("h4") |
+            local_name!("h5") |
+            local_name!("h6") |
+            local_name!("header") |
+            local_name!("li") |
+            local_name!("main") |
+            local_name!("nav") |
+            local_name!("ol") |
+            local_name!("p") |
+            local_name!("pre") |
+            local_name!("section") |
+            local_name!("ul")
+    )
+}
+
+/// The block that `node` is part of, which is `host` itself if `node` is not inside of a
+/// block in `host`.
+fn enclosing_block(node: &Node, host: &Element) -> DomRoot<Node> {
+    let host = host.upcast::<Node>();
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .find(|ancestor| is_block(ancestor))
+        .unwrap_or_else(|| DomRoot::from_ref(host))
+}
+
+/// The length in UTF-16 code units of the character that ends at `offset` in `data`.
+fn length_of_previous_character(data: &str, offset: u32) -> u32 {
+    let mut position = 0;
+    for character in data.chars() {
+        let length = character.len_utf16() as u32;
+        if position + length >= offset {
+            return length;
+        }
+        position += length;
+    }
+    0
+}
+
+/// The length in UTF-16 code units of the character that starts at `offset` in `data`.
+fn length_of_next_character(data: &str, offset: u32) -> u32 {
+    let mut position = 0;
+    for character in data.chars() {
+        let length = character.len_utf16() as u32;
+        if position >= offset {
+            return length;
+        }
+        position += length;
+    }
+    0
+}
+
+/// Give an empty block a `<br>`, so that it keeps the height of a line and the caret can be
+/// placed in it.
+fn ensure_block_is_not_empty(block: &Node, host: &Element, can_gc: CanGc) {
+    let has_leaves = block
+        .traverse_preorder(ShadowIncluding::No)
+        .any(|node| is_leaf(&node));
+    if has_leaves || block == host.upcast::<Node>() {
+        return;
+    }
+    let document = block.owner_doc();
+    let br = HTMLBRElement::new(local_name!("br"), None, &document, None, can_gc);
+    let _ = block.AppendChild(br.upcast(), can_gc);
+}
+
+fn insert_text(host: &Element, range: &Range, text: &str, can_gc: CanGc) -> bool {
+    if text.is_empty() {
+        return false;
+    }
+    if !range.collapsed() && range.DeleteContents().is_err() {
+        return false;
+    }
+
+    let document = host.owner_document();
+    let text_length = text.encode_utf16().count() as u32;
+    let node = range.start_container();
+    let offset = range.start_offset();
+    if node.is::<Text>() {
+        let character_data = node.downcast::<CharacterData>().unwrap();
+        if character_data
+            .InsertData(offset, DOMString::from(text))
+            .is_err()
+        {
+            return false;
+        }
+        collapse_selection(&document, &node, offset + text_length, can_gc);
+        return true;
+    }
+
+    // Extend a text node right before the caret, so that typing doesn't split the text
+    // into one node per character.
+    let child_before = offset
+        .checked_sub(1)
+        .and_then(|index| node.children().nth(index as usize));
+    if let Some(previous_text) = child_before.filter(|child| child.is::<Text>()) {
+        let length = previous_text.len();
+        previous_text
+            .downcast::<CharacterData>()
+            .unwrap()
+            .AppendData(DOMString::from(text));
+        collapse_selection(&document, &previous_text, length + text_length, can_gc);
+        return true;
+    }
+
+    let new_text = Text::new(DOMString::from(text), &document, can_gc);
+    let reference = node.children().nth(offset as usize);
+    if node
+        .InsertBefore(new_text.upcast(), reference.as_deref(), can_gc)
+        .is_err()
+    {
+        return false;
+    }
+    collapse_selection(&document, new_text.upcast(), text_length, can_gc);
+    true
+}
+
+fn delete(host: &Element, range: &Range, forward: bool, can_gc: CanGc) -> bool {
+    if !range.collapsed() {
+        return range.DeleteContents().is_ok();
+    }
+
+    let document = host.owner_document();
+    let node = range.start_container();
+    let offset = range.start_offset();
+
+    // Delete a character of the text node that the caret is in.
+    if let Some(character_data) = node
+        .downcast::<Text>()
+        .map(Castable::upcast::<CharacterData>)
+    {
+        let data = character_data.Data();
+        if forward && offset < node.len() {
+            let length = length_of_next_character(&data, offset);
+            return character_data.DeleteData(offset, length).is_ok();
+        }
+        if !forward && offset > 0 {
+            let length = length_of_previous_character(&data, offset);
+            let deleted = character_data.DeleteData(offset - length, length).is_ok();
+            collapse_selection(&document, &node, offset - length, can_gc);
+            return deleted;
+        }
+    }
+
+    let leaf = match forward {
+        true => next_leaf(host, &node, offset),
+        false => previous_leaf(host, &node, offset),
+    };
+    let Some(leaf) = leaf else {
+        return false;
+    };
+
+    // Deleting across the edge of a block joins the two blocks.
+    let caret_block = enclosing_block(&node, host);
+    let leaf_block = enclosing_block(&leaf, host);
+    if caret_block != leaf_block {
+        let (into, from, from_node, from_offset) = match forward {
+            true => {
+                let (parent, start, _) = boundary_points_around(&leaf).unwrap();
+                (caret_block, leaf_block, parent, start)
+            },
+            false => (leaf_block, caret_block, node, offset),
+        };
+        let (caret_node, caret_offset) =
+            merge_blocks(host, &into, &from, &from_node, from_offset, can_gc);
+        if !forward {
+            collapse_selection(&document, &caret_node, caret_offset, can_gc);
+        }
+        return true;
+    }
+
+    if leaf.is::<HTMLBRElement>() {
+        leaf.remove_self(can_gc);
+        return true;
+    }
+
+    let character_data = leaf.downcast::<CharacterData>().unwrap();
+    let data = character_data.Data();
+    if forward {
+        let length = length_of_next_character(&data, 0);
+        return character_data.DeleteData(0, length).is_ok();
+    }
+    let leaf_length = leaf.len();
+    let length = length_of_previous_character(&data, leaf_length);
+    let deleted = character_data
+        .DeleteData(leaf_length - length, length)
+        .is_ok();
+    collapse_selection(&document, &leaf, leaf_length - length, can_gc);
+    deleted
+}
+
+/// Move the content of the block `from` to the end of the block `into`, where the content
+/// to move starts at the boundary point `(from_node, from_offset)`. Returns the boundary
+/// point at which the moved content starts.
+fn merge_blocks(
+    host: &Element,
+    into: &Node,
+    from: &Node,
+    from_node: &Node,
+    from_offset: u32,
+    can_gc: CanGc,
+) -> (DomRoot<Node>, u32) {
+    // A `<br>` that only kept `into` from being empty is no longer needed.
+    if let Some(last_child) = into.GetLastChild() {
+        if last_child.is::<HTMLBRElement>() {
+            last_child.remove_self(can_gc);
+        }
+    }
+
+    // `from` is nested in `into`, e.g. a paragraph that follows text directly in the
+    // editing host: unwrap it.
+    if into.is_inclusive_ancestor_of(from) {
+        let parent = from.GetParentNode().unwrap();
+        let index = from.index();
+        for child in from.children() {
+            let _ = parent.InsertBefore(&child, Some(from), can_gc);
+        }
+        from.remove_self(can_gc);
+        return (parent, index);
+    }
+
+    let point = (DomRoot::from_ref(into), into.children_count());
+
+    // `into` is nested in `from`, e.g. text directly in the editing host that follows a
+    // paragraph: move the run of inline content that starts at the boundary point.
+    if from.is_inclusive_ancestor_of(into) {
+        let first = if from_node == from {
+            from.children().nth(from_offset as usize)
+        } else {
+            from_node
+                .inclusive_ancestors(ShadowIncluding::No)
+                .find(|ancestor| ancestor.GetParentNode().as_deref() == Some(from))
+        };
+        let mut next = first;
+        while let Some(child) = next {
+            if is_block(&child) {
+                break;
+            }
+            next = child.GetNextSibling();
+            let _ = into.AppendChild(&child, can_gc);
+        }
+        return point;
+    }
+
+    for child in from.children() {
+        let _ = into.AppendChild(&child, can_gc);
+    }
+    let parent = from.GetParentNode();
+    from.remove_self(can_gc);
+
+    // Don't leave an empty list or similar container behind.
+    if let Some(parent) = parent {
+        if parent.children_count() == 0 && &*parent != host.upcast::<Node>() {
+            parent.remove_self(can_gc);
+        }
+    }
+    point
+}
+
+fn insert_paragraph(host: &Element, range: &Range, can_gc: CanGc) -> bool {
+    if !range.collapsed() && range.DeleteContents().is_err() {
+        return false;
+    }
+
+    let node = range.start_container();
+    let offset = range.start_offset();
+    let block = enclosing_block(&node, host);
+
+    // Text directly in the editing host has no block to split, so start a new line instead.
+    if &*block == host.upcast::<Node>() {
+        return insert_line_break(host, range, can_gc);
+    }
+
+    let Some(new_block) = split_block(&block, &node, offset, can_gc) else {
+        return false;
+    };
+    ensure_block_is_not_empty(&block, host, can_gc);
+    ensure_block_is_not_empty(&new_block, host, can_gc);
+    collapse_selection(&host.owner_document(), &new_block, 0, can_gc);
+    true
+}
+
+/// Split `block` in two at the boundary point `(node, offset)` inside of it, by moving
+/// everything after that point into shallow copies of its ancestors. Returns the copy of
+/// `block`, which follows it.
+fn split_block(block: &Node, node: &Node, offset: u32, can_gc: CanGc) -> Option<DomRoot<Node>> {
+    let (mut container, mut offset) = match node.downcast::<Text>() {
+        Some(text) => {
+            let tail = text.SplitText(offset, can_gc).ok()?;
+            (node.GetParentNode()?, tail.upcast::<Node>().index())
+        },
+        None => (DomRoot::from_ref(node), offset),
+    };
+
+    loop {
+        let copy = Node::clone(
+            &container,
+            None,
+            CloneChildrenFlag::DoNotCloneChildren,
+            can_gc,
+        );
+        for child in container.children().skip(offset as usize) {
+            copy.AppendChild(&child, can_gc).ok()?;
+        }
+        let parent = container.GetParentNode()?;
+        parent
+            .InsertBefore(&copy, container.GetNextSibling().as_deref(), can_gc)
+            .ok()?;
+        if &*container == block {
+            return Some(copy);
+        }
+        offset = copy.index();
+        container = parent;
+    }
+}
+
+fn insert_line_break(host: &Element, range: &Range, can_gc: CanGc) -> bool {
+    if !range.collapsed() && range.DeleteContents().is_err() {
+        return false;
+    }
+
+    let document = host.owner_document();
+    let node = range.start_container();
+    let offset = range.start_offset();
+    let (parent, index) = match node.downcast::<Text>() {
+        Some(text) => {
+            let Some((parent, start, end)) = boundary_points_around(&node) else {
+                return false;
+            };
+            if offset == 0 {
+                (parent, start)
+            } else if offset < node.len() {
+                if text.SplitText(offset, can_gc).is_err() {
+                    return false;
+                }
+                (parent, end)
+            } else {
+                (parent, end)
+            }
+        },
+        None => (node.clone(), offset),
+    };
+
+    let br = HTMLBRElement::new(local_name!("br"), None, &document, None, can_gc);
+    let reference = parent.children().nth(index as usize);
+    if parent
+        .InsertBefore(br.upcast(), reference.as_deref(), can_gc)
+        .is_err()
+    {
+        return false;
+    }
+
+    // A `<br>` at the end of a block doesn't start a new line by itself, so the new line
+    // needs a second one until something is typed into it.
+    let block = enclosing_block(&parent, host);
+    let followed_by_leaf = next_leaf(host, &parent, index + 1)
+        .is_some_and(|leaf| enclosing_block(&leaf, host) == block);
+    if !followed_by_leaf {
+        let placeholder = HTMLBRElement::new(local_name!("br"), None, &document, None, can_gc);
+        let _ = parent.InsertBefore(
+            placeholder.upcast(),
+            br.upcast::<Node>().GetNextSibling().as_deref(),
+            can_gc,
+        );
+    }
+
+    collapse_selection(&document, &parent, index + 1, can_gc);
+    true
+}
+
+/// The inline styles that can be toggled on the selection.
+#[derive(Clone, Copy)]
+enum InlineStyle {
+    Bold,
+    Italic,
+}
+
+impl InlineStyle {
+    /// The element that applies this style to new content.
+    fn local_name(&self) -> LocalName {
+        match self {
+            InlineStyle::Bold => local_name!("b"),
+            InlineStyle::Italic => local_name!("i"),
+        }
+    }
+
+    fn is_applied_by(&self, node: &Node) -> bool {
+        let Some(element) = node.downcast::<HTMLElement>() else {
+            return false;
+        };
+        let local_name = element.upcast::<Element>().local_name();
+        match self {
+            InlineStyle::Bold => {
+                *local_name == local_name!("b") || *local_name == local_name!("strong")
+            },
+            InlineStyle::Italic => {
+                *local_name == local_name!("i") || *local_name == local_name!("em")
+            },
+        }
+    }
+
+    /// The element inside of `host` that applies this style to `node`, if there is one.
+    fn enclosing_element(&self, node: &Node, host: &Node) -> Option<DomRoot<Node>> {
+        node.inclusive_ancestors(ShadowIncluding::No)
+            .take_while(|ancestor| &**ancestor != host)
+            .find(|ancestor| self.is_applied_by(ancestor))
+    }
+}
+
+/// Remove `style` from the selection if all of it has that style, or apply `style` to it
+/// otherwise.
+fn toggle_inline_style(host: &Element, range: &Range, style: InlineStyle, can_gc: CanGc) -> bool {
+    if range.collapsed() {
+        return false;
+    }
+
+    if let Some(styled) = style.enclosing_element(&range.CommonAncestorContainer(), host.upcast()) {
+        let Some(parent) = styled.GetParentNode() else {
+            return false;
+        };
+        let first_child = styled.GetFirstChild();
+        let last_child = styled.GetLastChild();
+        for child in styled.children() {
+            let _ = parent.InsertBefore(&child, Some(&*styled), can_gc);
+        }
+        styled.remove_self(can_gc);
+        if let (Some(first_child), Some(last_child)) = (first_child, last_child) {
+            let _ = range.SetStartBefore(&first_child);
+            let _ = range.SetEndAfter(&last_child);
+        }
+        return true;
+    }
+
+    let document = host.owner_document();
+    let Ok(contents) = range.ExtractContents(can_gc) else {
+        return false;
+    };
+    let element = Element::create(
+        QualName::new(None, ns!(html), style.local_name()),
+        None,
+        &document,
+        ElementCreator::ScriptCreated,
+        CustomElementCreationMode::Synchronous,
+        None,
+        can_gc,
+    );
+    let element = element.upcast::<Node>();
+    if element.AppendChild(contents.upcast(), can_gc).is_err() ||
+        range.InsertNode(element, can_gc).is_err()
+    {
+        return false;
+    }
+    range.SelectNodeContents(element).is_ok()
+}
+
+/// Move the caret for one of the arrow, `Home` or `End` keys, extending the selection
+/// instead if `extend` is true.
+fn move_caret(host: &Element, key: &Key, extend: bool, can_gc: CanGc) {
+    let document = host.owner_document();
+    let Some(range) = caret_range(host, can_gc) else {
+        return;
+    };
+    let Some(selection) = document.GetSelection(can_gc) else {
+        return;
+    };
+    let forward = matches!(key, Key::ArrowRight | Key::End);
+
+    // Without Shift, the first arrow key press collapses a selection to one of its ends.
+    if !extend && !range.collapsed() && matches!(key, Key::ArrowLeft | Key::ArrowRight) {
+        let _ = match forward {
+            true => selection.CollapseToEnd(can_gc),
+            false => selection.CollapseToStart(can_gc),
+        };
+        return;
+    }
+
+    let (node, offset) = match (extend, selection.GetFocusNode()) {
+        (true, Some(focus_node)) => (focus_node, selection.FocusOffset()),
+        _ => (range.start_container(), range.start_offset()),
+    };
+    let position = match key {
+        Key::Home | Key::End => {
+            let block = enclosing_block(&node, host);
+            let offset = if forward { block.children_count() } else { 0 };
+            Some((block, offset))
+        },
+        _ => adjacent_caret_position(host, &node, offset, forward),
+    };
+    let Some((node, offset)) = position else {
+        return;
+    };
+
+    let _ = match extend {
+        true => selection.Extend(&node, offset, can_gc),
+        false => selection.Collapse(Some(&node), offset, can_gc),
+    };
+}
+
+/// The caret position one character after or before the boundary point `(node, offset)`.
+/// Crossing from one block into another counts as a character, since it moves the caret
+/// to another line.
+fn adjacent_caret_position(
+    host: &Element,
+    node: &Node,
+    offset: u32,
+    forward: bool,
+) -> Option<(DomRoot<Node>, u32)> {
+    if let Some(character_data) = node
+        .downcast::<Text>()
+        .map(Castable::upcast::<CharacterData>)
+    {
+        let data = character_data.Data();
+        if forward && offset < node.len() {
+            return Some((
+                DomRoot::from_ref(node),
+                offset + length_of_next_character(&data, offset),
+            ));
+        }
+        if !forward && offset > 0 {
+            return Some((
+                DomRoot::from_ref(node),
+                offset - length_of_previous_character(&data, offset),
+            ));
+        }
+    }
+
+    let leaf = match forward {
+        true => next_leaf(host, node, offset),
+        false => previous_leaf(host, node, offset),
+    }?;
+    if leaf.is::<HTMLBRElement>() {
+        let (parent, start, end) = boundary_points_around(&leaf)?;
+        return Some((parent, if forward { end } else { start }));
+    }
+
+    let same_block = enclosing_block(&leaf, host) == enclosing_block(node, host);
+    let data = leaf.downcast::<CharacterData>()?.Data();
+    let offset = match (forward, same_block) {
+        (true, true) => length_of_next_character(&data, 0),
+        (true, false) => 0,
+        (false, true) => leaf.len() - length_of_previous_character(&data, leaf.len()),
+        (false, false) => leaf.len(),
+    };
+    Some((leaf, offset))
+}
+
+/// A boundary point inside of an editing host, stored as the path of child indices from
+/// the host to the node, so that it can be found again in a copy of the host's contents.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+struct TreePosition {
+    path: Vec<u32>,
+    offset: u32,
+}
+
+impl TreePosition {
+    fn new(host: &Node, node: &Node, offset: u32) -> Option<TreePosition> {
+        let mut path = vec![];
+        for ancestor in node.inclusive_ancestors(ShadowIncluding::No) {
+            if &*ancestor == host {
+                path.reverse();
+                return Some(TreePosition { path, offset });
+            }
+            path.push(ancestor.index());
+        }
+        None
+    }
+
+    fn resolve(&self, host: &Node) -> Option<(DomRoot<Node>, u32)> {
+        let mut node = DomRoot::from_ref(host);
+        for index in &self.path {
+            node = node.children().nth(*index as usize)?;
+        }
+        Some((node, self.offset.min(node.len())))
+    }
+}
+
+/// The contents of an editing host and the selection inside of it, captured before or after
+/// an edit.
+struct CapturedContents {
+    children: Vec<DomRoot<Node>>,
+    selection: Option<(TreePosition, TreePosition)>,
+}
+
+impl CapturedContents {
+    fn capture(host: &Element, can_gc: CanGc) -> CapturedContents {
+        let host = host.upcast::<Node>();
+        let children = host
+            .children()
+            .map(|child| Node::clone(&child, None, CloneChildrenFlag::CloneChildren, can_gc))
+            .collect();
+        let selection = selection_range(&host.owner_doc(), can_gc).and_then(|range| {
+            Some((
+                TreePosition::new(host, &range.start_container(), range.start_offset())?,
+                TreePosition::new(host, &range.end_container(), range.end_offset())?,
+            ))
+        });
+        CapturedContents {
+            children,
+            selection,
+        }
+    }
+}
+
+/// A copy of the contents of an editing host and of the selection inside of it.
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct EditingSnapshot {
+    children: Vec<Dom<Node>>,
+    selection: Option<(TreePosition, TreePosition)>,
+}
+
+impl EditingSnapshot {
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn new(contents: CapturedContents) -> EditingSnapshot {
+        EditingSnapshot {
+            children: contents
+                .children
+                .iter()
+                .map(|child| Dom::from_ref(&**child))
+                .collect(),
+            selection: contents.selection,
+        }
+    }
+
+    /// Deep copies of the children in this snapshot, which can be inserted into the
+    /// editing host while the snapshot itself stays untouched.
+    fn copy_children(&self, can_gc: CanGc) -> Vec<DomRoot<Node>> {
+        self.children
+            .iter()
+            .map(|child| Node::clone(child, None, CloneChildrenFlag::CloneChildren, can_gc))
+            .collect()
+    }
+}
+
+/// An edit, as the contents of the editing host right before and right after it.
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct EditingStep {
+    before: EditingSnapshot,
+    after: EditingSnapshot,
+}
+
+/// The undo and redo history of an editing host.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) struct EditingHistory {
+    /// The edits made in the editing host, oldest first.
+    steps: Vec<EditingStep>,
+    /// The number of steps at the start of `steps` that have not been undone. The steps
+    /// after them can be redone.
+    done: usize,
+    /// Whether the last step was typing that more typing can be added to.
+    typing: bool,
+}
+
+impl EditingHistory {
+    fn extends_typing(&self) -> bool {
+        self.typing && self.done == self.steps.len() && self.done > 0
+    }
+
+    /// Record an edit. If `before` is `None`, the edit extends the typing of the last step.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn record(&mut self, before: Option<CapturedContents>, after: CapturedContents, typing: bool) {
+        let after = EditingSnapshot::new(after);
+        match before {
+            Some(before) => {
+                self.steps.truncate(self.done);
+                self.steps.push(EditingStep {
+                    before: EditingSnapshot::new(before),
+                    after,
+                });
+                if self.steps.len() > MAX_HISTORY_LENGTH {
+                    self.steps.remove(0);
+                }
+                self.done = self.steps.len();
+            },
+            None => {
+                if let Some(step) = self.steps.last_mut() {
+                    step.after = after;
+                }
+            },
+        }
+        self.typing = typing;
+    }
+
+    /// Move one step back (for undo) or forward (for redo) in the history, returning copies
+    /// of the contents to restore.
+    fn step(&mut self, undo: bool, can_gc: CanGc) -> Option<CapturedContents> {
+        self.typing = false;
+        let snapshot = if undo {
+            self.done = self.done.checked_sub(1)?;
+            &self.steps[self.done].before
+        } else {
+            let step = self.steps.get(self.done)?;
+            self.done += 1;
+            &step.after
+        };
+        Some(CapturedContents {
+            children: snapshot.copy_children(can_gc),
+            selection: snapshot.selection.clone(),
+        })
+    }
+}
+
+/// Undo or redo the last edit of `host` by replacing its contents with those from its
+/// editing history.
+fn restore_from_history(host: &Element, undo: bool, can_gc: CanGc) -> bool {
+    let Some(contents) = host.with_editing_history(|history| history.step(undo, can_gc)) else {
+        return false;
+    };
+
+    let host_node = host.upcast::<Node>();
+    for child in host_node.children() {
+        child.remove_self(can_gc);
+    }
+    for child in &contents.children {
+        let _ = host_node.AppendChild(child, can_gc);
+    }
+
+    let document = host.owner_document();
+    let Some(selection) = document.GetSelection(can_gc) else {
+        return true;
+    };
+    let positions = contents
+        .selection
+        .and_then(|(start, end)| Some((start.resolve(host_node)?, end.resolve(host_node)?)));
+    match positions {
+        Some(((start_node, start_offset), (end_node, end_offset))) => {
+            let _ = selection.SetBaseAndExtent(
+                &start_node,
+                start_offset,
+                &end_node,
+                end_offset,
+                can_gc,
+            );
+        },
+        None => {
+            let _ = selection.Collapse(Some(host_node), host_node.children_count(), can_gc);
+        },
+    }
+    true
+}
@@ -0,0 +1,111 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Loading the icons of top-level documents for the embedder, from their `<link rel=icon>`
+//! elements or else from `/favicon.ico`. Icons are fetched and decoded off the script
+//! thread, and the decoded icons are kept in a cache shared by the documents of the process,
+//! so that the pages of a site don't fetch and decode the same icon again.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use constellation_traits::ScriptToConstellationMessage;
+use embedder_traits::{EmbedderMsg, Favicon};
+use net_traits::request::{Destination, Initiator, Referrer};
+use net_traits::{FetchResponseMsg, fetch_async};
+use pixels::{CorsStatus, PixelFormat};
+use servo_url::ServoUrl;
+
+use crate::dom::document::Document;
+use crate::dom::node::NodeTraits;
+use crate::fetch::create_a_potential_cors_request;
+
+/// The number of decoded icons that are kept in the cache.
+const FAVICON_CACHE_SIZE: usize = 64;
+
+static FAVICON_CACHE: LazyLock<Mutex<HashMap<ServoUrl, Favicon>>> = LazyLock::new(Default::default);
+
+/// Load the icon at `url` for the given top-level document, and send it to the embedder
+/// once it has been decoded.
+pub(crate) fn load_favicon(document: &Document, url: ServoUrl) {
+    let window = document.window();
+    let webview_id = document.webview_id();
+    let script_to_constellation_chan = window
+        .as_global_scope()
+        .script_to_constellation_chan()
+        .clone();
+    let send_favicon = move |url: ServoUrl, favicon: Favicon| {
+        let _ = script_to_constellation_chan.send(ScriptToConstellationMessage::ForwardToEmbedder(
+            EmbedderMsg::FaviconLoaded(webview_id, url, favicon),
+        ));
+    };
+
+    if let Some(favicon) = FAVICON_CACHE.lock().unwrap().get(&url).cloned() {
+        send_favicon(url, favicon);
+        return;
+    }
+
+    let request = create_a_potential_cors_request(
+        Some(webview_id),
+        url.clone(),
+        Destination::Image,
+        None,
+        None,
+        Referrer::ReferrerUrl(document.url()),
+        document.insecure_requests_policy(),
+        document.has_trustworthy_ancestor_origin(),
+        document.policy_container().to_owned(),
+    )
+    .initiator(Initiator::Link)
+    .origin(document.origin().immutable().clone())
+    .pipeline_id(Some(window.pipeline_id()));
+
+    let mut bytes = Vec::new();
+    let mut failed = false;
+    fetch_async(
+        &window.as_global_scope().core_resource_thread(),
+        request,
+        None,
+        Box::new(move |message| match message {
+            FetchResponseMsg::ProcessResponse(_, Ok(metadata)) => {
+                failed = !metadata.metadata().status.is_success();
+            },
+            FetchResponseMsg::ProcessResponse(_, Err(_)) => failed = true,
+            FetchResponseMsg::ProcessResponseChunk(_, chunk) => bytes.extend(chunk),
+            FetchResponseMsg::ProcessResponseEOF(_, result) => {
+                if failed || result.is_err() {
+                    return;
+                }
+                let Some(favicon) = decode_favicon(&bytes) else {
+                    return;
+                };
+                let mut cache = FAVICON_CACHE.lock().unwrap();
+                if cache.len() >= FAVICON_CACHE_SIZE {
+                    cache.clear();
+                }
+                cache.insert(url.clone(), favicon.clone());
+                drop(cache);
+                send_favicon(url.clone(), favicon);
+            },
+            _ => {},
+        }),
+    );
+}
+
+/// Decode the first frame of an icon into RGBA pixels.
+fn decode_favicon(bytes: &[u8]) -> Option<Favicon> {
+    let image = pixels::load_from_memory(bytes, CorsStatus::Unsafe)?;
+    let frame = image.frames().next()?;
+    let mut rgba_pixels = frame.bytes.to_vec();
+    match image.format {
+        PixelFormat::BGRA8 => pixels::rgba8_byte_swap_colors_inplace(&mut rgba_pixels),
+        PixelFormat::RGBA8 => {},
+        _ => return None,
+    }
+    Some(Favicon {
+        width: frame.width,
+        height: frame.height,
+        rgba_pixels,
+    })
+}
@@ -7,6 +7,8 @@ use std::sync::{Arc, Mutex};
 
 use base::id::WebViewId;
 use ipc_channel::ipc;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
 use net_traits::policy_container::{PolicyContainer, RequestPolicyContainer};
 use net_traits::request::{
     CorsSettings, CredentialsMode, Destination, InsecureRequestsPolicy, Referrer,
@@ -19,6 +21,9 @@ use net_traits::{
 };
 use servo_url::ServoUrl;
 
+use crate::body::BodyMixin;
+use crate::dom::abortsignal::AbortAlgorithm;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::{
     RequestInfo, RequestInit, RequestMethods,
 };
@@ -40,12 +45,50 @@ use crate::dom::response::Response;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::network_listener::{self, PreInvoke, ResourceTimingListener, submit_timing_data};
 use crate::realms::{InRealm, enter_realm};
-use crate::script_runtime::CanGc;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
-struct FetchContext {
+pub(crate) struct FetchContext {
     fetch_promise: Option<TrustedPromise>,
+    request_object: Trusted<Request>,
     response_object: Trusted<Response>,
     resource_timing: ResourceFetchTiming,
+    /// Used to abort the ongoing fetch when the request's signal is aborted.
+    request_id: RequestId,
+    /// <https://fetch.spec.whatwg.org/#dom-global-fetch> locallyAborted
+    locally_aborted: bool,
+}
+
+impl FetchContext {
+    /// Step 11 of <https://fetch.spec.whatwg.org/#dom-global-fetch>
+    pub(crate) fn abort_fetch(
+        &mut self,
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        abort_reason: HandleValue,
+        can_gc: CanGc,
+    ) {
+        // Step 11.1. Set locallyAborted to true.
+        self.locally_aborted = true;
+
+        // Step 11.2. Assert: controller is non-null.
+        // Step 11.3. Abort controller with requestObject’s signal’s abort reason.
+        cancel_async_fetch(vec![self.request_id]);
+
+        // Step 11.4. Abort the fetch() call with p, request, responseObject,
+        //            and requestObject’s signal’s abort reason.
+        let Some(promise) = self.fetch_promise.take() else {
+            return;
+        };
+        abort_fetch_call(
+            cx,
+            global,
+            &promise.root(),
+            &self.request_object.root(),
+            Some(&self.response_object.root()),
+            abort_reason,
+            can_gc,
+        );
+    }
 }
 
 /// RAII fetch canceller object. By default initialized to not having a canceller
@@ -125,6 +168,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         policy_container: request.policy_container,
         insecure_requests_policy: request.insecure_requests_policy,
         has_trustworthy_ancestor_origin: request.has_trustworthy_ancestor_origin,
+        top_level_origin: request.top_level_origin,
         https_state: request.https_state,
         response_tainting: request.response_tainting,
         crash: None,
@@ -151,24 +195,42 @@ pub(crate) fn Fetch(
 
     // Step 2. Let requestObject be the result of invoking the initial value of Request as constructor
     //         with input and init as arguments. If this throws an exception, reject p with it and return p.
-    let request = match Request::Constructor(global, None, can_gc, input, init) {
+    let request_object = match Request::Constructor(global, None, can_gc, input, init) {
         Err(e) => {
             response.error_stream(e.clone(), can_gc);
             promise.reject_error(e, can_gc);
             return promise;
         },
-        Ok(r) => {
-            // Step 3. Let request be requestObject’s request.
-            r.get_request()
-        },
+        Ok(r) => r,
     };
+    // Step 3. Let request be requestObject’s request.
+    let request = request_object.get_request();
     let timing_type = request.timing_type();
 
     let mut request_init = request_init_from_request(request);
     request_init.policy_container =
         RequestPolicyContainer::PolicyContainer(global.policy_container());
 
-    // TODO: Step 4. If requestObject’s signal is aborted, then: [..]
+    // Step 4. If requestObject’s signal is aborted, then:
+    let signal = request_object.Signal(can_gc);
+    if signal.aborted() {
+        // Step 4.1. Abort the fetch() call with p, request, null, and requestObject’s signal’s abort reason.
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut abort_reason = UndefinedValue());
+        signal.Reason(cx, abort_reason.handle_mut());
+        abort_fetch_call(
+            cx,
+            global,
+            &promise,
+            &request_object,
+            None,
+            abort_reason.handle(),
+            can_gc,
+        );
+
+        // Step 4.2. Return p.
+        return promise;
+    }
 
     // Step 5. Let globalObject be request’s client’s global object.
     // NOTE:   We already get the global object as an argument
@@ -179,16 +241,28 @@ pub(crate) fn Fetch(
         request_init.service_workers_mode = ServiceWorkersMode::None;
     }
 
-    // TODO: Steps 8-11, abortcontroller stuff
-
-    // Step 12. Set controller to the result of calling fetch given request and
-    //           processResponse given response being these steps: [..]
+    // Step 8. Let relevantRealm be this’s relevant realm.
+    // Step 9. Let locallyAborted be false.
+    // Step 10. Let controller be null.
+    // NOTE: locallyAborted is kept on the fetch context, and the request id stands in for controller.
     let fetch_context = Arc::new(Mutex::new(FetchContext {
         fetch_promise: Some(TrustedPromise::new(promise.clone())),
+        request_object: Trusted::new(&*request_object),
         response_object: Trusted::new(&*response),
         resource_timing: ResourceFetchTiming::new(timing_type),
+        request_id: request_init.id,
+        locally_aborted: false,
     }));
 
+    // Step 11. Add the following abort steps to requestObject’s signal: [..]
+    // NOTE: The steps are implemented in `FetchContext::abort_fetch`.
+    let cx = GlobalScope::get_cx();
+    rooted!(in(*cx) let abort_algorithm = AbortAlgorithm::Fetch(fetch_context.clone()));
+    signal.add(&abort_algorithm);
+
+    // Step 12. Set controller to the result of calling fetch given request and
+    //           processResponse given response being these steps: [..]
+
     global.fetch(
         request_init,
         fetch_context,
@@ -199,6 +273,36 @@ pub(crate) fn Fetch(
     promise
 }
 
+/// <https://fetch.spec.whatwg.org/#abort-fetch>
+fn abort_fetch_call(
+    cx: SafeJSContext,
+    global: &GlobalScope,
+    promise: &Promise,
+    request_object: &Request,
+    response_object: Option<&Response>,
+    error: HandleValue,
+    can_gc: CanGc,
+) {
+    // Step 1. Reject promise with error.
+    promise.reject(cx, error, can_gc);
+
+    // Step 2. If request’s body is non-null and is readable, then cancel request’s body with error.
+    if let Some(body) = request_object.body() {
+        if body.is_readable() {
+            body.cancel(cx, global, error, can_gc);
+        }
+    }
+
+    // Step 3. If responseObject is null, then return.
+    let Some(response_object) = response_object else {
+        return;
+    };
+
+    // Step 4. Let response be responseObject’s response.
+    // Step 5. If response’s body is non-null and is readable, then error response’s body with error.
+    response_object.error_stream_with_reason(error, can_gc);
+}
+
 impl PreInvoke for FetchContext {}
 
 impl FetchResponseListener for FetchContext {
@@ -216,6 +320,11 @@ impl FetchResponseListener for FetchContext {
         _: RequestId,
         fetch_metadata: Result<FetchMetadata, NetworkError>,
     ) {
+        // Step 12.1. If locallyAborted is true, then abort these steps.
+        if self.locally_aborted {
+            return;
+        }
+
         let promise = self
             .fetch_promise
             .take()
@@ -280,6 +389,9 @@ impl FetchResponseListener for FetchContext {
     }
 
     fn process_response_chunk(&mut self, _: RequestId, chunk: Vec<u8>) {
+        if self.locally_aborted {
+            return;
+        }
         let response = self.response_object.root();
         response.stream_chunk(chunk, CanGc::note());
     }
@@ -289,6 +401,9 @@ impl FetchResponseListener for FetchContext {
         _: RequestId,
         _response: Result<ResourceFetchTiming, NetworkError>,
     ) {
+        if self.locally_aborted {
+            return;
+        }
         let response = self.response_object.root();
         let _ac = enter_realm(&*response);
         response.finish(CanGc::note());
@@ -359,6 +474,7 @@ pub(crate) fn load_whole_resource(
     loop {
         match action_receiver.recv().unwrap() {
             FetchResponseMsg::ProcessRequestBody(..) |
+            FetchResponseMsg::ProcessRequestBodyChunkLength(..) |
             FetchResponseMsg::ProcessRequestEOF(..) |
             FetchResponseMsg::ProcessCspViolations(..) => {},
             FetchResponseMsg::ProcessResponse(_, Ok(m)) => {
@@ -0,0 +1,301 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Finding text in a page, for the find bar of the embedder and for `window.find()`.
+//!
+//! The rendered text of a document is matched as the user sees it: runs of white space
+//! match a single space and text doesn't match across block boundaries. The matches of the
+//! text that the user is finding are highlighted, with the active match set apart from the
+//! others, until the embedder stops finding.
+
+use std::cell::Cell;
+
+use embedder_traits::{FindInPageRequest, FindInPageResult};
+use layout_api::HighlightKind;
+use style::values::computed::box_::DisplayOutside;
+
+use crate::dom::abstractrange::AbstractRange;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::range::Range;
+use crate::dom::text::Text;
+use crate::script_runtime::CanGc;
+
+/// The state of finding text in the page of a document for the embedder.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) struct FindInPage {
+    /// The request that found the current matches, if the user is finding text.
+    #[no_trace]
+    request: DomRefCell<Option<FindInPageRequest>>,
+
+    /// The matches of the text that the user is finding, in tree order.
+    matches: DomRefCell<Vec<Dom<Range>>>,
+
+    /// The index in `matches` of the active match.
+    active_match: Cell<Option<usize>>,
+}
+
+impl FindInPage {
+    /// Find the text of `request` in `document`. When the same text was found by the previous
+    /// request, the next match, or the previous one when finding backwards, becomes active.
+    /// Otherwise the matches are found again and the first or the last of them becomes
+    /// active. The active match is scrolled into view.
+    pub(crate) fn find(
+        &self,
+        document: &Document,
+        request: FindInPageRequest,
+        can_gc: CanGc,
+    ) -> FindInPageResult {
+        let same_text = self.request.borrow().as_ref().is_some_and(|previous| {
+            previous.text == request.text && previous.case_sensitive == request.case_sensitive
+        });
+
+        let number_of_matches = if same_text {
+            self.matches.borrow().len()
+        } else {
+            let matches = find_text(document, &request.text, request.case_sensitive, can_gc);
+            *self.matches.borrow_mut() = matches
+                .iter()
+                .map(|range| Dom::from_ref(&**range))
+                .collect();
+            self.active_match.set(None);
+            matches.len()
+        };
+
+        let active_match = match (self.active_match.get(), number_of_matches) {
+            (_, 0) => None,
+            (None, _) if request.backwards => Some(number_of_matches - 1),
+            (None, _) => Some(0),
+            (Some(index), _) if request.backwards => {
+                Some((index + number_of_matches - 1) % number_of_matches)
+            },
+            (Some(index), _) => Some((index + 1) % number_of_matches),
+        };
+        self.active_match.set(active_match);
+        *self.request.borrow_mut() = Some(request);
+
+        if let Some(active_match) = active_match {
+            let range = DomRoot::from_ref(&*self.matches.borrow()[active_match]);
+            scroll_range_into_view(&range, can_gc);
+        }
+        document.update_text_highlights();
+
+        FindInPageResult {
+            number_of_matches,
+            active_match_index: active_match,
+        }
+    }
+
+    /// Stop finding text, which removes the highlights of the matches.
+    pub(crate) fn stop(&self, document: &Document) {
+        *self.request.borrow_mut() = None;
+        self.matches.borrow_mut().clear();
+        self.active_match.set(None);
+        document.update_text_highlights();
+    }
+
+    /// The matches of the text that the user is finding, with the kind of highlight that
+    /// each of them is painted with.
+    pub(crate) fn highlighted_ranges(&self) -> Vec<(DomRoot<AbstractRange>, HighlightKind)> {
+        let active_match = self.active_match.get();
+        self.matches
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, range)| {
+                let kind = if Some(index) == active_match {
+                    HighlightKind::ActiveFindMatch
+                } else {
+                    HighlightKind::FindMatch
+                };
+                (DomRoot::from_ref(range.upcast::<AbstractRange>()), kind)
+            })
+            .collect()
+    }
+}
+
+/// A character of the rendered text of a document, with the text node that it comes from
+/// and its UTF-16 offset in the data of that node.
+struct RenderedCharacter {
+    character: char,
+    text: DomRoot<Text>,
+    offset: u32,
+    length: u32,
+}
+
+/// Find the ranges of the rendered text of `document` that match `needle`, in tree order.
+/// Matches can't overlap.
+pub(crate) fn find_text(
+    document: &Document,
+    needle: &str,
+    case_sensitive: bool,
+    can_gc: CanGc,
+) -> Vec<DomRoot<Range>> {
+    let fold = |character: char| -> char {
+        if case_sensitive {
+            character
+        } else {
+            character.to_lowercase().next().unwrap_or(character)
+        }
+    };
+    let needle: Vec<char> = collapse_white_space(needle).chars().map(fold).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for block in rendered_text_blocks(document, can_gc) {
+        let haystack: Vec<char> = block
+            .iter()
+            .map(|character| fold(character.character))
+            .collect();
+        let mut start = 0;
+        while start + needle.len() <= haystack.len() {
+            if haystack[start..start + needle.len()] != needle[..] {
+                start += 1;
+                continue;
+            }
+
+            let first = &block[start];
+            let last = &block[start + needle.len() - 1];
+            matches.push(Range::new(
+                document,
+                first.text.upcast(),
+                first.offset,
+                last.text.upcast(),
+                last.offset + last.length,
+                can_gc,
+            ));
+            start += needle.len();
+        }
+    }
+    matches
+}
+
+/// Whether the text of `range` is a whole word, that is not preceded or followed by a
+/// letter or a digit in the data of its text nodes.
+pub(crate) fn is_whole_word(range: &Range) -> bool {
+    let is_word_character = |character: Option<char>| {
+        character.is_some_and(|character| character.is_alphanumeric() || character == '_')
+    };
+    let character_at = |node: &Node, offset: u32, before: bool| -> Option<char> {
+        let data = node.downcast::<CharacterData>()?.data();
+        let mut units = 0;
+        let mut previous = None;
+        for character in data.chars() {
+            if units >= offset as usize {
+                return if before { previous } else { Some(character) };
+            }
+            units += character.len_utf16();
+            previous = Some(character);
+        }
+        if before { previous } else { None }
+    };
+
+    !is_word_character(character_at(
+        &range.start_container(),
+        range.start_offset(),
+        true,
+    )) && !is_word_character(character_at(
+        &range.end_container(),
+        range.end_offset(),
+        false,
+    ))
+}
+
+/// Scroll the element that contains the start of `range` into the center of the view.
+pub(crate) fn scroll_range_into_view(range: &Range, can_gc: CanGc) {
+    let Some(element) = range
+        .start_container()
+        .inclusive_ancestors(ShadowIncluding::No)
+        .find_map(DomRoot::downcast::<Element>)
+    else {
+        return;
+    };
+    element.scroll_into_view(
+        ScrollBehavior::Auto,
+        ScrollLogicalPosition::Center,
+        ScrollLogicalPosition::Nearest,
+        can_gc,
+    );
+}
+
+/// The rendered text of `document`, split into the text of its blocks, with runs of white
+/// space collapsed to a single space. Text that isn't rendered, like the text of elements
+/// with `display: none`, is left out.
+fn rendered_text_blocks(document: &Document, can_gc: CanGc) -> Vec<Vec<RenderedCharacter>> {
+    let Some(root) = document.GetDocumentElement() else {
+        return Vec::new();
+    };
+
+    let mut blocks: Vec<Vec<RenderedCharacter>> = Vec::new();
+    let mut current_block: Option<DomRoot<Node>> = None;
+    for node in root.upcast::<Node>().traverse_preorder(ShadowIncluding::No) {
+        let Some(text) = node.downcast::<Text>() else {
+            continue;
+        };
+        let Some(parent) = node.GetParentElement() else {
+            continue;
+        };
+        // Elements that aren't rendered, like those in a subtree with `display: none`, have
+        // no style. Inert text can't be found either.
+        if parent.style(can_gc).is_none() || node.is_inert() {
+            continue;
+        }
+
+        let block = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .skip(1)
+            .find(|ancestor| {
+                ancestor
+                    .style(can_gc)
+                    .is_some_and(|style| style.get_box().display.outside() == DisplayOutside::Block)
+            });
+        if blocks.is_empty() || block != current_block {
+            blocks.push(Vec::new());
+            current_block = block;
+        }
+
+        let characters = blocks.last_mut().unwrap();
+        let data = text.upcast::<CharacterData>().data();
+        let mut offset = 0;
+        for character in data.chars() {
+            let length = character.len_utf16() as u32;
+            let character = if character.is_whitespace() {
+                ' '
+            } else {
+                character
+            };
+            let previous_is_space = characters
+                .last()
+                .is_none_or(|previous| previous.character == ' ');
+            if character != ' ' || !previous_is_space {
+                characters.push(RenderedCharacter {
+                    character,
+                    text: DomRoot::from_ref(text),
+                    offset,
+                    length,
+                });
+            }
+            offset += length;
+        }
+    }
+    blocks
+}
+
+/// Collapse the runs of white space in `text` to a single space, and remove white space at
+/// its start and its end.
+fn collapse_white_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
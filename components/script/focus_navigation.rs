@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Sequential focus navigation, i.e. moving focus with the Tab key. See
+//! <https://html.spec.whatwg.org/multipage/#sequential-focus-navigation>.
+//!
+//! The document and every shadow root form a focus navigation scope, whose elements are
+//! ordered by `tabindex`: elements with a positive `tabindex` come first in increasing
+//! order of their value, followed by the remaining elements in tree order. The contents
+//! of a shadow root are inserted into the order of the enclosing scope right after their
+//! host, or in place of the host if it delegates focus, which gives a single flattened
+//! order for the whole document.
+//!
+//! Nested browsing contexts take part in the order of their container: when navigation
+//! reaches an `<iframe>`, it continues at the start or the end of its document and when
+//! it runs past either end of a nested document, it continues after or before the
+//! container in the parent document. Those documents may live in other script threads,
+//! so this handover goes through the constellation.
+
+use constellation_traits::{ScriptToConstellationMessage, SequentialFocusDirection};
+use layout_api::QueryMsg;
+
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::{Document, FocusInitiator};
+use crate::dom::element::Element;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::node::{Node, NodeFlags, ShadowIncluding};
+use crate::script_runtime::CanGc;
+
+/// Move focus to the element that follows or precedes `starting_point` in the sequential
+/// focus navigation order of `document`. If `starting_point` is `None`, navigation starts
+/// at the start or the end of the document.
+pub(crate) fn move_sequential_focus(
+    document: &Document,
+    direction: SequentialFocusDirection,
+    starting_point: Option<&Element>,
+    can_gc: CanGc,
+) {
+    // Whether elements are rendered and focusable depends on up-to-date style.
+    let window = document.window();
+    window.layout_reflow(QueryMsg::StyleQuery, can_gc);
+
+    let order = sequential_focus_navigation_order(document.upcast());
+    let next = match starting_point {
+        Some(starting_point) => match order
+            .iter()
+            .position(|element| **element == *starting_point)
+        {
+            Some(index) => match direction {
+                SequentialFocusDirection::Forward => order.get(index + 1),
+                SequentialFocusDirection::Backward => {
+                    index.checked_sub(1).and_then(|index| order.get(index))
+                },
+            },
+            // An element that isn't part of the order, for instance one that was focused
+            // by clicking it, continues navigation from its position in the tree.
+            None => {
+                let starting_node = starting_point.upcast::<Node>();
+                match direction {
+                    SequentialFocusDirection::Forward => order
+                        .iter()
+                        .find(|element| starting_node.is_before(element.upcast())),
+                    SequentialFocusDirection::Backward => order
+                        .iter()
+                        .rev()
+                        .find(|element| element.upcast::<Node>().is_before(starting_node)),
+                }
+            },
+        },
+        None => match direction {
+            SequentialFocusDirection::Forward => order.first(),
+            SequentialFocusDirection::Backward => order.last(),
+        },
+    };
+
+    let Some(next) = next else {
+        if window.is_top_level() {
+            // There is nothing left to focus in this direction, so the viewport gets
+            // focus and the next navigation starts over at the other end.
+            document.request_focus(None, FocusInitiator::Local, can_gc);
+        } else {
+            window.send_to_constellation(
+                ScriptToConstellationMessage::MoveSequentialFocusOutOfDocument(direction),
+            );
+        }
+        return;
+    };
+
+    if let Some(browsing_context_id) = next
+        .downcast::<HTMLIFrameElement>()
+        .and_then(HTMLIFrameElement::browsing_context_id)
+    {
+        window.send_to_constellation(ScriptToConstellationMessage::MoveSequentialFocusIntoChild(
+            browsing_context_id,
+            direction,
+        ));
+        return;
+    }
+
+    document.request_focus(Some(&**next), FocusInitiator::Local, can_gc);
+    next.scroll_into_view_if_needed(can_gc);
+}
+
+/// The flattened sequential focus navigation order of the focus navigation scope rooted
+/// at `scope_root`, which is either a document or a shadow root.
+fn sequential_focus_navigation_order(scope_root: &Node) -> Vec<DomRoot<Element>> {
+    let mut order = Vec::new();
+    append_scope_to_order(scope_root, &mut order);
+    order
+}
+
+fn append_scope_to_order(scope_root: &Node, order: &mut Vec<DomRoot<Element>>) {
+    let mut entries: Vec<(i32, DomRoot<Element>)> = scope_root
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .filter_map(|element| Some((tabindex_in_scope(&element)?, element)))
+        .filter(|(tabindex, _)| *tabindex >= 0)
+        .collect();
+
+    // Positive values come first in increasing order and zero comes last. The sort is
+    // stable, so elements with the same value stay in tree order.
+    entries.sort_by_key(|(tabindex, _)| (*tabindex == 0, *tabindex));
+
+    for (_, element) in entries {
+        let shadow_root = element.shadow_root();
+        let delegates_focus = shadow_root
+            .as_ref()
+            .is_some_and(|shadow_root| shadow_root.DelegatesFocus());
+        if !delegates_focus && is_sequentially_focusable(&element) {
+            order.push(element);
+        }
+        if let Some(shadow_root) = shadow_root {
+            append_scope_to_order(shadow_root.upcast(), order);
+        }
+    }
+}
+
+/// The `tabindex` value that orders `element` within its focus navigation scope, or
+/// `None` if it takes no part in the order at all. Shadow hosts that aren't focusable
+/// themselves still take part, as they own the scope of their shadow root.
+fn tabindex_in_scope(element: &Element) -> Option<i32> {
+    if element.upcast::<Node>().is_display_none() {
+        return None;
+    }
+    if !is_sequentially_focusable(element) && !element.is_shadow_host() {
+        return None;
+    }
+    Some(element.get_int_attribute(&local_name!("tabindex"), 0))
+}
+
+fn is_sequentially_focusable(element: &Element) -> bool {
+    if !element.is_focusable_area() {
+        return false;
+    }
+    if element.has_attribute(&local_name!("tabindex")) {
+        return true;
+    }
+    // Anchors are always focusable areas, but only links are reachable with the keyboard.
+    if element.is::<HTMLAnchorElement>() {
+        return element.has_attribute(&local_name!("href"));
+    }
+    element
+        .upcast::<Node>()
+        .get_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE) ||
+        element.is::<HTMLInputElement>()
+}
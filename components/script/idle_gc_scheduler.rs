@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scheduling of garbage collection work in the idle time between frames.
+//!
+//! SpiderMonkey normally collects garbage when an allocation crosses one of its heap
+//! thresholds, which tends to happen in the middle of running script or updating the
+//! rendering and makes the frame being produced miss its deadline. Instead, the
+//! [`IdleGarbageCollectionScheduler`] follows the frames that the compositor asks for and,
+//! once the `ScriptThread` runs out of work, gives SpiderMonkey a chance to collect garbage
+//! eagerly or run the next slice of an incremental collection, within a budget that fits
+//! in the time left before the next frame.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use js::jsapi::{JS_MaybeGC, JS_SetGCParameter, JSGCParamKey};
+use servo_config::pref;
+
+use crate::script_runtime::JSContext;
+
+/// The interval between frames assumed until the compositor has produced a few of them.
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// Frames that are further apart than this are not part of the same animation, so the time
+/// between them says nothing about the refresh rate of the compositor.
+const MAX_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The longest idle period when nothing is animating, which matches the longest deadline
+/// of `requestIdleCallback`, so that new input is not delayed for too long.
+const MAX_IDLE_PERIOD: Duration = Duration::from_millis(50);
+
+/// The smallest budget worth starting any garbage collection work for.
+const MIN_SLICE_BUDGET: Duration = Duration::from_millis(1);
+
+/// The weight of the most recent frame in the running averages of frame timings.
+const AVERAGE_WEIGHT: f64 = 0.2;
+
+#[derive(Default)]
+pub(crate) struct IdleGarbageCollectionScheduler {
+    /// The time at which the compositor last asked for a frame.
+    last_frame_start: Cell<Option<Instant>>,
+    /// The average interval between the frames that the compositor asks for.
+    frame_interval: Cell<Option<Duration>>,
+    /// The average time left before the next frame once the rendering of a frame has
+    /// been updated.
+    frame_headroom: Cell<Option<Duration>>,
+}
+
+impl IdleGarbageCollectionScheduler {
+    /// Note that the compositor asked for a new frame and that its rendering update
+    /// started at `now`. `refresh_interval` is the interval between frames reported by
+    /// the compositor, if it did so.
+    pub(crate) fn frame_started(&self, now: Instant, refresh_interval: Option<Duration>) {
+        if let Some(refresh_interval) = refresh_interval {
+            self.frame_interval.set(Some(refresh_interval));
+        } else if let Some(last_frame_start) = self.last_frame_start.get() {
+            let interval = now.saturating_duration_since(last_frame_start);
+            if interval <= MAX_FRAME_INTERVAL {
+                self.frame_interval
+                    .set(Some(running_average(self.frame_interval.get(), interval)));
+            }
+        }
+        self.last_frame_start.set(Some(now));
+    }
+
+    /// Note that the rendering update of the current frame finished at `now`.
+    pub(crate) fn frame_finished(&self, now: Instant) {
+        let Some(last_frame_start) = self.last_frame_start.get() else {
+            return;
+        };
+        let headroom = self
+            .frame_interval()
+            .saturating_sub(now.saturating_duration_since(last_frame_start));
+        self.frame_headroom
+            .set(Some(running_average(self.frame_headroom.get(), headroom)));
+    }
+
+    fn frame_interval(&self) -> Duration {
+        self.frame_interval.get().unwrap_or(DEFAULT_FRAME_INTERVAL)
+    }
+
+    /// The time by which garbage collection work has to be finished, so that it does not
+    /// delay the next frame if `animating`, or new input otherwise.
+    fn idle_deadline(&self, animating: bool, now: Instant) -> Instant {
+        match self.last_frame_start.get() {
+            Some(last_frame_start) if animating => {
+                // Frames that were missed will not be asked for anymore, so skip past them.
+                let interval = self.frame_interval();
+                let mut next_frame_start = last_frame_start + interval;
+                while next_frame_start <= now {
+                    next_frame_start += interval;
+                }
+                next_frame_start
+            },
+            _ => now + MAX_IDLE_PERIOD,
+        }
+    }
+
+    /// The budget of the next slice of garbage collection work. When animating, this
+    /// adapts to the time that recent frames had left to spare, so that pages that keep
+    /// the `ScriptThread` busy for most of each frame get shorter slices.
+    fn slice_budget(&self, animating: bool) -> Duration {
+        let max_budget = Duration::from_millis(pref!(js_mem_gc_incremental_slice_ms).max(0) as u64);
+        let budget = match self.frame_headroom.get() {
+            Some(frame_headroom) if animating => max_budget.min(frame_headroom * 3 / 4),
+            _ => max_budget,
+        };
+        budget.max(MIN_SLICE_BUDGET)
+    }
+
+    /// Called when the `ScriptThread` has no more work to do. Lets SpiderMonkey collect
+    /// garbage if it wants to and if there is enough time left before the idle deadline.
+    #[allow(unsafe_code)]
+    pub(crate) fn perform_idle_work(&self, cx: JSContext, animating: bool) {
+        if !pref!(js_mem_gc_idle_scheduling_enabled) {
+            return;
+        }
+
+        let now = Instant::now();
+        let idle_period = self
+            .idle_deadline(animating, now)
+            .saturating_duration_since(now);
+        let budget = self.slice_budget(animating);
+        if idle_period < budget {
+            return;
+        }
+
+        // `JS_MaybeGC` runs a slice of the incremental collection in progress, if any, or
+        // starts a new one when the heap is close to the thresholds that would otherwise
+        // trigger a collection during the next allocations.
+        unsafe {
+            JS_SetGCParameter(
+                *cx,
+                JSGCParamKey::JSGC_SLICE_TIME_BUDGET_MS,
+                budget.as_millis() as u32,
+            );
+            JS_MaybeGC(*cx);
+
+            // Slices that allocations trigger later on keep using the configured budget.
+            JS_SetGCParameter(
+                *cx,
+                JSGCParamKey::JSGC_SLICE_TIME_BUDGET_MS,
+                pref!(js_mem_gc_incremental_slice_ms).clamp(0, 100_000) as u32,
+            );
+        }
+    }
+}
+
+fn running_average(average: Option<Duration>, sample: Duration) -> Duration {
+    match average {
+        Some(average) => average.mul_f64(1. - AVERAGE_WEIGHT) + sample.mul_f64(AVERAGE_WEIGHT),
+        None => sample,
+    }
+}
@@ -0,0 +1,480 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Conversions between JS values and the keys, key ranges and key paths of IndexedDB.
+
+use std::ptr;
+
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{
+    ClippedTime, DateGetMsecSinceEpoch, ESClass, GetBuiltinClass, IsArrayBufferObject,
+    JS_GetOwnUCPropertyDescriptor, JS_GetStringLength, JS_GetUCProperty,
+    JS_IsArrayBufferViewObject, JS_NewPlainObject, JS_SetUCProperty, JSObject, JSPROP_ENUMERATE,
+    NewArrayObject1, NewDateObject, PropertyDescriptor,
+};
+use js::jsval::{DoubleValue, NullValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{GetArrayLength, IsArrayObject, JS_DefineElement, JS_GetElement};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use net_traits::indexeddb_thread::{IndexMetadata, IndexedDBKeyRange, IndexedDBKeyType, KeyPath};
+
+use crate::dom::bindings::conversions::{jsstring_to_str, root_from_handlevalue};
+use crate::dom::bindings::error::Error;
+use crate::dom::idbkeyrange::IDBKeyRange;
+use crate::script_runtime::JSContext as SafeJSContext;
+
+/// Converts a key to a JS value, see
+/// <https://w3c.github.io/IndexedDB/#convert-a-key-to-a-value>.
+#[allow(unsafe_code)]
+pub(crate) fn key_type_to_jsval(
+    cx: SafeJSContext,
+    key: &IndexedDBKeyType,
+    mut result: MutableHandleValue,
+) {
+    match key {
+        IndexedDBKeyType::Number(number) => result.set(DoubleValue(*number)),
+        IndexedDBKeyType::String(string) => unsafe { string.to_jsval(*cx, result) },
+        IndexedDBKeyType::Binary(bytes) => unsafe {
+            rooted!(in(*cx) let mut buffer = ptr::null_mut::<JSObject>());
+            assert!(
+                ArrayBuffer::create(*cx, CreateWith::Slice(bytes), buffer.handle_mut()).is_ok()
+            );
+            result.set(ObjectValue(buffer.get()));
+        },
+        IndexedDBKeyType::Date(time) => unsafe {
+            rooted!(in(*cx) let date = NewDateObject(*cx, ClippedTime { t: *time }));
+            result.set(ObjectValue(date.get()));
+        },
+        IndexedDBKeyType::Array(keys) => unsafe {
+            rooted!(in(*cx) let array = NewArrayObject1(*cx, keys.len()));
+            for (index, key) in keys.iter().enumerate() {
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                key_type_to_jsval(cx, key, value.handle_mut());
+                JS_DefineElement(
+                    *cx,
+                    array.handle(),
+                    index as u32,
+                    value.handle(),
+                    JSPROP_ENUMERATE as u32,
+                );
+            }
+            result.set(ObjectValue(array.get()));
+        },
+    }
+}
+
+/// Converts a key path to the value of the `keyPath` attribute of an object store or index.
+#[allow(unsafe_code)]
+pub(crate) fn key_path_to_jsval(
+    cx: SafeJSContext,
+    key_path: Option<&KeyPath>,
+    mut result: MutableHandleValue,
+) {
+    match key_path {
+        None => result.set(NullValue()),
+        Some(KeyPath::String(path)) => unsafe { path.to_jsval(*cx, result) },
+        Some(KeyPath::Sequence(paths)) => unsafe { paths.to_jsval(*cx, result) },
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#convert-value-to-key>
+///
+/// Invalid values are reported as a "DataError", other errors mean an exception is pending.
+#[allow(unsafe_code)]
+pub(crate) fn convert_value_to_key(
+    cx: SafeJSContext,
+    input: HandleValue,
+    seen: Option<Vec<*mut JSObject>>,
+) -> Result<IndexedDBKeyType, Error> {
+    // Step 1: If seen was not given, then let seen be a new empty set.
+    let mut seen = seen.unwrap_or_default();
+
+    // Step 3
+    if input.is_number() {
+        if input.to_number().is_nan() {
+            return Err(Error::Data);
+        }
+        return Ok(IndexedDBKeyType::Number(input.to_number()));
+    }
+
+    if input.is_string() {
+        let string_ptr = std::ptr::NonNull::new(input.to_string()).unwrap();
+        let key = unsafe { jsstring_to_str(*cx, string_ptr).str().to_string() };
+        return Ok(IndexedDBKeyType::String(key));
+    }
+
+    if input.is_object() {
+        rooted!(in(*cx) let object = input.to_object());
+        unsafe {
+            let mut built_in_class = ESClass::Other;
+
+            if !GetBuiltinClass(*cx, object.handle().into(), &mut built_in_class) {
+                return Err(Error::JSFailed);
+            }
+
+            if let ESClass::Date = built_in_class {
+                let mut time = f64::NAN;
+                if !DateGetMsecSinceEpoch(*cx, object.handle().into(), &mut time) {
+                    return Err(Error::JSFailed);
+                }
+                if time.is_nan() {
+                    return Err(Error::Data);
+                }
+                return Ok(IndexedDBKeyType::Date(time));
+            }
+
+            // Let bytes be the result of getting a copy of the bytes held by the buffer source.
+            if IsArrayBufferObject(*object) {
+                return ArrayBuffer::from(*object)
+                    .map(|buffer| IndexedDBKeyType::Binary(buffer.to_vec()))
+                    .map_err(|_| Error::Data);
+            }
+            if JS_IsArrayBufferViewObject(*object) {
+                return ArrayBufferView::from(*object)
+                    .map(|view| IndexedDBKeyType::Binary(view.to_vec()))
+                    .map_err(|_| Error::Data);
+            }
+
+            if let ESClass::Array = built_in_class {
+                // Step 2: If seen contains input, then return invalid.
+                if seen.contains(&object.get()) {
+                    return Err(Error::Data);
+                }
+                let mut length = 0;
+                if !GetArrayLength(*cx, object.handle(), &mut length) {
+                    return Err(Error::JSFailed);
+                }
+                seen.push(object.get());
+                let mut keys = Vec::with_capacity(length as usize);
+                for index in 0..length {
+                    rooted!(in(*cx) let mut entry = UndefinedValue());
+                    if !JS_GetElement(*cx, object.handle(), index, entry.handle_mut()) {
+                        return Err(Error::JSFailed);
+                    }
+                    keys.push(convert_value_to_key(
+                        cx,
+                        entry.handle(),
+                        Some(seen.clone()),
+                    )?);
+                }
+                return Ok(IndexedDBKeyType::Array(keys));
+            }
+        }
+    }
+
+    Err(Error::Data)
+}
+
+/// <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-key-range>
+#[allow(unsafe_code)]
+pub(crate) fn convert_value_to_key_range(
+    cx: SafeJSContext,
+    input: HandleValue,
+    null_disallowed: bool,
+) -> Result<IndexedDBKeyRange, Error> {
+    // Step 1. If value is a key range, return value.
+    if input.is_object() {
+        if let Ok(key_range) = unsafe { root_from_handlevalue::<IDBKeyRange>(input, *cx) } {
+            return Ok(key_range.inner().clone());
+        }
+    }
+
+    // Step 2. If value is undefined or is null, then throw a "DataError" DOMException if
+    // null disallowed flag is set, or return an unbounded key range otherwise.
+    if input.is_undefined() || input.is_null() {
+        if null_disallowed {
+            return Err(Error::Data);
+        }
+        return Ok(IndexedDBKeyRange::unbounded());
+    }
+
+    // Step 3. Let key be the result of converting a value to a key with value.
+    // Step 4. If key is invalid, throw a "DataError" DOMException.
+    let key = convert_value_to_key(cx, input, None)?;
+
+    // Step 5. Return a key range containing only key.
+    Ok(IndexedDBKeyRange::from(key))
+}
+
+/// Gets the value of an own property of an object, returning false if there is none.
+#[allow(unsafe_code)]
+fn get_own_property(
+    cx: SafeJSContext,
+    object: HandleObject,
+    name: &str,
+    result: MutableHandleValue,
+) -> Result<bool, Error> {
+    let name: Vec<u16> = name.encode_utf16().collect();
+    rooted!(in(*cx) let mut desc = PropertyDescriptor::default());
+    let mut is_descriptor_none = false;
+    unsafe {
+        if !JS_GetOwnUCPropertyDescriptor(
+            *cx,
+            object.into(),
+            name.as_ptr(),
+            name.len(),
+            desc.handle_mut().into(),
+            &mut is_descriptor_none,
+        ) {
+            return Err(Error::JSFailed);
+        }
+        if is_descriptor_none {
+            return Ok(false);
+        }
+        if !JS_GetUCProperty(*cx, object.into(), name.as_ptr(), name.len(), result.into()) {
+            return Err(Error::JSFailed);
+        }
+    }
+    Ok(true)
+}
+
+#[allow(unsafe_code)]
+fn set_property(cx: SafeJSContext, object: HandleObject, name: &str, value: HandleValue) -> bool {
+    let name: Vec<u16> = name.encode_utf16().collect();
+    unsafe { JS_SetUCProperty(*cx, object.into(), name.as_ptr(), name.len(), value.into()) }
+}
+
+/// <https://w3c.github.io/IndexedDB/#evaluate-a-key-path-on-a-value>
+///
+/// Returns false if the key path could not be evaluated.
+#[allow(unsafe_code)]
+pub(crate) fn evaluate_key_path_on_value(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key_path: &KeyPath,
+    mut result: MutableHandleValue,
+) -> Result<bool, Error> {
+    match key_path {
+        // Step 1. If keyPath is a list of strings, then evaluate each of them, and return
+        // the results as an array.
+        KeyPath::Sequence(paths) => unsafe {
+            rooted!(in(*cx) let array = NewArrayObject1(*cx, paths.len()));
+            for (index, path) in paths.iter().enumerate() {
+                rooted!(in(*cx) let mut key = UndefinedValue());
+                let path = KeyPath::String(path.clone());
+                if !evaluate_key_path_on_value(cx, value, &path, key.handle_mut())? {
+                    return Ok(false);
+                }
+                if !JS_DefineElement(
+                    *cx,
+                    array.handle(),
+                    index as u32,
+                    key.handle(),
+                    JSPROP_ENUMERATE as u32,
+                ) {
+                    return Err(Error::JSFailed);
+                }
+            }
+            result.set(ObjectValue(array.get()));
+            Ok(true)
+        },
+        KeyPath::String(path) => {
+            rooted!(in(*cx) let mut current = value.get());
+
+            // Step 2. If keyPath is the empty string, return value.
+            // Step 3. Let identifiers be the result of strictly splitting keyPath on
+            // U+002E FULL STOP characters (.).
+            // Step 4. For each identifier of identifiers, jump to the appropriate step below:
+            for identifier in path.split('.').filter(|_| !path.is_empty()) {
+                // If Type(value) is String, and identifier is "length", let value be a
+                // Number equal to the number of elements in value.
+                if identifier == "length" && current.is_string() {
+                    let length = unsafe { JS_GetStringLength(current.to_string()) };
+                    current.set(DoubleValue(length as f64));
+                    continue;
+                }
+
+                // FIXME: Support the properties of Blob and File objects.
+
+                // Otherwise, if Type(value) is not Object, return failure.
+                if !current.is_object() {
+                    return Ok(false);
+                }
+
+                // Let hop be ? HasOwnProperty(value, identifier).
+                // If hop is false, return failure.
+                // Let value be ! Get(value, identifier).
+                rooted!(in(*cx) let object = current.to_object());
+                if !get_own_property(cx, object.handle(), identifier, current.handle_mut())? {
+                    return Ok(false);
+                }
+            }
+
+            // Step 6. Return value.
+            result.set(current.get());
+            Ok(true)
+        },
+    }
+}
+
+/// The result of
+/// <https://w3c.github.io/IndexedDB/#extract-a-key-from-a-value-using-a-key-path>.
+pub(crate) enum ExtractionResult {
+    Key(IndexedDBKeyType),
+    Invalid,
+    Failure,
+}
+
+/// <https://w3c.github.io/IndexedDB/#extract-a-key-from-a-value-using-a-key-path>
+pub(crate) fn extract_key(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key_path: &KeyPath,
+) -> Result<ExtractionResult, Error> {
+    // Step 1. Let r be the result of evaluating a key path on a value with value and
+    // keyPath. Rethrow any exceptions.
+    rooted!(in(*cx) let mut r = UndefinedValue());
+    // Step 2. If r is failure, return failure.
+    if !evaluate_key_path_on_value(cx, value, key_path, r.handle_mut())? {
+        return Ok(ExtractionResult::Failure);
+    }
+
+    // Step 3. Let key be the result of converting a value to a key with r.
+    // Rethrow any exceptions.
+    // Step 4. If key is invalid, return invalid.
+    // Step 5. Return key.
+    match convert_value_to_key(cx, r.handle(), None) {
+        Ok(key) => Ok(ExtractionResult::Key(key)),
+        Err(Error::Data) => Ok(ExtractionResult::Invalid),
+        Err(error) => Err(error),
+    }
+}
+
+/// The keys of a value in an index, which are stored with the record of the value, see
+/// step 5 of <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>.
+#[allow(unsafe_code)]
+pub(crate) fn extract_index_keys(
+    cx: SafeJSContext,
+    value: HandleValue,
+    index: &IndexMetadata,
+) -> Result<Vec<IndexedDBKeyType>, Error> {
+    rooted!(in(*cx) let mut r = UndefinedValue());
+    if !evaluate_key_path_on_value(cx, value, &index.key_path, r.handle_mut())? {
+        return Ok(Vec::new());
+    }
+
+    // If index's multiEntry flag is set and the key path evaluates to an array, each
+    // valid and distinct key in the array is a key of the value in the index, see
+    // https://w3c.github.io/IndexedDB/#convert-a-value-to-a-multientry-key
+    let mut is_array = false;
+    if index.multi_entry &&
+        r.is_object() &&
+        unsafe { IsArrayObject(*cx, r.handle(), &mut is_array) } &&
+        is_array
+    {
+        rooted!(in(*cx) let object = r.to_object());
+        let mut length = 0;
+        if !unsafe { GetArrayLength(*cx, object.handle(), &mut length) } {
+            return Err(Error::JSFailed);
+        }
+        let mut keys = Vec::new();
+        for index in 0..length {
+            rooted!(in(*cx) let mut entry = UndefinedValue());
+            if !unsafe { JS_GetElement(*cx, object.handle(), index, entry.handle_mut()) } {
+                return Err(Error::JSFailed);
+            }
+            match convert_value_to_key(cx, entry.handle(), Some(vec![object.get()])) {
+                Ok(key) => keys.push(key),
+                Err(Error::Data) => {},
+                Err(error) => return Err(error),
+            }
+        }
+        keys.sort();
+        keys.dedup();
+        return Ok(keys);
+    }
+
+    match convert_value_to_key(cx, r.handle(), None) {
+        Ok(key) => Ok(vec![key]),
+        Err(Error::Data) => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#check-that-a-key-could-be-injected-into-a-value>
+pub(crate) fn can_inject_key(cx: SafeJSContext, value: HandleValue, key_path: &str) -> bool {
+    // Step 1. Let identifiers be the result of strictly splitting keyPath on U+002E
+    // FULL STOP characters (.).
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    // Step 3. Remove the last item of identifiers.
+    identifiers.pop();
+
+    rooted!(in(*cx) let mut current = value.get());
+    // Step 4. For each remaining identifier of identifiers, if any:
+    for identifier in identifiers {
+        // Step 4.1. If value is not an Object or an Array, return false.
+        if !current.is_object() {
+            return false;
+        }
+        // Step 4.2. Let hop be ? HasOwnProperty(value, identifier).
+        // Step 4.3. If hop is false, return true.
+        // Step 4.4. Let value be ! Get(value, identifier).
+        rooted!(in(*cx) let object = current.to_object());
+        match get_own_property(cx, object.handle(), identifier, current.handle_mut()) {
+            Ok(true) => {},
+            Ok(false) => return true,
+            Err(_) => return false,
+        }
+    }
+
+    // Step 5. Return true if value is an Object or an Array, or false otherwise.
+    current.is_object()
+}
+
+/// <https://w3c.github.io/IndexedDB/#inject-a-key-into-a-value-using-a-key-path>
+///
+/// Keys generated by the key generator of a store are injected when its values are read,
+/// since values are stored serialized and the backend generates the keys.
+#[allow(unsafe_code)]
+pub(crate) fn inject_key(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key: &IndexedDBKeyType,
+    key_path: &str,
+) {
+    // Step 1. Let identifiers be the result of strictly splitting keyPath on U+002E
+    // FULL STOP characters (.).
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    // Step 3. Let last be the last item of identifiers and remove it from the list.
+    let Some(last) = identifiers.pop() else {
+        return;
+    };
+
+    rooted!(in(*cx) let mut current = value.get());
+    // Step 4. For each remaining identifier of identifiers:
+    for identifier in identifiers {
+        // Step 4.1. Assert: value is an Object or an Array.
+        if !current.is_object() {
+            return;
+        }
+        // Step 4.2. Let hop be ! HasOwnProperty(value, identifier).
+        // Step 4.3. If hop is false, then let o be a new Object created as if by the
+        // expression ({}), and set identifier on value to o.
+        // Step 4.4. Let value be ! Get(value, identifier).
+        rooted!(in(*cx) let object = current.to_object());
+        match get_own_property(cx, object.handle(), identifier, current.handle_mut()) {
+            Ok(true) => {},
+            Ok(false) => {
+                rooted!(in(*cx) let new_object = unsafe { JS_NewPlainObject(*cx) });
+                current.set(ObjectValue(new_object.get()));
+                if !set_property(cx, object.handle(), identifier, current.handle()) {
+                    return;
+                }
+            },
+            Err(_) => return,
+        }
+    }
+
+    // Step 5. Assert: value is an Object or an Array.
+    if !current.is_object() {
+        return;
+    }
+
+    // Step 6. Let keyValue be the result of converting a key to a value with key.
+    // Step 7. Let status be CreateDataProperty(value, last, keyValue).
+    rooted!(in(*cx) let object = current.to_object());
+    rooted!(in(*cx) let mut key_value = UndefinedValue());
+    key_type_to_jsval(cx, key, key_value.handle_mut());
+    set_property(cx, object.handle(), last, key_value.handle());
+}
@@ -12,8 +12,8 @@ use fonts_traits::ByteIndex;
 use html5ever::{local_name, ns};
 use layout_api::wrapper_traits::{LayoutDataTrait, LayoutNode, ThreadSafeLayoutNode};
 use layout_api::{
-    GenericLayoutData, HTMLCanvasData, HTMLMediaData, LayoutNodeType, SVGSVGData, StyleData,
-    TrustedNodeAddress,
+    GenericLayoutData, HTMLCanvasData, HTMLMediaData, HighlightKind, LayoutNodeType, SVGSVGData,
+    StyleData, TrustedNodeAddress,
 };
 use net_traits::image_cache::Image;
 use pixels::ImageMetadata;
@@ -362,6 +362,48 @@ impl<'dom> ThreadSafeLayoutNode<'dom> for ServoThreadSafeLayoutNode<'dom> {
         })
     }
 
+    fn composition(&self) -> Option<Range<ByteIndex>> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.composition().map(|range| {
+            Range::new(
+                ByteIndex(range.start as isize),
+                ByteIndex(range.len() as isize),
+            )
+        })
+    }
+
+    fn misspellings(&self) -> Vec<Range<ByteIndex>> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.misspellings()
+            .into_iter()
+            .map(|range| {
+                Range::new(
+                    ByteIndex(range.start as isize),
+                    ByteIndex(range.len() as isize),
+                )
+            })
+            .collect()
+    }
+
+    fn highlights(&self) -> Vec<(Range<ByteIndex>, HighlightKind)> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.highlights()
+            .into_iter()
+            .map(|(range, kind)| {
+                (
+                    Range::new(
+                        ByteIndex(range.start as isize),
+                        ByteIndex(range.len() as isize),
+                    ),
+                    kind,
+                )
+            })
+            .collect()
+    }
+
     fn image_url(&self) -> Option<ServoUrl> {
         let this = unsafe { self.get_jsmanaged() };
         this.image_url()
@@ -27,8 +27,15 @@ mod task;
 mod body;
 pub(crate) mod clipboard_provider;
 pub(crate) mod conversions;
+mod coverage;
 mod devtools;
 pub(crate) mod document_loader;
+mod editing;
+mod favicon;
+mod find_in_page;
+mod focus_navigation;
+mod idle_gc_scheduler;
+mod indexed_db;
 #[macro_use]
 mod dom;
 mod canvas_context;
@@ -41,6 +48,7 @@ pub(crate) mod document_collection;
 pub(crate) mod iframe_collection;
 pub(crate) mod image_animation;
 pub mod layout_dom;
+mod media_source;
 #[allow(unsafe_code)]
 pub(crate) mod messaging;
 mod microtask;
@@ -62,6 +70,7 @@ mod task_manager;
 mod task_queue;
 mod task_source;
 pub mod test;
+mod text_highlights;
 #[allow(dead_code)]
 pub mod textinput;
 mod timers;
@@ -70,6 +79,7 @@ mod window_named_properties;
 
 mod unminify;
 
+mod drag_and_drop;
 mod drag_data_store;
 mod links;
 mod xpath;
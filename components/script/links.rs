@@ -7,6 +7,7 @@
 use constellation_traits::{LoadData, LoadOrigin, NavigationHistoryBehavior};
 use html5ever::{local_name, ns};
 use malloc_size_of::malloc_size_of_is_0;
+use net_traits::ReferrerPolicy;
 use net_traits::request::Referrer;
 use style::str::HTML_SPACE_CHARACTERS;
 
@@ -417,14 +418,16 @@ pub(crate) fn follow_hyperlink(
         };
 
         // Step 11: Let referrerPolicy be the current state of subject's referrerpolicy content attribute.
-        let referrer_policy = referrer_policy_for_element(subject);
+        let mut referrer_policy = referrer_policy_for_element(subject);
 
         // Step 12: If subject's link types includes the noreferrer keyword, then set
         //          referrerPolicy to "no-referrer".
+        // The referrer is taken from the source document, not from the navigated one.
         let referrer = if relations.contains(LinkRelations::NO_REFERRER) {
+            referrer_policy = ReferrerPolicy::NoReferrer;
             Referrer::NoReferrer
         } else {
-            target_window.as_global_scope().get_referrer()
+            window.as_global_scope().get_referrer()
         };
 
         // Step 13: Navigate targetNavigable to urlString using subject's node document,
@@ -0,0 +1,972 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! <https://www.w3.org/TR/mse-byte-stream-format-isobmff/>
+//!
+//! Initialization segments are a `ftyp` box followed by a `moov` box, and media segments
+//! are a `moof` box followed by a `mdat` box. Other top-level boxes, like `styp` and
+//! `sidx`, are skipped.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::{InitializationSegment, MediaSegment, ParseError, Segment, TrackInfo, TrackKind};
+
+type BoxType = [u8; 4];
+
+/// The header of a box, whose body follows it.
+struct BoxHeader {
+    box_type: BoxType,
+    header_len: usize,
+    size: usize,
+}
+
+/// A complete box within the body of another box.
+struct ChildBox {
+    box_type: BoxType,
+    /// The range of the whole box, in the body of its parent.
+    range: Range<usize>,
+    /// The range of the body of the box, in the body of its parent.
+    body: Range<usize>,
+}
+
+fn truncated() -> ParseError {
+    ParseError::Malformed("truncated box")
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ParseError> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(truncated)?;
+    Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, ParseError> {
+    let bytes = data.get(offset..offset + 8).ok_or_else(truncated)?;
+    Ok(u64::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) -> Result<(), ParseError> {
+    data.get_mut(offset..offset + 4)
+        .ok_or_else(truncated)?
+        .copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<(), ParseError> {
+    data.get_mut(offset..offset + 8)
+        .ok_or_else(truncated)?
+        .copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+/// The version of a full box, from the start of its body.
+fn full_box_version(body: &[u8]) -> Result<u8, ParseError> {
+    body.first().copied().ok_or_else(truncated)
+}
+
+/// The flags of a full box, from the start of its body.
+fn full_box_flags(body: &[u8]) -> Result<u32, ParseError> {
+    Ok(read_u32(body, 0)? & 0x00FF_FFFF)
+}
+
+/// Reads the header of the box at the start of `data`, or returns `None` if more bytes
+/// are needed.
+fn read_box_header(data: &[u8]) -> Result<Option<BoxHeader>, ParseError> {
+    if data.len() < 8 {
+        return Ok(None);
+    }
+    let box_type = data[4..8].try_into().unwrap();
+    let (size, header_len) = match read_u32(data, 0)? {
+        0 => {
+            return Err(ParseError::Unsupported(
+                "boxes that extend to the end of the stream",
+            ));
+        },
+        1 => {
+            if data.len() < 16 {
+                return Ok(None);
+            }
+            (read_u64(data, 8)?, 16)
+        },
+        size => (size as u64, 8),
+    };
+    if size < header_len as u64 {
+        return Err(ParseError::Malformed("box smaller than its header"));
+    }
+    let size = usize::try_from(size)
+        .map_err(|_| ParseError::Unsupported("boxes larger than the address space"))?;
+    Ok(Some(BoxHeader {
+        box_type,
+        header_len,
+        size,
+    }))
+}
+
+/// The boxes that make up `data`, which must all be complete.
+fn child_boxes(data: &[u8]) -> Result<Vec<ChildBox>, ParseError> {
+    let mut boxes = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let header = read_box_header(&data[offset..])?.ok_or_else(truncated)?;
+        let end = offset
+            .checked_add(header.size)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(truncated)?;
+        boxes.push(ChildBox {
+            box_type: header.box_type,
+            range: offset..end,
+            body: offset + header.header_len..end,
+        });
+        offset = end;
+    }
+    Ok(boxes)
+}
+
+fn find_child<'a>(boxes: &'a [ChildBox], box_type: &BoxType) -> Option<&'a ChildBox> {
+    boxes.iter().find(|child| &child.box_type == box_type)
+}
+
+/// Builds a box of the given type around `body`.
+fn make_box(box_type: &BoxType, body: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(body.len() + 8);
+    bytes.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+    bytes.extend_from_slice(box_type);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+#[derive(Clone, Copy, MallocSizeOf)]
+struct Track {
+    /// The number of time units per second of the media timeline of the track.
+    timescale: u32,
+    /// The default duration of samples, from the `trex` box of the track.
+    default_sample_duration: u32,
+}
+
+#[derive(Default, MallocSizeOf)]
+pub(crate) struct IsoBmffParser {
+    /// The appended bytes that do not make up a complete box yet.
+    input: Vec<u8>,
+    /// The `ftyp` box of the initialization segment whose `moov` box is awaited.
+    file_type: Option<Vec<u8>>,
+    /// The tracks of the last initialization segment, by track ID.
+    tracks: HashMap<u32, Track>,
+    /// The media segment whose `mdat` box is awaited.
+    media_segment: Option<MediaSegment>,
+    /// The decode time following the last fragment of each track, for fragments that do
+    /// not declare their decode time.
+    next_decode_times: HashMap<u32, u64>,
+}
+
+impl IsoBmffParser {
+    pub(crate) fn append(&mut self, data: &[u8]) -> Result<Vec<Segment>, ParseError> {
+        self.input.extend_from_slice(data);
+
+        let mut segments = vec![];
+        let mut offset = 0;
+        while let Some(header) = read_box_header(&self.input[offset..])? {
+            if self.input.len() - offset < header.size {
+                break;
+            }
+            let range = offset..offset + header.size;
+            let body = offset + header.header_len..offset + header.size;
+            offset += header.size;
+
+            match &header.box_type {
+                b"ftyp" => {
+                    if self.media_segment.is_some() {
+                        return Err(ParseError::Malformed("media segment without mdat"));
+                    }
+                    self.file_type = Some(self.input[range].to_vec());
+                },
+                b"moov" => {
+                    if self.media_segment.is_some() {
+                        return Err(ParseError::Malformed("media segment without mdat"));
+                    }
+                    let (tracks, duration) = self.parse_movie(&self.input[body].to_vec())?;
+                    let mut bytes = self
+                        .file_type
+                        .take()
+                        .ok_or(ParseError::Malformed("moov without ftyp"))?;
+                    bytes.extend_from_slice(&self.input[range]);
+                    segments.push(Segment::Initialization(InitializationSegment {
+                        bytes,
+                        tracks,
+                        duration,
+                    }));
+                },
+                b"moof" => {
+                    if self.tracks.is_empty() {
+                        return Err(ParseError::MissingInitializationSegment);
+                    }
+                    if self.media_segment.is_some() {
+                        return Err(ParseError::Malformed("media segment without mdat"));
+                    }
+                    let (start, end) = self.parse_movie_fragment(&self.input[body].to_vec())?;
+                    self.media_segment = Some(MediaSegment {
+                        bytes: self.input[range].to_vec(),
+                        start,
+                        end,
+                    });
+                },
+                b"mdat" => {
+                    let mut segment = self
+                        .media_segment
+                        .take()
+                        .ok_or(ParseError::Malformed("mdat without moof"))?;
+                    segment.bytes.extend_from_slice(&self.input[range]);
+                    segments.push(Segment::Media(segment));
+                },
+                _ => {},
+            }
+        }
+        self.input.drain(..offset);
+        Ok(segments)
+    }
+
+    pub(crate) fn is_parsing_media_segment(&self) -> bool {
+        self.media_segment.is_some()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.input.clear();
+        self.file_type = None;
+        self.media_segment = None;
+    }
+
+    /// Reads the tracks and the duration of a `moov` box.
+    fn parse_movie(&mut self, moov: &[u8]) -> Result<(Vec<TrackInfo>, Option<f64>), ParseError> {
+        let children = child_boxes(moov)?;
+
+        let mvhd =
+            find_child(&children, b"mvhd").ok_or(ParseError::Malformed("moov without mvhd"))?;
+        let mvhd = &moov[mvhd.body.clone()];
+        let (timescale, duration) = if full_box_version(mvhd)? == 1 {
+            (read_u32(mvhd, 20)?, read_u64(mvhd, 24)?)
+        } else {
+            (read_u32(mvhd, 12)?, read_u32(mvhd, 16)? as u64)
+        };
+        // Fragmented files usually leave the movie duration unknown, in which case the
+        // movie extends header may declare it.
+        let mut fragment_duration = 0;
+        let mut default_sample_durations = HashMap::new();
+        if let Some(mvex) = find_child(&children, b"mvex") {
+            let mvex = &moov[mvex.body.clone()];
+            for child in child_boxes(mvex)? {
+                let body = &mvex[child.body.clone()];
+                match &child.box_type {
+                    b"mehd" if full_box_version(body)? == 1 => {
+                        fragment_duration = read_u64(body, 4)?
+                    },
+                    b"mehd" => fragment_duration = read_u32(body, 4)? as u64,
+                    b"trex" => {
+                        default_sample_durations.insert(read_u32(body, 4)?, read_u32(body, 12)?);
+                    },
+                    _ => {},
+                }
+            }
+        }
+        let duration = [duration, fragment_duration]
+            .into_iter()
+            .find(|duration| ![0, u32::MAX as u64, u64::MAX].contains(duration))
+            .filter(|_| timescale != 0)
+            .map(|duration| duration as f64 / timescale as f64);
+
+        let mut tracks = HashMap::new();
+        let mut track_infos = vec![];
+        for trak in children.iter().filter(|child| &child.box_type == b"trak") {
+            let trak = &moov[trak.body.clone()];
+            let trak_children = child_boxes(trak)?;
+
+            let tkhd = find_child(&trak_children, b"tkhd")
+                .ok_or(ParseError::Malformed("trak without tkhd"))?;
+            let tkhd = &trak[tkhd.body.clone()];
+            let track_id = if full_box_version(tkhd)? == 1 {
+                read_u32(tkhd, 20)?
+            } else {
+                read_u32(tkhd, 12)?
+            };
+
+            let mdia = find_child(&trak_children, b"mdia")
+                .ok_or(ParseError::Malformed("trak without mdia"))?;
+            let mdia = &trak[mdia.body.clone()];
+            let mdia_children = child_boxes(mdia)?;
+            let mdhd = find_child(&mdia_children, b"mdhd")
+                .ok_or(ParseError::Malformed("mdia without mdhd"))?;
+            let mdhd = &mdia[mdhd.body.clone()];
+            let timescale = if full_box_version(mdhd)? == 1 {
+                read_u32(mdhd, 20)?
+            } else {
+                read_u32(mdhd, 12)?
+            };
+            if timescale == 0 {
+                return Err(ParseError::Malformed("track without timescale"));
+            }
+            let kind = match find_child(&mdia_children, b"hdlr")
+                .map(|hdlr| mdia.get(hdlr.body.start + 8..hdlr.body.start + 12))
+            {
+                Some(Some(b"soun")) => TrackKind::Audio,
+                Some(Some(b"vide")) => TrackKind::Video,
+                Some(Some(b"text" | b"subt" | b"sbtl")) => TrackKind::Text,
+                _ => TrackKind::Other,
+            };
+
+            tracks.insert(
+                track_id,
+                Track {
+                    timescale,
+                    default_sample_duration: default_sample_durations
+                        .get(&track_id)
+                        .copied()
+                        .unwrap_or(0),
+                },
+            );
+            track_infos.push(TrackInfo {
+                id: track_id as u64,
+                kind,
+            });
+        }
+        if tracks.is_empty() {
+            return Err(ParseError::Malformed("moov without tracks"));
+        }
+
+        self.tracks = tracks;
+        Ok((track_infos, duration))
+    }
+
+    /// Reads the presentation interval, in seconds, of the samples of a `moof` box.
+    fn parse_movie_fragment(&mut self, moof: &[u8]) -> Result<(f64, f64), ParseError> {
+        let mut start = f64::INFINITY;
+        let mut end = f64::NEG_INFINITY;
+
+        for traf in child_boxes(moof)?
+            .iter()
+            .filter(|child| &child.box_type == b"traf")
+        {
+            let traf = &moof[traf.body.clone()];
+            let traf_children = child_boxes(traf)?;
+
+            let tfhd = find_child(&traf_children, b"tfhd")
+                .ok_or(ParseError::Malformed("traf without tfhd"))?;
+            let tfhd = &traf[tfhd.body.clone()];
+            let tfhd_flags = full_box_flags(tfhd)?;
+            let track_id = read_u32(tfhd, 4)?;
+            let track = *self
+                .tracks
+                .get(&track_id)
+                .ok_or(ParseError::Malformed("fragment of an unknown track"))?;
+
+            // The optional fields of the header precede the default sample duration.
+            let mut default_sample_duration = track.default_sample_duration;
+            let mut offset = 8;
+            if tfhd_flags & 0x1 != 0 {
+                offset += 8;
+            }
+            if tfhd_flags & 0x2 != 0 {
+                offset += 4;
+            }
+            if tfhd_flags & 0x8 != 0 {
+                default_sample_duration = read_u32(tfhd, offset)?;
+            }
+
+            let mut decode_time = match find_child(&traf_children, b"tfdt") {
+                Some(tfdt) => {
+                    let tfdt = &traf[tfdt.body.clone()];
+                    if full_box_version(tfdt)? == 1 {
+                        read_u64(tfdt, 4)?
+                    } else {
+                        read_u32(tfdt, 4)? as u64
+                    }
+                },
+                None => self.next_decode_times.get(&track_id).copied().unwrap_or(0),
+            };
+
+            let timescale = track.timescale as f64;
+            for trun in traf_children
+                .iter()
+                .filter(|child| &child.box_type == b"trun")
+            {
+                let trun = &traf[trun.body.clone()];
+                let version = full_box_version(trun)?;
+                let flags = full_box_flags(trun)?;
+                let sample_count = read_u32(trun, 4)?;
+
+                // Without per-sample fields, all samples have the default duration.
+                if flags & 0xF00 == 0 {
+                    let duration = sample_count as u64 * default_sample_duration as u64;
+                    start = start.min(decode_time as f64 / timescale);
+                    end = end.max((decode_time + duration) as f64 / timescale);
+                    decode_time += duration;
+                    continue;
+                }
+
+                let mut offset = 8;
+                if flags & 0x1 != 0 {
+                    offset += 4;
+                }
+                if flags & 0x4 != 0 {
+                    offset += 4;
+                }
+                for _ in 0..sample_count {
+                    let mut duration = default_sample_duration;
+                    let mut composition_offset = 0;
+                    if flags & 0x100 != 0 {
+                        duration = read_u32(trun, offset)?;
+                        offset += 4;
+                    }
+                    if flags & 0x200 != 0 {
+                        offset += 4;
+                    }
+                    if flags & 0x400 != 0 {
+                        offset += 4;
+                    }
+                    if flags & 0x800 != 0 {
+                        let value = read_u32(trun, offset)?;
+                        composition_offset = if version == 0 {
+                            value as i64
+                        } else {
+                            value as i32 as i64
+                        };
+                        offset += 4;
+                    }
+                    let presentation_time = decode_time as i64 + composition_offset;
+                    start = start.min(presentation_time as f64 / timescale);
+                    end = end.max((presentation_time + duration as i64) as f64 / timescale);
+                    decode_time += duration as u64;
+                }
+            }
+            self.next_decode_times.insert(track_id, decode_time);
+        }
+
+        if start > end {
+            return Ok((0., 0.));
+        }
+        Ok((start, end))
+    }
+
+    /// Shifts the base media decode time of the track fragments of a media segment.
+    pub(crate) fn apply_timestamp_offset(
+        &self,
+        segment: &mut MediaSegment,
+        offset: f64,
+    ) -> Result<(), ParseError> {
+        let bytes = &mut segment.bytes;
+        for moof in child_boxes(bytes)?
+            .into_iter()
+            .filter(|child| &child.box_type == b"moof")
+        {
+            for traf in child_boxes(&bytes[moof.body.clone()])?
+                .into_iter()
+                .filter(|child| &child.box_type == b"traf")
+            {
+                let traf_start = moof.body.start + traf.body.start;
+                let traf_end = moof.body.start + traf.body.end;
+                let traf_children = child_boxes(&bytes[traf_start..traf_end])?;
+
+                let tfhd = find_child(&traf_children, b"tfhd")
+                    .ok_or(ParseError::Malformed("traf without tfhd"))?;
+                let track_id = read_u32(&bytes[traf_start + tfhd.body.start..], 4)?;
+                let track = self
+                    .tracks
+                    .get(&track_id)
+                    .ok_or(ParseError::Malformed("fragment of an unknown track"))?;
+
+                let tfdt = find_child(&traf_children, b"tfdt").ok_or(ParseError::Unsupported(
+                    "timestamp offsets for fragments without tfdt",
+                ))?;
+                let tfdt = &mut bytes[traf_start + tfdt.body.start..traf_start + tfdt.body.end];
+                let version = full_box_version(tfdt)?;
+                let decode_time = if version == 1 {
+                    read_u64(tfdt, 4)? as f64
+                } else {
+                    read_u32(tfdt, 4)? as f64
+                };
+                let decode_time = (decode_time + offset * track.timescale as f64).round();
+                if decode_time < 0. {
+                    return Err(ParseError::Unsupported("negative decode times"));
+                }
+                if version == 1 {
+                    write_u64(tfdt, 4, decode_time as u64)?;
+                } else if decode_time <= u32::MAX as f64 {
+                    write_u32(tfdt, 4, decode_time as u32)?;
+                } else {
+                    return Err(ParseError::Unsupported(
+                        "decode times that overflow a version 0 tfdt",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn map_track_id(map: &dyn Fn(u64) -> u64, track_id: u32) -> Result<u32, ParseError> {
+    u32::try_from(map(track_id as u64))
+        .map_err(|_| ParseError::Unsupported("track IDs over 32 bits"))
+}
+
+/// Renumbers the tracks of the `tkhd` and `trex` boxes of an initialization segment, or of
+/// the `tfhd` boxes of a media segment.
+pub(super) fn renumber_tracks(
+    bytes: &mut [u8],
+    map: &dyn Fn(u64) -> u64,
+) -> Result<(), ParseError> {
+    for top_level in child_boxes(bytes)? {
+        let base = top_level.body.start;
+        match &top_level.box_type {
+            b"moov" => {
+                for child in child_boxes(&bytes[top_level.body.clone()])? {
+                    let child_base = base + child.body.start;
+                    let child_body = child_base..base + child.body.end;
+                    match &child.box_type {
+                        b"trak" => {
+                            for trak_child in child_boxes(&bytes[child_body])? {
+                                if &trak_child.box_type != b"tkhd" {
+                                    continue;
+                                }
+                                let tkhd = &mut bytes[child_base + trak_child.body.start
+                                    ..child_base + trak_child.body.end];
+                                let offset = if full_box_version(tkhd)? == 1 { 20 } else { 12 };
+                                let track_id = map_track_id(map, read_u32(tkhd, offset)?)?;
+                                write_u32(tkhd, offset, track_id)?;
+                            }
+                        },
+                        b"mvex" => {
+                            for mvex_child in child_boxes(&bytes[child_body])? {
+                                if &mvex_child.box_type != b"trex" {
+                                    continue;
+                                }
+                                let trex = &mut bytes[child_base + mvex_child.body.start
+                                    ..child_base + mvex_child.body.end];
+                                let track_id = map_track_id(map, read_u32(trex, 4)?)?;
+                                write_u32(trex, 4, track_id)?;
+                            }
+                        },
+                        _ => {},
+                    }
+                }
+            },
+            b"moof" => {
+                for traf in child_boxes(&bytes[top_level.body.clone()])? {
+                    if &traf.box_type != b"traf" {
+                        continue;
+                    }
+                    let traf_base = base + traf.body.start;
+                    for traf_child in child_boxes(&bytes[traf_base..base + traf.body.end])? {
+                        if &traf_child.box_type != b"tfhd" {
+                            continue;
+                        }
+                        let tfhd = &mut bytes
+                            [traf_base + traf_child.body.start..traf_base + traf_child.body.end];
+                        let track_id = map_track_id(map, read_u32(tfhd, 4)?)?;
+                        write_u32(tfhd, 4, track_id)?;
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Merges initialization segments into the `ftyp` box of the first one, followed by a
+/// `moov` box with the `trak` and `trex` boxes of all of them.
+pub(super) fn merge_initialization_segments(segments: &[Vec<u8>]) -> Result<Vec<u8>, ParseError> {
+    let Some((first, others)) = segments.split_first() else {
+        return Ok(vec![]);
+    };
+    if others.is_empty() {
+        return Ok(first.clone());
+    }
+
+    let mut file_type = None;
+    let mut movie_header = None;
+    let mut movie_extends_header = None;
+    let mut other_movie_boxes = vec![];
+    let mut tracks = vec![];
+    let mut track_extends = vec![];
+    let mut max_track_id = 0;
+    for (index, segment) in segments.iter().enumerate() {
+        for top_level in child_boxes(segment)? {
+            match &top_level.box_type {
+                b"ftyp" if index == 0 => file_type = Some(&segment[top_level.range]),
+                b"moov" => {
+                    let moov = &segment[top_level.body];
+                    for child in child_boxes(moov)? {
+                        let child_bytes = &moov[child.range.clone()];
+                        match &child.box_type {
+                            b"trak" => {
+                                let trak = &moov[child.body.clone()];
+                                if let Some(tkhd) = find_child(&child_boxes(trak)?, b"tkhd")
+                                    .map(|tkhd| &trak[tkhd.body.clone()])
+                                {
+                                    let offset = if full_box_version(tkhd)? == 1 { 20 } else { 12 };
+                                    max_track_id = max_track_id.max(read_u32(tkhd, offset)?);
+                                }
+                                tracks.push(child_bytes);
+                            },
+                            b"mvex" => {
+                                let mvex = &moov[child.body.clone()];
+                                for mvex_child in child_boxes(mvex)? {
+                                    match &mvex_child.box_type {
+                                        b"trex" => track_extends.push(&mvex[mvex_child.range]),
+                                        b"mehd" if index == 0 => {
+                                            movie_extends_header = Some(&mvex[mvex_child.range])
+                                        },
+                                        _ => {},
+                                    }
+                                }
+                            },
+                            b"mvhd" if index == 0 => movie_header = Some(child_bytes.to_vec()),
+                            _ if index == 0 => other_movie_boxes.push(child_bytes),
+                            _ => {},
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    let file_type = file_type.ok_or(ParseError::Malformed("moov without ftyp"))?;
+    let mut movie_header = movie_header.ok_or(ParseError::Malformed("moov without mvhd"))?;
+
+    // The next track ID of the movie header follows the tracks of all segments.
+    let next_track_id_offset = if full_box_version(&movie_header[8..])? == 1 {
+        8 + 108
+    } else {
+        8 + 96
+    };
+    write_u32(
+        &mut movie_header,
+        next_track_id_offset,
+        max_track_id.saturating_add(1),
+    )?;
+
+    let mut movie_extends = vec![];
+    if let Some(movie_extends_header) = movie_extends_header {
+        movie_extends.extend_from_slice(movie_extends_header);
+    }
+    for track_extends in track_extends {
+        movie_extends.extend_from_slice(track_extends);
+    }
+
+    let mut movie = movie_header;
+    for track in tracks {
+        movie.extend_from_slice(track);
+    }
+    movie.extend(make_box(b"mvex", &movie_extends));
+    for other in other_movie_boxes {
+        movie.extend_from_slice(other);
+    }
+
+    let mut merged = file_type.to_vec();
+    merged.extend(make_box(b"moov", &movie));
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a full box with the given version and flags, whose body continues with
+    /// `fields`.
+    fn full_box(box_type: &BoxType, version: u8, flags: u32, fields: &[u32]) -> Vec<u8> {
+        let mut body = (flags | (version as u32) << 24).to_be_bytes().to_vec();
+        for field in fields {
+            body.extend_from_slice(&field.to_be_bytes());
+        }
+        make_box(box_type, &body)
+    }
+
+    fn boxes(boxes: &[Vec<u8>]) -> Vec<u8> {
+        boxes.concat()
+    }
+
+    /// An initialization segment with a single track, whose media timeline has 1000
+    /// units per second and samples of 100 units by default.
+    fn initialization_segment(track_id: u32, handler: &[u8; 4]) -> Vec<u8> {
+        let mut movie_header = vec![0; 24];
+        movie_header[2] = 1000;
+        movie_header[23] = track_id + 1;
+        let track = make_box(
+            b"trak",
+            &boxes(&[
+                full_box(b"tkhd", 0, 0, &[0, 0, track_id, 0, 0]),
+                make_box(
+                    b"mdia",
+                    &boxes(&[
+                        full_box(b"mdhd", 0, 0, &[0, 0, 1000, 0, 0]),
+                        full_box(b"hdlr", 0, 0, &[0, u32::from_be_bytes(*handler), 0, 0, 0]),
+                    ]),
+                ),
+            ]),
+        );
+        boxes(&[
+            make_box(b"ftyp", b"isom\0\0\0\0isomiso6"),
+            make_box(
+                b"moov",
+                &boxes(&[
+                    full_box(b"mvhd", 0, 0, &movie_header),
+                    track,
+                    make_box(
+                        b"mvex",
+                        &boxes(&[
+                            full_box(b"mehd", 0, 0, &[5000]),
+                            full_box(b"trex", 0, 0, &[track_id, 1, 100, 0, 0]),
+                        ]),
+                    ),
+                ]),
+            ),
+        ])
+    }
+
+    /// A media segment with a single track fragment of `sample_count` samples with the
+    /// default duration.
+    fn media_segment(track_id: u32, decode_time: Option<u32>, sample_count: u32) -> Vec<u8> {
+        let mut track_fragment = vec![full_box(b"tfhd", 0, 0, &[track_id])];
+        if let Some(decode_time) = decode_time {
+            track_fragment.push(full_box(b"tfdt", 0, 0, &[decode_time]));
+        }
+        track_fragment.push(full_box(b"trun", 0, 0, &[sample_count]));
+        boxes(&[
+            make_box(b"moof", &make_box(b"traf", &boxes(&track_fragment))),
+            make_box(b"mdat", b"samples"),
+        ])
+    }
+
+    fn media_intervals(segments: &[Segment]) -> Vec<(f64, f64)> {
+        segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Media(segment) => Some((segment.start, segment.end)),
+                Segment::Initialization(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_initialization_segment_followed_by_media_segments() {
+        let initialization = initialization_segment(1, b"vide");
+        let mut stream = initialization.clone();
+        stream.extend(media_segment(1, Some(0), 10));
+        // Without a `tfdt` box, a fragment follows the previous one of its track.
+        stream.extend(media_segment(1, None, 5));
+
+        let mut parser = IsoBmffParser::default();
+        let segments = parser.append(&stream).unwrap();
+        assert_eq!(segments.len(), 3);
+        let Segment::Initialization(segment) = &segments[0] else {
+            panic!("Expected an initialization segment first");
+        };
+        assert_eq!(segment.bytes, initialization);
+        assert_eq!(segment.duration, Some(5.));
+        assert_eq!(segment.tracks.len(), 1);
+        assert_eq!(segment.tracks[0].id, 1);
+        assert_eq!(segment.tracks[0].kind, TrackKind::Video);
+        assert_eq!(media_intervals(&segments), vec![(0., 1.), (1., 1.5)]);
+        assert!(!parser.is_parsing_media_segment());
+    }
+
+    #[test]
+    fn test_truncated_boxes_wait_for_more_bytes() {
+        let mut stream = initialization_segment(1, b"soun");
+        stream.extend(media_segment(1, Some(2000), 10));
+
+        let mut parser = IsoBmffParser::default();
+        let mut segments = vec![];
+        for byte in stream.iter() {
+            segments.extend(parser.append(&[*byte]).unwrap());
+        }
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(segments[0], Segment::Initialization(_)));
+        assert_eq!(media_intervals(&segments), vec![(2., 3.)]);
+
+        // A media segment whose `mdat` box has not been appended yet is being parsed.
+        let media = media_segment(1, None, 1);
+        let mdat_start = media.len() - 15;
+        assert!(parser.append(&media[..mdat_start]).unwrap().is_empty());
+        assert!(parser.is_parsing_media_segment());
+        assert_eq!(parser.append(&media[mdat_start..]).unwrap().len(), 1);
+        assert!(!parser.is_parsing_media_segment());
+    }
+
+    #[test]
+    fn test_per_sample_durations_and_composition_offsets() {
+        let trun = full_box(b"trun", 1, 0x100 | 0x800, &[2, 100, 50, 300, -50i32 as u32]);
+        let media = boxes(&[
+            make_box(
+                b"moof",
+                &make_box(
+                    b"traf",
+                    &boxes(&[
+                        full_box(b"tfhd", 0, 0, &[1]),
+                        full_box(b"tfdt", 1, 0, &[0, 1000]),
+                        trun,
+                    ]),
+                ),
+            ),
+            make_box(b"mdat", b""),
+        ]);
+
+        let mut parser = IsoBmffParser::default();
+        parser.append(&initialization_segment(1, b"vide")).unwrap();
+        let segments = parser.append(&media).unwrap();
+        // The samples are presented at 1.05 to 1.15 and 1.05 to 1.35 seconds.
+        assert_eq!(media_intervals(&segments), vec![(1.05, 1.35)]);
+    }
+
+    #[test]
+    fn test_size_0_boxes_are_unsupported() {
+        let mut parser = IsoBmffParser::default();
+        let mut stream = 0u32.to_be_bytes().to_vec();
+        stream.extend_from_slice(b"mdat");
+        assert!(matches!(
+            parser.append(&stream),
+            Err(ParseError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_size_1_boxes_have_a_64_bit_size() {
+        let mut parser = IsoBmffParser::default();
+        parser.append(&initialization_segment(1, b"vide")).unwrap();
+        let media = media_segment(1, Some(0), 10);
+        let moof_len = media.len() - 15;
+        assert!(parser.append(&media[..moof_len]).unwrap().is_empty());
+
+        let mut mdat = 1u32.to_be_bytes().to_vec();
+        mdat.extend_from_slice(b"mdat");
+        mdat.extend_from_slice(&20u64.to_be_bytes());
+        // The header of a box with a 64-bit size is 16 bytes long.
+        assert!(parser.append(&mdat[..12]).unwrap().is_empty());
+        assert!(parser.append(&mdat[12..]).unwrap().is_empty());
+        let segments = parser.append(b"data").unwrap();
+        assert_eq!(segments.len(), 1);
+        let Segment::Media(segment) = &segments[0] else {
+            panic!("Expected a media segment");
+        };
+        assert!(segment.bytes.ends_with(&[&mdat[..], b"data"].concat()));
+
+        // A 64-bit size must still cover the 16 byte header.
+        let mut small = 1u32.to_be_bytes().to_vec();
+        small.extend_from_slice(b"free");
+        small.extend_from_slice(&8u64.to_be_bytes());
+        assert!(matches!(
+            IsoBmffParser::default().append(&small),
+            Err(ParseError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_boxes_smaller_than_their_header_are_malformed() {
+        let mut stream = 4u32.to_be_bytes().to_vec();
+        stream.extend_from_slice(b"free");
+        assert!(matches!(
+            IsoBmffParser::default().append(&stream),
+            Err(ParseError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_overflowing_child_box_sizes() {
+        // A child box whose size overflows its parent, or the address space.
+        for size in [1000, u64::MAX] {
+            let mut child = 1u32.to_be_bytes().to_vec();
+            child.extend_from_slice(b"mvhd");
+            child.extend_from_slice(&size.to_be_bytes());
+            let mut stream = make_box(b"ftyp", b"isom");
+            stream.extend(make_box(b"moov", &child));
+            assert!(
+                IsoBmffParser::default().append(&stream).is_err(),
+                "A child box of size {size} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_child_boxes() {
+        // A `mvhd` box that is too short to hold its timescale and duration.
+        let mut stream = make_box(b"ftyp", b"isom");
+        stream.extend(make_box(b"moov", &full_box(b"mvhd", 0, 0, &[0, 0])));
+        assert!(matches!(
+            IsoBmffParser::default().append(&stream),
+            Err(ParseError::Malformed("truncated box"))
+        ));
+
+        // A `moof` box whose `trun` box declares more samples than it holds.
+        let mut parser = IsoBmffParser::default();
+        parser.append(&initialization_segment(1, b"vide")).unwrap();
+        let traf = boxes(&[
+            full_box(b"tfhd", 0, 0, &[1]),
+            full_box(b"trun", 0, 0x100, &[3, 100]),
+        ]);
+        assert!(matches!(
+            parser.append(&make_box(b"moof", &make_box(b"traf", &traf))),
+            Err(ParseError::Malformed("truncated box"))
+        ));
+    }
+
+    #[test]
+    fn test_media_segment_without_initialization_segment() {
+        assert!(matches!(
+            IsoBmffParser::default().append(&media_segment(1, Some(0), 1)),
+            Err(ParseError::MissingInitializationSegment)
+        ));
+
+        let mut parser = IsoBmffParser::default();
+        parser.append(&initialization_segment(1, b"vide")).unwrap();
+        assert!(matches!(
+            parser.append(&media_segment(2, Some(0), 1)),
+            Err(ParseError::Malformed("fragment of an unknown track"))
+        ));
+    }
+
+    #[test]
+    fn test_apply_timestamp_offset() {
+        let mut parser = IsoBmffParser::default();
+        parser.append(&initialization_segment(1, b"vide")).unwrap();
+        let mut segments = parser.append(&media_segment(1, Some(500), 10)).unwrap();
+        let Some(Segment::Media(mut segment)) = segments.pop() else {
+            panic!("Expected a media segment");
+        };
+        parser.apply_timestamp_offset(&mut segment, 2.).unwrap();
+        assert_eq!(segment.bytes, media_segment(1, Some(2500), 10));
+        assert!(matches!(
+            parser.apply_timestamp_offset(&mut segment, -3.),
+            Err(ParseError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_renumber_and_merge_initialization_segments() {
+        let video = initialization_segment(1, b"vide");
+        let mut audio = initialization_segment(1, b"soun");
+        renumber_tracks(&mut audio, &|track_id| track_id + 1).unwrap();
+
+        let merged = merge_initialization_segments(&[video, audio]).unwrap();
+        let mut parser = IsoBmffParser::default();
+        let segments = parser.append(&merged).unwrap();
+        let [Segment::Initialization(segment)] = &segments[..] else {
+            panic!("Expected a single initialization segment");
+        };
+        let tracks: Vec<_> = segment
+            .tracks
+            .iter()
+            .map(|track| (track.id, track.kind))
+            .collect();
+        assert_eq!(tracks, vec![(1, TrackKind::Video), (2, TrackKind::Audio)]);
+
+        // Fragments of both tracks have the default sample duration of their `trex` box.
+        let mut media = media_segment(2, Some(0), 3);
+        assert_eq!(
+            media_intervals(&parser.append(&media).unwrap()),
+            vec![(0., 0.3)]
+        );
+        renumber_tracks(&mut media, &|track_id| track_id + 1).unwrap();
+        assert!(matches!(
+            parser.append(&media),
+            Err(ParseError::Malformed("fragment of an unknown track"))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The byte stream formats of Media Source Extensions.
+//!
+//! Appended bytes are split into initialization and media segments, whose timestamps are
+//! read to keep track of buffered ranges. The media player has a single input, so the
+//! segments of all the `SourceBuffer`s of a `MediaSource` are muxed into one stream:
+//! their initialization segments are merged, and the tracks of their media segments are
+//! renumbered so that they do not clash.
+//!
+//! <https://www.w3.org/TR/mse-byte-stream-format-registry/>
+
+use data_url::mime::Mime;
+
+mod iso_bmff;
+mod webm;
+
+pub(crate) use iso_bmff::IsoBmffParser;
+pub(crate) use webm::WebMParser;
+
+/// <https://www.w3.org/TR/mse-byte-stream-format-registry/#registry>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq)]
+pub(crate) enum ByteStreamFormat {
+    /// <https://www.w3.org/TR/mse-byte-stream-format-isobmff/>
+    IsoBmff,
+    /// <https://www.w3.org/TR/mse-byte-stream-format-webm/>
+    WebM,
+}
+
+impl ByteStreamFormat {
+    /// The byte stream format of a MIME type, as passed to `addSourceBuffer()`, ignoring
+    /// its parameters.
+    pub(crate) fn for_mime_type(type_: &str) -> Option<ByteStreamFormat> {
+        let mime = type_.parse::<Mime>().ok()?;
+        match (mime.type_.as_str(), mime.subtype.as_str()) {
+            ("video" | "audio", "mp4") => Some(ByteStreamFormat::IsoBmff),
+            ("video" | "audio", "webm") => Some(ByteStreamFormat::WebM),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn new_parser(&self) -> SegmentParser {
+        match self {
+            ByteStreamFormat::IsoBmff => SegmentParser::IsoBmff(IsoBmffParser::default()),
+            ByteStreamFormat::WebM => SegmentParser::WebM(WebMParser::default()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq)]
+pub(crate) enum TrackKind {
+    Audio,
+    Video,
+    Text,
+    Other,
+}
+
+/// A track described by an initialization segment.
+#[derive(Clone, Debug, MallocSizeOf)]
+pub(crate) struct TrackInfo {
+    /// The track ID of an ISO BMFF track, or the track number of a WebM track.
+    pub(crate) id: u64,
+    pub(crate) kind: TrackKind,
+}
+
+/// <https://w3c.github.io/media-source/#init-segment>
+#[derive(Clone, Debug, MallocSizeOf)]
+pub(crate) struct InitializationSegment {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) tracks: Vec<TrackInfo>,
+    /// The duration of the presentation, in seconds, if the segment declares it.
+    pub(crate) duration: Option<f64>,
+}
+
+/// <https://w3c.github.io/media-source/#media-segment>
+#[derive(Debug, MallocSizeOf)]
+pub(crate) struct MediaSegment {
+    pub(crate) bytes: Vec<u8>,
+    /// The earliest presentation timestamp of the coded frames of this segment, in seconds.
+    pub(crate) start: f64,
+    /// The presentation end timestamp of the coded frames of this segment, in seconds.
+    pub(crate) end: f64,
+}
+
+#[derive(Debug)]
+pub(crate) enum Segment {
+    Initialization(InitializationSegment),
+    Media(MediaSegment),
+}
+
+/// The ways in which appended bytes can fail to be parsed, which all run the
+/// <https://w3c.github.io/media-source/#sourcebuffer-append-error> algorithm.
+#[derive(Debug)]
+pub(crate) enum ParseError {
+    /// The bytes do not follow the byte stream format.
+    Malformed(&'static str),
+    /// A media segment was appended before any initialization segment.
+    MissingInitializationSegment,
+    /// The bytes use a feature of the format that is not supported.
+    Unsupported(&'static str),
+}
+
+/// A parser of the segments of a byte stream format, which keeps the bytes of incomplete
+/// segments until more are appended.
+#[derive(MallocSizeOf)]
+pub(crate) enum SegmentParser {
+    IsoBmff(IsoBmffParser),
+    WebM(WebMParser),
+}
+
+impl SegmentParser {
+    pub(crate) fn format(&self) -> ByteStreamFormat {
+        match self {
+            SegmentParser::IsoBmff(_) => ByteStreamFormat::IsoBmff,
+            SegmentParser::WebM(_) => ByteStreamFormat::WebM,
+        }
+    }
+
+    /// Appends bytes to the input buffer, and returns the segments that they complete.
+    pub(crate) fn append(&mut self, data: &[u8]) -> Result<Vec<Segment>, ParseError> {
+        match self {
+            SegmentParser::IsoBmff(parser) => parser.append(data),
+            SegmentParser::WebM(parser) => parser.append(data),
+        }
+    }
+
+    /// Whether the input buffer holds the start of a media segment, which is
+    /// the `PARSING_MEDIA_SEGMENT` append state.
+    pub(crate) fn is_parsing_media_segment(&self) -> bool {
+        match self {
+            SegmentParser::IsoBmff(parser) => parser.is_parsing_media_segment(),
+            SegmentParser::WebM(parser) => parser.is_parsing_media_segment(),
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-reset-parser-state>
+    pub(crate) fn reset(&mut self) {
+        match self {
+            SegmentParser::IsoBmff(parser) => parser.reset(),
+            SegmentParser::WebM(parser) => parser.reset(),
+        }
+    }
+
+    /// Shifts the timestamps of the coded frames of a media segment by `offset` seconds.
+    pub(crate) fn apply_timestamp_offset(
+        &self,
+        segment: &mut MediaSegment,
+        offset: f64,
+    ) -> Result<(), ParseError> {
+        if offset == 0. {
+            return Ok(());
+        }
+        match self {
+            SegmentParser::IsoBmff(parser) => parser.apply_timestamp_offset(segment, offset)?,
+            SegmentParser::WebM(parser) => parser.apply_timestamp_offset(segment, offset)?,
+        }
+        segment.start += offset;
+        segment.end += offset;
+        Ok(())
+    }
+}
+
+/// Merges the initialization segments of the `SourceBuffer`s of a `MediaSource` into
+/// one, after their tracks have been renumbered with [`renumber_tracks`].
+pub(crate) fn merge_initialization_segments(
+    format: ByteStreamFormat,
+    segments: &[Vec<u8>],
+) -> Result<Vec<u8>, ParseError> {
+    match format {
+        ByteStreamFormat::IsoBmff => iso_bmff::merge_initialization_segments(segments),
+        ByteStreamFormat::WebM => webm::merge_initialization_segments(segments),
+    }
+}
+
+/// Renumbers the tracks of an initialization or media segment with `map`, which keeps
+/// the tracks of the `SourceBuffer`s of a `MediaSource` apart once they are muxed.
+pub(crate) fn renumber_tracks(
+    format: ByteStreamFormat,
+    bytes: &mut [u8],
+    map: &dyn Fn(u64) -> u64,
+) -> Result<(), ParseError> {
+    match format {
+        ByteStreamFormat::IsoBmff => iso_bmff::renumber_tracks(bytes, map),
+        ByteStreamFormat::WebM => webm::renumber_tracks(bytes, map),
+    }
+}
@@ -0,0 +1,941 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! <https://www.w3.org/TR/mse-byte-stream-format-webm/>
+//!
+//! Initialization segments are an EBML header followed by the start of a Segment element
+//! up to its Tracks element, and media segments are Cluster elements. Cues and other
+//! level 1 elements are skipped, and SeekHead elements are dropped since their positions
+//! do not hold once segments are muxed.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::{InitializationSegment, MediaSegment, ParseError, Segment, TrackInfo, TrackKind};
+
+const EBML: u32 = 0x1A45DFA3;
+const SEGMENT: u32 = 0x18538067;
+const INFO: u32 = 0x1549A966;
+const TIMECODE_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_TYPE: u32 = 0x83;
+const DEFAULT_DURATION: u32 = 0x23E383;
+const CLUSTER: u32 = 0x1F43B675;
+const TIMECODE: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const BLOCK_DURATION: u32 = 0x9B;
+
+/// The elements that end a Cluster of unknown size.
+const CLUSTER_TERMINATORS: [u32; 9] = [
+    EBML, SEGMENT, CLUSTER, INFO, TRACKS, 0x114D9B74, 0x1C53BB6B, 0x1043A770, 0x1254C367,
+];
+
+/// The size of a Segment or Cluster element whose end is not known.
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+/// The header of an element, whose body follows it.
+struct ElementHeader {
+    id: u32,
+    header_len: usize,
+    /// The size of the body, or `None` if it is unknown.
+    size: Option<u64>,
+}
+
+/// An element within the body of another element.
+struct ChildElement {
+    id: u32,
+    /// The range of the whole element, in the body of its parent.
+    range: Range<usize>,
+    /// The range of the body of the element, in the body of its parent.
+    body: Range<usize>,
+}
+
+fn truncated() -> ParseError {
+    ParseError::Malformed("truncated element")
+}
+
+/// Reads a variable-size integer, as its value without the length marker and its length,
+/// or returns `None` if more bytes are needed.
+fn read_vint(data: &[u8], max_len: usize) -> Result<Option<(u64, usize)>, ParseError> {
+    let Some(first) = data.first() else {
+        return Ok(None);
+    };
+    let len = first.leading_zeros() as usize + 1;
+    if len > max_len {
+        return Err(ParseError::Malformed("invalid variable-size integer"));
+    }
+    if data.len() < len {
+        return Ok(None);
+    }
+    let value = data[1..len]
+        .iter()
+        .fold((*first as u64) & (0xFF >> len), |value, byte| {
+            (value << 8) | *byte as u64
+        });
+    Ok(Some((value, len)))
+}
+
+/// Reads the header of the element at the start of `data`, or returns `None` if more
+/// bytes are needed.
+fn read_element_header(data: &[u8]) -> Result<Option<ElementHeader>, ParseError> {
+    let Some((_, id_len)) = read_vint(data, 4)? else {
+        return Ok(None);
+    };
+    let id = data[..id_len]
+        .iter()
+        .fold(0, |id, byte| (id << 8) | *byte as u32);
+    let Some((size, size_len)) = read_vint(&data[id_len..], 8)? else {
+        return Ok(None);
+    };
+    let unknown = size == (1 << (7 * size_len)) - 1;
+    Ok(Some(ElementHeader {
+        id,
+        header_len: id_len + size_len,
+        size: (!unknown).then_some(size),
+    }))
+}
+
+/// The elements that make up `data`, which must all be complete. An element of unknown
+/// size extends to the end of `data`.
+fn child_elements(data: &[u8]) -> Result<Vec<ChildElement>, ParseError> {
+    let mut elements = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let header = read_element_header(&data[offset..])?.ok_or_else(truncated)?;
+        let body_start = offset + header.header_len;
+        let end = match header.size {
+            Some(size) => usize::try_from(size)
+                .ok()
+                .and_then(|size| body_start.checked_add(size))
+                .filter(|end| *end <= data.len())
+                .ok_or_else(truncated)?,
+            None => data.len(),
+        };
+        elements.push(ChildElement {
+            id: header.id,
+            range: offset..end,
+            body: body_start..end,
+        });
+        offset = end;
+    }
+    Ok(elements)
+}
+
+fn read_uint(data: &[u8]) -> Result<u64, ParseError> {
+    if data.len() > 8 {
+        return Err(ParseError::Malformed("integer over 8 bytes"));
+    }
+    Ok(data
+        .iter()
+        .fold(0, |value, byte| (value << 8) | *byte as u64))
+}
+
+fn read_float(data: &[u8]) -> Result<f64, ParseError> {
+    match data.len() {
+        4 => Ok(f32::from_be_bytes(data.try_into().unwrap()) as f64),
+        8 => Ok(f64::from_be_bytes(data.try_into().unwrap())),
+        _ => Err(ParseError::Malformed("float of invalid size")),
+    }
+}
+
+/// Writes `value` over the unsigned integer `data`, which keeps its size.
+fn write_uint(data: &mut [u8], value: u64) -> Result<(), ParseError> {
+    if data.len() < 8 && value >> (8 * data.len()) != 0 {
+        return Err(ParseError::Unsupported(
+            "track numbers that outgrow their element",
+        ));
+    }
+    for (index, byte) in data.iter_mut().rev().enumerate() {
+        *byte = value.checked_shr(8 * index as u32).unwrap_or(0) as u8;
+    }
+    Ok(())
+}
+
+fn encode_id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+    bytes[skip..].to_vec()
+}
+
+/// Builds an element with the given ID around `body`, with an eight byte size.
+fn make_element(id: u32, body: &[u8]) -> Vec<u8> {
+    let mut bytes = encode_id(id);
+    let mut size = (body.len() as u64).to_be_bytes();
+    size[0] = 0x01;
+    bytes.extend_from_slice(&size);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+#[derive(Clone, Copy, MallocSizeOf)]
+struct Track {
+    /// The duration of the frames of the track, in nanoseconds, if it is constant.
+    default_duration: Option<u64>,
+}
+
+#[derive(Default, MallocSizeOf)]
+pub(crate) struct WebMParser {
+    /// The appended bytes that do not make up a complete element yet.
+    input: Vec<u8>,
+    /// The bytes of the initialization segment whose Tracks element is awaited.
+    initialization_segment: Option<Vec<u8>>,
+    /// The duration of the initialization segment whose Tracks element is awaited.
+    duration: Option<f64>,
+    /// Whether the input buffer is within the body of a Segment element.
+    in_segment: bool,
+    /// The number of nanoseconds per tick of the timecodes of the last Info element.
+    timecode_scale: u64,
+    /// The tracks of the last initialization segment, by track number.
+    tracks: HashMap<u64, Track>,
+    /// The length of the header and complete children of the Cluster of unknown size at
+    /// the start of the input buffer, if any.
+    cluster_len: Option<usize>,
+}
+
+impl WebMParser {
+    pub(crate) fn append(&mut self, data: &[u8]) -> Result<Vec<Segment>, ParseError> {
+        self.input.extend_from_slice(data);
+
+        let mut segments = vec![];
+        let mut offset = 0;
+        loop {
+            // A Cluster of unknown size ends with the start of the next level 1 element.
+            if let Some(cluster_len) = self.cluster_len {
+                let rest = &self.input[cluster_len..];
+                let Some(header) = read_element_header(rest)? else {
+                    break;
+                };
+                if CLUSTER_TERMINATORS.contains(&header.id) {
+                    segments.push(Segment::Media(
+                        self.parse_cluster(&self.input[..cluster_len])?,
+                    ));
+                    self.input.drain(..cluster_len);
+                    self.cluster_len = None;
+                    continue;
+                }
+                let size = header
+                    .size
+                    .and_then(|size| usize::try_from(size).ok())
+                    .ok_or(ParseError::Unsupported("cluster children of unknown size"))?;
+                if rest.len() < header.header_len + size {
+                    break;
+                }
+                self.cluster_len = Some(cluster_len + header.header_len + size);
+                continue;
+            }
+
+            let Some(header) = read_element_header(&self.input[offset..])? else {
+                break;
+            };
+            let body_start = offset + header.header_len;
+
+            match header.id {
+                SEGMENT => {
+                    let Some(initialization_segment) = self.initialization_segment.as_mut() else {
+                        return Err(ParseError::Malformed("segment without EBML header"));
+                    };
+                    // The Segment element is not awaited, since its body is the rest of
+                    // the stream.
+                    initialization_segment.extend(encode_id(SEGMENT));
+                    initialization_segment.extend_from_slice(&UNKNOWN_SIZE);
+                    self.in_segment = true;
+                    offset = body_start;
+                    continue;
+                },
+                CLUSTER if header.size.is_none() => {
+                    if self.tracks.is_empty() || self.initialization_segment.is_some() {
+                        return Err(ParseError::MissingInitializationSegment);
+                    }
+                    self.input.drain(..offset);
+                    offset = 0;
+                    self.cluster_len = Some(header.header_len);
+                    continue;
+                },
+                _ => {},
+            }
+
+            let size = header
+                .size
+                .and_then(|size| usize::try_from(size).ok())
+                .ok_or(ParseError::Unsupported("elements of unknown size"))?;
+            if self.input.len() - body_start < size {
+                break;
+            }
+            let range = offset..body_start + size;
+            let body = body_start..body_start + size;
+            offset = range.end;
+
+            match header.id {
+                EBML => {
+                    self.initialization_segment = Some(self.input[range].to_vec());
+                    self.duration = None;
+                    self.in_segment = false;
+                },
+                _ if !self.in_segment => {
+                    return Err(ParseError::Malformed("element outside of a segment"));
+                },
+                INFO => {
+                    let Some(initialization_segment) = self.initialization_segment.as_mut() else {
+                        continue;
+                    };
+                    initialization_segment.extend_from_slice(&self.input[range]);
+                    let (timecode_scale, duration) = parse_info(&self.input[body])?;
+                    self.timecode_scale = timecode_scale;
+                    self.duration = duration;
+                },
+                TRACKS => {
+                    let Some(mut bytes) = self.initialization_segment.take() else {
+                        continue;
+                    };
+                    bytes.extend_from_slice(&self.input[range]);
+                    let (tracks, track_infos) = parse_tracks(&self.input[body])?;
+                    if self.timecode_scale == 0 {
+                        self.timecode_scale = 1_000_000;
+                    }
+                    self.tracks = tracks;
+                    segments.push(Segment::Initialization(InitializationSegment {
+                        bytes,
+                        tracks: track_infos,
+                        duration: self.duration.take(),
+                    }));
+                },
+                CLUSTER => {
+                    if self.tracks.is_empty() || self.initialization_segment.is_some() {
+                        return Err(ParseError::MissingInitializationSegment);
+                    }
+                    segments.push(Segment::Media(self.parse_cluster(&self.input[range])?));
+                },
+                _ => {},
+            }
+        }
+        self.input.drain(..offset);
+        Ok(segments)
+    }
+
+    pub(crate) fn is_parsing_media_segment(&self) -> bool {
+        self.cluster_len.is_some()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.input.clear();
+        self.cluster_len = None;
+        if self.initialization_segment.take().is_some() {
+            self.in_segment = false;
+        }
+    }
+
+    /// Reads the presentation interval, in seconds, of the blocks of a Cluster element.
+    fn parse_cluster(&self, bytes: &[u8]) -> Result<MediaSegment, ParseError> {
+        let header = read_element_header(bytes)?.ok_or_else(truncated)?;
+        let body = &bytes[header.header_len..];
+
+        let mut cluster_timecode = None;
+        let mut blocks = vec![];
+        for child in child_elements(body)? {
+            match child.id {
+                TIMECODE => cluster_timecode = Some(read_uint(&body[child.body])?),
+                SIMPLE_BLOCK => blocks.push((&body[child.body], None)),
+                BLOCK_GROUP => {
+                    let group = &body[child.body];
+                    let mut block = None;
+                    let mut duration = None;
+                    for group_child in child_elements(group)? {
+                        match group_child.id {
+                            BLOCK => block = Some(&group[group_child.body]),
+                            BLOCK_DURATION => duration = Some(read_uint(&group[group_child.body])?),
+                            _ => {},
+                        }
+                    }
+                    blocks.push((
+                        block.ok_or(ParseError::Malformed("block group without block"))?,
+                        duration,
+                    ));
+                },
+                _ => {},
+            }
+        }
+        let cluster_timecode =
+            cluster_timecode.ok_or(ParseError::Malformed("cluster without timecode"))? as i64;
+
+        let timecode_scale = self.timecode_scale as f64;
+        let mut start = f64::INFINITY;
+        let mut end = f64::NEG_INFINITY;
+        let mut last_start = f64::NEG_INFINITY;
+        let block_count = blocks.len();
+        for (block, duration) in blocks {
+            let (track_number, track_len) = read_vint(block, 8)?.ok_or_else(truncated)?;
+            let track = self
+                .tracks
+                .get(&track_number)
+                .ok_or(ParseError::Malformed("block of an unknown track"))?;
+            let relative_timecode = block
+                .get(track_len..track_len + 2)
+                .map(|bytes| i16::from_be_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(truncated)?;
+
+            let block_start =
+                (cluster_timecode + relative_timecode as i64) as f64 * timecode_scale / 1e9;
+            let duration = match (duration, track.default_duration) {
+                (Some(duration), _) => duration as f64 * timecode_scale / 1e9,
+                (None, Some(default_duration)) => default_duration as f64 / 1e9,
+                (None, None) => 0.,
+            };
+            start = start.min(block_start);
+            end = end.max(block_start + duration);
+            last_start = last_start.max(block_start);
+        }
+        if start > end {
+            return Err(ParseError::Malformed("cluster without blocks"));
+        }
+
+        // Without durations, the last block is assumed to last as long as the average one.
+        if end <= last_start && last_start > start {
+            end = last_start + (last_start - start) / (block_count - 1) as f64;
+        }
+
+        Ok(MediaSegment {
+            bytes: bytes.to_vec(),
+            start,
+            end,
+        })
+    }
+
+    /// Shifts the timecode of the Cluster element of a media segment.
+    pub(crate) fn apply_timestamp_offset(
+        &self,
+        segment: &mut MediaSegment,
+        offset: f64,
+    ) -> Result<(), ParseError> {
+        let header = read_element_header(&segment.bytes)?.ok_or_else(truncated)?;
+        let body = &segment.bytes[header.header_len..];
+
+        let mut new_body = vec![];
+        for child in child_elements(body)? {
+            if child.id != TIMECODE {
+                new_body.extend_from_slice(&body[child.range]);
+                continue;
+            }
+            let timecode = read_uint(&body[child.body])? as f64;
+            let timecode = (timecode + offset * 1e9 / self.timecode_scale as f64).round();
+            if timecode < 0. {
+                return Err(ParseError::Unsupported("negative timecodes"));
+            }
+            new_body.extend(encode_id(TIMECODE));
+            new_body.push(0x88);
+            new_body.extend_from_slice(&(timecode as u64).to_be_bytes());
+        }
+
+        segment.bytes = match header.size {
+            Some(_) => make_element(CLUSTER, &new_body),
+            None => {
+                let mut bytes = encode_id(CLUSTER);
+                bytes.extend_from_slice(&UNKNOWN_SIZE);
+                bytes.extend(new_body);
+                bytes
+            },
+        };
+        Ok(())
+    }
+}
+
+/// Reads the timecode scale and the duration, in seconds, of an Info element.
+fn parse_info(info: &[u8]) -> Result<(u64, Option<f64>), ParseError> {
+    let mut timecode_scale = 1_000_000;
+    let mut duration = None;
+    for child in child_elements(info)? {
+        match child.id {
+            TIMECODE_SCALE => timecode_scale = read_uint(&info[child.body])?,
+            DURATION => duration = Some(read_float(&info[child.body])?),
+            _ => {},
+        }
+    }
+    if timecode_scale == 0 {
+        return Err(ParseError::Malformed("zero timecode scale"));
+    }
+    let duration = duration
+        .filter(|duration| duration.is_finite() && *duration > 0.)
+        .map(|duration| duration * timecode_scale as f64 / 1e9);
+    Ok((timecode_scale, duration))
+}
+
+/// Reads the track entries of a Tracks element.
+fn parse_tracks(tracks: &[u8]) -> Result<(HashMap<u64, Track>, Vec<TrackInfo>), ParseError> {
+    let mut parsed = HashMap::new();
+    let mut track_infos = vec![];
+    for entry in child_elements(tracks)?
+        .iter()
+        .filter(|child| child.id == TRACK_ENTRY)
+    {
+        let entry = &tracks[entry.body.clone()];
+        let mut track_number = None;
+        let mut kind = TrackKind::Other;
+        let mut default_duration = None;
+        for child in child_elements(entry)? {
+            let body = &entry[child.body];
+            match child.id {
+                TRACK_NUMBER => track_number = Some(read_uint(body)?),
+                TRACK_TYPE => {
+                    kind = match read_uint(body)? {
+                        1 => TrackKind::Video,
+                        2 => TrackKind::Audio,
+                        17 => TrackKind::Text,
+                        _ => TrackKind::Other,
+                    }
+                },
+                DEFAULT_DURATION => default_duration = Some(read_uint(body)?),
+                _ => {},
+            }
+        }
+        let track_number =
+            track_number.ok_or(ParseError::Malformed("track entry without number"))?;
+        parsed.insert(track_number, Track { default_duration });
+        track_infos.push(TrackInfo {
+            id: track_number,
+            kind,
+        });
+    }
+    if parsed.is_empty() {
+        return Err(ParseError::Malformed("tracks without entries"));
+    }
+    Ok((parsed, track_infos))
+}
+
+/// Writes `map(track_number)` over the track number at the start of a block, which keeps
+/// its length.
+fn renumber_block(block: &mut [u8], map: &dyn Fn(u64) -> u64) -> Result<(), ParseError> {
+    let (track_number, len) = read_vint(block, 8)?.ok_or_else(truncated)?;
+    let track_number = map(track_number);
+    if track_number >= (1 << (7 * len)) - 1 {
+        return Err(ParseError::Unsupported(
+            "track numbers that outgrow their block",
+        ));
+    }
+    for (index, byte) in block[..len].iter_mut().rev().enumerate() {
+        *byte = (track_number >> (8 * index)) as u8;
+    }
+    block[0] |= 0x80 >> (len - 1);
+    Ok(())
+}
+
+/// Renumbers the TrackNumber elements of an initialization segment, or the blocks of a
+/// media segment.
+pub(super) fn renumber_tracks(
+    bytes: &mut [u8],
+    map: &dyn Fn(u64) -> u64,
+) -> Result<(), ParseError> {
+    for top_level in child_elements(bytes)? {
+        match top_level.id {
+            SEGMENT => {
+                let base = top_level.body.start;
+                for tracks in child_elements(&bytes[top_level.body.clone()])? {
+                    if tracks.id != TRACKS {
+                        continue;
+                    }
+                    let tracks_base = base + tracks.body.start;
+                    for entry in child_elements(&bytes[tracks_base..base + tracks.body.end])? {
+                        if entry.id != TRACK_ENTRY {
+                            continue;
+                        }
+                        let entry_base = tracks_base + entry.body.start;
+                        for child in
+                            child_elements(&bytes[entry_base..tracks_base + entry.body.end])?
+                        {
+                            if child.id != TRACK_NUMBER {
+                                continue;
+                            }
+                            let number = &mut bytes
+                                [entry_base + child.body.start..entry_base + child.body.end];
+                            let track_number = map(read_uint(number)?);
+                            write_uint(number, track_number)?;
+                        }
+                    }
+                }
+            },
+            CLUSTER => {
+                let base = top_level.body.start;
+                for child in child_elements(&bytes[top_level.body.clone()])? {
+                    match child.id {
+                        SIMPLE_BLOCK => renumber_block(
+                            &mut bytes[base + child.body.start..base + child.body.end],
+                            map,
+                        )?,
+                        BLOCK_GROUP => {
+                            let group_base = base + child.body.start;
+                            for group_child in
+                                child_elements(&bytes[group_base..base + child.body.end])?
+                            {
+                                if group_child.id == BLOCK {
+                                    renumber_block(
+                                        &mut bytes[group_base + group_child.body.start
+                                            ..group_base + group_child.body.end],
+                                        map,
+                                    )?;
+                                }
+                            }
+                        },
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Merges initialization segments into the EBML header and Info element of the first
+/// one, followed by a Tracks element with the track entries of all of them.
+pub(super) fn merge_initialization_segments(segments: &[Vec<u8>]) -> Result<Vec<u8>, ParseError> {
+    let Some((first, others)) = segments.split_first() else {
+        return Ok(vec![]);
+    };
+    if others.is_empty() {
+        return Ok(first.clone());
+    }
+
+    let mut ebml_header = None;
+    let mut info = None;
+    let mut track_entries = vec![];
+    for (index, segment) in segments.iter().enumerate() {
+        for top_level in child_elements(segment)? {
+            match top_level.id {
+                EBML if index == 0 => ebml_header = Some(&segment[top_level.range]),
+                SEGMENT => {
+                    let body = &segment[top_level.body];
+                    for child in child_elements(body)? {
+                        match child.id {
+                            INFO if index == 0 => info = Some(&body[child.range]),
+                            TRACKS => {
+                                let tracks = &body[child.body];
+                                for entry in child_elements(tracks)? {
+                                    if entry.id == TRACK_ENTRY {
+                                        track_entries.extend_from_slice(&tracks[entry.range]);
+                                    }
+                                }
+                            },
+                            _ => {},
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    let mut merged = ebml_header
+        .ok_or(ParseError::Malformed("segment without EBML header"))?
+        .to_vec();
+    merged.extend(encode_id(SEGMENT));
+    merged.extend_from_slice(&UNKNOWN_SIZE);
+    if let Some(info) = info {
+        merged.extend_from_slice(info);
+    }
+    merged.extend(make_element(TRACKS, &track_entries));
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC_TYPE: u32 = 0x4282;
+    const CUES: u32 = 0x1C53BB6B;
+
+    /// Builds an element with a one byte size.
+    fn element(id: u32, body: &[u8]) -> Vec<u8> {
+        assert!(body.len() < 0x7F);
+        let mut bytes = encode_id(id);
+        bytes.push(0x80 | body.len() as u8);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    fn unknown_size_element(id: u32, body: &[u8]) -> Vec<u8> {
+        let mut bytes = encode_id(id);
+        bytes.extend_from_slice(&UNKNOWN_SIZE);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// An initialization segment whose Tracks element has the given body, with timecodes
+    /// in milliseconds and a duration of two seconds.
+    fn initialization_segment_with_tracks(tracks: &[u8]) -> Vec<u8> {
+        let info = [
+            element(TIMECODE_SCALE, &[0x0F, 0x42, 0x40]),
+            element(DURATION, &2000f64.to_be_bytes()),
+        ]
+        .concat();
+        [
+            element(EBML, &element(DOC_TYPE, b"webm")),
+            unknown_size_element(SEGMENT, &[]),
+            element(INFO, &info),
+            make_element(TRACKS, tracks),
+        ]
+        .concat()
+    }
+
+    fn initialization_segment(
+        track_number: u8,
+        track_type: u8,
+        default_duration: Option<u32>,
+    ) -> Vec<u8> {
+        let mut track_entry = [
+            element(TRACK_NUMBER, &[track_number]),
+            element(TRACK_TYPE, &[track_type]),
+        ]
+        .concat();
+        if let Some(default_duration) = default_duration {
+            track_entry.extend(element(DEFAULT_DURATION, &default_duration.to_be_bytes()));
+        }
+        initialization_segment_with_tracks(&element(TRACK_ENTRY, &track_entry))
+    }
+
+    /// The body of a Cluster element with a SimpleBlock for each of `blocks`, which are
+    /// pairs of a track number and a timecode relative to the cluster.
+    fn cluster_body(timecode: u16, blocks: &[(u8, i16)]) -> Vec<u8> {
+        let mut body = element(TIMECODE, &timecode.to_be_bytes());
+        for (track_number, relative_timecode) in blocks {
+            let mut block = vec![0x80 | track_number];
+            block.extend_from_slice(&relative_timecode.to_be_bytes());
+            block.extend_from_slice(&[0x80, 0xAA]);
+            body.extend(element(SIMPLE_BLOCK, &block));
+        }
+        body
+    }
+
+    fn cluster(timecode: u16, blocks: &[(u8, i16)]) -> Vec<u8> {
+        make_element(CLUSTER, &cluster_body(timecode, blocks))
+    }
+
+    fn media_intervals(segments: &[Segment]) -> Vec<(f64, f64)> {
+        segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Media(segment) => Some((segment.start, segment.end)),
+                Segment::Initialization(_) => None,
+            })
+            .collect()
+    }
+
+    fn stream() -> Vec<u8> {
+        [
+            initialization_segment(1, 1, Some(250_000_000)),
+            cluster(1000, &[(1, 0), (1, 250)]),
+            cluster(2000, &[(1, 0)]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_initialization_segment_followed_by_media_segments() {
+        let mut parser = WebMParser::default();
+        let segments = parser.append(&stream()).unwrap();
+        assert_eq!(segments.len(), 3);
+        let Segment::Initialization(segment) = &segments[0] else {
+            panic!("Expected an initialization segment first");
+        };
+        assert_eq!(
+            segment.bytes,
+            initialization_segment(1, 1, Some(250_000_000))
+        );
+        assert_eq!(segment.duration, Some(2.));
+        assert_eq!(segment.tracks.len(), 1);
+        assert_eq!(segment.tracks[0].id, 1);
+        assert_eq!(segment.tracks[0].kind, TrackKind::Video);
+        assert_eq!(media_intervals(&segments), vec![(1., 1.5), (2., 2.25)]);
+        assert!(!parser.is_parsing_media_segment());
+    }
+
+    #[test]
+    fn test_truncated_elements_wait_for_more_bytes() {
+        let mut parser = WebMParser::default();
+        let mut segments = vec![];
+        for byte in stream().iter() {
+            segments.extend(parser.append(&[*byte]).unwrap());
+        }
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(segments[0], Segment::Initialization(_)));
+        assert_eq!(media_intervals(&segments), vec![(1., 1.5), (2., 2.25)]);
+    }
+
+    #[test]
+    fn test_cluster_of_unknown_size_ends_at_next_level_1_element() {
+        let mut parser = WebMParser::default();
+        parser
+            .append(&initialization_segment(1, 2, Some(250_000_000)))
+            .unwrap();
+
+        let unknown_size_cluster =
+            unknown_size_element(CLUSTER, &cluster_body(1000, &[(1, 0), (1, 250)]));
+        // Stop in the middle of the header of the last block.
+        let split = unknown_size_cluster.len() - 6;
+        assert!(
+            parser
+                .append(&unknown_size_cluster[..split])
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parser.is_parsing_media_segment());
+        assert!(
+            parser
+                .append(&unknown_size_cluster[split..])
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parser.is_parsing_media_segment());
+
+        let segments = parser.append(&cluster(2000, &[(1, 0)])).unwrap();
+        assert_eq!(media_intervals(&segments), vec![(1., 1.5), (2., 2.25)]);
+        let Segment::Media(segment) = &segments[0] else {
+            panic!("Expected a media segment");
+        };
+        assert_eq!(segment.bytes, unknown_size_cluster);
+        assert!(!parser.is_parsing_media_segment());
+    }
+
+    #[test]
+    fn test_block_durations() {
+        let mut parser = WebMParser::default();
+        parser.append(&initialization_segment(1, 2, None)).unwrap();
+
+        // A BlockGroup declares the duration of its block.
+        let block_group = [
+            element(BLOCK, &[0x81, 0x00, 0x00, 0x00, 0xAA]),
+            element(BLOCK_DURATION, &[0x01, 0xF4]),
+        ]
+        .concat();
+        let body = [
+            element(TIMECODE, &[0x03, 0xE8]),
+            element(BLOCK_GROUP, &block_group),
+        ]
+        .concat();
+        let segments = parser.append(&make_element(CLUSTER, &body)).unwrap();
+        assert_eq!(media_intervals(&segments), vec![(1., 1.5)]);
+
+        // Without durations, the last block lasts as long as the average of the others.
+        let segments = parser
+            .append(&cluster(1000, &[(1, 0), (1, 250), (1, 500)]))
+            .unwrap();
+        assert_eq!(media_intervals(&segments), vec![(1., 1.75)]);
+    }
+
+    #[test]
+    fn test_invalid_variable_size_integers() {
+        // An element ID can be at most four bytes long.
+        assert!(matches!(
+            WebMParser::default().append(&[0x08, 0x00, 0x00, 0x00, 0x00, 0x80]),
+            Err(ParseError::Malformed("invalid variable-size integer"))
+        ));
+        // An element size can be at most eight bytes long.
+        assert!(matches!(
+            WebMParser::default().append(&[0x1A, 0x45, 0xDF, 0xA3, 0x00, 0x00]),
+            Err(ParseError::Malformed("invalid variable-size integer"))
+        ));
+    }
+
+    #[test]
+    fn test_overflowing_element_sizes() {
+        // A child element whose size overflows its parent.
+        let mut track_entry = encode_id(TRACK_ENTRY);
+        track_entry.extend_from_slice(&[0x01, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(matches!(
+            WebMParser::default().append(&initialization_segment_with_tracks(&track_entry)),
+            Err(ParseError::Malformed("truncated element"))
+        ));
+
+        // A level 1 element that is larger than the stream so far is awaited.
+        let mut parser = WebMParser::default();
+        parser.append(&initialization_segment(1, 1, None)).unwrap();
+        let mut huge_cluster = encode_id(CLUSTER);
+        huge_cluster.extend_from_slice(&[0x01, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(parser.append(&huge_cluster).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_sizes_are_only_supported_for_segments_and_clusters() {
+        let mut parser = WebMParser::default();
+        parser.append(&initialization_segment(1, 1, None)).unwrap();
+        assert!(matches!(
+            parser.append(&unknown_size_element(CUES, &[])),
+            Err(ParseError::Unsupported("elements of unknown size"))
+        ));
+    }
+
+    #[test]
+    fn test_elements_out_of_order() {
+        assert!(matches!(
+            WebMParser::default().append(&unknown_size_element(SEGMENT, &[])),
+            Err(ParseError::Malformed("segment without EBML header"))
+        ));
+        let mut stream = element(EBML, &element(DOC_TYPE, b"webm"));
+        stream.extend(element(INFO, &[]));
+        assert!(matches!(
+            WebMParser::default().append(&stream),
+            Err(ParseError::Malformed("element outside of a segment"))
+        ));
+
+        let mut stream = [
+            element(EBML, &element(DOC_TYPE, b"webm")),
+            unknown_size_element(SEGMENT, &[]),
+        ]
+        .concat();
+        stream.extend(cluster(0, &[(1, 0)]));
+        assert!(matches!(
+            WebMParser::default().append(&stream),
+            Err(ParseError::MissingInitializationSegment)
+        ));
+    }
+
+    #[test]
+    fn test_apply_timestamp_offset() {
+        let mut parser = WebMParser::default();
+        let mut segments = parser.append(&stream()).unwrap();
+        let Some(Segment::Media(mut segment)) = segments.pop() else {
+            panic!("Expected a media segment");
+        };
+        parser.apply_timestamp_offset(&mut segment, 1.).unwrap();
+        let shifted = parser.parse_cluster(&segment.bytes).unwrap();
+        assert_eq!((shifted.start, shifted.end), (3., 3.25));
+        assert!(matches!(
+            parser.apply_timestamp_offset(&mut segment, -4.),
+            Err(ParseError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_renumber_and_merge_initialization_segments() {
+        let video = initialization_segment(1, 1, None);
+        let mut audio = initialization_segment(1, 2, Some(250_000_000));
+        renumber_tracks(&mut audio, &|track_number| track_number + 1).unwrap();
+
+        let merged = merge_initialization_segments(&[video, audio]).unwrap();
+        let mut parser = WebMParser::default();
+        let segments = parser.append(&merged).unwrap();
+        let [Segment::Initialization(segment)] = &segments[..] else {
+            panic!("Expected a single initialization segment");
+        };
+        let tracks: Vec<_> = segment
+            .tracks
+            .iter()
+            .map(|track| (track.id, track.kind))
+            .collect();
+        assert_eq!(tracks, vec![(1, TrackKind::Video), (2, TrackKind::Audio)]);
+
+        let mut media = cluster(1000, &[(2, 0)]);
+        assert_eq!(
+            media_intervals(&parser.append(&media).unwrap()),
+            vec![(1., 1.25)]
+        );
+        renumber_tracks(&mut media, &|track_number| track_number + 1).unwrap();
+        assert!(matches!(
+            parser.append(&media),
+            Err(ParseError::Malformed("block of an unknown track"))
+        ));
+    }
+}
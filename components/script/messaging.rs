@@ -56,6 +56,8 @@ impl MixedMessage {
                     .or(Some(new_layout_info.new_pipeline_id)),
                 ScriptThreadMessage::Resize(id, ..) => Some(*id),
                 ScriptThreadMessage::ThemeChange(id, ..) => Some(*id),
+                ScriptThreadMessage::SetUserAgentStylesheetOverride(id, ..) => Some(*id),
+                ScriptThreadMessage::SetUserStylesheets(id, ..) => Some(*id),
                 ScriptThreadMessage::ResizeInactive(id, ..) => Some(*id),
                 ScriptThreadMessage::UnloadDocument(id) => Some(*id),
                 ScriptThreadMessage::ExitPipeline(_webview_id, id, ..) => Some(*id),
@@ -65,6 +67,8 @@ impl MixedMessage {
                 ScriptThreadMessage::GetTitle(id) => Some(*id),
                 ScriptThreadMessage::SetDocumentActivity(id, ..) => Some(*id),
                 ScriptThreadMessage::SetThrottled(id, ..) => Some(*id),
+                ScriptThreadMessage::SetTimersIntensivelyThrottled(id, ..) => Some(*id),
+                ScriptThreadMessage::SetFrozen(id, ..) => Some(*id),
                 ScriptThreadMessage::SetThrottledInContainingIframe(id, ..) => Some(*id),
                 ScriptThreadMessage::NavigateIframe(id, ..) => Some(*id),
                 ScriptThreadMessage::PostMessage { target: id, .. } => Some(*id),
@@ -73,6 +77,7 @@ impl MixedMessage {
                 ScriptThreadMessage::RemoveHistoryStates(id, ..) => Some(*id),
                 ScriptThreadMessage::FocusIFrame(id, ..) => Some(*id),
                 ScriptThreadMessage::FocusDocument(id, ..) => Some(*id),
+                ScriptThreadMessage::MoveSequentialFocus(id, ..) => Some(*id),
                 ScriptThreadMessage::Unfocus(id, ..) => Some(*id),
                 ScriptThreadMessage::WebDriverScriptCommand(id, ..) => Some(*id),
                 ScriptThreadMessage::TickAllAnimations(..) => None,
@@ -87,12 +92,22 @@ impl MixedMessage {
                 ScriptThreadMessage::Reload(id, ..) => Some(*id),
                 ScriptThreadMessage::PaintMetric(id, ..) => Some(*id),
                 ScriptThreadMessage::ExitFullScreen(id, ..) => Some(*id),
+                ScriptThreadMessage::SetFullscreenStateOfChild(id, ..) => Some(*id),
                 ScriptThreadMessage::MediaSessionAction(..) => None,
+                ScriptThreadMessage::ExitPictureInPicture(id) => Some(*id),
+                ScriptThreadMessage::PictureInPictureWindowResized(id, ..) => Some(*id),
+                ScriptThreadMessage::UpdatePlaceholderCanvas(id, ..) => Some(*id),
                 #[cfg(feature = "webgpu")]
                 ScriptThreadMessage::SetWebGPUPort(..) => None,
                 ScriptThreadMessage::SetScrollStates(id, ..) => Some(*id),
+                ScriptThreadMessage::IntersectionObservationsChanged(id, ..) => Some(*id),
                 ScriptThreadMessage::EvaluateJavaScript(id, _, _) => Some(*id),
+                ScriptThreadMessage::FindInPage(id, _) => Some(*id),
                 ScriptThreadMessage::SendImageKeysBatch(..) => None,
+                ScriptThreadMessage::CollectGarbage(..) => None,
+                ScriptThreadMessage::PreferencesChanged(..) => None,
+                ScriptThreadMessage::PermissionDecisionChanged(..) => None,
+                ScriptThreadMessage::ReportActivity(..) => None,
             },
             MixedMessage::FromScript(inner_msg) => match inner_msg {
                 MainThreadScriptMsg::Common(CommonScriptMsg::Task(_, _, pipeline_id, _)) => {
@@ -419,6 +434,19 @@ impl ScriptThreadReceivers {
         }
     }
 
+    /// Whether none of the receivers of this [`ScriptThreadReceivers`] nor the given
+    /// [`TaskQueue`] have any messages ready to be received.
+    pub(crate) fn is_empty(&self, task_queue: &TaskQueue<MainThreadScriptMsg>) -> bool {
+        #[cfg(feature = "webgpu")]
+        if !self.webgpu_receiver.borrow().is_empty() {
+            return false;
+        }
+        self.constellation_receiver.is_empty() &&
+            !task_queue.has_pending_tasks() &&
+            self.devtools_server_receiver.is_empty() &&
+            self.image_cache_receiver.is_empty()
+    }
+
     /// Try to receive a from any of the receivers of this [`ScriptThreadReceivers`] or the given
     /// [`TaskQueue`]. Return `None` if no messages are ready to be received.
     pub(crate) fn try_recv(
@@ -12,7 +12,7 @@ use base::cross_process_instant::CrossProcessInstant;
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use constellation_traits::LoadData;
 use crossbeam_channel::Sender;
-use embedder_traits::{Theme, ViewportDetails};
+use embedder_traits::{Theme, UserAgentStylesheetOverride, ViewportDetails};
 use http::header;
 use net_traits::request::{
     CredentialsMode, InsecureRequestsPolicy, RedirectMode, RequestBuilder, RequestMode,
@@ -162,6 +162,13 @@ pub(crate) struct InProgressLoad {
     /// The [`Theme`] to use for this page, once it loads.
     #[no_trace]
     pub(crate) theme: Theme,
+    /// The stylesheet that the embedder applies at the user agent origin of this page,
+    /// once it loads.
+    #[no_trace]
+    pub(crate) user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+    /// The stylesheets that the embedder applies at the user origin of this page, once it
+    /// loads.
+    pub(crate) user_stylesheets: Vec<String>,
 }
 
 impl InProgressLoad {
@@ -175,6 +182,8 @@ impl InProgressLoad {
         opener: Option<BrowsingContextId>,
         viewport_details: ViewportDetails,
         theme: Theme,
+        user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+        user_stylesheets: Vec<String>,
         origin: MutableOrigin,
         load_data: LoadData,
     ) -> InProgressLoad {
@@ -194,6 +203,8 @@ impl InProgressLoad {
             load_data,
             url_list: vec![url],
             theme,
+            user_agent_stylesheet_override,
+            user_stylesheets,
         }
     }
 
@@ -219,6 +230,7 @@ impl InProgressLoad {
                 .unwrap_or(InsecureRequestsPolicy::DoNotUpgrade),
         )
         .has_trustworthy_ancestor_origin(self.load_data.has_trustworthy_ancestor_origin)
+        .top_level_origin(self.load_data.top_level_origin.clone())
         .headers(self.load_data.headers.clone())
         .body(self.load_data.data.clone())
         .redirect_mode(RedirectMode::Manual)
@@ -908,7 +908,6 @@ impl ModuleTree {
                 // Step 8.
 
                 let visited_urls = self.visited_urls.borrow().clone();
-                let options = options.descendant_fetch_options();
 
                 for url in urls_to_fetch {
                     // https://html.spec.whatwg.org/multipage/#internal-module-script-graph-fetching-procedure
@@ -916,12 +915,13 @@ impl ModuleTree {
                     assert!(self.visited_urls.borrow().contains(&url));
 
                     // Step 2.
+                    let descendant_options = options.descendant_fetch_options(&url, &global);
                     fetch_single_module_script(
                         owner.clone(),
                         url,
                         visited_urls.clone(),
                         destination,
-                        options.clone(),
+                        descendant_options,
                         Some(parent_identity.clone()),
                         false,
                         None,
@@ -1475,10 +1475,13 @@ impl ScriptFetchOptions {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#descendant-script-fetch-options>
-    fn descendant_fetch_options(&self) -> ScriptFetchOptions {
+    fn descendant_fetch_options(&self, url: &ServoUrl, global: &GlobalScope) -> ScriptFetchOptions {
         Self {
             referrer: self.referrer.clone(),
-            integrity_metadata: String::new(),
+            // Step 2. Let integrity be the result of resolving a module integrity metadata
+            // with url and settingsObject.
+            // Step 3. Set newOptions's integrity metadata to integrity.
+            integrity_metadata: resolve_a_module_integrity_metadata(url, global),
             cryptographic_nonce: self.cryptographic_nonce.clone(),
             credentials_mode: self.credentials_mode,
             parser_metadata: self.parser_metadata,
@@ -1516,9 +1519,6 @@ fn fetch_an_import_module_script_graph(
     };
     let mut options = ScriptFetchOptions::default_classic_script(global);
     let module_data = unsafe { module_script_from_reference_private(&reference_private) };
-    if let Some(data) = module_data {
-        options = data.options.descendant_fetch_options();
-    }
     let url = ModuleTree::resolve_module_specifier(global, module_data, specifier, can_gc);
 
     // Step 2.
@@ -1526,6 +1526,11 @@ fn fetch_an_import_module_script_graph(
         let specifier_error = gen_type_error(global, "Wrong module specifier".to_owned(), can_gc);
         return Err(specifier_error);
     }
+    if let Some(data) = module_data {
+        options = data
+            .options
+            .descendant_fetch_options(url.as_ref().unwrap(), global);
+    }
 
     let dynamic_module_id = DynamicModuleId(Uuid::new_v4());
 
@@ -1866,6 +1871,7 @@ fn fetch_single_module_script(
                 request,
                 network_listener.into_callback(),
             );
+            document.update_load_progress();
         },
         None => global.fetch_with_network_listener(request, network_listener),
     }
@@ -2389,6 +2395,23 @@ fn sort_and_normalize_scopes(
     Ok(normalized)
 }
 
+/// <https://html.spec.whatwg.org/multipage/#resolving-a-module-integrity-metadata>
+pub(crate) fn resolve_a_module_integrity_metadata(url: &ServoUrl, global: &GlobalScope) -> String {
+    // Step 1. Let map be settingsObject's global object's import map.
+    // Only Window global objects have an import map; others use an empty one.
+    if !global.is::<Window>() {
+        return String::new();
+    }
+    // Step 2. If map's integrity[url] does not exist, then return the empty string.
+    // Step 3. Return map's integrity[url].
+    global
+        .import_map()
+        .integrity
+        .get(url)
+        .cloned()
+        .unwrap_or_default()
+}
+
 /// <https://html.spec.whatwg.org/multipage/#normalizing-a-module-integrity-map>
 fn normalize_module_integrity_map(
     global: &GlobalScope,
@@ -33,13 +33,16 @@ use background_hang_monitor_api::{
     MonitoredComponentType,
 };
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::{BrowsingContextId, HistoryStateId, PipelineId, PipelineNamespace, WebViewId};
+use base::id::{
+    BrowsingContextId, HistoryStateId, OffscreenCanvasId, PipelineId, PipelineNamespace, WebViewId,
+};
 use canvas_traits::webgl::WebGLPipeline;
 use chrono::{DateTime, Local};
 use compositing_traits::{CompositorMsg, CrossProcessCompositorApi, PipelineExitSource};
 use constellation_traits::{
-    JsEvalResult, LoadData, LoadOrigin, NavigationHistoryBehavior, ScriptToConstellationChan,
-    ScriptToConstellationMessage, StructuredSerializedData, WindowSizeType,
+    FrameClock, JsEvalResult, LoadData, LoadOrigin, NavigationHistoryBehavior,
+    ScriptThreadActivity, ScriptToConstellationChan, ScriptToConstellationMessage,
+    SequentialFocusDirection, StructuredSerializedData, WindowSizeType,
 };
 use crossbeam_channel::unbounded;
 use data_url::mime::Mime;
@@ -49,22 +52,24 @@ use devtools_traits::{
 };
 use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    EmbedderMsg, FocusSequenceNumber, InputEvent, JavaScriptEvaluationError,
+    EmbedderMsg, FindInPageRequest, FocusSequenceNumber, InputEvent, JavaScriptEvaluationError,
     JavaScriptEvaluationId, MediaSessionActionType, MouseButton, MouseButtonAction,
-    MouseButtonEvent, Theme, ViewportDetails, WebDriverScriptCommand,
+    MouseButtonEvent, PermissionFeature, Theme, UserAgentStylesheetOverride, ViewportDetails,
+    WebDriverScriptCommand,
 };
 use euclid::Point2D;
-use euclid::default::Rect;
+use euclid::default::{Rect, Size2D};
 use fonts::{FontContext, SystemFontServiceProxy};
 use headers::{HeaderMapExt, LastModified, ReferrerPolicy as ReferrerPolicyHeader};
 use html5ever::{local_name, ns};
 use http::header::REFRESH;
 use hyper_serde::Serde;
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::{
-    JS_AddInterruptCallback, JSContext as UnsafeJSContext, JSTracer, SetWindowProxyClass,
+    GCReason, JS_AddInterruptCallback, JS_GC, JSContext as UnsafeJSContext, JSTracer,
+    SetWindowProxyClass,
 };
 use js::jsval::UndefinedValue;
 use js::rust::ParentRuntime;
@@ -87,7 +92,7 @@ use script_traits::{
     ConstellationInputEvent, DiscardBrowsingContext, DocumentActivity, InitialScriptState,
     NewLayoutInfo, Painter, ProgressiveWebMetricType, ScriptThreadMessage, UpdatePipelineIdReason,
 };
-use servo_config::opts;
+use servo_config::{opts, prefs};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::thread_state::{self, ThreadState};
 use stylo_atoms::Atom;
@@ -95,8 +100,8 @@ use timers::{TimerEventRequest, TimerId, TimerScheduler};
 use url::Position;
 #[cfg(feature = "webgpu")]
 use webgpu_traits::{WebGPUDevice, WebGPUMsg};
-use webrender_api::ExternalScrollId;
-use webrender_api::units::{DevicePixel, LayoutVector2D};
+use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutVector2D};
+use webrender_api::{ExternalScrollId, ImageKey};
 
 use crate::document_collection::DocumentCollection;
 use crate::document_loader::DocumentLoader;
@@ -122,8 +127,10 @@ use crate::dom::csp::{CspReporting, GlobalCspReporting, Violation};
 use crate::dom::customelementregistry::{
     CallbackReaction, CustomElementDefinition, CustomElementReactionStack,
 };
+use crate::dom::dedicatedworkerglobalscope::running_dedicated_workers;
 use crate::dom::document::{
     Document, DocumentSource, FocusInitiator, HasBrowsingContext, IsHTMLDocument, TouchEventResult,
+    throttled_animation_frame_interval,
 };
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
@@ -140,6 +147,8 @@ use crate::dom::windowproxy::{CreatorBrowsingContextInfo, WindowProxy};
 use crate::dom::worklet::WorkletThreadPool;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
 use crate::fetch::FetchCanceller;
+use crate::focus_navigation;
+use crate::idle_gc_scheduler::IdleGarbageCollectionScheduler;
 use crate::messaging::{
     CommonScriptMsg, MainThreadScriptMsg, MixedMessage, ScriptEventLoopSender,
     ScriptThreadReceivers, ScriptThreadSenders,
@@ -200,6 +209,14 @@ type NodeIdSet = HashSet<String>;
 pub struct ScriptThread {
     /// <https://html.spec.whatwg.org/multipage/#last-render-opportunity-time>
     last_render_opportunity_time: DomRefCell<Option<Instant>>,
+    /// Schedules garbage collection work in the idle time between the frames that the
+    /// compositor asks for.
+    #[no_trace]
+    idle_gc_scheduler: IdleGarbageCollectionScheduler,
+    /// The time at which this thread last woke up to handle messages and run tasks, as
+    /// reported on the servo:services page.
+    #[no_trace]
+    last_wake_up: Cell<Instant>,
     /// The documents for pipelines managed by this thread
     documents: DomRefCell<DocumentCollection>,
     /// The window proxies known by this thread
@@ -348,6 +365,10 @@ pub struct ScriptThread {
     /// itself is managing animations the the timer fired triggering a [`ScriptThread`]-based
     /// animation tick.
     has_pending_animation_tick: Arc<AtomicBool>,
+
+    /// The [`FrameClock`] of the pending animation tick, if it was sent by the renderer.
+    #[no_trace]
+    pending_frame_clock: Cell<Option<FrameClock>>,
 }
 
 struct BHMExitSignal {
@@ -425,6 +446,8 @@ impl ScriptThreadFactory for ScriptThread {
                     state.opener,
                     state.viewport_details,
                     state.theme,
+                    state.user_agent_stylesheet_override.clone(),
+                    state.user_stylesheets.clone(),
                     MutableOrigin::new(load_data.url.origin()),
                     load_data,
                 );
@@ -601,6 +624,16 @@ impl ScriptThread {
             .store(true, Ordering::Relaxed);
     }
 
+    /// Whether an animation tick or input events are waiting for the next rendering update,
+    /// in which case background tasks should not delay it.
+    fn has_pending_rendering_critical_work(&self) -> bool {
+        self.has_pending_animation_tick.load(Ordering::Relaxed) ||
+            self.documents
+                .borrow()
+                .iter()
+                .any(|(_, document)| document.has_pending_input_events())
+    }
+
     /// Step 13 of <https://html.spec.whatwg.org/multipage/#navigate>
     pub(crate) fn navigate(
         pipeline_id: PipelineId,
@@ -693,6 +726,8 @@ impl ScriptThread {
         with_script_thread(|script_thread| script_thread.is_user_interacting.get())
     }
 
+    /// The ids of the documents whose tasks can run, which are the fully active ones that
+    /// are not frozen.
     pub(crate) fn get_fully_active_document_ids() -> HashSet<PipelineId> {
         with_script_thread(|script_thread| {
             script_thread
@@ -700,7 +735,7 @@ impl ScriptThread {
                 .borrow()
                 .iter()
                 .filter_map(|(id, document)| {
-                    if document.is_fully_active() {
+                    if document.is_fully_active() && !document.is_frozen() {
                         Some(id)
                     } else {
                         None
@@ -931,6 +966,8 @@ impl ScriptThread {
         ScriptThread {
             documents: DomRefCell::new(DocumentCollection::default()),
             last_render_opportunity_time: Default::default(),
+            idle_gc_scheduler: Default::default(),
+            last_wake_up: Cell::new(Instant::now()),
             window_proxies: DomRefCell::new(HashMapTracedValues::new()),
             incomplete_loads: DomRefCell::new(vec![]),
             incomplete_parser_contexts: IncompleteParserContexts(RefCell::new(vec![])),
@@ -973,6 +1010,7 @@ impl ScriptThread {
             relative_mouse_down_point: Cell::new(Point2D::zero()),
             scheduled_script_thread_animation_timer: Default::default(),
             has_pending_animation_tick: Arc::new(AtomicBool::new(false)),
+            pending_frame_clock: Default::default(),
         }
     }
 
@@ -1107,6 +1145,9 @@ impl ScriptThread {
                     self.topmost_mouse_over_target.take();
                     document.handle_mouse_leave_event(&event, can_gc);
                 },
+                InputEvent::ExternalDrag(ref external_drag_event) => {
+                    document.handle_external_drag_event(external_drag_event, &event, can_gc);
+                },
                 InputEvent::Touch(touch_event) => {
                     let touch_result =
                         document.handle_touch_event(touch_event, event.hit_test_result, can_gc);
@@ -1195,6 +1236,21 @@ impl ScriptThread {
             return;
         }
 
+        // Animation frame callbacks get the time at which the renderer started the frame
+        // being produced, so that animations stay in step with the frames that are actually
+        // presented. ScriptThread-based animation ticks use the current time instead.
+        let frame_clock = self.pending_frame_clock.take();
+        let frame_timestamp = frame_clock
+            .map(|frame_clock| frame_clock.frame_start)
+            .unwrap_or_else(CrossProcessInstant::now);
+
+        if is_animation_tick {
+            self.idle_gc_scheduler.frame_started(
+                Instant::now(),
+                frame_clock.map(|frame_clock| frame_clock.refresh_interval),
+            );
+        }
+
         let any_animations_running = self.documents.borrow().iter().any(|(_, document)| {
             document.is_fully_active() && document.animations().running_animation_count() != 0
         });
@@ -1246,6 +1302,8 @@ impl ScriptThread {
                 continue;
             }
 
+            document.window().set_updating_the_rendering(true);
+
             // TODO(#31581): The steps in the "Revealing the document" section need to be implemented
             // `process_pending_input_events` handles the focusing steps as well as other events
             // from the compositor.
@@ -1283,8 +1341,8 @@ impl ScriptThread {
             // > 14. For each doc of docs, run the animation frame callbacks for doc, passing
             // > in the relative high resolution time given frameTimestamp and doc's
             // > relevant global object as the timestamp.
-            if is_animation_tick {
-                document.run_the_animation_frame_callbacks(can_gc);
+            if is_animation_tick && document.animation_frame_callbacks_due(frame_timestamp) {
+                document.run_the_animation_frame_callbacks(frame_timestamp, can_gc);
             }
 
             // Run the resize observer steps.
@@ -1316,6 +1374,7 @@ impl ScriptThread {
             // > Step 22: For each doc of docs, update the rendering or user interface of
             // > doc and its node navigable to reflect the current state.
             saw_any_reflows = document.update_the_rendering(can_gc) || saw_any_reflows;
+            document.window().set_updating_the_rendering(false);
 
             // TODO: Process top layer removals according to
             // https://drafts.csswg.org/css-position-4/#process-top-layer-removals.
@@ -1334,8 +1393,10 @@ impl ScriptThread {
         // animation update in the case that the compositor might not do it due to
         // not receiving any display lists.
         if is_animation_tick {
+            self.idle_gc_scheduler.frame_finished(Instant::now());
             self.schedule_script_thread_animation_tick_if_necessary(saw_any_reflows);
         }
+        self.schedule_throttled_animation_tick_if_necessary();
     }
 
     // If there are any pending reflows and we are not having rendering opportunities
@@ -1393,12 +1454,7 @@ impl ScriptThread {
 
         // Always schedule a ScriptThread-based animation tick, unless none of the
         // documents are active and have animations running and/or rAF callbacks.
-        if !self.documents.borrow().iter().any(|(_, document)| {
-            document.is_fully_active() &&
-                !document.window().throttled() &&
-                (document.animations().running_animation_count() != 0 ||
-                    document.has_active_request_animation_frame_callbacks())
-        }) {
+        if !self.any_documents_animating() {
             return;
         }
 
@@ -1410,12 +1466,47 @@ impl ScriptThread {
         const SCRIPT_THREAD_ANIMATION_TICK_DELAY: u64 = 30;
 
         debug!("Scheduling ScriptThread animation frame.");
+        self.schedule_script_thread_animation_tick(Duration::from_millis(
+            SCRIPT_THREAD_ANIMATION_TICK_DELAY,
+        ));
+    }
+
+    /// The renderer does not send animation ticks for throttled pipelines, which are not
+    /// visible. If any of their documents have requestAnimationFrame callbacks, schedule a
+    /// ScriptThread-based animation tick to run them at the throttled animation frame rate,
+    /// unless another animation tick is already scheduled.
+    fn schedule_throttled_animation_tick_if_necessary(&self) {
+        if self
+            .scheduled_script_thread_animation_timer
+            .borrow()
+            .is_some()
+        {
+            return;
+        }
+
+        let Some(interval) = throttled_animation_frame_interval() else {
+            return;
+        };
+
+        if !self.documents.borrow().iter().any(|(_, document)| {
+            document.is_fully_active() &&
+                document.window().throttled() &&
+                document.has_active_request_animation_frame_callbacks()
+        }) {
+            return;
+        }
+
+        debug!("Scheduling ScriptThread animation frame for throttled documents.");
+        self.schedule_script_thread_animation_tick(interval);
+    }
+
+    fn schedule_script_thread_animation_tick(&self, duration: Duration) {
         let trigger_script_thread_animation = self.has_pending_animation_tick.clone();
         let timer_id = self.schedule_timer(TimerEventRequest {
             callback: Box::new(move || {
                 trigger_script_thread_animation.store(true, Ordering::Relaxed);
             }),
-            duration: Duration::from_millis(SCRIPT_THREAD_ANIMATION_TICK_DELAY),
+            duration,
         });
 
         let mut scheduled_script_thread_animation_timer =
@@ -1440,6 +1531,7 @@ impl ScriptThread {
         let mut event = self
             .receivers
             .recv(&self.task_queue, &self.timer_scheduler.borrow());
+        self.last_wake_up.set(Instant::now());
 
         loop {
             debug!("Handling event: {event:?}");
@@ -1510,7 +1602,9 @@ impl ScriptThread {
                     }),
                 MixedMessage::FromConstellation(ScriptThreadMessage::TickAllAnimations(
                     _webviews,
+                    frame_clock,
                 )) => {
+                    self.pending_frame_clock.set(Some(frame_clock));
                     self.set_has_pending_animation_tick();
                 },
                 MixedMessage::FromConstellation(ScriptThreadMessage::SendInputEvent(id, event)) => {
@@ -1548,9 +1642,30 @@ impl ScriptThread {
             }
         }
 
+        // Input events and animation ticks are handled when updating the rendering below, so
+        // while any are pending, tasks from background task sources are put back into the task
+        // queue instead of delaying them.
+        let defer_background_tasks = self.has_pending_rendering_critical_work();
+        let mut deferred = vec![];
+
         // Process the gathered events.
         debug!("Processing events.");
         for msg in sequential {
+            let msg = match msg {
+                MixedMessage::FromScript(
+                    inner_msg @ MainThreadScriptMsg::Common(CommonScriptMsg::Task(
+                        _,
+                        _,
+                        _,
+                        TaskSourceName::BackgroundPostedTask | TaskSourceName::PerformanceTimeline,
+                    )),
+                ) if defer_background_tasks => {
+                    deferred.push(inner_msg);
+                    continue;
+                },
+                msg => msg,
+            };
+
             debug!("Processing event {:?}.", msg);
             let category = self.categorize_msg(&msg);
             let pipeline_id = msg.pipeline_id();
@@ -1619,6 +1734,8 @@ impl ScriptThread {
             self.perform_a_microtask_checkpoint(can_gc);
         }
 
+        self.task_queue.defer_tasks(deferred);
+
         for (_, doc) in self.documents.borrow().iter() {
             let window = doc.window();
             window
@@ -1641,9 +1758,27 @@ impl ScriptThread {
         // message.
         self.update_the_rendering(can_gc);
 
+        // All pending work is done, so this is a good time to collect garbage, as long as that
+        // doesn't delay the next frame.
+        if self.receivers.is_empty(&self.task_queue) {
+            self.idle_gc_scheduler
+                .perform_idle_work(self.get_cx(), self.any_documents_animating());
+        }
+
         true
     }
 
+    /// Whether any document managed by this [`ScriptThread`] is running animations or has
+    /// `requestAnimationFrame` callbacks, so that the compositor keeps asking for frames.
+    fn any_documents_animating(&self) -> bool {
+        self.documents.borrow().iter().any(|(_, document)| {
+            document.is_fully_active() &&
+                !document.window().throttled() &&
+                (document.animations().running_animation_count() != 0 ||
+                    document.has_active_request_animation_frame_callbacks())
+        })
+    }
+
     fn categorize_msg(&self, msg: &MixedMessage) -> ScriptThreadEventCategory {
         match *msg {
             MixedMessage::FromConstellation(ref inner_msg) => match *inner_msg {
@@ -1857,6 +1992,21 @@ impl ScriptThread {
             ScriptThreadMessage::ThemeChange(_, theme) => {
                 self.handle_theme_change_msg(theme);
             },
+            ScriptThreadMessage::SetUserAgentStylesheetOverride(
+                pipeline_id,
+                stylesheet_override,
+            ) => {
+                if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+                    document
+                        .window()
+                        .handle_user_agent_stylesheet_override(stylesheet_override);
+                }
+            },
+            ScriptThreadMessage::SetUserStylesheets(pipeline_id, user_stylesheets) => {
+                if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+                    document.window().handle_user_stylesheets(user_stylesheets);
+                }
+            },
             ScriptThreadMessage::GetTitle(pipeline_id) => self.handle_get_title_msg(pipeline_id),
             ScriptThreadMessage::SetDocumentActivity(pipeline_id, activity) => {
                 self.handle_set_document_activity_msg(pipeline_id, activity, can_gc)
@@ -1864,6 +2014,19 @@ impl ScriptThread {
             ScriptThreadMessage::SetThrottled(pipeline_id, throttled) => {
                 self.handle_set_throttled_msg(pipeline_id, throttled)
             },
+            ScriptThreadMessage::SetTimersIntensivelyThrottled(pipeline_id, throttled) => {
+                if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+                    window.set_timers_intensively_throttled(throttled);
+                }
+            },
+            ScriptThreadMessage::SetFrozen(pipeline_id, frozen) => {
+                let document = self.documents.borrow().find_document(pipeline_id);
+                match document {
+                    Some(document) if frozen => document.freeze(can_gc),
+                    Some(document) => document.resume_from_freeze(),
+                    None => warn!("SetFrozen sent to nonexistent pipeline"),
+                }
+            },
             ScriptThreadMessage::SetThrottledInContainingIframe(
                 parent_pipeline_id,
                 browsing_context_id,
@@ -1914,6 +2077,24 @@ impl ScriptThread {
             ScriptThreadMessage::FocusDocument(pipeline_id, sequence) => {
                 self.handle_focus_document_msg(pipeline_id, sequence, can_gc)
             },
+            ScriptThreadMessage::SetFullscreenStateOfChild(
+                pipeline_id,
+                browsing_context_id,
+                fullscreen,
+            ) => self.handle_set_fullscreen_state_of_child_msg(
+                pipeline_id,
+                browsing_context_id,
+                fullscreen,
+                can_gc,
+            ),
+            ScriptThreadMessage::MoveSequentialFocus(pipeline_id, direction, starting_child) => {
+                self.handle_move_sequential_focus_msg(
+                    pipeline_id,
+                    direction,
+                    starting_child,
+                    can_gc,
+                )
+            },
             ScriptThreadMessage::Unfocus(pipeline_id, sequence) => {
                 self.handle_unfocus_msg(pipeline_id, sequence, can_gc)
             },
@@ -1965,6 +2146,27 @@ impl ScriptThread {
             ScriptThreadMessage::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action, can_gc)
             },
+            ScriptThreadMessage::ExitPictureInPicture(pipeline_id) => {
+                self.handle_exit_picture_in_picture(pipeline_id, can_gc)
+            },
+            ScriptThreadMessage::FindInPage(pipeline_id, request) => {
+                self.handle_find_in_page(pipeline_id, request, can_gc)
+            },
+            ScriptThreadMessage::PictureInPictureWindowResized(pipeline_id, size) => {
+                self.handle_picture_in_picture_window_resized(pipeline_id, size, can_gc)
+            },
+            ScriptThreadMessage::UpdatePlaceholderCanvas(
+                pipeline_id,
+                canvas_id,
+                image_key,
+                size,
+            ) => self.handle_update_placeholder_canvas(
+                pipeline_id,
+                canvas_id,
+                image_key,
+                size,
+                can_gc,
+            ),
             #[cfg(feature = "webgpu")]
             ScriptThreadMessage::SetWebGPUPort(port) => {
                 *self.receivers.webgpu_receiver.borrow_mut() =
@@ -1982,6 +2184,11 @@ impl ScriptThread {
             ScriptThreadMessage::SetScrollStates(pipeline_id, scroll_states) => {
                 self.handle_set_scroll_states(pipeline_id, scroll_states)
             },
+            ScriptThreadMessage::IntersectionObservationsChanged(pipeline_id, changes) => {
+                if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+                    document.handle_renderer_intersection_observation_changes(changes, can_gc);
+                }
+            },
             ScriptThreadMessage::EvaluateJavaScript(pipeline_id, evaluation_id, script) => {
                 self.handle_evaluate_javascript(pipeline_id, evaluation_id, script, can_gc);
             },
@@ -1997,6 +2204,48 @@ impl ScriptThread {
                     );
                 }
             },
+            ScriptThreadMessage::CollectGarbage(sender) => self.handle_collect_garbage(sender),
+            ScriptThreadMessage::PreferencesChanged(values) => prefs::set_values(&values),
+            ScriptThreadMessage::PermissionDecisionChanged(origin, feature) => {
+                self.handle_permission_decision_changed(origin, feature)
+            },
+            ScriptThreadMessage::ReportActivity(sender) => self.handle_report_activity(sender),
+        }
+    }
+
+    fn handle_permission_decision_changed(
+        &self,
+        origin: ImmutableOrigin,
+        feature: PermissionFeature,
+    ) {
+        for (_, document) in self.documents.borrow().iter() {
+            if document.origin().immutable() == &origin {
+                document
+                    .window()
+                    .as_global_scope()
+                    .permission_decision_changed(feature);
+            }
+        }
+    }
+
+    fn handle_report_activity(&self, sender: IpcSender<ScriptThreadActivity>) {
+        let activity = ScriptThreadActivity {
+            pid: std::process::id(),
+            queued_messages: self.receivers.constellation_receiver.len(),
+            queued_tasks: self.task_queue.pending_task_count(),
+            time_since_last_wake_up: self.last_wake_up.get().elapsed(),
+            running_dedicated_workers: running_dedicated_workers(),
+        };
+        let _ = sender.send(activity);
+    }
+
+    #[allow(unsafe_code)]
+    fn handle_collect_garbage(&self, sender: IpcSender<()>) {
+        unsafe {
+            JS_GC(*self.get_cx(), GCReason::API);
+        }
+        if sender.send(()).is_err() {
+            warn!("Could not notify the constellation that garbage was collected");
         }
     }
 
@@ -2045,6 +2294,7 @@ impl ScriptThread {
             WebGPUMsg::FreeSampler(id) => self.gpu_id_hub.free_sampler_id(id),
             WebGPUMsg::FreeShaderModule(id) => self.gpu_id_hub.free_shader_module_id(id),
             WebGPUMsg::FreeRenderBundle(id) => self.gpu_id_hub.free_render_bundle_id(id),
+            WebGPUMsg::FreeQuerySet(id) => self.gpu_id_hub.free_query_set_id(id),
             WebGPUMsg::FreeRenderPipeline(id) => self.gpu_id_hub.free_render_pipeline_id(id),
             WebGPUMsg::FreeTexture(id) => self.gpu_id_hub.free_texture_id(id),
             WebGPUMsg::FreeTextureView(id) => self.gpu_id_hub.free_texture_view_id(id),
@@ -2184,6 +2434,12 @@ impl ScriptThread {
             DevtoolScriptControlMsg::HighlightDomNode(id, node_id) => {
                 devtools::handle_highlight_dom_node(&documents, id, node_id)
             },
+            DevtoolScriptControlMsg::StartCoverage(id) => {
+                devtools::handle_start_coverage(&documents, id)
+            },
+            DevtoolScriptControlMsg::TakeCoverage(id, reply) => {
+                devtools::handle_take_coverage(&documents, id, reply)
+            },
         }
     }
 
@@ -2441,6 +2697,15 @@ impl ScriptThread {
             WebDriverScriptCommand::GetElementRect(node_id, reply) => {
                 webdriver_handlers::handle_get_rect(&documents, pipeline_id, node_id, reply, can_gc)
             },
+            WebDriverScriptCommand::GetMaskedRects(selectors, reply) => {
+                webdriver_handlers::handle_get_masked_rects(
+                    &documents,
+                    pipeline_id,
+                    selectors,
+                    reply,
+                    can_gc,
+                )
+            },
             WebDriverScriptCommand::GetBoundingClientRect(node_id, reply) => {
                 webdriver_handlers::handle_get_bounding_client_rect(
                     &documents,
@@ -2485,6 +2750,12 @@ impl ScriptThread {
             WebDriverScriptCommand::GetTitle(reply) => {
                 webdriver_handlers::handle_get_title(&documents, pipeline_id, reply)
             },
+            WebDriverScriptCommand::StartCoverage(reply) => {
+                webdriver_handlers::handle_start_coverage(&documents, pipeline_id, reply)
+            },
+            WebDriverScriptCommand::TakeCoverage(reply) => {
+                webdriver_handlers::handle_take_coverage(&documents, pipeline_id, reply)
+            },
             WebDriverScriptCommand::WillSendKeys(
                 element_id,
                 text,
@@ -2535,11 +2806,35 @@ impl ScriptThread {
     fn handle_exit_fullscreen(&self, id: PipelineId, can_gc: CanGc) {
         let document = self.documents.borrow().find_document(id);
         if let Some(document) = document {
+            // Documents that aren't fullscreen have nothing to exit.
+            if document.GetFullscreenElement().is_none() {
+                return;
+            }
             let _ac = enter_realm(&*document);
             document.exit_fullscreen(can_gc);
         }
     }
 
+    fn handle_set_fullscreen_state_of_child_msg(
+        &self,
+        pipeline_id: PipelineId,
+        browsing_context_id: BrowsingContextId,
+        fullscreen: bool,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!("Fullscreen state of a child of closed pipeline {pipeline_id}.");
+        };
+        let container = {
+            let iframes = document.iframes();
+            let Some(iframe) = iframes.get(browsing_context_id) else {
+                return;
+            };
+            DomRoot::from_ref(iframe.element.upcast::<Element>())
+        };
+        document.set_fullscreen_state_of_container(&container, fullscreen, can_gc);
+    }
+
     fn handle_viewport(&self, id: PipelineId, rect: Rect<f32>) {
         let document = self.documents.borrow().find_document(id);
         if let Some(document) = document {
@@ -2563,6 +2858,8 @@ impl ScriptThread {
             load_data,
             viewport_details,
             theme,
+            user_agent_stylesheet_override,
+            user_stylesheets,
         } = new_layout_info;
 
         // Kick off the fetch for the new resource.
@@ -2575,6 +2872,8 @@ impl ScriptThread {
             opener,
             viewport_details,
             theme,
+            user_agent_stylesheet_override,
+            user_stylesheets,
             origin,
             load_data,
         );
@@ -2598,6 +2897,7 @@ impl ScriptThread {
                     .window()
                     .layout()
                     .collect_reports(&mut reports, ops);
+                reports.push(document.dom_tree_memory_report(ops));
             }
 
             let prefix = format!("url({urls})");
@@ -2739,6 +3039,37 @@ impl ScriptThread {
         }
     }
 
+    fn handle_move_sequential_focus_msg(
+        &self,
+        pipeline_id: PipelineId,
+        direction: SequentialFocusDirection,
+        starting_child: Option<BrowsingContextId>,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!("Sequential focus navigation in closed pipeline {pipeline_id}.");
+        };
+
+        // Navigation continues after or before the container of the nested browsing
+        // context it came out of, if any, and otherwise at the start or end of the document.
+        let starting_point = match starting_child {
+            Some(browsing_context_id) => {
+                let iframes = document.iframes();
+                let Some(iframe) = iframes.get(browsing_context_id) else {
+                    return;
+                };
+                Some(DomRoot::from_ref(iframe.element.upcast::<Element>()))
+            },
+            None => None,
+        };
+        focus_navigation::move_sequential_focus(
+            &document,
+            direction,
+            starting_point.as_deref(),
+            can_gc,
+        );
+    }
+
     fn handle_unfocus_msg(
         &self,
         pipeline_id: PipelineId,
@@ -3334,6 +3665,11 @@ impl ScriptThread {
             compositor_api: self.compositor_api.clone(),
             viewport_details: incomplete.viewport_details,
             theme: incomplete.theme,
+            user_agent_stylesheet_override: incomplete.user_agent_stylesheet_override.clone(),
+            user_stylesheets: incomplete.user_stylesheets.clone(),
+            content_blocking_stylesheet: self
+                .resource_threads
+                .content_blocking_stylesheet(final_url.clone()),
         };
 
         // Create the window and document objects.
@@ -3378,6 +3714,8 @@ impl ScriptThread {
             self.gpu_id_hub.clone(),
             incomplete.load_data.inherited_secure_context,
             incomplete.theme,
+            incomplete.user_agent_stylesheet_override.clone(),
+            incomplete.user_stylesheets.clone(),
         );
 
         let _realm = enter_realm(&*window);
@@ -3462,6 +3800,8 @@ impl ScriptThread {
             .and_then(|h| h.typed_get::<ReferrerPolicyHeader>())
             .into();
         document.set_referrer_policy(referrer_policy);
+        document.set_top_level_origin(incomplete.load_data.top_level_origin.clone());
+        document.set_ancestor_origins(incomplete.load_data.ancestor_origins.clone());
 
         let refresh_header = metadata.headers.as_deref().and_then(|h| h.get(REFRESH));
         if let Some(refresh_val) = refresh_header {
@@ -3729,8 +4069,9 @@ impl ScriptThread {
             FetchResponseMsg::ProcessCspViolations(request_id, violations) => {
                 self.handle_csp_violations(pipeline_id, request_id, violations)
             },
-            FetchResponseMsg::ProcessRequestBody(..) | FetchResponseMsg::ProcessRequestEOF(..) => {
-            },
+            FetchResponseMsg::ProcessRequestBody(..) |
+            FetchResponseMsg::ProcessRequestBodyChunkLength(..) |
+            FetchResponseMsg::ProcessRequestEOF(..) => {},
         }
     }
 
@@ -3971,6 +4312,65 @@ impl ScriptThread {
         };
     }
 
+    /// The embedder closed the picture-in-picture window of a video in the given pipeline.
+    fn handle_exit_picture_in_picture(&self, pipeline_id: PipelineId, can_gc: CanGc) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!("Exit picture-in-picture of closed pipeline {}", pipeline_id);
+        };
+        document.exit_picture_in_picture(false, can_gc);
+    }
+
+    fn handle_find_in_page(
+        &self,
+        pipeline_id: PipelineId,
+        request: Option<FindInPageRequest>,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!("Find in page of closed pipeline {}", pipeline_id);
+        };
+        let Some(request) = request else {
+            document.stop_finding_in_page();
+            return;
+        };
+        let result = document.find_in_page(request, can_gc);
+        document.send_to_embedder(EmbedderMsg::FindInPageResult(document.webview_id(), result));
+    }
+
+    fn handle_picture_in_picture_window_resized(
+        &self,
+        pipeline_id: PipelineId,
+        size: DeviceIntSize,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!(
+                "Resize picture-in-picture of closed pipeline {}",
+                pipeline_id
+            );
+        };
+        document.picture_in_picture_window_resized(size, can_gc);
+    }
+
+    fn handle_update_placeholder_canvas(
+        &self,
+        pipeline_id: PipelineId,
+        canvas_id: OffscreenCanvasId,
+        image_key: Option<ImageKey>,
+        size: Size2D<u32>,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return debug!(
+                "Update placeholder canvas of closed pipeline {}",
+                pipeline_id
+            );
+        };
+        if let Some(canvas) = document.placeholder_canvas(canvas_id) {
+            canvas.update_placeholder(image_key, size, can_gc);
+        }
+    }
+
     pub(crate) fn enqueue_microtask(job: Microtask) {
         with_script_thread(|script_thread| {
             script_thread
@@ -26,6 +26,7 @@ use crate::dom::csppolicyviolationreport::{
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventComposed};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::report::ReportBodyData;
 use crate::dom::reportingobserver::ReportingObserver;
 use crate::dom::securitypolicyviolationevent::SecurityPolicyViolationEvent;
 use crate::dom::types::GlobalScope;
@@ -165,7 +166,7 @@ impl TaskOnce for CSPViolationReportTask {
             .find(|directive| directive.name == "report-to")
         {
             // Step 3.5.1. Let body be a new CSPViolationReportBody, initialized as follows:
-            let body = self.violation_report.clone().convert();
+            let body = ReportBodyData::CspViolation(self.violation_report.clone());
             // Step 3.5.2. Let settings object be violation’s global object’s relevant settings object.
             // Step 3.5.3. Generate and queue a report with the following arguments:
             ReportingObserver::generate_and_queue_a_report(
@@ -20,7 +20,7 @@ use constellation_traits::{
 use crossbeam_channel::{Receiver, RecvError, Sender, select, unbounded};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
-use net_traits::{CoreResourceMsg, CustomResponseMediator};
+use net_traits::{CoreResourceMsg, CustomResponseMediator, ServiceWorkerResponse};
 use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 
@@ -78,35 +78,39 @@ impl ServiceWorker {
 }
 
 /// When updating a registration, which worker are we targetting?
-#[allow(dead_code)]
 enum RegistrationUpdateTarget {
     Installing,
     Waiting,
     Active,
 }
 
-impl Drop for ServiceWorkerRegistration {
+/// The thread running the scope of a service worker.
+struct ServiceWorkerThread {
+    /// A channel to send control message to the worker,
+    /// currently only used to signal shutdown.
+    control_sender: Sender<ServiceWorkerControlMsg>,
+    /// A handle to join on the worker thread.
+    join_handle: Option<JoinHandle<()>>,
+    /// A context to request an interrupt.
+    context: ThreadSafeJSContext,
+    /// The closing flag for the worker.
+    closing: Arc<AtomicBool>,
+}
+
+impl Drop for ServiceWorkerThread {
     /// <https://html.spec.whatwg.org/multipage/#terminate-a-worker>
     fn drop(&mut self) {
         // Drop the channel to signal shutdown.
         if self
             .control_sender
-            .take()
-            .expect("No control sender to worker thread.")
             .send(ServiceWorkerControlMsg::Exit)
             .is_err()
         {
             warn!("Failed to send exit message to service worker scope.");
         }
 
-        self.closing
-            .take()
-            .expect("No close flag for worker")
-            .store(true, Ordering::SeqCst);
-        self.context
-            .take()
-            .expect("No context to request interrupt.")
-            .request_interrupt_callback();
+        self.closing.store(true, Ordering::SeqCst);
+        self.context.request_interrupt_callback();
 
         // TODO: Step 1, 2 and 3.
         if self
@@ -131,15 +135,9 @@ struct ServiceWorkerRegistration {
     waiting_worker: Option<ServiceWorker>,
     /// <https://w3c.github.io/ServiceWorker/#dfn-installing-worker>
     installing_worker: Option<ServiceWorker>,
-    /// A channel to send control message to the worker,
-    /// currently only used to signal shutdown.
-    control_sender: Option<Sender<ServiceWorkerControlMsg>>,
-    /// A handle to join on the worker thread.
-    join_handle: Option<JoinHandle<()>>,
-    /// A context to request an interrupt.
-    context: Option<ThreadSafeJSContext>,
-    /// The closing flag for the worker.
-    closing: Option<Arc<AtomicBool>>,
+    /// The threads of the workers of this registration, which are shut down
+    /// when the worker is replaced, or when the registration is dropped.
+    threads: HashMap<ServiceWorkerId, ServiceWorkerThread>,
 }
 
 impl ServiceWorkerRegistration {
@@ -149,42 +147,32 @@ impl ServiceWorkerRegistration {
             active_worker: None,
             waiting_worker: None,
             installing_worker: None,
-            join_handle: None,
-            control_sender: None,
-            context: None,
-            closing: None,
+            threads: HashMap::new(),
         }
     }
 
-    fn note_worker_thread(
-        &mut self,
-        join_handle: JoinHandle<()>,
-        control_sender: Sender<ServiceWorkerControlMsg>,
-        context: ThreadSafeJSContext,
-        closing: Arc<AtomicBool>,
-    ) {
-        assert!(self.join_handle.is_none());
-        self.join_handle = Some(join_handle);
-
-        assert!(self.control_sender.is_none());
-        self.control_sender = Some(control_sender);
-
-        assert!(self.context.is_none());
-        self.context = Some(context);
+    fn note_worker_thread(&mut self, worker_id: ServiceWorkerId, thread: ServiceWorkerThread) {
+        assert!(!self.threads.contains_key(&worker_id));
+        self.threads.insert(worker_id, thread);
+    }
 
-        assert!(self.closing.is_none());
-        self.closing = Some(closing);
+    /// <https://w3c.github.io/ServiceWorker/#terminate-service-worker>
+    fn terminate_worker(&mut self, worker: Option<ServiceWorker>) {
+        if let Some(worker) = worker {
+            // Signal shut-down, and join on the thread.
+            drop(self.threads.remove(&worker.id));
+        }
     }
 
     /// <https://w3c.github.io/ServiceWorker/#get-newest-worker>
     fn get_newest_worker(&self) -> Option<ServiceWorker> {
-        if let Some(worker) = self.active_worker.as_ref() {
+        if let Some(worker) = self.installing_worker.as_ref() {
             return Some(worker.clone());
         }
         if let Some(worker) = self.waiting_worker.as_ref() {
             return Some(worker.clone());
         }
-        if let Some(worker) = self.installing_worker.as_ref() {
+        if let Some(worker) = self.active_worker.as_ref() {
             return Some(worker.clone());
         }
         None
@@ -194,20 +182,30 @@ impl ServiceWorkerRegistration {
     fn update_registration_state(
         &mut self,
         target: RegistrationUpdateTarget,
-        worker: ServiceWorker,
+        worker: Option<ServiceWorker>,
     ) {
         match target {
             RegistrationUpdateTarget::Active => {
-                self.active_worker = Some(worker);
+                self.active_worker = worker;
             },
             RegistrationUpdateTarget::Waiting => {
-                self.waiting_worker = Some(worker);
+                self.waiting_worker = worker;
             },
             RegistrationUpdateTarget::Installing => {
-                self.installing_worker = Some(worker);
+                self.installing_worker = worker;
             },
         }
     }
+
+    /// The value a job for this registration is resolved with.
+    fn job_result_value(&self) -> JobResultValue {
+        JobResultValue::Registration {
+            id: self.id,
+            installing_worker: self.installing_worker.as_ref().map(|worker| worker.id),
+            waiting_worker: self.waiting_worker.as_ref().map(|worker| worker.id),
+            active_worker: self.active_worker.as_ref().map(|worker| worker.id),
+        }
+    }
 }
 
 /// A structure managing all registrations and workers for a given origin.
@@ -280,7 +278,7 @@ impl ServiceWorkerManager {
                 }
             }
         }
-        let _ = mediator.response_chan.send(None);
+        let _ = mediator.response_chan.send(ServiceWorkerResponse::Fallback);
         true
     }
 
@@ -311,9 +309,12 @@ impl ServiceWorkerManager {
                     self.handle_update_job(job);
                 },
                 JobType::Unregister => {
-                    // TODO: https://w3c.github.io/ServiceWorker/#unregister-algorithm
+                    self.handle_unregister_job(job);
                 },
             },
+            ServiceWorkerMsg::InstallFinished(scope_url, worker_id, succeeded) => {
+                self.handle_install_finished(scope_url, worker_id, succeeded);
+            },
             ServiceWorkerMsg::Exit => return false,
         }
         true
@@ -347,26 +348,13 @@ impl ServiceWorkerManager {
             let newest_worker = registration.get_newest_worker();
 
             // step 5.2
-            if newest_worker.is_some() {
-                // TODO: the various checks of job versus worker.
-
+            // TODO: compare the update via cache mode of the job and the registration.
+            if newest_worker.is_some_and(|worker| worker.script_url == job.script_url) {
                 // Step 2.1: Run resolve job.
                 let client = job.client.clone();
-                let _ = client.send(JobResult::ResolvePromise(
-                    job,
-                    JobResultValue::Registration {
-                        id: registration.id,
-                        installing_worker: registration
-                            .installing_worker
-                            .as_ref()
-                            .map(|worker| worker.id),
-                        waiting_worker: registration
-                            .waiting_worker
-                            .as_ref()
-                            .map(|worker| worker.id),
-                        active_worker: registration.active_worker.as_ref().map(|worker| worker.id),
-                    },
-                ));
+                let value = registration.job_result_value();
+                let _ = client.send(JobResult::ResolvePromise(job, value));
+                return;
             }
         } else {
             // Step 6: we do not have a registration.
@@ -375,87 +363,176 @@ impl ServiceWorkerManager {
             let new_registration = ServiceWorkerRegistration::new();
             self.registrations
                 .insert(job.scope_url.clone(), new_registration);
-
-            // Step 7: Schedule update
-            job.job_type = JobType::Update;
-            let _ = self.own_sender.send(ServiceWorkerMsg::ScheduleJob(job));
         }
+
+        // Step 7: Schedule update
+        job.job_type = JobType::Update;
+        let _ = self.own_sender.send(ServiceWorkerMsg::ScheduleJob(job));
     }
 
     /// <https://w3c.github.io/ServiceWorker/#update>
     fn handle_update_job(&mut self, job: Job) {
         // Step 1: Get registation
-        if let Some(registration) = self.registrations.get_mut(&job.scope_url) {
-            // Step 3.
-            let newest_worker = registration.get_newest_worker();
+        let Some(registration) = self.registrations.get_mut(&job.scope_url) else {
+            // Step 2
+            let _ = job
+                .client
+                .send(JobResult::RejectPromise(JobError::TypeError));
+            return;
+        };
 
-            // Step 4.
-            if let Some(worker) = newest_worker {
-                if worker.script_url != job.script_url {
-                    let _ = job
-                        .client
-                        .send(JobResult::RejectPromise(JobError::TypeError));
-                    return;
-                }
+        // Step 3.
+        let newest_worker = registration.get_newest_worker();
+
+        // Step 4.
+        if let Some(worker) = newest_worker {
+            if worker.script_url != job.script_url {
+                let _ = job
+                    .client
+                    .send(JobResult::RejectPromise(JobError::TypeError));
+                return;
             }
+        }
 
-            let scope_things = job
-                .scope_things
-                .clone()
-                .expect("Update job should have scope things.");
-
-            // Very roughly steps 5 to 18.
-            // TODO: implement all steps precisely.
-            let (new_worker, join_handle, control_sender, context, closing) =
-                update_serviceworker(self.own_sender.clone(), job.scope_url.clone(), scope_things);
-
-            // Since we've just started the worker thread, ensure we can shut it down later.
-            registration.note_worker_thread(join_handle, control_sender, context, closing);
-
-            // Step 19, run Install.
-
-            // Install: Step 4, run Update Registration State.
-            registration
-                .update_registration_state(RegistrationUpdateTarget::Installing, new_worker);
-
-            // Install: Step 7, run Resolve Job Promise.
-            let client = job.client.clone();
-            let _ = client.send(JobResult::ResolvePromise(
-                job,
-                JobResultValue::Registration {
-                    id: registration.id,
-                    installing_worker: registration
-                        .installing_worker
-                        .as_ref()
-                        .map(|worker| worker.id),
-                    waiting_worker: registration.waiting_worker.as_ref().map(|worker| worker.id),
-                    active_worker: registration.active_worker.as_ref().map(|worker| worker.id),
-                },
-            ));
-        } else {
-            // Step 2
+        let scope_things = job
+            .scope_things
+            .clone()
+            .expect("Update job should have scope things.");
+
+        // Very roughly steps 5 to 18.
+        // TODO: implement all steps precisely, notably comparing the fetched script
+        // byte-for-byte with the one of the newest worker, and not installing a new
+        // worker when it has not changed.
+        let worker_id = ServiceWorkerId::new();
+        let (new_worker, thread) = update_serviceworker(
+            self.own_sender.clone(),
+            job.scope_url.clone(),
+            worker_id,
+            scope_things,
+        );
+
+        // Since we've just started the worker thread, ensure we can shut it down later.
+        registration.note_worker_thread(worker_id, thread);
+
+        // Step 19, run Install.
+
+        // Install: Step 4, run Update Registration State,
+        // terminating a worker that was still installing from an earlier update.
+        let previous_worker = registration.installing_worker.take();
+        registration.terminate_worker(previous_worker);
+        registration
+            .update_registration_state(RegistrationUpdateTarget::Installing, Some(new_worker));
+
+        // Install: Step 7, run Resolve Job Promise.
+        let client = job.client.clone();
+        let value = registration.job_result_value();
+        let _ = client.send(JobResult::ResolvePromise(job, value));
+
+        // The rest of Install runs in `handle_install_finished`,
+        // once the worker has dispatched its install event.
+    }
+
+    /// The steps of <https://w3c.github.io/ServiceWorker/#installation-algorithm>
+    /// that run once the install event has been dispatched in the installing worker.
+    fn handle_install_finished(
+        &mut self,
+        scope_url: ServoUrl,
+        worker_id: ServiceWorkerId,
+        succeeded: bool,
+    ) {
+        let Some(registration) = self.registrations.get_mut(&scope_url) else {
+            return;
+        };
+
+        // The worker has been replaced by a later update while installing.
+        if registration
+            .installing_worker
+            .as_ref()
+            .is_none_or(|worker| worker.id != worker_id)
+        {
+            return;
+        }
+        let installing_worker = registration.installing_worker.take();
+
+        if !succeeded {
+            // "If installFailed is true", run Update Registration State
+            // with "installing" and null, then remove the registration
+            // if it has no newest worker.
+            registration.terminate_worker(installing_worker);
+            if registration.get_newest_worker().is_none() {
+                self.registrations.remove(&scope_url);
+            }
+            return;
+        }
+
+        // "If registration's waiting worker is not null", terminate it.
+        let waiting_worker = registration.waiting_worker.take();
+        registration.terminate_worker(waiting_worker);
+
+        // Run Update Registration State with "waiting" and the installing worker,
+        // and with "installing" and null.
+        registration
+            .update_registration_state(RegistrationUpdateTarget::Waiting, installing_worker);
+
+        // Invoke Try Activate.
+        try_activate(registration);
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#unregister-algorithm>
+    fn handle_unregister_job(&mut self, job: Job) {
+        // Step 1.
+        if job.scope_url.origin() != job.referrer.origin() {
             let _ = job
                 .client
-                .send(JobResult::RejectPromise(JobError::TypeError));
+                .send(JobResult::RejectPromise(JobError::SecurityError));
+            return;
         }
+
+        // Step 2 and 4: Get registration, and remove it from the scope to registration map.
+        let registration = self.registrations.remove(&job.scope_url);
+
+        // Step 3 and 5: Resolve the job promise with whether there was a registration.
+        let client = job.client.clone();
+        let value = JobResultValue::Unregistered(registration.is_some());
+        let _ = client.send(JobResult::ResolvePromise(job, value));
+
+        // Step 6: Try Clear Registration.
+        // Note: the clients using a registration are not tracked,
+        // so it is cleared right away, which terminates its workers.
+        drop(registration);
     }
 }
 
+/// <https://w3c.github.io/ServiceWorker/#try-activate-algorithm>
+fn try_activate(registration: &mut ServiceWorkerRegistration) {
+    // Step 1.
+    let Some(worker) = registration.waiting_worker.take() else {
+        return;
+    };
+
+    // Step 3: Invoke Activate.
+    // Note: the clients using a registration are not tracked, so the waiting worker
+    // is activated right away, as if it had called skipWaiting().
+
+    // Activate: Step 2, terminate the active worker.
+    let active_worker = registration.active_worker.take();
+    registration.terminate_worker(active_worker);
+
+    // Activate: Step 3 and 4, run Update Registration State
+    // with "active" and the waiting worker, and with "waiting" and null.
+    worker.send_message(ServiceWorkerScriptMsg::Activate);
+    registration.update_registration_state(RegistrationUpdateTarget::Active, Some(worker));
+}
+
 /// <https://w3c.github.io/ServiceWorker/#update-algorithm>
 fn update_serviceworker(
     own_sender: IpcSender<ServiceWorkerMsg>,
     scope_url: ServoUrl,
+    worker_id: ServiceWorkerId,
     scope_things: ScopeThings,
-) -> (
-    ServiceWorker,
-    JoinHandle<()>,
-    Sender<ServiceWorkerControlMsg>,
-    ThreadSafeJSContext,
-    Arc<AtomicBool>,
-) {
+) -> (ServiceWorker, ServiceWorkerThread) {
     let (sender, receiver) = unbounded();
     let (_devtools_sender, devtools_receiver) = ipc::channel().unwrap();
-    let worker_id = ServiceWorkerId::new();
 
     let (control_sender, control_receiver) = unbounded();
     let (context_sender, context_receiver) = unbounded();
@@ -468,6 +545,7 @@ fn update_serviceworker(
         devtools_receiver,
         own_sender,
         scope_url.clone(),
+        worker_id,
         control_receiver,
         context_sender,
         closing.clone(),
@@ -479,10 +557,12 @@ fn update_serviceworker(
 
     (
         ServiceWorker::new(scope_things.script_url, sender, worker_id),
-        join_handle,
-        control_sender,
-        context,
-        closing,
+        ServiceWorkerThread {
+            control_sender,
+            join_handle: Some(join_handle),
+            context,
+            closing,
+        },
     )
 }
 
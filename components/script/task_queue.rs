@@ -133,7 +133,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             };
 
             match task_source {
-                TaskSourceName::PerformanceTimeline => {
+                TaskSourceName::PerformanceTimeline | TaskSourceName::BackgroundPostedTask => {
                     to_be_throttled.push(incoming.remove(index - 1));
                     index -= 1; // We've removed an element, so the next has the same index.
                 },
@@ -191,6 +191,43 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         &self.port
     }
 
+    /// Whether there are tasks waiting to be received, either in the queue or in the port.
+    pub(crate) fn has_pending_tasks(&self) -> bool {
+        !self.msg_queue.borrow().is_empty() || !self.port.is_empty()
+    }
+
+    /// The number of tasks waiting to be run, including throttled tasks and tasks of
+    /// documents that are not fully active.
+    pub(crate) fn pending_task_count(&self) -> usize {
+        self.msg_queue.borrow().len() +
+            self.port.len() +
+            self.throttled
+                .borrow()
+                .values()
+                .map(VecDeque::len)
+                .sum::<usize>() +
+            self.inactive
+                .borrow()
+                .values()
+                .map(VecDeque::len)
+                .sum::<usize>()
+    }
+
+    /// Put back tasks that were taken for this iteration of the event-loop but not run,
+    /// so that they are the first ones handled in the next iteration.
+    /// Used to let rendering-critical work preempt background tasks.
+    pub(crate) fn defer_tasks(&self, tasks: Vec<T>) {
+        if tasks.is_empty() {
+            return;
+        }
+        let mut msg_queue = self.msg_queue.borrow_mut();
+        for task in tasks.into_iter().rev() {
+            msg_queue.push_front(task);
+        }
+        // Ensure the select wakes up in the next iteration of the event-loop.
+        let _ = self.wake_up_sender.send(T::wake_up_msg());
+    }
+
     /// Take a message from the front of the queue, without waiting if empty.
     pub(crate) fn recv(&self) -> Result<T, ()> {
         self.msg_queue.borrow_mut().pop_front().ok_or(())
@@ -46,6 +46,14 @@ pub(crate) enum TaskSourceName {
     Gamepad,
     /// <https://w3c.github.io/IntersectionObserver/#intersectionobserver-task-source>
     IntersectionObserver,
+    /// <https://w3c.github.io/geolocation/#dfn-geolocation-task-source>
+    Geolocation,
+    /// <https://wicg.github.io/scheduling-apis/#posted-task-task-source>
+    /// for tasks posted with a "user-blocking" or "user-visible" priority.
+    PostedTask,
+    /// <https://wicg.github.io/scheduling-apis/#posted-task-task-source>
+    /// for tasks posted with a "background" priority, which yield to rendering and input.
+    BackgroundPostedTask,
 }
 
 impl From<TaskSourceName> for ScriptThreadEventCategory {
@@ -72,6 +80,9 @@ impl From<TaskSourceName> for ScriptThreadEventCategory {
             TaskSourceName::Timer => ScriptThreadEventCategory::TimerEvent,
             TaskSourceName::Gamepad => ScriptThreadEventCategory::InputEvent,
             TaskSourceName::IntersectionObserver => ScriptThreadEventCategory::ScriptEvent,
+            TaskSourceName::Geolocation => ScriptThreadEventCategory::ScriptEvent,
+            TaskSourceName::PostedTask => ScriptThreadEventCategory::ScriptEvent,
+            TaskSourceName::BackgroundPostedTask => ScriptThreadEventCategory::ScriptEvent,
         }
     }
 }
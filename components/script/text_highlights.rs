@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Highlighting of ranges of text that aren't selected: the highlights that pages register
+//! with the CSS Custom Highlight API (<https://drafts.csswg.org/css-highlight-api-1/>) and
+//! the matches of finding text in the page. The highlighted ranges are resolved to byte
+//! ranges of the data of the text nodes they cover, which layout reads to paint them.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use layout_api::HighlightKind;
+use style::dom::OpaqueNode;
+
+use crate::dom::abstractrange::{AbstractRange, bp_position};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbstractRangeBinding::AbstractRangeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::node::{Node, NodeDamage, ShadowIncluding};
+use crate::dom::text::Text;
+
+/// The highlighted text of a document.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) struct TextHighlights {
+    /// The highlighted UTF-8 byte ranges of the data of each highlighted text node, in the
+    /// order in which they are painted.
+    #[no_trace]
+    #[ignore_malloc_size_of = "OpaqueNode"]
+    ranges: DomRefCell<HashMap<OpaqueNode, Vec<(Range<usize>, HighlightKind)>>>,
+
+    /// The highlighted text nodes, which are kept alive for as long as their addresses are
+    /// keys of `ranges`.
+    nodes: DomRefCell<Vec<Dom<Node>>>,
+}
+
+impl TextHighlights {
+    /// Replace the highlighted text with the text in `highlights`, which are given in the
+    /// order in which they are painted, and mark the text nodes whose highlights changed as
+    /// needing layout.
+    pub(crate) fn update(&self, highlights: &[(DomRoot<AbstractRange>, HighlightKind)]) {
+        let mut ranges: HashMap<OpaqueNode, Vec<(Range<usize>, HighlightKind)>> = HashMap::new();
+        let mut nodes: Vec<DomRoot<Node>> = Vec::new();
+        for (range, kind) in highlights {
+            for (text, text_range) in text_ranges_in(range) {
+                let node = text.upcast::<Node>();
+                let entry = ranges.entry(node.to_opaque()).or_default();
+                if entry.is_empty() {
+                    nodes.push(DomRoot::from_ref(node));
+                }
+                entry.push((text_range, *kind));
+            }
+        }
+
+        let old_ranges = std::mem::replace(&mut *self.ranges.borrow_mut(), ranges);
+        {
+            let new_ranges = self.ranges.borrow();
+            let dirty_if_changed = |node: &Node| {
+                let opaque = node.to_opaque();
+                if old_ranges.get(&opaque) != new_ranges.get(&opaque) {
+                    node.dirty(NodeDamage::Other);
+                }
+            };
+            self.nodes
+                .borrow()
+                .iter()
+                .for_each(|node| dirty_if_changed(node));
+            nodes.iter().for_each(|node| dirty_if_changed(node));
+        }
+
+        *self.nodes.borrow_mut() = nodes.iter().map(|node| Dom::from_ref(&**node)).collect();
+    }
+
+    /// The highlighted byte ranges of the data of the text node with the given address, for
+    /// layout.
+    #[allow(unsafe_code)]
+    pub(crate) fn ranges_for_layout(&self, node: OpaqueNode) -> Vec<(Range<usize>, HighlightKind)> {
+        unsafe { self.ranges.borrow_for_layout() }
+            .get(&node)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The text nodes that `range` covers, with the UTF-8 byte range of the data of each of them
+/// that is inside of `range`.
+fn text_ranges_in(range: &AbstractRange) -> Vec<(DomRoot<Text>, Range<usize>)> {
+    let start_container = range.StartContainer();
+    let start_offset = range.StartOffset();
+    let end_container = range.EndContainer();
+    let end_offset = range.EndOffset();
+
+    // Ranges that end before they start, which static ranges can do, cover nothing.
+    if bp_position(&start_container, start_offset, &end_container, end_offset) !=
+        Some(Ordering::Less)
+    {
+        return Vec::new();
+    }
+
+    let root = start_container
+        .inclusive_ancestors(ShadowIncluding::No)
+        .last()
+        .unwrap();
+    let mut text_ranges = Vec::new();
+    for node in root.traverse_preorder(ShadowIncluding::No) {
+        let Some(text) = node.downcast::<Text>() else {
+            continue;
+        };
+        if bp_position(&node, 0, &end_container, end_offset) != Some(Ordering::Less) {
+            break;
+        }
+
+        let data = text.upcast::<CharacterData>().data();
+        let length = data.encode_utf16().count() as u32;
+        let start = if *node == *start_container {
+            start_offset
+        } else if bp_position(&start_container, start_offset, &node, 0) != Some(Ordering::Greater) {
+            0
+        } else {
+            continue;
+        };
+        let end = if *node == *end_container {
+            end_offset
+        } else {
+            length
+        };
+
+        let start = utf8_offset(&data, start);
+        let end = utf8_offset(&data, end);
+        if start < end {
+            text_ranges.push((DomRoot::from_ref(text), start..end));
+        }
+    }
+    text_ranges
+}
+
+/// The UTF-8 byte offset in `text` of the given UTF-16 code unit offset.
+fn utf8_offset(text: &str, utf16_offset: u32) -> usize {
+    let mut utf16_length = 0;
+    for (offset, character) in text.char_indices() {
+        if utf16_length >= utf16_offset as usize {
+            return offset;
+        }
+        utf16_length += character.len_utf16();
+    }
+    text.len()
+}
@@ -14,15 +14,17 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::clipboard_provider::{ClipboardProvider, EmbedderClipboardProvider};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DragEventBinding::DragEventMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::event::Event;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::NodeTraits;
 use crate::dom::types::ClipboardEvent;
-use crate::drag_data_store::{DragDataStore, Kind};
+use crate::drag_data_store::DragDataStore;
 use crate::script_runtime::CanGc;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -196,6 +198,15 @@ pub struct TextInput<T: ClipboardProvider> {
 
     /// Was last change made by set_content?
     was_last_change_by_set_content: bool,
+
+    /// The byte offsets of the text that an input method is composing, if a composition
+    /// session is active. This text is shown in the control but is not committed yet.
+    /// <https://w3c.github.io/uievents/#events-compositionevents>
+    composition: Option<Range<UTF8Bytes>>,
+
+    /// The byte offsets of the words that the spellchecker has flagged as misspelled.
+    /// <https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking>
+    misspellings: Vec<Range<UTF8Bytes>>,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -282,6 +293,8 @@ impl<T: ClipboardProvider> TextInput<T> {
             min_length,
             selection_direction,
             was_last_change_by_set_content: true,
+            composition: None,
+            misspellings: Vec::new(),
         };
         i.set_content(initial);
         i
@@ -754,6 +767,55 @@ impl<T: ClipboardProvider> TextInput<T> {
         self.adjust_horizontal_to_limit(direction, Selection::NotSelected);
     }
 
+    /// Select the word around the edit point, as double clicking on it does. When the edit
+    /// point is between a word and the white space or punctuation after it, the word is
+    /// selected.
+    pub(crate) fn select_word(&mut self) {
+        let line = &self.lines[self.edit_point.line];
+        let UTF8Bytes(index) = self.edit_point.index;
+        let words: Vec<_> = line.split_word_bound_indices().collect();
+        let is_word = |text: &str| text.chars().any(char::is_alphanumeric);
+        let Some(mut position) = words
+            .iter()
+            .position(|(start, text)| index < start + text.len())
+            .or(words.len().checked_sub(1))
+        else {
+            return;
+        };
+        if position > 0 &&
+            words[position].0 == index &&
+            !is_word(words[position].1) &&
+            is_word(words[position - 1].1)
+        {
+            position -= 1;
+        }
+        let (start, text) = words[position];
+        self.selection_origin = Some(TextPoint {
+            line: self.edit_point.line,
+            index: UTF8Bytes(start),
+        });
+        self.edit_point.index = UTF8Bytes(start + text.len());
+        self.selection_direction = SelectionDirection::Forward;
+        self.assert_ok_selection();
+    }
+
+    /// Move the edit point to the grapheme cluster offset `index` of the current line, as
+    /// clicking on the text does. With [`Selection::Selected`], as for a Shift+click, the
+    /// selection is extended to the new edit point instead of being removed.
+    pub(crate) fn move_edit_point_to_index(&mut self, index: usize, select: Selection) {
+        match select {
+            Selection::Selected if self.selection_origin.is_none() => {
+                self.selection_origin = Some(self.edit_point);
+            },
+            Selection::Selected => {},
+            Selection::NotSelected => self.clear_selection(),
+        }
+        self.set_edit_point_index(index);
+        if select == Selection::Selected {
+            self.update_selection_direction();
+        }
+    }
+
     pub fn adjust_horizontal_by_word(&mut self, direction: Direction, select: Selection) {
         if self.adjust_selection_for_horizontal_change(direction, select) {
             return;
@@ -853,6 +915,10 @@ impl<T: ClipboardProvider> TextInput<T> {
 
     /// Process a given `KeyboardEvent` and return an action for the caller to execute.
     pub(crate) fn handle_keydown(&mut self, event: &KeyboardEvent) -> KeyReaction {
+        // Keys pressed during a composition session are handled by the input method.
+        if event.is_composing() {
+            return KeyReaction::Nothing;
+        }
         let key = event.key();
         let mods = event.modifiers();
         self.handle_keydown_aux(key, mods, cfg!(target_os = "macos"))
@@ -1006,22 +1072,104 @@ impl<T: ClipboardProvider> TextInput<T> {
             .unwrap()
     }
 
+    /// Start a composition session at the selection, which is replaced by the text that
+    /// is composed.
+    pub(crate) fn handle_compositionstart(&mut self) -> KeyReaction {
+        let start = self.selection_start_offset();
+        let end = self.selection_end_offset();
+        if start != end {
+            self.replace_selection(DOMString::new());
+        }
+        self.composition = Some(start..start);
+        KeyReaction::RedrawSelection
+    }
+
     pub(crate) fn handle_compositionend(&mut self, event: &CompositionEvent) -> KeyReaction {
-        self.insert_string(event.data());
+        self.replace_composition(event.data());
+        self.composition = None;
         KeyReaction::DispatchInput
     }
 
     pub(crate) fn handle_compositionupdate(&mut self, event: &CompositionEvent) -> KeyReaction {
-        let start = self.selection_start_offset().0;
-        self.insert_string(event.data());
+        let start = self.replace_composition(event.data());
+        self.composition = Some(start..self.edit_point_offset());
+        KeyReaction::DispatchInput
+    }
+
+    /// Replace the text of the active composition, or the selection if there is no
+    /// composition session, with `text`, leaving the edit point after it. Returns the
+    /// offset at which `text` was inserted.
+    fn replace_composition(&mut self, text: &str) -> UTF8Bytes {
+        let range = self
+            .composition
+            .clone()
+            .unwrap_or_else(|| self.sorted_selection_offsets_range());
+        self.set_selection_range(
+            range.start.0 as u32,
+            range.end.0 as u32,
+            SelectionDirection::Forward,
+        );
+        self.insert_string(text);
+        range.start
+    }
+
+    /// The byte offsets of the text that an input method is composing, if any.
+    pub(crate) fn composition_range(&self) -> Option<Range<UTF8Bytes>> {
+        self.composition.clone()
+    }
+
+    /// The byte offsets of the words that are marked as misspelled.
+    pub(crate) fn misspellings(&self) -> &[Range<UTF8Bytes>] {
+        &self.misspellings
+    }
+
+    /// Mark the words at `misspellings` as misspelled. While `typing`, the word that ends
+    /// at the edit point is not marked, as the user is probably not done writing it yet.
+    pub(crate) fn set_misspellings(&mut self, misspellings: Vec<Range<UTF8Bytes>>, typing: bool) {
+        let edit_point = self.edit_point_offset();
+        self.misspellings = misspellings
+            .into_iter()
+            .filter(|misspelling| !typing || misspelling.end != edit_point)
+            .collect();
+    }
+
+    /// The byte offsets of the misspelled word that the edit point is in, if any.
+    fn misspelling_at_edit_point(&self) -> Option<Range<UTF8Bytes>> {
+        let edit_point = self.edit_point_offset();
+        self.misspellings
+            .iter()
+            .find(|misspelling| misspelling.start <= edit_point && edit_point <= misspelling.end)
+            .cloned()
+    }
+
+    /// The misspelled word that the edit point is in, if any.
+    pub(crate) fn misspelled_word(&self) -> Option<String> {
+        let range = self.misspelling_at_edit_point()?;
+        self.get_content()
+            .get(range.start.0..range.end.0)
+            .map(String::from)
+    }
+
+    /// Replace the misspelled word that the edit point is in with `replacement`, for
+    /// instance one of the suggestions of the spellchecker that the user picked.
+    pub(crate) fn replace_misspelled_word(&mut self, replacement: &str) -> KeyReaction {
+        let Some(range) = self.misspelling_at_edit_point() else {
+            return KeyReaction::Nothing;
+        };
         self.set_selection_range(
-            start as u32,
-            (start + event.data().len_utf8().0) as u32,
+            range.start.0 as u32,
+            range.end.0 as u32,
             SelectionDirection::Forward,
         );
+        self.insert_string(replacement);
         KeyReaction::DispatchInput
     }
 
+    /// The byte offset of the edit point.
+    fn edit_point_offset(&self) -> UTF8Bytes {
+        self.text_point_to_offset(&self.edit_point)
+    }
+
     /// Whether the content is empty.
     pub(crate) fn is_empty(&self) -> bool {
         self.lines.len() <= 1 && self.lines.first().is_none_or(|line| line.is_empty())
@@ -1088,6 +1236,8 @@ impl<T: ClipboardProvider> TextInput<T> {
         };
 
         self.was_last_change_by_set_content = true;
+        self.composition = None;
+        self.misspellings.clear();
         self.edit_point = self.edit_point.constrain_to(&self.lines);
 
         if let Some(origin) = self.selection_origin {
@@ -1173,10 +1323,10 @@ impl<T: ClipboardProvider> TextInput<T> {
     }
 
     fn paste_contents(&mut self, drag_data_store: &DragDataStore) {
-        for item in drag_data_store.iter_item_list() {
-            if let Kind::Text { data, .. } = item {
-                self.insert_string(data.to_string());
-            }
+        // Text controls only hold plain text, so other flavors of the same contents, like
+        // HTML, are left out.
+        if let Some(data) = drag_data_store.find_matching_text("text/plain") {
+            self.insert_string(data.to_string());
         }
     }
 }
@@ -1250,3 +1400,34 @@ pub(crate) fn handle_text_clipboard_action(
     //Step 5
     true
 }
+
+/// The default action of a `drop` event in a text control: insert the dropped plain text
+/// where it was dropped, at the grapheme cluster offset `index` of the current line if it is
+/// known. Returns whether any text was inserted.
+/// <https://html.spec.whatwg.org/multipage/#drop-processing>
+pub(crate) fn handle_text_drop_action(
+    textinput: &DomRefCell<TextInput<EmbedderClipboardProvider>>,
+    event: &DragEvent,
+    index: Option<usize>,
+) -> bool {
+    if !event.upcast::<Event>().IsTrusted() {
+        return false;
+    }
+    let Some(data_transfer) = event.GetDataTransfer() else {
+        return false;
+    };
+    let Some(text) = data_transfer
+        .data_store()
+        .and_then(|data_store| data_store.find_matching_text("text/plain"))
+    else {
+        return false;
+    };
+
+    let mut textinput = textinput.borrow_mut();
+    if let Some(index) = index {
+        textinput.clear_selection();
+        textinput.set_edit_point_index(index);
+    }
+    textinput.insert_string(text.to_string());
+    true
+}
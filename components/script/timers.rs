@@ -18,6 +18,7 @@ use serde::{Deserialize, Serialize};
 use servo_config::pref;
 use timers::{BoxedTimerCallback, TimerEventRequest};
 
+use crate::dom::abortsignal::AbortSignalTimeoutCallback;
 use crate::dom::bindings::callback::ExceptionHandling::Report;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
@@ -29,7 +30,10 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::csp::CspReporting;
 use crate::dom::document::{ImageAnimationUpdateCallback, RefreshRedirectDue};
 use crate::dom::eventsource::EventSourceTimeoutCallback;
+use crate::dom::geolocation::GeolocationTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportingendpoint::ReportDeliveryCallback;
+use crate::dom::scheduler::SchedulerPostTaskDelayCallback;
 #[cfg(feature = "testbinding")]
 use crate::dom::testbinding::TestBindingCallback;
 use crate::dom::types::{Window, WorkerGlobalScope};
@@ -86,6 +90,10 @@ pub(crate) enum OneshotTimerCallback {
     TestBindingCallback(TestBindingCallback),
     RefreshRedirectDue(RefreshRedirectDue),
     ImageAnimationUpdate(ImageAnimationUpdateCallback),
+    GeolocationTimeout(GeolocationTimeoutCallback),
+    ReportDelivery(ReportDeliveryCallback),
+    AbortSignalTimeout(AbortSignalTimeoutCallback),
+    SchedulerPostTaskDelay(SchedulerPostTaskDelayCallback),
 }
 
 impl OneshotTimerCallback {
@@ -98,6 +106,10 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::RefreshRedirectDue(callback) => callback.invoke(can_gc),
             OneshotTimerCallback::ImageAnimationUpdate(callback) => callback.invoke(can_gc),
+            OneshotTimerCallback::GeolocationTimeout(callback) => callback.invoke(can_gc),
+            OneshotTimerCallback::ReportDelivery(callback) => callback.invoke(),
+            OneshotTimerCallback::AbortSignalTimeout(callback) => callback.invoke(can_gc),
+            OneshotTimerCallback::SchedulerPostTaskDelay(callback) => callback.invoke(),
         }
     }
 }
@@ -248,6 +260,13 @@ impl OneshotTimers {
             .set_min_duration(Duration::from_millis(min_duration_ms));
     }
 
+    /// Slow down timers even more, for documents that have been hidden for a long time.
+    pub(crate) fn slow_down_intensively(&self) {
+        let min_duration_ms = pref!(js_timers_intensive_throttling_minimum_duration) as u64;
+        self.js_timers
+            .set_min_duration(Duration::from_millis(min_duration_ms));
+    }
+
     pub(crate) fn speed_up(&self) {
         self.js_timers.remove_min_duration();
     }
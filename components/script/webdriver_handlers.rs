@@ -28,9 +28,10 @@ use net_traits::IpcSend;
 use script_bindings::codegen::GenericBindings::ShadowRootBinding::ShadowRootMethods;
 use script_bindings::conversions::is_array_like;
 use servo_url::ServoUrl;
-use webdriver::common::{WebElement, WebFrame, WebWindow};
+use webdriver::common::{ShadowRoot as WebDriverShadowRoot, WebElement, WebFrame, WebWindow};
 use webdriver::error::ErrorStatus;
 
+use crate::coverage;
 use crate::document_collection::DocumentCollection;
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
@@ -395,11 +396,31 @@ unsafe fn jsval_to_webdriver_inner(
             }
             Ok(WebDriverJSValue::ArrayLike(result))
         } else if let Ok(element) = root_from_object::<Element>(*object, cx) {
-            Ok(WebDriverJSValue::Element(WebElement(
-                element
-                    .upcast::<Node>()
-                    .unique_id(element.owner_document().window().pipeline_id()),
-            )))
+            // <https://w3c.github.io/webdriver/#dfn-internal-json-clone>
+            // If value is an element that is stale, return error with error code stale
+            // element reference.
+            if is_stale(&element) {
+                Err(WebDriverJSError::StaleElementReference)
+            } else {
+                Ok(WebDriverJSValue::Element(WebElement(
+                    element
+                        .upcast::<Node>()
+                        .unique_id(element.owner_document().window().pipeline_id()),
+                )))
+            }
+        } else if let Ok(shadow_root) = root_from_object::<ShadowRoot>(*object, cx) {
+            // If value is a shadow root that is detached, return error with error code
+            // detached shadow root.
+            let document = shadow_root.owner_document();
+            if !document.is_active() || is_stale(&shadow_root.Host()) {
+                Err(WebDriverJSError::DetachedShadowRoot)
+            } else {
+                Ok(WebDriverJSValue::ShadowRoot(WebDriverShadowRoot(
+                    shadow_root
+                        .upcast::<Node>()
+                        .unique_id(document.window().pipeline_id()),
+                )))
+            }
         } else if let Ok(window) = root_from_object::<Window>(*object, cx) {
             let window_proxy = window.window_proxy();
             if window_proxy.is_browsing_context_discarded() {
@@ -1141,14 +1162,20 @@ pub(crate) fn handle_get_computed_role(
     node_id: String,
     reply: IpcSender<Result<Option<String>, ErrorStatus>>,
 ) {
+    // Inert elements are left out of the accessibility tree, so they have no role.
     reply
         .send(
-            get_known_element(documents, pipeline, node_id)
-                .map(|element| element.GetRole().map(String::from)),
+            get_known_element(documents, pipeline, node_id).map(|element| {
+                if element.upcast::<Node>().is_inert() {
+                    return None;
+                }
+                element.GetRole().map(String::from)
+            }),
         )
         .unwrap();
 }
 
+/// <https://w3c.github.io/webdriver/#get-page-source>
 pub(crate) fn handle_get_page_source(
     documents: &DocumentCollection,
     pipeline: PipelineId,
@@ -1159,20 +1186,24 @@ pub(crate) fn handle_get_page_source(
         .send(
             documents
                 .find_document(pipeline)
-                .ok_or(ErrorStatus::UnknownError)
-                .and_then(|document| match document.GetDocumentElement() {
-                    Some(element) => match element.outer_html(can_gc) {
+                .ok_or(ErrorStatus::NoSuchWindow)
+                .and_then(|document| {
+                    // Step 3. Let source be the result of invoking the fragment serializing
+                    // algorithm on a fictional node whose only child is the document element,
+                    // which serializes the current state of the DOM rather than the bytes
+                    // that were loaded.
+                    let Some(element) = document.GetDocumentElement() else {
+                        return Ok(String::new());
+                    };
+                    match element.outer_html(can_gc) {
                         Ok(source) => Ok(source.to_string()),
-                        Err(_) => {
-                            match XMLSerializer::new(document.window(), None, can_gc)
-                                .SerializeToString(element.upcast::<Node>())
-                            {
-                                Ok(source) => Ok(source.to_string()),
-                                Err(_) => Err(ErrorStatus::UnknownError),
-                            }
-                        },
-                    },
-                    None => Err(ErrorStatus::UnknownError),
+                        // Step 4. If source is failure, then let source be the result of
+                        // serializing to string the document element with an XMLSerializer.
+                        Err(_) => XMLSerializer::new(document.window(), None, can_gc)
+                            .SerializeToString(element.upcast::<Node>())
+                            .map(String::from)
+                            .map_err(|_| ErrorStatus::UnknownError),
+                    }
                 }),
         )
         .unwrap();
@@ -1341,6 +1372,39 @@ pub(crate) fn handle_get_title(
         .unwrap();
 }
 
+pub(crate) fn handle_start_coverage(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    reply: IpcSender<Result<(), ErrorStatus>>,
+) {
+    reply
+        .send(
+            documents
+                .find_window(pipeline)
+                .ok_or(ErrorStatus::NoSuchWindow)
+                .map(|window| coverage::start_coverage(window.get_cx())),
+        )
+        .unwrap();
+}
+
+pub(crate) fn handle_take_coverage(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    reply: IpcSender<Result<String, ErrorStatus>>,
+) {
+    reply
+        .send(
+            documents
+                .find_document(pipeline)
+                .ok_or(ErrorStatus::NoSuchWindow)
+                .and_then(|document| {
+                    serde_json::to_string(&coverage::take_coverage(&document))
+                        .map_err(|_| ErrorStatus::UnknownError)
+                }),
+        )
+        .unwrap();
+}
+
 /// <https://w3c.github.io/webdriver/#dfn-calculate-the-absolute-position>
 fn calculate_absolute_position(
     documents: &DocumentCollection,
@@ -1414,6 +1478,37 @@ pub(crate) fn handle_get_bounding_client_rect(
         .unwrap();
 }
 
+/// Get the bounding client rects of all the elements that match any of `selectors`, for
+/// the areas to hide in a masked screenshot.
+pub(crate) fn handle_get_masked_rects(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    selectors: Vec<String>,
+    reply: IpcSender<Result<Vec<Rect<f32>>, ErrorStatus>>,
+    can_gc: CanGc,
+) {
+    let result =
+        retrieve_document_and_check_root_existence(documents, pipeline).and_then(|document| {
+            let mut rects = Vec::new();
+            for selector in selectors {
+                let nodes = document
+                    .QuerySelectorAll(DOMString::from(selector))
+                    .map_err(|_| ErrorStatus::InvalidSelector)?;
+                rects.extend(nodes.iter().filter_map(DomRoot::downcast::<Element>).map(
+                    |element| {
+                        let rect = element.GetBoundingClientRect(can_gc);
+                        Rect::new(
+                            Point2D::new(rect.X() as f32, rect.Y() as f32),
+                            Size2D::new(rect.Width() as f32, rect.Height() as f32),
+                        )
+                    },
+                ));
+            }
+            Ok(rects)
+        });
+    reply.send(result).unwrap();
+}
+
 /// <https://w3c.github.io/webdriver/#dfn-get-element-text>
 pub(crate) fn handle_get_text(
     documents: &DocumentCollection,
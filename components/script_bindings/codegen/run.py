@@ -113,6 +113,9 @@ def add_css_properties_attributes(css_properties_json, parser):
             ["layout.css.transition-behavior.enabled", "layout_css_transition_behavior_enabled"],
             ["layout.writing-mode.enabled", "layout_writing_mode_enabled"],
             ["layout.container-queries.enabled", "layout_container_queries_enabled"],
+            ["layout.css.anchor-positioning.enabled", "layout_css_anchor_positioning_enabled"],
+            ["layout.css.text-wrap-style.enabled", "layout_css_text_wrap_style_enabled"],
+            ["layout.css.backdrop-filter.enabled", "layout_css_backdrop_filter_enabled"],
         ]
         for mapping in MAPPING:
             if mapping[0] == preference_name:
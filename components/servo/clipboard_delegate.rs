@@ -2,18 +2,28 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::{ClipboardContents, ClipboardImage};
 use ipc_channel::ipc::IpcSender;
+use serde::Serialize;
 
 use crate::WebView;
 
-pub struct StringRequest {
-    pub(crate) result_sender: IpcSender<Result<String, String>>,
+/// A request for contents of the system clipboard, which sends a failure response when it is
+/// dropped without a response.
+pub struct ClipboardRequest<T: Serialize> {
+    pub(crate) result_sender: IpcSender<Result<T, String>>,
     response_sent: bool,
 }
 
-impl StringRequest {
-    pub fn success(mut self, string: String) {
-        let _ = self.result_sender.send(Ok(string));
+/// A request for text contents of the system clipboard.
+pub type StringRequest = ClipboardRequest<String>;
+
+/// A request for the image contents of the system clipboard.
+pub type ImageRequest = ClipboardRequest<ClipboardImage>;
+
+impl<T: Serialize> ClipboardRequest<T> {
+    pub fn success(mut self, contents: T) {
+        let _ = self.result_sender.send(Ok(contents));
         self.response_sent = true;
     }
 
@@ -23,8 +33,8 @@ impl StringRequest {
     }
 }
 
-impl From<IpcSender<Result<String, String>>> for StringRequest {
-    fn from(result_sender: IpcSender<Result<String, String>>) -> Self {
+impl<T: Serialize> From<IpcSender<Result<T, String>>> for ClipboardRequest<T> {
+    fn from(result_sender: IpcSender<Result<T, String>>) -> Self {
         Self {
             result_sender,
             response_sent: false,
@@ -32,7 +42,7 @@ impl From<IpcSender<Result<String, String>>> for StringRequest {
     }
 }
 
-impl Drop for StringRequest {
+impl<T: Serialize> Drop for ClipboardRequest<T> {
     fn drop(&mut self) {
         if !self.response_sent {
             let _ = self
@@ -55,8 +65,28 @@ pub trait ClipboardDelegate {
     /// [`StringRequest::failure`] with a failure message.
     fn get_text(&self, _webview: WebView, _request: StringRequest) {}
 
+    /// A request to get the HTML contents of the system clipboard. Once the contents are
+    /// retrieved the embedder should call [`StringRequest::success`] with the HTML or
+    /// [`StringRequest::failure`] if the clipboard doesn't contain any HTML. The HTML is
+    /// sanitized before it is exposed to web content.
+    fn get_html(&self, _webview: WebView, _request: StringRequest) {}
+
+    /// A request to get the image contents of the system clipboard. Once the contents are
+    /// retrieved the embedder should call [`ImageRequest::success`] with the image or
+    /// [`ImageRequest::failure`] if the clipboard doesn't contain an image. The image is
+    /// encoded as PNG before it is exposed to web content.
+    fn get_image(&self, _webview: WebView, _request: ImageRequest) {}
+
     /// A request to set the text contents of the system clipboard to `new_contents`.
     fn set_text(&self, _webview: WebView, _new_contents: String) {}
+
+    /// A request to replace the contents of the system clipboard with `new_contents`, which
+    /// may hold the same data in several formats. By default only the text is written.
+    fn set_contents(&self, webview: WebView, new_contents: ClipboardContents) {
+        if let Some(text) = new_contents.text {
+            self.set_text(webview, text);
+        }
+    }
 }
 
 pub(crate) struct DefaultClipboardDelegate;
@@ -70,9 +100,17 @@ impl ClipboardDelegate for DefaultClipboardDelegate {
         clipboard::get_text(request);
     }
 
+    fn get_image(&self, _webview: WebView, request: ImageRequest) {
+        clipboard::get_image(request);
+    }
+
     fn set_text(&self, _webview: WebView, new_contents: String) {
         clipboard::set_text(new_contents);
     }
+
+    fn set_contents(&self, _webview: WebView, new_contents: ClipboardContents) {
+        clipboard::set_contents(new_contents);
+    }
 }
 
 #[cfg(all(
@@ -80,12 +118,14 @@ impl ClipboardDelegate for DefaultClipboardDelegate {
     not(any(target_os = "android", target_env = "ohos"))
 ))]
 mod clipboard {
+    use std::borrow::Cow;
     use std::sync::OnceLock;
 
-    use arboard::Clipboard;
+    use arboard::{Clipboard, ImageData};
+    use embedder_traits::{ClipboardContents, ClipboardImage};
     use parking_lot::Mutex;
 
-    use super::StringRequest;
+    use super::{ImageRequest, StringRequest};
 
     /// A shared clipboard for use by the [`DefaultClipboardDelegate`]. This is protected by
     /// a mutex so that it can only be used by one thread at a time. The `arboard` documentation
@@ -114,18 +154,59 @@ mod clipboard {
         });
     }
 
+    pub(super) fn get_image(request: ImageRequest) {
+        with_shared_clipboard(move |clipboard| match clipboard.get_image() {
+            Ok(image) => request.success(ClipboardImage {
+                width: image.width as u32,
+                height: image.height as u32,
+                rgba_pixels: image.bytes.into_owned(),
+            }),
+            Err(error) => request.failure(format!("{error:?}")),
+        });
+    }
+
     pub(super) fn set_text(new_contents: String) {
         with_shared_clipboard(move |clipboard| {
             let _ = clipboard.set_text(new_contents);
         });
     }
+
+    /// `arboard` replaces the whole clipboard each time that it writes a format, so only the
+    /// richest format is written: HTML along with its plain text alternative, then plain
+    /// text, then an image.
+    pub(super) fn set_contents(new_contents: ClipboardContents) {
+        with_shared_clipboard(move |clipboard| {
+            let _ = match new_contents {
+                ClipboardContents {
+                    html: Some(html),
+                    text,
+                    ..
+                } => clipboard.set_html(html, text),
+                ClipboardContents {
+                    text: Some(text), ..
+                } => clipboard.set_text(text),
+                ClipboardContents {
+                    image: Some(image), ..
+                } => clipboard.set_image(ImageData {
+                    width: image.width as usize,
+                    height: image.height as usize,
+                    bytes: Cow::Owned(image.rgba_pixels),
+                }),
+                _ => clipboard.clear(),
+            };
+        });
+    }
 }
 
 #[cfg(any(not(feature = "clipboard"), target_os = "android", target_env = "ohos"))]
 mod clipboard {
-    use super::StringRequest;
+    use embedder_traits::ClipboardContents;
+
+    use super::{ImageRequest, StringRequest};
 
     pub(super) fn clear() {}
     pub(super) fn get_text(_: StringRequest) {}
+    pub(super) fn get_image(_: ImageRequest) {}
     pub(super) fn set_text(_: String) {}
+    pub(super) fn set_contents(_: ClipboardContents) {}
 }
@@ -0,0 +1,174 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use embedder_traits::GeolocationPosition;
+use ipc_channel::ipc::IpcSender;
+
+use crate::WebView;
+use crate::responders::ServoErrorSender;
+use crate::webview_delegate::IpcResponder;
+
+/// The message that content gets when the position of the device can't be determined.
+const POSITION_UNAVAILABLE_MESSAGE: &str = "Position unavailable";
+
+/// A request for the current position of the device. If the request is dropped without a
+/// response, the position is reported to content as unavailable.
+pub struct GeolocationRequest {
+    high_accuracy: bool,
+    responder: IpcResponder<Result<GeolocationPosition, String>>,
+    error_sender: ServoErrorSender,
+}
+
+impl GeolocationRequest {
+    pub(crate) fn new(
+        high_accuracy: bool,
+        response_sender: IpcSender<Result<GeolocationPosition, String>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            high_accuracy,
+            responder: IpcResponder::new(
+                response_sender,
+                Err(POSITION_UNAVAILABLE_MESSAGE.to_owned()),
+            ),
+            error_sender,
+        }
+    }
+
+    /// Whether content asked for the most accurate position that the device can provide,
+    /// which may be slower and use more power.
+    pub fn high_accuracy(&self) -> bool {
+        self.high_accuracy
+    }
+
+    /// Respond with the current position of the device.
+    pub fn respond(mut self, position: GeolocationPosition) {
+        if let Err(error) = self.responder.send(Ok(position)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+
+    /// Report that the position of the device can't be determined, with a message that
+    /// explains why.
+    pub fn fail(mut self, message: String) {
+        if let Err(error) = self.responder.send(Err(message)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A watch on the position of the device, which should be sent every new position until
+/// content stops watching.
+pub struct GeolocationWatch {
+    high_accuracy: bool,
+    sender: IpcSender<Result<GeolocationPosition, String>>,
+    /// Shared with [`crate::Servo`], which clears it when content stops watching.
+    active: Rc<Cell<bool>>,
+}
+
+impl GeolocationWatch {
+    pub(crate) fn new(
+        high_accuracy: bool,
+        sender: IpcSender<Result<GeolocationPosition, String>>,
+        active: Rc<Cell<bool>>,
+    ) -> Self {
+        Self {
+            high_accuracy,
+            sender,
+            active,
+        }
+    }
+
+    /// Whether content is still watching the position. Once it is not, the watch should be
+    /// dropped.
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    /// Whether content asked for the most accurate positions that the device can provide,
+    /// which may be slower and use more power.
+    pub fn high_accuracy(&self) -> bool {
+        self.high_accuracy
+    }
+
+    /// Send a new position of the device. Returns false when content stopped watching, in
+    /// which case the watch should be dropped.
+    pub fn update(&self, position: GeolocationPosition) -> bool {
+        self.is_active() && self.sender.send(Ok(position)).is_ok()
+    }
+
+    /// Report that the position of the device can't be determined, with a message that
+    /// explains why. Returns false when content stopped watching, in which case the watch
+    /// should be dropped.
+    pub fn fail(&self, message: String) -> bool {
+        self.is_active() && self.sender.send(Err(message)).is_ok()
+    }
+}
+
+/// A provider of the position of the device for the Geolocation API, such as a platform
+/// location service. By default, the position is always unavailable. Content is only
+/// given access to the position after the user grants the `geolocation` permission.
+pub trait GeolocationProvider {
+    /// Determine the current position of the device and respond to the request with it.
+    fn request_position(&self, _webview: WebView, _request: GeolocationRequest) {}
+
+    /// Start sending the position of the device to the watch, every time that it changes,
+    /// until [`GeolocationWatch::is_active`] returns false. Dropping an active watch means
+    /// that no more positions are sent, which content does not notice.
+    fn watch_position(&self, _webview: WebView, _watch: GeolocationWatch) {}
+}
+
+pub(crate) struct DefaultGeolocationProvider;
+
+impl GeolocationProvider for DefaultGeolocationProvider {}
+
+/// A provider that reports a position that is set by the embedder, for testing and for
+/// platforms without a location service.
+#[derive(Default)]
+pub struct MockGeolocationProvider {
+    position: Cell<Option<GeolocationPosition>>,
+    watches: RefCell<Vec<GeolocationWatch>>,
+}
+
+impl MockGeolocationProvider {
+    pub fn new(position: Option<GeolocationPosition>) -> Self {
+        Self {
+            position: Cell::new(position),
+            watches: Default::default(),
+        }
+    }
+
+    /// Change the position of the device, or make it unavailable, and send it to all
+    /// current watches.
+    pub fn set_position(&self, position: Option<GeolocationPosition>) {
+        self.position.set(position);
+        self.watches
+            .borrow_mut()
+            .retain(|watch| Self::send_position(watch, position));
+    }
+
+    fn send_position(watch: &GeolocationWatch, position: Option<GeolocationPosition>) -> bool {
+        match position {
+            Some(position) => watch.update(position),
+            None => watch.fail(POSITION_UNAVAILABLE_MESSAGE.to_owned()),
+        }
+    }
+}
+
+impl GeolocationProvider for MockGeolocationProvider {
+    fn request_position(&self, _webview: WebView, request: GeolocationRequest) {
+        if let Some(position) = self.position.get() {
+            request.respond(position);
+        }
+    }
+
+    fn watch_position(&self, _webview: WebView, watch: GeolocationWatch) {
+        if Self::send_position(&watch, self.position.get()) {
+            self.watches.borrow_mut().push(watch);
+        }
+    }
+}
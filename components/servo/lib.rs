@@ -18,10 +18,12 @@
 //! `WindowMethods` trait.
 
 mod clipboard_delegate;
+mod geolocation_provider;
 mod javascript_evaluator;
 mod proxies;
 mod responders;
 mod servo_delegate;
+mod spellcheck_delegate;
 mod webview;
 mod webview_delegate;
 
@@ -32,6 +34,7 @@ use std::path::PathBuf;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::SystemTime;
 
 pub use base::id::WebViewId;
 use base::id::{PipelineNamespace, PipelineNamespaceId};
@@ -40,7 +43,7 @@ use bluetooth::BluetoothThreadFactory;
 #[cfg(feature = "bluetooth")]
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::{GlType, WebGLThreads};
-use clipboard_delegate::StringRequest;
+use clipboard_delegate::{ImageRequest, StringRequest};
 pub use compositing::WebRenderDebugOption;
 use compositing::{IOCompositor, InitialCompositorState};
 pub use compositing_traits::rendering_context::{
@@ -79,6 +82,7 @@ use fonts::SystemFontService;
     not(target_env = "ohos"),
 ))]
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods};
+use geolocation_provider::DefaultGeolocationProvider;
 pub use gleam::gl;
 use gleam::gl::RENDERER;
 use ipc_channel::ipc::{self, IpcSender};
@@ -92,6 +96,8 @@ use log::{Log, Metadata, Record, debug, warn};
 use media::{GlApi, NativeDisplay, WindowGLContext};
 use net::protocols::ProtocolRegistry;
 use net::resource_thread::new_resource_threads;
+use net_traits::request::{CredentialsMode, Referrer, RequestBuilder};
+use net_traits::{CoreResourceThread, FetchResponseMsg, fetch_async};
 use profile::{mem as profile_mem, time as profile_time};
 use profile_traits::mem::MemoryReportResult;
 use profile_traits::{mem, time};
@@ -106,6 +112,7 @@ use servo_geometry::{
 use servo_media::ServoMedia;
 use servo_media::player::context::GlContext;
 use servo_url::ServoUrl;
+use url::Url;
 use webgl::WebGLComm;
 #[cfg(feature = "webgpu")]
 pub use webgpu;
@@ -125,14 +132,20 @@ pub use {
 #[cfg(feature = "bluetooth")]
 pub use {bluetooth, bluetooth_traits};
 
+pub use crate::geolocation_provider::{
+    GeolocationProvider, GeolocationRequest, GeolocationWatch, MockGeolocationProvider,
+};
 use crate::proxies::ConstellationProxy;
 use crate::responders::ServoErrorChannel;
 pub use crate::servo_delegate::{ServoDelegate, ServoError};
+pub use crate::spellcheck_delegate::{SpellCheckDelegate, WordListSpellChecker};
 use crate::webrender_api::FrameReadyParams;
 pub use crate::webview::{WebView, WebViewBuilder};
 pub use crate::webview_delegate::{
-    AllowOrDenyRequest, AuthenticationRequest, ColorPicker, FormControl, NavigationRequest,
-    PermissionRequest, SelectElement, WebResourceLoad, WebViewDelegate,
+    AllowOrDenyRequest, AuthenticationRequest, BluetoothDeviceSelectionRequest, ColorPicker,
+    DateTimePicker, FormControl, NavigationRequest, PermissionRequest, PictureInPictureRequest,
+    PublicKeyCredentialAssertionRequest, PublicKeyCredentialCreationRequest,
+    PushSubscriptionRequest, SelectElement, WebResourceLoad, WebViewDelegate,
 };
 
 #[cfg(feature = "webdriver")]
@@ -198,6 +211,10 @@ mod media_platform {
 // application and various browser components.
 pub struct Servo {
     delegate: RefCell<Rc<dyn ServoDelegate>>,
+    /// The provider of the position of the device for the Geolocation API.
+    geolocation_provider: RefCell<Rc<dyn GeolocationProvider>>,
+    /// Whether each [`GeolocationWatch`] given to the geolocation provider is still active.
+    geolocation_watches: RefCell<HashMap<GeolocationWatchId, Rc<Cell<bool>>>>,
     compositor: Rc<RefCell<IOCompositor>>,
     constellation_proxy: ConstellationProxy,
     embedder_receiver: Receiver<EmbedderMsg>,
@@ -219,6 +236,8 @@ pub struct Servo {
     _js_engine_setup: Option<JSEngineSetup>,
     /// Whether or not any WebView in this instance is animating or WebXR is enabled.
     animating: Cell<bool>,
+    /// The resource thread of the public storage partition, used for fetches of the embedder.
+    core_resource_thread: CoreResourceThread,
 }
 
 #[derive(Clone)]
@@ -448,7 +467,7 @@ impl Servo {
         let mut protocols = ProtocolRegistry::with_internal_protocols();
         protocols.merge(builder.protocol_registry);
 
-        let constellation_chan = create_constellation(
+        let (constellation_chan, core_resource_thread) = create_constellation(
             opts.config_dir.clone(),
             embedder_proxy,
             compositor_proxy.clone(),
@@ -493,6 +512,8 @@ impl Servo {
         let constellation_proxy = ConstellationProxy::new(constellation_chan);
         Self {
             delegate: RefCell::new(Rc::new(DefaultServoDelegate)),
+            geolocation_provider: RefCell::new(Rc::new(DefaultGeolocationProvider)),
+            geolocation_watches: Default::default(),
             compositor: Rc::new(RefCell::new(compositor)),
             javascript_evaluator: Rc::new(RefCell::new(JavaScriptEvaluator::new(
                 constellation_proxy.clone(),
@@ -504,6 +525,7 @@ impl Servo {
             servo_errors: ServoErrorChannel::default(),
             _js_engine_setup: js_engine_setup,
             animating: Cell::new(false),
+            core_resource_thread,
         }
     }
 
@@ -515,6 +537,16 @@ impl Servo {
         *self.delegate.borrow_mut() = delegate;
     }
 
+    pub fn geolocation_provider(&self) -> Rc<dyn GeolocationProvider> {
+        self.geolocation_provider.borrow().clone()
+    }
+
+    /// Set the provider of the position of the device for the Geolocation API, such as a
+    /// platform location service or a [`MockGeolocationProvider`].
+    pub fn set_geolocation_provider(&self, provider: Rc<dyn GeolocationProvider>) {
+        *self.geolocation_provider.borrow_mut() = provider;
+    }
+
     /// Whether or not any [`WebView`] of this Servo instance has animating content, such as a CSS
     /// animation or transition or is running `requestAnimationFrame` callbacks. In addition, this
     /// returns true if WebXR content is running. This indicates that the embedding application
@@ -641,6 +673,12 @@ impl Servo {
             .send(EmbedderToConstellationMessage::CreateMemoryReport(snd));
     }
 
+    /// Subscribe to the [`FrameReport`]s of the frames painted from now on, which break
+    /// down the time spent producing each frame and count the frames that were dropped.
+    pub fn subscribe_to_frame_reports(&self) -> Receiver<FrameReport> {
+        self.compositor.borrow_mut().subscribe_to_frame_reports()
+    }
+
     pub fn start_shutting_down(&self) {
         if self.shutdown_state.get() != ShutdownState::NotShuttingDown {
             warn!("Requested shutdown while already shutting down");
@@ -781,11 +819,48 @@ impl Servo {
                         .get_text(webview, StringRequest::from(result_sender));
                 }
             },
+            EmbedderMsg::GetClipboardHtml(webview_id, result_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .clipboard_delegate()
+                        .get_html(webview, StringRequest::from(result_sender));
+                }
+            },
+            EmbedderMsg::GetClipboardImage(webview_id, result_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .clipboard_delegate()
+                        .get_image(webview, ImageRequest::from(result_sender));
+                }
+            },
             EmbedderMsg::SetClipboardText(webview_id, string) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.clipboard_delegate().set_text(webview, string);
                 }
             },
+            EmbedderMsg::SetClipboardContents(webview_id, contents) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.clipboard_delegate().set_contents(webview, contents);
+                }
+            },
+            EmbedderMsg::CheckSpelling(webview_id, text, result_sender) => {
+                let misspellings = self
+                    .get_webview_handle(webview_id)
+                    .map(|webview| webview.spellcheck_delegate().check_spelling(webview, &text))
+                    .unwrap_or_default();
+                let _ = result_sender.send(misspellings);
+            },
+            EmbedderMsg::GetSpellingSuggestions(webview_id, word, result_sender) => {
+                let suggestions = self
+                    .get_webview_handle(webview_id)
+                    .map(|webview| {
+                        webview
+                            .spellcheck_delegate()
+                            .spelling_suggestions(webview, &word)
+                    })
+                    .unwrap_or_default();
+                let _ = result_sender.send(suggestions);
+            },
             EmbedderMsg::SetCursor(webview_id, cursor) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.set_cursor(cursor);
@@ -796,11 +871,26 @@ impl Servo {
                     webview.set_favicon_url(url.into_url());
                 }
             },
+            EmbedderMsg::FaviconLoaded(webview_id, url, favicon) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.set_favicon(url.into_url(), favicon);
+                }
+            },
+            EmbedderMsg::NotifyPaintMetricsChanged(webview_id, paint_metrics) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.set_paint_metrics(paint_metrics);
+                }
+            },
             EmbedderMsg::NotifyLoadStatusChanged(webview_id, load_status) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.set_load_status(load_status);
                 }
             },
+            EmbedderMsg::NotifyLoadProgressChanged(webview_id, load_progress) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.set_load_progress(load_progress);
+                }
+            },
             EmbedderMsg::HistoryChanged(webview_id, urls, current_index) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     let urls: Vec<_> = urls.into_iter().map(ServoUrl::into_url).collect();
@@ -813,6 +903,11 @@ impl Servo {
                 }
             },
             EmbedderMsg::NotifyFullscreenStateChanged(webview_id, fullscreen) => {
+                // The fullscreen element fills the viewport, so any pinch zoom that shows
+                // only a part of the viewport is undone.
+                if fullscreen {
+                    self.compositor.borrow_mut().reset_pinch_zoom(webview_id);
+                }
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview
                         .delegate()
@@ -844,37 +939,78 @@ impl Servo {
                     self.delegate().load_web_resource(web_resource_load);
                 }
             },
-            EmbedderMsg::Panic(webview_id, reason, backtrace) => {
+            EmbedderMsg::Panic(webview_id, crash_details) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().notify_crashed(webview, crash_details);
+                }
+            },
+            EmbedderMsg::SelectBluetoothDevice(webview_id, origin, devices, response_sender) => {
+                let request = BluetoothDeviceSelectionRequest::new(
+                    origin,
+                    devices,
+                    response_sender,
+                    self.servo_errors.sender(),
+                );
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview
                         .delegate()
-                        .notify_crashed(webview, reason, backtrace);
+                        .request_bluetooth_device(webview, request);
                 }
             },
-            EmbedderMsg::GetSelectedBluetoothDevice(webview_id, items, response_sender) => {
+            EmbedderMsg::SelectFiles(
+                webview_id,
+                filter_patterns,
+                allow_select_multiple,
+                response_sender,
+            ) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    webview.delegate().show_bluetooth_device_dialog(
+                    webview.delegate().show_file_selection_dialog(
                         webview,
-                        items,
+                        filter_patterns,
+                        allow_select_multiple,
                         response_sender,
                     );
                 }
             },
-            EmbedderMsg::SelectFiles(
+            EmbedderMsg::SelectDirectory(webview_id, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .show_directory_selection_dialog(webview, response_sender);
+                }
+            },
+            EmbedderMsg::GetDroppedFiles(webview_id, response_sender) => {
+                let dropped_files = self
+                    .get_webview_handle(webview_id)
+                    .map(|webview| webview.take_dropped_files())
+                    .unwrap_or_default();
+                let _ = response_sender.send(dropped_files);
+            },
+            EmbedderMsg::StartDrag(webview_id, data) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().start_drag(webview, data);
+                }
+            },
+            EmbedderMsg::SelectFileToSave(
                 webview_id,
                 filter_patterns,
-                allow_select_multiple,
+                suggested_name,
                 response_sender,
             ) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    webview.delegate().show_file_selection_dialog(
+                    webview.delegate().show_file_save_dialog(
                         webview,
                         filter_patterns,
-                        allow_select_multiple,
+                        suggested_name,
                         response_sender,
                     );
                 }
             },
+            EmbedderMsg::ContentBlocked(webview_id, url) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.notify_content_blocked(url.into_url());
+                }
+            },
             EmbedderMsg::RequestAuthentication(webview_id, url, for_proxy, response_sender) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     let authentication_request = AuthenticationRequest::new(
@@ -888,16 +1024,19 @@ impl Servo {
                         .request_authentication(webview, authentication_request);
                 }
             },
-            EmbedderMsg::PromptPermission(webview_id, requested_feature, response_sender) => {
+            EmbedderMsg::PromptPermission(
+                webview_id,
+                origin,
+                requested_feature,
+                response_sender,
+            ) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    let permission_request = PermissionRequest {
+                    let permission_request = PermissionRequest::new(
                         requested_feature,
-                        allow_deny_request: AllowOrDenyRequest::new(
-                            response_sender,
-                            AllowOrDeny::Deny,
-                            self.servo_errors.sender(),
-                        ),
-                    };
+                        origin,
+                        response_sender,
+                        self.servo_errors.sender(),
+                    );
                     webview
                         .delegate()
                         .request_permission(webview, permission_request);
@@ -919,6 +1058,11 @@ impl Servo {
                     webview.delegate().hide_ime(webview);
                 }
             },
+            EmbedderMsg::UpdateIMECursorArea(webview_id, position) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().update_ime_cursor_area(webview, position);
+                }
+            },
             EmbedderMsg::ReportProfile(_items) => {},
             EmbedderMsg::MediaSessionEvent(webview_id, media_session_event) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
@@ -975,16 +1119,115 @@ impl Servo {
                     None => self.delegate().show_notification(notification),
                 }
             },
+            EmbedderMsg::SubscribeToPush(scope, application_server_key, response_sender) => {
+                let request = PushSubscriptionRequest::new(
+                    scope.into_url(),
+                    application_server_key,
+                    response_sender,
+                    self.servo_errors.sender(),
+                );
+                self.delegate().request_push_subscription(request);
+            },
+            EmbedderMsg::GetPushSubscription(scope, response_sender) => {
+                let subscription = self.delegate().push_subscription(scope.as_url());
+                if let Err(error) = response_sender.send(subscription) {
+                    warn!("Failed to send push subscription response: {error}");
+                }
+            },
+            EmbedderMsg::UnsubscribeFromPush(scope, response_sender) => {
+                let unsubscribed = self.delegate().unsubscribe_from_push(scope.as_url());
+                if let Err(error) = response_sender.send(unsubscribed) {
+                    warn!("Failed to send push unsubscription response: {error}");
+                }
+            },
+            EmbedderMsg::CreatePublicKeyCredential(webview_id, parameters, response_sender) => {
+                let request = PublicKeyCredentialCreationRequest::new(
+                    parameters,
+                    response_sender,
+                    self.servo_errors.sender(),
+                );
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .request_public_key_credential_creation(webview, request);
+                }
+            },
+            EmbedderMsg::GetPublicKeyCredential(webview_id, parameters, response_sender) => {
+                let request = PublicKeyCredentialAssertionRequest::new(
+                    parameters,
+                    response_sender,
+                    self.servo_errors.sender(),
+                );
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .request_public_key_credential_assertion(webview, request);
+                }
+            },
+            EmbedderMsg::RequestPictureInPicture(webview_id, video_size, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    let request = PictureInPictureRequest::new(
+                        webview.clone(),
+                        video_size,
+                        response_sender,
+                        self.servo_errors.sender(),
+                    );
+                    webview
+                        .delegate()
+                        .request_picture_in_picture(webview, request);
+                }
+            },
+            EmbedderMsg::ExitPictureInPicture(webview_id) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.stop_painting_picture_in_picture();
+                    webview.delegate().notify_picture_in_picture_exited(webview);
+                }
+            },
+            EmbedderMsg::RequestGeolocationPosition(webview_id, high_accuracy, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    let request = GeolocationRequest::new(
+                        high_accuracy,
+                        response_sender,
+                        self.servo_errors.sender(),
+                    );
+                    self.geolocation_provider()
+                        .request_position(webview, request);
+                }
+            },
+            EmbedderMsg::WatchGeolocationPosition(webview_id, watch_id, high_accuracy, sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    let active = Rc::new(Cell::new(true));
+                    self.geolocation_watches
+                        .borrow_mut()
+                        .insert(watch_id, active.clone());
+                    let watch = GeolocationWatch::new(high_accuracy, sender, active);
+                    self.geolocation_provider().watch_position(webview, watch);
+                }
+            },
+            EmbedderMsg::ClearGeolocationWatch(_, watch_id) => {
+                if let Some(active) = self.geolocation_watches.borrow_mut().remove(&watch_id) {
+                    active.set(false);
+                }
+            },
+            EmbedderMsg::FindInPageResult(webview_id, result) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .notify_find_in_page_result(webview, result);
+                }
+            },
             EmbedderMsg::ShowFormControl(webview_id, position, form_control) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     let form_control = match form_control {
                         EmbedderFormControl::SelectElement(
                             options,
-                            selected_option,
+                            selected_options,
+                            allow_select_multiple,
                             ipc_sender,
                         ) => FormControl::SelectElement(SelectElement::new(
                             options,
-                            selected_option,
+                            selected_options,
+                            allow_select_multiple,
                             position,
                             ipc_sender,
                         )),
@@ -996,11 +1239,27 @@ impl Servo {
                                 self.servo_errors.sender(),
                             ))
                         },
+                        EmbedderFormControl::DateTimePicker(input_type, values, ipc_sender) => {
+                            FormControl::DateTimePicker(DateTimePicker::new(
+                                input_type,
+                                values,
+                                position,
+                                ipc_sender,
+                                self.servo_errors.sender(),
+                            ))
+                        },
                     };
 
                     webview.delegate().show_form_control(webview, form_control);
                 }
             },
+            EmbedderMsg::ShowValidationMessage(webview_id, position, message) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .show_validation_message(webview, position, message);
+                }
+            },
             EmbedderMsg::GetWindowRect(webview_id, response_sender) => {
                 let window_rect = || {
                     let Some(webview) = self.get_webview_handle(webview_id) else {
@@ -1018,6 +1277,16 @@ impl Servo {
                     warn!("Failed to respond to GetWindowRect: {error}");
                 }
             },
+            EmbedderMsg::GetErrorPage(webview_id, url, error, response_sender) => {
+                let error_page = self.get_webview_handle(webview_id).and_then(|webview| {
+                    webview
+                        .delegate()
+                        .error_page(webview, url.into_url(), error)
+                });
+                if let Err(error) = response_sender.send(error_page) {
+                    warn!("Failed to respond to GetErrorPage: {error}");
+                }
+            },
             EmbedderMsg::GetScreenMetrics(webview_id, response_sender) => {
                 let screen_metrics = || {
                     let Some(webview) = self.get_webview_handle(webview_id) else {
@@ -1046,6 +1315,67 @@ impl Servo {
         }
     }
 
+    /// Clear the local storage, session storage and IndexedDB databases of the origin of
+    /// `url`, or of every origin when it is `None`. When `since` is given, only the origins
+    /// whose data was modified at or after that time are cleared.
+    pub fn clear_site_data(&self, url: Option<Url>, since: Option<SystemTime>) {
+        let origin = url.map(|url| ServoUrl::from_url(url).origin());
+        self.constellation_proxy
+            .send(EmbedderToConstellationMessage::ClearSiteData(origin, since));
+    }
+
+    /// Block the requests and hide the elements matched by the given filter lists, which are
+    /// the contents of files in the Adblock Plus format, such as EasyList. This replaces the
+    /// filter lists that were set before, and an empty list disables content blocking.
+    pub fn set_content_blocking_filter_lists(&self, filter_lists: Vec<String>) {
+        self.constellation_proxy
+            .send(EmbedderToConstellationMessage::SetContentBlockingFilterLists(filter_lists));
+    }
+
+    /// Fetch the resource at `url` on behalf of the embedder, outside of any `WebView` and
+    /// without credentials, and call `callback` on a background thread with the body of the
+    /// response, or with an error if the fetch failed or the response status was not ok.
+    pub fn fetch(&self, url: Url, callback: impl FnOnce(Result<Vec<u8>, String>) + Send + 'static) {
+        let url = ServoUrl::from_url(url);
+        let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+            .origin(url.origin())
+            .credentials_mode(CredentialsMode::Omit);
+
+        let mut callback = Some(callback);
+        let mut body = Vec::new();
+        let mut error = None;
+        fetch_async(
+            &self.core_resource_thread,
+            request,
+            None,
+            Box::new(move |message| match message {
+                FetchResponseMsg::ProcessResponse(_, Ok(metadata)) => {
+                    let status = &metadata.metadata().status;
+                    if !status.is_success() {
+                        error = Some(format!("HTTP status {}", status.raw_code()));
+                    }
+                },
+                FetchResponseMsg::ProcessResponse(_, Err(network_error)) => {
+                    error = Some(format!("{network_error:?}"));
+                },
+                FetchResponseMsg::ProcessResponseChunk(_, chunk) => body.extend(chunk),
+                FetchResponseMsg::ProcessResponseEOF(_, result) => {
+                    let Some(callback) = callback.take() else {
+                        return;
+                    };
+                    let error = error
+                        .take()
+                        .or_else(|| result.err().map(|error| format!("{error:?}")));
+                    callback(match error {
+                        Some(error) => Err(error),
+                        None => Ok(std::mem::take(&mut body)),
+                    });
+                },
+                _ => {},
+            }),
+        );
+    }
+
     pub fn constellation_sender(&self) -> Sender<EmbedderToConstellationMessage> {
         self.constellation_proxy.sender()
     }
@@ -1111,7 +1441,7 @@ fn create_constellation(
     #[cfg(feature = "webgpu")] wgpu_image_map: WGPUImageMap,
     protocols: ProtocolRegistry,
     user_content_manager: UserContentManager,
-) -> Sender<EmbedderToConstellationMessage> {
+) -> (Sender<EmbedderToConstellationMessage>, CoreResourceThread) {
     // Global configuration options, parsed from the command line.
     let opts = opts::get();
 
@@ -1129,6 +1459,7 @@ fn create_constellation(
         opts.ignore_certificate_errors,
         Arc::new(protocols),
     );
+    let core_resource_thread = public_resource_threads.core_thread.clone();
 
     let system_font_service = Arc::new(
         SystemFontService::spawn(
@@ -1164,13 +1495,15 @@ fn create_constellation(
 
     let layout_factory = Arc::new(LayoutFactoryImpl());
 
-    Constellation::<script::ScriptThread, script::ServiceWorkerManager>::start(
-        initial_state,
-        layout_factory,
-        opts.random_pipeline_closure_probability,
-        opts.random_pipeline_closure_seed,
-        opts.hard_fail,
-    )
+    let constellation_chan =
+        Constellation::<script::ScriptThread, script::ServiceWorkerManager>::start(
+            initial_state,
+            layout_factory,
+            opts.random_pipeline_closure_probability,
+            opts.random_pipeline_closure_seed,
+            opts.hard_fail,
+        );
+    (constellation_chan, core_resource_thread)
 }
 
 // A logger that logs to two downstream loggers.
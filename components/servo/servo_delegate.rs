@@ -2,10 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use embedder_traits::Notification;
+use embedder_traits::{Notification, PushSubscription};
+use url::Url;
 
 use crate::Servo;
-use crate::webview_delegate::{AllowOrDenyRequest, WebResourceLoad};
+use crate::webview_delegate::{AllowOrDenyRequest, PushSubscriptionRequest, WebResourceLoad};
 
 #[derive(Debug)]
 pub enum ServoError {
@@ -44,6 +45,20 @@ pub trait ServoDelegate {
 
     /// Request to display a notification.
     fn show_notification(&self, _notification: Notification) {}
+
+    /// Request to subscribe a service worker registration to the push service used by the
+    /// embedder. If not handled, the subscription fails because no push service is available.
+    fn request_push_subscription(&self, _request: PushSubscriptionRequest) {}
+    /// Get the push subscription of the service worker registration with the given `scope`,
+    /// if the embedder's push service has one.
+    fn push_subscription(&self, _scope: &Url) -> Option<PushSubscription> {
+        None
+    }
+    /// Remove the push subscription of the service worker registration with the given `scope`
+    /// from the embedder's push service, returning whether there was one to remove.
+    fn unsubscribe_from_push(&self, _scope: &Url) -> bool {
+        false
+    }
 }
 
 pub(crate) struct DefaultServoDelegate;
@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use std::{fs, io};
+
+use crate::WebView;
+
+/// The maximum number of replacements returned by [`WordListSpellChecker`] for a single word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The maximum number of single character edits between a misspelled word and the words
+/// that [`WordListSpellChecker`] suggests as its replacement.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// A delegate that is responsible for spellchecking the contents of editable text in a
+/// [`WebView`]. By default no text is flagged as misspelled. An embedding application can
+/// provide its own dictionary backend, such as a system spellchecker, by implementing
+/// this trait, or use the word list based [`WordListSpellChecker`].
+pub trait SpellCheckDelegate {
+    /// Check the spelling of `text`, returning the UTF-8 byte ranges of the words in it that
+    /// are misspelled.
+    fn check_spelling(&self, _webview: WebView, _text: &str) -> Vec<Range<usize>> {
+        Vec::new()
+    }
+
+    /// Get the suggested replacements for the misspelled `word`, ordered from the most to
+    /// the least likely one.
+    fn spelling_suggestions(&self, _webview: WebView, _word: &str) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+pub(crate) struct DefaultSpellCheckDelegate;
+
+impl SpellCheckDelegate for DefaultSpellCheckDelegate {}
+
+/// A pure Rust spellchecker that accepts the words of a single dictionary. A word is
+/// considered misspelled when neither it nor its lowercase form is in the dictionary, and
+/// replacements are suggested from the dictionary words that are the fewest edits away.
+pub struct WordListSpellChecker {
+    words: HashSet<String>,
+}
+
+impl WordListSpellChecker {
+    /// Create a spellchecker from a list of words. Lines in the format of a Hunspell `.dic`
+    /// file are accepted as well: the affix flags after a `/` are ignored, as is a leading
+    /// line with the number of words.
+    pub fn from_word_list(word_list: &str) -> Self {
+        let words = word_list
+            .lines()
+            .filter_map(|line| line.split(['/', '\t']).next())
+            .map(str::trim)
+            .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+            .map(String::from)
+            .collect();
+        Self { words }
+    }
+
+    /// Create a spellchecker from the dictionary file at `path`, in the format accepted by
+    /// [`WordListSpellChecker::from_word_list`].
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        Ok(Self::from_word_list(&fs::read_to_string(path)?))
+    }
+
+    fn is_correct(&self, word: &str) -> bool {
+        // Words with digits in them are usually identifiers or codes rather than prose.
+        if word.chars().count() < 2 || word.chars().any(|c| c.is_numeric()) {
+            return true;
+        }
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+}
+
+impl SpellCheckDelegate for WordListSpellChecker {
+    fn check_spelling(&self, _webview: WebView, text: &str) -> Vec<Range<usize>> {
+        words(text)
+            .filter(|range| !self.is_correct(&text[range.clone()]))
+            .collect()
+    }
+
+    fn spelling_suggestions(&self, _webview: WebView, word: &str) -> Vec<String> {
+        let word: Vec<char> = word.to_lowercase().chars().collect();
+        let mut suggestions: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            .filter(|candidate| {
+                candidate.chars().count().abs_diff(word.len()) <= MAX_SUGGESTION_DISTANCE
+            })
+            .filter_map(|candidate| {
+                let characters: Vec<char> = candidate.to_lowercase().chars().collect();
+                let distance = edit_distance(&word, &characters);
+                (distance <= MAX_SUGGESTION_DISTANCE).then_some((distance, candidate))
+            })
+            .collect();
+        suggestions.sort();
+        suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate.clone())
+            .collect()
+    }
+}
+
+/// The UTF-8 byte ranges of the words in `text`: runs of alphabetic characters, which may
+/// contain apostrophes between their letters.
+fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let is_word_character = |character: char| character.is_alphabetic();
+    let mut characters = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (start, _) = characters.find(|&(_, character)| is_word_character(character))?;
+        let mut end = text.len();
+        while let Some(&(index, character)) = characters.peek() {
+            if is_word_character(character) {
+                characters.next();
+                continue;
+            }
+            let continues_after_apostrophe = matches!(character, '\'' | '’') &&
+                text[index + character.len_utf8()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_word_character);
+            if !continues_after_apostrophe {
+                end = index;
+                break;
+            }
+            characters.next();
+        }
+        Some(start..end)
+    })
+}
+
+/// The optimal string alignment distance between two words: the number of character
+/// insertions, deletions, substitutions and transpositions of adjacent characters needed
+/// to turn one into the other.
+fn edit_distance(first: &[char], second: &[char]) -> usize {
+    let mut distances = vec![vec![0; second.len() + 1]; first.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=first.len() {
+        for j in 1..=second.len() {
+            let cost = usize::from(first[i - 1] != second[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && first[i - 1] == second[j - 2] && first[i - 2] == second[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[first.len()][second.len()]
+}
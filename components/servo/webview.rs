@@ -4,26 +4,31 @@
 
 use std::cell::{Ref, RefCell, RefMut};
 use std::hash::Hash;
+use std::path::PathBuf;
 use std::rc::{Rc, Weak};
 use std::time::Duration;
 
 use base::id::WebViewId;
 use compositing::IOCompositor;
 use compositing_traits::WebViewTrait;
+use compositing_traits::rendering_context::RenderingContext;
 use constellation_traits::{EmbedderToConstellationMessage, TraversalDirection};
 use dpi::PhysicalSize;
 use embedder_traits::{
-    Cursor, InputEvent, JSValue, JavaScriptEvaluationError, LoadStatus, MediaSessionActionType,
-    ScreenGeometry, Theme, ViewportDetails,
+    Cursor, DragData, ExternalDragEvent, ExternalDragEventType, Favicon, FindInPageRequest,
+    InputEvent, JSValue, JavaScriptEvaluationError, LoadProgress, LoadStatus,
+    MediaSessionActionType, PaintMetrics, ScreenGeometry, Theme, UserAgentStylesheetOverride,
+    ViewportDetails,
 };
 use euclid::{Point2D, Scale, Size2D};
 use servo_geometry::DeviceIndependentPixel;
 use url::Url;
 use webrender_api::ScrollLocation;
-use webrender_api::units::{DeviceIntPoint, DevicePixel, DeviceRect};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePixel, DevicePoint, DeviceRect};
 
 use crate::clipboard_delegate::{ClipboardDelegate, DefaultClipboardDelegate};
 use crate::javascript_evaluator::JavaScriptEvaluator;
+use crate::spellcheck_delegate::{DefaultSpellCheckDelegate, SpellCheckDelegate};
 use crate::webview_delegate::{DefaultWebViewDelegate, WebViewDelegate};
 use crate::{ConstellationProxy, Servo, WebRenderDebugOption};
 
@@ -47,7 +52,7 @@ use crate::{ConstellationProxy, Servo, WebRenderDebugOption};
 /// 2. During window repainting, the application calls [`WebView::paint`] and the contents of the
 ///    [`RenderingContext`][crate::RenderingContext] are updated.
 /// 3. If the [`RenderingContext`][crate::RenderingContext] is double-buffered, the
-///    application then calls [`crate::RenderingContext::present()`] in order to swap the back buffer
+///    application then calls [`WebView::present`] in order to swap the back buffer
 ///    to the front, finally displaying the updated [`WebView`] contents.
 ///
 /// In cases where the [`WebView`] contents have not been updated, but a repaint is necessary, for
@@ -76,6 +81,7 @@ pub(crate) struct WebViewInner {
     pub(crate) compositor: Rc<RefCell<IOCompositor>>,
     pub(crate) delegate: Rc<dyn WebViewDelegate>,
     pub(crate) clipboard_delegate: Rc<dyn ClipboardDelegate>,
+    pub(crate) spellcheck_delegate: Rc<dyn SpellCheckDelegate>,
     javascript_evaluator: Rc<RefCell<JavaScriptEvaluator>>,
 
     rect: DeviceRect,
@@ -85,9 +91,20 @@ pub(crate) struct WebViewInner {
     status_text: Option<String>,
     page_title: Option<String>,
     favicon_url: Option<Url>,
+    /// The decoded icon at [`Self::favicon_url`], once it has been loaded.
+    favicon: Option<Favicon>,
+    load_progress: LoadProgress,
+    paint_metrics: PaintMetrics,
+    /// The number of requests of the current page blocked by the content blocker.
+    blocked_content_count: usize,
     focused: bool,
     animating: bool,
     cursor: Cursor,
+    /// The files from the most recent drop of [`WebView::notify_external_drag_event`],
+    /// which are handed to the file manager once content asks for them.
+    dropped_files: Vec<PathBuf>,
+    /// Whether this [`WebView`] uses the ephemeral private browsing partition.
+    private: bool,
 }
 
 impl Drop for WebViewInner {
@@ -119,6 +136,7 @@ impl WebView {
             compositor: servo.compositor.clone(),
             delegate: builder.delegate,
             clipboard_delegate: Rc::new(DefaultClipboardDelegate),
+            spellcheck_delegate: Rc::new(DefaultSpellCheckDelegate),
             javascript_evaluator: servo.javascript_evaluator.clone(),
             rect: DeviceRect::from_origin_and_size(Point2D::origin(), size),
             hidpi_scale_factor: builder.hidpi_scale_factor,
@@ -127,9 +145,15 @@ impl WebView {
             status_text: None,
             page_title: None,
             favicon_url: None,
+            favicon: None,
+            load_progress: LoadProgress::default(),
+            paint_metrics: PaintMetrics::default(),
+            blocked_content_count: 0,
             focused: false,
             animating: false,
             cursor: Cursor::Pointer,
+            dropped_files: Vec::new(),
+            private: builder.private,
         })));
 
         let viewport_details = webview.viewport_details();
@@ -158,6 +182,7 @@ impl WebView {
                     url.into(),
                     webview.id(),
                     viewport_details,
+                    builder.private,
                 ));
         }
 
@@ -207,10 +232,25 @@ impl WebView {
         self.inner_mut().clipboard_delegate = delegate;
     }
 
+    pub fn spellcheck_delegate(&self) -> Rc<dyn SpellCheckDelegate> {
+        self.inner().spellcheck_delegate.clone()
+    }
+
+    pub fn set_spellcheck_delegate(&self, delegate: Rc<dyn SpellCheckDelegate>) {
+        self.inner_mut().spellcheck_delegate = delegate;
+    }
+
     pub fn id(&self) -> WebViewId {
         self.inner().id
     }
 
+    /// Whether this [`WebView`] is in private browsing mode. Its cookies, cache and storage
+    /// are kept apart from those of other [`WebView`]s and are discarded once the last
+    /// private [`WebView`] is closed. Embedders should not record its history.
+    pub fn is_private(&self) -> bool {
+        self.inner().private
+    }
+
     pub fn load_status(&self) -> LoadStatus {
         self.inner().load_status
     }
@@ -220,9 +260,58 @@ impl WebView {
             return;
         }
         self.inner_mut().load_status = new_value;
+        if new_value == LoadStatus::Started {
+            self.inner_mut().load_progress = LoadProgress::default();
+            self.inner_mut().paint_metrics = PaintMetrics::default();
+            self.inner_mut().blocked_content_count = 0;
+            self.inner_mut().favicon_url = None;
+            if self.inner_mut().favicon.take().is_some() {
+                self.delegate().notify_favicon_changed(self.clone());
+            }
+        }
         self.delegate().notify_load_status_changed(self, new_value);
     }
 
+    /// The progress of the load of the page in this [`WebView`], which is only updated while
+    /// it is loading.
+    pub fn load_progress(&self) -> LoadProgress {
+        self.inner().load_progress
+    }
+
+    pub(crate) fn set_load_progress(self, new_value: LoadProgress) {
+        if self.inner().load_progress == new_value {
+            return;
+        }
+        self.inner_mut().load_progress = new_value;
+        self.delegate()
+            .notify_load_progress_changed(self, new_value);
+    }
+
+    /// The paint timing milestones reached so far by the page loaded in this [`WebView`].
+    pub fn paint_metrics(&self) -> PaintMetrics {
+        self.inner().paint_metrics
+    }
+
+    pub(crate) fn set_paint_metrics(self, new_value: PaintMetrics) {
+        if self.inner().paint_metrics == new_value {
+            return;
+        }
+        self.inner_mut().paint_metrics = new_value;
+        self.delegate()
+            .notify_paint_metrics_changed(self, new_value);
+    }
+
+    /// The number of requests of the page loaded in this [`WebView`] that were blocked by
+    /// the content blocking filter lists.
+    pub fn blocked_content_count(&self) -> usize {
+        self.inner().blocked_content_count
+    }
+
+    pub(crate) fn notify_content_blocked(self, url: Url) {
+        self.inner_mut().blocked_content_count += 1;
+        self.delegate().notify_content_blocked(self, url);
+    }
+
     pub fn url(&self) -> Option<Url> {
         self.inner().url.clone()
     }
@@ -281,6 +370,26 @@ impl WebView {
         self.delegate().notify_favicon_url_changed(self, new_value);
     }
 
+    /// The decoded favicon of the currently loaded page, if it has been loaded.
+    pub fn favicon(&self) -> Option<Favicon> {
+        self.inner().favicon.clone()
+    }
+
+    pub(crate) fn set_favicon(self, url: Url, favicon: Favicon) {
+        // Ignore icons that finished loading after the page switched to another icon.
+        if self
+            .inner()
+            .favicon_url
+            .as_ref()
+            .is_some_and(|favicon_url| favicon_url != &url)
+        {
+            return;
+        }
+        self.clone().set_favicon_url(url);
+        self.inner_mut().favicon = Some(favicon);
+        self.delegate().notify_favicon_changed(self);
+    }
+
     pub fn focused(&self) -> bool {
         self.inner().focused
     }
@@ -401,6 +510,34 @@ impl WebView {
             ))
     }
 
+    /// Apply `stylesheet_override` at the user agent origin of all documents of this
+    /// [`WebView`], or go back to the default user agent stylesheet if it is `None`.
+    /// Documents that are already loaded are restyled right away.
+    pub fn set_user_agent_stylesheet_override(
+        &self,
+        stylesheet_override: Option<UserAgentStylesheetOverride>,
+    ) {
+        self.inner().constellation_proxy.send(
+            EmbedderToConstellationMessage::SetUserAgentStylesheetOverride(
+                self.id(),
+                stylesheet_override,
+            ),
+        )
+    }
+
+    /// Apply `user_stylesheets` at the user origin of all documents of this [`WebView`],
+    /// replacing the ones that were set before. Like the stylesheets of the user of other
+    /// browsers, their `!important` declarations win over those of the page. Documents that
+    /// are already loaded are restyled right away.
+    pub fn set_user_stylesheets(&self, user_stylesheets: Vec<String>) {
+        self.inner()
+            .constellation_proxy
+            .send(EmbedderToConstellationMessage::SetUserStylesheets(
+                self.id(),
+                user_stylesheets,
+            ))
+    }
+
     pub fn load(&self, url: Url) {
         self.inner()
             .constellation_proxy
@@ -437,13 +574,27 @@ impl WebView {
     /// Ask the [`WebView`] to scroll web content. Note that positive scroll offsets reveal more
     /// content on the bottom and right of the page.
     pub fn notify_scroll_event(&self, location: ScrollLocation, point: DeviceIntPoint) {
-        self.inner()
-            .compositor
-            .borrow_mut()
-            .notify_scroll_event(self.id(), location, point);
+        let mut compositor = self.inner().compositor.borrow_mut();
+        compositor.stop_largest_contentful_paint_updates(self.id());
+        compositor.notify_scroll_event(self.id(), location, point);
     }
 
     pub fn notify_input_event(&self, event: InputEvent) {
+        // Like in other browsers, the largest contentful paint is only looked for until the
+        // user interacts with the page.
+        if matches!(
+            event,
+            InputEvent::Keyboard(_) |
+                InputEvent::MouseButton(_) |
+                InputEvent::Touch(_) |
+                InputEvent::Wheel(_)
+        ) {
+            self.inner()
+                .compositor
+                .borrow_mut()
+                .stop_largest_contentful_paint_updates(self.id());
+        }
+
         // Events with a `point` first go to the compositor for hit testing.
         if event.point().is_some() {
             self.inner()
@@ -462,6 +613,34 @@ impl WebView {
             ))
     }
 
+    /// Notify this [`WebView`] that `data` from outside of it, such as files from the file
+    /// manager of the operating system, was dragged over it, left it or was dropped onto it
+    /// at `point`. The page can accept the data in `dragover` and `drop` event handlers, and
+    /// `<input type=file>` elements accept dropped files by default.
+    pub fn notify_external_drag_event(
+        &self,
+        event_type: ExternalDragEventType,
+        point: DevicePoint,
+        mut data: DragData,
+    ) {
+        if event_type == ExternalDragEventType::Drop {
+            self.inner_mut().dropped_files = data.files.clone();
+        }
+        data.files = data
+            .files
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(PathBuf::from)
+            .collect();
+        self.notify_input_event(InputEvent::ExternalDrag(ExternalDragEvent::new(
+            event_type, point, data,
+        )));
+    }
+
+    pub(crate) fn take_dropped_files(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.inner_mut().dropped_files)
+    }
+
     pub fn notify_media_session_action_event(&self, event: MediaSessionActionType) {
         self.inner()
             .constellation_proxy
@@ -506,6 +685,67 @@ impl WebView {
             .send(EmbedderToConstellationMessage::ExitFullScreen(self.id()));
     }
 
+    /// Find text in the page of this [`WebView`]. The matches are highlighted and the active
+    /// match is scrolled into view. Repeating a request with the same text moves to the
+    /// next or the previous match. The result is passed to
+    /// [`WebViewDelegate::notify_find_in_page_result`].
+    pub fn find_in_page(&self, request: FindInPageRequest) {
+        self.inner()
+            .constellation_proxy
+            .send(EmbedderToConstellationMessage::FindInPage(
+                self.id(),
+                Some(request),
+            ));
+    }
+
+    /// Stop finding text in the page of this [`WebView`], which removes the highlights of
+    /// the matches.
+    pub fn stop_finding_in_page(&self) {
+        self.inner()
+            .constellation_proxy
+            .send(EmbedderToConstellationMessage::FindInPage(self.id(), None));
+    }
+
+    /// Notify Servo that the user closed the picture-in-picture window of this [`WebView`].
+    /// Servo stops painting to it, and its video leaves picture-in-picture.
+    pub fn exit_picture_in_picture(&self) {
+        self.stop_painting_picture_in_picture();
+        self.inner().constellation_proxy.send(
+            EmbedderToConstellationMessage::ExitPictureInPicture(self.id()),
+        );
+    }
+
+    /// Notify Servo that the picture-in-picture window of this [`WebView`] was resized.
+    pub fn resize_picture_in_picture(&self, new_size: PhysicalSize<u32>) {
+        self.inner()
+            .compositor
+            .borrow_mut()
+            .resize_picture_in_picture(self.id(), new_size);
+        self.inner().constellation_proxy.send(
+            EmbedderToConstellationMessage::PictureInPictureWindowResized(
+                self.id(),
+                DeviceIntSize::new(new_size.width as i32, new_size.height as i32),
+            ),
+        );
+    }
+
+    pub(crate) fn start_painting_picture_in_picture(
+        &self,
+        rendering_context: Rc<dyn RenderingContext>,
+    ) {
+        self.inner()
+            .compositor
+            .borrow_mut()
+            .enter_picture_in_picture(self.id(), rendering_context);
+    }
+
+    pub(crate) fn stop_painting_picture_in_picture(&self) {
+        self.inner()
+            .compositor
+            .borrow_mut()
+            .exit_picture_in_picture(self.id());
+    }
+
     pub fn set_throttled(&self, throttled: bool) {
         self.inner()
             .constellation_proxy
@@ -551,6 +791,13 @@ impl WebView {
         self.inner().compositor.borrow_mut().render()
     }
 
+    /// Present the frame last painted with [`WebView::paint`] by swapping the buffers of
+    /// its `RenderingContext`. This is the same as calling `RenderingContext::present`,
+    /// but also lets Servo include the time spent presenting in its frame reports.
+    pub fn present(&self) {
+        self.inner().compositor.borrow_mut().present()
+    }
+
     /// Evaluate the specified string of JavaScript code. Once execution is complete or an error
     /// occurs, Servo will call `callback`.
     pub fn evaluate_javascript<T: ToString>(
@@ -594,6 +841,7 @@ pub struct WebViewBuilder<'servo> {
     servo: &'servo Servo,
     delegate: Rc<dyn WebViewDelegate>,
     auxiliary: bool,
+    private: bool,
     url: Option<Url>,
     size: Option<PhysicalSize<u32>>,
     hidpi_scale_factor: Scale<f32, DeviceIndependentPixel, DevicePixel>,
@@ -604,6 +852,7 @@ impl<'servo> WebViewBuilder<'servo> {
         Self {
             servo,
             auxiliary: false,
+            private: false,
             url: None,
             size: None,
             hidpi_scale_factor: Scale::new(1.0),
@@ -627,6 +876,14 @@ impl<'servo> WebViewBuilder<'servo> {
         self
     }
 
+    /// Create the [`WebView`] in private browsing mode. Auxiliary [`WebView`]s always
+    /// share the mode of their opener, so this should match the opener's
+    /// [`WebView::is_private`] for them.
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
     pub fn size(mut self, size: PhysicalSize<u32>) -> Self {
         self.size = Some(size);
         self
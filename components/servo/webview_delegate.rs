@@ -3,17 +3,25 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use base::id::PipelineId;
+use compositing_traits::rendering_context::RenderingContext;
 use constellation_traits::EmbedderToConstellationMessage;
 use embedder_traits::{
-    AllowOrDeny, AuthenticationResponse, ContextMenuResult, Cursor, FilterPattern,
-    GamepadHapticEffectType, InputMethodType, KeyboardEvent, LoadStatus, MediaSessionEvent,
-    Notification, PermissionFeature, RgbColor, ScreenGeometry, SelectElementOptionOrOptgroup,
-    SimpleDialog, WebResourceRequest, WebResourceResponse, WebResourceResponseMsg,
+    AllowOrDeny, AuthenticationResponse, AuthenticatorAssertion, AuthenticatorAttestation,
+    BluetoothDeviceDescription, ContextMenuResult, CrashDetails, Cursor, DateTimePickerType,
+    DateTimePickerValues, DragData, FilterPattern, FindInPageResult, GamepadHapticEffectType,
+    InputMethodType, KeyboardEvent, LoadError, LoadProgress, LoadStatus, MediaSessionEvent,
+    Notification, PaintMetrics, PermissionDecision, PermissionFeature,
+    PublicKeyCredentialAssertionParameters, PublicKeyCredentialCreationParameters,
+    PushSubscription, PushSubscriptionError, RgbColor, ScreenGeometry,
+    SelectElementOptionOrOptgroup, SimpleDialog, WebAuthnError, WebResourceRequest,
+    WebResourceResponse, WebResourceResponseMsg,
 };
 use ipc_channel::ipc::IpcSender;
 use serde::Serialize;
+use servo_url::ImmutableOrigin;
 use url::Url;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 
@@ -104,24 +112,65 @@ impl<T: Serialize> Drop for IpcResponder<T> {
 }
 
 /// A permissions request for a [`WebView`] The embedder should allow or deny the request,
-/// either by reading a cached value or querying the user for permission via the user
-/// interface.
+/// usually by querying the user for permission via the user interface. Servo keeps the
+/// decision for the origin until it exits, or in the profile if the user asks to remember
+/// it, so the same origin is not asked for the same feature again. If the request is
+/// dropped without a response, it is denied for this request only.
 pub struct PermissionRequest {
-    pub(crate) requested_feature: PermissionFeature,
-    pub(crate) allow_deny_request: AllowOrDenyRequest,
+    requested_feature: PermissionFeature,
+    origin: ImmutableOrigin,
+    responder: IpcResponder<PermissionDecision>,
+    error_sender: ServoErrorSender,
 }
 
 impl PermissionRequest {
+    pub(crate) fn new(
+        requested_feature: PermissionFeature,
+        origin: ImmutableOrigin,
+        response_sender: IpcSender<PermissionDecision>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        let default_response = PermissionDecision {
+            allow_or_deny: AllowOrDeny::Deny,
+            remember: false,
+        };
+        Self {
+            requested_feature,
+            origin,
+            responder: IpcResponder::new(response_sender, default_response),
+            error_sender,
+        }
+    }
+
     pub fn feature(&self) -> PermissionFeature {
         self.requested_feature
     }
 
+    /// The origin of the content that is requesting the permission.
+    pub fn origin(&self) -> &ImmutableOrigin {
+        &self.origin
+    }
+
+    /// Allow the request, for the rest of this session.
     pub fn allow(self) {
-        self.allow_deny_request.allow();
+        self.respond(AllowOrDeny::Allow, false);
     }
 
+    /// Deny the request, for the rest of this session.
     pub fn deny(self) {
-        self.allow_deny_request.deny();
+        self.respond(AllowOrDeny::Deny, false);
+    }
+
+    /// Allow or deny the request. If `remember` is true, the decision is persisted in the
+    /// profile and also applies to later sessions.
+    pub fn respond(mut self, allow_or_deny: AllowOrDeny, remember: bool) {
+        let decision = PermissionDecision {
+            allow_or_deny,
+            remember,
+        };
+        if let Err(error) = self.responder.send(decision) {
+            self.error_sender.raise_response_send_error(error);
+        }
     }
 }
 
@@ -196,6 +245,246 @@ impl AuthenticationRequest {
     }
 }
 
+/// A request to subscribe the service worker registration with the given scope to a push
+/// service. Servo does not have a push service of its own, so the embedder is expected to
+/// create the subscription with the push service it uses. If not handled, the subscription
+/// fails as if the push service is unavailable.
+pub struct PushSubscriptionRequest {
+    pub(crate) scope: Url,
+    pub(crate) application_server_key: Option<Vec<u8>>,
+    pub(crate) responder: IpcResponder<Result<PushSubscription, PushSubscriptionError>>,
+    pub(crate) error_sender: ServoErrorSender,
+}
+
+impl PushSubscriptionRequest {
+    pub(crate) fn new(
+        scope: Url,
+        application_server_key: Option<Vec<u8>>,
+        response_sender: IpcSender<Result<PushSubscription, PushSubscriptionError>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            scope,
+            application_server_key,
+            responder: IpcResponder::new(
+                response_sender,
+                Err(PushSubscriptionError::PushServiceUnavailable),
+            ),
+            error_sender,
+        }
+    }
+
+    /// The scope URL of the service worker registration that is subscribing.
+    pub fn scope(&self) -> &Url {
+        &self.scope
+    }
+    /// The public key of the application server that will send push messages, if any.
+    pub fn application_server_key(&self) -> Option<&[u8]> {
+        self.application_server_key.as_deref()
+    }
+    /// Respond to the [`PushSubscriptionRequest`] with the subscription that was created.
+    pub fn subscribe(mut self, subscription: PushSubscription) {
+        if let Err(error) = self.responder.send(Ok(subscription)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+    /// Fail the [`PushSubscriptionRequest`] with the given error.
+    pub fn fail(mut self, error: PushSubscriptionError) {
+        if let Err(error) = self.responder.send(Err(error)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A request to create a public key credential, a passkey, for `navigator.credentials.create()`.
+/// The embedder should ask the user for consent and create the credential with one of its
+/// authenticators, such as the passkey store of the platform. If not handled, the request
+/// fails with a `NotAllowedError`.
+pub struct PublicKeyCredentialCreationRequest {
+    parameters: PublicKeyCredentialCreationParameters,
+    responder: IpcResponder<Result<AuthenticatorAttestation, WebAuthnError>>,
+    error_sender: ServoErrorSender,
+}
+
+impl PublicKeyCredentialCreationRequest {
+    pub(crate) fn new(
+        parameters: PublicKeyCredentialCreationParameters,
+        response_sender: IpcSender<Result<AuthenticatorAttestation, WebAuthnError>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            parameters,
+            responder: IpcResponder::new(response_sender, Err(WebAuthnError::NotAllowed)),
+            error_sender,
+        }
+    }
+
+    /// The parameters of the credential to create.
+    pub fn parameters(&self) -> &PublicKeyCredentialCreationParameters {
+        &self.parameters
+    }
+
+    /// Respond with the credential that was created.
+    pub fn respond(mut self, attestation: AuthenticatorAttestation) {
+        if let Err(error) = self.responder.send(Ok(attestation)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+
+    /// Fail the [`PublicKeyCredentialCreationRequest`] with the given error.
+    pub fn fail(mut self, error: WebAuthnError) {
+        if let Err(error) = self.responder.send(Err(error)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A request to sign in with a public key credential, a passkey, for
+/// `navigator.credentials.get()`. The embedder should ask the user to choose one of the
+/// matching credentials of its authenticators and sign the client data hash with it. If not
+/// handled, the request fails with a `NotAllowedError`.
+pub struct PublicKeyCredentialAssertionRequest {
+    parameters: PublicKeyCredentialAssertionParameters,
+    responder: IpcResponder<Result<AuthenticatorAssertion, WebAuthnError>>,
+    error_sender: ServoErrorSender,
+}
+
+impl PublicKeyCredentialAssertionRequest {
+    pub(crate) fn new(
+        parameters: PublicKeyCredentialAssertionParameters,
+        response_sender: IpcSender<Result<AuthenticatorAssertion, WebAuthnError>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            parameters,
+            responder: IpcResponder::new(response_sender, Err(WebAuthnError::NotAllowed)),
+            error_sender,
+        }
+    }
+
+    /// The parameters of the credentials that can be used.
+    pub fn parameters(&self) -> &PublicKeyCredentialAssertionParameters {
+        &self.parameters
+    }
+
+    /// Respond with the signature that the credential made.
+    pub fn respond(mut self, assertion: AuthenticatorAssertion) {
+        if let Err(error) = self.responder.send(Ok(assertion)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+
+    /// Fail the [`PublicKeyCredentialAssertionRequest`] with the given error.
+    pub fn fail(mut self, error: WebAuthnError) {
+        if let Err(error) = self.responder.send(Err(error)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A request to choose the Bluetooth device that content gets access to with
+/// `navigator.bluetooth.requestDevice()`. The embedder should present the devices to the
+/// user, for instance in a chooser dialog. If not handled, no device is chosen and the
+/// request fails with a `NotFoundError`.
+pub struct BluetoothDeviceSelectionRequest {
+    origin: String,
+    devices: Vec<BluetoothDeviceDescription>,
+    responder: IpcResponder<Option<String>>,
+    error_sender: ServoErrorSender,
+}
+
+impl BluetoothDeviceSelectionRequest {
+    pub(crate) fn new(
+        origin: String,
+        devices: Vec<BluetoothDeviceDescription>,
+        response_sender: IpcSender<Option<String>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            origin,
+            devices,
+            responder: IpcResponder::new(response_sender, None),
+            error_sender,
+        }
+    }
+
+    /// The ASCII serialization of the origin of the document that requested the device.
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// The devices that match the filters of the request, which the user can choose from.
+    pub fn devices(&self) -> &[BluetoothDeviceDescription] {
+        &self.devices
+    }
+
+    /// Give content access to the device with the given address, which should be one of
+    /// [`Self::devices`].
+    pub fn select(mut self, address: String) {
+        if let Err(error) = self.responder.send(Some(address)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+
+    /// Cancel the request, which means that no device is chosen.
+    pub fn cancel(mut self) {
+        if let Err(error) = self.responder.send(None) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A request to show a video of a [`WebView`] in a floating picture-in-picture window. The
+/// embedder should open a window and pass its [`RenderingContext`] to
+/// [`PictureInPictureRequest::open`], after which Servo paints the video to it, until
+/// [`WebViewDelegate::notify_picture_in_picture_exited`] is called or the embedder calls
+/// [`WebView::exit_picture_in_picture`]. If not handled, the request is denied.
+pub struct PictureInPictureRequest {
+    pub(crate) webview: WebView,
+    pub(crate) video_size: DeviceIntSize,
+    pub(crate) responder: IpcResponder<Option<DeviceIntSize>>,
+    pub(crate) error_sender: ServoErrorSender,
+}
+
+impl PictureInPictureRequest {
+    pub(crate) fn new(
+        webview: WebView,
+        video_size: DeviceIntSize,
+        response_sender: IpcSender<Option<DeviceIntSize>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            webview,
+            video_size,
+            responder: IpcResponder::new(response_sender, None),
+            error_sender,
+        }
+    }
+
+    /// The natural size of the video, which the embedder can use to choose the size of the
+    /// window.
+    pub fn video_size(&self) -> DeviceIntSize {
+        self.video_size
+    }
+
+    /// Show the video in the window with the given [`RenderingContext`].
+    pub fn open(mut self, rendering_context: Rc<dyn RenderingContext>) {
+        let window_size = rendering_context.size2d().to_i32();
+        self.webview
+            .start_painting_picture_in_picture(rendering_context);
+        if let Err(error) = self.responder.send(Some(window_size)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+
+    /// Deny the [`PictureInPictureRequest`].
+    pub fn deny(mut self) {
+        if let Err(error) = self.responder.send(None) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
 /// Information related to the loading of a web resource. These are created for all HTTP requests.
 /// The client may choose to intercept the load of web resources and send an alternate response
 /// by calling [`WebResourceLoad::intercept`].
@@ -301,26 +590,32 @@ pub enum FormControl {
     SelectElement(SelectElement),
     /// The picker of a `<input type=color>` element.
     ColorPicker(ColorPicker),
+    /// The picker of a `<input type=date>`, `<input type=time>` or
+    /// `<input type=datetime-local>` element.
+    DateTimePicker(DateTimePicker),
 }
 
 /// Represents a dialog triggered by clicking a `<select>` element.
 pub struct SelectElement {
     pub(crate) options: Vec<SelectElementOptionOrOptgroup>,
-    pub(crate) selected_option: Option<usize>,
+    pub(crate) selected_options: Vec<usize>,
+    pub(crate) allow_select_multiple: bool,
     pub(crate) position: DeviceIntRect,
-    pub(crate) responder: IpcResponder<Option<usize>>,
+    pub(crate) responder: IpcResponder<Option<Vec<usize>>>,
 }
 
 impl SelectElement {
     pub(crate) fn new(
         options: Vec<SelectElementOptionOrOptgroup>,
-        selected_option: Option<usize>,
+        selected_options: Vec<usize>,
+        allow_select_multiple: bool,
         position: DeviceIntRect,
-        ipc_sender: IpcSender<Option<usize>>,
+        ipc_sender: IpcSender<Option<Vec<usize>>>,
     ) -> Self {
         Self {
             options,
-            selected_option,
+            selected_options,
+            allow_select_multiple,
             position,
             responder: IpcResponder::new(ipc_sender, None),
         }
@@ -339,21 +634,53 @@ impl SelectElement {
         &self.options
     }
 
+    /// Whether the `<select>` element has the `multiple` attribute, in which case
+    /// the embedder should let the user select more than one option.
+    pub fn allow_select_multiple(&self) -> bool {
+        self.allow_select_multiple
+    }
+
     /// Mark a single option as selected.
     ///
     /// If there is already a selected option and the `<select>` element does not
     /// support selecting multiple options, then the previous option will be unselected.
+    /// Passing `None` unselects all options.
     pub fn select(&mut self, id: Option<usize>) {
-        self.selected_option = id;
+        let Some(id) = id else {
+            self.selected_options.clear();
+            return;
+        };
+        if !self.allow_select_multiple {
+            self.selected_options.clear();
+        }
+        if !self.selected_options.contains(&id) {
+            self.selected_options.push(id);
+        }
     }
 
+    /// Mark a single option as not selected.
+    pub fn unselect(&mut self, id: usize) {
+        self.selected_options
+            .retain(|selected_id| *selected_id != id);
+    }
+
+    /// Return the first selected option, if any.
     pub fn selected_option(&self) -> Option<usize> {
-        self.selected_option
+        self.selected_options.first().copied()
+    }
+
+    /// Return all selected options. This contains at most one option unless
+    /// [`allow_select_multiple`](Self::allow_select_multiple) is `true`.
+    pub fn selected_options(&self) -> &[usize] {
+        &self.selected_options
     }
 
     /// Resolve the prompt with the options that have been selected by calling [select] previously.
+    ///
+    /// Dropping the prompt without calling this method leaves the selection unchanged.
     pub fn submit(mut self) {
-        let _ = self.responder.send(self.selected_option);
+        let selected_options = std::mem::take(&mut self.selected_options);
+        let _ = self.responder.send(Some(selected_options));
     }
 }
 
@@ -399,6 +726,71 @@ impl ColorPicker {
     }
 }
 
+/// Represents a dialog triggered by clicking a `<input type=date>`, `<input type=time>`
+/// or `<input type=datetime-local>` element.
+pub struct DateTimePicker {
+    pub(crate) input_type: DateTimePickerType,
+    pub(crate) values: DateTimePickerValues,
+    pub(crate) position: DeviceIntRect,
+    pub(crate) responder: IpcResponder<Option<String>>,
+    pub(crate) error_sender: ServoErrorSender,
+}
+
+impl DateTimePicker {
+    pub(crate) fn new(
+        input_type: DateTimePickerType,
+        values: DateTimePickerValues,
+        position: DeviceIntRect,
+        ipc_sender: IpcSender<Option<String>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            input_type,
+            values,
+            position,
+            responder: IpcResponder::new(ipc_sender, None),
+            error_sender,
+        }
+    }
+
+    /// Get the area occupied by the `<input>` element that triggered the prompt.
+    ///
+    /// The embedder should use this value to position the prompt that is shown to the user.
+    pub fn position(&self) -> DeviceIntRect {
+        self.position
+    }
+
+    /// Get the kind of value that the user should pick.
+    pub fn input_type(&self) -> DateTimePickerType {
+        self.input_type
+    }
+
+    /// Get the value of the element before the prompt was opened.
+    pub fn current_value(&self) -> &str {
+        &self.values.value
+    }
+
+    /// Get the earliest value that the user is allowed to pick, if any.
+    pub fn min(&self) -> Option<&str> {
+        self.values.min.as_deref()
+    }
+
+    /// Get the latest value that the user is allowed to pick, if any.
+    pub fn max(&self) -> Option<&str> {
+        self.values.max.as_deref()
+    }
+
+    /// Resolve the prompt with the given value, or `None` to leave the value unchanged.
+    ///
+    /// The value must use the format of the [`input_type`](Self::input_type), otherwise
+    /// it is ignored.
+    pub fn select(&mut self, value: Option<String>) {
+        if let Err(error) = self.responder.send(value) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
 pub trait WebViewDelegate {
     /// Get the [`ScreenGeometry`] for this [`WebView`]. If this is unimplemented or returns `None`
     /// the screen will have the size of the [`WebView`]'s `RenderingContext` and `WebView` will be
@@ -426,12 +818,26 @@ pub trait WebViewDelegate {
     /// The `LoadStatus` of the currently loading or loaded page in this [`WebView`] has changed. The new
     /// status can accessed via [`WebView::load_status`].
     fn notify_load_status_changed(&self, _webview: WebView, _status: LoadStatus) {}
+    /// The [`LoadProgress`] of the page loading in this [`WebView`] has changed, which happens
+    /// as its resources load. The new progress can be accessed via [`WebView::load_progress`].
+    fn notify_load_progress_changed(&self, _webview: WebView, _progress: LoadProgress) {}
     /// The [`Cursor`] of the currently loaded page in this [`WebView`] has changed. The new
     /// cursor can accessed via [`WebView::cursor`].
     fn notify_cursor_changed(&self, _webview: WebView, _: Cursor) {}
     /// The favicon [`Url`] of the currently loaded page in this [`WebView`] has changed. The new
     /// favicon [`Url`] can accessed via [`WebView::favicon_url`].
     fn notify_favicon_url_changed(&self, _webview: WebView, _: Url) {}
+    /// The favicon of the currently loaded page in this [`WebView`] has been loaded, or was
+    /// cleared because a new page started loading. The new favicon can be accessed via
+    /// [`WebView::favicon`].
+    fn notify_favicon_changed(&self, _webview: WebView) {}
+    /// The paint timing milestones of the currently loaded page in this [`WebView`] have
+    /// changed. The new metrics can be accessed via [`WebView::paint_metrics`].
+    fn notify_paint_metrics_changed(&self, _webview: WebView, _: PaintMetrics) {}
+    /// A request of the currently loaded page in this [`WebView`] to the given [`Url`] was
+    /// blocked by the content blocking filter lists. The number of blocked requests of the
+    /// page can be accessed via [`WebView::blocked_content_count`].
+    fn notify_content_blocked(&self, _webview: WebView, _: Url) {}
 
     /// Notify the embedder that it needs to present a new frame.
     fn notify_new_frame_ready(&self, _webview: WebView) {}
@@ -448,8 +854,15 @@ pub trait WebViewDelegate {
     /// have an opportunity to handle it first. Apart from builtin keybindings, page
     /// content may expose custom keybindings as well.
     fn notify_keyboard_event(&self, _webview: WebView, _: KeyboardEvent) {}
-    /// A pipeline in the webview panicked. First string is the reason, second one is the backtrace.
-    fn notify_crashed(&self, _webview: WebView, _reason: String, _backtrace: Option<String>) {}
+    /// A pipeline in the webview panicked or its content process crashed. Servo replaces the
+    /// crashed content with a page describing the crash, from which it can be reloaded.
+    fn notify_crashed(&self, _webview: WebView, _crash_details: CrashDetails) {}
+    /// The page at the given [`Url`] failed to load in this [`WebView`]. Return the HTML of a
+    /// page to show instead, or `None` to show the error page of Servo, which describes the
+    /// [`LoadError`] and can reload the page.
+    fn error_page(&self, _webview: WebView, _url: Url, _error: LoadError) -> Option<String> {
+        None
+    }
     /// Notifies the embedder about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     fn notify_media_session_event(&self, _webview: WebView, _event: MediaSessionEvent) {}
@@ -459,6 +872,13 @@ pub trait WebViewDelegate {
     /// the page will enter or leave fullscreen state internally according to the [Fullscreen
     /// API](https://fullscreen.spec.whatwg.org/).
     fn notify_fullscreen_state_changed(&self, _webview: WebView, _: bool) {}
+    /// The video that this [`WebView`] showed in a picture-in-picture window has left
+    /// picture-in-picture, so the embedder should close the window. Servo no longer paints
+    /// to it.
+    fn notify_picture_in_picture_exited(&self, _webview: WebView) {}
+    /// Servo found the text of the last [`WebView::find_in_page`] request in the page of
+    /// this [`WebView`], with the given number of matches and active match.
+    fn notify_find_in_page_result(&self, _webview: WebView, _result: FindInPageResult) {}
 
     /// Whether or not to allow a [`WebView`] to load a URL in its main frame or one of its
     /// nested `<iframe>`s. [`NavigationRequest`]s are accepted by default.
@@ -477,10 +897,15 @@ pub trait WebViewDelegate {
     }
 
     /// Content in a [`WebView`] is requesting permission to access a feature requiring
-    /// permission from the user. The embedder should allow or deny the request, either by
-    /// reading a cached value or querying the user for permission via the user interface.
+    /// permission from the user, who has not decided about it for the origin of the content
+    /// yet. The embedder should allow or deny the request, usually by querying the user via
+    /// the user interface.
     fn request_permission(&self, _webview: WebView, _: PermissionRequest) {}
 
+    /// Content in a [`WebView`] is requesting to show a video in a picture-in-picture
+    /// window. If not handled, the request is denied.
+    fn request_picture_in_picture(&self, _webview: WebView, _: PictureInPictureRequest) {}
+
     fn request_authentication(
         &self,
         _webview: WebView,
@@ -518,15 +943,31 @@ pub trait WebViewDelegate {
         let _ = result_sender.send(ContextMenuResult::Ignored);
     }
 
-    /// Open dialog to select bluetooth device.
-    /// TODO: This API needs to be reworked to match the new model of how responses are sent.
-    fn show_bluetooth_device_dialog(
+    /// Content asked to create a public key credential. See
+    /// [`PublicKeyCredentialCreationRequest`] for details.
+    fn request_public_key_credential_creation(
         &self,
         _webview: WebView,
-        _: Vec<String>,
-        response_sender: IpcSender<Option<String>>,
+        _request: PublicKeyCredentialCreationRequest,
+    ) {
+    }
+
+    /// Content asked to sign in with a public key credential. See
+    /// [`PublicKeyCredentialAssertionRequest`] for details.
+    fn request_public_key_credential_assertion(
+        &self,
+        _webview: WebView,
+        _request: PublicKeyCredentialAssertionRequest,
+    ) {
+    }
+
+    /// Content asked for access to a Bluetooth device, which the user should choose. See
+    /// [`BluetoothDeviceSelectionRequest`] for details.
+    fn request_bluetooth_device(
+        &self,
+        _webview: WebView,
+        _request: BluetoothDeviceSelectionRequest,
     ) {
-        let _ = response_sender.send(None);
     }
 
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
@@ -540,6 +981,34 @@ pub trait WebViewDelegate {
         let _ = response_sender.send(None);
     }
 
+    /// Open file dialog to select a directory, whose files will be uploaded through an
+    /// `<input type=file webkitdirectory>` element.
+    fn show_directory_selection_dialog(
+        &self,
+        _webview: WebView,
+        response_sender: IpcSender<Option<PathBuf>>,
+    ) {
+        let _ = response_sender.send(None);
+    }
+
+    /// A drag-and-drop operation that started in the page of this [`WebView`] left it. The
+    /// embedder can continue the drag outside of the [`WebView`], for instance by starting
+    /// a drag of `data` in the operating system. The page isn't told where the data is
+    /// dropped.
+    fn start_drag(&self, _webview: WebView, _data: DragData) {}
+
+    /// Open file dialog to choose the path of a file to save, optionally suggesting a
+    /// file name.
+    fn show_file_save_dialog(
+        &self,
+        _webview: WebView,
+        _filter_pattern: Vec<FilterPattern>,
+        _suggested_name: Option<String>,
+        response_sender: IpcSender<Option<PathBuf>>,
+    ) {
+        let _ = response_sender.send(None);
+    }
+
     /// Request to present an IME to the user when an editable element is focused.
     /// If `type` is [`InputMethodType::Text`], then the `text` parameter specifies
     /// the pre-existing text content and the zero-based index into the string
@@ -557,10 +1026,25 @@ pub trait WebViewDelegate {
     /// Request to hide the IME when the editable element is blurred.
     fn hide_ime(&self, _webview: WebView) {}
 
+    /// The caret of the focused editable element moved to `position`, so the IME should
+    /// show its candidate window next to it.
+    fn update_ime_cursor_area(&self, _webview: WebView, _position: DeviceIntRect) {}
+
     /// Request that the embedder show UI elements for form controls that are not integrated
     /// into page content, such as dropdowns for `<select>` elements.
     fn show_form_control(&self, _webview: WebView, _form_control: FormControl) {}
 
+    /// Request that the embedder show the validation message of an invalid form control,
+    /// for instance after a call to `reportValidity()` or a blocked form submission. The
+    /// `position` is the rectangle of the control that the message should be anchored to.
+    fn show_validation_message(
+        &self,
+        _webview: WebView,
+        _position: DeviceIntRect,
+        _message: String,
+    ) {
+    }
+
     /// Request to play a haptic effect on a connected gamepad.
     fn play_gamepad_haptic_effect(
         &self,
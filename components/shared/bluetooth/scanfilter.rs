@@ -116,6 +116,8 @@ impl BluetoothScanfilterSequence {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RequestDeviceoptions {
     webview_id: WebViewId,
+    /// The ASCII serialization of the origin of the document that requested the device.
+    origin: String,
     filters: BluetoothScanfilterSequence,
     optional_services: ServiceUUIDSequence,
 }
@@ -123,11 +125,13 @@ pub struct RequestDeviceoptions {
 impl RequestDeviceoptions {
     pub fn new(
         webview_id: WebViewId,
+        origin: String,
         filters: BluetoothScanfilterSequence,
         services: ServiceUUIDSequence,
     ) -> RequestDeviceoptions {
         RequestDeviceoptions {
             webview_id,
+            origin,
             filters,
             optional_services: services,
         }
@@ -137,6 +141,10 @@ impl RequestDeviceoptions {
         self.webview_id
     }
 
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
     pub fn get_filters(&self) -> &BluetoothScanfilterSequence {
         &self.filters
     }
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::default::Default;
+use std::f64::consts::{FRAC_PI_2, PI};
 use std::str::FromStr;
 
 use euclid::Angle;
@@ -361,6 +362,131 @@ impl Path {
         self.0.move_to((x, y));
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    ///
+    /// `radii` are the normalized upper left, upper right, lower right and lower left
+    /// corner radii, as computed by steps 1 to 7 of the algorithm.
+    pub fn round_rect(&mut self, x: f64, y: f64, w: f64, h: f64, radii: [Point2D<f64>; 4]) {
+        // Step 1. If any of x, y, w, or h are infinite or NaN, then return.
+        if !(x.is_finite() && y.is_finite() && w.is_finite() && h.is_finite()) {
+            return;
+        }
+
+        let [
+            mut upper_left,
+            mut upper_right,
+            mut lower_right,
+            mut lower_left,
+        ] = radii;
+
+        // Step 8. Corner curves must not overlap. Scale all radii to prevent this:
+        // Step 8.1. Let top be upperLeft["x"] + upperRight["x"].
+        let top = upper_left.x + upper_right.x;
+        // Step 8.2. Let right be upperRight["y"] + lowerRight["y"].
+        let right = upper_right.y + lower_right.y;
+        // Step 8.3. Let bottom be lowerRight["x"] + lowerLeft["x"].
+        let bottom = lower_right.x + lower_left.x;
+        // Step 8.4. Let left be upperLeft["y"] + lowerLeft["y"].
+        let left = upper_left.y + lower_left.y;
+        // Step 8.5. Let scale be the minimum value of the ratios w / top, h / right,
+        // w / bottom, h / left.
+        let scale = [
+            w.abs() / top,
+            h.abs() / right,
+            w.abs() / bottom,
+            h.abs() / left,
+        ]
+        .into_iter()
+        .filter(|ratio| !ratio.is_nan())
+        .fold(f64::INFINITY, f64::min);
+        // Step 8.6. If scale is less than 1, then set the x and y members of upperLeft,
+        // upperRight, lowerLeft, and lowerRight to their current values multiplied by scale.
+        if scale < 1. {
+            for radius in [
+                &mut upper_left,
+                &mut upper_right,
+                &mut lower_right,
+                &mut lower_left,
+            ] {
+                *radius *= scale;
+            }
+        }
+
+        // A negative width or height mirrors the rectangle, so the corners are
+        // swapped along with it.
+        let (mut x, mut y, mut w, mut h) = (x, y, w, h);
+        if w < 0. {
+            x += w;
+            w = -w;
+            std::mem::swap(&mut upper_left, &mut upper_right);
+            std::mem::swap(&mut lower_left, &mut lower_right);
+        }
+        if h < 0. {
+            y += h;
+            h = -h;
+            std::mem::swap(&mut upper_left, &mut lower_left);
+            std::mem::swap(&mut upper_right, &mut lower_right);
+        }
+
+        // Step 9. Create a new subpath:
+        // Step 9.1. Move to the point (x + upperLeft["x"], y).
+        self.0.move_to((x + upper_left.x, y));
+        // Step 9.2. Draw a straight line to the point (x + w − upperRight["x"], y).
+        // Step 9.3. Draw an arc to the point (x + w, y + upperRight["y"]).
+        let _ = self.ellipse(
+            x + w - upper_right.x,
+            y + upper_right.y,
+            upper_right.x,
+            upper_right.y,
+            0.,
+            -FRAC_PI_2,
+            0.,
+            false,
+        );
+        // Step 9.4. Draw a straight line to the point (x + w, y + h − lowerRight["y"]).
+        // Step 9.5. Draw an arc to the point (x + w − lowerRight["x"], y + h).
+        let _ = self.ellipse(
+            x + w - lower_right.x,
+            y + h - lower_right.y,
+            lower_right.x,
+            lower_right.y,
+            0.,
+            0.,
+            FRAC_PI_2,
+            false,
+        );
+        // Step 9.6. Draw a straight line to the point (x + lowerLeft["x"], y + h).
+        // Step 9.7. Draw an arc to the point (x, y + h − lowerLeft["y"]).
+        let _ = self.ellipse(
+            x + lower_left.x,
+            y + h - lower_left.y,
+            lower_left.x,
+            lower_left.y,
+            0.,
+            FRAC_PI_2,
+            PI,
+            false,
+        );
+        // Step 9.8. Draw a straight line to the point (x, y + upperLeft["y"]).
+        // Step 9.9. Draw an arc to the point (x + upperLeft["x"], y).
+        let _ = self.ellipse(
+            x + upper_left.x,
+            y + upper_left.y,
+            upper_left.x,
+            upper_left.y,
+            0.,
+            PI,
+            PI + FRAC_PI_2,
+            false,
+        );
+
+        // Step 10. Mark the subpath as closed.
+        self.0.close_path();
+
+        // Step 11. Create a new subpath with the point (x, y) as the only point in the subpath.
+        self.0.move_to((x, y));
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath>
     pub fn is_point_in_path(&self, x: f64, y: f64, fill_rule: FillRule) -> bool {
         let p = Point::new(x, y);
@@ -440,10 +566,11 @@ pub enum Canvas2dMsg {
     IsPointInCurrentPath(f64, f64, FillRule, IpcSender<bool>),
     LineTo(Point2D<f32>),
     MoveTo(Point2D<f32>),
-    MeasureText(String, IpcSender<TextMetrics>),
+    MeasureText(String, bool, IpcSender<TextMetrics>),
     PutImageData(Rect<u32>, IpcSnapshot),
     QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
     Rect(Rect<f32>),
+    RoundRect(Rect<f32>, [Point2D<f32>; 4]),
     RestoreContext,
     SaveContext,
     StrokeRect(Rect<f32>, FillOrStrokeStyle),
@@ -465,6 +592,7 @@ pub enum Canvas2dMsg {
     SetFont(FontStyleStruct),
     SetTextAlign(TextAlign),
     SetTextBaseline(TextBaseline),
+    SetFilter(Vec<CanvasFilter>),
     UpdateImage(IpcSender<()>),
 }
 
@@ -539,6 +667,21 @@ impl RadialGradientStyle {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ConicGradientStyle {
+    /// The start angle of the gradient, in radians clockwise from the positive x axis.
+    pub angle: f64,
+    pub x: f64,
+    pub y: f64,
+    pub stops: Vec<CanvasGradientStop>,
+}
+
+impl ConicGradientStyle {
+    pub fn new(angle: f64, x: f64, y: f64, stops: Vec<CanvasGradientStop>) -> ConicGradientStyle {
+        ConicGradientStyle { angle, x, y, stops }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SurfaceStyle {
     pub surface_data: IpcSnapshot,
@@ -571,6 +714,7 @@ pub enum FillOrStrokeStyle {
     Color(AbsoluteColor),
     LinearGradient(LinearGradientStyle),
     RadialGradient(RadialGradientStyle),
+    ConicGradient(ConicGradientStyle),
     Surface(SurfaceStyle),
 }
 
@@ -737,6 +881,28 @@ pub enum Direction {
     Inherit,
 }
 
+/// A filter function of the canvas `filter` attribute, with all lengths resolved
+/// to CSS pixels and all angles to degrees.
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum CanvasFilter {
+    Blur(f64),
+    Brightness(f64),
+    Contrast(f64),
+    DropShadow {
+        offset_x: f64,
+        offset_y: f64,
+        blur: f64,
+        color: AbsoluteColor,
+    },
+    Grayscale(f64),
+    HueRotate(f64),
+    Invert(f64),
+    Opacity(f64),
+    Saturate(f64),
+    Sepia(f64),
+}
+
 #[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, Serialize)]
 pub struct TextMetrics {
     pub width: f32,
@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use base::id::ScrollTreeNodeId;
 use base::print_tree::PrintTree;
 use bitflags::bitflags;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, FrameStageTimings};
 use euclid::SideOffsets2D;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
@@ -18,8 +18,8 @@ use webrender_api::units::{
     LayoutPixel, LayoutPoint, LayoutRect, LayoutSize, LayoutTransform, LayoutVector2D,
 };
 use webrender_api::{
-    Epoch, ExternalScrollId, PipelineId, ReferenceFrameKind, ScrollLocation, SpatialId,
-    StickyOffsetBounds, TransformStyle,
+    Epoch, ExternalScrollId, PipelineId, PropertyBindingKey, ReferenceFrameKind, ScrollLocation,
+    SpatialId, StickyOffsetBounds, TransformStyle,
 };
 
 /// A scroll type, describing whether what kind of action originated this scroll request.
@@ -38,6 +38,16 @@ bitflags! {
     }
 }
 
+/// How a scroll requested by script moves a scroll node to its new offset.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScrollAnimation {
+    /// The scroll node jumps to its new offset in the next frame.
+    Instant,
+    /// The renderer animates the scroll node from its current offset to the new one over
+    /// the course of several frames.
+    Smooth,
+}
+
 /// Convert [Overflow] to [ScrollSensitivity].
 impl From<Overflow> for ScrollType {
     fn from(overflow: Overflow) -> Self {
@@ -91,6 +101,9 @@ pub struct ReferenceFrameNodeInfo {
     pub transform_style: TransformStyle,
     pub transform: LayoutTransform,
     pub kind: ReferenceFrameKind,
+    /// The key of the property binding of the transform of this reference frame, if its
+    /// transform is animated by the compositor.
+    pub transform_binding: Option<PropertyBindingKey<LayoutTransform>>,
 }
 
 /// Data stored for nodes in the [ScrollTree] that actually scroll,
@@ -479,6 +492,30 @@ impl ScrollTree {
     }
 }
 
+/// The interval between the values of a [`CompositorAnimation`], in seconds.
+pub const COMPOSITOR_ANIMATION_SAMPLE_INTERVAL: f64 = 1. / 60.;
+
+/// The values that an animated property bound to a WebRender property binding takes over
+/// the course of a [`CompositorAnimation`].
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CompositorAnimationValues {
+    Transform(PropertyBindingKey<LayoutTransform>, Vec<LayoutTransform>),
+    Opacity(PropertyBindingKey<f32>, Vec<f32>),
+}
+
+/// An animation of the `transform` or `opacity` of an element, which the compositor runs
+/// on its own so that it doesn't stutter when script or layout are busy. Layout samples
+/// the animation ahead of time, every [`COMPOSITOR_ANIMATION_SAMPLE_INTERVAL`] seconds,
+/// until the end of its current iteration.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CompositorAnimation {
+    /// The value of the animation timeline at which the animation takes its first value,
+    /// in seconds.
+    pub start_time: f64,
+    /// The values of the animated property.
+    pub values: CompositorAnimationValues,
+}
+
 /// A data structure which stores compositor-side information about
 /// display lists sent to the compositor.
 #[derive(Debug, Deserialize, Serialize)]
@@ -495,6 +532,11 @@ pub struct CompositorDisplayListInfo {
     /// The epoch of the display list.
     pub epoch: Epoch,
 
+    /// The epoch of the hit testing tags in this display list. Display lists that reuse
+    /// display items retained from the previous one keep adding to its `hit_test_info`,
+    /// so this is the epoch of the last display list that was built without reusing any.
+    pub hit_test_epoch: Epoch,
+
     /// An array of `HitTestInfo` which is used to store information
     /// to assist the compositor to take various actions (set the cursor,
     /// scroll without layout) using a WebRender hit test result.
@@ -517,9 +559,22 @@ pub struct CompositorDisplayListInfo {
     /// See <https://w3c.github.io/paint-timing/#first-contentful-paint>.
     pub is_contentful: bool,
 
+    /// The area of the largest image or text of the display list that is in the viewport,
+    /// which is used to find the largest contentful paint of the page.
+    /// See <https://w3c.github.io/largest-contentful-paint/>.
+    pub largest_contentful_area: f32,
+
     /// Whether the first layout or a subsequent (incremental) layout triggered this
     /// display list creation.
     pub first_reflow: bool,
+
+    /// The animations of `transform` and `opacity` in this display list that the
+    /// compositor runs on its own.
+    pub compositor_animations: Vec<CompositorAnimation>,
+
+    /// The time that script and layout spent producing this display list, which is
+    /// reported to embedders as part of the frame that it ends up in.
+    pub frame_timings: FrameStageTimings,
 }
 
 impl CompositorDisplayListInfo {
@@ -541,6 +596,7 @@ impl CompositorDisplayListInfo {
                 transform_style: TransformStyle::Flat,
                 transform: LayoutTransform::identity(),
                 kind: ReferenceFrameKind::default(),
+                transform_binding: None,
             }),
         );
         let root_scroll_node_id = scroll_tree.add_scroll_tree_node(
@@ -559,12 +615,16 @@ impl CompositorDisplayListInfo {
             viewport_size,
             content_size,
             epoch,
+            hit_test_epoch: epoch,
             hit_test_info: Default::default(),
             scroll_tree,
             root_reference_frame_id,
             root_scroll_node_id,
             is_contentful: false,
+            largest_contentful_area: 0.,
             first_reflow,
+            compositor_animations: Vec::new(),
+            frame_timings: FrameStageTimings::default(),
         }
     }
 
@@ -6,9 +6,10 @@
 
 use std::fmt::{Debug, Error, Formatter};
 
+use base::cross_process_instant::CrossProcessInstant;
 use base::id::{PipelineId, WebViewId};
 use crossbeam_channel::Sender;
-use embedder_traits::{AnimationState, EventLoopWaker, TouchEventResult};
+use embedder_traits::{AnimationState, EventLoopWaker, ScreenshotMetadata, TouchEventResult};
 use euclid::Rect;
 use ipc_channel::ipc::IpcSender;
 use log::warn;
@@ -27,13 +28,13 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use bitflags::bitflags;
-use display_list::CompositorDisplayListInfo;
+use display_list::{CompositorDisplayListInfo, ScrollAnimation};
 use embedder_traits::{CompositorHitTestResult, ScreenGeometry};
 use euclid::default::Size2D as UntypedSize2D;
 use ipc_channel::ipc::{self, IpcSharedMemory};
 use profile_traits::mem::{OpaqueSender, ReportsChan};
 use serde::{Deserialize, Serialize};
-use webrender_api::units::{DevicePoint, LayoutVector2D, TexelRect};
+use webrender_api::units::{DevicePoint, LayoutRect, LayoutVector2D, TexelRect};
 use webrender_api::{
     BuiltDisplayList, BuiltDisplayListDescriptor, ExternalImage, ExternalImageData,
     ExternalImageHandler, ExternalImageId, ExternalImageSource, ExternalScrollId,
@@ -86,6 +87,13 @@ pub enum CompositorMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<RasterImage>>,
     ),
+    /// Composite the whole viewport with the given rectangles of it filled with an opaque
+    /// color, and return the image with the metadata of the frame over a passed channel.
+    CreateMaskedPng(
+        WebViewId,
+        Vec<Rect<f32, CSSPixel>>,
+        IpcSender<Option<(RasterImage, ScreenshotMetadata)>>,
+    ),
     /// A reply to the compositor asking if the output image is stable.
     IsReadyToSaveImageReply(bool),
     /// Set whether to use less resources by stopping animations.
@@ -109,6 +117,7 @@ pub enum CompositorMsg {
         WebRenderPipelineId,
         LayoutVector2D,
         ExternalScrollId,
+        ScrollAnimation,
     ),
     /// Inform WebRender of a new display list for the given pipeline.
     SendDisplayList {
@@ -135,6 +144,9 @@ pub enum CompositorMsg {
     GenerateImageKeysForPipeline(PipelineId),
     /// Perform a resource update operation.
     UpdateImages(SmallVec<[ImageUpdate; 1]>),
+    /// Set the image that holds the current frame of the video that is shown in the
+    /// picture-in-picture window of the given `WebView`, or `None` if there is no such video.
+    SetPictureInPictureImage(WebViewId, Option<ImageKey>),
 
     /// Generate a new batch of font keys which can be used to allocate
     /// keys asynchronously.
@@ -156,6 +168,12 @@ pub enum CompositorMsg {
     CollectMemoryReport(ReportsChan),
     /// A top-level frame has parsed a viewport metatag and is sending the new constraints.
     Viewport(WebViewId, ViewportDescription),
+    /// Replace the `IntersectionObserver` targets of the given pipeline whose intersection
+    /// with its viewport the renderer tracks while scrolling.
+    UpdateIntersectionObservationTargets(WebViewId, PipelineId, Vec<IntersectionObservationTarget>),
+    /// The navigation timing of the document of the given pipeline changed, so that it can be
+    /// reported along with the paint timing of the frames it leads to.
+    UpdateNavigationTiming(WebViewId, PipelineId, NavigationTiming),
 }
 
 impl Debug for CompositorMsg {
@@ -178,6 +196,40 @@ pub struct CompositionPipeline {
     pub webview_id: WebViewId,
 }
 
+/// A target of an `IntersectionObserver` whose root is the viewport of the target's
+/// document. The renderer tracks the intersection of the target with the viewport while it
+/// scrolls the document, so that crossing a threshold doesn't have to wait for script to
+/// query layout.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IntersectionObservationTarget {
+    /// An identifier for this pair of observer and target, chosen by script.
+    pub id: u64,
+    /// The bounding box of the target in the coordinate space of its document, without the
+    /// scroll offset of the viewport applied.
+    pub rect: LayoutRect,
+    /// The root intersection rectangle of the observer, in the coordinate space of the
+    /// viewport.
+    pub root_bounds: LayoutRect,
+    /// The thresholds of the observer, in increasing order.
+    pub thresholds: Vec<f64>,
+    /// The index of the first threshold greater than the intersection ratio of the target,
+    /// as last reported to script.
+    pub threshold_index: i32,
+    /// Whether the target intersects the root, as last reported to script.
+    pub is_intersecting: bool,
+}
+
+/// The navigation timing milestones of a document that the renderer reports along with
+/// screenshots, so that lab tools get paint and load timing that is consistent with the
+/// frame they look at. See <https://w3c.github.io/navigation-timing/>.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct NavigationTiming {
+    pub navigation_start: CrossProcessInstant,
+    pub dom_interactive: Option<CrossProcessInstant>,
+    pub dom_content_loaded_event_end: Option<CrossProcessInstant>,
+    pub load_event_end: Option<CrossProcessInstant>,
+}
+
 /// A mechanism to send messages from ScriptThread to the parent process' WebRender instance.
 #[derive(Clone, Deserialize, MallocSizeOf, Serialize)]
 pub struct CrossProcessCompositorApi(pub IpcSender<CompositorMsg>);
@@ -202,6 +254,42 @@ impl CrossProcessCompositorApi {
         }
     }
 
+    /// Replace the `IntersectionObserver` targets of the given pipeline that the renderer
+    /// tracks while scrolling.
+    pub fn update_intersection_observation_targets(
+        &self,
+        webview_id: WebViewId,
+        pipeline_id: PipelineId,
+        targets: Vec<IntersectionObservationTarget>,
+    ) {
+        if let Err(error) = self
+            .0
+            .send(CompositorMsg::UpdateIntersectionObservationTargets(
+                webview_id,
+                pipeline_id,
+                targets,
+            ))
+        {
+            warn!("Error sending intersection observation targets: {error}");
+        }
+    }
+
+    /// Tell the renderer about the navigation timing of the document of the given pipeline.
+    pub fn update_navigation_timing(
+        &self,
+        webview_id: WebViewId,
+        pipeline_id: PipelineId,
+        navigation_timing: NavigationTiming,
+    ) {
+        if let Err(error) = self.0.send(CompositorMsg::UpdateNavigationTiming(
+            webview_id,
+            pipeline_id,
+            navigation_timing,
+        )) {
+            warn!("Error sending navigation timing: {error}");
+        }
+    }
+
     /// Perform a scroll operation.
     pub fn send_scroll_node(
         &self,
@@ -209,12 +297,14 @@ impl CrossProcessCompositorApi {
         pipeline_id: WebRenderPipelineId,
         point: LayoutVector2D,
         scroll_id: ExternalScrollId,
+        animation: ScrollAnimation,
     ) {
         if let Err(e) = self.0.send(CompositorMsg::SendScrollNode(
             webview_id,
             pipeline_id,
             point,
             scroll_id,
+            animation,
         )) {
             warn!("Error sending scroll node: {}", e);
         }
@@ -317,6 +407,17 @@ impl CrossProcessCompositorApi {
         }
     }
 
+    /// Set the image that holds the current frame of the video that is shown in the
+    /// picture-in-picture window of the given `WebView`. Updates of this image are painted
+    /// to that window, as well as the `WebView` itself.
+    pub fn set_picture_in_picture_image(&self, webview_id: WebViewId, image_key: Option<ImageKey>) {
+        if let Err(e) = self.0.send(CompositorMsg::SetPictureInPictureImage(
+            webview_id, image_key,
+        )) {
+            warn!("error sending picture-in-picture image: {}", e);
+        }
+    }
+
     pub fn remove_unused_font_resources(
         &self,
         keys: Vec<FontKey>,
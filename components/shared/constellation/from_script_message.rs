@@ -15,28 +15,35 @@ use base::id::{
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
 use embedder_traits::{
-    AnimationState, EmbedderMsg, FocusSequenceNumber, JSValue, JavaScriptEvaluationError,
-    JavaScriptEvaluationId, MediaSessionEvent, Theme, TouchEventResult, ViewportDetails,
-    WebDriverMessageId,
+    AllowOrDeny, AnimationState, EmbedderMsg, FocusSequenceNumber, JSValue,
+    JavaScriptEvaluationError, JavaScriptEvaluationId, MediaSessionEvent, PermissionFeature, Theme,
+    TouchEventResult, ViewportDetails, WebDriverMessageId,
 };
 use euclid::default::Size2D as UntypedSize2D;
-use http::{HeaderMap, Method};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue, Method};
 use ipc_channel::Error as IpcError;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use net_traits::policy_container::PolicyContainer;
-use net_traits::request::{Destination, InsecureRequestsPolicy, Referrer, RequestBody};
+use net_traits::request::{
+    CredentialsMode, Destination, InsecureRequestsPolicy, Referrer, RequestBody, RequestBuilder,
+    RequestMode, create_request_body_with_content,
+};
 use net_traits::storage_thread::StorageType;
 use net_traits::{CoreResourceMsg, ReferrerPolicy, ResourceThreads};
 use profile_traits::mem::MemoryReportResult;
 use profile_traits::{mem, time as profile_time};
 use serde::{Deserialize, Serialize};
+use servo_config::prefs::PrefValue;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use strum_macros::IntoStaticStr;
 #[cfg(feature = "webgpu")]
 use webgpu_traits::{WebGPU, WebGPUAdapterResponse};
 use webrender_api::ImageKey;
+use webrender_api::units::DeviceIntSize;
 
-use crate::structured_data::{BroadcastMsg, StructuredSerializedData};
+use crate::introspection::RuntimeServicesReport;
+use crate::structured_data::{BroadcastMsg, OffscreenCanvasPlaceholder, StructuredSerializedData};
 use crate::{
     LogEntry, MessagePortMsg, PortMessageTask, PortTransferInfo, TraversalDirection, WindowSizeType,
 };
@@ -57,6 +64,46 @@ impl ScriptToConstellationChan {
     }
 }
 
+/// A report to deliver to the reporting endpoint of a document if its pipeline crashes,
+/// which is generated in advance since the script thread can't generate it once it has crashed.
+///
+/// <https://wicg.github.io/crash-reporting/>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashReport {
+    /// The URL of the endpoint that the report is delivered to.
+    pub endpoint: ServoUrl,
+    /// The origin of the document.
+    pub origin: ImmutableOrigin,
+    /// The report, serialized as a list of reports in the `application/reports+json` format.
+    pub body: String,
+}
+
+impl CrashReport {
+    /// The request that delivers the report to its endpoint.
+    ///
+    /// <https://w3c.github.io/reporting/#try-delivery>
+    pub fn request(&self, webview_id: WebViewId) -> RequestBuilder {
+        let mut headers = HeaderMap::with_capacity(1);
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/reports+json"),
+        );
+        RequestBuilder::new(
+            Some(webview_id),
+            self.endpoint.clone(),
+            Referrer::NoReferrer,
+        )
+        .method(Method::POST)
+        .headers(headers)
+        .body(Some(create_request_body_with_content(&self.body)))
+        .destination(Destination::Report)
+        .origin(self.origin.clone())
+        .mode(RequestMode::CorsMode)
+        .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+        .unsafe_request(true)
+    }
+}
+
 /// The origin where a given load was initiated.
 /// Useful for origin checks, for example before evaluation a JS URL.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -110,6 +157,13 @@ pub struct LoadData {
     pub inherited_insecure_requests_policy: Option<InsecureRequestsPolicy>,
     /// Whether the page's ancestors have potentially trustworthy origin
     pub has_trustworthy_ancestor_origin: bool,
+    /// For loads of nested documents, the origin of the top-level document, by which the
+    /// cookies and storage of the loaded document are partitioned.
+    pub top_level_origin: Option<ImmutableOrigin>,
+    /// For loads of nested documents, the origins of the ancestor documents, starting with
+    /// the parent, as known to the constellation. Used to check `frame-ancestors` against
+    /// ancestors running in another script thread.
+    pub ancestor_origins: Vec<ImmutableOrigin>,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
     /// Destination, used for CSP checks
@@ -155,6 +209,8 @@ impl LoadData {
             crash: None,
             inherited_insecure_requests_policy,
             has_trustworthy_ancestor_origin,
+            top_level_origin: None,
+            ancestor_origins: Vec::new(),
             destination: Destination::Document,
         }
     }
@@ -222,6 +278,10 @@ pub enum ServiceWorkerMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>
     ScheduleJob(Job),
+    /// Sent by a service worker once its install event has been dispatched,
+    /// with whether its script was fetched and run successfully.
+    /// <https://w3c.github.io/ServiceWorker/#installation-algorithm>
+    InstallFinished(ServoUrl, ServiceWorkerId, bool),
     /// Exit the service worker manager
     Exit,
 }
@@ -271,6 +331,8 @@ pub enum JobResultValue {
         /// The active worker, if any.
         active_worker: Option<ServiceWorkerId>,
     },
+    /// Whether a registration was found and unregistered.
+    Unregistered(bool),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -471,6 +533,14 @@ pub struct IFrameSizeMsg {
     pub type_: WindowSizeType,
 }
 
+/// The direction of a sequential focus navigation, i.e. whether focus moves to the next
+/// or to the previous element in the sequential focus navigation order.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SequentialFocusDirection {
+    Forward,
+    Backward,
+}
+
 /// Messages from the script to the constellation.
 #[derive(Deserialize, IntoStaticStr, Serialize)]
 pub enum ScriptToConstellationMessage {
@@ -537,6 +607,13 @@ pub enum ScriptToConstellationMessage {
         UntypedSize2D<u64>,
         IpcSender<(IpcSender<CanvasMsg>, CanvasId, ImageKey)>,
     ),
+    /// Present a new frame of an OffscreenCanvas, with its image and size, in its
+    /// placeholder canvas element.
+    UpdatePlaceholderCanvas(
+        OffscreenCanvasPlaceholder,
+        Option<ImageKey>,
+        UntypedSize2D<u32>,
+    ),
     /// Notifies the constellation that this pipeline is requesting focus.
     ///
     /// When this message is sent, the sender pipeline has already its local
@@ -552,6 +629,17 @@ pub enum ScriptToConstellationMessage {
     Focus(Option<BrowsingContextId>, FocusSequenceNumber),
     /// Requests the constellation to focus the specified browsing context.
     FocusRemoteDocument(BrowsingContextId),
+    /// Continue a sequential focus navigation in the given nested browsing context, whose
+    /// container is the next element in the sequential focus navigation order of the
+    /// sender pipeline.
+    MoveSequentialFocusIntoChild(BrowsingContextId, SequentialFocusDirection),
+    /// Continue a sequential focus navigation in the parent of the sender pipeline, as
+    /// there are no more elements to focus in its document in the given direction.
+    MoveSequentialFocusOutOfDocument(SequentialFocusDirection),
+    /// Make the container of the browsing context of the sender pipeline the fullscreen
+    /// element of its document, or stop it being one, as the document of the sender entered
+    /// or exited fullscreen.
+    SetFullscreenStateOfContainer(bool),
     /// Get the top-level browsing context info for a given browsing context.
     GetTopForBrowsingContext(BrowsingContextId, IpcSender<Option<WebViewId>>),
     /// Get the browsing context id of the browsing context in which pipeline is
@@ -617,6 +705,9 @@ pub enum ScriptToConstellationMessage {
     SetLayoutEpoch(Epoch, IpcSender<bool>),
     /// Update the pipeline Url, which can change after redirections.
     SetFinalUrl(ServoUrl),
+    /// Set the report to deliver if the pipeline crashes, or `None` if the document has no
+    /// endpoint for crash reports.
+    SetCrashReport(Option<CrashReport>),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(TouchEventResult),
     /// A log entry, with the top-level browsing context id and thread name
@@ -635,6 +726,12 @@ pub enum ScriptToConstellationMessage {
     /// Notifies the constellation about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(PipelineId, MediaSessionEvent),
+    /// Request to show a video of this pipeline in a picture-in-picture window, given the
+    /// natural size of the video. The response is the size of the window, or `None` if the
+    /// request was denied.
+    RequestPictureInPicture(DeviceIntSize, IpcSender<Option<DeviceIntSize>>),
+    /// Notifies the constellation that the video of this pipeline left picture-in-picture.
+    ExitPictureInPicture,
     #[cfg(feature = "webgpu")]
     /// Create a WebGPU Adapter instance
     RequestAdapter(
@@ -651,6 +748,35 @@ pub enum ScriptToConstellationMessage {
     IFrameSizes(Vec<IFrameSizeMsg>),
     /// Request results from the memory reporter.
     ReportMemory(IpcSender<MemoryReportResult>),
+    /// Collect garbage in every script thread, then request results from the memory reporter.
+    CollectGarbageAndReportMemory(IpcSender<MemoryReportResult>),
+    /// The values of the given preferences were changed at runtime, for instance on the
+    /// servo:prefs page, and should be applied to all processes.
+    PreferencesChanged(Vec<(String, PrefValue)>),
+    /// Get the decision of the user about the given permission of the given origin from the
+    /// permission store, if there is one.
+    GetPermissionDecision(
+        ImmutableOrigin,
+        PermissionFeature,
+        IpcSender<Option<AllowOrDeny>>,
+    ),
+    /// Record the decision of the user about the given permission of the given origin in the
+    /// permission store, or forget it if the decision is `None`. The decision is persisted
+    /// in the profile if the user asked to remember it.
+    SetPermissionDecision(
+        ImmutableOrigin,
+        PermissionFeature,
+        Option<AllowOrDeny>,
+        bool,
+    ),
+    /// Request storage access for the document of the pipeline, which the constellation grants
+    /// for the pair of its site and the site of the top-level document if the user allows it.
+    /// The user is only asked if the document has transient activation, as given by the flag.
+    /// Replies whether access was granted.
+    RequestStorageAccess(bool, IpcSender<bool>),
+    /// Request a report on the state of the pipelines, event loops and other components
+    /// run by the constellation.
+    ReportRuntimeServices(IpcSender<RuntimeServicesReport>),
     /// Return the result of the evaluated JavaScript with the given [`JavaScriptEvaluationId`].
     FinishJavaScriptEvaluation(
         JavaScriptEvaluationId,
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reports on the state of the components run by the `Constellation`, used by the
+//! servo:services page to help diagnose hangs. Gathering a report is cheap: each component
+//! only reports a few counters that it keeps anyway, and components that do not answer in
+//! time are reported as not responding rather than waited for.
+
+use std::time::Duration;
+
+use base::id::{PipelineId, WebViewId};
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+/// A snapshot of the components run by the `Constellation`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RuntimeServicesReport {
+    pub pipelines: Vec<PipelineReport>,
+    pub event_loops: Vec<EventLoopReport>,
+    /// The number of fetches in progress in the resource threads, or `None` if they did not
+    /// answer in time.
+    pub active_fetches: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PipelineReport {
+    pub id: PipelineId,
+    pub webview_id: WebViewId,
+    /// The identifier of the [`EventLoopReport`] of the event loop running this pipeline.
+    pub event_loop_id: usize,
+    pub url: ServoUrl,
+    pub title: String,
+    pub completely_loaded: bool,
+    /// The time since the `Constellation` last received a message from this pipeline.
+    pub time_since_last_message: Duration,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EventLoopReport {
+    pub id: usize,
+    /// The site that this event loop was created for, if it is shared between the pipelines
+    /// of that site.
+    pub site: Option<String>,
+    /// The state of the script thread of this event loop, or `None` if it did not answer
+    /// in time, which usually means that it is busy running a long task or hung.
+    pub activity: Option<ScriptThreadActivity>,
+}
+
+/// What a script thread reports about itself, see [`EventLoopReport`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScriptThreadActivity {
+    /// The id of the process that runs the script thread.
+    pub pid: u32,
+    /// The number of messages from the `Constellation` waiting to be handled.
+    pub queued_messages: usize,
+    /// The number of tasks waiting to be run, including throttled ones and those of
+    /// documents that are not fully active.
+    pub queued_tasks: usize,
+    /// The time since the script thread last woke up to handle messages and run tasks.
+    pub time_since_last_wake_up: Duration,
+    /// The number of dedicated worker threads running in the process of the script thread.
+    pub running_dedicated_workers: usize,
+}
@@ -9,20 +9,23 @@
 //! on other parts of Servo.
 
 mod from_script_message;
+mod introspection;
 mod structured_data;
 
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use base::Epoch;
 use base::cross_process_instant::CrossProcessInstant;
 use base::id::{MessagePortId, PipelineId, WebViewId};
 use embedder_traits::{
-    CompositorHitTestResult, Cursor, InputEvent, JavaScriptEvaluationId, MediaSessionActionType,
-    Theme, ViewportDetails, WebDriverCommandMsg, WebDriverCommandResponse,
+    CompositorHitTestResult, Cursor, FindInPageRequest, InputEvent, JavaScriptEvaluationId,
+    MediaSessionActionType, Theme, UserAgentStylesheetOverride, ViewportDetails,
+    WebDriverCommandMsg, WebDriverCommandResponse,
 };
 pub use from_script_message::*;
+pub use introspection::*;
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use profile_traits::mem::MemoryReportResult;
@@ -30,7 +33,7 @@ use serde::{Deserialize, Serialize};
 use servo_url::{ImmutableOrigin, ServoUrl};
 pub use structured_data::*;
 use strum_macros::IntoStaticStr;
-use webrender_api::units::LayoutVector2D;
+use webrender_api::units::{DeviceIntSize, LayoutRect, LayoutVector2D};
 use webrender_api::{ExternalScrollId, ImageKey};
 
 /// Messages to the Constellation from the embedding layer, whether from `ServoRenderer` or
@@ -47,23 +50,36 @@ pub enum EmbedderToConstellationMessage {
     LoadUrl(WebViewId, ServoUrl),
     /// Clear the network cache.
     ClearCache,
+    /// Clear the local storage, session storage and IndexedDB databases of the given origin,
+    /// or of all origins, which were modified since the given time.
+    ClearSiteData(Option<ImmutableOrigin>, Option<SystemTime>),
+    /// Replace the content blocking filter lists, given as the contents of files in the
+    /// Adblock Plus format.
+    SetContentBlockingFilterLists(Vec<String>),
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(WebViewId, TraversalDirection),
     /// Inform the Constellation that a `WebView`'s [`ViewportDetails`] have changed.
     ChangeViewportDetails(WebViewId, ViewportDetails, WindowSizeType),
     /// Inform the constellation of a theme change.
     ThemeChange(WebViewId, Theme),
+    /// Set or clear the stylesheet that is applied at the user agent origin of the
+    /// documents of a `WebView`.
+    SetUserAgentStylesheetOverride(WebViewId, Option<UserAgentStylesheetOverride>),
+    /// Replace the stylesheets that are applied at the user origin of the documents of a
+    /// `WebView`.
+    SetUserStylesheets(WebViewId, Vec<String>),
     /// Requests that the constellation instruct script/layout to try to layout again and tick
-    /// animations.
-    TickAnimation(Vec<WebViewId>),
+    /// animations, for the frame described by the given [`FrameClock`].
+    TickAnimation(Vec<WebViewId>, FrameClock),
     /// Dispatch a webdriver command
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
     Reload(WebViewId),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<WebViewId>, Option<String>, LogEntry),
-    /// Create a new top level browsing context.
-    NewWebView(ServoUrl, WebViewId, ViewportDetails),
+    /// Create a new top level browsing context, which uses the private browsing
+    /// partition of the resource threads if the last field is `true`.
+    NewWebView(ServoUrl, WebViewId, ViewportDetails, bool),
     /// Close a top level browsing context.
     CloseWebView(WebViewId),
     /// Panic a top level browsing context.
@@ -82,6 +98,10 @@ pub enum EmbedderToConstellationMessage {
     ExitFullScreen(WebViewId),
     /// Media session action.
     MediaSessionAction(MediaSessionActionType),
+    /// The picture-in-picture window of the given `WebView` was closed by the user.
+    ExitPictureInPicture(WebViewId),
+    /// The picture-in-picture window of the given `WebView` was resized to the given size.
+    PictureInPictureWindowResized(WebViewId, DeviceIntSize),
     /// Set whether to use less resources, by stopping animations and running timers at a heavily limited rate.
     SetWebViewThrottled(WebViewId, bool),
     /// The Servo renderer scrolled and is updating the scroll states of the nodes in the
@@ -89,9 +109,14 @@ pub enum EmbedderToConstellationMessage {
     SetScrollStates(PipelineId, HashMap<ExternalScrollId, LayoutVector2D>),
     /// Notify the constellation that a particular paint metric event has happened for the given pipeline.
     PaintMetric(PipelineId, PaintMetricEvent),
+    /// The Servo renderer scrolled the given pipeline and the intersections of some of its
+    /// `IntersectionObserver` targets with its viewport changed.
+    IntersectionObservationsChanged(PipelineId, Vec<IntersectionObservationChange>),
     /// Evaluate a JavaScript string in the context of a `WebView`. When execution is complete or an
     /// error is encountered, a correpsonding message will be sent to the embedding layer.
     EvaluateJavaScript(WebViewId, JavaScriptEvaluationId, String),
+    /// Find text in the page of a `WebView`, or stop finding text if there is no request.
+    FindInPage(WebViewId, Option<FindInPageRequest>),
     /// Create a memory report and return it via the ipc sender
     CreateMemoryReport(IpcSender<MemoryReportResult>),
     /// Sends the generated image key to the image cache associated with this pipeline.
@@ -100,11 +125,43 @@ pub enum EmbedderToConstellationMessage {
     SetWebDriverResponseSender(IpcSender<WebDriverCommandResponse>),
 }
 
+/// The timing of the frame that the Servo renderer is about to produce, which is sent
+/// along with animation ticks so that script can align its rendering updates with the
+/// frames that are actually presented.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FrameClock {
+    /// The time at which the renderer started this frame. This is the timestamp passed
+    /// to `requestAnimationFrame` callbacks.
+    pub frame_start: CrossProcessInstant,
+    /// The time by which script needs to have updated the rendering for its changes to
+    /// be part of this frame.
+    pub deadline: CrossProcessInstant,
+    /// The interval between the frames that the renderer presents, which follows the
+    /// refresh rate of the display.
+    pub refresh_interval: Duration,
+}
+
 /// A description of a paint metric that is sent from the Servo renderer to the
 /// constellation.
 pub enum PaintMetricEvent {
     FirstPaint(CrossProcessInstant, bool /* first_reflow */),
     FirstContentfulPaint(CrossProcessInstant, bool /* first_reflow */),
+    LargestContentfulPaint(CrossProcessInstant),
+}
+
+/// A change of the intersection of an `IntersectionObserver` target with the viewport of
+/// its document, detected by the Servo renderer while scrolling. Rectangles are in the
+/// coordinate space of the viewport.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IntersectionObservationChange {
+    /// The identifier of the pair of observer and target, as chosen by script.
+    pub id: u64,
+    pub threshold_index: i32,
+    pub is_intersecting: bool,
+    pub target_rect: LayoutRect,
+    pub intersection_rect: LayoutRect,
+    pub intersection_ratio: f64,
+    pub root_bounds: LayoutRect,
 }
 
 impl fmt::Debug for EmbedderToConstellationMessage {
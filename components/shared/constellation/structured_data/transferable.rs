@@ -8,7 +8,7 @@
 
 use std::collections::VecDeque;
 
-use base::id::MessagePortId;
+use base::id::{MessagePortId, OffscreenCanvasId, PipelineId};
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
@@ -195,4 +195,15 @@ impl MessagePortImpl {
 pub struct TransferableOffscreenCanvas {
     pub width: u64,
     pub height: u64,
+    pub placeholder: Option<OffscreenCanvasPlaceholder>,
+}
+
+/// The placeholder canvas element of an OffscreenCanvas, which can live in another
+/// thread than the OffscreenCanvas once the latter is transferred.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct OffscreenCanvasPlaceholder {
+    /// The pipeline of the document of the canvas element.
+    pub pipeline_id: PipelineId,
+    /// The id under which the document keeps the canvas element.
+    pub canvas_id: OffscreenCanvasId,
 }
@@ -277,6 +277,10 @@ pub enum DevtoolScriptControlMsg {
     SimulateColorScheme(PipelineId, Theme),
     /// Highlight the given DOM node
     HighlightDomNode(PipelineId, Option<String>),
+    /// Start recording which scripts and style rules are used by the given pipeline.
+    StartCoverage(PipelineId),
+    /// Stop recording coverage for the given pipeline and retrieve the report as JSON.
+    TakeCoverage(PipelineId, IpcSender<Option<String>>),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reports about how the frames painted by Servo were produced, which embedders can use
+//! to diagnose janky frames.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The time spent by script and layout to produce a display list.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FrameStageTimings {
+    /// The time spent running script while updating the rendering, before layout was
+    /// asked for a new display list. This includes animation frame callbacks and the
+    /// events fired while updating the rendering.
+    pub script: Duration,
+    /// The time spent computing styles.
+    pub style: Duration,
+    /// The time spent building the box tree and laying it out.
+    pub layout: Duration,
+    /// The time spent building the stacking context tree and the display list.
+    pub display_list_build: Duration,
+}
+
+impl FrameStageTimings {
+    /// Add the timings of another display list that is part of the same frame.
+    pub fn accumulate(&mut self, other: &FrameStageTimings) {
+        self.script += other.script;
+        self.style += other.style;
+        self.layout += other.layout;
+        self.display_list_build += other.display_list_build;
+    }
+}
+
+/// A breakdown of the time spent producing a frame painted by Servo, as sent to the
+/// subscribers of `Servo::subscribe_to_frame_reports`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct FrameReport {
+    /// The number of this frame, counting from the first frame painted by Servo.
+    pub frame_number: u64,
+    /// The number of display lists that script and layout produced for this frame. When
+    /// this is zero, only the renderer updated the frame, for instance while scrolling.
+    pub display_lists: u32,
+    /// The time spent by script and layout on the display lists of this frame, summed
+    /// over all of them.
+    pub stages: FrameStageTimings,
+    /// The time spent by WebRender rendering this frame.
+    pub render: Duration,
+    /// The time spent presenting this frame, if it was presented with
+    /// `WebView::present`.
+    pub present: Option<Duration>,
+    /// The time since the previous frame, if both were part of the same animation.
+    pub frame_interval: Option<Duration>,
+    /// The interval between frames that the renderer aimed for, which follows the
+    /// refresh rate of the display.
+    pub refresh_interval: Duration,
+    /// The number of frames that should have been painted between the previous frame
+    /// and this one, but were not because producing them took too long.
+    pub dropped_frames: u32,
+    /// The number of frames dropped since Servo started.
+    pub total_dropped_frames: u64,
+}
+
+impl FrameReport {
+    /// Whether any frames were dropped right before this one.
+    pub fn is_janky(&self) -> bool {
+        self.dropped_frames > 0
+    }
+}
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::path::PathBuf;
+
 use keyboard_types::{Code, CompositionEvent, Key, KeyState, Location, Modifiers};
 use log::error;
 use malloc_size_of_derive::MallocSizeOf;
@@ -15,6 +17,7 @@ use crate::WebDriverMessageId;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum InputEvent {
     EditingAction(EditingActionEvent),
+    ExternalDrag(ExternalDragEvent),
     Gamepad(GamepadEvent),
     Ime(ImeEvent),
     Keyboard(KeyboardEvent),
@@ -34,10 +37,58 @@ pub enum EditingActionEvent {
     Paste,
 }
 
+/// The data of a drag-and-drop operation that crosses the boundary of a `WebView`, either
+/// because it is dragged onto the `WebView` from another application or because it is
+/// dragged out of the page.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DragData {
+    /// The plain text that is dragged.
+    pub text: Option<String>,
+    /// The HTML that is dragged, for instance when dragging a selection.
+    pub html: Option<String>,
+    /// The URL that is dragged, for instance when dragging a link or an image.
+    pub url: Option<String>,
+    /// The files that are dragged from the operating system.
+    pub files: Vec<PathBuf>,
+}
+
+/// The kind of an [`ExternalDragEvent`].
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ExternalDragEventType {
+    /// The drag moved over the `WebView`.
+    Over,
+    /// The drag left the `WebView` or was cancelled.
+    Leave,
+    /// The dragged data was dropped onto the `WebView`.
+    Drop,
+}
+
+/// A drag-and-drop operation from outside of a `WebView`, such as a drag of files from
+/// the file manager of the operating system. Only the names of dragged files are part of
+/// the event, so that their paths never reach script: they are handed to the file manager
+/// when content asks for the files of a drop.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExternalDragEvent {
+    pub event_type: ExternalDragEventType,
+    pub point: DevicePoint,
+    pub data: DragData,
+}
+
+impl ExternalDragEvent {
+    pub fn new(event_type: ExternalDragEventType, point: DevicePoint, data: DragData) -> Self {
+        Self {
+            event_type,
+            point,
+            data,
+        }
+    }
+}
+
 impl InputEvent {
     pub fn point(&self) -> Option<DevicePoint> {
         match self {
             InputEvent::EditingAction(..) => None,
+            InputEvent::ExternalDrag(event) => Some(event.point),
             InputEvent::Gamepad(..) => None,
             InputEvent::Ime(..) => None,
             InputEvent::Keyboard(..) => None,
@@ -53,6 +104,7 @@ impl InputEvent {
     pub fn webdriver_message_id(&self) -> Option<WebDriverMessageId> {
         match self {
             InputEvent::EditingAction(..) => None,
+            InputEvent::ExternalDrag(..) => None,
             InputEvent::Gamepad(..) => None,
             InputEvent::Ime(..) => None,
             InputEvent::Keyboard(event) => event.webdriver_id,
@@ -68,6 +120,7 @@ impl InputEvent {
     pub fn with_webdriver_message_id(mut self, webdriver_id: Option<WebDriverMessageId>) -> Self {
         match self {
             InputEvent::EditingAction(..) => {},
+            InputEvent::ExternalDrag(..) => {},
             InputEvent::Gamepad(..) => {},
             InputEvent::Ime(..) => {},
             InputEvent::Keyboard(ref mut event) => {
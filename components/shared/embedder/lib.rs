@@ -8,6 +8,7 @@
 //! is not exposed in the API or doesn't involve messages sent to the embedding/libservo layer, it
 //! is probably a better fit for the `constellation_traits` crate.
 
+mod frame_reports;
 pub mod input_events;
 pub mod resources;
 pub mod user_content_manager;
@@ -17,8 +18,10 @@ use std::collections::HashMap;
 use std::ffi::c_void;
 use std::fmt::{Debug, Display, Error, Formatter};
 use std::hash::Hash;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use base::id::{PipelineId, ScrollTreeNodeId, WebViewId};
 use crossbeam_channel::Sender;
@@ -32,13 +35,14 @@ use num_derive::FromPrimitive;
 use pixels::RasterImage;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use servo_geometry::{DeviceIndependentIntRect, DeviceIndependentIntSize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use strum_macros::IntoStaticStr;
 use style::queries::values::PrefersColorScheme;
 use style_traits::CSSPixel;
 use url::Url;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePixel};
 
+pub use crate::frame_reports::*;
 pub use crate::input_events::*;
 pub use crate::webdriver::*;
 
@@ -128,6 +132,31 @@ impl Clone for EmbedderProxy {
     }
 }
 
+/// An image on the system clipboard, as RGBA pixels that are not premultiplied.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba_pixels: Vec<u8>,
+}
+
+/// Contents that web content places on the system clipboard, in each of the formats that
+/// it can write. Formats that are `None` are left out.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ClipboardContents {
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub image: Option<ClipboardImage>,
+}
+
+/// The decoded icon of a page, as RGBA pixels that are not premultiplied.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Favicon {
+    pub width: u32,
+    pub height: u32,
+    pub rgba_pixels: Vec<u8>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum ContextMenuResult {
     Dismissed,
@@ -278,6 +307,15 @@ pub enum AllowOrDeny {
     Deny,
 }
 
+/// The response of the user to a request for permission to use a feature.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PermissionDecision {
+    pub allow_or_deny: AllowOrDeny,
+    /// Whether the decision should be remembered for the origin in later sessions, instead of
+    /// only until Servo exits.
+    pub remember: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SelectElementOption {
     /// A unique identifier for the option that can be used to select it.
@@ -332,6 +370,9 @@ pub enum EmbedderMsg {
     /// or `prompt()`). Since their messages are controlled by web content, they should be presented to the user in a
     /// way that makes them impossible to mistake for browser UI.
     ShowSimpleDialog(WebViewId, SimpleDialog),
+    /// A request made by the page in a webview was blocked by the content blocking filter
+    /// lists.
+    ContentBlocked(WebViewId, ServoUrl),
     /// Request authentication for a load or navigation from the embedder.
     RequestAuthentication(
         WebViewId,
@@ -364,31 +405,56 @@ pub enum EmbedderMsg {
     ClearClipboard(WebViewId),
     /// Gets system clipboard contents
     GetClipboardText(WebViewId, IpcSender<Result<String, String>>),
+    /// Gets the HTML contents of the system clipboard, if it contains HTML
+    GetClipboardHtml(WebViewId, IpcSender<Result<String, String>>),
+    /// Gets the image contents of the system clipboard, if it contains an image
+    GetClipboardImage(WebViewId, IpcSender<Result<ClipboardImage, String>>),
     /// Sets system clipboard contents
     SetClipboardText(WebViewId, String),
+    /// Sets system clipboard contents in several formats at once
+    SetClipboardContents(WebViewId, ClipboardContents),
+    /// Gets the UTF-8 byte ranges of the misspelled words in the text of an editable element.
+    CheckSpelling(WebViewId, String, IpcSender<Vec<Range<usize>>>),
+    /// Gets the suggested replacements for a misspelled word.
+    GetSpellingSuggestions(WebViewId, String, IpcSender<Vec<String>>),
     /// Changes the cursor.
     SetCursor(WebViewId, Cursor),
     /// A favicon was detected
     NewFavicon(WebViewId, ServoUrl),
+    /// The favicon at the given URL was loaded and decoded.
+    FaviconLoaded(WebViewId, ServoUrl, Favicon),
     /// The history state has changed.
     HistoryChanged(WebViewId, Vec<ServoUrl>, usize),
     /// Get the device independent window rectangle.
     GetWindowRect(WebViewId, IpcSender<DeviceIndependentIntRect>),
+    /// The page at the given URL failed to load. The embedder may respond with the HTML of a
+    /// page to show instead of the error page generated by Servo.
+    GetErrorPage(WebViewId, ServoUrl, LoadError, IpcSender<Option<String>>),
     /// Get the device independent screen size and available size.
     GetScreenMetrics(WebViewId, IpcSender<ScreenMetrics>),
     /// Entered or exited fullscreen.
     NotifyFullscreenStateChanged(WebViewId, bool),
     /// The [`LoadStatus`] of the Given `WebView` has changed.
     NotifyLoadStatusChanged(WebViewId, LoadStatus),
+    /// The [`LoadProgress`] of the page loading in the given `WebView` has changed.
+    NotifyLoadProgressChanged(WebViewId, LoadProgress),
+    /// The [`PaintMetrics`] of the page loaded in the given `WebView` have changed.
+    NotifyPaintMetricsChanged(WebViewId, PaintMetrics),
     WebResourceRequested(
         Option<WebViewId>,
         WebResourceRequest,
         IpcSender<WebResourceResponseMsg>,
     ),
-    /// A pipeline panicked. First string is the reason, second one is the backtrace.
-    Panic(WebViewId, String, Option<String>),
-    /// Open dialog to select bluetooth device.
-    GetSelectedBluetoothDevice(WebViewId, Vec<String>, IpcSender<Option<String>>),
+    /// A pipeline panicked or its content process crashed.
+    Panic(WebViewId, CrashDetails),
+    /// Ask the user to choose one of the given Bluetooth devices for the given origin,
+    /// responding with the address of the chosen device, if any.
+    SelectBluetoothDevice(
+        WebViewId,
+        String,
+        Vec<BluetoothDeviceDescription>,
+        IpcSender<Option<String>>,
+    ),
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
     SelectFiles(
         WebViewId,
@@ -396,8 +462,28 @@ pub enum EmbedderMsg {
         bool,
         IpcSender<Option<Vec<PathBuf>>>,
     ),
+    /// Open file dialog to choose the path of a file to save, optionally suggesting a
+    /// file name.
+    SelectFileToSave(
+        WebViewId,
+        Vec<FilterPattern>,
+        Option<String>,
+        IpcSender<Option<PathBuf>>,
+    ),
+    /// Open file dialog to select a directory.
+    SelectDirectory(WebViewId, IpcSender<Option<PathBuf>>),
+    /// Get the paths of the files that the user most recently dropped onto the webview.
+    GetDroppedFiles(WebViewId, IpcSender<Vec<PathBuf>>),
+    /// A drag-and-drop operation that started in the page left the webview, so that the
+    /// embedder can continue it outside of the webview.
+    StartDrag(WebViewId, DragData),
     /// Open interface to request permission specified by prompt.
-    PromptPermission(WebViewId, PermissionFeature, IpcSender<AllowOrDeny>),
+    PromptPermission(
+        WebViewId,
+        ImmutableOrigin,
+        PermissionFeature,
+        IpcSender<PermissionDecision>,
+    ),
     /// Request to present an IME to the user when an editable element is focused.
     /// If the input is text, the second parameter defines the pre-existing string
     /// text content and the zero-based index into the string locating the insertion point.
@@ -411,6 +497,9 @@ pub enum EmbedderMsg {
     ),
     /// Request to hide the IME when the editable element is blurred.
     HideIME(WebViewId),
+    /// The caret of the focused editable element moved, for instance while text is being
+    /// composed, so the IME should show its candidate window next to the given rectangle.
+    UpdateIMECursorArea(WebViewId, DeviceIntRect),
     /// Report a complete sampled profile
     ReportProfile(Vec<u8>),
     /// Notifies the embedder about media session events
@@ -430,8 +519,64 @@ pub enum EmbedderMsg {
     ShutdownComplete,
     /// Request to display a notification.
     ShowNotification(Option<WebViewId>, Notification),
+    /// Request to subscribe the service worker registration with the given scope to a push
+    /// service, with the public key of the application server, if any.
+    SubscribeToPush(
+        ServoUrl,
+        Option<Vec<u8>>,
+        IpcSender<Result<PushSubscription, PushSubscriptionError>>,
+    ),
+    /// Request the push subscription of the service worker registration with the given scope.
+    GetPushSubscription(ServoUrl, IpcSender<Option<PushSubscription>>),
+    /// Request to remove the push subscription of the service worker registration with the
+    /// given scope. The response is whether there was a subscription that was removed.
+    UnsubscribeFromPush(ServoUrl, IpcSender<bool>),
+    /// Request to create a public key credential with an authenticator of the embedder, for
+    /// `navigator.credentials.create()`.
+    CreatePublicKeyCredential(
+        WebViewId,
+        PublicKeyCredentialCreationParameters,
+        IpcSender<Result<AuthenticatorAttestation, WebAuthnError>>,
+    ),
+    /// Request to sign a challenge with a public key credential of an authenticator of the
+    /// embedder, for `navigator.credentials.get()`.
+    GetPublicKeyCredential(
+        WebViewId,
+        PublicKeyCredentialAssertionParameters,
+        IpcSender<Result<AuthenticatorAssertion, WebAuthnError>>,
+    ),
     /// Request to display a form control to the embedder.
     ShowFormControl(WebViewId, DeviceIntRect, FormControl),
+    /// Request to show the validation message of an invalid form control to the user, next
+    /// to the given rectangle of the control in the `WebView`.
+    ShowValidationMessage(WebViewId, DeviceIntRect, String),
+    /// Request to show a video in a floating picture-in-picture window, given the natural
+    /// size of the video. The response is the size of the window that was opened, or `None`
+    /// if the request was denied.
+    RequestPictureInPicture(WebViewId, DeviceIntSize, IpcSender<Option<DeviceIntSize>>),
+    /// Request to close the picture-in-picture window of the given `WebView`.
+    ExitPictureInPicture(WebViewId),
+    /// Request the current position of the device from the geolocation provider. The
+    /// boolean is whether a position of high accuracy was requested.
+    RequestGeolocationPosition(
+        WebViewId,
+        bool,
+        IpcSender<Result<GeolocationPosition, String>>,
+    ),
+    /// Request the geolocation provider to send the position of the device every time that
+    /// it changes, until the watch is cleared. The boolean is whether positions of high
+    /// accuracy were requested.
+    WatchGeolocationPosition(
+        WebViewId,
+        GeolocationWatchId,
+        bool,
+        IpcSender<Result<GeolocationPosition, String>>,
+    ),
+    /// Stop sending the position of the device for the given watch.
+    ClearGeolocationWatch(WebViewId, GeolocationWatchId),
+    /// Inform the embedding layer of the result of finding text in the page of the given
+    /// `WebView`.
+    FindInPageResult(WebViewId, FindInPageResult),
     /// Inform the embedding layer that a JavaScript evaluation has
     /// finished with the given result.
     FinishJavaScriptEvaluation(
@@ -450,13 +595,27 @@ impl Debug for EmbedderMsg {
 #[derive(Deserialize, Serialize)]
 pub enum FormControl {
     /// Indicates that the user has activated a `<select>` element.
+    ///
+    /// Contains the options of the element, the indices of the currently selected
+    /// options and whether more than one option may be selected. The embedder responds
+    /// with the indices of the selected options, or `None` if the popup was dismissed.
     SelectElement(
         Vec<SelectElementOptionOrOptgroup>,
-        Option<usize>,
-        IpcSender<Option<usize>>,
+        Vec<usize>,
+        bool,
+        IpcSender<Option<Vec<usize>>>,
     ),
     /// Indicates that the user has activated a `<input type=color>` element.
     ColorPicker(RgbColor, IpcSender<Option<RgbColor>>),
+    /// Indicates that the user has activated a `<input type=date>`, `<input type=time>`
+    /// or `<input type=datetime-local>` element.
+    ///
+    /// The embedder responds with the new value, or `None` if the picker was dismissed.
+    DateTimePicker(
+        DateTimePickerType,
+        DateTimePickerValues,
+        IpcSender<Option<String>>,
+    ),
 }
 
 /// Filter for file selection;
@@ -527,7 +686,7 @@ pub enum MediaSessionEvent {
 }
 
 /// Enum with variants that match the DOM PermissionName enum
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PermissionFeature {
     Geolocation,
     Notifications,
@@ -540,6 +699,13 @@ pub enum PermissionFeature {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    /// Reading the system clipboard with `navigator.clipboard`.
+    ClipboardRead,
+    /// Writing to the system clipboard with `navigator.clipboard`.
+    ClipboardWrite,
+    /// Unpartitioned access to cookies from a document embedded in another site, with
+    /// `document.requestStorageAccess()`.
+    StorageAccess,
 }
 
 /// Used to specify the kind of input method editor appropriate to edit a field.
@@ -674,6 +840,18 @@ impl From<Theme> for PrefersColorScheme {
     }
 }
 
+/// A stylesheet that the embedder applies at the user agent origin of all documents of a
+/// `WebView`, for instance to change the default margins or typography of pages.
+#[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum UserAgentStylesheetOverride {
+    /// Apply the given stylesheet after Servo's default user agent stylesheet.
+    Extend(String),
+    /// Apply the given stylesheet instead of Servo's default user agent stylesheet. The
+    /// stylesheets that Servo uses to render form controls and presentational hints still
+    /// apply.
+    Replace(String),
+}
+
 // The type of MediaSession action.
 /// <https://w3c.github.io/mediasession/#enumdef-mediasessionaction>
 #[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
@@ -711,12 +889,83 @@ pub enum LoadStatus {
     /// The `<head>` tag has been parsed in the currently loading page. At this point the page's
     /// `HTMLBodyElement` is now available in the DOM.
     HeadParsed,
+    /// The page has been parsed and its `DOMContentLoaded` event has been fired, but some of
+    /// its subresources may still be loading.
+    /// See <https://developer.mozilla.org/en-US/docs/Web/API/Document/DOMContentLoaded_event>
+    DomContentLoaded,
     /// The `Document` and all subresources have loaded. This is equivalent to
     /// `document.readyState` == `complete`.
     /// See <https://developer.mozilla.org/en-US/docs/Web/API/Document/readyState>
     Complete,
 }
 
+/// The progress of the load of the page in a `WebView`, see
+/// `WebViewDelegate::notify_load_progress_changed`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct LoadProgress {
+    /// An estimate of how much of the page has loaded, from 0 to 1, based on the resources
+    /// loaded so far and the bytes received of the page itself. It never decreases during
+    /// the load of a page, even when more subresources are discovered.
+    pub estimated_progress: f32,
+    /// The number of resources that delay the load event of the page which were requested so
+    /// far, including the page itself.
+    pub resources_requested: usize,
+    /// The number of those resources that finished loading.
+    pub resources_loaded: usize,
+    /// The number of bytes of the page itself that were received so far.
+    pub bytes_received: u64,
+    /// The size of the page itself, if the server sent its `Content-Length`.
+    pub bytes_expected: Option<u64>,
+}
+
+/// Why the page in a `WebView` failed to load, see `WebViewDelegate::error_page`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum LoadError {
+    /// The host of the URL could not be resolved to an address.
+    ResolveFailed(String),
+    /// The server refused the connection.
+    ConnectionRefused(String),
+    /// The certificate of the server could not be validated. Contains the reason and the DER
+    /// bytes of the certificate.
+    Certificate(String, Vec<u8>),
+    /// The server responded with an HTTP error status and an empty body. Contains the status
+    /// code and its reason phrase.
+    HttpStatus(u16, String),
+    /// Any other network error.
+    Network(String),
+}
+
+/// Details about a crash of the content of a `WebView`, see `WebViewDelegate::notify_crashed`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashDetails {
+    /// Why the content crashed, usually the message of a panic.
+    pub reason: String,
+    /// The backtrace of the panic, if one was captured.
+    pub backtrace: Option<String>,
+    /// The pipeline that crashed, if it is known. Otherwise, the crash happened somewhere
+    /// in the `WebView`, but it is not known in which of its pipelines.
+    pub pipeline_id: Option<PipelineId>,
+    /// The URL of the document of the crashed pipeline, or of the top-level document of
+    /// the `WebView` if the pipeline is not known.
+    pub url: Option<ServoUrl>,
+}
+
+/// The paint timing milestones of the page loaded in a `WebView`, measured from the
+/// start of the navigation to that page.
+///
+/// See <https://w3c.github.io/paint-timing/>.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PaintMetrics {
+    /// The time of the first paint of the page.
+    pub first_paint: Option<Duration>,
+    /// The time of the first paint of the page that included text, images or canvas.
+    pub first_contentful_paint: Option<Duration>,
+    /// The time of the paint of the largest image or text of the page so far, which stops
+    /// changing once the user interacts with the page.
+    /// See <https://w3c.github.io/largest-contentful-paint/>.
+    pub largest_contentful_paint: Option<Duration>,
+}
+
 /// Data that could be used to display a desktop notification to the end user
 /// when the [Notification API](<https://notifications.spec.whatwg.org/#notifications>) is called.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -753,6 +1002,163 @@ pub struct Notification {
     pub actions: Vec<NotificationAction>,
 }
 
+/// A subscription of a service worker registration to a push service, which application
+/// servers use to send push messages, see <https://w3c.github.io/push-api/#push-subscription>.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct PushSubscription {
+    /// The URL that application servers send push messages to.
+    pub endpoint: ServoUrl,
+    /// When the subscription expires, in milliseconds since the Unix epoch.
+    pub expiration_time: Option<u64>,
+    /// The P-256 ECDH public key of the subscription, in uncompressed form, used by
+    /// application servers to encrypt push messages.
+    pub p256dh: Vec<u8>,
+    /// The authentication secret of the subscription.
+    pub auth: Vec<u8>,
+}
+
+/// A position of the device, see <https://w3c.github.io/geolocation/#position_interface>.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct GeolocationPosition {
+    /// The latitude in decimal degrees.
+    pub latitude: f64,
+    /// The longitude in decimal degrees.
+    pub longitude: f64,
+    /// The accuracy of the latitude and longitude, in meters.
+    pub accuracy: f64,
+    /// The height above the WGS84 ellipsoid in meters, if known.
+    pub altitude: Option<f64>,
+    /// The accuracy of the altitude in meters, if known.
+    pub altitude_accuracy: Option<f64>,
+    /// The direction of travel in degrees clockwise from true north, if known.
+    pub heading: Option<f64>,
+    /// The horizontal speed in meters per second, if known.
+    pub speed: Option<f64>,
+}
+
+/// A Bluetooth device that content can be given access to by `requestDevice()`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BluetoothDeviceDescription {
+    /// The address of the device, which identifies it to the Bluetooth adapter.
+    pub address: String,
+    /// The name that the device advertises, if any.
+    pub name: Option<String>,
+}
+
+/// Identifies a watch of the position of the device by `watchPosition()`, by the pipeline
+/// of the document and the id of the watch in it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct GeolocationWatchId(pub PipelineId, pub i32);
+
+/// Whether the user has to be verified by the authenticator, for instance with a PIN or a
+/// fingerprint, see <https://w3c.github.io/webauthn/#enumdef-userverificationrequirement>.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UserVerificationRequirement {
+    Required,
+    Preferred,
+    Discouraged,
+}
+
+/// The parameters of the authenticatorMakeCredential operation,
+/// <https://w3c.github.io/webauthn/#sctn-op-make-cred>.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PublicKeyCredentialCreationParameters {
+    /// The origin of the document that creates the credential.
+    pub origin: ImmutableOrigin,
+    /// The id of the relying party, a domain that is the host of the origin or one of its
+    /// registrable suffixes.
+    pub rp_id: String,
+    /// The name of the relying party, to show to the user.
+    pub rp_name: String,
+    /// The user handle of the account that the credential is for.
+    pub user_id: Vec<u8>,
+    /// The name of the account, for instance an email address.
+    pub user_name: String,
+    /// The name of the user, to show to the user.
+    pub user_display_name: String,
+    /// The SHA-256 hash of the client data, which the authenticator signs.
+    pub client_data_hash: Vec<u8>,
+    /// The COSE identifiers of the algorithms that the relying party supports for the
+    /// credential, from most to least preferred.
+    pub algorithms: Vec<i32>,
+    /// The ids of credentials that the relying party already has for the account, which the
+    /// authenticator should not create another credential next to.
+    pub exclude_credentials: Vec<Vec<u8>>,
+    /// Whether the credential has to be discoverable, so that it can be used without the
+    /// relying party giving its id.
+    pub require_resident_key: bool,
+    pub user_verification: UserVerificationRequirement,
+}
+
+/// The parameters of the authenticatorGetAssertion operation,
+/// <https://w3c.github.io/webauthn/#sctn-op-get-assertion>.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PublicKeyCredentialAssertionParameters {
+    /// The origin of the document that uses the credential.
+    pub origin: ImmutableOrigin,
+    /// The id of the relying party, which the credential has to be scoped to.
+    pub rp_id: String,
+    /// The SHA-256 hash of the client data, which the authenticator signs.
+    pub client_data_hash: Vec<u8>,
+    /// The ids of the credentials that can be used, or empty if any discoverable credential
+    /// of the relying party can be.
+    pub allow_credentials: Vec<Vec<u8>>,
+    pub user_verification: UserVerificationRequirement,
+}
+
+/// A new public key credential, <https://w3c.github.io/webauthn/#authenticatorattestationresponse>.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuthenticatorAttestation {
+    /// The id of the credential.
+    pub credential_id: Vec<u8>,
+    /// The CBOR encoded attestation object, which contains the authenticator data with the
+    /// public key of the credential.
+    pub attestation_object: Vec<u8>,
+    /// The transports that the authenticator supports, such as "usb" or "internal".
+    pub transports: Vec<String>,
+    /// Whether the authenticator is built into the device ("platform") or not
+    /// ("cross-platform").
+    pub platform_attachment: bool,
+}
+
+/// A signature of a challenge with a public key credential,
+/// <https://w3c.github.io/webauthn/#authenticatorassertionresponse>.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuthenticatorAssertion {
+    /// The id of the credential that was used.
+    pub credential_id: Vec<u8>,
+    /// The authenticator data, which is signed with the client data hash.
+    pub authenticator_data: Vec<u8>,
+    /// The signature of the authenticator data and the client data hash.
+    pub signature: Vec<u8>,
+    /// The user handle of the account that the credential is for, if the credential is
+    /// discoverable.
+    pub user_handle: Option<Vec<u8>>,
+    /// Whether the authenticator is built into the device ("platform") or not
+    /// ("cross-platform").
+    pub platform_attachment: bool,
+}
+
+/// Why a public key credential could not be created or used.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum WebAuthnError {
+    /// The user cancelled, there is no authenticator, or it has no matching credential.
+    NotAllowed,
+    /// One of the excluded credentials is on the authenticator.
+    InvalidState,
+    /// The authenticator supports none of the algorithms.
+    NotSupported,
+}
+
+/// Why a push subscription could not be created.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum PushSubscriptionError {
+    /// The user or the embedder did not allow the subscription.
+    NotAllowed,
+    /// There is no push service, or it could not create the subscription.
+    PushServiceUnavailable,
+}
+
 /// Actions available for users to choose from for interacting with the notification.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct NotificationAction {
@@ -953,6 +1359,28 @@ impl Display for FocusSequenceNumber {
     }
 }
 
+/// A request to find text in the page of a `WebView`. Matches are highlighted and the
+/// active match is scrolled into view.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FindInPageRequest {
+    /// The text to find. Runs of white space in it match any run of white space in the page.
+    pub text: String,
+    /// Whether the text only matches text of the same case.
+    pub case_sensitive: bool,
+    /// Whether to move to the previous match rather than to the next one. When the text
+    /// differs from that of the previous request, finding backwards starts at the last match.
+    pub backwards: bool,
+}
+
+/// The result of finding text in the page of a `WebView`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FindInPageResult {
+    /// The number of matches of the text in the page.
+    pub number_of_matches: usize,
+    /// The index of the active match among all matches, or `None` if there are none.
+    pub active_match_index: Option<usize>,
+}
+
 /// An identifier for a particular JavaScript evaluation that is used to track the
 /// evaluation from the embedding layer to the script layer and then back.
 #[derive(Clone, Copy, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -968,6 +1396,7 @@ pub enum JSValue {
     Element(String),
     Frame(String),
     Window(String),
+    ShadowRoot(String),
     Array(Vec<JSValue>),
     Object(HashMap<String, JSValue>),
 }
@@ -984,6 +1413,7 @@ impl From<&WebDriverJSValue> for JSValue {
             WebDriverJSValue::Element(web_element) => Self::Element(web_element.0.clone()),
             WebDriverJSValue::Frame(web_frame) => Self::Frame(web_frame.0.clone()),
             WebDriverJSValue::Window(web_window) => Self::Window(web_window.0.clone()),
+            WebDriverJSValue::ShadowRoot(shadow_root) => Self::ShadowRoot(shadow_root.0.clone()),
             WebDriverJSValue::ArrayLike(vector) => {
                 Self::Array(vector.iter().map(Into::into).collect())
             },
@@ -1015,3 +1445,27 @@ pub struct RgbColor {
     pub green: u8,
     pub blue: u8,
 }
+
+/// The kind of value that is edited by a date or time picker.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DateTimePickerType {
+    /// A `<input type=date>`, whose value is a [date string](https://html.spec.whatwg.org/multipage/#valid-date-string).
+    Date,
+    /// A `<input type=time>`, whose value is a [time string](https://html.spec.whatwg.org/multipage/#valid-time-string).
+    Time,
+    /// A `<input type=datetime-local>`, whose value is a
+    /// [normalized local date and time string](https://html.spec.whatwg.org/multipage/#valid-normalised-local-date-and-time-string).
+    DatetimeLocal,
+}
+
+/// The values of a date or time `<input>` element that are relevant to its picker,
+/// serialized as described in <https://html.spec.whatwg.org/multipage/#dates-and-times>.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DateTimePickerValues {
+    /// The current value of the element, which is empty if no value was set.
+    pub value: String,
+    /// The value of the `min` attribute, if it is valid.
+    pub min: Option<String>,
+    /// The value of the `max` attribute, if it is valid.
+    pub max: Option<String>,
+}
@@ -81,15 +81,11 @@ pub enum Resource {
     /// A preloaded list of HTTP Strict Transport Security. It can be an empty list and
     /// `HstsList::default()` will be called.
     HstsPreloadList,
-    /// A HTML page to display when `net_traits::NetworkError::SslValidation` network error is
-    /// reported.
-    /// The page contains placeholder `${reason}` for the error code and `${bytes}` for the certificate bytes,
-    /// and also `${secret}` for the privileged secret.
-    /// It can be empty but then nothing will be displayed when a certificate error occurs.
-    BadCertHTML,
-    /// A HTML page to display when any network error occurs that is not related to SSL validation.
+    /// A HTML page to display when a page is blocked from being framed by the
+    /// `frame-ancestors` directive of its Content Security Policy. Pages that fail to load
+    /// because of network errors are shown a page generated by Servo instead.
     /// The message can contain a placeholder `${reason}` for the error code.
-    /// It can be empty but then nothing will be displayed when an internal error occurs.
+    /// It can be empty but then nothing will be displayed when framing is blocked.
     NetErrorHTML,
     /// A placeholder image to display if we couldn't get the requested image.
     ///
@@ -117,7 +113,6 @@ impl Resource {
             Resource::BluetoothBlocklist => "gatt_blocklist.txt",
             Resource::DomainList => "public_domains.txt",
             Resource::HstsPreloadList => "hsts_preload.fstmap",
-            Resource::BadCertHTML => "badcert.html",
             Resource::NetErrorHTML => "neterror.html",
             Resource::RippyPNG => "rippy.png",
             Resource::CrashHTML => "crash.html",
@@ -157,7 +152,6 @@ fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
                 Resource::HstsPreloadList => {
                     &include_bytes!("../../../resources/hsts_preload.fstmap")[..]
                 },
-                Resource::BadCertHTML => &include_bytes!("../../../resources/badcert.html")[..],
                 Resource::NetErrorHTML => &include_bytes!("../../../resources/neterror.html")[..],
                 Resource::RippyPNG => &include_bytes!("../../../resources/rippy.png")[..],
                 Resource::CrashHTML => &include_bytes!("../../../resources/crash.html")[..],
@@ -5,8 +5,10 @@
 #![allow(missing_docs)]
 
 use std::collections::HashMap;
+use std::time::Duration;
 
-use base::id::{BrowsingContextId, WebViewId};
+use base::Epoch;
+use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use cookie::Cookie;
 use euclid::default::Rect as UntypedRect;
 use euclid::{Rect, Size2D};
@@ -19,7 +21,7 @@ use serde::{Deserialize, Serialize};
 use servo_geometry::{DeviceIndependentIntRect, DeviceIndependentIntSize, DeviceIndependentPixel};
 use servo_url::ServoUrl;
 use style_traits::CSSPixel;
-use webdriver::common::{WebElement, WebFrame, WebWindow};
+use webdriver::common::{ShadowRoot, WebElement, WebFrame, WebWindow};
 use webdriver::error::ErrorStatus;
 use webrender_api::units::DevicePixel;
 
@@ -106,6 +108,13 @@ pub enum WebDriverCommandMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<RasterImage>>,
     ),
+    /// Take a screenshot of the window with the given rectangles of the viewport filled
+    /// with an opaque color, and return it with the metadata of the frame it shows.
+    TakeMaskedScreenshot(
+        WebViewId,
+        Vec<Rect<f32, CSSPixel>>,
+        IpcSender<Option<(RasterImage, ScreenshotMetadata)>>,
+    ),
     /// Create a new webview that loads about:blank. The constellation will use
     /// the provided channels to return the top level browsing context id
     /// associated with the new webview, and a notification when the initial
@@ -190,6 +199,10 @@ pub enum WebDriverScriptCommand {
     GetElementText(String, IpcSender<Result<String, ErrorStatus>>),
     GetElementInViewCenterPoint(String, IpcSender<Result<Option<(i64, i64)>, ErrorStatus>>),
     GetBoundingClientRect(String, IpcSender<Result<UntypedRect<f32>, ErrorStatus>>),
+    GetMaskedRects(
+        Vec<String>,
+        IpcSender<Result<Vec<UntypedRect<f32>>, ErrorStatus>>,
+    ),
     GetBrowsingContextId(
         WebDriverFrameId,
         IpcSender<Result<BrowsingContextId, ErrorStatus>>,
@@ -200,6 +213,11 @@ pub enum WebDriverScriptCommand {
     IsEnabled(String, IpcSender<Result<bool, ErrorStatus>>),
     IsSelected(String, IpcSender<Result<bool, ErrorStatus>>),
     GetTitle(IpcSender<String>),
+    /// Start recording which scripts are executed, for a later `TakeCoverage`.
+    StartCoverage(IpcSender<Result<(), ErrorStatus>>),
+    /// Stop recording script execution and return a JSON report of the scripts and style
+    /// rules that were used by the document.
+    TakeCoverage(IpcSender<Result<String, ErrorStatus>>),
     /// Match the element type before sending the event for webdriver `element send keys`.
     WillSendKeys(String, String, bool, IpcSender<Result<bool, ErrorStatus>>),
 }
@@ -215,6 +233,7 @@ pub enum WebDriverJSValue {
     Element(WebElement),
     Frame(WebFrame),
     Window(WebWindow),
+    ShadowRoot(ShadowRoot),
     ArrayLike(Vec<WebDriverJSValue>),
     Object(HashMap<String, WebDriverJSValue>),
 }
@@ -227,6 +246,7 @@ pub enum WebDriverJSError {
     JSException(WebDriverJSValue),
     JSError,
     StaleElementReference,
+    DetachedShadowRoot,
     Timeout,
     UnknownType,
 }
@@ -251,3 +271,37 @@ pub enum WebDriverLoadStatus {
     Canceled,
     Blocked,
 }
+
+/// Describes the frame that a masked screenshot was taken of, so that visual regression
+/// tests can tell whether two screenshots show the same rendering of a page.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScreenshotMetadata {
+    /// The epoch of the display list that was rendered for each pipeline of the webview.
+    pub pipeline_epochs: Vec<(PipelineId, Epoch)>,
+    /// The size of the viewport, in device pixels.
+    pub viewport_size: Size2D<i32, DevicePixel>,
+    /// The number of device pixels per CSS pixel, including the zoom of the page.
+    pub device_pixels_per_css_pixel: f32,
+    /// The paint and navigation timing of the document of the top-level pipeline, as of
+    /// the frame that the screenshot was taken of, or `None` if that document has not
+    /// reported its navigation start yet.
+    pub page_load_timing: Option<PageLoadTiming>,
+}
+
+/// Paint and navigation timing milestones of a document, as durations since its navigation
+/// start. Milestones that had not been reached when the timing was gathered are `None`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PageLoadTiming {
+    pub first_paint: Option<Duration>,
+    pub first_contentful_paint: Option<Duration>,
+    /// When the largest image or text seen so far was painted. This stops being updated
+    /// once the user interacts with the page.
+    /// See <https://w3c.github.io/largest-contentful-paint/>.
+    pub largest_contentful_paint: Option<Duration>,
+    /// The area of the largest contentful paint that is in the viewport, in square CSS
+    /// pixels.
+    pub largest_contentful_paint_size: Option<f32>,
+    pub dom_interactive: Option<Duration>,
+    pub dom_content_loaded_event_end: Option<Duration>,
+    pub load_event_end: Option<Duration>,
+}
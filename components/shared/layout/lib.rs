@@ -15,6 +15,7 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
+use std::time::Duration;
 
 use app_units::Au;
 use atomic_refcell::AtomicRefCell;
@@ -22,8 +23,9 @@ use base::Epoch;
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use bitflags::bitflags;
 use compositing_traits::CrossProcessCompositorApi;
+use compositing_traits::display_list::ScrollAnimation;
 use constellation_traits::LoadData;
-use embedder_traits::{Theme, UntrustedNodeAddress, ViewportDetails};
+use embedder_traits::{Theme, UntrustedNodeAddress, UserAgentStylesheetOverride, ViewportDetails};
 use euclid::default::{Point2D, Rect};
 use fnv::FnvHashMap;
 use fonts::{FontContext, SystemFontServiceProxy};
@@ -123,6 +125,20 @@ pub enum LayoutElementType {
     SVGSVGElement,
 }
 
+/// The kind of a highlight of a range of text, which decides how the highlighted text is
+/// painted.
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq)]
+pub enum HighlightKind {
+    /// A highlight that a page registered with the CSS Custom Highlight API.
+    /// <https://drafts.csswg.org/css-highlight-api-1/>
+    Custom,
+    /// A match of the text that the user is finding in the page.
+    FindMatch,
+    /// The match of the text that the user is finding in the page that is currently
+    /// active.
+    ActiveFindMatch,
+}
+
 pub struct HTMLCanvasData {
     pub source: Option<ImageKey>,
     pub width: u32,
@@ -198,6 +214,12 @@ pub struct LayoutConfig {
     pub compositor_api: CrossProcessCompositorApi,
     pub viewport_details: ViewportDetails,
     pub theme: Theme,
+    pub user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+    /// The stylesheets that the embedder applies at the user origin of the document.
+    pub user_stylesheets: Vec<String>,
+    /// The element hiding rules of the content blocking filter lists that apply to the
+    /// document, if any.
+    pub content_blocking_stylesheet: Option<String>,
 }
 
 pub trait LayoutFactory: Send + Sync {
@@ -238,6 +260,17 @@ pub trait Layout {
     /// Removes a stylesheet from the Layout.
     fn remove_stylesheet(&mut self, stylesheet: ServoArc<Stylesheet>);
 
+    /// Set or clear the stylesheet that the embedder applies at the user agent origin,
+    /// replacing the previous one. The next reflow restyles the document with it.
+    fn set_user_agent_stylesheet_override(
+        &mut self,
+        stylesheet_override: Option<UserAgentStylesheetOverride>,
+    );
+
+    /// Replace the stylesheets that the embedder applies at the user origin. The next
+    /// reflow restyles the document with them.
+    fn set_user_stylesheets(&mut self, user_stylesheets: Vec<String>);
+
     /// Requests a reflow.
     fn reflow(&mut self, reflow_request: ReflowRequest) -> Option<ReflowResult>;
 
@@ -263,11 +296,25 @@ pub trait Layout {
     fn query_content_boxes(&self, node: TrustedNodeAddress) -> Vec<Rect<Au>>;
     fn query_client_rect(&self, node: TrustedNodeAddress) -> Rect<i32>;
     fn query_element_inner_outer_text(&self, node: TrustedNodeAddress) -> String;
+    /// Get the rendered text of the part of the subtree of `root` between the boundary
+    /// points `start` and `end`, as it would be returned by `innerText`.
+    fn query_rendered_text_of_range(
+        &self,
+        root: TrustedNodeAddress,
+        start: (TrustedNodeAddress, usize),
+        end: (TrustedNodeAddress, usize),
+    ) -> String;
     fn query_nodes_from_point(
         &self,
         point: Point2D<f32>,
         query_type: NodesFromPointQueryType,
     ) -> Vec<UntrustedNodeAddress>;
+    /// Find the node and the UTF-8 offset within its text of the caret position closest
+    /// to the given point in the viewport.
+    fn query_caret_position(&self, point: Point2D<f32>) -> Option<(UntrustedNodeAddress, usize)>;
+    /// Get the rectangle of the caret in the text of the text control `node`, relative to
+    /// the initial containing block.
+    fn query_text_cursor_rect(&self, node: TrustedNodeAddress) -> Option<Rect<Au>>;
     fn query_offset_parent(&self, node: TrustedNodeAddress) -> OffsetParentResponse;
     fn query_resolved_style(
         &self,
@@ -314,6 +361,7 @@ pub enum NodesFromPointQueryType {
 
 #[derive(Debug, PartialEq)]
 pub enum QueryMsg {
+    CaretPositionQuery,
     ContentBox,
     ContentBoxes,
     ClientRectQuery,
@@ -324,7 +372,9 @@ pub enum QueryMsg {
     ResolvedStyleQuery,
     StyleQuery,
     ElementInnerOuterTextQuery,
+    RenderedTextOfRangeQuery,
     ResolvedFontStyleQuery,
+    TextCursorRectQuery,
     InnerWindowDimensionsQuery,
 }
 
@@ -344,8 +394,9 @@ pub enum ReflowGoal {
     LayoutQuery(QueryMsg),
 
     /// Tells layout about a single new scrolling offset from the script. The rest will
-    /// remain untouched and layout won't forward this back to script.
-    UpdateScrollNode(ExternalScrollId, LayoutVector2D),
+    /// remain untouched and layout won't forward this back to script. The
+    /// [`ScrollAnimation`] describes how the renderer moves the node to its new offset.
+    UpdateScrollNode(ExternalScrollId, LayoutVector2D, ScrollAnimation),
 }
 
 #[derive(Clone, Debug, MallocSizeOf)]
@@ -434,6 +485,9 @@ pub struct ReflowRequest {
     pub theme: Theme,
     /// The node highlighted by the devtools, if any
     pub highlighted_dom_node: Option<OpaqueNode>,
+    /// The time that script spent updating the rendering before asking for this reflow,
+    /// which is reported along with the display list that it produces.
+    pub script_time: Duration,
 }
 
 impl ReflowRequest {
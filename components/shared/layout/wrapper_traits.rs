@@ -26,7 +26,7 @@ use style::stylist::RuleInclusion;
 
 use crate::{
     FragmentType, GenericLayoutData, GenericLayoutDataTrait, HTMLCanvasData, HTMLMediaData,
-    LayoutNodeType, SVGSVGData, StyleData,
+    HighlightKind, LayoutNodeType, SVGSVGData, StyleData,
 };
 
 pub trait LayoutDataTrait: GenericLayoutDataTrait + Default + Send + Sync + 'static {}
@@ -215,6 +215,18 @@ pub trait ThreadSafeLayoutNode<'dom>: Clone + Copy + Debug + NodeInfo + PartialE
     /// If selection intersects this node, return it. Otherwise, returns `None`.
     fn selection(&self) -> Option<Range<ByteIndex>>;
 
+    /// If an input method is composing text in this node, return the range of that text.
+    /// Otherwise, returns `None`.
+    fn composition(&self) -> Option<Range<ByteIndex>>;
+
+    /// Return the ranges of the words in the text of this node that the spellchecker has
+    /// flagged as misspelled.
+    fn misspellings(&self) -> Vec<Range<ByteIndex>>;
+
+    /// Return the ranges of the text of this node that are highlighted, in the order in
+    /// which they are painted, with the kind of each highlight.
+    fn highlights(&self) -> Vec<(Range<ByteIndex>, HighlightKind)>;
+
     /// If this is an image element, returns its URL. If this is not an image element, fails.
     fn image_url(&self) -> Option<ServoUrl>;
 
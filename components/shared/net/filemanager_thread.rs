@@ -131,6 +131,8 @@ pub struct SelectedFile {
     pub size: u64,
     // https://w3c.github.io/FileAPI/#dfn-type
     pub type_string: String,
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    pub relative_path: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -153,6 +155,22 @@ pub enum FileManagerThreadMsg {
         Option<Vec<PathBuf>>,
     ),
 
+    /// Select a directory and all the files it contains, recursively. Last field is
+    /// pre-selected directory path for testing
+    SelectDirectory(
+        WebViewId,
+        IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        FileOrigin,
+        Option<PathBuf>,
+    ),
+
+    /// Select the files that the user most recently dropped onto the webview
+    SelectDroppedFiles(
+        WebViewId,
+        IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        FileOrigin,
+    ),
+
     /// Read FileID-indexed file in chunks, optionally check URL validity based on boolean flag
     ReadFile(
         IpcSender<FileManagerResult<ReadFileProgress>>,
@@ -180,6 +198,162 @@ pub enum FileManagerThreadMsg {
 
     /// Revoke Blob URL and send back the acknowledgement
     RevokeBlobURL(Uuid, FileOrigin, IpcSender<Result<(), BlobURLStoreError>>),
+
+    /// Ask the embedder to pick files to open for `showOpenFilePicker()`, and send back the
+    /// entries that the origin was granted access to. The boolean allows picking multiple
+    /// files.
+    ShowOpenFilePicker(
+        WebViewId,
+        bool,
+        Vec<FilterPattern>,
+        IpcSender<FileSystemResult<Vec<FileSystemEntry>>>,
+        FileOrigin,
+    ),
+
+    /// Ask the embedder to pick a file to save for `showSaveFilePicker()`, and send back
+    /// the entry that the origin was granted access to. The file is created if it does
+    /// not exist.
+    ShowSaveFilePicker(
+        WebViewId,
+        Option<String>,
+        Vec<FilterPattern>,
+        IpcSender<FileSystemResult<FileSystemEntry>>,
+        FileOrigin,
+    ),
+
+    /// An operation on a file system entry, or on a file opened from one.
+    FileSystem(FileSystemMsg, FileOrigin),
+}
+
+/// The root that the path of a [`FileSystemLocator`] is relative to.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FileSystemRoot {
+    /// <https://fs.spec.whatwg.org/#origin-private-file-system> of the origin.
+    OriginPrivate,
+    /// An entry that the user picked with a file picker.
+    Picked(Uuid),
+}
+
+/// <https://fs.spec.whatwg.org/#file-system-locator>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct FileSystemLocator {
+    pub root: FileSystemRoot,
+    /// The names of the entries from the root to the located entry.
+    pub path: Vec<String>,
+}
+
+impl FileSystemLocator {
+    /// The locator of a child entry named `name` of the directory located by `self`.
+    pub fn child(&self, name: String) -> FileSystemLocator {
+        let mut path = self.path.clone();
+        path.push(name);
+        FileSystemLocator {
+            root: self.root.clone(),
+            path,
+        }
+    }
+}
+
+/// <https://fs.spec.whatwg.org/#valid-file-name>
+pub fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// <https://fs.spec.whatwg.org/#locator-kind>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FileSystemHandleKind {
+    File,
+    Directory,
+}
+
+/// An entry of a file system, as seen by script.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileSystemEntry {
+    pub locator: FileSystemLocator,
+    pub kind: FileSystemHandleKind,
+    pub name: String,
+}
+
+/// How a file of a file system is opened.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum FileSystemOpenMode {
+    /// For a `FileSystemWritableFileStream`, whose writes go to a swap file that replaces
+    /// the file when the stream is closed. This takes a shared lock on the file.
+    Writable { keep_existing_data: bool },
+    /// For a `FileSystemSyncAccessHandle`, whose writes go to the file directly. This takes
+    /// an exclusive lock on the file.
+    SyncAccess,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum FileSystemMsg {
+    /// Get the child entry `name` of a directory, creating it if requested.
+    GetChild {
+        parent: FileSystemLocator,
+        name: String,
+        kind: FileSystemHandleKind,
+        create: bool,
+        sender: IpcSender<FileSystemResult<FileSystemEntry>>,
+    },
+    /// List the children of a directory.
+    Entries(
+        FileSystemLocator,
+        IpcSender<FileSystemResult<Vec<FileSystemEntry>>>,
+    ),
+    /// Remove the child entry `name` of a directory.
+    RemoveEntry {
+        parent: FileSystemLocator,
+        name: String,
+        recursive: bool,
+        sender: IpcSender<FileSystemResult<()>>,
+    },
+    /// Create a snapshot of a file, as a file manager entry for a `File`.
+    GetFile(FileSystemLocator, IpcSender<FileSystemResult<SelectedFile>>),
+    /// Open a file, and send back the id to use for operations on it.
+    Open(
+        FileSystemLocator,
+        FileSystemOpenMode,
+        IpcSender<FileSystemResult<Uuid>>,
+    ),
+    /// An operation on a file that was opened.
+    OpenFile(Uuid, OpenFileOperation),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum OpenFileOperation {
+    Read {
+        at: u64,
+        len: u64,
+        sender: IpcSender<FileSystemResult<Vec<u8>>>,
+    },
+    Write {
+        at: u64,
+        data: Vec<u8>,
+        sender: IpcSender<FileSystemResult<u64>>,
+    },
+    Truncate(u64, IpcSender<FileSystemResult<()>>),
+    GetSize(IpcSender<FileSystemResult<u64>>),
+    Flush(IpcSender<FileSystemResult<()>>),
+    /// Close the file, replacing it with the swap file of a writable stream and
+    /// releasing its lock.
+    Close(IpcSender<FileSystemResult<()>>),
+    /// Close the file, discarding the swap file of a writable stream and releasing its lock.
+    Abort,
+}
+
+pub type FileSystemResult<T> = Result<T, FileSystemError>;
+
+/// The errors of file system operations, which script turns into `DOMException`s.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum FileSystemError {
+    NotFound,
+    TypeMismatch,
+    InvalidModification,
+    NoModificationAllowed,
+    InvalidState,
+    /// The user dismissed a file picker.
+    Abort,
+    Io(String),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
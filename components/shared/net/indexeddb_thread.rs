@@ -2,7 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cmp::Ordering;
+use std::time::SystemTime;
+
 use ipc_channel::ipc::IpcSender;
+use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
 use servo_url::origin::ImmutableOrigin;
 
@@ -14,22 +18,76 @@ pub enum IndexedDBTxnMode {
     Versionchange,
 }
 
+// https://w3c.github.io/IndexedDB/#enumdef-idbtransactiondurability
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum IndexedDBTxnDurability {
+    Default,
+    Strict,
+    Relaxed,
+}
+
 // https://www.w3.org/TR/IndexedDB-2/#key-type
-// FIXME:(arihant2math) Ordering needs to completely be reimplemented as per https://www.w3.org/TR/IndexedDB-2/#compare-two-keys
-#[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum IndexedDBKeyType {
     Number(f64),
     String(String),
     Binary(Vec<u8>),
-    // FIXME:(arihant2math) Date should not be stored as a Vec<u8>
-    Date(Vec<u8>),
+    /// The time value of the date, in milliseconds since the Unix epoch.
+    Date(f64),
     Array(Vec<IndexedDBKeyType>),
-    // FIXME:(arihant2math) implment ArrayBuffer
 }
 
+impl IndexedDBKeyType {
+    /// The order of the types of keys, from lowest to highest, used when comparing keys
+    /// of different types.
+    fn type_order(&self) -> u8 {
+        match self {
+            IndexedDBKeyType::Number(_) => 0,
+            IndexedDBKeyType::Date(_) => 1,
+            IndexedDBKeyType::String(_) => 2,
+            IndexedDBKeyType::Binary(_) => 3,
+            IndexedDBKeyType::Array(_) => 4,
+        }
+    }
+}
+
+impl Ord for IndexedDBKeyType {
+    // https://www.w3.org/TR/IndexedDB-2/#compare-two-keys
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // NaN is never a valid key, so numbers and dates are totally ordered, and -0 is
+            // equal to 0.
+            (IndexedDBKeyType::Number(a), IndexedDBKeyType::Number(b)) |
+            (IndexedDBKeyType::Date(a), IndexedDBKeyType::Date(b)) => {
+                a.partial_cmp(b).unwrap_or(Ordering::Equal)
+            },
+            // Strings are compared by their code units.
+            (IndexedDBKeyType::String(a), IndexedDBKeyType::String(b)) => {
+                a.encode_utf16().cmp(b.encode_utf16())
+            },
+            (IndexedDBKeyType::Binary(a), IndexedDBKeyType::Binary(b)) => a.cmp(b),
+            (IndexedDBKeyType::Array(a), IndexedDBKeyType::Array(b)) => a.cmp(b),
+            _ => self.type_order().cmp(&other.type_order()),
+        }
+    }
+}
+
+impl PartialOrd for IndexedDBKeyType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexedDBKeyType {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexedDBKeyType {}
+
 // <https://www.w3.org/TR/IndexedDB-2/#key-range>
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
-#[allow(unused)]
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, Serialize)]
 pub struct IndexedDBKeyRange {
     pub lower: Option<IndexedDBKeyType>,
     pub upper: Option<IndexedDBKeyType>,
@@ -48,6 +106,29 @@ impl From<IndexedDBKeyType> for IndexedDBKeyRange {
 }
 
 impl IndexedDBKeyRange {
+    // <https://w3c.github.io/IndexedDB/#unbounded-key-range>
+    pub fn unbounded() -> Self {
+        Self::default()
+    }
+
+    // <https://w3c.github.io/IndexedDB/#dom-idbkeyrange-lowerbound>
+    pub fn lower_bound(key: IndexedDBKeyType, open: bool) -> Self {
+        IndexedDBKeyRange {
+            lower: Some(key),
+            lower_open: open,
+            ..Default::default()
+        }
+    }
+
+    // <https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upperbound>
+    pub fn upper_bound(key: IndexedDBKeyType, open: bool) -> Self {
+        IndexedDBKeyRange {
+            upper: Some(key),
+            upper_open: open,
+            ..Default::default()
+        }
+    }
+
     // <https://www.w3.org/TR/IndexedDB-2/#in>
     pub fn contains(&self, key: &IndexedDBKeyType) -> bool {
         // A key is in a key range if both of the following conditions are fulfilled:
@@ -67,31 +148,135 @@ impl IndexedDBKeyRange {
     }
 }
 
+// https://w3c.github.io/IndexedDB/#key-path-construct
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum KeyPath {
+    String(String),
+    Sequence(Vec<String>),
+}
+
+// https://w3c.github.io/IndexedDB/#index-concept
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct IndexMetadata {
+    pub name: String,
+    pub key_path: KeyPath,
+    // https://w3c.github.io/IndexedDB/#index-unique-flag
+    pub unique: bool,
+    // https://w3c.github.io/IndexedDB/#index-multientry-flag
+    pub multi_entry: bool,
+}
+
+// https://w3c.github.io/IndexedDB/#object-store-concept
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ObjectStoreMetadata {
+    pub name: String,
+    pub key_path: Option<KeyPath>,
+    // Whether the object store has a key generator,
+    // see https://w3c.github.io/IndexedDB/#key-generator
+    pub auto_increment: bool,
+    pub indexes: Vec<IndexMetadata>,
+}
+
+// https://w3c.github.io/IndexedDB/#enumdef-idbcursordirection
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum IndexedDBCursorDirection {
+    Next,
+    NextUnique,
+    Prev,
+    PrevUnique,
+}
+
+/// The keys of a record in each index of its object store, computed by evaluating the key
+/// path of the index on the value of the record.
+pub type IndexKeys = Vec<(
+    String,                // Index
+    Vec<IndexedDBKeyType>, // Keys of the record in the index
+)>;
+
+/// The arguments to <https://w3c.github.io/IndexedDB/#iterate-a-cursor>.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IterationParams {
+    /// The name of the index the cursor iterates over, or none for an object store.
+    pub index: Option<String>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-range>
+    pub range: IndexedDBKeyRange,
+    /// <https://w3c.github.io/IndexedDB/#cursor-direction>
+    pub direction: IndexedDBCursorDirection,
+    /// The key to continue to, passed to `continue()` or `continuePrimaryKey()`.
+    pub key: Option<IndexedDBKeyType>,
+    /// The primary key to continue to, passed to `continuePrimaryKey()`.
+    pub primary_key: Option<IndexedDBKeyType>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-position>
+    pub position: Option<IndexedDBKeyType>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-object-store-position>
+    pub object_store_position: Option<IndexedDBKeyType>,
+    /// How many records to step over, passed to `advance()`.
+    pub count: u32,
+    /// <https://w3c.github.io/IndexedDB/#cursor-key-only-flag>
+    pub key_only: bool,
+}
+
+/// A record found by iterating a cursor.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IndexedDBRecord {
+    pub key: IndexedDBKeyType,
+    pub primary_key: IndexedDBKeyType,
+    /// The serialized value of the record, unless the cursor only iterates keys.
+    pub value: Option<Vec<u8>>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum AsyncReadOnlyOperation {
-    /// Gets the value associated with the given key in the associated idb data
+    /// Gets the value of the first record in the key range
     GetItem(
-        IndexedDBKeyType, // Key
+        IndexedDBKeyRange,
+        Option<String>, // Index
+    ),
+
+    /// Gets the key of the first record in the key range
+    GetKey(
+        IndexedDBKeyRange,
+        Option<String>, // Index
+    ),
+
+    /// Gets the values of the records in the key range
+    GetAll(
+        IndexedDBKeyRange,
+        Option<u32>,    // Count
+        Option<String>, // Index
+    ),
+
+    /// Gets the keys of the records in the key range
+    GetAllKeys(
+        IndexedDBKeyRange,
+        Option<u32>,    // Count
+        Option<String>, // Index
     ),
 
     Count(
-        IndexedDBKeyType, // Key
+        IndexedDBKeyRange,
+        Option<String>, // Index
     ),
+
+    /// Finds the next record of a cursor
+    Iterate(IterationParams),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum AsyncReadWriteOperation {
     /// Sets the value of the given key in the associated idb data
     PutItem(
-        IndexedDBKeyType, // Key
-        Vec<u8>,          // Value
-        bool,             // Should overwrite
+        Option<IndexedDBKeyType>, // Key, or none to use the key generator of the store
+        Vec<u8>,                  // Value
+        bool,                     // Should overwrite
+        IndexKeys,
     ),
 
-    /// Removes the key/value pair for the given key in the associated idb data
-    RemoveItem(
-        IndexedDBKeyType, // Key
-    ),
+    /// Removes the records in the key range from the associated idb data
+    RemoveItem(IndexedDBKeyRange),
+
+    /// Removes all records from the associated idb data
+    Clear,
 }
 
 /// Operations that are not executed instantly, but rather added to a
@@ -102,6 +287,47 @@ pub enum AsyncOperation {
     ReadWrite(AsyncReadWriteOperation),
 }
 
+/// The result of an [`AsyncOperation`].
+#[derive(Debug, Deserialize, Serialize)]
+pub enum AsyncOperationResult {
+    /// A record with its serialized value, or none if there was no record. The primary key
+    /// is included so it can be injected into the value, for stores with a key generator.
+    Value(Option<IndexedDBRecord>),
+    /// A key, or none if there was no record.
+    Key(Option<IndexedDBKeyType>),
+    Values(Vec<IndexedDBRecord>),
+    Keys(Vec<IndexedDBKeyType>),
+    Count(u64),
+    /// The next record of a cursor, or none if the cursor reached the end of its range.
+    Record(Option<IndexedDBRecord>),
+    /// The operation has no result, e.g. when removing records.
+    Undefined,
+}
+
+/// Why an operation on a database failed.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum BackendError {
+    /// The operation would violate a constraint of the database, e.g. adding a record with
+    /// a key that already exists.
+    Constraint,
+    /// The origin has run out of storage space.
+    QuotaExceeded,
+    /// The backing store failed.
+    DbErr(String),
+}
+
+pub type BackendResult<T> = Result<T, BackendError>;
+
+/// The disk space used by the databases of an origin.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexedDBOriginUsage {
+    pub origin: ImmutableOrigin,
+    /// The size of the databases, in bytes
+    pub usage: u64,
+    /// When one of the databases was last written to
+    pub last_modified: Option<SystemTime>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum SyncOperation {
     /// Upgrades the version of the database
@@ -112,17 +338,33 @@ pub enum SyncOperation {
         u64,    // Serial number for the transaction
         u64,    // Version to upgrade to
     ),
-    /// Checks if an object store has a key generator, used in e.g. Put
-    HasKeyGenerator(
-        IpcSender<bool>,
+    /// Gets the metadata of an object store, e.g. its key path and indexes
+    GetObjectStore(
+        IpcSender<Option<ObjectStoreMetadata>>,
         ImmutableOrigin,
         String, // Database
         String, // Store
     ),
 
+    /// Gets the names of the object stores of the database
+    ObjectStoreNames(
+        IpcSender<Vec<String>>,
+        ImmutableOrigin,
+        String, // Database
+    ),
+
     /// Commits changes of a transaction to the database
     Commit(
-        IpcSender<Result<(), ()>>,
+        IpcSender<BackendResult<()>>,
+        ImmutableOrigin,
+        String, // Database
+        u64,    // Transaction serial number
+        IndexedDBTxnDurability,
+    ),
+
+    /// Reverts the changes made by a transaction
+    Abort(
+        IpcSender<BackendResult<()>>,
         ImmutableOrigin,
         String, // Database
         u64,    // Transaction serial number
@@ -130,18 +372,50 @@ pub enum SyncOperation {
 
     /// Creates a new store for the database
     CreateObjectStore(
+        IpcSender<Result<(), ()>>,
+        ImmutableOrigin,
+        String,          // Database
+        String,          // Store
+        Option<KeyPath>, // Key path
+        bool,            // Auto increment
+    ),
+
+    DeleteObjectStore(
         IpcSender<Result<(), ()>>,
         ImmutableOrigin,
         String, // Database
         String, // Store
-        bool,
     ),
 
-    DeleteObjectStore(
+    /// Gets the primary keys and values of all records in a store, used to populate
+    /// a new index
+    GetAllRecords(
+        IpcSender<BackendResult<Vec<(IndexedDBKeyType, Vec<u8>)>>>,
+        ImmutableOrigin,
+        String, // Database
+        String, // Store
+    ),
+
+    /// Creates a new index for a store, with the keys of the records that are
+    /// already in the store
+    CreateIndex(
+        IpcSender<BackendResult<()>>,
+        ImmutableOrigin,
+        String, // Database
+        String, // Store
+        IndexMetadata,
+        Vec<(
+            IndexedDBKeyType,      // Primary key
+            Vec<IndexedDBKeyType>, // Keys of the record in the index
+        )>,
+    ),
+
+    DeleteIndex(
         IpcSender<Result<(), ()>>,
         ImmutableOrigin,
         String, // Database
         String, // Store
+        String, // Index
     ),
 
     CloseDatabase(
@@ -151,7 +425,7 @@ pub enum SyncOperation {
     ),
 
     OpenDatabase(
-        IpcSender<u64>, // Returns the version
+        IpcSender<BackendResult<u64>>, // Returns the version
         ImmutableOrigin,
         String,      // Database
         Option<u64>, // Eventual version
@@ -188,6 +462,15 @@ pub enum SyncOperation {
         String, // Database
     ),
 
+    /// Gets the disk space used by the databases of every origin
+    Usage(IpcSender<Vec<IndexedDBOriginUsage>>),
+
+    /// Deletes all the databases of an origin
+    DeleteOrigin(IpcSender<()>, ImmutableOrigin),
+
+    /// Deletes all the databases of every origin
+    DeleteAll(IpcSender<()>),
+
     /// Send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
@@ -196,7 +479,7 @@ pub enum SyncOperation {
 pub enum IndexedDBThreadMsg {
     Sync(SyncOperation),
     Async(
-        IpcSender<Option<Vec<u8>>>, // Sender to send the result of the async operation
+        IpcSender<BackendResult<AsyncOperationResult>>, // Sender to send the result of the async operation
         ImmutableOrigin,
         String, // Database
         String, // ObjectStore
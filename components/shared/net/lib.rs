@@ -6,16 +6,16 @@
 
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::sync::{LazyLock, OnceLock};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 use std::thread;
 
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::HistoryStateId;
+use base::id::{HistoryStateId, PipelineId};
 use content_security_policy::{self as csp};
 use cookie::Cookie;
 use crossbeam_channel::{Receiver, Sender, unbounded};
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
-use http::{Error as HttpError, HeaderMap, HeaderValue, StatusCode, header};
+use http::{Error as HttpError, HeaderMap, HeaderValue, Method, StatusCode, header};
 use hyper_serde::Serde;
 use hyper_util::client::legacy::Error as HyperError;
 use ipc_channel::Error as IpcError;
@@ -32,8 +32,8 @@ use servo_url::{ImmutableOrigin, ServoUrl};
 
 use crate::filemanager_thread::FileManagerThreadMsg;
 use crate::http_status::HttpStatus;
-use crate::indexeddb_thread::IndexedDBThreadMsg;
-use crate::request::{Request, RequestBuilder};
+use crate::indexeddb_thread::{IndexedDBThreadMsg, SyncOperation};
+use crate::request::{Destination, Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
 
@@ -105,10 +105,36 @@ impl CustomResponse {
     }
 }
 
+/// The outcome of dispatching a `FetchEvent` for a request in a service worker, see
+/// <https://w3c.github.io/ServiceWorker/#handle-fetch>.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ServiceWorkerResponse {
+    /// No active service worker handled the request, so it is fetched from the network.
+    Fallback,
+    /// The response that the service worker passed to `respondWith()`.
+    Custom(CustomResponse),
+    /// The service worker responded with an error, or with something that is not a `Response`.
+    NetworkError,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CustomResponseMediator {
-    pub response_chan: IpcSender<Option<CustomResponse>>,
+    pub response_chan: IpcSender<ServiceWorkerResponse>,
     pub load_url: ServoUrl,
+    /// The method of the intercepted request.
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    /// The headers of the intercepted request.
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    /// The destination of the intercepted request.
+    pub destination: Destination,
 }
 
 /// [Policies](https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states)
@@ -136,6 +162,25 @@ pub enum ReferrerPolicy {
     StrictOriginWhenCrossOrigin,
 }
 
+impl ReferrerPolicy {
+    /// <https://w3c.github.io/webappsec-referrer-policy/#determine-policy-for-token>
+    pub fn from_token(token: &str) -> ReferrerPolicy {
+        match token.to_ascii_lowercase().as_str() {
+            "never" | "no-referrer" => ReferrerPolicy::NoReferrer,
+            "no-referrer-when-downgrade" => ReferrerPolicy::NoReferrerWhenDowngrade,
+            "origin" => ReferrerPolicy::Origin,
+            "same-origin" => ReferrerPolicy::SameOrigin,
+            "strict-origin" => ReferrerPolicy::StrictOrigin,
+            "default" | "strict-origin-when-cross-origin" => {
+                ReferrerPolicy::StrictOriginWhenCrossOrigin
+            },
+            "origin-when-cross-origin" => ReferrerPolicy::OriginWhenCrossOrigin,
+            "always" | "unsafe-url" => ReferrerPolicy::UnsafeUrl,
+            _ => ReferrerPolicy::EmptyString,
+        }
+    }
+}
+
 impl Display for ReferrerPolicy {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
@@ -197,6 +242,7 @@ impl From<ReferrerPolicy> for ReferrerPolicyHeader {
 pub enum FetchResponseMsg {
     // todo: should have fields for transmitted/total bytes
     ProcessRequestBody(RequestId),
+    ProcessRequestBodyChunkLength(RequestId, usize),
     ProcessRequestEOF(RequestId),
     // todo: send more info about the response (or perhaps the entire Response)
     ProcessResponse(RequestId, Result<FetchMetadata, NetworkError>),
@@ -209,6 +255,7 @@ impl FetchResponseMsg {
     pub fn request_id(&self) -> RequestId {
         match self {
             FetchResponseMsg::ProcessRequestBody(id) |
+            FetchResponseMsg::ProcessRequestBodyChunkLength(id, ..) |
             FetchResponseMsg::ProcessRequestEOF(id) |
             FetchResponseMsg::ProcessResponse(id, ..) |
             FetchResponseMsg::ProcessResponseChunk(id, ..) |
@@ -229,6 +276,18 @@ pub trait FetchTaskTarget {
     /// Fired when the entire request finishes being transmitted
     fn process_request_eof(&mut self, request: &Request);
 
+    /// <https://fetch.spec.whatwg.org/#fetch-params-process-request-body>
+    ///
+    /// The callback to run with the length of each chunk of the request body that is
+    /// transmitted, if the target wants to follow the progress of the upload. Unlike the other
+    /// methods it can be run from any thread, as the body is transmitted in parallel.
+    fn process_request_body_chunk_length(
+        &self,
+        _request: &Request,
+    ) -> Option<ProcessRequestBodyChunkLength> {
+        None
+    }
+
     /// <https://fetch.spec.whatwg.org/#process-response>
     ///
     /// Fired when headers are received
@@ -273,8 +332,12 @@ impl FetchMetadata {
     }
 }
 
+/// A callback run with the length of each chunk of a request body that is transmitted.
+pub type ProcessRequestBodyChunkLength = Arc<dyn Fn(usize) + Send + Sync>;
+
 pub trait FetchResponseListener {
     fn process_request_body(&mut self, request_id: RequestId);
+    fn process_request_body_chunk_length(&mut self, _request_id: RequestId, _length: usize) {}
     fn process_request_eof(&mut self, request_id: RequestId);
     fn process_response(
         &mut self,
@@ -302,6 +365,23 @@ impl FetchTaskTarget for IpcSender<FetchResponseMsg> {
         let _ = self.send(FetchResponseMsg::ProcessRequestEOF(request.id));
     }
 
+    fn process_request_body_chunk_length(
+        &self,
+        request: &Request,
+    ) -> Option<ProcessRequestBodyChunkLength> {
+        // The sender is not `Sync` on every platform.
+        let sender = Mutex::new(self.clone());
+        let request_id = request.id;
+        Some(Arc::new(move |length| {
+            let _ = sender
+                .lock()
+                .unwrap()
+                .send(FetchResponseMsg::ProcessRequestBodyChunkLength(
+                    request_id, length,
+                ));
+        }))
+    }
+
     fn process_response(&mut self, request: &Request, response: &Response) {
         let _ = self.send(FetchResponseMsg::ProcessResponse(
             request.id,
@@ -355,6 +435,9 @@ impl<T: FetchResponseListener> Action<T> for FetchResponseMsg {
             FetchResponseMsg::ProcessRequestBody(request_id) => {
                 listener.process_request_body(request_id)
             },
+            FetchResponseMsg::ProcessRequestBodyChunkLength(request_id, length) => {
+                listener.process_request_body_chunk_length(request_id, length)
+            },
             FetchResponseMsg::ProcessRequestEOF(request_id) => {
                 listener.process_request_eof(request_id)
             },
@@ -435,6 +518,40 @@ impl ResourceThreads {
     pub fn clear_cache(&self) {
         let _ = self.core_thread.send(CoreResourceMsg::ClearCache);
     }
+
+    pub fn set_content_blocking_filter_lists(&self, filter_lists: Vec<String>) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::SetContentBlockingFilterLists(filter_lists));
+    }
+
+    /// The element hiding stylesheet of the content blocking filter lists for a document at
+    /// the given URL, if any of their rules apply to it.
+    pub fn content_blocking_stylesheet(&self, url: ServoUrl) -> Option<String> {
+        let (sender, receiver) = ipc::channel().ok()?;
+        self.core_thread
+            .send(CoreResourceMsg::GetContentBlockingStylesheet(url, sender))
+            .ok()?;
+        receiver.recv().ok()?
+    }
+
+    /// Discard all of the state kept by these resource threads, including the local storage,
+    /// session storage and IndexedDB databases of every origin.
+    pub fn discard_state(&self) {
+        let _ = self.core_thread.send(CoreResourceMsg::DiscardState);
+        if let Ok((sender, _)) = ipc::channel() {
+            let _ = self.storage_thread.send(StorageThreadMsg::ClearSiteData {
+                sender,
+                origin: None,
+                since: None,
+            });
+        }
+        if let Ok((sender, _)) = ipc::channel() {
+            let _ = self
+                .idb_thread
+                .send(IndexedDBThreadMsg::Sync(SyncOperation::DeleteAll(sender)));
+        }
+    }
 }
 
 impl IpcSend<CoreResourceMsg> for ResourceThreads {
@@ -518,10 +635,22 @@ pub enum CoreResourceMsg {
     FetchRedirect(RequestBuilder, ResponseInit, IpcSender<FetchResponseMsg>),
     /// Store a cookie for a given originating URL
     SetCookieForUrl(ServoUrl, Serde<Cookie<'static>>, CookieSource),
-    /// Store a set of cookies for a given originating URL
-    SetCookiesForUrl(ServoUrl, Vec<Serde<Cookie<'static>>>, CookieSource),
-    /// Retrieve the stored cookies for a given URL
-    GetCookiesForUrl(ServoUrl, IpcSender<Option<String>>, CookieSource),
+    /// Store a set of cookies for a given originating URL, in the cookie partition of the
+    /// documents of the given pipeline
+    SetCookiesForUrl(
+        ServoUrl,
+        PipelineId,
+        Vec<Serde<Cookie<'static>>>,
+        CookieSource,
+    ),
+    /// Retrieve the stored cookies for a given URL, from the cookie partition of the
+    /// documents of the given pipeline
+    GetCookiesForUrl(
+        ServoUrl,
+        PipelineId,
+        IpcSender<Option<String>>,
+        CookieSource,
+    ),
     /// Get a cookie by name for a given originating URL
     GetCookiesDataForUrl(
         ServoUrl,
@@ -538,6 +667,28 @@ pub enum CoreResourceMsg {
     RemoveHistoryStates(Vec<HistoryStateId>),
     /// Clear the network cache.
     ClearCache,
+    /// Forget the cookies, network cache, authentication and HSTS state, as well as the
+    /// history states. Used to discard the ephemeral private browsing partition.
+    DiscardState,
+    /// Give the documents of the site of the second origin access to their unpartitioned
+    /// cookies when nested in documents of the site of the first origin, after the user
+    /// granted them storage access. Only sent by the constellation.
+    GrantStorageAccess(ImmutableOrigin, ImmutableOrigin),
+    /// Forget the cookie partition of the documents of a pipeline, which has exited.
+    PipelineExited(PipelineId),
+    /// Replace the filter lists used to block requests and hide elements, given as the
+    /// contents of files in the Adblock Plus format.
+    SetContentBlockingFilterLists(Vec<String>),
+    /// Get the element hiding stylesheet of the filter lists for a document at the given URL.
+    GetContentBlockingStylesheet(ServoUrl, IpcSender<Option<String>>),
+    /// Perform an action, with its value, of the `servo:` internal page at the given URL, as
+    /// requested by one of its documents through `navigator.servo`.
+    InternalPageAction(ServoUrl, String, String),
+    /// Count the fetches that are still in progress.
+    GetActiveFetchCount(IpcSender<usize>),
+    /// Resolve the host of a URL, or also connect to it, ahead of the requests that are
+    /// expected to be made to it.
+    SpeculativeConnection(ServoUrl, SpeculativeConnectionKind),
     /// Send the service worker network mediator for an origin to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>, ImmutableOrigin),
     /// Message forwarded to file manager's handler
@@ -931,6 +1082,15 @@ impl Metadata {
     }
 }
 
+/// The work done ahead of time for a host that requests are expected to be made to.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeculativeConnectionKind {
+    /// Only resolve the host, as for `<link rel=dns-prefetch>`.
+    DnsPrefetch,
+    /// Resolve the host and open a connection to it, as for `<link rel=preconnect>`.
+    Preconnect,
+}
+
 /// The creator of a given cookie
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum CookieSource {
@@ -946,7 +1106,11 @@ pub enum NetworkError {
     /// Could be any of the internal errors, like unsupported scheme, connection errors, etc.
     Internal(String),
     LoadCancelled,
-    /// SSL validation error, to be converted to Resource::BadCertHTML in the HTML parser.
+    /// The host of the URL could not be resolved to an address.
+    ResolveFailed(String),
+    /// The server refused the connection.
+    ConnectionRefused(String),
+    /// SSL validation error, with the DER bytes of the certificate that failed validation.
     SslValidation(String, Vec<u8>),
     /// Crash error, to be converted to Resource::Crash in the HTML parser.
     Crash(String),
@@ -955,9 +1119,31 @@ pub enum NetworkError {
 impl NetworkError {
     pub fn from_hyper_error(error: &HyperError, certificate: Option<CertificateDer>) -> Self {
         let error_string = error.to_string();
-        match certificate {
-            Some(certificate) => NetworkError::SslValidation(error_string, certificate.to_vec()),
-            _ => NetworkError::Internal(error_string),
+        if let Some(certificate) = certificate {
+            return NetworkError::SslValidation(error_string, certificate.to_vec());
+        }
+
+        // The connector reports why it could not connect in the sources of the error, which
+        // are also kept in the message as they are more useful than the error itself.
+        let mut details = error_string;
+        let mut resolve_failed = false;
+        let mut connection_refused = false;
+        let mut source = std::error::Error::source(error);
+        while let Some(error) = source {
+            let message = error.to_string();
+            resolve_failed |= message.starts_with("dns error");
+            connection_refused |= error
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|error| error.kind() == std::io::ErrorKind::ConnectionRefused);
+            details = format!("{details}: {message}");
+            source = error.source();
+        }
+        if resolve_failed {
+            NetworkError::ResolveFailed(details)
+        } else if connection_refused {
+            NetworkError::ConnectionRefused(details)
+        } else {
+            NetworkError::Internal(details)
         }
     }
 
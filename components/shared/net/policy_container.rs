@@ -47,11 +47,19 @@ impl PolicyContainer {
     }
 
     pub fn get_referrer_policy(&self) -> ReferrerPolicy {
+        self.get_referrer_policy_or(ReferrerPolicy::default())
+    }
+
+    /// Like [`Self::get_referrer_policy`], but with the user agent's default referrer policy
+    /// supplied by the caller, for instance from the preferences.
+    pub fn get_referrer_policy_or(&self, default_policy: ReferrerPolicy) -> ReferrerPolicy {
         // https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-empty-string
-        if self.referrer_policy == ReferrerPolicy::EmptyString {
-            return ReferrerPolicy::default();
+        if self.referrer_policy != ReferrerPolicy::EmptyString {
+            return self.referrer_policy;
         }
-
-        self.referrer_policy
+        if default_policy != ReferrerPolicy::EmptyString {
+            return default_policy;
+        }
+        ReferrerPolicy::default()
     }
 }
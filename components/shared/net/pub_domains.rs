@@ -15,6 +15,7 @@
 //! those cases are not present.
 
 use std::collections::HashSet;
+use std::fmt;
 use std::iter::FromIterator;
 use std::sync::LazyLock;
 
@@ -152,3 +153,41 @@ pub fn reg_host(url: &ServoUrl) -> Option<Host> {
         ImmutableOrigin::Opaque(_) => None,
     }
 }
+
+/// A site, which is the scheme of an origin together with its registered domain name.
+/// Documents of the same site may become same origin by setting `document.domain`, while
+/// documents of different sites never can, so that the latter can be isolated from each
+/// other in separate event loops and processes.
+///
+/// <https://html.spec.whatwg.org/multipage/#site>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Site {
+    pub scheme: String,
+    pub host: Host,
+}
+
+impl Site {
+    /// The site of a URL, or `None` if the URL has an opaque origin, which is only ever the
+    /// same site as itself.
+    pub fn for_url(url: &ServoUrl) -> Option<Site> {
+        Site::for_origin(url.origin())
+    }
+
+    /// The site of an origin, or `None` if the origin is opaque.
+    pub fn for_origin(origin: ImmutableOrigin) -> Option<Site> {
+        match origin {
+            ImmutableOrigin::Tuple(scheme, Host::Domain(domain), _) => Some(Site {
+                scheme,
+                host: Host::Domain(String::from(reg_suffix(&domain))),
+            }),
+            ImmutableOrigin::Tuple(scheme, ip, _) => Some(Site { scheme, host: ip }),
+            ImmutableOrigin::Opaque(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}://{}", self.scheme, self.host)
+    }
+}
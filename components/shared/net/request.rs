@@ -294,6 +294,9 @@ pub struct RequestBuilder {
     pub policy_container: RequestPolicyContainer,
     pub insecure_requests_policy: InsecureRequestsPolicy,
     pub has_trustworthy_ancestor_origin: bool,
+    /// For navigation requests of nested documents, the origin of the top-level document,
+    /// by which the cookies of the documents are partitioned.
+    pub top_level_origin: Option<ImmutableOrigin>,
 
     /// <https://fetch.spec.whatwg.org/#concept-request-referrer>
     pub referrer: Referrer,
@@ -347,6 +350,7 @@ impl RequestBuilder {
             policy_container: RequestPolicyContainer::default(),
             insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
             has_trustworthy_ancestor_origin: false,
+            top_level_origin: None,
             referrer,
             referrer_policy: ReferrerPolicy::EmptyString,
             pipeline_id: None,
@@ -504,6 +508,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn top_level_origin(mut self, top_level_origin: Option<ImmutableOrigin>) -> RequestBuilder {
+        self.top_level_origin = top_level_origin;
+        self
+    }
+
     /// <https://fetch.spec.whatwg.org/#request-service-workers-mode>
     pub fn service_workers_mode(
         mut self,
@@ -558,6 +567,7 @@ impl RequestBuilder {
         request.policy_container = self.policy_container;
         request.insecure_requests_policy = self.insecure_requests_policy;
         request.has_trustworthy_ancestor_origin = self.has_trustworthy_ancestor_origin;
+        request.top_level_origin = self.top_level_origin;
         request
     }
 }
@@ -634,6 +644,9 @@ pub struct Request {
     /// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy>
     pub insecure_requests_policy: InsecureRequestsPolicy,
     pub has_trustworthy_ancestor_origin: bool,
+    /// For navigation requests of nested documents, the origin of the top-level document,
+    /// by which the cookies of the documents are partitioned.
+    pub top_level_origin: Option<ImmutableOrigin>,
     pub https_state: HttpsState,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
@@ -682,6 +695,7 @@ impl Request {
             policy_container: RequestPolicyContainer::Client,
             insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
             has_trustworthy_ancestor_origin: false,
+            top_level_origin: None,
             https_state,
             crash: None,
         }
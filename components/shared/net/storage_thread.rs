@@ -2,12 +2,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::time::SystemTime;
+
 use base::id::WebViewId;
+use http::{HeaderMap, Method};
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use profile_traits::mem::ReportsChan;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+use crate::pub_domains::Site;
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum StorageType {
@@ -15,30 +20,151 @@ pub enum StorageType {
     Local,
 }
 
+/// <https://storage.spec.whatwg.org/#dictdef-storageestimate>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct StorageEstimate {
+    /// The number of bytes used by the origin
+    pub usage: u64,
+    /// The number of bytes the origin may use
+    pub quota: u64,
+}
+
+/// The key by which the storage data of documents is kept apart, which is the origin of
+/// their URL, together with the site of their top-level document when it is another site,
+/// so that third-party documents do not share their storage across the sites embedding them.
+///
+/// <https://storage.spec.whatwg.org/#storage-key>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StorageKey {
+    pub url: ServoUrl,
+    pub top_level_origin: Option<ImmutableOrigin>,
+}
+
+impl StorageKey {
+    /// The string under which the storage data is kept, which is the serialization of the
+    /// origin for the storage data of first-party documents.
+    pub fn serialize(&self) -> String {
+        let origin = self.url.origin().ascii_serialization();
+        match self.top_level_origin.clone().and_then(Site::for_origin) {
+            Some(top_level_site) if Site::for_url(&self.url).as_ref() != Some(&top_level_site) => {
+                format!("{origin}{PARTITION_SEPARATOR}{top_level_site}")
+            },
+            _ => origin,
+        }
+    }
+
+    /// Returns whether the serialized key is the key of the given origin, or of one of its
+    /// partitions.
+    pub fn is_for_origin(serialized_key: &str, origin: &str) -> bool {
+        serialized_key
+            .strip_prefix(origin)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(PARTITION_SEPARATOR))
+    }
+}
+
+/// Separates the origin from the top-level site in serialized partitioned storage keys.
+const PARTITION_SEPARATOR: char = '^';
+
+/// Identifies a cache of the Cache Storage API of an origin, which outlives the entry of
+/// its name in the <https://w3c.github.io/ServiceWorker/#dfn-relevant-name-to-cache-map>.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct CacheId(pub u64);
+
+/// A request stored in a <https://w3c.github.io/ServiceWorker/#dfn-request-response-list>.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct CachedRequest {
+    /// The URL of the request, without its fragment.
+    pub url: ServoUrl,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+}
+
+/// The <https://fetch.spec.whatwg.org/#concept-response-type> of a cached response.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum CachedResponseType {
+    Basic,
+    Cors,
+    Default,
+    Error,
+    Opaque,
+    OpaqueRedirect,
+}
+
+/// A response stored in a <https://w3c.github.io/ServiceWorker/#dfn-request-response-list>,
+/// with its whole body.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct CachedResponse {
+    pub response_type: CachedResponseType,
+    pub url: Option<ServoUrl>,
+    pub status: u16,
+    pub status_text: Vec<u8>,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// A request and its response, as stored in a cache.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct CacheEntry {
+    pub request: CachedRequest,
+    pub response: CachedResponse,
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dictdef-cachequeryoptions>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct CacheQueryOptions {
+    pub ignore_search: bool,
+    pub ignore_method: bool,
+    pub ignore_vary: bool,
+}
+
+/// The errors with which <https://w3c.github.io/ServiceWorker/#batch-cache-operations> fail.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CacheBatchError {
+    /// Several put operations of the batch are for the same request.
+    InvalidState,
+    /// Storing the responses would exceed the quota of the origin.
+    QuotaExceeded,
+}
+
 /// Request operations on the storage data associated with a particular url
 #[derive(Debug, Deserialize, Serialize)]
 pub enum StorageThreadMsg {
     /// gets the number of key/value pairs present in the associated storage data
-    Length(IpcSender<usize>, StorageType, WebViewId, ServoUrl),
+    Length(IpcSender<usize>, StorageType, WebViewId, StorageKey),
 
     /// gets the name of the key at the specified index in the associated storage data
     Key(
         IpcSender<Option<String>>,
         StorageType,
         WebViewId,
-        ServoUrl,
+        StorageKey,
         u32,
     ),
 
     /// Gets the available keys in the associated storage data
-    Keys(IpcSender<Vec<String>>, StorageType, WebViewId, ServoUrl),
+    Keys(IpcSender<Vec<String>>, StorageType, WebViewId, StorageKey),
 
     /// gets the value associated with the given key in the associated storage data
     GetItem(
         IpcSender<Option<String>>,
         StorageType,
         WebViewId,
-        ServoUrl,
+        StorageKey,
         String,
     ),
 
@@ -47,7 +173,7 @@ pub enum StorageThreadMsg {
         IpcSender<Result<(bool, Option<String>), ()>>,
         StorageType,
         WebViewId,
-        ServoUrl,
+        StorageKey,
         String,
         String,
     ),
@@ -57,12 +183,12 @@ pub enum StorageThreadMsg {
         IpcSender<Option<String>>,
         StorageType,
         WebViewId,
-        ServoUrl,
+        StorageKey,
         String,
     ),
 
     /// clears the associated storage data by removing all the key/value pairs
-    Clear(IpcSender<bool>, StorageType, WebViewId, ServoUrl),
+    Clear(IpcSender<bool>, StorageType, WebViewId, StorageKey),
 
     /// clones all storage data of the given top-level browsing context for a new browsing context.
     /// should only be used for sessionStorage.
@@ -72,6 +198,95 @@ pub enum StorageThreadMsg {
         dest: WebViewId,
     },
 
+    /// gets the storage usage and quota of an origin, across local storage, Cache Storage and
+    /// IndexedDB
+    Estimate(IpcSender<StorageEstimate>, ImmutableOrigin),
+
+    /// makes the storage of an origin persistent, so that it is not evicted under storage
+    /// pressure, and replies whether it is persistent
+    Persist(IpcSender<bool>, ImmutableOrigin),
+
+    /// gets whether the storage of an origin is persistent
+    Persisted(IpcSender<bool>, ImmutableOrigin),
+
+    /// gets the cache with the given name of the Cache Storage of an origin, creating it if
+    /// there is none
+    CacheStorageOpen(IpcSender<CacheId>, ImmutableOrigin, String),
+
+    /// gets whether there is a cache with the given name in the Cache Storage of an origin
+    CacheStorageHas(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// deletes the cache with the given name from the Cache Storage of an origin, and replies
+    /// whether there was one
+    CacheStorageDelete(IpcSender<bool>, ImmutableOrigin, String),
+
+    /// gets the names of the caches of the Cache Storage of an origin, in creation order
+    CacheStorageKeys(IpcSender<Vec<String>>, ImmutableOrigin),
+
+    /// gets the first entry matching the request, in the cache with the given name, or in all
+    /// the caches of the Cache Storage of an origin
+    CacheStorageMatch {
+        sender: IpcSender<Option<CacheEntry>>,
+        origin: ImmutableOrigin,
+        cache_name: Option<String>,
+        request: CachedRequest,
+        options: CacheQueryOptions,
+    },
+
+    /// gets the entries of a cache, or those matching the request when there is one
+    CacheMatchAll {
+        sender: IpcSender<Vec<CacheEntry>>,
+        origin: ImmutableOrigin,
+        cache: CacheId,
+        request: Option<CachedRequest>,
+        options: CacheQueryOptions,
+    },
+
+    /// gets the requests of the entries of a cache, or of those matching the request when
+    /// there is one
+    CacheKeys {
+        sender: IpcSender<Vec<CachedRequest>>,
+        origin: ImmutableOrigin,
+        cache: CacheId,
+        request: Option<CachedRequest>,
+        options: CacheQueryOptions,
+    },
+
+    /// gets the first entry of a cache matching the request
+    CacheMatch {
+        sender: IpcSender<Option<CacheEntry>>,
+        origin: ImmutableOrigin,
+        cache: CacheId,
+        request: CachedRequest,
+        options: CacheQueryOptions,
+    },
+
+    /// stores the entries in a cache, replacing the entries matching their requests, all of
+    /// them or none
+    CachePut {
+        sender: IpcSender<Result<(), CacheBatchError>>,
+        origin: ImmutableOrigin,
+        cache: CacheId,
+        entries: Vec<CacheEntry>,
+    },
+
+    /// deletes the entries of a cache matching the request, and replies whether there were any
+    CacheDelete {
+        sender: IpcSender<Result<bool, CacheBatchError>>,
+        origin: ImmutableOrigin,
+        cache: CacheId,
+        request: CachedRequest,
+        options: CacheQueryOptions,
+    },
+
+    /// clears the local storage, session storage, Cache Storage and IndexedDB databases of the
+    /// given origin, or of all origins, which were modified since the given time
+    ClearSiteData {
+        sender: IpcSender<()>,
+        origin: Option<ImmutableOrigin>,
+        since: Option<SystemTime>,
+    },
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 
@@ -15,21 +15,26 @@ use std::sync::Arc;
 
 use background_hang_monitor_api::BackgroundHangMonitorRegister;
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::{BrowsingContextId, HistoryStateId, PipelineId, PipelineNamespaceId, WebViewId};
+use base::id::{
+    BrowsingContextId, HistoryStateId, OffscreenCanvasId, PipelineId, PipelineNamespaceId,
+    WebViewId,
+};
 #[cfg(feature = "bluetooth")]
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use compositing_traits::CrossProcessCompositorApi;
 use constellation_traits::{
-    LoadData, NavigationHistoryBehavior, ScriptToConstellationChan, StructuredSerializedData,
-    WindowSizeType,
+    FrameClock, IntersectionObservationChange, LoadData, NavigationHistoryBehavior,
+    ScriptThreadActivity, ScriptToConstellationChan, SequentialFocusDirection,
+    StructuredSerializedData, WindowSizeType,
 };
 use crossbeam_channel::{RecvTimeoutError, Sender};
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    CompositorHitTestResult, FocusSequenceNumber, InputEvent, JavaScriptEvaluationId,
-    MediaSessionActionType, Theme, ViewportDetails, WebDriverScriptCommand,
+    CompositorHitTestResult, FindInPageRequest, FocusSequenceNumber, InputEvent,
+    JavaScriptEvaluationId, MediaSessionActionType, PermissionFeature, Theme,
+    UserAgentStylesheetOverride, ViewportDetails, WebDriverScriptCommand,
 };
 use euclid::{Rect, Scale, Size2D, UnknownUnit};
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
@@ -42,13 +47,14 @@ use net_traits::storage_thread::StorageType;
 use pixels::PixelFormat;
 use profile_traits::mem;
 use serde::{Deserialize, Serialize};
+use servo_config::prefs::PrefValue;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use strum_macros::IntoStaticStr;
 use style_traits::{CSSPixel, SpeculativePainter};
 use stylo_atoms::Atom;
 #[cfg(feature = "webgpu")]
 use webgpu_traits::WebGPUMsg;
-use webrender_api::units::{DevicePixel, LayoutVector2D};
+use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutVector2D};
 use webrender_api::{ExternalScrollId, ImageKey};
 
 /// The initial data required to create a new layout attached to an existing script thread.
@@ -71,6 +77,12 @@ pub struct NewLayoutInfo {
     pub viewport_details: ViewportDetails,
     /// The [`Theme`] of the new layout.
     pub theme: Theme,
+    /// The stylesheet that the embedder applies at the user agent origin of the documents
+    /// of the `WebView`, if any.
+    pub user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+    /// The stylesheets that the embedder applies at the user origin of the documents of
+    /// the `WebView`.
+    pub user_stylesheets: Vec<String>,
 }
 
 /// When a pipeline is closed, should its browsing context be discarded too?
@@ -106,6 +118,8 @@ pub enum ProgressiveWebMetricType {
     FirstPaint,
     /// Time to first contentful paint
     FirstContentfulPaint,
+    /// Time to the paint of the largest image or text so far
+    LargestContentfulPaint,
     /// Time to interactive
     TimeToInteractive,
 }
@@ -133,10 +147,20 @@ pub enum ScriptThreadMessage {
     Resize(PipelineId, ViewportDetails, WindowSizeType),
     /// Theme changed.
     ThemeChange(PipelineId, Theme),
+    /// The embedder set or cleared the stylesheet that is applied at the user agent origin
+    /// of the documents of the `WebView` of the given pipeline.
+    SetUserAgentStylesheetOverride(PipelineId, Option<UserAgentStylesheetOverride>),
+    /// The embedder replaced the stylesheets that are applied at the user origin of the
+    /// documents of the `WebView` of the given pipeline.
+    SetUserStylesheets(PipelineId, Vec<String>),
     /// Notifies script that window has been resized but to not take immediate action.
     ResizeInactive(PipelineId, ViewportDetails),
     /// Window switched from fullscreen mode.
     ExitFullScreen(PipelineId),
+    /// Make the container of the given nested browsing context the fullscreen element of the
+    /// document, or stop it being one, as the document of the nested browsing context
+    /// entered or exited fullscreen.
+    SetFullscreenStateOfChild(PipelineId, BrowsingContextId, bool),
     /// Notifies the script that the document associated with this pipeline should 'unload'.
     UnloadDocument(PipelineId),
     /// Notifies the script that a pipeline should be closed.
@@ -153,6 +177,12 @@ pub enum ScriptThreadMessage {
     SetDocumentActivity(PipelineId, DocumentActivity),
     /// Set whether to use less resources by running timers at a heavily limited rate.
     SetThrottled(PipelineId, bool),
+    /// Set whether to run the timers of a pipeline of a `WebView` that has been hidden for a
+    /// long time at an even more limited rate than those of throttled pipelines.
+    SetTimersIntensivelyThrottled(PipelineId, bool),
+    /// Freeze or resume a pipeline of a `WebView` that has been hidden for a long time.
+    /// See <https://wicg.github.io/page-lifecycle/>.
+    SetFrozen(PipelineId, bool),
     /// Notify the containing iframe (in PipelineId) that the nested browsing context (BrowsingContextId) is throttled.
     SetThrottledInContainingIframe(PipelineId, BrowsingContextId, bool),
     /// Notifies script thread that a url should be loaded in this iframe.
@@ -197,6 +227,14 @@ pub enum ScriptThreadMessage {
     FocusIFrame(PipelineId, BrowsingContextId, FocusSequenceNumber),
     /// Focus the document. Used when the container gains focus.
     FocusDocument(PipelineId, FocusSequenceNumber),
+    /// Move focus to the next or previous element in the sequential focus navigation order
+    /// of the document. When a browsing context is given, navigation starts at its
+    /// container element, otherwise at the start or the end of the document.
+    MoveSequentialFocus(
+        PipelineId,
+        SequentialFocusDirection,
+        Option<BrowsingContextId>,
+    ),
     /// Notifies that the document's container (e.g., an iframe) is not included
     /// in the top-level browsing context's focus chain (not considering system
     /// focus) anymore.
@@ -206,7 +244,7 @@ pub enum ScriptThreadMessage {
     /// Passes a webdriver command to the script thread for execution
     WebDriverScriptCommand(PipelineId, WebDriverScriptCommand),
     /// Notifies script thread that all animations are done
-    TickAllAnimations(Vec<WebViewId>),
+    TickAllAnimations(Vec<WebViewId>, FrameClock),
     /// Notifies the script thread that a new Web font has been loaded, and thus the page should be
     /// reflowed.
     WebFontLoaded(PipelineId, bool /* success */),
@@ -242,17 +280,46 @@ pub enum ScriptThreadMessage {
     ),
     /// Notifies the media session about a user requested media session action.
     MediaSessionAction(PipelineId, MediaSessionActionType),
+    /// The user closed the picture-in-picture window of the video of this pipeline.
+    ExitPictureInPicture(PipelineId),
+    /// The picture-in-picture window of the video of this pipeline was resized.
+    PictureInPictureWindowResized(PipelineId, DeviceIntSize),
+    /// An OffscreenCanvas presented a new frame, with its image and size, in the placeholder
+    /// canvas element that the document of this pipeline keeps under the given id.
+    UpdatePlaceholderCanvas(
+        PipelineId,
+        OffscreenCanvasId,
+        Option<ImageKey>,
+        Size2D<u32, UnknownUnit>,
+    ),
     /// Notifies script thread that WebGPU server has started
     #[cfg(feature = "webgpu")]
     SetWebGPUPort(IpcReceiver<WebGPUMsg>),
     /// The compositor scrolled and is updating the scroll states of the nodes in the given
     /// pipeline via the Constellation.
     SetScrollStates(PipelineId, HashMap<ExternalScrollId, LayoutVector2D>),
+    /// The compositor scrolled the given pipeline and the intersections of some of its
+    /// `IntersectionObserver` targets with its viewport changed.
+    IntersectionObservationsChanged(PipelineId, Vec<IntersectionObservationChange>),
     /// Evaluate the given JavaScript and return a result via a corresponding message
     /// to the Constellation.
     EvaluateJavaScript(PipelineId, JavaScriptEvaluationId, String),
+    /// Find text in the page of the given pipeline, or stop finding text if there is no
+    /// request. The result is sent to the embedder.
+    FindInPage(PipelineId, Option<FindInPageRequest>),
     /// A new batch of keys for the image cache for the specific pipeline.
     SendImageKeysBatch(PipelineId, Vec<ImageKey>),
+    /// Perform a full, non-incremental garbage collection of the JavaScript runtime of the
+    /// script thread, replying on the given channel once it is done.
+    CollectGarbage(IpcSender<()>),
+    /// The values of the given preferences were changed at runtime and should be applied to
+    /// the process of this script thread.
+    PreferencesChanged(Vec<(String, PrefValue)>),
+    /// The decision of the user about the given permission of the given origin changed in
+    /// the permission store.
+    PermissionDecisionChanged(ImmutableOrigin, PermissionFeature),
+    /// Report the state of the script thread, to help diagnose hangs.
+    ReportActivity(IpcSender<ScriptThreadActivity>),
 }
 
 impl fmt::Debug for ScriptThreadMessage {
@@ -328,6 +395,12 @@ pub struct InitialScriptState {
     pub viewport_details: ViewportDetails,
     /// Initial [`Theme`] for the frame that is initiating this `ScriptThread`.
     pub theme: Theme,
+    /// The stylesheet that the embedder applies at the user agent origin of the documents
+    /// of the `WebView` of the frame that is initiating this `ScriptThread`, if any.
+    pub user_agent_stylesheet_override: Option<UserAgentStylesheetOverride>,
+    /// The stylesheets that the embedder applies at the user origin of the documents of
+    /// the `WebView` of the frame that is initiating this `ScriptThread`.
+    pub user_stylesheets: Vec<String>,
     /// The ID of the pipeline namespace for this script thread.
     pub pipeline_namespace_id: PipelineNamespaceId,
     /// A ping will be sent on this channel once the script thread shuts down.
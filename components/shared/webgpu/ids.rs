@@ -9,8 +9,8 @@ pub use wgpu_core::id::markers::{
 };
 use wgpu_core::id::{
     AdapterId, BindGroupId, BindGroupLayoutId, BufferId, CommandBufferId, CommandEncoderId,
-    ComputePipelineId, DeviceId, PipelineLayoutId, QueueId, RenderBundleId, RenderPipelineId,
-    SamplerId, ShaderModuleId, SurfaceId, TextureId, TextureViewId,
+    ComputePipelineId, DeviceId, PipelineLayoutId, QuerySetId, QueueId, RenderBundleId,
+    RenderPipelineId, SamplerId, ShaderModuleId, SurfaceId, TextureId, TextureViewId,
 };
 pub use wgpu_core::id::{
     ComputePassEncoderId as ComputePassId, RenderPassEncoderId as RenderPassId,
@@ -40,6 +40,7 @@ webgpu_resource!(WebGPUCommandEncoder, CommandEncoderId);
 webgpu_resource!(WebGPUComputePipeline, ComputePipelineId);
 webgpu_resource!(WebGPUDevice, DeviceId);
 webgpu_resource!(WebGPUPipelineLayout, PipelineLayoutId);
+webgpu_resource!(WebGPUQuerySet, QuerySetId);
 webgpu_resource!(WebGPUQueue, QueueId);
 webgpu_resource!(WebGPURenderBundle, RenderBundleId);
 webgpu_resource!(WebGPURenderPipeline, RenderPipelineId);
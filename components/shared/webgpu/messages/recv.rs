@@ -17,7 +17,7 @@ use wgpu_core::binding_model::{
     BindGroupDescriptor, BindGroupLayoutDescriptor, PipelineLayoutDescriptor,
 };
 use wgpu_core::command::{
-    RenderBundleDescriptor, RenderBundleEncoder, RenderPassColorAttachment,
+    PassTimestampWrites, RenderBundleDescriptor, RenderBundleEncoder, RenderPassColorAttachment,
     RenderPassDepthStencilAttachment, TexelCopyBufferInfo, TexelCopyTextureInfo,
 };
 use wgpu_core::device::HostMap;
@@ -41,7 +41,7 @@ use wgpu_core::resource::{
 };
 use wgpu_types::{
     BufferAddress, CommandBufferDescriptor, CommandEncoderDescriptor, DeviceDescriptor, Extent3d,
-    TexelCopyBufferLayout,
+    QuerySetDescriptor, TexelCopyBufferLayout,
 };
 
 use crate::{
@@ -51,6 +51,18 @@ use crate::{
     WebGPURenderPipelineResponse,
 };
 
+/// Ids of the internal objects a device uses to quantize resolved timestamp queries.
+///
+/// These are allocated by script, like every other id, and are sent along with the
+/// first timestamp query set created on a device.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TimestampQuantizerIds {
+    pub shader_module_id: ShaderModuleId,
+    pub bind_group_layout_id: BindGroupLayoutId,
+    pub pipeline_layout_id: PipelineLayoutId,
+    pub pipeline_id: ComputePipelineId,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebGPURequest {
     BufferMapAsync {
@@ -125,6 +137,14 @@ pub enum WebGPURequest {
         pipeline_layout_id: PipelineLayoutId,
         descriptor: PipelineLayoutDescriptor<'static>,
     },
+    CreateQuerySet {
+        device_id: DeviceId,
+        queue_id: QueueId,
+        query_set_id: QuerySetId,
+        descriptor: QuerySetDescriptor<Label<'static>>,
+        /// present only for the first timestamp query set of a device
+        timestamp_quantizer: Option<TimestampQuantizerIds>,
+    },
     CreateRenderPipeline {
         device_id: DeviceId,
         render_pipeline_id: RenderPipelineId,
@@ -232,6 +252,7 @@ pub enum WebGPURequest {
         command_encoder_id: CommandEncoderId,
         compute_pass_id: ComputePassId,
         label: Label<'static>,
+        timestamp_writes: Option<PassTimestampWrites>,
         device_id: DeviceId,
     },
     ComputePassSetPipeline {
@@ -271,6 +292,8 @@ pub enum WebGPURequest {
         label: Label<'static>,
         color_attachments: Vec<Option<RenderPassColorAttachment>>,
         depth_stencil_attachment: Option<RenderPassDepthStencilAttachment>,
+        timestamp_writes: Option<PassTimestampWrites>,
+        occlusion_query_set: Option<QuerySetId>,
         device_id: DeviceId,
     },
     RenderPassCommand {
@@ -283,6 +306,17 @@ pub enum WebGPURequest {
         device_id: DeviceId,
         command_encoder_id: CommandEncoderId,
     },
+    ResolveQuerySet {
+        command_encoder_id: CommandEncoderId,
+        device_id: DeviceId,
+        query_set_id: QuerySetId,
+        first_query: u32,
+        query_count: u32,
+        destination_id: BufferId,
+        destination_offset: BufferAddress,
+        /// present only when resolving timestamp queries, which get quantized
+        quantization_bind_group_id: Option<BindGroupId>,
+    },
     Submit {
         device_id: DeviceId,
         queue_id: QueueId,
@@ -32,7 +32,7 @@ use embedder_traits::{
 use euclid::{Rect, Size2D};
 use http::method::Method;
 use image::{DynamicImage, ImageFormat, RgbaImage};
-use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender, TryRecvError};
 use ipc_channel::router::ROUTER;
 use keyboard_types::webdriver::send_keys;
 use log::{debug, info};
@@ -87,6 +87,10 @@ impl WebDriverMessageIdGenerator {
     }
 }
 
+/// The keyword of the `tEXt` chunk of masked screenshots that holds the metadata of the
+/// frame they show, as JSON.
+const SCREENSHOT_METADATA_KEYWORD: &str = "Servo Screenshot Metadata";
+
 fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
     vec![
         (
@@ -104,6 +108,21 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/coverage/start",
+            ServoExtensionRoute::StartCoverage,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/coverage/take",
+            ServoExtensionRoute::TakeCoverage,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/screenshot/masked",
+            ServoExtensionRoute::TakeMaskedScreenshot,
+        ),
     ]
 }
 
@@ -263,6 +282,9 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    StartCoverage,
+    TakeCoverage,
+    TakeMaskedScreenshot,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -286,6 +308,13 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::StartCoverage => ServoExtensionCommand::StartCoverage,
+            ServoExtensionRoute::TakeCoverage => ServoExtensionCommand::TakeCoverage,
+            ServoExtensionRoute::TakeMaskedScreenshot => {
+                let parameters: MaskedScreenshotParameters =
+                    serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::TakeMaskedScreenshot(parameters)
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -297,6 +326,9 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    StartCoverage,
+    TakeCoverage,
+    TakeMaskedScreenshot(MaskedScreenshotParameters),
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -305,6 +337,8 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::TakeMaskedScreenshot(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::StartCoverage | ServoExtensionCommand::TakeCoverage => None,
         }
     }
 }
@@ -327,6 +361,7 @@ impl Serialize for SendableWebDriverJSValue {
             WebDriverJSValue::Element(ref x) => x.serialize(serializer),
             WebDriverJSValue::Frame(ref x) => x.serialize(serializer),
             WebDriverJSValue::Window(ref x) => x.serialize(serializer),
+            WebDriverJSValue::ShadowRoot(ref x) => x.serialize(serializer),
             WebDriverJSValue::ArrayLike(ref x) => x
                 .iter()
                 .map(|element| SendableWebDriverJSValue(element.clone()))
@@ -422,6 +457,14 @@ struct GetPrefsParameters {
     prefs: Vec<String>,
 }
 
+/// The parameters of a masked screenshot: the elements that match any of the `mask`
+/// selectors are hidden from the screenshot.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct MaskedScreenshotParameters {
+    #[serde(default)]
+    mask: Vec<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 struct SetPrefsParameters {
     #[serde(deserialize_with = "map_to_vec")]
@@ -1755,7 +1798,7 @@ impl Handler {
         let (sender, receiver) = ipc::channel().unwrap();
         let cmd = WebDriverScriptCommand::ExecuteScript(script, sender);
         self.browsing_context_script_command(cmd, VerifyBrowsingContextIsOpen::Yes)?;
-        let result = wait_for_script_response(receiver)?;
+        let result = self.wait_for_script_result(receiver)?;
         self.postprocess_js_result(result)
     }
 
@@ -1773,24 +1816,23 @@ impl Handler {
             .collect();
         args_string.push("resolve".to_string());
 
-        let timeout_script = if let Some(script_timeout) = self.session()?.script_timeout {
-            format!("setTimeout(webdriverTimeout, {});", script_timeout)
-        } else {
-            "".into()
-        };
+        // Step 5.1-5.6. The last argument of the function is the resolving function of a
+        // promise. Exceptions thrown by the function reject it, and if the function returns
+        // a thenable, the promise follows it.
         let script = format!(
             r#"(function() {{
-              let webdriverPromise = new Promise(function(resolve, reject) {{
-                  {}
-                  (async function() {{
+              new Promise(function(resolve, reject) {{
+                  let result = (function() {{
                     {}
-                  }})({})
-                    .then((v) => {{}}, (err) => reject(err))
+                  }})({});
+                  if (result !== null && typeof result === "object" &&
+                      typeof result.then === "function") {{
+                    result.then(resolve, reject);
+                  }}
               }})
               .then((v) => window.webdriverCallback(v), (r) => window.webdriverException(r))
               .catch((r) => window.webdriverException(r));
             }})();"#,
-            timeout_script,
             func_body,
             args_string.join(", "),
         );
@@ -1799,10 +1841,29 @@ impl Handler {
         let (sender, receiver) = ipc::channel().unwrap();
         let cmd = WebDriverScriptCommand::ExecuteAsyncScript(script, sender);
         self.browsing_context_script_command(cmd, VerifyBrowsingContextIsOpen::Yes)?;
-        let result = wait_for_script_response(receiver)?;
+        let result = self.wait_for_script_result(receiver)?;
         self.postprocess_js_result(result)
     }
 
+    /// Wait for the result of a script for at most the session script timeout. The timeout
+    /// is enforced here rather than in the page, so that it also applies to scripts that
+    /// never yield to the event loop.
+    fn wait_for_script_result(
+        &self,
+        receiver: IpcReceiver<WebDriverJSResult>,
+    ) -> WebDriverResult<WebDriverJSResult> {
+        let Some(script_timeout) = self.session()?.script_timeout else {
+            return wait_for_script_response(receiver);
+        };
+        match receiver.try_recv_timeout(Duration::from_millis(script_timeout)) {
+            Ok(result) => Ok(result),
+            Err(TryRecvError::Empty) => Ok(Err(WebDriverJSError::Timeout)),
+            Err(TryRecvError::IpcError(_)) => {
+                Err(WebDriverError::new(ErrorStatus::NoSuchWindow, ""))
+            },
+        }
+    }
+
     fn postprocess_js_result(
         &self,
         result: WebDriverJSResult,
@@ -1827,6 +1888,10 @@ impl Handler {
                 ErrorStatus::StaleElementReference,
                 "Stale element",
             )),
+            Err(WebDriverJSError::DetachedShadowRoot) => Err(WebDriverError::new(
+                ErrorStatus::DetachedShadowRoot,
+                "Detached shadow root",
+            )),
             Err(WebDriverJSError::Timeout) => {
                 Err(WebDriverError::new(ErrorStatus::ScriptTimeout, ""))
             },
@@ -1958,41 +2023,48 @@ impl Handler {
         }
     }
 
-    fn take_screenshot(&self, rect: Option<Rect<f32, CSSPixel>>) -> WebDriverResult<String> {
-        // Step 1. If session's current top-level browsing context is no longer open,
-        // return error with error code no such window.
-        self.verify_top_level_browsing_context_is_open(self.session()?.webview_id)?;
-        let mut img = None;
-
+    /// Send the screenshot command made by `command` until the compositor is ready to
+    /// take the screenshot and replies with it.
+    fn wait_for_screenshot<T>(
+        &self,
+        command: impl Fn(IpcSender<Option<T>>) -> WebDriverCommandMsg,
+    ) -> WebDriverResult<T>
+    where
+        T: for<'de> Deserialize<'de> + Serialize,
+    {
         let interval = 1000;
         let iterations = 30000 / interval;
 
         for _ in 0..iterations {
             let (sender, receiver) = ipc::channel().unwrap();
-
-            let cmd_msg =
-                WebDriverCommandMsg::TakeScreenshot(self.session()?.webview_id, rect, sender);
             self.constellation_chan
-                .send(EmbedderToConstellationMessage::WebDriverCommand(cmd_msg))
+                .send(EmbedderToConstellationMessage::WebDriverCommand(command(
+                    sender,
+                )))
                 .unwrap();
 
-            if let Some(x) = wait_for_script_response(receiver)? {
-                img = Some(x);
-                break;
+            if let Some(screenshot) = wait_for_script_response(receiver)? {
+                return Ok(screenshot);
             };
 
             thread::sleep(Duration::from_millis(interval));
         }
 
-        let img = match img {
-            Some(img) => img,
-            None => {
-                return Err(WebDriverError::new(
-                    ErrorStatus::Timeout,
-                    "Taking screenshot timed out",
-                ));
-            },
-        };
+        Err(WebDriverError::new(
+            ErrorStatus::Timeout,
+            "Taking screenshot timed out",
+        ))
+    }
+
+    fn take_screenshot(&self, rect: Option<Rect<f32, CSSPixel>>) -> WebDriverResult<String> {
+        // Step 1. If session's current top-level browsing context is no longer open,
+        // return error with error code no such window.
+        let webview_id = self.session()?.webview_id;
+        self.verify_top_level_browsing_context_is_open(webview_id)?;
+
+        let img = self.wait_for_screenshot(|sender| {
+            WebDriverCommandMsg::TakeScreenshot(webview_id, rect, sender)
+        })?;
 
         // The compositor always sends RGBA pixels.
         assert_eq!(
@@ -2015,6 +2087,86 @@ impl Handler {
         Ok(base64::engine::general_purpose::STANDARD.encode(png_data.get_ref()))
     }
 
+    /// Take a screenshot of the viewport of the current top-level browsing context with
+    /// the elements that match the `mask` selectors filled with an opaque color. The
+    /// epochs of the rendered pipelines, the viewport metadata and the paint and navigation
+    /// timing of the page as of the rendered frame are embedded into the PNG as a `tEXt`
+    /// chunk and returned along with it, so that visual diffs can tell whether two
+    /// screenshots show the same rendering, and lab tools get timing consistent with it.
+    fn handle_take_masked_screenshot(
+        &self,
+        parameters: &MaskedScreenshotParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let webview_id = self.session()?.webview_id;
+        self.verify_top_level_browsing_context_is_open(webview_id)?;
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd = WebDriverScriptCommand::GetMaskedRects(parameters.mask.clone(), sender);
+        self.top_level_script_command(cmd, VerifyBrowsingContextIsOpen::No)?;
+        let masks: Vec<_> = wait_for_script_response(receiver)?
+            .map_err(|error| WebDriverError::new(error, ""))?
+            .iter()
+            .map(Rect::from_untyped)
+            .collect();
+
+        let (img, metadata) = self.wait_for_screenshot(|sender| {
+            WebDriverCommandMsg::TakeMaskedScreenshot(webview_id, masks.clone(), sender)
+        })?;
+
+        // The compositor always sends RGBA pixels.
+        assert_eq!(
+            img.format,
+            PixelFormat::RGBA8,
+            "Unexpected screenshot pixel format"
+        );
+
+        let metadata = json!({
+            "pipelineEpochs": metadata
+                .pipeline_epochs
+                .iter()
+                .map(|(pipeline_id, epoch)| json!({
+                    "pipeline": pipeline_id.to_string(),
+                    "epoch": epoch.0,
+                }))
+                .collect::<Vec<_>>(),
+            "viewportWidth": metadata.viewport_size.width,
+            "viewportHeight": metadata.viewport_size.height,
+            "devicePixelRatio": metadata.device_pixels_per_css_pixel,
+            "pageLoadTiming": metadata.page_load_timing.map(|timing| {
+                let milliseconds = |duration: Option<Duration>| {
+                    duration.map(|duration| duration.as_secs_f64() * 1000.)
+                };
+                json!({
+                    "firstPaint": milliseconds(timing.first_paint),
+                    "firstContentfulPaint": milliseconds(timing.first_contentful_paint),
+                    "largestContentfulPaint": milliseconds(timing.largest_contentful_paint),
+                    "largestContentfulPaintSize": timing.largest_contentful_paint_size,
+                    "domInteractive": milliseconds(timing.dom_interactive),
+                    "domContentLoadedEventEnd": milliseconds(timing.dom_content_loaded_event_end),
+                    "loadEventEnd": milliseconds(timing.load_event_end),
+                })
+            }),
+        });
+
+        let mut png_data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_data, img.metadata.width, img.metadata.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .add_text_chunk(SCREENSHOT_METADATA_KEYWORD.to_owned(), metadata.to_string())
+            .and_then(|_| encoder.write_header())
+            .and_then(|mut writer| {
+                writer.write_image_data(img.first_frame().bytes)?;
+                writer.finish()
+            })
+            .map_err(|error| WebDriverError::new(ErrorStatus::UnknownError, error.to_string()))?;
+
+        Ok(WebDriverResponse::Generic(ValueResponse(json!({
+            "image": base64::engine::general_purpose::STANDARD.encode(&png_data),
+            "metadata": metadata,
+        }))))
+    }
+
     fn handle_take_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
         let encoded = self.take_screenshot(None)?;
 
@@ -2110,6 +2262,34 @@ impl Handler {
         )))
     }
 
+    /// Start recording code coverage in the current browsing context. See
+    /// [`Self::handle_take_coverage`].
+    fn handle_start_coverage(&self) -> WebDriverResult<WebDriverResponse> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd = WebDriverScriptCommand::StartCoverage(sender);
+        self.browsing_context_script_command(cmd, VerifyBrowsingContextIsOpen::Yes)?;
+
+        match wait_for_script_response(receiver)? {
+            Ok(()) => Ok(WebDriverResponse::Void),
+            Err(error) => Err(WebDriverError::new(error, "")),
+        }
+    }
+
+    /// Stop recording code coverage in the current browsing context, returning an object
+    /// describing the scripts which were executed and the style rules which were used.
+    fn handle_take_coverage(&self) -> WebDriverResult<WebDriverResponse> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd = WebDriverScriptCommand::TakeCoverage(sender);
+        self.browsing_context_script_command(cmd, VerifyBrowsingContextIsOpen::Yes)?;
+
+        match wait_for_script_response(receiver)? {
+            Ok(report) => Ok(WebDriverResponse::Generic(ValueResponse(
+                serde_json::from_str(&report)?,
+            ))),
+            Err(error) => Err(WebDriverError::new(error, "")),
+        }
+    }
+
     fn verify_top_level_browsing_context_is_open(
         &self,
         webview_id: WebViewId,
@@ -2250,6 +2430,11 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::StartCoverage => self.handle_start_coverage(),
+                ServoExtensionCommand::TakeCoverage => self.handle_take_coverage(),
+                ServoExtensionCommand::TakeMaskedScreenshot(ref x) => {
+                    self.handle_take_masked_screenshot(x)
+                },
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
@@ -2274,7 +2459,7 @@ const SHADOW_ROOT_IDENTIFIER: &str = "shadow-6066-11e4-a52e-4f735466cecf";
 
 fn webdriver_value_to_js_argument(v: &Value) -> String {
     match v {
-        Value::String(s) => format!("\"{}\"", s),
+        Value::String(s) => serde_json::to_string(s).unwrap(),
         Value::Null => "null".to_string(),
         Value::Bool(b) => b.to_string(),
         Value::Number(n) => n.to_string(),
@@ -2304,7 +2489,13 @@ fn webdriver_value_to_js_argument(v: &Value) -> String {
             }
             let elems = map
                 .iter()
-                .map(|(k, v)| format!("{}: {}", k, webdriver_value_to_js_argument(v)))
+                .map(|(k, v)| {
+                    format!(
+                        "{}: {}",
+                        serde_json::to_string(k).unwrap(),
+                        webdriver_value_to_js_argument(v)
+                    )
+                })
                 .collect::<Vec<_>>();
             format!("{{{}}}", elems.join(", "))
         },
@@ -10,6 +10,7 @@ use wgpu_thread::WGPU;
 pub use {wgpu_core as wgc, wgpu_types as wgt};
 
 mod poll_thread;
+mod timestamp_quantizer;
 mod wgpu_thread;
 
 use std::borrow::Cow;
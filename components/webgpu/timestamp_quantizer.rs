@@ -0,0 +1,271 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Quantization of resolved timestamp queries.
+//!
+//! <https://gpuweb.github.io/gpuweb/#timestamp>
+//!
+//! Raw GPU timestamps are precise enough to be used for timing attacks, so after
+//! every timestamp resolve we run a small compute pass over the destination buffer
+//! that converts the ticks into nanoseconds and rounds them down.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use log::warn;
+use webgpu_traits::{Error, TimestampQuantizerIds, WebGPUMsg};
+use wgpu_core::binding_model::{
+    BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindingResource, BufferBinding,
+    PipelineLayoutDescriptor,
+};
+use wgpu_core::command::ComputePassDescriptor;
+use wgpu_core::id::{BindGroupId, BufferId, CommandEncoderId, DeviceId, QueueId};
+use wgpu_core::pipeline::{
+    ComputePipelineDescriptor, ProgrammableStageDescriptor, ShaderModuleDescriptor,
+    ShaderModuleSource,
+};
+
+use crate::wgt;
+
+/// Timestamps are rounded down to a multiple of 2^17 ns (~131µs).
+const QUANTIZATION_BITS: u32 = 17;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Each timestamp is a little endian u64, which WGSL can only address as a pair of u32.
+const SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read_write> timestamps: array<vec2<u32>>;
+
+// The timestamp period in nanoseconds per tick, as 16.16 fixed point.
+override period: u32;
+override quantization_mask: u32;
+
+// Returns (ticks * period) >> 16, computed with 16 bit limbs.
+fn to_nanoseconds(ticks: vec2<u32>) -> vec2<u32> {
+    var a = array<u32, 4>(ticks.x & 0xffffu, ticks.x >> 16u, ticks.y & 0xffffu, ticks.y >> 16u);
+    var b = array<u32, 2>(period & 0xffffu, period >> 16u);
+    var r = array<u32, 6>(0u, 0u, 0u, 0u, 0u, 0u);
+    for (var i = 0u; i < 4u; i++) {
+        var carry = 0u;
+        for (var j = 0u; j < 2u; j++) {
+            let v = a[i] * b[j] + r[i + j] + carry;
+            r[i + j] = v & 0xffffu;
+            carry = v >> 16u;
+        }
+        r[i + 2u] = carry;
+    }
+    return vec2<u32>(r[1] | (r[2] << 16u), r[3] | (r[4] << 16u));
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= arrayLength(&timestamps)) {
+        return;
+    }
+    let nanoseconds = to_nanoseconds(timestamps[id.x]);
+    timestamps[id.x] = vec2<u32>(nanoseconds.x & quantization_mask, nanoseconds.y);
+}
+"#;
+
+impl crate::WGPU {
+    /// Creates the pipeline used to quantize the timestamp queries of a device.
+    pub(crate) fn create_timestamp_quantizer(
+        &mut self,
+        device_id: DeviceId,
+        queue_id: QueueId,
+        ids: TimestampQuantizerIds,
+    ) {
+        let global = &self.global;
+        let period = global.queue_get_timestamp_period(queue_id);
+
+        let (_, error) = global.device_create_shader_module(
+            device_id,
+            &ShaderModuleDescriptor {
+                label: Some(Cow::Borrowed("timestamp quantizer")),
+                runtime_checks: wgt::ShaderRuntimeChecks::checked(),
+            },
+            ShaderModuleSource::Wgsl(Cow::Borrowed(SHADER)),
+            Some(ids.shader_module_id),
+        );
+        if let Some(error) = error {
+            warn!("Failed to create timestamp quantizer shader: {error:?}");
+        }
+
+        let (_, error) = global.device_create_bind_group_layout(
+            device_id,
+            &BindGroupLayoutDescriptor {
+                label: None,
+                entries: Cow::Owned(vec![wgt::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgt::ShaderStages::COMPUTE,
+                    ty: wgt::BindingType::Buffer {
+                        ty: wgt::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }]),
+            },
+            Some(ids.bind_group_layout_id),
+        );
+        if let Some(error) = error {
+            warn!("Failed to create timestamp quantizer bind group layout: {error:?}");
+        }
+
+        let (_, error) = global.device_create_pipeline_layout(
+            device_id,
+            &PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: Cow::Owned(vec![ids.bind_group_layout_id]),
+                push_constant_ranges: Cow::Owned(vec![]),
+            },
+            Some(ids.pipeline_layout_id),
+        );
+        if let Some(error) = error {
+            warn!("Failed to create timestamp quantizer pipeline layout: {error:?}");
+        }
+
+        let constants = HashMap::from([
+            ("period".to_owned(), (period as f64 * 65536.0).round()),
+            (
+                "quantization_mask".to_owned(),
+                (!((1u32 << QUANTIZATION_BITS) - 1)) as f64,
+            ),
+        ]);
+        let (_, error) = global.device_create_compute_pipeline(
+            device_id,
+            &ComputePipelineDescriptor {
+                label: Some(Cow::Borrowed("timestamp quantizer")),
+                layout: Some(ids.pipeline_layout_id),
+                stage: ProgrammableStageDescriptor {
+                    module: ids.shader_module_id,
+                    entry_point: Some(Cow::Borrowed("main")),
+                    constants,
+                    zero_initialize_workgroup_memory: true,
+                },
+                cache: None,
+            },
+            Some(ids.pipeline_id),
+            None,
+        );
+        if let Some(error) = error {
+            warn!("Failed to create timestamp quantizer pipeline: {error:?}");
+        }
+
+        self.timestamp_quantizers.insert(device_id, ids);
+    }
+
+    /// Records a compute pass that quantizes `query_count` resolved timestamps
+    /// starting at `offset` in `buffer_id`.
+    pub(crate) fn quantize_timestamps(
+        &mut self,
+        device_id: DeviceId,
+        command_encoder_id: CommandEncoderId,
+        bind_group_id: BindGroupId,
+        buffer_id: BufferId,
+        offset: wgt::BufferAddress,
+        query_count: u32,
+    ) -> Result<(), Error> {
+        let Some(ids) = self.timestamp_quantizers.get(&device_id).copied() else {
+            if let Err(e) = self
+                .script_sender
+                .send(WebGPUMsg::FreeBindGroup(bind_group_id))
+            {
+                warn!(
+                    "Unable to send FreeBindGroup({:?}) ({:?})",
+                    bind_group_id, e
+                );
+            }
+            return Err(Error::Internal(
+                "Device has no timestamp quantizer".to_string(),
+            ));
+        };
+        let global = &self.global;
+
+        let (_, error) = global.device_create_bind_group(
+            device_id,
+            &BindGroupDescriptor {
+                label: None,
+                layout: ids.bind_group_layout_id,
+                entries: Cow::Owned(vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: buffer_id,
+                        offset,
+                        size: wgt::BufferSize::new(query_count as u64 * 8),
+                    }),
+                }]),
+            },
+            Some(bind_group_id),
+        );
+        let record_pass = || -> Result<(), Error> {
+            if let Some(error) = error {
+                return Err(Error::from_error(error));
+            }
+            let (mut pass, error) = global.command_encoder_begin_compute_pass(
+                command_encoder_id,
+                &ComputePassDescriptor {
+                    label: None,
+                    timestamp_writes: None,
+                },
+            );
+            if let Some(error) = error {
+                return Err(Error::from_error(error));
+            }
+            global
+                .compute_pass_set_pipeline(&mut pass, ids.pipeline_id)
+                .map_err(Error::from_error)?;
+            global
+                .compute_pass_set_bind_group(&mut pass, 0, Some(bind_group_id), &[])
+                .map_err(Error::from_error)?;
+            global
+                .compute_pass_dispatch_workgroups(
+                    &mut pass,
+                    query_count.div_ceil(WORKGROUP_SIZE),
+                    1,
+                    1,
+                )
+                .map_err(Error::from_error)?;
+            global
+                .compute_pass_end(&mut pass)
+                .map_err(Error::from_error)
+        };
+        let result = record_pass();
+
+        // The command encoder keeps the bind group alive for as long as it needs it.
+        global.bind_group_drop(bind_group_id);
+        if let Err(e) = self
+            .script_sender
+            .send(WebGPUMsg::FreeBindGroup(bind_group_id))
+        {
+            warn!(
+                "Unable to send FreeBindGroup({:?}) ({:?})",
+                bind_group_id, e
+            );
+        }
+        result
+    }
+
+    /// Releases the timestamp quantizer of a device, if it has one.
+    pub(crate) fn drop_timestamp_quantizer(&mut self, device_id: DeviceId) {
+        let Some(ids) = self.timestamp_quantizers.remove(&device_id) else {
+            return;
+        };
+        let global = &self.global;
+        global.compute_pipeline_drop(ids.pipeline_id);
+        global.pipeline_layout_drop(ids.pipeline_layout_id);
+        global.bind_group_layout_drop(ids.bind_group_layout_id);
+        global.shader_module_drop(ids.shader_module_id);
+        for msg in [
+            WebGPUMsg::FreeComputePipeline(ids.pipeline_id),
+            WebGPUMsg::FreePipelineLayout(ids.pipeline_layout_id),
+            WebGPUMsg::FreeBindGroupLayout(ids.bind_group_layout_id),
+            WebGPUMsg::FreeShaderModule(ids.shader_module_id),
+        ] {
+            if let Err(e) = self.script_sender.send(msg) {
+                warn!("Unable to free timestamp quantizer ids ({:?})", e);
+            }
+        }
+    }
+}
@@ -18,8 +18,8 @@ use log::{info, warn};
 use servo_config::pref;
 use webgpu_traits::{
     Adapter, ComputePassId, DeviceLostReason, Error, ErrorScope, Mapping, Pipeline, PopError,
-    RenderPassId, ShaderCompilationInfo, WebGPU, WebGPUAdapter, WebGPUContextId, WebGPUDevice,
-    WebGPUMsg, WebGPUQueue, WebGPURequest, apply_render_command,
+    RenderPassId, ShaderCompilationInfo, TimestampQuantizerIds, WebGPU, WebGPUAdapter,
+    WebGPUContextId, WebGPUDevice, WebGPUMsg, WebGPUQueue, WebGPURequest, apply_render_command,
 };
 use webrender_api::ExternalImageId;
 use wgc::command::{ComputePass, ComputePassDescriptor, RenderPass};
@@ -113,6 +113,8 @@ pub(crate) struct WGPU {
     compute_passes: HashMap<ComputePassId, Pass<ComputePass>>,
     /// Store render passes
     render_passes: HashMap<RenderPassId, Pass<RenderPass>>,
+    /// Pipelines used to quantize resolved timestamp queries, per device
+    pub(crate) timestamp_quantizers: HashMap<DeviceId, TimestampQuantizerIds>,
 }
 
 impl WGPU {
@@ -154,6 +156,7 @@ impl WGPU {
             wgpu_image_map,
             compute_passes: HashMap::new(),
             render_passes: HashMap::new(),
+            timestamp_quantizers: HashMap::new(),
         }
     }
 
@@ -327,9 +330,13 @@ impl WGPU {
                     WebGPURequest::CreateBuffer {
                         device_id,
                         buffer_id,
-                        descriptor,
+                        mut descriptor,
                     } => {
                         let global = &self.global;
+                        if descriptor.usage.contains(wgt::BufferUsages::QUERY_RESOLVE) {
+                            // Resolved timestamps are quantized in place by a compute shader
+                            descriptor.usage |= wgt::BufferUsages::STORAGE;
+                        }
                         let (_, error) =
                             global.device_create_buffer(device_id, &descriptor, Some(buffer_id));
 
@@ -407,6 +414,24 @@ impl WGPU {
                         );
                         self.maybe_dispatch_wgpu_error(device_id, error);
                     },
+                    WebGPURequest::CreateQuerySet {
+                        device_id,
+                        queue_id,
+                        query_set_id,
+                        descriptor,
+                        timestamp_quantizer,
+                    } => {
+                        let global = &self.global;
+                        let (_, error) = global.device_create_query_set(
+                            device_id,
+                            &descriptor,
+                            Some(query_set_id),
+                        );
+                        self.maybe_dispatch_wgpu_error(device_id, error);
+                        if let Some(ids) = timestamp_quantizer {
+                            self.create_timestamp_quantizer(device_id, queue_id, ids);
+                        }
+                    },
                     WebGPURequest::CreateRenderPipeline {
                         device_id,
                         render_pipeline_id,
@@ -611,6 +636,7 @@ impl WGPU {
                         };
                     },
                     WebGPURequest::DropDevice(device_id) => {
+                        self.drop_timestamp_quantizer(device_id);
                         let global = &self.global;
                         global.device_drop(device_id);
                         let device_scope = self
@@ -745,6 +771,7 @@ impl WGPU {
                         command_encoder_id,
                         compute_pass_id,
                         label,
+                        timestamp_writes,
                         device_id: _device_id,
                     } => {
                         let global = &self.global;
@@ -752,7 +779,7 @@ impl WGPU {
                             command_encoder_id,
                             &ComputePassDescriptor {
                                 label,
-                                timestamp_writes: None,
+                                timestamp_writes,
                             },
                         );
                         assert!(
@@ -891,6 +918,8 @@ impl WGPU {
                         label,
                         color_attachments,
                         depth_stencil_attachment,
+                        timestamp_writes,
+                        occlusion_query_set,
                         device_id: _device_id,
                     } => {
                         let global = &self.global;
@@ -898,8 +927,8 @@ impl WGPU {
                             label,
                             color_attachments: color_attachments.into(),
                             depth_stencil_attachment: depth_stencil_attachment.as_ref(),
-                            timestamp_writes: None,
-                            occlusion_query_set: None,
+                            timestamp_writes: timestamp_writes.as_ref(),
+                            occlusion_query_set,
                         };
                         let (pass, error) =
                             global.command_encoder_begin_render_pass(command_encoder_id, desc);
@@ -958,6 +987,53 @@ impl WGPU {
                             );
                         };
                     },
+                    WebGPURequest::ResolveQuerySet {
+                        command_encoder_id,
+                        device_id,
+                        query_set_id,
+                        first_query,
+                        query_count,
+                        destination_id,
+                        destination_offset,
+                        quantization_bind_group_id,
+                    } => {
+                        let global = &self.global;
+                        let result = global.command_encoder_resolve_query_set(
+                            command_encoder_id,
+                            query_set_id,
+                            first_query,
+                            query_count,
+                            destination_id,
+                            destination_offset,
+                        );
+                        let failed = result.is_err();
+                        self.encoder_record_error(command_encoder_id, &result);
+                        if let Some(bind_group_id) = quantization_bind_group_id {
+                            let result = if failed {
+                                // Nothing was resolved, so the bind group is never created
+                                if let Err(e) = self
+                                    .script_sender
+                                    .send(WebGPUMsg::FreeBindGroup(bind_group_id))
+                                {
+                                    warn!(
+                                        "Unable to send FreeBindGroup({:?}) ({:?})",
+                                        bind_group_id, e
+                                    );
+                                }
+                                Ok(())
+                            } else {
+                                self.quantize_timestamps(
+                                    device_id,
+                                    command_encoder_id,
+                                    bind_group_id,
+                                    destination_id,
+                                    destination_offset,
+                                    query_count,
+                                )
+                            };
+                            self.encoder_record_error(command_encoder_id, &result);
+                        }
+                    },
                     WebGPURequest::Submit {
                         device_id,
                         queue_id,
@@ -25,13 +25,14 @@ use surfman::{
     Context as SurfmanContext, Device as SurfmanDevice, Error as SurfmanError, SurfaceTexture,
 };
 use webxr_api;
-use webxr_api::util::{self, ClipPlanes};
+use webxr_api::util::{self, ClipPlanes, HitTestList};
 use webxr_api::{
-    BaseSpace, Capture, ContextId, DeviceAPI, DiscoveryAPI, Display, Error, Event, EventBuffer,
-    Floor, Frame, GLContexts, InputId, InputSource, LayerGrandManager, LayerId, LayerInit,
-    LayerManager, LayerManagerAPI, LeftEye, Native, Quitter, RightEye, SelectKind,
-    Session as WebXrSession, SessionBuilder, SessionInit, SessionMode, SubImage, SubImages, View,
-    ViewerPose, Viewport, Viewports, Views, Visibility, WebXrSender,
+    ApiSpace, BaseSpace, Capture, ContextId, DeviceAPI, DiscoveryAPI, Display, Error, Event,
+    EventBuffer, Floor, Frame, GLContexts, HitTestId, HitTestResult, HitTestSource, InputId,
+    InputSource, LayerGrandManager, LayerId, LayerInit, LayerManager, LayerManagerAPI, LeftEye,
+    Native, Quitter, Ray, RightEye, SelectKind, Session as WebXrSession, SessionBuilder,
+    SessionInit, SessionMode, Space as WebXrSpace, SubImage, SubImages, View, ViewerPose, Viewport,
+    Viewports, Views, Visibility, WebXrSender,
 };
 
 use crate::SurfmanGL;
@@ -300,6 +301,12 @@ impl DiscoveryAPI<SurfmanGL> for OpenXrDiscovery {
             if instance.supports_secondary && init.first_person_observer_view {
                 supported_features.push("secondary-views".into());
             }
+            // Hit tests only find the floor, and anchors are not tracked by the runtime, see
+            // `OpenXrDevice::hit_test_floor` and `XRSession::create_anchor`.
+            if mode == SessionMode::ImmersiveAR {
+                supported_features.push("hit-test".into());
+                supported_features.push("anchors".into());
+            }
             let granted_features = init.validate(mode, &supported_features)?;
             let context_menu_provider = self.context_menu_provider.take();
             xr.spawn(move |grand_manager| {
@@ -359,6 +366,7 @@ struct OpenXrDevice {
     right_hand: OpenXRInput,
     left_hand: OpenXRInput,
     granted_features: Vec<String>,
+    hit_tests: HitTestList,
     context_menu_provider: Option<Box<dyn ContextMenuProvider>>,
     context_menu_future: Option<Box<dyn ContextMenuFuture>>,
 }
@@ -1048,11 +1056,73 @@ impl OpenXrDevice {
             right_hand,
             left_hand,
             granted_features,
+            hit_tests: HitTestList::default(),
             context_menu_provider,
             context_menu_future: None,
         })
     }
 
+    /// Convert a ray relative to a given space into a ray in native space,
+    /// using the poses reported in `frame`.
+    fn native_ray(
+        &self,
+        frame: &Frame,
+        ray: Ray<ApiSpace>,
+        space: WebXrSpace,
+    ) -> Option<Ray<Native>> {
+        let input = |id: InputId| frame.inputs.iter().find(|input| input.id == id);
+        let origin: RigidTransform3D<f32, ApiSpace, Native> = match space.base {
+            BaseSpace::Local => RigidTransform3D::identity(),
+            BaseSpace::Floor | BaseSpace::BoundedFloor => {
+                self.floor_transform()?.inverse().cast_unit()
+            },
+            BaseSpace::Viewer => frame.pose.as_ref()?.transform.cast_unit(),
+            BaseSpace::TargetRay(id) => input(id)?.target_ray_origin?.cast_unit(),
+            BaseSpace::Grip(id) => input(id)?.grip_origin?.cast_unit(),
+            // Hit testing from hand joints is not supported.
+            BaseSpace::Joint(..) => return None,
+        };
+        let space_origin = space.offset.then(&origin);
+
+        let origin_rigid: RigidTransform3D<f32, ApiSpace, ApiSpace> = ray.origin.into();
+        Some(Ray {
+            origin: origin_rigid.then(&space_origin).translation,
+            direction: space_origin.rotation.transform_vector3d(ray.direction),
+        })
+    }
+
+    /// OpenXR does not expose real world geometry without vendor extensions,
+    /// so the only plane we can hit test against is the floor.
+    fn hit_test_floor(&self, frame: &Frame) -> Vec<HitTestResult> {
+        let Some(floor) = self.floor_transform() else {
+            return vec![];
+        };
+        // The floor is the plane y = 0 in floor space.
+        let floor_height = -floor.translation.y;
+        self.hit_tests
+            .tests()
+            .iter()
+            .filter(|source| source.types.plane)
+            .filter_map(|source| {
+                let ray = self.native_ray(frame, source.ray, source.space)?;
+                if ray.direction.y > -f32::EPSILON {
+                    // The ray is parallel to, or pointing away from, the floor.
+                    return None;
+                }
+                let t = (floor_height - ray.origin.y) / ray.direction.y;
+                if t < 0. {
+                    return None;
+                }
+                let point = ray.origin + ray.direction * t;
+                // The floor normal already points along the Y axis, as the spec requires.
+                Some(HitTestResult {
+                    id: source.id,
+                    space: RigidTransform3D::from_translation(point.cast_unit()),
+                })
+            })
+            .collect()
+    }
+
     fn handle_openxr_events(&mut self) -> bool {
         use openxr::Event::*;
         let mut stopped = false;
@@ -1354,14 +1424,15 @@ impl DeviceAPI for OpenXrDevice {
         let left_input_changed = left.frame.input_changed;
         let right_input_changed = right.frame.input_changed;
 
-        let frame = Frame {
+        let mut frame = Frame {
             pose: Some(ViewerPose { transform, views }),
             inputs: vec![right.frame, left.frame],
-            events: vec![],
+            events: self.hit_tests.commit_tests(),
             sub_images,
             hit_test_results: vec![],
             predicted_display_time: frame_state.predicted_display_time.as_nanos() as f64,
         };
+        frame.hit_test_results = self.hit_test_floor(&frame);
 
         if let Some(right_select) = right.select {
             self.events.callback(Event::Select(
@@ -1489,6 +1560,14 @@ impl DeviceAPI for OpenXrDevice {
         &self.granted_features
     }
 
+    fn request_hit_test(&mut self, source: HitTestSource) {
+        self.hit_tests.request_hit_test(source)
+    }
+
+    fn cancel_hit_test(&mut self, id: HitTestId) {
+        self.hit_tests.cancel_hit_test(id)
+    }
+
     fn update_frame_rate(&mut self, rate: f32) -> f32 {
         if self.supports_updating_framerate {
             self.session
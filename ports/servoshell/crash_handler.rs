@@ -13,7 +13,7 @@ pub fn install() {
 
     use sig::signal;
 
-    use crate::backtrace;
+    use crate::{backtrace, crash_reports};
 
     extern "C" fn handler(sig: i32) {
         // Only print crash message and backtrace the first time, to avoid
@@ -36,6 +36,10 @@ pub fn install() {
             // we’re handling a non-main-thread (e.g. layout) segfault. Strictly
             // speaking in POSIX terms, this is also undefined behaviour.
             let _ = backtrace::print(&mut stderr);
+
+            if let Some(path) = crash_reports::write_native_crash_report(sig) {
+                let _ = writeln!(&mut stderr, "Wrote crash report to {}", path.display());
+            }
         }
 
         // Outside the BEEN_HERE_BEFORE check, we must only call functions we
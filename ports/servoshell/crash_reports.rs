@@ -0,0 +1,205 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Crash reports, written to the `crashes` directory of the config directory so that crashes
+//! can be looked into after the fact.
+//!
+//! Native crashes of the main process are reported by its signal handler, into a file that
+//! is created at startup since creating files is not async-signal-safe. Crashes of the
+//! content of a `WebView` are reported by the main process when the constellation notifies
+//! it, with the URL and pipeline that crashed, as content processes may be sandboxed and
+//! unable to write files. Reports are plain text files with a backtrace rather than
+//! minidumps.
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use servo::CrashDetails;
+use servo::config::opts;
+
+/// A description of the graphics adapter used for rendering, included in crash reports of
+/// the main process since many crashes are specific to a driver.
+static GRAPHICS_ADAPTER: OnceLock<String> = OnceLock::new();
+
+/// The report that the crash handler of the main process writes to, if it could be created.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+static NATIVE_CRASH_REPORT: OnceLock<NativeCrashReport> = OnceLock::new();
+
+/// A crash report created ahead of a native crash. Until the process crashes, it has a
+/// hidden name, so that it is not listed with the reports of actual crashes.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+struct NativeCrashReport {
+    file: File,
+    pending_path: CString,
+    path: CString,
+    display_path: PathBuf,
+}
+
+pub(crate) fn set_graphics_adapter(graphics_adapter: String) {
+    if GRAPHICS_ADAPTER.set(graphics_adapter.clone()).is_err() {
+        return;
+    }
+    // The header of the native crash report is written before the adapter is known.
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    if let Some(report) = NATIVE_CRASH_REPORT.get() {
+        let _ = writeln!(&report.file, "Graphics adapter: {graphics_adapter}");
+    }
+}
+
+/// The directory crash reports are written to, or `None` if there is no config directory.
+fn crash_report_directory() -> Option<PathBuf> {
+    Some(crash_report_directory_in(
+        opts::get().config_dir.as_deref()?,
+    ))
+}
+
+fn crash_report_directory_in(config_dir: &Path) -> PathBuf {
+    config_dir.join("crashes")
+}
+
+/// The name of a new crash report of the given kind, which starts with the current time.
+fn crash_report_name(kind: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!("{timestamp}-{}-{kind}.txt", process::id())
+}
+
+fn create_crash_report(kind: &str) -> Option<(PathBuf, File)> {
+    let directory = crash_report_directory()?;
+    fs::create_dir_all(&directory).ok()?;
+    let path = directory.join(crash_report_name(kind));
+    let file = File::create(&path).ok()?;
+    Some((path, file))
+}
+
+/// Create the report that the crash handler writes to if the main process crashes, in the
+/// given config directory. It is named after the time at which the process started.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub(crate) fn prepare_native_crash_report(config_dir: Option<&Path>) {
+    let Some(directory) = config_dir.map(crash_report_directory_in) else {
+        return;
+    };
+    if fs::create_dir_all(&directory).is_err() {
+        return;
+    }
+    let name = crash_report_name("native");
+    let path = directory.join(&name);
+    let pending_path = directory.join(format!(".{name}"));
+    let Ok(mut file) = File::create(&pending_path) else {
+        return;
+    };
+    let _ = write_header(&mut file);
+    let (Ok(pending_path), Ok(c_path)) = (
+        CString::new(pending_path.as_os_str().as_bytes()),
+        CString::new(path.as_os_str().as_bytes()),
+    ) else {
+        return;
+    };
+    let _ = NATIVE_CRASH_REPORT.set(NativeCrashReport {
+        file,
+        pending_path,
+        path: c_path,
+        display_path: path,
+    });
+}
+
+/// Remove the report created by [`prepare_native_crash_report`], when shutting down
+/// without having crashed.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub(crate) fn discard_native_crash_report() {
+    if let Some(report) = NATIVE_CRASH_REPORT.get() {
+        unsafe {
+            libc::unlink(report.pending_path.as_ptr());
+        }
+    }
+}
+
+/// The names of the crash reports, most recent first.
+pub(crate) fn crash_reports() -> Vec<String> {
+    let Some(entries) = crash_report_directory().and_then(|directory| fs::read_dir(directory).ok())
+    else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".txt"))
+        .collect();
+    // Reports are named after the time at which they were created.
+    names.sort_by(|a, b| b.cmp(a));
+    names
+}
+
+/// Read the crash report with the given name, as returned by [`crash_reports`].
+pub(crate) fn read_crash_report(name: &str) -> Option<String> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return None;
+    }
+    fs::read_to_string(crash_report_directory()?.join(name)).ok()
+}
+
+fn write_header(file: &mut File) -> io::Result<()> {
+    writeln!(file, "{}", crate::servo_version())?;
+    writeln!(
+        file,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )?;
+    writeln!(file, "Process: {} (main)", process::id())?;
+    if let Some(graphics_adapter) = GRAPHICS_ADAPTER.get() {
+        writeln!(file, "Graphics adapter: {graphics_adapter}")?;
+    }
+    Ok(())
+}
+
+/// Write a report for a signal caught by the crash handler, with a backtrace of the thread
+/// that caught it, into the report created by [`prepare_native_crash_report`]. Returns the
+/// path of the report if there is one.
+///
+/// Apart from printing the backtrace, which allocates like the one printed to stderr by the
+/// crash handler, this only writes to and renames a file that is already open.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub(crate) fn write_native_crash_report(signal: i32) -> Option<&'static Path> {
+    let report = NATIVE_CRASH_REPORT.get()?;
+    let mut file = &report.file;
+    let _ = writeln!(file, "Signal: {signal}");
+    if let Some(name) = std::thread::current().name() {
+        let _ = writeln!(file, "Thread: {name}");
+    }
+    let _ = writeln!(file);
+    // Give the report its final name before printing the backtrace, in case that fails.
+    unsafe {
+        libc::rename(report.pending_path.as_ptr(), report.path.as_ptr());
+    }
+    let _ = crate::backtrace::print(&mut file);
+    Some(&report.display_path)
+}
+
+/// Write a report for the crash of the content of a `WebView`, as reported by the
+/// constellation. Returns the path of the report if it could be written.
+pub(crate) fn write_content_crash_report(crash_details: &CrashDetails) -> Option<PathBuf> {
+    let (path, mut file) = create_crash_report("content")?;
+    let _ = write_header(&mut file);
+    if let Some(url) = crash_details.url.as_ref() {
+        let _ = writeln!(&mut file, "URL: {url}");
+    }
+    if let Some(pipeline_id) = crash_details.pipeline_id {
+        let _ = writeln!(&mut file, "Pipeline: {pipeline_id}");
+    }
+    let _ = writeln!(&mut file, "Reason: {}", crash_details.reason);
+    if let Some(backtrace) = crash_details.backtrace.as_ref() {
+        let _ = writeln!(&mut file, "\n{backtrace}");
+    }
+    Some(path)
+}
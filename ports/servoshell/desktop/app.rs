@@ -8,12 +8,13 @@ use std::cell::Cell;
 use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::{env, fs};
 
 use ::servo::ServoBuilder;
 use constellation_traits::EmbedderToConstellationMessage;
-use crossbeam_channel::unbounded;
+use crossbeam_channel::{Receiver, unbounded};
 use euclid::{Point2D, Vector2D};
 use ipc_channel::ipc;
 use keyboard_types::webdriver::Event as WebDriverInputEvent;
@@ -27,7 +28,7 @@ use servo::user_content_manager::{UserContentManager, UserScript};
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::DeviceIntSize;
 use servo::{
-    EventLoopWaker, ImeEvent, InputEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent,
+    EventLoopWaker, ImeEvent, InputEvent, JSValue, KeyboardEvent, MouseButtonEvent, MouseMoveEvent,
     WebDriverCommandMsg, WebDriverScriptCommand, WebDriverUserPromptAction, WheelDelta, WheelEvent,
     WheelMode,
 };
@@ -40,6 +41,10 @@ use winit::window::WindowId;
 use super::app_state::AppState;
 use super::events_loop::{AppEvent, EventLoopProxy, EventsLoop};
 use super::minibrowser::{Minibrowser, MinibrowserEvent};
+use super::picture_in_picture::PictureInPictureWindow;
+use super::places::Places;
+use super::repl::{self, ReplCommand};
+use super::search::SearchEngines;
 use super::{headed_window, headless_window};
 use crate::desktop::app_state::RunningAppState;
 use crate::desktop::protocols;
@@ -61,6 +66,10 @@ pub struct App {
     t_start: Instant,
     t: Instant,
     state: AppState,
+    /// A [`Receiver`] for commands typed into the interactive prompt, if `--repl` was passed.
+    repl_receiver: Option<Receiver<ReplCommand>>,
+    /// The floating window that shows a video in picture-in-picture, if any.
+    picture_in_picture: Option<PictureInPictureWindow>,
 
     // This is the last field of the struct to ensure that windows are dropped *after* all other
     // references to the relevant rendering contexts have been destroyed.
@@ -106,6 +115,8 @@ impl App {
             t_start: t,
             t,
             state: AppState::Initializing,
+            repl_receiver: None,
+            picture_in_picture: None,
         }
     }
 
@@ -141,17 +152,20 @@ impl App {
             user_content_manager.add_script(script);
         }
 
+        let places = Arc::new(Mutex::new(Places::load(
+            self.servoshell_preferences.places_file.clone(),
+        )));
+
         let mut protocol_registry = ProtocolRegistry::default();
         let _ = protocol_registry.register(
             "urlinfo",
             protocols::urlinfo::UrlInfoProtocolHander::default(),
         );
-        let _ =
-            protocol_registry.register("servo", protocols::servo::ServoProtocolHandler::default());
         let _ = protocol_registry.register(
             "resource",
             protocols::resource::ResourceProtocolHandler::default(),
         );
+        protocols::servo::register_internal_pages(&mut protocol_registry, places.clone());
 
         let servo_builder = ServoBuilder::new(window.rendering_context())
             .opts(self.opts.clone())
@@ -202,6 +216,12 @@ impl App {
             window.clone(),
             self.servoshell_preferences.clone(),
             webdriver_receiver,
+            places,
+            SearchEngines::new(
+                self.servoshell_preferences.search_engines_file.clone(),
+                self.servoshell_preferences.search_suggestions_url.clone(),
+                self.waker.clone(),
+            ),
         ));
         running_state.create_and_focus_toplevel_webview(self.initial_url.clone().into_url());
 
@@ -211,6 +231,11 @@ impl App {
         }
 
         self.state = AppState::Running(running_state);
+
+        if self.servoshell_preferences.repl {
+            repl::print_help();
+            self.repl_receiver = Some(repl::start(self.waker.clone()));
+        }
     }
 
     pub(crate) fn animating(&self) -> bool {
@@ -255,7 +280,69 @@ impl App {
         }
 
         if matches!(self.state, AppState::ShuttingDown) {
+            self.picture_in_picture = None;
             event_loop.exit();
+            return;
+        }
+
+        self.update_picture_in_picture(event_loop);
+    }
+
+    /// Open or close the picture-in-picture window, following the requests of Servo.
+    fn update_picture_in_picture(&mut self, event_loop: &ActiveEventLoop) {
+        let AppState::Running(state) = &self.state else {
+            return;
+        };
+
+        if let Some(webview_id) = state.take_picture_in_picture_exited() {
+            if self
+                .picture_in_picture
+                .as_ref()
+                .is_some_and(|window| window.webview().id() == webview_id)
+            {
+                self.picture_in_picture = None;
+            }
+        }
+
+        if let Some((webview, request)) = state.take_picture_in_picture_request() {
+            // Only one video is shown in picture-in-picture at a time. A video of the same
+            // `WebView` replaces the previous one, and Servo stops painting that one once the
+            // new window is opened, so the previous window is closed afterwards.
+            let previous = self.picture_in_picture.take();
+            if let Some(previous) = &previous {
+                if previous.webview().id() != webview.id() {
+                    previous.webview().exit_picture_in_picture();
+                }
+            }
+            self.picture_in_picture = PictureInPictureWindow::open(event_loop, webview, request);
+            if let Some(previous) = previous {
+                if self.picture_in_picture.is_none() {
+                    previous.webview().exit_picture_in_picture();
+                }
+            }
+        }
+
+        if let Some(window) = &mut self.picture_in_picture {
+            if let Some(playing) = state.media_playing(window.webview().id()) {
+                window.set_playing(playing);
+            }
+        }
+    }
+
+    /// Handle an event of the picture-in-picture window, closing it if the video left
+    /// picture-in-picture.
+    fn handle_picture_in_picture_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        event: WindowEvent,
+    ) {
+        if let Some(window) = &mut self.picture_in_picture {
+            if !window.handle_winit_event(&event) {
+                self.picture_in_picture = None;
+            }
+        }
+        if let Some(window) = self.windows.values().next().cloned() {
+            self.handle_events_with_winit(event_loop, window);
         }
     }
 
@@ -302,6 +389,12 @@ impl App {
             match event {
                 MinibrowserEvent::Go(location) => {
                     minibrowser.update_location_dirty(false);
+                    if let Some(url) = state.search_engines().keyword_search_url(&location) {
+                        if let Some(focused_webview) = state.focused_webview() {
+                            focused_webview.load(url);
+                        }
+                        continue;
+                    }
                     let Some(url) = location_bar_input_to_url(
                         &location.clone(),
                         &self.servoshell_preferences.searchpage,
@@ -337,6 +430,34 @@ impl App {
                     minibrowser.update_location_dirty(false);
                     state.close_webview(id);
                 },
+                MinibrowserEvent::OpenDroppedUrl(url) => match Url::parse(&url) {
+                    Ok(url) => state.create_and_focus_toplevel_webview(url),
+                    Err(error) => warn!("Could not open dropped URL {url}: {error}"),
+                },
+                MinibrowserEvent::FindInPage(request) => {
+                    if let Some(focused_webview) = state.focused_webview() {
+                        focused_webview.find_in_page(request);
+                    }
+                },
+                MinibrowserEvent::StopFindingInPage => {
+                    if let Some(focused_webview) = state.focused_webview() {
+                        state.stop_finding_in_page(&focused_webview);
+                    }
+                },
+                MinibrowserEvent::ToggleBookmark => {
+                    let Some(focused_webview) = state.focused_webview() else {
+                        continue;
+                    };
+                    let Some(url) = focused_webview.url() else {
+                        continue;
+                    };
+                    let mut places = state.places().lock().unwrap();
+                    if places.is_bookmarked(url.as_str()) {
+                        places.remove_bookmark(url.as_str());
+                    } else {
+                        places.add_bookmark(url.as_str(), focused_webview.page_title());
+                    }
+                },
             }
         }
     }
@@ -366,8 +487,8 @@ impl App {
                     running_state.forward_webdriver_command(msg);
                 },
                 WebDriverCommandMsg::NewWebView(response_sender, load_status_sender) => {
-                    let new_webview =
-                        running_state.create_toplevel_webview(Url::parse("about:blank").unwrap());
+                    let new_webview = running_state
+                        .create_toplevel_webview(Url::parse("about:blank").unwrap(), false);
 
                     if let Err(error) = response_sender.send(new_webview.id()) {
                         warn!("Failed to send response of NewWebview: {error}");
@@ -600,7 +721,8 @@ impl App {
                         warn!("Failed to send response of GetAlertText: {error}");
                     };
                 },
-                WebDriverCommandMsg::TakeScreenshot(..) => {
+                WebDriverCommandMsg::TakeScreenshot(..) |
+                WebDriverCommandMsg::TakeMaskedScreenshot(..) => {
                     warn!(
                         "WebDriverCommand {:?} is still not moved from constellation to embedder",
                         msg
@@ -610,6 +732,71 @@ impl App {
         }
     }
 
+    /// Execute any commands typed into the interactive prompt enabled by `--repl`.
+    pub fn handle_repl_commands(&self) {
+        let AppState::Running(running_state) = &self.state else {
+            return;
+        };
+        let Some(repl_receiver) = self.repl_receiver.as_ref() else {
+            return;
+        };
+
+        while let Ok(command) = repl_receiver.try_recv() {
+            match command {
+                ReplCommand::Open(input) => {
+                    let Some(url) =
+                        location_bar_input_to_url(&input, &self.servoshell_preferences.searchpage)
+                    else {
+                        repl::print_async_output(&format!("Could not parse URL: {input}"));
+                        continue;
+                    };
+                    match running_state.focused_webview() {
+                        Some(webview) => webview.load(url.into_url()),
+                        None => running_state.create_and_focus_toplevel_webview(url.into_url()),
+                    }
+                },
+                ReplCommand::Js(script) => {
+                    let Some(webview) = running_state.focused_webview() else {
+                        repl::print_async_output("There is no focused WebView.");
+                        continue;
+                    };
+                    webview.evaluate_javascript(script, |result| {
+                        repl::print_async_output(&match result {
+                            Ok(value) => repl::js_value_to_json(&value).to_string(),
+                            Err(error) => format!("Error: {error:?}"),
+                        });
+                    });
+                },
+                ReplCommand::Metrics => {
+                    let Some(webview) = running_state.focused_webview() else {
+                        repl::print_async_output("There is no focused WebView.");
+                        continue;
+                    };
+                    webview.evaluate_javascript(ReplCommand::metrics_script(), |result| {
+                        let output = match result {
+                            Ok(JSValue::String(json)) => {
+                                serde_json::from_str::<serde_json::Value>(&json)
+                                    .and_then(|value| serde_json::to_string_pretty(&value))
+                                    .unwrap_or(json)
+                            },
+                            Ok(value) => repl::js_value_to_json(&value).to_string(),
+                            Err(error) => format!("Error: {error:?}"),
+                        };
+                        repl::print_async_output(&output);
+                    });
+                },
+                ReplCommand::Screenshot(path) => {
+                    repl::print_async_output(&match running_state.save_screenshot(&path) {
+                        Ok(()) => format!("Saved screenshot to {}", path.display()),
+                        Err(error) => error,
+                    });
+                },
+                ReplCommand::Help => repl::print_async_output(repl::HELP),
+                ReplCommand::Quit => running_state.servo().start_shutting_down(),
+            }
+        }
+    }
+
     fn handle_webdriver_script_commnd(
         &self,
         msg: &WebDriverScriptCommand,
@@ -649,6 +836,15 @@ impl ApplicationHandler<AppEvent> for App {
         );
         self.t = now;
 
+        if self
+            .picture_in_picture
+            .as_ref()
+            .is_some_and(|window| window.id() == window_id)
+        {
+            self.handle_picture_in_picture_event(event_loop, event);
+            return;
+        }
+
         let AppState::Running(state) = &self.state else {
             return;
         };
@@ -4,34 +4,46 @@
 
 use std::cell::{Ref, RefCell, RefMut};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use crossbeam_channel::Receiver;
 use euclid::Vector2D;
 use keyboard_types::{Key, Modifiers, ShortcutMatcher};
-use log::{error, info};
+use log::{error, info, warn};
 use servo::base::id::WebViewId;
 use servo::config::pref;
 use servo::ipc_channel::ipc::IpcSender;
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntPoint, DeviceIntSize};
 use servo::{
-    AllowOrDenyRequest, AuthenticationRequest, FilterPattern, FormControl, GamepadHapticEffectType,
-    KeyboardEvent, LoadStatus, PermissionRequest, Servo, ServoDelegate, ServoError, SimpleDialog,
-    WebDriverCommandMsg, WebDriverJSResult, WebDriverJSValue, WebDriverLoadStatus, WebView,
-    WebViewBuilder, WebViewDelegate,
+    AllowOrDenyRequest, AuthenticationRequest, BluetoothDeviceSelectionRequest, CrashDetails,
+    DragData, FilterPattern, FindInPageResult, FormControl, GamepadHapticEffectType, JSValue,
+    KeyboardEvent, LoadProgress, LoadStatus, MediaSessionEvent, MediaSessionPlaybackState,
+    Notification, PermissionRequest, PictureInPictureRequest, Servo, ServoDelegate, ServoError,
+    SimpleDialog, WebDriverCommandMsg, WebDriverJSResult, WebDriverJSValue, WebDriverLoadStatus,
+    WebView, WebViewBuilder, WebViewDelegate, WordListSpellChecker,
 };
 use url::Url;
 
 use super::app::PumpResult;
-use super::dialog::Dialog;
+use super::dialog::{Dialog, ValidationMessage};
 use super::gamepad::GamepadSupport;
 use super::keyutils::CMD_OR_CONTROL;
+use super::performance_hud::PerformanceHud;
+use super::places::SharedPlaces;
+use super::search::{OPENSEARCH_DISCOVERY_SCRIPT, SearchEngines};
+use super::stylesheet_watcher::{StylesheetOrigin, StylesheetWatcher};
 use super::window_trait::{LINE_HEIGHT, WindowPortsMethods};
-use crate::output_image::save_output_image_if_necessary;
+use crate::crash_reports;
+use crate::output_image::{read_thumbnail, save_image, save_output_image_if_necessary};
 use crate::prefs::ServoShellPreferences;
 
+/// The size of the screenshots of pages shown on `servo:newtab`.
+const THUMBNAIL_WIDTH: u32 = 320;
+const THUMBNAIL_HEIGHT: u32 = 200;
+
 pub(crate) enum AppState {
     Initializing,
     Running(Rc<RunningAppState>),
@@ -58,6 +70,11 @@ pub(crate) struct RunningAppState {
     /// was enabled.
     webdriver_receiver: Option<Receiver<WebDriverCommandMsg>>,
     webdriver_senders: RefCell<WebDriverSenders>,
+    /// The history and bookmarks, which are shared with the handler of the servo:history
+    /// and servo:bookmarks pages.
+    places: SharedPlaces,
+    /// The search engines of the location bar.
+    search_engines: SearchEngines,
     inner: RefCell<RunningAppStateInner>,
 }
 
@@ -77,18 +94,51 @@ pub struct RunningAppStateInner {
     /// The current set of open dialogs.
     dialogs: HashMap<WebViewId, Vec<Dialog>>,
 
+    /// The validation message of an invalid form control that is currently shown, if any.
+    validation_messages: HashMap<WebViewId, ValidationMessage>,
+
     /// A handle to the Window that Servo is rendering in -- either headed or headless.
     window: Rc<dyn WindowPortsMethods>,
 
     /// Gamepad support, which may be `None` if it failed to initialize.
     gamepad_support: Option<GamepadSupport>,
 
+    /// The stylesheet given with `--user-agent-stylesheet` and the `user.css` of the
+    /// profile directory, if any.
+    stylesheet_watchers: Vec<StylesheetWatcher>,
+
+    /// The spellchecker for the dictionary given with `--spellcheck-dictionary`, if any.
+    spellchecker: Option<Rc<WordListSpellChecker>>,
+
     /// Whether or not the application interface needs to be updated.
     need_update: bool,
 
     /// Whether or not Servo needs to repaint its display. Currently this is global
     /// because every `WebView` shares a `RenderingContext`.
     need_repaint: bool,
+
+    /// The performance overlay, if it is shown.
+    performance_hud: Option<PerformanceHud>,
+
+    /// A request to show a video in picture-in-picture, which is handled once a window can
+    /// be created for it.
+    picture_in_picture_request: Option<(WebView, PictureInPictureRequest)>,
+
+    /// The `WebView` whose video left picture-in-picture, if its window is still open.
+    picture_in_picture_exited: Option<WebViewId>,
+
+    /// Whether the media session of each `WebView` is playing, if it said so.
+    media_playing: HashMap<WebViewId, bool>,
+
+    /// The data of a drag that left a `WebView` and has not been dropped yet.
+    outgoing_drag: Option<DragData>,
+
+    /// The result of the last request to find text in the page of each `WebView`.
+    find_in_page_results: HashMap<WebViewId, FindInPageResult>,
+
+    /// The pages that finished loading and whose screenshot for `servo:newtab` is taken
+    /// the next time that their `WebView` is painted.
+    pending_thumbnails: HashMap<WebViewId, Url>,
 }
 
 impl Drop for RunningAppState {
@@ -103,35 +153,94 @@ impl RunningAppState {
         window: Rc<dyn WindowPortsMethods>,
         servoshell_preferences: ServoShellPreferences,
         webdriver_receiver: Option<Receiver<WebDriverCommandMsg>>,
+        places: SharedPlaces,
+        search_engines: SearchEngines,
     ) -> RunningAppState {
         servo.set_delegate(Rc::new(ServoShellServoDelegate));
+        let filter_lists: Vec<String> = servoshell_preferences
+            .content_blocking_filter_lists
+            .iter()
+            .filter_map(|path| match fs::read_to_string(path) {
+                Ok(filter_list) => Some(filter_list),
+                Err(error) => {
+                    warn!("Could not load filter list {path:?}: {error}");
+                    None
+                },
+            })
+            .collect();
+        if !filter_lists.is_empty() {
+            servo.set_content_blocking_filter_lists(filter_lists);
+        }
+        let user_agent_stylesheet_watcher = servoshell_preferences
+            .user_agent_stylesheet
+            .clone()
+            .map(|path| StylesheetWatcher::new(path, StylesheetOrigin::UserAgent));
+        let user_stylesheet_watcher = servoshell_preferences
+            .user_stylesheet
+            .clone()
+            .map(|path| StylesheetWatcher::new(path, StylesheetOrigin::User));
+        let stylesheet_watchers = user_agent_stylesheet_watcher
+            .into_iter()
+            .chain(user_stylesheet_watcher)
+            .collect();
+        let spellchecker = servoshell_preferences
+            .spellcheck_dictionary
+            .as_deref()
+            .and_then(|path| match WordListSpellChecker::from_path(path) {
+                Ok(spellchecker) => Some(Rc::new(spellchecker)),
+                Err(error) => {
+                    warn!("Could not load spellcheck dictionary {path:?}: {error}");
+                    None
+                },
+            });
         RunningAppState {
             servo,
             servoshell_preferences,
             webdriver_receiver,
             webdriver_senders: RefCell::default(),
+            places,
+            search_engines,
             inner: RefCell::new(RunningAppStateInner {
                 webviews: HashMap::default(),
                 creation_order: Default::default(),
                 focused_webview_id: None,
                 dialogs: Default::default(),
+                validation_messages: Default::default(),
                 window,
                 gamepad_support: GamepadSupport::maybe_new(),
+                stylesheet_watchers,
+                spellchecker,
                 need_update: false,
                 need_repaint: false,
+                performance_hud: None,
+                picture_in_picture_request: None,
+                picture_in_picture_exited: None,
+                media_playing: Default::default(),
+                outgoing_drag: None,
+                find_in_page_results: Default::default(),
+                pending_thumbnails: Default::default(),
             }),
         }
     }
 
     pub(crate) fn create_and_focus_toplevel_webview(self: &Rc<Self>, url: Url) {
-        let webview = self.create_toplevel_webview(url);
+        let webview = self.create_toplevel_webview(url, false);
         webview.focus();
         webview.raise_to_top(true);
     }
 
-    pub(crate) fn create_toplevel_webview(self: &Rc<Self>, url: Url) -> WebView {
+    /// Open a tab in private browsing mode, which does not share cookies, cache or storage
+    /// with the other tabs and whose data is discarded once the last private tab is closed.
+    pub(crate) fn create_and_focus_private_toplevel_webview(self: &Rc<Self>, url: Url) {
+        let webview = self.create_toplevel_webview(url, true);
+        webview.focus();
+        webview.raise_to_top(true);
+    }
+
+    pub(crate) fn create_toplevel_webview(self: &Rc<Self>, url: Url, private: bool) -> WebView {
         let webview = WebViewBuilder::new(self.servo())
             .url(url)
+            .private(private)
             .hidpi_scale_factor(self.inner().window.hidpi_scale_factor())
             .delegate(self.clone())
             .build();
@@ -189,16 +298,51 @@ impl RunningAppState {
             &self.servoshell_preferences,
             &self.inner().window.rendering_context(),
         );
+        self.save_thumbnail_if_necessary(&webview);
 
-        let mut inner_mut = self.inner_mut();
-        inner_mut.window.rendering_context().present();
-        inner_mut.need_repaint = false;
+        webview.present();
+        self.inner_mut().need_repaint = false;
 
         if self.servoshell_preferences.exit_after_stable_image {
             self.servo().start_shutting_down();
         }
     }
 
+    /// Save the screenshot of the page of the given `WebView` for `servo:newtab`, if it
+    /// finished loading since it was last painted. This must be done before presenting.
+    fn save_thumbnail_if_necessary(&self, webview: &WebView) {
+        let Some(url) = self.inner_mut().pending_thumbnails.remove(&webview.id()) else {
+            return;
+        };
+        if webview.url().as_ref() != Some(&url) {
+            return;
+        }
+        let rendering_context = self.inner().window.rendering_context();
+        if let Some(thumbnail) =
+            read_thumbnail(&rendering_context, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)
+        {
+            self.places
+                .lock()
+                .unwrap()
+                .save_thumbnail(url.as_str(), thumbnail);
+        }
+    }
+
+    /// Paint the focused `WebView` and save the result to `path`, presenting the frame
+    /// afterward.
+    pub(crate) fn save_screenshot(&self, path: &Path) -> Result<(), String> {
+        let Some(webview) = self.focused_webview() else {
+            return Err("There is no focused WebView to take a screenshot of.".to_owned());
+        };
+        webview.paint();
+
+        let rendering_context = self.inner().window.rendering_context();
+        let result = save_image(&rendering_context, path);
+        webview.present();
+        self.inner_mut().need_repaint = false;
+        result
+    }
+
     /// Spins the internal application event loop.
     ///
     /// - Notifies Servo about incoming gamepad events
@@ -207,14 +351,25 @@ impl RunningAppState {
         if pref!(dom_gamepad_enabled) {
             self.handle_gamepad_events();
         }
+        self.reload_stylesheets_if_changed();
 
         if !self.servo().spin_event_loop() {
             return PumpResult::Shutdown;
         }
+        self.search_engines.fetch_pending_descriptions(&self.servo);
+        if self.search_engines.take_updated() {
+            self.inner_mut().need_update = true;
+        }
 
         // Delegate handlers may have asked us to present or update compositor contents.
         // Currently, egui-file-dialog dialogs need to be constantly redrawn or animations aren't fluid.
-        let need_window_redraw = self.inner().need_repaint || self.has_active_dialog();
+        let performance_hud_updated = self
+            .inner_mut()
+            .performance_hud
+            .as_mut()
+            .is_some_and(|performance_hud| performance_hud.update(&self.servo));
+        let need_window_redraw =
+            self.inner().need_repaint || self.has_active_dialog() || performance_hud_updated;
         let need_update = std::mem::replace(&mut self.inner_mut().need_update, false);
 
         PumpResult::Continue {
@@ -223,7 +378,25 @@ impl RunningAppState {
         }
     }
 
+    fn reload_stylesheets_if_changed(&self) {
+        let mut inner = self.inner_mut();
+        let inner = &mut *inner;
+        for watcher in inner.stylesheet_watchers.iter_mut() {
+            if watcher.check_for_changes() {
+                for webview in inner.webviews.values() {
+                    watcher.apply(webview);
+                }
+            }
+        }
+    }
+
     pub(crate) fn add(&self, webview: WebView) {
+        for watcher in &self.inner().stylesheet_watchers {
+            watcher.apply(&webview);
+        }
+        if let Some(spellchecker) = self.inner().spellchecker.clone() {
+            webview.set_spellcheck_delegate(spellchecker);
+        }
         self.inner_mut().creation_order.push(webview.id());
         self.inner_mut().webviews.insert(webview.id(), webview);
     }
@@ -259,6 +432,10 @@ impl RunningAppState {
         inner.webviews.retain(|&id, _| id != webview_id);
         inner.creation_order.retain(|&id| id != webview_id);
         inner.dialogs.remove(&webview_id);
+        inner.validation_messages.remove(&webview_id);
+        inner.media_playing.remove(&webview_id);
+        inner.find_in_page_results.remove(&webview_id);
+        inner.pending_thumbnails.remove(&webview_id);
         if Some(webview_id) == inner.focused_webview_id {
             inner.focused_webview_id = None;
         }
@@ -274,6 +451,25 @@ impl RunningAppState {
         }
     }
 
+    pub(crate) fn take_picture_in_picture_request(
+        &self,
+    ) -> Option<(WebView, PictureInPictureRequest)> {
+        self.inner_mut().picture_in_picture_request.take()
+    }
+
+    pub(crate) fn take_picture_in_picture_exited(&self) -> Option<WebViewId> {
+        self.inner_mut().picture_in_picture_exited.take()
+    }
+
+    /// Take the data of the drag that left a `WebView`, if any, when the user drops it.
+    pub(crate) fn take_outgoing_drag(&self) -> Option<DragData> {
+        self.inner_mut().outgoing_drag.take()
+    }
+
+    pub(crate) fn media_playing(&self, webview_id: WebViewId) -> Option<bool> {
+        self.inner().media_playing.get(&webview_id).copied()
+    }
+
     pub fn focused_webview(&self) -> Option<WebView> {
         self.inner()
             .focused_webview_id
@@ -319,6 +515,49 @@ impl RunningAppState {
         inner_mut.need_update = true;
     }
 
+    /// Show the performance overlay if it is hidden, or hide it if it is shown.
+    pub(crate) fn toggle_performance_hud(&self) {
+        let mut inner_mut = self.inner_mut();
+        inner_mut.performance_hud = match inner_mut.performance_hud.take() {
+            Some(_) => None,
+            None => Some(PerformanceHud::new(&self.servo)),
+        };
+        inner_mut.need_repaint = true;
+    }
+
+    /// Show the performance overlay, if it is enabled, over the focused `WebView`.
+    pub(crate) fn show_performance_hud(&self, ctx: &egui::Context) {
+        let paint_metrics = self.focused_webview().as_ref().map(WebView::paint_metrics);
+        if let Some(performance_hud) = self.inner().performance_hud.as_ref() {
+            performance_hud.show(ctx, paint_metrics);
+        }
+    }
+
+    /// Show the validation message of the focused `WebView`, if it has one that has not expired.
+    /// The result of the last request to find text in the page of the given `WebView`.
+    pub(crate) fn find_in_page_result(&self, webview_id: WebViewId) -> Option<FindInPageResult> {
+        self.inner().find_in_page_results.get(&webview_id).copied()
+    }
+
+    /// Stop finding text in the page of the given `WebView`.
+    pub(crate) fn stop_finding_in_page(&self, webview: &WebView) {
+        webview.stop_finding_in_page();
+        self.inner_mut().find_in_page_results.remove(&webview.id());
+    }
+
+    pub(crate) fn show_validation_message(&self, ctx: &egui::Context) {
+        let Some(webview_id) = self.focused_webview().as_ref().map(WebView::id) else {
+            return;
+        };
+        let mut inner_mut = self.inner_mut();
+        let Some(validation_message) = inner_mut.validation_messages.get(&webview_id) else {
+            return;
+        };
+        if !validation_message.update(ctx) {
+            inner_mut.validation_messages.remove(&webview_id);
+        }
+    }
+
     pub(crate) fn has_active_dialog(&self) -> bool {
         let last_created_webview_id = self.inner().creation_order.last().cloned();
         let Some(webview_id) = self
@@ -369,6 +608,24 @@ impl RunningAppState {
             .and_then(|dialog| dialog.message())
     }
 
+    pub(crate) fn places(&self) -> &SharedPlaces {
+        &self.places
+    }
+
+    pub(crate) fn search_engines(&self) -> &SearchEngines {
+        &self.search_engines
+    }
+
+    /// The URL under which the page of the given `WebView` is recorded in the history, if it
+    /// should be. Pages of private `WebView`s and internal pages are not recorded.
+    fn history_url(&self, webview: &WebView) -> Option<Url> {
+        if webview.is_private() {
+            return None;
+        }
+        let url = webview.url()?;
+        matches!(url.scheme(), "http" | "https" | "file").then_some(url)
+    }
+
     pub(crate) fn get_focused_webview_index(&self) -> Option<usize> {
         let focused_id = self.inner().focused_webview_id?;
         self.webviews()
@@ -485,6 +742,22 @@ impl ServoDelegate for ServoShellServoDelegate {
     fn notify_error(&self, _servo: &Servo, error: ServoError) {
         error!("Saw Servo error: {error:?}!");
     }
+
+    fn show_notification(&self, notification: Notification) {
+        show_desktop_notification(notification);
+    }
+}
+
+/// Show a notification from web content using the notification service of the platform.
+fn show_desktop_notification(notification: Notification) {
+    let mut desktop_notification = notify_rust::Notification::new();
+    desktop_notification
+        .appname("Servo")
+        .summary(&notification.title)
+        .body(&notification.body);
+    if let Err(error) = desktop_notification.show() {
+        warn!("Failed to show notification: {error}");
+    }
 }
 
 impl WebViewDelegate for RunningAppState {
@@ -492,11 +765,29 @@ impl WebViewDelegate for RunningAppState {
         Some(self.inner().window.screen_geometry())
     }
 
+    fn notify_crashed(&self, webview: WebView, crash_details: CrashDetails) {
+        error!("{:?} crashed: {}", webview.id(), crash_details.reason);
+        if let Some(path) = crash_reports::write_content_crash_report(&crash_details) {
+            info!("Wrote crash report to {}", path.display());
+        }
+        self.inner_mut().need_update = true;
+    }
+
     fn notify_status_text_changed(&self, _webview: servo::WebView, _status: Option<String>) {
         self.inner_mut().need_update = true;
     }
 
+    fn notify_content_blocked(&self, _webview: servo::WebView, _url: Url) {
+        self.inner_mut().need_update = true;
+    }
+
     fn notify_page_title_changed(&self, webview: servo::WebView, title: Option<String>) {
+        if let Some(url) = self.history_url(&webview) {
+            self.places
+                .lock()
+                .unwrap()
+                .set_title(url.as_str(), title.clone());
+        }
         if webview.focused() {
             let window_title = format!("{} - Servo", title.clone().unwrap_or_default());
             self.inner().window.set_title(&window_title);
@@ -504,6 +795,23 @@ impl WebViewDelegate for RunningAppState {
         }
     }
 
+    fn notify_load_progress_changed(&self, webview: servo::WebView, _: LoadProgress) {
+        if webview.focused() {
+            self.inner_mut().need_update = true;
+        }
+    }
+
+    fn notify_favicon_changed(&self, webview: servo::WebView) {
+        if let (Some(url), Some(favicon_url)) = (self.history_url(&webview), webview.favicon_url())
+        {
+            self.places
+                .lock()
+                .unwrap()
+                .set_favicon_url(url.as_str(), favicon_url.as_str());
+        }
+        self.inner_mut().need_update = true;
+    }
+
     fn request_move_to(&self, _: servo::WebView, new_position: DeviceIntPoint) {
         self.inner().window.set_position(new_position);
     }
@@ -573,6 +881,7 @@ impl WebViewDelegate for RunningAppState {
         parent_webview: servo::WebView,
     ) -> Option<servo::WebView> {
         let webview = WebViewBuilder::new_auxiliary(&self.servo)
+            .private(parent_webview.is_private())
             .hidpi_scale_factor(self.inner().window.hidpi_scale_factor())
             .delegate(parent_webview.delegate())
             .build();
@@ -612,10 +921,35 @@ impl WebViewDelegate for RunningAppState {
         self.inner().window.set_cursor(cursor);
     }
 
+    fn start_drag(&self, _webview: servo::WebView, data: DragData) {
+        self.inner_mut().outgoing_drag = Some(data);
+    }
+
     fn notify_load_status_changed(&self, webview: servo::WebView, status: LoadStatus) {
         self.inner_mut().need_update = true;
 
         if status == LoadStatus::Complete {
+            if let Some(url) = self.history_url(&webview) {
+                let mut places = self.places.lock().unwrap();
+                places.record_visit(url.as_str(), webview.page_title());
+                // The favicon of the page may have been loaded before the page finished loading.
+                if let Some(favicon_url) = webview.favicon().and(webview.favicon_url()) {
+                    places.set_favicon_url(url.as_str(), favicon_url.as_str());
+                }
+                drop(places);
+                self.inner_mut()
+                    .pending_thumbnails
+                    .insert(webview.id(), url.clone());
+
+                let pending_descriptions = self.search_engines.pending_descriptions();
+                webview.evaluate_javascript(OPENSEARCH_DISCOVERY_SCRIPT, move |result| {
+                    if let Ok(JSValue::String(description_url)) = result {
+                        if let Ok(description_url) = Url::parse(&description_url) {
+                            pending_descriptions.lock().unwrap().push(description_url);
+                        }
+                    }
+                });
+            }
             if let Some(sender) = self
                 .webdriver_senders
                 .borrow_mut()
@@ -631,16 +965,45 @@ impl WebViewDelegate for RunningAppState {
         self.inner().window.set_fullscreen(fullscreen_state);
     }
 
-    fn show_bluetooth_device_dialog(
+    fn request_picture_in_picture(&self, webview: WebView, request: PictureInPictureRequest) {
+        if self.servoshell_preferences.headless {
+            request.deny();
+            return;
+        }
+        let previous_request = self
+            .inner_mut()
+            .picture_in_picture_request
+            .replace((webview, request));
+        if let Some((_, previous_request)) = previous_request {
+            previous_request.deny();
+        }
+    }
+
+    fn notify_picture_in_picture_exited(&self, webview: WebView) {
+        self.inner_mut().picture_in_picture_exited = Some(webview.id());
+    }
+
+    fn notify_find_in_page_result(&self, webview: WebView, result: FindInPageResult) {
+        let mut inner_mut = self.inner_mut();
+        inner_mut.find_in_page_results.insert(webview.id(), result);
+        inner_mut.need_update = true;
+    }
+
+    fn notify_media_session_event(&self, webview: WebView, event: MediaSessionEvent) {
+        if let MediaSessionEvent::PlaybackStateChange(state) = event {
+            self.inner_mut().media_playing.insert(
+                webview.id(),
+                matches!(state, MediaSessionPlaybackState::Playing),
+            );
+        }
+    }
+
+    fn request_bluetooth_device(
         &self,
         webview: servo::WebView,
-        devices: Vec<String>,
-        response_sender: IpcSender<Option<String>>,
+        request: BluetoothDeviceSelectionRequest,
     ) {
-        self.add_dialog(
-            webview,
-            Dialog::new_device_selection_dialog(devices, response_sender),
-        );
+        self.add_dialog(webview, Dialog::new_device_selection_dialog(request));
     }
 
     fn show_file_selection_dialog(
@@ -655,6 +1018,26 @@ impl WebViewDelegate for RunningAppState {
         self.add_dialog(webview, file_dialog);
     }
 
+    fn show_file_save_dialog(
+        &self,
+        webview: servo::WebView,
+        filter_pattern: Vec<FilterPattern>,
+        suggested_name: Option<String>,
+        response_sender: IpcSender<Option<PathBuf>>,
+    ) {
+        let file_dialog =
+            Dialog::new_save_file_dialog(suggested_name, response_sender, filter_pattern);
+        self.add_dialog(webview, file_dialog);
+    }
+
+    fn show_directory_selection_dialog(
+        &self,
+        webview: servo::WebView,
+        response_sender: IpcSender<Option<PathBuf>>,
+    ) {
+        self.add_dialog(webview, Dialog::new_directory_dialog(response_sender));
+    }
+
     fn request_permission(&self, webview: servo::WebView, permission_request: PermissionRequest) {
         if self.servoshell_preferences.headless &&
             self.servoshell_preferences.webdriver_port.is_none()
@@ -717,6 +1100,14 @@ impl WebViewDelegate for RunningAppState {
         self.inner().window.hide_ime();
     }
 
+    fn update_ime_cursor_area(
+        &self,
+        _webview: WebView,
+        position: servo::webrender_api::units::DeviceIntRect,
+    ) {
+        self.inner().window.update_ime_cursor_area(position);
+    }
+
     fn show_form_control(&self, webview: WebView, form_control: FormControl) {
         if self.servoshell_preferences.headless &&
             self.servoshell_preferences.webdriver_port.is_none()
@@ -740,6 +1131,34 @@ impl WebViewDelegate for RunningAppState {
                     Dialog::new_color_picker_dialog(color_picker, offset),
                 );
             },
+            FormControl::DateTimePicker(date_time_picker) => {
+                // FIXME: Reading the toolbar height is needed here to properly position the picker.
+                // But if the toolbar height changes while the dialog is open then the position won't be updated
+                let offset = self.inner().window.toolbar_height();
+                self.add_dialog(
+                    webview,
+                    Dialog::new_date_time_picker_dialog(date_time_picker, offset),
+                );
+            },
         }
     }
+
+    fn show_validation_message(
+        &self,
+        webview: WebView,
+        position: servo::webrender_api::units::DeviceIntRect,
+        message: String,
+    ) {
+        if self.servoshell_preferences.headless {
+            return;
+        }
+
+        let offset = self.inner().window.toolbar_height();
+        let mut inner_mut = self.inner_mut();
+        inner_mut.validation_messages.insert(
+            webview.id(),
+            ValidationMessage::new(message, position, offset),
+        );
+        inner_mut.need_update = true;
+    }
 }
@@ -28,6 +28,9 @@ pub fn main() {
 
     crate::init_tracing(servoshell_preferences.tracing_filter.as_deref());
 
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    crate::crash_reports::prepare_native_crash_report(opts.config_dir.as_deref());
+
     let clean_shutdown = servoshell_preferences.clean_shutdown;
     let has_output_file = servoshell_preferences.output_image_path.is_some();
     let event_loop = EventsLoop::new(servoshell_preferences.headless, has_output_file)
@@ -38,5 +41,8 @@ pub fn main() {
         event_loop.run_app(&mut app);
     }
 
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    crate::crash_reports::discard_native_crash_report();
+
     crate::platform::deinit(clean_shutdown)
 }
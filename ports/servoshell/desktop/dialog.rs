@@ -4,6 +4,7 @@
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use egui::Modal;
 use egui_file_dialog::{DialogState, FileDialog as EguiFileDialog};
@@ -11,8 +12,10 @@ use euclid::Length;
 use log::warn;
 use servo::ipc_channel::ipc::IpcSender;
 use servo::servo_geometry::DeviceIndependentPixel;
+use servo::webrender_api::units::DeviceIntRect;
 use servo::{
-    AlertResponse, AuthenticationRequest, ColorPicker, ConfirmResponse, FilterPattern,
+    AlertResponse, AllowOrDeny, AuthenticationRequest, BluetoothDeviceSelectionRequest,
+    ColorPicker, ConfirmResponse, DateTimePicker, DateTimePickerType, FilterPattern,
     PermissionRequest, PromptResponse, RgbColor, SelectElement, SelectElementOption,
     SelectElementOptionOrOptgroup, SimpleDialog,
 };
@@ -23,6 +26,14 @@ pub enum Dialog {
         multiple: bool,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
     },
+    SaveFile {
+        dialog: EguiFileDialog,
+        response_sender: IpcSender<Option<PathBuf>>,
+    },
+    Directory {
+        dialog: EguiFileDialog,
+        response_sender: IpcSender<Option<PathBuf>>,
+    },
     #[allow(clippy::enum_variant_names, reason = "spec terminology")]
     SimpleDialog(SimpleDialog),
     Authentication {
@@ -32,12 +43,12 @@ pub enum Dialog {
     },
     Permission {
         message: String,
+        remember: bool,
         request: Option<PermissionRequest>,
     },
     SelectDevice {
-        devices: Vec<String>,
         selected_device_index: usize,
-        response_sender: IpcSender<Option<String>>,
+        request: Option<BluetoothDeviceSelectionRequest>,
     },
     SelectElement {
         maybe_prompt: Option<SelectElement>,
@@ -48,6 +59,11 @@ pub enum Dialog {
         maybe_prompt: Option<ColorPicker>,
         toolbar_offset: Length<f32, DeviceIndependentPixel>,
     },
+    DateTimePicker {
+        state: DateTimePickerState,
+        maybe_prompt: Option<DateTimePicker>,
+        toolbar_offset: Length<f32, DeviceIndependentPixel>,
+    },
 }
 
 impl Dialog {
@@ -55,27 +71,32 @@ impl Dialog {
         multiple: bool,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
         patterns: Vec<FilterPattern>,
+    ) -> Self {
+        Dialog::File {
+            dialog: file_dialog_with_filter(EguiFileDialog::new(), patterns),
+            multiple,
+            response_sender,
+        }
+    }
+
+    pub fn new_save_file_dialog(
+        suggested_name: Option<String>,
+        response_sender: IpcSender<Option<PathBuf>>,
+        patterns: Vec<FilterPattern>,
     ) -> Self {
         let mut dialog = EguiFileDialog::new();
-        if !patterns.is_empty() {
-            dialog = dialog
-                .add_file_filter(
-                    "All Supported Types",
-                    Arc::new(move |path: &Path| {
-                        path.extension()
-                            .and_then(|e| e.to_str())
-                            .is_some_and(|ext| {
-                                let ext = ext.to_lowercase();
-                                patterns.iter().any(|pattern| ext == pattern.0)
-                            })
-                    }),
-                )
-                .default_file_filter("All Supported Types");
+        if let Some(suggested_name) = suggested_name {
+            dialog = dialog.default_file_name(&suggested_name);
+        }
+        Dialog::SaveFile {
+            dialog: file_dialog_with_filter(dialog, patterns),
+            response_sender,
         }
+    }
 
-        Dialog::File {
-            dialog,
-            multiple,
+    pub fn new_directory_dialog(response_sender: IpcSender<Option<PathBuf>>) -> Self {
+        Dialog::Directory {
+            dialog: EguiFileDialog::new(),
             response_sender,
         }
     }
@@ -94,23 +115,21 @@ impl Dialog {
 
     pub fn new_permission_request_dialog(permission_request: PermissionRequest) -> Self {
         let message = format!(
-            "Do you want to grant permission for {:?}?",
+            "Do you want to grant {} permission for {:?}?",
+            permission_request.origin().ascii_serialization(),
             permission_request.feature()
         );
         Dialog::Permission {
             message,
+            remember: false,
             request: Some(permission_request),
         }
     }
 
-    pub fn new_device_selection_dialog(
-        devices: Vec<String>,
-        response_sender: IpcSender<Option<String>>,
-    ) -> Self {
+    pub fn new_device_selection_dialog(request: BluetoothDeviceSelectionRequest) -> Self {
         Dialog::SelectDevice {
-            devices,
             selected_device_index: 0,
-            response_sender,
+            request: Some(request),
         }
     }
 
@@ -140,6 +159,17 @@ impl Dialog {
         }
     }
 
+    pub fn new_date_time_picker_dialog(
+        prompt: DateTimePicker,
+        toolbar_offset: Length<f32, DeviceIndependentPixel>,
+    ) -> Self {
+        Dialog::DateTimePicker {
+            state: DateTimePickerState::new(prompt.current_value()),
+            maybe_prompt: Some(prompt),
+            toolbar_offset,
+        }
+    }
+
     pub fn accept(&self) {
         #[allow(clippy::single_match)]
         match self {
@@ -207,6 +237,58 @@ impl Dialog {
                     DialogState::Closed => false,
                 }
             },
+            Dialog::SaveFile {
+                dialog,
+                response_sender,
+            } => {
+                if dialog.state() == DialogState::Closed {
+                    dialog.save_file();
+                }
+
+                let state = dialog.update(ctx).state();
+                match state {
+                    DialogState::Open => true,
+                    DialogState::Picked(path) => {
+                        if let Err(e) = response_sender.send(Some(path)) {
+                            warn!("Failed to send file save response: {}", e);
+                        }
+                        false
+                    },
+                    DialogState::Cancelled | DialogState::PickedMultiple(_) => {
+                        if let Err(e) = response_sender.send(None) {
+                            warn!("Failed to send cancellation response: {}", e);
+                        }
+                        false
+                    },
+                    DialogState::Closed => false,
+                }
+            },
+            Dialog::Directory {
+                dialog,
+                response_sender,
+            } => {
+                if dialog.state() == DialogState::Closed {
+                    dialog.pick_directory();
+                }
+
+                let state = dialog.update(ctx).state();
+                match state {
+                    DialogState::Open => true,
+                    DialogState::Picked(path) => {
+                        if let Err(e) = response_sender.send(Some(path)) {
+                            warn!("Failed to send directory selection response: {}", e);
+                        }
+                        false
+                    },
+                    DialogState::Cancelled | DialogState::PickedMultiple(_) => {
+                        if let Err(e) = response_sender.send(None) {
+                            warn!("Failed to send cancellation response: {}", e);
+                        }
+                        false
+                    },
+                    DialogState::Closed => false,
+                }
+            },
             Dialog::SimpleDialog(SimpleDialog::Alert {
                 message,
                 response_sender,
@@ -357,11 +439,16 @@ impl Dialog {
                 });
                 is_open
             },
-            Dialog::Permission { message, request } => {
+            Dialog::Permission {
+                message,
+                remember,
+                request,
+            } => {
                 let mut is_open = true;
                 let modal = Modal::new("permission".into());
                 modal.show(ctx, |ui| {
                     make_dialog_label(message, ui, None);
+                    ui.checkbox(remember, "Remember this decision");
                     egui::Sides::new().show(
                         ui,
                         |_ui| {},
@@ -371,7 +458,7 @@ impl Dialog {
                             {
                                 let request =
                                     request.take().expect("non-None until dialog is closed");
-                                request.allow();
+                                request.respond(AllowOrDeny::Allow, *remember);
                                 is_open = false;
                             }
                             if ui.button("Deny").clicked() ||
@@ -379,7 +466,7 @@ impl Dialog {
                             {
                                 let request =
                                     request.take().expect("non-None until dialog is closed");
-                                request.deny();
+                                request.respond(AllowOrDeny::Deny, *remember);
                                 is_open = false;
                             }
                         },
@@ -388,10 +475,23 @@ impl Dialog {
                 is_open
             },
             Dialog::SelectDevice {
-                devices,
                 selected_device_index,
-                response_sender,
+                request,
             } => {
+                let Some(device_request) = request else {
+                    return false;
+                };
+                let origin = device_request.origin().to_owned();
+                let device_labels: Vec<String> = device_request
+                    .devices()
+                    .iter()
+                    .map(|device| match &device.name {
+                        Some(name) => format!("{name} ({})", device.address),
+                        None => device.address.clone(),
+                    })
+                    .collect();
+                let has_devices = !device_labels.is_empty();
+
                 let mut is_open = true;
                 let modal = Modal::new("device_picker".into());
                 modal.show(ctx, |ui| {
@@ -399,16 +499,22 @@ impl Dialog {
                     frame.content_ui.set_min_width(150.0);
 
                     frame.content_ui.heading("Choose a Device");
+                    frame
+                        .content_ui
+                        .label(format!("{origin} wants to connect to a Bluetooth device"));
                     frame.content_ui.add_space(10.0);
 
-                    egui::ComboBox::from_label("")
-                        .selected_text(&devices[*selected_device_index + 1])
-                        .show_ui(&mut frame.content_ui, |ui| {
-                            for i in (0..devices.len() - 1).step_by(2) {
-                                let device_name = &devices[i + 1];
-                                ui.selectable_value(selected_device_index, i, device_name);
-                            }
-                        });
+                    if has_devices {
+                        egui::ComboBox::from_label("")
+                            .selected_text(&device_labels[*selected_device_index])
+                            .show_ui(&mut frame.content_ui, |ui| {
+                                for (index, label) in device_labels.iter().enumerate() {
+                                    ui.selectable_value(selected_device_index, index, label);
+                                }
+                            });
+                    } else {
+                        frame.content_ui.label("No compatible devices found");
+                    }
 
                     frame.end(ui);
 
@@ -416,22 +522,24 @@ impl Dialog {
                         ui,
                         |_ui| {},
                         |ui| {
-                            if ui.button("Ok").clicked() ||
-                                ui.input(|i| i.key_pressed(egui::Key::Enter))
+                            if has_devices &&
+                                (ui.button("Ok").clicked() ||
+                                    ui.input(|i| i.key_pressed(egui::Key::Enter)))
                             {
-                                if let Err(e) = response_sender
-                                    .send(Some(devices[*selected_device_index].clone()))
-                                {
-                                    warn!("Failed to send device selection: {}", e);
-                                }
+                                let request =
+                                    request.take().expect("non-None until dialog is closed");
+                                let address =
+                                    request.devices()[*selected_device_index].address.clone();
+                                request.select(address);
                                 is_open = false;
                             }
-                            if ui.button("Cancel").clicked() ||
-                                ui.input(|i| i.key_pressed(egui::Key::Escape))
+                            if is_open &&
+                                (ui.button("Cancel").clicked() ||
+                                    ui.input(|i| i.key_pressed(egui::Key::Escape)))
                             {
-                                if let Err(e) = response_sender.send(None) {
-                                    warn!("Failed to send cancellation: {}", e);
-                                }
+                                let request =
+                                    request.take().expect("non-None until dialog is closed");
+                                request.cancel();
                                 is_open = false;
                             }
                         },
@@ -455,17 +563,18 @@ impl Dialog {
                 let area = egui::Area::new(egui::Id::new("select-window"))
                     .fixed_pos(egui::pos2(position.min.x as f32, position.max.y as f32));
 
-                let mut selected_option = prompt.selected_option();
+                let allow_select_multiple = prompt.allow_select_multiple();
+                let mut selected_options = prompt.selected_options().to_vec();
 
                 fn display_option(
                     ui: &mut egui::Ui,
                     option: &SelectElementOption,
-                    selected_option: &mut Option<usize>,
+                    selected_options: &mut Vec<usize>,
+                    allow_select_multiple: bool,
                     is_open: &mut bool,
                     in_group: bool,
                 ) {
-                    let is_checked =
-                        selected_option.is_some_and(|selected_index| selected_index == option.id);
+                    let is_checked = selected_options.contains(&option.id);
 
                     // TODO: Surely there's a better way to align text in a selectable label in egui.
                     let label_text = if in_group {
@@ -487,8 +596,14 @@ impl Dialog {
                         .inner;
 
                     if clickable_area.clicked() && !option.is_disabled {
-                        *selected_option = Some(option.id);
-                        *is_open = false;
+                        if !allow_select_multiple {
+                            *selected_options = vec![option.id];
+                            *is_open = false;
+                        } else if is_checked {
+                            selected_options.retain(|id| *id != option.id);
+                        } else {
+                            selected_options.push(option.id);
+                        }
                     }
 
                     if clickable_area.hovered() && option.is_disabled {
@@ -505,7 +620,8 @@ impl Dialog {
                                     display_option(
                                         ui,
                                         option,
-                                        &mut selected_option,
+                                        &mut selected_options,
+                                        allow_select_multiple,
                                         &mut is_open,
                                         false,
                                     );
@@ -517,7 +633,8 @@ impl Dialog {
                                         display_option(
                                             ui,
                                             option,
-                                            &mut selected_option,
+                                            &mut selected_options,
+                                            allow_select_multiple,
                                             &mut is_open,
                                             true,
                                         );
@@ -526,9 +643,20 @@ impl Dialog {
                             }
                         }
                     });
+
+                    // A `<select multiple>` stays open until the user is done toggling options.
+                    if allow_select_multiple {
+                        ui.separator();
+                        if ui.button("Done").clicked() {
+                            is_open = false;
+                        }
+                    }
                 });
 
-                prompt.select(selected_option);
+                prompt.select(None);
+                for id in selected_options {
+                    prompt.select(Some(id));
+                }
 
                 if !is_open {
                     maybe_prompt.take().unwrap().submit();
@@ -578,12 +706,300 @@ impl Dialog {
                     }
                 });
 
+                is_open
+            },
+            Dialog::DateTimePicker {
+                state,
+                maybe_prompt,
+                toolbar_offset,
+            } => {
+                let Some(prompt) = maybe_prompt else {
+                    // Prompt was dismissed, so the dialog should be closed too.
+                    return false;
+                };
+                let mut is_open = true;
+
+                let mut position = prompt.position();
+                position.min.y += toolbar_offset.0 as i32;
+                position.max.y += toolbar_offset.0 as i32;
+                let area = egui::Area::new(egui::Id::new("date-time-window"))
+                    .fixed_pos(egui::pos2(position.min.x as f32, position.max.y as f32));
+
+                let input_type = prompt.input_type();
+                let date_part = |value: &str| value.split('T').next().unwrap_or(value).to_owned();
+                let min = prompt.min().map(date_part);
+                let max = prompt.max().map(date_part);
+                let mut response = None;
+
+                let modal = Modal::new("date_time_picker".into()).area(area);
+                modal.show(ctx, |ui| {
+                    if input_type != DateTimePickerType::Time {
+                        state.show_calendar(ui, min.as_deref(), max.as_deref());
+                    }
+                    if input_type != DateTimePickerType::Date {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut state.hour).range(0..=23));
+                            ui.label(":");
+                            ui.add(egui::DragValue::new(&mut state.minute).range(0..=59));
+                        });
+                    }
+
+                    ui.add_space(10.);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Dismiss").clicked() {
+                            is_open = false;
+                        }
+                        if ui.button("Clear").clicked() {
+                            is_open = false;
+                            response = Some(String::new());
+                        }
+                        if ui.button("Select").clicked() {
+                            is_open = false;
+                            response = Some(state.value(input_type));
+                        }
+                    });
+                });
+
+                if !is_open {
+                    prompt.select(response);
+                }
+
                 is_open
             },
         }
     }
 }
 
+/// The validation message of an invalid form control. Unlike a [`Dialog`], it does not block
+/// interaction with the page and goes away on its own after a few seconds.
+pub struct ValidationMessage {
+    message: String,
+    position: DeviceIntRect,
+    toolbar_offset: Length<f32, DeviceIndependentPixel>,
+    shown_at: Instant,
+}
+
+impl ValidationMessage {
+    const DURATION: Duration = Duration::from_secs(5);
+
+    pub fn new(
+        message: String,
+        position: DeviceIntRect,
+        toolbar_offset: Length<f32, DeviceIndependentPixel>,
+    ) -> Self {
+        Self {
+            message,
+            position,
+            toolbar_offset,
+            shown_at: Instant::now(),
+        }
+    }
+
+    /// Show the message below its form control. Returns `false` once the message has
+    /// expired and should be removed.
+    pub fn update(&self, ctx: &egui::Context) -> bool {
+        let Some(remaining) = Self::DURATION.checked_sub(self.shown_at.elapsed()) else {
+            return false;
+        };
+
+        let mut position = self.position;
+        position.min.y += self.toolbar_offset.0 as i32;
+        position.max.y += self.toolbar_offset.0 as i32;
+        egui::Area::new(egui::Id::new("validation-message"))
+            .fixed_pos(egui::pos2(position.min.x as f32, position.max.y as f32))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("⚠ {}", self.message));
+                });
+            });
+
+        ctx.request_repaint_after(remaining);
+        true
+    }
+}
+
+/// The date and time that is being edited by a [`Dialog::DateTimePicker`].
+struct DateTimePickerState {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+}
+
+impl DateTimePickerState {
+    /// Start from the current value of the `<input>` element, falling back to
+    /// the current date at midnight if it is empty.
+    fn new(value: &str) -> Self {
+        let (date, time) = match value.split_once('T') {
+            Some((date, time)) => (date, time),
+            None if value.contains(':') => ("", value),
+            None => (value, ""),
+        };
+        let (year, month, day) = parse_date(date).unwrap_or_else(today);
+        let (hour, minute) = parse_time(time).unwrap_or((0, 0));
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+        }
+    }
+
+    /// Serialize the state in the format expected for the given input type.
+    fn value(&self, input_type: DateTimePickerType) -> String {
+        let date = format_date(self.year, self.month, self.day);
+        let time = format!("{:02}:{:02}", self.hour, self.minute);
+        match input_type {
+            DateTimePickerType::Date => date,
+            DateTimePickerType::Time => time,
+            DateTimePickerType::DatetimeLocal => format!("{date}T{time}"),
+        }
+    }
+
+    fn show_calendar(&mut self, ui: &mut egui::Ui, min: Option<&str>, max: Option<&str>) {
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                self.shift_month(-1);
+            }
+            ui.label(format!(
+                "{} {}",
+                MONTH_NAMES[self.month as usize - 1],
+                self.year
+            ));
+            if ui.button(">").clicked() {
+                self.shift_month(1);
+            }
+        });
+
+        egui::Grid::new("date_time_picker_calendar").show(ui, |ui| {
+            for weekday in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"] {
+                ui.label(weekday);
+            }
+            ui.end_row();
+
+            let first_weekday = weekday(self.year, self.month, 1);
+            for _ in 0..first_weekday {
+                ui.label("");
+            }
+            for day in 1..=days_in_month(self.year, self.month) {
+                // Dates with four digit years compare chronologically as strings.
+                let date = format_date(self.year, self.month, day);
+                let in_range = min.is_none_or(|min| date.as_str() >= min) &&
+                    max.is_none_or(|max| date.as_str() <= max);
+                let label = egui::SelectableLabel::new(day == self.day, day.to_string());
+                if ui.add_enabled(in_range, label).clicked() {
+                    self.day = day;
+                }
+                if (first_weekday + day) % 7 == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
+    fn shift_month(&mut self, delta: i32) {
+        let months = self.year * 12 + self.month as i32 - 1 + delta;
+        self.year = months.div_euclid(12);
+        self.month = months.rem_euclid(12) as u32 + 1;
+        self.day = self.day.min(days_in_month(self.year, self.month));
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn format_date(year: i32, month: u32, day: u32) -> String {
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn parse_date(date: &str) -> Option<(i32, u32, u32)> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|month| (1..=12).contains(month))?;
+    let day = parts.next()?.parse().ok()?;
+    (1..=days_in_month(year, month))
+        .contains(&day)
+        .then_some((year, month, day))
+}
+
+fn parse_time(time: &str) -> Option<(u32, u32)> {
+    let mut parts = time.split(':');
+    let hour = parts.next()?.parse().ok().filter(|hour| *hour < 24)?;
+    let minute = parts.next()?.parse().ok().filter(|minute| *minute < 60)?;
+    Some((hour, minute))
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the day of the week, where Monday is 0.
+fn weekday(year: i32, month: u32, day: u32) -> u32 {
+    // Sakamoto's method, which returns 0 for Sunday.
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let year = if month < 3 { year - 1 } else { year };
+    let sunday_based = (year + year.div_euclid(4) - year.div_euclid(100) +
+        year.div_euclid(400) +
+        OFFSETS[month as usize - 1] +
+        day as i32)
+        .rem_euclid(7);
+    (sunday_based as u32 + 6) % 7
+}
+
+/// Returns today's date in UTC.
+fn today() -> (i32, u32, u32) {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| (duration.as_secs() / 86400) as i32);
+    // Convert days since the epoch to a civil date, as described in
+    // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 fn make_dialog_label(message: &str, ui: &mut egui::Ui, input_text: Option<&mut String>) {
     let mut frame = egui::Frame::default().inner_margin(10.0).begin(ui);
     frame.content_ui.set_min_width(150.0);
@@ -593,3 +1009,22 @@ fn make_dialog_label(message: &str, ui: &mut egui::Ui, input_text: Option<&mut S
     }
     frame.end(ui);
 }
+
+fn file_dialog_with_filter(dialog: EguiFileDialog, patterns: Vec<FilterPattern>) -> EguiFileDialog {
+    if patterns.is_empty() {
+        return dialog;
+    }
+    dialog
+        .add_file_filter(
+            "All Supported Types",
+            Arc::new(move |path: &Path| {
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|ext| {
+                        let ext = ext.to_lowercase();
+                        patterns.iter().any(|pattern| ext == pattern.0)
+                    })
+            }),
+        )
+        .default_file_filter("All Supported Types")
+}
@@ -106,6 +106,7 @@ impl EventsLoop {
                 loop {
                     self.sleep(flag, condvar);
                     app.handle_webdriver_messages();
+                    app.handle_repl_commands();
                     if !app.handle_events_with_headless() {
                         break;
                     }
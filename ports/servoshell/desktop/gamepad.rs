@@ -5,12 +5,12 @@
 use std::collections::HashMap;
 
 use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
-use gilrs::{EventType, Gilrs};
+use gilrs::{EventType, GamepadId, Gilrs};
 use log::{debug, warn};
 use servo::ipc_channel::ipc::IpcSender;
 use servo::{
     GamepadEvent, GamepadHapticEffectType, GamepadIndex, GamepadInputBounds,
-    GamepadSupportedHapticEffects, GamepadUpdateType, WebView,
+    GamepadSupportedHapticEffects, GamepadUpdateType, WebView, WebViewId,
 };
 
 pub struct HapticEffect {
@@ -21,6 +21,8 @@ pub struct HapticEffect {
 pub(crate) struct GamepadSupport {
     handle: Gilrs,
     haptic_effects: HashMap<usize, HapticEffect>,
+    /// The webview that gamepad events were last sent to.
+    active_webview_id: Option<WebViewId>,
 }
 
 impl GamepadSupport {
@@ -35,11 +37,22 @@ impl GamepadSupport {
         Some(Self {
             handle,
             haptic_effects: Default::default(),
+            active_webview_id: None,
         })
     }
 
     /// Handle updates to connected gamepads from GilRs
     pub(crate) fn handle_gamepad_events(&mut self, active_webview: WebView) {
+        // Gamepads that were connected while another webview was active were never
+        // reported to this one, so report them now.
+        if self.active_webview_id != Some(active_webview.id()) {
+            self.active_webview_id = Some(active_webview.id());
+            for (id, gamepad) in self.handle.gamepads() {
+                let event = Self::connected_event(id, gamepad.name());
+                active_webview.notify_input_event(servo::InputEvent::Gamepad(event));
+            }
+        }
+
         while let Some(event) = self.handle.next_event() {
             let gamepad = self.handle.gamepad(event.id);
             let name = gamepad.name();
@@ -93,22 +106,7 @@ impl GamepadSupport {
                     }
                 },
                 EventType::Connected => {
-                    let name = String::from(name);
-                    let bounds = GamepadInputBounds {
-                        axis_bounds: (-1.0, 1.0),
-                        button_bounds: (0.0, 1.0),
-                    };
-                    // GilRs does not yet support trigger rumble
-                    let supported_haptic_effects = GamepadSupportedHapticEffects {
-                        supports_dual_rumble: true,
-                        supports_trigger_rumble: false,
-                    };
-                    gamepad_event = Some(GamepadEvent::Connected(
-                        index,
-                        name,
-                        bounds,
-                        supported_haptic_effects,
-                    ));
+                    gamepad_event = Some(Self::connected_event(event.id, name));
                 },
                 EventType::Disconnected => {
                     gamepad_event = Some(GamepadEvent::Disconnected(index));
@@ -133,6 +131,24 @@ impl GamepadSupport {
         }
     }
 
+    fn connected_event(id: GamepadId, name: &str) -> GamepadEvent {
+        let bounds = GamepadInputBounds {
+            axis_bounds: (-1.0, 1.0),
+            button_bounds: (0.0, 1.0),
+        };
+        // GilRs does not yet support trigger rumble
+        let supported_haptic_effects = GamepadSupportedHapticEffects {
+            supports_dual_rumble: true,
+            supports_trigger_rumble: false,
+        };
+        GamepadEvent::Connected(
+            GamepadIndex(id.into()),
+            name.into(),
+            bounds,
+            supported_haptic_effects,
+        )
+    }
+
     // Map button index and value to represent Standard Gamepad button
     // <https://www.w3.org/TR/gamepad/#dfn-represents-a-standard-gamepad-button>
     fn map_gamepad_button(button: gilrs::Button) -> usize {
@@ -7,6 +7,7 @@
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::time::Duration;
 
@@ -19,11 +20,11 @@ use servo::servo_geometry::{DeviceIndependentIntRect, DeviceIndependentPixel};
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePixel};
 use servo::{
-    Cursor, ImeEvent, InputEvent, Key, KeyState, KeyboardEvent, MouseButton as ServoMouseButton,
-    MouseButtonAction, MouseButtonEvent, MouseLeaveEvent, MouseMoveEvent,
-    OffscreenRenderingContext, RenderingContext, ScreenGeometry, Theme, TouchEvent, TouchEventType,
-    TouchId, WebRenderDebugOption, WebView, WheelDelta, WheelEvent, WheelMode,
-    WindowRenderingContext,
+    Cursor, DragData, ExternalDragEventType, ImeEvent, InputEvent, Key, KeyState, KeyboardEvent,
+    MouseButton as ServoMouseButton, MouseButtonAction, MouseButtonEvent, MouseLeaveEvent,
+    MouseMoveEvent, OffscreenRenderingContext, RenderingContext, ScreenGeometry, Theme, TouchEvent,
+    TouchEventType, TouchId, WebRenderDebugOption, WebView, WheelDelta, WheelEvent, WheelMode,
+    WindowRenderingContext, gl,
 };
 use surfman::{Context, Device};
 use url::Url;
@@ -45,6 +46,7 @@ use super::app_state::RunningAppState;
 use super::geometry::{winit_position_to_euclid_point, winit_size_to_euclid_size};
 use super::keyutils::{CMD_OR_ALT, keyboard_event_from_winit};
 use super::window_trait::{LINE_HEIGHT, WindowPortsMethods};
+use crate::crash_reports;
 use crate::desktop::accelerated_gl_media::setup_gl_accelerated_media;
 use crate::desktop::keyutils::CMD_OR_CONTROL;
 use crate::prefs::ServoShellPreferences;
@@ -63,6 +65,13 @@ pub struct Window {
     device_pixel_ratio_override: Option<f32>,
     xr_window_poses: RefCell<Vec<Rc<XRWindowPose>>>,
     modifiers_state: Cell<ModifiersState>,
+    /// The files that are being dragged over the window, as announced by winit.
+    hovered_files: RefCell<Vec<PathBuf>>,
+    /// The files of the last drop whose `DroppedFile` event has not been received yet.
+    pending_dropped_files: RefCell<Vec<PathBuf>>,
+    /// Whether the IME is composing text, i.e. whether a composition session was started
+    /// with a preedit string and has not been committed or canceled yet.
+    ime_composing: Cell<bool>,
 
     /// The RenderingContext that renders directly onto the Window. This is used as
     /// the target of egui rendering and also where Servo rendering results are finally
@@ -143,6 +152,11 @@ impl Window {
 
         // Make sure the gl context is made current.
         window_rendering_context.make_current().unwrap();
+        crash_reports::set_graphics_adapter(
+            window_rendering_context
+                .gleam_gl_api()
+                .get_string(gl::RENDERER),
+        );
 
         let rendering_context = Rc::new(window_rendering_context.offscreen_context(inner_size));
 
@@ -159,6 +173,9 @@ impl Window {
             device_pixel_ratio_override: servoshell_preferences.device_pixel_ratio_override,
             xr_window_poses: RefCell::new(vec![]),
             modifiers_state: Cell::new(ModifiersState::empty()),
+            hovered_files: Default::default(),
+            pending_dropped_files: Default::default(),
+            ime_composing: Cell::new(false),
             toolbar_height: Cell::new(Default::default()),
             window_rendering_context,
             rendering_context,
@@ -211,6 +228,7 @@ impl Window {
         // First, handle servoshell key bindings that are not overridable by, or visible to, the page.
         let mut keyboard_event =
             keyboard_event_from_winit(&winit_event, self.modifiers_state.get());
+        keyboard_event.event.is_composing = self.ime_composing.get();
         if self.handle_intercepted_key_bindings(state.clone(), &keyboard_event) {
             return;
         }
@@ -256,6 +274,60 @@ impl Window {
         // See the handler for EmbedderMsg::Keyboard in webview.rs for those.
     }
 
+    fn notify_composition_event(
+        &self,
+        webview: &WebView,
+        state: servo::CompositionState,
+        data: String,
+    ) {
+        webview.notify_input_event(InputEvent::Ime(ImeEvent::Composition(
+            servo::CompositionEvent { state, data },
+        )));
+    }
+
+    /// Turn winit's IME events into composition events. winit has no event for the start
+    /// of a composition session, which begins with the first non-empty preedit string. An
+    /// empty preedit string clears the composed text, and is followed by a commit unless
+    /// the composition was canceled.
+    fn handle_ime_event(&self, webview: &WebView, ime: Ime) {
+        match ime {
+            Ime::Enabled => {},
+            Ime::Preedit(text, _) => {
+                if text.is_empty() && !self.ime_composing.get() {
+                    return;
+                }
+                if !self.ime_composing.replace(true) {
+                    self.notify_composition_event(
+                        webview,
+                        servo::CompositionState::Start,
+                        String::new(),
+                    );
+                }
+                self.notify_composition_event(webview, servo::CompositionState::Update, text);
+            },
+            Ime::Commit(text) => {
+                if !self.ime_composing.replace(false) {
+                    self.notify_composition_event(
+                        webview,
+                        servo::CompositionState::Start,
+                        String::new(),
+                    );
+                }
+                self.notify_composition_event(webview, servo::CompositionState::End, text);
+            },
+            Ime::Disabled => {
+                if self.ime_composing.replace(false) {
+                    self.notify_composition_event(
+                        webview,
+                        servo::CompositionState::End,
+                        String::new(),
+                    );
+                }
+                webview.notify_input_event(InputEvent::Ime(ImeEvent::Dismissed));
+            },
+        }
+    }
+
     /// Helper function to handle a click
     fn handle_mouse(&self, webview: &WebView, button: MouseButton, action: ElementState) {
         let mouse_button = match &button {
@@ -296,6 +368,9 @@ impl Window {
             .shortcut(CMD_OR_CONTROL, 'W', || {
                 state.close_webview(focused_webview.id());
             })
+            .shortcut(CMD_OR_CONTROL | Modifiers::SHIFT, 'P', || {
+                state.toggle_performance_hud();
+            })
             .shortcut(CMD_OR_CONTROL, 'P', || {
                 let rate = env::var("SAMPLING_RATE")
                     .ok()
@@ -393,6 +468,10 @@ impl Window {
                     state.focus_webview_by_index(new_index)
                 }
             })
+            .shortcut(CMD_OR_CONTROL | Modifiers::SHIFT, 'N', || {
+                state
+                    .create_and_focus_private_toplevel_webview(Url::parse("servo:newtab").unwrap());
+            })
             .shortcut(CMD_OR_CONTROL, 'T', || {
                 state.create_and_focus_toplevel_webview(Url::parse("servo:newtab").unwrap());
             })
@@ -659,6 +738,48 @@ impl WindowPortsMethods for Window {
                     Point2D::new(touch.location.x as f32, touch.location.y as f32),
                 )));
             },
+            WindowEvent::HoveredFile(path) => {
+                self.hovered_files.borrow_mut().push(path);
+                let data = DragData {
+                    files: self.hovered_files.borrow().clone(),
+                    ..Default::default()
+                };
+                let point = self.webview_relative_mouse_point.get();
+                webview.notify_external_drag_event(ExternalDragEventType::Over, point, data);
+            },
+            WindowEvent::HoveredFileCancelled => {
+                self.hovered_files.borrow_mut().clear();
+                let point = self.webview_relative_mouse_point.get();
+                webview.notify_external_drag_event(
+                    ExternalDragEventType::Leave,
+                    point,
+                    DragData::default(),
+                );
+            },
+            WindowEvent::DroppedFile(path) => {
+                // winit reports every dropped file separately, but announces all of them
+                // with `HoveredFile` events beforehand, so that they can be dropped at once.
+                let mut pending_dropped_files = self.pending_dropped_files.borrow_mut();
+                if let Some(index) = pending_dropped_files.iter().position(|file| *file == path) {
+                    pending_dropped_files.remove(index);
+                    return;
+                }
+
+                let mut paths = std::mem::take(&mut *self.hovered_files.borrow_mut());
+                if !paths.contains(&path) {
+                    paths = vec![path.clone()];
+                }
+                pending_dropped_files.extend(paths.iter().filter(|file| **file != path).cloned());
+
+                // Cursor movement is not reported during a drag on every platform, so this
+                // point may be where the cursor entered the window instead.
+                let point = self.webview_relative_mouse_point.get();
+                let data = DragData {
+                    files: paths,
+                    ..Default::default()
+                };
+                webview.notify_external_drag_event(ExternalDragEventType::Drop, point, data);
+            },
             WindowEvent::PinchGesture { delta, .. } => {
                 webview.set_pinch_zoom(delta as f32 + 1.0);
             },
@@ -670,6 +791,14 @@ impl WindowPortsMethods for Window {
                     self.window_rendering_context.resize(new_size);
                     self.inner_size.set(new_size);
                 }
+
+                // The window can leave fullscreen without the page asking, for instance when
+                // the user switches it back with the window manager, which exits fullscreen
+                // in the page too.
+                if self.fullscreen.get() && self.winit_window.fullscreen().is_none() {
+                    self.fullscreen.set(false);
+                    webview.exit_fullscreen();
+                }
             },
             WindowEvent::ThemeChanged(theme) => {
                 webview.notify_theme_change(match theme {
@@ -677,35 +806,7 @@ impl WindowPortsMethods for Window {
                     winit::window::Theme::Dark => Theme::Dark,
                 });
             },
-            WindowEvent::Ime(ime) => match ime {
-                Ime::Enabled => {
-                    webview.notify_input_event(InputEvent::Ime(ImeEvent::Composition(
-                        servo::CompositionEvent {
-                            state: servo::CompositionState::Start,
-                            data: String::new(),
-                        },
-                    )));
-                },
-                Ime::Preedit(text, _) => {
-                    webview.notify_input_event(InputEvent::Ime(ImeEvent::Composition(
-                        servo::CompositionEvent {
-                            state: servo::CompositionState::Update,
-                            data: text,
-                        },
-                    )));
-                },
-                Ime::Commit(text) => {
-                    webview.notify_input_event(InputEvent::Ime(ImeEvent::Composition(
-                        servo::CompositionEvent {
-                            state: servo::CompositionState::End,
-                            data: text,
-                        },
-                    )));
-                },
-                Ime::Disabled => {
-                    webview.notify_input_event(InputEvent::Ime(ImeEvent::Dismissed));
-                },
-            },
+            WindowEvent::Ime(ime) => self.handle_ime_event(&webview, ime),
             _ => {},
         }
     }
@@ -763,6 +864,14 @@ impl WindowPortsMethods for Window {
         position: servo::webrender_api::units::DeviceIntRect,
     ) {
         self.winit_window.set_ime_allowed(true);
+        self.update_ime_cursor_area(position);
+    }
+
+    fn hide_ime(&self) {
+        self.winit_window.set_ime_allowed(false);
+    }
+
+    fn update_ime_cursor_area(&self, position: servo::webrender_api::units::DeviceIntRect) {
         self.winit_window.set_ime_cursor_area(
             LogicalPosition::new(
                 position.min.x,
@@ -775,10 +884,6 @@ impl WindowPortsMethods for Window {
         );
     }
 
-    fn hide_ime(&self) {
-        self.winit_window.set_ime_allowed(false);
-    }
-
     fn theme(&self) -> servo::Theme {
         match self.winit_window.theme() {
             Some(winit::window::Theme::Dark) => servo::Theme::Dark,
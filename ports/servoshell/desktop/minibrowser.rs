@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Instant;
@@ -22,7 +23,11 @@ use servo::base::id::WebViewId;
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::servo_url::ServoUrl;
 use servo::webrender_api::units::DevicePixel;
-use servo::{LoadStatus, OffscreenRenderingContext, RenderingContext, WebView};
+use servo::{
+    FindInPageRequest, FindInPageResult, LoadStatus, OffscreenRenderingContext, RenderingContext,
+    WebView,
+};
+use url::Url;
 use winit::event::{ElementState, MouseButton, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
 use winit::window::Window;
@@ -48,7 +53,25 @@ pub struct Minibrowser {
 
     load_status: LoadStatus,
 
+    /// The estimated progress of the load of the focused page, from 0 to 1.
+    load_progress: f32,
+
     status_text: Option<String>,
+
+    /// The number of requests of the focused page blocked by the content blocker.
+    blocked_content_count: usize,
+
+    /// Whether the find bar is shown.
+    find_bar_open: Cell<bool>,
+
+    /// The text in the find bar.
+    find_text: RefCell<String>,
+
+    /// Whether finding text in the page matches case.
+    find_case_sensitive: Cell<bool>,
+
+    /// The textures of the favicons of the tabs, with the URLs of the favicons.
+    favicons: RefCell<HashMap<WebViewId, (Url, egui::TextureHandle)>>,
 }
 
 pub enum MinibrowserEvent {
@@ -59,6 +82,14 @@ pub enum MinibrowserEvent {
     Reload,
     NewWebView,
     CloseWebView(WebViewId),
+    /// A URL that was dragged out of a webview was dropped onto the toolbar.
+    OpenDroppedUrl(String),
+    /// The text in the find bar changed, or the next or previous match was requested.
+    FindInPage(FindInPageRequest),
+    /// The find bar was closed or emptied.
+    StopFindingInPage,
+    /// The bookmark button was clicked, to bookmark the focused page or remove its bookmark.
+    ToggleBookmark,
 }
 
 fn truncate_with_ellipsis(input: &str, max_length: usize) -> String {
@@ -110,7 +141,13 @@ impl Minibrowser {
             location: RefCell::new(initial_url.to_string()),
             location_dirty: false.into(),
             load_status: LoadStatus::Complete,
+            load_progress: 1.,
+            blocked_content_count: 0,
             status_text: None,
+            find_bar_open: false.into(),
+            find_text: Default::default(),
+            find_case_sensitive: false.into(),
+            favicons: Default::default(),
         }
     }
 
@@ -162,6 +199,25 @@ impl Minibrowser {
                 self.event_queue.borrow_mut().push(MinibrowserEvent::Back);
                 true
             },
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => {
+                // Links and images that are dragged out of a webview and dropped onto the
+                // toolbar are opened in a new webview.
+                let over_toolbar = self
+                    .last_mouse_position
+                    .is_some_and(|p| self.is_in_egui_toolbar_rect(p));
+                if let Some(url) = app_state.take_outgoing_drag().and_then(|data| data.url) {
+                    if over_toolbar {
+                        self.event_queue
+                            .borrow_mut()
+                            .push(MinibrowserEvent::OpenDroppedUrl(url));
+                    }
+                }
+                over_toolbar
+            },
             WindowEvent::MouseWheel { .. } | WindowEvent::MouseInput { .. } => self
                 .last_mouse_position
                 .is_some_and(|p| self.is_in_egui_toolbar_rect(p)),
@@ -182,15 +238,59 @@ impl Minibrowser {
             .min_size(Vec2 { x: 20.0, y: 20.0 })
     }
 
+    /// Create the textures of the favicons that were loaded since the last update, and drop
+    /// the ones of tabs that were closed or whose favicon was cleared.
+    fn update_favicons(
+        ctx: &egui::Context,
+        favicons: &RefCell<HashMap<WebViewId, (Url, egui::TextureHandle)>>,
+        webviews: &[(WebViewId, WebView)],
+    ) {
+        let mut favicons = favicons.borrow_mut();
+        favicons.retain(|webview_id, (url, _)| {
+            webviews.iter().any(|(id, webview)| {
+                id == webview_id && webview.favicon_url().as_ref() == Some(url)
+            })
+        });
+        for (webview_id, webview) in webviews {
+            if favicons.contains_key(webview_id) {
+                continue;
+            }
+            let (Some(url), Some(favicon)) = (webview.favicon_url(), webview.favicon()) else {
+                continue;
+            };
+            let size = [favicon.width as usize, favicon.height as usize];
+            if favicon.rgba_pixels.len() != size[0] * size[1] * 4 {
+                continue;
+            }
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, &favicon.rgba_pixels);
+            let texture = ctx.load_texture(url.as_str(), image, egui::TextureOptions::LINEAR);
+            favicons.insert(*webview_id, (url, texture));
+        }
+    }
+
     /// Draws a browser tab, checking for clicks and queues appropriate `MinibrowserEvent`s.
     /// Using a custom widget here would've been nice, but it doesn't seem as though egui
     /// supports that, so we arrange multiple Widgets in a way that they look connected.
-    fn browser_tab(ui: &mut egui::Ui, webview: WebView, event_queue: &mut Vec<MinibrowserEvent>) {
+    fn browser_tab(
+        ui: &mut egui::Ui,
+        webview: WebView,
+        favicon: Option<&egui::TextureHandle>,
+        event_queue: &mut Vec<MinibrowserEvent>,
+    ) {
         let label = match (webview.page_title(), webview.url()) {
             (Some(title), _) if !title.is_empty() => title,
             (_, Some(url)) => url.to_string(),
             _ => "New Tab".into(),
         };
+        let label = if webview.is_private() {
+            format!("[Private] {label}")
+        } else {
+            label
+        };
+
+        if let Some(favicon) = favicon {
+            ui.add(egui::Image::new((favicon.id(), egui::vec2(16.0, 16.0))));
+        }
 
         let old_item_spacing = ui.spacing().item_spacing;
         let old_visuals = ui.visuals().clone();
@@ -263,6 +363,96 @@ impl Minibrowser {
         }
     }
 
+    /// Draws the progress of the load of the focused page as a line along the bottom of the
+    /// toolbar, without taking up space in its layout so that the page is not resized.
+    fn load_progress_bar(ui: &egui::Ui, progress: f32) {
+        let rect = ui.max_rect();
+        let bar = egui::Rect::from_min_size(
+            pos2(rect.left(), rect.bottom() + 2.0),
+            egui::vec2(rect.width() * progress, 2.0),
+        );
+        ui.painter()
+            .rect_filled(bar, 0.0, ui.visuals().selection.bg_fill);
+    }
+
+    /// Draws the find bar, which finds the text typed into it in the page of the focused
+    /// `WebView` and shows how many matches there are. Enter moves to the next match, and
+    /// Shift+Enter to the previous one.
+    fn find_bar(
+        ui: &mut egui::Ui,
+        find_bar_open: &Cell<bool>,
+        find_text: &RefCell<String>,
+        find_case_sensitive: &Cell<bool>,
+        find_in_page_result: Option<FindInPageResult>,
+        event_queue: &mut Vec<MinibrowserEvent>,
+    ) {
+        let request = |backwards| {
+            if find_text.borrow().is_empty() {
+                MinibrowserEvent::StopFindingInPage
+            } else {
+                MinibrowserEvent::FindInPage(FindInPageRequest {
+                    text: find_text.borrow().clone(),
+                    case_sensitive: find_case_sensitive.get(),
+                    backwards,
+                })
+            }
+        };
+
+        ui.allocate_ui_with_layout(
+            ui.available_size(),
+            egui::Layout::left_to_right(egui::Align::Center),
+            |ui| {
+                let find_field = ui.add(
+                    egui::TextEdit::singleline(&mut *find_text.borrow_mut())
+                        .id(egui::Id::new("find_input"))
+                        .hint_text("Find in page")
+                        .desired_width(200.0),
+                );
+                if find_field.changed() {
+                    event_queue.push(request(false));
+                }
+                if find_field.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                    event_queue.push(request(ui.input(|input| input.modifiers.shift)));
+                    find_field.request_focus();
+                }
+
+                if ui.add(Minibrowser::toolbar_button("⏶")).clicked() {
+                    event_queue.push(request(true));
+                }
+                if ui.add(Minibrowser::toolbar_button("⏷")).clicked() {
+                    event_queue.push(request(false));
+                }
+                let mut case_sensitive = find_case_sensitive.get();
+                if ui.checkbox(&mut case_sensitive, "Match case").changed() {
+                    find_case_sensitive.set(case_sensitive);
+                    event_queue.push(request(false));
+                }
+
+                match find_in_page_result {
+                    Some(FindInPageResult {
+                        number_of_matches,
+                        active_match_index: Some(index),
+                    }) => {
+                        ui.label(format!("{} of {number_of_matches}", index + 1));
+                    },
+                    Some(_) if !find_text.borrow().is_empty() => {
+                        ui.label("No matches");
+                    },
+                    _ => {},
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let escape_pressed =
+                        find_field.has_focus() && ui.input(|input| input.key_pressed(Key::Escape));
+                    if ui.add(Minibrowser::toolbar_button("✕")).clicked() || escape_pressed {
+                        find_bar_open.set(false);
+                        event_queue.push(MinibrowserEvent::StopFindingInPage);
+                    }
+                });
+            },
+        );
+    }
+
     /// Update the minibrowser, but don’t paint.
     /// If `servo_framebuffer_id` is given, set up a paint callback to blit its contents to our
     /// CentralPanel when [`Minibrowser::paint`] is called.
@@ -281,6 +471,7 @@ impl Minibrowser {
             last_update,
             location,
             location_dirty,
+            favicons,
             ..
         } = self;
 
@@ -304,7 +495,9 @@ impl Minibrowser {
                             }
 
                             match self.load_status {
-                                LoadStatus::Started | LoadStatus::HeadParsed => {
+                                LoadStatus::Started |
+                                LoadStatus::HeadParsed |
+                                LoadStatus::DomContentLoaded => {
                                     if ui.add(Minibrowser::toolbar_button("X")).clicked() {
                                         warn!("Do not support stop yet.");
                                     }
@@ -315,12 +508,38 @@ impl Minibrowser {
                                     }
                                 },
                             }
+                            if self.blocked_content_count > 0 {
+                                ui.label(format!("🛡 {}", self.blocked_content_count))
+                                    .on_hover_text("Requests blocked by the filter lists");
+                            }
                             ui.add_space(2.0);
 
                             ui.allocate_ui_with_layout(
                                 ui.available_size(),
                                 egui::Layout::right_to_left(egui::Align::Center),
                                 |ui| {
+                                    let bookmarked = state
+                                        .focused_webview()
+                                        .and_then(|webview| webview.url())
+                                        .is_some_and(|url| {
+                                            state
+                                                .places()
+                                                .lock()
+                                                .unwrap()
+                                                .is_bookmarked(url.as_str())
+                                        });
+                                    let bookmark_button =
+                                        Minibrowser::toolbar_button(if bookmarked {
+                                            "★"
+                                        } else {
+                                            "☆"
+                                        });
+                                    if ui.add(bookmark_button).clicked() {
+                                        event_queue
+                                            .borrow_mut()
+                                            .push(MinibrowserEvent::ToggleBookmark);
+                                    }
+
                                     let location_id = egui::Id::new("location_input");
                                     let location_field = ui.add_sized(
                                         ui.available_size(),
@@ -364,10 +583,23 @@ impl Minibrowser {
                                             .borrow_mut()
                                             .push(MinibrowserEvent::Go(location.borrow().clone()));
                                     }
+
+                                    Self::location_suggestions(
+                                        ui,
+                                        &location_field,
+                                        state,
+                                        location,
+                                        location_dirty,
+                                        &mut event_queue.borrow_mut(),
+                                    );
                                 },
                             );
                         },
                     );
+
+                    if self.load_status != LoadStatus::Complete {
+                        Self::load_progress_bar(ui, self.load_progress);
+                    }
                 });
             };
 
@@ -377,8 +609,12 @@ impl Minibrowser {
                     ui.available_size(),
                     egui::Layout::left_to_right(egui::Align::Center),
                     |ui| {
-                        for (_, webview) in state.webviews().into_iter() {
-                            Self::browser_tab(ui, webview, &mut event_queue.borrow_mut());
+                        let webviews = state.webviews();
+                        Self::update_favicons(ctx, favicons, &webviews);
+                        for (webview_id, webview) in webviews.into_iter() {
+                            let favicons = favicons.borrow();
+                            let favicon = favicons.get(&webview_id).map(|(_, texture)| texture);
+                            Self::browser_tab(ui, webview, favicon, &mut event_queue.borrow_mut());
                         }
                         if ui.add(Minibrowser::toolbar_button("+")).clicked() {
                             event_queue.borrow_mut().push(MinibrowserEvent::NewWebView);
@@ -387,6 +623,26 @@ impl Minibrowser {
                 );
             });
 
+            let find_in_page_result = state
+                .focused_webview()
+                .and_then(|webview| state.find_in_page_result(webview.id()));
+            if ctx.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::F)) {
+                self.find_bar_open.set(true);
+                ctx.memory_mut(|memory| memory.request_focus(egui::Id::new("find_input")));
+            }
+            if self.find_bar_open.get() {
+                TopBottomPanel::top("find_bar").show(ctx, |ui| {
+                    Self::find_bar(
+                        ui,
+                        &self.find_bar_open,
+                        &self.find_text,
+                        &self.find_case_sensitive,
+                        find_in_page_result,
+                        &mut event_queue.borrow_mut(),
+                    );
+                });
+            }
+
             // The toolbar height is where the Context’s available rect starts.
             // For reasons that are unclear, the TopBottomPanel’s ui cursor exceeds this by one egui
             // point, but the Context is correct and the TopBottomPanel is wrong.
@@ -399,6 +655,9 @@ impl Minibrowser {
                 state.for_each_active_dialog(|dialog| dialog.update(ctx));
             });
 
+            state.show_performance_hud(ctx);
+            state.show_validation_message(ctx);
+
             let Some(webview) = state.focused_webview() else {
                 return;
             };
@@ -449,6 +708,83 @@ impl Minibrowser {
         });
     }
 
+    /// Show the pages of the history and bookmarks that match the text typed in the location
+    /// field below it, while it is being edited, followed by the suggestions of the search
+    /// engine. Clicking one of them goes to it or searches for it.
+    fn location_suggestions(
+        ui: &egui::Ui,
+        location_field: &egui::Response,
+        state: &RunningAppState,
+        location: &RefCell<String>,
+        location_dirty: &Cell<bool>,
+        event_queue: &mut Vec<MinibrowserEvent>,
+    ) {
+        let suggestions_id = egui::Id::new("location_suggestions");
+        let pointer_over_suggestions = ui
+            .ctx()
+            .memory(|memory| memory.area_rect(suggestions_id))
+            .zip(ui.ctx().pointer_hover_pos())
+            .is_some_and(|(rect, position)| rect.contains(position));
+        if !location_dirty.get() || !(location_field.has_focus() || pointer_over_suggestions) {
+            return;
+        }
+        let text = location.borrow().clone();
+        let suggestions = state.places().lock().unwrap().suggestions(&text, 8);
+        // Do not send what is typed in private WebViews to the search engine.
+        let private = state
+            .focused_webview()
+            .is_some_and(|webview| webview.is_private());
+        let search_suggestions = if private {
+            Vec::new()
+        } else {
+            state
+                .search_engines()
+                .request_suggestions(state.servo(), &text);
+            state.search_engines().suggestions(&text)
+        };
+        if suggestions.is_empty() && search_suggestions.is_empty() {
+            return;
+        }
+
+        egui::Area::new(suggestions_id)
+            .order(egui::Order::Foreground)
+            .fixed_pos(location_field.rect.left_bottom())
+            .show(ui.ctx(), |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(location_field.rect.width());
+                    for suggestion in suggestions {
+                        let text = match &suggestion.title {
+                            Some(title) => format!(
+                                "{} — {}",
+                                truncate_with_ellipsis(title, 60),
+                                suggestion.url
+                            ),
+                            None => suggestion.url.clone(),
+                        };
+                        if ui.add(SelectableLabel::new(false, text)).clicked() {
+                            *location.borrow_mut() = suggestion.url.clone();
+                            event_queue.push(MinibrowserEvent::Go(suggestion.url));
+                        }
+                    }
+                    if !search_suggestions.is_empty() {
+                        ui.separator();
+                    }
+                    for search_suggestion in search_suggestions.into_iter().take(6) {
+                        if ui
+                            .add(SelectableLabel::new(
+                                false,
+                                format!("🔍 {search_suggestion}"),
+                            ))
+                            .clicked()
+                        {
+                            *location.borrow_mut() = search_suggestion.clone();
+                            event_queue.push(MinibrowserEvent::Go(search_suggestion));
+                        }
+                    }
+                });
+            });
+    }
+
     /// Paint the minibrowser, as of the last update.
     pub fn paint(&mut self, window: &Window) {
         self.rendering_context
@@ -491,6 +827,24 @@ impl Minibrowser {
         old_status != self.load_status
     }
 
+    pub fn update_load_progress(&mut self, state: &RunningAppState) -> bool {
+        let progress = state
+            .focused_webview()
+            .map(|webview| webview.load_progress().estimated_progress)
+            .unwrap_or(1.);
+        let old_progress = std::mem::replace(&mut self.load_progress, progress);
+        old_progress != self.load_progress
+    }
+
+    pub fn update_blocked_content_count(&mut self, state: &RunningAppState) -> bool {
+        let count = state
+            .focused_webview()
+            .map(|webview| webview.blocked_content_count())
+            .unwrap_or_default();
+        let old_count = std::mem::replace(&mut self.blocked_content_count, count);
+        old_count != self.blocked_content_count
+    }
+
     pub fn update_status_text(&mut self, state: &RunningAppState) -> bool {
         let state_status = state
             .focused_webview()
@@ -508,6 +862,8 @@ impl Minibrowser {
         //       does not short-circuit.
         self.update_location_in_toolbar(state) |
             self.update_load_status(state) |
+            self.update_load_progress(state) |
+            self.update_blocked_content_count(state) |
             self.update_status_text(state)
     }
 
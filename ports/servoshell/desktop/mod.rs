@@ -17,7 +17,13 @@ mod headed_window;
 mod headless_window;
 mod keyutils;
 mod minibrowser;
+mod performance_hud;
+mod picture_in_picture;
+mod places;
 mod protocols;
+mod repl;
+mod search;
+mod stylesheet_watcher;
 mod tracing;
 mod webxr;
 mod window_trait;
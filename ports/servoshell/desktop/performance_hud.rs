@@ -0,0 +1,263 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An overlay showing how fast Servo produces frames for the focused `WebView`, toggled
+//! with Ctrl+Shift+P (Cmd+Shift+P on macOS).
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Receiver;
+use egui::{Area, Color32, Frame, Order, Sense, Stroke, pos2, vec2};
+use log::warn;
+use servo::ipc_channel::ipc::{self, IpcReceiver};
+use servo::profile_traits::mem::MemoryReportResult;
+use servo::{FrameReport, PaintMetrics, Servo};
+
+/// How many frames are kept for the frame time graph.
+const MAX_RECENT_FRAMES: usize = 120;
+
+/// How often the memory usage of Servo's processes is measured.
+const MEMORY_REPORT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The width of the overlay, in egui points.
+const HUD_WIDTH: f32 = 260.0;
+
+/// The height of the frame time graph, in egui points.
+const GRAPH_HEIGHT: f32 = 60.0;
+
+/// The frame time that the top of the frame time graph stands for.
+const GRAPH_MAX_FRAME_TIME: Duration = Duration::from_millis(50);
+
+/// The resident memory of one of Servo's processes.
+struct ProcessMemory {
+    pid: u32,
+    is_main_process: bool,
+    resident: usize,
+}
+
+pub(crate) struct PerformanceHud {
+    /// The reports of the frames painted since the overlay was shown.
+    frame_reports: Receiver<FrameReport>,
+    /// The most recent frame reports, with the time at which they were received.
+    recent_frames: VecDeque<(Instant, FrameReport)>,
+    /// The channel on which the memory report that was last asked for will arrive.
+    pending_memory_report: Option<IpcReceiver<MemoryReportResult>>,
+    /// The last time a memory report was asked for.
+    last_memory_report_request: Option<Instant>,
+    /// The memory usage of each process, as of the last memory report.
+    process_memory: Vec<ProcessMemory>,
+}
+
+impl PerformanceHud {
+    pub(crate) fn new(servo: &Servo) -> Self {
+        Self {
+            frame_reports: servo.subscribe_to_frame_reports(),
+            recent_frames: VecDeque::with_capacity(MAX_RECENT_FRAMES),
+            pending_memory_report: None,
+            last_memory_report_request: None,
+            process_memory: Vec::new(),
+        }
+    }
+
+    /// Collect the frame and memory reports that arrived since the last update, asking
+    /// for a new memory report when needed. Returns true if there is anything new to show.
+    pub(crate) fn update(&mut self, servo: &Servo) -> bool {
+        let now = Instant::now();
+        let mut updated = false;
+        for report in self.frame_reports.try_iter() {
+            if self.recent_frames.len() == MAX_RECENT_FRAMES {
+                self.recent_frames.pop_front();
+            }
+            self.recent_frames.push_back((now, report));
+            updated = true;
+        }
+
+        if let Some(receiver) = self.pending_memory_report.as_ref() {
+            if let Ok(result) = receiver.try_recv() {
+                self.process_memory = result
+                    .results
+                    .iter()
+                    .map(|process| ProcessMemory {
+                        pid: process.pid,
+                        is_main_process: process.is_main_process,
+                        resident: process
+                            .reports
+                            .iter()
+                            .find(|report| report.path == ["resident"])
+                            .map(|report| report.size)
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                self.pending_memory_report = None;
+                updated = true;
+            }
+        }
+
+        let memory_report_due = self
+            .last_memory_report_request
+            .is_none_or(|last_request| now - last_request >= MEMORY_REPORT_INTERVAL);
+        if self.pending_memory_report.is_none() && memory_report_due {
+            match ipc::channel() {
+                Ok((sender, receiver)) => {
+                    servo.create_memory_report(sender);
+                    self.pending_memory_report = Some(receiver);
+                },
+                Err(error) => warn!("Could not create channel for memory report: {error}"),
+            }
+            self.last_memory_report_request = Some(now);
+        }
+
+        updated
+    }
+
+    /// The number of frames painted during the last second.
+    fn frames_per_second(&self) -> usize {
+        let now = Instant::now();
+        self.recent_frames
+            .iter()
+            .filter(|(received, _)| now - *received <= Duration::from_secs(1))
+            .count()
+    }
+
+    /// Show the overlay in the top right corner of the area of `ctx` that is still
+    /// available, which is where the focused `WebView` is painted.
+    pub(crate) fn show(&self, ctx: &egui::Context, paint_metrics: Option<PaintMetrics>) {
+        let available_rect = ctx.available_rect();
+        let position = pos2(
+            available_rect.max.x - HUD_WIDTH - 8.0,
+            available_rect.min.y + 8.0,
+        );
+
+        Area::new(egui::Id::new("performance_hud"))
+            .order(Order::Foreground)
+            .fixed_pos(position)
+            .interactable(false)
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(HUD_WIDTH);
+                    self.show_frame_stats(ui);
+                    self.show_frame_time_graph(ui);
+                    ui.separator();
+                    Self::show_paint_metrics(ui, paint_metrics.unwrap_or_default());
+                    ui.separator();
+                    self.show_memory(ui);
+                });
+            });
+    }
+
+    fn show_frame_stats(&self, ui: &mut egui::Ui) {
+        ui.monospace(format!("FPS: {}", self.frames_per_second()));
+
+        let Some((_, last_frame)) = self.recent_frames.back() else {
+            ui.monospace("No frames painted yet");
+            return;
+        };
+
+        let frame_count = self.recent_frames.len() as u32;
+        let average = |duration: fn(&FrameReport) -> Duration| {
+            self.recent_frames
+                .iter()
+                .map(|(_, report)| duration(report))
+                .sum::<Duration>() /
+                frame_count
+        };
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.;
+
+        ui.monospace(format!(
+            "Script {:5.1} ms  Style {:5.1} ms",
+            milliseconds(average(|report| report.stages.script)),
+            milliseconds(average(|report| report.stages.style)),
+        ));
+        ui.monospace(format!(
+            "Layout {:5.1} ms  DL    {:5.1} ms",
+            milliseconds(average(|report| report.stages.layout)),
+            milliseconds(average(|report| report.stages.display_list_build)),
+        ));
+        ui.monospace(format!(
+            "Render {:5.1} ms  Present {:4.1} ms",
+            milliseconds(average(|report| report.render)),
+            milliseconds(average(|report| report.present.unwrap_or_default())),
+        ));
+        ui.monospace(format!(
+            "Refresh {:4.1} ms  Dropped {}",
+            milliseconds(last_frame.refresh_interval),
+            last_frame.total_dropped_frames,
+        ));
+    }
+
+    /// Draw the time spent on each recent frame as a bar, with janky frames in red and a
+    /// line at the refresh interval of the display.
+    fn show_frame_time_graph(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(vec2(HUD_WIDTH, GRAPH_HEIGHT), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::from_black_alpha(96));
+
+        let height_for = |duration: Duration| {
+            let fraction = duration.as_secs_f32() / GRAPH_MAX_FRAME_TIME.as_secs_f32();
+            fraction.min(1.0) * rect.height()
+        };
+
+        let bar_width = rect.width() / MAX_RECENT_FRAMES as f32;
+        for (index, (_, report)) in self.recent_frames.iter().enumerate() {
+            let frame_time = report.stages.script +
+                report.stages.style +
+                report.stages.layout +
+                report.stages.display_list_build +
+                report.render +
+                report.present.unwrap_or_default();
+            let color = if report.is_janky() {
+                Color32::from_rgb(230, 70, 70)
+            } else {
+                Color32::from_rgb(90, 200, 120)
+            };
+            let left = rect.min.x + index as f32 * bar_width;
+            let bar = egui::Rect::from_min_max(
+                pos2(left, rect.max.y - height_for(frame_time)),
+                pos2(left + bar_width, rect.max.y),
+            );
+            painter.rect_filled(bar, 0.0, color);
+        }
+
+        if let Some((_, last_frame)) = self.recent_frames.back() {
+            let y = rect.max.y - height_for(last_frame.refresh_interval);
+            painter.hline(rect.x_range(), y, Stroke::new(1.0, Color32::YELLOW));
+        }
+    }
+
+    fn show_paint_metrics(ui: &mut egui::Ui, paint_metrics: PaintMetrics) {
+        let format_metric = |metric: Option<Duration>| match metric {
+            Some(duration) => format!("{:.0} ms", duration.as_secs_f64() * 1000.),
+            None => "-".to_owned(),
+        };
+        ui.monospace(format!("FP  {}", format_metric(paint_metrics.first_paint)));
+        ui.monospace(format!(
+            "FCP {}",
+            format_metric(paint_metrics.first_contentful_paint)
+        ));
+        ui.monospace(format!(
+            "LCP {}",
+            format_metric(paint_metrics.largest_contentful_paint)
+        ));
+    }
+
+    fn show_memory(&self, ui: &mut egui::Ui) {
+        if self.process_memory.is_empty() {
+            ui.monospace("Measuring memory usage…");
+            return;
+        }
+        for process in &self.process_memory {
+            let kind = if process.is_main_process {
+                "Main"
+            } else {
+                "Content"
+            };
+            ui.monospace(format!(
+                "{kind:7} {:>7} {:7.1} MiB",
+                process.pid,
+                process.resident as f64 / (1024. * 1024.)
+            ));
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A floating window that shows a video of a `WebView` in picture-in-picture.
+
+use std::rc::Rc;
+
+use log::{debug, warn};
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use servo::webrender_api::units::DeviceIntSize;
+use servo::{MediaSessionActionType, PictureInPictureRequest, WebView, WindowRenderingContext};
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, KeyEvent, MouseButton, WindowEvent};
+use winit::event_loop::ActiveEventLoop;
+use winit::keyboard::{Key as LogicalKey, NamedKey};
+use winit::window::{WindowAttributes, WindowId, WindowLevel};
+
+/// The largest size of the window when it is opened, in physical pixels. Smaller videos are
+/// shown at their natural size.
+const MAX_INITIAL_SIZE: PhysicalSize<u32> = PhysicalSize::new(480, 360);
+
+pub(crate) struct PictureInPictureWindow {
+    /// The `WebView` whose video is shown.
+    webview: WebView,
+    /// Whether the video is playing, as last reported by the media session of the `WebView`.
+    playing: bool,
+    /// The window itself. Servo holds the [`WindowRenderingContext`] of the window until it
+    /// stops painting to it, which happens before the window is dropped.
+    winit_window: winit::window::Window,
+}
+
+impl PictureInPictureWindow {
+    /// Open a window for the given [`PictureInPictureRequest`], or deny the request if that
+    /// fails.
+    pub(crate) fn open(
+        event_loop: &ActiveEventLoop,
+        webview: WebView,
+        request: PictureInPictureRequest,
+    ) -> Option<Self> {
+        let window_attributes = WindowAttributes::default()
+            .with_title("Picture-in-Picture")
+            .with_window_level(WindowLevel::AlwaysOnTop)
+            .with_inner_size(initial_size(request.video_size()))
+            .with_min_inner_size(PhysicalSize::new(64, 36));
+        let winit_window = match event_loop.create_window(window_attributes) {
+            Ok(winit_window) => winit_window,
+            Err(error) => {
+                warn!("Failed to create picture-in-picture window: {error}");
+                request.deny();
+                return None;
+            },
+        };
+
+        let (Ok(display_handle), Ok(window_handle)) =
+            (event_loop.display_handle(), winit_window.window_handle())
+        else {
+            request.deny();
+            return None;
+        };
+        let rendering_context = match WindowRenderingContext::new(
+            display_handle,
+            window_handle,
+            winit_window.inner_size(),
+        ) {
+            Ok(rendering_context) => Rc::new(rendering_context),
+            Err(error) => {
+                warn!("Failed to create picture-in-picture rendering context: {error:?}");
+                request.deny();
+                return None;
+            },
+        };
+
+        debug!("Created picture-in-picture window {:?}", winit_window.id());
+        request.open(rendering_context);
+        Some(Self {
+            webview,
+            playing: true,
+            winit_window,
+        })
+    }
+
+    pub(crate) fn id(&self) -> WindowId {
+        self.winit_window.id()
+    }
+
+    pub(crate) fn webview(&self) -> &WebView {
+        &self.webview
+    }
+
+    pub(crate) fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    /// Handle an event of the window. Returns false if the window should be closed, in which
+    /// case the video has left picture-in-picture.
+    pub(crate) fn handle_winit_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CloseRequested => {
+                self.webview.exit_picture_in_picture();
+                return false;
+            },
+            WindowEvent::Resized(new_size) => {
+                self.webview.resize_picture_in_picture(*new_size);
+            },
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => self.toggle_playback(),
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: LogicalKey::Named(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => match key {
+                NamedKey::Escape => {
+                    self.webview.exit_picture_in_picture();
+                    return false;
+                },
+                NamedKey::Space => self.toggle_playback(),
+                NamedKey::ArrowLeft => self
+                    .webview
+                    .notify_media_session_action_event(MediaSessionActionType::SeekBackward),
+                NamedKey::ArrowRight => self
+                    .webview
+                    .notify_media_session_action_event(MediaSessionActionType::SeekForward),
+                _ => {},
+            },
+            _ => {},
+        }
+        true
+    }
+
+    fn toggle_playback(&mut self) {
+        let action = if self.playing {
+            MediaSessionActionType::Pause
+        } else {
+            MediaSessionActionType::Play
+        };
+        self.playing = !self.playing;
+        self.webview.notify_media_session_action_event(action);
+    }
+}
+
+/// The size of a newly opened window for a video of the given size: the size of the video,
+/// scaled down to fit [`MAX_INITIAL_SIZE`].
+fn initial_size(video_size: DeviceIntSize) -> PhysicalSize<u32> {
+    let width = video_size.width.max(1) as f32;
+    let height = video_size.height.max(1) as f32;
+    let scale = (MAX_INITIAL_SIZE.width as f32 / width)
+        .min(MAX_INITIAL_SIZE.height as f32 / height)
+        .min(1.0);
+    PhysicalSize::new(
+        (width * scale).round() as u32,
+        (height * scale).round() as u32,
+    )
+}
@@ -0,0 +1,357 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The history of visited pages and the bookmarks of servoshell, which are kept in a
+//! `places.json` file in the profile directory. The store is shared between the UI and the
+//! handler of the `servo:history`, `servo:bookmarks` and `servo:newtab` pages.
+//!
+//! The screenshots of visited pages shown on `servo:newtab` are kept as PNG files in a
+//! `thumbnails` directory next to the places file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::{DynamicImage, ImageFormat};
+use log::warn;
+use serde_json::{Value, json};
+
+pub(crate) type SharedPlaces = Arc<Mutex<Places>>;
+
+/// A page in the history.
+pub(crate) struct HistoryEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub visit_count: u32,
+    /// The time of the last visit, in seconds since the Unix epoch.
+    pub last_visit: u64,
+    /// The URL of the icon of the page, if it has one.
+    pub favicon_url: Option<String>,
+}
+
+pub(crate) struct Bookmark {
+    pub url: String,
+    pub title: Option<String>,
+    /// The time at which the bookmark was added, in seconds since the Unix epoch.
+    pub added: u64,
+}
+
+/// A page suggested for the text typed in the location bar.
+pub(crate) struct Suggestion {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// A frequently visited site, shown as a tile of `servo:newtab`.
+pub(crate) struct TopSite<'a> {
+    /// The most visited page of the site.
+    pub entry: &'a HistoryEntry,
+    /// The number of visits of all the pages of the site.
+    pub visit_count: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct Places {
+    /// Where the places are saved, or `None` if they are only kept in memory.
+    path: Option<PathBuf>,
+    history: HashMap<String, HistoryEntry>,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Places {
+    /// Load the places saved in the file at `path`, if it exists.
+    pub(crate) fn load(path: Option<PathBuf>) -> Places {
+        let mut places = Places {
+            path,
+            ..Default::default()
+        };
+        let Some(contents) = places
+            .path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return places;
+        };
+        let value: Value = match serde_json::from_str(&contents) {
+            Ok(value) => value,
+            Err(error) => {
+                warn!("Could not parse the history and bookmarks: {error}");
+                return places;
+            },
+        };
+
+        for entry in value["history"].as_array().into_iter().flatten() {
+            let Some(url) = entry["url"].as_str() else {
+                continue;
+            };
+            places.history.insert(
+                url.to_owned(),
+                HistoryEntry {
+                    url: url.to_owned(),
+                    title: entry["title"].as_str().map(str::to_owned),
+                    visit_count: entry["visitCount"].as_u64().unwrap_or(1) as u32,
+                    last_visit: entry["lastVisit"].as_u64().unwrap_or_default(),
+                    favicon_url: entry["faviconUrl"].as_str().map(str::to_owned),
+                },
+            );
+        }
+        places.bookmarks = value["bookmarks"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|bookmark| {
+                Some(Bookmark {
+                    url: bookmark["url"].as_str()?.to_owned(),
+                    title: bookmark["title"].as_str().map(str::to_owned),
+                    added: bookmark["added"].as_u64().unwrap_or_default(),
+                })
+            })
+            .collect();
+        places
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let history: Vec<_> = self
+            .history
+            .values()
+            .map(|entry| {
+                json!({
+                    "url": entry.url,
+                    "title": entry.title,
+                    "visitCount": entry.visit_count,
+                    "lastVisit": entry.last_visit,
+                    "faviconUrl": entry.favicon_url,
+                })
+            })
+            .collect();
+        let bookmarks: Vec<_> = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| {
+                json!({
+                    "url": bookmark.url,
+                    "title": bookmark.title,
+                    "added": bookmark.added,
+                })
+            })
+            .collect();
+        let contents = json!({ "history": history, "bookmarks": bookmarks }).to_string();
+        if let Err(error) = fs::write(path, contents) {
+            warn!("Could not save the history and bookmarks to {path:?}: {error}");
+        }
+    }
+
+    /// Record a visit of the page with the given URL.
+    pub(crate) fn record_visit(&mut self, url: &str, title: Option<String>) {
+        let entry = self
+            .history
+            .entry(url.to_owned())
+            .or_insert_with(|| HistoryEntry {
+                url: url.to_owned(),
+                title: None,
+                visit_count: 0,
+                last_visit: 0,
+                favicon_url: None,
+            });
+        entry.visit_count += 1;
+        entry.last_visit = now();
+        if title.is_some() {
+            entry.title = title;
+        }
+        self.save();
+    }
+
+    /// Update the title of the page with the given URL, if it is in the history.
+    pub(crate) fn set_title(&mut self, url: &str, title: Option<String>) {
+        match self.history.get_mut(url) {
+            Some(entry) if title.is_some() && entry.title != title => entry.title = title,
+            _ => return,
+        }
+        self.save();
+    }
+
+    /// Update the URL of the icon of the page with the given URL, if it is in the history.
+    pub(crate) fn set_favicon_url(&mut self, url: &str, favicon_url: &str) {
+        match self.history.get_mut(url) {
+            Some(entry) if entry.favicon_url.as_deref() != Some(favicon_url) => {
+                entry.favicon_url = Some(favicon_url.to_owned())
+            },
+            _ => return,
+        }
+        self.save();
+    }
+
+    /// The pages in the history, most recently visited first.
+    pub(crate) fn history(&self) -> Vec<&HistoryEntry> {
+        let mut history: Vec<_> = self.history.values().collect();
+        history.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
+        history
+    }
+
+    pub(crate) fn remove_history_entry(&mut self, url: &str) {
+        if self.history.remove(url).is_some() {
+            self.remove_thumbnail(url);
+            self.save();
+        }
+    }
+
+    pub(crate) fn clear_history(&mut self) {
+        if let Some(directory) = self.thumbnail_directory() {
+            let _ = fs::remove_dir_all(directory);
+        }
+        self.history.clear();
+        self.save();
+    }
+
+    /// The `limit` most visited sites, counting the visits of all the pages of a site, which
+    /// are represented by their most visited page.
+    pub(crate) fn top_sites(&self, limit: usize) -> Vec<TopSite<'_>> {
+        let mut sites: HashMap<String, TopSite> = HashMap::new();
+        for entry in self.history.values() {
+            let Ok(url) = url::Url::parse(&entry.url) else {
+                continue;
+            };
+            let site = url
+                .host_str()
+                .unwrap_or(url.as_str())
+                .trim_start_matches("www.");
+            let top_site = sites.entry(site.to_owned()).or_insert(TopSite {
+                entry,
+                visit_count: 0,
+            });
+            top_site.visit_count += entry.visit_count;
+            if (entry.visit_count, entry.last_visit) >
+                (top_site.entry.visit_count, top_site.entry.last_visit)
+            {
+                top_site.entry = entry;
+            }
+        }
+        let mut sites: Vec<_> = sites.into_values().collect();
+        sites.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then(b.entry.last_visit.cmp(&a.entry.last_visit))
+        });
+        sites.truncate(limit);
+        sites
+    }
+
+    fn thumbnail_directory(&self) -> Option<PathBuf> {
+        Some(self.path.as_ref()?.with_file_name("thumbnails"))
+    }
+
+    /// The file of the screenshot of the page with the given URL, whether or not it exists.
+    pub(crate) fn thumbnail_path(&self, url: &str) -> Option<PathBuf> {
+        // FNV-1a, which unlike the hasher of the standard library is stable across releases.
+        let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Some(self.thumbnail_directory()?.join(format!("{hash:016x}.png")))
+    }
+
+    /// Save the screenshot of the page with the given URL, if it is in the history.
+    pub(crate) fn save_thumbnail(&self, url: &str, thumbnail: DynamicImage) {
+        if !self.history.contains_key(url) {
+            return;
+        }
+        let Some(path) = self.thumbnail_path(url) else {
+            return;
+        };
+        if let Some(directory) = path.parent() {
+            let _ = fs::create_dir_all(directory);
+        }
+        if let Err(error) = thumbnail.save_with_format(&path, ImageFormat::Png) {
+            warn!("Could not save the thumbnail of {url} to {path:?}: {error}");
+        }
+    }
+
+    fn remove_thumbnail(&self, url: &str) {
+        if let Some(path) = self.thumbnail_path(url) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// The bookmarks, in the order in which they were added.
+    pub(crate) fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub(crate) fn is_bookmarked(&self, url: &str) -> bool {
+        self.bookmarks.iter().any(|bookmark| bookmark.url == url)
+    }
+
+    pub(crate) fn add_bookmark(&mut self, url: &str, title: Option<String>) {
+        if self.is_bookmarked(url) {
+            return;
+        }
+        self.bookmarks.push(Bookmark {
+            url: url.to_owned(),
+            title,
+            added: now(),
+        });
+        self.save();
+    }
+
+    pub(crate) fn remove_bookmark(&mut self, url: &str) {
+        let count = self.bookmarks.len();
+        self.bookmarks.retain(|bookmark| bookmark.url != url);
+        if self.bookmarks.len() != count {
+            self.save();
+        }
+    }
+
+    /// Suggest at most `limit` pages whose URL or title contains `text`, bookmarks first and
+    /// then the most visited pages.
+    pub(crate) fn suggestions(&self, text: &str, limit: usize) -> Vec<Suggestion> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        let matches = |url: &str, title: &Option<String>| {
+            url.to_lowercase().contains(&text) ||
+                title
+                    .as_ref()
+                    .is_some_and(|title| title.to_lowercase().contains(&text))
+        };
+
+        let mut suggestions: Vec<Suggestion> = self
+            .bookmarks
+            .iter()
+            .filter(|bookmark| matches(&bookmark.url, &bookmark.title))
+            .map(|bookmark| Suggestion {
+                url: bookmark.url.clone(),
+                title: bookmark.title.clone(),
+            })
+            .collect();
+
+        let mut history: Vec<_> = self
+            .history
+            .values()
+            .filter(|entry| matches(&entry.url, &entry.title) && !self.is_bookmarked(&entry.url))
+            .collect();
+        history.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then(b.last_visit.cmp(&a.last_visit))
+        });
+        suggestions.extend(history.into_iter().map(|entry| Suggestion {
+            url: entry.url.clone(),
+            title: entry.title.clone(),
+        }));
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -2,42 +2,329 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-//! Loads resources using a mapping from well-known shortcuts to resource: urls.
-//! Recognized shortcuts:
-//! - servo:newtab
-
-use std::future::Future;
-use std::pin::Pin;
-
-use net::fetch::methods::{DoneChannel, FetchContext};
-use net::protocols::ProtocolHandler;
-use net_traits::request::Request;
-use net_traits::response::Response;
-
-use crate::desktop::protocols::resource::ResourceProtocolHandler;
-
-#[derive(Default)]
-pub struct ServoProtocolHandler {}
-
-impl ProtocolHandler for ServoProtocolHandler {
-    fn load(
-        &self,
-        request: &mut Request,
-        done_chan: &mut DoneChannel,
-        context: &FetchContext,
-    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
-        let url = request.current_url();
-
-        match url.path() {
-            "newtab" => ResourceProtocolHandler::response_for_path(
-                request,
-                done_chan,
-                context,
-                "/newtab.html",
-            ),
-            _ => Box::pin(std::future::ready(Response::network_internal_error(
-                "Invalid shortcut",
-            ))),
+//! The `servo:` pages of servoshell, which are registered with the internal pages of Servo:
+//! - servo:prefs and servo:services, loaded from the resource directory
+//! - servo:newtab, servo:history and servo:bookmarks, generated from the places store
+//! - servo:crashes, which lists the crash reports in the config directory
+//!
+//! servo:memory and servo:version are pages of Servo itself.
+
+use std::fs;
+
+use net::fetch::methods::FetchContext;
+use net::protocols::{InternalPage, ProtocolRegistry};
+use servo::servo_url::ServoUrl;
+use url::Url;
+
+use crate::crash_reports;
+use crate::desktop::places::{Places, SharedPlaces};
+
+/// Register the pages of servoshell with the internal pages of `registry`.
+pub(crate) fn register_internal_pages(registry: &mut ProtocolRegistry, places: SharedPlaces) {
+    for (name, file) in [("prefs", "prefs.html"), ("services", "services.html")] {
+        let _ = registry.register_internal_page(name, move |_: &ServoUrl, _: &FetchContext| {
+            resource_page(file)
+        });
+    }
+
+    let newtab_places = places.clone();
+    let _ = registry.register_internal_page("newtab", move |_: &ServoUrl, _: &FetchContext| {
+        Ok(newtab_page(&newtab_places.lock().unwrap()))
+    });
+    let _ = registry.register_internal_page(
+        "history",
+        HistoryPage {
+            places: places.clone(),
+        },
+    );
+    let _ = registry.register_internal_page("bookmarks", BookmarksPage { places });
+    let _ = registry.register_internal_page("crashes", |url: &ServoUrl, _: &FetchContext| {
+        Ok(crashes_page(&query_pairs(url)))
+    });
+}
+
+/// Read a page from the `resource_protocol` directory of the resources.
+fn resource_page(file: &str) -> Result<String, String> {
+    let path = crate::resources::resources_dir_path()
+        .join("resource_protocol")
+        .join(file);
+    fs::read_to_string(&path).map_err(|error| format!("Could not read {path:?}: {error}"))
+}
+
+fn query_pairs(url: &ServoUrl) -> Vec<(String, String)> {
+    url.as_url()
+        .query_pairs()
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect()
+}
+
+const PLACES_STYLE: &str = "
+body { font-family: sans-serif; margin: 1em 2em; }
+table { width: 100%; border-collapse: collapse; }
+th, td { text-align: left; padding: 0.3em 0.5em; border-bottom: 1px solid #ddd; }
+td.url { color: #666; word-break: break-all; }
+img.favicon { width: 16px; height: 16px; margin-right: 0.4em; vertical-align: middle; }
+";
+
+/// The number of sites shown on the servo:newtab page.
+const NEWTAB_SITE_COUNT: usize = 12;
+
+const NEWTAB_STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 70em; padding: 0 1em; }
+.tiles { display: grid; grid-template-columns: repeat(auto-fill, minmax(12em, 1fr)); gap: 1em; }
+.tile { display: block; color: inherit; text-decoration: none; border: 1px solid #ddd;
+        border-radius: 6px; overflow: hidden; }
+.tile:hover { border-color: #888; }
+.thumbnail { display: block; width: 100%; aspect-ratio: 8 / 5; object-fit: cover;
+             background: #f4f4f4; }
+.placeholder { display: flex; align-items: center; justify-content: center; font-size: 3em;
+               color: #888; }
+.title { display: block; padding: 0.4em 0.6em; white-space: nowrap; overflow: hidden;
+         text-overflow: ellipsis; }
+img.favicon { width: 16px; height: 16px; margin-right: 0.4em; vertical-align: middle; }
+";
+
+/// Generate the servo:newtab page, with a tile for each of the most visited sites showing
+/// the last screenshot of its page.
+fn newtab_page(places: &Places) -> String {
+    let tiles: String = places
+        .top_sites(NEWTAB_SITE_COUNT)
+        .into_iter()
+        .map(|site| {
+            let entry = site.entry;
+            let title = entry.title.as_deref().unwrap_or(&entry.url);
+            let thumbnail = places
+                .thumbnail_path(&entry.url)
+                .filter(|path| path.exists())
+                .and_then(|path| Url::from_file_path(path).ok());
+            let thumbnail = match thumbnail {
+                Some(thumbnail_url) => format!(
+                    "<img class=\"thumbnail\" src=\"{}\" alt=\"\">",
+                    escape_html(thumbnail_url.as_str())
+                ),
+                None => format!(
+                    "<span class=\"thumbnail placeholder\">{}</span>",
+                    escape_html(
+                        &title
+                            .chars()
+                            .next()
+                            .unwrap_or_default()
+                            .to_uppercase()
+                            .to_string()
+                    )
+                ),
+            };
+            let favicon = entry
+                .favicon_url
+                .as_ref()
+                .map(|favicon_url| {
+                    format!(
+                        "<img class=\"favicon\" src=\"{}\" alt=\"\">",
+                        escape_html(favicon_url)
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "<a class=\"tile\" href=\"{url}\" title=\"{url}\">{thumbnail}\
+                 <span class=\"title\">{favicon}{title}</span></a>",
+                url = escape_html(&entry.url),
+                title = escape_html(title),
+            )
+        })
+        .collect();
+
+    let content = if tiles.is_empty() {
+        "<p>The sites that you visit most will be shown here.</p>".to_owned()
+    } else {
+        format!("<div class=\"tiles\">{tiles}</div>")
+    };
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>New Tab</title>\
+         <style>{NEWTAB_STYLE}</style></head><body>{content}</body></html>"
+    )
+}
+
+/// Format the `data-time` attributes of the page, which are in seconds since the Unix epoch,
+/// as local dates.
+const PLACES_SCRIPT: &str = "
+for (const element of document.querySelectorAll('[data-time]')) {
+  element.textContent = new Date(element.dataset.time * 1000).toLocaleString();
+}
+";
+
+/// Run an action of a page with `navigator.servo`, then reload the page to show its result.
+/// Loads of the page never change the places, so that other sites cannot change them by
+/// linking to the page.
+const PLACES_ACTION_SCRIPT: &str = "
+for (const button of document.querySelectorAll('button[data-action]')) {
+  button.addEventListener('click', () => {
+    navigator.servo.performAction(button.dataset.action, button.dataset.value || '');
+    location.reload();
+  });
+}
+";
+
+/// The servo:history page. The `q` parameter of its query only shows the pages that match
+/// some text, and its actions are `remove` to remove a page from the history and `clear` to
+/// clear it.
+struct HistoryPage {
+    places: SharedPlaces,
+}
+
+impl InternalPage for HistoryPage {
+    fn generate(&self, url: &ServoUrl, _: &FetchContext) -> Result<String, String> {
+        Ok(history_page(
+            &self.places.lock().unwrap(),
+            &query_pairs(url),
+        ))
+    }
+
+    fn perform_action(&self, action: &str, value: &str) {
+        let mut places = self.places.lock().unwrap();
+        match action {
+            "remove" => places.remove_history_entry(value),
+            "clear" => places.clear_history(),
+            _ => {},
+        }
+    }
+}
+
+fn history_page(places: &Places, query: &[(String, String)]) -> String {
+    let filter = query
+        .iter()
+        .find(|(name, _)| name == "q")
+        .map(|(_, value)| value.to_lowercase());
+
+    let rows: String = places
+        .history()
+        .into_iter()
+        .filter(|entry| {
+            filter.as_ref().is_none_or(|filter| {
+                entry.url.to_lowercase().contains(filter) ||
+                    entry
+                        .title
+                        .as_ref()
+                        .is_some_and(|title| title.to_lowercase().contains(filter))
+            })
+        })
+        .map(|entry| {
+            let favicon = entry
+                .favicon_url
+                .as_ref()
+                .map(|favicon_url| {
+                    format!(
+                        "<img class=\"favicon\" src=\"{}\" alt=\"\">",
+                        escape_html(favicon_url)
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "<tr><td>{favicon}<a href=\"{url}\">{title}</a></td><td class=\"url\">{url}</td>\
+                 <td>{visit_count}</td><td data-time=\"{last_visit}\"></td>\
+                 <td><button data-action=\"remove\" data-value=\"{url}\">Remove</button></td></tr>",
+                url = escape_html(&entry.url),
+                title = escape_html(entry.title.as_deref().unwrap_or(&entry.url)),
+                visit_count = entry.visit_count,
+                last_visit = entry.last_visit,
+            )
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>History</title>\
+         <style>{PLACES_STYLE}</style></head><body><h1>History</h1>\
+         <form action=\"servo:history\"><input name=\"q\" placeholder=\"Search history\" value=\"{filter}\"> \
+         <button type=\"button\" data-action=\"clear\">Clear history</button></form>\
+         <table><tr><th>Title</th><th>URL</th><th>Visits</th><th>Last visit</th><th></th></tr>\
+         {rows}</table><script>{PLACES_SCRIPT}{PLACES_ACTION_SCRIPT}</script></body></html>",
+        filter = escape_html(filter.as_deref().unwrap_or_default()),
+    )
+}
+
+/// The servo:bookmarks page, whose `remove` action removes a bookmark.
+struct BookmarksPage {
+    places: SharedPlaces,
+}
+
+impl InternalPage for BookmarksPage {
+    fn generate(&self, _: &ServoUrl, _: &FetchContext) -> Result<String, String> {
+        Ok(bookmarks_page(&self.places.lock().unwrap()))
+    }
+
+    fn perform_action(&self, action: &str, value: &str) {
+        if action == "remove" {
+            self.places.lock().unwrap().remove_bookmark(value);
         }
     }
 }
+
+fn bookmarks_page(places: &Places) -> String {
+    let rows: String = places
+        .bookmarks()
+        .iter()
+        .map(|bookmark| {
+            format!(
+                "<tr><td><a href=\"{url}\">{title}</a></td><td class=\"url\">{url}</td>\
+                 <td data-time=\"{added}\"></td>\
+                 <td><button data-action=\"remove\" data-value=\"{url}\">Remove</button></td></tr>",
+                url = escape_html(&bookmark.url),
+                title = escape_html(bookmark.title.as_deref().unwrap_or(&bookmark.url)),
+                added = bookmark.added,
+            )
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Bookmarks</title>\
+         <style>{PLACES_STYLE}</style></head><body><h1>Bookmarks</h1>\
+         <table><tr><th>Title</th><th>URL</th><th>Added</th><th></th></tr>\
+         {rows}</table><script>{PLACES_SCRIPT}{PLACES_ACTION_SCRIPT}</script></body></html>"
+    )
+}
+
+/// Generate the servo:crashes page, which lists the crash reports, or shows the one named by
+/// the `report` parameter of its query.
+fn crashes_page(query: &[(String, String)]) -> String {
+    let report = query
+        .iter()
+        .find(|(name, _)| name == "report")
+        .map(|(_, name)| name);
+    if let Some(name) = report {
+        let contents = crash_reports::read_crash_report(name)
+            .unwrap_or_else(|| "This crash report could not be read.".to_owned());
+        return format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{name}</title>\
+             <style>{PLACES_STYLE}</style></head><body><p><a href=\"servo:crashes\">All crash \
+             reports</a></p><h1>{name}</h1><pre>{contents}</pre></body></html>",
+            name = escape_html(name),
+            contents = escape_html(&contents),
+        );
+    }
+
+    let rows: String = crash_reports::crash_reports()
+        .iter()
+        .map(|name| {
+            format!(
+                "<tr><td><a href=\"servo:crashes?report={encoded_name}\">{name}</a></td></tr>",
+                name = escape_html(name),
+                encoded_name = encode_query_value(name),
+            )
+        })
+        .collect();
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Crash reports</title>\
+         <style>{PLACES_STYLE}</style></head><body><h1>Crash reports</h1>\
+         <table><tr><th>Report</th></tr>{rows}</table></body></html>"
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn encode_query_value(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
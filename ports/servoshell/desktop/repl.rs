@@ -0,0 +1,201 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An interactive prompt for controlling a headless servoshell from the terminal, enabled
+//! with `--repl`. Commands are read from stdin on a separate thread and handed to the
+//! application event loop, which executes them against the focused `WebView`. There is no
+//! remote control protocol for servoshell, so commands use the `WebView` API directly, and
+//! screenshots are read back from the rendering context after painting the `WebView`.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::thread;
+
+use crossbeam_channel::{Receiver, unbounded};
+use log::warn;
+use serde_json::{Map, Number, Value};
+use servo::{EventLoopWaker, JSValue};
+
+const PROMPT: &str = "servo> ";
+
+pub(crate) const HELP: &str = "\
+Commands:
+  open <url>             Load a URL (or search for a term) in the focused WebView
+  js <expression>        Evaluate a JavaScript expression and print the result
+  screenshot <file.png>  Save the current contents of the viewport to a file
+  metrics                Print the navigation and paint timing metrics of the page
+  help                   Print this message
+  quit                   Shut down servoshell";
+
+/// A script which collects the performance entries relevant to page load into a
+/// JSON-serializable object.
+const METRICS_SCRIPT: &str = "\
+(() => {
+    const entries = (type) => performance.getEntriesByType(type).map((entry) => entry.toJSON());
+    return JSON.stringify({
+        url: document.URL,
+        navigation: entries('navigation'),
+        paint: entries('paint'),
+        largestContentfulPaint: entries('largest-contentful-paint'),
+    });
+})()";
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ReplCommand {
+    Open(String),
+    Js(String),
+    Screenshot(PathBuf),
+    Metrics,
+    Help,
+    Quit,
+}
+
+impl ReplCommand {
+    pub(crate) fn parse(line: &str) -> Result<Option<Self>, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        let require_argument = |usage: &str| {
+            if argument.is_empty() {
+                Err(format!("Usage: {usage}"))
+            } else {
+                Ok(argument.to_owned())
+            }
+        };
+
+        Ok(Some(match command {
+            "open" => ReplCommand::Open(require_argument("open <url>")?),
+            "js" => ReplCommand::Js(require_argument("js <expression>")?),
+            "screenshot" => {
+                ReplCommand::Screenshot(require_argument("screenshot <file.png>")?.into())
+            },
+            "metrics" => ReplCommand::Metrics,
+            "help" => ReplCommand::Help,
+            "quit" | "exit" => ReplCommand::Quit,
+            _ => {
+                return Err(format!(
+                    "Unknown command: {command:?}. Type `help` for a list."
+                ));
+            },
+        }))
+    }
+
+    /// The script to evaluate in the page when running `metrics`.
+    pub(crate) fn metrics_script() -> &'static str {
+        METRICS_SCRIPT
+    }
+}
+
+/// Start reading commands from stdin on a new thread, returning a [`Receiver`] for them.
+/// The `waker` is used to kick the event loop whenever a new command is available.
+pub(crate) fn start(waker: Box<dyn EventLoopWaker>) -> Receiver<ReplCommand> {
+    let (sender, receiver) = unbounded();
+    thread::Builder::new()
+        .name("ReplInput".to_owned())
+        .spawn(move || {
+            let stdin = std::io::stdin();
+            let mut lines = stdin.lock().lines();
+            loop {
+                print_prompt();
+                let command = match lines.next() {
+                    Some(Ok(line)) => match ReplCommand::parse(&line) {
+                        Ok(Some(command)) => command,
+                        Ok(None) => continue,
+                        Err(message) => {
+                            println!("{message}");
+                            continue;
+                        },
+                    },
+                    // Treat the end of input like `quit`, so that piping a script of
+                    // commands into servoshell shuts it down once they have run.
+                    Some(Err(_)) | None => ReplCommand::Quit,
+                };
+                let is_quit = command == ReplCommand::Quit;
+                if sender.send(command).is_err() {
+                    break;
+                }
+                waker.wake();
+                if is_quit {
+                    break;
+                }
+            }
+        })
+        .expect("Could not spawn REPL input thread");
+    receiver
+}
+
+pub(crate) fn print_help() {
+    println!("{HELP}");
+}
+
+/// Print the output of an asynchronously completed command. The prompt is printed again
+/// afterward, as the input thread will already have printed it while waiting for input.
+pub(crate) fn print_async_output(output: &str) {
+    println!("\n{output}");
+    print_prompt();
+}
+
+fn print_prompt() {
+    print!("{PROMPT}");
+    if let Err(error) = std::io::stdout().flush() {
+        warn!("Could not flush stdout: {error}");
+    }
+}
+
+/// Convert a [`JSValue`] into JSON for printing.
+pub(crate) fn js_value_to_json(value: &JSValue) -> Value {
+    match value {
+        JSValue::Undefined | JSValue::Null => Value::Null,
+        JSValue::Boolean(value) => Value::Bool(*value),
+        JSValue::Number(value) => Number::from_f64(*value)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::String(value.to_string())),
+        JSValue::String(value) => Value::String(value.clone()),
+        JSValue::Element(id) => Value::String(format!("[element {id}]")),
+        JSValue::Frame(id) => Value::String(format!("[frame {id}]")),
+        JSValue::Window(id) => Value::String(format!("[window {id}]")),
+        JSValue::ShadowRoot(id) => Value::String(format!("[shadow root {id}]")),
+        JSValue::Array(values) => Value::Array(values.iter().map(js_value_to_json).collect()),
+        JSValue::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), js_value_to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReplCommand;
+
+    #[test]
+    fn test_parse_repl_commands() {
+        assert_eq!(ReplCommand::parse("   "), Ok(None));
+        assert_eq!(
+            ReplCommand::parse("open https://servo.org"),
+            Ok(Some(ReplCommand::Open("https://servo.org".into())))
+        );
+        assert_eq!(
+            ReplCommand::parse("js  document.title + ' ' + 1"),
+            Ok(Some(ReplCommand::Js("document.title + ' ' + 1".into())))
+        );
+        assert_eq!(
+            ReplCommand::parse("screenshot out.png"),
+            Ok(Some(ReplCommand::Screenshot("out.png".into())))
+        );
+        assert_eq!(
+            ReplCommand::parse("metrics"),
+            Ok(Some(ReplCommand::Metrics))
+        );
+        assert_eq!(ReplCommand::parse("quit"), Ok(Some(ReplCommand::Quit)));
+        assert!(ReplCommand::parse("open").is_err());
+        assert!(ReplCommand::parse("frobnicate").is_err());
+    }
+}
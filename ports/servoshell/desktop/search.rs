@@ -0,0 +1,364 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The search engines of the location bar: search suggestions from the suggestions API of
+//! the default search engine, and keyword searches with the search engines of visited sites,
+//! which are discovered from their OpenSearch descriptions and kept in the profile.
+//!
+//! <https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md>
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::warn;
+use serde_json::{Value, json};
+use servo::{EventLoopWaker, Servo};
+use url::Url;
+
+/// A script that returns the URL of the OpenSearch description of the page, if it has one.
+pub(crate) const OPENSEARCH_DISCOVERY_SCRIPT: &str = "(() => {
+    const link = document.querySelector(
+        'link[rel~=\"search\" i][type=\"application/opensearchdescription+xml\" i][href]');
+    return link ? link.href : null;
+})()";
+
+/// A search engine, whose URLs have `%s` standing in for the search terms.
+#[derive(Clone)]
+pub(crate) struct SearchEngine {
+    pub name: String,
+    /// The first word of the location bar text that searches with this engine, which is the
+    /// host of the site of the engine.
+    pub keyword: String,
+    pub search_url: String,
+    pub suggestions_url: Option<String>,
+}
+
+impl SearchEngine {
+    fn search_url_for(&self, terms: &str) -> Option<Url> {
+        Url::parse(&self.search_url.replace("%s", &encode_search_terms(terms))).ok()
+    }
+}
+
+/// The suggestions for the last text that they were requested for.
+#[derive(Default)]
+struct Suggestions {
+    text: String,
+    suggestions: Vec<String>,
+}
+
+pub(crate) struct SearchEngines {
+    /// The file in which the discovered search engines are kept, if any.
+    path: Option<PathBuf>,
+    /// The suggestions API of the default search engine, if search suggestions are enabled.
+    suggestions_url: Option<String>,
+    /// The search engines discovered from the OpenSearch descriptions of visited sites.
+    engines: Arc<Mutex<Vec<SearchEngine>>>,
+    suggestions: Arc<Mutex<Suggestions>>,
+    /// The OpenSearch descriptions found in pages, which have not been fetched yet.
+    pending_descriptions: Arc<Mutex<Vec<Url>>>,
+    /// The OpenSearch descriptions that were already fetched in this session.
+    fetched_descriptions: Mutex<HashSet<Url>>,
+    /// Whether suggestions were received or search engines were discovered since the last call
+    /// to [`Self::take_updated`].
+    updated: Arc<AtomicBool>,
+    waker: Box<dyn EventLoopWaker>,
+}
+
+impl SearchEngines {
+    pub(crate) fn new(
+        path: Option<PathBuf>,
+        suggestions_url: Option<String>,
+        waker: Box<dyn EventLoopWaker>,
+    ) -> Self {
+        let engines = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| parse_engines(&contents))
+            .unwrap_or_default();
+        Self {
+            path,
+            suggestions_url: suggestions_url.filter(|url| !url.is_empty()),
+            engines: Arc::new(Mutex::new(engines)),
+            suggestions: Default::default(),
+            pending_descriptions: Default::default(),
+            fetched_descriptions: Default::default(),
+            updated: Default::default(),
+            waker,
+        }
+    }
+
+    /// If the first word of `input` is the keyword of a search engine, return the URL to
+    /// search for the rest of the input with it.
+    pub(crate) fn keyword_search_url(&self, input: &str) -> Option<Url> {
+        let (keyword, terms) = input.trim().split_once(char::is_whitespace)?;
+        let keyword = keyword.to_lowercase();
+        self.engines
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|engine| engine.keyword == keyword)?
+            .search_url_for(terms.trim())
+    }
+
+    /// The suggestions of the search engine for `text`, if they have been received.
+    pub(crate) fn suggestions(&self, text: &str) -> Vec<String> {
+        let suggestions = self.suggestions.lock().unwrap();
+        if suggestions.text == text {
+            suggestions.suggestions.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Request the suggestions of the search engine for `text`, unless they were the last
+    /// ones requested. The suggestions of a keyword search come from the engine of the keyword.
+    pub(crate) fn request_suggestions(&self, servo: &Servo, text: &str) {
+        {
+            let mut suggestions = self.suggestions.lock().unwrap();
+            if suggestions.text == text {
+                return;
+            }
+            suggestions.text = text.to_owned();
+            suggestions.suggestions.clear();
+        }
+
+        let keyword_engine =
+            text.trim()
+                .split_once(char::is_whitespace)
+                .and_then(|(keyword, terms)| {
+                    let keyword = keyword.to_lowercase();
+                    let engines = self.engines.lock().unwrap();
+                    let engine = engines.iter().find(|engine| engine.keyword == keyword)?;
+                    Some((engine.suggestions_url.clone(), terms.trim().to_owned()))
+                });
+        let (suggestions_url, terms) = match keyword_engine {
+            Some((suggestions_url, terms)) => (suggestions_url, terms),
+            None => (self.suggestions_url.clone(), text.trim().to_owned()),
+        };
+        let Some(suggestions_url) = suggestions_url else {
+            return;
+        };
+        if terms.is_empty() {
+            return;
+        }
+        let Ok(url) = Url::parse(&suggestions_url.replace("%s", &encode_search_terms(&terms)))
+        else {
+            return;
+        };
+
+        let text = text.to_owned();
+        let suggestions = self.suggestions.clone();
+        let updated = self.updated.clone();
+        let waker = self.waker.clone();
+        servo.fetch(url, move |result| {
+            let Ok(body) = result else {
+                return;
+            };
+            // The response is in the OpenSearch suggestions format: the query, followed by
+            // an array of suggested completions.
+            let Ok(Value::Array(response)) = serde_json::from_slice::<Value>(&body) else {
+                return;
+            };
+            let mut suggestions = suggestions.lock().unwrap();
+            if suggestions.text != text {
+                return;
+            }
+            suggestions.suggestions = response
+                .get(1)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|suggestion| suggestion.as_str().map(str::to_owned))
+                .collect();
+            updated.store(true, Ordering::Relaxed);
+            waker.wake();
+        });
+    }
+
+    /// The list to which the URLs of the OpenSearch descriptions of visited pages are added,
+    /// to be fetched later by [`Self::fetch_pending_descriptions`].
+    pub(crate) fn pending_descriptions(&self) -> Arc<Mutex<Vec<Url>>> {
+        self.pending_descriptions.clone()
+    }
+
+    /// Fetch the OpenSearch descriptions found in visited pages, and add their search engines.
+    pub(crate) fn fetch_pending_descriptions(&self, servo: &Servo) {
+        let pending_descriptions = std::mem::take(&mut *self.pending_descriptions.lock().unwrap());
+        for url in pending_descriptions {
+            if !self
+                .fetched_descriptions
+                .lock()
+                .unwrap()
+                .insert(url.clone())
+            {
+                continue;
+            }
+            let path = self.path.clone();
+            let engines = self.engines.clone();
+            let updated = self.updated.clone();
+            let waker = self.waker.clone();
+            let description_url = url.clone();
+            servo.fetch(url, move |result| {
+                let engine = match result {
+                    Ok(body) => parse_opensearch_description(
+                        &String::from_utf8_lossy(&body),
+                        &description_url,
+                    ),
+                    Err(error) => {
+                        warn!("Could not fetch OpenSearch description {description_url}: {error}");
+                        return;
+                    },
+                };
+                let Some(engine) = engine else {
+                    return;
+                };
+                let mut engines = engines.lock().unwrap();
+                engines.retain(|existing| existing.keyword != engine.keyword);
+                engines.push(engine);
+                save_engines(path.as_ref(), &engines);
+                updated.store(true, Ordering::Relaxed);
+                waker.wake();
+            });
+        }
+    }
+
+    /// Whether suggestions were received or search engines were discovered since the last
+    /// call, which means that the location bar needs to be updated.
+    pub(crate) fn take_updated(&self) -> bool {
+        self.updated.swap(false, Ordering::Relaxed)
+    }
+}
+
+fn encode_search_terms(terms: &str) -> String {
+    url::form_urlencoded::byte_serialize(terms.as_bytes()).collect()
+}
+
+fn parse_engines(contents: &str) -> Vec<SearchEngine> {
+    let Ok(Value::Array(engines)) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    engines
+        .iter()
+        .filter_map(|engine| {
+            Some(SearchEngine {
+                name: engine["name"].as_str()?.to_owned(),
+                keyword: engine["keyword"].as_str()?.to_owned(),
+                search_url: engine["searchUrl"].as_str()?.to_owned(),
+                suggestions_url: engine["suggestionsUrl"].as_str().map(str::to_owned),
+            })
+        })
+        .collect()
+}
+
+fn save_engines(path: Option<&PathBuf>, engines: &[SearchEngine]) {
+    let Some(path) = path else {
+        return;
+    };
+    let engines: Vec<_> = engines
+        .iter()
+        .map(|engine| {
+            json!({
+                "name": engine.name,
+                "keyword": engine.keyword,
+                "searchUrl": engine.search_url,
+                "suggestionsUrl": engine.suggestions_url,
+            })
+        })
+        .collect();
+    if let Err(error) = fs::write(path, Value::Array(engines).to_string()) {
+        warn!("Could not save the search engines to {path:?}: {error}");
+    }
+}
+
+/// Parse an OpenSearch description, returning the search engine that it describes if it has
+/// an HTML search URL with the same origin as the description.
+fn parse_opensearch_description(description: &str, description_url: &Url) -> Option<SearchEngine> {
+    let name = element_text(description, "ShortName")?;
+    let mut search_url = None;
+    let mut suggestions_url = None;
+    for url_element in description.split("<Url").skip(1) {
+        let tag = url_element
+            .split('>')
+            .next()
+            .unwrap_or_default()
+            .replace(char::is_whitespace, " ");
+        let tag = tag.as_str();
+        let Some(template) = attribute(tag, "template") else {
+            continue;
+        };
+        let Some(template) = template_to_url(&template, description_url) else {
+            continue;
+        };
+        match attribute(tag, "type").as_deref() {
+            Some("text/html") => search_url = Some(template),
+            Some("application/x-suggestions+json") => suggestions_url = Some(template),
+            _ => {},
+        }
+    }
+
+    let keyword = description_url
+        .host_str()?
+        .trim_start_matches("www.")
+        .to_lowercase();
+    Some(SearchEngine {
+        name,
+        keyword,
+        search_url: search_url?,
+        suggestions_url,
+    })
+}
+
+/// Turn an OpenSearch URL template into a URL with `%s` standing in for the search terms,
+/// leaving out the optional parameters. Only templates with the origin of the description
+/// are accepted, so that a site cannot add a search engine of another site.
+fn template_to_url(template: &str, description_url: &Url) -> Option<String> {
+    let mut url = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        url.push_str(&rest[..start]);
+        let end = rest[start..].find('}')? + start;
+        match &rest[start + 1..end] {
+            "searchTerms" => url.push_str("%s"),
+            "inputEncoding" | "outputEncoding" => url.push_str("UTF-8"),
+            parameter if parameter.ends_with('?') => {},
+            _ => return None,
+        }
+        rest = &rest[end + 1..];
+    }
+    url.push_str(rest);
+
+    let url = description_url.join(&url).ok()?;
+    if url.origin() != description_url.origin() || !url.as_str().contains("%s") {
+        return None;
+    }
+    Some(url.into())
+}
+
+fn element_text(document: &str, name: &str) -> Option<String> {
+    let start = document.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = document[start..].find(&format!("</{name}>"))? + start;
+    Some(unescape_xml(document[start..end].trim()))
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {name}="))? + name.len() + 2;
+    let quote = tag[start..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let value = &tag[start + 1..];
+    let end = value.find(quote)?;
+    Some(unescape_xml(&value[..end]))
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Applying the stylesheet given with `--user-agent-stylesheet` and the `user.css` of the
+//! profile directory to all `WebView`s, and reloading them whenever their file changes,
+//! which makes it easy to iterate on them.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::warn;
+use servo::{UserAgentStylesheetOverride, WebView};
+
+/// How often to check whether the stylesheet file has changed.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The origin at which a watched stylesheet is applied.
+#[derive(Clone, Copy)]
+pub(crate) enum StylesheetOrigin {
+    /// After the default user agent stylesheet.
+    UserAgent,
+    /// At the user origin, whose `!important` declarations win over those of the page.
+    User,
+}
+
+pub(crate) struct StylesheetWatcher {
+    path: PathBuf,
+    origin: StylesheetOrigin,
+    /// The modification time of the file when it was last read.
+    last_modified: Option<SystemTime>,
+    last_check: Instant,
+    contents: Option<String>,
+}
+
+impl StylesheetWatcher {
+    pub(crate) fn new(path: PathBuf, origin: StylesheetOrigin) -> Self {
+        let mut watcher = Self {
+            path,
+            origin,
+            last_modified: None,
+            last_check: Instant::now(),
+            contents: None,
+        };
+        watcher.reload();
+        watcher
+    }
+
+    /// Reread the stylesheet if the file changed since it was last read, returning true
+    /// if it has to be applied to the existing `WebView`s again.
+    pub(crate) fn check_for_changes(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified == self.last_modified {
+            return false;
+        }
+        self.reload()
+    }
+
+    /// Apply the current stylesheet to `webview`.
+    pub(crate) fn apply(&self, webview: &WebView) {
+        match self.origin {
+            StylesheetOrigin::UserAgent => webview.set_user_agent_stylesheet_override(
+                self.contents
+                    .clone()
+                    .map(UserAgentStylesheetOverride::Extend),
+            ),
+            StylesheetOrigin::User => {
+                webview.set_user_stylesheets(self.contents.clone().into_iter().collect())
+            },
+        }
+    }
+
+    fn reload(&mut self) -> bool {
+        self.last_modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => Some(contents),
+            // The `user.css` of the profile directory is optional, and removing it stops
+            // applying it.
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => {
+                warn!("Couldn't read stylesheet {}: {error}", self.path.display());
+                return false;
+            },
+        };
+
+        if contents == self.contents {
+            return false;
+        }
+        self.contents = contents;
+        true
+    }
+}
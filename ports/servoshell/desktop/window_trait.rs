@@ -50,6 +50,7 @@ pub trait WindowPortsMethods {
     ) {
     }
     fn hide_ime(&self) {}
+    fn update_ime_cursor_area(&self, _position: servo::webrender_api::units::DeviceIntRect) {}
     fn theme(&self) -> servo::Theme {
         servo::Theme::Light
     }
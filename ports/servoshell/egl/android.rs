@@ -494,7 +494,7 @@ impl HostTrait for HostCallbacks {
                 env.call_method(self.callbacks.as_obj(), "onLoadStarted", "()V", &[])
                     .unwrap();
             },
-            LoadStatus::HeadParsed => {},
+            LoadStatus::HeadParsed | LoadStatus::DomContentLoaded => {},
             LoadStatus::Complete => {
                 env.call_method(self.callbacks.as_obj(), "onLoadEnded", "()V", &[])
                     .unwrap();
@@ -19,7 +19,6 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::HstsPreloadList => {
                 &include_bytes!("../../../../resources/hsts_preload.fstmap")[..]
             },
-            Resource::BadCertHTML => &include_bytes!("../../../../resources/badcert.html")[..],
             Resource::NetErrorHTML => &include_bytes!("../../../../resources/neterror.html")[..],
             Resource::RippyPNG => &include_bytes!("../../../../resources/rippy.png")[..],
             Resource::DomainList => &include_bytes!("../../../../resources/public_domains.txt")[..],
@@ -16,11 +16,12 @@ use servo::servo_geometry::DeviceIndependentPixel;
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntRect, DeviceIntSize, DevicePixel};
 use servo::{
-    AllowOrDenyRequest, ContextMenuResult, ImeEvent, InputEvent, InputMethodType, Key, KeyState,
-    KeyboardEvent, LoadStatus, MediaSessionActionType, MediaSessionEvent, MouseButton,
-    MouseButtonAction, MouseButtonEvent, MouseMoveEvent, NavigationRequest, PermissionRequest,
-    RenderingContext, ScreenGeometry, Servo, ServoDelegate, ServoError, SimpleDialog, TouchEvent,
-    TouchEventType, TouchId, WebView, WebViewBuilder, WebViewDelegate, WindowRenderingContext,
+    AllowOrDenyRequest, ContextMenuResult, CrashDetails, ImeEvent, InputEvent, InputMethodType,
+    Key, KeyState, KeyboardEvent, LoadStatus, MediaSessionActionType, MediaSessionEvent,
+    MouseButton, MouseButtonAction, MouseButtonEvent, MouseMoveEvent, NavigationRequest,
+    PermissionRequest, RenderingContext, ScreenGeometry, Servo, ServoDelegate, ServoError,
+    SimpleDialog, TouchEvent, TouchEventType, TouchId, WebView, WebViewBuilder, WebViewDelegate,
+    WindowRenderingContext,
 };
 use url::Url;
 
@@ -217,8 +218,10 @@ impl WebViewDelegate for RunningAppState {
         };
     }
 
-    fn notify_crashed(&self, _webview: WebView, reason: String, backtrace: Option<String>) {
-        self.callbacks.host_callbacks.on_panic(reason, backtrace);
+    fn notify_crashed(&self, _webview: WebView, crash_details: CrashDetails) {
+        self.callbacks
+            .host_callbacks
+            .on_panic(crash_details.reason, crash_details.backtrace);
     }
 
     fn notify_new_frame_ready(&self, _webview: WebView) {
@@ -239,6 +242,7 @@ impl WebViewDelegate for RunningAppState {
 
     fn request_open_auxiliary_webview(&self, parent_webview: WebView) -> Option<WebView> {
         let webview = WebViewBuilder::new_auxiliary(&self.servo)
+            .private(parent_webview.is_private())
             .delegate(parent_webview.delegate())
             .hidpi_scale_factor(self.inner().hidpi_scale_factor)
             .build();
@@ -684,11 +688,12 @@ impl RunningAppState {
     pub fn present_if_needed(&self) {
         if self.inner().need_present {
             self.inner_mut().need_present = false;
-            if !self.active_webview().paint() {
+            let webview = self.active_webview();
+            if !webview.paint() {
                 return;
             }
             save_output_image_if_necessary(&self.servoshell_preferences, &self.rendering_context);
-            self.rendering_context.present();
+            webview.present();
             if self.servoshell_preferences.exit_after_stable_image {
                 self.request_shutdown();
             }
@@ -12,6 +12,8 @@ mod backtrace;
 #[cfg(not(target_env = "ohos"))]
 mod crash_handler;
 #[cfg(not(any(target_os = "android", target_env = "ohos")))]
+mod crash_reports;
+#[cfg(not(any(target_os = "android", target_env = "ohos")))]
 pub(crate) mod desktop;
 #[cfg(any(target_os = "android", target_env = "ohos"))]
 mod egl;
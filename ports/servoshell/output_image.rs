@@ -2,9 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::path::Path;
 use std::rc::Rc;
 
-use euclid::Point2D;
+use euclid::{Point2D, Size2D};
 use image::{DynamicImage, ImageFormat};
 use log::error;
 use servo::RenderingContext;
@@ -24,16 +25,53 @@ pub(crate) fn save_output_image_if_necessary<T>(
         return;
     };
 
+    if let Err(error) = save_image(rendering_context, Path::new(output_path)) {
+        error!("{error}");
+    }
+}
+
+/// Read the entire viewport of the given `RenderingContext` and save it to `output_path`,
+/// using the image format implied by the file extension or PNG otherwise. Like
+/// [`save_output_image_if_necessary`], this must be done before presenting.
+pub(crate) fn save_image<T>(rendering_context: &Rc<T>, output_path: &Path) -> Result<(), String>
+where
+    T: RenderingContext + ?Sized,
+{
     let size = rendering_context.size2d().to_i32();
     let viewport_rect = DeviceIntRect::from_origin_and_size(Point2D::origin(), size);
     let Some(image) = rendering_context.read_to_image(viewport_rect) else {
-        error!("Failed to read output image.");
-        return;
+        return Err("Failed to read output image.".to_owned());
     };
 
     let image_format = ImageFormat::from_path(output_path).unwrap_or(ImageFormat::Png);
-    if let Err(error) = DynamicImage::ImageRgba8(image).save_with_format(output_path, image_format)
-    {
-        error!("Failed to save {output_path}: {error}.");
+    DynamicImage::ImageRgba8(image)
+        .save_with_format(output_path, image_format)
+        .map_err(|error| format!("Failed to save {}: {error}.", output_path.display()))
+}
+
+/// Read the top of the viewport of the given `RenderingContext` with the aspect ratio of a
+/// `width` by `height` thumbnail, and scale it down to that size. Like
+/// [`save_output_image_if_necessary`], this must be done before presenting.
+pub(crate) fn read_thumbnail<T>(
+    rendering_context: &Rc<T>,
+    width: u32,
+    height: u32,
+) -> Option<DynamicImage>
+where
+    T: RenderingContext + ?Sized,
+{
+    let size = rendering_context.size2d().to_i32();
+    let cropped_height = size
+        .height
+        .min((size.width as i64 * height as i64 / width as i64) as i32);
+    if size.width <= 0 || cropped_height <= 0 {
+        return None;
     }
+    // The rectangle is in framebuffer coordinates, whose origin is at the bottom.
+    let rect = DeviceIntRect::from_origin_and_size(
+        Point2D::new(0, size.height - cropped_height),
+        Size2D::new(size.width, cropped_height),
+    );
+    let image = rendering_context.read_to_image(rect)?;
+    Some(DynamicImage::ImageRgba8(image).thumbnail_exact(width, height))
 }
@@ -36,9 +36,15 @@ pub(crate) struct ServoShellPreferences {
     /// URL string of the search engine page with '%s' standing in for the search term.
     /// For example <https://duckduckgo.com/html/?q=%s>.
     pub searchpage: String,
+    /// URL string of the suggestions API of the search engine, in the OpenSearch suggestions
+    /// format, with '%s' standing in for the search term, or `None` to disable suggestions.
+    pub search_suggestions_url: Option<String>,
     /// Whether or not to run servoshell in headless mode. While running in headless
     /// mode, image output is supported.
     pub headless: bool,
+    /// Whether or not to read commands from an interactive prompt on stdin. This implies
+    /// headless mode.
+    pub repl: bool,
     /// Filter directives for our tracing implementation.
     ///
     /// Overrides directives specified via `SERVO_TRACING` if set.
@@ -57,6 +63,24 @@ pub(crate) struct ServoShellPreferences {
     /// Where to load userscripts from, if any.
     /// and if the option isn't passed userscripts won't be loaded.
     pub userscripts_directory: Option<PathBuf>,
+    /// A stylesheet to apply at the user agent origin of every document, which is reloaded
+    /// whenever the file changes.
+    pub user_agent_stylesheet: Option<PathBuf>,
+    /// The `user.css` of the profile directory, applied at the user origin of every document
+    /// if it exists, and reloaded whenever the file changes.
+    pub user_stylesheet: Option<PathBuf>,
+    /// A dictionary file with the words that are accepted when spellchecking editable text,
+    /// one word per line as in a Hunspell `.dic` file.
+    pub spellcheck_dictionary: Option<PathBuf>,
+    /// Filter lists in the Adblock Plus format, such as EasyList, used to block requests and
+    /// hide elements of pages.
+    pub content_blocking_filter_lists: Vec<PathBuf>,
+    /// The file of the profile in which the history and bookmarks are kept, or `None` to only
+    /// keep them in memory.
+    pub places_file: Option<PathBuf>,
+    /// The file of the profile in which the search engines discovered from the OpenSearch
+    /// descriptions of visited sites are kept.
+    pub search_engines_file: Option<PathBuf>,
     /// `None` to disable WebDriver or `Some` with a port number to start a server to listen to
     /// remote WebDriver commands.
     pub webdriver_port: Option<u16>,
@@ -77,16 +101,24 @@ impl Default for ServoShellPreferences {
             clean_shutdown: false,
             device_pixel_ratio_override: None,
             headless: false,
+            repl: false,
             homepage: "https://servo.org".into(),
             initial_window_size: Size2D::new(1024, 740),
             no_native_titlebar: true,
             screen_size_override: None,
             searchpage: "https://duckduckgo.com/html/?q=%s".into(),
+            search_suggestions_url: Some("https://duckduckgo.com/ac/?q=%s&type=list".into()),
             tracing_filter: None,
             url: None,
             output_image_path: None,
             exit_after_stable_image: false,
             userscripts_directory: None,
+            user_agent_stylesheet: None,
+            user_stylesheet: None,
+            spellcheck_dictionary: None,
+            content_blocking_filter_lists: Vec::new(),
+            places_file: None,
+            search_engines_file: None,
             webdriver_port: None,
             #[cfg(target_env = "ohos")]
             log_filter: None,
@@ -96,6 +128,14 @@ impl Default for ServoShellPreferences {
     }
 }
 
+/// Whether content processes are sandboxed in multiprocess mode unless `--no-sandbox` is
+/// passed. On Linux, the sandbox puts them in their own namespaces and restricts the system
+/// calls that they can make, and system fonts are read for them by the system font service.
+const SANDBOX_BY_DEFAULT: bool = cfg!(all(
+    target_os = "linux",
+    any(target_arch = "x86", target_arch = "x86_64")
+));
+
 #[cfg(all(
     unix,
     not(target_os = "macos"),
@@ -247,6 +287,31 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         "A user stylesheet to be added to every document",
         "file.css",
     );
+    opts.optopt(
+        "",
+        "user-agent-stylesheet",
+        "A stylesheet to apply after the default user agent stylesheet, reloaded on change",
+        "file.css",
+    );
+    opts.optopt(
+        "",
+        "spellcheck-dictionary",
+        "A dictionary with the words to accept when spellchecking editable text",
+        "en_US.dic",
+    );
+    opts.optmulti(
+        "",
+        "filter-list",
+        "A filter list in the Adblock Plus format to block requests and hide elements with",
+        "easylist.txt",
+    );
+    opts.optopt(
+        "",
+        "search-suggestions-url",
+        "The suggestions API of the search engine, with %s standing in for the search term, \
+         or an empty string to disable search suggestions",
+        "https://duckduckgo.com/ac/?q=%s&type=list",
+    );
     opts.optopt(
         "",
         "shaders",
@@ -254,6 +319,11 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         "",
     );
     opts.optflag("z", "headless", "Headless mode");
+    opts.optflag(
+        "",
+        "repl",
+        "Headless mode controlled by an interactive prompt on stdin (type `help` for commands)",
+    );
     opts.optflag(
         "f",
         "hard-fail",
@@ -286,6 +356,11 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
     opts.optflag("M", "multiprocess", "Run in multiprocess mode");
     opts.optflag("B", "bhm", "Background Hang Monitor enabled");
     opts.optflag("S", "sandbox", "Run in a sandbox if multiprocess");
+    opts.optflag(
+        "",
+        "no-sandbox",
+        "Do not run content processes in a sandbox, even on platforms where they are sandboxed by default",
+    );
     opts.optopt(
         "",
         "random-pipeline-closure-probability",
@@ -589,6 +664,7 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         vec![
             "dom_async_clipboard_enabled",
             "dom_fontface_enabled",
+            "dom_highlight_api_enabled",
             "dom_imagebitmap_enabled",
             "dom_intersection_observer_enabled",
             "dom_mouse_event_which_enabled",
@@ -661,7 +737,8 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         no_native_titlebar,
         device_pixel_ratio_override,
         clean_shutdown: opt_match.opt_present("clean-shutdown"),
-        headless: opt_match.opt_present("z"),
+        headless: opt_match.opt_present("z") || opt_match.opt_present("repl"),
+        repl: opt_match.opt_present("repl"),
         tracing_filter,
         initial_window_size,
         screen_size_override,
@@ -670,6 +747,25 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         userscripts_directory: opt_match
             .opt_default("userscripts", "resources/user-agent-js")
             .map(PathBuf::from),
+        user_agent_stylesheet: opt_match
+            .opt_str("user-agent-stylesheet")
+            .map(PathBuf::from),
+        user_stylesheet: config_dir.as_ref().map(|path| path.join("user.css")),
+        spellcheck_dictionary: opt_match
+            .opt_str("spellcheck-dictionary")
+            .map(PathBuf::from),
+        content_blocking_filter_lists: opt_match
+            .opt_strs("filter-list")
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+        places_file: config_dir.as_ref().map(|path| path.join("places.json")),
+        search_suggestions_url: opt_match
+            .opt_str("search-suggestions-url")
+            .or_else(|| ServoShellPreferences::default().search_suggestions_url),
+        search_engines_file: config_dir
+            .as_ref()
+            .map(|path| path.join("search-engines.json")),
         webdriver_port,
         #[cfg(target_env = "ohos")]
         log_filter,
@@ -697,7 +793,8 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         hard_fail: opt_match.opt_present("f") && !opt_match.opt_present("F"),
         multiprocess: opt_match.opt_present("M"),
         background_hang_monitor: opt_match.opt_present("B"),
-        sandbox: opt_match.opt_present("S"),
+        sandbox: !opt_match.opt_present("no-sandbox") &&
+            (opt_match.opt_present("S") || SANDBOX_BY_DEFAULT),
         random_pipeline_closure_probability,
         random_pipeline_closure_seed,
         config_dir,